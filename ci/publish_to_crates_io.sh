@@ -62,4 +62,5 @@ publish() {
 # least-dependent to most.
 publish types
 publish listener
+publish client
 publish sidecar
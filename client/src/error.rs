@@ -0,0 +1,43 @@
+use reqwest::StatusCode;
+use serde::Deserialize;
+use thiserror::Error;
+
+/// An error querying the sidecar or reading its event stream.
+#[derive(Debug, Error)]
+pub enum ClientError {
+    /// The URL of a server of the sidecar is invalid.
+    #[error("invalid URL: {0}")]
+    InvalidUrl(String),
+    /// The sidecar couldn't be reached, or its response couldn't be read.
+    #[error("request to the sidecar failed: {0}")]
+    Http(#[from] reqwest::Error),
+    /// The requested item isn't stored by the sidecar.
+    #[error("not found: {0}")]
+    NotFound(String),
+    /// The sidecar rejected the request.
+    #[error("the sidecar responded with {status}: {message}")]
+    Api { status: StatusCode, message: String },
+    /// An event of the event stream couldn't be read.
+    #[error("couldn't read the event: {0}")]
+    Event(String),
+}
+
+/// The body of the error responses of the REST server.
+#[derive(Deserialize)]
+struct ApiError {
+    message: String,
+}
+
+impl ClientError {
+    /// The error for a response of the sidecar with an unsuccessful `status` and the given body.
+    pub(crate) fn from_response(status: StatusCode, body: &str) -> Self {
+        let message = serde_json::from_str::<ApiError>(body)
+            .map(|error| error.message)
+            .unwrap_or_else(|_| body.to_string());
+        if status == StatusCode::NOT_FOUND {
+            ClientError::NotFound(message)
+        } else {
+            ClientError::Api { status, message }
+        }
+    }
+}
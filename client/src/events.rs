@@ -0,0 +1,64 @@
+use crate::ClientError;
+use async_stream::stream;
+use casper_event_types::sse_data::{self, SseData};
+use eventsource_stream::Eventsource;
+use futures_util::{Stream, StreamExt};
+use reqwest::{Client, Url};
+
+/// An event received on the event stream of the sidecar.
+#[derive(Clone, Debug)]
+pub struct Event {
+    /// The ID of the event, to resume the stream from with `start_from`. The `ApiVersion` event
+    /// opening the stream has none.
+    pub id: Option<u32>,
+    pub data: SseData,
+}
+
+/// Connects to the event stream at `url` and yields its events until it ends.
+///
+/// A failure to connect, or a broken stream, is yielded as the last item of the stream. Events
+/// which can't be parsed are yielded as errors, without ending the stream.
+pub(crate) fn subscribe(
+    http: Client,
+    url: Url,
+) -> impl Stream<Item = Result<Event, ClientError>> + Send {
+    stream! {
+        let response = match http.get(url).send().await {
+            Ok(response) => response,
+            Err(error) => {
+                yield Err(ClientError::from(error));
+                return;
+            }
+        };
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            yield Err(ClientError::from_response(status, &body));
+            return;
+        }
+        let mut events = response.bytes_stream().eventsource();
+        while let Some(event) = events.next().await {
+            match event {
+                Ok(event) => yield parse_event(&event),
+                Err(error) => {
+                    yield Err(ClientError::Event(error.to_string()));
+                    return;
+                }
+            }
+        }
+    }
+}
+
+fn parse_event(event: &eventsource_stream::Event) -> Result<Event, ClientError> {
+    let id = if event.id.is_empty() {
+        None
+    } else {
+        let id = event.id.parse().map_err(|_| {
+            ClientError::Event(format!("expected a numeric event ID, got {}", event.id))
+        })?;
+        Some(id)
+    };
+    let (data, _) = sse_data::deserialize(&event.data)
+        .map_err(|error| ClientError::Event(error.to_string()))?;
+    Ok(Event { id, data })
+}
@@ -0,0 +1,880 @@
+#![deny(clippy::complexity)]
+#![deny(clippy::cognitive_complexity)]
+#![deny(clippy::too_many_lines)]
+
+//! Typed client of the sidecar: async functions for the endpoints of its REST API, and a stream
+//! of the events of its event stream.
+//!
+//! Stored events are returned as the types of [casper_event_types::sse_events] and streamed events
+//! as [SseData], which the sidecar itself uses, so that both stay in sync with the server.
+
+mod error;
+mod events;
+pub mod types;
+
+pub use casper_event_types::{sse_data::SseData, sse_events, Filter};
+pub use error::ClientError;
+pub use events::Event;
+pub use reqwest::Url;
+
+use casper_event_types::{
+    sse_events::{BlockAdded, DeployAccepted, DeployExpired, DeployProcessed, Fault, Step},
+    FinalitySignature,
+};
+use futures_util::Stream;
+use reqwest::{Client, Response};
+use serde::{de::DeserializeOwned, Serialize};
+use std::time::Duration;
+use types::{
+    AccountEffectEntry, BlockFinalitySignatures, ContractDayStats, DeployAccountEntry,
+    DeployAggregate, DeployApprovals, DeployErrorEntry, DeploySizeStats, DeployStatus,
+    DeployTimestampEntry, Enrichment, Equivocator, GraphQLResponse, NftEventEntry, StateProof,
+    TokenTransferEntry, TransferEntry, ValidatorSet,
+};
+
+/// Client of the REST server and the event stream server of a sidecar.
+#[derive(Clone, Debug)]
+pub struct SidecarClient {
+    http: Client,
+    rest_url: Url,
+    sse_url: Url,
+}
+
+impl SidecarClient {
+    /// A client of the REST server at `rest_url` and of the event stream server at `sse_url`,
+    /// e.g. `http://127.0.0.1:18888` and `http://127.0.0.1:19999`.
+    pub fn new(rest_url: &str, sse_url: &str) -> Result<Self, ClientError> {
+        Self::with_http_client(Client::new(), rest_url, sse_url)
+    }
+
+    /// Same as [SidecarClient::new], sending the requests with `http`, e.g. to set timeouts.
+    pub fn with_http_client(
+        http: Client,
+        rest_url: &str,
+        sse_url: &str,
+    ) -> Result<Self, ClientError> {
+        Ok(SidecarClient {
+            http,
+            rest_url: base_url(rest_url)?,
+            sse_url: base_url(sse_url)?,
+        })
+    }
+
+    /// The latest stored block.
+    pub async fn get_latest_block(&self) -> Result<BlockAdded, ClientError> {
+        self.get_json(&["block"], &[]).await
+    }
+
+    /// The stored block with the hex-encoded `block_hash`.
+    pub async fn get_block_by_hash(&self, block_hash: &str) -> Result<BlockAdded, ClientError> {
+        self.get_json(&["block", block_hash], &[]).await
+    }
+
+    /// The canonical serialized bytes of the stored block with the hex-encoded `block_hash`.
+    pub async fn get_raw_block_by_hash(&self, block_hash: &str) -> Result<Vec<u8>, ClientError> {
+        self.get_bytes(&["block", block_hash, "raw"]).await
+    }
+
+    /// The stored block at `height`.
+    pub async fn get_block_by_height(&self, height: u64) -> Result<BlockAdded, ClientError> {
+        self.get_json(&["block", &height.to_string()], &[]).await
+    }
+
+    /// The finality signatures of the block with the hex-encoded `block_hash`, along with the
+    /// weight of the validators which signed it.
+    pub async fn get_block_finality_signatures(
+        &self,
+        block_hash: &str,
+    ) -> Result<BlockFinalitySignatures, ClientError> {
+        self.get_json(&["block", block_hash, "finality_signatures"], &[])
+            .await
+    }
+
+    /// The stored blocks with heights between `from` and `to`, both inclusive, ordered by height.
+    pub async fn get_blocks_by_height_range(
+        &self,
+        from: Option<u64>,
+        to: Option<u64>,
+    ) -> Result<Vec<BlockAdded>, ClientError> {
+        let mut query = Vec::new();
+        if let Some(from) = from {
+            query.push(("from", from.to_string()));
+        }
+        if let Some(to) = to {
+            query.push(("to", to.to_string()));
+        }
+        self.get_json(&["blocks"], &query).await
+    }
+
+    /// The stored events of the deploy with the hex-encoded `deploy_hash`.
+    pub async fn get_deploy_aggregate_by_hash(
+        &self,
+        deploy_hash: &str,
+    ) -> Result<DeployAggregate, ClientError> {
+        self.get_json(&["deploy", deploy_hash], &[]).await
+    }
+
+    /// The approvals of the deploy with the hex-encoded `deploy_hash` and the weight they collected.
+    pub async fn get_deploy_approvals(
+        &self,
+        deploy_hash: &str,
+    ) -> Result<DeployApprovals, ClientError> {
+        self.get_json(&["deploy", deploy_hash, "approvals"], &[])
+            .await
+    }
+
+    /// The lifecycle status of the deploy with the hex-encoded `deploy_hash`.
+    pub async fn get_deploy_status(&self, deploy_hash: &str) -> Result<DeployStatus, ClientError> {
+        self.get_json(&["deploy", deploy_hash, "status"], &[]).await
+    }
+
+    /// The lifecycle status of the deploy with the hex-encoded `deploy_hash`, once it is processed
+    /// or expired or once `timeout` elapses, whichever comes first. The sidecar awaits at most
+    /// 300 seconds, and 60 seconds if `timeout` is `None`; the timeout of the HTTP client, if any,
+    /// has to be longer.
+    pub async fn await_deploy(
+        &self,
+        deploy_hash: &str,
+        timeout: Option<Duration>,
+    ) -> Result<DeployStatus, ClientError> {
+        let query: Vec<_> = timeout
+            .map(|timeout| ("timeout", format!("{}s", timeout.as_secs())))
+            .into_iter()
+            .collect();
+        self.get_json(&["deploy", deploy_hash, "await"], &query)
+            .await
+    }
+
+    /// The deploys accepted or processed between `from` and `to`, each given as an RFC 3339
+    /// timestamp or a `YYYY-MM-DD` date.
+    pub async fn get_deploy_timestamps(
+        &self,
+        from: &str,
+        to: &str,
+    ) -> Result<Vec<DeployTimestampEntry>, ClientError> {
+        let query = [("from", from.to_string()), ("to", to.to_string())];
+        self.get_json(&["deploys"], &query).await
+    }
+
+    /// The failed deploys whose error message contains `contains`.
+    pub async fn get_deploys_by_error_message(
+        &self,
+        contains: &str,
+    ) -> Result<Vec<DeployErrorEntry>, ClientError> {
+        let query = [("contains", contains.to_string())];
+        self.get_json(&["deploys", "errors"], &query).await
+    }
+
+    /// The deploys sent by `account`, a public key, an account hash or an `@`-prefixed alias.
+    pub async fn get_deploys_by_account(
+        &self,
+        account: &str,
+    ) -> Result<Vec<DeployAccountEntry>, ClientError> {
+        self.get_json(&["deploys", "account", account], &[]).await
+    }
+
+    /// The deploys whose execution touched the state of `account`, a public key, an account hash
+    /// or an `@`-prefixed alias.
+    pub async fn get_deploys_affecting_account(
+        &self,
+        account: &str,
+    ) -> Result<Vec<AccountEffectEntry>, ClientError> {
+        self.get_json(&["account", account, "affected-by"], &[])
+            .await
+    }
+
+    /// The native transfers sent or received by `account`, a public key, an account hash or an
+    /// `@`-prefixed alias.
+    pub async fn get_transfers_by_account(
+        &self,
+        account: &str,
+    ) -> Result<Vec<TransferEntry>, ClientError> {
+        self.get_json(&["transfers", "account", account], &[]).await
+    }
+
+    /// The native transfers of the deploys processed in the block with the hex-encoded
+    /// `block_hash`.
+    pub async fn get_transfers_by_block(
+        &self,
+        block_hash: &str,
+    ) -> Result<Vec<TransferEntry>, ClientError> {
+        self.get_json(&["transfers", "block", block_hash], &[])
+            .await
+    }
+
+    /// The native transfers with the user-defined id `transfer_id`.
+    pub async fn get_transfers_by_transfer_id(
+        &self,
+        transfer_id: u64,
+    ) -> Result<Vec<TransferEntry>, ClientError> {
+        let query = [("transfer_id", transfer_id.to_string())];
+        self.get_json(&["transfers"], &query).await
+    }
+
+    /// The transfers of the CEP-18 token with the hex-encoded `contract_hash`.
+    pub async fn get_token_transfers(
+        &self,
+        contract_hash: &str,
+    ) -> Result<Vec<TokenTransferEntry>, ClientError> {
+        self.get_json(&["tokens", contract_hash, "transfers"], &[])
+            .await
+    }
+
+    /// The events of the CEP-78 NFT collection with the hex-encoded `contract_hash`.
+    pub async fn get_nft_events_by_collection(
+        &self,
+        contract_hash: &str,
+    ) -> Result<Vec<NftEventEntry>, ClientError> {
+        self.get_json(&["nfts", contract_hash, "events"], &[]).await
+    }
+
+    /// The events of the token `token_id` of the CEP-78 NFT collection with the hex-encoded
+    /// `contract_hash`.
+    pub async fn get_nft_events_by_token(
+        &self,
+        contract_hash: &str,
+        token_id: &str,
+    ) -> Result<Vec<NftEventEntry>, ClientError> {
+        self.get_json(&["nfts", contract_hash, "tokens", token_id, "events"], &[])
+            .await
+    }
+
+    /// The stored acceptance of the deploy with the hex-encoded `deploy_hash`.
+    pub async fn get_deploy_accepted_by_hash(
+        &self,
+        deploy_hash: &str,
+    ) -> Result<DeployAccepted, ClientError> {
+        self.get_json(&["deploy", "accepted", deploy_hash], &[])
+            .await
+    }
+
+    /// The canonical serialized bytes of the stored deploy with the hex-encoded `deploy_hash`.
+    pub async fn get_raw_deploy_by_hash(&self, deploy_hash: &str) -> Result<Vec<u8>, ClientError> {
+        self.get_bytes(&["deploy", deploy_hash, "raw"]).await
+    }
+
+    /// The stored expiration of the deploy with the hex-encoded `deploy_hash`.
+    pub async fn get_deploy_expired_by_hash(
+        &self,
+        deploy_hash: &str,
+    ) -> Result<DeployExpired, ClientError> {
+        self.get_json(&["deploy", "expired", deploy_hash], &[])
+            .await
+    }
+
+    /// The stored processing of the deploy with the hex-encoded `deploy_hash`.
+    pub async fn get_deploy_processed_by_hash(
+        &self,
+        deploy_hash: &str,
+    ) -> Result<DeployProcessed, ClientError> {
+        self.get_json(&["deploy", "processed", deploy_hash], &[])
+            .await
+    }
+
+    /// The faults of the validator with the hex-encoded `public_key`.
+    pub async fn get_faults_by_public_key(
+        &self,
+        public_key: &str,
+    ) -> Result<Vec<Fault>, ClientError> {
+        self.get_json(&["faults", public_key], &[]).await
+    }
+
+    /// The faults reported in `era`.
+    pub async fn get_faults_by_era(&self, era: u64) -> Result<Vec<Fault>, ClientError> {
+        self.get_json(&["faults", &era.to_string()], &[]).await
+    }
+
+    /// The daily outcomes of the deploys calling the contract with the hex-encoded
+    /// `contract_hash`, and the gas they spent.
+    pub async fn get_contract_stats(
+        &self,
+        contract_hash: &str,
+    ) -> Result<Vec<ContractDayStats>, ClientError> {
+        self.get_json(&["contract", contract_hash, "stats"], &[])
+            .await
+    }
+
+    /// The distribution of the size, approvals count and args count of the accepted deploys.
+    pub async fn get_deploy_size_stats(&self) -> Result<DeploySizeStats, ClientError> {
+        self.get_json(&["stats", "deploys", "size"], &[]).await
+    }
+
+    /// The validators reported in a fault of `era`, and whether they were ejected.
+    pub async fn get_equivocators(&self, era: u64) -> Result<Vec<Equivocator>, ClientError> {
+        let query = [("era", era.to_string())];
+        self.get_json(&["validators", "equivocators"], &query).await
+    }
+
+    /// The validators of the era of the block at `height`, or of the latest block if `None`.
+    pub async fn get_validator_set(
+        &self,
+        maybe_height: Option<u64>,
+    ) -> Result<ValidatorSet, ClientError> {
+        let query: Vec<_> = maybe_height
+            .map(|height| ("at_height", height.to_string()))
+            .into_iter()
+            .collect();
+        self.get_json(&["validators"], &query).await
+    }
+
+    /// The finality signatures of the block with the hex-encoded `block_hash`.
+    pub async fn get_finality_signatures_by_block(
+        &self,
+        block_hash: &str,
+    ) -> Result<Vec<FinalitySignature>, ClientError> {
+        self.get_json(&["signatures", block_hash], &[]).await
+    }
+
+    /// The context attached to the block or deploy with the hex-encoded `hash`.
+    pub async fn get_enrichments_by_hash(
+        &self,
+        hash: &str,
+    ) -> Result<Vec<Enrichment>, ClientError> {
+        self.get_json(&["enrichments", hash], &[]).await
+    }
+
+    /// The proof of `key` under `state_root_hash`, fetched from the node by the sidecar, with the
+    /// related stored events.
+    pub async fn get_state_proof(
+        &self,
+        state_root_hash: &str,
+        key: &str,
+    ) -> Result<StateProof, ClientError> {
+        self.get_json(&["state", state_root_hash, key, "proof"], &[])
+            .await
+    }
+
+    /// The step event emitted at the end of `era_id`.
+    pub async fn get_step_by_era(&self, era_id: u64) -> Result<Step, ClientError> {
+        self.get_json(&["step", &era_id.to_string()], &[]).await
+    }
+
+    /// The result of the GraphQL `query` with the given `variables`, if any. The errors of the
+    /// fields which couldn't be resolved are returned in the response, along with the other
+    /// fields.
+    pub async fn query_graphql(
+        &self,
+        query: &str,
+        variables: Option<serde_json::Value>,
+    ) -> Result<GraphQLResponse, ClientError> {
+        let request = GraphQLRequest { query, variables };
+        self.post_json(&["graphql"], &request).await
+    }
+
+    /// Subscribes to the events of the event stream selected by `filter`, starting from the
+    /// buffered event with the ID `start_from` if given, and from the next event otherwise.
+    pub fn subscribe(
+        &self,
+        filter: Filter,
+        start_from: Option<u32>,
+    ) -> impl Stream<Item = Result<Event, ClientError>> + Send {
+        let mut url = append_path(
+            &self.sse_url,
+            &filter.to_string().split('/').collect::<Vec<_>>(),
+        );
+        if let Some(start_from) = start_from {
+            url.query_pairs_mut()
+                .append_pair("start_from", &start_from.to_string());
+        }
+        events::subscribe(self.http.clone(), url)
+    }
+
+    async fn get_json<T: DeserializeOwned>(
+        &self,
+        path: &[&str],
+        query: &[(&str, String)],
+    ) -> Result<T, ClientError> {
+        let response = self
+            .http
+            .get(append_path(&self.rest_url, path))
+            .query(query)
+            .send()
+            .await?;
+        Ok(successful(response).await?.json().await?)
+    }
+
+    async fn post_json<B: Serialize, T: DeserializeOwned>(
+        &self,
+        path: &[&str],
+        body: &B,
+    ) -> Result<T, ClientError> {
+        let response = self
+            .http
+            .post(append_path(&self.rest_url, path))
+            .json(body)
+            .send()
+            .await?;
+        Ok(successful(response).await?.json().await?)
+    }
+
+    async fn get_bytes(&self, path: &[&str]) -> Result<Vec<u8>, ClientError> {
+        let response = self
+            .http
+            .get(append_path(&self.rest_url, path))
+            .send()
+            .await?;
+        Ok(successful(response).await?.bytes().await?.to_vec())
+    }
+}
+
+/// The body of a GraphQL request.
+#[derive(Serialize)]
+struct GraphQLRequest<'a> {
+    query: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    variables: Option<serde_json::Value>,
+}
+
+fn base_url(url: &str) -> Result<Url, ClientError> {
+    let url = Url::parse(url).map_err(|error| ClientError::InvalidUrl(error.to_string()))?;
+    if url.cannot_be_a_base() {
+        return Err(ClientError::InvalidUrl(format!(
+            "{} can't be the base of the sidecar's paths",
+            url
+        )));
+    }
+    Ok(url)
+}
+
+/// `base` followed by the percent-encoded `segments`.
+fn append_path(base: &Url, segments: &[&str]) -> Url {
+    let mut url = base.clone();
+    url.path_segments_mut()
+        .expect("base URLs are checked on creation")
+        .pop_if_empty()
+        .extend(segments);
+    url
+}
+
+async fn successful(response: Response) -> Result<Response, ClientError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    Err(ClientError::from_response(status, &body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use casper_event_types::sse_data::test_support::{
+        example_api_version, example_block_added_1_5_2,
+    };
+    use casper_types::testing::TestRng;
+    use futures_util::StreamExt;
+    use mockito::{Matcher, Mock, Server, ServerGuard};
+    use serde_json::{json, Value};
+    use types::{DeployState, NftEventKind, TokenTransferKind};
+
+    const BLOCK_HASH: &str = "ca52062424e9d5631a34b7b401e123927ce29d4bd10bc97c7df0aa752f131bb7";
+    const DEPLOY_HASH: &str = "f01544d37354c5f9b2c4956826d32f8e44198f94fb6752e87f422fe3071ab58a";
+    const CONTRACT_HASH: &str = "b1a1e0c7d3fa9bc6a9fe1e83c4b8e2f7c1b6d8e0a6f0bcb83e1c5e6a3f5b7c2d";
+    const ACCOUNT_HASH: &str = "ad7e091267d82c3b9ed1987cb780a005a550e6b3d1ca333b743e2dba70680877";
+
+    /// Mocks a `GET` of `path` responding with `body`.
+    async fn mock_get(server: &mut ServerGuard, path: &str, body: &Value) -> Mock {
+        server
+            .mock("GET", path)
+            .with_header("content-type", "application/json")
+            .with_body(body.to_string())
+            .create_async()
+            .await
+    }
+
+    fn transfer() -> Value {
+        json!({
+            "transfer_hash": "9f0cb3a2d1e4f5a6b7c8d9e0f1a2b3c4d5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0",
+            "deploy_hash": DEPLOY_HASH,
+            "block_hash": BLOCK_HASH,
+            "from": ACCOUNT_HASH,
+            "to": null,
+            "source": "uref-0101010101010101010101010101010101010101010101010101010101010101-007",
+            "target": "uref-0202020202020202020202020202020202020202020202020202020202020202-004",
+            "amount": "2500000000",
+            "gas": "0",
+            "id": 12345
+        })
+    }
+
+    fn deploy_status() -> Value {
+        json!({
+            "deploy_hash": DEPLOY_HASH,
+            "state": "executed_success",
+            "transitions": [
+                {"state": "pending", "timestamp": "2024-05-01 10:00:00"},
+                {"state": "executed_success", "timestamp": "2024-05-01 10:01:05.5"}
+            ]
+        })
+    }
+
+    #[tokio::test]
+    async fn should_fetch_typed_blocks() {
+        let mut rng = TestRng::new();
+        let block_added = BlockAdded::random(&mut rng);
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock(
+                "GET",
+                format!("/block/{}", block_added.hex_encoded_hash()).as_str(),
+            )
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_string(&block_added).unwrap())
+            .create_async()
+            .await;
+        let client = SidecarClient::new(&server.url(), &server.url()).unwrap();
+
+        let fetched = client
+            .get_block_by_hash(&block_added.hex_encoded_hash())
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(fetched.hex_encoded_hash(), block_added.hex_encoded_hash());
+        assert_eq!(fetched.get_height(), block_added.get_height());
+    }
+
+    #[tokio::test]
+    async fn should_report_missing_items_as_not_found() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", format!("/deploy/accepted/{}", BLOCK_HASH).as_str())
+            .with_status(404)
+            .with_body(r#"{"code":404,"message":"Query returned no data"}"#)
+            .create_async()
+            .await;
+        let client = SidecarClient::new(&server.url(), &server.url()).unwrap();
+
+        let result = client.get_deploy_accepted_by_hash(BLOCK_HASH).await;
+
+        assert!(
+            matches!(result, Err(ClientError::NotFound(message)) if message == "Query returned no data")
+        );
+    }
+
+    #[tokio::test]
+    async fn should_stream_typed_events_from_the_given_id() {
+        let body = format!(
+            "data:{}\n\nid:5\ndata:{}\n\n",
+            example_api_version(),
+            example_block_added_1_5_2(BLOCK_HASH, "7")
+        );
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/events/main")
+            .match_query(Matcher::UrlEncoded("start_from".into(), "5".into()))
+            .with_header("content-type", "text/event-stream")
+            .with_body(body)
+            .create_async()
+            .await;
+        let client = SidecarClient::new(&server.url(), &server.url()).unwrap();
+
+        let events: Vec<_> = client.subscribe(Filter::Main, Some(5)).collect().await;
+
+        mock.assert_async().await;
+        assert_eq!(events.len(), 2);
+        let api_version = events[0].as_ref().unwrap();
+        assert_eq!(api_version.id, None);
+        assert!(matches!(api_version.data, SseData::ApiVersion(_)));
+        let block_added = events[1].as_ref().unwrap();
+        assert_eq!(block_added.id, Some(5));
+        assert!(
+            matches!(&block_added.data, SseData::BlockAdded { block_hash, .. } if hex::encode(block_hash.inner()) == BLOCK_HASH)
+        );
+    }
+
+    #[tokio::test]
+    async fn should_fetch_transfers_by_account() {
+        let mut server = Server::new_async().await;
+        let mock = mock_get(
+            &mut server,
+            "/transfers/account/@treasury",
+            &json!([transfer()]),
+        )
+        .await;
+        let client = SidecarClient::new(&server.url(), &server.url()).unwrap();
+
+        let transfers = client.get_transfers_by_account("@treasury").await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].from, ACCOUNT_HASH);
+        assert_eq!(transfers[0].amount, "2500000000");
+    }
+
+    #[tokio::test]
+    async fn should_fetch_transfers_by_block() {
+        let mut server = Server::new_async().await;
+        let mock = mock_get(
+            &mut server,
+            format!("/transfers/block/{}", BLOCK_HASH).as_str(),
+            &json!([transfer()]),
+        )
+        .await;
+        let client = SidecarClient::new(&server.url(), &server.url()).unwrap();
+
+        let transfers = client.get_transfers_by_block(BLOCK_HASH).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].block_hash, BLOCK_HASH);
+        assert_eq!(transfers[0].to, None);
+    }
+
+    #[tokio::test]
+    async fn should_fetch_transfers_by_transfer_id() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/transfers")
+            .match_query(Matcher::UrlEncoded("transfer_id".into(), "12345".into()))
+            .with_header("content-type", "application/json")
+            .with_body(json!([transfer()]).to_string())
+            .create_async()
+            .await;
+        let client = SidecarClient::new(&server.url(), &server.url()).unwrap();
+
+        let transfers = client.get_transfers_by_transfer_id(12345).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].id, Some(12345));
+    }
+
+    #[tokio::test]
+    async fn should_fetch_token_transfers() {
+        let body = json!([{
+            "contract_hash": CONTRACT_HASH,
+            "event_id": 3,
+            "kind": "TransferFrom",
+            "from": format!("account-hash-{}", ACCOUNT_HASH),
+            "to": format!("hash-{}", CONTRACT_HASH),
+            "spender": format!("account-hash-{}", ACCOUNT_HASH),
+            "amount": "1000",
+            "deploy_hash": DEPLOY_HASH,
+            "block_hash": BLOCK_HASH
+        }]);
+        let mut server = Server::new_async().await;
+        let mock = mock_get(
+            &mut server,
+            format!("/tokens/{}/transfers", CONTRACT_HASH).as_str(),
+            &body,
+        )
+        .await;
+        let client = SidecarClient::new(&server.url(), &server.url()).unwrap();
+
+        let transfers = client.get_token_transfers(CONTRACT_HASH).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].kind, TokenTransferKind::TransferFrom);
+        assert_eq!(transfers[0].amount, "1000");
+    }
+
+    #[tokio::test]
+    async fn should_fetch_nft_events_of_collections_and_tokens() {
+        let body = json!([{
+            "contract_hash": CONTRACT_HASH,
+            "event_id": 0,
+            "kind": "Mint",
+            "token_id": "42",
+            "owner": null,
+            "recipient": format!("account-hash-{}", ACCOUNT_HASH),
+            "spender": null,
+            "deploy_hash": DEPLOY_HASH,
+            "block_hash": BLOCK_HASH
+        }]);
+        let mut server = Server::new_async().await;
+        let collection_mock = mock_get(
+            &mut server,
+            format!("/nfts/{}/events", CONTRACT_HASH).as_str(),
+            &body,
+        )
+        .await;
+        let token_mock = mock_get(
+            &mut server,
+            format!("/nfts/{}/tokens/42/events", CONTRACT_HASH).as_str(),
+            &body,
+        )
+        .await;
+        let client = SidecarClient::new(&server.url(), &server.url()).unwrap();
+
+        let collection_events = client
+            .get_nft_events_by_collection(CONTRACT_HASH)
+            .await
+            .unwrap();
+        let token_events = client
+            .get_nft_events_by_token(CONTRACT_HASH, "42")
+            .await
+            .unwrap();
+
+        collection_mock.assert_async().await;
+        token_mock.assert_async().await;
+        assert_eq!(collection_events, token_events);
+        assert_eq!(token_events[0].kind, NftEventKind::Mint);
+        assert_eq!(token_events[0].token_id, "42");
+    }
+
+    #[tokio::test]
+    async fn should_fetch_deploy_approvals() {
+        let body = json!({
+            "deploy_hash": DEPLOY_HASH,
+            "account_hash": ACCOUNT_HASH,
+            "approvals": [
+                {"signer": "01aa", "weight": 1, "added_later": false},
+                {"signer": "01bb", "weight": 1, "added_later": true}
+            ],
+            "collected_weight": 2,
+            "deployment_threshold": 2,
+            "threshold_reached": true
+        });
+        let mut server = Server::new_async().await;
+        let mock = mock_get(
+            &mut server,
+            format!("/deploy/{}/approvals", DEPLOY_HASH).as_str(),
+            &body,
+        )
+        .await;
+        let client = SidecarClient::new(&server.url(), &server.url()).unwrap();
+
+        let approvals = client.get_deploy_approvals(DEPLOY_HASH).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(approvals.approvals.len(), 2);
+        assert!(approvals.approvals[1].added_later);
+        assert_eq!(approvals.threshold_reached, Some(true));
+    }
+
+    #[tokio::test]
+    async fn should_fetch_deploy_status() {
+        let mut server = Server::new_async().await;
+        let mock = mock_get(
+            &mut server,
+            format!("/deploy/{}/status", DEPLOY_HASH).as_str(),
+            &deploy_status(),
+        )
+        .await;
+        let client = SidecarClient::new(&server.url(), &server.url()).unwrap();
+
+        let status = client.get_deploy_status(DEPLOY_HASH).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(status.state, DeployState::ExecutedSuccess);
+        assert_eq!(status.transitions.len(), 2);
+        assert_eq!(status.transitions[0].state, DeployState::Pending);
+    }
+
+    #[tokio::test]
+    async fn should_await_deploys_with_the_given_timeout() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", format!("/deploy/{}/await", DEPLOY_HASH).as_str())
+            .match_query(Matcher::UrlEncoded("timeout".into(), "90s".into()))
+            .with_header("content-type", "application/json")
+            .with_body(deploy_status().to_string())
+            .create_async()
+            .await;
+        let client = SidecarClient::new(&server.url(), &server.url()).unwrap();
+
+        let status = client
+            .await_deploy(DEPLOY_HASH, Some(Duration::from_secs(90)))
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(status.state, DeployState::ExecutedSuccess);
+    }
+
+    #[tokio::test]
+    async fn should_fetch_block_finality_signatures() {
+        let body = json!({
+            "block_hash": BLOCK_HASH,
+            "era_id": 7,
+            "signatures": [],
+            "signers": ["01aa", "01bb"],
+            "checkpointed": true,
+            "signed_weight": "200",
+            "total_weight": "300"
+        });
+        let mut server = Server::new_async().await;
+        let mock = mock_get(
+            &mut server,
+            format!("/block/{}/finality_signatures", BLOCK_HASH).as_str(),
+            &body,
+        )
+        .await;
+        let client = SidecarClient::new(&server.url(), &server.url()).unwrap();
+
+        let signatures = client
+            .get_block_finality_signatures(BLOCK_HASH)
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert!(signatures.checkpointed);
+        assert_eq!(signatures.signers.len(), 2);
+        assert_eq!(signatures.signed_weight.as_deref(), Some("200"));
+    }
+
+    #[tokio::test]
+    async fn should_fetch_the_validator_set_at_the_given_height() {
+        let body = json!({"height": 100, "era_id": 7, "validators": ["01aa", "01bb"]});
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/validators")
+            .match_query(Matcher::UrlEncoded("at_height".into(), "100".into()))
+            .with_header("content-type", "application/json")
+            .with_body(body.to_string())
+            .create_async()
+            .await;
+        let client = SidecarClient::new(&server.url(), &server.url()).unwrap();
+
+        let validator_set = client.get_validator_set(Some(100)).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(validator_set.era_id, 7);
+        assert_eq!(validator_set.validators, vec!["01aa", "01bb"]);
+    }
+
+    #[tokio::test]
+    async fn should_post_graphql_queries() {
+        let query = "query($height: Int!) { block(height: $height) { hash } }";
+        let variables = json!({"height": 100});
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/graphql")
+            .match_body(Matcher::Json(
+                json!({"query": query, "variables": variables}),
+            ))
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "data": {"block": null},
+                    "errors": [{"message": "Query returned no data", "path": ["block"]}]
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+        let client = SidecarClient::new(&server.url(), &server.url()).unwrap();
+
+        let response = client
+            .query_graphql(query, Some(variables.clone()))
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(response.data, json!({"block": null}));
+        assert_eq!(response.errors.len(), 1);
+        assert_eq!(response.errors[0].path, vec![json!("block")]);
+    }
+
+    #[test]
+    fn should_percent_encode_path_segments() {
+        let base = base_url("http://127.0.0.1:18888/sidecar/").unwrap();
+
+        assert_eq!(
+            append_path(&base, &["deploys", "account", "@treasury wallet"]).as_str(),
+            "http://127.0.0.1:18888/sidecar/deploys/account/@treasury%20wallet"
+        );
+    }
+}
@@ -0,0 +1,343 @@
+//! The responses of the REST API which aren't events. Events are returned as the types of
+//! [casper_event_types::sse_events], shared with the sidecar.
+
+use casper_event_types::{
+    sse_events::{DeployAccepted, DeployProcessed, Step},
+    FinalitySignature,
+};
+use serde::{Deserialize, Serialize};
+
+/// The events stored for a deploy.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DeployAggregate {
+    pub deploy_hash: String,
+    pub deploy_accepted: Option<DeployAccepted>,
+    pub deploy_processed: Option<DeployProcessed>,
+    pub deploy_expired: bool,
+}
+
+/// The event of a deploy recorded in a [DeployTimestampEntry].
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DeployTimestampKind {
+    /// The deploy was accepted by the node, timestamped with the time it was stored.
+    Accepted,
+    /// The deploy was processed, timestamped with the timestamp of its block.
+    Processed,
+}
+
+/// A deploy accepted or processed within a time range.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct DeployTimestampEntry {
+    pub deploy_hash: String,
+    pub kind: DeployTimestampKind,
+    /// Milliseconds since the UNIX epoch at which the deploy was accepted or processed.
+    pub timestamp: u64,
+}
+
+/// A deploy whose execution failed, along with its error message.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct DeployErrorEntry {
+    pub deploy_hash: String,
+    /// Hash of the block the deploy was executed in.
+    pub block_hash: String,
+    pub error_message: String,
+}
+
+/// The approvals of a deploy along with the weight they collected.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct DeployApprovals {
+    pub deploy_hash: String,
+    /// Hex-encoded hash of the account which sent the deploy.
+    pub account_hash: String,
+    pub approvals: Vec<SignerApproval>,
+    /// Sum of the weights of the signers, if the keys of the account are known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collected_weight: Option<u32>,
+    /// Weight the approvals need to collect for the deploy to be executed, if the keys of the
+    /// account are known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deployment_threshold: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold_reached: Option<bool>,
+}
+
+/// An approval of a deploy.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct SignerApproval {
+    /// Hex-encoded public key of the signer.
+    pub signer: String,
+    /// Weight of the key of the signer for the account, `0` if it isn't one of its associated
+    /// keys, if the keys of the account are known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<u8>,
+    /// Whether the approval was only carried by a later acceptance of the deploy.
+    pub added_later: bool,
+}
+
+/// A state of the lifecycle of a deploy.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeployState {
+    /// The deploy was accepted by the node and awaits its execution.
+    Pending,
+    /// The deploy expired before being executed.
+    Expired,
+    /// The deploy was executed successfully.
+    ExecutedSuccess,
+    /// The execution of the deploy failed.
+    ExecutedFailure,
+}
+
+/// A transition of a deploy into a state.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct DeployTransition {
+    pub state: DeployState,
+    /// UTC time the event causing the transition was stored at, formatted as
+    /// `YYYY-MM-DD HH:MM:SS`, possibly followed by fractional seconds.
+    pub timestamp: String,
+}
+
+/// The current state of a deploy along with the transitions which led to it.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct DeployStatus {
+    pub deploy_hash: String,
+    pub state: DeployState,
+    /// Transitions of the deploy, oldest first.
+    pub transitions: Vec<DeployTransition>,
+}
+
+/// A deploy along with the account which sent it.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct DeployAccountEntry {
+    pub deploy_hash: String,
+    /// Hex-encoded hash of the account which sent the deploy.
+    pub account_hash: String,
+    /// Alias of the account, if known to the sidecar.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_alias: Option<String>,
+}
+
+/// A deploy along with an account whose state its execution touched.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct AccountEffectEntry {
+    pub deploy_hash: String,
+    /// Hex-encoded hash of the account affected by the deploy.
+    pub account_hash: String,
+}
+
+/// A native transfer recorded by the execution of a processed deploy.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct TransferEntry {
+    /// Hex-encoded address of the transfer.
+    pub transfer_hash: String,
+    pub deploy_hash: String,
+    /// Hash of the block the deploy was processed in.
+    pub block_hash: String,
+    /// Hex-encoded hash of the account which sent the transfer.
+    pub from: String,
+    /// Hex-encoded hash of the recipient account, if the transfer named one.
+    pub to: Option<String>,
+    /// Formatted URef of the purse the motes were taken from.
+    pub source: String,
+    /// Formatted URef of the purse the motes were sent to.
+    pub target: String,
+    /// Amount of motes transferred, in decimal.
+    pub amount: String,
+    /// Gas spent by the transfer, in decimal.
+    pub gas: String,
+    /// User-defined id of the transfer.
+    pub id: Option<u64>,
+}
+
+/// The event of a CEP-18 token recorded in a [TokenTransferEntry].
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum TokenTransferKind {
+    /// Tokens were minted to the recipient.
+    Mint,
+    /// Tokens of the owner were burnt.
+    Burn,
+    /// The sender transferred its tokens.
+    Transfer,
+    /// A spender transferred the tokens of their owner within its allowance.
+    TransferFrom,
+}
+
+/// A transfer of a CEP-18 token, decoded from an event its contract emitted.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct TokenTransferEntry {
+    /// Hex-encoded hash of the token contract.
+    pub contract_hash: String,
+    /// Number of the event among the events of the contract, in the order they were emitted.
+    pub event_id: u64,
+    pub kind: TokenTransferKind,
+    /// Formatted key of the account or contract the tokens were taken from, unless minted.
+    pub from: Option<String>,
+    /// Formatted key of the account or contract the tokens were sent to, unless burnt.
+    pub to: Option<String>,
+    /// Formatted key of the spender of a `TransferFrom`.
+    pub spender: Option<String>,
+    /// Amount of tokens transferred, in decimal.
+    pub amount: String,
+    pub deploy_hash: String,
+    /// Hash of the block the deploy was processed in.
+    pub block_hash: String,
+}
+
+/// The event of a CEP-78 collection recorded in an [NftEventEntry].
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum NftEventKind {
+    /// The token was minted to the recipient.
+    Mint,
+    /// The token of the owner was burnt by the spender.
+    Burn,
+    /// The token was transferred from the owner to the recipient, by the spender if given.
+    Transfer,
+    /// The owner approved the spender to transfer the token.
+    Approval,
+}
+
+/// An event of a CEP-78 NFT collection, decoded from an event its contract emitted.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct NftEventEntry {
+    /// Hex-encoded hash of the collection contract.
+    pub contract_hash: String,
+    /// Number of the event among the events of the contract, in the order they were emitted.
+    pub event_id: u64,
+    pub kind: NftEventKind,
+    /// Identifier of the token within the collection, its index or its hash depending on the
+    /// identifier mode of the collection.
+    pub token_id: String,
+    /// Formatted key of the owner of the token, unless minted.
+    pub owner: Option<String>,
+    /// Formatted key of the account or contract the token was sent to by a `Mint` or `Transfer`.
+    pub recipient: Option<String>,
+    /// Formatted key of the burner of a `Burn`, the approved spender of an `Approval`, or the
+    /// operator of a `Transfer` not made by the owner.
+    pub spender: Option<String>,
+    pub deploy_hash: String,
+    /// Hash of the block the deploy was processed in.
+    pub block_hash: String,
+}
+
+/// Outcomes of the deploys calling a contract on one day, and the gas they spent.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct ContractDayStats {
+    /// UTC date of the deploys' timestamps, formatted as `YYYY-MM-DD`.
+    pub day: String,
+    pub successes: u64,
+    pub failures: u64,
+    pub gas_spent: u64,
+}
+
+/// Distribution of a measure of the accepted deploys. Percentiles are nearest-rank ones.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Distribution {
+    pub min: u64,
+    pub max: u64,
+    pub mean: f64,
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+}
+
+/// Distribution statistics of the size and weight of the accepted deploys.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct DeploySizeStats {
+    /// Number of the deploys the statistics are computed from.
+    pub deploys: u64,
+    /// Length, in bytes, of the serialized deploys.
+    pub size_bytes: Distribution,
+    /// Number of the approvals of the deploys.
+    pub approvals: Distribution,
+    /// Number of the runtime args of the payment and session of the deploys.
+    pub args: Distribution,
+}
+
+/// A validator reported in a fault of an era.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct Equivocator {
+    /// Hex-encoded public key of the validator.
+    pub public_key: String,
+    pub era_id: u64,
+    /// Time of the earliest fault of the validator in the era.
+    pub fault_timestamp: String,
+    /// Whether the validator is missing from the validator set of the next era. Unknown until the
+    /// switch block of the era is stored.
+    pub ejected: Option<bool>,
+}
+
+/// The validators of the era of a block.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct ValidatorSet {
+    pub height: u64,
+    pub era_id: u64,
+    /// Hex-encoded public keys of the validators, ordered.
+    pub validators: Vec<String>,
+}
+
+/// The finality signatures of a block along with the weight of the validators which signed it.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct BlockFinalitySignatures {
+    pub block_hash: String,
+    pub era_id: u64,
+    /// The stored signatures, which don't include those replaced with a checkpoint.
+    pub signatures: Vec<FinalitySignature>,
+    /// Hex-encoded public keys of the validators which signed the block, in order.
+    pub signers: Vec<String>,
+    /// Whether signatures of the block were replaced with a checkpoint.
+    #[serde(default)]
+    pub checkpointed: bool,
+    /// Sum of the weights of the validators which signed the block, in decimal, if the weights of
+    /// the validators of the era are known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signed_weight: Option<String>,
+    /// Sum of the weights of all the validators of the era, in decimal, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_weight: Option<String>,
+}
+
+/// Context fetched from a node's RPC server and attached to a stored block or deploy.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct Enrichment {
+    /// Hex-encoded hash of the block or deploy the context belongs to.
+    pub entity_hash: String,
+    /// The kind of context, e.g. `era_summary`.
+    pub kind: String,
+    /// The context as returned by the node.
+    pub value: serde_json::Value,
+}
+
+/// A proof of global state with the stored events related to the queried key.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct StateProof {
+    pub state_root_hash: String,
+    pub key: String,
+    /// Result of the node's `query_global_state`, including the stored value and the Merkle proof.
+    pub proof: serde_json::Value,
+    /// The stored deploy, if the key is the `deploy-` key of a stored deploy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deploy: Option<DeployAggregate>,
+    /// The stored step, if the key is the `era-` key of an era with a stored step.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<Step>,
+}
+
+/// The response to a GraphQL query: the selected data, along with the errors of the fields which
+/// couldn't be resolved.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct GraphQLResponse {
+    #[serde(default)]
+    pub data: serde_json::Value,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<GraphQLError>,
+}
+
+/// An error of a GraphQL query.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct GraphQLError {
+    pub message: String,
+    /// Path of the field the error is about, if any.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub path: Vec<serde_json::Value>,
+}
@@ -0,0 +1,20 @@
+//! Feeds arbitrary bytes to the event stream parser used for node connections, then checks the
+//! `data` field of every parsed event like the `sse_data_deserialize` target does.
+
+#![no_main]
+
+use bytes::Bytes;
+use casper_event_types::arbitrary::check_default_sse_data_round_trip;
+use eventsource_stream::Eventsource;
+use futures::{executor::block_on, stream, StreamExt};
+use libfuzzer_sys::fuzz_target;
+use std::convert::Infallible;
+
+fuzz_target!(|data: &[u8]| {
+    let frames = stream::iter(vec![Ok::<_, Infallible>(Bytes::copy_from_slice(data))]);
+    block_on(frames.eventsource().for_each(|maybe_event| async move {
+        if let Ok(event) = maybe_event {
+            check_default_sse_data_round_trip(&event.data);
+        }
+    }));
+});
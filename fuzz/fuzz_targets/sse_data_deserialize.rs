@@ -0,0 +1,13 @@
+//! Feeds arbitrary input to the `data` field deserializer and checks that everything it accepts
+//! survives a serialization round trip.
+
+#![no_main]
+
+use casper_event_types::arbitrary::check_default_sse_data_round_trip;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(json_raw) = std::str::from_utf8(data) {
+        check_default_sse_data_round_trip(json_raw);
+    }
+});
@@ -6,8 +6,9 @@ use crate::{
 use anyhow::Error;
 use async_trait::async_trait;
 use casper_event_types::{
+    capture::CapturedFrame,
     metrics,
-    sse_data::{deserialize, SseData},
+    sse_data::{SseData, SseDataDeserializer},
     Filter,
 };
 use eventsource_stream::Event;
@@ -16,11 +17,11 @@ use reqwest::Url;
 use std::{
     fmt::{self, Debug, Display},
     pin::Pin,
-    time::Duration,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 use tokio::sync::mpsc::Sender;
 use tokio_stream::StreamExt;
-use tracing::{error, trace, warn};
+use tracing::{error, field, info_span, trace, warn, Instrument, Span};
 
 const API_VERSION: &str = "ApiVersion";
 const FETCHING_FROM_STREAM_FAILED: &str = "fetching_from_stream_failed";
@@ -31,6 +32,7 @@ const DESERIALIZATION_ERROR: &str = "deserialization_error";
 const EVENT_WITHOUT_ID: &str = "event_without_id";
 const SENDING_FAILED: &str = "sending_downstream_failed";
 const API_VERSION_SENDING_FAILED: &str = "api_version_sending_failed";
+const CAPTURE_SENDING_FAILED: &str = "capture_sending_failed";
 const API_VERSION_DESERIALIZATION_FAILED: &str = "api_version_deserialization_failed";
 const API_VERSION_EXPECTED: &str = "api_version_expected";
 const OTHER_TYPE_OF_MESSAGE_WHEN_API_VERSION_EXPECTED: &str =
@@ -50,6 +52,8 @@ pub struct DefaultConnectionManager {
     maybe_tasks: Option<ConnectionTasks>,
     filter: Filter,
     current_event_id_sender: Sender<(Filter, u32)>,
+    deserializer: SseDataDeserializer,
+    capture_sender: Option<Sender<CapturedFrame>>,
 }
 
 #[derive(Debug)]
@@ -99,6 +103,12 @@ pub struct DefaultConnectionManagerBuilder {
     pub(super) sleep_between_keep_alive_checks: Duration,
     /// Time of inactivity of a node connection that is allowed by KeepAliveMonitor
     pub(super) no_message_timeout: Duration,
+    /// Function used to turn the data of each received event into [SseData]
+    pub(super) deserializer: SseDataDeserializer,
+    /// If set, every raw frame received from the node is sent to this channel to be recorded
+    pub(super) capture_sender: Option<Sender<CapturedFrame>>,
+    /// If set, every keepalive comment received from the node is reported to this channel
+    pub(super) keep_alive_sender: Option<Sender<()>>,
 }
 
 #[async_trait::async_trait]
@@ -117,6 +127,7 @@ impl DefaultConnectionManagerBuilder {
             bind_address: self.bind_address.clone(),
             sleep_between_keepalive_checks: self.sleep_between_keep_alive_checks,
             no_message_timeout: self.no_message_timeout,
+            keep_alive_sender: self.keep_alive_sender,
         });
         DefaultConnectionManager {
             connector,
@@ -126,6 +137,8 @@ impl DefaultConnectionManagerBuilder {
             maybe_tasks: self.maybe_tasks,
             filter: self.filter,
             current_event_id_sender: self.current_event_id_sender,
+            deserializer: self.deserializer,
+            capture_sender: self.capture_sender,
         }
     }
 }
@@ -183,6 +196,7 @@ impl DefaultConnectionManager {
         while let Some(event) = receiver.next().await {
             match event {
                 Ok(event) => {
+                    self.capture(&event).await;
                     match event.id.parse::<u32>() {
                         Ok(id) => {
                             self.current_event_id = Some(id);
@@ -221,35 +235,51 @@ impl DefaultConnectionManager {
     }
 
     async fn handle_event(&mut self, event: Event) -> Result<(), Error> {
-        match deserialize(&event.data) {
-            Err(serde_error) => {
+        let span = info_span!(
+            "ingest_event",
+            source = %self.bind_address,
+            filter = %self.filter,
+            event_id = %event.id,
+            event_type = field::Empty,
+        );
+        let (sse_data, needs_raw_json) = self.deserialize(&event.data, &span)?;
+        let payload_size = event.data.len();
+        let mut raw_json_data = None;
+        if needs_raw_json {
+            raw_json_data = Some(event.data);
+        }
+        self.observe_bytes(payload_size);
+        span.record("event_type", sse_data.type_label());
+        let sse_event = SseEvent::new(
+            event.id.parse().unwrap_or(0),
+            sse_data,
+            self.bind_address.clone(),
+            raw_json_data,
+            self.filter.clone(),
+        )
+        .with_span(span.clone());
+        // Waiting for room in the channel is where a sidecar lagging behind shows first.
+        self.sse_event_sender
+            .send(sse_event)
+            .instrument(info_span!(parent: &span, "enqueue"))
+            .await
+            .map_err(|_| {
+                count_error(SENDING_FAILED);
+                Error::msg(ERROR_WHEN_TRYING_TO_SEND_MESSAGE)
+            })?;
+        Ok(())
+    }
+
+    fn deserialize(&self, data: &str, span: &Span) -> Result<(SseData, bool), Error> {
+        info_span!(parent: span, "deserialize", bytes = data.len())
+            .in_scope(|| (self.deserializer)(data))
+            .map_err(|serde_error| {
                 let reason = format!("{}:{}", DESERIALIZATION_ERROR, self.filter);
                 count_error(&reason);
                 let error_message = format!("Serde Error: {}", serde_error);
                 error!(error_message);
-                return Err(Error::msg(error_message));
-            }
-            Ok((sse_data, needs_raw_json)) => {
-                let payload_size = event.data.len();
-                let mut raw_json_data = None;
-                if needs_raw_json {
-                    raw_json_data = Some(event.data);
-                }
-                self.observe_bytes(payload_size);
-                let sse_event = SseEvent::new(
-                    event.id.parse().unwrap_or(0),
-                    sse_data,
-                    self.bind_address.clone(),
-                    raw_json_data,
-                    self.filter.clone(),
-                );
-                self.sse_event_sender.send(sse_event).await.map_err(|_| {
-                    count_error(SENDING_FAILED);
-                    Error::msg(ERROR_WHEN_TRYING_TO_SEND_MESSAGE)
-                })?;
-            }
-        }
-        Ok(())
+                Error::msg(error_message)
+            })
     }
 
     async fn consume_api_version(
@@ -263,6 +293,7 @@ impl DefaultConnectionManager {
             None => Err(recoverable_error(Error::msg(FIRST_EVENT_EMPTY))),
             Some(Err(error)) => Err(failed_to_get_first_event(error)),
             Some(Ok(event)) => {
+                self.capture(&event).await;
                 let payload_size = event.data.len();
                 self.observe_bytes(payload_size);
                 if event.data.contains(API_VERSION) {
@@ -280,7 +311,7 @@ impl DefaultConnectionManager {
         receiver: Pin<Box<dyn Stream<Item = EventResult> + Send + 'static>>,
     ) -> Result<Pin<Box<dyn Stream<Item = EventResult> + Send + 'static>>, ConnectionManagerError>
     {
-        match deserialize(&event.data) {
+        match (self.deserializer)(&event.data) {
             //at this point we
             // are assuming that it's an ApiVersion and ApiVersion is the same across all semvers
             Ok((SseData::ApiVersion(semver), _)) => {
@@ -313,6 +344,31 @@ impl DefaultConnectionManager {
         Ok(receiver)
     }
 
+    /// Sends the raw frame to the capture, if one is configured. Failing to record a frame doesn't
+    /// interrupt the connection.
+    async fn capture(&self, event: &Event) {
+        if let Some(capture_sender) = &self.capture_sender {
+            let received_at = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_millis() as u64)
+                .unwrap_or_default();
+            let frame = CapturedFrame {
+                received_at,
+                source: self.bind_address.origin().ascii_serialization(),
+                filter: self.filter.to_string(),
+                id: (!event.id.is_empty()).then(|| event.id.clone()),
+                data: event.data.clone(),
+            };
+            if capture_sender.send(frame).await.is_err() {
+                count_error(CAPTURE_SENDING_FAILED);
+                warn!(
+                    "Capture stopped, frame from {} not recorded",
+                    self.bind_address
+                );
+            }
+        }
+    }
+
     fn observe_bytes(&self, payload_size: usize) {
         metrics::RECEIVED_BYTES
             .with_label_values(&[self.filter.to_string().as_str()])
@@ -367,8 +423,16 @@ pub mod tests {
         SseEvent,
     };
     use anyhow::Error;
-    use casper_event_types::{sse_data::test_support::*, Filter};
-    use std::time::Duration;
+    use bytes::Bytes;
+    use casper_event_types::{
+        arbitrary,
+        sse_data::{deserialize, test_support::*, SseData, SseDataDeserializeError},
+        Filter,
+    };
+    use eventsource_stream::Eventsource;
+    use futures::{executor::block_on, stream::iter, StreamExt};
+    use proptest::prelude::*;
+    use std::{convert::Infallible, time::Duration};
     use tokio::{
         sync::mpsc::{channel, Receiver, Sender},
         time::sleep,
@@ -460,6 +524,69 @@ pub mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn given_injected_deserializer_should_use_it_for_every_message() {
+        let block_added = example_block_added_1_5_2(BLOCK_HASH_1, "1");
+        let data = vec![example_api_version(), block_added.clone()];
+        let connector = Box::new(MockSseConnection::build_with_data(data));
+        let (mut connection_manager, data_tx, _event_ids) = build_manager(connector);
+        connection_manager.deserializer = deserialize_keeping_raw_json;
+        let events_join = tokio::spawn(async move { poll_events(data_tx).await });
+        tokio::spawn(async move { connection_manager.do_start_handling().await });
+        let events = events_join.await.unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].json_data, Some(block_added));
+    }
+
+    #[tokio::test]
+    async fn given_capture_sender_should_record_every_frame() {
+        let block_added = example_block_added_1_5_2(BLOCK_HASH_1, "1");
+        let data = vec![example_api_version(), block_added.clone()];
+        let connector = Box::new(MockSseConnection::build_with_data(data));
+        let (mut connection_manager, data_tx, _event_ids) = build_manager(connector);
+        let (capture_tx, capture_rx) = channel(100);
+        connection_manager.capture_sender = Some(capture_tx);
+        let frames_join = tokio::spawn(async move { poll_events(capture_rx).await });
+        tokio::spawn(async move { connection_manager.do_start_handling().await });
+        let events = poll_events(data_tx).await;
+        let frames = frames_join.await.unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].data, example_api_version());
+        assert_eq!(frames[1].id, Some("1".to_string()));
+        assert_eq!(frames[1].data, block_added);
+        assert_eq!(frames[1].source, "http://localhost:123");
+        assert_eq!(frames[1].filter, Filter::Sigs.to_string());
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+        #[test]
+        fn node_frames_should_round_trip_to_sse_data(
+            json in arbitrary::sse_data_json(),
+            id in any::<u32>()
+        ) {
+            let frame = format!("id:{}\ndata:{}\n\n", id, json);
+            let events = block_on(
+                iter(vec![Ok::<_, Infallible>(Bytes::from(frame))])
+                    .eventsource()
+                    .collect::<Vec<_>>(),
+            );
+            prop_assert_eq!(events.len(), 1);
+            let event = events.into_iter().next().unwrap().unwrap();
+            prop_assert_eq!(event.id, id.to_string());
+            prop_assert_eq!(&event.data, &json);
+            prop_assert!(deserialize(&event.data).is_ok());
+            arbitrary::check_default_sse_data_round_trip(&event.data);
+        }
+    }
+
+    fn deserialize_keeping_raw_json(
+        json_raw: &str,
+    ) -> Result<(SseData, bool), SseDataDeserializeError> {
+        deserialize(json_raw).map(|(sse_data, _)| (sse_data, true))
+    }
+
     pub async fn poll_events<T>(mut receiver: Receiver<T>) -> Vec<T> {
         let mut events_received = Vec::new();
         while let Some(event) = receiver.recv().await {
@@ -486,6 +613,8 @@ pub mod tests {
             maybe_tasks: None,
             filter: Filter::Sigs,
             current_event_id_sender: event_id_tx,
+            deserializer: deserialize,
+            capture_sender: None,
         };
         (manager, data_rx, event_id_rx)
     }
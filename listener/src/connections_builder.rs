@@ -1,6 +1,6 @@
 use anyhow::Error;
 use async_trait::async_trait;
-use casper_event_types::Filter;
+use casper_event_types::{capture::CapturedFrame, sse_data::deserialize, Filter};
 use casper_types::ProtocolVersion;
 use std::{collections::HashMap, net::IpAddr, sync::Arc, time::Duration};
 use tokio::sync::{mpsc::Sender, Mutex};
@@ -31,6 +31,8 @@ pub struct DefaultConnectionsBuilder {
     pub ip_address: IpAddr,
     pub sse_port: u16,
     pub allow_partial_connection: bool,
+    pub capture_sender: Option<Sender<CapturedFrame>>,
+    pub keep_alive_sender: Option<Sender<()>>,
 }
 
 #[async_trait]
@@ -84,6 +86,9 @@ impl DefaultConnectionsBuilder {
             current_event_id_sender: last_seen_event_id_sender,
             sleep_between_keep_alive_checks: self.sleep_between_keep_alive_checks,
             no_message_timeout: self.no_message_timeout,
+            deserializer: deserialize,
+            capture_sender: self.capture_sender.clone(),
+            keep_alive_sender: self.keep_alive_sender.clone(),
         };
         Ok(Box::new(builder.build()))
     }
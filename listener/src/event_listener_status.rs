@@ -34,5 +34,32 @@ impl EventListenerStatus {
         metrics::NODE_STATUSES
             .with_label_values(&[node_label.as_str()])
             .set(status);
+        if let EventListenerStatus::Reconnecting = self {
+            metrics::UPSTREAM_RECONNECTS
+                .with_label_values(&[node_label.as_str()])
+                .inc();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_count_reconnections() {
+        EventListenerStatus::Connected.log_status("127.0.0.1", 9996);
+        EventListenerStatus::Reconnecting.log_status("127.0.0.1", 9996);
+        EventListenerStatus::Connected.log_status("127.0.0.1", 9996);
+        EventListenerStatus::Reconnecting.log_status("127.0.0.1", 9996);
+
+        let reconnects = metrics::UPSTREAM_RECONNECTS
+            .with_label_values(&["127.0.0.1:9996"])
+            .get();
+        assert_eq!(reconnects, 2);
+        let status = metrics::NODE_STATUSES
+            .with_label_values(&["127.0.0.1:9996"])
+            .get();
+        assert_eq!(status, 3.0);
     }
 }
@@ -0,0 +1,172 @@
+use std::{
+    collections::BTreeSet,
+    time::{Duration, Instant},
+};
+
+/// Outcome of observing the event IDs received from a node.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct GapOutcome {
+    /// Number of missing events received after they were fetched again.
+    pub(crate) recovered: u64,
+    /// Number of missing events given up on, as fetching them again didn't bring them back.
+    pub(crate) lost: u64,
+    /// If set, the events from this ID on should be fetched again with `?start_from=<ID>`.
+    pub(crate) refetch_from: Option<u32>,
+}
+
+/// Detects the event IDs a node skipped.
+///
+/// A node numbers the events of all its filters with a single sequence, so the IDs received on one
+/// filter skip those of the events of the other filters. The IDs received on all filters combined
+/// don't, but as the filters are separate streams they can arrive slightly out of order. An ID is
+/// therefore only considered missing once later IDs were received for longer than the grace period.
+/// Missing IDs are fetched again once; if they're still missing a grace period after that, they're
+/// considered lost.
+pub(crate) struct GapDetector {
+    grace_period: Duration,
+    /// The lowest ID neither received nor given up on, once any ID was received.
+    next: Option<u32>,
+    /// The IDs above `next` received already.
+    received: BTreeSet<u32>,
+    /// Since when `next` has been missing while later IDs were received.
+    missing_since: Option<Instant>,
+    /// The IDs below this one were fetched again already.
+    refetched_below: u32,
+}
+
+impl GapDetector {
+    pub(crate) fn new(grace_period: Duration) -> Self {
+        GapDetector {
+            grace_period,
+            next: None,
+            received: BTreeSet::new(),
+            missing_since: None,
+            refetched_below: 0,
+        }
+    }
+
+    /// The ID from which the events are being fetched again, if missing IDs weren't received or
+    /// given up on yet since they were requested.
+    pub(crate) fn pending_refetch(&self) -> Option<u32> {
+        self.next.filter(|next| *next < self.refetched_below)
+    }
+
+    /// Records that the event with the given ID was received at `now`.
+    pub(crate) fn observe(&mut self, id: u32, now: Instant) -> GapOutcome {
+        let next = *self.next.get_or_insert(id);
+        let mut outcome = GapOutcome::default();
+        if id >= next && self.received.insert(id) && id < self.refetched_below {
+            outcome.recovered += 1;
+        }
+        self.advance(now);
+        let checked = self.check(now);
+        outcome.lost = checked.lost;
+        outcome.refetch_from = checked.refetch_from;
+        outcome
+    }
+
+    /// Checks whether the missing IDs have been missing for longer than the grace period at `now`.
+    pub(crate) fn check(&mut self, now: Instant) -> GapOutcome {
+        let mut outcome = GapOutcome::default();
+        let (Some(next), Some(missing_since), Some(&first_received)) =
+            (self.next, self.missing_since, self.received.first())
+        else {
+            return outcome;
+        };
+        if now.duration_since(missing_since) < self.grace_period {
+            return outcome;
+        }
+        if next < self.refetched_below {
+            let given_up_below = first_received.min(self.refetched_below);
+            outcome.lost = u64::from(given_up_below - next);
+            self.next = Some(given_up_below);
+            self.missing_since = None;
+            self.advance(now);
+        } else {
+            outcome.refetch_from = Some(next);
+            self.refetched_below = first_received;
+            self.missing_since = Some(now);
+        }
+        outcome
+    }
+
+    fn advance(&mut self, now: Instant) {
+        let Some(next) = self.next.as_mut() else {
+            return;
+        };
+        while self.received.remove(next) {
+            *next += 1;
+        }
+        if self.received.is_empty() {
+            self.missing_since = None;
+        } else {
+            self.missing_since.get_or_insert(now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn should_not_report_ids_arriving_out_of_order_within_the_grace_period() {
+        let start = Instant::now();
+        let mut detector = GapDetector::new(GRACE_PERIOD);
+
+        for id in [1, 2, 4, 3, 5] {
+            assert_eq!(detector.observe(id, start), GapOutcome::default());
+        }
+        assert_eq!(
+            detector.check(start + GRACE_PERIOD * 2),
+            GapOutcome::default()
+        );
+    }
+
+    #[test]
+    fn should_refetch_missing_ids_and_count_those_recovered() {
+        let start = Instant::now();
+        let mut detector = GapDetector::new(GRACE_PERIOD);
+        for id in [1, 2, 5, 6] {
+            detector.observe(id, start);
+        }
+
+        let outcome = detector.observe(7, start + GRACE_PERIOD);
+
+        assert_eq!(outcome.refetch_from, Some(3));
+        assert_eq!(detector.pending_refetch(), Some(3));
+        let recovered: u64 = [3, 4, 5, 6, 7]
+            .into_iter()
+            .map(|id| detector.observe(id, start + GRACE_PERIOD).recovered)
+            .sum();
+        assert_eq!(recovered, 2);
+        assert_eq!(detector.pending_refetch(), None);
+        assert_eq!(
+            detector.check(start + GRACE_PERIOD * 3),
+            GapOutcome::default()
+        );
+    }
+
+    #[test]
+    fn should_count_ids_still_missing_after_refetch_as_lost() {
+        let start = Instant::now();
+        let mut detector = GapDetector::new(GRACE_PERIOD);
+        for id in [1, 4] {
+            detector.observe(id, start);
+        }
+        assert_eq!(detector.check(start + GRACE_PERIOD).refetch_from, Some(2));
+
+        assert_eq!(detector.observe(3, start + GRACE_PERIOD).recovered, 1);
+        let outcome = detector.check(start + GRACE_PERIOD * 2);
+
+        assert_eq!(outcome.lost, 1);
+        assert_eq!(outcome.refetch_from, None);
+        assert_eq!(detector.pending_refetch(), None);
+        assert_eq!(
+            detector.observe(5, start + GRACE_PERIOD * 3),
+            GapOutcome::default()
+        );
+    }
+}
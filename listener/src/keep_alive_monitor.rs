@@ -1,10 +1,14 @@
+use casper_event_types::metrics;
 use std::{
     sync::Arc,
     time::{Duration, Instant},
 };
-use tokio::sync::{
-    mpsc::{channel, Receiver, Sender},
-    Mutex,
+use tokio::{
+    select,
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Mutex,
+    },
 };
 use tokio_util::sync::CancellationToken;
 
@@ -14,7 +18,11 @@ use tokio_util::sync::CancellationToken;
 /// But for some reason the eventsource implementation doesn't support timing out when a period of inactivity happens.
 /// Reqwest theoretically has connection properties that should allow to timeout after a period of receiving no data, but they don't seem to actually timeout if there are no keepalive messages.
 /// The KeepAliveMonitor has the responsibility to count ticks. If more time than configured happened since last "tick", we send a posion pill via cancelling a CancellationToken.
+/// Half-open TCP connections are detected this way as well, since they deliver no bytes at all.
+/// The time since the last tick and the timeouts are published as metrics labelled with the monitored node.
 pub struct KeepAliveMonitor {
+    /// Label of the monitored node in the metrics
+    node_label: String,
     /// Address of the endpoint which the KeepAliveMonitor needs to observe
     cancellation_token: CancellationToken,
     /// Time the check job sleeps between checks
@@ -27,6 +35,8 @@ pub struct KeepAliveMonitor {
     receiver: Arc<Mutex<Receiver<()>>>,
     /// Internal queue which collects "ticks". A "tick" happens every time we observe any message from `receiver` field.
     sender: Sender<()>,
+    /// Cancelled when the KeepAliveMonitor is dropped, so that the check job stops once the monitored datasource is gone
+    stop_token: CancellationToken,
 }
 
 impl KeepAliveMonitor {
@@ -42,17 +52,23 @@ impl KeepAliveMonitor {
     }
 
     /// Assembles a new `KeepAliveMonitor`. It still doesn't collect data, you need to call the `start` function for that.
-    pub fn new(sleep_between_checks: Duration, no_message_timeout: Duration) -> Self {
+    pub fn new(
+        node_label: String,
+        sleep_between_checks: Duration,
+        no_message_timeout: Duration,
+    ) -> Self {
         let cancellation_token = CancellationToken::new();
         let last_message_seen_at = Arc::new(Mutex::new(None));
         let (tx, rx) = channel(10);
         KeepAliveMonitor {
+            node_label,
             cancellation_token,
             sleep_between_checks,
             no_message_timeout,
             last_message_seen_at,
             receiver: Arc::new(Mutex::new(rx)),
             sender: tx,
+            stop_token: CancellationToken::new(),
         }
     }
 
@@ -93,13 +109,25 @@ impl KeepAliveMonitor {
         no_message_timeout: Duration,
         cancellation_token: CancellationToken,
     ) {
+        let node_label = self.node_label.clone();
+        let stop_token = self.stop_token.clone();
         tokio::spawn(async move {
             loop {
-                tokio::time::sleep(sleep_between_checks).await;
+                select! {
+                    _ = tokio::time::sleep(sleep_between_checks) => {},
+                    _ = stop_token.cancelled() => break,
+                }
                 let mut guard = last_activity_holder.lock().await;
                 match &mut *guard {
                     Some(last_seen_at) => {
-                        if last_seen_at.elapsed() > no_message_timeout {
+                        let elapsed = last_seen_at.elapsed();
+                        metrics::UPSTREAM_SECONDS_SINCE_LAST_BYTES
+                            .with_label_values(&[node_label.as_str()])
+                            .set(elapsed.as_secs_f64());
+                        if elapsed > no_message_timeout {
+                            metrics::UPSTREAM_INACTIVITY_TIMEOUTS
+                                .with_label_values(&[node_label.as_str()])
+                                .inc();
                             cancellation_token.cancel();
                             break;
                         }
@@ -117,15 +145,26 @@ impl KeepAliveMonitor {
     }
 }
 
+impl Drop for KeepAliveMonitor {
+    fn drop(&mut self) {
+        self.stop_token.cancel();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::keep_alive_monitor::KeepAliveMonitor;
+    use casper_event_types::metrics;
     use std::{sync::Arc, time::Duration};
     use tokio::{select, time::sleep};
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
     async fn should_cancel_when_first_check_finds_no_activity() {
-        let monitor = KeepAliveMonitor::new(Duration::from_secs(1), Duration::from_secs(2));
+        let monitor = KeepAliveMonitor::new(
+            "127.0.0.1:9999".to_string(),
+            Duration::from_secs(1),
+            Duration::from_secs(2),
+        );
         monitor.start().await;
         let cancellation_token = monitor.get_cancellation_token();
         select! {
@@ -139,6 +178,7 @@ mod tests {
     #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
     async fn should_not_cancel_if_endpoint_produces_data() {
         let monitor = Arc::new(KeepAliveMonitor::new(
+            "127.0.0.1:9999".to_string(),
             Duration::from_secs(10),
             Duration::from_secs(30),
         ));
@@ -157,6 +197,7 @@ mod tests {
     #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
     async fn should_cancel_if_no_activity_for_prolonged_period_of_time() {
         let monitor = Arc::new(KeepAliveMonitor::new(
+            "127.0.0.1:9999".to_string(),
             Duration::from_secs(1),
             Duration::from_secs(3),
         ));
@@ -172,6 +213,33 @@ mod tests {
         }
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn should_count_inactivity_timeouts_of_the_node() {
+        let node_label = "127.0.0.1:9998";
+        let monitor = KeepAliveMonitor::new(
+            node_label.to_string(),
+            Duration::from_secs(1),
+            Duration::from_secs(2),
+        );
+        monitor.start().await;
+        let cancellation_token = monitor.get_cancellation_token();
+        select! {
+            _ = cancellation_token.cancelled() => {},
+            _ = sleep(Duration::from_secs(10)) => {
+                unreachable!()
+            },
+        }
+
+        let timeouts = metrics::UPSTREAM_INACTIVITY_TIMEOUTS
+            .with_label_values(&[node_label])
+            .get();
+        assert_eq!(timeouts, 1);
+        let seconds_since_last_bytes = metrics::UPSTREAM_SECONDS_SINCE_LAST_BYTES
+            .with_label_values(&[node_label])
+            .get();
+        assert!(seconds_since_last_bytes > 2.0);
+    }
+
     fn sse_server(monitor: Arc<KeepAliveMonitor>, interval_in_seconds: u64) {
         tokio::spawn(async move {
             monitor.tick().await;
@@ -6,27 +6,35 @@ mod connection_manager;
 mod connection_tasks;
 pub mod connections_builder;
 mod event_listener_status;
+mod gap_detector;
 mod keep_alive_monitor;
 mod sse_connector;
 mod types;
 mod version_fetcher;
 use crate::event_listener_status::*;
 use anyhow::Error;
-use casper_event_types::Filter;
+use casper_event_types::{capture::CapturedFrame, metrics, Filter};
 use casper_types::ProtocolVersion;
 use connection_manager::{ConnectionManager, ConnectionManagerError};
 use connection_tasks::ConnectionTasks;
 use connections_builder::{ConnectionsBuilder, DefaultConnectionsBuilder};
-use std::{collections::HashMap, net::IpAddr, str::FromStr, sync::Arc, time::Duration};
+use gap_detector::{GapDetector, GapOutcome};
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tokio::{
     sync::{
-        mpsc::{self, Sender},
+        mpsc::{self, Receiver, Sender},
         Mutex,
     },
-    time::sleep,
+    time::{sleep, timeout},
 };
 use tracing::{debug, error, info, warn};
-pub use types::{NodeConnectionInterface, SseEvent};
+pub use types::{EventIdGap, NodeConnectionInterface, SseEvent};
 use url::Url;
 use version_fetcher::{for_status_endpoint, BuildVersionFetchError, VersionFetcher};
 
@@ -41,10 +49,25 @@ pub struct EventListenerBuilder {
     pub connection_timeout: Duration,
     pub sleep_between_keep_alive_checks: Duration,
     pub no_message_timeout: Duration,
+    /// If set, every raw frame received from the node is sent to this channel to be recorded.
+    pub capture_sender: Option<Sender<CapturedFrame>>,
+    /// If set, every keepalive comment received from the node is reported to this channel.
+    pub keep_alive_sender: Option<Sender<()>>,
+    /// Time the event IDs skipped by the node may be missing before they're fetched again.
+    pub gap_grace_period: Duration,
+    /// If set, the event IDs skipped by the node are reported to this channel.
+    pub gap_sender: Option<Sender<EventIdGap>>,
 }
 
 type FilterWithEventId = Sender<(Filter, u32)>;
 type CurrentFilterToIdHolder = Arc<Mutex<HashMap<Filter, u32>>>;
+
+/// Handles of the task keeping track of the last event ID seen on each filter.
+struct LastEventIdRegistry {
+    last_event_id_for_filter: CurrentFilterToIdHolder,
+    last_seen_event_id_sender: FilterWithEventId,
+    refetch_receiver: Receiver<u32>,
+}
 impl EventListenerBuilder {
     pub fn build(&self) -> Result<EventListener, Error> {
         let status_endpoint = status_endpoint(self.node.ip_address, self.node.rest_port)?;
@@ -58,6 +81,8 @@ impl EventListenerBuilder {
             ip_address: self.node.ip_address,
             sse_port: self.node.sse_port,
             allow_partial_connection: self.allow_partial_connection,
+            capture_sender: self.capture_sender.clone(),
+            keep_alive_sender: self.keep_alive_sender.clone(),
         });
         Ok(EventListener {
             node_build_version: ProtocolVersion::from_parts(1, 0, 0),
@@ -67,6 +92,8 @@ impl EventListenerBuilder {
             allow_partial_connection: self.allow_partial_connection,
             version_fetcher,
             connections_builder,
+            gap_grace_period: self.gap_grace_period,
+            gap_sender: self.gap_sender.clone(),
         })
     }
 }
@@ -88,11 +115,17 @@ pub struct EventListener {
     version_fetcher: Arc<dyn VersionFetcher>,
     /// Builder of the connections to the node
     connections_builder: Arc<dyn ConnectionsBuilder>,
+    /// Time the event IDs skipped by the node may be missing before they're fetched again.
+    gap_grace_period: Duration,
+    /// If set, the event IDs skipped by the node are reported to this channel.
+    gap_sender: Option<Sender<EventIdGap>>,
 }
 
 enum ConnectOutcome {
     ConnectionLost,
     SystemReconnect, //In this case we don't increase the current_attempt counter
+    /// The node skipped event IDs, the connections are restarted to fetch them again.
+    Refetch,
 }
 
 enum GetVersionResult {
@@ -109,7 +142,7 @@ impl EventListener {
     /// Spins up the connections and starts pushing data from node
     pub async fn stream_aggregated_events(&mut self) -> Result<(), Error> {
         log_status_for_event_listener(EventListenerStatus::Preparing, self);
-        let (last_event_id_for_filter, last_seen_event_id_sender) =
+        let mut registry =
             self.start_last_event_id_registry(self.node.ip_address.to_string(), self.node.sse_port);
         log_status_for_event_listener(EventListenerStatus::Connecting, self);
         let mut current_attempt = 1;
@@ -132,14 +165,12 @@ impl EventListener {
                 GetVersionResult::Error(e) => return Err(e),
                 _ => {}
             }
-            if let Ok(ConnectOutcome::ConnectionLost) = self
-                .do_connect(
-                    last_event_id_for_filter.clone(),
-                    last_seen_event_id_sender.clone(),
-                )
-                .await
-            {
-                warn_connection_lost(self, current_attempt);
+            match self.do_connect(&mut registry).await {
+                Ok(ConnectOutcome::Refetch) => continue,
+                Ok(ConnectOutcome::ConnectionLost) => {
+                    warn_connection_lost(self, current_attempt);
+                }
+                _ => {}
             }
             current_attempt += 1;
         }
@@ -149,26 +180,48 @@ impl EventListener {
 
     async fn do_connect(
         &mut self,
-        last_event_id_for_filter: Arc<Mutex<HashMap<Filter, u32>>>,
-        last_seen_event_id_sender: FilterWithEventId,
+        registry: &mut LastEventIdRegistry,
     ) -> Result<ConnectOutcome, Error> {
         let connections = self
             .connections_builder
             .build_connections(
-                last_event_id_for_filter.clone(),
-                last_seen_event_id_sender.clone(),
+                registry.last_event_id_for_filter.clone(),
+                registry.last_seen_event_id_sender.clone(),
                 self.node_build_version,
             )
             .await?;
         let connection_join_handles = start_connections(connections);
+        let node_address = self.node.ip_address;
+        let abort_handles: Vec<_> = connection_join_handles
+            .iter()
+            .map(|join_handle| join_handle.abort_handle())
+            .collect();
+        tokio::select! {
+            outcome = self.wait_for_connections(connection_join_handles) => Ok(outcome),
+            Some(start_from) = registry.refetch_receiver.recv() => {
+                info!(
+                    "Reconnecting to node {} to fetch the events from {} again",
+                    node_address, start_from
+                );
+                for abort_handle in abort_handles {
+                    abort_handle.abort();
+                }
+                Ok(ConnectOutcome::Refetch)
+            }
+        }
+    }
+
+    async fn wait_for_connections(
+        &mut self,
+        connection_join_handles: Vec<tokio::task::JoinHandle<Result<(), ConnectionManagerError>>>,
+    ) -> ConnectOutcome {
         if self.allow_partial_connection {
             // We wait until either
             //  * all of the connections return error OR
             //  * one of the connection returns Err(NonRecoverableError) OR
             //  * one of the connection returns Ok(()) -> this means that we need to do a force reconnect to the node
-            Ok(self
-                .allow_partial_connection_wait(connection_join_handles)
-                .await)
+            self.allow_partial_connection_wait(connection_join_handles)
+                .await
         } else {
             // Return on the first completed connection
             let select_result = futures::future::select_all(connection_join_handles).await;
@@ -176,12 +229,12 @@ impl EventListener {
             if let Ok(res) = task_result {
                 if res.is_err() {
                     log_status_for_event_listener(EventListenerStatus::Reconnecting, self);
-                    return Ok(ConnectOutcome::ConnectionLost);
+                    return ConnectOutcome::ConnectionLost;
                 }
-                Ok(ConnectOutcome::SystemReconnect)
+                ConnectOutcome::SystemReconnect
             } else {
                 log_status_for_event_listener(EventListenerStatus::Reconnecting, self);
-                Ok(ConnectOutcome::ConnectionLost)
+                ConnectOutcome::ConnectionLost
             }
         }
     }
@@ -260,25 +313,104 @@ impl EventListener {
         }
     }
 
+    /// Starts the task keeping track of the last event ID seen on each filter, so that the
+    /// connections are restarted from it. It also detects the event IDs the node skipped: once
+    /// they're found missing, the IDs of the filters are lowered to the first missing one and a
+    /// reconnection is requested through the returned receiver.
     fn start_last_event_id_registry(
         &self,
         node_address: String,
         sse_port: u16,
-    ) -> (CurrentFilterToIdHolder, FilterWithEventId) {
+    ) -> LastEventIdRegistry {
         let (last_seen_event_id_sender, mut last_seen_event_id_receiver) = mpsc::channel(10);
+        let (refetch_sender, refetch_receiver) = mpsc::channel(1);
         let last_event_id_for_filter: CurrentFilterToIdHolder =
             Arc::new(Mutex::new(HashMap::<Filter, u32>::new()));
         let last_event_id_for_filter_for_thread = last_event_id_for_filter.clone();
+        let mut gap_detector = GapDetector::new(self.gap_grace_period);
+        let gap_grace_period = self.gap_grace_period;
+        let maybe_gap_sender = self.gap_sender.clone();
         tokio::spawn(async move {
-            while let Some((filter, id)) = last_seen_event_id_receiver.recv().await {
-                EventListenerStatus::Connected.log_status(node_address.as_str(), sse_port);
-                let last_event_id_for_filter_clone = last_event_id_for_filter_for_thread.clone();
-                let mut guard = last_event_id_for_filter_clone.lock().await;
-                guard.insert(filter, id);
+            let node_label = format!("{}:{}", node_address, sse_port);
+            loop {
+                let maybe_observed_id =
+                    match timeout(gap_grace_period, last_seen_event_id_receiver.recv()).await {
+                        Ok(None) => break,
+                        Ok(Some(filter_with_id)) => Some(filter_with_id),
+                        Err(_elapsed) => None,
+                    };
+                let mut guard = last_event_id_for_filter_for_thread.lock().await;
+                let outcome = match maybe_observed_id {
+                    Some((filter, id)) => {
+                        EventListenerStatus::Connected.log_status(node_address.as_str(), sse_port);
+                        observe_event_id(&mut gap_detector, &mut guard, filter, id)
+                    }
+                    None => gap_detector.check(Instant::now()),
+                };
+                record_skipped_events(&node_label, &outcome);
+                if let Some(start_from) = outcome.refetch_from {
+                    for id in guard.values_mut() {
+                        *id = (*id).min(start_from);
+                    }
+                    // A reconnection already requested will restart from the lowered IDs too.
+                    let _ = refetch_sender.try_send(start_from);
+                    report_gap(&node_label, start_from, maybe_gap_sender.as_ref());
+                }
                 drop(guard);
             }
         });
-        (last_event_id_for_filter, last_seen_event_id_sender)
+        LastEventIdRegistry {
+            last_event_id_for_filter,
+            last_seen_event_id_sender,
+            refetch_receiver,
+        }
+    }
+}
+
+/// Records the ID of the event seen on the filter, returning the gaps it reveals.
+fn observe_event_id(
+    gap_detector: &mut GapDetector,
+    last_event_id_for_filter: &mut HashMap<Filter, u32>,
+    filter: Filter,
+    id: u32,
+) -> GapOutcome {
+    let outcome = gap_detector.observe(id, Instant::now());
+    // While missing events are fetched again, the connections have to restart from the first of
+    // them rather than from the latest ID seen.
+    let restart_from = gap_detector
+        .pending_refetch()
+        .map_or(id, |start_from| start_from.min(id));
+    last_event_id_for_filter.insert(filter, restart_from);
+    outcome
+}
+
+fn report_gap(node_label: &str, start_from: u32, maybe_gap_sender: Option<&Sender<EventIdGap>>) {
+    warn!(
+        "Node {} skipped the events from ID {}, fetching them again",
+        node_label, start_from
+    );
+    if let Some(gap_sender) = maybe_gap_sender {
+        let _ = gap_sender.try_send(EventIdGap {
+            node: node_label.to_string(),
+            start_from,
+        });
+    }
+}
+
+fn record_skipped_events(node_label: &str, outcome: &GapOutcome) {
+    if outcome.recovered > 0 {
+        metrics::SKIPPED_EVENTS
+            .with_label_values(&[node_label, "recovered"])
+            .inc_by(outcome.recovered);
+    }
+    if outcome.lost > 0 {
+        warn!(
+            "{} events skipped by node {} could not be fetched again",
+            outcome.lost, node_label
+        );
+        metrics::SKIPPED_EVENTS
+            .with_label_values(&[node_label, "lost"])
+            .inc_by(outcome.lost);
     }
 }
 
@@ -424,6 +556,8 @@ mod tests {
             allow_partial_connection,
             version_fetcher: Arc::new(version_fetcher),
             connections_builder,
+            gap_grace_period: Duration::from_secs(5),
+            gap_sender: None,
         };
         listener.stream_aggregated_events().await.unwrap_err()
     }
@@ -4,12 +4,13 @@ use anyhow::Error;
 use async_stream::stream;
 use async_trait::async_trait;
 use bytes::Bytes;
+use casper_event_types::metrics;
 use eventsource_stream::{Event, EventStream, EventStreamError, Eventsource};
 use futures::StreamExt;
 use reqwest::Client;
 use std::pin::Pin;
 use std::{fmt::Debug, sync::Arc, time::Duration};
-use tokio::select;
+use tokio::{select, sync::mpsc::Sender};
 use tokio_stream::Stream;
 use tracing::debug;
 use url::Url;
@@ -41,6 +42,8 @@ pub struct SseConnection {
     pub bind_address: Url,
     pub sleep_between_keepalive_checks: Duration,
     pub no_message_timeout: Duration,
+    /// If set, every keepalive comment received from the node is reported to this channel.
+    pub keep_alive_sender: Option<Sender<()>>,
 }
 
 impl SseConnection {
@@ -65,15 +68,32 @@ impl SseConnection {
         Ok(Box::pin(stream.eventsource()))
     }
 
+    /// Label of the node in the metrics, `<ip>:<sse port>`.
+    fn node_label(&self) -> String {
+        format!(
+            "{}:{}",
+            self.bind_address.host_str().unwrap_or_default(),
+            self.bind_address
+                .port_or_known_default()
+                .unwrap_or_default()
+        )
+    }
+
     async fn build_byte_stream(
         &mut self,
         sse_response: reqwest::Response,
     ) -> impl Stream<Item = Result<Bytes, SseDataStreamingError>> {
-        let monitor =
-            KeepAliveMonitor::new(self.sleep_between_keepalive_checks, self.no_message_timeout);
+        let monitor = KeepAliveMonitor::new(
+            self.node_label(),
+            self.sleep_between_keepalive_checks,
+            self.no_message_timeout,
+        );
         monitor.start().await;
         let cancellation_token = monitor.get_cancellation_token();
         let mut stream = sse_response.bytes_stream();
+        let node_label = self.node_label();
+        let maybe_keep_alive_sender = self.keep_alive_sender.clone();
+        let mut comments = CommentCounter::default();
         stream! {
             loop {
                 select! {
@@ -82,6 +102,10 @@ impl SseConnection {
                             match res_bytes {
                                 Ok(bytes) => {
                                     monitor.tick().await;
+                                    let keep_alives = comments.count(&bytes);
+                                    if keep_alives > 0 {
+                                        record_keep_alives(&node_label, keep_alives, maybe_keep_alive_sender.as_ref());
+                                    }
                                     yield Ok(bytes);
                                 },
                                 Err(err) => {
@@ -138,6 +162,48 @@ impl StreamConnector for SseConnection {
     }
 }
 
+/// Counts the comment lines, i.e. those starting with `:`, of an event stream read in chunks of
+/// arbitrary boundaries. The node sends a comment as keepalive when it has no event to send.
+/// Comments are skipped by the event stream parser, so they are never mistaken for malformed
+/// events, and are only counted here to tell a quiet node from a silent connection.
+#[derive(Debug)]
+struct CommentCounter {
+    at_line_start: bool,
+}
+
+impl Default for CommentCounter {
+    fn default() -> Self {
+        CommentCounter {
+            at_line_start: true,
+        }
+    }
+}
+
+impl CommentCounter {
+    /// Returns the number of comment lines starting in `bytes`, the next chunk of the stream.
+    fn count(&mut self, bytes: &[u8]) -> usize {
+        let mut comments = 0;
+        for byte in bytes {
+            if self.at_line_start && *byte == b':' {
+                comments += 1;
+            }
+            self.at_line_start = *byte == b'\n' || *byte == b'\r';
+        }
+        comments
+    }
+}
+
+/// Counts the keepalives received from the node and reports them, without waiting for the receiver
+/// of the reports, which only cares about the latest ones.
+fn record_keep_alives(node_label: &str, keep_alives: usize, maybe_sender: Option<&Sender<()>>) {
+    metrics::UPSTREAM_KEEP_ALIVES
+        .with_label_values(&[node_label])
+        .inc_by(keep_alives as u64);
+    if let Some(sender) = maybe_sender {
+        let _ = sender.try_send(());
+    }
+}
+
 fn couldnt_connect(
     last_error: Option<ConnectionManagerError>,
     url: Url,
@@ -167,11 +233,12 @@ fn couldnt_connect(
 pub mod tests {
     use crate::{
         connection_manager::ConnectionManagerError,
-        sse_connector::{SseConnection, StreamConnector},
+        sse_connector::{CommentCounter, SseConnection, StreamConnector},
     };
     use anyhow::Error;
     use async_stream::stream;
     use async_trait::async_trait;
+    use casper_event_types::metrics;
     use eventsource_stream::{Event, Eventsource};
     use futures_util::{stream::iter, Stream};
     use std::{
@@ -323,6 +390,7 @@ pub mod tests {
             .unwrap(),
             sleep_between_keepalive_checks: Duration::from_secs(20),
             no_message_timeout: Duration::from_secs(20),
+            keep_alive_sender: None,
         };
 
         let data = fetch_data(&mut connection).await;
@@ -342,6 +410,7 @@ pub mod tests {
             .unwrap(),
             sleep_between_keepalive_checks: Duration::from_secs(20),
             no_message_timeout: Duration::from_secs(20),
+            keep_alive_sender: None,
         };
         let res = connection.connect(None).await;
         assert!(res.is_err());
@@ -361,6 +430,7 @@ pub mod tests {
             .unwrap(),
             sleep_between_keepalive_checks: Duration::from_secs(1),
             no_message_timeout: Duration::from_secs(5),
+            keep_alive_sender: None,
         };
         let start = Instant::now();
         let data = fetch_data_with_timeout(&mut connection, Duration::from_secs(20)).await;
@@ -369,6 +439,43 @@ pub mod tests {
         assert!(elapsed.as_secs() >= 5); // It should take more then 5 seconds before the inactivity check kicks in
     }
 
+    #[tokio::test]
+    async fn given_keep_alives_should_report_them_without_reading_them_as_events() {
+        let sse_port = portpicker::pick_unused_port().unwrap();
+        sse_server_keep_alives(sse_port);
+        let (keep_alive_sender, mut keep_alive_receiver) = channel(10);
+        let mut connection = SseConnection {
+            max_attempts: 5,
+            delay_between_attempts: Duration::from_secs(2),
+            connection_timeout: Duration::from_secs(10),
+            bind_address: Url::parse(
+                format!("http://localhost:{}/notifications", sse_port).as_str(),
+            )
+            .unwrap(),
+            sleep_between_keepalive_checks: Duration::from_secs(20),
+            no_message_timeout: Duration::from_secs(20),
+            keep_alive_sender: Some(keep_alive_sender),
+        };
+
+        let data = fetch_data_with_timeout(&mut connection, Duration::from_secs(3)).await;
+
+        assert_eq!(data, vec!["msg 1"]);
+        assert!(keep_alive_receiver.try_recv().is_ok());
+        let keep_alives = metrics::UPSTREAM_KEEP_ALIVES
+            .with_label_values(&[format!("localhost:{}", sse_port).as_str()])
+            .get();
+        assert!(keep_alives >= 1);
+    }
+
+    #[test]
+    fn should_count_comments_split_across_chunks() {
+        let mut comments = CommentCounter::default();
+        assert_eq!(comments.count(b":\n\ndata:{\"a\":1}\nid:1"), 1);
+        assert_eq!(comments.count(b"\n\n"), 0);
+        assert_eq!(comments.count(b":"), 1);
+        assert_eq!(comments.count(b"\r\n\r\n:\n\ndata:x:y\n\n"), 1);
+    }
+
     #[tokio::test]
     async fn given_mock_sse_connection_should_read_data() {
         let data1 = Event {
@@ -455,6 +562,19 @@ pub mod tests {
         });
     }
 
+    fn sse_server_keep_alives(sse_port: u16) {
+        let routes = warp::path("notifications").and(warp::get()).map(|| {
+            let events = iter(vec![Ok::<_, Infallible>(SseEvent::default().data("msg 1"))])
+                .chain(futures_util::stream::pending());
+            warp::sse::reply(
+                warp::sse::keep_alive()
+                    .interval(Duration::from_millis(500))
+                    .stream(events),
+            )
+        });
+        tokio::spawn(async move { warp::serve(routes).run(([127, 0, 0, 1], sse_port)).await });
+    }
+
     async fn sse_server_finite_messages(sse_port: u16) {
         fn sse_events() -> impl futures_util::Stream<Item = Result<SseEvent, Infallible>> {
             iter(vec![
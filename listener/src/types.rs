@@ -3,7 +3,9 @@ use reqwest::Url;
 use std::{
     fmt::{Display, Formatter},
     net::IpAddr,
+    time::SystemTime,
 };
+use tracing::Span;
 
 /// Data on how to connect to a node
 #[derive(Clone)]
@@ -37,6 +39,15 @@ pub struct SseEvent {
     pub json_data: Option<String>,
     /// Info from which filter we received the message. For some events (Shutdown in particularly) we want to push only to the same outbound as we received them from so we don't duplicate.
     pub inbound_filter: Filter,
+    /// Time at which the message was received from the node, by the clock of the sidecar.
+    pub received_at: SystemTime,
+    /// Position of the message in the ingestion journal of the sidecar, if it was journaled.
+    pub journal_sequence: Option<u64>,
+    /// Hash of the content of the message, if the messages of several nodes are deduplicated.
+    pub content_hash: Option<[u8; 32]>,
+    /// Span of the ingestion of the message, from its receipt on. The processing of the message
+    /// is traced in spans within it.
+    pub span: Span,
 }
 
 impl SseEvent {
@@ -56,8 +67,18 @@ impl SseEvent {
             source,
             json_data,
             inbound_filter,
+            received_at: SystemTime::now(),
+            journal_sequence: None,
+            content_hash: None,
+            span: Span::none(),
         }
     }
+
+    /// The message with the span of its ingestion.
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = span;
+        self
+    }
 }
 
 impl Display for SseEvent {
@@ -69,3 +90,12 @@ impl Display for SseEvent {
         )
     }
 }
+
+/// Event IDs a node skipped on its event streams, reported when they're fetched again.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EventIdGap {
+    /// Address of the node, as `<ip_address>:<sse_port>`.
+    pub node: String,
+    /// The first skipped ID, from which the events are fetched again.
+    pub start_from: u32,
+}
@@ -0,0 +1,248 @@
+//! Names operators give to the accounts they deal with, e.g. `treasury`.
+//!
+//! The REST API accepts `@<alias>` wherever it takes an account, and annotates the accounts of its
+//! responses with their alias. The aliases are read from the config, optionally merged with the
+//! ones served by an endpoint at startup, and can be changed at runtime through the admin server;
+//! such changes are not persisted.
+
+use crate::{types::config::AccountAliasesConfig, validation};
+use anyhow::{Context, Error};
+use casper_types::{account::AccountHash, AsymmetricType, PublicKey};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
+    time::Duration,
+};
+
+/// The prefix of the identifiers referring to an account by alias.
+pub(crate) const ALIAS_PREFIX: char = '@';
+
+/// Time allowed to fetch the aliases served by the configured endpoint.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The account an alias stands for.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct AliasedAccount {
+    /// Hex-encoded hash of the account.
+    pub(crate) account_hash: String,
+    /// Hex-encoded public key of the account, if the alias was given one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) public_key: Option<String>,
+}
+
+impl AliasedAccount {
+    /// Parses an account given as a hex-encoded public key, as a formatted `account-hash-…` or as
+    /// a hex-encoded account hash.
+    pub(crate) fn parse(account: &str) -> Result<Self, String> {
+        let account = account.trim().to_lowercase();
+        if let Ok(account_hash) = AccountHash::from_formatted_str(&account) {
+            return Ok(AliasedAccount {
+                account_hash: hex::encode(account_hash.value()),
+                public_key: None,
+            });
+        }
+        // Public keys are prefixed by their algorithm tag, so they are longer than account hashes.
+        if let Ok(account_hash) = validation::parse_hash(&account) {
+            return Ok(AliasedAccount {
+                account_hash,
+                public_key: None,
+            });
+        }
+        match PublicKey::from_hex(&account) {
+            Ok(public_key) => Ok(AliasedAccount {
+                account_hash: hex::encode(public_key.to_account_hash().value()),
+                public_key: Some(public_key.to_hex()),
+            }),
+            Err(_) => Err(format!(
+                "expected a public key, an account hash or an account-hash- formatted string, \
+                 received: {}",
+                account
+            )),
+        }
+    }
+}
+
+/// Checks that `name` can be used in the paths of the REST API, after the `@` prefix.
+fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("expected a non-empty alias".to_string());
+    }
+    if !name
+        .chars()
+        .all(|character| character.is_ascii_alphanumeric() || "-_.".contains(character))
+    {
+        return Err(format!(
+            "expected an alias made of letters, digits, '-', '_' and '.', received: {}",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// Handle to the account aliases. Cloned handles share the same aliases.
+#[derive(Clone, Debug, Default)]
+pub struct AccountAliases {
+    aliases: Arc<RwLock<BTreeMap<String, AliasedAccount>>>,
+}
+
+impl AccountAliases {
+    /// Builds the aliases of the config and merges in the ones served by the configured endpoint,
+    /// as a JSON object of aliases to accounts. The aliases of the config take precedence. Fails on
+    /// the first invalid alias.
+    pub(crate) async fn load(config: &AccountAliasesConfig) -> Result<Self, Error> {
+        let account_aliases = AccountAliases::default();
+        if let Some(url) = &config.url {
+            let served = fetch_aliases(url)
+                .await
+                .with_context(|| format!("Error fetching the account aliases from {}", url))?;
+            account_aliases
+                .add_all(&served)
+                .with_context(|| format!("Invalid account alias served by {}", url))?;
+        }
+        account_aliases.add_all(&config.aliases)?;
+        Ok(account_aliases)
+    }
+
+    fn add_all(&self, aliases: &BTreeMap<String, String>) -> Result<(), Error> {
+        for (name, account) in aliases {
+            self.add(name, account)
+                .map_err(|error| Error::msg(format!("account alias '{}': {}", name, error)))?;
+        }
+        Ok(())
+    }
+
+    /// Makes `name` stand for `account`. Returns whether `name` is a new alias.
+    pub(crate) fn add(&self, name: &str, account: &str) -> Result<bool, String> {
+        validate_name(name)?;
+        let account = AliasedAccount::parse(account)?;
+        Ok(self.write().insert(name.to_string(), account).is_none())
+    }
+
+    /// Removes the alias `name`. Returns false if there was no such alias.
+    pub(crate) fn remove(&self, name: &str) -> bool {
+        self.write().remove(name).is_some()
+    }
+
+    pub(crate) fn entries(&self) -> BTreeMap<String, AliasedAccount> {
+        self.read().clone()
+    }
+
+    /// Resolves `identifier`, an `@`-prefixed alias or an account as accepted by
+    /// [AliasedAccount::parse], to the hex-encoded hash of the account.
+    pub(crate) fn resolve(&self, identifier: &str) -> Result<String, String> {
+        match identifier.strip_prefix(ALIAS_PREFIX) {
+            Some(name) => self
+                .read()
+                .get(name)
+                .map(|account| account.account_hash.clone())
+                .ok_or_else(|| format!("unknown account alias: {}", name)),
+            None => AliasedAccount::parse(identifier).map(|account| account.account_hash),
+        }
+    }
+
+    /// Returns the alias of the account with the hex-encoded `account_hash`, the first one in
+    /// alphabetical order if it has several.
+    pub(crate) fn alias_of(&self, account_hash: &str) -> Option<String> {
+        self.read()
+            .iter()
+            .find(|(_, account)| account.account_hash == account_hash)
+            .map(|(name, _)| name.clone())
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, BTreeMap<String, AliasedAccount>> {
+        self.aliases
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, BTreeMap<String, AliasedAccount>> {
+        self.aliases
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+async fn fetch_aliases(url: &str) -> Result<BTreeMap<String, String>, Error> {
+    let response = reqwest::Client::new()
+        .get(url)
+        .timeout(FETCH_TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?;
+    let body = response.bytes().await?;
+    Ok(serde_json::from_slice(&body)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use casper_types::{testing::TestRng, SecretKey};
+
+    #[tokio::test]
+    async fn should_resolve_aliases_and_accounts() {
+        let mut rng = TestRng::new();
+        let public_key = PublicKey::from(&SecretKey::random(&mut rng));
+        let account_hash = hex::encode(public_key.to_account_hash().value());
+        let mut aliases = BTreeMap::new();
+        aliases.insert("treasury".to_string(), public_key.to_hex());
+        let account_aliases = AccountAliases::load(&AccountAliasesConfig { aliases, url: None })
+            .await
+            .expect("Error loading account aliases");
+
+        assert_eq!(
+            account_aliases.resolve("@treasury"),
+            Ok(account_hash.clone())
+        );
+        assert_eq!(
+            account_aliases.resolve(&public_key.to_hex()),
+            Ok(account_hash.clone())
+        );
+        assert_eq!(
+            account_aliases.resolve(&format!("account-hash-{}", account_hash)),
+            Ok(account_hash.clone())
+        );
+        assert_eq!(
+            account_aliases.resolve(&account_hash.to_uppercase()),
+            Ok(account_hash.clone())
+        );
+        assert!(account_aliases.resolve("@unknown").is_err());
+        assert!(account_aliases.resolve("treasury").is_err());
+        assert_eq!(
+            account_aliases.alias_of(&account_hash),
+            Some("treasury".to_string())
+        );
+    }
+
+    #[test]
+    fn should_add_and_remove_aliases() {
+        let account_aliases = AccountAliases::default();
+        let account_hash = "ab".repeat(32);
+
+        assert_eq!(account_aliases.add("ops", &account_hash), Ok(true));
+        assert_eq!(account_aliases.add("ops", &account_hash), Ok(false));
+        assert!(account_aliases.add("not/valid", &account_hash).is_err());
+        assert!(account_aliases.add("ops", "not-an-account").is_err());
+        assert_eq!(
+            account_aliases.entries().get("ops"),
+            Some(&AliasedAccount {
+                account_hash: account_hash.clone(),
+                public_key: None,
+            })
+        );
+
+        assert!(account_aliases.remove("ops"));
+        assert!(!account_aliases.remove("ops"));
+        assert_eq!(account_aliases.alias_of(&account_hash), None);
+    }
+
+    #[tokio::test]
+    async fn should_reject_invalid_alias_of_config() {
+        let mut aliases = BTreeMap::new();
+        aliases.insert("treasury".to_string(), "01xyz".to_string());
+        let error = AccountAliases::load(&AccountAliasesConfig { aliases, url: None })
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("treasury"));
+    }
+}
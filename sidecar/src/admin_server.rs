@@ -1,50 +1,136 @@
-use crate::types::config::AdminServerConfig;
+use crate::account_aliases::AccountAliases;
+use crate::api_keys::{require_scope, ApiKeyManagementError, ApiKeys};
+use crate::audit::{audit_requests, AuditAction, AuditLog};
+use crate::health::{Health, HealthStatus};
+use crate::observability_pack::observability_pack;
+use crate::rebuild::{RebuildError, Rebuilds};
+use crate::source_scoring::SourceScores;
+use crate::types::config::{AdminServerConfig, ApiKeyScope, MetricsServerConfig};
+use crate::types::database::{DatabaseReadError, DatabaseReader, DatabaseWriter, DerivedTable};
 use crate::utils::{resolve_address, root_filter, Unexpected};
+use crate::watch_list::{WatchList, WatchedKind};
 use anyhow::Error;
 use casper_event_types::metrics::metrics_summary;
 use hyper::Server;
+use serde::Deserialize;
 use std::net::TcpListener;
 use std::time::Duration;
 use tower::{buffer::Buffer, make::Shared, ServiceBuilder};
+use warp::filters::BoxedFilter;
+use warp::http::StatusCode;
+use warp::reply::Response;
 use warp::Filter;
 use warp::{Rejection, Reply};
 
-const BIND_ALL_INTERFACES: &str = "0.0.0.0";
-struct AdminServer {
-    port: u16,
-    max_concurrent_requests: u32,
-    max_requests_per_second: u32,
+/// Maximum number of audit entries returned by a single request to the `audit` endpoint.
+const MAX_AUDIT_ENTRIES_PER_REQUEST: u32 = 1000;
+/// Maximum number of operational events returned by a single request to the `events` endpoint.
+const MAX_OPERATIONAL_EVENTS_PER_REQUEST: u32 = 1000;
+
+/// The admin server, reporting on and managing the Sidecar storing its events in `database`. With
+/// `maybe_api_keys`, it answers only the requests with a key of the `admin` scope.
+pub(crate) struct AdminServer<Db> {
+    pub(crate) database: Db,
+    pub(crate) maybe_audit_log: Option<AuditLog>,
+    pub(crate) health: Health,
+    pub(crate) watch_list: WatchList,
+    pub(crate) source_scores: SourceScores,
+    pub(crate) account_aliases: AccountAliases,
+    pub(crate) rebuilds: Rebuilds,
+    pub(crate) maybe_api_keys: Option<ApiKeys>,
 }
 
-impl AdminServer {
-    pub async fn start(&self) -> Result<(), Error> {
-        let api = root_filter().or(metrics_filter());
-        let address = format!("{}:{}", BIND_ALL_INTERFACES, self.port);
-        let socket_address = resolve_address(&address)?;
-        let listener = TcpListener::bind(socket_address)?;
+impl<Db: DatabaseReader + DatabaseWriter + Clone + Send + Sync + 'static> AdminServer<Db> {
+    /// The routes of the server, e.g. to serve them with those of other networks.
+    pub(crate) fn routes(&self) -> BoxedFilter<(Response,)> {
+        let routes = root_filter()
+            .or(metrics_filter())
+            .or(observability_pack_filter())
+            .or(health_filter(self.health.clone()))
+            .or(ready_filter(self.health.clone()))
+            .or(audit_filter(self.database.clone()))
+            .or(operational_events_filter(self.database.clone()))
+            .or(event_ids_filter(self.database.clone()))
+            .or(watch_list_filter(self.watch_list.clone()))
+            .or(watch_filter(self.watch_list.clone()))
+            .or(unwatch_filter(self.watch_list.clone()))
+            .or(sources_filter(self.source_scores.clone()))
+            .or(account_aliases_filter(self.account_aliases.clone()))
+            .or(add_account_alias_filter(self.account_aliases.clone()))
+            .or(remove_account_alias_filter(self.account_aliases.clone()))
+            .or(rebuilds_filter(self.rebuilds.clone()))
+            .or(start_rebuild_filter(
+                self.database.clone(),
+                self.rebuilds.clone(),
+            ))
+            .or(api_keys_filter(self.maybe_api_keys.clone()))
+            .or(create_api_key_filter(
+                self.database.clone(),
+                self.maybe_api_keys.clone(),
+            ))
+            .or(revoke_api_key_filter(
+                self.database.clone(),
+                self.maybe_api_keys.clone(),
+            ))
+            .map(Reply::into_response)
+            .boxed();
+        // Refused requests are audited too.
+        require_scope(self.maybe_api_keys.clone(), ApiKeyScope::Admin, routes)
+            .with(audit_requests(
+                self.maybe_audit_log.clone(),
+                AuditAction::AdminApiCall,
+                self.maybe_api_keys.clone(),
+            ))
+            .map(Reply::into_response)
+            .boxed()
+    }
+}
 
-        let warp_service = warp::service(api);
-        let tower_service = ServiceBuilder::new()
-            .concurrency_limit(self.max_concurrent_requests as usize)
-            .rate_limit(self.max_requests_per_second as u64, Duration::from_secs(1))
-            .service(warp_service);
+/// Serves `routes` on the configured address, e.g. the routes of several networks mounted under
+/// their names.
+pub(crate) async fn serve(
+    config: AdminServerConfig,
+    routes: BoxedFilter<(Response,)>,
+) -> Result<(), Error> {
+    let socket_address = resolve_address(&config.bind_address())?;
+    let listener = TcpListener::bind(socket_address)?;
 
-        Server::from_tcp(listener)?
-            .serve(Shared::new(Buffer::new(tower_service, 50)))
-            .await?;
+    let warp_service = warp::service(routes);
+    let tower_service = ServiceBuilder::new()
+        .concurrency_limit(config.max_concurrent_requests as usize)
+        .rate_limit(
+            config.max_requests_per_second as u64,
+            Duration::from_secs(1),
+        )
+        .service(warp_service);
 
-        Err(Error::msg("Admin server shutting down"))
-    }
+    Server::from_tcp(listener)?
+        .serve(Shared::new(Buffer::new(tower_service, 50)))
+        .await?;
+
+    Err(Error::msg("Admin server shutting down"))
 }
 
-pub async fn run_server(config: AdminServerConfig) -> Result<(), Error> {
-    AdminServer {
-        port: config.port,
-        max_concurrent_requests: config.max_concurrent_requests,
-        max_requests_per_second: config.max_requests_per_second,
-    }
-    .start()
-    .await
+/// Runs a server exposing only the `metrics` endpoint, so that metrics can be scraped on
+/// an interface the rest of the admin API isn't reachable on.
+pub async fn run_metrics_server(config: MetricsServerConfig) -> Result<(), Error> {
+    let socket_address = resolve_address(&config.bind_address())?;
+    let listener = TcpListener::bind(socket_address)?;
+
+    let warp_service = warp::service(metrics_filter());
+    let tower_service = ServiceBuilder::new()
+        .concurrency_limit(config.max_concurrent_requests as usize)
+        .rate_limit(
+            config.max_requests_per_second as u64,
+            Duration::from_secs(1),
+        )
+        .service(warp_service);
+
+    Server::from_tcp(listener)?
+        .serve(Shared::new(Buffer::new(tower_service, 50)))
+        .await?;
+
+    Err(Error::msg("Metrics server shutting down"))
 }
 
 /// Return metrics data at a given time.
@@ -63,28 +149,956 @@ async fn metrics_handler() -> Result<impl Reply, Rejection> {
     Ok(res_custom)
 }
 
+/// Return a Grafana dashboard plotting the metrics of the sidecar and Prometheus alert rules on
+/// them, both generated from the metrics the sidecar exposes.
+/// Return: JSON object with the `dashboard`, to be imported in Grafana, and the `alert_rules`, to
+/// be saved as a Prometheus rule file.
+/// Example: curl http://127.0.0.1:18887/observability-pack
+fn observability_pack_filter(
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("observability-pack")
+        .and(warp::get())
+        .map(|| warp::reply::json(&observability_pack()))
+}
+
+/// Return the health of the sidecar.
+/// Return: JSON object with the status, `healthy` or `degraded`, and the reasons of the
+/// degradation keyed by component. Responds with 503 while the sidecar is degraded.
+/// Example: curl http://127.0.0.1:18887/health
+fn health_filter(
+    health: Health,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("health").and(warp::get()).map(move || {
+        let report = health.report();
+        let status = match report.status {
+            HealthStatus::Healthy => StatusCode::OK,
+            HealthStatus::Degraded => StatusCode::SERVICE_UNAVAILABLE,
+        };
+        warp::reply::with_status(warp::reply::json(&report), status)
+    })
+}
+
+/// Return whether the sidecar is ready to serve traffic, i.e. whether it caught up with the chain.
+/// Return: JSON object with `ready` and the reasons the sidecar isn't ready keyed by component.
+/// Responds with 503 while the sidecar isn't ready.
+/// Example: curl http://127.0.0.1:18887/ready
+fn ready_filter(
+    health: Health,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("ready").and(warp::get()).map(move || {
+        let report = health.readiness();
+        let status = if report.ready {
+            StatusCode::OK
+        } else {
+            StatusCode::SERVICE_UNAVAILABLE
+        };
+        warp::reply::with_status(warp::reply::json(&report), status)
+    })
+}
+
+#[derive(Deserialize)]
+struct AuditQuery {
+    from: Option<u64>,
+    limit: Option<u32>,
+}
+
+/// Return entries of the audit log, oldest first.
+/// Input: `from` - id of the first entry to return (defaults to the oldest entry),
+/// `limit` - maximum number of entries to return (at most 1000).
+/// Return: JSON array of audit entries.
+/// Example: curl http://127.0.0.1:18887/audit?from=100&limit=10
+fn audit_filter<Db: DatabaseReader + Clone + Send + Sync>(
+    db: Db,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("audit")
+        .and(warp::get())
+        .and(warp::query::<AuditQuery>())
+        .and(warp::any().map(move || db.clone()))
+        .and_then(audit_handler)
+}
+
+async fn audit_handler<Db: DatabaseReader + Clone + Send + Sync>(
+    query: AuditQuery,
+    db: Db,
+) -> Result<impl Reply, Rejection> {
+    let limit = query
+        .limit
+        .unwrap_or(MAX_AUDIT_ENTRIES_PER_REQUEST)
+        .min(MAX_AUDIT_ENTRIES_PER_REQUEST);
+    let audit_entries = db
+        .get_audit_entries(query.from.unwrap_or(0), limit)
+        .await
+        .map_err(|err| warp::reject::custom(Unexpected(Error::msg(format!("{:?}", err)))))?;
+
+    Ok(warp::reply::json(&audit_entries))
+}
+
+#[derive(Deserialize)]
+struct OperationalEventsQuery {
+    from: Option<u64>,
+    limit: Option<u32>,
+}
+
+/// Return the operational history of the sidecar: its starts and stops, failovers, event id gaps
+/// and pruning runs, oldest first.
+/// Input: `from` - id of the first event to return (defaults to the oldest event),
+/// `limit` - maximum number of events to return (at most 1000).
+/// Return: JSON array of operational events.
+/// Example: curl http://127.0.0.1:18887/events?from=100&limit=10
+fn operational_events_filter<Db: DatabaseReader + Clone + Send + Sync>(
+    db: Db,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("events")
+        .and(warp::get())
+        .and(warp::query::<OperationalEventsQuery>())
+        .and(warp::any().map(move || db.clone()))
+        .and_then(operational_events_handler)
+}
+
+async fn operational_events_handler<Db: DatabaseReader + Clone + Send + Sync>(
+    query: OperationalEventsQuery,
+    db: Db,
+) -> Result<impl Reply, Rejection> {
+    let limit = query
+        .limit
+        .unwrap_or(MAX_OPERATIONAL_EVENTS_PER_REQUEST)
+        .min(MAX_OPERATIONAL_EVENTS_PER_REQUEST);
+    let operational_events = db
+        .get_operational_events(query.from.unwrap_or(0), limit)
+        .await
+        .map_err(|err| warp::reject::custom(Unexpected(Error::msg(format!("{:?}", err)))))?;
+
+    Ok(warp::reply::json(&operational_events))
+}
+
+/// Return the state of the allocator of outbound event ids.
+/// Return: JSON object with the id which will be given to the next outbound event and the time
+/// of the last allocation, or 404 if no event id has been allocated yet.
+/// Example: curl http://127.0.0.1:18887/event_ids
+fn event_ids_filter<Db: DatabaseReader + Clone + Send + Sync>(
+    db: Db,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("event_ids")
+        .and(warp::get())
+        .and(warp::any().map(move || db.clone()))
+        .and_then(event_ids_handler)
+}
+
+async fn event_ids_handler<Db: DatabaseReader + Clone + Send + Sync>(
+    db: Db,
+) -> Result<impl Reply, Rejection> {
+    match db.get_event_id_allocator_state().await {
+        Ok(state) => Ok(warp::reply::json(&state)),
+        Err(DatabaseReadError::NotFound) => Err(warp::reject::not_found()),
+        Err(err) => Err(warp::reject::custom(Unexpected(Error::msg(format!(
+            "{:?}",
+            err
+        ))))),
+    }
+}
+
+/// Return the entities of the watch list.
+/// Return: JSON object with the `accounts`, `contracts` and `validators` being watched.
+/// Example: curl http://127.0.0.1:18887/watch_list
+fn watch_list_filter(
+    watch_list: WatchList,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("watch_list")
+        .and(warp::get())
+        .map(move || warp::reply::json(&watch_list.entities()))
+}
+
+/// Start watching an entity until the sidecar restarts.
+/// Input: the kind of entity, either `accounts`, `contracts` or `validators`, and its hex-encoded
+/// public key or hash.
+/// Return: 201 if the entity wasn't watched yet, 200 otherwise, or 400 with the reason if the entity
+/// is malformed.
+/// Example: curl -X PUT http://127.0.0.1:18887/watch_list/accounts/01aa...
+fn watch_filter(
+    watch_list: WatchList,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("watch_list" / WatchedKind / String)
+        .and(warp::put())
+        .map(
+            move |kind, entity: String| match watch_list.add(kind, &entity) {
+                Ok(true) => warp::reply::with_status(String::new(), StatusCode::CREATED),
+                Ok(false) => warp::reply::with_status(String::new(), StatusCode::OK),
+                Err(error) => warp::reply::with_status(error, StatusCode::BAD_REQUEST),
+            },
+        )
+}
+
+/// Stop watching an entity until the sidecar restarts.
+/// Input: the kind of entity, either `accounts`, `contracts` or `validators`, and its hex-encoded
+/// public key or hash.
+/// Return: 204, 404 if the entity wasn't watched, or 400 with the reason if the entity is malformed.
+/// Example: curl -X DELETE http://127.0.0.1:18887/watch_list/accounts/01aa...
+fn unwatch_filter(
+    watch_list: WatchList,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("watch_list" / WatchedKind / String)
+        .and(warp::delete())
+        .map(
+            move |kind, entity: String| match watch_list.remove(kind, &entity) {
+                Ok(true) => warp::reply::with_status(String::new(), StatusCode::NO_CONTENT),
+                Ok(false) => warp::reply::with_status(String::new(), StatusCode::NOT_FOUND),
+                Err(error) => warp::reply::with_status(error, StatusCode::BAD_REQUEST),
+            },
+        )
+}
+
+/// Return the lag of every connected node.
+/// Return: JSON object with the `head_divergence` of the nodes, i.e. the difference between the
+/// highest and the lowest of their latest blocks, and for each node its latest block, the latest
+/// block ingested from it, how many `blocks_behind_head` the ingestion is and the
+/// `seconds_since_last_event` ingested from it. The latest blocks of the nodes are only known if
+/// the `source_scoring` section is configured.
+/// Example: curl http://127.0.0.1:18887/sources
+fn sources_filter(
+    source_scores: SourceScores,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("sources")
+        .and(warp::get())
+        .map(move || warp::reply::json(&source_scores.report()))
+}
+
+/// Return the account aliases.
+/// Return: JSON object with the `account_hash`, and the `public_key` if given, of every alias.
+/// Example: curl http://127.0.0.1:18887/account_aliases
+fn account_aliases_filter(
+    account_aliases: AccountAliases,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("account_aliases")
+        .and(warp::get())
+        .map(move || warp::reply::json(&account_aliases.entries()))
+}
+
+/// Make an alias stand for an account until the sidecar restarts.
+/// Input: the alias and the account, as a hex-encoded public key or account hash or as an
+/// `account-hash-` formatted string.
+/// Return: 201 if the alias is new, 200 if it was changed, 400 if the alias or account is invalid.
+/// Example: curl -X PUT http://127.0.0.1:18887/account_aliases/treasury/01aa...
+fn add_account_alias_filter(
+    account_aliases: AccountAliases,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("account_aliases" / String / String)
+        .and(warp::put())
+        .map(
+            move |name: String, account: String| match account_aliases.add(&name, &account) {
+                Ok(true) => warp::reply::with_status(String::new(), StatusCode::CREATED),
+                Ok(false) => warp::reply::with_status(String::new(), StatusCode::OK),
+                Err(error) => warp::reply::with_status(error, StatusCode::BAD_REQUEST),
+            },
+        )
+}
+
+/// Remove an alias until the sidecar restarts.
+/// Input: the alias.
+/// Return: 204, or 404 if there was no such alias.
+/// Example: curl -X DELETE http://127.0.0.1:18887/account_aliases/treasury
+fn remove_account_alias_filter(
+    account_aliases: AccountAliases,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("account_aliases" / String)
+        .and(warp::delete())
+        .map(move |name: String| {
+            let status = if account_aliases.remove(&name) {
+                StatusCode::NO_CONTENT
+            } else {
+                StatusCode::NOT_FOUND
+            };
+            warp::reply::with_status(warp::reply(), status)
+        })
+}
+
+/// Return the last rebuild of each derived table started since the sidecar started.
+/// Return: JSON array with the `table`, `state` (`running`, `completed` or `failed`), start and
+/// finish times, number of `processed_deploys` and `stored_rows`, and `error` of each rebuild.
+/// Example: curl http://127.0.0.1:18887/rebuild
+fn rebuilds_filter(
+    rebuilds: Rebuilds,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rebuild")
+        .and(warp::get())
+        .map(move || warp::reply::json(&rebuilds.jobs()))
+}
+
+#[derive(Deserialize)]
+struct RebuildQuery {
+    index: String,
+}
+
+/// Start rebuilding a table derived from the stored processed deploys, in the background.
+/// Input: `index` - the table to rebuild: `transfers`, `token_transfers`, `nft_events` or
+/// `contract_events`.
+/// Return: 202 with the started rebuild, 400 if the table is unknown or its decoding isn't
+/// configured, 409 if a rebuild of the table is already running.
+/// Example: curl -X POST http://127.0.0.1:18887/rebuild?index=transfers
+fn start_rebuild_filter<Db: DatabaseReader + DatabaseWriter + Clone + Send + Sync + 'static>(
+    database: Db,
+    rebuilds: Rebuilds,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rebuild")
+        .and(warp::post())
+        .and(warp::query::<RebuildQuery>())
+        .map(move |query: RebuildQuery| {
+            let Some(table) = DerivedTable::from_name(&query.index) else {
+                let names: Vec<&str> = DerivedTable::ALL.iter().map(DerivedTable::name).collect();
+                let message = format!(
+                    "unknown index {}, expected one of {}",
+                    query.index,
+                    names.join(", ")
+                );
+                return warp::reply::with_status(message, StatusCode::BAD_REQUEST).into_response();
+            };
+            match rebuilds.start(table, database.clone()) {
+                Ok(job) => warp::reply::with_status(warp::reply::json(&job), StatusCode::ACCEPTED)
+                    .into_response(),
+                Err(RebuildError::AlreadyRunning) => warp::reply::with_status(
+                    format!("a rebuild of {} is already running", table.name()),
+                    StatusCode::CONFLICT,
+                )
+                .into_response(),
+                Err(RebuildError::NotConfigured(message)) => {
+                    warp::reply::with_status(message, StatusCode::BAD_REQUEST).into_response()
+                }
+            }
+        })
+}
+
+/// Return the accepted API keys, without the keys themselves.
+/// Return: JSON array with the `name`, `scopes` and `source` (`config` or `database`) of each key,
+/// or 404 if API keys are not configured.
+/// Example: curl -H "X-API-Key: ..." http://127.0.0.1:18887/api_keys
+fn api_keys_filter(
+    maybe_api_keys: Option<ApiKeys>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("api_keys").and(warp::get()).and_then(move || {
+        let maybe_entries = maybe_api_keys.as_ref().map(ApiKeys::entries);
+        async move {
+            maybe_entries
+                .map(|entries| warp::reply::json(&entries))
+                .ok_or_else(warp::reject::not_found)
+        }
+    })
+}
+
+#[derive(Deserialize)]
+struct CreateApiKeyQuery {
+    scopes: String,
+}
+
+/// Create an API key, whose hash is stored in the database.
+/// Input: the name of the key and `scopes` - its scopes separated by commas: `read_events`,
+/// `admin`.
+/// Return: 201 with the `name`, `scopes` and `key`, which is returned only once, 400 if a scope is
+/// unknown or the keys are not stored in the database, 409 if a key of that name exists.
+/// Example: curl -X POST -H "X-API-Key: ..." http://127.0.0.1:18887/api_keys/indexer?scopes=read_events
+fn create_api_key_filter<Db: DatabaseWriter + Clone + Send + Sync + 'static>(
+    database: Db,
+    maybe_api_keys: Option<ApiKeys>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("api_keys" / String)
+        .and(warp::post())
+        .and(warp::query::<CreateApiKeyQuery>())
+        .and_then(move |name: String, query: CreateApiKeyQuery| {
+            let database = database.clone();
+            let maybe_api_keys = maybe_api_keys.clone();
+            async move {
+                let api_keys = maybe_api_keys.ok_or_else(warp::reject::not_found)?;
+                let scopes: Option<Vec<ApiKeyScope>> = query
+                    .scopes
+                    .split(',')
+                    .map(|scope| ApiKeyScope::from_name(scope.trim()))
+                    .collect();
+                let Some(scopes) = scopes else {
+                    let message = format!(
+                        "unknown scope in {}, expected read_events or admin",
+                        query.scopes
+                    );
+                    return Ok::<_, Rejection>(
+                        warp::reply::with_status(message, StatusCode::BAD_REQUEST).into_response(),
+                    );
+                };
+                let response = match api_keys.create(&database, &name, scopes.clone()).await {
+                    Ok(key) => warp::reply::with_status(
+                        warp::reply::json(&serde_json::json!({
+                            "name": name,
+                            "scopes": scopes,
+                            "key": key,
+                        })),
+                        StatusCode::CREATED,
+                    )
+                    .into_response(),
+                    Err(error) => api_key_management_error_response(error),
+                };
+                Ok(response)
+            }
+        })
+}
+
+/// Revoke an API key stored in the database.
+/// Input: the name of the key.
+/// Return: 204, 404 if there is no such key, 400 if the key is set in the config or the keys are
+/// not stored in the database.
+/// Example: curl -X DELETE -H "X-API-Key: ..." http://127.0.0.1:18887/api_keys/indexer
+fn revoke_api_key_filter<Db: DatabaseWriter + Clone + Send + Sync + 'static>(
+    database: Db,
+    maybe_api_keys: Option<ApiKeys>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("api_keys" / String)
+        .and(warp::delete())
+        .and_then(move |name: String| {
+            let database = database.clone();
+            let maybe_api_keys = maybe_api_keys.clone();
+            async move {
+                let api_keys = maybe_api_keys.ok_or_else(warp::reject::not_found)?;
+                let response = match api_keys.revoke(&database, &name).await {
+                    Ok(()) => StatusCode::NO_CONTENT.into_response(),
+                    Err(error) => api_key_management_error_response(error),
+                };
+                Ok::<_, Rejection>(response)
+            }
+        })
+}
+
+fn api_key_management_error_response(error: ApiKeyManagementError) -> Response {
+    let status = match &error {
+        ApiKeyManagementError::NameTaken(_) => StatusCode::CONFLICT,
+        ApiKeyManagementError::NotFound(_) => StatusCode::NOT_FOUND,
+        ApiKeyManagementError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        ApiKeyManagementError::NotStoredInDatabase
+        | ApiKeyManagementError::SetInConfig(_)
+        | ApiKeyManagementError::Incomplete => StatusCode::BAD_REQUEST,
+    };
+    warp::reply::with_status(error.to_string(), status).into_response()
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{admin_server::run_server, types::config::AdminServerConfig};
+    use crate::{
+        account_aliases::AccountAliases,
+        admin_server::{run_metrics_server, serve, AdminServer},
+        api_keys::{ApiKeys, API_KEY_HEADER},
+        audit::AuditLog,
+        health::Health,
+        rebuild::Rebuilds,
+        source_scoring::SourceScores,
+        testing::fake_database::FakeDatabase,
+        types::{
+            config::{
+                AdminServerConfig, ApiKeyConfig, ApiKeyScope, ApiKeysConfig, Connection,
+                MetricsServerConfig,
+            },
+            database::{AuditEntry, DatabaseWriter, EventIdAllocatorState, OperationalEvent},
+        },
+        watch_list::WatchList,
+    };
+    use casper_event_types::sse_data::SseData;
+    use casper_types::testing::TestRng;
     use portpicker::pick_unused_port;
-    use reqwest::Response;
+    use reqwest::{RequestBuilder, Response};
+    use std::time::Duration;
+    use warp::filters::BoxedFilter;
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
     async fn given_config_should_start_admin_server() {
         let port = pick_unused_port().unwrap();
         let request_url = format!("http://localhost:{}/metrics", port);
         let admin_config = AdminServerConfig {
+            address: None,
             port,
             max_concurrent_requests: 1,
             max_requests_per_second: 1,
         };
-        tokio::spawn(run_server(admin_config));
+        tokio::spawn(serve(
+            admin_config,
+            admin_server(FakeDatabase::new()).routes(),
+        ));
 
         let response = fetch_metrics_data(&request_url).await;
         let text = response.text().await.unwrap();
         assert!(text.contains("process_cpu_seconds_total"));
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn should_serve_only_metrics_on_metrics_server() {
+        let port = pick_unused_port().unwrap();
+        let metrics_config = MetricsServerConfig {
+            address: Some("127.0.0.1".to_string()),
+            port,
+            max_concurrent_requests: 1,
+            max_requests_per_second: 10,
+        };
+        tokio::spawn(run_metrics_server(metrics_config));
+
+        let response = fetch_metrics_data(&format!("http://127.0.0.1:{}/metrics", port)).await;
+        let text = response.text().await.unwrap();
+        assert!(text.contains("process_cpu_seconds_total"));
+        let response = reqwest::get(format!("http://127.0.0.1:{}/health", port))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 404);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn should_serve_observability_pack() {
+        let port = pick_unused_port().unwrap();
+        let admin_config = admin_config(port);
+        tokio::spawn(serve(
+            admin_config,
+            admin_server(FakeDatabase::new()).routes(),
+        ));
+
+        let request_url = format!("http://localhost:{}/observability-pack", port);
+        let response = fetch_metrics_data(&request_url).await;
+        assert_eq!(response.status(), 200);
+        let pack: serde_json::Value =
+            serde_json::from_str(&response.text().await.unwrap()).unwrap();
+        assert_eq!(pack["dashboard"]["uid"], "casper-event-sidecar");
+        assert!(!pack["dashboard"]["panels"].as_array().unwrap().is_empty());
+        assert!(!pack["alert_rules"]["groups"][0]["rules"]
+            .as_array()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn should_export_audit_entries_from_given_id() {
+        let port = pick_unused_port().unwrap();
+        let database = FakeDatabase::new();
+        for action in ["first", "second", "third"] {
+            database
+                .save_audit_entry("operator".to_string(), action.to_string(), None)
+                .await
+                .unwrap();
+        }
+        let admin_config = admin_config(port);
+        tokio::spawn(serve(admin_config, admin_server(database).routes()));
+
+        let request_url = format!("http://localhost:{}/audit?from=2&limit=1", port);
+        let entries = fetch_audit_entries(&request_url).await;
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id, 2);
+        assert_eq!(entries[0].action, "second");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn should_serve_operational_events_from_given_id() {
+        let port = pick_unused_port().unwrap();
+        let database = FakeDatabase::new();
+        for kind in ["started", "failover", "stopped"] {
+            database
+                .save_operational_event(kind.to_string(), None)
+                .await
+                .unwrap();
+        }
+        let admin_config = admin_config(port);
+        tokio::spawn(serve(admin_config, admin_server(database).routes()));
+
+        let request_url = format!("http://localhost:{}/events?from=2", port);
+        let response = fetch_metrics_data(&request_url).await;
+        let events: Vec<OperationalEvent> =
+            serde_json::from_str(&response.text().await.unwrap()).unwrap();
+
+        let kinds: Vec<&str> = events.iter().map(|event| event.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["failover", "stopped"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn should_record_admin_api_calls_in_audit_log() {
+        let port = pick_unused_port().unwrap();
+        let database = FakeDatabase::new();
+        let admin_config = admin_config(port);
+        let admin_server = AdminServer {
+            maybe_audit_log: Some(AuditLog::start(database.clone(), Vec::new())),
+            ..admin_server(database.clone())
+        };
+        tokio::spawn(serve(admin_config, admin_server.routes()));
+
+        let request_url = format!("http://localhost:{}/metrics", port);
+        fetch_metrics_data(&request_url).await;
+
+        let audit_url = format!("http://localhost:{}/audit", port);
+        let mut entries: Vec<AuditEntry> = Vec::new();
+        for _ in 0..20 {
+            entries = fetch_audit_entries(&audit_url).await;
+            if !entries.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert_eq!(entries[0].action, "admin_api_call");
+        assert_eq!(entries[0].details, Some("GET /metrics 200".to_string()));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn should_expose_event_id_allocator_state() {
+        let port = pick_unused_port().unwrap();
+        let database = FakeDatabase::new();
+        database.initialize_event_id_allocator(41).await.unwrap();
+        database.reserve_event_ids(1).await.unwrap();
+        let admin_config = admin_config(port);
+        tokio::spawn(serve(admin_config, admin_server(database).routes()));
+
+        let request_url = format!("http://localhost:{}/event_ids", port);
+        let body = fetch_metrics_data(&request_url).await.text().await.unwrap();
+        let state: EventIdAllocatorState = serde_json::from_str(&body).unwrap();
+
+        assert_eq!(state.next_event_id, 42);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn should_report_degraded_health_as_unavailable() {
+        let port = pick_unused_port().unwrap();
+        let health = Health::new();
+        let admin_config = admin_config(port);
+        let admin_server = AdminServer {
+            health: health.clone(),
+            ..admin_server(FakeDatabase::new())
+        };
+        tokio::spawn(serve(admin_config, admin_server.routes()));
+
+        let request_url = format!("http://localhost:{}/health", port);
+        let response = fetch_metrics_data(&request_url).await;
+        assert_eq!(response.status(), 200);
+        let body = response.text().await.unwrap();
+        assert_eq!(body, r#"{"status":"healthy"}"#);
+
+        health.set_degraded("database_pool", "all 10 connections are in use".to_string());
+        let response = fetch_metrics_data(&request_url).await;
+        assert_eq!(response.status(), 503);
+        let report: serde_json::Value =
+            serde_json::from_str(&response.text().await.unwrap()).unwrap();
+        assert_eq!(report["status"], "degraded");
+        assert_eq!(
+            report["degraded"]["database_pool"],
+            "all 10 connections are in use"
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn should_report_not_ready_as_unavailable() {
+        let port = pick_unused_port().unwrap();
+        let health = Health::new();
+        let admin_config = admin_config(port);
+        let admin_server = AdminServer {
+            health: health.clone(),
+            ..admin_server(FakeDatabase::new())
+        };
+        tokio::spawn(serve(admin_config, admin_server.routes()));
+
+        let request_url = format!("http://localhost:{}/ready", port);
+        let response = fetch_metrics_data(&request_url).await;
+        assert_eq!(response.status(), 200);
+        let body = response.text().await.unwrap();
+        assert_eq!(body, r#"{"ready":true}"#);
+
+        health.set_not_ready(
+            "catch_up",
+            "1110 blocks behind the chain head at 2310, more than 5".to_string(),
+        );
+        let response = fetch_metrics_data(&request_url).await;
+        assert_eq!(response.status(), 503);
+        let report: serde_json::Value =
+            serde_json::from_str(&response.text().await.unwrap()).unwrap();
+        assert_eq!(report["ready"], false);
+        assert_eq!(
+            report["not_ready"]["catch_up"],
+            "1110 blocks behind the chain head at 2310, more than 5"
+        );
+        // Readiness doesn't affect the health of the sidecar.
+        let health_url = format!("http://localhost:{}/health", port);
+        assert_eq!(fetch_metrics_data(&health_url).await.status(), 200);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn should_update_watch_list() {
+        let port = pick_unused_port().unwrap();
+        let watch_list = WatchList::default();
+        let admin_config = admin_config(port);
+        let admin_server = AdminServer {
+            watch_list: watch_list.clone(),
+            ..admin_server(FakeDatabase::new())
+        };
+        tokio::spawn(serve(admin_config, admin_server.routes()));
+
+        let client = reqwest::Client::new();
+        let entity_url = format!(
+            "http://localhost:{}/watch_list/contracts/hash-{}",
+            port,
+            "AB".repeat(32)
+        );
+        let response = send_once_started(|| client.put(&entity_url)).await;
+        assert_eq!(response.status(), 201);
+        let response = client.put(&entity_url).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert!(watch_list.entities().contracts.contains(&"ab".repeat(32)));
+
+        let list_url = format!("http://localhost:{}/watch_list", port);
+        let body = fetch_metrics_data(&list_url).await.text().await.unwrap();
+        assert_eq!(
+            body,
+            format!(
+                r#"{{"accounts":[],"contracts":["{}"],"validators":[]}}"#,
+                "ab".repeat(32)
+            )
+        );
+
+        let response = client.delete(&entity_url).send().await.unwrap();
+        assert_eq!(response.status(), 204);
+        let response = client.delete(&entity_url).send().await.unwrap();
+        assert_eq!(response.status(), 404);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn should_reject_malformed_watch_list_entities() {
+        let port = pick_unused_port().unwrap();
+        let routes = admin_server(FakeDatabase::new()).routes();
+        tokio::spawn(serve(admin_config(port), routes));
+
+        let client = reqwest::Client::new();
+        let malformed_url = format!("http://localhost:{}/watch_list/contracts/hash-ABCD", port);
+        let response = send_once_started(|| client.put(&malformed_url)).await;
+        assert_eq!(response.status(), 400);
+        assert_eq!(
+            response.text().await.unwrap(),
+            "expected a hash of 32 bytes, received 2 bytes: abcd"
+        );
+        let unknown_kind_url = format!("http://localhost:{}/watch_list/purses/abcd", port);
+        let response = client.put(&unknown_kind_url).send().await.unwrap();
+        assert_eq!(response.status(), 404);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn should_report_lag_of_sources() {
+        let port = pick_unused_port().unwrap();
+        let source_scores = SourceScores::new(&[
+            Connection::example_connection_1(),
+            Connection::example_connection_2(),
+        ]);
+        source_scores
+            .recorder(1)
+            .record(&SseData::random_deploy_expired(&mut TestRng::new()));
+        let admin_config = admin_config(port);
+        let admin_server = AdminServer {
+            source_scores,
+            ..admin_server(FakeDatabase::new())
+        };
+        tokio::spawn(serve(admin_config, admin_server.routes()));
+
+        let request_url = format!("http://localhost:{}/sources", port);
+        let response = fetch_metrics_data(&request_url).await;
+        assert_eq!(response.status(), 200);
+        let report: serde_json::Value =
+            serde_json::from_str(&response.text().await.unwrap()).unwrap();
+        assert_eq!(report["head_divergence"], serde_json::Value::Null);
+        assert_eq!(report["sources"][0]["node"], "127.0.0.1:18101");
+        assert_eq!(
+            report["sources"][0]["seconds_since_last_event"],
+            serde_json::Value::Null
+        );
+        assert_eq!(report["sources"][1]["node"], "127.0.0.1:18102");
+        assert_eq!(report["sources"][1]["seconds_since_last_event"], 0);
+        assert_eq!(report["sources"][1]["preferred"], false);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn should_update_account_aliases() {
+        let port = pick_unused_port().unwrap();
+        let account_aliases = AccountAliases::default();
+        let admin_config = admin_config(port);
+        let admin_server = AdminServer {
+            account_aliases: account_aliases.clone(),
+            ..admin_server(FakeDatabase::new())
+        };
+        tokio::spawn(serve(admin_config, admin_server.routes()));
+
+        let client = reqwest::Client::new();
+        let account_hash = "ab".repeat(32);
+        let alias_url = format!(
+            "http://localhost:{}/account_aliases/treasury/account-hash-{}",
+            port, account_hash
+        );
+        let response = send_once_started(|| client.put(&alias_url)).await;
+        assert_eq!(response.status(), 201);
+        let response = client.put(&alias_url).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(
+            account_aliases.alias_of(&account_hash),
+            Some("treasury".to_string())
+        );
+        let invalid_url = format!("http://localhost:{}/account_aliases/treasury/xyz", port);
+        let response = client.put(&invalid_url).send().await.unwrap();
+        assert_eq!(response.status(), 400);
+
+        let list_url = format!("http://localhost:{}/account_aliases", port);
+        let body = fetch_metrics_data(&list_url).await.text().await.unwrap();
+        assert_eq!(
+            body,
+            format!(r#"{{"treasury":{{"account_hash":"{}"}}}}"#, account_hash)
+        );
+
+        let remove_url = format!("http://localhost:{}/account_aliases/treasury", port);
+        let response = client.delete(&remove_url).send().await.unwrap();
+        assert_eq!(response.status(), 204);
+        let response = client.delete(&remove_url).send().await.unwrap();
+        assert_eq!(response.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn should_require_an_admin_key() {
+        let (routes, _) = routes_with_admin_key().await;
+
+        let response = warp::test::request().path("/api_keys").reply(&routes).await;
+        assert_eq!(response.status(), 401);
+        let response = warp::test::request().path("/health").reply(&routes).await;
+        assert_ne!(response.status(), 401);
+        // Only the probes themselves are answered without a key, not the routes ending like them.
+        for (method, path) in [
+            ("POST", "/api_keys/health?scopes=admin"),
+            ("DELETE", "/api_keys/ready"),
+            ("DELETE", "/account_aliases/health"),
+        ] {
+            let response = warp::test::request()
+                .method(method)
+                .path(path)
+                .reply(&routes)
+                .await;
+            assert_eq!(response.status(), 401);
+        }
+        let response = warp::test::request()
+            .path("/api_keys")
+            .header(API_KEY_HEADER, "admin-key")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn should_manage_stored_api_keys() {
+        let (routes, api_keys) = routes_with_admin_key().await;
+        let request = |method: &str, path: &str| {
+            warp::test::request()
+                .method(method)
+                .path(path)
+                .header(API_KEY_HEADER, "admin-key")
+        };
+
+        let response = request("POST", "/api_keys/indexer?scopes=read_events")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), 201);
+        let created: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        let key = created["key"].as_str().unwrap();
+        assert_eq!(
+            api_keys.authorize(Some(key), ApiKeyScope::ReadEvents),
+            Ok("indexer".to_string())
+        );
+        let response = request("POST", "/api_keys/other?scopes=everything")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), 400);
+
+        let response = request("GET", "/api_keys").reply(&routes).await;
+        assert_eq!(
+            String::from_utf8(response.body().to_vec()).unwrap(),
+            r#"[{"name":"indexer","scopes":["read_events"],"source":"database"},{"name":"operator","scopes":["admin"],"source":"config"}]"#
+        );
+
+        let response = request("DELETE", "/api_keys/operator").reply(&routes).await;
+        assert_eq!(response.status(), 400);
+        let response = request("DELETE", "/api_keys/indexer").reply(&routes).await;
+        assert_eq!(response.status(), 204);
+        let response = request("DELETE", "/api_keys/indexer").reply(&routes).await;
+        assert_eq!(response.status(), 404);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn should_start_rebuilds_of_derived_tables() {
+        let port = pick_unused_port().unwrap();
+        let admin_config = admin_config(port);
+        tokio::spawn(serve(
+            admin_config,
+            admin_server(FakeDatabase::new()).routes(),
+        ));
+
+        let client = reqwest::Client::new();
+        let rebuild_url = format!("http://localhost:{}/rebuild?index=transfers", port);
+        let response = send_once_started(|| client.post(&rebuild_url)).await;
+        assert_eq!(response.status(), 202);
+        let job: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+        assert_eq!(job["table"], "transfers");
+        assert_eq!(job["state"], "running");
+        for index in ["rewards", "nft_events"] {
+            let url = format!("http://localhost:{}/rebuild?index={}", port, index);
+            let response = client.post(&url).send().await.unwrap();
+            assert_eq!(response.status(), 400);
+        }
+
+        let list_url = format!("http://localhost:{}/rebuild", port);
+        let jobs: serde_json::Value =
+            serde_json::from_str(&fetch_metrics_data(&list_url).await.text().await.unwrap())
+                .unwrap();
+        assert_eq!(jobs[0]["table"], "transfers");
+    }
+
+    /// An admin server answering with the data of `database`, with none of the optional services.
+    fn admin_server(database: FakeDatabase) -> AdminServer<FakeDatabase> {
+        AdminServer {
+            database,
+            maybe_audit_log: None,
+            health: Health::new(),
+            watch_list: WatchList::default(),
+            source_scores: SourceScores::default(),
+            account_aliases: AccountAliases::default(),
+            rebuilds: Rebuilds::default(),
+            maybe_api_keys: None,
+        }
+    }
+
+    /// The routes of an admin server storing API keys in its database, with the `admin-key` key of
+    /// the `admin` scope in its config.
+    async fn routes_with_admin_key() -> (BoxedFilter<(warp::reply::Response,)>, ApiKeys) {
+        let api_keys_config = ApiKeysConfig {
+            keys: vec![ApiKeyConfig {
+                name: "operator".to_string(),
+                key: "admin-key".to_string(),
+                scopes: vec![ApiKeyScope::Admin],
+            }],
+            from_database: true,
+        };
+        let database = FakeDatabase::new();
+        let api_keys = ApiKeys::load(&api_keys_config, &database).await.unwrap();
+        let routes = AdminServer {
+            maybe_api_keys: Some(api_keys.clone()),
+            ..admin_server(database)
+        }
+        .routes();
+        (routes, api_keys)
+    }
+
+    fn admin_config(port: u16) -> AdminServerConfig {
+        AdminServerConfig {
+            address: None,
+            port,
+            max_concurrent_requests: 1,
+            max_requests_per_second: 10,
+        }
+    }
+
+    /// Sends the request built by `request` once the server, which may still be starting, answers.
+    async fn send_once_started(request: impl Fn() -> RequestBuilder) -> Response {
+        for _ in 0..20 {
+            if let Ok(response) = request().send().await {
+                return response;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("Admin server isn't answering");
+    }
+
     async fn fetch_metrics_data(request_url: &String) -> Response {
         reqwest::Client::new()
             .get(request_url)
@@ -92,4 +1106,16 @@ mod tests {
             .await
             .expect("Error requesting the /metrics endpoint")
     }
+
+    async fn fetch_audit_entries(request_url: &String) -> Vec<AuditEntry> {
+        let body = reqwest::Client::new()
+            .get(request_url)
+            .send()
+            .await
+            .expect("Error requesting the /audit endpoint")
+            .text()
+            .await
+            .expect("Error reading the /audit response");
+        serde_json::from_str(&body).expect("Error parsing audit entries")
+    }
 }
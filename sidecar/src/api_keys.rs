@@ -0,0 +1,573 @@
+//! Authentication of the requests to the REST, event stream and admin servers with API keys.
+//!
+//! The keys are set in the `[api_keys]` table of the config and, if `from_database` is set,
+//! created and revoked through the admin server, which stores their hashes in the database. Every
+//! request needs to present a key in the `X-API-Key` header, with the `read_events` scope for the
+//! REST and event stream servers and with the `admin` scope for the admin server. Browsers can't
+//! set headers on `EventSource` and WebSocket connections, so the event stream server also accepts
+//! the key in the `api_key` query parameter or cookie. Only the `health` and `ready` probes of the
+//! admin server are answered without a key.
+
+use crate::types::{
+    config::{ApiKeyScope, ApiKeysConfig},
+    database::{
+        DatabaseReadError, DatabaseReader, DatabaseWriteError, DatabaseWriter, StoredApiKey,
+    },
+};
+use casper_event_types::metrics::API_KEY_REJECTIONS;
+use hyper::Body;
+use rand::Rng;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use thiserror::Error;
+use tracing::info;
+use warp::{
+    filters::BoxedFilter,
+    http::{Method, StatusCode},
+    path::{FullPath, Tail},
+    reply::Response,
+    Filter,
+};
+
+pub(crate) const API_KEY_HEADER: &str = "x-api-key";
+/// The query parameter and the cookie in which the event stream server also accepts a key.
+pub(crate) const API_KEY_PARAMETER: &str = "api_key";
+
+/// Paths of the admin server answered without a key, for the probes of orchestrators.
+const UNAUTHENTICATED_ADMIN_PATHS: [&str; 2] = ["health", "ready"];
+
+/// Number of random bytes of the keys created through the admin server.
+const GENERATED_KEY_LENGTH_IN_BYTES: usize = 32;
+
+/// Where an API key is set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ApiKeySource {
+    Config,
+    Database,
+}
+
+/// An accepted API key, as listed by the admin server. The key itself is never listed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct ApiKeyInfo {
+    pub(crate) name: String,
+    pub(crate) scopes: Vec<ApiKeyScope>,
+    pub(crate) source: ApiKeySource,
+}
+
+/// Why a request was refused.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub(crate) enum ApiKeyError {
+    #[error("missing API key")]
+    Missing,
+    #[error("invalid API key")]
+    Invalid,
+    #[error("API key {name} lacks the {} scope", .scope.as_str())]
+    Forbidden { name: String, scope: ApiKeyScope },
+}
+
+impl ApiKeyError {
+    fn reason(&self) -> &'static str {
+        match self {
+            ApiKeyError::Missing => "missing",
+            ApiKeyError::Invalid => "invalid",
+            ApiKeyError::Forbidden { .. } => "forbidden",
+        }
+    }
+
+    /// 401 (Unauthorized) if no valid key was presented, 403 (Forbidden) if the key lacks the
+    /// scope.
+    fn into_response(self) -> Response {
+        let status = match &self {
+            ApiKeyError::Missing | ApiKeyError::Invalid => StatusCode::UNAUTHORIZED,
+            ApiKeyError::Forbidden { .. } => StatusCode::FORBIDDEN,
+        };
+        let mut response = Response::new(Body::from(format!("unauthorized: {}\n", self)));
+        *response.status_mut() = status;
+        response
+    }
+}
+
+/// Why an API key couldn't be created or revoked through the admin server.
+#[derive(Debug, Error)]
+pub(crate) enum ApiKeyManagementError {
+    #[error("API keys are not stored in the database")]
+    NotStoredInDatabase,
+    #[error("an API key named {0} already exists")]
+    NameTaken(String),
+    #[error("API key {0} is set in the config")]
+    SetInConfig(String),
+    #[error("no API key is named {0}")]
+    NotFound(String),
+    #[error("an API key needs a name and at least one scope")]
+    Incomplete,
+    #[error("error storing the API key: {}", .0.to_string())]
+    Database(DatabaseWriteError),
+}
+
+/// The accepted API keys, by the hex-encoded SHA-256 hash of the key. Cloned handles share the
+/// same keys.
+#[derive(Clone, Debug)]
+pub(crate) struct ApiKeys {
+    keys: Arc<RwLock<HashMap<String, ApiKeyInfo>>>,
+    from_database: bool,
+}
+
+impl ApiKeys {
+    /// The keys of the config.
+    pub(crate) fn new(config: &ApiKeysConfig) -> Self {
+        let keys = config
+            .keys
+            .iter()
+            .map(|key| {
+                let info = ApiKeyInfo {
+                    name: key.name.clone(),
+                    scopes: key.scopes.clone(),
+                    source: ApiKeySource::Config,
+                };
+                (hash_key(&key.key), info)
+            })
+            .collect();
+        ApiKeys {
+            keys: Arc::new(RwLock::new(keys)),
+            from_database: config.from_database,
+        }
+    }
+
+    /// The keys of the config along with, if `from_database` is set, the keys stored in
+    /// `database`.
+    pub(crate) async fn load<Db: DatabaseReader>(
+        config: &ApiKeysConfig,
+        database: &Db,
+    ) -> Result<Self, DatabaseReadError> {
+        let api_keys = ApiKeys::new(config);
+        if config.from_database {
+            for stored_key in database.get_api_keys().await? {
+                let info = ApiKeyInfo {
+                    name: stored_key.name,
+                    scopes: stored_key.scopes,
+                    source: ApiKeySource::Database,
+                };
+                api_keys.write().insert(stored_key.key_hash, info);
+            }
+        }
+        Ok(api_keys)
+    }
+
+    /// Checks that `maybe_key` is an accepted key with `scope` and returns the name of the key.
+    pub(crate) fn authorize(
+        &self,
+        maybe_key: Option<&str>,
+        scope: ApiKeyScope,
+    ) -> Result<String, ApiKeyError> {
+        let key = maybe_key
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+            .ok_or(ApiKeyError::Missing)?;
+        let keys = self.keys.read().expect("API keys lock poisoned");
+        let info = keys.get(&hash_key(key)).ok_or(ApiKeyError::Invalid)?;
+        if !info.scopes.contains(&scope) {
+            return Err(ApiKeyError::Forbidden {
+                name: info.name.clone(),
+                scope,
+            });
+        }
+        Ok(info.name.clone())
+    }
+
+    /// The name of `key`, if it is accepted, whatever its scopes.
+    pub(crate) fn name_of(&self, key: &str) -> Option<String> {
+        self.keys
+            .read()
+            .expect("API keys lock poisoned")
+            .get(&hash_key(key.trim()))
+            .map(|info| info.name.clone())
+    }
+
+    /// The accepted keys, by name.
+    pub(crate) fn entries(&self) -> Vec<ApiKeyInfo> {
+        let mut entries: Vec<ApiKeyInfo> = self
+            .keys
+            .read()
+            .expect("API keys lock poisoned")
+            .values()
+            .cloned()
+            .collect();
+        entries.sort_by(|left, right| left.name.cmp(&right.name));
+        entries
+    }
+
+    /// Creates a random key with `scopes`, stores its hash in `database` and returns the key,
+    /// which can't be retrieved afterwards.
+    pub(crate) async fn create<Db: DatabaseWriter>(
+        &self,
+        database: &Db,
+        name: &str,
+        scopes: Vec<ApiKeyScope>,
+    ) -> Result<String, ApiKeyManagementError> {
+        if !self.from_database {
+            return Err(ApiKeyManagementError::NotStoredInDatabase);
+        }
+        if name.is_empty() || scopes.is_empty() {
+            return Err(ApiKeyManagementError::Incomplete);
+        }
+        if self.find(name).is_some() {
+            return Err(ApiKeyManagementError::NameTaken(name.to_string()));
+        }
+        let key = hex::encode(rand::thread_rng().gen::<[u8; GENERATED_KEY_LENGTH_IN_BYTES]>());
+        let key_hash = hash_key(&key);
+        let created_timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or_default();
+        database
+            .save_api_key(StoredApiKey {
+                name: name.to_string(),
+                key_hash: key_hash.clone(),
+                scopes: scopes.clone(),
+                created_timestamp,
+            })
+            .await
+            .map_err(|error| match error {
+                DatabaseWriteError::UniqueConstraint(_) => {
+                    ApiKeyManagementError::NameTaken(name.to_string())
+                }
+                error => ApiKeyManagementError::Database(error),
+            })?;
+        let info = ApiKeyInfo {
+            name: name.to_string(),
+            scopes,
+            source: ApiKeySource::Database,
+        };
+        self.write().insert(key_hash, info);
+        info!(name, "created API key");
+        Ok(key)
+    }
+
+    /// Deletes the key named `name` from `database`, after which it is refused.
+    pub(crate) async fn revoke<Db: DatabaseWriter>(
+        &self,
+        database: &Db,
+        name: &str,
+    ) -> Result<(), ApiKeyManagementError> {
+        if !self.from_database {
+            return Err(ApiKeyManagementError::NotStoredInDatabase);
+        }
+        match self.find(name) {
+            None => return Err(ApiKeyManagementError::NotFound(name.to_string())),
+            Some(ApiKeySource::Config) => {
+                return Err(ApiKeyManagementError::SetInConfig(name.to_string()))
+            }
+            Some(ApiKeySource::Database) => {}
+        }
+        database
+            .delete_api_key(name)
+            .await
+            .map_err(ApiKeyManagementError::Database)?;
+        self.write().retain(|_, info| info.name != name);
+        info!(name, "revoked API key");
+        Ok(())
+    }
+
+    fn find(&self, name: &str) -> Option<ApiKeySource> {
+        self.keys
+            .read()
+            .expect("API keys lock poisoned")
+            .values()
+            .find(|info| info.name == name)
+            .map(|info| info.source)
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<String, ApiKeyInfo>> {
+        self.keys.write().expect("API keys lock poisoned")
+    }
+}
+
+/// Guards `routes` with `maybe_api_keys`, if any: the requests without a key having `scope` in
+/// their `X-API-Key` header are answered with 401 or 403 instead of being routed. Preflight
+/// requests are always routed, so that browsers can learn which headers to send.
+pub(crate) fn require_scope(
+    maybe_api_keys: Option<ApiKeys>,
+    scope: ApiKeyScope,
+    routes: BoxedFilter<(Response,)>,
+) -> BoxedFilter<(Response,)> {
+    let presented_key = warp::header::optional::<String>(API_KEY_HEADER).boxed();
+    guard(maybe_api_keys, scope, presented_key, routes)
+}
+
+/// Guards the event streams in `routes` like [require_scope], also accepting the key in the
+/// `api_key` query parameter or cookie of the request when it has no `X-API-Key` header.
+pub(crate) fn require_scope_of_streams(
+    maybe_api_keys: Option<ApiKeys>,
+    scope: ApiKeyScope,
+    routes: BoxedFilter<(Response,)>,
+) -> BoxedFilter<(Response,)> {
+    let presented_key = warp::header::optional::<String>(API_KEY_HEADER)
+        .and(
+            warp::query::<HashMap<String, String>>()
+                .or(warp::any().map(HashMap::new))
+                .unify(),
+        )
+        .and(warp::cookie::optional::<String>(API_KEY_PARAMETER))
+        .map(
+            |maybe_header: Option<String>,
+             mut query: HashMap<String, String>,
+             maybe_cookie: Option<String>| {
+                maybe_header
+                    .or_else(|| query.remove(API_KEY_PARAMETER))
+                    .or(maybe_cookie)
+            },
+        )
+        .boxed();
+    guard(maybe_api_keys, scope, presented_key, routes)
+}
+
+fn guard(
+    maybe_api_keys: Option<ApiKeys>,
+    scope: ApiKeyScope,
+    presented_key: BoxedFilter<(Option<String>,)>,
+    routes: BoxedFilter<(Response,)>,
+) -> BoxedFilter<(Response,)> {
+    let Some(api_keys) = maybe_api_keys else {
+        return routes;
+    };
+    // The guard goes first and lets the request through to the routes by rejecting it.
+    warp::method()
+        .and(warp::path::full())
+        .and(warp::path::tail())
+        .and(presented_key)
+        .and_then(
+            move |method: Method, path: FullPath, tail: Tail, maybe_key: Option<String>| {
+                let result = if method == Method::OPTIONS
+                    || is_unauthenticated(scope, &method, tail.as_str())
+                {
+                    Ok(())
+                } else {
+                    api_keys.authorize(maybe_key.as_deref(), scope).map(|_| ())
+                };
+                async move {
+                    match result {
+                        Ok(()) => Err(warp::reject()),
+                        Err(error) => {
+                            API_KEY_REJECTIONS
+                                .with_label_values(&[scope.as_str(), error.reason()])
+                                .inc();
+                            info!(%error, path = path.as_str(), "refusing request");
+                            Ok(error.into_response())
+                        }
+                    }
+                }
+            },
+        )
+        .or(routes)
+        .unify()
+        .boxed()
+}
+
+/// Whether the `method` request to `tail`, the path relative to the network the routes are
+/// mounted under if any, is answered without a key. Only the whole path of a probe matches, so
+/// that the routes ending in a segment chosen by the caller, e.g. `api_keys/<name>`, never do.
+fn is_unauthenticated(scope: ApiKeyScope, method: &Method, tail: &str) -> bool {
+    scope == ApiKeyScope::Admin
+        && method == Method::GET
+        && UNAUTHENTICATED_ADMIN_PATHS.contains(&tail)
+}
+
+fn hash_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::fake_database::FakeDatabase, types::config::ApiKeyConfig, utils::mount_networks,
+    };
+    use warp::Reply;
+
+    const READ_KEY: &str = "read-key";
+    const ADMIN_KEY: &str = "admin-key";
+
+    fn config(from_database: bool) -> ApiKeysConfig {
+        ApiKeysConfig {
+            keys: vec![
+                ApiKeyConfig {
+                    name: "explorer".to_string(),
+                    key: READ_KEY.to_string(),
+                    scopes: vec![ApiKeyScope::ReadEvents],
+                },
+                ApiKeyConfig {
+                    name: "operator".to_string(),
+                    key: ADMIN_KEY.to_string(),
+                    scopes: vec![ApiKeyScope::Admin],
+                },
+            ],
+            from_database,
+        }
+    }
+
+    fn guarded(scope: ApiKeyScope) -> BoxedFilter<(Response,)> {
+        let routes = warp::path!("block")
+            .or(warp::path!("health"))
+            .unify()
+            .map(|| warp::reply().into_response())
+            .boxed();
+        require_scope(Some(ApiKeys::new(&config(false))), scope, routes)
+    }
+
+    #[test]
+    fn should_authorize_keys_with_the_scope() {
+        let api_keys = ApiKeys::new(&config(false));
+
+        assert_eq!(
+            api_keys.authorize(Some(READ_KEY), ApiKeyScope::ReadEvents),
+            Ok("explorer".to_string())
+        );
+        assert_eq!(
+            api_keys.authorize(Some(READ_KEY), ApiKeyScope::Admin),
+            Err(ApiKeyError::Forbidden {
+                name: "explorer".to_string(),
+                scope: ApiKeyScope::Admin
+            })
+        );
+        assert_eq!(
+            api_keys.authorize(Some("other-key"), ApiKeyScope::ReadEvents),
+            Err(ApiKeyError::Invalid)
+        );
+        assert_eq!(
+            api_keys.authorize(None, ApiKeyScope::ReadEvents),
+            Err(ApiKeyError::Missing)
+        );
+    }
+
+    #[tokio::test]
+    async fn should_route_only_the_requests_with_a_key_of_the_scope() {
+        let routes = guarded(ApiKeyScope::ReadEvents);
+        let status = |maybe_key: Option<&str>| {
+            let mut request = warp::test::request().path("/block");
+            if let Some(key) = maybe_key {
+                request = request.header(API_KEY_HEADER, key);
+            }
+            let routes = routes.clone();
+            async move { request.reply(&routes).await.status() }
+        };
+
+        assert_eq!(status(Some(READ_KEY)).await, StatusCode::OK);
+        assert_eq!(status(None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some("other-key")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some(ADMIN_KEY)).await, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn should_accept_the_key_of_a_stream_in_its_query_or_cookie() {
+        let routes = warp::path!("events")
+            .map(|| warp::reply().into_response())
+            .boxed();
+        let api_keys = Some(ApiKeys::new(&config(false)));
+        let streams =
+            require_scope_of_streams(api_keys.clone(), ApiKeyScope::ReadEvents, routes.clone());
+        let others = require_scope(api_keys, ApiKeyScope::ReadEvents, routes);
+        let in_query =
+            || warp::test::request().path(&format!("/events?{}={}", API_KEY_PARAMETER, READ_KEY));
+        let in_cookie = || {
+            warp::test::request()
+                .path("/events")
+                .header("cookie", format!("{}={}", API_KEY_PARAMETER, READ_KEY))
+        };
+
+        assert_eq!(in_query().reply(&streams).await.status(), StatusCode::OK);
+        assert_eq!(in_cookie().reply(&streams).await.status(), StatusCode::OK);
+        assert_eq!(
+            in_query().reply(&others).await.status(),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            in_cookie().reply(&others).await.status(),
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[tokio::test]
+    async fn should_answer_the_admin_probes_without_a_key() {
+        let routes = guarded(ApiKeyScope::Admin);
+
+        let health = warp::test::request().path("/health").reply(&routes).await;
+        let block = warp::test::request().path("/block").reply(&routes).await;
+
+        assert_eq!(health.status(), StatusCode::OK);
+        assert_eq!(block.status(), StatusCode::UNAUTHORIZED);
+        let mounted = mount_networks(vec![("mainnet".to_string(), routes)]);
+        let mounted_health = warp::test::request()
+            .path("/mainnet/health")
+            .reply(&mounted)
+            .await;
+        assert_eq!(mounted_health.status(), StatusCode::OK);
+        assert!(!is_unauthenticated(
+            ApiKeyScope::Admin,
+            &Method::POST,
+            "health"
+        ));
+        assert!(!is_unauthenticated(
+            ApiKeyScope::Admin,
+            &Method::GET,
+            "api_keys/health"
+        ));
+        assert!(!is_unauthenticated(
+            ApiKeyScope::ReadEvents,
+            &Method::GET,
+            "health"
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_create_and_revoke_keys_stored_in_the_database() {
+        let database = FakeDatabase::new();
+        let api_keys = ApiKeys::load(&config(true), &database).await.unwrap();
+
+        let key = api_keys
+            .create(&database, "indexer", vec![ApiKeyScope::ReadEvents])
+            .await
+            .unwrap();
+        assert!(matches!(
+            api_keys
+                .create(&database, "indexer", vec![ApiKeyScope::Admin])
+                .await,
+            Err(ApiKeyManagementError::NameTaken(_))
+        ));
+        assert_eq!(
+            api_keys.authorize(Some(&key), ApiKeyScope::ReadEvents),
+            Ok("indexer".to_string())
+        );
+        let reloaded = ApiKeys::load(&config(true), &database).await.unwrap();
+        assert_eq!(reloaded.entries(), api_keys.entries());
+
+        assert!(matches!(
+            api_keys.revoke(&database, "explorer").await,
+            Err(ApiKeyManagementError::SetInConfig(_))
+        ));
+        api_keys.revoke(&database, "indexer").await.unwrap();
+        assert_eq!(
+            api_keys.authorize(Some(&key), ApiKeyScope::ReadEvents),
+            Err(ApiKeyError::Invalid)
+        );
+        assert!(database.get_api_keys().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_not_create_keys_unless_stored_in_the_database() {
+        let database = FakeDatabase::new();
+        let api_keys = ApiKeys::load(&config(false), &database).await.unwrap();
+
+        assert!(matches!(
+            api_keys
+                .create(&database, "indexer", vec![ApiKeyScope::ReadEvents])
+                .await,
+            Err(ApiKeyManagementError::NotStoredInDatabase)
+        ));
+    }
+}
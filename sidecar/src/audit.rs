@@ -0,0 +1,288 @@
+//! Append-only audit trail of administrative and data-access actions.
+//!
+//! Actions are handed over to a background task which writes them to the `AuditLog` table in the
+//! order in which they were recorded, so recording an action never delays the request which
+//! triggered it.
+
+use crate::{
+    api_keys::{ApiKeys, API_KEY_HEADER},
+    types::database::DatabaseWriter,
+};
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tracing::warn;
+use warp::log::{Info, Log};
+
+const FORWARDED_FOR_HEADER: &str = "x-forwarded-for";
+const UNKNOWN_ACTOR: &str = "unknown";
+
+/// Kinds of actions which are recorded in the audit log.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum AuditAction {
+    /// The sidecar started with a freshly loaded configuration.
+    ConfigLoaded,
+    /// A request was made to the admin API.
+    AdminApiCall,
+    /// An event stream subscriber authenticated with a valid token.
+    SubscriberTokenAccepted,
+    /// An event stream subscriber presented a token which was rejected.
+    SubscriberTokenRejected,
+    /// A query was made to the REST API.
+    RestQuery,
+}
+
+impl AuditAction {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::ConfigLoaded => "config_loaded",
+            AuditAction::AdminApiCall => "admin_api_call",
+            AuditAction::SubscriberTokenAccepted => "subscriber_token_accepted",
+            AuditAction::SubscriberTokenRejected => "subscriber_token_rejected",
+            AuditAction::RestQuery => "rest_query",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct AuditRecord {
+    actor: String,
+    action: AuditAction,
+    details: Option<String>,
+}
+
+/// Handle used to record actions in the audit log. Cloned handles share the same writer task.
+#[derive(Clone, Debug)]
+pub(crate) struct AuditLog {
+    sender: UnboundedSender<AuditRecord>,
+    /// The reverse proxies whose `X-Forwarded-For` header is trusted.
+    trusted_proxies: Arc<Vec<IpAddr>>,
+}
+
+impl AuditLog {
+    /// Spawns the task writing recorded actions to the given database. The client addresses
+    /// reported by `trusted_proxies` are recorded for their requests instead of their own.
+    pub(crate) fn start<Db: DatabaseWriter + Send + Sync + 'static>(
+        database: Db,
+        trusted_proxies: Vec<IpAddr>,
+    ) -> Self {
+        let (sender, mut receiver) = unbounded_channel::<AuditRecord>();
+        tokio::spawn(async move {
+            while let Some(record) = receiver.recv().await {
+                if let Err(error) = database
+                    .save_audit_entry(
+                        record.actor,
+                        record.action.as_str().to_string(),
+                        record.details,
+                    )
+                    .await
+                {
+                    warn!(
+                        ?error,
+                        action = record.action.as_str(),
+                        "Error saving audit entry"
+                    );
+                }
+            }
+        });
+        AuditLog {
+            sender,
+            trusted_proxies: Arc::new(trusted_proxies),
+        }
+    }
+
+    pub(crate) fn record(&self, actor: &str, action: AuditAction, details: Option<String>) {
+        let _ = self.sender.send(AuditRecord {
+            actor: actor.to_string(),
+            action,
+            details,
+        });
+    }
+}
+
+/// Builds a warp logging wrapper which records every request served by the wrapped filter as
+/// `action`, by the name of its key among `maybe_api_keys` if it presented a valid one. Nothing is
+/// recorded if `maybe_audit_log` is `None`.
+pub(crate) fn audit_requests(
+    maybe_audit_log: Option<AuditLog>,
+    action: AuditAction,
+    maybe_api_keys: Option<ApiKeys>,
+) -> Log<impl Fn(Info<'_>) + Clone + Send + Sync + 'static> {
+    warp::log::custom(move |info: Info<'_>| {
+        if let Some(audit_log) = &maybe_audit_log {
+            let details = format!(
+                "{} {} {}",
+                info.method(),
+                info.path(),
+                info.status().as_u16()
+            );
+            let actor = request_actor(&info, &audit_log.trusted_proxies, maybe_api_keys.as_ref());
+            audit_log.record(&actor, action, Some(details));
+        }
+    })
+}
+
+/// The name of the API key presented with the request, if it is accepted, or else the address of
+/// the client which made it.
+fn request_actor(
+    info: &Info<'_>,
+    trusted_proxies: &[IpAddr],
+    maybe_api_keys: Option<&ApiKeys>,
+) -> String {
+    let headers = info.request_headers();
+    let maybe_key_name = maybe_api_keys.and_then(|api_keys| {
+        let key = headers.get(API_KEY_HEADER)?.to_str().ok()?;
+        api_keys.name_of(key)
+    });
+    maybe_key_name.unwrap_or_else(|| {
+        let maybe_forwarded_for = headers
+            .get(FORWARDED_FOR_HEADER)
+            .and_then(|value| value.to_str().ok());
+        client_address(info.remote_addr(), maybe_forwarded_for, trusted_proxies)
+    })
+}
+
+/// The address of the client which made the request. The `X-Forwarded-For` header can be set by
+/// any client, so it is only used if the peer is one of `trusted_proxies`: the client is then the
+/// last address of the header which isn't one of them.
+fn client_address(
+    maybe_peer: Option<SocketAddr>,
+    maybe_forwarded_for: Option<&str>,
+    trusted_proxies: &[IpAddr],
+) -> String {
+    let Some(peer) = maybe_peer else {
+        return UNKNOWN_ACTOR.to_string();
+    };
+    if !trusted_proxies.contains(&peer.ip()) {
+        return peer.to_string();
+    }
+    maybe_forwarded_for
+        .and_then(|forwarded_for| {
+            forwarded_for
+                .split(',')
+                .map(str::trim)
+                .rev()
+                .find(|address| {
+                    address
+                        .parse::<IpAddr>()
+                        .map_or(true, |ip| !trusted_proxies.contains(&ip))
+                })
+        })
+        .map_or_else(|| peer.to_string(), str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        api_keys::require_scope,
+        testing::fake_database::FakeDatabase,
+        types::{
+            config::{ApiKeyConfig, ApiKeyScope, ApiKeysConfig},
+            database::{AuditEntry, DatabaseReader},
+        },
+    };
+    use std::time::Duration;
+    use warp::{Filter, Reply};
+
+    /// The entries saved in `database`, once there are `count` of them.
+    async fn saved_entries(database: &FakeDatabase, count: usize) -> Vec<AuditEntry> {
+        let mut entries = Vec::new();
+        for _ in 0..50 {
+            entries = database.get_audit_entries(0, 10).await.unwrap();
+            if entries.len() == count {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        entries
+    }
+
+    #[tokio::test]
+    async fn should_save_recorded_actions_in_order() {
+        let database = FakeDatabase::new();
+        let audit_log = AuditLog::start(database.clone(), Vec::new());
+
+        audit_log.record("sidecar", AuditAction::ConfigLoaded, None);
+        audit_log.record(
+            "127.0.0.1",
+            AuditAction::AdminApiCall,
+            Some("GET /metrics 200".to_string()),
+        );
+
+        let entries = saved_entries(&database, 2).await;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].action, "config_loaded");
+        assert_eq!(entries[0].actor, "sidecar");
+        assert_eq!(entries[1].action, "admin_api_call");
+        assert_eq!(entries[1].details, Some("GET /metrics 200".to_string()));
+    }
+
+    #[tokio::test]
+    async fn should_audit_refused_requests_and_record_the_key_name() {
+        let database = FakeDatabase::new();
+        let audit_log = AuditLog::start(database.clone(), Vec::new());
+        let api_keys = ApiKeys::new(&ApiKeysConfig {
+            keys: vec![ApiKeyConfig {
+                name: "explorer".to_string(),
+                key: "read-key".to_string(),
+                scopes: vec![ApiKeyScope::ReadEvents],
+            }],
+            from_database: false,
+        });
+        let routes = warp::path!("block")
+            .map(|| warp::reply().into_response())
+            .boxed();
+        let routes = require_scope(Some(api_keys.clone()), ApiKeyScope::ReadEvents, routes).with(
+            audit_requests(Some(audit_log), AuditAction::RestQuery, Some(api_keys)),
+        );
+
+        let refused = warp::test::request()
+            .path("/block")
+            .remote_addr("192.0.2.1:40000".parse().unwrap())
+            .header(FORWARDED_FOR_HEADER, "203.0.113.7")
+            .reply(&routes)
+            .await;
+        assert_eq!(refused.status(), 401);
+        let accepted = warp::test::request()
+            .path("/block")
+            .header(API_KEY_HEADER, "read-key")
+            .reply(&routes)
+            .await;
+        assert_eq!(accepted.status(), 200);
+
+        let entries = saved_entries(&database, 2).await;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].actor, "192.0.2.1:40000");
+        assert_eq!(entries[0].details, Some("GET /block 401".to_string()));
+        assert_eq!(entries[1].actor, "explorer");
+        assert_eq!(entries[1].details, Some("GET /block 200".to_string()));
+    }
+
+    #[test]
+    fn should_trust_the_forwarded_address_only_from_trusted_proxies() {
+        let proxy: IpAddr = "10.0.0.5".parse().unwrap();
+        let peer = Some(SocketAddr::new(proxy, 40000));
+        let client = Some(SocketAddr::new("192.0.2.1".parse().unwrap(), 40000));
+
+        assert_eq!(
+            client_address(peer, Some("203.0.113.7, 10.0.0.5"), &[proxy]),
+            "203.0.113.7"
+        );
+        assert_eq!(
+            client_address(peer, Some("198.51.100.2, 203.0.113.7"), &[proxy]),
+            "203.0.113.7"
+        );
+        assert_eq!(
+            client_address(client, Some("203.0.113.7"), &[proxy]),
+            "192.0.2.1:40000"
+        );
+        assert_eq!(client_address(peer, None, &[]), "10.0.0.5:40000");
+        assert_eq!(
+            client_address(None, Some("203.0.113.7"), &[proxy]),
+            "unknown"
+        );
+    }
+}
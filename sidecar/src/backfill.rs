@@ -0,0 +1,328 @@
+//! Backfill of the blocks and processed deploys missing from the database.
+//!
+//! At startup, the blocks of the configured range of heights which aren't stored are fetched from a
+//! node's RPC server with `chain_get_block`, and the deploys of every block of the range whose
+//! execution isn't stored with `info_get_deploy`. They are stored as if their `BlockAdded` and
+//! `DeployProcessed` events had been received from the node, so that the REST API serves a
+//! continuous history, but aren't broadcast to subscribers. Requests are rate-limited so that the
+//! backfill doesn't overload the node.
+
+use crate::{
+    node_rpc::{RateLimiter, RpcClient},
+    types::{
+        config::BackfillConfig,
+        database::{DatabaseReadError, DatabaseReader, DatabaseWriter},
+        sse_events::{BlockAdded, DeployProcessed},
+    },
+};
+use anyhow::{anyhow, Error};
+use casper_event_types::{metrics::BACKFILLED_EVENTS, BlockHash, Deploy, JsonBlock};
+use casper_types::ExecutionResult;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::{info, warn};
+
+/// Backfilled events weren't received from an event stream, so they have no event ID of their own.
+const BACKFILLED_EVENT_ID: u32 = 0;
+
+/// The `result` of an `info_get_deploy` request.
+#[derive(Deserialize)]
+struct DeployResult {
+    deploy: Deploy,
+    execution_results: Vec<ExecutionInfo>,
+}
+
+#[derive(Deserialize)]
+struct ExecutionInfo {
+    block_hash: BlockHash,
+    result: ExecutionResult,
+}
+
+/// Extracts the block from the `result` of a `chain_get_block` request.
+fn parse_block(mut result: Value) -> Result<JsonBlock, Error> {
+    match result.get_mut("block").map(Value::take) {
+        None | Some(Value::Null) => Err(anyhow!("no block in response")),
+        Some(block) => Ok(serde_json::from_value(block)?),
+    }
+}
+
+/// Builds the `DeployProcessed` event of the execution of the deploy in the block with the
+/// hex-encoded hash `block_hash` from the `result` of an `info_get_deploy` request.
+fn parse_deploy_processed(result: Value, block_hash: &str) -> Result<DeployProcessed, Error> {
+    let DeployResult {
+        deploy,
+        execution_results,
+    } = serde_json::from_value(result)?;
+    let execution = execution_results
+        .into_iter()
+        .find(|execution| hex::encode(execution.block_hash.inner()) == block_hash)
+        .ok_or_else(|| anyhow!("no execution result in block {}", block_hash))?;
+    let header = deploy.header();
+    Ok(DeployProcessed::new(
+        Box::new(*deploy.hash()),
+        Box::new(header.account().clone()),
+        header.timestamp(),
+        header.ttl(),
+        header.dependencies().clone(),
+        Box::new(execution.block_hash),
+        Box::new(execution.result),
+    ))
+}
+
+fn record(event_type: &str, outcome: &str) {
+    BACKFILLED_EVENTS
+        .with_label_values(&[event_type, outcome])
+        .inc();
+}
+
+struct Backfiller<Db> {
+    database: Db,
+    rpc_client: RpcClient,
+    rate_limiter: RateLimiter,
+    /// Address of the RPC server, stored as the source of the backfilled events.
+    rpc_address: String,
+}
+
+impl<Db: DatabaseReader + DatabaseWriter> Backfiller<Db> {
+    async fn call(&mut self, method: &str, params: Value) -> Result<Value, Error> {
+        self.rate_limiter.acquire().await;
+        self.rpc_client.call(method, params).await
+    }
+
+    async fn latest_height(&mut self) -> Result<u64, Error> {
+        let result = self.call("chain_get_block", json!([])).await?;
+        Ok(parse_block(result)?.header.height)
+    }
+
+    /// Returns the stored block at `height`, fetching and storing it first if it's missing, and
+    /// whether it was missing.
+    async fn block_at(&mut self, height: u64) -> Result<(BlockAdded, bool), Error> {
+        match self.database.get_block_by_height(height).await {
+            Ok(block_added) => return Ok((block_added, false)),
+            Err(DatabaseReadError::NotFound) => {}
+            Err(error) => return Err(anyhow!("error reading block: {:?}", error)),
+        }
+        let result = self
+            .call(
+                "chain_get_block",
+                json!({ "block_identifier": { "Height": height } }),
+            )
+            .await?;
+        let block = parse_block(result)?;
+        let block_added = BlockAdded::new(block.hash, Box::new(block));
+        block_added.verify()?;
+        self.database
+            .save_block_added(
+                block_added.clone(),
+                BACKFILLED_EVENT_ID,
+                self.rpc_address.clone(),
+            )
+            .await
+            .map_err(|error| anyhow!("error saving block: {:?}", error))?;
+        Ok((block_added, true))
+    }
+
+    /// Fetches and stores the execution of the deploy in the block, unless it's already stored.
+    /// Returns whether it was missing.
+    async fn backfill_deploy(
+        &mut self,
+        deploy_hash: &str,
+        block_hash: &str,
+    ) -> Result<bool, Error> {
+        match self
+            .database
+            .get_deploy_processed_by_hash(deploy_hash)
+            .await
+        {
+            Ok(_) => return Ok(false),
+            Err(DatabaseReadError::NotFound) => {}
+            Err(error) => return Err(anyhow!("error reading deploy: {:?}", error)),
+        }
+        let result = self
+            .call("info_get_deploy", json!({ "deploy_hash": deploy_hash }))
+            .await?;
+        let deploy_processed = parse_deploy_processed(result, block_hash)?;
+        self.database
+            .save_deploy_processed(
+                deploy_processed,
+                BACKFILLED_EVENT_ID,
+                self.rpc_address.clone(),
+            )
+            .await
+            .map_err(|error| anyhow!("error saving deploy: {:?}", error))?;
+        Ok(true)
+    }
+
+    /// Backfills the block at `height` and the executions of its deploys.
+    async fn backfill(&mut self, height: u64) {
+        let block_added = match self.block_at(height).await {
+            Ok((block_added, was_missing)) => {
+                if was_missing {
+                    record("BlockAdded", "stored");
+                }
+                block_added
+            }
+            Err(error) => {
+                warn!(?error, height, "Error backfilling block");
+                record("BlockAdded", "failed");
+                return;
+            }
+        };
+        let block_hash = block_added.hex_encoded_hash();
+        for deploy_hash in block_added.hex_encoded_deploy_hashes() {
+            match self.backfill_deploy(&deploy_hash, &block_hash).await {
+                Ok(true) => record("DeployProcessed", "stored"),
+                Ok(false) => {}
+                Err(error) => {
+                    warn!(?error, deploy_hash, "Error backfilling deploy");
+                    record("DeployProcessed", "failed");
+                }
+            }
+        }
+    }
+}
+
+/// Spawns the task backfilling the given database according to `config`.
+pub(crate) fn start_backfill<Db: DatabaseReader + DatabaseWriter + Send + Sync + 'static>(
+    config: BackfillConfig,
+    database: Db,
+) {
+    let mut backfiller = Backfiller {
+        database,
+        rpc_client: RpcClient::new(config.rpc_address.clone()),
+        rate_limiter: RateLimiter::new(config.max_requests_per_second()),
+        rpc_address: config.rpc_address.clone(),
+    };
+    tokio::spawn(async move {
+        let to_height = match config.to_height {
+            Some(to_height) => to_height,
+            None => match backfiller.latest_height().await {
+                Ok(latest_height) => latest_height,
+                Err(error) => {
+                    warn!(?error, "Error fetching the latest block, not backfilling");
+                    return;
+                }
+            },
+        };
+        info!(
+            "Backfilling blocks {} to {} from {}",
+            config.from_height, to_height, config.rpc_address
+        );
+        for height in config.from_height..=to_height {
+            backfiller.backfill(height).await;
+        }
+        info!("Backfilled blocks {} to {}", config.from_height, to_height);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::fake_database::FakeDatabase;
+    use casper_types::testing::TestRng;
+    use rand::Rng;
+    use std::{fmt::Debug, future::Future, time::Duration};
+    use warp::Filter;
+
+    /// Serves an RPC answering with `block` and, for any deploy, a random deploy executed in it.
+    /// Returns the port it is served on.
+    fn serve_rpc(rng: &mut TestRng, block: &JsonBlock) -> u16 {
+        let execution_result = serde_json::to_value(rng.gen::<ExecutionResult>()).unwrap();
+        let deploy = serde_json::to_value(Deploy::random(rng)).unwrap();
+        let block_json = serde_json::to_value(block).unwrap();
+        let rpc_route = warp::post()
+            .and(warp::path("rpc"))
+            .and(warp::body::json())
+            .map(move |body: Value| {
+                let result = match body["method"].as_str() {
+                    Some("chain_get_block") => json!({ "block": block_json }),
+                    _ => {
+                        let mut deploy = deploy.clone();
+                        deploy["hash"] = body["params"]["deploy_hash"].clone();
+                        json!({
+                            "deploy": deploy,
+                            "execution_results": [{
+                                "block_hash": block_json["hash"],
+                                "result": execution_result,
+                            }],
+                        })
+                    }
+                };
+                warp::reply::json(&json!({ "jsonrpc": "2.0", "id": body["id"], "result": result }))
+            });
+        let port = portpicker::pick_unused_port().expect("Unable to pick a port");
+        tokio::spawn(warp::serve(rpc_route).bind(([127, 0, 0, 1], port)));
+        port
+    }
+
+    /// Retries `read` until it succeeds, for up to 5 seconds.
+    async fn read_eventually<T, E: Debug, F: Future<Output = Result<T, E>>>(
+        what: &str,
+        read: impl Fn() -> F,
+    ) -> T {
+        let mut attempts = 0;
+        loop {
+            match read().await {
+                Ok(value) => return value,
+                Err(_) if attempts < 50 => {
+                    attempts += 1;
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+                Err(error) => panic!("{} was not backfilled: {:?}", what, error),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn should_store_missing_blocks_and_deploys() {
+        let mut rng = TestRng::new();
+        let block = JsonBlock::random(&mut rng);
+        let height = block.header.height;
+        let block_hash = hex::encode(block.hash.inner());
+        let port = serve_rpc(&mut rng, &block);
+
+        let database = FakeDatabase::new();
+        let config = BackfillConfig {
+            rpc_address: format!("http://127.0.0.1:{}/rpc", port),
+            from_height: height,
+            to_height: Some(height),
+            max_requests_per_second: Some(1000),
+        };
+        start_backfill(config, database.clone());
+
+        let block_added = read_eventually("Block", || database.get_block_by_height(height)).await;
+        assert_eq!(block_added.hex_encoded_hash(), block_hash);
+        if let Some(deploy_hash) = block_added.hex_encoded_deploy_hashes().first() {
+            let deploy_processed = read_eventually("Deploy", || {
+                database.get_deploy_processed_by_hash(deploy_hash)
+            })
+            .await;
+            assert_eq!(deploy_processed.hex_encoded_block_hash(), block_hash);
+        }
+    }
+
+    #[test]
+    fn should_only_accept_execution_in_the_block() {
+        let mut rng = TestRng::new();
+        let result = json!({
+            "deploy": Deploy::random(&mut rng),
+            "execution_results": [{
+                "block_hash": BlockHash::random(&mut rng),
+                "result": rng.gen::<ExecutionResult>(),
+            }],
+        });
+        let other_block_hash = hex::encode(BlockHash::random(&mut rng).inner());
+
+        let error = parse_deploy_processed(result, &other_block_hash).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("no execution result in block {}", other_block_hash)
+        );
+        assert_eq!(
+            parse_block(json!({ "block": null }))
+                .unwrap_err()
+                .to_string(),
+            "no block in response"
+        );
+    }
+}
@@ -0,0 +1,280 @@
+//! `BlockFinalized` events, derived from the finality signatures of the blocks.
+//!
+//! Once the validators which signed a block hold at least the configured share of the weight of
+//! the validators of its era, a `BlockFinalized` event is stored in the `DerivedEvent` table and
+//! published as a `Derived` event on the `/events/derived` stream, as for the events of the event
+//! derivers. The signed weight is the one served by `/block/<hash>/finality_signatures`, so blocks
+//! of eras whose validator weights aren't known are never finalized.
+//!
+//! Blocks already finalized are remembered in memory, up to [MAX_FINALIZED_BLOCKS] of them, so a
+//! block may be finalized again after a restart if more of its signatures are then received.
+
+use crate::{
+    block_signatures::get_block_finality_signatures,
+    types::{
+        config::BlockFinalityConfig,
+        database::{DatabaseReadError, DatabaseReader, DatabaseWriter, DerivedEvent},
+    },
+};
+use casper_event_types::{metrics::DERIVATIONS, sse_data::SseData, Filter};
+use casper_types::U512;
+use serde::Serialize;
+use serde_json::value::to_raw_value;
+use std::collections::{HashSet, VecDeque};
+use tokio::sync::mpsc::{channel, error::TrySendError, Sender};
+use tracing::{debug, warn};
+
+/// Name under which `BlockFinalized` events are stored and published.
+pub(crate) const BLOCK_FINALIZED_DERIVER: &str = "BlockFinalized";
+/// How many of the latest finalized blocks are remembered not to finalize them again.
+const MAX_FINALIZED_BLOCKS: usize = 10_000;
+
+type OutboundSender = Sender<(SseData, Option<Filter>, Option<String>)>;
+
+/// Payload of a `BlockFinalized` event.
+#[derive(Debug, Serialize)]
+struct BlockFinalized {
+    block_hash: String,
+    era_id: u64,
+    signed_weight: U512,
+    total_weight: U512,
+    signatures: usize,
+}
+
+/// The latest finalized blocks.
+#[derive(Default)]
+struct FinalizedBlocks {
+    hashes: HashSet<String>,
+    order: VecDeque<String>,
+}
+
+impl FinalizedBlocks {
+    fn contains(&self, block_hash: &str) -> bool {
+        self.hashes.contains(block_hash)
+    }
+
+    fn insert(&mut self, block_hash: String) {
+        if self.order.len() == MAX_FINALIZED_BLOCKS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.hashes.remove(&oldest);
+            }
+        }
+        self.hashes.insert(block_hash.clone());
+        self.order.push_back(block_hash);
+    }
+}
+
+struct FinalityWorker<Db> {
+    threshold_percent: u8,
+    database: Db,
+    outbound_sender: OutboundSender,
+    finalized_blocks: FinalizedBlocks,
+}
+
+impl<Db: DatabaseReader + DatabaseWriter + Sync> FinalityWorker<Db> {
+    /// Finalizes the block if its signatures reached the threshold, returning the outcome
+    /// reported in the metrics.
+    async fn check(&mut self, block_hash: String) -> &'static str {
+        if self.finalized_blocks.contains(&block_hash) {
+            return "none";
+        }
+        let block_signatures =
+            match get_block_finality_signatures(&self.database, &block_hash).await {
+                Ok(block_signatures) => block_signatures,
+                Err(DatabaseReadError::NotFound) => return "none",
+                Err(error) => {
+                    warn!(?error, %block_hash, "Error getting finality signatures");
+                    return "failed";
+                }
+            };
+        let (signed_weight, total_weight) = match (
+            block_signatures.signed_weight,
+            block_signatures.total_weight,
+        ) {
+            (Some(signed_weight), Some(total_weight)) if !total_weight.is_zero() => {
+                (signed_weight, total_weight)
+            }
+            _ => return "none",
+        };
+        if signed_weight * U512::from(100) < total_weight * U512::from(self.threshold_percent) {
+            return "none";
+        }
+        let block_finalized = BlockFinalized {
+            block_hash: block_hash.clone(),
+            era_id: block_signatures.era_id,
+            signed_weight,
+            total_weight,
+            signatures: block_signatures.signers.len(),
+        };
+        self.finalized_blocks.insert(block_hash);
+        self.emit(block_finalized).await
+    }
+
+    /// Stores the event and publishes it on the outbound stream.
+    async fn emit(&self, block_finalized: BlockFinalized) -> &'static str {
+        let payload = match serde_json::to_value(&block_finalized) {
+            Ok(payload) => payload,
+            Err(error) => {
+                warn!(?error, "Error serializing BlockFinalized event");
+                return "failed";
+            }
+        };
+        let raw_payload = match to_raw_value(&payload) {
+            Ok(raw_payload) => raw_payload,
+            Err(error) => {
+                warn!(?error, "Error serializing BlockFinalized event");
+                return "failed";
+            }
+        };
+        let derived_event = DerivedEvent {
+            deriver: BLOCK_FINALIZED_DERIVER.to_string(),
+            source_event_type: "FinalitySignature".to_string(),
+            payload,
+        };
+        if let Err(error) = self.database.save_derived_event(derived_event).await {
+            warn!(?error, "Error saving BlockFinalized event");
+            return "failed";
+        }
+        let sse_data = SseData::Derived {
+            deriver: BLOCK_FINALIZED_DERIVER.to_string(),
+            source_event_type: "FinalitySignature".to_string(),
+            payload: raw_payload,
+        };
+        if let Err(error) = self.outbound_sender.send((sse_data, None, None)).await {
+            debug!(
+                "Error when sending to outbound_sse_data_sender. Error: {}",
+                error
+            );
+        }
+        "derived"
+    }
+}
+
+/// Handle used to submit the stored finality signatures for aggregation. Does nothing unless
+/// started.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct BlockFinality {
+    maybe_sender: Option<Sender<String>>,
+}
+
+impl BlockFinality {
+    /// Spawns the task aggregating the finality signatures of the given database and sending the
+    /// `BlockFinalized` events to `outbound_sender`.
+    pub(crate) fn start<Db: DatabaseReader + DatabaseWriter + Send + Sync + 'static>(
+        config: &BlockFinalityConfig,
+        database: Db,
+        outbound_sender: OutboundSender,
+    ) -> Self {
+        let mut worker = FinalityWorker {
+            threshold_percent: config.threshold_percent(),
+            database,
+            outbound_sender,
+            finalized_blocks: FinalizedBlocks::default(),
+        };
+        let (sender, mut receiver) = channel::<String>(config.queue_size());
+        tokio::spawn(async move {
+            while let Some(block_hash) = receiver.recv().await {
+                let outcome = worker.check(block_hash).await;
+                DERIVATIONS
+                    .with_label_values(&[BLOCK_FINALIZED_DERIVER, outcome])
+                    .inc();
+            }
+        });
+        BlockFinality {
+            maybe_sender: Some(sender),
+        }
+    }
+
+    /// Queues the block of a finality signature to check whether it's finalized. Never waits for
+    /// the queue to have capacity.
+    pub(crate) fn submit(&self, sse_data: &SseData) {
+        let (sender, finality_signature) = match (&self.maybe_sender, sse_data) {
+            (Some(sender), SseData::FinalitySignature(finality_signature)) => {
+                (sender, finality_signature)
+            }
+            _ => return,
+        };
+        let block_hash = hex::encode(finality_signature.block_hash().inner());
+        if let Err(TrySendError::Full(_)) = sender.try_send(block_hash) {
+            DERIVATIONS
+                .with_label_values(&[BLOCK_FINALIZED_DERIVER, "dropped"])
+                .inc();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{fake_database::FakeDatabase, shared::recv_derived_payload},
+        types::sse_events::*,
+    };
+    use casper_event_types::{BlockHash, FinalitySignature as FinSig};
+    use casper_types::{testing::TestRng, PublicKey};
+    use std::time::Duration;
+
+    /// Stores the switch block of era 11, with two validators for era 12, and the finality
+    /// signature of one of them for a block of era 12, which it returns.
+    async fn store_signed_switch_block(rng: &mut TestRng, database: &FakeDatabase) -> FinSig {
+        let finality_signature = FinSig::random_for_block(BlockHash::random(rng), 12, rng);
+        let switch_block = BlockAdded::random_switch_block(
+            rng,
+            11,
+            vec![
+                finality_signature.public_key().clone(),
+                PublicKey::random(rng),
+            ],
+        );
+        database
+            .save_block_added(switch_block, 1, "127.0.0.1".to_string())
+            .await
+            .expect("Error saving block_added");
+        database
+            .save_finality_signature(
+                FinalitySignature::new(Box::new(finality_signature.clone())),
+                2,
+                "127.0.0.1".to_string(),
+            )
+            .await
+            .expect("Error saving finality_signature");
+        finality_signature
+    }
+
+    #[tokio::test]
+    async fn should_finalize_block_once_threshold_is_reached() {
+        let mut rng = TestRng::new();
+        let database = FakeDatabase::new();
+        let finality_signature = store_signed_switch_block(&mut rng, &database).await;
+        let (outbound_sender, mut outbound_receiver) = channel(10);
+        let sse_data = SseData::FinalitySignature(Box::new(finality_signature));
+
+        let above_threshold = BlockFinality::start(
+            &BlockFinalityConfig {
+                threshold_percent: Some(50),
+                queue_size: None,
+            },
+            database.clone(),
+            outbound_sender.clone(),
+        );
+        let below_threshold = BlockFinality::start(
+            &BlockFinalityConfig::default(),
+            database.clone(),
+            outbound_sender,
+        );
+        below_threshold.submit(&sse_data);
+        above_threshold.submit(&sse_data);
+        above_threshold.submit(&sse_data);
+
+        let payload = recv_derived_payload(&mut outbound_receiver, BLOCK_FINALIZED_DERIVER).await;
+        assert_eq!(payload["era_id"], 12);
+        assert_eq!(payload["signatures"], 1);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(outbound_receiver.try_recv().is_err());
+        let stored = database
+            .get_derived_events_by_deriver(BLOCK_FINALIZED_DERIVER)
+            .await
+            .expect("BlockFinalized event was not stored");
+        assert_eq!(stored.len(), 1);
+    }
+}
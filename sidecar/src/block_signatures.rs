@@ -0,0 +1,304 @@
+//! The finality signatures of a block along with the weight of the validators which signed it.
+//!
+//! The weights of the validators of an era are stored from the switch block of the previous era,
+//! so the signed weight of a block is only known if that switch block was stored with them.
+//!
+//! If the signatures of a block were checkpointed, the validators which signed it are taken from
+//! the bitset of the checkpoint, along with those of the signatures stored since.
+
+use crate::types::database::{DatabaseReadError, DatabaseReader, FinalitySignatureCheckpoint};
+use casper_event_types::FinalitySignature as FinSig;
+use casper_types::{AsymmetricType, U512};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use utoipa::ToSchema;
+
+/// The finality signatures of a block.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, ToSchema)]
+pub struct BlockFinalitySignatures {
+    pub(crate) block_hash: String,
+    pub(crate) era_id: u64,
+    /// The stored signatures, which don't include those replaced with a checkpoint.
+    pub(crate) signatures: Vec<FinSig>,
+    /// Hex-encoded public keys of the validators which signed the block, in order.
+    pub(crate) signers: Vec<String>,
+    /// Whether signatures of the block were replaced with a checkpoint.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) checkpointed: bool,
+    /// Sum of the weights of the validators which signed the block, if the weights of the
+    /// validators of the era are known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub(crate) signed_weight: Option<U512>,
+    /// Sum of the weights of all the validators of the era, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub(crate) total_weight: Option<U512>,
+}
+
+/// Returns the weights of the validators of the era by public key, or `None` if they aren't known.
+async fn get_era_weights<Db: DatabaseReader + Sync>(
+    db: &Db,
+    era_id: u64,
+) -> Result<Option<BTreeMap<String, U512>>, DatabaseReadError> {
+    match db.get_era_validator_weights(era_id).await {
+        Ok(validator_weights) => Ok(validator_weights
+            .into_iter()
+            .map(|validator_weight| {
+                let weight = U512::from_dec_str(validator_weight.weight.as_deref()?).ok()?;
+                Some((validator_weight.public_key, weight))
+            })
+            .collect()),
+        Err(DatabaseReadError::NotFound) => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Encodes the `signers` among the validators, in order of public key, as a hex-encoded bitset.
+fn encode_signers(weights: &BTreeMap<String, U512>, signers: &BTreeSet<String>) -> String {
+    let mut bitset = vec![0u8; weights.len().div_ceil(8)];
+    for (index, public_key) in weights.keys().enumerate() {
+        if signers.contains(public_key) {
+            bitset[index / 8] |= 1 << (index % 8);
+        }
+    }
+    hex::encode(bitset)
+}
+
+/// Decodes the hex-encoded bitset of the signers among the validators, in order of public key.
+fn decode_signers(weights: &BTreeMap<String, U512>, bitset: &str) -> BTreeSet<String> {
+    let bitset = hex::decode(bitset).unwrap_or_default();
+    weights
+        .keys()
+        .enumerate()
+        .filter(|(index, _)| {
+            bitset
+                .get(index / 8)
+                .map_or(false, |byte| byte & (1 << (index % 8)) != 0)
+        })
+        .map(|(_, public_key)| public_key.clone())
+        .collect()
+}
+
+fn sum_weights<'a>(
+    weights: &BTreeMap<String, U512>,
+    public_keys: impl Iterator<Item = &'a String>,
+) -> U512 {
+    public_keys
+        .filter_map(|public_key| weights.get(public_key))
+        .fold(U512::zero(), |sum, weight| sum + weight)
+}
+
+/// Returns the stored signatures, possibly none, and the checkpoint, if any, of the block.
+async fn get_signatures_and_checkpoint<Db: DatabaseReader + Sync>(
+    db: &Db,
+    block_hash: &str,
+) -> Result<(Vec<FinSig>, Option<FinalitySignatureCheckpoint>), DatabaseReadError> {
+    let signatures = match db.get_finality_signatures_by_block(block_hash).await {
+        Ok(signatures) => signatures,
+        Err(DatabaseReadError::NotFound) => Vec::new(),
+        Err(error) => return Err(error),
+    };
+    let maybe_checkpoint = match db.get_finality_signature_checkpoint(block_hash).await {
+        Ok(checkpoint) => Some(checkpoint),
+        Err(DatabaseReadError::NotFound) => None,
+        Err(error) => return Err(error),
+    };
+    Ok((signatures, maybe_checkpoint))
+}
+
+/// Returns the valid finality signatures stored for the block with the hex-encoded `block_hash`,
+/// or `NotFound` if there are none and they weren't checkpointed.
+pub(crate) async fn get_block_finality_signatures<Db: DatabaseReader + Sync>(
+    db: &Db,
+    block_hash: &str,
+) -> Result<BlockFinalitySignatures, DatabaseReadError> {
+    let (signatures, maybe_checkpoint) = get_signatures_and_checkpoint(db, block_hash).await?;
+    let era_id = match (signatures.first(), &maybe_checkpoint) {
+        (Some(signature), _) => signature.era_id().value(),
+        (None, Some(checkpoint)) => checkpoint.era_id,
+        (None, None) => return Err(DatabaseReadError::NotFound),
+    };
+    let maybe_weights = get_era_weights(db, era_id).await?;
+    let mut signers: BTreeSet<String> = signatures
+        .iter()
+        .map(|signature| signature.public_key().to_hex())
+        .collect();
+    if let (Some(checkpoint), Some(weights)) = (&maybe_checkpoint, &maybe_weights) {
+        signers.extend(decode_signers(weights, &checkpoint.signers));
+    }
+    let (signed_weight, total_weight) = match (&maybe_weights, &maybe_checkpoint) {
+        (Some(weights), _) => (
+            Some(sum_weights(weights, signers.iter())),
+            Some(sum_weights(weights, weights.keys())),
+        ),
+        (None, Some(checkpoint)) => (
+            Some(checkpoint.signed_weight),
+            Some(checkpoint.total_weight),
+        ),
+        (None, None) => (None, None),
+    };
+    Ok(BlockFinalitySignatures {
+        block_hash: block_hash.to_string(),
+        era_id,
+        signatures,
+        signers: signers.into_iter().collect(),
+        checkpointed: maybe_checkpoint.is_some(),
+        signed_weight,
+        total_weight,
+    })
+}
+
+/// Aggregates the signatures stored for the block with the hex-encoded `block_hash`, along with
+/// those of its previous checkpoint if any, into a checkpoint. Returns `None` if the weights of the
+/// validators of its era aren't known, as the signers can't be encoded without them.
+pub(crate) async fn checkpoint_block_signatures<Db: DatabaseReader + Sync>(
+    db: &Db,
+    block_hash: &str,
+) -> Result<Option<FinalitySignatureCheckpoint>, DatabaseReadError> {
+    let (signatures, maybe_checkpoint) = get_signatures_and_checkpoint(db, block_hash).await?;
+    let era_id = match (signatures.first(), &maybe_checkpoint) {
+        (Some(signature), _) => signature.era_id().value(),
+        (None, Some(checkpoint)) => checkpoint.era_id,
+        (None, None) => return Err(DatabaseReadError::NotFound),
+    };
+    let weights = match get_era_weights(db, era_id).await? {
+        Some(weights) => weights,
+        None => return Ok(None),
+    };
+    let mut signers: BTreeSet<String> = signatures
+        .iter()
+        .map(|signature| signature.public_key().to_hex())
+        .collect();
+    if let Some(checkpoint) = &maybe_checkpoint {
+        signers.extend(decode_signers(&weights, &checkpoint.signers));
+    }
+    Ok(Some(FinalitySignatureCheckpoint {
+        block_hash: block_hash.to_string(),
+        era_id,
+        signers: encode_signers(&weights, &signers),
+        signed_weight: sum_weights(&weights, signers.iter()),
+        total_weight: sum_weights(&weights, weights.keys()),
+    }))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{
+        testing::fake_database::FakeDatabase,
+        types::{database::DatabaseWriter, sse_events::*},
+    };
+    use casper_event_types::BlockHash;
+    use casper_types::{testing::TestRng, PublicKey};
+
+    /// A finality signature, by a random validator, of a random block of the era.
+    pub(crate) fn random_signature(rng: &mut TestRng, era_id: u64) -> FinalitySignature {
+        FinalitySignature::new(Box::new(FinSig::random_for_block(
+            BlockHash::random(rng),
+            era_id,
+            rng,
+        )))
+    }
+
+    #[tokio::test]
+    async fn should_sum_weights_of_signers() {
+        let mut rng = TestRng::new();
+        let database = FakeDatabase::new();
+        let finality_signature = random_signature(&mut rng, 12);
+        let other_validator = PublicKey::random(&mut rng);
+        database
+            .save_finality_signature(finality_signature.clone(), 1, "127.0.0.1".to_string())
+            .await
+            .expect("Error saving finality_signature");
+        let hex_block_hash = finality_signature.hex_encoded_block_hash();
+
+        let unknown_weights = get_block_finality_signatures(&database, &hex_block_hash)
+            .await
+            .unwrap();
+        assert_eq!(unknown_weights.era_id, 12);
+        assert_eq!(unknown_weights.signatures.len(), 1);
+        assert_eq!(unknown_weights.signed_weight, None);
+
+        let switch_block = BlockAdded::random_switch_block(
+            &mut rng,
+            11,
+            vec![
+                finality_signature.inner().public_key().clone(),
+                other_validator,
+            ],
+        );
+        database
+            .save_block_added(switch_block, 2, "127.0.0.1".to_string())
+            .await
+            .expect("Error saving block_added");
+
+        let known_weights = get_block_finality_signatures(&database, &hex_block_hash)
+            .await
+            .unwrap();
+        assert_eq!(known_weights.signed_weight, Some(U512::one()));
+        assert_eq!(known_weights.total_weight, Some(U512::from(2)));
+    }
+
+    #[test]
+    fn should_encode_signers_in_order_of_public_key() {
+        let weights: BTreeMap<String, U512> = (0..10u8)
+            .map(|index| (format!("{:02x}", index), U512::one()))
+            .collect();
+        let signers: BTreeSet<String> = ["00", "03", "09"]
+            .iter()
+            .map(|public_key| public_key.to_string())
+            .collect();
+
+        let bitset = encode_signers(&weights, &signers);
+
+        assert_eq!(bitset, "0902");
+        assert_eq!(decode_signers(&weights, &bitset), signers);
+    }
+
+    #[tokio::test]
+    async fn should_serve_signers_of_checkpointed_block() {
+        let mut rng = TestRng::new();
+        let database = FakeDatabase::new();
+        let finality_signature = random_signature(&mut rng, 12);
+        let signer = finality_signature.inner().public_key().clone();
+        database
+            .save_finality_signature(finality_signature.clone(), 1, "127.0.0.1".to_string())
+            .await
+            .expect("Error saving finality_signature");
+        let hex_block_hash = finality_signature.hex_encoded_block_hash();
+        let unknown_weights = checkpoint_block_signatures(&database, &hex_block_hash)
+            .await
+            .unwrap();
+        assert_eq!(unknown_weights, None);
+        let switch_block = BlockAdded::random_switch_block(
+            &mut rng,
+            11,
+            vec![signer.clone(), PublicKey::random(&mut rng)],
+        );
+        database
+            .save_block_added(switch_block, 2, "127.0.0.1".to_string())
+            .await
+            .expect("Error saving block_added");
+
+        let checkpoint = checkpoint_block_signatures(&database, &hex_block_hash)
+            .await
+            .unwrap()
+            .expect("Weights of the era should be known");
+        assert_eq!(checkpoint.signed_weight, U512::one());
+        database
+            .save_finality_signature_checkpoint(checkpoint)
+            .await
+            .expect("Error saving checkpoint");
+
+        let checkpointed = get_block_finality_signatures(&database, &hex_block_hash)
+            .await
+            .unwrap();
+        assert!(checkpointed.checkpointed);
+        assert!(checkpointed.signatures.is_empty());
+        assert_eq!(checkpointed.signers, vec![signer.to_hex()]);
+        assert_eq!(checkpointed.era_id, 12);
+        assert_eq!(checkpointed.signed_weight, Some(U512::one()));
+        assert_eq!(checkpointed.total_weight, Some(U512::from(2)));
+    }
+}
@@ -0,0 +1,107 @@
+//! Canonical JSON, for consumers hashing or signing the output of the sidecar.
+//!
+//! The canonical form of a JSON document has no whitespace, the keys of its objects sorted by
+//! their UTF-16 code units and its numbers written in a fixed form: integers, including floating
+//! point numbers without a fraction up to 2^53, are written without a fraction or an exponent, and
+//! the other numbers in their shortest form. It follows the JSON Canonicalization Scheme of
+//! RFC 8785, apart from the exponents of the numbers beyond the integers.
+
+use serde::Serialize;
+use serde_json::{Number, Value};
+
+/// The largest integer held exactly by a floating point number.
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+/// Serializes `value` as canonical JSON.
+pub(crate) fn to_canonical_string<T: Serialize + ?Sized>(
+    value: &T,
+) -> Result<String, serde_json::Error> {
+    let value = serde_json::to_value(value)?;
+    let mut output = String::new();
+    write_value(&value, &mut output);
+    Ok(output)
+}
+
+/// Rewrites the JSON document `json` in its canonical form.
+pub(crate) fn canonicalize(json: &str) -> Result<String, serde_json::Error> {
+    to_canonical_string(&serde_json::from_str::<Value>(json)?)
+}
+
+fn write_value(value: &Value, output: &mut String) {
+    match value {
+        Value::Null => output.push_str("null"),
+        Value::Bool(boolean) => output.push_str(if *boolean { "true" } else { "false" }),
+        Value::Number(number) => write_number(number, output),
+        Value::String(string) => write_string(string, output),
+        Value::Array(items) => {
+            output.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+                write_value(item, output);
+            }
+            output.push(']');
+        }
+        Value::Object(object) => {
+            let mut entries: Vec<(&String, &Value)> = object.iter().collect();
+            entries.sort_by(|(key, _), (other_key, _)| {
+                key.encode_utf16().cmp(other_key.encode_utf16())
+            });
+            output.push('{');
+            for (index, (key, item)) in entries.into_iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+                write_string(key, output);
+                output.push(':');
+                write_value(item, output);
+            }
+            output.push('}');
+        }
+    }
+}
+
+fn write_number(number: &Number, output: &mut String) {
+    match number.as_f64() {
+        Some(float)
+            if !number.is_i64()
+                && !number.is_u64()
+                && float.fract() == 0.0
+                && float.abs() <= MAX_SAFE_INTEGER =>
+        {
+            output.push_str(&(float as i64).to_string())
+        }
+        _ => output.push_str(&number.to_string()),
+    }
+}
+
+fn write_string(string: &str, output: &mut String) {
+    // Serializing a string can't fail.
+    output.push_str(&Value::from(string).to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_sort_keys_and_remove_whitespace() {
+        let canonical =
+            canonicalize(r#"{ "b": [1, {"z": null, "a": true}], "a": "x\ny", "｡": 1, "😀": 2 }"#)
+                .unwrap();
+
+        assert_eq!(
+            canonical,
+            r#"{"a":"x\ny","b":[1,{"a":true,"z":null}],"😀":2,"｡":1}"#
+        );
+    }
+
+    #[test]
+    fn should_write_numbers_in_fixed_form() {
+        let canonical =
+            canonicalize(r#"[1.0, -0.0, 2.5, 1e3, 18446744073709551615, 1e300]"#).unwrap();
+
+        assert_eq!(canonical, "[1,0,2.5,1000,18446744073709551615,1e300]");
+    }
+}
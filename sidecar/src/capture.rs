@@ -0,0 +1,174 @@
+//! Recording of upstream traffic.
+//!
+//! Every raw frame received from the connected nodes is appended to a capture file in the format
+//! described in [casper_event_types::capture], which can be replayed with the `simulate`
+//! subcommand. Files are rotated once they reach the configured size and only the newest files
+//! are kept.
+
+use crate::types::config::CaptureConfig;
+use anyhow::{Context, Error};
+use casper_event_types::capture::CapturedFrame;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+use tokio::sync::mpsc::{channel as mpsc_channel, Sender};
+use tracing::{error, info};
+
+const CAPTURE_FILE_PREFIX: &str = "capture-";
+const CAPTURE_FILE_EXTENSION: &str = ".ndjson";
+const CAPTURE_CHANNEL_SIZE: usize = 1000;
+
+/// Writes frames to numbered capture files in a directory, starting a new file when the current
+/// one exceeds `max_file_size` and deleting the oldest files beyond `max_files`.
+pub(crate) struct RotatingCaptureWriter {
+    directory: PathBuf,
+    max_file_size: u64,
+    max_files: usize,
+    next_index: u64,
+    current_file: Option<(File, u64)>,
+}
+
+impl RotatingCaptureWriter {
+    pub(crate) fn new(config: &CaptureConfig) -> Result<Self, Error> {
+        let directory = PathBuf::from(&config.directory);
+        fs::create_dir_all(&directory)
+            .with_context(|| format!("Error creating capture directory {}", directory.display()))?;
+        let next_index = list_capture_files(&directory)?
+            .last()
+            .map_or(0, |(index, _)| index + 1);
+        Ok(RotatingCaptureWriter {
+            directory,
+            max_file_size: config.max_file_size_in_bytes(),
+            max_files: config.max_files(),
+            next_index,
+            current_file: None,
+        })
+    }
+
+    pub(crate) fn write(&mut self, frame: &CapturedFrame) -> Result<(), Error> {
+        let mut line = serde_json::to_string(frame).context("Error serializing captured frame")?;
+        line.push('\n');
+        let needs_rotation = match &self.current_file {
+            None => true,
+            Some((_, written)) => *written > 0 && written + line.len() as u64 > self.max_file_size,
+        };
+        if needs_rotation {
+            self.rotate()?;
+        }
+        if let Some((file, written)) = &mut self.current_file {
+            file.write_all(line.as_bytes())
+                .context("Error writing to capture file")?;
+            *written += line.len() as u64;
+        }
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> Result<(), Error> {
+        let path = self.directory.join(capture_file_name(self.next_index));
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Error creating capture file {}", path.display()))?;
+        self.next_index += 1;
+        self.current_file = Some((file, 0));
+        let capture_files = list_capture_files(&self.directory)?;
+        let excess = capture_files.len().saturating_sub(self.max_files);
+        for (_, old_file) in capture_files.into_iter().take(excess) {
+            fs::remove_file(&old_file)
+                .with_context(|| format!("Error removing capture file {}", old_file.display()))?;
+        }
+        info!("Capturing upstream traffic to {}", path.display());
+        Ok(())
+    }
+}
+
+/// Starts writing captured frames in a background task and returns the sender to be handed to
+/// the event listeners.
+pub(crate) fn start_capture(config: &CaptureConfig) -> Result<Sender<CapturedFrame>, Error> {
+    let mut writer = RotatingCaptureWriter::new(config)?;
+    let (sender, mut receiver) = mpsc_channel::<CapturedFrame>(CAPTURE_CHANNEL_SIZE);
+    tokio::task::spawn_blocking(move || {
+        while let Some(frame) = receiver.blocking_recv() {
+            if let Err(error) = writer.write(&frame) {
+                error!("Stopping capture of upstream traffic: {:?}", error);
+                break;
+            }
+        }
+    });
+    Ok(sender)
+}
+
+fn capture_file_name(index: u64) -> String {
+    format!(
+        "{}{:08}{}",
+        CAPTURE_FILE_PREFIX, index, CAPTURE_FILE_EXTENSION
+    )
+}
+
+/// Lists the capture files in `directory`, oldest first.
+fn list_capture_files(directory: &Path) -> Result<Vec<(u64, PathBuf)>, Error> {
+    let mut capture_files = Vec::new();
+    let entries = fs::read_dir(directory)
+        .with_context(|| format!("Error reading capture directory {}", directory.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        let maybe_index = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix(CAPTURE_FILE_PREFIX))
+            .and_then(|name| name.strip_suffix(CAPTURE_FILE_EXTENSION))
+            .and_then(|index| index.parse::<u64>().ok());
+        if let Some(index) = maybe_index {
+            capture_files.push((index, path));
+        }
+    }
+    capture_files.sort();
+    Ok(capture_files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(id: u32) -> CapturedFrame {
+        CapturedFrame {
+            received_at: 1_700_000_000_000 + id as u64,
+            source: "http://127.0.0.1:18101".to_string(),
+            filter: "events/main".to_string(),
+            id: Some(id.to_string()),
+            data: "\"Shutdown\"".to_string(),
+        }
+    }
+
+    #[test]
+    fn should_rotate_files_and_keep_only_the_newest() {
+        let directory = tempfile::tempdir().unwrap();
+        let frame_size = serde_json::to_string(&frame(10)).unwrap().len() as u64 + 1;
+        let config = CaptureConfig {
+            directory: directory.path().to_string_lossy().to_string(),
+            max_file_size_in_bytes: Some(frame_size * 2),
+            max_files: Some(2),
+        };
+        let mut writer = RotatingCaptureWriter::new(&config).unwrap();
+
+        for id in 10..16 {
+            writer.write(&frame(id)).unwrap();
+        }
+
+        let capture_files = list_capture_files(directory.path()).unwrap();
+        let indexes: Vec<u64> = capture_files.iter().map(|(index, _)| *index).collect();
+        assert_eq!(indexes, vec![1, 2]);
+        let last_file = fs::read_to_string(&capture_files[1].1).unwrap();
+        let frames: Vec<CapturedFrame> = last_file
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(frames, vec![frame(14), frame(15)]);
+
+        let writer = RotatingCaptureWriter::new(&config).unwrap();
+        assert_eq!(writer.next_index, 3);
+    }
+}
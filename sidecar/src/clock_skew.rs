@@ -0,0 +1,159 @@
+//! Comparison of the timestamps embedded in the events received from the nodes with the times the
+//! sidecar received them, so that chain time can be told apart from arrival time.
+//!
+//! The timestamp of a block is the time its proposer created it, so the skew of a block includes
+//! the time it took to be executed and to reach the sidecar, usually a few seconds. A negative
+//! skew, or one growing steadily, hints at a misbehaving clock, of the node or of the sidecar.
+
+use crate::types::{
+    config::ClockSkewConfig,
+    database::{DatabaseWriter, Enrichment},
+};
+use casper_event_types::metrics::{CLOCK_SKEW_EXCESSES, SOURCE_CLOCK_SKEW_SECONDS};
+use casper_types::Timestamp;
+use reqwest::Url;
+use serde_json::json;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{debug, warn};
+
+/// Kind of the enrichments recording the time a block, or the acceptance of a deploy, was first
+/// received.
+pub(crate) const RECEIPT_KIND: &str = "receipt";
+/// Kind of the enrichments recording the time the processing of a deploy was first received.
+pub(crate) const PROCESSED_RECEIPT_KIND: &str = "processed_receipt";
+
+/// Records the skew between the timestamp of a block and the time it was received from the node
+/// at `source`, and returns it in milliseconds.
+pub(crate) fn observe_block_skew(
+    config: &ClockSkewConfig,
+    source: &Url,
+    block_timestamp: Timestamp,
+    received_at: SystemTime,
+) -> i64 {
+    let skew_in_milliseconds = millis_since_epoch(received_at) - block_timestamp.millis() as i64;
+    let node = node_label(source);
+    SOURCE_CLOCK_SKEW_SECONDS
+        .with_label_values(&[&node])
+        .set(skew_in_milliseconds as f64 / 1000.0);
+    if skew_in_milliseconds.unsigned_abs() > config.max_skew_in_seconds() * 1000 {
+        warn!(%node, skew_in_milliseconds, "Block timestamp far from the time it was received");
+        CLOCK_SKEW_EXCESSES.with_label_values(&[&node]).inc();
+    }
+    skew_in_milliseconds
+}
+
+/// Stores the time the entity with the hex-encoded `entity_hash` was received from the node at
+/// `source`, along with the skew of its timestamp if known.
+pub(crate) async fn annotate_receipt<Db: DatabaseWriter>(
+    database: &Db,
+    entity_hash: String,
+    kind: &str,
+    source: &Url,
+    received_at: SystemTime,
+    maybe_skew_in_milliseconds: Option<i64>,
+) {
+    let enrichment = Enrichment {
+        entity_hash,
+        kind: kind.to_string(),
+        value: receipt(source, received_at, maybe_skew_in_milliseconds),
+    };
+    if let Err(error) = database.save_enrichment(enrichment).await {
+        debug!("Couldn't store the receipt time: {:?}", error);
+    }
+}
+
+fn receipt(
+    source: &Url,
+    received_at: SystemTime,
+    maybe_skew_in_milliseconds: Option<i64>,
+) -> serde_json::Value {
+    let received_at_millis = millis_since_epoch(received_at);
+    let mut value = json!({
+        "received_at": Timestamp::from(received_at_millis as u64).to_string(),
+        "source": node_label(source),
+    });
+    if let Some(skew_in_milliseconds) = maybe_skew_in_milliseconds {
+        value["skew_in_milliseconds"] = json!(skew_in_milliseconds);
+    }
+    value
+}
+
+fn millis_since_epoch(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|since_epoch| since_epoch.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+/// Label of the node in the metrics, `<ip>:<sse port>`.
+pub(crate) fn node_label(source: &Url) -> String {
+    format!(
+        "{}:{}",
+        source.host_str().unwrap_or_default(),
+        source.port_or_known_default().unwrap_or_default()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn should_count_blocks_skewed_beyond_the_max_skew() {
+        let config = ClockSkewConfig {
+            max_skew_in_seconds: Some(10),
+            annotate_receipt_time: false,
+        };
+        let source = Url::parse("http://127.0.0.1:19101").unwrap();
+        let received_at = UNIX_EPOCH + Duration::from_millis(1_714_521_600_000);
+        let node = "127.0.0.1:19101";
+        // The metrics are global, so only their changes are asserted on.
+        let excesses_before = CLOCK_SKEW_EXCESSES.with_label_values(&[node]).get();
+
+        let skew = observe_block_skew(
+            &config,
+            &source,
+            Timestamp::from(1_714_521_598_500),
+            received_at,
+        );
+        assert_eq!(skew, 1_500);
+        assert_eq!(
+            CLOCK_SKEW_EXCESSES.with_label_values(&[node]).get(),
+            excesses_before
+        );
+
+        let skew = observe_block_skew(
+            &config,
+            &source,
+            Timestamp::from(1_714_521_630_000),
+            received_at,
+        );
+        assert_eq!(skew, -30_000);
+        assert_eq!(
+            CLOCK_SKEW_EXCESSES.with_label_values(&[node]).get(),
+            excesses_before + 1
+        );
+        assert_eq!(
+            SOURCE_CLOCK_SKEW_SECONDS.with_label_values(&[node]).get(),
+            -30.0
+        );
+    }
+
+    #[test]
+    fn should_record_the_receipt_time_and_source() {
+        let source = Url::parse("http://127.0.0.1:19101").unwrap();
+        let received_at = UNIX_EPOCH + Duration::from_millis(1_714_521_600_000);
+
+        assert_eq!(
+            receipt(&source, received_at, Some(1_500)),
+            json!({
+                "received_at": "2024-05-01T00:00:00.000Z",
+                "source": "127.0.0.1:19101",
+                "skew_in_milliseconds": 1_500,
+            })
+        );
+        assert!(receipt(&source, received_at, None)
+            .get("skew_in_milliseconds")
+            .is_none());
+    }
+}
@@ -0,0 +1,450 @@
+//! Events of contracts following the Casper Event Standard, decoded with the schemas of the events.
+//!
+//! Contracts following the standard emit their events to their `__events` dictionary, which is how
+//! the events of the configured contracts are recognized among the execution effects of the
+//! deploys, see [crate::token_transfers]. They also describe their events in their
+//! `__events_schema` named key: the names and types of the fields of each event, in the order they
+//! are serialized. The schemas are written when the contract is installed or upgraded, so they are
+//! learned from the execution effects of the deploys as well, or fetched from a node when the
+//! Sidecar starts if none are stored for the contract yet, and stored in the `ContractEventSchema`
+//! table.
+//!
+//! The events are decoded with the schema of their event, stored in the `ContractEvent` table and
+//! published as `ContractEvent` events on the `/events/derived` stream, see
+//! [crate::deploy_decoding]. The events whose schema
+//! isn't known, e.g. emitted by a contract installed before the Sidecar was started without a node
+//! to fetch its schemas from, are skipped.
+
+use crate::{
+    deploy_decoding::{DecodingWorker, DeployDecoder},
+    node_rpc::RpcClient,
+    rest_server::decoded_args,
+    token_transfers::{decode_standard_events, parse_contract_hashes, EVENT_NAME_PREFIX},
+    types::{
+        config::{ContractEventsConfig, TokenConfig},
+        database::{
+            ContractEventEntry, ContractEventField, ContractEventSchemas, DatabaseReadError,
+            DatabaseReader, DatabaseWriteError, DatabaseWriter,
+        },
+        sse_events::DeployProcessed,
+    },
+};
+use anyhow::{anyhow, Error};
+use async_trait::async_trait;
+use casper_types::{bytesrepr::FromBytes, CLType, CLValue, Key, Transform, URef, URefAddr};
+use serde_json::{json, Map};
+use std::collections::{BTreeMap, HashMap};
+use tracing::{debug, info, warn};
+
+/// Name under which `ContractEvent` events are stored and published.
+pub(crate) const CONTRACT_EVENT_DERIVER: &str = "ContractEvent";
+
+/// Handle used to submit the processed deploys for the decoding of contract events.
+pub(crate) type ContractEvents = DecodingWorker;
+
+/// The fields of each event of a contract, by name of the event.
+type Schemas = BTreeMap<String, Vec<ContractEventField>>;
+
+/// Recognizes the events and the schemas of the configured contracts among the transforms of the
+/// processed deploys.
+#[derive(Debug)]
+pub(crate) struct ContractEventDecoder {
+    /// Hex-encoded hashes of the contracts, by the address of their events URef.
+    contract_hashes: HashMap<URefAddr, String>,
+    /// Hex-encoded hashes of the contracts, by the address of their schema URef.
+    schema_contract_hashes: HashMap<URefAddr, String>,
+    /// The schema URefs of the contracts, by hex-encoded hash of the contract.
+    schema_urefs: HashMap<String, URef>,
+    /// The known schemas of the contracts, by hex-encoded hash of the contract.
+    schemas: HashMap<String, Schemas>,
+    /// The client of the node the schemas not stored yet are fetched from when the decoding starts.
+    maybe_rpc_client: Option<RpcClient>,
+}
+
+impl ContractEventDecoder {
+    /// Returns an error if the hash of a contract or one of its URefs isn't valid.
+    pub(crate) fn new(config: &ContractEventsConfig) -> Result<Self, Error> {
+        let contracts: Vec<TokenConfig> = config
+            .contracts
+            .iter()
+            .map(|contract| TokenConfig {
+                contract_hash: contract.contract_hash.clone(),
+                events_uref: contract.events_uref.clone(),
+            })
+            .collect();
+        let contract_hashes = parse_contract_hashes(&contracts)?;
+        let mut schema_contract_hashes = HashMap::new();
+        let mut schema_urefs = HashMap::new();
+        for contract in &config.contracts {
+            let contract_hash = contract.contract_hash.to_lowercase();
+            let schema_uref = URef::from_formatted_str(&contract.schema_uref).map_err(|error| {
+                anyhow!(
+                    "schema URef {} of contract {} isn't a formatted URef: {:?}",
+                    contract.schema_uref,
+                    contract.contract_hash,
+                    error
+                )
+            })?;
+            schema_contract_hashes.insert(schema_uref.addr(), contract_hash.clone());
+            schema_urefs.insert(contract_hash, schema_uref);
+        }
+        Ok(ContractEventDecoder {
+            contract_hashes,
+            schema_contract_hashes,
+            schema_urefs,
+            schemas: HashMap::new(),
+            maybe_rpc_client: config.rpc_address.clone().map(RpcClient::new),
+        })
+    }
+
+    /// Loads the stored schemas of the contracts, fetching those not stored yet from the node if
+    /// `maybe_rpc_client` is given.
+    pub(crate) async fn load_schemas<Db: DatabaseReader + DatabaseWriter>(
+        &mut self,
+        database: &Db,
+        maybe_rpc_client: Option<&RpcClient>,
+    ) {
+        for (contract_hash, schema_uref) in &self.schema_urefs {
+            let contract_event_schemas =
+                match database.get_contract_event_schemas(contract_hash).await {
+                    Ok(contract_event_schemas) => contract_event_schemas,
+                    Err(DatabaseReadError::NotFound) => {
+                        let Some(rpc_client) = maybe_rpc_client else {
+                            continue;
+                        };
+                        match fetch_schemas(rpc_client, contract_hash, schema_uref).await {
+                            Ok(contract_event_schemas) => {
+                                save_schemas(database, contract_event_schemas.clone()).await;
+                                contract_event_schemas
+                            }
+                            Err(error) => {
+                                warn!(
+                                    %contract_hash,
+                                    %error,
+                                    "Unable to fetch the schemas of the contract events"
+                                );
+                                continue;
+                            }
+                        }
+                    }
+                    Err(error) => {
+                        warn!(?error, "Error getting the schemas of the contract events");
+                        continue;
+                    }
+                };
+            self.schemas
+                .insert(contract_hash.clone(), contract_event_schemas.schemas);
+        }
+    }
+
+    /// Learns the schemas of the contracts written by the deploy, returning them to be stored.
+    pub(crate) fn learn_schemas(
+        &mut self,
+        deploy_processed: &DeployProcessed,
+    ) -> Vec<ContractEventSchemas> {
+        let learned: Vec<ContractEventSchemas> = deploy_processed
+            .transforms()
+            .iter()
+            .filter_map(
+                |entry| match (&entry.transform, Key::from_formatted_str(&entry.key)) {
+                    (Transform::WriteCLValue(cl_value), Ok(Key::URef(uref))) => {
+                        Some(ContractEventSchemas {
+                            contract_hash: self.schema_contract_hashes.get(&uref.addr())?.clone(),
+                            schemas: parse_schemas(cl_value.inner_bytes())?,
+                        })
+                    }
+                    _ => None,
+                },
+            )
+            .collect();
+        for contract_event_schemas in &learned {
+            info!(
+                contract_hash = %contract_event_schemas.contract_hash,
+                "Learned the schemas of the contract events"
+            );
+            self.schemas.insert(
+                contract_event_schemas.contract_hash.clone(),
+                contract_event_schemas.schemas.clone(),
+            );
+        }
+        learned
+    }
+
+    /// The events of the configured contracts emitted by the deploy whose schema is known, in the
+    /// order of its transforms.
+    pub(crate) fn decode(&self, deploy_processed: &DeployProcessed) -> Vec<ContractEventEntry> {
+        decode_standard_events(&self.contract_hashes, deploy_processed)
+            .into_iter()
+            .filter_map(|(contract_hash, event_id, event_bytes)| {
+                let maybe_event = self
+                    .schemas
+                    .get(&contract_hash)
+                    .and_then(|schemas| decode_event(schemas, &event_bytes));
+                if maybe_event.is_none() {
+                    debug!(
+                        %contract_hash,
+                        event_id, "Skipped contract event without matching schema"
+                    );
+                }
+                let (event_name, fields) = maybe_event?;
+                Some(ContractEventEntry {
+                    contract_hash,
+                    event_id,
+                    event_name,
+                    fields,
+                    deploy_hash: deploy_processed.hex_encoded_hash(),
+                    block_hash: deploy_processed.hex_encoded_block_hash(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl DeployDecoder for ContractEventDecoder {
+    const DERIVER: &'static str = CONTRACT_EVENT_DERIVER;
+
+    type Entry = ContractEventEntry;
+
+    async fn prepare<Db: DatabaseReader + DatabaseWriter + Sync>(&mut self, database: &Db) {
+        let maybe_rpc_client = self.maybe_rpc_client.take();
+        self.load_schemas(database, maybe_rpc_client.as_ref()).await;
+    }
+
+    async fn decode_deploy<Db: DatabaseWriter + Sync>(
+        &mut self,
+        database: &Db,
+        deploy_processed: &DeployProcessed,
+    ) -> Vec<ContractEventEntry> {
+        for contract_event_schemas in self.learn_schemas(deploy_processed) {
+            save_schemas(database, contract_event_schemas).await;
+        }
+        self.decode(deploy_processed)
+    }
+
+    async fn save<Db: DatabaseWriter + Sync>(
+        database: &Db,
+        contract_event: ContractEventEntry,
+    ) -> Result<u64, DatabaseWriteError> {
+        database.save_contract_event(contract_event).await
+    }
+}
+
+/// Parses the schemas of the events of a contract, serialized as the fields of each event by name
+/// of the event.
+fn parse_schemas(bytes: &[u8]) -> Option<Schemas> {
+    let (schemas, _) = BTreeMap::<String, Vec<(String, CLType)>>::from_bytes(bytes).ok()?;
+    Some(
+        schemas
+            .into_iter()
+            .map(|(event_name, fields)| {
+                let fields = fields
+                    .into_iter()
+                    .map(|(name, cl_type)| ContractEventField { name, cl_type })
+                    .collect();
+                (event_name, fields)
+            })
+            .collect(),
+    )
+}
+
+/// Decodes the name and fields of an event, returning `None` if its bytes don't match the schema
+/// of its event or the event has no schema.
+fn decode_event(
+    schemas: &Schemas,
+    bytes: &[u8],
+) -> Option<(String, Map<String, serde_json::Value>)> {
+    let (name, mut remainder) = String::from_bytes(bytes).ok()?;
+    let event_name = name.strip_prefix(EVENT_NAME_PREFIX)?;
+    let mut fields = Map::new();
+    for field in schemas.get(event_name)? {
+        let (value, next) = decoded_args::decode(&field.cl_type, remainder).ok()?;
+        fields.insert(field.name.clone(), value);
+        remainder = next;
+    }
+    Some((event_name.to_string(), fields))
+}
+
+/// Fetches the schemas of the events of the contract from the latest global state of the node.
+async fn fetch_schemas(
+    rpc_client: &RpcClient,
+    contract_hash: &str,
+    schema_uref: &URef,
+) -> Result<ContractEventSchemas, Error> {
+    let params = json!({
+        "key": schema_uref.to_formatted_string(),
+        "path": [],
+    });
+    let result = rpc_client.call("query_global_state", params).await?;
+    let cl_value: CLValue = serde_json::from_value(result["stored_value"]["CLValue"].clone())?;
+    let schemas = parse_schemas(cl_value.inner_bytes())
+        .ok_or_else(|| anyhow!("invalid schemas of contract {}", contract_hash))?;
+    Ok(ContractEventSchemas {
+        contract_hash: contract_hash.to_string(),
+        schemas,
+    })
+}
+
+async fn save_schemas<Db: DatabaseWriter>(
+    database: &Db,
+    contract_event_schemas: ContractEventSchemas,
+) {
+    if let Err(error) = database
+        .save_contract_event_schemas(contract_event_schemas)
+        .await
+    {
+        warn!(?error, "Error saving the schemas of the contract events");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{fake_database::FakeDatabase, shared::recv_derived_payload},
+        token_transfers::tests::{deploy_processed_with, event_transform},
+        types::config::EventContractConfig,
+    };
+    use casper_types::{
+        account::AccountHash, bytesrepr::ToBytes, testing::TestRng, AccessRights, TransformEntry,
+        U256,
+    };
+    use tokio::sync::mpsc::channel;
+
+    const CONTRACT_HASH: &str = "0303030303030303030303030303030303030303030303030303030303030303";
+
+    fn key(byte: u8) -> Key {
+        Key::Account(AccountHash::new([byte; 32]))
+    }
+
+    fn uref(addr: URefAddr) -> URef {
+        URef::new(addr, AccessRights::READ_ADD_WRITE)
+    }
+
+    fn config() -> ContractEventsConfig {
+        ContractEventsConfig {
+            contracts: vec![EventContractConfig {
+                contract_hash: CONTRACT_HASH.to_uppercase(),
+                events_uref: uref([7; 32]).to_formatted_string(),
+                schema_uref: uref([9; 32]).to_formatted_string(),
+            }],
+            rpc_address: None,
+            queue_size: None,
+        }
+    }
+
+    fn schemas() -> BTreeMap<String, Vec<(String, CLType)>> {
+        let mut schemas = BTreeMap::new();
+        schemas.insert(
+            "Deposit".to_string(),
+            vec![
+                ("account".to_string(), CLType::Key),
+                ("amount".to_string(), CLType::U256),
+                ("memo".to_string(), CLType::Option(Box::new(CLType::String))),
+            ],
+        );
+        schemas
+    }
+
+    /// The transform writing the schemas of the contract to the URef with the given address.
+    fn schema_transform(schema_uref_addr: URefAddr) -> TransformEntry {
+        TransformEntry {
+            key: Key::URef(uref(schema_uref_addr)).to_formatted_string(),
+            transform: Transform::WriteCLValue(CLValue::from_components(
+                CLType::Any,
+                schemas().to_bytes().unwrap(),
+            )),
+        }
+    }
+
+    fn deposit(account: Key, amount: u64, memo: Option<&str>) -> Vec<u8> {
+        let mut bytes = "event_Deposit".to_string().to_bytes().unwrap();
+        bytes.append(&mut account.to_bytes().unwrap());
+        bytes.append(&mut U256::from(amount).to_bytes().unwrap());
+        bytes.append(&mut memo.map(str::to_string).to_bytes().unwrap());
+        bytes
+    }
+
+    #[test]
+    fn should_decode_events_with_learned_schemas() {
+        let mut rng = TestRng::new();
+        let mut decoder = ContractEventDecoder::new(&config()).unwrap();
+        let unknown_event = "event_Withdrawal".to_string().to_bytes().unwrap();
+        let deploy_processed = DeployProcessed::random(&mut rng, None).with_transforms(vec![
+            event_transform([7; 32], 0, deposit(key(2), 5, None)),
+            schema_transform([8; 32]),
+        ]);
+
+        assert!(decoder.learn_schemas(&deploy_processed).is_empty());
+        assert!(decoder.decode(&deploy_processed).is_empty());
+
+        let deploy_processed = DeployProcessed::random(&mut rng, None).with_transforms(vec![
+            schema_transform([9; 32]),
+            event_transform([7; 32], 1, deposit(key(2), 5, Some("rent"))),
+            event_transform([7; 32], 2, unknown_event),
+            event_transform([8; 32], 3, deposit(key(2), 5, None)),
+        ]);
+
+        let learned = decoder.learn_schemas(&deploy_processed);
+        assert_eq!(learned.len(), 1);
+        assert_eq!(learned[0].contract_hash, CONTRACT_HASH);
+        assert_eq!(learned[0].schemas["Deposit"][1].cl_type, CLType::U256);
+        let contract_events = decoder.decode(&deploy_processed);
+        assert_eq!(contract_events.len(), 1);
+        assert_eq!(contract_events[0].contract_hash, CONTRACT_HASH);
+        assert_eq!(contract_events[0].event_id, 1);
+        assert_eq!(contract_events[0].event_name, "Deposit");
+        assert_eq!(
+            serde_json::Value::Object(contract_events[0].fields.clone()),
+            json!({
+                "account": key(2).to_formatted_string(),
+                "amount": "5",
+                "memo": "rent",
+            })
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_contract_config() {
+        let mut config = config();
+        config.contracts[0].schema_uref = "uref-xyz".to_string();
+        assert!(ContractEventDecoder::new(&config).is_err());
+    }
+
+    #[tokio::test]
+    async fn should_store_and_publish_contract_events_with_stored_schemas() {
+        let mut rng = TestRng::new();
+        let database = FakeDatabase::new();
+        database
+            .save_contract_event_schemas(ContractEventSchemas {
+                contract_hash: CONTRACT_HASH.to_string(),
+                schemas: parse_schemas(&schemas().to_bytes().unwrap()).unwrap(),
+            })
+            .await
+            .unwrap();
+        let (outbound_sender, mut outbound_receiver) = channel(10);
+        let config = config();
+        let contract_events = ContractEvents::start(
+            ContractEventDecoder::new(&config).unwrap(),
+            config.queue_size(),
+            database.clone(),
+            outbound_sender,
+        );
+        let sse_data = deploy_processed_with(
+            &mut rng,
+            vec![event_transform([7; 32], 4, deposit(key(3), 12, None))],
+        );
+
+        contract_events.submit(&sse_data);
+
+        let payload = recv_derived_payload(&mut outbound_receiver, CONTRACT_EVENT_DERIVER).await;
+        assert_eq!(payload["event_name"], "Deposit");
+        assert_eq!(payload["fields"]["amount"], "12");
+        assert_eq!(payload["fields"]["memo"], serde_json::Value::Null);
+        let stored = database
+            .get_contract_events(CONTRACT_HASH, Some("Deposit"), None, 10)
+            .await
+            .expect("Contract event was not stored");
+        assert_eq!(stored.len(), 1);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::types::database::{
     DatabaseReadError, DatabaseReader, DatabaseWriteError, DatabaseWriter, Migration,
+    MigrationExecutor,
 };
 use anyhow::Error;
 use itertools::Itertools;
@@ -11,7 +12,7 @@ mod tests;
 /// [MigrationManager] executes given migrations against a *db*
 pub struct MigrationManager<T>
 where
-    T: DatabaseWriter + DatabaseReader + Send + Sync + 'static,
+    T: DatabaseWriter + DatabaseReader + MigrationExecutor + Send + Sync + 'static,
 {
     /// Database connection against which the migrations will be executed
     db: T,
@@ -19,7 +20,7 @@ where
 
 impl<T> MigrationManager<T>
 where
-    T: DatabaseWriter + DatabaseReader + Send + Sync + 'static,
+    T: DatabaseWriter + DatabaseReader + MigrationExecutor + Send + Sync + 'static,
 {
     /// Executes a collection of migrations. Does basic checks on the migration:
     /// * checks if they are uniquely numbered.
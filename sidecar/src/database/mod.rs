@@ -6,8 +6,9 @@ pub mod database_errors;
 pub mod env_vars;
 pub mod errors;
 pub mod migration_manager;
+pub mod pool_monitor;
+#[cfg(feature = "postgres")]
 pub mod postgresql_database;
+pub mod snapshot;
 pub mod sqlite_database;
-#[cfg(test)]
-pub mod tests;
 pub mod types;
@@ -0,0 +1,160 @@
+//! Periodic sampling of the utilization of a database connection pool.
+//!
+//! Every sample updates the `db_pool_*` metrics and, once the pool is saturated, marks the database
+//! as degraded in [Health] so that operators learn that the database is the bottleneck before
+//! requests start timing out.
+
+use crate::health::Health;
+use casper_event_types::metrics::{
+    DB_POOL_ACQUIRE_TIMES, DB_POOL_CONNECTIONS, DB_POOL_SATURATED, DB_POOL_TIMEOUTS,
+};
+use sqlx::{Database, Pool};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Name under which the pool is reported in [Health].
+pub const HEALTH_COMPONENT: &str = "database_pool";
+const SAMPLING_INTERVAL: Duration = Duration::from_secs(5);
+/// Acquiring a connection taking longer than this means requests are queuing up for the pool.
+const SLOW_ACQUIRE_THRESHOLD: Duration = Duration::from_millis(500);
+
+/// State of the pool at the time of a sample.
+#[derive(Debug, PartialEq, Eq)]
+struct PoolSample {
+    size: u32,
+    idle: u32,
+    max_connections: u32,
+    /// Time it took to acquire a connection, `None` if acquiring timed out.
+    acquire_time: Option<Duration>,
+}
+
+impl PoolSample {
+    fn active(&self) -> u32 {
+        self.size.saturating_sub(self.idle)
+    }
+
+    /// Describes why the pool is saturated, `None` if it is not.
+    fn saturation_reason(&self) -> Option<String> {
+        match self.acquire_time {
+            None => Some("acquiring a connection timed out".to_string()),
+            Some(acquire_time) if acquire_time > SLOW_ACQUIRE_THRESHOLD => Some(format!(
+                "acquiring a connection took {}ms",
+                acquire_time.as_millis()
+            )),
+            Some(_) if self.idle == 0 && self.size >= self.max_connections => Some(format!(
+                "all {} connections are in use",
+                self.max_connections
+            )),
+            Some(_) => None,
+        }
+    }
+}
+
+/// Spawns a task sampling `pool` periodically.
+pub fn start_pool_monitor<DB: Database>(pool: Pool<DB>, health: Health) {
+    tokio::spawn(async move {
+        let mut saturated = false;
+        loop {
+            let sample = sample_pool(&pool).await;
+            record_sample(&sample);
+            match (sample.saturation_reason(), saturated) {
+                (Some(reason), false) => {
+                    warn!("Database pool is saturated: {}", reason);
+                    health.set_degraded(HEALTH_COMPONENT, reason);
+                    saturated = true;
+                }
+                (Some(reason), true) => health.set_degraded(HEALTH_COMPONENT, reason),
+                (None, true) => {
+                    info!("Database pool is no longer saturated");
+                    health.clear(HEALTH_COMPONENT);
+                    saturated = false;
+                }
+                (None, false) => {}
+            }
+            tokio::time::sleep(SAMPLING_INTERVAL).await;
+        }
+    });
+}
+
+async fn sample_pool<DB: Database>(pool: &Pool<DB>) -> PoolSample {
+    // The pool is inspected before acquiring so that the probe itself is not counted as active.
+    let size = pool.size();
+    let idle = pool.num_idle() as u32;
+    let max_connections = pool.options().get_max_connections();
+    let started = Instant::now();
+    let acquire_time = match pool.acquire().await {
+        Ok(_connection) => Some(started.elapsed()),
+        Err(sqlx::Error::PoolTimedOut) => {
+            DB_POOL_TIMEOUTS.inc();
+            None
+        }
+        Err(error) => {
+            warn!(
+                ?error,
+                "Error acquiring a connection from the database pool"
+            );
+            Some(started.elapsed())
+        }
+    };
+    PoolSample {
+        size,
+        idle,
+        max_connections,
+        acquire_time,
+    }
+}
+
+fn record_sample(sample: &PoolSample) {
+    DB_POOL_CONNECTIONS
+        .with_label_values(&["active"])
+        .set(sample.active() as f64);
+    DB_POOL_CONNECTIONS
+        .with_label_values(&["idle"])
+        .set(sample.idle as f64);
+    DB_POOL_CONNECTIONS
+        .with_label_values(&["max"])
+        .set(sample.max_connections as f64);
+    if let Some(acquire_time) = sample.acquire_time {
+        DB_POOL_ACQUIRE_TIMES.observe(acquire_time.as_secs_f64() * 1000.0);
+    }
+    let saturated = if sample.saturation_reason().is_some() {
+        1.0
+    } else {
+        0.0
+    };
+    DB_POOL_SATURATED.set(saturated);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(size: u32, idle: u32, acquire_time: Option<Duration>) -> PoolSample {
+        PoolSample {
+            size,
+            idle,
+            max_connections: 10,
+            acquire_time,
+        }
+    }
+
+    #[test]
+    fn should_detect_saturation() {
+        let fast = Some(Duration::from_millis(1));
+        assert_eq!(sample(5, 0, fast).saturation_reason(), None);
+        assert_eq!(sample(10, 2, fast).saturation_reason(), None);
+        assert_eq!(sample(10, 2, fast).active(), 8);
+        assert_eq!(
+            sample(10, 0, fast).saturation_reason(),
+            Some("all 10 connections are in use".to_string())
+        );
+        assert_eq!(
+            sample(3, 3, Some(Duration::from_millis(800))).saturation_reason(),
+            Some("acquiring a connection took 800ms".to_string())
+        );
+        assert_eq!(
+            sample(10, 0, None).saturation_reason(),
+            Some("acquiring a connection timed out".to_string())
+        );
+    }
+}
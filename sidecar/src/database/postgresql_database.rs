@@ -1,8 +1,10 @@
+mod partitioning;
 mod reader;
 #[cfg(test)]
 mod tests;
 mod writer;
 use anyhow::Error;
+pub use partitioning::start_partition_maintenance;
 use sea_query::PostgresQueryBuilder;
 use sqlx::{
     postgres::{PgConnectOptions, PgPool, PgPoolOptions},
@@ -10,9 +12,15 @@ use sqlx::{
 };
 
 use crate::{
-    database::migration_manager::MigrationManager,
+    database::{
+        migration_manager::MigrationManager,
+        snapshot::{ReadConnection, SnapshotTransaction},
+    },
     sql::tables,
-    types::{config::PostgresqlConfig, database::DatabaseWriteError},
+    types::{
+        config::PostgresqlConfig,
+        database::{DatabaseReadError, DatabaseWriteError},
+    },
 };
 
 /// [PostgreSqlDatabase] can be cloned to allow multiple components access to the database.
@@ -20,6 +28,12 @@ use crate::{
 #[derive(Clone)]
 pub struct PostgreSqlDatabase {
     pub connection_pool: PgPool,
+    /// Whether the times at which deploys are accepted and processed are indexed.
+    pub index_deploy_timestamps: bool,
+    /// Whether the accounts touched by the processed deploys are indexed.
+    pub index_account_effects: bool,
+    /// The transaction the database reads from, if it is a read-only snapshot.
+    pub snapshot: Option<SnapshotTransaction<Postgres>>,
 }
 
 impl PostgreSqlDatabase {
@@ -29,7 +43,12 @@ impl PostgreSqlDatabase {
             .max_connections(30)
             .connect(uri.as_str())
             .await?;
-        let db = PostgreSqlDatabase { connection_pool };
+        let db = PostgreSqlDatabase {
+            connection_pool,
+            index_deploy_timestamps: true,
+            index_account_effects: true,
+            snapshot: None,
+        };
         MigrationManager::apply_all_migrations(db.clone()).await?;
         Ok(db)
     }
@@ -41,6 +60,8 @@ impl PostgreSqlDatabase {
         let database_password = config.database_password;
         let port = config.port;
         let max_connections = config.max_connections_in_pool;
+        let index_deploy_timestamps = config.index_deploy_timestamps;
+        let index_account_effects = config.index_account_effects;
 
         let db_connection_config = PgConnectOptions::new()
             .host(host.as_str())
@@ -54,7 +75,12 @@ impl PostgreSqlDatabase {
             .max_connections(max_connections)
             .connect_lazy_with(db_connection_config);
 
-        let db = PostgreSqlDatabase { connection_pool };
+        let db = PostgreSqlDatabase {
+            connection_pool,
+            index_deploy_timestamps,
+            index_account_effects,
+            snapshot: None,
+        };
 
         MigrationManager::apply_all_migrations(db.clone()).await?;
 
@@ -90,4 +116,20 @@ impl PostgreSqlDatabase {
     pub async fn get_transaction(&self) -> Result<Transaction<Postgres>, sqlx::Error> {
         self.connection_pool.begin().await
     }
+
+    async fn get_read_connection(&self) -> Result<ReadConnection<Postgres>, DatabaseReadError> {
+        ReadConnection::acquire(&self.connection_pool, &self.snapshot)
+            .await
+            .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+    }
+
+    /// Begins the transaction of a snapshot. Its repeatable read isolation makes every statement
+    /// read the database as it was at the first one.
+    async fn begin_snapshot(&self) -> Result<Transaction<'static, Postgres>, sqlx::Error> {
+        let mut transaction = self.connection_pool.begin().await?;
+        transaction
+            .execute("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+            .await?;
+        Ok(transaction)
+    }
 }
@@ -0,0 +1,116 @@
+use super::PostgreSqlDatabase;
+use crate::{
+    sql::partitioning::{self, MaintenancePlan, PartitionKey, PartitionRange, PartitionedTable},
+    types::{config::PartitioningConfig, database::DatabaseWriteError},
+};
+use anyhow::Error;
+use sea_query::PostgresQueryBuilder;
+use sqlx::{Executor, Row};
+use std::time::Duration;
+use tracing::{info, warn};
+
+impl PostgreSqlDatabase {
+    /// Creates the partitions for the upcoming eras and blocks of every partitioned table and, if
+    /// a retention is configured, drops the partitions holding the oldest data.
+    pub async fn maintain_partitions(
+        &self,
+        config: &PartitioningConfig,
+    ) -> Result<(), DatabaseWriteError> {
+        for table in PartitionedTable::ALL {
+            let plan = self.maintain_partitions_of(table, config).await?;
+            for range in &plan.to_create {
+                info!("Created partition {}", range.partition_name(table));
+            }
+            for range in &plan.to_drop {
+                info!("Dropped partition {}", range.partition_name(table));
+            }
+        }
+        Ok(())
+    }
+
+    async fn maintain_partitions_of(
+        &self,
+        table: PartitionedTable,
+        config: &PartitioningConfig,
+    ) -> Result<MaintenancePlan, DatabaseWriteError> {
+        let db_connection = &self.connection_pool;
+        let list_partitions_stmt =
+            partitioning::create_list_partitions_stmt(table).to_string(PostgresQueryBuilder);
+        let mut existing = Vec::new();
+        for row in db_connection
+            .fetch_all(list_partitions_stmt.as_str())
+            .await?
+        {
+            let name = row.try_get::<String, usize>(0)?;
+            if let Some(range) = PartitionRange::from_partition_name(table, &name) {
+                existing.push(range);
+            }
+        }
+        existing.sort_unstable();
+        let max_key = self.get_max_key(table, false).await?;
+        let max_key_in_default = self.get_max_key(table, true).await?;
+        let partition_width = match table.partition_key() {
+            PartitionKey::Height => config.blocks_per_partition(),
+            PartitionKey::Era => config.eras_per_partition(),
+        };
+
+        let plan = partitioning::plan_maintenance(
+            &existing,
+            max_key,
+            max_key_in_default,
+            partition_width,
+            config.partitions_ahead(),
+            config.retained_partitions,
+        );
+        for range in &plan.to_create {
+            let create_partition_stmt = partitioning::create_partition_stmt(table, range);
+            db_connection
+                .execute(create_partition_stmt.as_str())
+                .await?;
+        }
+        for range in &plan.to_drop {
+            let drop_partition_stmt = partitioning::create_drop_partition_stmt(table, range)
+                .to_string(PostgresQueryBuilder);
+            db_connection.execute(drop_partition_stmt.as_str()).await?;
+        }
+        Ok(plan)
+    }
+
+    async fn get_max_key(
+        &self,
+        table: PartitionedTable,
+        only_default_partition: bool,
+    ) -> Result<Option<u64>, DatabaseWriteError> {
+        let stmt = partitioning::create_get_max_key_stmt(table, only_default_partition)
+            .to_string(PostgresQueryBuilder);
+        let maybe_max_key = self
+            .connection_pool
+            .fetch_one(stmt.as_str())
+            .await?
+            .try_get::<Option<String>, usize>(0)?;
+        maybe_max_key
+            .map(|max_key| {
+                max_key.parse::<u64>().map_err(|err| {
+                    DatabaseWriteError::Unhandled(Error::msg(format!(
+                        "Error parsing the highest key of {}: {:?}",
+                        table.table_name(),
+                        err
+                    )))
+                })
+            })
+            .transpose()
+    }
+}
+
+/// Spawns a task running the partition maintenance right away and then periodically.
+pub fn start_partition_maintenance(database: PostgreSqlDatabase, config: PartitioningConfig) {
+    let interval = Duration::from_secs(config.maintenance_interval_in_seconds());
+    tokio::spawn(async move {
+        loop {
+            if let Err(error) = database.maintain_partitions(&config).await {
+                warn!(?error, "Error maintaining partitions");
+            }
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
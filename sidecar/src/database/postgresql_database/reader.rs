@@ -1,10 +1,10 @@
 use super::PostgreSqlDatabase;
 use crate::database_reader_implementation;
 use sea_query::PostgresQueryBuilder;
-use sqlx::{postgres::PgRow, PgPool};
+use sqlx::{postgres::PgRow, PgConnection};
 
 async fn fetch_optional_with_error_check(
-    connection: &PgPool,
+    connection: &mut PgConnection,
     stmt: String,
 ) -> Result<PgRow, DatabaseReadError> {
     connection
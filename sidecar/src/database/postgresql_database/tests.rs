@@ -1,11 +1,17 @@
+use super::PostgreSqlDatabase;
 use crate::{
     sql::tables::{self, event_type::EventTypeId},
-    types::{database::DatabaseWriter, sse_events::*},
+    types::{
+        config::PartitioningConfig,
+        database::{DatabaseReadError, DatabaseReader, DatabaseWriter},
+        sse_events::*,
+    },
     utils::tests::build_postgres_database,
 };
-use casper_types::testing::TestRng;
+use casper_types::{testing::TestRng, EraId};
 use sea_query::{Asterisk, Expr, PostgresQueryBuilder, Query, SqliteQueryBuilder};
 use sqlx::Row;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 #[tokio::test]
 async fn should_save_and_retrieve_a_u32max_id() {
@@ -33,144 +39,465 @@ async fn should_save_and_retrieve_a_u32max_id() {
 #[tokio::test]
 async fn should_save_and_retrieve_block_added() {
     let test_context = build_postgres_database().await.unwrap();
-    crate::database::tests::should_save_and_retrieve_block_added(test_context.db.clone()).await;
+    casper_event_storage::conformance::checks::should_save_and_retrieve_block_added(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_exclude_blocks_failing_integrity() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_exclude_blocks_failing_integrity(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_retrieve_blocks_by_height_range() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_retrieve_blocks_by_height_range(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_retrieve_deploys_by_timestamp_range() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_retrieve_deploys_by_timestamp_range(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_retrieve_accepted_deploys_by_timestamp_range() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_retrieve_accepted_deploys_by_timestamp_range(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_retrieve_deploys_by_error_message() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_retrieve_deploys_by_error_message(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_retrieve_deploys_by_account() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_retrieve_deploys_by_account(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_retrieve_page_of_deploys_by_account() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_retrieve_page_of_deploys_by_account(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_retrieve_deploys_by_account_processed_by() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_retrieve_deploys_by_account_processed_by(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_save_approvals_of_later_acceptances() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_save_approvals_of_later_acceptances(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_retrieve_deploy_event_times() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_retrieve_deploy_event_times(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_retrieve_deploys_affecting_account() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_retrieve_deploys_affecting_account(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_retrieve_native_transfers() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_retrieve_native_transfers(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_retrieve_native_transfers_by_transfer_id() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_retrieve_native_transfers_by_transfer_id(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_retrieve_token_transfers() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_retrieve_token_transfers(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_clear_derived_table() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_clear_derived_table(test_context.db.clone())
+        .await;
+}
+
+#[tokio::test]
+async fn should_retrieve_nft_events() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_retrieve_nft_events(test_context.db.clone())
+        .await;
+}
+
+#[tokio::test]
+async fn should_save_network_identity_once() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_save_network_identity_once(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_retrieve_contract_events() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_retrieve_contract_events(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_replace_contract_event_schemas() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_replace_contract_event_schemas(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_roll_up_contract_stats() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_roll_up_contract_stats(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_compute_deploy_size_stats() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_compute_deploy_size_stats(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_save_and_retrieve_derived_events() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_save_and_retrieve_derived_events(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_save_and_retrieve_webhook_delivery_attempts() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_save_and_retrieve_webhook_delivery_attempts(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_save_and_delete_api_keys() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_save_and_delete_api_keys(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_sample_latest_raw_payloads() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_sample_latest_raw_payloads(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_retrieve_era_validators_of_switch_blocks() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_retrieve_era_validators_of_switch_blocks(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_retrieve_raw_payloads_after_event_log_id() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_retrieve_raw_payloads_after_event_log_id(
+        test_context.db.clone(),
+    )
+    .await;
 }
 
 #[tokio::test]
 async fn should_save_and_retrieve_deploy_accepted() {
     let test_context = build_postgres_database().await.unwrap();
-    crate::database::tests::should_save_and_retrieve_deploy_accepted(test_context.db.clone()).await;
+    casper_event_storage::conformance::checks::should_save_and_retrieve_deploy_accepted(
+        test_context.db.clone(),
+    )
+    .await;
 }
 
 #[tokio::test]
 async fn should_save_and_retrieve_deploy_processed() {
     let test_context = build_postgres_database().await.unwrap();
-    crate::database::tests::should_save_and_retrieve_deploy_processed(test_context.db.clone())
-        .await;
+    casper_event_storage::conformance::checks::should_save_and_retrieve_deploy_processed(
+        test_context.db.clone(),
+    )
+    .await;
 }
 
 #[tokio::test]
 async fn should_save_and_retrieve_deploy_expired() {
     let test_context = build_postgres_database().await.unwrap();
-    crate::database::tests::should_save_and_retrieve_deploy_expired(test_context.db.clone()).await;
+    casper_event_storage::conformance::checks::should_save_and_retrieve_deploy_expired(
+        test_context.db.clone(),
+    )
+    .await;
 }
 
 #[tokio::test]
 async fn should_retrieve_deploy_aggregate_of_accepted() {
     let test_context = build_postgres_database().await.unwrap();
-    crate::database::tests::should_retrieve_deploy_aggregate_of_accepted(test_context.db.clone())
-        .await;
+    casper_event_storage::conformance::checks::should_retrieve_deploy_aggregate_of_accepted(
+        test_context.db.clone(),
+    )
+    .await;
 }
 
 #[tokio::test]
 async fn should_retrieve_deploy_aggregate_of_processed() {
     let test_context = build_postgres_database().await.unwrap();
-    crate::database::tests::should_retrieve_deploy_aggregate_of_processed(test_context.db.clone())
-        .await;
+    casper_event_storage::conformance::checks::should_retrieve_deploy_aggregate_of_processed(
+        test_context.db.clone(),
+    )
+    .await;
 }
 
 #[tokio::test]
 async fn should_retrieve_deploy_aggregate_of_expired() {
     let test_context = build_postgres_database().await.unwrap();
-    crate::database::tests::should_retrieve_deploy_aggregate_of_expired(test_context.db.clone())
-        .await;
+    casper_event_storage::conformance::checks::should_retrieve_deploy_aggregate_of_expired(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_read_a_snapshot_unaffected_by_later_writes() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_read_a_snapshot_unaffected_by_later_writes(
+        test_context.db.clone(),
+    )
+    .await;
 }
 
 #[tokio::test]
 async fn should_save_and_retrieve_fault() {
     let test_context = build_postgres_database().await.unwrap();
-    crate::database::tests::should_save_and_retrieve_fault(test_context.db.clone()).await;
+    casper_event_storage::conformance::checks::should_save_and_retrieve_fault(
+        test_context.db.clone(),
+    )
+    .await;
 }
 
 #[tokio::test]
 async fn should_save_and_retrieve_fault_with_a_u64max() {
     let test_context = build_postgres_database().await.unwrap();
-    crate::database::tests::should_save_and_retrieve_fault_with_a_u64max(test_context.db.clone())
-        .await;
+    casper_event_storage::conformance::checks::should_save_and_retrieve_fault_with_a_u64max(
+        test_context.db.clone(),
+    )
+    .await;
 }
 
 #[tokio::test]
 async fn should_save_and_retrieve_finality_signature() {
     let test_context = build_postgres_database().await.unwrap();
-    crate::database::tests::should_save_and_retrieve_finality_signature(test_context.db.clone())
-        .await;
+    casper_event_storage::conformance::checks::should_save_and_retrieve_finality_signature(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_exclude_invalid_finality_signatures() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_exclude_invalid_finality_signatures(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_checkpoint_finality_signatures() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_checkpoint_finality_signatures(
+        test_context.db.clone(),
+    )
+    .await;
 }
 
 #[tokio::test]
 async fn should_save_and_retrieve_step() {
     let test_context = build_postgres_database().await.unwrap();
-    crate::database::tests::should_save_and_retrieve_step(test_context.db.clone()).await;
+    casper_event_storage::conformance::checks::should_save_and_retrieve_step(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_save_and_retrieve_unknown_events() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_save_and_retrieve_unknown_events(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_save_and_retrieve_enrichments() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_save_and_retrieve_enrichments(
+        test_context.db.clone(),
+    )
+    .await;
 }
 
 #[tokio::test]
 async fn should_save_and_retrieve_a_step_with_u64_max_era() {
     let test_context = build_postgres_database().await.unwrap();
-    crate::database::tests::should_save_and_retrieve_a_step_with_u64_max_era(
+    casper_event_storage::conformance::checks::should_save_and_retrieve_a_step_with_u64_max_era(
         test_context.db.clone(),
     )
     .await;
 }
 
 #[tokio::test]
-async fn should_disallow_duplicate_event_id_from_source() {
+async fn should_ignore_duplicate_event_id_from_source() {
     let test_context = build_postgres_database().await.unwrap();
-    crate::database::tests::should_disallow_duplicate_event_id_from_source(test_context.db.clone())
-        .await;
+    casper_event_storage::conformance::checks::should_ignore_duplicate_event_id_from_source(
+        test_context.db.clone(),
+    )
+    .await;
 }
 
 #[tokio::test]
-async fn should_disallow_insert_of_existing_block_added() {
+async fn should_ignore_insert_of_existing_block_added() {
     let test_context = build_postgres_database().await.unwrap();
-    crate::database::tests::should_disallow_insert_of_existing_block_added(test_context.db.clone())
-        .await;
+    casper_event_storage::conformance::checks::should_ignore_insert_of_existing_block_added(
+        test_context.db.clone(),
+    )
+    .await;
 }
 
 #[tokio::test]
-async fn should_disallow_insert_of_existing_deploy_accepted() {
+async fn should_ignore_insert_of_existing_deploy_accepted() {
     let test_context = build_postgres_database().await.unwrap();
-    crate::database::tests::should_disallow_insert_of_existing_deploy_accepted(
+    casper_event_storage::conformance::checks::should_ignore_insert_of_existing_deploy_accepted(
         test_context.db.clone(),
     )
     .await;
 }
 
 #[tokio::test]
-async fn should_disallow_insert_of_existing_deploy_expired() {
+async fn should_ignore_insert_of_existing_deploy_expired() {
     let test_context = build_postgres_database().await.unwrap();
-    crate::database::tests::should_disallow_insert_of_existing_deploy_expired(
+    casper_event_storage::conformance::checks::should_ignore_insert_of_existing_deploy_expired(
         test_context.db.clone(),
     )
     .await;
 }
 
 #[tokio::test]
-async fn should_disallow_insert_of_existing_deploy_processed() {
+async fn should_ignore_insert_of_existing_deploy_processed() {
     let test_context = build_postgres_database().await.unwrap();
-    crate::database::tests::should_disallow_insert_of_existing_deploy_processed(
+    casper_event_storage::conformance::checks::should_ignore_insert_of_existing_deploy_processed(
         test_context.db.clone(),
     )
     .await;
 }
 
 #[tokio::test]
-async fn should_disallow_insert_of_existing_fault() {
+async fn should_ignore_insert_of_existing_fault() {
     let test_context = build_postgres_database().await.unwrap();
-    crate::database::tests::should_disallow_insert_of_existing_fault(test_context.db.clone()).await;
+    casper_event_storage::conformance::checks::should_ignore_insert_of_existing_fault(
+        test_context.db.clone(),
+    )
+    .await;
 }
 
 #[tokio::test]
-async fn should_disallow_insert_of_existing_finality_signature() {
+async fn should_ignore_insert_of_existing_finality_signature() {
     let test_context = build_postgres_database().await.unwrap();
-    crate::database::tests::should_disallow_insert_of_existing_finality_signature(
+    casper_event_storage::conformance::checks::should_ignore_insert_of_existing_finality_signature(
         test_context.db.clone(),
     )
     .await;
 }
 
 #[tokio::test]
-async fn should_disallow_insert_of_existing_step() {
+async fn should_ignore_insert_of_existing_step() {
     let test_context = build_postgres_database().await.unwrap();
-    crate::database::tests::should_disallow_insert_of_existing_step(test_context.db.clone()).await;
+    casper_event_storage::conformance::checks::should_ignore_insert_of_existing_step(
+        test_context.db.clone(),
+    )
+    .await;
 }
 
 #[tokio::test]
@@ -397,14 +724,110 @@ async fn should_save_and_retrieve_a_shutdown() {
 #[tokio::test]
 async fn get_number_of_events_should_return_0() {
     let test_context = build_postgres_database().await.unwrap();
-    crate::database::tests::get_number_of_events_should_return_0(test_context.db.clone()).await;
+    casper_event_storage::conformance::checks::get_number_of_events_should_return_0(
+        test_context.db.clone(),
+    )
+    .await;
 }
 
 #[tokio::test]
 async fn get_number_of_events_should_return_1_when_event_stored() {
     let test_context = build_postgres_database().await.unwrap();
-    crate::database::tests::get_number_of_events_should_return_1_when_event_stored(
+    casper_event_storage::conformance::checks::get_number_of_events_should_return_1_when_event_stored(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_save_and_retrieve_audit_entries() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_save_and_retrieve_audit_entries(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_save_and_retrieve_operational_events() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_save_and_retrieve_operational_events(
         test_context.db.clone(),
     )
     .await;
 }
+
+#[tokio::test]
+async fn should_prune_events_beyond_max_rows_and_age() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_prune_events_beyond_max_rows_and_age(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_reserve_event_ids_without_gaps() {
+    let test_context = build_postgres_database().await.unwrap();
+    casper_event_storage::conformance::checks::should_reserve_event_ids_without_gaps(
+        test_context.db.clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_create_and_drop_era_partitions() {
+    let test_context = build_postgres_database().await.unwrap();
+    let db = &test_context.db;
+    let mut test_rng = TestRng::new();
+    let mut config = PartitioningConfig {
+        eras_per_partition: Some(10),
+        partitions_ahead: Some(1),
+        ..Default::default()
+    };
+    db.maintain_partitions(&config).await.unwrap();
+    for (event_id, era) in [(1, 5), (2, 15)] {
+        let mut step = Step::random(&mut test_rng);
+        step.era_id = EraId::new(era);
+        db.save_step(step, event_id, "127.0.0.1".to_string())
+            .await
+            .unwrap();
+    }
+    let count_partition_rows = |partition: &'static str| async move {
+        db.fetch_one(&format!("SELECT COUNT(*) FROM \"{}\"", partition))
+            .await
+            .try_get::<i64, usize>(0)
+            .unwrap()
+    };
+    assert_eq!(count_partition_rows("Step_0_10").await, 1);
+    assert_eq!(count_partition_rows("Step_10_20").await, 1);
+    assert_eq!(count_partition_rows("Step_default").await, 0);
+
+    config.retained_partitions = Some(1);
+    db.maintain_partitions(&config).await.unwrap();
+
+    assert_eq!(count_partition_rows("Step_20_30").await, 0);
+    assert!(matches!(
+        db.get_step_by_era(5).await,
+        Err(DatabaseReadError::NotFound)
+    ));
+    assert_eq!(db.get_step_by_era(15).await.unwrap().era_id.value(), 15);
+}
+
+#[tokio::test]
+async fn should_pass_storage_conformance_suite() {
+    let test_context = build_postgres_database().await.unwrap();
+    let pg = &test_context.pg;
+    let database_counter = &AtomicUsize::new(0);
+    casper_event_storage::conformance::run_conformance_suite(move || async move {
+        let database_name = format!(
+            "conformance_{}",
+            database_counter.fetch_add(1, Ordering::SeqCst)
+        );
+        pg.create_database(&database_name).await.unwrap();
+        PostgreSqlDatabase::new_from_postgres_uri(pg.full_db_uri(&database_name))
+            .await
+            .unwrap()
+    })
+    .await;
+}
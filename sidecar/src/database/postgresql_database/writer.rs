@@ -12,6 +12,7 @@ database_writer_implementation!(
     DDLConfiguration {
         is_big_integer_id: true,
         db_supports_unsigned: false,
+        supports_partitioning: true,
     }
 );
 
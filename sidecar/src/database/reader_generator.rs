@@ -8,23 +8,51 @@ macro_rules! database_reader_implementation {
         use casper_event_types::FinalitySignature as FinSig;
         use serde::Deserialize;
         use sqlx::{Executor, Row};
+        use std::sync::Arc;
+        use tokio::sync::Mutex;
         use $crate::{
             database::errors::{wrap_query_error, DbError},
             sql::tables,
             types::{
-                database::{DatabaseReadError, DatabaseReader, DeployAggregate},
+                config::ApiKeyScope,
+                database::{
+                    AccountEffectEntry, AuditEntry, ContractDayStats, ContractEventEntry,
+                    ContractEventSchemas, DatabaseReadError, DatabaseReader, DeployAccountEntry,
+                    DeployAggregate, DeployApprovalEntry, DeployErrorEntry, DeployEventTime,
+                    DeploySizeStats, DeployTimestampEntry, DeployTimestampKind, DerivedEvent,
+                    Distribution, Enrichment, EraValidatorWeight, EventIdAllocatorState,
+                    FinalitySignatureCheckpoint, NetworkIdentity, NftEventEntry, OperationalEvent,
+                    PayloadTable, RawPayloadEntry, StoredApiKey, TokenTransferEntry, TransferEntry,
+                    WebhookDeliveryAttempt,
+                },
                 sse_events::*,
             },
         };
 
         #[async_trait]
         impl DatabaseReader for $extended_type {
+            type Snapshot = Self;
+
+            async fn snapshot(&self) -> Result<Self, DatabaseReadError> {
+                if self.snapshot.is_some() {
+                    return Ok(self.clone());
+                }
+                let transaction = self
+                    .begin_snapshot()
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))?;
+                Ok(Self {
+                    snapshot: Some(Arc::new(Mutex::new(transaction))),
+                    ..self.clone()
+                })
+            }
+
             async fn get_latest_block(&self) -> Result<BlockAdded, DatabaseReadError> {
-                let db_connection = &self.connection_pool;
+                let mut db_connection = self.get_read_connection().await?;
 
                 let stmt = tables::block_added::create_get_latest_stmt()
                     .to_string($query_materializer_expr);
-                let row = fetch_optional_with_error_check(db_connection, stmt).await?;
+                let row = fetch_optional_with_error_check(&mut db_connection, stmt).await?;
 
                 parse_block_from_row(row)
             }
@@ -33,18 +61,18 @@ macro_rules! database_reader_implementation {
                 &self,
                 height: u64,
             ) -> Result<BlockAdded, DatabaseReadError> {
-                let db_connection = &self.connection_pool;
+                let mut db_connection = self.get_read_connection().await?;
 
                 let stmt = tables::block_added::create_get_by_height_stmt(height)
                     .to_string($query_materializer_expr);
 
-                let row = fetch_optional_with_error_check(db_connection, stmt).await?;
+                let row = fetch_optional_with_error_check(&mut db_connection, stmt).await?;
 
                 parse_block_from_row(row)
             }
 
             async fn get_block_by_hash(&self, hash: &str) -> Result<BlockAdded, DatabaseReadError> {
-                let db_connection = &self.connection_pool;
+                let mut db_connection = self.get_read_connection().await?;
 
                 let stmt = tables::block_added::create_get_by_hash_stmt(hash.to_string())
                     .to_string($query_materializer_expr);
@@ -59,15 +87,38 @@ macro_rules! database_reader_implementation {
                     })
             }
 
+            async fn get_blocks_by_height_range(
+                &self,
+                from: u64,
+                to: u64,
+                limit: u32,
+            ) -> Result<Vec<BlockAdded>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt = tables::block_added::create_get_by_height_range_stmt(from, to, limit)
+                    .to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))?
+                    .into_iter()
+                    .map(parse_block_from_row)
+                    .collect()
+            }
+
             async fn get_deploy_aggregate_by_hash(
                 &self,
                 hash: &str,
             ) -> Result<DeployAggregate, DatabaseReadError> {
+                // The events of the deploy are read from a snapshot, so that a deploy processed or
+                // expired in the meantime isn't returned without its outcome.
+                let snapshot = self.snapshot().await?;
                 // We may return here with NotFound because if there's no accepted record then theoretically there should be no other records for the given hash.
-                let deploy_accepted = self.get_deploy_accepted_by_hash(hash).await?;
+                let deploy_accepted = snapshot.get_deploy_accepted_by_hash(hash).await?;
 
                 // However we handle the Err case for DeployProcessed explicitly as we don't want to return NotFound when we've got a DeployAccepted to return
-                match self.get_deploy_processed_by_hash(hash).await {
+                match snapshot.get_deploy_processed_by_hash(hash).await {
                     Ok(deploy_processed) => Ok(DeployAggregate {
                         deploy_hash: hash.to_string(),
                         deploy_accepted: Some(deploy_accepted),
@@ -79,7 +130,7 @@ macro_rules! database_reader_implementation {
                         if !matches!(DatabaseReadError::NotFound, _err) {
                             return Err(err);
                         }
-                        match self.get_deploy_expired_by_hash(hash).await {
+                        match snapshot.get_deploy_expired_by_hash(hash).await {
                             Ok(_) => Ok(DeployAggregate {
                                 deploy_hash: hash.to_string(),
                                 deploy_accepted: Some(deploy_accepted),
@@ -107,7 +158,7 @@ macro_rules! database_reader_implementation {
                 &self,
                 hash: &str,
             ) -> Result<DeployAccepted, DatabaseReadError> {
-                let db_connection = &self.connection_pool;
+                let mut db_connection = self.get_read_connection().await?;
 
                 let stmt = tables::deploy_accepted::create_get_by_hash_stmt(hash.to_string())
                     .to_string($query_materializer_expr);
@@ -131,7 +182,7 @@ macro_rules! database_reader_implementation {
                 &self,
                 hash: &str,
             ) -> Result<DeployProcessed, DatabaseReadError> {
-                let db_connection = &self.connection_pool;
+                let mut db_connection = self.get_read_connection().await?;
 
                 let stmt = tables::deploy_processed::create_get_by_hash_stmt(hash.to_string())
                     .to_string($query_materializer_expr);
@@ -155,7 +206,7 @@ macro_rules! database_reader_implementation {
                 &self,
                 hash: &str,
             ) -> Result<DeployExpired, DatabaseReadError> {
-                let db_connection = &self.connection_pool;
+                let mut db_connection = self.get_read_connection().await?;
 
                 let stmt = tables::deploy_expired::create_get_by_hash_stmt(hash.to_string())
                     .to_string($query_materializer_expr);
@@ -179,7 +230,7 @@ macro_rules! database_reader_implementation {
                 &self,
                 public_key: &str,
             ) -> Result<Vec<Fault>, DatabaseReadError> {
-                let db_connection = &self.connection_pool;
+                let mut db_connection = self.get_read_connection().await?;
 
                 let stmt =
                     tables::fault::create_get_faults_by_public_key_stmt(public_key.to_string())
@@ -193,7 +244,7 @@ macro_rules! database_reader_implementation {
             }
 
             async fn get_faults_by_era(&self, era: u64) -> Result<Vec<Fault>, DatabaseReadError> {
-                let db_connection = &self.connection_pool;
+                let mut db_connection = self.get_read_connection().await?;
 
                 let stmt = tables::fault::create_get_faults_by_era_stmt(era)
                     .to_string($query_materializer_expr);
@@ -209,7 +260,7 @@ macro_rules! database_reader_implementation {
                 &self,
                 block_hash: &str,
             ) -> Result<Vec<FinSig>, DatabaseReadError> {
-                let db_connection = &self.connection_pool;
+                let mut db_connection = self.get_read_connection().await?;
 
                 let stmt =
                     tables::finality_signature::create_get_finality_signatures_by_block_stmt(
@@ -224,8 +275,76 @@ macro_rules! database_reader_implementation {
                     .and_then(parse_finality_signatures_from_rows)
             }
 
+            async fn get_finality_signature_checkpoint(
+                &self,
+                block_hash: &str,
+            ) -> Result<FinalitySignatureCheckpoint, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt = tables::finality_signature_checkpoint::create_get_by_block_stmt(
+                    block_hash.to_string(),
+                )
+                .to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_optional(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+                    .and_then(|maybe_row| match maybe_row {
+                        None => Err(DatabaseReadError::NotFound),
+                        Some(row) => {
+                            let raw = row
+                                .try_get::<String, &str>("raw")
+                                .map_err(|sqlx_error| wrap_query_error(sqlx_error.into()))?;
+                            deserialize_data::<FinalitySignatureCheckpoint>(&raw)
+                                .map_err(wrap_query_error)
+                        }
+                    })
+            }
+
+            async fn get_blocks_to_checkpoint(
+                &self,
+                stored_before: u64,
+                after: Option<String>,
+                limit: u32,
+            ) -> Result<Vec<String>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt = tables::event_log::create_get_last_inserted_before_stmt(
+                    &tables::event_log::timestamp_literal(stored_before),
+                )
+                .to_string($query_materializer_expr);
+                let maybe_event_log_id = db_connection
+                    .fetch_one(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))?
+                    .try_get::<Option<i64>, usize>(0)
+                    .map_err(|sqlx_error| wrap_query_error(sqlx_error.into()))?;
+                let event_log_id = match maybe_event_log_id {
+                    Some(event_log_id) => event_log_id as u64,
+                    None => return Ok(Vec::new()),
+                };
+
+                let stmt = tables::finality_signature::create_get_blocks_signed_up_to_stmt(
+                    event_log_id,
+                    after,
+                    limit,
+                )
+                .to_string($query_materializer_expr);
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))?
+                    .into_iter()
+                    .map(|row| {
+                        row.try_get::<String, &str>("block_hash")
+                            .map_err(|sqlx_error| wrap_query_error(sqlx_error.into()))
+                    })
+                    .collect()
+            }
+
             async fn get_step_by_era(&self, era: u64) -> Result<Step, DatabaseReadError> {
-                let db_connection = &self.connection_pool;
+                let mut db_connection = self.get_read_connection().await?;
 
                 let stmt =
                     tables::step::create_get_by_era_stmt(era).to_string($query_materializer_expr);
@@ -245,8 +364,87 @@ macro_rules! database_reader_implementation {
                     })
             }
 
+            async fn get_unknown_events_by_type(
+                &self,
+                event_type: &str,
+            ) -> Result<Vec<UnknownEvent>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt =
+                    tables::unknown_event::create_get_by_event_type_stmt(event_type.to_string())
+                        .to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+                    .and_then(parse_unknown_events_from_rows)
+            }
+
+            async fn get_enrichments_by_hash(
+                &self,
+                entity_hash: &str,
+            ) -> Result<Vec<Enrichment>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt =
+                    tables::enrichment::create_get_by_entity_hash_stmt(entity_hash.to_string())
+                        .to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+                    .and_then(parse_enrichments_from_rows)
+            }
+
+            async fn get_derived_events_by_deriver(
+                &self,
+                deriver: &str,
+            ) -> Result<Vec<DerivedEvent>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt = tables::derived_event::create_get_by_deriver_stmt(deriver.to_string())
+                    .to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+                    .and_then(parse_derived_events_from_rows)
+            }
+
+            async fn get_webhook_delivery_attempts(
+                &self,
+                webhook: &str,
+            ) -> Result<Vec<WebhookDeliveryAttempt>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt = tables::webhook_delivery::create_get_by_webhook_stmt(webhook)
+                    .to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+                    .and_then(parse_webhook_delivery_attempts_from_rows)
+            }
+
+            async fn get_api_keys(&self) -> Result<Vec<StoredApiKey>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt =
+                    tables::api_key::create_get_all_stmt().to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+                    .and_then(parse_api_keys_from_rows)
+            }
+
             async fn get_number_of_events(&self) -> Result<u64, DatabaseReadError> {
-                let db_connection = &self.connection_pool;
+                let mut db_connection = self.get_read_connection().await?;
 
                 let stmt = tables::event_log::count().to_string($query_materializer_expr);
 
@@ -264,7 +462,7 @@ macro_rules! database_reader_implementation {
             async fn get_newest_migration_version(
                 &self,
             ) -> Result<Option<(u32, bool)>, DatabaseReadError> {
-                let db_connection = &self.connection_pool;
+                let mut db_connection = self.get_read_connection().await?;
 
                 let stmt = tables::migration::create_get_newest_migration_stmt()
                     .to_string($query_materializer_expr);
@@ -275,6 +473,519 @@ macro_rules! database_reader_implementation {
                     .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
                     .and_then(parse_migration_row)
             }
+
+            async fn get_audit_entries(
+                &self,
+                from_id: u64,
+                limit: u32,
+            ) -> Result<Vec<AuditEntry>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt = tables::audit_log::create_list_stmt(from_id, limit)
+                    .to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+                    .and_then(parse_audit_entries_from_rows)
+            }
+
+            async fn get_operational_events(
+                &self,
+                from_id: u64,
+                limit: u32,
+            ) -> Result<Vec<OperationalEvent>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt = tables::operational_event::create_list_stmt(from_id, limit)
+                    .to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+                    .and_then(parse_operational_events_from_rows)
+            }
+
+            async fn get_event_id_allocator_state(
+                &self,
+            ) -> Result<EventIdAllocatorState, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt = tables::event_id_allocator::create_get_stmt()
+                    .to_string($query_materializer_expr);
+                let row = fetch_optional_with_error_check(&mut db_connection, stmt).await?;
+
+                Ok(EventIdAllocatorState {
+                    next_event_id: row
+                        .try_get::<i64, &str>("next_event_id")
+                        .map_err(|err| wrap_query_error(err.into()))?
+                        as u32,
+                    updated_timestamp: row
+                        .try_get::<i64, &str>("updated_timestamp")
+                        .map_err(|err| wrap_query_error(err.into()))?
+                        as u64,
+                })
+            }
+
+            async fn get_network_identity(&self) -> Result<NetworkIdentity, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt =
+                    tables::network_identity::create_get_stmt().to_string($query_materializer_expr);
+                let row = fetch_optional_with_error_check(&mut db_connection, stmt).await?;
+
+                Ok(NetworkIdentity {
+                    chainspec_name: row
+                        .try_get::<String, &str>("chainspec_name")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                    starting_state_root_hash: row
+                        .try_get::<String, &str>("starting_state_root_hash")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                })
+            }
+
+            async fn get_deploy_timestamps(
+                &self,
+                from: u64,
+                to: u64,
+                after: Option<DeployTimestampEntry>,
+                limit: u32,
+            ) -> Result<Vec<DeployTimestampEntry>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let after = after.map(|entry| {
+                    (
+                        entry.timestamp,
+                        entry.deploy_hash,
+                        entry.kind.event_type_id(),
+                    )
+                });
+                let stmt = tables::deploy_timestamp::create_get_by_timestamp_range_stmt(
+                    from, to, after, limit,
+                )
+                .to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+                    .and_then(parse_deploy_timestamps_from_rows)
+            }
+
+            async fn get_deploys_by_error_message(
+                &self,
+                contains: &str,
+                after: Option<String>,
+                limit: u32,
+            ) -> Result<Vec<DeployErrorEntry>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt =
+                    tables::deploy_error::create_get_by_error_message_stmt(contains, after, limit)
+                        .to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+                    .and_then(parse_deploy_errors_from_rows)
+            }
+
+            async fn get_deploys_by_account(
+                &self,
+                account_hash: &str,
+                processed_by: Option<u64>,
+                after: Option<String>,
+                limit: u32,
+            ) -> Result<Vec<DeployAccountEntry>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt = match processed_by {
+                    Some(processed_by) => {
+                        tables::deploy_account::create_get_processed_by_account_stmt(
+                            account_hash,
+                            processed_by,
+                            after,
+                            limit,
+                        )
+                    }
+                    None => tables::deploy_account::create_get_by_account_stmt(
+                        account_hash,
+                        after,
+                        limit,
+                    ),
+                }
+                .to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+                    .and_then(parse_deploy_accounts_from_rows)
+            }
+
+            async fn get_deploys_by_account_page(
+                &self,
+                account_hash: &str,
+                processed_by: Option<u64>,
+                offset: u32,
+                limit: u32,
+            ) -> Result<Vec<DeployAccountEntry>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt = tables::deploy_account::create_get_page_by_account_stmt(
+                    account_hash,
+                    processed_by,
+                    offset,
+                    limit,
+                )
+                .to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+                    .and_then(parse_deploy_accounts_from_rows)
+            }
+
+            async fn get_deploy_approvals(
+                &self,
+                hash: &str,
+            ) -> Result<Vec<DeployApprovalEntry>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt =
+                    tables::deploy_approval::create_get_by_deploy_hash_stmt(hash.to_string())
+                        .to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+                    .and_then(parse_deploy_approvals_from_rows)
+            }
+
+            async fn get_deploy_event_times(
+                &self,
+                hash: &str,
+            ) -> Result<Vec<DeployEventTime>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt =
+                    tables::deploy_event::create_get_times_by_deploy_hash_stmt(hash.to_string())
+                        .to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+                    .and_then(parse_deploy_event_times_from_rows)
+            }
+
+            async fn get_deploys_affecting_account(
+                &self,
+                account_hash: &str,
+                after: Option<String>,
+                limit: u32,
+            ) -> Result<Vec<AccountEffectEntry>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt =
+                    tables::account_effect::create_get_by_account_stmt(account_hash, after, limit)
+                        .to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+                    .and_then(parse_account_effects_from_rows)
+            }
+
+            async fn get_transfers_by_account(
+                &self,
+                account_hash: &str,
+                after: Option<String>,
+                limit: u32,
+            ) -> Result<Vec<TransferEntry>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt = tables::transfer::create_get_by_account_stmt(account_hash, after, limit)
+                    .to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+                    .and_then(parse_transfers_from_rows)
+            }
+
+            async fn get_transfers_by_transfer_id(
+                &self,
+                transfer_id: u64,
+                after: Option<String>,
+                limit: u32,
+            ) -> Result<Vec<TransferEntry>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt =
+                    tables::transfer::create_get_by_transfer_id_stmt(transfer_id, after, limit)
+                        .to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+                    .and_then(parse_transfers_from_rows)
+            }
+
+            async fn get_transfers_by_block(
+                &self,
+                block_hash: &str,
+            ) -> Result<Vec<TransferEntry>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt = tables::transfer::create_get_by_block_stmt(block_hash.to_string())
+                    .to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+                    .and_then(parse_transfers_from_rows)
+            }
+
+            async fn get_token_transfers(
+                &self,
+                contract_hash: &str,
+                after: Option<u64>,
+                limit: u32,
+            ) -> Result<Vec<TokenTransferEntry>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt = tables::token_transfer::create_get_by_contract_stmt(
+                    contract_hash,
+                    after,
+                    limit,
+                )
+                .to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+                    .and_then(parse_token_transfers_from_rows)
+            }
+
+            async fn get_nft_events(
+                &self,
+                contract_hash: &str,
+                token_id: Option<&str>,
+                after: Option<u64>,
+                limit: u32,
+            ) -> Result<Vec<NftEventEntry>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt = tables::nft_event::create_get_by_contract_stmt(
+                    contract_hash,
+                    token_id,
+                    after,
+                    limit,
+                )
+                .to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+                    .and_then(parse_nft_events_from_rows)
+            }
+
+            async fn get_contract_event_schemas(
+                &self,
+                contract_hash: &str,
+            ) -> Result<ContractEventSchemas, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt = tables::contract_event_schema::create_get_stmt(contract_hash)
+                    .to_string($query_materializer_expr);
+                let row = fetch_optional_with_error_check(&mut db_connection, stmt).await?;
+
+                let raw = row
+                    .try_get::<String, &str>("raw")
+                    .map_err(|err| wrap_query_error(err.into()))?;
+                deserialize_data::<ContractEventSchemas>(&raw).map_err(wrap_query_error)
+            }
+
+            async fn get_contract_events(
+                &self,
+                contract_hash: &str,
+                event_name: Option<&str>,
+                after: Option<u64>,
+                limit: u32,
+            ) -> Result<Vec<ContractEventEntry>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt = tables::contract_event::create_get_by_contract_stmt(
+                    contract_hash,
+                    event_name,
+                    after,
+                    limit,
+                )
+                .to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+                    .and_then(parse_contract_events_from_rows)
+            }
+
+            async fn get_contract_stats(
+                &self,
+                contract_hash: &str,
+            ) -> Result<Vec<ContractDayStats>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt =
+                    tables::contract_stats::create_get_by_contract_stmt(contract_hash.to_string())
+                        .to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+                    .and_then(parse_contract_stats_from_rows)
+            }
+
+            async fn get_deploy_size_stats(&self) -> Result<DeploySizeStats, DatabaseReadError> {
+                // The distributions are computed from a snapshot, so that they all count the same
+                // deploys.
+                let snapshot = self.snapshot().await?;
+                let (deploys, size_bytes) = snapshot
+                    .get_deploy_size_distribution(tables::deploy_size::DeploySize::SizeBytes)
+                    .await?;
+                let (_, approvals) = snapshot
+                    .get_deploy_size_distribution(tables::deploy_size::DeploySize::Approvals)
+                    .await?;
+                let (_, args) = snapshot
+                    .get_deploy_size_distribution(tables::deploy_size::DeploySize::Args)
+                    .await?;
+                Ok(DeploySizeStats {
+                    deploys,
+                    size_bytes,
+                    approvals,
+                    args,
+                })
+            }
+
+            async fn get_era_validators(&self, era: u64) -> Result<Vec<String>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt = tables::era_validator::create_get_by_era_stmt(era)
+                    .to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+                    .and_then(parse_era_validators_from_rows)
+            }
+
+            async fn get_era_validator_weights(
+                &self,
+                era: u64,
+            ) -> Result<Vec<EraValidatorWeight>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt = tables::era_validator::create_get_weights_by_era_stmt(era)
+                    .to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+                    .and_then(parse_era_validator_weights_from_rows)
+            }
+
+            async fn get_raw_payload_sample(
+                &self,
+                table: PayloadTable,
+                limit: u32,
+            ) -> Result<Vec<String>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt = tables::raw_payload::create_get_latest_stmt(table.table_name(), limit)
+                    .to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+                    .and_then(parse_raw_payloads_from_rows)
+            }
+
+            async fn get_raw_payloads_after(
+                &self,
+                table: PayloadTable,
+                after: u64,
+                limit: u32,
+            ) -> Result<Vec<RawPayloadEntry>, DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt =
+                    tables::raw_payload::create_get_after_stmt(table.table_name(), after, limit)
+                        .to_string($query_materializer_expr);
+
+                db_connection
+                    .fetch_all(stmt.as_str())
+                    .await
+                    .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+                    .and_then(parse_raw_payload_entries_from_rows)
+            }
+        }
+
+        impl $extended_type {
+            /// Returns the number of recorded deploys along with the distribution of the measure.
+            async fn get_deploy_size_distribution(
+                &self,
+                measure: tables::deploy_size::DeploySize,
+            ) -> Result<(u64, Distribution), DatabaseReadError> {
+                let mut db_connection = self.get_read_connection().await?;
+
+                let stmt = tables::deploy_size::create_get_summary_stmt(measure)
+                    .to_string($query_materializer_expr);
+                let row = fetch_optional_with_error_check(&mut db_connection, stmt).await?;
+                let deploys = get_u64_column(&row, "deploys")?;
+                if deploys == 0 {
+                    return Err(DatabaseReadError::NotFound);
+                }
+                let mut percentiles = Vec::new();
+                for percentile in [50, 90, 99] {
+                    let offset = Distribution::rank_offset(deploys, percentile);
+                    let stmt = tables::deploy_size::create_get_ranked_stmt(measure, offset)
+                        .to_string($query_materializer_expr);
+                    let ranked_row =
+                        fetch_optional_with_error_check(&mut db_connection, stmt).await?;
+                    percentiles.push(get_u64_column(&ranked_row, "value")?);
+                }
+                let distribution = Distribution {
+                    min: get_u64_column(&row, "min")?,
+                    max: get_u64_column(&row, "max")?,
+                    mean: get_u64_column(&row, "sum")? as f64 / deploys as f64,
+                    p50: percentiles[0],
+                    p90: percentiles[1],
+                    p99: percentiles[2],
+                };
+                Ok((deploys, distribution))
+            }
+        }
+
+        fn get_u64_column(row: &$row_type, column: &str) -> Result<u64, DatabaseReadError> {
+            row.try_get::<i64, &str>(column)
+                .map(|value| value as u64)
+                .map_err(|err| wrap_query_error(err.into()))
         }
 
         fn deserialize_data<'de, T: Deserialize<'de>>(data: &'de str) -> Result<T, DbError> {
@@ -324,5 +1035,467 @@ macro_rules! database_reader_implementation {
             }
             Ok(faults)
         }
+
+        fn parse_unknown_events_from_rows(
+            rows: Vec<$row_type>,
+        ) -> Result<Vec<UnknownEvent>, DatabaseReadError> {
+            let mut unknown_events = Vec::new();
+            for row in rows {
+                let raw = row
+                    .try_get::<String, &str>("raw")
+                    .map_err(|err| wrap_query_error(err.into()))?;
+
+                let unknown_event =
+                    deserialize_data::<UnknownEvent>(&raw).map_err(wrap_query_error)?;
+                unknown_events.push(unknown_event);
+            }
+
+            if unknown_events.is_empty() {
+                return Err(DatabaseReadError::NotFound);
+            }
+            Ok(unknown_events)
+        }
+
+        fn parse_enrichments_from_rows(
+            rows: Vec<$row_type>,
+        ) -> Result<Vec<Enrichment>, DatabaseReadError> {
+            let mut enrichments = Vec::new();
+            for row in rows {
+                let raw = row
+                    .try_get::<String, &str>("raw")
+                    .map_err(|err| wrap_query_error(err.into()))?;
+
+                let enrichment = deserialize_data::<Enrichment>(&raw).map_err(wrap_query_error)?;
+                enrichments.push(enrichment);
+            }
+
+            if enrichments.is_empty() {
+                return Err(DatabaseReadError::NotFound);
+            }
+            Ok(enrichments)
+        }
+
+        fn parse_derived_events_from_rows(
+            rows: Vec<$row_type>,
+        ) -> Result<Vec<DerivedEvent>, DatabaseReadError> {
+            let mut derived_events = Vec::new();
+            for row in rows {
+                let raw = row
+                    .try_get::<String, &str>("raw")
+                    .map_err(|err| wrap_query_error(err.into()))?;
+
+                let derived_event =
+                    deserialize_data::<DerivedEvent>(&raw).map_err(wrap_query_error)?;
+                derived_events.push(derived_event);
+            }
+
+            if derived_events.is_empty() {
+                return Err(DatabaseReadError::NotFound);
+            }
+            Ok(derived_events)
+        }
+
+        fn parse_webhook_delivery_attempts_from_rows(
+            rows: Vec<$row_type>,
+        ) -> Result<Vec<WebhookDeliveryAttempt>, DatabaseReadError> {
+            let mut delivery_attempts = Vec::new();
+            for row in rows {
+                let delivery_attempt = WebhookDeliveryAttempt {
+                    webhook: row
+                        .try_get::<String, &str>("webhook")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                    event_type: row
+                        .try_get::<String, &str>("event_type")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                    attempt: row
+                        .try_get::<i32, &str>("attempt")
+                        .map_err(|err| wrap_query_error(err.into()))? as u32,
+                    timestamp: row
+                        .try_get::<i64, &str>("attempted_timestamp")
+                        .map_err(|err| wrap_query_error(err.into()))? as u64,
+                    status_code: row
+                        .try_get::<Option<i32>, &str>("status_code")
+                        .map_err(|err| wrap_query_error(err.into()))?
+                        .map(|status_code| status_code as u16),
+                    error: row
+                        .try_get::<Option<String>, &str>("error")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                };
+                delivery_attempts.push(delivery_attempt);
+            }
+            Ok(delivery_attempts)
+        }
+
+        fn parse_api_keys_from_rows(
+            rows: Vec<$row_type>,
+        ) -> Result<Vec<StoredApiKey>, DatabaseReadError> {
+            let mut api_keys = Vec::new();
+            for row in rows {
+                let scopes = row
+                    .try_get::<String, &str>("scopes")
+                    .map_err(|err| wrap_query_error(err.into()))?;
+                let api_key = StoredApiKey {
+                    name: row
+                        .try_get::<String, &str>("name")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                    key_hash: row
+                        .try_get::<String, &str>("key_hash")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                    scopes: scopes
+                        .split(',')
+                        .filter(|scope| !scope.is_empty())
+                        .map(|scope| {
+                            ApiKeyScope::from_name(scope).ok_or_else(|| {
+                                DatabaseReadError::Unhandled(Error::msg(format!(
+                                    "unknown API key scope {}",
+                                    scope
+                                )))
+                            })
+                        })
+                        .collect::<Result<_, _>>()?,
+                    created_timestamp: row
+                        .try_get::<i64, &str>("created_timestamp")
+                        .map_err(|err| wrap_query_error(err.into()))?
+                        as u64,
+                };
+                api_keys.push(api_key);
+            }
+            Ok(api_keys)
+        }
+
+        fn parse_raw_payload_entries_from_rows(
+            rows: Vec<$row_type>,
+        ) -> Result<Vec<RawPayloadEntry>, DatabaseReadError> {
+            let mut entries = Vec::new();
+            for row in rows {
+                entries.push(RawPayloadEntry {
+                    event_log_id: row
+                        .try_get::<i64, &str>("event_log_id")
+                        .map_err(|err| wrap_query_error(err.into()))?
+                        as u64,
+                    event_source_address: row
+                        .try_get::<String, &str>("event_source_address")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                    event_id: row
+                        .try_get::<i64, &str>("event_id")
+                        .map_err(|err| wrap_query_error(err.into()))? as u64,
+                    inserted_timestamp: row
+                        .try_get::<String, &str>("inserted_timestamp")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                    raw: row
+                        .try_get::<String, &str>("raw")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                });
+            }
+            Ok(entries)
+        }
+
+        fn parse_raw_payloads_from_rows(
+            rows: Vec<$row_type>,
+        ) -> Result<Vec<String>, DatabaseReadError> {
+            let mut raw_payloads = Vec::new();
+            for row in rows {
+                let raw = row
+                    .try_get::<String, &str>("raw")
+                    .map_err(|err| wrap_query_error(err.into()))?;
+                raw_payloads.push(raw);
+            }
+
+            if raw_payloads.is_empty() {
+                return Err(DatabaseReadError::NotFound);
+            }
+            Ok(raw_payloads)
+        }
+
+        fn parse_audit_entries_from_rows(
+            rows: Vec<$row_type>,
+        ) -> Result<Vec<AuditEntry>, DatabaseReadError> {
+            let mut audit_entries = Vec::new();
+            for row in rows {
+                let audit_entry = AuditEntry {
+                    id: row
+                        .try_get::<i64, &str>("audit_log_id")
+                        .map_err(|err| wrap_query_error(err.into()))? as u64,
+                    timestamp: row
+                        .try_get::<i64, &str>("recorded_timestamp")
+                        .map_err(|err| wrap_query_error(err.into()))? as u64,
+                    actor: row
+                        .try_get::<String, &str>("actor")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                    action: row
+                        .try_get::<String, &str>("action")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                    details: row
+                        .try_get::<Option<String>, &str>("details")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                };
+                audit_entries.push(audit_entry);
+            }
+            Ok(audit_entries)
+        }
+
+        fn parse_operational_events_from_rows(
+            rows: Vec<$row_type>,
+        ) -> Result<Vec<OperationalEvent>, DatabaseReadError> {
+            let mut operational_events = Vec::new();
+            for row in rows {
+                let operational_event = OperationalEvent {
+                    id: row
+                        .try_get::<i64, &str>("operational_event_id")
+                        .map_err(|err| wrap_query_error(err.into()))? as u64,
+                    timestamp: row
+                        .try_get::<i64, &str>("recorded_timestamp")
+                        .map_err(|err| wrap_query_error(err.into()))? as u64,
+                    kind: row
+                        .try_get::<String, &str>("kind")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                    details: row
+                        .try_get::<Option<String>, &str>("details")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                };
+                operational_events.push(operational_event);
+            }
+            Ok(operational_events)
+        }
+
+        fn parse_deploy_timestamps_from_rows(
+            rows: Vec<$row_type>,
+        ) -> Result<Vec<DeployTimestampEntry>, DatabaseReadError> {
+            let mut entries = Vec::new();
+            for row in rows {
+                let event_type_id = row
+                    .try_get::<i16, &str>("event_type_id")
+                    .map_err(|err| wrap_query_error(err.into()))?;
+                let kind = DeployTimestampKind::from_event_type_id(event_type_id as u8)
+                    .ok_or_else(|| {
+                        DatabaseReadError::Unhandled(Error::msg(format!(
+                            "Unexpected event type id {} in deploy timestamp index",
+                            event_type_id
+                        )))
+                    })?;
+                entries.push(DeployTimestampEntry {
+                    deploy_hash: row
+                        .try_get::<String, &str>("deploy_hash")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                    kind,
+                    timestamp: row
+                        .try_get::<i64, &str>("timestamp")
+                        .map_err(|err| wrap_query_error(err.into()))? as u64,
+                });
+            }
+            Ok(entries)
+        }
+
+        fn parse_deploy_errors_from_rows(
+            rows: Vec<$row_type>,
+        ) -> Result<Vec<DeployErrorEntry>, DatabaseReadError> {
+            let mut entries = Vec::new();
+            for row in rows {
+                entries.push(DeployErrorEntry {
+                    deploy_hash: row
+                        .try_get::<String, &str>("deploy_hash")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                    block_hash: row
+                        .try_get::<String, &str>("block_hash")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                    error_message: row
+                        .try_get::<String, &str>("error_message")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                });
+            }
+            Ok(entries)
+        }
+
+        fn parse_deploy_accounts_from_rows(
+            rows: Vec<$row_type>,
+        ) -> Result<Vec<DeployAccountEntry>, DatabaseReadError> {
+            let mut entries = Vec::new();
+            for row in rows {
+                entries.push(DeployAccountEntry {
+                    deploy_hash: row
+                        .try_get::<String, &str>("deploy_hash")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                    account_hash: row
+                        .try_get::<String, &str>("account_hash")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                    account_alias: None,
+                });
+            }
+            Ok(entries)
+        }
+
+        fn parse_deploy_approvals_from_rows(
+            rows: Vec<$row_type>,
+        ) -> Result<Vec<DeployApprovalEntry>, DatabaseReadError> {
+            let mut entries = Vec::new();
+            for row in rows {
+                entries.push(DeployApprovalEntry {
+                    deploy_hash: row
+                        .try_get::<String, &str>("deploy_hash")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                    signer: row
+                        .try_get::<String, &str>("signer")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                    event_log_id: row
+                        .try_get::<i64, &str>("event_log_id")
+                        .map_err(|err| wrap_query_error(err.into()))?
+                        as u64,
+                });
+            }
+            Ok(entries)
+        }
+
+        fn parse_deploy_event_times_from_rows(
+            rows: Vec<$row_type>,
+        ) -> Result<Vec<DeployEventTime>, DatabaseReadError> {
+            let mut entries = Vec::new();
+            for row in rows {
+                entries.push(DeployEventTime {
+                    event_type_id: row
+                        .try_get::<i16, &str>("event_type_id")
+                        .map_err(|err| wrap_query_error(err.into()))?
+                        as u8,
+                    inserted_timestamp: row
+                        .try_get::<String, &str>("inserted_timestamp")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                });
+            }
+            Ok(entries)
+        }
+
+        fn parse_account_effects_from_rows(
+            rows: Vec<$row_type>,
+        ) -> Result<Vec<AccountEffectEntry>, DatabaseReadError> {
+            let mut entries = Vec::new();
+            for row in rows {
+                entries.push(AccountEffectEntry {
+                    deploy_hash: row
+                        .try_get::<String, &str>("deploy_hash")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                    account_hash: row
+                        .try_get::<String, &str>("account_hash")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                });
+            }
+            Ok(entries)
+        }
+
+        fn parse_transfers_from_rows(
+            rows: Vec<$row_type>,
+        ) -> Result<Vec<TransferEntry>, DatabaseReadError> {
+            let mut transfers = Vec::new();
+            for row in rows {
+                let raw = row
+                    .try_get::<String, &str>("raw")
+                    .map_err(|err| wrap_query_error(err.into()))?;
+                transfers.push(deserialize_data::<TransferEntry>(&raw).map_err(wrap_query_error)?);
+            }
+            Ok(transfers)
+        }
+
+        fn parse_token_transfers_from_rows(
+            rows: Vec<$row_type>,
+        ) -> Result<Vec<TokenTransferEntry>, DatabaseReadError> {
+            let mut token_transfers = Vec::new();
+            for row in rows {
+                let raw = row
+                    .try_get::<String, &str>("raw")
+                    .map_err(|err| wrap_query_error(err.into()))?;
+                token_transfers
+                    .push(deserialize_data::<TokenTransferEntry>(&raw).map_err(wrap_query_error)?);
+            }
+            Ok(token_transfers)
+        }
+
+        fn parse_nft_events_from_rows(
+            rows: Vec<$row_type>,
+        ) -> Result<Vec<NftEventEntry>, DatabaseReadError> {
+            let mut nft_events = Vec::new();
+            for row in rows {
+                let raw = row
+                    .try_get::<String, &str>("raw")
+                    .map_err(|err| wrap_query_error(err.into()))?;
+                nft_events.push(deserialize_data::<NftEventEntry>(&raw).map_err(wrap_query_error)?);
+            }
+            Ok(nft_events)
+        }
+
+        fn parse_contract_events_from_rows(
+            rows: Vec<$row_type>,
+        ) -> Result<Vec<ContractEventEntry>, DatabaseReadError> {
+            let mut contract_events = Vec::new();
+            for row in rows {
+                let raw = row
+                    .try_get::<String, &str>("raw")
+                    .map_err(|err| wrap_query_error(err.into()))?;
+                contract_events
+                    .push(deserialize_data::<ContractEventEntry>(&raw).map_err(wrap_query_error)?);
+            }
+            Ok(contract_events)
+        }
+
+        fn parse_contract_stats_from_rows(
+            rows: Vec<$row_type>,
+        ) -> Result<Vec<ContractDayStats>, DatabaseReadError> {
+            let mut stats = Vec::new();
+            for row in rows {
+                stats.push(ContractDayStats {
+                    day: row
+                        .try_get::<String, &str>("day")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                    successes: row
+                        .try_get::<i64, &str>("successes")
+                        .map_err(|err| wrap_query_error(err.into()))? as u64,
+                    failures: row
+                        .try_get::<i64, &str>("failures")
+                        .map_err(|err| wrap_query_error(err.into()))? as u64,
+                    gas_spent: row
+                        .try_get::<i64, &str>("gas_spent")
+                        .map_err(|err| wrap_query_error(err.into()))? as u64,
+                });
+            }
+
+            if stats.is_empty() {
+                return Err(DatabaseReadError::NotFound);
+            }
+            Ok(stats)
+        }
+
+        fn parse_era_validators_from_rows(
+            rows: Vec<$row_type>,
+        ) -> Result<Vec<String>, DatabaseReadError> {
+            let mut validators = Vec::new();
+            for row in rows {
+                validators.push(
+                    row.try_get::<String, &str>("public_key")
+                        .map_err(|err| wrap_query_error(err.into()))?,
+                );
+            }
+
+            if validators.is_empty() {
+                return Err(DatabaseReadError::NotFound);
+            }
+            Ok(validators)
+        }
+
+        fn parse_era_validator_weights_from_rows(
+            rows: Vec<$row_type>,
+        ) -> Result<Vec<EraValidatorWeight>, DatabaseReadError> {
+            let mut validator_weights = Vec::new();
+            for row in rows {
+                let public_key = row
+                    .try_get::<String, &str>("public_key")
+                    .map_err(|err| wrap_query_error(err.into()))?;
+                let weight = row
+                    .try_get::<Option<String>, &str>("weight")
+                    .map_err(|err| wrap_query_error(err.into()))?;
+                validator_weights.push(EraValidatorWeight { public_key, weight });
+            }
+
+            if validator_weights.is_empty() {
+                return Err(DatabaseReadError::NotFound);
+            }
+            Ok(validator_weights)
+        }
     };
 }
@@ -0,0 +1,52 @@
+use sqlx::{pool::PoolConnection, Database, Pool, Transaction};
+use std::{
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
+use tokio::sync::{Mutex, MutexGuard};
+
+/// The transaction a read-only snapshot of a database reads from, shared by the clones of the
+/// snapshot. It is rolled back, returning its connection to the pool, once the last clone is
+/// dropped.
+pub type SnapshotTransaction<DB> = Arc<Mutex<Transaction<'static, DB>>>;
+
+/// The connection a query of a [DatabaseReader](crate::types::database::DatabaseReader) is run
+/// on: either any connection of the pool, or the transaction of the snapshot the database is
+/// pinned to, locked for the duration of the query.
+pub enum ReadConnection<'a, DB: Database> {
+    Pooled(PoolConnection<DB>),
+    Snapshot(MutexGuard<'a, Transaction<'static, DB>>),
+}
+
+impl<'a, DB: Database> ReadConnection<'a, DB> {
+    /// Acquires a connection of `pool`, unless the database is pinned to `maybe_snapshot`.
+    pub async fn acquire(
+        pool: &Pool<DB>,
+        maybe_snapshot: &'a Option<SnapshotTransaction<DB>>,
+    ) -> Result<Self, sqlx::Error> {
+        match maybe_snapshot {
+            Some(snapshot) => Ok(ReadConnection::Snapshot(snapshot.lock().await)),
+            None => pool.acquire().await.map(ReadConnection::Pooled),
+        }
+    }
+}
+
+impl<DB: Database> Deref for ReadConnection<'_, DB> {
+    type Target = DB::Connection;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            ReadConnection::Pooled(connection) => connection,
+            ReadConnection::Snapshot(transaction) => transaction,
+        }
+    }
+}
+
+impl<DB: Database> DerefMut for ReadConnection<'_, DB> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            ReadConnection::Pooled(connection) => connection,
+            ReadConnection::Snapshot(transaction) => transaction,
+        }
+    }
+}
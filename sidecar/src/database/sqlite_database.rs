@@ -2,12 +2,18 @@ mod reader;
 #[cfg(test)]
 mod tests;
 mod writer;
-use super::migration_manager::MigrationManager;
+use super::{
+    migration_manager::MigrationManager,
+    snapshot::{ReadConnection, SnapshotTransaction},
+};
 #[cfg(test)]
 use crate::types::config::StorageConfig;
 use crate::{
     sql::tables,
-    types::{config::SqliteConfig, database::DatabaseWriteError},
+    types::{
+        config::SqliteConfig,
+        database::{DatabaseReadError, DatabaseWriteError},
+    },
 };
 use anyhow::Error;
 use sea_query::SqliteQueryBuilder;
@@ -32,6 +38,12 @@ const WAL_AUTOCHECKPOINT_KEY: &str = "wal_autocheckpoint";
 pub struct SqliteDatabase {
     pub connection_pool: SqlitePool,
     pub file_path: PathBuf,
+    /// Whether the times at which deploys are accepted and processed are indexed.
+    pub index_deploy_timestamps: bool,
+    /// Whether the accounts touched by the processed deploys are indexed.
+    pub index_account_effects: bool,
+    /// The transaction the database reads from, if it is a read-only snapshot.
+    pub snapshot: Option<SnapshotTransaction<Sqlite>>,
 }
 
 impl SqliteDatabase {
@@ -57,6 +69,9 @@ impl SqliteDatabase {
                 let sqlite_db = SqliteDatabase {
                     connection_pool,
                     file_path: Path::new(&path).into(),
+                    index_deploy_timestamps: config.index_deploy_timestamps,
+                    index_account_effects: config.index_account_effects,
+                    snapshot: None,
                 };
                 MigrationManager::apply_all_migrations(sqlite_db.clone()).await?;
 
@@ -94,6 +109,18 @@ impl SqliteDatabase {
     async fn get_transaction(&self) -> Result<Transaction<Sqlite>, sqlx::Error> {
         self.connection_pool.begin().await
     }
+
+    async fn get_read_connection(&self) -> Result<ReadConnection<Sqlite>, DatabaseReadError> {
+        ReadConnection::acquire(&self.connection_pool, &self.snapshot)
+            .await
+            .map_err(|sql_err| DatabaseReadError::Unhandled(Error::from(sql_err)))
+    }
+
+    /// Begins the transaction of a snapshot. Sqlite transactions are deferred, so in WAL mode the
+    /// transaction reads the database as it was at its first read, until it is rolled back.
+    async fn begin_snapshot(&self) -> Result<Transaction<'static, Sqlite>, sqlx::Error> {
+        self.connection_pool.begin().await
+    }
 }
 
 #[cfg(test)]
@@ -131,6 +158,9 @@ impl SqliteDatabase {
         let sqlite_db = SqliteDatabase {
             connection_pool,
             file_path: Path::new("in_memory").into(),
+            index_deploy_timestamps: true,
+            index_account_effects: true,
+            snapshot: None,
         };
         Ok(sqlite_db)
     }
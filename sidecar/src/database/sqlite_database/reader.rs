@@ -2,10 +2,10 @@ use crate::database_reader_implementation;
 
 use super::SqliteDatabase;
 use sea_query::SqliteQueryBuilder;
-use sqlx::{sqlite::SqliteRow, SqlitePool};
+use sqlx::{sqlite::SqliteRow, SqliteConnection};
 
 async fn fetch_optional_with_error_check(
-    connection: &SqlitePool,
+    connection: &mut SqliteConnection,
     stmt: String,
 ) -> Result<SqliteRow, DatabaseReadError> {
     connection
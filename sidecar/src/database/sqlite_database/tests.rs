@@ -1,12 +1,23 @@
+use proptest::prelude::*;
 use sea_query::{Asterisk, Expr, Query, SqliteQueryBuilder};
+use serde_json::Value;
 use sqlx::Row;
 
+use casper_event_types::{
+    arbitrary,
+    sse_data::{deserialize, SseData},
+};
 use casper_types::testing::TestRng;
 
 use super::SqliteDatabase;
 use crate::{
+    deploy_decoding::deploy_processed_of,
     sql::tables::{self, event_type::EventTypeId},
-    types::{database::DatabaseWriter, sse_events::*},
+    types::{
+        config::SqliteConfig,
+        database::{DatabaseReader, DatabaseWriter},
+        sse_events::*,
+    },
 };
 
 const MAX_CONNECTIONS: u32 = 100;
@@ -45,121 +56,366 @@ async fn should_save_and_retrieve_a_u32max_id() {
 #[tokio::test]
 async fn should_save_and_retrieve_block_added() {
     let sqlite_db = build_database().await;
-    crate::database::tests::should_save_and_retrieve_block_added(sqlite_db).await;
+    casper_event_storage::conformance::checks::should_save_and_retrieve_block_added(sqlite_db)
+        .await;
+}
+
+#[tokio::test]
+async fn should_exclude_blocks_failing_integrity() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_exclude_blocks_failing_integrity(sqlite_db)
+        .await;
+}
+
+#[tokio::test]
+async fn should_retrieve_blocks_by_height_range() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_retrieve_blocks_by_height_range(sqlite_db)
+        .await;
+}
+
+#[tokio::test]
+async fn should_retrieve_deploys_by_timestamp_range() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_retrieve_deploys_by_timestamp_range(
+        sqlite_db,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_retrieve_deploys_by_error_message() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_retrieve_deploys_by_error_message(sqlite_db)
+        .await;
+}
+
+#[tokio::test]
+async fn should_retrieve_deploys_by_account() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_retrieve_deploys_by_account(sqlite_db).await;
+}
+
+#[tokio::test]
+async fn should_save_approvals_of_later_acceptances() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_save_approvals_of_later_acceptances(
+        sqlite_db,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_retrieve_deploy_event_times() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_retrieve_deploy_event_times(sqlite_db).await;
+}
+
+#[tokio::test]
+async fn should_retrieve_deploys_affecting_account() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_retrieve_deploys_affecting_account(sqlite_db)
+        .await;
+}
+
+#[tokio::test]
+async fn should_retrieve_native_transfers() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_retrieve_native_transfers(sqlite_db).await;
+}
+
+#[tokio::test]
+async fn should_retrieve_token_transfers() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_retrieve_token_transfers(sqlite_db).await;
+}
+
+#[tokio::test]
+async fn should_clear_derived_table() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_clear_derived_table(sqlite_db).await;
+}
+
+#[tokio::test]
+async fn should_retrieve_nft_events() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_retrieve_nft_events(sqlite_db).await;
+}
+
+#[tokio::test]
+async fn should_save_network_identity_once() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_save_network_identity_once(sqlite_db).await;
+}
+
+#[tokio::test]
+async fn should_retrieve_contract_events() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_retrieve_contract_events(sqlite_db).await;
+}
+
+#[tokio::test]
+async fn should_replace_contract_event_schemas() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_replace_contract_event_schemas(sqlite_db)
+        .await;
+}
+
+#[tokio::test]
+async fn should_roll_up_contract_stats() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_roll_up_contract_stats(sqlite_db).await;
+}
+
+#[tokio::test]
+async fn should_compute_deploy_size_stats() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_compute_deploy_size_stats(sqlite_db).await;
+}
+
+#[tokio::test]
+async fn should_save_and_retrieve_derived_events() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_save_and_retrieve_derived_events(sqlite_db)
+        .await;
+}
+
+#[tokio::test]
+async fn should_save_and_retrieve_webhook_delivery_attempts() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_save_and_retrieve_webhook_delivery_attempts(
+        sqlite_db,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_save_and_delete_api_keys() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_save_and_delete_api_keys(sqlite_db).await;
+}
+
+#[tokio::test]
+async fn should_sample_latest_raw_payloads() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_sample_latest_raw_payloads(sqlite_db).await;
+}
+
+#[tokio::test]
+async fn should_retrieve_era_validators_of_switch_blocks() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_retrieve_era_validators_of_switch_blocks(
+        sqlite_db,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_retrieve_raw_payloads_after_event_log_id() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_retrieve_raw_payloads_after_event_log_id(
+        sqlite_db,
+    )
+    .await;
 }
 
 #[tokio::test]
 async fn should_save_and_retrieve_deploy_accepted() {
     let sqlite_db = build_database().await;
-    crate::database::tests::should_save_and_retrieve_deploy_accepted(sqlite_db).await;
+    casper_event_storage::conformance::checks::should_save_and_retrieve_deploy_accepted(sqlite_db)
+        .await;
 }
 
 #[tokio::test]
 async fn should_save_and_retrieve_deploy_processed() {
     let sqlite_db = build_database().await;
-    crate::database::tests::should_save_and_retrieve_deploy_processed(sqlite_db).await;
+    casper_event_storage::conformance::checks::should_save_and_retrieve_deploy_processed(sqlite_db)
+        .await;
 }
 
 #[tokio::test]
 async fn should_save_and_retrieve_deploy_expired() {
     let sqlite_db = build_database().await;
-    crate::database::tests::should_save_and_retrieve_deploy_expired(sqlite_db).await;
+    casper_event_storage::conformance::checks::should_save_and_retrieve_deploy_expired(sqlite_db)
+        .await;
 }
 
 #[tokio::test]
 async fn should_retrieve_deploy_aggregate_of_accepted() {
     let sqlite_db = build_database().await;
-    crate::database::tests::should_retrieve_deploy_aggregate_of_accepted(sqlite_db).await;
+    casper_event_storage::conformance::checks::should_retrieve_deploy_aggregate_of_accepted(
+        sqlite_db,
+    )
+    .await;
 }
 
 #[tokio::test]
 async fn should_retrieve_deploy_aggregate_of_processed() {
     let sqlite_db = build_database().await;
-    crate::database::tests::should_retrieve_deploy_aggregate_of_processed(sqlite_db).await;
+    casper_event_storage::conformance::checks::should_retrieve_deploy_aggregate_of_processed(
+        sqlite_db,
+    )
+    .await;
 }
 
 #[tokio::test]
 async fn should_retrieve_deploy_aggregate_of_expired() {
     let sqlite_db = build_database().await;
-    crate::database::tests::should_retrieve_deploy_aggregate_of_expired(sqlite_db).await;
+    casper_event_storage::conformance::checks::should_retrieve_deploy_aggregate_of_expired(
+        sqlite_db,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_read_a_snapshot_unaffected_by_later_writes() {
+    // The connections to an in-memory database don't share it, so the snapshot and the writes
+    // need a database file.
+    let storage_dir = tempfile::tempdir().expect("Error creating a temporary directory");
+    let sqlite_db = SqliteDatabase::new(storage_dir.path(), SqliteConfig::default())
+        .await
+        .expect("Error opening database");
+    casper_event_storage::conformance::checks::should_read_a_snapshot_unaffected_by_later_writes(
+        sqlite_db,
+    )
+    .await;
 }
 
 #[tokio::test]
 async fn should_save_and_retrieve_fault() {
     let sqlite_db = build_database().await;
-    crate::database::tests::should_save_and_retrieve_fault(sqlite_db).await;
+    casper_event_storage::conformance::checks::should_save_and_retrieve_fault(sqlite_db).await;
 }
 
 #[tokio::test]
 async fn should_save_and_retrieve_fault_with_a_u64max() {
     let sqlite_db = build_database().await;
-    crate::database::tests::should_save_and_retrieve_fault_with_a_u64max(sqlite_db).await;
+    casper_event_storage::conformance::checks::should_save_and_retrieve_fault_with_a_u64max(
+        sqlite_db,
+    )
+    .await;
 }
 
 #[tokio::test]
 async fn should_save_and_retrieve_finality_signature() {
     let sqlite_db = build_database().await;
-    crate::database::tests::should_save_and_retrieve_finality_signature(sqlite_db).await;
+    casper_event_storage::conformance::checks::should_save_and_retrieve_finality_signature(
+        sqlite_db,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_exclude_invalid_finality_signatures() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_exclude_invalid_finality_signatures(
+        sqlite_db,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_checkpoint_finality_signatures() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_checkpoint_finality_signatures(sqlite_db)
+        .await;
 }
 
 #[tokio::test]
 async fn should_save_and_retrieve_step() {
     let sqlite_db = build_database().await;
-    crate::database::tests::should_save_and_retrieve_step(sqlite_db).await;
+    casper_event_storage::conformance::checks::should_save_and_retrieve_step(sqlite_db).await;
+}
+
+#[tokio::test]
+async fn should_save_and_retrieve_unknown_events() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_save_and_retrieve_unknown_events(sqlite_db)
+        .await;
+}
+
+#[tokio::test]
+async fn should_save_and_retrieve_enrichments() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_save_and_retrieve_enrichments(sqlite_db)
+        .await;
 }
 
 #[tokio::test]
 async fn should_save_and_retrieve_a_step_with_u64_max_era() {
     let sqlite_db = build_database().await;
-    crate::database::tests::should_save_and_retrieve_a_step_with_u64_max_era(sqlite_db).await;
+    casper_event_storage::conformance::checks::should_save_and_retrieve_a_step_with_u64_max_era(
+        sqlite_db,
+    )
+    .await;
 }
 
 #[tokio::test]
-async fn should_disallow_duplicate_event_id_from_source() {
+async fn should_ignore_duplicate_event_id_from_source() {
     let sqlite_db = build_database().await;
-    crate::database::tests::should_disallow_duplicate_event_id_from_source(sqlite_db).await;
+    casper_event_storage::conformance::checks::should_ignore_duplicate_event_id_from_source(
+        sqlite_db,
+    )
+    .await;
 }
 
 #[tokio::test]
-async fn should_disallow_insert_of_existing_block_added() {
+async fn should_ignore_insert_of_existing_block_added() {
     let sqlite_db = build_database().await;
-    crate::database::tests::should_disallow_insert_of_existing_block_added(sqlite_db).await;
+    casper_event_storage::conformance::checks::should_ignore_insert_of_existing_block_added(
+        sqlite_db,
+    )
+    .await;
 }
 
 #[tokio::test]
-async fn should_disallow_insert_of_existing_deploy_accepted() {
+async fn should_ignore_insert_of_existing_deploy_accepted() {
     let sqlite_db = build_database().await;
-    crate::database::tests::should_disallow_insert_of_existing_deploy_accepted(sqlite_db).await;
+    casper_event_storage::conformance::checks::should_ignore_insert_of_existing_deploy_accepted(
+        sqlite_db,
+    )
+    .await;
 }
 
 #[tokio::test]
-async fn should_disallow_insert_of_existing_deploy_expired() {
+async fn should_ignore_insert_of_existing_deploy_expired() {
     let sqlite_db = build_database().await;
-    crate::database::tests::should_disallow_insert_of_existing_deploy_expired(sqlite_db).await;
+    casper_event_storage::conformance::checks::should_ignore_insert_of_existing_deploy_expired(
+        sqlite_db,
+    )
+    .await;
 }
 
 #[tokio::test]
-async fn should_disallow_insert_of_existing_deploy_processed() {
+async fn should_ignore_insert_of_existing_deploy_processed() {
     let sqlite_db = build_database().await;
-    crate::database::tests::should_disallow_insert_of_existing_deploy_processed(sqlite_db).await;
+    casper_event_storage::conformance::checks::should_ignore_insert_of_existing_deploy_processed(
+        sqlite_db,
+    )
+    .await;
 }
 
 #[tokio::test]
-async fn should_disallow_insert_of_existing_fault() {
+async fn should_ignore_insert_of_existing_fault() {
     let sqlite_db = build_database().await;
-    crate::database::tests::should_disallow_insert_of_existing_fault(sqlite_db).await;
+    casper_event_storage::conformance::checks::should_ignore_insert_of_existing_fault(sqlite_db)
+        .await;
 }
 
 #[tokio::test]
-async fn should_disallow_insert_of_existing_finality_signature() {
+async fn should_ignore_insert_of_existing_finality_signature() {
     let sqlite_db = build_database().await;
-    crate::database::tests::should_disallow_insert_of_existing_finality_signature(sqlite_db).await;
+    casper_event_storage::conformance::checks::should_ignore_insert_of_existing_finality_signature(
+        sqlite_db,
+    )
+    .await;
 }
 
 #[tokio::test]
-async fn should_disallow_insert_of_existing_step() {
+async fn should_ignore_insert_of_existing_step() {
     let sqlite_db = build_database().await;
-    crate::database::tests::should_disallow_insert_of_existing_step(sqlite_db).await;
+    casper_event_storage::conformance::checks::should_ignore_insert_of_existing_step(sqlite_db)
+        .await;
 }
 
 #[tokio::test]
@@ -378,11 +634,172 @@ async fn should_save_and_retrieve_a_shutdown() {
 #[tokio::test]
 async fn get_number_of_events_should_return_0() {
     let sqlite_db = build_database().await;
-    crate::database::tests::get_number_of_events_should_return_0(sqlite_db).await;
+    casper_event_storage::conformance::checks::get_number_of_events_should_return_0(sqlite_db)
+        .await;
 }
 
 #[tokio::test]
 async fn get_number_of_events_should_return_1_when_event_stored() {
     let sqlite_db = build_database().await;
-    crate::database::tests::get_number_of_events_should_return_1_when_event_stored(sqlite_db).await;
+    casper_event_storage::conformance::checks::get_number_of_events_should_return_1_when_event_stored(sqlite_db).await;
+}
+
+#[tokio::test]
+async fn should_save_and_retrieve_audit_entries() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_save_and_retrieve_audit_entries(sqlite_db)
+        .await;
+}
+
+#[tokio::test]
+async fn should_save_and_retrieve_operational_events() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_save_and_retrieve_operational_events(
+        sqlite_db,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_prune_events_beyond_max_rows_and_age() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_prune_events_beyond_max_rows_and_age(
+        sqlite_db,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn should_reserve_event_ids_without_gaps() {
+    let sqlite_db = build_database().await;
+    casper_event_storage::conformance::checks::should_reserve_event_ids_without_gaps(sqlite_db)
+        .await;
+}
+
+#[tokio::test]
+async fn should_pass_storage_conformance_suite() {
+    casper_event_storage::conformance::run_conformance_suite(build_database).await;
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+    #[test]
+    fn stored_events_should_round_trip_to_rest_json(node_json in arbitrary::sse_data_json()) {
+        let runtime = tokio::runtime::Runtime::new().expect("Error building runtime");
+        if let Some((node_body, rest_json)) = runtime.block_on(store_and_fetch(&node_json)) {
+            prop_assert_eq!(node_body, rest_json);
+        }
+        // The outbound event stream re-serializes the deserialized event.
+        arbitrary::check_default_sse_data_round_trip(&node_json);
+    }
+}
+
+/// Deserializes `node_json` like the listener, stores the event like the sidecar and fetches it
+/// like the REST server does. Returns the body of the node's event and the JSON the REST server
+/// would respond with, or `None` for events which aren't queryable.
+async fn store_and_fetch(node_json: &str) -> Option<(Value, Value)> {
+    let db = build_database().await;
+    let (sse_data, _) = deserialize(node_json).expect("Error deserializing node json");
+    let node_value: Value = serde_json::from_str(node_json).expect("Error parsing node json");
+    let node_body = node_value.as_object()?.values().next()?.clone();
+    let rest_json = match sse_data {
+        SseData::BlockAdded { block_hash, block } => {
+            store_and_fetch_block_added(&db, BlockAdded::new(block_hash, block)).await
+        }
+        SseData::DeployAccepted { deploy } => {
+            store_and_fetch_deploy_accepted(&db, DeployAccepted::new(deploy)).await
+        }
+        SseData::DeployProcessed { .. } => {
+            store_and_fetch_deploy_processed(&db, deploy_processed_of(&sse_data)?).await
+        }
+        SseData::Fault {
+            era_id,
+            public_key,
+            timestamp,
+        } => store_and_fetch_fault(&db, Fault::new(era_id, public_key, timestamp)).await,
+        SseData::FinalitySignature(finality_signature) => {
+            store_and_fetch_finality_signature(&db, FinalitySignature::new(finality_signature))
+                .await
+        }
+        SseData::Step {
+            era_id,
+            execution_effect,
+        } => store_and_fetch_step(&db, Step::new(era_id, execution_effect)).await,
+        SseData::ApiVersion(_)
+        | SseData::SidecarVersion(_)
+        | SseData::DeployExpired { .. }
+        | SseData::Shutdown
+        | SseData::Unknown { .. }
+        | SseData::Derived { .. } => return None,
+    }
+    .expect("Error serializing REST response");
+    Some((node_body, rest_json))
+}
+
+const SOURCE: &str = "127.0.0.1";
+
+async fn store_and_fetch_block_added(
+    db: &SqliteDatabase,
+    block_added: BlockAdded,
+) -> serde_json::Result<Value> {
+    let hash = block_added.hex_encoded_hash();
+    db.save_block_added(block_added, 1, SOURCE.to_string())
+        .await
+        .expect("Error saving block_added");
+    serde_json::to_value(db.get_block_by_hash(&hash).await.unwrap())
+}
+
+async fn store_and_fetch_deploy_accepted(
+    db: &SqliteDatabase,
+    deploy_accepted: DeployAccepted,
+) -> serde_json::Result<Value> {
+    let hash = deploy_accepted.hex_encoded_hash();
+    db.save_deploy_accepted(deploy_accepted, 1, SOURCE.to_string())
+        .await
+        .expect("Error saving deploy_accepted");
+    let stored = db.get_deploy_accepted_by_hash(&hash).await.unwrap();
+    serde_json::to_value(stored).map(|value| value["deploy"].clone())
+}
+
+async fn store_and_fetch_deploy_processed(
+    db: &SqliteDatabase,
+    deploy_processed: DeployProcessed,
+) -> serde_json::Result<Value> {
+    let hash = deploy_processed.hex_encoded_hash();
+    db.save_deploy_processed(deploy_processed, 1, SOURCE.to_string())
+        .await
+        .expect("Error saving deploy_processed");
+    serde_json::to_value(db.get_deploy_processed_by_hash(&hash).await.unwrap())
+}
+
+async fn store_and_fetch_fault(db: &SqliteDatabase, fault: Fault) -> serde_json::Result<Value> {
+    let era_id = fault.era_id.value();
+    db.save_fault(fault, 1, SOURCE.to_string())
+        .await
+        .expect("Error saving fault");
+    let faults = db.get_faults_by_era(era_id).await.unwrap();
+    serde_json::to_value(&faults[0])
+}
+
+async fn store_and_fetch_finality_signature(
+    db: &SqliteDatabase,
+    finality_signature: FinalitySignature,
+) -> serde_json::Result<Value> {
+    let block_hash = finality_signature.hex_encoded_block_hash();
+    db.save_finality_signature(finality_signature, 1, SOURCE.to_string())
+        .await
+        .expect("Error saving finality_signature");
+    let signatures = db
+        .get_finality_signatures_by_block(&block_hash)
+        .await
+        .unwrap();
+    serde_json::to_value(&signatures[0])
+}
+
+async fn store_and_fetch_step(db: &SqliteDatabase, step: Step) -> serde_json::Result<Value> {
+    let era_id = step.era_id.value();
+    db.save_step(step, 1, SOURCE.to_string())
+        .await
+        .expect("Error saving step");
+    serde_json::to_value(db.get_step_by_era(era_id).await.unwrap())
 }
@@ -13,6 +13,7 @@ database_writer_implementation!(
     DDLConfiguration {
         is_big_integer_id: false,
         db_supports_unsigned: true,
+        supports_partitioning: false,
     }
 );
 
@@ -5,4 +5,6 @@ pub struct DDLConfiguration {
     pub is_big_integer_id: bool,
     /// Postgresql doesn't support unsigned integers, so for some fields we need to be mindful of the fact that in postgres we might need to use a bigger type to accomodate scope of field
     pub db_supports_unsigned: bool,
+    /// Postgresql supports native partitioning of tables, which is used to split the tables keyed by era or block height into ranges that can be pruned by queries and dropped as a whole.
+    pub supports_partitioning: bool,
 }
@@ -8,7 +8,6 @@ macro_rules! database_writer_implementation {
 use anyhow::Context;
 use async_trait::async_trait;
 use casper_types::AsymmetricType;
-#[cfg(feature = "additional-metrics")]
 use casper_event_types::metrics;
 use itertools::Itertools;
 use tokio::sync::Mutex;
@@ -16,12 +15,14 @@ use $crate::{
     sql::{tables, tables::event_type::EventTypeId},
     types::{
         database::{
-            DatabaseWriteError, DatabaseWriter, Migration, StatementWrapper, TransactionWrapper,
+            ContractEventEntry, ContractEventSchemas, DatabaseWriteError, DatabaseWriter,
+            DerivedEvent, DerivedTable, Enrichment,
+            FinalitySignatureCheckpoint, Migration, MigrationExecutor, NetworkIdentity, NftEventEntry, PayloadTable, StatementWrapper,
+            StoredApiKey, TokenTransferEntry, TransactionWrapper, TransferEntry, WebhookDeliveryAttempt,
         },
         sse_events::*,
     },
 };
-#[cfg(feature = "additional-metrics")]
 use std::time::Instant;
 use std::{
     sync::Arc,
@@ -53,44 +54,55 @@ impl DatabaseWriter for $extended_type {
         event_id: u32,
         event_source_address: String,
     ) -> Result<u64, DatabaseWriteError> {
-        #[cfg(feature = "additional-metrics")]
         let start = Instant::now();
         let mut transaction = self.get_transaction().await?;
-        let json = serde_json::to_string(&block_added)?;
-        let encoded_hash = block_added.hex_encoded_hash();
-        let event_log_id = save_event_log(
-                EventTypeId::BlockAdded as u8,
-                &event_source_address,
-                event_id,
-                &encoded_hash,
-                &mut transaction,
-            )
-            .await?;
-
-        let insert_stmt = tables::block_added::create_insert_stmt(
-            block_added.get_height(),
-            encoded_hash,
-            json,
-            event_log_id,
-        )?
-        .to_string($query_materializer_expr);
-
-        let res = handle_result(transaction.execute(insert_stmt.as_str()).await);
+        let res = save_block_added_with_integrity(
+            block_added,
+            event_id,
+            &event_source_address,
+            false,
+            self.index_deploy_timestamps,
+            &mut transaction,
+        )
+        .await;
         if res.is_ok() {
             transaction.commit().await?;
         }
-        #[cfg(feature = "additional-metrics")]
         observe_db_operation_time("save_block_added", start);
         res
     }
 
+    async fn save_block_added_failing_integrity(
+        &self,
+        block_added: BlockAdded,
+        event_id: u32,
+        event_source_address: String,
+    ) -> Result<u64, DatabaseWriteError> {
+        let start = Instant::now();
+        let mut transaction = self.get_transaction().await?;
+        // The timestamps of a block which may have been tampered with are not indexed.
+        let res = save_block_added_with_integrity(
+            block_added,
+            event_id,
+            &event_source_address,
+            true,
+            false,
+            &mut transaction,
+        )
+        .await;
+        if res.is_ok() {
+            transaction.commit().await?;
+        }
+        observe_db_operation_time("save_block_added_failing_integrity", start);
+        res
+    }
+
     async fn save_deploy_accepted(
         &self,
         deploy_accepted: DeployAccepted,
         event_id: u32,
         event_source_address: String,
     ) -> Result<u64, DatabaseWriteError> {
-        #[cfg(feature = "additional-metrics")]
         let start = Instant::now();
         let mut transaction = self.get_transaction().await?;
         let json = serde_json::to_string(&deploy_accepted)?;
@@ -105,19 +117,30 @@ impl DatabaseWriter for $extended_type {
             )
             .await?;
 
-        let batched_insert_stmts = vec![
-            tables::deploy_accepted::create_insert_stmt(encoded_hash.clone(), json, event_log_id)?,
-            tables::deploy_event::create_insert_stmt(event_log_id, encoded_hash)?,
-        ]
-        .iter()
-        .map(|stmt| stmt.to_string($query_materializer_expr))
-        .join(";");
+        let deploy_accepted_stmt =
+            tables::deploy_accepted::create_insert_stmt(encoded_hash.clone(), json, event_log_id)?;
+        let insert_stmts = create_deploy_accepted_related_stmts(
+            &deploy_accepted,
+            &encoded_hash,
+            event_log_id,
+            self.index_deploy_timestamps,
+            &mut transaction,
+        )
+        .await?;
 
-        let res = handle_result(transaction.execute(batched_insert_stmts.as_str()).await);
+        let mut res = save_event(deploy_accepted_stmt, insert_stmts, &mut transaction).await;
+        if res.is_ok() {
+            // Approvals are saved even if the deploy was already stored, as a later acceptance may
+            // carry approvals the first one didn't.
+            if let Err(error) =
+                save_approvals(&deploy_accepted, encoded_hash, event_log_id, &mut transaction).await
+            {
+                res = Err(error);
+            }
+        }
         if res.is_ok() {
             transaction.commit().await?;
         }
-        #[cfg(feature = "additional-metrics")]
         observe_db_operation_time("save_deploy_accepted", start);
         res
     }
@@ -128,7 +151,6 @@ impl DatabaseWriter for $extended_type {
         event_id: u32,
         event_source_address: String,
     ) -> Result<u64, DatabaseWriteError> {
-        #[cfg(feature = "additional-metrics")]
         let start = Instant::now();
         let mut transaction = self.get_transaction().await?;
         let json = serde_json::to_string(&deploy_processed)?;
@@ -142,19 +164,22 @@ impl DatabaseWriter for $extended_type {
             )
             .await?;
 
-        let batched_insert_stmts = vec![
-            tables::deploy_processed::create_insert_stmt(encoded_hash.clone(), json, event_log_id)?,
-            tables::deploy_event::create_insert_stmt(event_log_id, encoded_hash)?,
-        ]
-        .iter()
-        .map(|stmt| stmt.to_string($query_materializer_expr))
-        .join(";");
+        let deploy_processed_stmt =
+            tables::deploy_processed::create_insert_stmt(encoded_hash.clone(), json, event_log_id)?;
+        let insert_stmts = create_deploy_processed_related_stmts(
+            &deploy_processed,
+            &encoded_hash,
+            event_log_id,
+            self.index_account_effects,
+            self.index_deploy_timestamps,
+            &mut transaction,
+        )
+        .await?;
 
-        let res = handle_result(transaction.execute(batched_insert_stmts.as_str()).await);
+        let res = save_event(deploy_processed_stmt, insert_stmts, &mut transaction).await;
         if res.is_ok() {
             transaction.commit().await?;
         }
-        #[cfg(feature = "additional-metrics")]
         observe_db_operation_time("save_deploy_processed", start);
         res
     }
@@ -165,7 +190,6 @@ impl DatabaseWriter for $extended_type {
         event_id: u32,
         event_source_address: String,
     ) -> Result<u64, DatabaseWriteError> {
-        #[cfg(feature = "additional-metrics")]
         let start = Instant::now();
         let mut transaction = self.get_transaction().await?;
         let json = serde_json::to_string(&deploy_expired)?;
@@ -179,19 +203,15 @@ impl DatabaseWriter for $extended_type {
             )
             .await?;
 
-        let batched_insert_stmts = vec![
-            tables::deploy_expired::create_insert_stmt(encoded_hash.clone(), event_log_id, json)?,
-            tables::deploy_event::create_insert_stmt(event_log_id, encoded_hash)?,
-        ]
-        .iter()
-        .map(|stmt| stmt.to_string($query_materializer_expr))
-        .join(";");
+        let deploy_expired_stmt =
+            tables::deploy_expired::create_insert_stmt(encoded_hash.clone(), event_log_id, json)?;
+        let insert_stmts =
+            vec![tables::deploy_event::create_insert_stmt(event_log_id, encoded_hash)?];
 
-        let res = handle_result(transaction.execute(batched_insert_stmts.as_str()).await);
+        let res = save_event(deploy_expired_stmt, insert_stmts, &mut transaction).await;
         if res.is_ok() {
             transaction.commit().await?;
         }
-        #[cfg(feature = "additional-metrics")]
         observe_db_operation_time("save_deploy_expired", start);
         res
     }
@@ -202,7 +222,6 @@ impl DatabaseWriter for $extended_type {
         event_id: u32,
         event_source_address: String,
     ) -> Result<u64, DatabaseWriteError> {
-        #[cfg(feature = "additional-metrics")]
         let start = Instant::now();
         let mut transaction = self.get_transaction().await?;
         let json = serde_json::to_string(&fault)?;
@@ -219,13 +238,11 @@ impl DatabaseWriter for $extended_type {
             .await?;
 
         let insert_stmt =
-            tables::fault::create_insert_stmt(era_id, public_key, json, event_log_id)?
-                .to_string($query_materializer_expr);
-        let res = handle_result(transaction.execute(insert_stmt.as_str()).await);
+            tables::fault::create_insert_stmt(era_id, public_key, json, event_log_id)?;
+        let res = save_event(insert_stmt, vec![], &mut transaction).await;
         if res.is_ok() {
             transaction.commit().await?;
         }
-        #[cfg(feature = "additional-metrics")]
         observe_db_operation_time("save_fault", start);
         res
     }
@@ -236,85 +253,320 @@ impl DatabaseWriter for $extended_type {
         event_id: u32,
         event_source_address: String,
     ) -> Result<u64, DatabaseWriteError> {
-        #[cfg(feature = "additional-metrics")]
         let start = Instant::now();
         let mut transaction = self.get_transaction().await?;
-        let json = serde_json::to_string(&finality_signature)?;
-        let block_hash = finality_signature.hex_encoded_block_hash();
-        let public_key = finality_signature.hex_encoded_public_key();
-        let event_key = format!("{block_hash} {public_key}");
+        let res = save_finality_signature_with_validity(
+            finality_signature,
+            event_id,
+            &event_source_address,
+            false,
+            &mut transaction,
+        )
+        .await;
+        if res.is_ok() {
+            transaction.commit().await?;
+        }
+        observe_db_operation_time("save_finality_signature", start);
+        res
+    }
+
+    async fn save_invalid_finality_signature(
+        &self,
+        finality_signature: FinalitySignature,
+        event_id: u32,
+        event_source_address: String,
+    ) -> Result<u64, DatabaseWriteError> {
+        let start = Instant::now();
+        let mut transaction = self.get_transaction().await?;
+        let res = save_finality_signature_with_validity(
+            finality_signature,
+            event_id,
+            &event_source_address,
+            true,
+            &mut transaction,
+        )
+        .await;
+        if res.is_ok() {
+            transaction.commit().await?;
+        }
+        observe_db_operation_time("save_invalid_finality_signature", start);
+        res
+    }
+
+    async fn save_step(
+        &self,
+        step: Step,
+        event_id: u32,
+        event_source_address: String,
+    ) -> Result<u64, DatabaseWriteError> {
+        let start = Instant::now();
+        let mut transaction = self.get_transaction().await?;
+        let json = serde_json::to_string(&step)?;
+        let era_id = step.era_id.value();
 
         let event_log_id = save_event_log(
-                EventTypeId::FinalitySignature as u8,
+                EventTypeId::Step as u8,
                 &event_source_address,
                 event_id,
-                &event_key,
+                &era_id.to_string(),
                 &mut transaction,
             )
             .await?;
 
-        let insert_stmt = tables::finality_signature::create_insert_stmt(
-            block_hash,
-            public_key,
-            json,
-            event_log_id,
-        )?
-        .to_string($query_materializer_expr);
+        let insert_stmt = tables::step::create_insert_stmt(era_id, json, event_log_id)?;
 
-        let res = handle_result(transaction.execute(insert_stmt.as_str()).await);
+        let res = save_event(insert_stmt, vec![], &mut transaction).await;
         if res.is_ok() {
             transaction.commit().await?;
         }
-        #[cfg(feature = "additional-metrics")]
-        observe_db_operation_time("save_finality_signature", start);
+        observe_db_operation_time("save_step", start);
         res
     }
 
-    async fn save_step(
+    async fn save_unknown_event(
         &self,
-        step: Step,
+        unknown_event: UnknownEvent,
         event_id: u32,
         event_source_address: String,
     ) -> Result<u64, DatabaseWriteError> {
-        #[cfg(feature = "additional-metrics")]
         let start = Instant::now();
         let mut transaction = self.get_transaction().await?;
-        let json = serde_json::to_string(&step)?;
-        let era_id = step.era_id.value();
+        let json = serde_json::to_string(&unknown_event)?;
+        let event_type = unknown_event.event_type;
 
         let event_log_id = save_event_log(
-                EventTypeId::Step as u8,
+                EventTypeId::Unknown as u8,
                 &event_source_address,
                 event_id,
-                &era_id.to_string(),
+                &event_type,
                 &mut transaction,
             )
             .await?;
 
-        let insert_stmt = tables::step::create_insert_stmt(era_id, json, event_log_id)?
-            .to_string($query_materializer_expr);
+        let insert_stmt =
+            tables::unknown_event::create_insert_stmt(event_type, json, event_log_id)?;
 
-        let res = handle_result(transaction.execute(insert_stmt.as_str()).await);
+        let res = save_event(insert_stmt, vec![], &mut transaction).await;
         if res.is_ok() {
             transaction.commit().await?;
         }
-        #[cfg(feature = "additional-metrics")]
-        observe_db_operation_time("save_step", start);
+        observe_db_operation_time("save_unknown_event", start);
         res
     }
 
+    async fn save_enrichment(&self, enrichment: Enrichment) -> Result<u64, DatabaseWriteError> {
+        let db_connection = &self.connection_pool;
+        let json = serde_json::to_string(&enrichment)?;
+
+        let insert_stmt =
+            tables::enrichment::create_insert_stmt(enrichment.entity_hash, enrichment.kind, json)?
+                .to_string($query_materializer_expr);
+
+        handle_result(db_connection.execute(insert_stmt.as_str()).await)
+    }
+
+    async fn save_derived_event(
+        &self,
+        derived_event: DerivedEvent,
+    ) -> Result<u64, DatabaseWriteError> {
+        let db_connection = &self.connection_pool;
+        let json = serde_json::to_string(&derived_event)?;
+
+        let insert_stmt = tables::derived_event::create_insert_stmt(derived_event.deriver, json)?
+            .to_string($query_materializer_expr);
+
+        handle_result(db_connection.execute(insert_stmt.as_str()).await)
+    }
+
+    async fn save_token_transfer(
+        &self,
+        token_transfer: TokenTransferEntry,
+    ) -> Result<u64, DatabaseWriteError> {
+        let db_connection = &self.connection_pool;
+        let json = serde_json::to_string(&token_transfer)?;
+
+        let insert_stmt = tables::token_transfer::create_insert_stmt(
+            token_transfer.contract_hash,
+            token_transfer.event_id,
+            token_transfer.deploy_hash,
+            json,
+        )?
+        .to_string($query_materializer_expr);
+
+        handle_result(db_connection.execute(insert_stmt.as_str()).await)
+    }
+
+    async fn save_nft_event(&self, nft_event: NftEventEntry) -> Result<u64, DatabaseWriteError> {
+        let db_connection = &self.connection_pool;
+        let json = serde_json::to_string(&nft_event)?;
+
+        let insert_stmt = tables::nft_event::create_insert_stmt(
+            nft_event.contract_hash,
+            nft_event.event_id,
+            nft_event.token_id,
+            nft_event.deploy_hash,
+            json,
+        )?
+        .to_string($query_materializer_expr);
+
+        handle_result(db_connection.execute(insert_stmt.as_str()).await)
+    }
+
+    async fn save_contract_event_schemas(
+        &self,
+        contract_event_schemas: ContractEventSchemas,
+    ) -> Result<u64, DatabaseWriteError> {
+        let db_connection = &self.connection_pool;
+        let json = serde_json::to_string(&contract_event_schemas)?;
+
+        let upsert_stmt = tables::contract_event_schema::create_upsert_stmt(
+            contract_event_schemas.contract_hash,
+            json,
+        )?
+        .to_string($query_materializer_expr);
+
+        handle_result(db_connection.execute(upsert_stmt.as_str()).await)
+    }
+
+    async fn save_contract_event(
+        &self,
+        contract_event: ContractEventEntry,
+    ) -> Result<u64, DatabaseWriteError> {
+        let db_connection = &self.connection_pool;
+        let json = serde_json::to_string(&contract_event)?;
+
+        let insert_stmt = tables::contract_event::create_insert_stmt(
+            contract_event.contract_hash,
+            contract_event.event_id,
+            contract_event.event_name,
+            contract_event.deploy_hash,
+            json,
+        )?
+        .to_string($query_materializer_expr);
+
+        handle_result(db_connection.execute(insert_stmt.as_str()).await)
+    }
+
+    async fn save_transfer(&self, transfer: TransferEntry) -> Result<u64, DatabaseWriteError> {
+        let db_connection = &self.connection_pool;
+        let json = serde_json::to_string(&transfer)?;
+
+        let insert_stmt = tables::transfer::create_insert_stmt(
+            transfer.transfer_hash,
+            transfer.deploy_hash,
+            transfer.block_hash,
+            transfer.from,
+            transfer.to,
+            transfer.id,
+            json,
+        )?
+        .to_string($query_materializer_expr);
+
+        handle_result(db_connection.execute(insert_stmt.as_str()).await)
+    }
+
+    async fn clear_derived_table(&self, table: DerivedTable) -> Result<u64, DatabaseWriteError> {
+        let db_connection = &self.connection_pool;
+
+        let delete_stmt = match table {
+            DerivedTable::Transfers => tables::transfer::create_delete_all_stmt(),
+            DerivedTable::TokenTransfers => tables::token_transfer::create_delete_all_stmt(),
+            DerivedTable::NftEvents => tables::nft_event::create_delete_all_stmt(),
+            DerivedTable::ContractEvents => tables::contract_event::create_delete_all_stmt(),
+        }
+        .to_string($query_materializer_expr);
+
+        handle_result(db_connection.execute(delete_stmt.as_str()).await)
+    }
+
+    async fn save_network_identity(
+        &self,
+        network_identity: NetworkIdentity,
+    ) -> Result<u64, DatabaseWriteError> {
+        let db_connection = &self.connection_pool;
+
+        let insert_stmt = tables::network_identity::create_insert_stmt(
+            network_identity.chainspec_name,
+            network_identity.starting_state_root_hash,
+        )?
+        .to_string($query_materializer_expr);
+
+        handle_result(db_connection.execute(insert_stmt.as_str()).await)
+    }
+
+    async fn save_finality_signature_checkpoint(
+        &self,
+        checkpoint: FinalitySignatureCheckpoint,
+    ) -> Result<u64, DatabaseWriteError> {
+        let mut transaction = self.get_transaction().await?;
+        let json = serde_json::to_string(&checkpoint)?;
+
+        let upsert_stmt = tables::finality_signature_checkpoint::create_upsert_stmt(
+            checkpoint.block_hash.clone(),
+            checkpoint.era_id,
+            json,
+        )?
+        .to_string($query_materializer_expr);
+        transaction.execute(upsert_stmt.as_str()).await?;
+        let delete_stmt =
+            tables::finality_signature::create_delete_by_block_stmt(checkpoint.block_hash)
+                .to_string($query_materializer_expr);
+        let deleted = handle_result(transaction.execute(delete_stmt.as_str()).await)?;
+        transaction.commit().await?;
+        Ok(deleted)
+    }
+
+    async fn save_webhook_delivery_attempt(
+        &self,
+        delivery_attempt: WebhookDeliveryAttempt,
+    ) -> Result<u64, DatabaseWriteError> {
+        let db_connection = &self.connection_pool;
+
+        let insert_stmt = tables::webhook_delivery::create_insert_stmt(
+            &delivery_attempt.webhook,
+            &delivery_attempt.event_type,
+            delivery_attempt.attempt,
+            delivery_attempt.timestamp,
+            delivery_attempt.status_code,
+            delivery_attempt.error.as_deref(),
+        )?
+        .to_string($query_materializer_expr);
+
+        handle_result(db_connection.execute(insert_stmt.as_str()).await)
+    }
+
+    async fn save_api_key(&self, api_key: StoredApiKey) -> Result<u64, DatabaseWriteError> {
+        let db_connection = &self.connection_pool;
+        let scopes = api_key.scopes.iter().map(|scope| scope.as_str()).join(",");
+
+        let insert_stmt = tables::api_key::create_insert_stmt(
+            &api_key.name,
+            &api_key.key_hash,
+            &scopes,
+            api_key.created_timestamp,
+        )?
+        .to_string($query_materializer_expr);
+
+        handle_result(db_connection.execute(insert_stmt.as_str()).await)
+    }
+
+    async fn delete_api_key(&self, name: &str) -> Result<u64, DatabaseWriteError> {
+        let db_connection = &self.connection_pool;
+
+        let delete_stmt =
+            tables::api_key::create_delete_stmt(name).to_string($query_materializer_expr);
+
+        handle_result(db_connection.execute(delete_stmt.as_str()).await)
+    }
+
     async fn save_shutdown(
         &self,
         event_id: u32,
         event_source_address: String,
     ) -> Result<u64, DatabaseWriteError> {
-        #[cfg(feature = "additional-metrics")]
         let start = Instant::now();
         let mut transaction = self.get_transaction().await?;
-        let unix_timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards")
-            .as_secs();
+        let unix_timestamp = time_since_epoch()?.as_secs();
         let event_key = format!("{}-{}", event_source_address, unix_timestamp);
 
         let event_log_id = save_event_log(
@@ -326,17 +578,149 @@ impl DatabaseWriter for $extended_type {
             )
             .await?;
 
-        let insert_stmt = tables::shutdown::create_insert_stmt(event_source_address, event_log_id)?
-            .to_string($query_materializer_expr);
-        let res = handle_result(transaction.execute(insert_stmt.as_str()).await);
+        let insert_stmt = tables::shutdown::create_insert_stmt(event_source_address, event_log_id)?;
+        let res = save_event(insert_stmt, vec![], &mut transaction).await;
         if res.is_ok() {
             transaction.commit().await?;
         }
-        #[cfg(feature = "additional-metrics")]
         observe_db_operation_time("save_shutdown", start);
         res
     }
 
+    async fn save_audit_entry(
+        &self,
+        actor: String,
+        action: String,
+        details: Option<String>,
+    ) -> Result<u64, DatabaseWriteError> {
+        let db_connection = &self.connection_pool;
+        let recorded_timestamp = time_since_epoch()?.as_secs();
+
+        let insert_stmt = tables::audit_log::create_insert_stmt(
+            recorded_timestamp,
+            &actor,
+            &action,
+            details.as_deref(),
+        )?
+        .to_string($query_materializer_expr);
+        let audit_log_id = db_connection
+            .fetch_one(insert_stmt.as_str())
+            .await?
+            .try_get::<i64, usize>(0)
+            .context("save_audit_entry: Error parsing audit_log_id from row")?
+            as u64;
+        Ok(audit_log_id)
+    }
+
+    async fn save_operational_event(
+        &self,
+        kind: String,
+        details: Option<String>,
+    ) -> Result<u64, DatabaseWriteError> {
+        let db_connection = &self.connection_pool;
+        let recorded_timestamp = time_since_epoch()?.as_secs();
+
+        let insert_stmt = tables::operational_event::create_insert_stmt(
+            recorded_timestamp,
+            &kind,
+            details.as_deref(),
+        )?
+        .to_string($query_materializer_expr);
+        let operational_event_id = db_connection
+            .fetch_one(insert_stmt.as_str())
+            .await?
+            .try_get::<i64, usize>(0)
+            .context("save_operational_event: Error parsing operational_event_id from row")?
+            as u64;
+        Ok(operational_event_id)
+    }
+
+    async fn prune_events(
+        &self,
+        table: PayloadTable,
+        maybe_stored_before: Option<u64>,
+        maybe_max_rows: Option<u64>,
+    ) -> Result<u64, DatabaseWriteError> {
+        let db_connection = &self.connection_pool;
+        let mut maybe_prune_up_to = None;
+        if let Some(stored_before) = maybe_stored_before {
+            let stmt = tables::event_log::create_get_last_inserted_before_stmt(
+                &tables::event_log::timestamp_literal(stored_before),
+            )
+            .to_string($query_materializer_expr);
+            maybe_prune_up_to = db_connection
+                .fetch_one(stmt.as_str())
+                .await?
+                .try_get::<Option<i64>, usize>(0)
+                .context("prune_events: Error parsing event_log_id from row")?
+                .map(|event_log_id| event_log_id as u64);
+        }
+        if let Some(max_rows) = maybe_max_rows {
+            let stmt = tables::raw_payload::create_get_nth_newest_event_log_id_stmt(
+                table.table_name(),
+                max_rows,
+            )
+            .to_string($query_materializer_expr);
+            if let Some(row) = db_connection.fetch_optional(stmt.as_str()).await? {
+                let event_log_id = row
+                    .try_get::<i64, usize>(0)
+                    .context("prune_events: Error parsing event_log_id from row")?
+                    as u64;
+                maybe_prune_up_to = maybe_prune_up_to.max(Some(event_log_id));
+            }
+        }
+        match maybe_prune_up_to {
+            Some(prune_up_to) => {
+                let delete_stmt =
+                    tables::raw_payload::create_delete_up_to_stmt(table.table_name(), prune_up_to)
+                        .to_string($query_materializer_expr);
+                handle_result(db_connection.execute(delete_stmt.as_str()).await)
+            }
+            None => Ok(0),
+        }
+    }
+
+    async fn initialize_event_id_allocator(
+        &self,
+        next_event_id: u32,
+    ) -> Result<u32, DatabaseWriteError> {
+        let mut transaction = self.connection_pool.begin().await?;
+        let updated_timestamp = time_since_epoch()?.as_secs();
+
+        let insert_stmt =
+            tables::event_id_allocator::create_initialise_stmt(next_event_id, updated_timestamp)?
+                .to_string($query_materializer_expr);
+        transaction.execute(insert_stmt.as_str()).await?;
+        let select_stmt = tables::event_id_allocator::create_get_stmt()
+            .to_string($query_materializer_expr);
+        let stored_next_event_id = transaction
+            .fetch_one(select_stmt.as_str())
+            .await?
+            .try_get::<i64, usize>(0)
+            .context("initialize_event_id_allocator: Error parsing next_event_id from row")?
+            as u32;
+        transaction.commit().await?;
+        Ok(stored_next_event_id)
+    }
+
+    async fn reserve_event_ids(&self, count: u32) -> Result<u32, DatabaseWriteError> {
+        let db_connection = &self.connection_pool;
+        let updated_timestamp = time_since_epoch()?.as_secs();
+
+        let update_stmt = tables::event_id_allocator::create_reserve_stmt(count, updated_timestamp)
+            .to_string($query_materializer_expr);
+        let next_event_id = db_connection
+            .fetch_one(update_stmt.as_str())
+            .await?
+            .try_get::<i64, usize>(0)
+            .context("reserve_event_ids: Error parsing next_event_id from row")?
+            as u64;
+        Ok(tables::event_id_allocator::first_reserved_event_id(next_event_id, count))
+    }
+}
+
+#[async_trait]
+impl MigrationExecutor for $extended_type {
     async fn execute_migration(&self, migration: Migration) -> Result<(), DatabaseWriteError> {
         let transaction = self.connection_pool.begin().await?;
         let transaction_shared = Arc::new(Mutex::new(transaction));
@@ -350,7 +734,13 @@ impl DatabaseWriter for $extended_type {
                 let sqls = materialize_statements(migration.get_migrations($database_specific_configuration)?);
                 sqls.iter().join(";")
             };
-            match wrapper_arc.clone().execute(sql.as_str()).await {
+            // Migrations which don't apply to this database produce no statements
+            let execution_result = if sql.is_empty() {
+                Ok(())
+            } else {
+                wrapper_arc.clone().execute(sql.as_str()).await
+            };
+            match execution_result {
                 Ok(_) => {
                     if let Some(script_executor) = migration.script_executor {
                         script_executor.execute(wrapper_arc.clone()).await
@@ -380,6 +770,12 @@ fn materialize_statements(wrappers: Vec<StatementWrapper>) -> Vec<String> {
             StatementWrapper::TableCreateStatement(statement) => {
                 statement.to_string($query_materializer_expr)
             }
+            StatementWrapper::IndexCreateStatement(statement) => {
+                statement.to_string($query_materializer_expr)
+            }
+            StatementWrapper::TableAlterStatement(statement) => {
+                statement.to_string($query_materializer_expr)
+            }
             StatementWrapper::InsertStatement(statement) => statement.to_string($query_materializer_expr),
             StatementWrapper::Raw(sql) => sql.to_string(),
         })
@@ -394,6 +790,196 @@ fn handle_result(
         .map_err(std::convert::From::from)
 }
 
+/// Returns the statement adding the outcome of the deploy to the daily rollup of the contract its
+/// session calls, if it calls one.
+fn create_contract_stats_stmt(
+    deploy_accepted: &DeployAccepted,
+    deploy_processed: &DeployProcessed,
+) -> Result<Option<sea_query::InsertStatement>, DatabaseWriteError> {
+    let contract_hash = match deploy_accepted.hex_encoded_target_contract() {
+        Some(contract_hash) => contract_hash,
+        None => return Ok(None),
+    };
+    let stmt = tables::contract_stats::create_upsert_stmt(
+        contract_hash,
+        deploy_processed.timestamp_day(),
+        deploy_processed.error_message().is_none(),
+        deploy_processed.gas_spent(),
+    )?;
+    Ok(Some(stmt))
+}
+
+/// Returns the statements storing the rows related to an accepted deploy, other than its
+/// approvals.
+async fn create_deploy_accepted_related_stmts(
+    deploy_accepted: &DeployAccepted,
+    encoded_hash: &str,
+    event_log_id: u64,
+    index_deploy_timestamps: bool,
+    transaction: &mut Transaction<'_, $database_type>,
+) -> Result<Vec<sea_query::InsertStatement>, DatabaseWriteError> {
+    let mut insert_stmts = vec![
+        tables::deploy_event::create_insert_stmt(event_log_id, encoded_hash.to_string())?,
+        tables::deploy_account::create_insert_stmt(
+            encoded_hash.to_string(),
+            deploy_accepted.hex_encoded_account_hash(),
+        )?,
+        tables::deploy_size::create_insert_stmt(
+            encoded_hash.to_string(),
+            deploy_accepted.serialized_length() as u64,
+            deploy_accepted.approvals_count() as u64,
+            deploy_accepted.args_count() as u64,
+        )?,
+    ];
+    // Deploys processed before being accepted are rolled up once their session is known.
+    let select_processed_stmt =
+        tables::deploy_processed::create_get_by_hash_stmt(encoded_hash.to_string());
+    if let Some(deploy_processed) =
+        fetch_stored::<DeployProcessed>(select_processed_stmt, transaction).await?
+    {
+        insert_stmts.extend(create_contract_stats_stmt(deploy_accepted, &deploy_processed)?);
+    }
+    if index_deploy_timestamps {
+        let accepted_timestamp = time_since_epoch()?.as_millis() as u64;
+        insert_stmts.push(tables::deploy_timestamp::create_insert_stmt(
+            encoded_hash.to_string(),
+            EventTypeId::DeployAccepted as u8,
+            accepted_timestamp,
+        )?);
+    }
+    Ok(insert_stmts)
+}
+
+/// Returns the statements storing the rows related to a processed deploy.
+async fn create_deploy_processed_related_stmts(
+    deploy_processed: &DeployProcessed,
+    encoded_hash: &str,
+    event_log_id: u64,
+    index_account_effects: bool,
+    index_deploy_timestamps: bool,
+    transaction: &mut Transaction<'_, $database_type>,
+) -> Result<Vec<sea_query::InsertStatement>, DatabaseWriteError> {
+    let mut insert_stmts = vec![
+        tables::deploy_event::create_insert_stmt(event_log_id, encoded_hash.to_string())?,
+    ];
+    if let Some(error_message) = deploy_processed.error_message() {
+        insert_stmts.push(tables::deploy_error::create_insert_stmt(
+            encoded_hash.to_string(),
+            deploy_processed.hex_encoded_block_hash(),
+            error_message.to_string(),
+        )?);
+    }
+    insert_stmts.extend(create_transfer_stmts(deploy_processed)?);
+    if index_account_effects {
+        for account_hash in deploy_processed.hex_encoded_affected_account_hashes() {
+            insert_stmts.push(tables::account_effect::create_insert_stmt(
+                account_hash,
+                encoded_hash.to_string(),
+            )?);
+        }
+    }
+    let select_accepted_stmt =
+        tables::deploy_accepted::create_get_by_hash_stmt(encoded_hash.to_string());
+    if let Some(deploy_accepted) =
+        fetch_stored::<DeployAccepted>(select_accepted_stmt, transaction).await?
+    {
+        insert_stmts.extend(create_contract_stats_stmt(&deploy_accepted, deploy_processed)?);
+    }
+    if index_deploy_timestamps {
+        // Deploys processed before their block was stored are indexed when the block is saved.
+        let select_block_stmt =
+            tables::block_added::create_get_by_hash_stmt(deploy_processed.hex_encoded_block_hash());
+        if let Some(block_added) = fetch_stored::<BlockAdded>(select_block_stmt, transaction).await? {
+            insert_stmts.push(tables::deploy_timestamp::create_insert_stmt(
+                encoded_hash.to_string(),
+                EventTypeId::DeployProcessed as u8,
+                block_added.get_timestamp().millis(),
+            )?);
+        }
+    }
+    Ok(insert_stmts)
+}
+
+fn create_transfer_stmts(
+    deploy_processed: &DeployProcessed,
+) -> Result<Vec<sea_query::InsertStatement>, DatabaseWriteError> {
+    TransferEntry::of_deploy_processed(deploy_processed)
+        .into_iter()
+        .map(|transfer| {
+            Ok(tables::transfer::create_insert_stmt(
+                transfer.transfer_hash.clone(),
+                transfer.deploy_hash.clone(),
+                transfer.block_hash.clone(),
+                transfer.from.clone(),
+                transfer.to.clone(),
+                transfer.id,
+                serde_json::to_string(&transfer)?,
+            )?)
+        })
+        .collect()
+}
+
+/// Returns the stored event selected by `select_stmt`, if any.
+async fn fetch_stored<T: serde::de::DeserializeOwned>(
+    select_stmt: sea_query::SelectStatement,
+    transaction: &mut Transaction<'_, $database_type>,
+) -> Result<Option<T>, DatabaseWriteError> {
+    let select_stmt = select_stmt.to_string($query_materializer_expr);
+    match transaction.fetch_optional(select_stmt.as_str()).await? {
+        Some(row) => {
+            let raw = row.try_get::<String, &str>("raw")?;
+            Ok(Some(serde_json::from_str::<T>(&raw)?))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Stores the approvals of an accepted deploy which aren't stored yet.
+async fn save_approvals(
+    deploy_accepted: &DeployAccepted,
+    encoded_hash: String,
+    event_log_id: u64,
+    transaction: &mut Transaction<'_, $database_type>,
+) -> Result<(), DatabaseWriteError> {
+    let approval_stmts = deploy_accepted
+        .hex_encoded_approval_signers()
+        .into_iter()
+        .map(|signer| {
+            tables::deploy_approval::create_insert_stmt(encoded_hash.clone(), signer, event_log_id)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if approval_stmts.is_empty() {
+        return Ok(());
+    }
+    let batched_approval_stmts = approval_stmts
+        .iter()
+        .map(|stmt| stmt.to_string($query_materializer_expr))
+        .join(";");
+    handle_result(transaction.execute(batched_approval_stmts.as_str()).await)?;
+    Ok(())
+}
+
+/// Stores an event with `event_stmt`, and then the rows related to it with `related_stmts` unless
+/// it was already stored. Returns the number of events stored, 0 if it already was.
+async fn save_event(
+    event_stmt: sea_query::InsertStatement,
+    related_stmts: Vec<sea_query::InsertStatement>,
+    transaction: &mut Transaction<'_, $database_type>,
+) -> Result<u64, DatabaseWriteError> {
+    let event_stmt = event_stmt.to_string($query_materializer_expr);
+    let stored = handle_result(transaction.execute(event_stmt.as_str()).await)?;
+    // Related rows such as contract rollups aren't idempotent, so they are only stored once.
+    if stored == 0 || related_stmts.is_empty() {
+        return Ok(stored);
+    }
+    let batched_related_stmts = related_stmts
+        .iter()
+        .map(|stmt| stmt.to_string($query_materializer_expr))
+        .join(";");
+    handle_result(transaction.execute(batched_related_stmts.as_str()).await)?;
+    Ok(stored)
+}
+
 async fn save_event_log(
     event_type_id: u8,
     event_source_address: &str,
@@ -417,7 +1003,110 @@ async fn save_event_log(
     Ok(event_log_id)
 }
 
-#[cfg(feature = "additional-metrics")]
+async fn save_block_added_with_integrity(
+    block_added: BlockAdded,
+    event_id: u32,
+    event_source_address: &str,
+    integrity_failed: bool,
+    index_deploy_timestamps: bool,
+    transaction: &mut Transaction<'_, $database_type>,
+) -> Result<u64, DatabaseWriteError> {
+    let json = serde_json::to_string(&block_added)?;
+    let encoded_hash = block_added.hex_encoded_hash();
+    let event_log_id = save_event_log(
+        EventTypeId::BlockAdded as u8,
+        event_source_address,
+        event_id,
+        &encoded_hash,
+        transaction,
+    )
+    .await?;
+
+    let block_added_stmt = tables::block_added::create_insert_stmt(
+        block_added.get_height(),
+        encoded_hash,
+        json,
+        event_log_id,
+        integrity_failed,
+    )?;
+    let insert_stmts =
+        create_block_added_related_stmts(&block_added, integrity_failed, index_deploy_timestamps)?;
+
+    save_event(block_added_stmt, insert_stmts, transaction).await
+}
+
+/// Returns the statements storing the validator weights of the next era announced by the block,
+/// unless it failed the integrity checks, and the timestamps of its deploys if they are indexed.
+fn create_block_added_related_stmts(
+    block_added: &BlockAdded,
+    integrity_failed: bool,
+    index_deploy_timestamps: bool,
+) -> Result<Vec<sea_query::InsertStatement>, DatabaseWriteError> {
+    let mut insert_stmts = vec![];
+    if !integrity_failed {
+        if let Some((next_era_id, validator_weights)) = block_added.next_era_validator_weights() {
+            for (public_key, weight) in validator_weights {
+                insert_stmts.push(tables::era_validator::create_insert_stmt(
+                    next_era_id,
+                    public_key,
+                    weight.to_string(),
+                )?);
+            }
+        }
+    }
+    if index_deploy_timestamps {
+        let timestamp = block_added.get_timestamp().millis();
+        for deploy_hash in block_added.hex_encoded_deploy_hashes() {
+            insert_stmts.push(tables::deploy_timestamp::create_insert_stmt(
+                deploy_hash,
+                EventTypeId::DeployProcessed as u8,
+                timestamp,
+            )?);
+        }
+    }
+    Ok(insert_stmts)
+}
+
+async fn save_finality_signature_with_validity(
+    finality_signature: FinalitySignature,
+    event_id: u32,
+    event_source_address: &str,
+    invalid: bool,
+    transaction: &mut Transaction<'_, $database_type>,
+) -> Result<u64, DatabaseWriteError> {
+    let json = serde_json::to_string(&finality_signature)?;
+    let block_hash = finality_signature.hex_encoded_block_hash();
+    let public_key = finality_signature.hex_encoded_public_key();
+    let event_key = format!("{block_hash} {public_key}");
+
+    let event_log_id = save_event_log(
+        EventTypeId::FinalitySignature as u8,
+        event_source_address,
+        event_id,
+        &event_key,
+        transaction,
+    )
+    .await?;
+
+    let insert_stmt = tables::finality_signature::create_insert_stmt(
+        block_hash,
+        public_key,
+        json,
+        event_log_id,
+        invalid,
+    )?;
+
+    save_event(insert_stmt, vec![], transaction).await
+}
+
+/// The time elapsed since the UNIX epoch, failing rather than panicking if the system clock is set
+/// before it.
+fn time_since_epoch() -> Result<std::time::Duration, DatabaseWriteError> {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|error| DatabaseWriteError::Unhandled(anyhow::Error::new(error)))
+}
+
 fn observe_db_operation_time(operation_name: &str, start: Instant) {
     let duration = start.elapsed();
     metrics::DB_OPERATION_TIMES
@@ -0,0 +1,268 @@
+//! Deduplication of the events received from several nodes at once.
+//!
+//! Unless failover is configured, the events of every configured node are streamed at once, so
+//! each event is received once from every node, and the node failed over to sends the events of
+//! its event stream from its start again. The `BlockAdded`, `DeployProcessed` and
+//! `FinalitySignature` events are identified by the hash of their serialized content, so a copy
+//! altered by a node doesn't pass for the copies of the other nodes. Once a copy is stored, the
+//! later copies, whichever node they come from, are dropped before reaching the database. Copies
+//! received while the first one is being stored are passed on, the database recognising those
+//! already stored by their key, so that an event is not lost if storing its first copy fails. The
+//! other events are passed on as they are.
+
+use crate::{clock_skew::node_label, source_scoring::SourceRecorder};
+use casper_event_listener::SseEvent;
+use casper_event_types::{metrics::DEDUPLICATED_EVENTS, sse_data::SseData};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::{Arc, Mutex},
+};
+use tokio::sync::mpsc::{Receiver, Sender};
+
+/// Number of content hashes remembered. Copies of an event received once this many events were
+/// stored after it are passed on again.
+const SEEN_EVENTS_CAPACITY: usize = 50_000;
+
+/// Hashes of the content of the events stored, forgetting the oldest once `capacity` is reached.
+struct SeenEvents {
+    capacity: usize,
+    hashes: HashSet<[u8; 32]>,
+    insertion_order: VecDeque<[u8; 32]>,
+}
+
+impl SeenEvents {
+    fn new(capacity: usize) -> Self {
+        SeenEvents {
+            capacity,
+            hashes: HashSet::new(),
+            insertion_order: VecDeque::new(),
+        }
+    }
+
+    fn contains(&self, hash: &[u8; 32]) -> bool {
+        self.hashes.contains(hash)
+    }
+
+    /// Records `hash`, returning whether it wasn't seen yet.
+    fn insert(&mut self, hash: [u8; 32]) -> bool {
+        if !self.hashes.insert(hash) {
+            return false;
+        }
+        self.insertion_order.push_back(hash);
+        while self.insertion_order.len() > self.capacity {
+            if let Some(oldest) = self.insertion_order.pop_front() {
+                self.hashes.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+/// Hash of the serialized content of the event, `None` if events of its type aren't deduplicated.
+fn content_hash(data: &SseData) -> Option<[u8; 32]> {
+    if !matches!(
+        data,
+        SseData::BlockAdded { .. }
+            | SseData::DeployProcessed { .. }
+            | SseData::FinalitySignature(_)
+    ) {
+        return None;
+    }
+    let serialized = serde_json::to_vec(data).ok()?;
+    Some(Sha256::digest(serialized).into())
+}
+
+/// Deduplicator shared by the event streams of all the nodes.
+#[derive(Clone)]
+pub(crate) struct Deduplicator {
+    seen_events: Arc<Mutex<SeenEvents>>,
+}
+
+impl Deduplicator {
+    pub(crate) fn new() -> Self {
+        Deduplicator {
+            seen_events: Arc::new(Mutex::new(SeenEvents::new(SEEN_EVENTS_CAPACITY))),
+        }
+    }
+
+    /// Returns whether no copy of the event was stored yet, recording which node sent it. The
+    /// copies passed on carry the hash of their content, to be acknowledged once stored.
+    fn is_first_copy(&self, sse_event: &mut SseEvent) -> bool {
+        let Some(hash) = content_hash(&sse_event.data) else {
+            return true;
+        };
+        let first = !self
+            .seen_events
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .contains(&hash);
+        DEDUPLICATED_EVENTS
+            .with_label_values(&[
+                sse_event.data.type_label(),
+                &node_label(&sse_event.source),
+                if first { "first" } else { "duplicate" },
+            ])
+            .inc();
+        if first {
+            sse_event.content_hash = Some(hash);
+        }
+        first
+    }
+
+    /// Records that the event of the given content hash was stored, so that its later copies are
+    /// dropped.
+    pub(crate) fn acknowledge_stored(&self, content_hash: [u8; 32]) {
+        self.seen_events
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(content_hash);
+    }
+
+    /// Passes on the events received from a node on `inbound` to `deduplicated`, dropping the
+    /// copies of the events already received from another node. Dropped copies are still recorded
+    /// as ingested from the node by `source_recorder`, as its ingestion doesn't lag behind.
+    pub(crate) fn start_deduplicating(
+        &self,
+        mut inbound: Receiver<SseEvent>,
+        deduplicated: Sender<SseEvent>,
+        source_recorder: SourceRecorder,
+    ) {
+        let deduplicator = self.clone();
+        tokio::spawn(async move {
+            while let Some(mut sse_event) = inbound.recv().await {
+                if !deduplicator.is_first_copy(&mut sse_event) {
+                    source_recorder.record(&sse_event.data);
+                    continue;
+                }
+                if deduplicated.send(sse_event).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source_scoring::SourceScores;
+    use casper_event_types::Filter;
+    use casper_types::testing::TestRng;
+    use reqwest::Url;
+    use tokio::sync::mpsc::channel;
+
+    fn sse_event_from(node: &str, data: SseData) -> SseEvent {
+        SseEvent::new(
+            1,
+            data,
+            Url::parse(&format!("http://{}/events/main", node)).unwrap(),
+            None,
+            Filter::Main,
+        )
+    }
+
+    #[test]
+    fn should_forget_oldest_hashes_beyond_capacity() {
+        let mut seen_events = SeenEvents::new(2);
+
+        assert!(seen_events.insert([1; 32]));
+        assert!(!seen_events.insert([1; 32]));
+        assert!(seen_events.insert([2; 32]));
+        assert!(seen_events.insert([3; 32]));
+        assert!(seen_events.insert([1; 32]));
+        assert!(!seen_events.insert([3; 32]));
+    }
+
+    /// Sends `events` from `node` through `deduplicator`, returning the events passed on.
+    async fn deduplicate(
+        deduplicator: &Deduplicator,
+        node: &str,
+        events: &[&SseData],
+    ) -> Vec<SseEvent> {
+        let scores = SourceScores::default();
+        let (inbound_sender, inbound_receiver) = channel(10);
+        let (deduplicated_sender, mut deduplicated_receiver) = channel(10);
+        deduplicator.start_deduplicating(inbound_receiver, deduplicated_sender, scores.recorder(0));
+        for data in events {
+            inbound_sender
+                .send(sse_event_from(node, (*data).clone()))
+                .await
+                .unwrap();
+        }
+        drop(inbound_sender);
+        let mut received = Vec::new();
+        while let Some(sse_event) = deduplicated_receiver.recv().await {
+            received.push(sse_event);
+        }
+        received
+    }
+
+    fn type_labels(sse_events: &[SseEvent]) -> Vec<&'static str> {
+        sse_events
+            .iter()
+            .map(|sse_event| sse_event.data.type_label())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn should_drop_copies_of_stored_events() {
+        let mut rng = TestRng::new();
+        let block_added = SseData::random_block_added(&mut rng);
+        let finality_signature = SseData::random_finality_signature(&mut rng);
+        let deploy_expired = SseData::random_deploy_expired(&mut rng);
+        let events = [&block_added, &finality_signature, &deploy_expired];
+        let deduplicator = Deduplicator::new();
+
+        let first_copies = deduplicate(&deduplicator, "10.0.0.1:9999", &events).await;
+        assert_eq!(
+            type_labels(&first_copies),
+            vec!["BlockAdded", "FinalitySignature", "DeployExpired"]
+        );
+        assert!(first_copies[2].content_hash.is_none());
+        for content_hash in first_copies.iter().filter_map(|event| event.content_hash) {
+            deduplicator.acknowledge_stored(content_hash);
+        }
+
+        let second_copies = deduplicate(&deduplicator, "10.0.0.2:9999", &events).await;
+        assert_eq!(type_labels(&second_copies), vec!["DeployExpired"]);
+    }
+
+    #[tokio::test]
+    async fn should_pass_on_copies_of_events_not_stored_yet() {
+        let mut rng = TestRng::new();
+        let block_added = SseData::random_block_added(&mut rng);
+        let deduplicator = Deduplicator::new();
+
+        let first_copies = deduplicate(&deduplicator, "10.0.0.1:9999", &[&block_added]).await;
+        let second_copies = deduplicate(&deduplicator, "10.0.0.2:9999", &[&block_added]).await;
+
+        assert_eq!(type_labels(&first_copies), vec!["BlockAdded"]);
+        assert_eq!(type_labels(&second_copies), vec!["BlockAdded"]);
+    }
+
+    #[test]
+    fn should_hash_the_whole_content_of_the_event() {
+        let mut rng = TestRng::new();
+        let block_added = SseData::random_block_added(&mut rng);
+        let SseData::BlockAdded { block_hash, block } = block_added.clone() else {
+            unreachable!()
+        };
+        let mut altered_block = block;
+        altered_block.header.height += 1;
+        let altered_block_added = SseData::BlockAdded {
+            block_hash,
+            block: altered_block,
+        };
+
+        assert!(content_hash(&block_added).is_some());
+        assert_ne!(
+            content_hash(&block_added),
+            content_hash(&altered_block_added)
+        );
+        assert_eq!(
+            content_hash(&SseData::random_deploy_expired(&mut rng)),
+            None
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! Progress of the signing of multi-signature deploys.
+//!
+//! A deploy is only executed if the weights of the associated keys of its account which approved
+//! it add up to the account's deployment threshold. The approvals of a deploy are stored as the
+//! node accepts it, including those only carried by a later acceptance of the same deploy. The
+//! progress compares the stored approvals with the associated keys and deployment threshold of the
+//! account in the latest global state of the node proxied for state proofs, if enabled.
+
+use crate::types::{database::DeployApprovalEntry, sse_events::DeployAccepted};
+use anyhow::{anyhow, Error};
+use casper_types::{AsymmetricType, PublicKey};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{collections::HashMap, convert::TryFrom};
+use utoipa::ToSchema;
+
+/// Prefix of the formatted account hashes of the associated keys.
+const ACCOUNT_HASH_PREFIX: &str = "account-hash-";
+
+/// The approvals of a deploy along with the weight they collected.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, ToSchema)]
+pub struct DeployApprovals {
+    deploy_hash: String,
+    /// Hex-encoded hash of the account which sent the deploy.
+    account_hash: String,
+    approvals: Vec<SignerApproval>,
+    /// Sum of the weights of the signers, if the keys of the account are known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    collected_weight: Option<u32>,
+    /// Weight the approvals need to collect for the deploy to be executed, if the keys of the
+    /// account are known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deployment_threshold: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    threshold_reached: Option<bool>,
+}
+
+/// An approval of a deploy.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, ToSchema)]
+pub struct SignerApproval {
+    /// Hex-encoded public key of the signer.
+    signer: String,
+    /// Weight of the key of the signer for the account, `0` if it isn't one of its associated
+    /// keys, if the keys of the account are known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    weight: Option<u8>,
+    /// Whether the approval was only carried by a later acceptance of the deploy.
+    added_later: bool,
+}
+
+/// The associated keys and deployment threshold of an account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AccountKeys {
+    /// Weights of the associated keys, by hex-encoded account hash.
+    weights: HashMap<String, u8>,
+    deployment_threshold: u8,
+}
+
+impl AccountKeys {
+    /// Reads the keys from the result of the node's `query_global_state` for an account hash key.
+    pub(crate) fn from_query_result(result: &Value) -> Result<Self, Error> {
+        let account = result
+            .pointer("/stored_value/Account")
+            .ok_or_else(|| anyhow!("no account in the stored value"))?;
+        let weight_of = |value: &Value| {
+            value
+                .as_u64()
+                .and_then(|weight| u8::try_from(weight).ok())
+                .ok_or_else(|| anyhow!("invalid key weight: {}", value))
+        };
+        let mut weights = HashMap::new();
+        for associated_key in account["associated_keys"]
+            .as_array()
+            .ok_or_else(|| anyhow!("no associated keys in the account"))?
+        {
+            let account_hash = associated_key["account_hash"]
+                .as_str()
+                .ok_or_else(|| anyhow!("no account hash in the associated key"))?;
+            weights.insert(
+                account_hash
+                    .trim_start_matches(ACCOUNT_HASH_PREFIX)
+                    .to_string(),
+                weight_of(&associated_key["weight"])?,
+            );
+        }
+        Ok(AccountKeys {
+            weights,
+            deployment_threshold: weight_of(&account["action_thresholds"]["deployment"])?,
+        })
+    }
+
+    /// Weight of the key of the signer, `0` if it isn't associated with the account.
+    fn weight_of(&self, signer: &str) -> u8 {
+        PublicKey::from_hex(signer)
+            .ok()
+            .and_then(|public_key| {
+                let account_hash = hex::encode(public_key.to_account_hash().value());
+                self.weights.get(&account_hash).copied()
+            })
+            .unwrap_or(0)
+    }
+}
+
+impl DeployApprovals {
+    /// Builds the progress of the signing of `deploy_accepted` from its stored approvals `entries`
+    /// and, if known, the keys of its account. A deploy stored without its approvals is given the
+    /// approvals of its stored acceptance.
+    pub(crate) fn new(
+        deploy_accepted: &DeployAccepted,
+        entries: Vec<DeployApprovalEntry>,
+        maybe_account_keys: Option<&AccountKeys>,
+    ) -> Self {
+        let approvals: Vec<SignerApproval> = signers(deploy_accepted, entries)
+            .into_iter()
+            .map(|(signer, added_later)| SignerApproval {
+                weight: maybe_account_keys.map(|account_keys| account_keys.weight_of(&signer)),
+                signer,
+                added_later,
+            })
+            .collect();
+        let collected_weight = maybe_account_keys.map(|_| {
+            approvals
+                .iter()
+                .filter_map(|approval| approval.weight)
+                .map(u32::from)
+                .sum()
+        });
+        let deployment_threshold =
+            maybe_account_keys.map(|account_keys| account_keys.deployment_threshold);
+        let threshold_reached = collected_weight
+            .zip(deployment_threshold)
+            .map(|(collected, threshold)| collected >= u32::from(threshold));
+        DeployApprovals {
+            deploy_hash: deploy_accepted.hex_encoded_hash(),
+            account_hash: deploy_accepted.hex_encoded_account_hash(),
+            approvals,
+            collected_weight,
+            deployment_threshold,
+            threshold_reached,
+        }
+    }
+}
+
+/// The signers of the deploy, each with whether it was added after the deploy was first stored.
+fn signers(
+    deploy_accepted: &DeployAccepted,
+    entries: Vec<DeployApprovalEntry>,
+) -> Vec<(String, bool)> {
+    if entries.is_empty() {
+        return deploy_accepted
+            .hex_encoded_approval_signers()
+            .into_iter()
+            .map(|signer| (signer, false))
+            .collect();
+    }
+    let first_event_log_id = entries.iter().map(|entry| entry.event_log_id).min();
+    entries
+        .into_iter()
+        .map(|entry| {
+            let added_later = Some(entry.event_log_id) != first_event_log_id;
+            (entry.signer, added_later)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use casper_types::{testing::TestRng, SecretKey};
+    use serde_json::json;
+
+    fn account_hash_of(secret_key: &SecretKey) -> String {
+        hex::encode(PublicKey::from(secret_key).to_account_hash().value())
+    }
+
+    /// The keys of the account of the deploy: its own with a weight of 1 and the cosigner's with a
+    /// weight of 2, with a deployment threshold of 3.
+    fn account_keys(deploy_accepted: &DeployAccepted, cosigner: &SecretKey) -> AccountKeys {
+        AccountKeys::from_query_result(&json!({
+            "stored_value": {
+                "Account": {
+                    "associated_keys": [
+                        {
+                            "account_hash": format!(
+                                "account-hash-{}",
+                                deploy_accepted.hex_encoded_account_hash()
+                            ),
+                            "weight": 1
+                        },
+                        {
+                            "account_hash": format!("account-hash-{}", account_hash_of(cosigner)),
+                            "weight": 2
+                        }
+                    ],
+                    "action_thresholds": { "deployment": 3, "key_management": 3 }
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn should_compare_collected_weight_with_threshold() {
+        let mut rng = TestRng::new();
+        let cosigner = SecretKey::random(&mut rng);
+        let stranger = SecretKey::random(&mut rng);
+        let deploy_accepted = DeployAccepted::random(&mut rng);
+        let sender_signer = deploy_accepted.hex_encoded_approval_signers().remove(0);
+        let account_keys = account_keys(&deploy_accepted, &cosigner);
+        let entry = |signer: String, event_log_id| DeployApprovalEntry {
+            deploy_hash: deploy_accepted.hex_encoded_hash(),
+            signer,
+            event_log_id,
+        };
+        let stranger_signer = PublicKey::from(&stranger).to_hex();
+        let cosigner_signer = PublicKey::from(&cosigner).to_hex();
+
+        let partial = DeployApprovals::new(
+            &deploy_accepted,
+            vec![
+                entry(sender_signer.clone(), 1),
+                entry(stranger_signer.clone(), 1),
+            ],
+            Some(&account_keys),
+        );
+        assert_eq!(partial.collected_weight, Some(1));
+        assert_eq!(partial.threshold_reached, Some(false));
+        assert_eq!(partial.approvals[1].weight, Some(0));
+
+        let complete = DeployApprovals::new(
+            &deploy_accepted,
+            vec![
+                entry(sender_signer, 1),
+                entry(stranger_signer, 1),
+                entry(cosigner_signer, 4),
+            ],
+            Some(&account_keys),
+        );
+        assert_eq!(complete.collected_weight, Some(3));
+        assert_eq!(complete.deployment_threshold, Some(3));
+        assert_eq!(complete.threshold_reached, Some(true));
+        assert!(!complete.approvals[0].added_later);
+        assert!(complete.approvals[2].added_later);
+    }
+
+    #[test]
+    fn should_list_approvals_of_deploy_without_known_keys() {
+        let mut rng = TestRng::new();
+        let deploy_accepted = DeployAccepted::random(&mut rng);
+
+        let approvals = DeployApprovals::new(&deploy_accepted, vec![], None);
+
+        assert_eq!(approvals.approvals.len(), 1);
+        assert_eq!(approvals.approvals[0].weight, None);
+        assert_eq!(approvals.collected_weight, None);
+        assert_eq!(approvals.threshold_reached, None);
+    }
+}
@@ -0,0 +1,158 @@
+//! Decoding of the processed deploys as they are received, into the rows of the derived tables of
+//! the token transfers, the NFT events and the contract events.
+//!
+//! Each decoder is run by a task of its own, fed by a queue of processed deploys; if the queue is
+//! full, the deploy is dropped for that decoder rather than delaying the broadcasting of events.
+//! The decoded rows are stored in the table of the decoder only and published as `Derived` events
+//! on the `/events/derived` stream, under the name of the decoder.
+
+use crate::types::{
+    database::{DatabaseReader, DatabaseWriteError, DatabaseWriter},
+    sse_events::DeployProcessed,
+};
+use async_trait::async_trait;
+use casper_event_types::{metrics::DERIVATIONS, sse_data::SseData, Filter};
+use serde::Serialize;
+use serde_json::value::to_raw_value;
+use tokio::sync::mpsc::{channel, error::TrySendError, Sender};
+use tracing::{debug, warn};
+
+type OutboundSender = Sender<(SseData, Option<Filter>, Option<String>)>;
+
+/// Decodes the rows of a derived table from the processed deploys.
+#[async_trait]
+pub(crate) trait DeployDecoder: Send + 'static {
+    /// Name under which the decoded rows are published and reported in the metrics.
+    const DERIVER: &'static str;
+
+    /// A decoded row.
+    type Entry: Serialize + Send;
+
+    /// Runs once, before the first deploy is decoded.
+    async fn prepare<Db: DatabaseReader + DatabaseWriter + Sync>(&mut self, _database: &Db) {}
+
+    /// The rows decoded from the deploy, in the order of its transforms.
+    async fn decode_deploy<Db: DatabaseWriter + Sync>(
+        &mut self,
+        database: &Db,
+        deploy_processed: &DeployProcessed,
+    ) -> Vec<Self::Entry>;
+
+    /// Stores a decoded row in the table of the decoder, returning the number of rows stored.
+    async fn save<Db: DatabaseWriter + Sync>(
+        database: &Db,
+        entry: Self::Entry,
+    ) -> Result<u64, DatabaseWriteError>;
+}
+
+/// Handle used to submit the processed deploys to a decoder. Does nothing unless started.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct DecodingWorker {
+    deriver: &'static str,
+    maybe_sender: Option<Sender<DeployProcessed>>,
+}
+
+impl DecodingWorker {
+    /// Spawns the task decoding the processed deploys with `decoder`, storing the decoded rows in
+    /// the given database and sending them to `outbound_sender`.
+    pub(crate) fn start<D, Db>(
+        mut decoder: D,
+        queue_size: usize,
+        database: Db,
+        outbound_sender: OutboundSender,
+    ) -> Self
+    where
+        D: DeployDecoder,
+        Db: DatabaseReader + DatabaseWriter + Send + Sync + 'static,
+    {
+        let (sender, mut receiver) = channel::<DeployProcessed>(queue_size);
+        tokio::spawn(async move {
+            decoder.prepare(&database).await;
+            while let Some(deploy_processed) = receiver.recv().await {
+                for entry in decoder.decode_deploy(&database, &deploy_processed).await {
+                    let outcome = emit::<D, _>(&database, &outbound_sender, entry).await;
+                    DERIVATIONS.with_label_values(&[D::DERIVER, outcome]).inc();
+                }
+            }
+        });
+        DecodingWorker {
+            deriver: D::DERIVER,
+            maybe_sender: Some(sender),
+        }
+    }
+
+    /// Queues a processed deploy for decoding. Never waits for the queue to have capacity.
+    pub(crate) fn submit(&self, sse_data: &SseData) {
+        let Some(sender) = &self.maybe_sender else {
+            return;
+        };
+        let Some(deploy_processed) = deploy_processed_of(sse_data) else {
+            return;
+        };
+        if let Err(TrySendError::Full(_)) = sender.try_send(deploy_processed) {
+            DERIVATIONS
+                .with_label_values(&[self.deriver, "dropped"])
+                .inc();
+        }
+    }
+}
+
+/// The processed deploy of the event, if it is a `DeployProcessed` event.
+pub(crate) fn deploy_processed_of(sse_data: &SseData) -> Option<DeployProcessed> {
+    match sse_data {
+        SseData::DeployProcessed {
+            deploy_hash,
+            account,
+            timestamp,
+            ttl,
+            dependencies,
+            block_hash,
+            execution_result,
+        } => Some(DeployProcessed::new(
+            deploy_hash.clone(),
+            account.clone(),
+            *timestamp,
+            *ttl,
+            dependencies.clone(),
+            block_hash.clone(),
+            execution_result.clone(),
+        )),
+        _ => None,
+    }
+}
+
+/// Stores the decoded row and publishes it on the outbound stream, returning the outcome reported
+/// in the metrics.
+async fn emit<D: DeployDecoder, Db: DatabaseWriter + Sync>(
+    database: &Db,
+    outbound_sender: &OutboundSender,
+    entry: D::Entry,
+) -> &'static str {
+    let payload = match to_raw_value(&entry) {
+        Ok(payload) => payload,
+        Err(error) => {
+            warn!(
+                ?error,
+                deriver = D::DERIVER,
+                "Error serializing decoded event"
+            );
+            return "failed";
+        }
+    };
+    if let Err(error) = D::save(database, entry).await {
+        warn!(?error, deriver = D::DERIVER, "Error saving decoded event");
+        return "failed";
+    }
+    let sse_data = SseData::Derived {
+        deriver: D::DERIVER.to_string(),
+        source_event_type: "DeployProcessed".to_string(),
+        payload,
+    };
+    if let Err(error) = outbound_sender.send((sse_data, None, None)).await {
+        debug!(
+            "Error when sending to outbound_sse_data_sender. Error: {}",
+            error
+        );
+    }
+    "derived"
+}
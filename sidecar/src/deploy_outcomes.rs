@@ -0,0 +1,108 @@
+//! Notifications of the outcome of deploys, awaited by the REST server.
+//!
+//! Every event is submitted by the event broadcasting task once it is stored. The hashes of the
+//! processed and expired deploys are passed on to the requests awaiting the outcome of a deploy, so
+//! that a wallet can wait for a submitted deploy with a single long-polling request rather than by
+//! polling the REST API in a loop. A request which falls behind the notifications, e.g. while its
+//! client is slow, is woken up as if its deploy was notified, and checks the stored events again.
+
+use casper_event_types::sse_data::SseData;
+use tokio::sync::broadcast::{self, error::RecvError, Receiver, Sender};
+
+/// Number of notifications kept for the requests which didn't receive them yet.
+const CHANNEL_SIZE: usize = 1024;
+
+/// Broadcasts the hex-encoded hashes of the deploys which were processed or expired.
+#[derive(Clone, Debug)]
+pub(crate) struct DeployOutcomes {
+    sender: Sender<String>,
+}
+
+impl Default for DeployOutcomes {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_SIZE);
+        DeployOutcomes { sender }
+    }
+}
+
+impl DeployOutcomes {
+    /// Notifies the requests awaiting the deploy of the event, if it is an outcome of a deploy.
+    pub(crate) fn submit(&self, sse_data: &SseData) {
+        let deploy_hash = match sse_data {
+            SseData::DeployProcessed { deploy_hash, .. } => hex::encode(deploy_hash.inner()),
+            SseData::DeployExpired { deploy_hash } => hex::encode(deploy_hash.inner()),
+            _ => return,
+        };
+        // Sending only fails when no request is awaiting a deploy.
+        let _ = self.sender.send(deploy_hash);
+    }
+
+    /// Subscribes to the outcomes submitted from now on.
+    pub(crate) fn subscribe(&self) -> DeployOutcomeReceiver {
+        DeployOutcomeReceiver {
+            receiver: self.sender.subscribe(),
+        }
+    }
+}
+
+/// The outcomes of deploys submitted since the subscription.
+pub(crate) struct DeployOutcomeReceiver {
+    receiver: Receiver<String>,
+}
+
+impl DeployOutcomeReceiver {
+    /// Resolves once an outcome of the deploy with the given hex-encoded hash may have been
+    /// submitted, or never if no more outcomes will be submitted.
+    pub(crate) async fn outcome_of(&mut self, deploy_hash: &str) {
+        loop {
+            match self.receiver.recv().await {
+                Ok(notified_hash) if notified_hash == deploy_hash => return,
+                Ok(_) => {}
+                Err(RecvError::Lagged(_)) => return,
+                Err(RecvError::Closed) => std::future::pending().await,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use casper_types::testing::TestRng;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    fn deploy_hash_of(sse_data: &SseData) -> String {
+        match sse_data {
+            SseData::DeployProcessed { deploy_hash, .. } => hex::encode(deploy_hash.inner()),
+            SseData::DeployExpired { deploy_hash } => hex::encode(deploy_hash.inner()),
+            _ => unreachable!(),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_notify_the_outcome_of_the_awaited_deploy() {
+        let mut rng = TestRng::new();
+        let deploy_outcomes = DeployOutcomes::default();
+        let processed = SseData::random_deploy_processed(&mut rng);
+        let expired = SseData::random_deploy_expired(&mut rng);
+        let mut receiver = deploy_outcomes.subscribe();
+
+        deploy_outcomes.submit(&SseData::random_block_added(&mut rng));
+        deploy_outcomes.submit(&processed);
+        deploy_outcomes.submit(&expired);
+
+        timeout(
+            Duration::from_secs(1),
+            receiver.outcome_of(&deploy_hash_of(&expired)),
+        )
+        .await
+        .expect("the outcome of the deploy should be notified");
+        assert!(timeout(
+            Duration::from_millis(100),
+            receiver.outcome_of(&deploy_hash_of(&processed))
+        )
+        .await
+        .is_err());
+    }
+}
@@ -0,0 +1,190 @@
+//! Lifecycle status of deploys.
+//!
+//! A deploy is pending once the node accepts it and leaves that state when it is processed, which
+//! executes it successfully or not, or when it expires unprocessed. The status is rebuilt from the
+//! `DeployAccepted`, `DeployProcessed` and `DeployExpired` events of the deploy in the order in
+//! which they were stored, so that clients don't have to fetch the three events and reconstruct it
+//! themselves. States only move forward: a processed deploy is final, even if it is later reported
+//! as expired, and a deploy only processed or expired by the time the Sidecar received it skips
+//! the pending state.
+
+use crate::{
+    sql::tables::event_type::EventTypeId,
+    types::{database::DeployEventTime, sse_events::DeployProcessed},
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// A state of the lifecycle of a deploy.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DeployState {
+    /// The deploy was accepted by the node and awaits its execution.
+    Pending,
+    /// The deploy expired before being executed.
+    Expired,
+    /// The deploy was executed successfully.
+    ExecutedSuccess,
+    /// The execution of the deploy failed.
+    ExecutedFailure,
+}
+
+impl DeployState {
+    /// The order of the state in the lifecycle, which only moves to later states.
+    fn stage(&self) -> u8 {
+        match self {
+            DeployState::Pending => 0,
+            DeployState::Expired => 1,
+            DeployState::ExecutedSuccess | DeployState::ExecutedFailure => 2,
+        }
+    }
+}
+
+/// A transition of a deploy into a state.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct DeployTransition {
+    state: DeployState,
+    /// UTC time the event causing the transition was stored at, formatted as
+    /// `YYYY-MM-DD HH:MM:SS`, possibly followed by fractional seconds.
+    timestamp: String,
+}
+
+/// The current state of a deploy along with the transitions which led to it.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct DeployStatus {
+    deploy_hash: String,
+    state: DeployState,
+    /// Transitions of the deploy, oldest first.
+    transitions: Vec<DeployTransition>,
+}
+
+impl DeployStatus {
+    /// Builds the status of the deploy from the times its events were stored at, oldest first, and
+    /// the `DeployProcessed` event if the deploy was processed. Returns `None` if no event of the
+    /// deploy is stored.
+    pub(crate) fn new(
+        deploy_hash: &str,
+        event_times: &[DeployEventTime],
+        maybe_deploy_processed: Option<&DeployProcessed>,
+    ) -> Option<Self> {
+        let mut transitions: Vec<DeployTransition> = Vec::new();
+        for event_time in event_times {
+            let state = match event_time.event_type_id {
+                id if id == EventTypeId::DeployAccepted as u8 => DeployState::Pending,
+                id if id == EventTypeId::DeployExpired as u8 => DeployState::Expired,
+                id if id == EventTypeId::DeployProcessed as u8 => {
+                    match maybe_deploy_processed.map(DeployProcessed::error_message) {
+                        Some(None) => DeployState::ExecutedSuccess,
+                        Some(Some(_)) => DeployState::ExecutedFailure,
+                        None => continue,
+                    }
+                }
+                _ => continue,
+            };
+            if transitions
+                .last()
+                .map_or(true, |last| state.stage() > last.state.stage())
+            {
+                transitions.push(DeployTransition {
+                    state,
+                    timestamp: event_time.inserted_timestamp.clone(),
+                });
+            }
+        }
+        let state = transitions.last()?.state;
+        Some(DeployStatus {
+            deploy_hash: deploy_hash.to_string(),
+            state,
+            transitions,
+        })
+    }
+
+    /// Whether the deploy reached a state it can't leave anymore, i.e. it was processed or expired.
+    pub(crate) fn is_final(&self) -> bool {
+        self.state != DeployState::Pending
+    }
+
+    /// Whether the events of the deploy include a `DeployProcessed` event, whose outcome is needed
+    /// to build the status.
+    pub(crate) fn is_processed(event_times: &[DeployEventTime]) -> bool {
+        event_times
+            .iter()
+            .any(|event_time| event_time.event_type_id == EventTypeId::DeployProcessed as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use casper_types::testing::TestRng;
+
+    fn event_time(event_type_id: EventTypeId, second: u8) -> DeployEventTime {
+        DeployEventTime {
+            event_type_id: event_type_id as u8,
+            inserted_timestamp: format!("2024-01-01 00:00:{:02}", second),
+        }
+    }
+
+    fn states(deploy_status: &DeployStatus) -> Vec<DeployState> {
+        deploy_status
+            .transitions
+            .iter()
+            .map(|transition| transition.state)
+            .collect()
+    }
+
+    #[test]
+    fn should_move_from_pending_to_the_outcome_of_the_execution() {
+        let mut rng = TestRng::new();
+        let accepted = [event_time(EventTypeId::DeployAccepted, 1)];
+        let processed = [
+            event_time(EventTypeId::DeployAccepted, 1),
+            event_time(EventTypeId::DeployProcessed, 2),
+        ];
+
+        let pending = DeployStatus::new("ab", &accepted, None).unwrap();
+        assert_eq!(pending.state, DeployState::Pending);
+        assert!(!pending.is_final());
+        assert_eq!(pending.transitions[0].timestamp, "2024-01-01 00:00:01");
+        assert!(!DeployStatus::is_processed(&accepted));
+
+        assert!(DeployStatus::is_processed(&processed));
+        let deploy_processed = DeployProcessed::random(&mut rng, None);
+        let succeeded = DeployStatus::new("ab", &processed, Some(&deploy_processed)).unwrap();
+        assert_eq!(
+            states(&succeeded),
+            vec![DeployState::Pending, DeployState::ExecutedSuccess]
+        );
+        assert_eq!(succeeded.transitions[1].timestamp, "2024-01-01 00:00:02");
+        assert!(succeeded.is_final());
+
+        let deploy_processed = deploy_processed.with_error_message("Out of gas");
+        let failed = DeployStatus::new("ab", &processed, Some(&deploy_processed)).unwrap();
+        assert_eq!(failed.state, DeployState::ExecutedFailure);
+    }
+
+    #[test]
+    fn should_only_move_forward() {
+        let mut rng = TestRng::new();
+        let deploy_processed = DeployProcessed::random(&mut rng, None);
+        let event_times = [
+            event_time(EventTypeId::DeployExpired, 1),
+            event_time(EventTypeId::DeployAccepted, 2),
+            event_time(EventTypeId::DeployProcessed, 3),
+            event_time(EventTypeId::DeployExpired, 4),
+        ];
+
+        let deploy_status = DeployStatus::new("ab", &event_times, Some(&deploy_processed)).unwrap();
+
+        assert_eq!(
+            states(&deploy_status),
+            vec![DeployState::Expired, DeployState::ExecutedSuccess]
+        );
+        assert_eq!(deploy_status.state, DeployState::ExecutedSuccess);
+    }
+
+    #[test]
+    fn should_have_no_status_without_events() {
+        assert!(DeployStatus::new("ab", &[], None).is_none());
+    }
+}
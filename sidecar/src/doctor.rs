@@ -0,0 +1,413 @@
+//! `doctor`, a check of the setup of the sidecar, run before starting it.
+//!
+//! Every connected node is checked for its event stream and REST server to be reachable and for
+//! its clock to agree with the one of the host, as the sidecar reasons on the timestamps of the
+//! events. The ports of the servers of the sidecar are checked to be free, the storage directory
+//! to be writable and to have enough free space, and the database to be reachable, which creates
+//! and migrates it if needed. Each check passes, fails with a suggested fix, or is skipped when it
+//! can't be made.
+
+use crate::{
+    build_database,
+    source_scoring::node_label,
+    types::config::{Config, Connection, StorageConfig},
+    utils::resolve_address,
+};
+use anyhow::Error;
+use serde_json::Value;
+use std::{
+    fmt::{self, Display, Formatter},
+    fs,
+    net::TcpListener,
+    path::Path,
+    time::{Duration, Instant, SystemTime},
+};
+use tokio::{net::TcpStream, time::timeout};
+
+/// Time allowed to connect to a node or to get its status.
+const NODE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Clock skew with a node beyond which the check fails. The clock of the node is only known to the
+/// second.
+const MAX_CLOCK_SKEW_IN_SECONDS: i64 = 5;
+/// Free space of the storage directory below which the check fails.
+const MIN_FREE_DISK_SPACE: u64 = 1024 * 1024 * 1024;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Outcome {
+    Pass,
+    Fail { fix: String },
+    Skip,
+}
+
+/// The result of a check, e.g. of the reachability of a node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Check {
+    name: String,
+    detail: String,
+    outcome: Outcome,
+}
+
+impl Check {
+    fn pass(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Check {
+            name: name.into(),
+            detail: detail.into(),
+            outcome: Outcome::Pass,
+        }
+    }
+
+    fn fail(name: impl Into<String>, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Check {
+            name: name.into(),
+            detail: detail.into(),
+            outcome: Outcome::Fail { fix: fix.into() },
+        }
+    }
+
+    fn skip(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Check {
+            name: name.into(),
+            detail: detail.into(),
+            outcome: Outcome::Skip,
+        }
+    }
+}
+
+impl Display for Check {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        let tag = match self.outcome {
+            Outcome::Pass => "PASS",
+            Outcome::Fail { .. } => "FAIL",
+            Outcome::Skip => "SKIP",
+        };
+        write!(formatter, "[{}] {} - {}", tag, self.name, self.detail)?;
+        if let Outcome::Fail { fix } = &self.outcome {
+            write!(formatter, "\n       fix: {}", fix)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs every check of the setup described by `config`, printing a report. Fails if any check
+/// failed.
+pub(crate) async fn run_doctor(config: Config) -> Result<(), Error> {
+    let client = reqwest::Client::builder().timeout(NODE_TIMEOUT).build()?;
+    let mut checks = Vec::new();
+    for connection in &config.connections {
+        checks.extend(check_node(&client, connection).await);
+    }
+    checks.extend(check_ports(&config));
+    checks.extend(check_storage(&config.storage.get_storage_path()));
+    checks.push(check_database(&config.storage).await);
+
+    for check in &checks {
+        println!("{}", check);
+    }
+    let count = |outcome: fn(&Outcome) -> bool| {
+        checks
+            .iter()
+            .filter(|check| outcome(&check.outcome))
+            .count()
+    };
+    let failed = count(|outcome| matches!(outcome, Outcome::Fail { .. }));
+    println!(
+        "\n{} passed, {} failed, {} skipped",
+        count(|outcome| *outcome == Outcome::Pass),
+        failed,
+        count(|outcome| *outcome == Outcome::Skip)
+    );
+    if failed > 0 {
+        return Err(Error::msg(format!("{} check(s) failed", failed)));
+    }
+    Ok(())
+}
+
+async fn check_node(client: &reqwest::Client, connection: &Connection) -> Vec<Check> {
+    let node = node_label(connection);
+    let rest_server = format!("{}:{}", connection.ip_address, connection.rest_port);
+    let mut checks = vec![check_event_stream(&node).await];
+    match fetch_status(client, &rest_server).await {
+        Ok((status, maybe_node_time)) => {
+            checks.push(Check::pass(
+                format!("REST server of {}", node),
+                describe_status(&status),
+            ));
+            checks.push(check_clock(&node, maybe_node_time, SystemTime::now()));
+        }
+        Err(error) => {
+            checks.push(Check::fail(
+                format!("REST server of {}", node),
+                error.to_string(),
+                "check that `rest_port` is the port of the REST server of the node, 8888 by \
+                 default",
+            ));
+            checks.push(Check::skip(
+                format!("clock of {}", node),
+                "the REST server of the node is unreachable",
+            ));
+        }
+    }
+    checks
+}
+
+async fn check_event_stream(node: &str) -> Check {
+    let name = format!("event stream of {}", node);
+    match timeout(NODE_TIMEOUT, TcpStream::connect(node)).await {
+        Ok(Ok(_)) => Check::pass(name, "reachable"),
+        Ok(Err(error)) => Check::fail(
+            name,
+            error.to_string(),
+            "check that the node is running, that `sse_port` is the port of its event stream, \
+             9999 by default, and that no firewall blocks it",
+        ),
+        Err(_) => Check::fail(
+            name,
+            format!("no connection within {}s", NODE_TIMEOUT.as_secs()),
+            "check that `ip_address` is the address of the node and that no firewall drops the \
+             connections to `sse_port`",
+        ),
+    }
+}
+
+/// Returns the status of the node with the REST server at `address`, and the time of the node as
+/// given by the `Date` header of the response.
+async fn fetch_status(
+    client: &reqwest::Client,
+    address: &str,
+) -> Result<(Value, Option<SystemTime>), reqwest::Error> {
+    let response = client
+        .get(format!("http://{}/status", address))
+        .send()
+        .await?
+        .error_for_status()?;
+    let maybe_node_time = response
+        .headers()
+        .get(http::header::DATE)
+        .and_then(|date| date.to_str().ok())
+        .and_then(|date| httpdate::parse_http_date(date).ok());
+    let body = response.bytes().await?;
+    Ok((
+        serde_json::from_slice(&body).unwrap_or_default(),
+        maybe_node_time,
+    ))
+}
+
+fn describe_status(status: &Value) -> String {
+    let api_version = status
+        .get("api_version")
+        .and_then(Value::as_str)
+        .unwrap_or("unknown");
+    match status
+        .get("last_added_block_info")
+        .and_then(|block| block.get("height"))
+        .and_then(Value::as_u64)
+    {
+        Some(height) => format!("api version {}, latest block {}", api_version, height),
+        None => format!("api version {}, no block added yet", api_version),
+    }
+}
+
+fn check_clock(node: &str, maybe_node_time: Option<SystemTime>, now: SystemTime) -> Check {
+    let name = format!("clock of {}", node);
+    let node_time = match maybe_node_time {
+        Some(node_time) => node_time,
+        None => return Check::skip(name, "the node didn't send its time"),
+    };
+    let skew = match node_time.duration_since(now) {
+        Ok(ahead) => ahead.as_secs() as i64,
+        Err(behind) => -(behind.duration().as_secs() as i64),
+    };
+    let detail = match skew {
+        0 => "in sync with this host".to_string(),
+        skew if skew > 0 => format!("{}s ahead of this host", skew),
+        skew => format!("{}s behind this host", -skew),
+    };
+    if skew.abs() < MAX_CLOCK_SKEW_IN_SECONDS {
+        Check::pass(name, detail)
+    } else {
+        Check::fail(
+            name,
+            detail,
+            "synchronize the clocks of the node and of this host with NTP, e.g. with \
+             `timedatectl set-ntp true`",
+        )
+    }
+}
+
+fn check_ports(config: &Config) -> Vec<Check> {
+    let mut servers = vec![
+        ("REST server", config.rest_server.bind_address()),
+        (
+            "event stream server",
+            config.event_stream_server.bind_address(),
+        ),
+    ];
+    if let Some(admin_server) = &config.admin_server {
+        servers.push(("admin server", admin_server.bind_address()));
+    }
+    if let Some(metrics_server) = &config.metrics_server {
+        servers.push(("metrics server", metrics_server.bind_address()));
+    }
+    servers
+        .into_iter()
+        .map(|(server, address)| check_port(server, &address))
+        .collect()
+}
+
+fn check_port(server: &str, address: &str) -> Check {
+    let name = format!("port of the {}", server);
+    let bound = resolve_address(address)
+        .map_err(|error| error.to_string())
+        .and_then(|socket_address| {
+            TcpListener::bind(socket_address).map_err(|error| error.to_string())
+        });
+    match bound {
+        Ok(_) => Check::pass(name, format!("{} is free", address)),
+        Err(error) => Check::fail(
+            name,
+            format!("can't listen on {}: {}", address, error),
+            format!(
+                "stop the process listening on {}, e.g. a running sidecar, or change the \
+                 `address` and `port` of the {}",
+                address, server
+            ),
+        ),
+    }
+}
+
+fn check_storage(storage_path: &str) -> Vec<Check> {
+    let path = Path::new(storage_path);
+    let probe = path.join(format!(".doctor-{}", std::process::id()));
+    let writable = fs::create_dir_all(path)
+        .and_then(|_| fs::write(&probe, b"doctor"))
+        .and_then(|_| fs::remove_file(&probe));
+    let writable_check = match writable {
+        Ok(()) => Check::pass("storage directory", format!("{} is writable", storage_path)),
+        Err(error) => Check::fail(
+            "storage directory",
+            format!("can't write to {}: {}", storage_path, error),
+            "make `storage_path` a directory the user running the sidecar can write to",
+        ),
+    };
+    vec![writable_check, check_disk_space(storage_path)]
+}
+
+fn check_disk_space(storage_path: &str) -> Check {
+    let name = "disk space";
+    match available_space(Path::new(storage_path)) {
+        Ok(Some(available)) if available >= MIN_FREE_DISK_SPACE => Check::pass(
+            name,
+            format!("{} MiB free in {}", available / 1024 / 1024, storage_path),
+        ),
+        Ok(Some(available)) => Check::fail(
+            name,
+            format!(
+                "only {} MiB free in {}",
+                available / 1024 / 1024,
+                storage_path
+            ),
+            "free disk space or move `storage_path` to a larger volume, the stored events take \
+             several gigabytes over weeks",
+        ),
+        Ok(None) => Check::skip(name, "not checked on this platform"),
+        Err(error) => Check::skip(
+            name,
+            format!("can't read the free space of {}: {}", storage_path, error),
+        ),
+    }
+}
+
+/// Returns the space available to unprivileged users on the filesystem of `path`.
+#[cfg(unix)]
+fn available_space(path: &Path) -> std::io::Result<Option<u64>> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let path = CString::new(path.as_os_str().as_bytes())?;
+    // SAFETY: `statvfs` is a plain C struct for which zeroed memory is a valid value.
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: `path` is a NUL-terminated string and `stat` is valid for writes.
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    // The field types vary between platforms.
+    #[allow(clippy::unnecessary_cast)]
+    Ok(Some(stat.f_bavail as u64 * stat.f_frsize as u64))
+}
+
+#[cfg(not(unix))]
+fn available_space(_path: &Path) -> std::io::Result<Option<u64>> {
+    Ok(None)
+}
+
+async fn check_database(config: &StorageConfig) -> Check {
+    let name = "database";
+    let started = Instant::now();
+    match build_database(config).await {
+        Ok(_) => Check::pass(
+            name,
+            format!(
+                "connected and migrated in {}ms",
+                started.elapsed().as_millis()
+            ),
+        ),
+        Err(error) => {
+            let fix = match config {
+                StorageConfig::SqliteDbConfig { .. } => {
+                    "check the `file_name` of the `storage.sqlite_config` section and that no \
+                     other process holds the database"
+                }
+                StorageConfig::PostgreSqlDbConfig { .. } => {
+                    "check that PostgreSQL is reachable with the host, port, database name, \
+                     username and password of the `storage.postgresql_config` section, and that \
+                     the user can create tables"
+                }
+            };
+            Check::fail(name, format!("{:#}", error), fix)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_fail_on_clock_skew() {
+        let now = SystemTime::now();
+
+        let check = check_clock("node", Some(now + Duration::from_secs(2)), now);
+        assert_eq!(check.outcome, Outcome::Pass);
+        assert_eq!(check.detail, "2s ahead of this host");
+
+        let check = check_clock("node", Some(now - Duration::from_secs(30)), now);
+        assert!(matches!(check.outcome, Outcome::Fail { .. }));
+        assert_eq!(check.detail, "30s behind this host");
+
+        assert_eq!(check_clock("node", None, now).outcome, Outcome::Skip);
+    }
+
+    #[test]
+    fn should_fail_on_port_in_use() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        let check = check_port("REST server", &address);
+
+        assert!(matches!(check.outcome, Outcome::Fail { .. }), "{}", check);
+        drop(listener);
+        assert_eq!(check_port("REST server", &address).outcome, Outcome::Pass);
+    }
+
+    #[test]
+    fn should_check_storage_directory() {
+        let storage = tempfile::tempdir().unwrap();
+        let storage_path = storage.path().join("storage");
+
+        let checks = check_storage(storage_path.to_str().unwrap());
+
+        assert_eq!(checks[0].outcome, Outcome::Pass);
+        assert!(storage_path.is_dir());
+        assert_eq!(fs::read_dir(&storage_path).unwrap().count(), 0);
+        assert_ne!(checks[1].detail, "");
+    }
+}
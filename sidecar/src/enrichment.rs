@@ -0,0 +1,356 @@
+//! Enrichment of stored events with context fetched from a node's RPC server.
+//!
+//! Events which qualify for an enabled enrichment are queued for a background task which fetches
+//! the context with JSON-RPC requests and stores it in the `Enrichment` table, keyed by the hash of
+//! the block or deploy. Requests are rate-limited and their results cached, so that a burst of
+//! events doesn't overload the node. If the queue is full, the enrichment is dropped rather than
+//! delaying the broadcasting of events.
+
+use crate::{
+    node_rpc::{RateLimiter, ResponseCache, RpcClient},
+    types::{
+        config::EnrichmentConfig,
+        database::{DatabaseWriter, Enrichment},
+    },
+};
+use anyhow::{anyhow, Error};
+use casper_event_types::{metrics::ENRICHMENTS, sse_data::SseData};
+use casper_types::AsymmetricType;
+use serde_json::{json, Value};
+use tokio::sync::mpsc::{channel, error::TrySendError, Sender};
+use tracing::warn;
+
+/// Kind of the summary of the era ended by a switch block.
+pub(crate) const ERA_SUMMARY_KIND: &str = "era_summary";
+/// Kind of the main purse of the account which sent a deploy.
+pub(crate) const ACCOUNT_MAIN_PURSE_KIND: &str = "account_main_purse";
+
+/// Context to fetch for a stored block or deploy.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum EnrichmentRequest {
+    EraSummary {
+        block_hash: String,
+    },
+    AccountMainPurse {
+        deploy_hash: String,
+        account: String,
+        block_hash: String,
+    },
+}
+
+impl EnrichmentRequest {
+    /// Returns the requests for the enrichments of `sse_data` enabled in `config`.
+    fn for_event(config: &EnrichmentConfig, sse_data: &SseData) -> Vec<EnrichmentRequest> {
+        match sse_data {
+            SseData::BlockAdded { block_hash, block }
+                if config.era_summary && block.header.era_end.is_some() =>
+            {
+                vec![EnrichmentRequest::EraSummary {
+                    block_hash: hex::encode(block_hash.inner()),
+                }]
+            }
+            SseData::DeployProcessed {
+                deploy_hash,
+                account,
+                block_hash,
+                ..
+            } if config.account_main_purse => vec![EnrichmentRequest::AccountMainPurse {
+                deploy_hash: hex::encode(deploy_hash.inner()),
+                account: account.to_hex(),
+                block_hash: hex::encode(block_hash.inner()),
+            }],
+            _ => vec![],
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            EnrichmentRequest::EraSummary { .. } => ERA_SUMMARY_KIND,
+            EnrichmentRequest::AccountMainPurse { .. } => ACCOUNT_MAIN_PURSE_KIND,
+        }
+    }
+
+    /// Hash of the block or deploy the enrichment is stored for.
+    fn entity_hash(&self) -> &str {
+        match self {
+            EnrichmentRequest::EraSummary { block_hash } => block_hash,
+            EnrichmentRequest::AccountMainPurse { deploy_hash, .. } => deploy_hash,
+        }
+    }
+
+    /// Key under which the result of the RPC request is cached. The main purse of an account never
+    /// changes, so it is shared by all deploys of the account.
+    fn cache_key(&self) -> String {
+        match self {
+            EnrichmentRequest::EraSummary { block_hash } => {
+                format!("{}-{}", ERA_SUMMARY_KIND, block_hash)
+            }
+            EnrichmentRequest::AccountMainPurse { account, .. } => {
+                format!("{}-{}", ACCOUNT_MAIN_PURSE_KIND, account)
+            }
+        }
+    }
+
+    /// The JSON-RPC method and params fetching the context.
+    fn rpc_call(&self) -> (&'static str, Value) {
+        match self {
+            EnrichmentRequest::EraSummary { block_hash } => (
+                "chain_get_era_info_by_switch_block",
+                json!({ "block_identifier": { "Hash": block_hash } }),
+            ),
+            EnrichmentRequest::AccountMainPurse {
+                account,
+                block_hash,
+                ..
+            } => (
+                "state_get_account_info",
+                json!({ "public_key": account, "block_identifier": { "Hash": block_hash } }),
+            ),
+        }
+    }
+
+    /// Extracts the context from the `result` of the RPC request.
+    fn extract(&self, mut result: Value) -> Result<Value, Error> {
+        let pointer = match self {
+            EnrichmentRequest::EraSummary { .. } => "/era_summary",
+            EnrichmentRequest::AccountMainPurse { .. } => "/account/main_purse",
+        };
+        let value = match result.pointer_mut(pointer).map(Value::take) {
+            None | Some(Value::Null) => return Err(anyhow!("no {} in response", &pointer[1..])),
+            Some(value) => value,
+        };
+        match self {
+            EnrichmentRequest::EraSummary { .. } => Ok(value),
+            EnrichmentRequest::AccountMainPurse { account, .. } => {
+                Ok(json!({ "account": account, "main_purse": value }))
+            }
+        }
+    }
+}
+
+struct EnrichmentWorker<Db> {
+    database: Db,
+    rpc_client: RpcClient,
+    rate_limiter: RateLimiter,
+    cache: ResponseCache,
+}
+
+impl<Db: DatabaseWriter> EnrichmentWorker<Db> {
+    /// Fetches and stores the context for `request`, returning the outcome reported in the metrics.
+    async fn enrich(&mut self, request: &EnrichmentRequest) -> &'static str {
+        let Some((value, outcome)) = self.fetch(request).await else {
+            return "failed";
+        };
+        let enrichment = Enrichment {
+            entity_hash: request.entity_hash().to_string(),
+            kind: request.kind().to_string(),
+            value,
+        };
+        match self.database.save_enrichment(enrichment).await {
+            Ok(_) => outcome,
+            Err(error) => {
+                warn!(
+                    ?error,
+                    kind = request.kind(),
+                    hash = request.entity_hash(),
+                    "Error saving enrichment"
+                );
+                "failed"
+            }
+        }
+    }
+
+    /// The context for `request`, from the cache if it was fetched already, along with the outcome
+    /// reported in the metrics if it is stored.
+    async fn fetch(&mut self, request: &EnrichmentRequest) -> Option<(Value, &'static str)> {
+        let cache_key = request.cache_key();
+        if let Some(value) = self.cache.get(&cache_key) {
+            return Some((value.clone(), "cached"));
+        }
+        self.rate_limiter.acquire().await;
+        let (method, params) = request.rpc_call();
+        let result = self.rpc_client.call(method, params).await;
+        match result.and_then(|result| request.extract(result)) {
+            Ok(value) => {
+                self.cache.insert(cache_key, value.clone());
+                Some((value, "stored"))
+            }
+            Err(error) => {
+                warn!(
+                    ?error,
+                    kind = request.kind(),
+                    hash = request.entity_hash(),
+                    "Error fetching enrichment"
+                );
+                None
+            }
+        }
+    }
+}
+
+/// Handle used to submit stored events for enrichment. Cloned handles share the same worker task.
+#[derive(Clone, Debug)]
+pub(crate) struct Enricher {
+    config: EnrichmentConfig,
+    sender: Sender<EnrichmentRequest>,
+}
+
+impl Enricher {
+    /// Spawns the task enriching submitted events and saving the results to the given database.
+    pub(crate) fn start<Db: DatabaseWriter + Send + Sync + 'static>(
+        config: EnrichmentConfig,
+        database: Db,
+    ) -> Self {
+        let (sender, mut receiver) = channel::<EnrichmentRequest>(config.queue_size());
+        let mut worker = EnrichmentWorker {
+            database,
+            rpc_client: RpcClient::new(config.rpc_address.clone()),
+            rate_limiter: RateLimiter::new(config.max_requests_per_second()),
+            cache: ResponseCache::new(config.cache_size()),
+        };
+        tokio::spawn(async move {
+            while let Some(request) = receiver.recv().await {
+                let outcome = worker.enrich(&request).await;
+                ENRICHMENTS
+                    .with_label_values(&[request.kind(), outcome])
+                    .inc();
+            }
+        });
+        Enricher { config, sender }
+    }
+
+    /// Queues the enrichments of a stored event. Never waits for the queue to have capacity.
+    pub(crate) fn submit(&self, sse_data: &SseData) {
+        for request in EnrichmentRequest::for_event(&self.config, sse_data) {
+            if let Err(TrySendError::Full(request)) = self.sender.try_send(request) {
+                ENRICHMENTS
+                    .with_label_values(&[request.kind(), "dropped"])
+                    .inc();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::fake_database::FakeDatabase, types::database::DatabaseReader};
+    use casper_types::testing::TestRng;
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+    use warp::Filter;
+
+    fn build_config(rpc_address: String) -> EnrichmentConfig {
+        EnrichmentConfig {
+            rpc_address,
+            max_requests_per_second: Some(100),
+            cache_size: None,
+            queue_size: None,
+            era_summary: true,
+            account_main_purse: true,
+        }
+    }
+
+    fn deploy_processed_for_account(rng: &mut TestRng, account_of: &SseData) -> SseData {
+        let mut sse_data = SseData::random_deploy_processed(rng);
+        if let (
+            SseData::DeployProcessed { account, .. },
+            SseData::DeployProcessed {
+                account: other_account,
+                ..
+            },
+        ) = (&mut sse_data, account_of)
+        {
+            *account = other_account.clone();
+        }
+        sse_data
+    }
+
+    #[test]
+    fn should_only_request_enabled_enrichments() {
+        let mut test_rng = TestRng::new();
+        let deploy_processed = SseData::random_deploy_processed(&mut test_rng);
+        let mut config = build_config("http://127.0.0.1:1/rpc".to_string());
+
+        let requests = EnrichmentRequest::for_event(&config, &deploy_processed);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].kind(), ACCOUNT_MAIN_PURSE_KIND);
+
+        config.account_main_purse = false;
+        assert!(EnrichmentRequest::for_event(&config, &deploy_processed).is_empty());
+
+        let mut block_added = SseData::random_block_added(&mut test_rng);
+        if let SseData::BlockAdded { block, .. } = &mut block_added {
+            block.header.era_end = None;
+        }
+        assert!(EnrichmentRequest::for_event(&config, &block_added).is_empty());
+        let fault = SseData::random_fault(&mut test_rng);
+        assert!(EnrichmentRequest::for_event(&config, &fault).is_empty());
+    }
+
+    /// Serves an RPC answering every request with the same main purse, counting the requests in
+    /// `request_count`. Returns the port it is served on.
+    fn serve_rpc(request_count: Arc<AtomicUsize>) -> u16 {
+        let rpc_route = warp::post()
+            .and(warp::path("rpc"))
+            .and(warp::body::json())
+            .map(move |body: Value| {
+                request_count.fetch_add(1, Ordering::SeqCst);
+                warp::reply::json(&json!({
+                    "jsonrpc": "2.0",
+                    "id": body["id"],
+                    "result": { "account": { "main_purse": "uref-01-007" } },
+                }))
+            });
+        let port = portpicker::pick_unused_port().expect("Unable to pick a port");
+        tokio::spawn(warp::serve(rpc_route).bind(([127, 0, 0, 1], port)));
+        port
+    }
+
+    async fn await_enrichments(database: &FakeDatabase, sse_data: &SseData) -> Vec<Enrichment> {
+        let deploy_hash = match sse_data {
+            SseData::DeployProcessed { deploy_hash, .. } => hex::encode(deploy_hash.inner()),
+            _ => unreachable!(),
+        };
+        let mut attempts = 0;
+        loop {
+            match database.get_enrichments_by_hash(&deploy_hash).await {
+                Ok(enrichments) => return enrichments,
+                Err(_) if attempts < 50 => {
+                    attempts += 1;
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+                Err(error) => panic!("Enrichment was not stored: {:?}", error),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn should_store_main_purse_fetched_once_per_account() {
+        let request_count = Arc::new(AtomicUsize::new(0));
+        let port = serve_rpc(request_count.clone());
+
+        let database = FakeDatabase::new();
+        let enricher = Enricher::start(
+            build_config(format!("http://127.0.0.1:{}/rpc", port)),
+            database.clone(),
+        );
+        let mut test_rng = TestRng::new();
+        let first = SseData::random_deploy_processed(&mut test_rng);
+        let second = deploy_processed_for_account(&mut test_rng, &first);
+        enricher.submit(&first);
+        enricher.submit(&second);
+
+        for sse_data in [&first, &second] {
+            let enrichments = await_enrichments(&database, sse_data).await;
+            assert_eq!(enrichments[0].kind, ACCOUNT_MAIN_PURSE_KIND);
+            assert_eq!(enrichments[0].value["main_purse"], json!("uref-01-007"));
+        }
+        assert_eq!(request_count.load(Ordering::SeqCst), 1);
+    }
+}
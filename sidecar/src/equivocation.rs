@@ -0,0 +1,194 @@
+//! Tracking of the validators which equivocated in an era, i.e. were reported in a fault, and of
+//! whether they were ejected from the validator set of the next era.
+
+use crate::{
+    history::AsOf,
+    types::database::{DatabaseReadError, DatabaseReader},
+};
+use casper_event_types::metrics::EJECTED_EQUIVOCATORS;
+use casper_types::AsymmetricType;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use tracing::{error, warn};
+use utoipa::ToSchema;
+
+/// A validator reported in a fault of an era.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct Equivocator {
+    /// Hex-encoded public key of the validator.
+    pub(crate) public_key: String,
+    pub(crate) era_id: u64,
+    /// Time of the earliest fault of the validator in the era.
+    pub(crate) fault_timestamp: String,
+    /// Whether the validator is missing from the validator set of the next era. Unknown until the
+    /// switch block of the era is stored.
+    pub(crate) ejected: Option<bool>,
+}
+
+/// Returns the validators reported in the faults of `era_id`, ordered by public key. If
+/// `maybe_as_of` is given, only the faults up to the time of its block are taken into account, and
+/// whether the validators were ejected is unknown unless the era had ended by its block.
+pub(crate) async fn get_equivocators<Db: DatabaseReader + Sync>(
+    db: &Db,
+    era_id: u64,
+    maybe_as_of: Option<AsOf>,
+) -> Result<Vec<Equivocator>, DatabaseReadError> {
+    let faults = match db.get_faults_by_era(era_id).await {
+        Ok(faults) => faults,
+        Err(DatabaseReadError::NotFound) => return Ok(Vec::new()),
+        Err(error) => return Err(error),
+    };
+    let maybe_next_era_validators = match maybe_as_of {
+        Some(as_of) if !as_of.has_ended(era_id) => None,
+        _ => match db.get_era_validators(era_id + 1).await {
+            Ok(validators) => Some(validators.into_iter().collect::<HashSet<_>>()),
+            Err(DatabaseReadError::NotFound) => None,
+            Err(error) => return Err(error),
+        },
+    };
+    // A validator can be reported in several faults of the same era.
+    let mut earliest_faults = BTreeMap::new();
+    for fault in faults {
+        if let Some(as_of) = maybe_as_of {
+            if fault.timestamp.millis() > as_of.timestamp {
+                continue;
+            }
+        }
+        let earliest = earliest_faults
+            .entry(fault.public_key.to_hex())
+            .or_insert(fault.timestamp);
+        *earliest = (*earliest).min(fault.timestamp);
+    }
+    Ok(earliest_faults
+        .into_iter()
+        .map(|(public_key, timestamp)| Equivocator {
+            ejected: maybe_next_era_validators
+                .as_ref()
+                .map(|validators| !validators.contains(&public_key)),
+            public_key,
+            era_id,
+            fault_timestamp: timestamp.to_string(),
+        })
+        .collect())
+}
+
+/// Warns about and counts the equivocators of `era_id` which were ejected from the validator set
+/// of the next era. To be called once the switch block of `era_id` is stored.
+pub(crate) async fn alert_on_ejected_equivocators<Db: DatabaseReader + Sync>(db: &Db, era_id: u64) {
+    let equivocators = match get_equivocators(db, era_id, None).await {
+        Ok(equivocators) => equivocators,
+        Err(error) => {
+            error!(era_id, "Error getting equivocators: {:?}", error);
+            return;
+        }
+    };
+    for equivocator in equivocators {
+        if equivocator.ejected == Some(true) {
+            warn!(
+                era_id,
+                validator = %equivocator.public_key,
+                "Equivocator ejected from the validator set of the next era"
+            );
+            EJECTED_EQUIVOCATORS
+                .with_label_values(&[&equivocator.public_key])
+                .inc();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::fake_database::FakeDatabase,
+        types::{database::DatabaseWriter, sse_events::*},
+    };
+    use casper_types::{testing::TestRng, EraId, PublicKey, Timestamp};
+
+    #[tokio::test]
+    async fn should_flag_equivocators_missing_from_next_era() {
+        let mut rng = TestRng::new();
+        let database = FakeDatabase::new();
+        let fault = Fault::new(EraId::new(7), PublicKey::random(&mut rng), Timestamp::now());
+        database
+            .save_fault(fault.clone(), 1, "127.0.0.1".to_string())
+            .await
+            .expect("Error saving fault");
+
+        let equivocators = get_equivocators(&database, 7, None).await.unwrap();
+        assert_eq!(
+            equivocators,
+            vec![Equivocator {
+                public_key: fault.public_key.to_hex(),
+                era_id: 7,
+                fault_timestamp: fault.timestamp.to_string(),
+                ejected: None,
+            }]
+        );
+
+        let next_era_validator = PublicKey::random(&mut rng);
+        let switch_block = BlockAdded::random_switch_block(&mut rng, 7, vec![next_era_validator]);
+        database
+            .save_block_added(switch_block, 2, "127.0.0.1".to_string())
+            .await
+            .expect("Error saving block");
+        let equivocators = get_equivocators(&database, 7, None).await.unwrap();
+        assert_eq!(equivocators[0].ejected, Some(true));
+
+        let label = fault.public_key.to_hex();
+        let ejected_before = EJECTED_EQUIVOCATORS.with_label_values(&[&label]).get();
+        alert_on_ejected_equivocators(&database, 7).await;
+        assert_eq!(
+            EJECTED_EQUIVOCATORS.with_label_values(&[&label]).get(),
+            ejected_before + 1
+        );
+
+        assert!(get_equivocators(&database, 8, None)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_answer_as_of_a_block() {
+        let mut rng = TestRng::new();
+        let database = FakeDatabase::new();
+        let fault = Fault::new(
+            EraId::new(7),
+            PublicKey::random(&mut rng),
+            Timestamp::from(2_000),
+        );
+        database
+            .save_fault(fault, 1, "127.0.0.1".to_string())
+            .await
+            .expect("Error saving fault");
+        let switch_block =
+            BlockAdded::random_switch_block(&mut rng, 7, vec![PublicKey::random(&mut rng)]);
+        database
+            .save_block_added(switch_block, 2, "127.0.0.1".to_string())
+            .await
+            .expect("Error saving block");
+        let as_of = |era_id, timestamp| AsOf {
+            height: 0,
+            era_id,
+            is_switch_block: false,
+            timestamp,
+        };
+
+        let equivocators = get_equivocators(&database, 7, Some(as_of(7, 1_000)))
+            .await
+            .unwrap();
+        assert!(equivocators.is_empty());
+
+        let equivocators = get_equivocators(&database, 7, Some(as_of(7, 3_000)))
+            .await
+            .unwrap();
+        assert_eq!(equivocators.len(), 1);
+        assert_eq!(equivocators[0].ejected, None);
+
+        let equivocators = get_equivocators(&database, 7, Some(as_of(8, 5_000)))
+            .await
+            .unwrap();
+        assert_eq!(equivocators[0].ejected, Some(true));
+    }
+}
@@ -0,0 +1,456 @@
+//! Derivation of custom events by WASM plugins registered in the config.
+//!
+//! Every ingested event is passed to each plugin as the JSON of its `data` field. A plugin may
+//! answer with any number of payloads, which are stored in the `DerivedEvent` table and published
+//! as `Derived` events on the `/events/derived` stream. Plugins are sandboxed: they can't import
+//! anything from the host, their memory is limited and a run is stopped once it consumes its fuel
+//! or exceeds its timeout. A failing run only loses the events derived from the event at hand.
+//!
+//! A plugin is a WASM module exporting:
+//! - `memory`, its linear memory,
+//! - `alloc(len: i32) -> i32`, returning the address of `len` bytes to write the event to,
+//! - `derive(ptr: i32, len: i32) -> i64`, returning the address of a UTF-8 JSON array of derived
+//!   payloads in its upper 32 bits and its length in the lower ones, or 0 if nothing is derived.
+//!
+//! Each event is derived in a fresh instance of the module, so plugins can't keep state between
+//! events. Each plugin has a queue of its own; if it is full, the event is dropped for that plugin
+//! rather than delaying the broadcasting of events.
+//!
+//! The plugins are run by `wasmtime`, which is only compiled with the `wasm-derivers` feature.
+
+#[cfg(feature = "wasm-derivers")]
+use crate::types::database::DerivedEvent;
+use crate::types::{config::EventDeriverConfig, database::DatabaseWriter};
+use anyhow::Error;
+#[cfg(feature = "wasm-derivers")]
+use anyhow::{anyhow, Context};
+use casper_event_types::{metrics::DERIVATIONS, sse_data::SseData, Filter};
+#[cfg(feature = "wasm-derivers")]
+use serde_json::{value::to_raw_value, Value};
+use std::sync::Arc;
+#[cfg(feature = "wasm-derivers")]
+use std::{convert::TryFrom, thread, time::Duration};
+#[cfg(feature = "wasm-derivers")]
+use tokio::sync::mpsc::channel;
+use tokio::sync::mpsc::{error::TrySendError, Sender};
+#[cfg(feature = "wasm-derivers")]
+use tracing::debug;
+use tracing::warn;
+#[cfg(feature = "wasm-derivers")]
+use wasmtime::{
+    Config as EngineConfig, Engine, Instance, Memory, Module, Store, StoreLimits,
+    StoreLimitsBuilder, Trap, TypedFunc,
+};
+
+/// Interval at which the epoch of the engine is incremented, i.e. the resolution of timeouts.
+#[cfg(feature = "wasm-derivers")]
+const EPOCH_TICK: Duration = Duration::from_millis(10);
+/// The largest linear memory a plugin may grow.
+#[cfg(feature = "wasm-derivers")]
+const MAX_MEMORY_SIZE_IN_BYTES: usize = 64 * 1024 * 1024;
+/// The largest output a plugin may return for an event.
+#[cfg(feature = "wasm-derivers")]
+const MAX_OUTPUT_SIZE_IN_BYTES: usize = 1024 * 1024;
+
+type OutboundSender = Sender<(SseData, Option<Filter>, Option<String>)>;
+
+/// A compiled plugin and the limits of its runs.
+#[cfg(feature = "wasm-derivers")]
+#[derive(Clone)]
+struct Plugin {
+    engine: Engine,
+    module: Module,
+    max_fuel: u64,
+    /// Number of epoch ticks after which a run is interrupted.
+    deadline_in_ticks: u64,
+}
+
+/// The exports of an instance of a plugin.
+#[cfg(feature = "wasm-derivers")]
+struct PluginInstance {
+    memory: Memory,
+    alloc: TypedFunc<u32, u32>,
+    derive: TypedFunc<(u32, u32), u64>,
+}
+
+#[cfg(feature = "wasm-derivers")]
+impl Plugin {
+    fn load(engine: &Engine, config: &EventDeriverConfig) -> Result<Self, Error> {
+        let module = Module::from_file(engine, &config.wasm_path).with_context(|| {
+            format!(
+                "Error loading event deriver {} from {}",
+                config.name, config.wasm_path
+            )
+        })?;
+        let tick_in_milliseconds = EPOCH_TICK.as_millis() as u64;
+        let plugin = Plugin {
+            engine: engine.clone(),
+            module,
+            max_fuel: config.max_fuel(),
+            deadline_in_ticks: (config.timeout_in_milliseconds() + tick_in_milliseconds - 1)
+                / tick_in_milliseconds,
+        };
+        // Instantiated once so that a plugin with imports or missing exports is rejected on
+        // startup rather than on every event.
+        plugin
+            .instantiate(&mut plugin.new_store()?)
+            .with_context(|| format!("Invalid event deriver {}", config.name))?;
+        Ok(plugin)
+    }
+
+    fn new_store(&self) -> Result<Store<StoreLimits>, Error> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(MAX_MEMORY_SIZE_IN_BYTES)
+            .build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store.set_fuel(self.max_fuel)?;
+        store.set_epoch_deadline(self.deadline_in_ticks);
+        Ok(store)
+    }
+
+    fn instantiate(&self, store: &mut Store<StoreLimits>) -> Result<PluginInstance, Error> {
+        // No imports are provided, so that plugins can't reach the host.
+        let instance = Instance::new(&mut *store, &self.module, &[])?;
+        let memory = instance
+            .get_memory(&mut *store, "memory")
+            .ok_or_else(|| anyhow!("no exported memory"))?;
+        Ok(PluginInstance {
+            memory,
+            alloc: instance.get_typed_func(&mut *store, "alloc")?,
+            derive: instance.get_typed_func(&mut *store, "derive")?,
+        })
+    }
+
+    /// Runs the plugin on the JSON of an event and returns the derived payloads.
+    fn derive(&self, event_json: &str) -> Result<Vec<Value>, Error> {
+        let mut store = self.new_store()?;
+        let instance = self.instantiate(&mut store)?;
+        let input = event_json.as_bytes();
+        let input_len = u32::try_from(input.len())?;
+        let input_ptr = instance.alloc.call(&mut store, input_len)?;
+        instance
+            .memory
+            .write(&mut store, input_ptr as usize, input)?;
+        let packed = instance.derive.call(&mut store, (input_ptr, input_len))?;
+        if packed == 0 {
+            return Ok(Vec::new());
+        }
+        let output_ptr = (packed >> 32) as usize;
+        let output_len = (packed & u64::from(u32::MAX)) as usize;
+        if output_len > MAX_OUTPUT_SIZE_IN_BYTES {
+            return Err(anyhow!(
+                "output of {} bytes exceeds the limit of {} bytes",
+                output_len,
+                MAX_OUTPUT_SIZE_IN_BYTES
+            ));
+        }
+        let mut output = vec![0; output_len];
+        instance.memory.read(&store, output_ptr, &mut output)?;
+        serde_json::from_slice(&output).context("output is not a JSON array")
+    }
+}
+
+/// The outcome of a failed run, as reported in the metrics.
+#[cfg(feature = "wasm-derivers")]
+fn failure_outcome(error: &Error) -> &'static str {
+    match error.downcast_ref::<Trap>() {
+        Some(Trap::OutOfFuel) => "out_of_fuel",
+        Some(Trap::Interrupt) => "timed_out",
+        _ => "failed",
+    }
+}
+
+/// Events the derivers receive: the ones ingested from the nodes.
+fn is_derivable(sse_data: &SseData) -> bool {
+    !matches!(
+        sse_data,
+        SseData::ApiVersion(_) | SseData::SidecarVersion(_) | SseData::Derived { .. }
+    )
+}
+
+/// An ingested event queued for derivation.
+#[derive(Debug)]
+#[cfg_attr(not(feature = "wasm-derivers"), allow(dead_code))]
+struct QueuedEvent {
+    event_type: &'static str,
+    json: Arc<str>,
+}
+
+#[cfg(feature = "wasm-derivers")]
+struct DeriverWorker<Db> {
+    name: String,
+    plugin: Plugin,
+    database: Db,
+    outbound_sender: OutboundSender,
+}
+
+#[cfg(feature = "wasm-derivers")]
+impl<Db: DatabaseWriter> DeriverWorker<Db> {
+    /// Runs the plugin on `event` and emits the derived events, returning the outcome reported in
+    /// the metrics.
+    async fn derive(&self, event: QueuedEvent) -> &'static str {
+        let plugin = self.plugin.clone();
+        let json = event.json.clone();
+        let result = tokio::task::spawn_blocking(move || plugin.derive(&json))
+            .await
+            .map_err(Error::from)
+            .and_then(|result| result);
+        let payloads = match result {
+            Ok(payloads) => payloads,
+            Err(error) => {
+                warn!(
+                    ?error,
+                    deriver = %self.name,
+                    event_type = event.event_type,
+                    "Error deriving events"
+                );
+                return failure_outcome(&error);
+            }
+        };
+        if payloads.is_empty() {
+            return "none";
+        }
+        for payload in payloads {
+            self.emit(event.event_type, payload).await;
+        }
+        "derived"
+    }
+
+    /// Stores a derived payload and publishes it on the outbound stream.
+    async fn emit(&self, source_event_type: &str, payload: Value) {
+        let raw_payload = match to_raw_value(&payload) {
+            Ok(raw_payload) => raw_payload,
+            Err(error) => {
+                warn!(?error, deriver = %self.name, "Error serializing derived event");
+                return;
+            }
+        };
+        let derived_event = DerivedEvent {
+            deriver: self.name.clone(),
+            source_event_type: source_event_type.to_string(),
+            payload,
+        };
+        if let Err(error) = self.database.save_derived_event(derived_event).await {
+            warn!(?error, deriver = %self.name, "Error saving derived event");
+            return;
+        }
+        let sse_data = SseData::Derived {
+            deriver: self.name.clone(),
+            source_event_type: source_event_type.to_string(),
+            payload: raw_payload,
+        };
+        if let Err(error) = self.outbound_sender.send((sse_data, None, None)).await {
+            debug!(
+                "Error when sending to outbound_sse_data_sender. Error: {}",
+                error
+            );
+        }
+    }
+}
+
+/// The engine shared by all plugins, with fuel metering and an epoch incremented every
+/// [EPOCH_TICK] to interrupt runs exceeding their timeout.
+#[cfg(feature = "wasm-derivers")]
+fn build_engine() -> Result<Engine, Error> {
+    let mut engine_config = EngineConfig::new();
+    engine_config.consume_fuel(true).epoch_interruption(true);
+    let engine = Engine::new(&engine_config)?;
+    let ticking_engine = engine.clone();
+    thread::spawn(move || loop {
+        thread::sleep(EPOCH_TICK);
+        ticking_engine.increment_epoch();
+    });
+    Ok(engine)
+}
+
+/// Handle used to submit ingested events to the configured event derivers. Cloned handles share
+/// the same worker tasks.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct EventDerivers {
+    senders: Vec<(String, Sender<QueuedEvent>)>,
+}
+
+impl EventDerivers {
+    /// Loads the plugins and spawns a task per plugin, saving the derived events to the given
+    /// database and sending them to `outbound_sender`. Fails if a plugin can't be loaded.
+    #[cfg(feature = "wasm-derivers")]
+    pub(crate) fn start<Db: DatabaseWriter + Clone + Send + Sync + 'static>(
+        configs: &[EventDeriverConfig],
+        database: Db,
+        outbound_sender: OutboundSender,
+    ) -> Result<Self, Error> {
+        if configs.is_empty() {
+            return Ok(EventDerivers::default());
+        }
+        let engine = build_engine()?;
+        let mut senders = Vec::with_capacity(configs.len());
+        for config in configs {
+            let worker = DeriverWorker {
+                name: config.name.clone(),
+                plugin: Plugin::load(&engine, config)?,
+                database: database.clone(),
+                outbound_sender: outbound_sender.clone(),
+            };
+            let (sender, mut receiver) = channel::<QueuedEvent>(config.queue_size());
+            tokio::spawn(async move {
+                while let Some(event) = receiver.recv().await {
+                    let outcome = worker.derive(event).await;
+                    DERIVATIONS
+                        .with_label_values(&[&worker.name, outcome])
+                        .inc();
+                }
+            });
+            senders.push((config.name.clone(), sender));
+        }
+        Ok(EventDerivers { senders })
+    }
+
+    /// Without the `wasm-derivers` feature, no deriver can be loaded: fails if any is configured.
+    #[cfg(not(feature = "wasm-derivers"))]
+    pub(crate) fn start<Db: DatabaseWriter + Clone + Send + Sync + 'static>(
+        configs: &[EventDeriverConfig],
+        _database: Db,
+        _outbound_sender: OutboundSender,
+    ) -> Result<Self, Error> {
+        if configs.is_empty() {
+            return Ok(EventDerivers::default());
+        }
+        Err(Error::msg(
+            "the sidecar was built without the `wasm-derivers` feature to run event derivers",
+        ))
+    }
+
+    /// Queues an ingested event for every deriver. Never waits for the queues to have capacity.
+    pub(crate) fn submit(&self, sse_data: &SseData) {
+        if self.senders.is_empty() || !is_derivable(sse_data) {
+            return;
+        }
+        let json: Arc<str> = match serde_json::to_string(sse_data) {
+            Ok(json) => json.into(),
+            Err(error) => {
+                warn!(?error, "Error serializing event for the event derivers");
+                return;
+            }
+        };
+        for (name, sender) in &self.senders {
+            let event = QueuedEvent {
+                event_type: sse_data.type_label(),
+                json: json.clone(),
+            };
+            if let Err(TrySendError::Full(_)) = sender.try_send(event) {
+                DERIVATIONS.with_label_values(&[name, "dropped"]).inc();
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "wasm-derivers"))]
+mod tests {
+    use super::*;
+    use crate::{testing::fake_database::FakeDatabase, types::database::DatabaseReader};
+    use casper_types::testing::TestRng;
+    use serde_json::json;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    /// Derives `[{"seen":true}]` from every event.
+    const CONSTANT_DERIVER: &str = r#"(module
+        (memory (export "memory") 1)
+        (data (i32.const 0) "[{\"seen\":true}]")
+        (func (export "alloc") (param i32) (result i32) i32.const 1024)
+        (func (export "derive") (param i32 i32) (result i64) i64.const 15))"#;
+    /// Never returns.
+    const LOOPING_DERIVER: &str = r#"(module
+        (memory (export "memory") 1)
+        (func (export "alloc") (param i32) (result i32) i32.const 1024)
+        (func (export "derive") (param i32 i32) (result i64) (loop br 0) i64.const 0))"#;
+    /// Asks the host for the time.
+    const IMPORTING_DERIVER: &str = r#"(module
+        (import "env" "now" (func $now (result i64)))
+        (memory (export "memory") 1)
+        (func (export "alloc") (param i32) (result i32) i32.const 1024)
+        (func (export "derive") (param i32 i32) (result i64) call $now))"#;
+
+    fn write_module(wat: &str) -> NamedTempFile {
+        let mut file = NamedTempFile::new().expect("Error creating module file");
+        file.write_all(wat.as_bytes())
+            .expect("Error writing module file");
+        file
+    }
+
+    fn build_config(name: &str, file: &NamedTempFile) -> EventDeriverConfig {
+        EventDeriverConfig {
+            name: name.to_string(),
+            wasm_path: file.path().to_string_lossy().to_string(),
+            max_fuel: None,
+            timeout_in_milliseconds: None,
+            queue_size: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn should_store_and_publish_derived_events() {
+        let file = write_module(CONSTANT_DERIVER);
+        let database = FakeDatabase::new();
+        let (outbound_sender, mut outbound_receiver) = channel(10);
+        let derivers = EventDerivers::start(
+            &[build_config("seen", &file)],
+            database.clone(),
+            outbound_sender,
+        )
+        .expect("Error starting event derivers");
+
+        let mut rng = TestRng::new();
+        derivers.submit(&SseData::random_api_version(&mut rng));
+        derivers.submit(&SseData::random_block_added(&mut rng));
+
+        let (sse_data, inbound_filter, _) =
+            tokio::time::timeout(Duration::from_secs(10), outbound_receiver.recv())
+                .await
+                .expect("No derived event was published")
+                .unwrap();
+        match sse_data {
+            SseData::Derived {
+                deriver,
+                source_event_type,
+                payload,
+            } => {
+                assert_eq!(deriver, "seen");
+                assert_eq!(source_event_type, "BlockAdded");
+                assert_eq!(payload.get(), "{\"seen\":true}");
+            }
+            other => panic!("expected a derived event, got {:?}", other),
+        }
+        assert!(inbound_filter.is_none());
+        let stored = database
+            .get_derived_events_by_deriver("seen")
+            .await
+            .expect("Derived event was not stored");
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].payload, json!({ "seen": true }));
+    }
+
+    #[test]
+    fn should_stop_runs_out_of_fuel_or_time() {
+        let file = write_module(LOOPING_DERIVER);
+        let engine = build_engine().unwrap();
+        let mut config = build_config("looping", &file);
+        let plugin = Plugin::load(&engine, &config).unwrap();
+        let error = plugin.derive("{}").unwrap_err();
+        assert_eq!(failure_outcome(&error), "out_of_fuel");
+
+        config.max_fuel = Some(u64::MAX);
+        config.timeout_in_milliseconds = Some(20);
+        let plugin = Plugin::load(&engine, &config).unwrap();
+        let error = plugin.derive("{}").unwrap_err();
+        assert_eq!(failure_outcome(&error), "timed_out");
+    }
+
+    #[test]
+    fn should_reject_plugins_with_imports() {
+        let file = write_module(IMPORTING_DERIVER);
+        let engine = build_engine().unwrap();
+        assert!(Plugin::load(&engine, &build_config("importing", &file)).is_err());
+    }
+}
@@ -23,22 +23,46 @@
 mod config;
 mod endpoint;
 mod event_indexer;
+mod event_selection;
+mod fair_scheduler;
 mod http_server;
+mod priority;
+mod retained_events;
+mod slow_subscriber;
 mod sse_server;
+mod status_events;
+mod subscriber_auth;
 #[cfg(test)]
 mod tests;
-use crate::utils::{resolve_address, ListeningError};
+mod websocket;
+use crate::{
+    api_keys::{require_scope_of_streams, ApiKeys},
+    audit::AuditLog,
+    types::{config::ApiKeyScope, database::DatabaseWriteError},
+    utils::{resolve_address, ListeningError},
+    watch_list::WatchList,
+};
 use casper_event_types::{sse_data::SseData, Filter as SseFilter};
 pub use config::Config;
+pub(crate) use event_indexer::EventIndexStore;
 use event_indexer::{EventIndex, EventIndexer};
-use sse_server::ChannelsAndFilter;
-use std::{fmt::Debug, net::SocketAddr, path::PathBuf};
+use futures::{future, Future};
+use http_server::OutboundReceiver;
+use priority::PriorityClasses;
+use retained_events::RetainedEvents;
+pub(crate) use sse_server::sse_data_to_json;
+use sse_server::{BroadcastChannelMessage, ChannelsAndFilter, NewSubscriberInfo, SubscriberPolicy};
+pub(crate) use status_events::start_health_status_monitor;
+use status_events::{StatusBroadcaster, StatusEvent};
+use std::{fmt::Debug, net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
+use subscriber_auth::JwtValidator;
 use tokio::sync::{
+    broadcast,
     mpsc::{self, UnboundedSender},
     oneshot,
 };
 use tracing::{info, warn};
-use warp::Filter;
+use warp::{filters::BoxedFilter, reply::Response, Filter};
 
 /// This is used to define the number of events to buffer in the tokio broadcast channel to help
 /// slower clients to try to avoid missing events (See
@@ -55,6 +79,7 @@ pub type OutboundSender = UnboundedSender<(
     SseData,
     Option<SseFilter>,
     Option<String>,
+    bool,
 )>;
 
 #[derive(Debug)]
@@ -62,25 +87,56 @@ pub(crate) struct EventStreamServer {
     /// Channel sender to pass event-stream data to the event-stream server.
     sse_data_sender: OutboundSender,
     event_indexer: EventIndexer,
+    watch_list: WatchList,
+    status_broadcaster: StatusBroadcaster,
     // This is linted as unused because in this implementation it is only printed to the output.
+    // `None` if the event streams are served by the server shared by the networks.
     #[allow(unused)]
-    listening_address: SocketAddr,
+    maybe_listening_address: Option<SocketAddr>,
+}
+
+/// The channels feeding the event streams of a server, until its HTTP server is started.
+struct PendingStreams {
+    config: Config,
+    sse_data_receiver: OutboundReceiver,
+    event_broadcaster: broadcast::Sender<BroadcastChannelMessage>,
+    new_subscriber_info_receiver: mpsc::UnboundedReceiver<NewSubscriberInfo>,
+    retained_events: RetainedEvents,
+}
+
+impl PendingStreams {
+    fn start(
+        self,
+        server_with_shutdown: impl Future<Output = ()> + Send + 'static,
+        shutdown_sender: oneshot::Sender<()>,
+    ) {
+        tokio::spawn(http_server::run(
+            self.config,
+            server_with_shutdown,
+            shutdown_sender,
+            self.sse_data_receiver,
+            self.event_broadcaster,
+            self.new_subscriber_info_receiver,
+            self.retained_events,
+        ));
+    }
 }
 
 impl EventStreamServer {
-    pub(crate) fn new(config: Config, storage_path: PathBuf) -> Result<Self, ListeningError> {
+    pub(crate) fn new(
+        config: Config,
+        storage_path: PathBuf,
+        maybe_audit_log: Option<AuditLog>,
+        watch_list: WatchList,
+        maybe_api_keys: Option<ApiKeys>,
+    ) -> Result<Self, ListeningError> {
         let required_address = resolve_address_and_retype(&config.address)?;
-        let event_indexer = EventIndexer::new(storage_path);
-        let (sse_data_sender, sse_data_receiver) = mpsc::unbounded_channel();
-
-        // Event stream channels and filter.
-        let ChannelsAndFilter {
-            event_broadcaster,
-            new_subscriber_info_receiver,
-            sse_filter,
-        } = ChannelsAndFilter::new(
-            get_broadcast_channel_size(&config),
-            config.max_concurrent_subscribers,
+        let (mut event_stream_server, sse_filter, pending_streams) = Self::build(
+            config,
+            storage_path,
+            maybe_audit_log,
+            watch_list,
+            maybe_api_keys,
         );
         let (shutdown_sender, shutdown_receiver) = oneshot::channel::<()>();
         let (listening_address, server_with_shutdown) =
@@ -94,35 +150,155 @@ impl EventStreamServer {
                 })?;
         info!(address=%listening_address, "started event stream server");
 
-        tokio::spawn(http_server::run(
+        pending_streams.start(server_with_shutdown, shutdown_sender);
+        event_stream_server.maybe_listening_address = Some(listening_address);
+        Ok(event_stream_server)
+    }
+
+    /// Creates the server of one of several networks, whose event streams are served by the server
+    /// shared by the networks, see [serve_networks]. Returns the routes of the event streams, to be
+    /// mounted under the name of the network.
+    pub(crate) fn new_mounted(
+        config: Config,
+        storage_path: PathBuf,
+        maybe_audit_log: Option<AuditLog>,
+        watch_list: WatchList,
+        maybe_api_keys: Option<ApiKeys>,
+    ) -> (Self, BoxedFilter<(Response,)>) {
+        let (event_stream_server, sse_filter, pending_streams) = Self::build(
             config,
-            server_with_shutdown,
-            shutdown_sender,
-            sse_data_receiver,
+            storage_path,
+            maybe_audit_log,
+            watch_list,
+            maybe_api_keys,
+        );
+        let (shutdown_sender, _) = oneshot::channel::<()>();
+        pending_streams.start(future::pending::<()>(), shutdown_sender);
+        (event_stream_server, sse_filter)
+    }
+
+    fn build(
+        config: Config,
+        storage_path: PathBuf,
+        maybe_audit_log: Option<AuditLog>,
+        watch_list: WatchList,
+        maybe_api_keys: Option<ApiKeys>,
+    ) -> (Self, BoxedFilter<(Response,)>, PendingStreams) {
+        let event_indexer = EventIndexer::new(storage_path);
+        let (sse_data_sender, sse_data_receiver) = mpsc::unbounded_channel();
+        let retained_events = RetainedEvents::new(config.event_stream_buffer_length);
+
+        let policy = subscriber_policy(&config, maybe_audit_log);
+
+        // Event stream channels and filter.
+        let ChannelsAndFilter {
             event_broadcaster,
             new_subscriber_info_receiver,
-        ));
-        Ok(EventStreamServer {
+            status_broadcaster,
+            sse_filter,
+        } = ChannelsAndFilter::new(
+            get_broadcast_channel_size(&config),
+            policy,
+            retained_events.clone(),
+            config.reject_unavailable_replay,
+            config.network_name.clone(),
+        );
+        // A subscriber needs a key before its token, if any, is verified.
+        let sse_filter =
+            require_scope_of_streams(maybe_api_keys, ApiKeyScope::ReadEvents, sse_filter);
+        let event_stream_server = EventStreamServer {
             sse_data_sender,
             event_indexer,
-            listening_address,
-        })
+            watch_list,
+            status_broadcaster,
+            maybe_listening_address: None,
+        };
+        let pending_streams = PendingStreams {
+            config,
+            sse_data_receiver,
+            event_broadcaster,
+            new_subscriber_info_receiver,
+            retained_events,
+        };
+        (event_stream_server, sse_filter, pending_streams)
+    }
+
+    /// Reserves the ids of all further events in `store` instead of only caching the next id in
+    /// the storage directory, so ids aren't reused after a crash.
+    pub(crate) async fn use_event_index_store(
+        &mut self,
+        store: EventIndexStore,
+    ) -> Result<(), DatabaseWriteError> {
+        self.event_indexer.use_store(store).await
+    }
+
+    /// Handle used to send status events to the subscribers which opted in to them.
+    pub(crate) fn status_broadcaster(&self) -> StatusBroadcaster {
+        self.status_broadcaster.clone()
     }
 
-    /// Broadcasts the SSE data to all clients connected to the event stream.
-    pub(crate) fn broadcast(
+    /// Broadcasts the SSE data to all clients connected to the event stream. Events relating to an
+    /// entity of the watch list are also published on the `/events/watched` stream. Fails if the id
+    /// of the event can't be allocated in the event index store.
+    pub(crate) async fn broadcast(
         &mut self,
         sse_data: SseData,
         inbound_filter: Option<SseFilter>,
         maybe_json_data: Option<String>,
-    ) {
+    ) -> Result<(), DatabaseWriteError> {
         let event_index = match sse_data {
             SseData::ApiVersion(..) => None,
-            _ => Some(self.event_indexer.next_index()),
+            _ => Some(self.event_indexer.allocate_index().await?),
         };
-        let _ = self
-            .sse_data_sender
-            .send((event_index, sse_data, inbound_filter, maybe_json_data));
+        let watched = self.watch_list.count_if_watched(&sse_data);
+        if let Some(upstream_lag) = StatusEvent::upstream_lag(&sse_data) {
+            self.status_broadcaster.send(upstream_lag);
+        }
+        let _ = self.sse_data_sender.send((
+            event_index,
+            sse_data,
+            inbound_filter,
+            maybe_json_data,
+            watched,
+        ));
+        Ok(())
+    }
+}
+
+/// Serves the event streams of several networks, mounted under the names of the networks, on the
+/// configured address.
+pub(crate) async fn serve_networks(
+    address: &String,
+    routes: BoxedFilter<(Response,)>,
+) -> Result<(), ListeningError> {
+    let required_address = resolve_address_and_retype(address)?;
+    let (listening_address, server) = warp::serve(routes.with(warp::cors().allow_any_origin()))
+        .try_bind_ephemeral(required_address)
+        .map_err(|error| ListeningError::Listen {
+            address: required_address,
+            error: Box::new(error),
+        })?;
+    info!(address=%listening_address, "started event stream server of the networks");
+    server.await;
+    Ok(())
+}
+
+/// The policy under which the subscribers are admitted and monitored.
+fn subscriber_policy(config: &Config, maybe_audit_log: Option<AuditLog>) -> SubscriberPolicy {
+    SubscriberPolicy {
+        max_concurrent_subscribers: config.max_concurrent_subscribers,
+        maybe_jwt_validator: config
+            .jwt_auth
+            .as_ref()
+            .map(|jwt_auth| Arc::new(JwtValidator::new(jwt_auth, maybe_audit_log))),
+        maybe_slow_subscriber_timeout: config
+            .slow_subscriber_timeout_in_seconds
+            .map(Duration::from_secs),
+        maybe_priority_classes: config
+            .priority_classes
+            .as_ref()
+            .map(PriorityClasses::new)
+            .map(Arc::new),
     }
 }
 
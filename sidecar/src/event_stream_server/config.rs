@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::types::config::{JwtAuthConfig, PriorityClassesConfig};
+
 /// Default binding address for the SSE HTTP server.
 ///
 /// Uses a fixed port per node, but binds on any interface.
@@ -24,11 +26,32 @@ pub struct Config {
 
     /// Default maximum number of subscribers across all event streams permitted at any one time.
     pub max_concurrent_subscribers: u32,
+
+    /// If set, subscribers need to authenticate with a JWT signed with the configured secret.
+    pub jwt_auth: Option<JwtAuthConfig>,
+
+    /// If set, subscribers whose buffer stays saturated for longer than this are disconnected.
+    pub slow_subscriber_timeout_in_seconds: Option<u64>,
+
+    /// If set, events of low priority classes are dropped for subscribers falling behind.
+    pub priority_classes: Option<PriorityClassesConfig>,
+
+    /// If set, subscribers asking to replay events which are no longer buffered are rejected, unless
+    /// they opted in to status events.
+    pub reject_unavailable_replay: bool,
+
+    /// Name of the network of the events, served on `/info` if known.
+    pub network_name: Option<String>,
 }
 
 impl Config {
     /// Creates an instance for `EventStreamServer`.
-    pub fn new(port: u16, buffer_length: Option<u32>, max_subscribers: Option<u32>) -> Self {
+    pub fn new(
+        port: u16,
+        buffer_length: Option<u32>,
+        max_subscribers: Option<u32>,
+        jwt_auth: Option<JwtAuthConfig>,
+    ) -> Self {
         let address = format!("{}:{}", DEFAULT_ADDRESS, port);
 
         Config {
@@ -36,12 +59,17 @@ impl Config {
             event_stream_buffer_length: buffer_length.unwrap_or(DEFAULT_EVENT_STREAM_BUFFER_LENGTH),
             max_concurrent_subscribers: max_subscribers
                 .unwrap_or(DEFAULT_MAX_CONCURRENT_SUBSCRIBERS),
+            jwt_auth,
+            slow_subscriber_timeout_in_seconds: None,
+            priority_classes: None,
+            reject_unavailable_replay: false,
+            network_name: None,
         }
     }
 }
 
 impl Default for Config {
     fn default() -> Self {
-        Config::new(0, None, None)
+        Config::new(0, None, None, None)
     }
 }
@@ -12,6 +12,9 @@ pub enum Endpoint {
     Deploys,
     Sigs,
     Sidecar,
+    Unknown,
+    Derived,
+    Watched,
 }
 
 impl Endpoint {
@@ -24,6 +27,20 @@ impl Endpoint {
                 | (Endpoint::Sigs, Filter::Sigs)
         )
     }
+
+    /// The path of the endpoint, as used in metrics.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Endpoint::Events => "events",
+            Endpoint::Main => "events/main",
+            Endpoint::Deploys => "events/deploys",
+            Endpoint::Sigs => "events/sigs",
+            Endpoint::Sidecar => "events/sidecar",
+            Endpoint::Unknown => "events/unknown",
+            Endpoint::Derived => "events/derived",
+            Endpoint::Watched => "events/watched",
+        }
+    }
 }
 
 #[cfg(test)]
@@ -31,13 +48,7 @@ impl Display for Endpoint {
     /// This implementation is for test only and created to mimick how Display is implemented for Filter.
     /// We use this trick to easily test `is_corresponding_to` with all possible inputs.
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Endpoint::Events => write!(f, "events"),
-            Endpoint::Main => write!(f, "events/main"),
-            Endpoint::Deploys => write!(f, "events/deploys"),
-            Endpoint::Sigs => write!(f, "events/sigs"),
-            Endpoint::Sidecar => write!(f, "events/sidecar"),
-        }
+        write!(f, "{}", self.label())
     }
 }
 
@@ -55,6 +66,9 @@ mod tests {
             Endpoint::Deploys,
             Endpoint::Sigs,
             Endpoint::Sidecar,
+            Endpoint::Unknown,
+            Endpoint::Derived,
+            Endpoint::Watched,
         ];
         for endpoint in all_endpoints.iter() {
             for filter in all_filters.iter() {
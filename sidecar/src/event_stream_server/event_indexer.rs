@@ -1,15 +1,51 @@
-use std::{fs, path::PathBuf};
+use std::{
+    fmt::{self, Debug, Formatter},
+    fs,
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
 
-use tracing::{debug, error, warn};
+use tokio::time::sleep;
+use tracing::{debug, error, info, warn};
+
+use crate::types::database::{DatabaseWriteError, DatabaseWriter};
 
 const CACHE_FILENAME: &str = "sse_index";
+/// The number of indices reserved in the database at once.
+const RESERVATION_SIZE: u32 = 100;
+const MAX_RESERVATION_ATTEMPTS: u32 = 3;
+const RESERVATION_RETRY_DELAY: Duration = Duration::from_millis(500);
 
 pub(super) type EventIndex = u32;
 
-#[derive(Debug)]
+/// Database in which event indices are allocated.
+pub(crate) type EventIndexStore = Arc<dyn DatabaseWriter + Send + Sync>;
+
+/// Allocates the indices of outbound events.
+///
+/// By default the next index is kept in memory and written to a cache file in the storage
+/// directory when the indexer is dropped, so indices are reused after a crash. Once a database is
+/// set with [EventIndexer::use_store], indices are reserved in the database in blocks of
+/// [RESERVATION_SIZE] before they are used, so they are never reused, while those of the block
+/// which weren't used before a crash are skipped.
 pub(super) struct EventIndexer {
     index: EventIndex,
+    /// The number of indices from `index` on which are reserved in the store.
+    reserved: u32,
     persistent_cache: PathBuf,
+    maybe_store: Option<EventIndexStore>,
+}
+
+impl Debug for EventIndexer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventIndexer")
+            .field("index", &self.index)
+            .field("reserved", &self.reserved)
+            .field("persistent_cache", &self.persistent_cache)
+            .field("uses_store", &self.maybe_store.is_some())
+            .finish()
+    }
 }
 
 impl EventIndexer {
@@ -44,22 +80,79 @@ impl EventIndexer {
         let index = EventIndex::from_le_bytes(bytes);
         EventIndexer {
             index,
+            reserved: 0,
             persistent_cache,
+            maybe_store: None,
         }
     }
 
+    /// Allocates all further indices in `store`.
+    ///
+    /// If the store doesn't hold an allocator state yet, it is created from the index read from
+    /// the cache file, so upgrading doesn't restart the indices at 0. Otherwise the store's state
+    /// wins as it is always up to date, while the cache file isn't written after a crash.
+    pub(super) async fn use_store(
+        &mut self,
+        store: EventIndexStore,
+    ) -> Result<(), DatabaseWriteError> {
+        let next_index = store.initialize_event_id_allocator(self.index).await?;
+        if next_index != self.index {
+            info!(
+                cached_index = %self.index,
+                stored_index = %next_index,
+                "recovered sse index from database"
+            );
+        }
+        self.index = next_index;
+        self.maybe_store = Some(store);
+        Ok(())
+    }
+
     pub(super) fn next_index(&mut self) -> EventIndex {
         let index = self.index;
         self.index = index.wrapping_add(1);
         index
     }
 
+    /// Allocates the next index. With a store, a block of indices is reserved once the previous
+    /// one is used up. The reservation is retried a few times and its error returned if it keeps
+    /// failing, since handing out an index which wasn't reserved could lead to it being reused.
+    pub(super) async fn allocate_index(&mut self) -> Result<EventIndex, DatabaseWriteError> {
+        if let Some(store) = &self.maybe_store {
+            if self.reserved == 0 {
+                self.index = reserve_indices(store).await?;
+                self.reserved = RESERVATION_SIZE;
+            }
+            self.reserved -= 1;
+        }
+        Ok(self.next_index())
+    }
+
     #[cfg(test)]
     pub(super) fn current_index(&self) -> EventIndex {
         self.index
     }
 }
 
+/// Reserves the next [RESERVATION_SIZE] indices in `store` and returns the first of them.
+async fn reserve_indices(store: &EventIndexStore) -> Result<EventIndex, DatabaseWriteError> {
+    let mut attempt = 1;
+    loop {
+        match store.reserve_event_ids(RESERVATION_SIZE).await {
+            Ok(first_index) => return Ok(first_index),
+            Err(error) if attempt < MAX_RESERVATION_ATTEMPTS => {
+                warn!(?error, %attempt, "failed to reserve sse indices, retrying");
+                sleep(RESERVATION_RETRY_DELAY).await;
+                attempt += 1;
+            }
+            Err(error) => {
+                error!(?error, "failed to reserve sse indices");
+                return Err(error);
+            }
+        }
+    }
+}
+
 impl Drop for EventIndexer {
     fn drop(&mut self) {
         match fs::write(&self.persistent_cache, self.index.to_le_bytes()) {
@@ -82,6 +175,7 @@ mod tests {
     use std::iter;
 
     use super::*;
+    use crate::testing::fake_database::FakeDatabase;
 
     #[test]
     fn should_persist_in_cache() {
@@ -106,6 +200,49 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn should_recover_index_from_store_after_crash() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let store: EventIndexStore = Arc::new(FakeDatabase::new());
+        {
+            let mut event_indexer = EventIndexer::new(tempdir.path().to_path_buf());
+            for _ in 0..5 {
+                event_indexer.next_index();
+            }
+        }
+
+        // The store is initialized from the cache file.
+        let mut event_indexer = EventIndexer::new(tempdir.path().to_path_buf());
+        event_indexer.use_store(store.clone()).await.unwrap();
+        for expected_index in 5..8 {
+            assert_eq!(
+                event_indexer.allocate_index().await.unwrap(),
+                expected_index
+            );
+        }
+        // Simulate a crash, the cache file isn't updated.
+        std::mem::forget(event_indexer);
+
+        // The indices reserved but not used before the crash are skipped rather than reused.
+        let mut event_indexer = EventIndexer::new(tempdir.path().to_path_buf());
+        assert_eq!(event_indexer.current_index(), 5);
+        event_indexer.use_store(store).await.unwrap();
+        let next_index = 5 + RESERVATION_SIZE;
+        assert_eq!(event_indexer.current_index(), next_index);
+        assert_eq!(event_indexer.allocate_index().await.unwrap(), next_index);
+    }
+
+    #[tokio::test]
+    async fn should_return_error_once_reservation_keeps_failing() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut event_indexer = EventIndexer::new(tempdir.path().to_path_buf());
+        // The allocator state isn't initialized in the store, so reservations fail.
+        event_indexer.maybe_store = Some(Arc::new(FakeDatabase::new()));
+
+        assert!(event_indexer.allocate_index().await.is_err());
+        assert_eq!(event_indexer.current_index(), 0);
+    }
+
     #[test]
     fn should_wrap() {
         let tempdir = tempfile::tempdir().unwrap();
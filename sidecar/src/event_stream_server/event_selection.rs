@@ -0,0 +1,365 @@
+//! Selection of the events sent to a subscriber by the query of its request.
+//!
+//! A subscriber can narrow the events of the path it subscribed to down to some event types with
+//! `?include=BlockAdded,DeployProcessed`, to the deploy events of an account with
+//! `?account=<public key>`, to the transfers of a CEP-18 token with `?token=<contract hash>`, and
+//! to the contract events of a contract or of a name with `?contract=<contract hash>` and
+//! `?event=<event name>`. The events it didn't select are skipped before they are serialized, so
+//! they cost neither the sidecar nor the subscriber anything.
+
+use crate::{contract_events::CONTRACT_EVENT_DERIVER, token_transfers::TOKEN_TRANSFER_DERIVER};
+use casper_event_types::{sse_data::SseData, DeployHash};
+use casper_types::{AsymmetricType, PublicKey};
+use serde::Deserialize;
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+/// The event types a subscriber can select. The other events are needed for the stream to be
+/// understood, so they are always sent.
+const SELECTABLE_EVENT_TYPES: [&str; 9] = [
+    "BlockAdded",
+    "DeployAccepted",
+    "DeployProcessed",
+    "DeployExpired",
+    "Fault",
+    "FinalitySignature",
+    "Step",
+    "Unknown",
+    "Derived",
+];
+
+/// The account whose deploy events a subscriber selected.
+#[derive(Debug)]
+struct SelectedAccount {
+    /// Lowercase hex-encoded public key of the account.
+    public_key: String,
+    /// The deploys of the account accepted since the subscriber connected which are neither
+    /// processed nor expired yet. `DeployExpired` events don't carry the account, so they are
+    /// recognized by the hashes of these deploys.
+    pending_deploys: Mutex<HashSet<DeployHash>>,
+}
+
+impl SelectedAccount {
+    fn selects(&self, data: &SseData) -> bool {
+        let mut pending_deploys = self
+            .pending_deploys
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        match data {
+            SseData::DeployAccepted { deploy } => {
+                let selected = deploy.header().account().to_hex().to_lowercase() == self.public_key;
+                if selected {
+                    pending_deploys.insert(*deploy.hash());
+                }
+                selected
+            }
+            SseData::DeployProcessed {
+                deploy_hash,
+                account,
+                ..
+            } => {
+                pending_deploys.remove(deploy_hash);
+                account.to_hex().to_lowercase() == self.public_key
+            }
+            SseData::DeployExpired { deploy_hash } => pending_deploys.remove(deploy_hash),
+            _ => false,
+        }
+    }
+}
+
+/// The contract hash of a `TokenTransfer` event, the only field needed to select it.
+#[derive(Deserialize)]
+struct TokenTransferContract {
+    contract_hash: String,
+}
+
+/// Returns true if the event is a `TokenTransfer` event of the token with the lowercase
+/// hex-encoded `contract_hash`.
+fn is_token_transfer_of(data: &SseData, contract_hash: &str) -> bool {
+    match data {
+        SseData::Derived {
+            deriver, payload, ..
+        } if deriver == TOKEN_TRANSFER_DERIVER => {
+            serde_json::from_str::<TokenTransferContract>(payload.get())
+                .map_or(false, |token_transfer| {
+                    token_transfer.contract_hash == contract_hash
+                })
+        }
+        _ => false,
+    }
+}
+
+/// The contract hash and event name of a `ContractEvent` event, the only fields needed to select
+/// it.
+#[derive(Deserialize)]
+struct ContractEventName {
+    contract_hash: String,
+    event_name: String,
+}
+
+/// Returns true if the event is a `ContractEvent` event of the contract with the lowercase
+/// hex-encoded `maybe_contract_hash` and named `maybe_event_name`, each if given.
+fn is_contract_event_of(
+    data: &SseData,
+    maybe_contract_hash: Option<&str>,
+    maybe_event_name: Option<&str>,
+) -> bool {
+    match data {
+        SseData::Derived {
+            deriver, payload, ..
+        } if deriver == CONTRACT_EVENT_DERIVER => {
+            serde_json::from_str::<ContractEventName>(payload.get()).map_or(false, |event| {
+                maybe_contract_hash
+                    .map_or(true, |contract_hash| event.contract_hash == contract_hash)
+                    && maybe_event_name.map_or(true, |event_name| event.event_name == event_name)
+            })
+        }
+        _ => false,
+    }
+}
+
+/// The events a subscriber selected, all of those of its path if it didn't select any.
+#[derive(Clone, Debug, Default)]
+pub(super) struct EventSelection {
+    maybe_included_types: Option<Arc<HashSet<&'static str>>>,
+    maybe_account: Option<Arc<SelectedAccount>>,
+    /// Lowercase hex-encoded hash of the contract of the token whose transfers were selected.
+    maybe_token: Option<Arc<str>>,
+    /// Lowercase hex-encoded hash of the contract whose contract events were selected.
+    maybe_contract: Option<Arc<str>>,
+    /// Name of the contract events selected.
+    maybe_event_name: Option<Arc<str>>,
+}
+
+impl EventSelection {
+    /// Parses a comma-separated list of event types, returning `None` if it is empty or any of
+    /// them isn't selectable.
+    pub(super) fn with_included_types(self, included_types: &str) -> Option<Self> {
+        let mut types = HashSet::new();
+        for event_type in included_types.split(',').map(str::trim) {
+            let selectable = SELECTABLE_EVENT_TYPES
+                .iter()
+                .find(|selectable| **selectable == event_type)?;
+            types.insert(*selectable);
+        }
+        Some(EventSelection {
+            maybe_included_types: Some(Arc::new(types)),
+            ..self
+        })
+    }
+
+    /// Parses the hex-encoded public key of an account, returning `None` if it isn't valid.
+    pub(super) fn with_account(self, public_key: &str) -> Option<Self> {
+        let public_key = PublicKey::from_hex(public_key).ok()?;
+        Some(EventSelection {
+            maybe_account: Some(Arc::new(SelectedAccount {
+                public_key: public_key.to_hex().to_lowercase(),
+                pending_deploys: Mutex::new(HashSet::new()),
+            })),
+            ..self
+        })
+    }
+
+    /// Parses the hex-encoded hash of the contract of a token, returning `None` if it isn't valid.
+    pub(super) fn with_token(self, contract_hash: &str) -> Option<Self> {
+        match hex::decode(contract_hash) {
+            Ok(bytes) if bytes.len() == 32 => Some(EventSelection {
+                maybe_token: Some(Arc::from(contract_hash.to_lowercase())),
+                ..self
+            }),
+            _ => None,
+        }
+    }
+
+    /// Parses the hex-encoded hash of a contract, returning `None` if it isn't valid.
+    pub(super) fn with_contract(self, contract_hash: &str) -> Option<Self> {
+        match hex::decode(contract_hash) {
+            Ok(bytes) if bytes.len() == 32 => Some(EventSelection {
+                maybe_contract: Some(Arc::from(contract_hash.to_lowercase())),
+                ..self
+            }),
+            _ => None,
+        }
+    }
+
+    /// Takes the name of the selected contract events, returning `None` if it is empty.
+    pub(super) fn with_event_name(self, event_name: &str) -> Option<Self> {
+        if event_name.is_empty() {
+            return None;
+        }
+        Some(EventSelection {
+            maybe_event_name: Some(Arc::from(event_name)),
+            ..self
+        })
+    }
+
+    /// Returns true if the subscriber selected the given event. To be called once for every event
+    /// of the subscriber's path, in order, as the deploys of the selected account are tracked.
+    pub(super) fn selects(&self, data: &SseData) -> bool {
+        if !SELECTABLE_EVENT_TYPES.contains(&data.type_label()) {
+            return true;
+        }
+        self.includes_type(data)
+            && self
+                .maybe_account
+                .as_ref()
+                .map_or(true, |account| account.selects(data))
+            && self.maybe_token.as_ref().map_or(true, |contract_hash| {
+                is_token_transfer_of(data, contract_hash)
+            })
+            && self.selects_contract_event(data)
+    }
+
+    /// Returns true if the subscriber selected no contract events, or the given event is one of
+    /// those it selected.
+    fn selects_contract_event(&self, data: &SseData) -> bool {
+        if self.maybe_contract.is_none() && self.maybe_event_name.is_none() {
+            return true;
+        }
+        is_contract_event_of(
+            data,
+            self.maybe_contract.as_deref(),
+            self.maybe_event_name.as_deref(),
+        )
+    }
+
+    /// Returns true if the subscriber selected the type of the given event.
+    pub(super) fn includes_type(&self, data: &SseData) -> bool {
+        let event_type = data.type_label();
+        match &self.maybe_included_types {
+            None => true,
+            Some(included_types) => {
+                !SELECTABLE_EVENT_TYPES.contains(&event_type) || included_types.contains(event_type)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use casper_types::testing::TestRng;
+
+    #[test]
+    fn should_parse_included_types() {
+        let parse = |included_types| EventSelection::default().with_included_types(included_types);
+        assert!(parse("BlockAdded,DeployProcessed").is_some());
+        assert!(parse("BlockAdded, Step").is_some());
+        assert!(parse("").is_none());
+        assert!(parse("BlockAdded,").is_none());
+        assert!(parse("Blockadded").is_none());
+        assert!(parse("ApiVersion").is_none());
+    }
+
+    #[test]
+    fn should_select_included_types_and_stream_control_events() {
+        let mut rng = TestRng::new();
+        let selection = EventSelection::default()
+            .with_included_types("BlockAdded")
+            .unwrap();
+
+        assert!(selection.selects(&SseData::random_block_added(&mut rng)));
+        assert!(selection.selects(&SseData::random_api_version(&mut rng)));
+        assert!(selection.selects(&SseData::Shutdown));
+        assert!(!selection.selects(&SseData::random_deploy_processed(&mut rng)));
+        assert!(!selection.selects(&SseData::random_finality_signature(&mut rng)));
+
+        let everything = EventSelection::default();
+        assert!(everything.selects(&SseData::random_deploy_processed(&mut rng)));
+    }
+
+    #[test]
+    fn should_select_deploy_events_of_account_only() {
+        let mut rng = TestRng::new();
+        let (deploy_accepted, deploy) = SseData::random_deploy_accepted(&mut rng);
+        let public_key = deploy.header().account().to_hex();
+        assert!(EventSelection::default().with_account("01zz").is_none());
+        let selection = EventSelection::default()
+            .with_account(&public_key.to_uppercase())
+            .unwrap();
+
+        let (other_deploy_accepted, _) = SseData::random_deploy_accepted(&mut rng);
+        assert!(selection.selects(&deploy_accepted));
+        assert!(!selection.selects(&other_deploy_accepted));
+        assert!(!selection.selects(&SseData::random_block_added(&mut rng)));
+        assert!(selection.selects(&SseData::random_api_version(&mut rng)));
+
+        let expired = SseData::DeployExpired {
+            deploy_hash: *deploy.hash(),
+        };
+        assert!(selection.selects(&expired));
+        assert!(!selection.selects(&expired));
+        assert!(!selection.selects(&SseData::random_deploy_expired(&mut rng)));
+    }
+
+    #[test]
+    fn should_select_transfers_of_token_only() {
+        let mut rng = TestRng::new();
+        let contract_hash = "0a".repeat(32);
+        let token_transfer = |deriver: &str, contract_hash: &str| SseData::Derived {
+            deriver: deriver.to_string(),
+            source_event_type: "DeployProcessed".to_string(),
+            payload: serde_json::value::to_raw_value(
+                &serde_json::json!({ "contract_hash": contract_hash, "event_id": 1 }),
+            )
+            .unwrap(),
+        };
+        assert!(EventSelection::default().with_token("0a").is_none());
+        assert!(EventSelection::default()
+            .with_token(&"zz".repeat(32))
+            .is_none());
+        let selection = EventSelection::default()
+            .with_token(&contract_hash.to_uppercase())
+            .unwrap();
+
+        assert!(selection.selects(&token_transfer(TOKEN_TRANSFER_DERIVER, &contract_hash)));
+        assert!(!selection.selects(&token_transfer(TOKEN_TRANSFER_DERIVER, &"0b".repeat(32))));
+        assert!(!selection.selects(&token_transfer("Other", &contract_hash)));
+        assert!(!selection.selects(&SseData::random_deploy_processed(&mut rng)));
+        assert!(selection.selects(&SseData::random_api_version(&mut rng)));
+    }
+
+    #[test]
+    fn should_select_contract_events_of_contract_and_name() {
+        let mut rng = TestRng::new();
+        let contract_hash = "0c".repeat(32);
+        let contract_event =
+            |deriver: &str, contract_hash: &str, event_name: &str| SseData::Derived {
+                deriver: deriver.to_string(),
+                source_event_type: "DeployProcessed".to_string(),
+                payload: serde_json::value::to_raw_value(&serde_json::json!({
+                    "contract_hash": contract_hash,
+                    "event_id": 1,
+                    "event_name": event_name,
+                }))
+                .unwrap(),
+            };
+        assert!(EventSelection::default().with_contract("0c").is_none());
+        assert!(EventSelection::default().with_event_name("").is_none());
+        let by_contract = EventSelection::default()
+            .with_contract(&contract_hash.to_uppercase())
+            .unwrap();
+        let by_name = EventSelection::default()
+            .with_event_name("Deposit")
+            .unwrap();
+        let by_both = by_contract.clone().with_event_name("Deposit").unwrap();
+
+        let deposit = contract_event(CONTRACT_EVENT_DERIVER, &contract_hash, "Deposit");
+        let withdrawal = contract_event(CONTRACT_EVENT_DERIVER, &contract_hash, "Withdrawal");
+        let other_deposit = contract_event(CONTRACT_EVENT_DERIVER, &"0d".repeat(32), "Deposit");
+        assert!(by_contract.selects(&deposit));
+        assert!(by_contract.selects(&withdrawal));
+        assert!(!by_contract.selects(&other_deposit));
+        assert!(by_name.selects(&deposit));
+        assert!(!by_name.selects(&withdrawal));
+        assert!(by_name.selects(&other_deposit));
+        assert!(by_both.selects(&deposit));
+        assert!(!by_both.selects(&withdrawal));
+        assert!(!by_both.selects(&other_deposit));
+        assert!(!by_both.selects(&contract_event("Other", &contract_hash, "Deposit")));
+        assert!(!by_both.selects(&SseData::random_deploy_processed(&mut rng)));
+        assert!(by_both.selects(&SseData::random_api_version(&mut rng)));
+    }
+}
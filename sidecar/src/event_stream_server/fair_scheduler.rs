@@ -0,0 +1,109 @@
+//! Fair scheduling of the event streams of the subscribers.
+//!
+//! The stream of every subscriber is polled by the task serving its connection. During a burst of
+//! events, a subscriber with a large backlog always has its next event ready, so its task would keep
+//! writing events without giving the executor back to the tasks of the other subscribers. To
+//! round-robin the flushes across subscribers, a stream ends its turn after `EVENTS_PER_TURN`
+//! consecutive events by yielding to the executor, and is polled again once the other ready tasks
+//! had their turn.
+
+use futures::Stream;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Maximum number of events a subscriber's stream yields in a single turn.
+const EVENTS_PER_TURN: usize = 32;
+
+/// Stream yielding to the executor after every turn of `EVENTS_PER_TURN` consecutive items.
+pub(super) struct FairShare<S> {
+    inner: S,
+    yielded_in_turn: usize,
+}
+
+impl<S> FairShare<S> {
+    pub(super) fn new(inner: S) -> Self {
+        FairShare {
+            inner,
+            yielded_in_turn: 0,
+        }
+    }
+}
+
+impl<S: Stream + Unpin> Stream for FairShare<S> {
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.yielded_in_turn >= EVENTS_PER_TURN {
+            // End the turn, asking to be polled again after the other ready tasks.
+            self.yielded_in_turn = 0;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        let poll = Pin::new(&mut self.inner).poll_next(cx);
+        match &poll {
+            Poll::Ready(Some(_)) => self.yielded_in_turn += 1,
+            // Waiting for the next event ends the turn anyway.
+            Poll::Ready(None) | Poll::Pending => self.yielded_in_turn = 0,
+        }
+        poll
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{stream, task::noop_waker, StreamExt};
+
+    #[test]
+    fn should_yield_after_each_turn() {
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut events = FairShare::new(stream::iter(0..EVENTS_PER_TURN * 2));
+
+        for expected in 0..EVENTS_PER_TURN {
+            assert_eq!(events.poll_next_unpin(&mut cx), Poll::Ready(Some(expected)));
+        }
+        assert_eq!(events.poll_next_unpin(&mut cx), Poll::Pending);
+        for expected in EVENTS_PER_TURN..EVENTS_PER_TURN * 2 {
+            assert_eq!(events.poll_next_unpin(&mut cx), Poll::Ready(Some(expected)));
+        }
+        assert_eq!(events.poll_next_unpin(&mut cx), Poll::Pending);
+        assert_eq!(events.poll_next_unpin(&mut cx), Poll::Ready(None));
+    }
+
+    #[tokio::test]
+    async fn should_interleave_subscribers_with_backlogs() {
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let handles: Vec<_> = vec!["a", "b"]
+            .into_iter()
+            .map(|subscriber| {
+                let sender = sender.clone();
+                let mut events = FairShare::new(stream::iter(0..EVENTS_PER_TURN * 4));
+                tokio::spawn(async move {
+                    while events.next().await.is_some() {
+                        let _ = sender.send(subscriber);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        drop(sender);
+
+        let mut order = Vec::new();
+        while let Some(subscriber) = receiver.recv().await {
+            order.push(subscriber);
+        }
+        // The second subscriber is served before the first one has flushed its whole backlog.
+        let first_turns = &order[..EVENTS_PER_TURN * 2];
+        assert!(first_turns.contains(&"a"), "{:?}", order);
+        assert!(first_turns.contains(&"b"), "{:?}", order);
+    }
+}
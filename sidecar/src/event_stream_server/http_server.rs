@@ -1,9 +1,11 @@
-use std::str::FromStr;
+use std::{str::FromStr, time::Instant};
 
 use super::{
     config::Config,
     event_indexer::EventIndex,
+    retained_events::{in_wraparound_zone, missed_event_count, RetainedEvents},
     sse_server::{BroadcastChannelMessage, Id, NewSubscriberInfo, ServerSentEvent},
+    status_events::StatusEvent,
 };
 use casper_event_types::{sse_data::SseData, Filter};
 use casper_types::ProtocolVersion;
@@ -20,9 +22,14 @@ use tokio::{
 };
 use tracing::{error, info, trace};
 use wheelbuf::WheelBuf;
-pub type InboundData = (Option<u32>, SseData, Option<Filter>, Option<String>);
-pub type OutboundReceiver =
-    mpsc::UnboundedReceiver<(Option<EventIndex>, SseData, Option<Filter>, Option<String>)>;
+pub type InboundData = (Option<u32>, SseData, Option<Filter>, Option<String>, bool);
+pub type OutboundReceiver = mpsc::UnboundedReceiver<(
+    Option<EventIndex>,
+    SseData,
+    Option<Filter>,
+    Option<String>,
+    bool,
+)>;
 pub static SIDECAR_VERSION: Lazy<ProtocolVersion> = Lazy::new(|| {
     let major: u32 = FromStr::from_str(env!("CARGO_PKG_VERSION_MAJOR")).unwrap();
     let minor: u32 = FromStr::from_str(env!("CARGO_PKG_VERSION_MINOR")).unwrap();
@@ -40,6 +47,7 @@ pub static SIDECAR_VERSION: Lazy<ProtocolVersion> = Lazy::new(|| {
 /// * `new_subscriber_info_receiver` is used to notify the server of the details of a new client
 ///   having subscribed to the event stream.  It allows the server to populate that client's stream
 ///   with the requested number of historical events.
+/// * `retained_events` is kept up to date with the IDs of the earliest and latest buffered events.
 pub(super) async fn run(
     config: Config,
     server_with_shutdown: impl Future<Output = ()> + Send + 'static,
@@ -47,6 +55,7 @@ pub(super) async fn run(
     mut data_receiver: OutboundReceiver,
     broadcaster: broadcast::Sender<BroadcastChannelMessage>,
     mut new_subscriber_info_receiver: mpsc::UnboundedReceiver<NewSubscriberInfo>,
+    retained_events: RetainedEvents,
 ) {
     let server_joiner = task::spawn(server_with_shutdown);
     let mut buffer = build_buffer(config);
@@ -63,7 +72,7 @@ pub(super) async fn run(
                     }
                 }
                 maybe_data = data_receiver.recv() => {
-                    if handle_incoming_data(maybe_data, &mut latest_protocol_version, &mut buffer, &broadcaster).await.is_err() {
+                    if handle_incoming_data(maybe_data, &mut latest_protocol_version, &mut buffer, &broadcaster, &retained_events).await.is_err() {
                         break;
                     }
                 }
@@ -116,9 +125,10 @@ async fn handle_incoming_data(
         (ProtocolVersion, ServerSentEvent),
     >,
     broadcaster: &broadcast::Sender<BroadcastChannelMessage>,
+    retained_events: &RetainedEvents,
 ) -> Result<(), ()> {
     match maybe_data {
-        Some((maybe_event_index, data, inbound_filter, maybe_json_data)) => {
+        Some((maybe_event_index, data, inbound_filter, maybe_json_data, watched)) => {
             // Buffer the data and broadcast it to subscribed clients.
             trace!("Event stream server received {:?}", data);
             let event = ServerSentEvent {
@@ -126,6 +136,8 @@ async fn handle_incoming_data(
                 data: data.clone(),
                 json_data: maybe_json_data,
                 inbound_filter,
+                watched,
+                broadcast_at: Some(Instant::now()),
             };
             match data {
                 SseData::ApiVersion(v) => *latest_protocol_version = Some(v),
@@ -134,7 +146,12 @@ async fn handle_incoming_data(
                         error!("Trying to buffer data without an api version observed beforehand");
                     }
                     Some(v) => {
-                        buffer.push((*v, event.clone()));
+                        let replayed_event = ServerSentEvent {
+                            broadcast_at: None,
+                            ..event.clone()
+                        };
+                        buffer.push((*v, replayed_event));
+                        update_retained_events(buffer, maybe_event_index, retained_events);
                     }
                 },
             };
@@ -152,6 +169,19 @@ async fn handle_incoming_data(
     }
 }
 
+fn update_retained_events(
+    buffer: &WheelBuf<Vec<(ProtocolVersion, ServerSentEvent)>, (ProtocolVersion, ServerSentEvent)>,
+    maybe_latest_event_id: Option<Id>,
+    retained_events: &RetainedEvents,
+) {
+    let maybe_earliest_event_id = buffer.iter().next().and_then(|tuple| tuple.1.id);
+    if let (Some(earliest_event_id), Some(latest_event_id)) =
+        (maybe_earliest_event_id, maybe_latest_event_id)
+    {
+        retained_events.set(earliest_event_id, latest_event_id);
+    }
+}
+
 async fn register_new_subscriber(
     subscriber: NewSubscriberInfo,
     buffer: &WheelBuf<Vec<(ProtocolVersion, ServerSentEvent)>, (ProtocolVersion, ServerSentEvent)>,
@@ -171,16 +201,14 @@ async fn register_new_subscriber(
         // the wrapping transition.
         let mut observed_protocol_version: Option<ProtocolVersion> = None;
         let buffer_size = buffer.capacity() as Id;
-        let in_wraparound_zone = buffer
+        let ids_wrap_round = buffer
             .iter()
             .next()
-            .map(|event| {
-                let id = event.1.id.unwrap();
-                id > Id::MAX - buffer_size || id < buffer_size
-            })
+            .map(|event| in_wraparound_zone(event.1.id.unwrap(), buffer_size))
             .unwrap_or_default();
+        notify_unavailable_events(&subscriber, buffer, start_index);
         for tuple in buffer.iter().skip_while(|tuple| {
-            if in_wraparound_zone {
+            if ids_wrap_round {
                 tuple.1.id.unwrap().wrapping_add(buffer_size)
                     < start_index.wrapping_add(buffer_size)
             } else {
@@ -206,6 +234,31 @@ async fn register_new_subscriber(
     send_api_version_if_necessary(observed_events, latest_protocol_version, subscriber).await;
 }
 
+/// Tells a subscriber which opted in to status events that some of the events it asked to be
+/// replayed are no longer buffered, and from which event on the replay starts instead.
+fn notify_unavailable_events(
+    subscriber: &NewSubscriberInfo,
+    buffer: &WheelBuf<Vec<(ProtocolVersion, ServerSentEvent)>, (ProtocolVersion, ServerSentEvent)>,
+    start_index: Id,
+) {
+    let status_sender = match &subscriber.maybe_status_sender {
+        Some(status_sender) => status_sender,
+        None => return,
+    };
+    let first_id = match buffer.iter().next().and_then(|tuple| tuple.1.id) {
+        Some(first_id) => first_id,
+        None => return,
+    };
+    let missed_events = missed_event_count(first_id, start_index, buffer.capacity() as Id);
+    if missed_events > 0 {
+        let _ = status_sender.send(StatusEvent::ReplayUnavailable {
+            requested_event_id: start_index,
+            earliest_event_id: first_id,
+            missed_events: u64::from(missed_events),
+        });
+    }
+}
+
 async fn send_api_version_if_necessary(
     observed_events: bool,
     latest_protocol_version: Option<ProtocolVersion>,
@@ -0,0 +1,169 @@
+//! Priority classes of the outbound events.
+//!
+//! Every event type belongs to the high, normal or low priority class. While a subscriber falls
+//! behind, the events of the normal and low classes reaching its stream later than the shedding
+//! delay of their class are dropped for this subscriber, so that it catches up on the high priority
+//! events first. The low class has the shorter delay, so it is shed first.
+
+use super::sse_server::ServerSentEvent;
+use crate::types::config::PriorityClassesConfig;
+use casper_event_types::metrics::OUTBOUND_DROPS;
+use std::{collections::HashMap, time::Duration};
+use tracing::warn;
+
+/// The event types which can be assigned a priority class. The other events are needed for the
+/// stream to be understood, so they are never dropped.
+const CLASSIFIABLE_EVENT_TYPES: [&str; 9] = [
+    "BlockAdded",
+    "DeployAccepted",
+    "DeployProcessed",
+    "DeployExpired",
+    "Fault",
+    "FinalitySignature",
+    "Step",
+    "Unknown",
+    "Derived",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum PriorityClass {
+    High,
+    Normal,
+    Low,
+}
+
+impl PriorityClass {
+    /// The reason of dropping an event of this class, as used in metrics.
+    fn shedding_reason(&self) -> &'static str {
+        match self {
+            PriorityClass::High => "shed_high_priority",
+            PriorityClass::Normal => "shed_normal_priority",
+            PriorityClass::Low => "shed_low_priority",
+        }
+    }
+}
+
+/// The priority classes of the event types, along with the shedding delays of the classes.
+#[derive(Clone, Debug)]
+pub(super) struct PriorityClasses {
+    classes: HashMap<&'static str, PriorityClass>,
+    normal_shedding_delay: Duration,
+    low_shedding_delay: Duration,
+}
+
+impl PriorityClasses {
+    pub(super) fn new(config: &PriorityClassesConfig) -> Self {
+        let mut classes = HashMap::new();
+        for (class, event_types) in [
+            (PriorityClass::Normal, &config.normal),
+            (PriorityClass::Low, &config.low),
+        ] {
+            for event_type in event_types {
+                match CLASSIFIABLE_EVENT_TYPES
+                    .iter()
+                    .find(|classifiable| **classifiable == event_type.as_str())
+                {
+                    Some(classifiable) => {
+                        classes.insert(*classifiable, class);
+                    }
+                    None => warn!(
+                        %event_type,
+                        "ignoring unknown or unclassifiable event type of priority class"
+                    ),
+                }
+            }
+        }
+        PriorityClasses {
+            classes,
+            normal_shedding_delay: Duration::from_millis(
+                config.normal_shedding_delay_in_milliseconds,
+            ),
+            low_shedding_delay: Duration::from_millis(config.low_shedding_delay_in_milliseconds),
+        }
+    }
+
+    fn class_of(&self, event: &ServerSentEvent) -> PriorityClass {
+        self.classes
+            .get(event.data.type_label())
+            .copied()
+            .unwrap_or(PriorityClass::High)
+    }
+
+    /// Returns whether the broadcast `event` reaches the stream of a subscriber of `stream_filter`
+    /// too late for its priority class, counting it as dropped if so.
+    pub(super) fn should_shed(&self, event: &ServerSentEvent, stream_filter: &str) -> bool {
+        let delay = match event.broadcast_at {
+            Some(broadcast_at) => broadcast_at.elapsed(),
+            None => return false,
+        };
+        let class = self.class_of(event);
+        let shed = match class {
+            PriorityClass::High => false,
+            PriorityClass::Normal => delay > self.normal_shedding_delay,
+            PriorityClass::Low => delay > self.low_shedding_delay,
+        };
+        if shed {
+            OUTBOUND_DROPS
+                .with_label_values(&[stream_filter, class.shedding_reason()])
+                .inc();
+        }
+        shed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use casper_event_types::sse_data::SseData;
+    use casper_types::testing::TestRng;
+    use std::time::Instant;
+
+    fn event_broadcast_ago(data: SseData, ago: Duration) -> ServerSentEvent {
+        ServerSentEvent {
+            id: Some(1),
+            data,
+            json_data: None,
+            inbound_filter: None,
+            watched: false,
+            broadcast_at: Instant::now().checked_sub(ago),
+        }
+    }
+
+    #[test]
+    fn should_shed_low_priority_classes_first() {
+        let mut rng = TestRng::new();
+        let classes = PriorityClasses::new(&PriorityClassesConfig::default());
+        let delay = Duration::from_secs(2);
+        let block = event_broadcast_ago(SseData::random_block_added(&mut rng), delay);
+        let deploy = event_broadcast_ago(SseData::random_deploy_expired(&mut rng), delay);
+        let signature = event_broadcast_ago(SseData::random_finality_signature(&mut rng), delay);
+
+        assert!(!classes.should_shed(&block, "events/main"));
+        assert!(!classes.should_shed(&deploy, "events/main"));
+        assert!(classes.should_shed(&signature, "events/main"));
+
+        let late_deploy = event_broadcast_ago(
+            SseData::random_deploy_expired(&mut rng),
+            Duration::from_secs(6),
+        );
+        assert!(classes.should_shed(&late_deploy, "events/main"));
+    }
+
+    #[test]
+    fn should_never_shed_replayed_or_unclassifiable_events() {
+        let mut rng = TestRng::new();
+        let config = PriorityClassesConfig {
+            low: vec!["FinalitySignature".to_string(), "Shutdown".to_string()],
+            ..PriorityClassesConfig::default()
+        };
+        let classes = PriorityClasses::new(&config);
+        let mut replayed =
+            event_broadcast_ago(SseData::random_finality_signature(&mut rng), Duration::ZERO);
+        replayed.broadcast_at = None;
+        assert!(!classes.should_shed(&replayed, "events/sigs"));
+
+        let shutdown = event_broadcast_ago(SseData::Shutdown, Duration::from_secs(60));
+        assert_eq!(classes.class_of(&shutdown), PriorityClass::High);
+        assert!(!classes.should_shed(&shutdown, "events/sigs"));
+    }
+}
@@ -0,0 +1,116 @@
+//! The range of event IDs retained in the buffer of the event stream server, i.e. of the events
+//! which can still be replayed to subscribers.
+
+use super::sse_server::Id;
+use serde::Serialize;
+use std::sync::{Arc, RwLock};
+
+/// The replay window of the event stream server, as returned by its `/info` endpoint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub(super) struct ReplayWindow {
+    /// ID of the earliest buffered event, `None` until an event is buffered.
+    pub(super) earliest_event_id: Option<Id>,
+    /// ID of the latest buffered event, `None` until an event is buffered.
+    pub(super) latest_event_id: Option<Id>,
+    /// Maximum number of buffered events.
+    pub(super) buffer_length: u32,
+}
+
+/// The IDs of the earliest and latest buffered events, updated by the task owning the buffer and
+/// read by the handlers of new subscriptions.
+#[derive(Clone, Debug)]
+pub(super) struct RetainedEvents {
+    range: Arc<RwLock<Option<(Id, Id)>>>,
+    buffer_length: u32,
+}
+
+impl RetainedEvents {
+    pub(super) fn new(buffer_length: u32) -> Self {
+        RetainedEvents {
+            range: Arc::new(RwLock::new(None)),
+            buffer_length,
+        }
+    }
+
+    /// Records the IDs of the earliest and latest buffered events.
+    pub(super) fn set(&self, earliest_event_id: Id, latest_event_id: Id) {
+        if let Ok(mut range) = self.range.write() {
+            *range = Some((earliest_event_id, latest_event_id));
+        }
+    }
+
+    pub(super) fn replay_window(&self) -> ReplayWindow {
+        let maybe_range = self.range.read().ok().and_then(|range| *range);
+        ReplayWindow {
+            earliest_event_id: maybe_range.map(|(earliest, _)| earliest),
+            latest_event_id: maybe_range.map(|(_, latest)| latest),
+            buffer_length: self.buffer_length,
+        }
+    }
+
+    /// Returns the ID of the earliest buffered event if the events from `start_from` onwards
+    /// aren't all buffered anymore.
+    pub(super) fn earliest_if_unavailable(&self, start_from: Id) -> Option<Id> {
+        let (earliest_event_id, _) = self.range.read().ok().and_then(|range| *range)?;
+        if missed_event_count(earliest_event_id, start_from, self.buffer_length as Id) > 0 {
+            Some(earliest_event_id)
+        } else {
+            None
+        }
+    }
+}
+
+/// Whether the IDs of the buffered events are considered to wrap round, or did so recently, given
+/// the ID of the earliest buffered event: it is in the range [0, buffer size) or
+/// (Id::MAX - buffer size, Id::MAX].
+pub(super) fn in_wraparound_zone(earliest_event_id: Id, buffer_size: Id) -> bool {
+    earliest_event_id > Id::MAX - buffer_size || earliest_event_id < buffer_size
+}
+
+/// Returns how many of the events from `start_from` onwards are no longer buffered, given the ID
+/// of the earliest buffered event.
+///
+/// In the wraparound zone, `buffer_size` is added to both IDs, effectively shifting them past the
+/// wrapping transition.
+pub(super) fn missed_event_count(earliest_event_id: Id, start_from: Id, buffer_size: Id) -> Id {
+    let shift = if in_wraparound_zone(earliest_event_id, buffer_size) {
+        buffer_size
+    } else {
+        0
+    };
+    earliest_event_id
+        .wrapping_add(shift)
+        .saturating_sub(start_from.wrapping_add(shift))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_report_unavailable_start_from_across_wraparound() {
+        let retained_events = RetainedEvents::new(10);
+        assert_eq!(retained_events.earliest_if_unavailable(0), None);
+        assert_eq!(
+            retained_events.replay_window(),
+            ReplayWindow {
+                earliest_event_id: None,
+                latest_event_id: None,
+                buffer_length: 10,
+            }
+        );
+
+        retained_events.set(100, 109);
+        assert_eq!(retained_events.earliest_if_unavailable(99), Some(100));
+        assert_eq!(retained_events.earliest_if_unavailable(100), None);
+        assert_eq!(retained_events.earliest_if_unavailable(120), None);
+
+        retained_events.set(Id::MAX - 4, 4);
+        assert_eq!(
+            retained_events.earliest_if_unavailable(Id::MAX - 5),
+            Some(Id::MAX - 4)
+        );
+        assert_eq!(retained_events.earliest_if_unavailable(Id::MAX), None);
+        assert_eq!(retained_events.earliest_if_unavailable(2), None);
+    }
+}
@@ -0,0 +1,300 @@
+//! Detection of event stream subscribers which can't keep up with the events.
+//!
+//! A subscriber is saturated while the events reaching its stream were broadcast more than
+//! `SATURATION_DELAY` earlier, i.e. while they pile up in its buffer. A subscriber saturated for
+//! longer than the configured timeout, or whose buffer overflowed, is sent a `warning` event and a
+//! comment giving the reason, then disconnected. Until then, a subscriber falling behind may be
+//! spared the events of low priority classes. The saturated subscribers are counted in the
+//! `event_stream_saturated_subscribers` metric whether they are disconnected or not.
+
+use super::{endpoint::Endpoint, priority::PriorityClasses, sse_server::ServerSentEvent};
+use crate::schema_version::{self, SchemaVersion};
+use casper_event_types::metrics::{EVENT_STREAM_SATURATED_SUBSCRIBERS, EVENT_STREAM_SUBSCRIBERS};
+use serde_json::json;
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::warn;
+use warp::sse::Event as WarpServerSentEvent;
+
+/// Delay since the broadcast of an event beyond which the subscriber it is sent to is saturated.
+const SATURATION_DELAY: Duration = Duration::from_secs(1);
+/// Name of the SSE sent to a subscriber about to be disconnected.
+const WARNING_EVENT_NAME: &str = "warning";
+
+/// Why a subscriber is disconnected by the server.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Disconnection {
+    /// The subscriber's buffer overflowed, so it missed this many events.
+    Lagged(u64),
+    /// The subscriber's buffer stayed saturated for this long.
+    Saturated(Duration),
+}
+
+impl Disconnection {
+    pub(super) fn reason(&self) -> String {
+        match self {
+            Disconnection::Lagged(amount) => {
+                format!("subscriber lagged behind and missed {} events", amount)
+            }
+            Disconnection::Saturated(duration) => format!(
+                "subscriber's buffer was saturated for {} seconds",
+                duration.as_secs()
+            ),
+        }
+    }
+
+    /// The frames sent to the subscriber before it is disconnected. A lagged subscriber missed
+    /// events, so its stream ends with an error rather than gracefully. The warning is serialized
+    /// in `schema_version`.
+    pub(super) fn into_warp_events(
+        self,
+        schema_version: SchemaVersion,
+    ) -> Vec<Result<WarpServerSentEvent, RecvError>> {
+        let reason = self.reason();
+        let data = if schema_version::is_enveloped(schema_version) {
+            json!({ "schema_version": schema_version, "reason": reason })
+        } else {
+            json!({ "reason": reason })
+        };
+        let warning = WarpServerSentEvent::default()
+            .event(WARNING_EVENT_NAME)
+            .json_data(data)
+            .unwrap_or_else(|error| {
+                warn!(%error, "failed to jsonify warning event");
+                WarpServerSentEvent::default().event(WARNING_EVENT_NAME)
+            });
+        let comment = WarpServerSentEvent::default().comment(format!("disconnected: {}", reason));
+        let mut frames = vec![Ok(warning), Ok(comment)];
+        if let Disconnection::Lagged(amount) = self {
+            frames.push(Err(RecvError::Lagged(amount)));
+        }
+        frames
+    }
+}
+
+/// Watches the events sent to a single subscriber to tell when it has to be disconnected. The
+/// subscriber is counted as connected for as long as its monitor lives.
+pub(super) struct SlowSubscriberMonitor {
+    /// Identity of the subscriber, used for logging only.
+    subscriber: String,
+    stream_filter: &'static Endpoint,
+    maybe_timeout: Option<Duration>,
+    maybe_priority_classes: Option<Arc<PriorityClasses>>,
+    saturated_since: Option<Instant>,
+    disconnection: Arc<Mutex<Option<Disconnection>>>,
+}
+
+impl SlowSubscriberMonitor {
+    pub(super) fn new(
+        subscriber: String,
+        stream_filter: &'static Endpoint,
+        maybe_timeout: Option<Duration>,
+        maybe_priority_classes: Option<Arc<PriorityClasses>>,
+    ) -> Self {
+        EVENT_STREAM_SUBSCRIBERS
+            .with_label_values(&[stream_filter.label()])
+            .inc();
+        SlowSubscriberMonitor {
+            subscriber,
+            stream_filter,
+            maybe_timeout,
+            maybe_priority_classes,
+            saturated_since: None,
+            disconnection: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Handle to the reason of the disconnection, set once the subscriber is to be disconnected.
+    pub(super) fn disconnection(&self) -> Arc<Mutex<Option<Disconnection>>> {
+        Arc::clone(&self.disconnection)
+    }
+
+    /// Takes note of the delay of a broadcast `event`, returning the reason to disconnect the
+    /// subscriber if it has been saturated for longer than the timeout.
+    pub(super) fn observe(&mut self, event: &ServerSentEvent) -> Option<Disconnection> {
+        let broadcast_at = event.broadcast_at?;
+        if broadcast_at.elapsed() <= SATURATION_DELAY {
+            self.set_saturated_since(None);
+            return None;
+        }
+        let saturated_since = match self.saturated_since {
+            Some(saturated_since) => saturated_since,
+            None => {
+                let saturated_since = broadcast_at + SATURATION_DELAY;
+                self.set_saturated_since(Some(saturated_since));
+                saturated_since
+            }
+        };
+        let timeout = self.maybe_timeout?;
+        let saturated_for = saturated_since.elapsed();
+        if saturated_for <= timeout {
+            return None;
+        }
+        Some(Disconnection::Saturated(saturated_for))
+    }
+
+    /// Records when the subscriber became saturated, counting it among the saturated subscribers of
+    /// its path until it isn't anymore.
+    fn set_saturated_since(&mut self, maybe_saturated_since: Option<Instant>) {
+        let saturated_subscribers =
+            EVENT_STREAM_SATURATED_SUBSCRIBERS.with_label_values(&[self.stream_filter.label()]);
+        match (self.saturated_since, maybe_saturated_since) {
+            (None, Some(_)) => saturated_subscribers.inc(),
+            (Some(_), None) => saturated_subscribers.dec(),
+            _ => {}
+        }
+        self.saturated_since = maybe_saturated_since;
+    }
+
+    /// Returns whether the broadcast `event` is to be dropped as it reaches the subscriber too late
+    /// for its priority class.
+    pub(super) fn should_shed(&self, event: &ServerSentEvent) -> bool {
+        self.maybe_priority_classes
+            .as_ref()
+            .map(|classes| classes.should_shed(event, self.stream_filter.label()))
+            .unwrap_or_default()
+    }
+
+    /// Records that the subscriber is to be disconnected, ending its stream of ongoing events.
+    pub(super) fn disconnect(
+        &self,
+        disconnection: Disconnection,
+    ) -> Option<Result<ServerSentEvent, RecvError>> {
+        warn!(
+            subscriber = %self.subscriber,
+            path = self.stream_filter.label(),
+            reason = %disconnection.reason(),
+            "disconnecting event stream subscriber"
+        );
+        *self
+            .disconnection
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(disconnection);
+        Some(Err(RecvError::Closed))
+    }
+}
+
+impl Drop for SlowSubscriberMonitor {
+    fn drop(&mut self) {
+        self.set_saturated_since(None);
+        EVENT_STREAM_SUBSCRIBERS
+            .with_label_values(&[self.stream_filter.label()])
+            .dec();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use casper_event_types::sse_data::SseData;
+    use casper_types::testing::TestRng;
+
+    fn event_broadcast_ago(rng: &mut TestRng, ago: Duration) -> ServerSentEvent {
+        ServerSentEvent {
+            id: Some(1),
+            data: SseData::random_block_added(rng),
+            json_data: None,
+            inbound_filter: None,
+            watched: false,
+            broadcast_at: Instant::now().checked_sub(ago),
+        }
+    }
+
+    #[test]
+    fn should_disconnect_subscriber_saturated_beyond_timeout() {
+        let mut rng = TestRng::new();
+        let mut monitor = SlowSubscriberMonitor::new(
+            "127.0.0.1:12345".to_string(),
+            &Endpoint::Main,
+            Some(Duration::from_secs(2)),
+            None,
+        );
+        let fresh = event_broadcast_ago(&mut rng, Duration::ZERO);
+        assert_eq!(monitor.observe(&fresh), None);
+
+        let delayed = event_broadcast_ago(&mut rng, Duration::from_secs(2));
+        assert_eq!(monitor.observe(&delayed), None);
+        assert!(monitor.saturated_since.is_some());
+
+        let stale = event_broadcast_ago(&mut rng, Duration::from_secs(5));
+        assert!(matches!(
+            monitor.observe(&stale),
+            Some(Disconnection::Saturated(_))
+        ));
+
+        assert_eq!(monitor.observe(&fresh), None);
+        assert!(monitor.saturated_since.is_none());
+    }
+
+    #[test]
+    fn should_not_disconnect_without_timeout() {
+        let mut rng = TestRng::new();
+        let mut monitor =
+            SlowSubscriberMonitor::new("127.0.0.1:12345".to_string(), &Endpoint::Main, None, None);
+        let stale = event_broadcast_ago(&mut rng, Duration::from_secs(60));
+        assert_eq!(monitor.observe(&stale), None);
+    }
+
+    #[test]
+    fn should_count_saturated_subscribers() {
+        let mut rng = TestRng::new();
+        let saturated_subscribers = || {
+            EVENT_STREAM_SATURATED_SUBSCRIBERS
+                .with_label_values(&[Endpoint::Derived.label()])
+                .get()
+        };
+        let before = saturated_subscribers();
+        let mut monitor = SlowSubscriberMonitor::new(
+            "127.0.0.1:12345".to_string(),
+            &Endpoint::Derived,
+            None,
+            None,
+        );
+
+        let stale = event_broadcast_ago(&mut rng, Duration::from_secs(5));
+        assert_eq!(monitor.observe(&stale), None);
+        assert_eq!(monitor.observe(&stale), None);
+        assert_eq!(saturated_subscribers(), before + 1.0);
+
+        let fresh = event_broadcast_ago(&mut rng, Duration::ZERO);
+        assert_eq!(monitor.observe(&fresh), None);
+        assert_eq!(saturated_subscribers(), before);
+
+        assert_eq!(monitor.observe(&stale), None);
+        drop(monitor);
+        assert_eq!(saturated_subscribers(), before);
+    }
+
+    #[test]
+    fn should_notify_subscriber_before_disconnecting() {
+        let monitor =
+            SlowSubscriberMonitor::new("127.0.0.1:12345".to_string(), &Endpoint::Main, None, None);
+        let disconnection = monitor.disconnection();
+        assert!(matches!(
+            monitor.disconnect(Disconnection::Lagged(3)),
+            Some(Err(RecvError::Closed))
+        ));
+        let recorded = disconnection.lock().unwrap().take().unwrap();
+        assert_eq!(recorded, Disconnection::Lagged(3));
+
+        let frames = recorded.into_warp_events(1);
+        assert_eq!(frames.len(), 3);
+        let warning = frames[0].as_ref().unwrap().to_string();
+        assert!(warning.starts_with("event:warning\n"));
+        assert!(warning.contains("missed 3 events"));
+        assert!(frames[1]
+            .as_ref()
+            .unwrap()
+            .to_string()
+            .starts_with(":disconnected: subscriber lagged behind"));
+        assert!(matches!(frames[2], Err(RecvError::Lagged(3))));
+
+        let frames = Disconnection::Saturated(Duration::from_secs(30)).into_warp_events(2);
+        assert_eq!(frames.len(), 2);
+        let warning = frames[0].as_ref().unwrap().to_string();
+        assert!(warning.contains("\"schema_version\":2"));
+    }
+}
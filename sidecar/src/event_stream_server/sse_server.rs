@@ -1,18 +1,37 @@
 //! Types and functions used by the http server to manage the event-stream.
 
-use super::endpoint::Endpoint;
+use super::{
+    endpoint::Endpoint,
+    event_selection::EventSelection,
+    fair_scheduler::FairShare,
+    priority::PriorityClasses,
+    retained_events::{ReplayWindow, RetainedEvents},
+    slow_subscriber::{Disconnection, SlowSubscriberMonitor},
+    status_events::{StatusBroadcaster, StatusEvent},
+    subscriber_auth::{JwtValidator, SubscriberRestrictions},
+    websocket::websocket_filter,
+};
+use crate::api_keys::API_KEY_PARAMETER;
+use crate::schema_version::{self, SchemaVersion, ACCEPT_VERSION_HEADER, SCHEMA_VERSION_HEADER};
 #[cfg(feature = "additional-metrics")]
 use crate::utils::start_metrics_thread;
-use casper_event_types::{sse_data::EventFilter, sse_data::SseData, Deploy, Filter as SseFilter};
+use casper_event_types::{
+    metrics::{OUTBOUND_BYTES, OUTBOUND_DROPS, OUTBOUND_EVENTS, OUTBOUND_SEND_LATENCIES},
+    sse_data::EventFilter,
+    sse_data::SseData,
+    Deploy, Filter as SseFilter,
+};
 use casper_types::ProtocolVersion;
-use futures::{future, Stream, StreamExt};
-use http::StatusCode;
+use futures::{future, stream::BoxStream, Stream, StreamExt};
+use http::{HeaderValue, StatusCode};
 use hyper::Body;
 use serde::Serialize;
-use serde_json::Value;
+use serde_json::{json, Value};
 use std::{
     collections::{HashMap, HashSet},
-    sync::{Arc, RwLock},
+    net::SocketAddr,
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
 };
 #[cfg(feature = "additional-metrics")]
 use tokio::sync::mpsc::Sender;
@@ -44,8 +63,43 @@ pub const SSE_API_DEPLOYS_PATH: &str = "deploys";
 pub const SSE_API_SIGNATURES_PATH: &str = "sigs";
 /// The URL path part to subscribe to sidecar specific events.
 pub const SSE_API_SIDECAR_PATH: &str = "sidecar";
+/// The URL path part to subscribe to events of types unknown to the sidecar.
+pub const SSE_API_UNKNOWN_PATH: &str = "unknown";
+/// The URL path part to subscribe to the events emitted by the configured event derivers.
+pub const SSE_API_DERIVED_PATH: &str = "derived";
+/// The URL path part to subscribe to events relating to entities of the watch list.
+pub const SSE_API_WATCHED_PATH: &str = "watched";
 /// The URL query string field name.
 pub const QUERY_FIELD: &str = "start_from";
+/// The URL query string field name of the window over which events are sent in batches, e.g.
+/// `100ms` or `1s`.
+pub const BATCH_QUERY_FIELD: &str = "batch";
+/// The URL query string field name of the maximum number of events in a batch.
+pub const BATCH_SIZE_QUERY_FIELD: &str = "batch_size";
+/// The URL query string field name to opt in to status events, `true` or `false`.
+pub const STATUS_EVENTS_QUERY_FIELD: &str = "status_events";
+/// The URL query string field name of the comma-separated event types the subscriber selects, e.g.
+/// `BlockAdded,DeployProcessed`.
+pub const INCLUDE_QUERY_FIELD: &str = "include";
+/// The URL query string field name of the hex-encoded public key of the account whose deploy events
+/// the subscriber selects.
+pub const ACCOUNT_QUERY_FIELD: &str = "account";
+/// The URL query string field name of the hex-encoded contract hash of the CEP-18 token whose
+/// transfers the subscriber selects.
+pub const TOKEN_QUERY_FIELD: &str = "token";
+/// The URL query string field name of the hex-encoded hash of the contract whose contract events
+/// the subscriber selects.
+pub const CONTRACT_QUERY_FIELD: &str = "contract";
+/// The URL query string field name of the name of the contract events the subscriber selects.
+pub const EVENT_QUERY_FIELD: &str = "event";
+/// The number of events in a batch if the subscriber doesn't limit it.
+const DEFAULT_BATCH_SIZE: usize = 100;
+/// The maximum number of events in a batch.
+const MAX_BATCH_SIZE: usize = 10_000;
+/// The longest window over which events can be batched.
+const MAX_BATCH_WINDOW: Duration = Duration::from_secs(10);
+/// The header carrying the subscriber's bearer token.
+pub(super) const AUTHORIZATION_HEADER: &str = "authorization";
 
 /// The filter associated with `/events` path.
 const EVENTS_FILTER: [EventFilter; 5] = [
@@ -72,9 +126,40 @@ const SIGNATURES_FILTER: [EventFilter; 2] =
     [EventFilter::ApiVersion, EventFilter::FinalitySignature];
 /// The filter associated with `/events/sidecar` path.
 const SIDECAR_FILTER: [EventFilter; 1] = [EventFilter::SidecarVersion];
+/// The filter associated with `/events/unknown` path.
+const UNKNOWN_FILTER: [EventFilter; 2] = [EventFilter::ApiVersion, EventFilter::Unknown];
+/// The filter associated with `/events/derived` path.
+const DERIVED_FILTER: [EventFilter; 2] = [EventFilter::SidecarVersion, EventFilter::Derived];
+/// The filter associated with `/events/watched` path.
+const WATCHED_FILTER: [EventFilter; 6] = [
+    EventFilter::ApiVersion,
+    EventFilter::BlockAdded,
+    EventFilter::DeployAccepted,
+    EventFilter::DeployProcessed,
+    EventFilter::Fault,
+    EventFilter::FinalitySignature,
+];
 /// The "id" field of the events sent on the event stream to clients.
 pub type Id = u32;
-type UrlProps = (&'static [EventFilter], &'static Endpoint, Option<u32>);
+type UrlProps = (SseSubscription, Option<u32>, bool);
+
+/// How the events are coalesced into frames holding a JSON array of events: a frame is sent once
+/// `max_events` are collected, or `window` after the first event of the batch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct Batching {
+    pub(super) window: Duration,
+    pub(super) max_events: usize,
+}
+
+/// What the `/info` endpoint returns.
+#[derive(Serialize)]
+struct ServerInfo {
+    #[serde(flatten)]
+    replay_window: ReplayWindow,
+    /// Name of the network of the events, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    network_name: Option<String>,
+}
 
 #[derive(Serialize)]
 #[serde(rename_all = "PascalCase")]
@@ -93,6 +178,11 @@ pub(super) struct ServerSentEvent {
     pub(super) json_data: Option<String>,
     /// Information which endpoint we got the event from
     pub(super) inbound_filter: Option<SseFilter>,
+    /// Whether the event relates to an entity of the watch list.
+    pub(super) watched: bool,
+    /// When the event was broadcast to the subscribers. `None` for replayed events and for those
+    /// sent to a single subscriber.
+    pub(super) broadcast_at: Option<Instant>,
 }
 
 impl ServerSentEvent {
@@ -103,6 +193,8 @@ impl ServerSentEvent {
             data: SseData::ApiVersion(client_api_version),
             json_data: None,
             inbound_filter: None,
+            watched: false,
+            broadcast_at: None,
         }
     }
     pub(super) fn sidecar_version_event(version: ProtocolVersion) -> Self {
@@ -111,6 +203,8 @@ impl ServerSentEvent {
             data: SseData::SidecarVersion(version),
             json_data: None,
             inbound_filter: None,
+            watched: false,
+            broadcast_at: None,
         }
     }
 }
@@ -129,19 +223,29 @@ pub(super) enum BroadcastChannelMessage {
     Shutdown,
 }
 
-fn event_to_warp_event(event: &ServerSentEvent) -> warp::sse::Event {
+/// Maps the `event` to a warp event without ID, returning it with the number of bytes of its data.
+fn event_to_warp_event(event: &ServerSentEvent) -> (warp::sse::Event, usize) {
     let maybe_value = event
         .json_data
         .as_ref()
         .map(|el| serde_json::from_str::<Value>(el).unwrap());
-    match &maybe_value {
-        Some(json_data) => WarpServerSentEvent::default().json_data(json_data),
-        None => WarpServerSentEvent::default().json_data(&event.data),
+    let serialized = match (&maybe_value, &event.data) {
+        (Some(json_data), _) => serde_json::to_string(json_data),
+        (None, SseData::DeployAccepted { deploy }) => serde_json::to_string(&DeployAccepted {
+            deploy_accepted: deploy.clone(),
+        }),
+        (None, data) => serde_json::to_string(data),
+    };
+    match serialized {
+        Ok(data) => {
+            let size = data.len();
+            (WarpServerSentEvent::default().data(data), size)
+        }
+        Err(error) => {
+            warn!(%error, ?event, "failed to jsonify sse event");
+            (WarpServerSentEvent::default(), 0)
+        }
     }
-    .unwrap_or_else(|error| {
-        warn!(%error, ?event, "failed to jsonify sse event");
-        WarpServerSentEvent::default()
-    })
 }
 
 /// Passed to the server whenever a new client subscribes.
@@ -151,6 +255,8 @@ pub(super) struct NewSubscriberInfo {
     /// A channel to send the initial events to the client's handler.  This will always send the
     /// ApiVersion as the first event, and then any buffered events as indicated by `start_from`.
     pub(super) initial_events_sender: mpsc::UnboundedSender<ServerSentEvent>,
+    /// A channel to send status events to the client's handler, if the client opted in to them.
+    pub(super) maybe_status_sender: Option<mpsc::UnboundedSender<StatusEvent>>,
 }
 
 /// Filters the `event`, mapping it to a warp event, or `None` if it should be filtered out.
@@ -159,42 +265,75 @@ async fn filter_map_server_sent_event(
     stream_filter: &Endpoint,
     event_filter: &[EventFilter],
 ) -> Option<Result<WarpServerSentEvent, RecvError>> {
-    if !event.data.should_include(event_filter) {
-        return None;
-    }
-    let id = match determine_id(event) {
-        Some(id) => id,
-        None => return None,
-    };
+    let id = forwarded_id(event, stream_filter, event_filter)?;
 
+    let (warp_event, size) = event_to_warp_event(event);
+    record_sent_event(event, stream_filter);
+    record_sent_bytes(stream_filter, event.data.type_label(), size);
     match &event.data {
-        &SseData::ApiVersion { .. } | &SseData::SidecarVersion { .. } => {
-            let warp_event = event_to_warp_event(event);
-            Some(Ok(warp_event))
-        }
+        &SseData::ApiVersion { .. } | &SseData::SidecarVersion { .. } => Some(Ok(warp_event)),
         &SseData::BlockAdded { .. }
+        | &SseData::DeployAccepted { .. }
         | &SseData::DeployProcessed { .. }
         | &SseData::DeployExpired { .. }
         | &SseData::Fault { .. }
         | &SseData::Step { .. }
-        | &SseData::FinalitySignature(_) => {
-            let warp_event = event_to_warp_event(event).id(id);
-            Some(Ok(warp_event))
-        }
-        SseData::DeployAccepted { deploy } => handle_deploy_accepted(event, deploy, &id),
-        &SseData::Shutdown => {
-            if should_send_shutdown(event, stream_filter) {
-                build_event_for_outbound(event, id)
-            } else {
-                None
-            }
-        }
+        | &SseData::FinalitySignature(_)
+        | &SseData::Unknown { .. }
+        | &SseData::Derived { .. }
+        | &SseData::Shutdown => Some(Ok(warp_event.id(id))),
+    }
+}
+
+/// Counts the `event` as sent to a subscriber of `stream_filter`, recording its latency if it was
+/// broadcast.
+pub(super) fn record_sent_event(event: &ServerSentEvent, stream_filter: &Endpoint) {
+    let labels = [stream_filter.label(), event.data.type_label()];
+    OUTBOUND_EVENTS.with_label_values(&labels).inc();
+    if let Some(broadcast_at) = event.broadcast_at {
+        OUTBOUND_SEND_LATENCIES
+            .with_label_values(&labels)
+            .observe(broadcast_at.elapsed().as_secs_f64() * 1000.0);
     }
 }
 
+pub(super) fn record_sent_bytes(stream_filter: &Endpoint, event_type: &str, size: usize) {
+    OUTBOUND_BYTES
+        .with_label_values(&[stream_filter.label(), event_type])
+        .inc_by(size as u64);
+}
+
+/// Returns the ID with which the `event` is sent to the client, or `None` if it should be filtered
+/// out.
+pub(super) fn forwarded_id(
+    event: &ServerSentEvent,
+    stream_filter: &Endpoint,
+    event_filter: &[EventFilter],
+) -> Option<String> {
+    if !event.data.should_include(event_filter) || is_unwatched(event, stream_filter) {
+        return None;
+    }
+    let id = determine_id(event)?;
+    if matches!(event.data, SseData::Shutdown) && !should_send_shutdown(event, stream_filter) {
+        return None;
+    }
+    Some(id)
+}
+
+/// Whether the event is withheld from the `/events/watched` stream as it doesn't relate to a
+/// watched entity.
+fn is_unwatched(event: &ServerSentEvent, stream_filter: &Endpoint) -> bool {
+    *stream_filter == Endpoint::Watched
+        && !event.watched
+        && !matches!(
+            event.data,
+            SseData::ApiVersion(_) | SseData::SidecarVersion(_) | SseData::Shutdown
+        )
+}
+
 fn should_send_shutdown(event: &ServerSentEvent, stream_filter: &Endpoint) -> bool {
     match (&event.inbound_filter, stream_filter) {
-        (None, Endpoint::Sidecar) => true,
+        (None, Endpoint::Sidecar) | (None, Endpoint::Watched) => true,
         (None, _) => false,
         (Some(SseFilter::Main), Endpoint::Events) => true, //If this filter handles the `/events` endpoint
         // then it should also propagate from inbounds `/events/main`
@@ -205,32 +344,6 @@ fn should_send_shutdown(event: &ServerSentEvent, stream_filter: &Endpoint) -> bo
     }
 }
 
-fn handle_deploy_accepted(
-    event: &ServerSentEvent,
-    deploy: &Arc<Deploy>,
-    id: &String,
-) -> Option<Result<WarpServerSentEvent, RecvError>> {
-    let maybe_value = event
-        .json_data
-        .as_ref()
-        .map(|el| serde_json::from_str::<Value>(el).unwrap());
-    let warp_event = match maybe_value {
-        Some(json_data) => WarpServerSentEvent::default().json_data(json_data),
-        None => {
-            let deploy_accepted = &DeployAccepted {
-                deploy_accepted: deploy.clone(),
-            };
-            WarpServerSentEvent::default().json_data(deploy_accepted)
-        }
-    }
-    .unwrap_or_else(|error| {
-        warn!(%error, ?event, "failed to jsonify sse event");
-        WarpServerSentEvent::default()
-    })
-    .id(id);
-    Some(Ok(warp_event))
-}
-
 fn determine_id(event: &ServerSentEvent) -> Option<String> {
     match event.id {
         Some(id) => {
@@ -253,24 +366,6 @@ fn determine_id(event: &ServerSentEvent) -> Option<String> {
     }
 }
 
-fn build_event_for_outbound(
-    event: &ServerSentEvent,
-    id: String,
-) -> Option<Result<WarpServerSentEvent, RecvError>> {
-    let maybe_value = event
-        .json_data
-        .as_ref()
-        .map(|el| serde_json::from_str::<Value>(el).unwrap())
-        .unwrap_or_else(|| serde_json::to_value(&event.data).unwrap());
-    Some(Ok(WarpServerSentEvent::default()
-        .json_data(&maybe_value)
-        .unwrap_or_else(|error| {
-            warn!(%error, ?event, "failed to jsonify sse event");
-            WarpServerSentEvent::default()
-        })
-        .id(id)))
-}
-
 pub(super) fn path_to_filter(path_param: &str) -> Option<&'static Endpoint> {
     match path_param {
         SSE_API_ROOT_PATH => Some(&Endpoint::Events),
@@ -278,6 +373,9 @@ pub(super) fn path_to_filter(path_param: &str) -> Option<&'static Endpoint> {
         SSE_API_DEPLOYS_PATH => Some(&Endpoint::Deploys),
         SSE_API_SIGNATURES_PATH => Some(&Endpoint::Sigs),
         SSE_API_SIDECAR_PATH => Some(&Endpoint::Sidecar),
+        SSE_API_UNKNOWN_PATH => Some(&Endpoint::Unknown),
+        SSE_API_DERIVED_PATH => Some(&Endpoint::Derived),
+        SSE_API_WATCHED_PATH => Some(&Endpoint::Watched),
         _ => None,
     }
 }
@@ -289,15 +387,115 @@ pub(super) fn get_filter(path_param: &str) -> Option<&'static [EventFilter]> {
         SSE_API_DEPLOYS_PATH => Some(&DEPLOYS_FILTER[..]),
         SSE_API_SIGNATURES_PATH => Some(&SIGNATURES_FILTER[..]),
         SSE_API_SIDECAR_PATH => Some(&SIDECAR_FILTER[..]),
+        SSE_API_UNKNOWN_PATH => Some(&UNKNOWN_FILTER[..]),
+        SSE_API_DERIVED_PATH => Some(&DERIVED_FILTER[..]),
+        SSE_API_WATCHED_PATH => Some(&WATCHED_FILTER[..]),
         _ => None,
     }
 }
 
+/// Extracts the starting event ID, the batching of events, whether status events are sent and the
+/// selection of events from the provided query.
+fn parse_query(
+    mut query: HashMap<String, String>,
+) -> Result<(Option<Id>, Option<Batching>, bool, EventSelection), Response> {
+    // The API key, if any, was checked before the subscription.
+    query.remove(API_KEY_PARAMETER);
+    let maybe_batching = parse_batching(&mut query)?;
+    let status_events = parse_status_events(&mut query)?;
+    let selection = parse_selection(&mut query)?;
+    let start_from = parse_start_from(query)?;
+    Ok((start_from, maybe_batching, status_events, selection))
+}
+
+/// Removes the included event types, account, token, contract and event fields from `query`,
+/// returning the selection of all events if they are absent.
+///
+/// Returns a 422 response if `include` isn't a comma-separated list of selectable event types,
+/// `account` isn't a hex-encoded public key, `token` or `contract` isn't a hex-encoded contract
+/// hash, or `event` is empty.
+pub(super) fn parse_selection(
+    query: &mut HashMap<String, String>,
+) -> Result<EventSelection, Response> {
+    let mut selection = EventSelection::default();
+    if let Some(included_types) = query.remove(INCLUDE_QUERY_FIELD) {
+        selection = selection
+            .with_included_types(&included_types)
+            .ok_or_else(create_422_include)?;
+    }
+    if let Some(account) = query.remove(ACCOUNT_QUERY_FIELD) {
+        selection = selection
+            .with_account(&account)
+            .ok_or_else(create_422_account)?;
+    }
+    if let Some(contract_hash) = query.remove(TOKEN_QUERY_FIELD) {
+        selection = selection
+            .with_token(&contract_hash)
+            .ok_or_else(create_422_token)?;
+    }
+    if let Some(contract_hash) = query.remove(CONTRACT_QUERY_FIELD) {
+        selection = selection
+            .with_contract(&contract_hash)
+            .ok_or_else(create_422_contract)?;
+    }
+    if let Some(event_name) = query.remove(EVENT_QUERY_FIELD) {
+        selection = selection
+            .with_event_name(&event_name)
+            .ok_or_else(create_422_event)?;
+    }
+    Ok(selection)
+}
+
+/// Removes the status events field from `query`, returning whether the subscriber opted in.
+///
+/// Returns a 422 response if the field is neither `true` nor `false`.
+fn parse_status_events(query: &mut HashMap<String, String>) -> Result<bool, Response> {
+    match query.remove(STATUS_EVENTS_QUERY_FIELD).as_deref() {
+        None | Some("false") => Ok(false),
+        Some("true") => Ok(true),
+        Some(_) => Err(create_422_status_events()),
+    }
+}
+
+/// Removes the batching fields from `query`, returning `None` if they're absent.
+///
+/// Returns a 422 response if `batch` isn't a window of up to 10 seconds, or `batch_size` isn't a
+/// number of events between 1 and 10000 or is given without `batch`.
+fn parse_batching(query: &mut HashMap<String, String>) -> Result<Option<Batching>, Response> {
+    let maybe_window = query.remove(BATCH_QUERY_FIELD);
+    let maybe_size = query.remove(BATCH_SIZE_QUERY_FIELD);
+    let window = match maybe_window {
+        Some(window) => parse_batch_window(&window).ok_or_else(create_422_batch)?,
+        None if maybe_size.is_none() => return Ok(None),
+        None => return Err(create_422_batch()),
+    };
+    let max_events = match maybe_size {
+        Some(size) => size
+            .parse::<usize>()
+            .ok()
+            .filter(|size| (1..=MAX_BATCH_SIZE).contains(size))
+            .ok_or_else(create_422_batch)?,
+        None => DEFAULT_BATCH_SIZE,
+    };
+    Ok(Some(Batching { window, max_events }))
+}
+
+/// Parses a window given in milliseconds, e.g. `100ms`, or in seconds, e.g. `1s`.
+fn parse_batch_window(window: &str) -> Option<Duration> {
+    let (amount, millis_per_unit) = match window.strip_suffix("ms") {
+        Some(millis) => (millis, 1),
+        None => (window.strip_suffix('s')?, 1000),
+    };
+    let millis = amount.parse::<u64>().ok()?.checked_mul(millis_per_unit)?;
+    Some(Duration::from_millis(millis))
+        .filter(|window| !window.is_zero() && *window <= MAX_BATCH_WINDOW)
+}
+
 /// Extracts the starting event ID from the provided query, or `None` if `query` is empty.
 ///
 /// If `query` is not empty, returns a 422 response if `query` doesn't have exactly one entry,
 /// "starts_from" mapped to a value representing an event ID.
-fn parse_query(query: HashMap<String, String>) -> Result<Option<Id>, Response> {
+pub(super) fn parse_start_from(query: HashMap<String, String>) -> Result<Option<Id>, Response> {
     if query.is_empty() {
         return Ok(None);
     }
@@ -316,7 +514,7 @@ fn parse_query(query: HashMap<String, String>) -> Result<Option<Id>, Response> {
 }
 
 /// Creates a 404 response with a useful error message in the body.
-fn create_404() -> Response {
+pub(super) fn create_404() -> Response {
     let mut response = Response::new(Body::from(format!(
         "invalid path: expected '/{root}/{main}', '/{root}/{deploys}' or '/{root}/{sigs}'\n",
         root = SSE_API_ROOT_PATH,
@@ -339,6 +537,124 @@ fn create_422() -> Response {
     response
 }
 
+/// Creates a 422 response with a useful error message in the body for use in case of a bad
+/// batching of events.
+fn create_422_batch() -> Response {
+    let mut response = Response::new(Body::from(format!(
+        "invalid query: expected '{}=<WINDOW>' of 1ms to 10s, e.g. '100ms', and optionally '{}=<EVENTS>' of 1 to {}\n",
+        BATCH_QUERY_FIELD, BATCH_SIZE_QUERY_FIELD, MAX_BATCH_SIZE
+    )));
+    *response.status_mut() = StatusCode::UNPROCESSABLE_ENTITY;
+    response
+}
+
+/// Creates a 422 response with a useful error message in the body for use in case of a bad opt-in
+/// to status events.
+fn create_422_status_events() -> Response {
+    let mut response = Response::new(Body::from(format!(
+        "invalid query: expected '{}=true' or '{}=false'\n",
+        STATUS_EVENTS_QUERY_FIELD, STATUS_EVENTS_QUERY_FIELD
+    )));
+    *response.status_mut() = StatusCode::UNPROCESSABLE_ENTITY;
+    response
+}
+
+/// Creates a 422 response with a useful error message in the body for use in case of a bad
+/// selection of event types.
+fn create_422_include() -> Response {
+    let mut response = Response::new(Body::from(format!(
+        "invalid query: expected '{}=<EVENT TYPES>' of comma-separated event types, e.g. 'BlockAdded,DeployProcessed'\n",
+        INCLUDE_QUERY_FIELD
+    )));
+    *response.status_mut() = StatusCode::UNPROCESSABLE_ENTITY;
+    response
+}
+
+/// Creates a 422 response with a useful error message in the body for use in case of a bad
+/// selection of an account.
+fn create_422_account() -> Response {
+    let mut response = Response::new(Body::from(format!(
+        "invalid query: expected '{}=<PUBLIC KEY>' of a hex-encoded public key\n",
+        ACCOUNT_QUERY_FIELD
+    )));
+    *response.status_mut() = StatusCode::UNPROCESSABLE_ENTITY;
+    response
+}
+
+/// Creates a 422 response with a useful error message in the body for use in case of a bad
+/// selection of a token.
+fn create_422_token() -> Response {
+    let mut response = Response::new(Body::from(format!(
+        "invalid query: expected '{}=<CONTRACT HASH>' of a hex-encoded contract hash\n",
+        TOKEN_QUERY_FIELD
+    )));
+    *response.status_mut() = StatusCode::UNPROCESSABLE_ENTITY;
+    response
+}
+
+/// Creates a 422 response with a useful error message in the body for use in case of a bad
+/// selection of a contract.
+fn create_422_contract() -> Response {
+    let mut response = Response::new(Body::from(format!(
+        "invalid query: expected '{}=<CONTRACT HASH>' of a hex-encoded contract hash\n",
+        CONTRACT_QUERY_FIELD
+    )));
+    *response.status_mut() = StatusCode::UNPROCESSABLE_ENTITY;
+    response
+}
+
+/// Creates a 422 response with a useful error message in the body for use in case of a bad
+/// selection of an event name.
+fn create_422_event() -> Response {
+    let mut response = Response::new(Body::from(format!(
+        "invalid query: expected '{}=<EVENT NAME>' of a non-empty event name\n",
+        EVENT_QUERY_FIELD
+    )));
+    *response.status_mut() = StatusCode::UNPROCESSABLE_ENTITY;
+    response
+}
+
+/// Creates a 401 response (Unauthorized) to be returned if the subscriber didn't provide a valid
+/// token.
+fn create_401(reason: String) -> Response {
+    let mut response = Response::new(Body::from(format!("unauthorized: {}\n", reason)));
+    *response.status_mut() = StatusCode::UNAUTHORIZED;
+    response
+}
+
+/// Creates a 403 response (Forbidden) to be returned if the subscriber's token doesn't allow
+/// access to the requested path.
+fn create_403(path_param: &str) -> Response {
+    let mut response = Response::new(Body::from(format!(
+        "token doesn't grant access to '/{}'\n",
+        path_param
+    )));
+    *response.status_mut() = StatusCode::FORBIDDEN;
+    response
+}
+
+/// Creates a 416 response (Range Not Satisfiable) to be returned if the subscriber asked to replay
+/// events which are no longer buffered.
+fn create_416(start_from: Id, earliest_event_id: Id) -> Response {
+    let mut response = Response::new(Body::from(format!(
+        "event {} is no longer available: the earliest available event ID is {}\n",
+        start_from, earliest_event_id
+    )));
+    *response.status_mut() = StatusCode::RANGE_NOT_SATISFIABLE;
+    response
+}
+
+/// Creates a 406 response (Not Acceptable) to be returned if the subscriber pinned a schema version
+/// which isn't supported.
+fn create_406(reason: String) -> Response {
+    let mut response = Response::new(Body::from(format!(
+        "invalid '{}' header: {}\n",
+        ACCEPT_VERSION_HEADER, reason
+    )));
+    *response.status_mut() = StatusCode::NOT_ACCEPTABLE;
+    response
+}
+
 /// Creates a 503 response (Service Unavailable) to be returned if the server has too many
 /// subscribers.
 fn create_503() -> Response {
@@ -350,33 +666,187 @@ fn create_503() -> Response {
 pub(super) struct ChannelsAndFilter {
     pub(super) event_broadcaster: broadcast::Sender<BroadcastChannelMessage>,
     pub(super) new_subscriber_info_receiver: mpsc::UnboundedReceiver<NewSubscriberInfo>,
+    pub(super) status_broadcaster: StatusBroadcaster,
     pub(super) sse_filter: BoxedFilter<(Response,)>,
 }
 
-fn serve_sse_response_handler(
+/// How the subscribers of the SSE and WebSocket endpoints are admitted and monitored.
+#[derive(Clone)]
+pub(super) struct SubscriberPolicy {
+    pub(super) max_concurrent_subscribers: u32,
+    pub(super) maybe_jwt_validator: Option<Arc<JwtValidator>>,
+    pub(super) maybe_slow_subscriber_timeout: Option<Duration>,
+    pub(super) maybe_priority_classes: Option<Arc<PriorityClasses>>,
+}
+
+impl SubscriberPolicy {
+    /// Admits a subscriber of the stream at `maybe_path_param` unless the server has too many
+    /// subscribers already or the subscriber's token, if required, doesn't grant access to the
+    /// stream. Returns the restrictions its token imposes on the stream.
+    pub(super) fn admit(
+        &self,
+        broadcaster: &broadcast::Sender<BroadcastChannelMessage>,
+        maybe_path_param: Option<&str>,
+        maybe_authorization_header: Option<&str>,
+    ) -> Result<SubscriberRestrictions, http::Response<Body>> {
+        if let Some(error_response) = validate(broadcaster, self.max_concurrent_subscribers) {
+            return Err(error_response);
+        }
+        authenticate(
+            maybe_path_param,
+            maybe_authorization_header,
+            self.maybe_jwt_validator.as_deref(),
+        )
+    }
+
+    /// The monitor of the subscriber at `maybe_remote_address`, subscribed to `stream_filter`.
+    pub(super) fn monitor(
+        &self,
+        maybe_remote_address: Option<SocketAddr>,
+        stream_filter: &'static Endpoint,
+    ) -> SlowSubscriberMonitor {
+        let subscriber = maybe_remote_address
+            .map(|address| address.to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        SlowSubscriberMonitor::new(
+            subscriber,
+            stream_filter,
+            self.maybe_slow_subscriber_timeout,
+            self.maybe_priority_classes.clone(),
+        )
+    }
+}
+
+/// The request of a subscriber to an SSE endpoint.
+struct SseRequest {
     maybe_path_param: Option<String>,
     query: HashMap<String, String>,
-    cloned_broadcaster: tokio::sync::broadcast::Sender<BroadcastChannelMessage>,
-    max_concurrent_subscribers: u32,
-    new_subscriber_info_sender: UnboundedSender<NewSubscriberInfo>,
+    maybe_authorization_header: Option<String>,
+    maybe_accept_version: Option<String>,
+    maybe_remote_address: Option<SocketAddr>,
+}
+
+/// What a subscriber to an SSE endpoint asked for in its request, and what it is allowed to see.
+struct SseSubscription {
+    stream_filter: &'static Endpoint,
+    event_filter: &'static [EventFilter],
+    restrictions: SubscriberRestrictions,
+    selection: EventSelection,
+    maybe_batching: Option<Batching>,
+    schema_version: SchemaVersion,
+}
+
+fn serve_sse_response_handler(
+    request: SseRequest,
+    broadcaster: &broadcast::Sender<BroadcastChannelMessage>,
+    new_subscriber_info_sender: &UnboundedSender<NewSubscriberInfo>,
+    policy: &SubscriberPolicy,
+    status_broadcaster: &StatusBroadcaster,
+    maybe_retained_events: Option<&RetainedEvents>,
     #[cfg(feature = "additional-metrics")] metrics_sender: Sender<()>,
 ) -> http::Response<Body> {
-    if let Some(value) = validate(&cloned_broadcaster, max_concurrent_subscribers) {
-        return value;
+    let maybe_remote_address = request.maybe_remote_address;
+    let (subscription, start_from, status_events) =
+        match admit_request(request, broadcaster, policy) {
+            Ok(value) => value,
+            Err(error_response) => return error_response,
+        };
+    // Subscribers which opted in to status events are told about unavailable events in-band.
+    let maybe_retained_events = maybe_retained_events.filter(|_| !status_events);
+    if let Some(error_response) = reject_unavailable_replay(start_from, maybe_retained_events) {
+        return error_response;
     }
-    let (event_filter, stream_filter, start_from) = match parse_url_props(maybe_path_param, query) {
-        Ok(value) => value,
-        Err(error_response) => return error_response,
-    };
 
+    let (initial_events_receiver, maybe_status_events) = register_subscriber(
+        new_subscriber_info_sender,
+        start_from,
+        status_events.then_some(status_broadcaster),
+    );
+    let schema_version = subscription.schema_version;
+    let monitor = policy.monitor(maybe_remote_address, subscription.stream_filter);
+    let mut response = sse::reply(sse::keep_alive().stream(stream_to_client(
+        initial_events_receiver,
+        // Create a channel for the client's handler to receive the stream of ongoing events.
+        broadcaster.subscribe(),
+        subscription,
+        monitor,
+        maybe_status_events,
+        #[cfg(feature = "additional-metrics")]
+        metrics_sender,
+    )))
+    .into_response();
+    response
+        .headers_mut()
+        .insert(SCHEMA_VERSION_HEADER, HeaderValue::from(schema_version));
+    response
+}
+
+/// Admits the subscriber of the request and parses what it asked for, see [parse_url_props].
+fn admit_request(
+    request: SseRequest,
+    broadcaster: &broadcast::Sender<BroadcastChannelMessage>,
+    policy: &SubscriberPolicy,
+) -> Result<UrlProps, http::Response<Body>> {
+    let restrictions = policy.admit(
+        broadcaster,
+        request.maybe_path_param.as_deref(),
+        request.maybe_authorization_header.as_deref(),
+    )?;
+    let schema_version =
+        schema_version::negotiate(request.maybe_accept_version.as_deref()).map_err(create_406)?;
+    parse_url_props(
+        request.maybe_path_param,
+        request.query,
+        restrictions,
+        schema_version,
+    )
+}
+
+/// Rejects the subscriber if it asked to replay events which are no longer retained, if
+/// `maybe_retained_events` are given.
+fn reject_unavailable_replay(
+    maybe_start_from: Option<Id>,
+    maybe_retained_events: Option<&RetainedEvents>,
+) -> Option<http::Response<Body>> {
+    let start_from = maybe_start_from?;
+    let earliest_event_id = maybe_retained_events?.earliest_if_unavailable(start_from)?;
+    info!(
+        start_from,
+        earliest_event_id, "rejecting event stream subscriber: replay unavailable"
+    );
+    Some(create_416(start_from, earliest_event_id))
+}
+
+/// Registers a new subscriber with the server, which sends it the initial events from
+/// `start_from`. Returns the receiver of the initial events, along with the status events of the
+/// subscriber if it opted in to them with `maybe_status_broadcaster`.
+pub(super) fn register_subscriber(
+    new_subscriber_info_sender: &UnboundedSender<NewSubscriberInfo>,
+    start_from: Option<Id>,
+    maybe_status_broadcaster: Option<&StatusBroadcaster>,
+) -> (
+    mpsc::UnboundedReceiver<ServerSentEvent>,
+    Option<BoxStream<'static, StatusEvent>>,
+) {
     // Create a channel for the client's handler to receive the stream of initial events.
     let (initial_events_sender, initial_events_receiver) = mpsc::unbounded_channel();
+    // If the client opted in, create a channel for the status events concerning this client only,
+    // merged with those broadcast to all clients.
+    let (maybe_status_sender, maybe_status_events) = match maybe_status_broadcaster {
+        Some(status_broadcaster) => {
+            let (status_sender, status_receiver) = mpsc::unbounded_channel();
+            let status_events = status_broadcaster.subscribe(status_receiver).boxed();
+            (Some(status_sender), Some(status_events))
+        }
+        None => (None, None),
+    };
 
     // Supply the server with the sender part of the channel along with the client's
     // requested starting point.
     let new_subscriber_info = NewSubscriberInfo {
         start_from,
         initial_events_sender,
+        maybe_status_sender,
     };
     if new_subscriber_info_sender
         .send(new_subscriber_info)
@@ -384,24 +854,45 @@ fn serve_sse_response_handler(
     {
         error!("failed to send new subscriber info");
     }
+    (initial_events_receiver, maybe_status_events)
+}
 
-    // Create a channel for the client's handler to receive the stream of ongoing events.
-    let ongoing_events_receiver = cloned_broadcaster.subscribe();
-
-    sse::reply(sse::keep_alive().stream(stream_to_client(
-        initial_events_receiver,
-        ongoing_events_receiver,
-        stream_filter,
-        event_filter,
-        #[cfg(feature = "additional-metrics")]
-        metrics_sender,
-    )))
-    .into_response()
+/// Verifies the subscriber's token if authentication is enabled and returns the restrictions its
+/// claims impose on the stream.
+pub(super) fn authenticate(
+    maybe_path_param: Option<&str>,
+    maybe_authorization_header: Option<&str>,
+    maybe_jwt_validator: Option<&JwtValidator>,
+) -> Result<SubscriberRestrictions, http::Response<Body>> {
+    let jwt_validator = match maybe_jwt_validator {
+        None => return Ok(SubscriberRestrictions::default()),
+        Some(jwt_validator) => jwt_validator,
+    };
+    let claims = jwt_validator
+        .verify(maybe_authorization_header)
+        .map_err(|error| {
+            info!(%error, "rejecting unauthenticated event stream subscriber");
+            create_401(error.to_string())
+        })?;
+    let path_param = maybe_path_param.unwrap_or(SSE_API_ROOT_PATH);
+    if !claims.allows_path(path_param) {
+        info!(
+            subscriber = ?claims.sub,
+            path = path_param,
+            "rejecting event stream subscriber: path not granted by token"
+        );
+        return Err(create_403(path_param));
+    }
+    Ok(claims.restrictions())
 }
 
+/// Parses what the subscriber asked for in its request. Returns its subscription along with the
+/// event ID from which its stream starts, and whether it opted in to status events.
 fn parse_url_props(
     maybe_path_param: Option<String>,
     query: HashMap<String, String>,
+    restrictions: SubscriberRestrictions,
+    schema_version: SchemaVersion,
 ) -> Result<UrlProps, http::Response<Body>> {
     let path_param = maybe_path_param.unwrap_or_else(|| SSE_API_ROOT_PATH.to_string());
     let event_filter = match get_filter(path_param.as_str()) {
@@ -412,14 +903,22 @@ fn parse_url_props(
         Some(filter) => filter,
         None => return Err(create_404()),
     };
-    let start_from = match parse_query(query) {
-        Ok(maybe_id) => maybe_id,
+    let (start_from, maybe_batching, status_events, selection) = match parse_query(query) {
+        Ok(props) => props,
         Err(error_response) => return Err(error_response),
     };
-    Ok((event_filter, stream_filter, start_from))
+    let subscription = SseSubscription {
+        stream_filter,
+        event_filter,
+        restrictions,
+        selection,
+        maybe_batching,
+        schema_version,
+    };
+    Ok((subscription, start_from, status_events))
 }
 
-fn validate(
+pub(super) fn validate(
     cloned_broadcaster: &broadcast::Sender<BroadcastChannelMessage>,
     max_concurrent_subscribers: u32,
 ) -> Option<http::Response<Body>> {
@@ -437,49 +936,121 @@ fn validate(
 impl ChannelsAndFilter {
     /// Creates the message-passing channels required to run the event-stream server and the warp
     /// filter for the event-stream server.
-    pub(super) fn new(broadcast_channel_size: usize, max_concurrent_subscribers: u32) -> Self {
+    ///
+    /// The filter also serves the replay window of `retained_events` and the name of the network,
+    /// if known, on `/info` and the WebSocket mirror of the event stream on `/ws/events`, and
+    /// rejects the subscribers asking to replay unavailable events if `reject_unavailable_replay`
+    /// is set.
+    pub(super) fn new(
+        broadcast_channel_size: usize,
+        policy: SubscriberPolicy,
+        retained_events: RetainedEvents,
+        reject_unavailable_replay: bool,
+        maybe_network_name: Option<String>,
+    ) -> Self {
         // Create a channel to broadcast new events to all subscribed clients' streams.
         let (event_broadcaster, _) = broadcast::channel(broadcast_channel_size);
-        let cloned_broadcaster = event_broadcaster.clone();
-
-        #[cfg(feature = "additional-metrics")]
-        let tx = start_metrics_thread("pushing outbound_events".to_string());
+        let status_broadcaster = StatusBroadcaster::new();
         // Create a channel for `NewSubscriberInfo`s to pass the information required to handle a
         // new client subscription.
         let (new_subscriber_info_sender, new_subscriber_info_receiver) = mpsc::unbounded_channel();
-        let opt = warp::path::param::<String>()
-            .map(Some)
-            .or_else(|_| async { Ok::<(Option<String>,), std::convert::Infallible>((None,)) });
-        let sse_filter = warp::get()
-            .and(warp::path!("events" / ..))
-            .and(opt)
-            .and(path::end())
-            .and(warp::query())
-            .map(
-                move |maybe_path_param: Option<String>, query: HashMap<String, String>| {
-                    let new_subscriber_info_sender_clone = new_subscriber_info_sender.clone();
-                    serve_sse_response_handler(
-                        maybe_path_param,
-                        query,
-                        cloned_broadcaster.clone(),
-                        max_concurrent_subscribers,
-                        new_subscriber_info_sender_clone,
-                        #[cfg(feature = "additional-metrics")]
-                        tx.clone(),
-                    )
-                },
-            )
+        let websocket_filter = websocket_filter(
+            event_broadcaster.clone(),
+            new_subscriber_info_sender.clone(),
+            policy.clone(),
+        );
+        let events_filter = events_filter(
+            event_broadcaster.clone(),
+            new_subscriber_info_sender,
+            policy,
+            status_broadcaster.clone(),
+            reject_unavailable_replay.then(|| retained_events.clone()),
+        );
+        let sse_filter = info_filter(retained_events, maybe_network_name)
+            .or(events_filter)
+            .unify()
+            .or(websocket_filter)
+            .unify()
             .or_else(|_| async move { Ok::<_, Rejection>((create_404(),)) })
             .boxed();
 
         ChannelsAndFilter {
             event_broadcaster,
             new_subscriber_info_receiver,
+            status_broadcaster,
             sse_filter,
         }
     }
 }
 
+/// Creates the filter serving the replay window of `retained_events` and the name of the network
+/// on `/info`.
+fn info_filter(
+    retained_events: RetainedEvents,
+    maybe_network_name: Option<String>,
+) -> BoxedFilter<(Response,)> {
+    warp::get()
+        .and(warp::path!("info"))
+        .map(move || {
+            warp::reply::json(&ServerInfo {
+                replay_window: retained_events.replay_window(),
+                network_name: maybe_network_name.clone(),
+            })
+            .into_response()
+        })
+        .boxed()
+}
+
+/// Creates the filter serving the SSE endpoints. The subscribers asking to replay events which are
+/// no longer retained are rejected if `maybe_retained_events` are given.
+fn events_filter(
+    broadcaster: broadcast::Sender<BroadcastChannelMessage>,
+    new_subscriber_info_sender: UnboundedSender<NewSubscriberInfo>,
+    policy: SubscriberPolicy,
+    status_broadcaster: StatusBroadcaster,
+    maybe_retained_events: Option<RetainedEvents>,
+) -> BoxedFilter<(Response,)> {
+    #[cfg(feature = "additional-metrics")]
+    let tx = start_metrics_thread("pushing outbound_events".to_string());
+    let opt = warp::path::param::<String>()
+        .map(Some)
+        .or_else(|_| async { Ok::<(Option<String>,), std::convert::Infallible>((None,)) });
+    warp::get()
+        .and(warp::path!("events" / ..))
+        .and(opt)
+        .and(path::end())
+        .and(warp::query())
+        .and(warp::header::optional::<String>(AUTHORIZATION_HEADER))
+        .and(warp::header::optional::<String>(ACCEPT_VERSION_HEADER))
+        .and(warp::addr::remote())
+        .map(
+            move |maybe_path_param: Option<String>,
+                  query: HashMap<String, String>,
+                  maybe_authorization_header: Option<String>,
+                  maybe_accept_version: Option<String>,
+                  maybe_remote_address: Option<SocketAddr>| {
+                let request = SseRequest {
+                    maybe_path_param,
+                    query,
+                    maybe_authorization_header,
+                    maybe_accept_version,
+                    maybe_remote_address,
+                };
+                serve_sse_response_handler(
+                    request,
+                    &broadcaster,
+                    &new_subscriber_info_sender,
+                    &policy,
+                    &status_broadcaster,
+                    maybe_retained_events.as_ref(),
+                    #[cfg(feature = "additional-metrics")]
+                    tx.clone(),
+                )
+            },
+        )
+        .boxed()
+}
+
 /// This takes the two channel receivers and turns them into a stream of SSEs to the subscribed
 /// client.
 ///
@@ -492,15 +1063,114 @@ impl ChannelsAndFilter {
 /// variant via the channel).  This channel will receive all SSEs created from the moment the client
 /// subscribed to the server's event stream.
 ///
-/// It also takes an `EventFilter` which causes events to which the client didn't subscribe to be
-/// skipped.
+/// It also takes the `SseSubscription`, with the `EventFilter` which causes events to which the
+/// client didn't subscribe to be skipped, the `SubscriberRestrictions` which cause events the client isn't allowed to see to be
+/// skipped, and the `EventSelection` which causes events the client didn't select in its query to
+/// be skipped before they are serialized. If the client asked for `Batching`, the events are
+/// coalesced into frames holding a JSON array of events.
+///
+/// The `SlowSubscriberMonitor` ends the stream if the client can't keep up with the events, in
+/// which case the client is told why before being disconnected. If the client opted in, the status
+/// events are interleaved with the events until the latter end. The frames of batches, the status
+/// events and the warnings are serialized in the negotiated `schema_version`.
+///
+/// The stream yields to the executor after every turn of events, so that a burst is flushed to all
+/// clients in a round-robin fashion rather than to one client after the other.
 fn stream_to_client(
+    initial_events: mpsc::UnboundedReceiver<ServerSentEvent>,
+    ongoing_events: broadcast::Receiver<BroadcastChannelMessage>,
+    subscription: SseSubscription,
+    monitor: SlowSubscriberMonitor,
+    maybe_status_events: Option<BoxStream<'static, StatusEvent>>,
+    #[cfg(feature = "additional-metrics")] metrics_sender: Sender<()>,
+) -> BoxStream<'static, Result<WarpServerSentEvent, RecvError>> {
+    let schema_version = subscription.schema_version;
+    let farewell = farewell_stream(
+        monitor.disconnection(),
+        maybe_status_events.is_some(),
+        schema_version,
+    );
+    let events = subscribed_events(
+        initial_events,
+        ongoing_events,
+        subscription.stream_filter,
+        subscription.event_filter,
+        subscription.selection.clone(),
+        monitor,
+    );
+    let events_to_client = filter_events(events, subscription);
+    // Once the events end, tell the client why if it is being disconnected.
+    let events_to_client = events_to_client.chain(farewell);
+    let events_to_client = match maybe_status_events {
+        None => events_to_client.boxed(),
+        Some(status_events) => {
+            interleave_status_events(events_to_client, status_events, schema_version).boxed()
+        }
+    };
+    FairShare::new(events_to_client).boxed()
+}
+
+/// Filters the events as dictated by the subscription, coalescing them into batches if the client
+/// asked for them.
+fn filter_events(
+    events: impl Stream<Item = Result<ServerSentEvent, RecvError>> + Send + 'static,
+    subscription: SseSubscription,
+) -> BoxStream<'static, Result<WarpServerSentEvent, RecvError>> {
+    match subscription.maybe_batching {
+        None => build_combined_events_stream(
+            events,
+            subscription.stream_filter,
+            subscription.event_filter,
+            subscription.restrictions,
+            subscription.selection,
+        )
+        .boxed(),
+        Some(batching) => build_batched_events_stream(
+            events,
+            subscription.stream_filter,
+            subscription.event_filter,
+            subscription.restrictions,
+            subscription.selection,
+            batching,
+            subscription.schema_version,
+        )
+        .boxed(),
+    }
+}
+
+/// The frames sent to the client once its events end, see [farewell_events]. They are only known
+/// then, as the client may be disconnected until the last event.
+fn farewell_stream(
+    disconnection: Arc<Mutex<Option<Disconnection>>>,
+    status_events: bool,
+    schema_version: SchemaVersion,
+) -> impl Stream<Item = Result<WarpServerSentEvent, RecvError>> + Send + 'static {
+    futures::stream::once(async move {
+        disconnection
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .take()
+    })
+    .flat_map(move |maybe_disconnection| {
+        futures::stream::iter(farewell_events(
+            maybe_disconnection,
+            status_events,
+            schema_version,
+        ))
+    })
+}
+
+/// Chains the initial events to the ongoing ones, which end once the server shuts down or the
+/// `monitor` disconnects the client. The ongoing events already sent in the initial stream are
+/// skipped, as are those shed by the `monitor` when the client can't keep up.
+pub(super) fn subscribed_events(
     initial_events: mpsc::UnboundedReceiver<ServerSentEvent>,
     ongoing_events: broadcast::Receiver<BroadcastChannelMessage>,
     stream_filter: &'static Endpoint,
     event_filter: &'static [EventFilter],
-    #[cfg(feature = "additional-metrics")] metrics_sender: Sender<()>,
-) -> impl Stream<Item = Result<WarpServerSentEvent, RecvError>> + 'static {
+    shedding_selection: EventSelection,
+    mut monitor: SlowSubscriberMonitor,
+) -> impl Stream<Item = Result<ServerSentEvent, RecvError>> + Send + 'static {
     // Keep a record of the IDs of the events delivered via the `initial_events` receiver.
     let initial_stream_ids = Arc::new(RwLock::new(HashSet::new()));
     let cloned_initial_ids = Arc::clone(&initial_stream_ids);
@@ -508,39 +1178,78 @@ fn stream_to_client(
     // that have already been sent in the initial stream.
     let ongoing_stream = BroadcastStream::new(ongoing_events)
         .filter_map(move |result| {
-            let cloned_initial_ids = Arc::clone(&cloned_initial_ids);
-            async move {
-                match result {
-                    Ok(BroadcastChannelMessage::ServerSentEvent(event)) => {
-                        handle_sse_event(event, cloned_initial_ids)
+            let outcome = match result {
+                Ok(BroadcastChannelMessage::ServerSentEvent(event)) => {
+                    match monitor.observe(&event) {
+                        Some(reason) => monitor.disconnect(reason),
+                        None if event.data.should_include(event_filter)
+                            && shedding_selection.includes_type(&event.data)
+                            && monitor.should_shed(&event) =>
+                        {
+                            None
+                        }
+                        None => handle_sse_event(event, Arc::clone(&cloned_initial_ids)),
                     }
-                    Ok(BroadcastChannelMessage::Shutdown) => Some(Err(RecvError::Closed)),
-                    Err(BroadcastStreamRecvError::Lagged(amount)) => handle_lagged(amount),
                 }
-            }
+                Ok(BroadcastChannelMessage::Shutdown) => Some(Err(RecvError::Closed)),
+                Err(BroadcastStreamRecvError::Lagged(amount)) => {
+                    handle_lagged(amount, stream_filter);
+                    monitor.disconnect(Disconnection::Lagged(amount))
+                }
+            };
+            future::ready(outcome)
         })
         .take_while(|result| future::ready(!matches!(result, Err(RecvError::Closed))))
         .boxed();
+    chain_initial_and_ongoing_events(initial_events, initial_stream_ids, ongoing_stream)
+}
 
-    build_combined_events_stream(
-        initial_events,
-        initial_stream_ids,
-        ongoing_stream,
-        stream_filter,
-        event_filter,
-    )
+/// The frames sent to the client after its events end. A client which opted in to status events
+/// is told about the events it missed if it lagged behind.
+fn farewell_events(
+    maybe_disconnection: Option<Disconnection>,
+    status_events: bool,
+    schema_version: SchemaVersion,
+) -> Vec<Result<WarpServerSentEvent, RecvError>> {
+    let disconnection = match maybe_disconnection {
+        Some(disconnection) => disconnection,
+        None => return Vec::new(),
+    };
+    let mut frames = Vec::new();
+    if let (true, Disconnection::Lagged(amount)) = (status_events, disconnection) {
+        let overflow = StatusEvent::BufferOverflow {
+            missed_events: amount,
+        };
+        frames.push(Ok(overflow.to_warp_event(schema_version)));
+    }
+    frames.extend(disconnection.into_warp_events(schema_version));
+    frames
 }
 
-// Builds stream that serves the initial events followed by the ongoing ones, filtering as dictated by the `event_filter`.
-fn build_combined_events_stream(
+/// Interleaves the `status_events` with the `events`, ending once the latter end.
+fn interleave_status_events(
+    events: impl Stream<Item = Result<WarpServerSentEvent, RecvError>> + Send + 'static,
+    status_events: BoxStream<'static, StatusEvent>,
+    schema_version: SchemaVersion,
+) -> impl Stream<Item = Result<WarpServerSentEvent, RecvError>> + Send + 'static {
+    let events = events
+        .map(Some)
+        .chain(futures::stream::once(future::ready(None)));
+    let status_events =
+        status_events.map(move |event| Some(Ok(event.to_warp_event(schema_version))));
+    futures::stream::select(events, status_events)
+        .take_while(|maybe_frame| future::ready(maybe_frame.is_some()))
+        .filter_map(future::ready)
+}
+
+// Builds stream that serves the initial events followed by the ongoing ones.
+fn chain_initial_and_ongoing_events(
     initial_events: mpsc::UnboundedReceiver<ServerSentEvent>,
     initial_stream_ids: Arc<RwLock<HashSet<u32>>>,
     ongoing_stream: std::pin::Pin<
         Box<dyn Stream<Item = Result<ServerSentEvent, RecvError>> + Send>,
     >,
-    stream_filter: &'static Endpoint,
-    event_filter: &'static [EventFilter],
-) -> impl Stream<Item = Result<WarpServerSentEvent, RecvError>> + 'static {
+) -> impl Stream<Item = Result<ServerSentEvent, RecvError>> + Send + 'static {
     UnboundedReceiverStream::new(initial_events)
         .map(move |event| {
             if let Some(id) = event.id {
@@ -549,35 +1258,152 @@ fn build_combined_events_stream(
             Ok(event)
         })
         .chain(ongoing_stream)
-        .filter_map(move |result| {
+}
+
+// Filters the combined initial and ongoing events as dictated by the `event_filter`, the
+// `restrictions` and the `selection`.
+fn build_combined_events_stream(
+    events: impl Stream<Item = Result<ServerSentEvent, RecvError>> + Send + 'static,
+    stream_filter: &'static Endpoint,
+    event_filter: &'static [EventFilter],
+    restrictions: SubscriberRestrictions,
+    selection: EventSelection,
+) -> impl Stream<Item = Result<WarpServerSentEvent, RecvError>> + Send + 'static {
+    events.filter_map(move |result| {
+        #[cfg(feature = "additional-metrics")]
+        let metrics_sender = metrics_sender.clone();
+        let restrictions = restrictions.clone();
+        let selection = selection.clone();
+        async move {
             #[cfg(feature = "additional-metrics")]
-            let metrics_sender = metrics_sender.clone();
-            async move {
-                #[cfg(feature = "additional-metrics")]
-                let sender = metrics_sender;
-                match result {
-                    Ok(event) => {
-                        let fitlered_data =
-                            filter_map_server_sent_event(&event, stream_filter, event_filter).await;
-                        #[cfg(feature = "additional-metrics")]
-                        if let Some(_) = fitlered_data {
-                            let _ = sender.clone().send(()).await;
-                        }
-                        #[allow(clippy::let_and_return)]
-                        fitlered_data
+            let sender = metrics_sender;
+            match result {
+                Ok(event)
+                    if !restrictions.permits(&event.data) || !selection.selects(&event.data) =>
+                {
+                    None
+                }
+                Ok(event) => {
+                    let fitlered_data =
+                        filter_map_server_sent_event(&event, stream_filter, event_filter).await;
+                    #[cfg(feature = "additional-metrics")]
+                    if let Some(_) = fitlered_data {
+                        let _ = sender.clone().send(()).await;
                     }
-                    Err(error) => Some(Err(error)),
+                    #[allow(clippy::let_and_return)]
+                    fitlered_data
                 }
+                Err(error) => Some(Err(error)),
             }
+        }
+    })
+}
+
+// Filters the combined initial and ongoing events as dictated by the `event_filter`, the
+// `restrictions` and the `selection`, coalescing them into frames as dictated by the `batching`.
+fn build_batched_events_stream(
+    events: impl Stream<Item = Result<ServerSentEvent, RecvError>> + Send + 'static,
+    stream_filter: &'static Endpoint,
+    event_filter: &'static [EventFilter],
+    restrictions: SubscriberRestrictions,
+    selection: EventSelection,
+    batching: Batching,
+    schema_version: SchemaVersion,
+) -> impl Stream<Item = Result<WarpServerSentEvent, RecvError>> + Send + 'static {
+    let forwarded_events = events.filter(move |result| {
+        future::ready(match result {
+            Ok(event) => {
+                restrictions.permits(&event.data)
+                    && selection.selects(&event.data)
+                    && forwarded_id(event, stream_filter, event_filter).is_some()
+            }
+            Err(_) => true,
+        })
+    });
+    tokio_stream::StreamExt::chunks_timeout(forwarded_events, batching.max_events, batching.window)
+        .flat_map(move |batch| {
+            futures::stream::iter(batch_to_warp_events(batch, stream_filter, schema_version))
         })
 }
 
-fn handle_lagged(amount: u64) -> Option<Result<ServerSentEvent, RecvError>> {
-    info!(
-        "client lagged by {} events - dropping event stream connection to client",
-        amount
-    );
-    Some(Err(RecvError::Lagged(amount)))
+/// Turns a batch of events into a single frame holding the JSON array of the events, with the ID
+/// of the last event. As of schema version 2, the array is the `events` of an object holding the
+/// `schema_version`. An error ends the batch, and is passed on after the frame.
+fn batch_to_warp_events(
+    batch: Vec<Result<ServerSentEvent, RecvError>>,
+    stream_filter: &Endpoint,
+    schema_version: SchemaVersion,
+) -> Vec<Result<WarpServerSentEvent, RecvError>> {
+    let mut values = Vec::with_capacity(batch.len());
+    let mut maybe_last_id = None;
+    let mut maybe_error = None;
+    for result in batch {
+        match result {
+            Ok(event) => {
+                maybe_last_id = event.id.or(maybe_last_id);
+                record_sent_event(&event, stream_filter);
+                values.push(event_to_json(&event));
+            }
+            Err(error) => {
+                maybe_error = Some(error);
+                break;
+            }
+        }
+    }
+    let mut frames = Vec::with_capacity(2);
+    if !values.is_empty() {
+        let frame_value = if schema_version::is_enveloped(schema_version) {
+            json!({ "schema_version": schema_version, "events": values })
+        } else {
+            Value::Array(values)
+        };
+        let frame = match serde_json::to_string(&frame_value) {
+            Ok(data) => {
+                record_sent_bytes(stream_filter, "Batch", data.len());
+                WarpServerSentEvent::default().data(data)
+            }
+            Err(error) => {
+                warn!(%error, "failed to jsonify batch of sse events");
+                WarpServerSentEvent::default()
+            }
+        };
+        frames.push(Ok(match maybe_last_id {
+            Some(id) => frame.id(id.to_string()),
+            None => frame,
+        }));
+    }
+    frames.extend(maybe_error.map(Err));
+    frames
+}
+
+/// The JSON value of the `event` as sent in a batch, the same as its data when sent on its own.
+pub(super) fn event_to_json(event: &ServerSentEvent) -> Value {
+    sse_data_to_json(&event.data, event.json_data.as_deref())
+}
+
+/// The JSON value of `data` as sent to subscribers, i.e. the raw JSON it was received as if any.
+pub(crate) fn sse_data_to_json(data: &SseData, maybe_json_data: Option<&str>) -> Value {
+    if let Some(json_data) = maybe_json_data {
+        if let Ok(value) = serde_json::from_str(json_data) {
+            return value;
+        }
+    }
+    match data {
+        SseData::DeployAccepted { deploy } => serde_json::to_value(&DeployAccepted {
+            deploy_accepted: deploy.clone(),
+        }),
+        data => serde_json::to_value(data),
+    }
+    .unwrap_or_else(|error| {
+        warn!(%error, ?data, "failed to jsonify sse event");
+        Value::Null
+    })
+}
+
+fn handle_lagged(amount: u64, stream_filter: &Endpoint) {
+    OUTBOUND_DROPS
+        .with_label_values(&[stream_filter.label(), "lagged"])
+        .inc_by(amount);
 }
 
 fn handle_sse_event(
@@ -596,10 +1422,14 @@ fn handle_sse_event(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        event_stream_server::subscriber_auth::tests::sign_token, types::config::JwtAuthConfig,
+    };
     use casper_event_types::DeployHash;
     use casper_types::testing::TestRng;
     use rand::Rng;
     use regex::Regex;
+    use serde_json::{json, value::RawValue};
     use std::iter;
     #[cfg(feature = "additional-metrics")]
     use tokio::sync::mpsc::channel;
@@ -643,12 +1473,16 @@ mod tests {
             data: SseData::random_api_version(&mut rng),
             json_data: None,
             inbound_filter: None,
+            watched: false,
+            broadcast_at: None,
         };
         let block_added = ServerSentEvent {
             id: Some(rng.gen()),
             data: SseData::random_block_added(&mut rng),
             json_data: None,
             inbound_filter: None,
+            watched: false,
+            broadcast_at: None,
         };
         let (sse_data, deploy) = SseData::random_deploy_accepted(&mut rng);
         let deploy_accepted = ServerSentEvent {
@@ -656,6 +1490,8 @@ mod tests {
             data: sse_data,
             json_data: None,
             inbound_filter: None,
+            watched: false,
+            broadcast_at: None,
         };
         let mut deploys = HashMap::new();
         let _ = deploys.insert(*deploy.hash(), deploy);
@@ -664,36 +1500,48 @@ mod tests {
             data: SseData::random_deploy_processed(&mut rng),
             json_data: None,
             inbound_filter: None,
+            watched: false,
+            broadcast_at: None,
         };
         let deploy_expired = ServerSentEvent {
             id: Some(rng.gen()),
             data: SseData::random_deploy_expired(&mut rng),
             json_data: None,
             inbound_filter: None,
+            watched: false,
+            broadcast_at: None,
         };
         let fault = ServerSentEvent {
             id: Some(rng.gen()),
             data: SseData::random_fault(&mut rng),
             json_data: None,
             inbound_filter: None,
+            watched: false,
+            broadcast_at: None,
         };
         let finality_signature = ServerSentEvent {
             id: Some(rng.gen()),
             data: SseData::random_finality_signature(&mut rng),
             json_data: None,
             inbound_filter: None,
+            watched: false,
+            broadcast_at: None,
         };
         let step = ServerSentEvent {
             id: Some(rng.gen()),
             data: SseData::random_step(&mut rng),
             json_data: None,
             inbound_filter: None,
+            watched: false,
+            broadcast_at: None,
         };
         let shutdown = ServerSentEvent {
             id: Some(rng.gen()),
             data: SseData::Shutdown,
             json_data: None,
             inbound_filter: Some(SseFilter::Main),
+            watched: false,
+            broadcast_at: None,
             //For shutdown we need to provide the inbound
             //filter because we send shutdowns only to corresponding outbounds to prevent duplicates
         };
@@ -737,6 +1585,152 @@ mod tests {
         should_filter_out(&step, &SIGNATURES_FILTER[..]).await;
     }
 
+    #[tokio::test]
+    async fn should_forward_unknown_events_only_on_unknown_filter() {
+        let mut rng = TestRng::new();
+        let raw = "{\"BlockFinalized\":{\"height\":10}}".to_string();
+        let (data, _) = casper_event_types::sse_data::deserialize(&raw).unwrap();
+        let unknown = ServerSentEvent {
+            id: Some(rng.gen()),
+            data,
+            json_data: Some(raw.clone()),
+            inbound_filter: Some(SseFilter::Main),
+            watched: false,
+            broadcast_at: None,
+        };
+        let block_added = ServerSentEvent {
+            id: Some(rng.gen()),
+            data: SseData::random_block_added(&mut rng),
+            json_data: None,
+            inbound_filter: None,
+            watched: false,
+            broadcast_at: None,
+        };
+
+        should_not_filter_out(&unknown, &UNKNOWN_FILTER[..]).await;
+        should_filter_out(&block_added, &UNKNOWN_FILTER[..]).await;
+        for filter in [
+            &EVENTS_FILTER[..],
+            &MAIN_FILTER[..],
+            &DEPLOYS_FILTER[..],
+            &SIGNATURES_FILTER[..],
+            &SIDECAR_FILTER[..],
+        ] {
+            should_filter_out(&unknown, filter).await;
+        }
+        assert_eq!(get_filter(SSE_API_UNKNOWN_PATH), Some(&UNKNOWN_FILTER[..]));
+        assert_eq!(
+            path_to_filter(SSE_API_UNKNOWN_PATH),
+            Some(&Endpoint::Unknown)
+        );
+    }
+
+    #[tokio::test]
+    async fn should_forward_derived_events_only_on_derived_filter() {
+        let mut rng = TestRng::new();
+        let derived = ServerSentEvent {
+            id: Some(rng.gen()),
+            data: SseData::Derived {
+                deriver: "large_transfers".to_string(),
+                source_event_type: "DeployProcessed".to_string(),
+                payload: RawValue::from_string("{\"amount\":\"1000\"}".to_string()).unwrap(),
+            },
+            json_data: None,
+            inbound_filter: None,
+            watched: false,
+            broadcast_at: None,
+        };
+
+        should_not_filter_out(&derived, &DERIVED_FILTER[..]).await;
+        for filter in [
+            &EVENTS_FILTER[..],
+            &MAIN_FILTER[..],
+            &DEPLOYS_FILTER[..],
+            &SIGNATURES_FILTER[..],
+            &SIDECAR_FILTER[..],
+            &UNKNOWN_FILTER[..],
+            &WATCHED_FILTER[..],
+        ] {
+            should_filter_out(&derived, filter).await;
+        }
+        assert_eq!(get_filter(SSE_API_DERIVED_PATH), Some(&DERIVED_FILTER[..]));
+        assert_eq!(
+            path_to_filter(SSE_API_DERIVED_PATH),
+            Some(&Endpoint::Derived)
+        );
+    }
+
+    #[tokio::test]
+    async fn should_record_outbound_metrics_per_filter_and_event_type() {
+        let mut rng = TestRng::new();
+        let event = ServerSentEvent {
+            id: Some(rng.gen()),
+            data: SseData::random_block_added(&mut rng),
+            json_data: None,
+            inbound_filter: None,
+            watched: false,
+            broadcast_at: Some(Instant::now()),
+        };
+        let labels = ["events/main", "BlockAdded"];
+        let events_before = OUTBOUND_EVENTS.with_label_values(&labels).get();
+        let bytes_before = OUTBOUND_BYTES.with_label_values(&labels).get();
+        let latencies_before = OUTBOUND_SEND_LATENCIES
+            .with_label_values(&labels)
+            .get_sample_count();
+
+        let main_endpoint = path_to_filter(SSE_API_MAIN_PATH).unwrap();
+        filter_map_server_sent_event(&event, main_endpoint, &MAIN_FILTER[..])
+            .await
+            .unwrap()
+            .unwrap();
+
+        let size = serde_json::to_string(&event.data).unwrap().len() as u64;
+        assert!(OUTBOUND_EVENTS.with_label_values(&labels).get() > events_before);
+        assert!(OUTBOUND_BYTES.with_label_values(&labels).get() >= bytes_before + size);
+        assert!(
+            OUTBOUND_SEND_LATENCIES
+                .with_label_values(&labels)
+                .get_sample_count()
+                > latencies_before
+        );
+
+        let drops = OUTBOUND_DROPS.with_label_values(&["events/sigs", "lagged"]);
+        let drops_before = drops.get();
+        handle_lagged(3, &Endpoint::Sigs);
+        assert!(drops.get() >= drops_before + 3);
+    }
+
+    #[tokio::test]
+    async fn should_forward_only_watched_events_on_watched_filter() {
+        let mut rng = TestRng::new();
+        let mut watched = ServerSentEvent {
+            id: Some(rng.gen()),
+            data: SseData::random_finality_signature(&mut rng),
+            json_data: None,
+            inbound_filter: Some(SseFilter::Sigs),
+            watched: true,
+            broadcast_at: None,
+        };
+        let api_version = ServerSentEvent::initial_event(ProtocolVersion::V1_0_0);
+        let watched_filter = get_filter(SSE_API_WATCHED_PATH).unwrap();
+        let watched_endpoint = path_to_filter(SSE_API_WATCHED_PATH).unwrap();
+
+        for event in [&watched, &api_version] {
+            assert!(
+                filter_map_server_sent_event(event, watched_endpoint, watched_filter)
+                    .await
+                    .is_some()
+            );
+        }
+        watched.watched = false;
+        assert!(
+            filter_map_server_sent_event(&watched, watched_endpoint, watched_filter)
+                .await
+                .is_none()
+        );
+        should_not_filter_out(&watched, &SIGNATURES_FILTER[..]).await;
+    }
+
     /// This test checks that events with incorrect IDs (i.e. no types have an ID except for
     /// `ApiVersion`) are filtered out.
     #[allow(clippy::too_many_lines)]
@@ -749,12 +1743,16 @@ mod tests {
             data: SseData::random_api_version(&mut rng),
             json_data: None,
             inbound_filter: None,
+            watched: false,
+            broadcast_at: None,
         };
         let malformed_block_added = ServerSentEvent {
             id: None,
             data: SseData::random_block_added(&mut rng),
             json_data: None,
             inbound_filter: None,
+            watched: false,
+            broadcast_at: None,
         };
         let (sse_data, deploy) = SseData::random_deploy_accepted(&mut rng);
         let malformed_deploy_accepted = ServerSentEvent {
@@ -762,6 +1760,8 @@ mod tests {
             data: sse_data,
             json_data: None,
             inbound_filter: None,
+            watched: false,
+            broadcast_at: None,
         };
         let mut deploys = HashMap::new();
         let _ = deploys.insert(*deploy.hash(), deploy);
@@ -770,36 +1770,48 @@ mod tests {
             data: SseData::random_deploy_processed(&mut rng),
             json_data: None,
             inbound_filter: None,
+            watched: false,
+            broadcast_at: None,
         };
         let malformed_deploy_expired = ServerSentEvent {
             id: None,
             data: SseData::random_deploy_expired(&mut rng),
             json_data: None,
             inbound_filter: None,
+            watched: false,
+            broadcast_at: None,
         };
         let malformed_fault = ServerSentEvent {
             id: None,
             data: SseData::random_fault(&mut rng),
             json_data: None,
             inbound_filter: None,
+            watched: false,
+            broadcast_at: None,
         };
         let malformed_finality_signature = ServerSentEvent {
             id: None,
             data: SseData::random_finality_signature(&mut rng),
             json_data: None,
             inbound_filter: None,
+            watched: false,
+            broadcast_at: None,
         };
         let malformed_step = ServerSentEvent {
             id: None,
             data: SseData::random_step(&mut rng),
             json_data: None,
             inbound_filter: None,
+            watched: false,
+            broadcast_at: None,
         };
         let malformed_shutdown = ServerSentEvent {
             id: None,
             data: SseData::Shutdown,
             json_data: None,
             inbound_filter: None,
+            watched: false,
+            broadcast_at: None,
         };
 
         for filter in &[
@@ -872,8 +1884,16 @@ mod tests {
             let received_events: Vec<Result<WarpServerSentEvent, RecvError>> = stream_to_client(
                 initial_events_receiver,
                 ongoing_events_receiver,
-                stream_filter,
-                get_filter(path_filter).unwrap(),
+                SseSubscription {
+                    stream_filter,
+                    event_filter: get_filter(path_filter).unwrap(),
+                    restrictions: SubscriberRestrictions::default(),
+                    selection: EventSelection::default(),
+                    maybe_batching: None,
+                    schema_version: schema_version::CURRENT_SCHEMA_VERSION,
+                },
+                SlowSubscriberMonitor::new("subscriber".to_string(), stream_filter, None, None),
+                None,
                 #[cfg(feature = "additional-metrics")]
                 tx,
             )
@@ -922,6 +1942,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn should_authenticate_subscribers_only_if_validator_is_configured() {
+        let jwt_validator = JwtValidator::new(
+            &JwtAuthConfig {
+                secret: "secret".to_string(),
+                issuer: None,
+            },
+            None,
+        );
+        let token = sign_token("secret", json!({"filters": ["deploys"]}));
+        let authorization_header = format!("Bearer {}", token);
+
+        assert!(authenticate(Some(SSE_API_MAIN_PATH), None, None).is_ok());
+        assert_eq!(
+            authenticate(Some(SSE_API_MAIN_PATH), None, Some(&jwt_validator))
+                .unwrap_err()
+                .status(),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            authenticate(
+                Some(SSE_API_MAIN_PATH),
+                Some(&authorization_header),
+                Some(&jwt_validator)
+            )
+            .unwrap_err()
+            .status(),
+            StatusCode::FORBIDDEN
+        );
+        assert!(authenticate(
+            Some(SSE_API_DEPLOYS_PATH),
+            Some(&authorization_header),
+            Some(&jwt_validator)
+        )
+        .is_ok());
+    }
+
     /// This test checks that main events from the initial stream which are duplicated in the
     /// ongoing stream are filtered out.
     #[tokio::test]
@@ -971,6 +2028,8 @@ mod tests {
                     data,
                     json_data: None,
                     inbound_filter: None,
+                    watched: false,
+                    broadcast_at: None,
                 }
             })
             .collect()
@@ -0,0 +1,251 @@
+//! Status events sent in-band to subscribers which opted in with `status_events=true`.
+//!
+//! They tell subscribers about degradation affecting their own stream: a change of the health of
+//! the sidecar, events the subscriber missed, or events arriving late from the upstream node.
+//! Status events have no ID and are neither buffered nor replayed.
+
+use crate::{
+    health::{Health, HealthReport},
+    schema_version::{self, Envelope, SchemaVersion},
+};
+use casper_event_types::sse_data::SseData;
+use casper_types::Timestamp;
+use futures::{stream, Stream, StreamExt};
+use serde::Serialize;
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+use tokio::{
+    sync::{broadcast, mpsc},
+    time::interval,
+};
+use tokio_stream::wrappers::{BroadcastStream, UnboundedReceiverStream};
+use tracing::warn;
+use warp::sse::Event as WarpServerSentEvent;
+
+/// Number of status events to buffer for each subscriber.
+const STATUS_CHANNEL_SIZE: usize = 64;
+/// How often the health of the sidecar is checked for changes.
+const HEALTH_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Delay between the proposal of a block and it being broadcast beyond which the upstream node is
+/// considered to lag.
+const UPSTREAM_LAG_THRESHOLD: Duration = Duration::from_secs(60);
+
+/// A status event of the sidecar.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub(crate) enum StatusEvent {
+    /// The health of the sidecar changed, or it is the health at the time of subscription.
+    SidecarStatus(HealthReport),
+    /// The subscriber missed events, as its buffer overflowed.
+    BufferOverflow { missed_events: u64 },
+    /// Some of the events the subscriber asked to be replayed are no longer buffered, so the replay
+    /// starts from the earliest buffered event instead.
+    ReplayUnavailable {
+        requested_event_id: u32,
+        earliest_event_id: u32,
+        missed_events: u64,
+    },
+    /// A block was broadcast long after it was proposed, so the upstream node is lagging.
+    UpstreamLag {
+        block_height: u64,
+        lag_in_seconds: u64,
+    },
+}
+
+impl StatusEvent {
+    /// Returns the lag of the upstream node if `data` is a block broadcast too late.
+    pub(super) fn upstream_lag(data: &SseData) -> Option<StatusEvent> {
+        let block = match data {
+            SseData::BlockAdded { block, .. } => block,
+            _ => return None,
+        };
+        let lag_in_millis = Timestamp::now()
+            .millis()
+            .saturating_sub(block.header.timestamp.millis());
+        if Duration::from_millis(lag_in_millis) <= UPSTREAM_LAG_THRESHOLD {
+            return None;
+        }
+        Some(StatusEvent::UpstreamLag {
+            block_height: block.header.height,
+            lag_in_seconds: lag_in_millis / 1000,
+        })
+    }
+
+    /// The frame of the event as serialized in `schema_version`.
+    pub(super) fn to_warp_event(&self, schema_version: SchemaVersion) -> WarpServerSentEvent {
+        let frame = if schema_version::is_enveloped(schema_version) {
+            WarpServerSentEvent::default().json_data(Envelope {
+                schema_version,
+                payload: self,
+            })
+        } else {
+            WarpServerSentEvent::default().json_data(self)
+        };
+        frame.unwrap_or_else(|error| {
+            warn!(%error, ?self, "failed to jsonify status event");
+            WarpServerSentEvent::default()
+        })
+    }
+}
+
+/// Handle used to send status events to all subscribers which opted in. Cloned handles share the
+/// same channel.
+#[derive(Clone, Debug)]
+pub(crate) struct StatusBroadcaster {
+    sender: broadcast::Sender<StatusEvent>,
+    latest_health: Arc<RwLock<Option<HealthReport>>>,
+}
+
+impl StatusBroadcaster {
+    pub(super) fn new() -> Self {
+        let (sender, _) = broadcast::channel(STATUS_CHANNEL_SIZE);
+        StatusBroadcaster {
+            sender,
+            latest_health: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    pub(crate) fn send(&self, event: StatusEvent) {
+        if let StatusEvent::SidecarStatus(report) = &event {
+            *self
+                .latest_health
+                .write()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(report.clone());
+        }
+        // This fails if no subscriber opted in, which is fine.
+        let _ = self.sender.send(event);
+    }
+
+    /// Returns the status events of a new subscriber: the latest health of the sidecar, followed by
+    /// the events broadcast from now on and by those sent over `own_events` to this subscriber
+    /// only. Status events the subscriber lagged behind on are skipped.
+    pub(super) fn subscribe(
+        &self,
+        own_events: mpsc::UnboundedReceiver<StatusEvent>,
+    ) -> impl Stream<Item = StatusEvent> + Send + 'static {
+        let receiver = self.sender.subscribe();
+        let maybe_latest_health = self
+            .latest_health
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+            .map(StatusEvent::SidecarStatus);
+        let broadcast_events =
+            BroadcastStream::new(receiver).filter_map(|result| futures::future::ready(result.ok()));
+        stream::iter(maybe_latest_health).chain(stream::select(
+            broadcast_events,
+            UnboundedReceiverStream::new(own_events),
+        ))
+    }
+}
+
+/// Starts a task broadcasting the health of the sidecar whenever it changes.
+pub(crate) fn start_health_status_monitor(health: Health, broadcaster: StatusBroadcaster) {
+    tokio::spawn(async move {
+        let mut ticker = interval(HEALTH_POLL_INTERVAL);
+        let mut maybe_last_report: Option<HealthReport> = None;
+        loop {
+            ticker.tick().await;
+            let report = health.report();
+            if maybe_last_report.as_ref() != Some(&report) {
+                broadcaster.send(StatusEvent::SidecarStatus(report.clone()));
+                maybe_last_report = Some(report);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::health::HealthStatus;
+    use casper_types::testing::TestRng;
+
+    #[test]
+    fn should_serialize_status_events_externally_tagged() {
+        let event = StatusEvent::BufferOverflow { missed_events: 3 };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"BufferOverflow":{"missed_events":3}}"#
+        );
+        assert_eq!(
+            event.to_warp_event(1).to_string(),
+            "data:{\"BufferOverflow\":{\"missed_events\":3}}\n\n"
+        );
+        assert_eq!(
+            event.to_warp_event(2).to_string(),
+            "data:{\"schema_version\":2,\"BufferOverflow\":{\"missed_events\":3}}\n\n"
+        );
+        let event = StatusEvent::ReplayUnavailable {
+            requested_event_id: 5,
+            earliest_event_id: 8,
+            missed_events: 3,
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"ReplayUnavailable":{"requested_event_id":5,"earliest_event_id":8,"missed_events":3}}"#
+        );
+    }
+
+    #[test]
+    fn should_report_upstream_lag_of_late_blocks_only() {
+        let mut rng = TestRng::new();
+        // Random blocks are proposed now.
+        let data = SseData::random_block_added(&mut rng);
+        assert_eq!(StatusEvent::upstream_lag(&data), None);
+        assert_eq!(
+            StatusEvent::upstream_lag(&SseData::random_deploy_expired(&mut rng)),
+            None
+        );
+
+        let late_data = match data {
+            SseData::BlockAdded {
+                block_hash,
+                mut block,
+            } => {
+                block.header.timestamp = Timestamp::from(
+                    Timestamp::now().millis() - UPSTREAM_LAG_THRESHOLD.as_millis() as u64 - 5000,
+                );
+                SseData::BlockAdded { block_hash, block }
+            }
+            _ => unreachable!(),
+        };
+        assert!(matches!(
+            StatusEvent::upstream_lag(&late_data),
+            Some(StatusEvent::UpstreamLag { lag_in_seconds, .. }) if lag_in_seconds >= 65
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_start_status_events_with_latest_health() {
+        let broadcaster = StatusBroadcaster::new();
+        let health = Health::new().report();
+        broadcaster.send(StatusEvent::SidecarStatus(health.clone()));
+        let (own_sender, own_receiver) = mpsc::unbounded_channel();
+        let mut events = Box::pin(broadcaster.subscribe(own_receiver));
+
+        broadcaster.send(StatusEvent::BufferOverflow { missed_events: 1 });
+        own_sender
+            .send(StatusEvent::BufferOverflow { missed_events: 2 })
+            .unwrap();
+
+        assert_eq!(
+            events.next().await,
+            Some(StatusEvent::SidecarStatus(health.clone()))
+        );
+        assert_eq!(health.status, HealthStatus::Healthy);
+        let mut received = vec![events.next().await.unwrap(), events.next().await.unwrap()];
+        received.sort_by_key(|event| match event {
+            StatusEvent::BufferOverflow { missed_events } => *missed_events,
+            _ => 0,
+        });
+        assert_eq!(
+            received,
+            vec![
+                StatusEvent::BufferOverflow { missed_events: 1 },
+                StatusEvent::BufferOverflow { missed_events: 2 },
+            ]
+        );
+    }
+}
@@ -0,0 +1,416 @@
+//! Authentication of event stream subscribers.
+//!
+//! Subscribers authenticate with a JWT bearer token issued by the operator's own auth system and
+//! signed with a shared secret (HS256). The claims of the token constrain which event stream
+//! paths the subscriber may connect to and which accounts' deploy events it will receive, which
+//! allows a single sidecar to be shared between customers whose data needs to be kept separate.
+
+use crate::{
+    audit::{AuditAction, AuditLog},
+    token_transfers::TOKEN_TRANSFER_DERIVER,
+    types::{config::JwtAuthConfig, database::TokenTransferEntry},
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use casper_event_types::sse_data::SseData;
+use casper_types::{AsymmetricType, Key, PublicKey};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use std::{
+    collections::HashSet,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use thiserror::Error;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const BEARER_PREFIX: &str = "Bearer ";
+const SUPPORTED_ALGORITHM: &str = "HS256";
+const ANONYMOUS_SUBSCRIBER: &str = "anonymous";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub(super) enum SubscriberAuthError {
+    #[error("missing bearer token")]
+    MissingToken,
+    #[error("malformed token")]
+    MalformedToken,
+    #[error("unsupported signing algorithm: {0}")]
+    UnsupportedAlgorithm(String),
+    #[error("invalid token signature")]
+    InvalidSignature,
+    #[error("token expired")]
+    Expired,
+    #[error("unexpected token issuer")]
+    InvalidIssuer,
+}
+
+#[derive(Deserialize)]
+struct JwtHeader {
+    alg: String,
+}
+
+/// Claims of a subscriber's token which the event stream server understands.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub(super) struct SubscriberClaims {
+    /// Identifier of the subscriber, used for logging only.
+    pub(super) sub: Option<String>,
+    /// Expiration time as seconds since the UNIX epoch.
+    pub(super) exp: Option<u64>,
+    /// Issuer of the token.
+    pub(super) iss: Option<String>,
+    /// Event stream paths (e.g. `main`, `deploys`) the subscriber may connect to. All paths are
+    /// allowed if this claim is absent.
+    pub(super) filters: Option<Vec<String>>,
+    /// Hex-encoded public keys of the accounts whose deploy events and token transfers the
+    /// subscriber may receive. The events of all accounts are sent if this claim is absent.
+    pub(super) accounts: Option<Vec<String>>,
+}
+
+impl SubscriberClaims {
+    /// Returns true if the claims allow connecting to the given event stream path.
+    pub(super) fn allows_path(&self, path_param: &str) -> bool {
+        match &self.filters {
+            None => true,
+            Some(filters) => filters.iter().any(|filter| filter == path_param),
+        }
+    }
+
+    pub(super) fn restrictions(&self) -> SubscriberRestrictions {
+        SubscriberRestrictions {
+            allowed_accounts: self.accounts.as_ref().map(|accounts| {
+                let public_keys: HashSet<String> = accounts
+                    .iter()
+                    .map(|account| account.to_lowercase())
+                    .collect();
+                // Accounts which aren't valid public keys can't match the key of an account.
+                let account_keys = public_keys
+                    .iter()
+                    .filter_map(|public_key| PublicKey::from_hex(public_key).ok())
+                    .map(|public_key| Key::Account(public_key.to_account_hash()))
+                    .map(|key| key.to_formatted_string())
+                    .collect();
+                Arc::new(AllowedAccounts {
+                    public_keys,
+                    account_keys,
+                })
+            }),
+        }
+    }
+}
+
+/// The accounts of the `accounts` claim.
+#[derive(Debug)]
+struct AllowedAccounts {
+    /// Lowercase hex-encoded public keys, as found in the deploy events.
+    public_keys: HashSet<String>,
+    /// Formatted keys of the accounts, as found in the token transfers.
+    account_keys: HashSet<String>,
+}
+
+/// Restrictions derived from a subscriber's claims, applied to every event before it is sent to
+/// that subscriber.
+#[derive(Clone, Debug, Default)]
+pub(super) struct SubscriberRestrictions {
+    allowed_accounts: Option<Arc<AllowedAccounts>>,
+}
+
+impl SubscriberRestrictions {
+    /// Returns true if the subscriber is allowed to see the given event.
+    ///
+    /// Account restrictions apply to deploy events and to the token transfers derived from them,
+    /// sent only if their sender or recipient is allowed. `DeployExpired` doesn't carry the
+    /// account, nor can the other derived events be attributed to one, so they are withheld from
+    /// subscribers with an account restriction.
+    pub(super) fn permits(&self, data: &SseData) -> bool {
+        let allowed_accounts = match &self.allowed_accounts {
+            None => return true,
+            Some(allowed_accounts) => allowed_accounts,
+        };
+        match data {
+            SseData::DeployAccepted { deploy } => allowed_accounts
+                .public_keys
+                .contains(&deploy.header().account().to_hex().to_lowercase()),
+            SseData::DeployProcessed { account, .. } => allowed_accounts
+                .public_keys
+                .contains(&account.to_hex().to_lowercase()),
+            SseData::DeployExpired { .. } => false,
+            SseData::Derived {
+                deriver, payload, ..
+            } if deriver == TOKEN_TRANSFER_DERIVER => {
+                serde_json::from_str::<TokenTransferEntry>(payload.get()).map_or(false, |entry| {
+                    [entry.from, entry.to]
+                        .iter()
+                        .flatten()
+                        .any(|key| allowed_accounts.account_keys.contains(key))
+                })
+            }
+            SseData::Derived { .. } => false,
+            _ => true,
+        }
+    }
+}
+
+/// Verifies subscriber tokens against the configured secret.
+pub(super) struct JwtValidator {
+    secret: Vec<u8>,
+    issuer: Option<String>,
+    maybe_audit_log: Option<AuditLog>,
+}
+
+impl JwtValidator {
+    pub(super) fn new(config: &JwtAuthConfig, maybe_audit_log: Option<AuditLog>) -> Self {
+        JwtValidator {
+            secret: config.secret.as_bytes().to_vec(),
+            issuer: config.issuer.clone(),
+            maybe_audit_log,
+        }
+    }
+
+    /// Verifies the token from the value of the `Authorization` header and returns its claims.
+    /// The outcome is recorded in the audit log, if one is configured.
+    pub(super) fn verify(
+        &self,
+        maybe_authorization_header: Option<&str>,
+    ) -> Result<SubscriberClaims, SubscriberAuthError> {
+        let result = self.verify_token(maybe_authorization_header);
+        if let Some(audit_log) = &self.maybe_audit_log {
+            match &result {
+                Ok(claims) => audit_log.record(
+                    claims.sub.as_deref().unwrap_or(ANONYMOUS_SUBSCRIBER),
+                    AuditAction::SubscriberTokenAccepted,
+                    None,
+                ),
+                Err(error) => audit_log.record(
+                    ANONYMOUS_SUBSCRIBER,
+                    AuditAction::SubscriberTokenRejected,
+                    Some(error.to_string()),
+                ),
+            }
+        }
+        result
+    }
+
+    fn verify_token(
+        &self,
+        maybe_authorization_header: Option<&str>,
+    ) -> Result<SubscriberClaims, SubscriberAuthError> {
+        let token = maybe_authorization_header
+            .and_then(|header| header.strip_prefix(BEARER_PREFIX))
+            .map(str::trim)
+            .ok_or(SubscriberAuthError::MissingToken)?;
+        let mut parts = token.split('.');
+        let (header, payload, signature) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(header), Some(payload), Some(signature)) if parts.next().is_none() => {
+                (header, payload, signature)
+            }
+            _ => return Err(SubscriberAuthError::MalformedToken),
+        };
+        let jwt_header: JwtHeader = decode_json_part(header)?;
+        if jwt_header.alg != SUPPORTED_ALGORITHM {
+            return Err(SubscriberAuthError::UnsupportedAlgorithm(jwt_header.alg));
+        }
+        self.verify_signature(header, payload, signature)?;
+        let claims: SubscriberClaims = decode_json_part(payload)?;
+        self.validate_claims(&claims)?;
+        Ok(claims)
+    }
+
+    fn verify_signature(
+        &self,
+        header: &str,
+        payload: &str,
+        signature: &str,
+    ) -> Result<(), SubscriberAuthError> {
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| SubscriberAuthError::MalformedToken)?;
+        let mut mac = HmacSha256::new_from_slice(&self.secret)
+            .map_err(|_| SubscriberAuthError::InvalidSignature)?;
+        mac.update(header.as_bytes());
+        mac.update(b".");
+        mac.update(payload.as_bytes());
+        mac.verify_slice(&signature)
+            .map_err(|_| SubscriberAuthError::InvalidSignature)
+    }
+
+    fn validate_claims(&self, claims: &SubscriberClaims) -> Result<(), SubscriberAuthError> {
+        if let Some(exp) = claims.exp {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default();
+            if exp <= now {
+                return Err(SubscriberAuthError::Expired);
+            }
+        }
+        if let Some(issuer) = &self.issuer {
+            if claims.iss.as_ref() != Some(issuer) {
+                return Err(SubscriberAuthError::InvalidIssuer);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn decode_json_part<T: for<'de> Deserialize<'de>>(part: &str) -> Result<T, SubscriberAuthError> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(part)
+        .map_err(|_| SubscriberAuthError::MalformedToken)?;
+    serde_json::from_slice(&bytes).map_err(|_| SubscriberAuthError::MalformedToken)
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use crate::types::database::TokenTransferKind;
+    use casper_types::{testing::TestRng, U256};
+    use serde_json::{json, Value};
+
+    const SECRET: &str = "very-secret";
+
+    pub(in crate::event_stream_server) fn sign_token(secret: &str, claims: Value) -> String {
+        let header = URL_SAFE_NO_PAD.encode(json!({"alg": "HS256", "typ": "JWT"}).to_string());
+        let payload = URL_SAFE_NO_PAD.encode(claims.to_string());
+        let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(format!("{}.{}", header, payload).as_bytes());
+        let signature = URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes());
+        format!("{}.{}.{}", header, payload, signature)
+    }
+
+    fn validator(issuer: Option<&str>) -> JwtValidator {
+        JwtValidator::new(
+            &JwtAuthConfig {
+                secret: SECRET.to_string(),
+                issuer: issuer.map(str::to_string),
+            },
+            None,
+        )
+    }
+
+    fn bearer(token: &str) -> String {
+        format!("Bearer {}", token)
+    }
+
+    #[test]
+    fn should_accept_correctly_signed_token() {
+        let token = sign_token(SECRET, json!({"sub": "customer-1", "filters": ["main"]}));
+
+        let claims = validator(None).verify(Some(&bearer(&token))).unwrap();
+
+        assert_eq!(claims.sub, Some("customer-1".to_string()));
+        assert!(claims.allows_path("main"));
+        assert!(!claims.allows_path("deploys"));
+    }
+
+    #[test]
+    fn should_reject_missing_or_malformed_token() {
+        let validator = validator(None);
+        assert_eq!(
+            validator.verify(None),
+            Err(SubscriberAuthError::MissingToken)
+        );
+        assert_eq!(
+            validator.verify(Some("Basic abc")),
+            Err(SubscriberAuthError::MissingToken)
+        );
+        assert_eq!(
+            validator.verify(Some("Bearer abc.def")),
+            Err(SubscriberAuthError::MalformedToken)
+        );
+    }
+
+    #[test]
+    fn should_reject_token_signed_with_other_secret() {
+        let token = sign_token("other-secret", json!({}));
+
+        let result = validator(None).verify(Some(&bearer(&token)));
+
+        assert_eq!(result, Err(SubscriberAuthError::InvalidSignature));
+    }
+
+    #[test]
+    fn should_reject_expired_token() {
+        let token = sign_token(SECRET, json!({"exp": 1}));
+
+        let result = validator(None).verify(Some(&bearer(&token)));
+
+        assert_eq!(result, Err(SubscriberAuthError::Expired));
+    }
+
+    #[test]
+    fn should_reject_token_from_unexpected_issuer() {
+        let token = sign_token(SECRET, json!({"iss": "someone-else"}));
+
+        let result = validator(Some("operator")).verify(Some(&bearer(&token)));
+
+        assert_eq!(result, Err(SubscriberAuthError::InvalidIssuer));
+    }
+
+    #[test]
+    fn should_restrict_deploy_events_to_allowed_accounts() {
+        let mut rng = TestRng::new();
+        let (deploy_accepted, deploy) = SseData::random_deploy_accepted(&mut rng);
+        let other_account = PublicKey::random(&mut rng);
+        let allowed = SubscriberClaims {
+            accounts: Some(vec![deploy.header().account().to_hex().to_uppercase()]),
+            ..Default::default()
+        };
+        let not_allowed = SubscriberClaims {
+            accounts: Some(vec![other_account.to_hex()]),
+            ..Default::default()
+        };
+
+        assert!(allowed.restrictions().permits(&deploy_accepted));
+        assert!(!not_allowed.restrictions().permits(&deploy_accepted));
+        assert!(!not_allowed
+            .restrictions()
+            .permits(&SseData::random_deploy_expired(&mut rng)));
+        assert!(not_allowed
+            .restrictions()
+            .permits(&SseData::random_block_added(&mut rng)));
+        assert!(SubscriberRestrictions::default().permits(&deploy_accepted));
+    }
+
+    #[test]
+    fn should_restrict_token_transfers_to_allowed_accounts() {
+        let mut rng = TestRng::new();
+        let sender = PublicKey::random(&mut rng);
+        let recipient = PublicKey::random(&mut rng);
+        let other_account = PublicKey::random(&mut rng);
+        let token_transfer = SseData::Derived {
+            deriver: TOKEN_TRANSFER_DERIVER.to_string(),
+            source_event_type: "DeployProcessed".to_string(),
+            payload: serde_json::value::to_raw_value(&TokenTransferEntry {
+                contract_hash: "ab".to_string(),
+                event_id: 0,
+                kind: TokenTransferKind::Transfer,
+                from: Some(Key::Account(sender.to_account_hash()).to_formatted_string()),
+                to: Some(Key::Account(recipient.to_account_hash()).to_formatted_string()),
+                spender: None,
+                amount: U256::from(10),
+                deploy_hash: "cd".to_string(),
+                block_hash: "ef".to_string(),
+            })
+            .unwrap(),
+        };
+        let other_derived = SseData::Derived {
+            deriver: "BlockFinalized".to_string(),
+            source_event_type: "FinalitySignature".to_string(),
+            payload: serde_json::value::to_raw_value(&json!({})).unwrap(),
+        };
+        let restrictions = |account: &PublicKey| {
+            SubscriberClaims {
+                accounts: Some(vec![account.to_hex()]),
+                ..Default::default()
+            }
+            .restrictions()
+        };
+
+        assert!(restrictions(&sender).permits(&token_transfer));
+        assert!(restrictions(&recipient).permits(&token_transfer));
+        assert!(!restrictions(&other_account).permits(&token_transfer));
+        assert!(!restrictions(&sender).permits(&other_derived));
+        assert!(SubscriberRestrictions::default().permits(&token_transfer));
+    }
+}
@@ -6,9 +6,10 @@ use pretty_assertions::assert_eq;
 use reqwest::Response;
 use serde_json::Value;
 use sse_server::{
-    DeployAccepted, Id, QUERY_FIELD, SSE_API_DEPLOYS_PATH as DEPLOYS_PATH,
+    DeployAccepted, Id, ACCOUNT_QUERY_FIELD, BATCH_QUERY_FIELD, BATCH_SIZE_QUERY_FIELD,
+    CONTRACT_QUERY_FIELD, INCLUDE_QUERY_FIELD, QUERY_FIELD, SSE_API_DEPLOYS_PATH as DEPLOYS_PATH,
     SSE_API_MAIN_PATH as MAIN_PATH, SSE_API_ROOT_PATH as ROOT_PATH,
-    SSE_API_SIGNATURES_PATH as SIGS_PATH,
+    SSE_API_SIGNATURES_PATH as SIGS_PATH, STATUS_EVENTS_QUERY_FIELD, TOKEN_QUERY_FIELD,
 };
 use std::{
     collections::HashMap,
@@ -80,6 +81,8 @@ struct ServerBehavior {
     /// If `Some`, sets the `max_concurrent_subscribers` server config value, otherwise uses the
     /// config default.
     max_concurrent_subscribers: Option<u32>,
+    /// Sets the `reject_unavailable_replay` server config value.
+    reject_unavailable_replay: bool,
     clients: Vec<ClientSyncBehavior>,
 }
 
@@ -92,6 +95,7 @@ impl ServerBehavior {
             has_delay_between_events: true,
             repeat_events: false,
             max_concurrent_subscribers: None,
+            reject_unavailable_replay: false,
             clients: Vec::new(),
         }
     }
@@ -105,6 +109,7 @@ impl ServerBehavior {
             has_delay_between_events: false,
             repeat_events: true,
             max_concurrent_subscribers: None,
+            reject_unavailable_replay: false,
             clients: Vec::new(),
         }
     }
@@ -264,10 +269,17 @@ impl TestFixture {
             max_concurrent_subscribers: server_behavior
                 .max_concurrent_subscribers
                 .unwrap_or(Config::default().max_concurrent_subscribers),
+            reject_unavailable_replay: server_behavior.reject_unavailable_replay,
             ..Default::default()
         };
-        let mut server =
-            EventStreamServer::new(config, self.storage_dir.path().to_path_buf()).unwrap();
+        let mut server = EventStreamServer::new(
+            config,
+            self.storage_dir.path().to_path_buf(),
+            None,
+            WatchList::default(),
+            None,
+        )
+        .unwrap();
 
         self.first_event_id = server.event_indexer.current_index();
 
@@ -284,7 +296,10 @@ impl TestFixture {
             };
             let api_version_event = SseData::ApiVersion(protocol_version);
 
-            server.broadcast(api_version_event.clone(), Some(SseFilter::Main), None);
+            server
+                .broadcast(api_version_event.clone(), Some(SseFilter::Main), None)
+                .await
+                .expect("Error broadcasting event");
             for (id, (event, maybe_json_data)) in
                 events.iter().cycle().enumerate().take(event_count as usize)
             {
@@ -295,13 +310,16 @@ impl TestFixture {
                 server_behavior
                     .wait_for_clients((id as Id).wrapping_add(first_event_id))
                     .await;
-                server.broadcast(
-                    event.clone(),
-                    Some(SseFilter::Main),
-                    maybe_json_data
-                        .as_ref()
-                        .map(|el| serde_json::from_str(el.as_str()).unwrap()),
-                );
+                server
+                    .broadcast(
+                        event.clone(),
+                        Some(SseFilter::Main),
+                        maybe_json_data
+                            .as_ref()
+                            .map(|el| serde_json::from_str(el.as_str()).unwrap()),
+                    )
+                    .await
+                    .expect("Error broadcasting event");
                 server_behavior.sleep_if_required().await;
             }
 
@@ -654,6 +672,84 @@ async fn should_serve_signature_events_with_no_query() {
     should_serve_events_with_no_query(SIGS_PATH).await;
 }
 
+/// Client setup:
+///   * `<IP:port>/events/main?batch=50ms&batch_size=10`
+///   * connected before first event
+///
+/// Expected to receive all main events in frames holding the schema version and arrays of at most
+/// 10 events, each frame having the ID of its last event.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn should_serve_batched_events() {
+    let mut rng = TestRng::new();
+    let mut fixture = TestFixture::new(&mut rng);
+
+    let mut server_behavior = ServerBehavior::new();
+    let barrier = server_behavior.add_client_sync_before_event(0);
+    let server_address = fixture.run_server(server_behavior).await;
+
+    let url = format!(
+        "{}?{}=50ms&{}=10",
+        url(server_address, MAIN_PATH, None),
+        BATCH_QUERY_FIELD,
+        BATCH_SIZE_QUERY_FIELD
+    );
+    let (expected_events, final_id) = fixture.all_filtered_events(MAIN_PATH);
+    let received_frames = subscribe(&url, barrier, final_id, "client").await.unwrap();
+    fixture.stop_server().await;
+
+    let mut received_data = Vec::new();
+    for frame in &received_frames {
+        let mut frame_value = serde_json::from_str::<Value>(&frame.data).unwrap();
+        assert_eq!(frame_value["schema_version"], 2);
+        let batch = match frame_value["events"].take() {
+            Value::Array(batch) => batch,
+            other => panic!("expected a batch of events, got {}", other),
+        };
+        assert!(!batch.is_empty() && batch.len() <= 10);
+        received_data.extend(batch);
+    }
+    let expected_data: Vec<Value> = expected_events
+        .iter()
+        .map(|event| serde_json::from_str(&event.data).unwrap())
+        .collect();
+    assert_eq!(received_data, expected_data);
+    assert_eq!(received_frames.last().unwrap().id, Some(final_id));
+}
+
+/// Client setup:
+///   * `<IP:port>/events/main?include=BlockAdded`
+///   * connected before first event
+///
+/// Expected to receive the `ApiVersion` event followed by the `BlockAdded` events only.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn should_serve_included_event_types_only() {
+    let mut rng = TestRng::new();
+    let mut fixture = TestFixture::new(&mut rng);
+
+    let mut server_behavior = ServerBehavior::new();
+    let barrier = server_behavior.add_client_sync_before_event(0);
+    let server_address = fixture.run_server(server_behavior).await;
+
+    let url = format!(
+        "{}?{}=BlockAdded",
+        url(server_address, MAIN_PATH, None),
+        INCLUDE_QUERY_FIELD
+    );
+    let (all_events, _) = fixture.all_filtered_events(MAIN_PATH);
+    let expected_events: Vec<ReceivedEvent> = all_events
+        .into_iter()
+        .filter(|event| {
+            let data = serde_json::from_str::<Value>(&event.data).unwrap();
+            event.id.is_none() || data.get("BlockAdded").is_some()
+        })
+        .collect();
+    let final_id = expected_events.last().unwrap().id.unwrap();
+    let received_events = subscribe(&url, barrier, final_id, "client").await.unwrap();
+    fixture.stop_server().await;
+
+    assert_eq!(received_events, expected_events);
+}
+
 /// Client setup:
 ///   * `<IP:port>/events/<path>?start_from=25`
 ///   * connected just before event ID 50
@@ -735,6 +831,59 @@ async fn should_serve_remaining_signature_events_with_query() {
     should_serve_remaining_events_with_query(SIGS_PATH).await;
 }
 
+/// Client setup:
+///   * `<IP:port>/events/main?start_from=0`, with unavailable replays being rejected
+///   * connected just before event ID 75
+///
+/// Expected to be rejected with a 416 naming event 25 as the earliest available one, as events 0 to
+/// 24 should have been purged from the server buffer. `/info` should report the buffered events 25
+/// to 74.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn should_reject_replay_of_unavailable_events() {
+    let mut rng = TestRng::new();
+    let mut fixture = TestFixture::new(&mut rng);
+
+    let connect_at_event_id = BUFFER_LENGTH * 3 / 2;
+
+    let mut server_behavior = ServerBehavior::new();
+    server_behavior.reject_unavailable_replay = true;
+    let barrier = server_behavior.add_client_sync_before_event(connect_at_event_id);
+    let server_address = fixture.run_server(server_behavior).await;
+
+    timeout(Duration::from_secs(60), barrier.wait())
+        .await
+        .unwrap();
+    let response = reqwest::get(&url(server_address, MAIN_PATH, Some(0)))
+        .await
+        .unwrap();
+    let info_response = reqwest::get(&format!("http://{}/info", server_address))
+        .await
+        .unwrap();
+    timeout(Duration::from_secs(60), barrier.wait())
+        .await
+        .unwrap();
+    fixture.stop_server().await;
+
+    let earliest_event_id = connect_at_event_id - BUFFER_LENGTH;
+    assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+    assert_eq!(
+        response.text().await.unwrap(),
+        format!(
+            "event 0 is no longer available: the earliest available event ID is {}\n",
+            earliest_event_id
+        )
+    );
+    let info: Value = serde_json::from_str(&info_response.text().await.unwrap()).unwrap();
+    assert_eq!(
+        info,
+        serde_json::json!({
+            "earliest_event_id": earliest_event_id,
+            "latest_event_id": connect_at_event_id - 1,
+            "buffer_length": BUFFER_LENGTH,
+        })
+    );
+}
+
 /// Client setup:
 ///   * `<IP:port>/events/<path>?start_from=25`
 ///   * connected before first event
@@ -949,29 +1098,129 @@ async fn should_handle_bad_url_query() {
         format!("{}?{}=0&extra=1", main_url, QUERY_FIELD),
         format!("{}?{}=0&extra=1", deploys_url, QUERY_FIELD),
         format!("{}?{}=0&extra=1", sigs_url, QUERY_FIELD),
+        format!(
+            "{}?{}=0&extra=1&{}=1s",
+            main_url, QUERY_FIELD, BATCH_QUERY_FIELD
+        ),
     ];
     let expected_body = format!(
         "invalid query: expected single field '{}=<EVENT ID>'",
         QUERY_FIELD
     );
     for url in &urls {
-        let response = reqwest::get(url).await.unwrap();
-        assert_eq!(
-            response.status(),
-            StatusCode::UNPROCESSABLE_ENTITY,
-            "URL: {}",
-            url
-        );
-        assert_eq!(
-            response.text().await.unwrap().trim(),
-            &expected_body,
-            "URL: {}",
-            url
-        );
+        assert_invalid_query(url, &expected_body).await;
+    }
+    fixture.stop_server().await;
+}
+
+async fn assert_invalid_query(url: &str, expected_message: &str) {
+    let response = reqwest::get(url).await.unwrap();
+    assert_eq!(
+        response.status(),
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "URL: {}",
+        url
+    );
+    assert!(
+        response.text().await.unwrap().starts_with(expected_message),
+        "URL: {}",
+        url
+    );
+}
+
+/// Checks that clients selecting events with an invalid query get a helpful error response.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn should_handle_bad_selection_query() {
+    let (mut fixture, server_address) = start_query_url_test().await;
+    let (main_url, _, _) = build_urls(server_address);
+    let batch_urls = [
+        format!("{}?{}=0", main_url, BATCH_QUERY_FIELD),
+        format!("{}?{}=11s", main_url, BATCH_QUERY_FIELD),
+        format!("{}?{}=100", main_url, BATCH_QUERY_FIELD),
+        format!(
+            "{}?{}=1s&{}=0",
+            main_url, BATCH_QUERY_FIELD, BATCH_SIZE_QUERY_FIELD
+        ),
+        format!("{}?{}=10", main_url, BATCH_SIZE_QUERY_FIELD),
+    ];
+    for url in &batch_urls {
+        assert_invalid_query(url, "invalid query: expected 'batch=<WINDOW>'").await;
+    }
+    let include_urls = [
+        format!("{}?{}=", main_url, INCLUDE_QUERY_FIELD),
+        format!("{}?{}=BlockAdded,NotAnEvent", main_url, INCLUDE_QUERY_FIELD),
+    ];
+    for url in &include_urls {
+        assert_invalid_query(url, "invalid query: expected 'include=<EVENT TYPES>'").await;
+    }
+    fixture.stop_server().await;
+}
+
+/// Checks that clients filtering events by an invalid entity get a helpful error response.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn should_handle_bad_entity_query() {
+    let (mut fixture, server_address) = start_query_url_test().await;
+    let (main_url, deploys_url, _) = build_urls(server_address);
+    let invalid_queries = [
+        (
+            format!("{}?{}=not-a-key", deploys_url, ACCOUNT_QUERY_FIELD),
+            "invalid query: expected 'account=<PUBLIC KEY>'",
+        ),
+        (
+            format!("{}?{}=not-a-hash", main_url, TOKEN_QUERY_FIELD),
+            "invalid query: expected 'token=<CONTRACT HASH>'",
+        ),
+        (
+            format!("{}?{}=not-a-hash", main_url, CONTRACT_QUERY_FIELD),
+            "invalid query: expected 'contract=<CONTRACT HASH>'",
+        ),
+        (
+            format!("{}?{}=yes", main_url, STATUS_EVENTS_QUERY_FIELD),
+            "invalid query: expected 'status_events=true'",
+        ),
+    ];
+    for (url, expected_message) in &invalid_queries {
+        assert_invalid_query(url, expected_message).await;
     }
     fixture.stop_server().await;
 }
 
+/// Checks that clients are served the schema version they pin with the `Accept-Version` header,
+/// the latest one if they don't, and are rejected if they pin an unsupported one.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn should_negotiate_the_schema_version() {
+    let (mut fixture, server_address) = start_query_url_test().await;
+    let (main_url, _, _) = build_urls(server_address);
+    let client = reqwest::Client::new();
+
+    let latest = client.get(&main_url).send().await.unwrap();
+    assert_eq!(latest.status(), StatusCode::OK);
+    assert_eq!(latest.headers()["schema-version"], "2");
+    drop(latest);
+    let pinned = client
+        .get(&main_url)
+        .header("Accept-Version", "1")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(pinned.status(), StatusCode::OK);
+    assert_eq!(pinned.headers()["schema-version"], "1");
+    drop(pinned);
+    let unsupported = client
+        .get(&main_url)
+        .header("Accept-Version", "3")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(unsupported.status(), StatusCode::NOT_ACCEPTABLE);
+    assert!(unsupported
+        .text()
+        .await
+        .unwrap()
+        .starts_with("invalid 'accept-version' header: unsupported schema version '3'"));
+    fixture.stop_server().await;
+}
+
 /// Check that a server which restarts continues from the previous numbering of event IDs.
 async fn should_persist_event_ids(path: &str) {
     let mut rng = TestRng::new();
@@ -0,0 +1,415 @@
+//! WebSocket mirror of the event stream.
+//!
+//! `/ws/events` and `/ws/events/<path>` serve the same events as their SSE counterparts, for
+//! clients which struggle with the reconnection of `EventSource`. Every event is sent as a text
+//! message holding its `id` and its `data`. In place of the `Last-Event-ID` header, the client sends
+//! `{"last_event_id": <ID>}` as its first message to resume after the last event it received, or
+//! `{}` to receive the new events only.
+
+use super::{
+    endpoint::Endpoint,
+    event_selection::EventSelection,
+    slow_subscriber::{Disconnection, SlowSubscriberMonitor},
+    sse_server::{
+        create_404, event_to_json, forwarded_id, get_filter, parse_selection, parse_start_from,
+        path_to_filter, record_sent_bytes, record_sent_event, register_subscriber,
+        subscribed_events, BroadcastChannelMessage, Id, NewSubscriberInfo, ServerSentEvent,
+        SubscriberPolicy, AUTHORIZATION_HEADER, SSE_API_ROOT_PATH,
+    },
+    subscriber_auth::SubscriberRestrictions,
+};
+use crate::api_keys::API_KEY_PARAMETER;
+use casper_event_types::sse_data::EventFilter;
+use futures::{Sink, SinkExt, Stream, StreamExt};
+use serde::Deserialize;
+use serde_json::json;
+use std::{collections::HashMap, net::SocketAddr, sync::Mutex, time::Duration};
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    mpsc::UnboundedSender,
+};
+use tracing::{error, info};
+use warp::{
+    filters::BoxedFilter,
+    path,
+    reply::Response,
+    ws::{Message, WebSocket, Ws},
+    Filter, Reply,
+};
+
+/// The URL path prefix of the WebSocket endpoints.
+pub const WEBSOCKET_PATH: &str = "ws";
+/// The time a client has to send its first message after connecting.
+const RESUMPTION_TIMEOUT: Duration = Duration::from_secs(10);
+/// The close code sent when the client's first message isn't a valid resumption message.
+const CLOSE_POLICY_VIOLATION: u16 = 1008;
+/// The close code sent when the server shuts down.
+const CLOSE_GOING_AWAY: u16 = 1001;
+
+/// The first message of a client, telling from where its stream starts.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Resumption {
+    /// The ID of the last event the client received, `None` to receive the new events only.
+    last_event_id: Option<Id>,
+}
+
+/// What a subscriber asked for in its request, and what it is allowed to see.
+struct Subscription {
+    stream_filter: &'static Endpoint,
+    event_filter: &'static [EventFilter],
+    restrictions: SubscriberRestrictions,
+    selection: EventSelection,
+    /// The ID of the first event to send if the client doesn't resume after an event.
+    start_from: Option<Id>,
+}
+
+/// Creates the filter serving the WebSocket endpoints, subscribing the clients like the SSE ones.
+pub(super) fn websocket_filter(
+    broadcaster: broadcast::Sender<BroadcastChannelMessage>,
+    new_subscriber_info_sender: UnboundedSender<NewSubscriberInfo>,
+    policy: SubscriberPolicy,
+) -> BoxedFilter<(Response,)> {
+    let opt = warp::path::param::<String>()
+        .map(Some)
+        .or_else(|_| async { Ok::<(Option<String>,), std::convert::Infallible>((None,)) });
+    warp::get()
+        .and(warp::path(WEBSOCKET_PATH))
+        .and(warp::path(SSE_API_ROOT_PATH))
+        .and(opt)
+        .and(path::end())
+        .and(warp::ws())
+        .and(warp::query())
+        .and(warp::header::optional::<String>(AUTHORIZATION_HEADER))
+        .and(warp::addr::remote())
+        .map(
+            move |maybe_path_param: Option<String>,
+                  ws: Ws,
+                  query: HashMap<String, String>,
+                  maybe_authorization_header: Option<String>,
+                  maybe_remote_address: Option<SocketAddr>| {
+                let subscription = match admit_subscription(
+                    &policy,
+                    &broadcaster,
+                    maybe_path_param,
+                    query,
+                    maybe_authorization_header,
+                ) {
+                    Ok(subscription) => subscription,
+                    Err(error_response) => return error_response,
+                };
+                upgrade(
+                    ws,
+                    subscription,
+                    maybe_remote_address,
+                    &policy,
+                    &broadcaster,
+                    &new_subscriber_info_sender,
+                )
+            },
+        )
+        .boxed()
+}
+
+/// Admits the subscriber under `policy` and parses the subscription it requests.
+fn admit_subscription(
+    policy: &SubscriberPolicy,
+    broadcaster: &broadcast::Sender<BroadcastChannelMessage>,
+    maybe_path_param: Option<String>,
+    mut query: HashMap<String, String>,
+    maybe_authorization_header: Option<String>,
+) -> Result<Subscription, Response> {
+    let restrictions = policy.admit(
+        broadcaster,
+        maybe_path_param.as_deref(),
+        maybe_authorization_header.as_deref(),
+    )?;
+    let path_param = maybe_path_param.unwrap_or_else(|| SSE_API_ROOT_PATH.to_string());
+    let (event_filter, stream_filter) = match (get_filter(&path_param), path_to_filter(&path_param))
+    {
+        (Some(event_filter), Some(stream_filter)) => (event_filter, stream_filter),
+        _ => return Err(create_404()),
+    };
+    // The API key, if any, was checked before the subscription.
+    query.remove(API_KEY_PARAMETER);
+    let selection = parse_selection(&mut query)?;
+    let start_from = parse_start_from(query)?;
+    Ok(Subscription {
+        stream_filter,
+        event_filter,
+        restrictions,
+        selection,
+        start_from,
+    })
+}
+
+/// Upgrades the connection of the admitted subscriber to a WebSocket serving its subscription.
+fn upgrade(
+    ws: Ws,
+    subscription: Subscription,
+    maybe_remote_address: Option<SocketAddr>,
+    policy: &SubscriberPolicy,
+    broadcaster: &broadcast::Sender<BroadcastChannelMessage>,
+    new_subscriber_info_sender: &UnboundedSender<NewSubscriberInfo>,
+) -> Response {
+    let monitor = policy.monitor(maybe_remote_address, subscription.stream_filter);
+    let broadcaster = broadcaster.clone();
+    let new_subscriber_info_sender = new_subscriber_info_sender.clone();
+    ws.on_upgrade(move |socket| {
+        serve_websocket(
+            socket,
+            subscription,
+            monitor,
+            broadcaster,
+            new_subscriber_info_sender,
+        )
+    })
+    .into_response()
+}
+
+/// Waits for the client's resumption message, then sends it its events until either side closes
+/// the connection.
+async fn serve_websocket(
+    socket: WebSocket,
+    subscription: Subscription,
+    monitor: SlowSubscriberMonitor,
+    broadcaster: broadcast::Sender<BroadcastChannelMessage>,
+    new_subscriber_info_sender: UnboundedSender<NewSubscriberInfo>,
+) {
+    let (mut sink, mut incoming) = socket.split();
+    let resumption = match tokio::time::timeout(RESUMPTION_TIMEOUT, read_resumption(&mut incoming))
+        .await
+        .unwrap_or_else(|_| Err("expected a resumption message".to_string()))
+    {
+        Ok(resumption) => resumption,
+        Err(reason) => {
+            info!(%reason, "rejecting websocket subscriber");
+            let _ = sink
+                .send(Message::close_with(CLOSE_POLICY_VIOLATION, reason))
+                .await;
+            return;
+        }
+    };
+    let start_from = match resumption.last_event_id {
+        Some(last_event_id) => Some(last_event_id.wrapping_add(1)),
+        None => subscription.start_from,
+    };
+    let (initial_events_receiver, _) =
+        register_subscriber(&new_subscriber_info_sender, start_from, None);
+    let disconnection = monitor.disconnection();
+    let messages = messages_to_client(
+        subscribed_events(
+            initial_events_receiver,
+            broadcaster.subscribe(),
+            subscription.stream_filter,
+            subscription.event_filter,
+            subscription.selection.clone(),
+            monitor,
+        ),
+        subscription,
+    );
+    if forward_messages(messages, &mut sink, &mut incoming).await {
+        let _ = sink.send(close_message(&disconnection)).await;
+    }
+}
+
+/// Sends the messages to the client until they run out, returning whether the connection should
+/// then be closed: `false` if the client went away first.
+async fn forward_messages(
+    messages: impl Stream<Item = Message>,
+    sink: &mut (impl Sink<Message> + Unpin),
+    incoming: &mut (impl Stream<Item = Result<Message, warp::Error>> + Unpin),
+) -> bool {
+    futures::pin_mut!(messages);
+    loop {
+        tokio::select! {
+            maybe_message = messages.next() => match maybe_message {
+                Some(message) => {
+                    if sink.send(message).await.is_err() {
+                        return false;
+                    }
+                }
+                None => return true,
+            },
+            maybe_incoming = incoming.next() => {
+                // Further messages of the client are ignored, until it closes the connection.
+                if !matches!(maybe_incoming, Some(Ok(ref message)) if !message.is_close()) {
+                    return false;
+                }
+            }
+        }
+    }
+}
+
+/// The message closing the connection: with the reason of the disconnection of a slow subscriber,
+/// or as going away when the server shuts down.
+fn close_message(disconnection: &Mutex<Option<Disconnection>>) -> Message {
+    let maybe_disconnection = disconnection
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .take();
+    match maybe_disconnection {
+        Some(disconnection) => Message::close_with(CLOSE_POLICY_VIOLATION, disconnection.reason()),
+        None => Message::close_with(CLOSE_GOING_AWAY, "server shutting down"),
+    }
+}
+
+/// Reads the first text message of the client, skipping pings and pongs.
+async fn read_resumption(
+    incoming: &mut (impl Stream<Item = Result<Message, warp::Error>> + Unpin),
+) -> Result<Resumption, String> {
+    while let Some(result) = incoming.next().await {
+        let message = result.map_err(|error| error.to_string())?;
+        if message.is_ping() || message.is_pong() {
+            continue;
+        }
+        return message
+            .to_str()
+            .ok()
+            .and_then(|text| serde_json::from_str(text).ok())
+            .ok_or_else(|| {
+                "invalid resumption message: expected '{\"last_event_id\": <EVENT ID>}' or '{}'"
+                    .to_string()
+            });
+    }
+    Err("connection closed before the resumption message".to_string())
+}
+
+/// Turns the events the client subscribed to into text messages holding their ID and data.
+fn messages_to_client(
+    events: impl Stream<Item = Result<ServerSentEvent, RecvError>> + Send + 'static,
+    subscription: Subscription,
+) -> impl Stream<Item = Message> + Send + 'static {
+    events
+        .take_while(|result| futures::future::ready(result.is_ok()))
+        .filter_map(move |result| {
+            let maybe_message = result.ok().and_then(|event| {
+                if !subscription.restrictions.permits(&event.data)
+                    || !subscription.selection.selects(&event.data)
+                {
+                    return None;
+                }
+                forwarded_id(
+                    &event,
+                    subscription.stream_filter,
+                    subscription.event_filter,
+                )?;
+                record_sent_event(&event, subscription.stream_filter);
+                let text = json!({ "id": event.id, "data": event_to_json(&event) }).to_string();
+                record_sent_bytes(
+                    subscription.stream_filter,
+                    event.data.type_label(),
+                    text.len(),
+                );
+                Some(Message::text(text))
+            });
+            futures::future::ready(maybe_message)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_stream_server::{
+        retained_events::RetainedEvents, sse_server::ChannelsAndFilter,
+    };
+    use casper_event_types::{sse_data::SseData, Filter as SseFilter};
+    use casper_types::{testing::TestRng, ProtocolVersion};
+    use serde_json::Value;
+
+    fn channels_and_filter() -> ChannelsAndFilter {
+        let policy = SubscriberPolicy {
+            max_concurrent_subscribers: 10,
+            maybe_jwt_validator: None,
+            maybe_slow_subscriber_timeout: None,
+            maybe_priority_classes: None,
+        };
+        ChannelsAndFilter::new(10, policy, RetainedEvents::new(10), false, None)
+    }
+
+    fn block_added(rng: &mut TestRng, id: Id) -> ServerSentEvent {
+        ServerSentEvent {
+            id: Some(id),
+            data: SseData::random_block_added(rng),
+            json_data: None,
+            inbound_filter: Some(SseFilter::Main),
+            watched: false,
+            broadcast_at: None,
+        }
+    }
+
+    async fn connect(path: &str, sse_filter: BoxedFilter<(Response,)>) -> warp::test::WsClient {
+        warp::test::ws()
+            .path(path)
+            .handshake(sse_filter)
+            .await
+            .unwrap()
+    }
+
+    async fn recv_json(client: &mut warp::test::WsClient) -> Value {
+        let message = client.recv().await.unwrap();
+        serde_json::from_str(message.to_str().unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_resume_after_last_event_id() {
+        let mut rng = TestRng::new();
+        let ChannelsAndFilter {
+            event_broadcaster,
+            mut new_subscriber_info_receiver,
+            sse_filter,
+            ..
+        } = channels_and_filter();
+        let mut client = connect("/ws/events/main", sse_filter).await;
+        client.send_text(r#"{"last_event_id": 4}"#).await;
+
+        let subscriber = new_subscriber_info_receiver.recv().await.unwrap();
+        assert_eq!(subscriber.start_from, Some(5));
+        let replayed = block_added(&mut rng, 5);
+        for initial_event in [
+            ServerSentEvent::initial_event(ProtocolVersion::V1_0_0),
+            replayed.clone(),
+        ] {
+            subscriber
+                .initial_events_sender
+                .send(initial_event)
+                .unwrap();
+        }
+        drop(subscriber);
+        for ongoing in [replayed, block_added(&mut rng, 6)] {
+            event_broadcaster
+                .send(BroadcastChannelMessage::ServerSentEvent(ongoing))
+                .unwrap();
+        }
+
+        let api_version = recv_json(&mut client).await;
+        assert_eq!(api_version["id"], Value::Null);
+        assert_eq!(api_version["data"]["ApiVersion"], "1.0.0");
+        assert_eq!(recv_json(&mut client).await["id"], 5);
+        // The replayed event isn't sent again when it is also broadcast.
+        let ongoing = recv_json(&mut client).await;
+        assert_eq!(ongoing["id"], 6);
+        assert!(ongoing["data"]["BlockAdded"].is_object());
+
+        event_broadcaster
+            .send(BroadcastChannelMessage::Shutdown)
+            .unwrap();
+        assert!(client.recv().await.unwrap().is_close());
+    }
+
+    #[tokio::test]
+    async fn should_reject_invalid_resumption_message() {
+        let ChannelsAndFilter {
+            mut new_subscriber_info_receiver,
+            sse_filter,
+            ..
+        } = channels_and_filter();
+        let mut client = connect("/ws/events", sse_filter).await;
+        client.send_text(r#"{"start_from": 4}"#).await;
+
+        let close = client.recv().await.unwrap();
+        assert!(close.is_close());
+        assert_eq!(close.close_frame().unwrap().0, CLOSE_POLICY_VIOLATION);
+        assert!(new_subscriber_info_receiver.try_recv().is_err());
+    }
+}
@@ -0,0 +1,438 @@
+//! Export of the stored events in a format BigQuery can load.
+//!
+//! `export` writes the raw payloads of every event table as newline-delimited JSON files,
+//! partitioned by the UTC date the events were stored on in Hive style, e.g.
+//! `BlockAdded/dt=2024-05-01/part-00000000000000000042.ndjson`, along with the BigQuery schema of
+//! each table and a `manifest.json` listing the files the export wrote. The files can thus be
+//! copied to Google Cloud Storage and loaded with the hive partitioning of BigQuery.
+//!
+//! In incremental mode, the export of each table resumes after the last event listed in the
+//! manifest of the previous export, so only the files of the events stored since are written. The
+//! manifest is replaced once all tables are exported; an interrupted export writes the same files
+//! again when rerun.
+
+use crate::{
+    build_database,
+    types::{
+        config::Config,
+        database::{Database, DatabaseReader, PayloadTable, RawPayloadEntry},
+    },
+};
+use anyhow::{Context, Error};
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, value::RawValue, Value};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::info;
+
+/// The number of events read from the database at once.
+const EXPORT_PAGE_SIZE: u32 = 1000;
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+const SCHEMA_FILE_NAME: &str = "schema.json";
+
+/// Options of `export`.
+#[derive(Args, Clone, Debug)]
+pub(crate) struct ExportOptions {
+    /// Directory to write the export to.
+    #[arg(long, value_name = "DIR")]
+    pub(crate) output: PathBuf,
+    /// Only export the events stored since the export listed in the manifest of the output
+    /// directory.
+    #[arg(long)]
+    pub(crate) incremental: bool,
+    /// Maximum number of events per data file.
+    #[arg(long, default_value_t = 100_000, value_parser = clap::value_parser!(u32).range(1..))]
+    pub(crate) rows_per_file: u32,
+}
+
+/// The files written by an export, and up to which event each table was exported.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct Manifest {
+    /// Seconds since the UNIX epoch at which the export finished.
+    exported_at: u64,
+    tables: Vec<TableExport>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct TableExport {
+    table: String,
+    /// Path of the BigQuery schema of the table, relative to the output directory.
+    schema: String,
+    /// Event log id of the last exported event of the table, 0 if none was ever exported.
+    last_event_log_id: u64,
+    /// The data files written by this export.
+    files: Vec<ExportedFile>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct ExportedFile {
+    /// Path of the file, relative to the output directory.
+    path: String,
+    /// UTC date the events of the file were stored on, formatted as `YYYY-MM-DD`.
+    partition: String,
+    rows: u64,
+    first_event_log_id: u64,
+    last_event_log_id: u64,
+}
+
+/// A row of a data file.
+#[derive(Serialize)]
+struct ExportedRow<'a> {
+    event_log_id: u64,
+    event_source_address: &'a str,
+    event_id: u64,
+    inserted_timestamp: &'a str,
+    payload: &'a RawValue,
+}
+
+/// A data file being written.
+struct PartFile {
+    writer: BufWriter<File>,
+    file: ExportedFile,
+}
+
+/// The BigQuery schema of the rows of the data files, the same for every table. The `dt` partition
+/// column is derived from the paths of the files.
+fn bigquery_schema() -> Value {
+    json!([
+        {
+            "name": "event_log_id",
+            "type": "INT64",
+            "mode": "REQUIRED",
+            "description": "Id of the event in the event log of the sidecar, increasing with the order of storage"
+        },
+        {
+            "name": "event_source_address",
+            "type": "STRING",
+            "mode": "REQUIRED",
+            "description": "Address of the node the event was received from"
+        },
+        {
+            "name": "event_id",
+            "type": "INT64",
+            "mode": "REQUIRED",
+            "description": "Id of the event on the event stream of the node"
+        },
+        {
+            "name": "inserted_timestamp",
+            "type": "TIMESTAMP",
+            "mode": "REQUIRED",
+            "description": "UTC time the event was stored at"
+        },
+        {
+            "name": "payload",
+            "type": "JSON",
+            "mode": "REQUIRED",
+            "description": "The event as received from the node"
+        }
+    ])
+}
+
+/// Exports the events stored in the database configured in `config` to the output directory of
+/// `options`.
+pub(crate) async fn run_export(config: Config, options: ExportOptions) -> Result<(), Error> {
+    let manifest = match build_database(&config.storage).await? {
+        Database::SqliteDatabaseWrapper(db) => export(&db, &options).await?,
+        #[cfg(feature = "postgres")]
+        Database::PostgreSqlDatabaseWrapper(db) => export(&db, &options).await?,
+    };
+    for table in &manifest.tables {
+        info!(
+            "Exported {} event(s) of {} in {} file(s), up to event log id {}",
+            table.files.iter().map(|file| file.rows).sum::<u64>(),
+            table.table,
+            table.files.len(),
+            table.last_event_log_id
+        );
+    }
+    info!("Export written to {}", options.output.display());
+    Ok(())
+}
+
+async fn export<Db: DatabaseReader + Sync>(
+    db: &Db,
+    options: &ExportOptions,
+) -> Result<Manifest, Error> {
+    let manifest_path = options.output.join(MANIFEST_FILE_NAME);
+    let exported_up_to: HashMap<String, u64> = if options.incremental && manifest_path.exists() {
+        let previous: Manifest = serde_json::from_slice(&fs::read(&manifest_path)?)
+            .with_context(|| format!("Error parsing {}", manifest_path.display()))?;
+        previous
+            .tables
+            .into_iter()
+            .map(|table| (table.table, table.last_event_log_id))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+    let mut manifest = Manifest::default();
+    for table in PayloadTable::ALL {
+        let after = exported_up_to.get(table.table_name()).copied().unwrap_or(0);
+        manifest
+            .tables
+            .push(export_table(db, table, after, options).await?);
+    }
+    manifest.exported_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs();
+    // The manifest is replaced at once so that it never lists partially written files.
+    let temporary_path = options.output.join(format!("{}.tmp", MANIFEST_FILE_NAME));
+    fs::write(&temporary_path, serde_json::to_vec_pretty(&manifest)?)?;
+    fs::rename(&temporary_path, &manifest_path)
+        .with_context(|| format!("Error writing {}", manifest_path.display()))?;
+    Ok(manifest)
+}
+
+async fn export_table<Db: DatabaseReader + Sync>(
+    db: &Db,
+    table: PayloadTable,
+    after: u64,
+    options: &ExportOptions,
+) -> Result<TableExport, Error> {
+    let table_name = table.table_name();
+    write_schema(&options.output, table_name)?;
+    let mut export = TableExport {
+        table: table_name.to_string(),
+        schema: format!("{}/{}", table_name, SCHEMA_FILE_NAME),
+        last_event_log_id: after,
+        files: Vec::new(),
+    };
+    let mut maybe_part: Option<PartFile> = None;
+    loop {
+        let page = db
+            .get_raw_payloads_after(table, export.last_event_log_id, EXPORT_PAGE_SIZE)
+            .await
+            .map_err(|error| {
+                Error::msg(format!(
+                    "Error reading the events of {}: {:?}",
+                    table_name, error
+                ))
+            })?;
+        for entry in &page {
+            export_row(&mut export, &mut maybe_part, entry, options)?;
+        }
+        if page.len() < EXPORT_PAGE_SIZE as usize {
+            break;
+        }
+    }
+    if let Some(part) = maybe_part {
+        export.files.push(finish(part)?);
+    }
+    Ok(export)
+}
+
+fn write_schema(output: &Path, table_name: &str) -> Result<(), Error> {
+    let table_dir = output.join(table_name);
+    fs::create_dir_all(&table_dir)
+        .with_context(|| format!("Error creating {}", table_dir.display()))?;
+    fs::write(
+        table_dir.join(SCHEMA_FILE_NAME),
+        serde_json::to_vec_pretty(&bigquery_schema())?,
+    )?;
+    Ok(())
+}
+
+/// Writes the row of the event to the current data file of the table, first starting a new one if
+/// the current one is full or of another partition.
+fn export_row(
+    export: &mut TableExport,
+    maybe_part: &mut Option<PartFile>,
+    entry: &RawPayloadEntry,
+    options: &ExportOptions,
+) -> Result<(), Error> {
+    let partition = partition_of(entry)?;
+    let is_full = |part: &PartFile| {
+        part.file.partition != partition || part.file.rows >= u64::from(options.rows_per_file)
+    };
+    if maybe_part.as_ref().map_or(true, is_full) {
+        if let Some(part) = maybe_part.take() {
+            export.files.push(finish(part)?);
+        }
+        *maybe_part = Some(create_part(
+            &options.output,
+            &export.table,
+            partition,
+            entry,
+        )?);
+    }
+    if let Some(part) = maybe_part.as_mut() {
+        write_row(part, entry)?;
+    }
+    export.last_event_log_id = entry.event_log_id;
+    Ok(())
+}
+
+/// Returns the `YYYY-MM-DD` date the event was stored on.
+fn partition_of(entry: &RawPayloadEntry) -> Result<&str, Error> {
+    let date = entry.inserted_timestamp.get(..10).unwrap_or_default();
+    let bytes = date.as_bytes();
+    if bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+        return Err(Error::msg(format!(
+            "Unexpected timestamp of the event with event log id {}: {}",
+            entry.event_log_id, entry.inserted_timestamp
+        )));
+    }
+    Ok(date)
+}
+
+fn create_part(
+    output: &Path,
+    table_name: &str,
+    partition: &str,
+    first_entry: &RawPayloadEntry,
+) -> Result<PartFile, Error> {
+    let path = format!(
+        "{}/dt={}/part-{:020}.ndjson",
+        table_name, partition, first_entry.event_log_id
+    );
+    let full_path = output.join(&path);
+    if let Some(dir) = full_path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let file = File::create(&full_path).with_context(|| format!("Error creating {}", path))?;
+    Ok(PartFile {
+        writer: BufWriter::new(file),
+        file: ExportedFile {
+            path,
+            partition: partition.to_string(),
+            rows: 0,
+            first_event_log_id: first_entry.event_log_id,
+            last_event_log_id: first_entry.event_log_id,
+        },
+    })
+}
+
+fn write_row(part: &mut PartFile, entry: &RawPayloadEntry) -> Result<(), Error> {
+    let payload: &RawValue = serde_json::from_str(&entry.raw).with_context(|| {
+        format!(
+            "Invalid payload of the event with event log id {}",
+            entry.event_log_id
+        )
+    })?;
+    let row = ExportedRow {
+        event_log_id: entry.event_log_id,
+        event_source_address: &entry.event_source_address,
+        event_id: entry.event_id,
+        inserted_timestamp: &entry.inserted_timestamp,
+        payload,
+    };
+    serde_json::to_writer(&mut part.writer, &row)?;
+    part.writer.write_all(b"\n")?;
+    part.file.rows += 1;
+    part.file.last_event_log_id = entry.event_log_id;
+    Ok(())
+}
+
+fn finish(mut part: PartFile) -> Result<ExportedFile, Error> {
+    part.writer
+        .flush()
+        .with_context(|| format!("Error writing {}", part.file.path))?;
+    Ok(part.file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        database::sqlite_database::SqliteDatabase,
+        types::{database::DatabaseWriter, sse_events::BlockAdded},
+    };
+    use casper_types::testing::TestRng;
+
+    async fn save_blocks(db: &SqliteDatabase, rng: &mut TestRng, count: u32) {
+        for event_id in 0..count {
+            db.save_block_added(BlockAdded::random(rng), event_id, "127.0.0.1".to_string())
+                .await
+                .expect("Error saving block_added");
+        }
+    }
+
+    fn options(output: &Path) -> ExportOptions {
+        ExportOptions {
+            output: output.to_path_buf(),
+            incremental: true,
+            rows_per_file: 2,
+        }
+    }
+
+    #[tokio::test]
+    async fn should_export_events_in_files_of_limited_rows() {
+        let mut rng = TestRng::new();
+        let db = SqliteDatabase::new_in_memory(10)
+            .await
+            .expect("Error opening database in memory");
+        let output = tempfile::tempdir().expect("Error creating temporary directory");
+        save_blocks(&db, &mut rng, 3).await;
+
+        let manifest = export(&db, &options(output.path())).await.unwrap();
+
+        assert_eq!(manifest.tables.len(), PayloadTable::ALL.len());
+        let blocks = &manifest.tables[0];
+        assert_eq!(blocks.table, "BlockAdded");
+        assert_eq!(
+            blocks
+                .files
+                .iter()
+                .map(|file| file.rows)
+                .collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+        assert_eq!(blocks.last_event_log_id, blocks.files[1].last_event_log_id);
+        let data = fs::read_to_string(output.path().join(&blocks.files[0].path)).unwrap();
+        let rows: Vec<Value> = data
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1]["event_id"], 1);
+        assert!(rows[1]["payload"]["block_hash"].is_string());
+        assert!(blocks.files[0]
+            .path
+            .starts_with(&format!("BlockAdded/dt={}/", blocks.files[0].partition)));
+        assert!(output.path().join("BlockAdded/schema.json").exists());
+        assert!(manifest.tables[1..]
+            .iter()
+            .all(|table| table.files.is_empty() && table.last_event_log_id == 0));
+    }
+
+    #[tokio::test]
+    async fn should_export_only_new_events_incrementally() {
+        let mut rng = TestRng::new();
+        let db = SqliteDatabase::new_in_memory(10)
+            .await
+            .expect("Error opening database in memory");
+        let output = tempfile::tempdir().expect("Error creating temporary directory");
+        let mut options = options(output.path());
+        save_blocks(&db, &mut rng, 3).await;
+        export(&db, &options).await.unwrap();
+
+        save_blocks(&db, &mut rng, 1).await;
+        let manifest = export(&db, &options).await.unwrap();
+
+        let blocks = &manifest.tables[0];
+        assert_eq!(blocks.files.len(), 1);
+        assert_eq!(blocks.files[0].rows, 1);
+        let written: Manifest =
+            serde_json::from_slice(&fs::read(output.path().join(MANIFEST_FILE_NAME)).unwrap())
+                .unwrap();
+        assert_eq!(written, manifest);
+
+        options.incremental = false;
+        let manifest = export(&db, &options).await.unwrap();
+        assert_eq!(
+            manifest.tables[0]
+                .files
+                .iter()
+                .map(|file| file.rows)
+                .sum::<u64>(),
+            4
+        );
+    }
+}
@@ -0,0 +1,199 @@
+//! Delivery of the broadcast events to the external sinks, Kafka and the webhooks, apart from their
+//! broadcasting to the subscribers of the event stream server.
+//!
+//! Every sink has its own delivery path: a queue of `sink_queue_size` events and a task handing
+//! them to the sink, so that a slow sink neither delays the other sinks nor the local clients by
+//! default. The `priority` of the `[delivery]` config decides which path gives way when a sink is
+//! saturated:
+//! * `local_clients`: the events a sink has no room for are dropped, and the events are broadcast
+//!   without waiting for the sinks,
+//! * `external_sinks`: the sinks wait for room in their own queues and the broadcasting of events
+//!   waits for room in the queues of the sinks, so the events reach the local clients late rather
+//!   than not reaching the sinks.
+
+#[cfg(feature = "kafka")]
+use crate::kafka_sink::KafkaSink;
+use crate::types::config::{DeliveryConfig, DeliveryPriority};
+#[cfg(feature = "webhooks")]
+use crate::webhooks::Webhooks;
+use casper_event_types::{metrics::SINK_EVENTS_DROPPED, sse_data::SseData};
+use tokio::sync::mpsc::{channel, error::TrySendError, Sender};
+
+type SinkEvent = (SseData, Option<String>);
+
+enum Sink {
+    #[cfg(feature = "kafka")]
+    Kafka(KafkaSink),
+    #[cfg(feature = "webhooks")]
+    Webhooks(Webhooks),
+}
+
+impl Sink {
+    /// The name of the sink in the metrics.
+    fn name(&self) -> &'static str {
+        match *self {
+            #[cfg(feature = "kafka")]
+            Sink::Kafka(_) => "kafka",
+            #[cfg(feature = "webhooks")]
+            Sink::Webhooks(_) => "webhooks",
+        }
+    }
+
+    // Without any sink compiled in there's no sink to deliver to.
+    #[cfg_attr(
+        not(any(feature = "kafka", feature = "webhooks")),
+        allow(unused_variables)
+    )]
+    async fn deliver(&self, sse_data: &SseData, maybe_json_data: Option<&str>, wait: bool) {
+        match *self {
+            #[cfg(feature = "kafka")]
+            Sink::Kafka(ref kafka_sink) if wait => {
+                kafka_sink.submit_waiting(sse_data, maybe_json_data).await
+            }
+            #[cfg(feature = "kafka")]
+            Sink::Kafka(ref kafka_sink) => kafka_sink.submit(sse_data, maybe_json_data),
+            #[cfg(feature = "webhooks")]
+            Sink::Webhooks(ref webhooks) if wait => {
+                webhooks.submit_waiting(sse_data, maybe_json_data).await
+            }
+            #[cfg(feature = "webhooks")]
+            Sink::Webhooks(ref webhooks) => webhooks.submit(sse_data, maybe_json_data),
+        }
+    }
+}
+
+struct SinkPath {
+    name: &'static str,
+    sender: Sender<SinkEvent>,
+}
+
+/// Handle used to submit broadcast events to the external sinks. Does nothing unless started.
+#[derive(Default)]
+pub(crate) struct ExternalSinks {
+    priority: DeliveryPriority,
+    paths: Vec<SinkPath>,
+}
+
+impl ExternalSinks {
+    /// Spawns a task per configured sink.
+    pub(crate) fn start(
+        config: &DeliveryConfig,
+        #[cfg(feature = "kafka")] maybe_kafka_sink: Option<KafkaSink>,
+        #[cfg(feature = "webhooks")] webhooks: Webhooks,
+    ) -> Self {
+        #[cfg_attr(not(any(feature = "kafka", feature = "webhooks")), allow(unused_mut))]
+        let mut sinks: Vec<Sink> = Vec::new();
+        #[cfg(feature = "kafka")]
+        sinks.extend(maybe_kafka_sink.map(Sink::Kafka));
+        #[cfg(feature = "webhooks")]
+        if !webhooks.is_empty() {
+            sinks.push(Sink::Webhooks(webhooks));
+        }
+        let wait = config.priority == DeliveryPriority::ExternalSinks;
+        let paths = sinks
+            .into_iter()
+            .map(|sink| {
+                let (sender, mut receiver) = channel::<SinkEvent>(config.sink_queue_size());
+                let name = sink.name();
+                tokio::spawn(async move {
+                    while let Some((sse_data, maybe_json_data)) = receiver.recv().await {
+                        sink.deliver(&sse_data, maybe_json_data.as_deref(), wait)
+                            .await;
+                    }
+                });
+                SinkPath { name, sender }
+            })
+            .collect();
+        ExternalSinks {
+            priority: config.priority,
+            paths,
+        }
+    }
+
+    /// Queues a broadcast event for every sink. Waits for the queues to have capacity only if the
+    /// external sinks have priority.
+    pub(crate) async fn submit(&self, sse_data: &SseData, maybe_json_data: Option<&str>) {
+        for path in &self.paths {
+            let event = (sse_data.clone(), maybe_json_data.map(str::to_string));
+            match self.priority {
+                DeliveryPriority::LocalClients => {
+                    if let Err(TrySendError::Full(_)) = path.sender.try_send(event) {
+                        SINK_EVENTS_DROPPED.with_label_values(&[path.name]).inc();
+                    }
+                }
+                DeliveryPriority::ExternalSinks => {
+                    let _ = path.sender.send(event).await;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use casper_types::testing::TestRng;
+    use std::time::Duration;
+    use tokio::sync::mpsc::Receiver;
+
+    fn saturated_sinks(priority: DeliveryPriority) -> (ExternalSinks, Receiver<SinkEvent>) {
+        let (sender, receiver) = channel(1);
+        sender
+            .try_send((SseData::Shutdown, None))
+            .expect("queue should have room");
+        let external_sinks = ExternalSinks {
+            priority,
+            paths: vec![SinkPath {
+                name: "webhooks",
+                sender,
+            }],
+        };
+        (external_sinks, receiver)
+    }
+
+    #[tokio::test]
+    async fn should_drop_events_without_waiting_when_local_clients_have_priority() {
+        let (external_sinks, mut receiver) = saturated_sinks(DeliveryPriority::LocalClients);
+        let dropped_before = SINK_EVENTS_DROPPED.with_label_values(&["webhooks"]).get();
+        let block_added = SseData::random_block_added(&mut TestRng::new());
+
+        tokio::time::timeout(
+            Duration::from_secs(1),
+            external_sinks.submit(&block_added, None),
+        )
+        .await
+        .expect("submit should not wait");
+
+        assert!(SINK_EVENTS_DROPPED.with_label_values(&["webhooks"]).get() > dropped_before);
+        assert!(matches!(
+            receiver.recv().await,
+            Some((SseData::Shutdown, None))
+        ));
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn should_wait_for_room_when_external_sinks_have_priority() {
+        let (external_sinks, mut receiver) = saturated_sinks(DeliveryPriority::ExternalSinks);
+        let block_added = SseData::random_block_added(&mut TestRng::new());
+
+        let submit = external_sinks.submit(&block_added, None);
+        tokio::pin!(submit);
+        assert!(
+            tokio::time::timeout(Duration::from_millis(100), &mut submit)
+                .await
+                .is_err(),
+            "submit should wait for the queue to have room"
+        );
+        assert!(matches!(
+            receiver.recv().await,
+            Some((SseData::Shutdown, None))
+        ));
+        submit.await;
+
+        assert!(matches!(
+            receiver.recv().await,
+            Some((SseData::BlockAdded { .. }, None))
+        ));
+    }
+}
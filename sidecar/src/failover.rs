@@ -0,0 +1,169 @@
+//! Failover between the configured nodes.
+//!
+//! By default the events of every configured node are streamed at once. If any connection has a
+//! priority, the events are streamed from a single node at a time instead. The nodes are ranked by
+//! their score when source scoring is configured: the highest head first, then the lowest
+//! `priority`, connections without one coming after those with one, then the lowest latency and
+//! finally the order they're configured in. Nodes which didn't return their status come after
+//! those which did, in the order of their priority. Once the active node is lost, i.e. its event
+//! listener ran out of connection attempts, the sidecar fails over to the best ranked node among
+//! those not lost since events were last ingested, ranked by their latest scores. It stops once
+//! all of them were lost in a row.
+//!
+//! The event IDs of a node are unrelated to those of another, so the event stream of the node
+//! failed over to is read from its start. The events already stored are recognised by their key,
+//! e.g. the hash of a block, and are neither stored nor broadcast again.
+
+use crate::{
+    operational_events::{OperationalEventKind, OperationalEvents},
+    source_scoring::{node_label, SourceScores, SourceStatus},
+    types::config::Connection,
+};
+use casper_event_listener::EventListener;
+use std::{cmp::Reverse, collections::HashSet};
+use tracing::{error, info, warn};
+
+pub(crate) fn is_failover_enabled(connections: &[Connection]) -> bool {
+    connections
+        .iter()
+        .any(|connection| connection.priority.is_some())
+}
+
+/// Indices of the connections, from the best ranked to the worst, `statuses` being their latest
+/// scores in the order of the connections.
+fn failover_order(connections: &[Connection], statuses: &[SourceStatus]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..connections.len()).collect();
+    order.sort_by_key(|index| {
+        let maybe_status = statuses.get(*index);
+        let head_height = maybe_status.and_then(|status| status.head_height);
+        let latency = maybe_status.and_then(|status| status.latency);
+        let priority = connections[*index].priority;
+        (
+            head_height.is_none(),
+            Reverse(head_height),
+            priority.is_none(),
+            priority,
+            latency,
+        )
+    });
+    order
+}
+
+/// Returns the index of the best ranked connection which isn't `lost`.
+fn next_source(order: &[usize], lost: &HashSet<usize>) -> Option<usize> {
+    order.iter().copied().find(|index| !lost.contains(index))
+}
+
+/// Streams the events of one node of `connections` at a time, failing over to another once it's
+/// lost. `event_listeners` are given in the order of the connections.
+pub(crate) async fn stream_with_failover(
+    connections: Vec<Connection>,
+    mut event_listeners: Vec<EventListener>,
+    source_scores: SourceScores,
+    operational_events: OperationalEvents,
+) {
+    let last_event_at = |index: usize| {
+        source_scores
+            .statuses()
+            .get(index)
+            .and_then(|status| status.last_event_at)
+    };
+    let mut lost = HashSet::new();
+    let mut maybe_previous = None;
+    while let Some(index) = next_source(
+        &failover_order(&connections, &source_scores.statuses()),
+        &lost,
+    ) {
+        let node = node_label(&connections[index]);
+        match maybe_previous {
+            Some(previous) => {
+                let previous_node = node_label(&connections[previous]);
+                warn!("Failing over from {} to {}", previous_node, node);
+                operational_events.record(
+                    OperationalEventKind::Failover,
+                    Some(format!("failed over from {} to {}", previous_node, node)),
+                );
+            }
+            None => info!("Streaming the events of {}", node),
+        }
+        let maybe_last_event_at = last_event_at(index);
+        source_scores.set_active(index, true);
+        if let Err(error) = event_listeners[index].stream_aggregated_events().await {
+            error!("Disconnected from {}. Reason: {}", node, error);
+        }
+        source_scores.set_active(index, false);
+        if last_event_at(index) != maybe_last_event_at {
+            // Events were ingested from the node, the nodes lost before may be available again.
+            lost.clear();
+        }
+        lost.insert(index);
+        maybe_previous = Some(index);
+    }
+    error!("Lost all the nodes to fail over to");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::testing_config::TestingConfig;
+    use std::time::Duration;
+
+    fn connections_with_priorities(priorities: &[Option<u32>]) -> Vec<Connection> {
+        let mut testing_config = TestingConfig::default();
+        testing_config.add_connection(None, None, None);
+        let connection = testing_config.config.connections.remove(0);
+        priorities
+            .iter()
+            .map(|priority| Connection {
+                priority: *priority,
+                ..connection.clone()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_order_connections_by_priority_then_configured_order() {
+        let connections = connections_with_priorities(&[None, Some(2), None, Some(1), Some(2)]);
+
+        assert!(is_failover_enabled(&connections));
+        assert_eq!(failover_order(&connections, &[]), vec![3, 1, 4, 0, 2]);
+        assert!(!is_failover_enabled(&connections_with_priorities(&[
+            None, None
+        ])));
+    }
+
+    fn status(head_height: Option<u64>, latency_in_milliseconds: u64) -> SourceStatus {
+        let mut status = SourceStatus::new("127.0.0.1:9999".to_string(), Duration::from_secs(1));
+        status.head_height = head_height;
+        status.latency = head_height.map(|_| Duration::from_millis(latency_in_milliseconds));
+        status
+    }
+
+    #[test]
+    fn should_order_connections_by_score_then_priority() {
+        let connections = connections_with_priorities(&[Some(1), Some(2), Some(3), None, Some(4)]);
+        let statuses = vec![
+            status(Some(100), 10),
+            status(Some(105), 50),
+            status(Some(105), 5),
+            status(Some(105), 1),
+            status(None, 0),
+        ];
+
+        assert_eq!(failover_order(&connections, &statuses), vec![1, 2, 3, 0, 4]);
+    }
+
+    #[test]
+    fn should_fail_over_to_highest_priority_node_not_lost() {
+        let order = vec![2, 0, 1];
+        let mut lost = HashSet::new();
+
+        assert_eq!(next_source(&order, &lost), Some(2));
+        lost.insert(2);
+        assert_eq!(next_source(&order, &lost), Some(0));
+        lost.insert(0);
+        assert_eq!(next_source(&order, &lost), Some(1));
+        lost.insert(1);
+        assert_eq!(next_source(&order, &lost), None);
+    }
+}
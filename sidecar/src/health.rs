@@ -0,0 +1,139 @@
+//! Health of the sidecar as reported by the `health` endpoint of the admin server.
+//!
+//! Components which detect that they can no longer keep up, e.g. a saturated database pool, mark
+//! themselves as degraded with a reason and clear the mark once they have recovered. The sidecar is
+//! healthy as long as no component is degraded.
+//!
+//! Readiness, reported by the `ready` endpoint, is tracked the same way: components holding the
+//! sidecar back from serving up to date data, e.g. a backlog of events waiting to be ingested, mark
+//! it as not ready until they have caught up.
+
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, RwLock},
+};
+
+/// Overall status of the sidecar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Healthy,
+    Degraded,
+}
+
+/// Snapshot of the health of the sidecar.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    /// Reasons of the degradation, keyed by the name of the degraded component.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub degraded: BTreeMap<String, String>,
+}
+
+/// Snapshot of the readiness of the sidecar to serve traffic.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ReadinessReport {
+    pub ready: bool,
+    /// Reasons the sidecar isn't ready, keyed by the name of the component holding it back.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub not_ready: BTreeMap<String, String>,
+}
+
+/// Shared handle to the health of the sidecar.
+#[derive(Clone, Debug, Default)]
+pub struct Health {
+    degraded: Arc<RwLock<BTreeMap<String, String>>>,
+    not_ready: Arc<RwLock<BTreeMap<String, String>>>,
+}
+
+impl Health {
+    pub fn new() -> Self {
+        Health::default()
+    }
+
+    /// Marks `component` as degraded for the given `reason`, replacing any previous reason.
+    pub fn set_degraded(&self, component: &str, reason: String) {
+        self.degraded
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(component.to_string(), reason);
+    }
+
+    /// Marks `component` as healthy again.
+    pub fn clear(&self, component: &str) {
+        self.degraded
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(component);
+    }
+
+    pub fn report(&self) -> HealthReport {
+        let degraded = self
+            .degraded
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+        let status = if degraded.is_empty() {
+            HealthStatus::Healthy
+        } else {
+            HealthStatus::Degraded
+        };
+        HealthReport { status, degraded }
+    }
+
+    /// Marks the sidecar as not ready because of `component` for the given `reason`, replacing
+    /// any previous reason.
+    pub fn set_not_ready(&self, component: &str, reason: String) {
+        self.not_ready
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(component.to_string(), reason);
+    }
+
+    /// Marks `component` as no longer holding the sidecar back.
+    pub fn set_ready(&self, component: &str) {
+        self.not_ready
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(component);
+    }
+
+    pub fn readiness(&self) -> ReadinessReport {
+        let not_ready = self
+            .not_ready
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+        ReadinessReport {
+            ready: not_ready.is_empty(),
+            not_ready,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_be_degraded_until_all_components_recovered() {
+        let health = Health::new();
+        assert_eq!(health.report().status, HealthStatus::Healthy);
+
+        health.set_degraded("database_pool", "all connections in use".to_string());
+        health.set_degraded("other", "lagging".to_string());
+        let report = health.report();
+        assert_eq!(report.status, HealthStatus::Degraded);
+        assert_eq!(
+            report.degraded.get("database_pool"),
+            Some(&"all connections in use".to_string())
+        );
+
+        health.clear("database_pool");
+        assert_eq!(health.report().status, HealthStatus::Degraded);
+        health.clear("other");
+        assert_eq!(health.report().status, HealthStatus::Healthy);
+        assert!(health.report().degraded.is_empty());
+    }
+}
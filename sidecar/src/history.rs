@@ -0,0 +1,139 @@
+//! Time-travel queries: answers about state derived from the stored events, e.g. the validator set
+//! of an era, as of a given block height rather than as of the latest stored block. Only the
+//! stored events are taken into account, so the history preceding the first stored block is
+//! unknown.
+
+use crate::types::{
+    database::{DatabaseReadError, DatabaseReader},
+    sse_events::BlockAdded,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// The point in the history of the chain a query is answered as of: the block at the queried
+/// height.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct AsOf {
+    pub(crate) height: u64,
+    pub(crate) era_id: u64,
+    pub(crate) is_switch_block: bool,
+    /// Milliseconds since the UNIX epoch at which the block was proposed.
+    pub(crate) timestamp: u64,
+}
+
+impl AsOf {
+    pub(crate) fn of_block(block: &BlockAdded) -> Self {
+        AsOf {
+            height: block.get_height(),
+            era_id: block.get_era_id(),
+            is_switch_block: block.is_switch_block(),
+            timestamp: block.get_timestamp().millis(),
+        }
+    }
+
+    /// Whether the switch block of `era_id` is at or below the height, i.e. whether the era had
+    /// ended.
+    pub(crate) fn has_ended(&self, era_id: u64) -> bool {
+        self.era_id > era_id || (self.era_id == era_id && self.is_switch_block)
+    }
+}
+
+/// Returns the point in history of the stored block at `height`, or of the latest stored block if
+/// no height is given.
+pub(crate) async fn as_of_height<Db: DatabaseReader + Sync>(
+    db: &Db,
+    maybe_height: Option<u64>,
+) -> Result<AsOf, DatabaseReadError> {
+    let block = match maybe_height {
+        Some(height) => db.get_block_by_height(height).await?,
+        None => db.get_latest_block().await?,
+    };
+    Ok(AsOf::of_block(&block))
+}
+
+/// The validators of the era of a block.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct ValidatorSet {
+    /// Height of the block the validator set is the one of.
+    pub(crate) height: u64,
+    pub(crate) era_id: u64,
+    /// Hex-encoded public keys of the validators, ordered.
+    pub(crate) validators: Vec<String>,
+}
+
+/// Returns the validators of the era of the stored block at `height`, or of the latest stored
+/// block if no height is given. They are known once the switch block of the previous era is
+/// stored.
+pub(crate) async fn get_validator_set<Db: DatabaseReader + Sync>(
+    db: &Db,
+    maybe_height: Option<u64>,
+) -> Result<ValidatorSet, DatabaseReadError> {
+    let as_of = as_of_height(db, maybe_height).await?;
+    let validators = db.get_era_validators(as_of.era_id).await?;
+    Ok(ValidatorSet {
+        height: as_of.height,
+        era_id: as_of.era_id,
+        validators,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::fake_database::FakeDatabase, types::database::DatabaseWriter};
+    use casper_types::{testing::TestRng, AsymmetricType, PublicKey};
+
+    #[test]
+    fn an_era_should_have_ended_once_its_switch_block_is_reached() {
+        let as_of = AsOf {
+            height: 100,
+            era_id: 5,
+            is_switch_block: false,
+            timestamp: 0,
+        };
+        assert!(as_of.has_ended(4));
+        assert!(!as_of.has_ended(5));
+
+        let as_of_switch_block = AsOf {
+            is_switch_block: true,
+            ..as_of
+        };
+        assert!(as_of_switch_block.has_ended(5));
+        assert!(!as_of_switch_block.has_ended(6));
+    }
+
+    #[tokio::test]
+    async fn should_return_the_validators_of_the_era_of_the_block_at_height() {
+        let database = FakeDatabase::new();
+        let mut rng = TestRng::new();
+        let validator = PublicKey::random(&mut rng);
+        let switch_block = BlockAdded::random_switch_block(&mut rng, 4, vec![validator.clone()]);
+        database
+            .save_block_added(switch_block.clone(), 1, "127.0.0.1".to_string())
+            .await
+            .expect("Error saving BlockAdded");
+
+        assert!(matches!(
+            get_validator_set(&database, Some(switch_block.get_height())).await,
+            Err(DatabaseReadError::NotFound)
+        ));
+
+        let block_of_next_era = BlockAdded::random_switch_block(&mut rng, 5, Vec::new());
+        database
+            .save_block_added(block_of_next_era.clone(), 2, "127.0.0.1".to_string())
+            .await
+            .expect("Error saving BlockAdded");
+
+        let validator_set = get_validator_set(&database, Some(block_of_next_era.get_height()))
+            .await
+            .expect("Error getting the validator set");
+        assert_eq!(
+            validator_set,
+            ValidatorSet {
+                height: block_of_next_era.get_height(),
+                era_id: 5,
+                validators: vec![validator.to_hex()],
+            }
+        );
+    }
+}
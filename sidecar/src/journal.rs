@@ -0,0 +1,483 @@
+//! Write-ahead journal of the ingested events.
+//!
+//! Every event received from the connected nodes is appended to a segment file and synced to disk
+//! before it is handed to the event processors storing it. Entries are a [CapturedFrame] numbered
+//! with a sequence, one per line. The journal tracks which of its events were stored, and
+//! periodically persists the sequence up to which all of them were as a checkpoint. Events whose
+//! storing failed are handed to the processors again after a while, and those past the checkpoint
+//! after a restart, so that a stalled or failing database doesn't lose any event. Segments are
+//! deleted once all their events are stored.
+
+use crate::{simulation::to_sse_event, types::config::JournalConfig};
+use anyhow::{Context, Error};
+use casper_event_listener::SseEvent;
+use casper_event_types::{
+    capture::CapturedFrame, metrics::JOURNAL_UNAPPLIED_EVENTS, sse_data::SseData,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant, UNIX_EPOCH},
+};
+use tokio::sync::mpsc::{Receiver, Sender};
+use tracing::{error, info, warn};
+
+const SEGMENT_FILE_PREFIX: &str = "journal-";
+const SEGMENT_FILE_EXTENSION: &str = ".ndjson";
+const CHECKPOINT_FILE_NAME: &str = "checkpoint";
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JournalEntry {
+    sequence: u64,
+    #[serde(flatten)]
+    frame: CapturedFrame,
+}
+
+impl JournalEntry {
+    fn into_sse_event(self) -> Result<SseEvent, Error> {
+        let received_at = UNIX_EPOCH + Duration::from_millis(self.frame.received_at);
+        let mut sse_event = to_sse_event(self.frame)?;
+        sse_event.received_at = received_at;
+        sse_event.journal_sequence = Some(self.sequence);
+        Ok(sse_event)
+    }
+}
+
+/// Handle to the journal. Cloned handles share the same files.
+#[derive(Clone)]
+pub(crate) struct Journal {
+    state: Arc<Mutex<JournalState>>,
+}
+
+struct JournalState {
+    directory: PathBuf,
+    max_segment_size: u64,
+    /// First sequences of the segments, oldest first. Events are appended to the last one.
+    segments: Vec<u64>,
+    current_segment: Option<(File, u64)>,
+    next_sequence: u64,
+    /// Sequence up to which all events are stored, as persisted in the checkpoint file.
+    checkpoint: u64,
+    /// Journaled events which aren't stored yet.
+    unapplied: BTreeSet<u64>,
+    /// Unapplied events waiting to be handed to the event processors: those left over from a
+    /// previous run and those whose storing failed.
+    waiting: BTreeSet<u64>,
+}
+
+impl Journal {
+    /// Opens the journal in the directory of `config`. The events journaled by a previous run which
+    /// weren't stored are handed to the event processors once the application is started.
+    pub(crate) fn open(config: &JournalConfig) -> Result<Self, Error> {
+        let directory = PathBuf::from(&config.directory);
+        fs::create_dir_all(&directory)
+            .with_context(|| format!("Error creating journal directory {}", directory.display()))?;
+        let checkpoint = read_checkpoint(&directory)?;
+        let segments = list_segments(&directory)?;
+        let mut unapplied = BTreeSet::new();
+        let mut last_sequence = checkpoint;
+        for first_sequence in &segments {
+            for entry in read_segment(&directory, *first_sequence)? {
+                last_sequence = last_sequence.max(entry.sequence);
+                if entry.sequence > checkpoint {
+                    unapplied.insert(entry.sequence);
+                }
+            }
+        }
+        if !unapplied.is_empty() {
+            info!(
+                "{} journaled event(s) weren't stored by the previous run and are replayed",
+                unapplied.len()
+            );
+        }
+        Ok(Journal {
+            state: Arc::new(Mutex::new(JournalState {
+                directory,
+                max_segment_size: config.max_segment_size_in_bytes(),
+                segments,
+                current_segment: None,
+                next_sequence: last_sequence + 1,
+                checkpoint,
+                waiting: unapplied.clone(),
+                unapplied,
+            })),
+        })
+    }
+
+    /// Journals the events received on `inbound` and passes them on to `journaled`. Events which
+    /// can't be journaled are passed on regardless, as they can still be stored.
+    pub(crate) fn start_journaling(
+        &self,
+        mut inbound: Receiver<SseEvent>,
+        journaled: Sender<SseEvent>,
+    ) {
+        let journal = self.clone();
+        tokio::task::spawn_blocking(move || {
+            while let Some(mut sse_event) = inbound.blocking_recv() {
+                if is_journaled(&sse_event.data) {
+                    if let Err(error) = journal.append(&mut sse_event) {
+                        error!("Error journaling {}: {:?}", sse_event, error);
+                    }
+                }
+                if journaled.blocking_send(sse_event).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Hands the waiting events to `sender` every `retry_interval`, and persists the checkpoint
+    /// as the events get stored.
+    pub(crate) fn start_application(&self, sender: Sender<SseEvent>, retry_interval: Duration) {
+        let journal = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut maybe_handed_over_at: Option<Instant> = None;
+            while !sender.is_closed() {
+                if maybe_handed_over_at.map_or(true, |at| at.elapsed() >= retry_interval) {
+                    if let Err(error) = journal.hand_over_waiting(&sender) {
+                        warn!("Error reading journaled events: {:?}", error);
+                    }
+                    maybe_handed_over_at = Some(Instant::now());
+                }
+                if let Err(error) = journal.checkpoint() {
+                    warn!("Error checkpointing the journal: {:?}", error);
+                }
+                std::thread::sleep(CHECKPOINT_INTERVAL);
+            }
+        });
+    }
+
+    /// Records whether the event journaled under `sequence` was stored. Events which weren't are
+    /// handed to the event processors again.
+    pub(crate) fn acknowledge(&self, sequence: u64, stored: bool) {
+        let mut state = self.lock();
+        if stored {
+            state.unapplied.remove(&sequence);
+        } else {
+            state.waiting.insert(sequence);
+        }
+    }
+
+    fn append(&self, sse_event: &mut SseEvent) -> Result<(), Error> {
+        let mut state = self.lock();
+        let sequence = state.next_sequence;
+        let entry = JournalEntry {
+            sequence,
+            frame: to_frame(sse_event)?,
+        };
+        let mut line = serde_json::to_string(&entry).context("Error serializing journal entry")?;
+        line.push('\n');
+        state.write(sequence, line.as_bytes())?;
+        state.next_sequence += 1;
+        state.unapplied.insert(sequence);
+        sse_event.journal_sequence = Some(sequence);
+        Ok(())
+    }
+
+    /// Reads the waiting events from the segments and sends them to `sender`, oldest first.
+    fn hand_over_waiting(&self, sender: &Sender<SseEvent>) -> Result<(), Error> {
+        let (directory, segments, mut waiting) = {
+            let mut state = self.lock();
+            if state.waiting.is_empty() {
+                return Ok(());
+            }
+            let waiting = std::mem::take(&mut state.waiting);
+            (state.directory.clone(), state.segments.clone(), waiting)
+        };
+        let result = self.send_waiting(&directory, &segments, &mut waiting, sender);
+        let mut state = self.lock();
+        match result {
+            Ok(()) => {
+                for sequence in waiting {
+                    warn!(sequence, "Journaled event is missing from the segments");
+                    state.unapplied.remove(&sequence);
+                }
+            }
+            Err(_) => state.waiting.extend(waiting),
+        }
+        result
+    }
+
+    /// Sends the `waiting` events read from the segments to `sender`, removing them from `waiting`.
+    fn send_waiting(
+        &self,
+        directory: &Path,
+        segments: &[u64],
+        waiting: &mut BTreeSet<u64>,
+        sender: &Sender<SseEvent>,
+    ) -> Result<(), Error> {
+        for first_sequence in segments {
+            for entry in read_segment(directory, *first_sequence)? {
+                let sequence = entry.sequence;
+                if !waiting.remove(&sequence) {
+                    continue;
+                }
+                match entry.into_sse_event() {
+                    Ok(sse_event) => {
+                        if sender.blocking_send(sse_event).is_err() {
+                            return Err(Error::msg("The event processors stopped"));
+                        }
+                    }
+                    Err(error) => {
+                        warn!(sequence, "Skipping malformed journal entry: {:?}", error);
+                        self.acknowledge(sequence, true);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Persists the sequence up to which all events are stored, and deletes the segments whose
+    /// events all are.
+    fn checkpoint(&self) -> Result<(), Error> {
+        let mut state = self.lock();
+        JOURNAL_UNAPPLIED_EVENTS.set(state.unapplied.len() as f64);
+        let stored_up_to = state
+            .unapplied
+            .first()
+            .map_or(state.next_sequence - 1, |sequence| sequence - 1);
+        if stored_up_to == state.checkpoint {
+            return Ok(());
+        }
+        write_checkpoint(&state.directory, stored_up_to)?;
+        state.checkpoint = stored_up_to;
+        while state.segments.len() > 1 && state.segments[1] - 1 <= stored_up_to {
+            let path = state.directory.join(segment_file_name(state.segments[0]));
+            fs::remove_file(&path)
+                .with_context(|| format!("Error removing journal segment {}", path.display()))?;
+            state.segments.remove(0);
+        }
+        Ok(())
+    }
+
+    fn lock(&self) -> MutexGuard<JournalState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl JournalState {
+    fn write(&mut self, sequence: u64, line: &[u8]) -> Result<(), Error> {
+        let needs_rotation = match &self.current_segment {
+            None => true,
+            Some((_, written)) => {
+                *written > 0 && written + line.len() as u64 > self.max_segment_size
+            }
+        };
+        if needs_rotation {
+            let path = self.directory.join(segment_file_name(sequence));
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .with_context(|| format!("Error creating journal segment {}", path.display()))?;
+            self.segments.push(sequence);
+            self.current_segment = Some((file, 0));
+        }
+        if let Some((file, written)) = &mut self.current_segment {
+            file.write_all(line)
+                .context("Error writing to journal segment")?;
+            file.sync_data().context("Error syncing journal segment")?;
+            *written += line.len() as u64;
+        }
+        Ok(())
+    }
+}
+
+/// Events which don't carry chain data aren't journaled: the API version isn't stored and a
+/// replayed shutdown would be sent to the subscribers again.
+fn is_journaled(data: &SseData) -> bool {
+    !matches!(
+        data,
+        SseData::ApiVersion(_)
+            | SseData::SidecarVersion(_)
+            | SseData::Shutdown
+            | SseData::Derived { .. }
+    )
+}
+
+fn to_frame(sse_event: &SseEvent) -> Result<CapturedFrame, Error> {
+    let data = match &sse_event.json_data {
+        Some(json_data) => json_data.clone(),
+        None => serde_json::to_string(&sse_event.data).context("Error serializing event")?,
+    };
+    let received_at = sse_event
+        .received_at
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    Ok(CapturedFrame {
+        received_at,
+        source: sse_event.source.origin().ascii_serialization(),
+        filter: sse_event.inbound_filter.to_string(),
+        id: Some(sse_event.id.to_string()),
+        data,
+    })
+}
+
+fn segment_file_name(first_sequence: u64) -> String {
+    format!(
+        "{}{:020}{}",
+        SEGMENT_FILE_PREFIX, first_sequence, SEGMENT_FILE_EXTENSION
+    )
+}
+
+/// Lists the first sequences of the segments in `directory`, oldest first.
+fn list_segments(directory: &Path) -> Result<Vec<u64>, Error> {
+    let mut segments = Vec::new();
+    let entries = fs::read_dir(directory)
+        .with_context(|| format!("Error reading journal directory {}", directory.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        let maybe_first_sequence = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix(SEGMENT_FILE_PREFIX))
+            .and_then(|name| name.strip_suffix(SEGMENT_FILE_EXTENSION))
+            .and_then(|first_sequence| first_sequence.parse::<u64>().ok());
+        if let Some(first_sequence) = maybe_first_sequence {
+            segments.push(first_sequence);
+        }
+    }
+    segments.sort();
+    Ok(segments)
+}
+
+/// Reads the entries of a segment. A line torn by a crash while it was appended is skipped, as
+/// its event was never handed to the event processors.
+fn read_segment(directory: &Path, first_sequence: u64) -> Result<Vec<JournalEntry>, Error> {
+    let path = directory.join(segment_file_name(first_sequence));
+    let file = File::open(&path)
+        .with_context(|| format!("Error opening journal segment {}", path.display()))?;
+    let mut entries = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line =
+            line.with_context(|| format!("Error reading journal segment {}", path.display()))?;
+        if let Ok(entry) = serde_json::from_str(&line) {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+fn read_checkpoint(directory: &Path) -> Result<u64, Error> {
+    let path = directory.join(CHECKPOINT_FILE_NAME);
+    if !path.exists() {
+        return Ok(0);
+    }
+    let checkpoint = fs::read_to_string(&path)
+        .with_context(|| format!("Error reading journal checkpoint {}", path.display()))?;
+    checkpoint
+        .trim()
+        .parse()
+        .with_context(|| format!("Invalid journal checkpoint {}", path.display()))
+}
+
+/// Replaces the checkpoint file atomically, so that a crash leaves either the old or the new one.
+fn write_checkpoint(directory: &Path, checkpoint: u64) -> Result<(), Error> {
+    let path = directory.join(CHECKPOINT_FILE_NAME);
+    let temporary_path = path.with_extension("tmp");
+    let mut file = File::create(&temporary_path)
+        .with_context(|| format!("Error creating {}", temporary_path.display()))?;
+    file.write_all(checkpoint.to_string().as_bytes())
+        .and_then(|_| file.sync_data())
+        .with_context(|| format!("Error writing {}", temporary_path.display()))?;
+    fs::rename(&temporary_path, &path)
+        .with_context(|| format!("Error replacing journal checkpoint {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use casper_event_types::Filter;
+    use casper_types::testing::TestRng;
+    use reqwest::Url;
+    use tokio::sync::mpsc::channel;
+
+    fn journal_config(directory: &Path, max_segment_size_in_bytes: u64) -> JournalConfig {
+        JournalConfig {
+            directory: directory.to_string_lossy().to_string(),
+            max_segment_size_in_bytes: Some(max_segment_size_in_bytes),
+            retry_interval_in_seconds: None,
+        }
+    }
+
+    fn sse_event(id: u32, data: SseData) -> SseEvent {
+        let source = Url::parse("http://127.0.0.1:18101/events/main").unwrap();
+        SseEvent::new(id, data, source, None, Filter::Main)
+    }
+
+    #[test]
+    fn should_replay_unstored_events_after_reopening() {
+        let mut rng = TestRng::new();
+        let directory = tempfile::tempdir().unwrap();
+        let config = journal_config(directory.path(), 1024 * 1024);
+        let journal = Journal::open(&config).unwrap();
+        let mut events: Vec<SseEvent> = (1..=3)
+            .map(|id| sse_event(id, SseData::random_block_added(&mut rng)))
+            .collect();
+        for event in &mut events {
+            journal.append(event).unwrap();
+        }
+        journal.acknowledge(1, true);
+        journal.acknowledge(3, true);
+        journal.checkpoint().unwrap();
+        drop(journal);
+
+        let journal = Journal::open(&config).unwrap();
+        assert_eq!(read_checkpoint(directory.path()).unwrap(), 1);
+        let (sender, mut receiver) = channel(10);
+        journal.hand_over_waiting(&sender).unwrap();
+        drop(sender);
+        let mut replayed = Vec::new();
+        while let Some(event) = receiver.blocking_recv() {
+            replayed.push(event);
+        }
+        let sequences: Vec<Option<u64>> = replayed
+            .iter()
+            .map(|event| event.journal_sequence)
+            .collect();
+        assert_eq!(sequences, vec![Some(2), Some(3)]);
+        assert_eq!(replayed[0].id, 2);
+        assert_eq!(replayed[0].source, events[1].source);
+        assert_eq!(
+            serde_json::to_string(&replayed[0].data).unwrap(),
+            serde_json::to_string(&events[1].data).unwrap()
+        );
+    }
+
+    #[test]
+    fn should_keep_segments_until_their_events_are_stored() {
+        let mut rng = TestRng::new();
+        let directory = tempfile::tempdir().unwrap();
+        // Every event gets a segment of its own.
+        let journal = Journal::open(&journal_config(directory.path(), 1)).unwrap();
+        for id in 1..=3 {
+            journal
+                .append(&mut sse_event(
+                    id,
+                    SseData::random_deploy_processed(&mut rng),
+                ))
+                .unwrap();
+        }
+        assert_eq!(list_segments(directory.path()).unwrap(), vec![1, 2, 3]);
+
+        journal.acknowledge(1, true);
+        journal.acknowledge(2, false);
+        journal.acknowledge(3, true);
+        journal.checkpoint().unwrap();
+        assert_eq!(list_segments(directory.path()).unwrap(), vec![2, 3]);
+
+        journal.acknowledge(2, true);
+        journal.checkpoint().unwrap();
+        assert_eq!(list_segments(directory.path()).unwrap(), vec![3]);
+        assert_eq!(read_checkpoint(directory.path()).unwrap(), 3);
+    }
+}
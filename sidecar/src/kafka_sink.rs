@@ -0,0 +1,185 @@
+//! Publishing of the ingested events to Kafka.
+//!
+//! Every event ingested from the nodes is published as the JSON it is sent to subscribers as, to a
+//! topic named after its type. Block and deploy events are keyed by the hash of the block or
+//! deploy, so that the events of a deploy land on the same partition in order. Events are handed to
+//! the producer without waiting for Kafka. If the producer's queue is full, they are dropped or
+//! wait for room depending on the priority of the external sinks in the `[delivery]` config.
+
+use crate::{event_stream_server::sse_data_to_json, types::config::KafkaConfig};
+use anyhow::{Context, Error};
+use casper_event_types::{metrics::KAFKA_MESSAGES, sse_data::SseData};
+use rdkafka::{
+    config::ClientConfig,
+    error::{KafkaError, RDKafkaErrorCode},
+    producer::{FutureProducer, FutureRecord},
+};
+use std::time::Duration;
+use tracing::warn;
+
+/// Placeholder of the topic template replaced with the type of the event.
+const EVENT_TYPE_PLACEHOLDER: &str = "{event_type}";
+/// How often a full queue of the producer is checked for capacity when waiting for it.
+const QUEUE_FULL_RETRY_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Handle used to publish ingested events. Cloned handles share the same producer.
+#[derive(Clone)]
+pub(crate) struct KafkaSink {
+    producer: FutureProducer,
+    topic_template: String,
+}
+
+impl KafkaSink {
+    /// Creates the producer publishing to the brokers of `config`. The brokers are connected to in
+    /// the background, so an unreachable broker doesn't prevent the sidecar from starting.
+    pub(crate) fn start(config: &KafkaConfig) -> Result<Self, Error> {
+        let producer = ClientConfig::new()
+            .set("bootstrap.servers", config.brokers.join(","))
+            .set("compression.type", config.compression.codec())
+            .set(
+                "message.timeout.ms",
+                config.message_timeout_in_milliseconds().to_string(),
+            )
+            .set(
+                "queue.buffering.max.messages",
+                config.queue_size().to_string(),
+            )
+            .create()
+            .context("Error creating the Kafka producer")?;
+        Ok(KafkaSink {
+            producer,
+            topic_template: config.topic_template().to_string(),
+        })
+    }
+
+    /// Queues an ingested event for publishing. Never waits for the queue to have capacity. Derived
+    /// events aren't ingested from the nodes, so they aren't published.
+    pub(crate) fn submit(&self, sse_data: &SseData, maybe_json_data: Option<&str>) {
+        if let Some(message) = self.message(sse_data, maybe_json_data) {
+            if !self.enqueue(&message) {
+                KAFKA_MESSAGES
+                    .with_label_values(&[message.event_type, "dropped"])
+                    .inc();
+            }
+        }
+    }
+
+    /// Queues an ingested event for publishing, waiting for the queue to have capacity if it's
+    /// full.
+    pub(crate) async fn submit_waiting(&self, sse_data: &SseData, maybe_json_data: Option<&str>) {
+        if let Some(message) = self.message(sse_data, maybe_json_data) {
+            while !self.enqueue(&message) {
+                tokio::time::sleep(QUEUE_FULL_RETRY_INTERVAL).await;
+            }
+        }
+    }
+
+    /// The message publishing the event, `None` for the events which aren't published.
+    fn message(&self, sse_data: &SseData, maybe_json_data: Option<&str>) -> Option<Message> {
+        if matches!(sse_data, SseData::Derived { .. }) {
+            return None;
+        }
+        let event_type = sse_data.type_label();
+        Some(Message {
+            event_type,
+            topic: topic(&self.topic_template, event_type),
+            payload: sse_data_to_json(sse_data, maybe_json_data).to_string(),
+            maybe_key: message_key(sse_data),
+        })
+    }
+
+    /// Hands `message` to the producer, returning `false` if the producer's queue is full.
+    fn enqueue(&self, message: &Message) -> bool {
+        let event_type = message.event_type;
+        let mut record = FutureRecord::to(&message.topic).payload(&message.payload);
+        if let Some(key) = &message.maybe_key {
+            record = record.key(key);
+        }
+        match self.producer.send_result(record) {
+            Ok(delivery) => {
+                tokio::spawn(async move {
+                    let outcome = match delivery.await {
+                        Ok(Ok(_)) => "published",
+                        Ok(Err((error, _))) => {
+                            warn!(%error, event_type, "Error publishing event to Kafka");
+                            "failed"
+                        }
+                        Err(_) => "failed",
+                    };
+                    KAFKA_MESSAGES
+                        .with_label_values(&[event_type, outcome])
+                        .inc();
+                });
+                true
+            }
+            Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), _)) => false,
+            Err((error, _)) => {
+                warn!(%error, event_type, "Error publishing event to Kafka");
+                KAFKA_MESSAGES
+                    .with_label_values(&[event_type, "failed"])
+                    .inc();
+                true
+            }
+        }
+    }
+}
+
+/// An event ready to be handed to the producer.
+struct Message {
+    event_type: &'static str,
+    topic: String,
+    payload: String,
+    maybe_key: Option<String>,
+}
+
+fn topic(topic_template: &str, event_type: &str) -> String {
+    topic_template.replace(EVENT_TYPE_PLACEHOLDER, event_type)
+}
+
+/// The hex-encoded hash of the block or deploy the event is about, `None` for the other events.
+fn message_key(sse_data: &SseData) -> Option<String> {
+    match sse_data {
+        SseData::BlockAdded { block_hash, .. } => Some(hex::encode(block_hash.inner())),
+        SseData::DeployAccepted { deploy } => Some(hex::encode(deploy.hash().inner())),
+        SseData::DeployProcessed { deploy_hash, .. } => Some(hex::encode(deploy_hash.inner())),
+        SseData::DeployExpired { deploy_hash } => Some(hex::encode(deploy_hash.inner())),
+        SseData::FinalitySignature(signature) => Some(hex::encode(signature.block_hash().inner())),
+        SseData::ApiVersion(_)
+        | SseData::SidecarVersion(_)
+        | SseData::Fault { .. }
+        | SseData::Step { .. }
+        | SseData::Shutdown
+        | SseData::Unknown { .. }
+        | SseData::Derived { .. } => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use casper_types::testing::TestRng;
+
+    #[test]
+    fn should_name_topic_after_event_type() {
+        assert_eq!(
+            topic("casper.{event_type}", "BlockAdded"),
+            "casper.BlockAdded"
+        );
+        assert_eq!(topic("casper-events", "BlockAdded"), "casper-events");
+    }
+
+    #[test]
+    fn should_key_deploy_events_by_deploy_hash() {
+        let mut rng = TestRng::new();
+        let (deploy_accepted, deploy) = SseData::random_deploy_accepted(&mut rng);
+        let deploy_hash = hex::encode(deploy.hash().inner());
+        let deploy_expired = SseData::DeployExpired {
+            deploy_hash: *deploy.hash(),
+        };
+
+        assert_eq!(message_key(&deploy_accepted), Some(deploy_hash.clone()));
+        assert_eq!(message_key(&deploy_expired), Some(deploy_hash));
+        assert!(message_key(&SseData::random_block_added(&mut rng)).is_some());
+        assert_eq!(message_key(&SseData::Shutdown), None);
+    }
+}
@@ -3,57 +3,164 @@
 #![deny(clippy::too_many_lines)]
 
 extern crate core;
+mod account_aliases;
 mod admin_server;
+mod api_keys;
 mod api_version_manager;
+mod audit;
+mod backfill;
+mod block_finality;
+mod block_signatures;
+mod canonical_json;
+mod capture;
+mod clock_skew;
+mod contract_events;
 mod database;
+mod deduplication;
+mod deploy_approvals;
+mod deploy_decoding;
+mod deploy_outcomes;
+mod deploy_status;
+mod doctor;
+mod enrichment;
+mod equivocation;
+mod event_derivers;
 mod event_stream_server;
+mod export;
+mod external_sinks;
+mod failover;
+mod health;
+mod history;
+mod journal;
+#[cfg(feature = "kafka")]
+mod kafka_sink;
+mod network_identity;
+mod nft_events;
+mod node_rpc;
+mod observability_pack;
+mod operational_events;
+#[cfg(feature = "otlp")]
+mod otlp;
+mod readiness;
+mod rebuild;
 pub mod rest_server;
+mod retention;
+mod schema_version;
+mod signature_checkpoints;
+mod simulation;
+#[cfg(any(test, feature = "smoke-test"))]
+mod smoke_test;
+mod source_scoring;
 mod sql;
-#[cfg(test)]
+mod state_proofs;
+mod storage_analysis;
+#[cfg(any(test, feature = "smoke-test"))]
 pub(crate) mod testing;
 #[cfg(test)]
 pub(crate) mod tests;
+mod token_transfers;
+mod top;
 mod types;
 mod utils;
+mod validation;
+mod watch_list;
+#[cfg(feature = "webhooks")]
+mod webhooks;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
+use std::sync::Arc;
 use std::{
     net::IpAddr,
     path::{Path, PathBuf},
     str::FromStr,
-    time::Duration,
+    time::{Duration, SystemTime},
 };
 
 use crate::{
-    admin_server::run_server as start_admin_server,
-    database::sqlite_database::SqliteDatabase,
-    event_stream_server::{Config as SseConfig, EventStreamServer},
-    rest_server::run_server as start_rest_server,
+    account_aliases::AccountAliases,
+    admin_server::{
+        run_metrics_server as start_metrics_server, serve as serve_admin_server, AdminServer,
+    },
+    api_keys::ApiKeys,
+    audit::{AuditAction, AuditLog},
+    backfill::start_backfill,
+    block_finality::{BlockFinality, BLOCK_FINALIZED_DERIVER},
+    capture::start_capture,
+    contract_events::{ContractEventDecoder, ContractEvents, CONTRACT_EVENT_DERIVER},
+    database::{pool_monitor::start_pool_monitor, sqlite_database::SqliteDatabase},
+    deduplication::Deduplicator,
+    deploy_decoding::deploy_processed_of,
+    deploy_outcomes::DeployOutcomes,
+    doctor::run_doctor,
+    enrichment::Enricher,
+    event_derivers::EventDerivers,
+    event_stream_server::{
+        serve_networks as serve_event_streams, sse_data_to_json, start_health_status_monitor,
+        Config as SseConfig, EventIndexStore, EventStreamServer,
+    },
+    export::{run_export, ExportOptions},
+    external_sinks::ExternalSinks,
+    failover::{is_failover_enabled, stream_with_failover},
+    health::Health,
+    journal::Journal,
+    network_identity::check_network_identity,
+    nft_events::{NftDecoder, NftEvents, NFT_EVENT_DERIVER},
+    operational_events::{OperationalEventKind, OperationalEvents},
+    readiness::{start_readiness_monitor, IngestionQueue},
+    rebuild::Rebuilds,
+    rest_server::{serve as serve_rest_server, RestServer},
+    retention::start_retention,
+    signature_checkpoints::start_signature_checkpoints,
+    simulation::{run_simulation, Speed},
+    state_proofs::StateProofs,
+    storage_analysis::{run_storage_analysis, AnalysisOptions},
+    token_transfers::{TokenDecoder, TokenTransfers, TOKEN_TRANSFER_DERIVER},
+    top::{run_top, TopOptions},
     types::{
-        config::{read_config, Config},
-        database::{DatabaseWriteError, DatabaseWriter},
+        config::{
+            read_config, BackfillConfig, ClockSkewConfig, Config,
+            FinalitySignatureCheckpointsConfig, JournalConfig, RetentionConfig,
+        },
+        database::{DatabaseWriteError, DatabaseWriter, NetworkIdentity},
         sse_events::*,
     },
+    utils::mount_networks,
+    watch_list::WatchList,
 };
 use anyhow::{Context, Error};
 use api_version_manager::{ApiVersionManager, GuardedApiVersionManager};
 use casper_event_listener::{
-    EventListener, EventListenerBuilder, NodeConnectionInterface, SseEvent,
+    EventIdGap, EventListener, EventListenerBuilder, NodeConnectionInterface, SseEvent,
+};
+use casper_event_types::{
+    block::BlockValidationError, capture::CapturedFrame, metrics, sse_data::SseData, BlockHash,
+    Deploy, DeployHash, Filter, JsonBlock,
+};
+use casper_types::{AsymmetricType, EraId, ProtocolVersion, PublicKey, Timestamp};
+use clap::{Parser, Subcommand};
+#[cfg(feature = "postgres")]
+use database::postgresql_database::{start_partition_maintenance, PostgreSqlDatabase};
+use futures::{
+    future::{join_all, try_join_all},
+    Future,
 };
-use casper_event_types::{metrics, sse_data::SseData, Filter};
-use clap::Parser;
-use database::postgresql_database::PostgreSqlDatabase;
-use futures::future::join_all;
 use hex_fmt::HexFmt;
+use reqwest::Url;
+use serde_json::value::RawValue;
+use source_scoring::{start_liveness_monitor, start_source_scoring, SourceRecorder, SourceScores};
 #[cfg(not(target_env = "msvc"))]
 use tikv_jemallocator::Jemalloc;
 use tokio::{
-    sync::mpsc::{channel as mpsc_channel, Receiver, Sender},
+    sync::{
+        mpsc::{channel as mpsc_channel, Receiver, Sender},
+        oneshot,
+    },
     task::JoinHandle,
     time::sleep,
 };
-use tracing::{debug, error, info, trace, warn};
+use tracing::{debug, error, info, info_span, trace, warn, Instrument};
+use tracing_subscriber::{filter::LevelFilter, layer::SubscriberExt, util::SubscriberInitExt};
 use types::config::Connection;
 use types::{
     config::StorageConfig,
@@ -61,6 +168,9 @@ use types::{
 };
 #[cfg(feature = "additional-metrics")]
 use utils::start_metrics_thread;
+use warp::{filters::BoxedFilter, reply::Response};
+#[cfg(feature = "webhooks")]
+use webhooks::{Webhooks, WEBHOOK_EVENT_TYPES};
 
 #[cfg(not(target_env = "msvc"))]
 #[global_allocator]
@@ -72,192 +182,1235 @@ struct CmdLineArgs {
     /// Path to the TOML-formatted config file
     #[arg(short, long, value_name = "FILE")]
     path_to_config: String,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Replay a recorded capture of node events through storage and the event stream server, then
+    /// validate the stored data
+    Simulate {
+        /// Path to the NDJSON capture file
+        #[arg(long, value_name = "FILE")]
+        capture: PathBuf,
+        /// Replay speed relative to the recording, e.g. `10x`, or `max` to replay without delays
+        #[arg(long, default_value = "1x")]
+        speed: Speed,
+        /// Write a canonical dump of the frames sent to the event stream server and of the stored
+        /// events to this file, e.g. to compare it with the dump of another version
+        #[arg(long, value_name = "FILE")]
+        dump: Option<PathBuf>,
+    },
+    /// Sample the stored event payloads, train a zstd dictionary on them and report how much
+    /// storage compressing the payloads would save
+    AnalyzeStorage(AnalysisOptions),
+    /// Export the stored events as newline-delimited JSON files partitioned by date, along with
+    /// their BigQuery schemas and a manifest, for loading into BigQuery
+    Export(ExportOptions),
+    /// Show a live view of the event rates, subscribers, lag of the nodes, database throughput and
+    /// recent errors of the running sidecar, refreshed from its admin server
+    Top {
+        /// URL of the admin server, e.g. `http://127.0.0.1:18887`. Defaults to the admin server of
+        /// the config, reached locally
+        #[arg(long, value_name = "URL")]
+        admin_url: Option<String>,
+        /// Seconds between two refreshes
+        #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u64).range(1..))]
+        refresh_seconds: u64,
+    },
+    /// Check the connectivity to the nodes, their clocks, the ports of the servers, the storage
+    /// directory, the free disk space and the database, and report what to fix
+    Doctor,
+    /// Run the sidecar against a mock node and a temporary database, and check that the events of
+    /// a scripted scenario come out of the REST API and the event stream
+    #[cfg(feature = "smoke-test")]
+    SmokeTest,
 }
 
 const DEFAULT_CHANNEL_SIZE: usize = 1000;
 
+/// Checks of the integrity of events made before they are stored.
+#[derive(Clone, Copy, Debug)]
+struct IngestionChecks {
+    verify_finality_signatures: bool,
+    verify_block_hashes: bool,
+    clock_skew: ClockSkewConfig,
+}
+
+impl From<&Config> for IngestionChecks {
+    fn from(config: &Config) -> Self {
+        IngestionChecks {
+            verify_finality_signatures: config.verify_finality_signatures,
+            verify_block_hashes: config.verify_block_hashes,
+            clock_skew: config.clock_skew.unwrap_or_default(),
+        }
+    }
+}
+
+/// Told whether each ingested event was stored: the journal the event was recorded in, and the
+/// deduplication of the events of several nodes.
+#[derive(Clone, Default)]
+struct StorageAcknowledgements {
+    maybe_journal: Option<Journal>,
+    maybe_deduplicator: Option<Deduplicator>,
+}
+
+impl StorageAcknowledgements {
+    fn acknowledge(
+        &self,
+        maybe_journal_sequence: Option<u64>,
+        maybe_content_hash: Option<[u8; 32]>,
+        stored: bool,
+    ) {
+        if let (Some(journal), Some(sequence)) = (&self.maybe_journal, maybe_journal_sequence) {
+            journal.acknowledge(sequence, stored);
+        }
+        if let (true, Some(deduplicator), Some(content_hash)) =
+            (stored, &self.maybe_deduplicator, maybe_content_hash)
+        {
+            deduplicator.acknowledge_stored(content_hash);
+        }
+    }
+}
+
+/// What the processing of the ingested events shares between the events: where they are stored
+/// and forwarded to, and the checks they go through.
+#[derive(Clone)]
+struct EventProcessor<Db> {
+    database: Db,
+    outbound_sse_data_sender: Sender<(SseData, Option<Filter>, Option<String>)>,
+    enable_event_logging: bool,
+    ingestion_checks: IngestionChecks,
+    watch_list: WatchList,
+    api_version_manager: GuardedApiVersionManager,
+    acknowledgements: StorageAcknowledgements,
+}
+
+impl<Db> EventProcessor<Db> {
+    /// The same processing, storing the events in `database`.
+    fn with_database<T>(self, database: T) -> EventProcessor<T> {
+        EventProcessor {
+            database,
+            outbound_sse_data_sender: self.outbound_sse_data_sender,
+            enable_event_logging: self.enable_event_logging,
+            ingestion_checks: self.ingestion_checks,
+            watch_list: self.watch_list,
+            api_version_manager: self.api_version_manager,
+            acknowledgements: self.acknowledgements,
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Error> {
-    // Install global collector for tracing
-    tracing_subscriber::fmt::init();
-
     let args = CmdLineArgs::parse();
 
     let path_to_config = args.path_to_config;
 
     let config_serde = read_config(&path_to_config).context("Error constructing config")?;
-    let config = config_serde.try_into()?;
+    let config: Config = config_serde.try_into()?;
+
+    init_tracing(&config)?;
+    #[cfg(feature = "otlp")]
+    let exports_traces = config.tracing.is_some();
 
     info!("Configuration loaded");
-    run(config).await
+    let result = run_command(config, args.command).await;
+    #[cfg(feature = "otlp")]
+    if exports_traces {
+        otlp::shutdown();
+    }
+    result
+}
+
+/// Runs the given command, or the sidecar itself if there's none.
+async fn run_command(config: Config, maybe_command: Option<Command>) -> Result<(), Error> {
+    match maybe_command {
+        None => run(config).await,
+        Some(Command::Simulate {
+            capture,
+            speed,
+            dump,
+        }) => run_simulation(config, &capture, speed, dump.as_deref()).await,
+        Some(Command::AnalyzeStorage(options)) => run_storage_analysis(config, options).await,
+        Some(Command::Export(options)) => run_export(config, options).await,
+        Some(Command::Top {
+            admin_url,
+            refresh_seconds,
+        }) => {
+            let options = TopOptions {
+                admin_url,
+                refresh_interval: Duration::from_secs(refresh_seconds),
+            };
+            run_top(config, options).await
+        }
+        Some(Command::Doctor) => run_doctor(config).await,
+        #[cfg(feature = "smoke-test")]
+        Some(Command::SmokeTest) => smoke_test::run_smoke_test(config).await,
+    }
+}
+
+/// Installs the global collector of the logs and, if configured, of the traces exported over OTLP.
+fn init_tracing(config: &Config) -> Result<(), Error> {
+    let subscriber = tracing_subscriber::registry()
+        .with(LevelFilter::INFO)
+        .with(tracing_subscriber::fmt::layer());
+    #[cfg(feature = "otlp")]
+    let subscriber = subscriber.with(config.tracing.as_ref().map(otlp::layer).transpose()?);
+    #[cfg(not(feature = "otlp"))]
+    let _ = config;
+    subscriber.init();
+    Ok(())
 }
 
 async fn run(config: Config) -> Result<(), Error> {
     validate_config(&config)?;
-    let (event_listeners, sse_data_receivers) = build_event_listeners(&config)?;
-    let admin_server_handle = build_and_start_admin_server(&config);
+    if config.networks.is_empty() {
+        run_network(config, Mount::default()).await
+    } else {
+        run_networks(config).await
+    }
+}
+
+/// The routes of one of the servers of a network.
+type Routes = BoxedFilter<(Response,)>;
+
+/// Where the pipeline of a network hands the routes of its servers when the Sidecar ingests the
+/// events of several networks, for the servers shared by the networks to serve them under the name
+/// of the network. By default, the pipeline starts servers of its own.
+#[derive(Default)]
+struct Mount {
+    maybe_network: Option<String>,
+    maybe_rest_routes_sender: Option<oneshot::Sender<Routes>>,
+    maybe_sse_routes_sender: Option<oneshot::Sender<Routes>>,
+    maybe_admin_routes_sender: Option<oneshot::Sender<Routes>>,
+}
+
+/// The receivers of the routes of one of the servers of each network, named after the network.
+type RoutesReceivers = Vec<(String, oneshot::Receiver<Routes>)>;
+
+/// Runs the pipeline of each configured network, serving the events of each network under its
+/// name, e.g. `/mainnet/events/main`, on the servers shared by the networks.
+async fn run_networks(config: Config) -> Result<(), Error> {
+    let mut network_handles = Vec::new();
+    let mut rest_routes_receivers = Vec::new();
+    let mut sse_routes_receivers = Vec::new();
+    let mut admin_routes_receivers = Vec::new();
+    for network in &config.networks {
+        let (rest_routes_sender, rest_routes_receiver) = oneshot::channel();
+        let (sse_routes_sender, sse_routes_receiver) = oneshot::channel();
+        let maybe_admin_routes_sender = config.admin_server.as_ref().map(|_| {
+            let (admin_routes_sender, admin_routes_receiver) = oneshot::channel();
+            admin_routes_receivers.push((network.name.clone(), admin_routes_receiver));
+            admin_routes_sender
+        });
+        rest_routes_receivers.push((network.name.clone(), rest_routes_receiver));
+        sse_routes_receivers.push((network.name.clone(), sse_routes_receiver));
+        let mount = Mount {
+            maybe_network: Some(network.name.clone()),
+            maybe_rest_routes_sender: Some(rest_routes_sender),
+            maybe_sse_routes_sender: Some(sse_routes_sender),
+            maybe_admin_routes_sender,
+        };
+        network_handles.push(tokio::spawn(run_network(
+            config.for_network(network),
+            mount,
+        )));
+    }
+    let networks = try_join_all(network_handles.into_iter().map(flatten_handle));
+    let servers = serve_shared_servers(
+        &config,
+        rest_routes_receivers,
+        sse_routes_receivers,
+        admin_routes_receivers,
+    );
+    tokio::select! {
+        biased;
+        result = networks => result.map(|_| ()),
+        result = servers => result,
+    }
+}
+
+/// Serves the routes of every network on the servers shared by the networks, once the pipelines of
+/// the networks hand them over.
+async fn serve_shared_servers(
+    config: &Config,
+    rest_routes_receivers: RoutesReceivers,
+    sse_routes_receivers: RoutesReceivers,
+    admin_routes_receivers: RoutesReceivers,
+) -> Result<(), Error> {
+    let rest_routes = mount_networks(receive_routes(rest_routes_receivers).await?);
+    let sse_routes = mount_networks(receive_routes(sse_routes_receivers).await?);
+    let rest_server_config = config.rest_server.clone();
+    let rest_server_handle =
+        tokio::spawn(async move { serve_rest_server(rest_server_config, rest_routes).await });
+    let event_stream_server_address = config.event_stream_server.bind_address();
+    let event_stream_server_handle = tokio::spawn(async move {
+        serve_event_streams(&event_stream_server_address, sse_routes)
+            .await
+            .context("Error starting the event stream server of the networks")?;
+        Err::<(), Error>(Error::msg("Event stream server shutting down"))
+    });
+    let admin_server_handle = match config.admin_server.clone() {
+        Some(admin_server_config) => {
+            let admin_routes = mount_networks(receive_routes(admin_routes_receivers).await?);
+            tokio::spawn(async move { serve_admin_server(admin_server_config, admin_routes).await })
+        }
+        None => tokio::spawn(async { Ok(()) }),
+    };
+    tokio::try_join!(
+        flatten_handle(rest_server_handle),
+        flatten_handle(event_stream_server_handle),
+        flatten_handle(admin_server_handle),
+        flatten_handle(build_and_start_metrics_server(config)),
+    )
+    .map(|_| ())
+}
+
+/// Waits for the routes of the servers of each network, named after the network.
+async fn receive_routes(routes_receivers: RoutesReceivers) -> Result<Vec<(String, Routes)>, Error> {
+    let mut routes = Vec::new();
+    for (network, routes_receiver) in routes_receivers {
+        let network_routes = routes_receiver
+            .await
+            .map_err(|_| Error::msg(format!("Network {} stopped before serving", network)))?;
+        routes.push((network, network_routes));
+    }
+    Ok(routes)
+}
+
+/// Serves the routes of one of the servers of a network on its own, or hands them to the server
+/// shared by the networks.
+fn serve_or_mount<F: Future<Output = Result<(), Error>> + Send + 'static>(
+    routes: Routes,
+    maybe_routes_sender: Option<oneshot::Sender<Routes>>,
+    serve: impl FnOnce(Routes) -> F,
+) -> JoinHandle<Result<(), Error>> {
+    match maybe_routes_sender {
+        None => tokio::spawn(serve(routes)),
+        Some(routes_sender) => {
+            let _ = routes_sender.send(routes);
+            tokio::spawn(std::future::pending())
+        }
+    }
+}
+
+/// The ingestion of the events of the nodes of a network, up to the queues of the event processors.
+struct Ingestion {
+    event_listeners: Vec<EventListener>,
+    sse_data_receivers: Vec<Receiver<SseEvent>>,
+    ingestion_queues: Vec<IngestionQueue>,
+    gap_receiver: Receiver<EventIdGap>,
+    acknowledgements: StorageAcknowledgements,
+}
+
+/// The services of a network, shared by its servers and the processing of its events.
+struct NetworkServices {
+    database: Database,
+    maybe_audit_log: Option<AuditLog>,
+    maybe_api_keys: Option<ApiKeys>,
+    watch_list: WatchList,
+    account_aliases: AccountAliases,
+    health: Health,
+    source_scores: SourceScores,
+    deploy_outcomes: DeployOutcomes,
+    operational_events: OperationalEvents,
+}
+
+async fn run_network(config: Config, mount: Mount) -> Result<(), Error> {
+    let source_scores = SourceScores::new(&config.connections);
+    let ingestion = build_ingestion(&config, &source_scores)?;
+    let database = build_database(&config.storage).await?;
+    let maybe_network_identity = check_network(&config, &database).await?;
+    let services = build_network_services(&config, database, source_scores).await?;
+    let handles =
+        start_network_tasks(&config, mount, ingestion, &services, maybe_network_identity)?;
+
+    let result = tokio::select! {
+        result = try_join_all(handles.into_iter().map(flatten_handle)) => result.map(|_| ()),
+        () = shutdown_signal() => {
+            info!("Shutting down");
+            Ok(())
+        }
+    };
+    let details = result.as_ref().err().map(|error| error.to_string());
+    services
+        .operational_events
+        .record_and_wait(OperationalEventKind::Stopped, details)
+        .await;
+    result
+}
+
+/// Builds the listeners of the nodes, whose events are deduplicated if there are several nodes and
+/// journaled if enabled in the config.
+fn build_ingestion(config: &Config, source_scores: &SourceScores) -> Result<Ingestion, Error> {
+    let maybe_capture_sender = config.capture.as_ref().map(start_capture).transpose()?;
+    let (gap_sender, gap_receiver) = mpsc_channel(DEFAULT_CHANNEL_SIZE);
+    let (event_listeners, mut sse_data_receivers, mut ingestion_queues) =
+        build_event_listeners(config, maybe_capture_sender, gap_sender, source_scores)?;
+    let mut maybe_deduplicator = None;
+    if config.connections.len() > 1 {
+        let deduplicator = Deduplicator::new();
+        sse_data_receivers = deduplicate_inbound_events(
+            config,
+            &deduplicator,
+            sse_data_receivers,
+            &mut ingestion_queues,
+            source_scores,
+        );
+        maybe_deduplicator = Some(deduplicator);
+    }
+    let maybe_journal = config.journal.as_ref().map(Journal::open).transpose()?;
+    if let Some(journal) = &maybe_journal {
+        sse_data_receivers =
+            journal_inbound_events(config, journal, sse_data_receivers, &mut ingestion_queues);
+    }
+    Ok(Ingestion {
+        event_listeners,
+        sse_data_receivers,
+        ingestion_queues,
+        gap_receiver,
+        acknowledgements: StorageAcknowledgements {
+            maybe_journal,
+            maybe_deduplicator,
+        },
+    })
+}
+
+/// Starts the services of the network storing its events in `database`.
+async fn build_network_services(
+    config: &Config,
+    database: Database,
+    source_scores: SourceScores,
+) -> Result<NetworkServices, Error> {
+    let maybe_audit_log = build_audit_log(config, &database);
+    let maybe_api_keys = build_api_keys(config, &database).await?;
+    let operational_events = build_operational_events(config, &database);
+    Ok(NetworkServices {
+        maybe_audit_log,
+        maybe_api_keys,
+        watch_list: build_watch_list(config)?,
+        account_aliases: build_account_aliases(config).await?,
+        health: Health::new(),
+        source_scores,
+        deploy_outcomes: DeployOutcomes::default(),
+        operational_events,
+        database,
+    })
+}
+
+/// Starts the servers of the network and the processing and broadcasting of its events, returning
+/// their tasks.
+fn start_network_tasks(
+    config: &Config,
+    mut mount: Mount,
+    ingestion: Ingestion,
+    services: &NetworkServices,
+    maybe_network_identity: Option<NetworkIdentity>,
+) -> Result<Vec<JoinHandle<Result<(), Error>>>, Error> {
     // This channel allows SseData to be sent from multiple connected nodes to the single EventStreamServer.
     let (outbound_sse_data_sender, outbound_sse_data_receiver) =
         mpsc_channel(config.outbound_channel_size.unwrap_or(DEFAULT_CHANNEL_SIZE));
-    let connection_configs = config.connections.clone();
-    let storage_config = config.storage.clone();
-    let database = build_database(&storage_config).await?;
-    let rest_server_handle = build_and_start_rest_server(&config, database.clone());
-
+    let consumers = BroadcastConsumers::start(config, services, &outbound_sse_data_sender)?;
+    let admin_server_handle =
+        build_and_start_admin_server(config, services, mount.maybe_admin_routes_sender.take());
+    // The metrics of the networks are served once, by `run_networks`.
+    let metrics_server_handle = match mount.maybe_network {
+        None => build_and_start_metrics_server(config),
+        Some(_) => tokio::spawn(async { Ok(()) }),
+    };
+    let rest_server_handle =
+        build_and_start_rest_server(config, services, mount.maybe_rest_routes_sender.take());
     // Task to manage incoming events from all three filters
-    let listening_task_handle = start_sse_processors(
-        connection_configs,
+    let listening_task_handle =
+        start_ingestion(config, ingestion, services, outbound_sse_data_sender);
+    let context = EventStreamServerContext::new(services, maybe_network_identity, mount);
+    let event_broadcasting_handle =
+        start_event_broadcasting(config, outbound_sse_data_receiver, consumers, context);
+    Ok(vec![
+        event_broadcasting_handle,
+        rest_server_handle,
+        listening_task_handle,
+        admin_server_handle,
+        metrics_server_handle,
+    ])
+}
+
+/// Starts the processing of the ingested events, and the tasks monitoring and maintaining the
+/// stored events.
+fn start_ingestion(
+    config: &Config,
+    ingestion: Ingestion,
+    services: &NetworkServices,
+    outbound_sse_data_sender: Sender<(SseData, Option<Filter>, Option<String>)>,
+) -> JoinHandle<Result<(), Error>> {
+    let Ingestion {
         event_listeners,
         sse_data_receivers,
-        database.clone(),
-        outbound_sse_data_sender.clone(),
+        mut ingestion_queues,
+        gap_receiver,
+        acknowledgements,
+    } = ingestion;
+    ingestion_queues.push(IngestionQueue::of(&outbound_sse_data_sender));
+    services
+        .operational_events
+        .record_event_id_gaps(gap_receiver);
+    let processor = EventProcessor {
+        database: services.database.clone(),
+        outbound_sse_data_sender,
+        enable_event_logging: false,
+        ingestion_checks: IngestionChecks::from(config),
+        watch_list: services.watch_list.clone(),
+        api_version_manager: ApiVersionManager::new(),
+        acknowledgements,
+    };
+    start_background_tasks(config, services, &processor, ingestion_queues);
+    start_sse_processors(
+        config.connections.clone(),
+        event_listeners,
+        sse_data_receivers,
+        processor,
+        services.source_scores.clone(),
+        services.operational_events.clone(),
+    )
+}
+
+/// Starts the tasks monitoring the network and maintaining its stored events, as enabled in the
+/// config.
+fn start_background_tasks(
+    config: &Config,
+    services: &NetworkServices,
+    processor: &EventProcessor<Database>,
+    ingestion_queues: Vec<IngestionQueue>,
+) {
+    let database = &services.database;
+    start_database_pool_monitor(database, services.health.clone());
+    if let Some(retention_config) = &config.retention {
+        start_database_retention(
+            retention_config,
+            database,
+            services.operational_events.clone(),
+        );
+    }
+    if let Some(checkpoints_config) = &config.finality_signature_checkpoints {
+        start_database_signature_checkpoints(checkpoints_config, database);
+    }
+    if let Some(backfill_config) = &config.backfill {
+        start_database_backfill(backfill_config, database);
+    }
+    let maybe_journal = &processor.acknowledgements.maybe_journal;
+    if let (Some(journal_config), Some(journal)) = (&config.journal, maybe_journal) {
+        start_journal_application(config, journal_config, journal, processor);
+    }
+    if let Some(scoring_config) = &config.source_scoring {
+        start_source_scoring(
+            scoring_config,
+            &config.connections,
+            services.source_scores.clone(),
+        );
+    }
+    start_readiness_monitor(
+        config.readiness.unwrap_or_default(),
+        ingestion_queues,
+        services.source_scores.clone(),
+        services.health.clone(),
     );
+    start_liveness_monitor(services.source_scores.clone(), services.health.clone());
+}
 
-    let event_broadcasting_handle =
-        start_event_broadcasting(&config, &storage_config, outbound_sse_data_receiver);
+/// Resolves once the sidecar is asked to stop, with Ctrl+C or, on unix, with SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if tokio::signal::ctrl_c().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+    tokio::select! {
+        () = ctrl_c => {}
+        () = terminate => {}
+    }
+}
 
-    tokio::try_join!(
-        flatten_handle(event_broadcasting_handle),
-        flatten_handle(rest_server_handle),
-        flatten_handle(listening_task_handle),
-        flatten_handle(admin_server_handle),
-    )
-    .map(|_| Ok(()))?
+/// The consumers of the broadcast events, apart from the subscribers of the event stream server.
+#[derive(Default)]
+struct BroadcastConsumers {
+    maybe_enricher: Option<Enricher>,
+    event_derivers: EventDerivers,
+    block_finality: BlockFinality,
+    token_transfers: TokenTransfers,
+    nft_events: NftEvents,
+    contract_events: ContractEvents,
+    external_sinks: ExternalSinks,
+    deploy_outcomes: DeployOutcomes,
+}
+
+impl BroadcastConsumers {
+    /// Starts the consumers enabled in the config, the events they derive being broadcast too.
+    fn start(
+        config: &Config,
+        services: &NetworkServices,
+        outbound_sse_data_sender: &Sender<(SseData, Option<Filter>, Option<String>)>,
+    ) -> Result<Self, Error> {
+        let database = &services.database;
+        Ok(BroadcastConsumers {
+            maybe_enricher: build_enricher(config, database),
+            event_derivers: build_event_derivers(
+                config,
+                database,
+                outbound_sse_data_sender.clone(),
+            )?,
+            block_finality: build_block_finality(
+                config,
+                database,
+                outbound_sse_data_sender.clone(),
+            ),
+            token_transfers: build_token_transfers(
+                config,
+                database,
+                outbound_sse_data_sender.clone(),
+            )?,
+            nft_events: build_nft_events(config, database, outbound_sse_data_sender.clone())?,
+            contract_events: build_contract_events(
+                config,
+                database,
+                outbound_sse_data_sender.clone(),
+            )?,
+            external_sinks: build_external_sinks(config, database)?,
+            deploy_outcomes: services.deploy_outcomes.clone(),
+        })
+    }
+
+    async fn submit(&self, sse_data: &SseData, maybe_json_data: Option<&str>) {
+        if let Some(enricher) = &self.maybe_enricher {
+            enricher.submit(sse_data);
+        }
+        self.event_derivers.submit(sse_data);
+        self.block_finality.submit(sse_data);
+        self.token_transfers.submit(sse_data);
+        self.nft_events.submit(sse_data);
+        self.contract_events.submit(sse_data);
+        self.external_sinks.submit(sse_data, maybe_json_data).await;
+        self.deploy_outcomes.submit(sse_data);
+    }
+}
+
+/// What the event stream server of a network is started with, apart from the config.
+struct EventStreamServerContext {
+    maybe_audit_log: Option<AuditLog>,
+    watch_list: WatchList,
+    maybe_api_keys: Option<ApiKeys>,
+    event_index_store: EventIndexStore,
+    health: Health,
+    maybe_network_name: Option<String>,
+    maybe_network: Option<String>,
+    maybe_sse_routes_sender: Option<oneshot::Sender<Routes>>,
+}
+
+impl EventStreamServerContext {
+    fn new(
+        services: &NetworkServices,
+        maybe_network_identity: Option<NetworkIdentity>,
+        mount: Mount,
+    ) -> Self {
+        EventStreamServerContext {
+            maybe_audit_log: services.maybe_audit_log.clone(),
+            watch_list: services.watch_list.clone(),
+            maybe_api_keys: services.maybe_api_keys.clone(),
+            event_index_store: event_index_store(&services.database),
+            health: services.health.clone(),
+            maybe_network_name: maybe_network_identity
+                .map(|network_identity| network_identity.chainspec_name),
+            maybe_network: mount.maybe_network,
+            maybe_sse_routes_sender: mount.maybe_sse_routes_sender,
+        }
+    }
 }
 
 fn start_event_broadcasting(
     config: &Config,
-    storage_config: &StorageConfig,
     mut outbound_sse_data_receiver: Receiver<(SseData, Option<Filter>, Option<String>)>,
+    consumers: BroadcastConsumers,
+    context: EventStreamServerContext,
 ) -> JoinHandle<Result<(), Error>> {
-    let storage_path = storage_config.get_storage_path();
-    let event_stream_server_port = config.event_stream_server.port;
-    let buffer_length = config.event_stream_server.event_stream_buffer_length;
-    let max_concurrent_subscribers = config.event_stream_server.max_concurrent_subscribers;
+    let sse_config = build_sse_config(config, context.maybe_network_name.clone());
+    let storage_path = PathBuf::from(config.storage.get_storage_path());
+    let canonical_json = config.canonical_json;
+    let maybe_network = context.maybe_network.clone();
     tokio::spawn(async move {
-        // Create new instance for the Sidecar's Event Stream Server
-        let mut event_stream_server = EventStreamServer::new(
-            SseConfig::new(
-                event_stream_server_port,
-                Some(buffer_length),
-                Some(max_concurrent_subscribers),
-            ),
-            PathBuf::from(storage_path),
-        )
-        .context("Error starting EventStreamServer")?;
-        while let Some((sse_data, inbound_filter, maybe_json_data)) =
+        let mut event_stream_server =
+            start_event_stream_server(sse_config, storage_path, context).await?;
+        while let Some((sse_data, inbound_filter, mut maybe_json_data)) =
             outbound_sse_data_receiver.recv().await
         {
-            event_stream_server.broadcast(sse_data, inbound_filter, maybe_json_data);
+            if canonical_json {
+                maybe_json_data = to_canonical_json_data(&sse_data, maybe_json_data);
+            }
+            consumers
+                .submit(&sse_data, maybe_json_data.as_deref())
+                .await;
+            if let Some(network) = &maybe_network {
+                metrics::NETWORK_EVENTS
+                    .with_label_values(&[network.as_str(), sse_data.type_label()])
+                    .inc();
+            }
+            let span = info_span!("broadcast", event_type = sse_data.type_label());
+            event_stream_server
+                .broadcast(sse_data, inbound_filter, maybe_json_data)
+                .instrument(span)
+                .await
+                .map_err(|error| Error::msg(error.to_string()))
+                .context("Error allocating the id of an outbound event")?;
         }
         Err::<(), Error>(Error::msg("Event broadcasting finished"))
     })
 }
 
+fn build_sse_config(config: &Config, maybe_network_name: Option<String>) -> SseConfig {
+    let event_stream_server_config = &config.event_stream_server;
+    SseConfig {
+        address: event_stream_server_config.bind_address(),
+        slow_subscriber_timeout_in_seconds: event_stream_server_config
+            .slow_subscriber_timeout_in_seconds,
+        priority_classes: event_stream_server_config.priority_classes.clone(),
+        reject_unavailable_replay: event_stream_server_config.reject_unavailable_replay,
+        network_name: maybe_network_name,
+        ..SseConfig::new(
+            event_stream_server_config.port,
+            Some(event_stream_server_config.event_stream_buffer_length),
+            Some(event_stream_server_config.max_concurrent_subscribers),
+            event_stream_server_config.jwt_auth.clone(),
+        )
+    }
+}
+
+/// Starts the event stream server of a network, serving on its own or handing its routes to the
+/// server shared by the networks.
+async fn start_event_stream_server(
+    sse_config: SseConfig,
+    storage_path: PathBuf,
+    context: EventStreamServerContext,
+) -> Result<EventStreamServer, Error> {
+    // Create new instance for the Sidecar's Event Stream Server
+    let mut event_stream_server = match context.maybe_sse_routes_sender {
+        None => EventStreamServer::new(
+            sse_config,
+            storage_path,
+            context.maybe_audit_log,
+            context.watch_list,
+            context.maybe_api_keys,
+        )
+        .context("Error starting EventStreamServer")?,
+        Some(sse_routes_sender) => {
+            let (event_stream_server, sse_routes) = EventStreamServer::new_mounted(
+                sse_config,
+                storage_path,
+                context.maybe_audit_log,
+                context.watch_list,
+                context.maybe_api_keys,
+            );
+            let _ = sse_routes_sender.send(sse_routes);
+            event_stream_server
+        }
+    };
+    start_health_status_monitor(context.health, event_stream_server.status_broadcaster());
+    event_stream_server
+        .use_event_index_store(context.event_index_store)
+        .await
+        .map_err(|error| Error::msg(error.to_string()))
+        .context("Error initializing the event id allocator")?;
+    Ok(event_stream_server)
+}
+
+/// The JSON of the event, as emitted on the event stream, in its canonical form. The event is
+/// emitted as it would be otherwise if it can't be serialized.
+fn to_canonical_json_data(sse_data: &SseData, maybe_json_data: Option<String>) -> Option<String> {
+    let json = sse_data_to_json(sse_data, maybe_json_data.as_deref());
+    match canonical_json::to_canonical_string(&json) {
+        Ok(canonical_json_data) => Some(canonical_json_data),
+        Err(error) => {
+            warn!(?error, "Error serializing event as canonical JSON");
+            maybe_json_data
+        }
+    }
+}
+
 fn start_sse_processors(
     connection_configs: Vec<Connection>,
     event_listeners: Vec<EventListener>,
     sse_data_receivers: Vec<Receiver<SseEvent>>,
-    database: Database,
-    outbound_sse_data_sender: Sender<(SseData, Option<Filter>, Option<String>)>,
+    processor: EventProcessor<Database>,
+    source_scores: SourceScores,
+    operational_events: OperationalEvents,
 ) -> JoinHandle<Result<(), Error>> {
     tokio::spawn(async move {
-        let mut join_handles = Vec::with_capacity(event_listeners.len());
-        let api_version_manager = ApiVersionManager::new();
-
-        for ((mut event_listener, connection_config), sse_data_receiver) in event_listeners
+        let join_handles: Vec<_> = sse_data_receivers
             .into_iter()
-            .zip(connection_configs)
-            .zip(sse_data_receivers)
-        {
-            tokio::spawn(async move {
-                let res = event_listener.stream_aggregated_events().await;
-                if let Err(e) = res {
-                    let addr = event_listener.get_node_interface().ip_address.to_string();
-                    error!("Disconnected from {}. Reason: {}", addr, e.to_string());
-                }
-            });
-            let join_handle = spawn_sse_processor(
-                &database,
-                sse_data_receiver,
-                &outbound_sse_data_sender,
-                connection_config,
-                &api_version_manager,
-            );
-            join_handles.push(join_handle);
+            .zip(&connection_configs)
+            .enumerate()
+            .map(|(index, (sse_data_receiver, connection_config))| {
+                let processor = EventProcessor {
+                    enable_event_logging: connection_config.enable_logging,
+                    ..processor.clone()
+                };
+                spawn_sse_processor(processor, sse_data_receiver, source_scores.recorder(index))
+            })
+            .collect();
+        if is_failover_enabled(&connection_configs) {
+            tokio::spawn(stream_with_failover(
+                connection_configs,
+                event_listeners,
+                source_scores,
+                operational_events,
+            ));
+        } else {
+            start_event_listeners(event_listeners, &source_scores);
         }
 
         let _ = join_all(join_handles).await;
-        //Send Shutdown to the sidecar sse endpoint
-        let _ = outbound_sse_data_sender
-            .send((SseData::Shutdown, None, None))
-            .await;
-        // Below sleep is a workaround to allow the above Shutdown to propagate.
-        // If we don't do this there is a race condition between handling of the message and dropping of the outbound server
-        // which happens when we leave this function and the `tokio::try_join!` exits due to this. This race condition causes 9 of 10
-        // tries to not propagate the Shutdown (ususally drop happens faster than message propagation to outbound).
-        // Fixing this race condition would require rewriting a lot of code. AFAICT the only drawback to this workaround is that the
-        // rest server and the sse server will exit 200ms later than it would without it.
-        sleep(Duration::from_millis(200)).await;
+        send_shutdown(&processor.outbound_sse_data_sender).await;
         Err::<(), Error>(Error::msg("Connected node(s) are unavailable"))
     })
 }
 
+/// Streams the events of every node at once, rather than failing over from one node to the next.
+fn start_event_listeners(event_listeners: Vec<EventListener>, source_scores: &SourceScores) {
+    for (index, mut event_listener) in event_listeners.into_iter().enumerate() {
+        let source_scores = source_scores.clone();
+        tokio::spawn(async move {
+            source_scores.set_active(index, true);
+            let res = event_listener.stream_aggregated_events().await;
+            source_scores.set_active(index, false);
+            if let Err(e) = res {
+                let addr = event_listener.get_node_interface().ip_address.to_string();
+                error!("Disconnected from {}. Reason: {}", addr, e.to_string());
+            }
+        });
+    }
+}
+
+async fn send_shutdown(
+    outbound_sse_data_sender: &Sender<(SseData, Option<Filter>, Option<String>)>,
+) {
+    //Send Shutdown to the sidecar sse endpoint
+    let _ = outbound_sse_data_sender
+        .send((SseData::Shutdown, None, None))
+        .await;
+    // Below sleep is a workaround to allow the above Shutdown to propagate.
+    // If we don't do this there is a race condition between handling of the message and dropping of the outbound server
+    // which happens when we leave this function and the `tokio::try_join!` exits due to this. This race condition causes 9 of 10
+    // tries to not propagate the Shutdown (ususally drop happens faster than message propagation to outbound).
+    // Fixing this race condition would require rewriting a lot of code. AFAICT the only drawback to this workaround is that the
+    // rest server and the sse server will exit 200ms later than it would without it.
+    sleep(Duration::from_millis(200)).await;
+}
+
 fn spawn_sse_processor(
-    database: &Database,
+    processor: EventProcessor<Database>,
     sse_data_receiver: Receiver<SseEvent>,
-    outbound_sse_data_sender: &Sender<(SseData, Option<Filter>, Option<String>)>,
-    connection_config: Connection,
-    api_version_manager: &std::sync::Arc<tokio::sync::Mutex<ApiVersionManager>>,
+    source_recorder: SourceRecorder,
 ) -> JoinHandle<Result<(), Error>> {
-    match database.clone() {
+    match processor.database.clone() {
         Database::SqliteDatabaseWrapper(db) => tokio::spawn(sse_processor(
             sse_data_receiver,
-            outbound_sse_data_sender.clone(),
-            db.clone(),
+            processor.with_database(db),
             false,
-            connection_config.enable_logging,
-            api_version_manager.clone(),
+            source_recorder,
         )),
+        #[cfg(feature = "postgres")]
         Database::PostgreSqlDatabaseWrapper(db) => tokio::spawn(sse_processor(
             sse_data_receiver,
-            outbound_sse_data_sender.clone(),
-            db.clone(),
+            processor.with_database(db),
             true,
-            connection_config.enable_logging,
-            api_version_manager.clone(),
+            source_recorder,
         )),
     }
 }
 
 fn build_and_start_rest_server(
     config: &Config,
-    database: Database,
+    services: &NetworkServices,
+    maybe_routes_sender: Option<oneshot::Sender<Routes>>,
 ) -> JoinHandle<Result<(), Error>> {
     let rest_server_config = config.rest_server.clone();
-    tokio::spawn(async move {
-        match database {
-            Database::SqliteDatabaseWrapper(db) => {
-                start_rest_server(rest_server_config, db.clone()).await
-            }
-            Database::PostgreSqlDatabaseWrapper(db) => {
-                start_rest_server(rest_server_config, db.clone()).await
-            }
+    let routes = match services.database.clone() {
+        Database::SqliteDatabaseWrapper(db) => build_rest_server(config, services, db).routes(),
+        #[cfg(feature = "postgres")]
+        Database::PostgreSqlDatabaseWrapper(db) => build_rest_server(config, services, db).routes(),
+    };
+    serve_or_mount(routes, maybe_routes_sender, |routes| {
+        serve_rest_server(rest_server_config, routes)
+    })
+}
+
+/// The REST server of the network, answering with the data of `database`.
+fn build_rest_server<Db>(
+    config: &Config,
+    services: &NetworkServices,
+    database: Db,
+) -> RestServer<Db> {
+    // REST queries are only audited if explicitly requested as they can be very numerous.
+    let maybe_audit_log = services.maybe_audit_log.clone().filter(|_| {
+        config
+            .audit
+            .as_ref()
+            .map_or(false, |audit_config| audit_config.log_rest_queries)
+    });
+    RestServer {
+        database,
+        maybe_audit_log,
+        maybe_state_proofs: config.state_proofs.as_ref().map(StateProofs::new),
+        account_aliases: services.account_aliases.clone(),
+        deploy_outcomes: services.deploy_outcomes.clone(),
+        canonical_json: config.canonical_json,
+        event_stream_port: config.event_stream_server.port,
+        maybe_api_keys: services.maybe_api_keys.clone(),
+    }
+}
+
+fn build_and_start_admin_server(
+    config: &Config,
+    services: &NetworkServices,
+    maybe_routes_sender: Option<oneshot::Sender<Routes>>,
+) -> JoinHandle<Result<(), Error>> {
+    let Some(admin_server_config) = config.admin_server.clone() else {
+        return tokio::spawn(async { Ok(()) });
+    };
+    let routes = match services.database.clone() {
+        Database::SqliteDatabaseWrapper(db) => build_admin_server(config, services, db).routes(),
+        #[cfg(feature = "postgres")]
+        Database::PostgreSqlDatabaseWrapper(db) => {
+            build_admin_server(config, services, db).routes()
         }
+    };
+    serve_or_mount(routes, maybe_routes_sender, |routes| {
+        serve_admin_server(admin_server_config, routes)
     })
 }
 
-fn build_and_start_admin_server(config: &Config) -> JoinHandle<Result<(), Error>> {
-    let admin_server_config = config.admin_server.clone();
+/// The admin server of the network, reporting on and managing the events stored in `database`.
+fn build_admin_server<Db>(
+    config: &Config,
+    services: &NetworkServices,
+    database: Db,
+) -> AdminServer<Db> {
+    AdminServer {
+        database,
+        maybe_audit_log: services.maybe_audit_log.clone(),
+        health: services.health.clone(),
+        watch_list: services.watch_list.clone(),
+        source_scores: services.source_scores.clone(),
+        account_aliases: services.account_aliases.clone(),
+        rebuilds: Rebuilds::new(config),
+        maybe_api_keys: services.maybe_api_keys.clone(),
+    }
+}
+
+fn build_and_start_metrics_server(config: &Config) -> JoinHandle<Result<(), Error>> {
+    let metrics_server_config = config.metrics_server.clone();
     tokio::spawn(async move {
-        if let Some(config) = admin_server_config {
-            start_admin_server(config).await
-        } else {
-            Ok(())
+        match metrics_server_config {
+            Some(config) => start_metrics_server(config).await,
+            None => Ok(()),
+        }
+    })
+}
+
+fn start_database_pool_monitor(database: &Database, health: Health) {
+    match database {
+        Database::SqliteDatabaseWrapper(db) => {
+            start_pool_monitor(db.connection_pool.clone(), health)
+        }
+        #[cfg(feature = "postgres")]
+        Database::PostgreSqlDatabaseWrapper(db) => {
+            start_pool_monitor(db.connection_pool.clone(), health)
+        }
+    }
+}
+
+fn start_database_retention(
+    config: &RetentionConfig,
+    database: &Database,
+    operational_events: OperationalEvents,
+) {
+    match database.clone() {
+        Database::SqliteDatabaseWrapper(db) => {
+            start_retention(config.clone(), db, operational_events)
+        }
+        #[cfg(feature = "postgres")]
+        Database::PostgreSqlDatabaseWrapper(db) => {
+            start_retention(config.clone(), db, operational_events)
+        }
+    }
+}
+
+fn start_database_signature_checkpoints(
+    config: &FinalitySignatureCheckpointsConfig,
+    database: &Database,
+) {
+    match database.clone() {
+        Database::SqliteDatabaseWrapper(db) => start_signature_checkpoints(config.clone(), db),
+        #[cfg(feature = "postgres")]
+        Database::PostgreSqlDatabaseWrapper(db) => start_signature_checkpoints(config.clone(), db),
+    }
+}
+
+fn start_database_backfill(config: &BackfillConfig, database: &Database) {
+    match database.clone() {
+        Database::SqliteDatabaseWrapper(db) => start_backfill(config.clone(), db),
+        #[cfg(feature = "postgres")]
+        Database::PostgreSqlDatabaseWrapper(db) => start_backfill(config.clone(), db),
+    }
+}
+
+/// The database in which the ids of outbound events are allocated.
+fn event_index_store(database: &Database) -> EventIndexStore {
+    match database.clone() {
+        Database::SqliteDatabaseWrapper(db) => Arc::new(db),
+        #[cfg(feature = "postgres")]
+        Database::PostgreSqlDatabaseWrapper(db) => Arc::new(db),
+    }
+}
+
+/// Starts the audit log if it is enabled in the config and records the loaded configuration.
+fn build_audit_log(config: &Config, database: &Database) -> Option<AuditLog> {
+    let trusted_proxies = config.audit.as_ref()?.trusted_proxies.clone();
+    let audit_log = match database.clone() {
+        Database::SqliteDatabaseWrapper(db) => AuditLog::start(db, trusted_proxies),
+        #[cfg(feature = "postgres")]
+        Database::PostgreSqlDatabaseWrapper(db) => AuditLog::start(db, trusted_proxies),
+    };
+    audit_log.record(
+        "sidecar",
+        AuditAction::ConfigLoaded,
+        Some(format!(
+            "{} connection(s), rest server port {}, event stream server port {}",
+            config.connections.len(),
+            config.rest_server.port,
+            config.event_stream_server.port
+        )),
+    );
+    Some(audit_log)
+}
+
+/// Starts the recording of the operational history of the sidecar, beginning with its start.
+fn build_operational_events(config: &Config, database: &Database) -> OperationalEvents {
+    let operational_events = match database.clone() {
+        Database::SqliteDatabaseWrapper(db) => OperationalEvents::start(db),
+        #[cfg(feature = "postgres")]
+        Database::PostgreSqlDatabaseWrapper(db) => OperationalEvents::start(db),
+    };
+    operational_events.record(
+        OperationalEventKind::Started,
+        Some(format!(
+            "version {}, {} connection(s)",
+            env!("CARGO_PKG_VERSION"),
+            config.connections.len()
+        )),
+    );
+    operational_events
+}
+
+/// Builds the watch list of the config, failing if it names a malformed entity.
+fn build_watch_list(config: &Config) -> Result<WatchList, Error> {
+    match &config.watch_list {
+        Some(watch_list_config) => WatchList::new(watch_list_config),
+        None => Ok(WatchList::default()),
+    }
+}
+
+/// Loads the API keys of the config and, if they are stored in the database, of the database.
+async fn build_api_keys(config: &Config, database: &Database) -> Result<Option<ApiKeys>, Error> {
+    let Some(api_keys_config) = &config.api_keys else {
+        return Ok(None);
+    };
+    let api_keys = match database {
+        Database::SqliteDatabaseWrapper(db) => ApiKeys::load(api_keys_config, db).await,
+        #[cfg(feature = "postgres")]
+        Database::PostgreSqlDatabaseWrapper(db) => ApiKeys::load(api_keys_config, db).await,
+    }
+    .map_err(|error| Error::msg(format!("Error loading the API keys: {:?}", error)))?;
+    Ok(Some(api_keys))
+}
+
+/// Loads the account aliases of the config, and of the endpoint it refers to if any.
+async fn build_account_aliases(config: &Config) -> Result<AccountAliases, Error> {
+    match &config.account_aliases {
+        Some(aliases_config) => AccountAliases::load(aliases_config).await,
+        None => Ok(AccountAliases::default()),
+    }
+}
+
+fn build_enricher(config: &Config, database: &Database) -> Option<Enricher> {
+    let enrichment_config = config.enrichment.clone()?;
+    let enricher = match database.clone() {
+        Database::SqliteDatabaseWrapper(db) => Enricher::start(enrichment_config, db),
+        #[cfg(feature = "postgres")]
+        Database::PostgreSqlDatabaseWrapper(db) => Enricher::start(enrichment_config, db),
+    };
+    Some(enricher)
+}
+
+/// Loads the event derivers registered in the config.
+fn build_event_derivers(
+    config: &Config,
+    database: &Database,
+    outbound_sse_data_sender: Sender<(SseData, Option<Filter>, Option<String>)>,
+) -> Result<EventDerivers, Error> {
+    match database.clone() {
+        Database::SqliteDatabaseWrapper(db) => {
+            EventDerivers::start(&config.event_derivers, db, outbound_sse_data_sender)
+        }
+        #[cfg(feature = "postgres")]
+        Database::PostgreSqlDatabaseWrapper(db) => {
+            EventDerivers::start(&config.event_derivers, db, outbound_sse_data_sender)
+        }
+    }
+}
+
+/// Starts the aggregation of the finality signatures into `BlockFinalized` events, if enabled in
+/// the config.
+fn build_block_finality(
+    config: &Config,
+    database: &Database,
+    outbound_sse_data_sender: Sender<(SseData, Option<Filter>, Option<String>)>,
+) -> BlockFinality {
+    let Some(block_finality_config) = &config.block_finality else {
+        return BlockFinality::default();
+    };
+    match database.clone() {
+        Database::SqliteDatabaseWrapper(db) => {
+            BlockFinality::start(block_finality_config, db, outbound_sse_data_sender)
+        }
+        #[cfg(feature = "postgres")]
+        Database::PostgreSqlDatabaseWrapper(db) => {
+            BlockFinality::start(block_finality_config, db, outbound_sse_data_sender)
+        }
+    }
+}
+
+/// Starts the decoding of the transfers of the CEP-18 tokens registered in the config.
+fn build_token_transfers(
+    config: &Config,
+    database: &Database,
+    outbound_sse_data_sender: Sender<(SseData, Option<Filter>, Option<String>)>,
+) -> Result<TokenTransfers, Error> {
+    let Some(token_transfers_config) = &config.token_transfers else {
+        return Ok(TokenTransfers::default());
+    };
+    let decoder = TokenDecoder::new(token_transfers_config)?;
+    let queue_size = token_transfers_config.queue_size();
+    Ok(match database.clone() {
+        Database::SqliteDatabaseWrapper(db) => {
+            TokenTransfers::start(decoder, queue_size, db, outbound_sse_data_sender)
+        }
+        #[cfg(feature = "postgres")]
+        Database::PostgreSqlDatabaseWrapper(db) => {
+            TokenTransfers::start(decoder, queue_size, db, outbound_sse_data_sender)
         }
     })
 }
 
+/// Starts the decoding of the events of the CEP-78 collections registered in the config.
+fn build_nft_events(
+    config: &Config,
+    database: &Database,
+    outbound_sse_data_sender: Sender<(SseData, Option<Filter>, Option<String>)>,
+) -> Result<NftEvents, Error> {
+    let Some(nft_events_config) = &config.nft_events else {
+        return Ok(NftEvents::default());
+    };
+    let decoder = NftDecoder::new(nft_events_config)?;
+    let queue_size = nft_events_config.queue_size();
+    Ok(match database.clone() {
+        Database::SqliteDatabaseWrapper(db) => {
+            NftEvents::start(decoder, queue_size, db, outbound_sse_data_sender)
+        }
+        #[cfg(feature = "postgres")]
+        Database::PostgreSqlDatabaseWrapper(db) => {
+            NftEvents::start(decoder, queue_size, db, outbound_sse_data_sender)
+        }
+    })
+}
+
+/// Starts the decoding of the events of the contracts registered in the config.
+fn build_contract_events(
+    config: &Config,
+    database: &Database,
+    outbound_sse_data_sender: Sender<(SseData, Option<Filter>, Option<String>)>,
+) -> Result<ContractEvents, Error> {
+    let Some(contract_events_config) = &config.contract_events else {
+        return Ok(ContractEvents::default());
+    };
+    let decoder = ContractEventDecoder::new(contract_events_config)?;
+    let queue_size = contract_events_config.queue_size();
+    Ok(match database.clone() {
+        Database::SqliteDatabaseWrapper(db) => {
+            ContractEvents::start(decoder, queue_size, db, outbound_sse_data_sender)
+        }
+        #[cfg(feature = "postgres")]
+        Database::PostgreSqlDatabaseWrapper(db) => {
+            ContractEvents::start(decoder, queue_size, db, outbound_sse_data_sender)
+        }
+    })
+}
+
+/// Starts the delivery of events to the webhooks registered in the config.
+#[cfg(feature = "webhooks")]
+fn build_webhooks(config: &Config, database: &Database) -> Result<Webhooks, Error> {
+    match database.clone() {
+        Database::SqliteDatabaseWrapper(db) => Webhooks::start(&config.webhooks, db),
+        #[cfg(feature = "postgres")]
+        Database::PostgreSqlDatabaseWrapper(db) => Webhooks::start(&config.webhooks, db),
+    }
+}
+
+/// Starts the delivery of the broadcast events to Kafka and to the webhooks, as configured.
+#[cfg_attr(not(feature = "webhooks"), allow(unused_variables))]
+fn build_external_sinks(config: &Config, database: &Database) -> Result<ExternalSinks, Error> {
+    #[cfg(feature = "kafka")]
+    let maybe_kafka_sink = config
+        .kafka
+        .as_ref()
+        .map(kafka_sink::KafkaSink::start)
+        .transpose()?;
+    Ok(ExternalSinks::start(
+        &config.delivery.clone().unwrap_or_default(),
+        #[cfg(feature = "kafka")]
+        maybe_kafka_sink,
+        #[cfg(feature = "webhooks")]
+        build_webhooks(config, database)?,
+    ))
+}
+
+/// Checks that the connected nodes are on the network of the stored events.
+async fn check_network(
+    config: &Config,
+    database: &Database,
+) -> Result<Option<NetworkIdentity>, Error> {
+    let network_check_config = config.network_check.clone().unwrap_or_default();
+    match database {
+        Database::SqliteDatabaseWrapper(db) => {
+            check_network_identity(&network_check_config, &config.connections, db).await
+        }
+        #[cfg(feature = "postgres")]
+        Database::PostgreSqlDatabaseWrapper(db) => {
+            check_network_identity(&network_check_config, &config.connections, db).await
+        }
+    }
+}
+
 async fn build_database(config: &StorageConfig) -> Result<Database, Error> {
     match config {
         StorageConfig::SqliteDbConfig {
@@ -270,35 +1423,145 @@ async fn build_database(config: &StorageConfig) -> Result<Database, Error> {
                 .context("Error instantiating sqlite database")?;
             Ok(Database::SqliteDatabaseWrapper(sqlite_database))
         }
+        #[cfg(feature = "postgres")]
         StorageConfig::PostgreSqlDbConfig {
             postgresql_config, ..
         } => {
             let postgres_database = PostgreSqlDatabase::new(postgresql_config.clone())
                 .await
                 .context("Error instantiating postgres database")?;
+            start_partition_maintenance(
+                postgres_database.clone(),
+                postgresql_config.partitioning.clone(),
+            );
             Ok(Database::PostgreSqlDatabaseWrapper(postgres_database))
         }
+        #[cfg(not(feature = "postgres"))]
+        StorageConfig::PostgreSqlDbConfig { .. } => Err(Error::msg(
+            "Unable to run: the sidecar was built without the `postgres` feature",
+        )),
+    }
+}
+
+fn build_event_listeners(
+    config: &Config,
+    maybe_capture_sender: Option<Sender<CapturedFrame>>,
+    gap_sender: Sender<EventIdGap>,
+    source_scores: &SourceScores,
+) -> Result<
+    (
+        Vec<EventListener>,
+        Vec<Receiver<SseEvent>>,
+        Vec<IngestionQueue>,
+    ),
+    Error,
+> {
+    let mut event_listeners = Vec::with_capacity(config.connections.len());
+    let mut sse_data_receivers = Vec::new();
+    let mut ingestion_queues = Vec::new();
+    for (index, connection) in config.connections.iter().enumerate() {
+        let (inbound_sse_data_sender, inbound_sse_data_receiver) =
+            mpsc_channel(config.inbound_channel_size.unwrap_or(DEFAULT_CHANNEL_SIZE));
+        sse_data_receivers.push(inbound_sse_data_receiver);
+        ingestion_queues.push(IngestionQueue::of(&inbound_sse_data_sender));
+        // Only the time of the latest keepalive matters, the others are dropped while it's queued.
+        let (keep_alive_sender, keep_alive_receiver) = mpsc_channel(1);
+        source_scores
+            .recorder(index)
+            .record_keep_alives(keep_alive_receiver);
+        let event_listener = builder(
+            connection,
+            inbound_sse_data_sender,
+            maybe_capture_sender.clone(),
+            gap_sender.clone(),
+            keep_alive_sender,
+        )?
+        .build();
+        event_listeners.push(event_listener?);
     }
+    Ok((event_listeners, sse_data_receivers, ingestion_queues))
+}
+
+/// Deduplicates the events received from the nodes, `sse_data_receivers` being given in the order
+/// of the connections.
+fn deduplicate_inbound_events(
+    config: &Config,
+    deduplicator: &Deduplicator,
+    sse_data_receivers: Vec<Receiver<SseEvent>>,
+    ingestion_queues: &mut Vec<IngestionQueue>,
+    source_scores: &SourceScores,
+) -> Vec<Receiver<SseEvent>> {
+    sse_data_receivers
+        .into_iter()
+        .enumerate()
+        .map(|(index, inbound_sse_data_receiver)| {
+            let (deduplicated_sse_data_sender, deduplicated_sse_data_receiver) =
+                mpsc_channel(config.inbound_channel_size.unwrap_or(DEFAULT_CHANNEL_SIZE));
+            ingestion_queues.push(IngestionQueue::of(&deduplicated_sse_data_sender));
+            deduplicator.start_deduplicating(
+                inbound_sse_data_receiver,
+                deduplicated_sse_data_sender,
+                source_scores.recorder(index),
+            );
+            deduplicated_sse_data_receiver
+        })
+        .collect()
+}
+
+/// Journals the events received from every node before they are handed to the event processors.
+fn journal_inbound_events(
+    config: &Config,
+    journal: &Journal,
+    sse_data_receivers: Vec<Receiver<SseEvent>>,
+    ingestion_queues: &mut Vec<IngestionQueue>,
+) -> Vec<Receiver<SseEvent>> {
+    sse_data_receivers
+        .into_iter()
+        .map(|inbound_sse_data_receiver| {
+            let (journaled_sse_data_sender, journaled_sse_data_receiver) =
+                mpsc_channel(config.inbound_channel_size.unwrap_or(DEFAULT_CHANNEL_SIZE));
+            ingestion_queues.push(IngestionQueue::of(&journaled_sse_data_sender));
+            journal.start_journaling(inbound_sse_data_receiver, journaled_sse_data_sender);
+            journaled_sse_data_receiver
+        })
+        .collect()
 }
 
-fn build_event_listeners(
+/// Stores the journaled events which weren't stored by a previous run or whose storing failed.
+/// They aren't attributed to any of the nodes, as they may have been received from any of them.
+fn start_journal_application(
     config: &Config,
-) -> Result<(Vec<EventListener>, Vec<Receiver<SseEvent>>), Error> {
-    let mut event_listeners = Vec::with_capacity(config.connections.len());
-    let mut sse_data_receivers = Vec::new();
-    for connection in &config.connections {
-        let (inbound_sse_data_sender, inbound_sse_data_receiver) =
-            mpsc_channel(config.inbound_channel_size.unwrap_or(DEFAULT_CHANNEL_SIZE));
-        sse_data_receivers.push(inbound_sse_data_receiver);
-        let event_listener = builder(connection, inbound_sse_data_sender)?.build();
-        event_listeners.push(event_listener?);
-    }
-    Ok((event_listeners, sse_data_receivers))
+    journal_config: &JournalConfig,
+    journal: &Journal,
+    processor: &EventProcessor<Database>,
+) {
+    let (journaled_sse_data_sender, journaled_sse_data_receiver) =
+        mpsc_channel(config.inbound_channel_size.unwrap_or(DEFAULT_CHANNEL_SIZE));
+    let processor = EventProcessor {
+        api_version_manager: ApiVersionManager::new(),
+        acknowledgements: StorageAcknowledgements {
+            maybe_journal: Some(journal.clone()),
+            maybe_deduplicator: None,
+        },
+        ..processor.clone()
+    };
+    spawn_sse_processor(
+        processor,
+        journaled_sse_data_receiver,
+        SourceScores::default().recorder(0),
+    );
+    journal.start_application(
+        journaled_sse_data_sender,
+        Duration::from_secs(journal_config.retry_interval_in_seconds()),
+    );
 }
 
 fn builder(
     connection: &Connection,
     inbound_sse_data_sender: Sender<SseEvent>,
+    maybe_capture_sender: Option<Sender<CapturedFrame>>,
+    gap_sender: Sender<EventIdGap>,
+    keep_alive_sender: Sender<()>,
 ) -> Result<EventListenerBuilder, Error> {
     let node_interface = NodeConnectionInterface {
         ip_address: IpAddr::from_str(&connection.ip_address)?,
@@ -321,9 +1584,13 @@ fn builder(
                 .sleep_between_keep_alive_checks_in_seconds
                 .unwrap_or(60) as u64,
         ),
-        no_message_timeout: Duration::from_secs(
-            connection.no_message_timeout_in_seconds.unwrap_or(120) as u64,
+        no_message_timeout: Duration::from_secs(connection.no_message_timeout_in_seconds()),
+        capture_sender: maybe_capture_sender,
+        keep_alive_sender: Some(keep_alive_sender),
+        gap_grace_period: Duration::from_secs(
+            connection.gap_grace_period_in_seconds.unwrap_or(5) as u64
         ),
+        gap_sender: Some(gap_sender),
     };
     Ok(event_listener_builder)
 }
@@ -332,12 +1599,173 @@ fn validate_config(config: &Config) -> Result<(), Error> {
     if config
         .connections
         .iter()
+        .chain(
+            config
+                .networks
+                .iter()
+                .flat_map(|network| network.connections.iter()),
+        )
         .any(|connection| connection.max_attempts < 1)
     {
         return Err(Error::msg(
             "Unable to run: max_attempts setting must be above 0 for the sidecar to attempt connection"
         ));
     }
+    validate_networks(config)?;
+    validate_features(config)?;
+    if config
+        .kafka
+        .as_ref()
+        .is_some_and(|kafka| kafka.brokers.is_empty())
+    {
+        return Err(Error::msg(
+            "Unable to run: at least one Kafka broker must be configured to publish events to Kafka",
+        ));
+    }
+    validate_event_derivers(config)?;
+    #[cfg(feature = "webhooks")]
+    validate_webhooks(config)?;
+    Ok(())
+}
+
+/// Checks that the sidecar was built with the features the config relies on.
+fn validate_features(config: &Config) -> Result<(), Error> {
+    if !cfg!(feature = "kafka") && config.kafka.is_some() {
+        return Err(Error::msg(
+            "Unable to run: the sidecar was built without the `kafka` feature to publish events to Kafka",
+        ));
+    }
+    if !cfg!(feature = "wasm-derivers") && !config.event_derivers.is_empty() {
+        return Err(Error::msg(
+            "Unable to run: the sidecar was built without the `wasm-derivers` feature to run event derivers",
+        ));
+    }
+    if !cfg!(feature = "webhooks") && !config.webhooks.is_empty() {
+        return Err(Error::msg(
+            "Unable to run: the sidecar was built without the `webhooks` feature to deliver events to webhooks",
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that the event derivers have unique names, which aren't those of the built-in derived
+/// events which are enabled.
+fn validate_event_derivers(config: &Config) -> Result<(), Error> {
+    validate_reserved_deriver_names(config)?;
+    let mut deriver_names = HashSet::new();
+    if let Some(deriver) = config
+        .event_derivers
+        .iter()
+        .find(|deriver| !deriver_names.insert(deriver.name.as_str()))
+    {
+        return Err(Error::msg(format!(
+            "Unable to run: event deriver name {} is used more than once",
+            deriver.name
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that the event derivers aren't named after the built-in derived events which are enabled.
+fn validate_reserved_deriver_names(config: &Config) -> Result<(), Error> {
+    let reserved_names = [
+        (
+            config.block_finality.is_some(),
+            BLOCK_FINALIZED_DERIVER,
+            "the block finality events",
+        ),
+        (
+            config.token_transfers.is_some(),
+            TOKEN_TRANSFER_DERIVER,
+            "the token transfer events",
+        ),
+        (
+            config.nft_events.is_some(),
+            NFT_EVENT_DERIVER,
+            "the NFT events",
+        ),
+        (
+            config.contract_events.is_some(),
+            CONTRACT_EVENT_DERIVER,
+            "the contract events",
+        ),
+    ];
+    for (enabled, name, events) in reserved_names {
+        if enabled
+            && config
+                .event_derivers
+                .iter()
+                .any(|deriver| deriver.name == name)
+        {
+            return Err(Error::msg(format!(
+                "Unable to run: event deriver name {} is reserved for {}",
+                name, events
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the webhooks have unique names and are registered for known event types.
+#[cfg(feature = "webhooks")]
+fn validate_webhooks(config: &Config) -> Result<(), Error> {
+    let mut webhook_names = HashSet::new();
+    for webhook in &config.webhooks {
+        if !webhook_names.insert(webhook.name.as_str()) {
+            return Err(Error::msg(format!(
+                "Unable to run: webhook name {} is used more than once",
+                webhook.name
+            )));
+        }
+        if let Some(event_type) = webhook
+            .event_types
+            .iter()
+            .find(|event_type| !WEBHOOK_EVENT_TYPES.contains(&event_type.as_str()))
+        {
+            return Err(Error::msg(format!(
+                "Unable to run: webhook {} is registered for unknown event type {}",
+                webhook.name, event_type
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the networks, if any, can be told apart in the URLs and in the storage.
+fn validate_networks(config: &Config) -> Result<(), Error> {
+    if config.networks.is_empty() {
+        return Ok(());
+    }
+    if !config.connections.is_empty() {
+        return Err(Error::msg(
+            "Unable to run: the connections must be configured per network when networks are configured",
+        ));
+    }
+    if config.capture.is_some() || config.journal.is_some() {
+        return Err(Error::msg(
+            "Unable to run: capturing upstream traffic and journaling events aren't supported with several networks",
+        ));
+    }
+    let mut names = HashSet::new();
+    for network in &config.networks {
+        let valid_name = !network.name.is_empty()
+            && network
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid_name {
+            return Err(Error::msg(format!(
+                "Unable to run: invalid network name '{}', only letters, digits, '-' and '_' are allowed",
+                network.name
+            )));
+        }
+        if !names.insert(network.name.as_str()) {
+            return Err(Error::msg(format!(
+                "Unable to run: network {} is configured more than once",
+                network.name
+            )));
+        }
+    }
     Ok(())
 }
 
@@ -349,360 +1777,600 @@ async fn flatten_handle<T>(handle: JoinHandle<Result<T, Error>>) -> Result<T, Er
     }
 }
 
-async fn handle_database_save_result<F>(
-    entity_name: &str,
-    entity_identifier: &str,
-    res: Result<u64, DatabaseWriteError>,
-    outbound_sse_data_sender: &Sender<(SseData, Option<Filter>, Option<String>)>,
+/// What the processing of an event needs of the event, apart from its data.
+struct Receipt {
+    id: u32,
+    source: Url,
+    received_at: SystemTime,
     inbound_filter: Filter,
     json_data: Option<String>,
-    build_sse_data: F,
-) where
-    F: FnOnce() -> SseData,
-{
-    match res {
-        Ok(_) => {
-            count_internal_event("main_inbound_sse_data", "db_save_end");
-            count_internal_event("main_inbound_sse_data", "outbound_sse_data_send_start");
-            if let Err(error) = outbound_sse_data_sender
-                .send((build_sse_data(), Some(inbound_filter), json_data))
+}
+
+impl Receipt {
+    fn split(sse_event: SseEvent) -> (SseData, Receipt) {
+        let receipt = Receipt {
+            id: sse_event.id,
+            source: sse_event.source,
+            received_at: sse_event.received_at,
+            inbound_filter: sse_event.inbound_filter,
+            json_data: sse_event.json_data,
+        };
+        (sse_event.data, receipt)
+    }
+}
+
+impl<Db: DatabaseReader + DatabaseWriter + Clone + Send + Sync> EventProcessor<Db> {
+    /// Stores the event and forwards it to the event stream server, then acknowledges it.
+    async fn process(&self, sse_event: SseEvent) {
+        let maybe_journal_sequence = sse_event.journal_sequence;
+        let maybe_content_hash = sse_event.content_hash;
+        let span = info_span!(parent: &sse_event.span, "process");
+        //don't do debug counting for ApiVersion since we don't store it
+        if !matches!(sse_event.data, SseData::ApiVersion(_) | SseData::Shutdown) {
+            count_internal_event("main_inbound_sse_data", "event_received_start");
+        }
+        let (sse_data, receipt) = Receipt::split(sse_event);
+        let stored = self
+            .handle_single_event(sse_data, receipt)
+            .instrument(span)
+            .await;
+        self.acknowledgements
+            .acknowledge(maybe_journal_sequence, maybe_content_hash, stored);
+    }
+
+    /// Function to handle single event in the sse_processor.
+    /// Returns false if the event couldn't be stored and should be handled again.
+    /// Returns true otherwise.
+    async fn handle_single_event(&self, sse_data: SseData, receipt: Receipt) -> bool {
+        match sse_data {
+            SseData::SidecarVersion(_) | SseData::Derived { .. } => {
+                //Do nothing -> the inbound shouldn't produce these events, they can be only produced by sidecar to the outbound
+                true
+            }
+            SseData::ApiVersion(version) => self.handle_api_version(version, receipt).await,
+            SseData::BlockAdded { block, block_hash } => {
+                self.handle_block_added(receipt, block_hash, block).await
+            }
+            SseData::DeployAccepted { deploy } => {
+                self.handle_deploy_accepted(receipt, deploy).await
+            }
+            SseData::DeployExpired { deploy_hash } => {
+                self.handle_deploy_expired(receipt, deploy_hash).await
+            }
+            SseData::DeployProcessed { .. } => {
+                self.handle_deploy_processed(receipt, sse_data).await
+            }
+            SseData::Fault {
+                era_id,
+                timestamp,
+                public_key,
+            } => {
+                self.handle_fault(receipt, era_id, timestamp, public_key)
+                    .await
+            }
+            SseData::FinalitySignature(fs) => self.handle_finality_signature(receipt, fs).await,
+            SseData::Step {
+                era_id,
+                execution_effect,
+            } => self.handle_step(receipt, era_id, execution_effect).await,
+            SseData::Unknown {
+                event_type,
+                payload,
+            } => {
+                self.handle_unknown_event(receipt, event_type, payload)
+                    .await
+            }
+            SseData::Shutdown => self.handle_shutdown(receipt).await,
+        }
+    }
+
+    /// Forwards the version if it is the newest yet. API versions aren't stored, so they are never
+    /// handled again.
+    async fn handle_api_version(&self, version: ProtocolVersion, receipt: Receipt) -> bool {
+        let mut manager_guard = self.api_version_manager.lock().await;
+        let changed_newest_version = manager_guard.store_version(version);
+        if changed_newest_version {
+            if let Err(error) = self
+                .outbound_sse_data_sender
+                .send((
+                    SseData::ApiVersion(version),
+                    Some(receipt.inbound_filter),
+                    None,
+                ))
                 .await
             {
-                count_internal_event("main_inbound_sse_data", "outbound_sse_data_send_end");
                 debug!(
                     "Error when sending to outbound_sse_data_sender. Error: {}",
                     error
                 );
-            } else {
-                count_internal_event("main_inbound_sse_data", "outbound_sse_data_send_end");
             }
         }
-        Err(DatabaseWriteError::UniqueConstraint(uc_err)) => {
-            count_internal_event("main_inbound_sse_data", "db_save_end");
-            debug!(
-                "Already received {} ({}), logged in event_log",
-                entity_name, entity_identifier,
-            );
-            trace!(?uc_err);
-        }
-        Err(other_err) => {
-            count_internal_event("main_inbound_sse_data", "db_save_end");
-            count_error(format!("db_save_error_{}", entity_name).as_str());
-            warn!(?other_err, "Unexpected error saving {}", entity_identifier);
+        drop(manager_guard);
+        if self.enable_event_logging {
+            info!(%version, "API Version");
         }
+        true
     }
-    count_internal_event("main_inbound_sse_data", "event_received_end");
-}
 
-/// Function to handle single event in the sse_processor.
-/// Returns false if the handling indicated that no other messages should be processed.
-/// Returns true otherwise.
-#[allow(clippy::too_many_lines)]
-async fn handle_single_event<Db: DatabaseReader + DatabaseWriter + Clone + Send + Sync>(
-    sse_event: SseEvent,
-    database: Db,
-    enable_event_logging: bool,
-    outbound_sse_data_sender: Sender<(SseData, Option<Filter>, Option<String>)>,
-    api_version_manager: GuardedApiVersionManager,
-) {
-    match sse_event.data {
-        SseData::ApiVersion(_) | SseData::Shutdown => {
-            //don't do debug counting for ApiVersion since we don't store it
+    async fn handle_block_added(
+        &self,
+        receipt: Receipt,
+        block_hash: BlockHash,
+        block: Box<JsonBlock>,
+    ) -> bool {
+        if self.enable_event_logging {
+            let hex_block_hash = HexFmt(block_hash.inner());
+            info!("Block Added: {:18}", hex_block_hash);
+            debug!("Block Added: {}", hex_block_hash);
         }
-        _ => {
-            count_internal_event("main_inbound_sse_data", "event_received_start");
+        let skew_in_milliseconds = clock_skew::observe_block_skew(
+            &self.ingestion_checks.clock_skew,
+            &receipt.source,
+            block.header.timestamp,
+            receipt.received_at,
+        );
+        let block_added = BlockAdded::new(block_hash, block.clone());
+        if self.ingestion_checks.verify_block_hashes {
+            if let Err(error) = block_added.verify() {
+                return self
+                    .handle_block_failing_integrity(block_added, error, receipt)
+                    .await;
+            }
         }
-    }
-    match sse_event.data {
-        SseData::SidecarVersion(_) => {
-            //Do nothing -> the inbound shouldn't produce this endpoint, it can be only produced by sidecar to the outbound
+        let maybe_ended_era_id = block
+            .header
+            .era_end
+            .as_ref()
+            .map(|_| block.header.era_id.value());
+        let (saved, stored) = self
+            .store_block_added(receipt, block_added, skew_in_milliseconds, || {
+                SseData::BlockAdded { block, block_hash }
+            })
+            .await;
+        if let (true, Some(era_id)) = (saved, maybe_ended_era_id) {
+            equivocation::alert_on_ejected_equivocators(&self.database, era_id).await;
         }
-        SseData::ApiVersion(version) => {
-            handle_api_version(
-                api_version_manager,
-                version,
-                &outbound_sse_data_sender,
-                sse_event.inbound_filter,
-                enable_event_logging,
+        stored
+    }
+
+    /// Stores the block and forwards the event built by `build_sse_data` if it wasn't stored yet.
+    /// Returns whether the block was saved, and whether it was stored, now or before.
+    async fn store_block_added(
+        &self,
+        receipt: Receipt,
+        block_added: BlockAdded,
+        skew_in_milliseconds: i64,
+        build_sse_data: impl FnOnce() -> SseData,
+    ) -> (bool, bool) {
+        let hex_block_hash = block_added.hex_encoded_hash();
+        count_internal_event("main_inbound_sse_data", "db_save_start");
+        let res = self
+            .database
+            .save_block_added(block_added, receipt.id, receipt.source.to_string())
+            .instrument(info_span!("db_write"))
+            .await;
+        let saved = self
+            .annotate_receipt(
+                &res,
+                &receipt,
+                &hex_block_hash,
+                clock_skew::RECEIPT_KIND,
+                Some(skew_in_milliseconds),
             )
             .await;
-        }
-        SseData::BlockAdded { block, block_hash } => {
-            if enable_event_logging {
-                let hex_block_hash = HexFmt(block_hash.inner());
-                info!("Block Added: {:18}", hex_block_hash);
-                debug!("Block Added: {}", hex_block_hash);
-            }
-            count_internal_event("main_inbound_sse_data", "db_save_start");
-            let res = database
-                .save_block_added(
-                    BlockAdded::new(block_hash, block.clone()),
-                    sse_event.id,
-                    sse_event.source.to_string(),
-                )
-                .await;
-            handle_database_save_result(
+        let stored = self
+            .handle_database_save_result(
                 "BlockAdded",
-                HexFmt(block_hash.inner()).to_string().as_str(),
+                &hex_block_hash,
                 res,
-                &outbound_sse_data_sender,
-                sse_event.inbound_filter,
-                sse_event.json_data,
-                || SseData::BlockAdded { block, block_hash },
+                receipt,
+                build_sse_data,
             )
             .await;
-        }
-        SseData::DeployAccepted { deploy } => {
-            if enable_event_logging {
-                let hex_deploy_hash = HexFmt(deploy.hash().inner());
-                info!("Deploy Accepted: {:18}", hex_deploy_hash);
-                debug!("Deploy Accepted: {}", hex_deploy_hash);
-            }
-            let deploy_accepted = DeployAccepted::new(deploy.clone());
-            count_internal_event("main_inbound_sse_data", "db_save_start");
-            let res = database
-                .save_deploy_accepted(deploy_accepted, sse_event.id, sse_event.source.to_string())
-                .await;
-            handle_database_save_result(
-                "DeployAccepted",
-                HexFmt(deploy.hash().inner()).to_string().as_str(),
-                res,
-                &outbound_sse_data_sender,
-                sse_event.inbound_filter,
-                sse_event.json_data,
-                || SseData::DeployAccepted { deploy },
-            )
+        (saved, stored)
+    }
+
+    /// Stores a block whose hashes don't match its contents marked as such. The block is not
+    /// forwarded to subscribers. Returns false if it couldn't be stored.
+    async fn handle_block_failing_integrity(
+        &self,
+        block_added: BlockAdded,
+        error: BlockValidationError,
+        receipt: Receipt,
+    ) -> bool {
+        let event_source_address = receipt.source.to_string();
+        error!(
+            block_hash = %block_added.hex_encoded_hash(),
+            source = %event_source_address,
+            "Block failed integrity verification and is not forwarded: {}",
+            error
+        );
+        metrics::BLOCK_INTEGRITY_FAILURES
+            .with_label_values(&[&event_source_address])
+            .inc();
+        count_internal_event("main_inbound_sse_data", "db_save_start");
+        let res = self
+            .database
+            .save_block_added_failing_integrity(block_added, receipt.id, event_source_address)
+            .instrument(info_span!("db_write"))
             .await;
-        }
-        SseData::DeployExpired { deploy_hash } => {
-            if enable_event_logging {
-                let hex_deploy_hash = HexFmt(deploy_hash.inner());
-                info!("Deploy Expired: {:18}", hex_deploy_hash);
-                debug!("Deploy Expired: {}", hex_deploy_hash);
+        count_internal_event("main_inbound_sse_data", "db_save_end");
+        let stored = match res {
+            Ok(_) | Err(DatabaseWriteError::UniqueConstraint(_)) => true,
+            Err(other_err) => {
+                count_error("db_save_error_BlockAdded");
+                warn!(
+                    ?other_err,
+                    "Unexpected error saving block failing integrity verification"
+                );
+                false
             }
-            count_internal_event("main_inbound_sse_data", "db_save_start");
-            let res = database
-                .save_deploy_expired(
-                    DeployExpired::new(deploy_hash),
-                    sse_event.id,
-                    sse_event.source.to_string(),
-                )
-                .await;
-            handle_database_save_result(
-                "DeployExpired",
-                HexFmt(deploy_hash.inner()).to_string().as_str(),
-                res,
-                &outbound_sse_data_sender,
-                sse_event.inbound_filter,
-                sse_event.json_data,
-                || SseData::DeployExpired { deploy_hash },
-            )
+        };
+        count_internal_event("main_inbound_sse_data", "event_received_end");
+        stored
+    }
+
+    async fn handle_deploy_accepted(&self, receipt: Receipt, deploy: Arc<Deploy>) -> bool {
+        let deploy_hash = *deploy.hash();
+        self.log_deploy_event("Deploy Accepted", &deploy_hash);
+        let deploy_accepted = DeployAccepted::new(deploy.clone());
+        count_internal_event("main_inbound_sse_data", "db_save_start");
+        let res = self
+            .database
+            .save_deploy_accepted(deploy_accepted, receipt.id, receipt.source.to_string())
+            .instrument(info_span!("db_write"))
             .await;
-        }
-        SseData::DeployProcessed {
-            deploy_hash,
-            account,
-            timestamp,
-            ttl,
-            dependencies,
-            block_hash,
-            execution_result,
-        } => {
-            if enable_event_logging {
-                let hex_deploy_hash = HexFmt(deploy_hash.inner());
-                info!("Deploy Processed: {:18}", hex_deploy_hash);
-                debug!("Deploy Processed: {}", hex_deploy_hash);
-            }
-            let deploy_processed = DeployProcessed::new(
-                deploy_hash.clone(),
-                account.clone(),
-                timestamp,
-                ttl,
-                dependencies.clone(),
-                block_hash.clone(),
-                execution_result.clone(),
-            );
-            count_internal_event("main_inbound_sse_data", "db_save_start");
-            let res = database
-                .save_deploy_processed(
-                    deploy_processed.clone(),
-                    sse_event.id,
-                    sse_event.source.to_string(),
-                )
-                .await;
+        let hex_deploy_hash = hex::encode(deploy_hash.inner());
+        self.annotate_receipt(
+            &res,
+            &receipt,
+            &hex_deploy_hash,
+            clock_skew::RECEIPT_KIND,
+            None,
+        )
+        .await;
+        self.handle_database_save_result("DeployAccepted", &hex_deploy_hash, res, receipt, || {
+            SseData::DeployAccepted { deploy }
+        })
+        .await
+    }
 
-            handle_database_save_result(
-                "DeployProcessed",
-                HexFmt(deploy_hash.inner()).to_string().as_str(),
-                res,
-                &outbound_sse_data_sender,
-                sse_event.inbound_filter,
-                sse_event.json_data,
-                || SseData::DeployProcessed {
-                    deploy_hash,
-                    account,
-                    timestamp,
-                    ttl,
-                    dependencies,
-                    block_hash,
-                    execution_result,
-                },
+    async fn handle_deploy_expired(&self, receipt: Receipt, deploy_hash: DeployHash) -> bool {
+        self.log_deploy_event("Deploy Expired", &deploy_hash);
+        count_internal_event("main_inbound_sse_data", "db_save_start");
+        let res = self
+            .database
+            .save_deploy_expired(
+                DeployExpired::new(deploy_hash),
+                receipt.id,
+                receipt.source.to_string(),
             )
+            .instrument(info_span!("db_write"))
             .await;
-        }
-        SseData::Fault {
-            era_id,
-            timestamp,
-            public_key,
-        } => {
-            let fault = Fault::new(era_id, public_key.clone(), timestamp);
-            warn!(%fault, "Fault reported");
-            count_internal_event("main_inbound_sse_data", "db_save_start");
-            let res = database
-                .save_fault(fault.clone(), sse_event.id, sse_event.source.to_string())
-                .await;
+        self.handle_database_save_result(
+            "DeployExpired",
+            &hex::encode(deploy_hash.inner()),
+            res,
+            receipt,
+            || SseData::DeployExpired { deploy_hash },
+        )
+        .await
+    }
 
-            handle_database_save_result(
-                "Fault",
-                format!("{:#?}", fault).as_str(),
-                res,
-                &outbound_sse_data_sender,
-                sse_event.inbound_filter,
-                sse_event.json_data,
-                || SseData::Fault {
-                    era_id,
-                    timestamp,
-                    public_key,
-                },
-            )
+    async fn handle_deploy_processed(&self, receipt: Receipt, sse_data: SseData) -> bool {
+        let Some(deploy_processed) = deploy_processed_of(&sse_data) else {
+            return true;
+        };
+        let deploy_hash = deploy_processed.deploy_hash();
+        self.log_deploy_event("Deploy Processed", &deploy_hash);
+        count_internal_event("main_inbound_sse_data", "db_save_start");
+        let res = self
+            .database
+            .save_deploy_processed(deploy_processed, receipt.id, receipt.source.to_string())
+            .instrument(info_span!("db_write"))
+            .await;
+        let hex_deploy_hash = hex::encode(deploy_hash.inner());
+        self.annotate_receipt(
+            &res,
+            &receipt,
+            &hex_deploy_hash,
+            clock_skew::PROCESSED_RECEIPT_KIND,
+            None,
+        )
+        .await;
+        self.handle_database_save_result("DeployProcessed", &hex_deploy_hash, res, receipt, || {
+            sse_data
+        })
+        .await
+    }
+
+    async fn handle_fault(
+        &self,
+        receipt: Receipt,
+        era_id: EraId,
+        timestamp: Timestamp,
+        public_key: PublicKey,
+    ) -> bool {
+        let fault = Fault::new(era_id, public_key.clone(), timestamp);
+        warn!(%fault, "Fault reported");
+        count_internal_event("main_inbound_sse_data", "db_save_start");
+        let res = self
+            .database
+            .save_fault(fault.clone(), receipt.id, receipt.source.to_string())
+            .instrument(info_span!("db_write"))
             .await;
+        self.handle_database_save_result(
+            "Fault",
+            format!("{:#?}", fault).as_str(),
+            res,
+            receipt,
+            || SseData::Fault {
+                era_id,
+                timestamp,
+                public_key,
+            },
+        )
+        .await
+    }
+
+    async fn handle_finality_signature(
+        &self,
+        receipt: Receipt,
+        fs: Box<casper_event_types::FinalitySignature>,
+    ) -> bool {
+        if self.enable_event_logging {
+            debug!(
+                "Finality Signature: {} for {}",
+                fs.signature(),
+                fs.block_hash()
+            );
         }
-        SseData::FinalitySignature(fs) => {
-            if enable_event_logging {
-                debug!(
-                    "Finality Signature: {} for {}",
-                    fs.signature(),
-                    fs.block_hash()
+        count_internal_event("main_inbound_sse_data", "db_save_start");
+        let res = self.save_finality_signature(&fs, &receipt).await;
+        self.handle_database_save_result("FinalitySignature", "", res, receipt, || {
+            SseData::FinalitySignature(fs)
+        })
+        .await
+    }
+
+    /// Stores the finality signature, with the invalid ones marked as such if they are verified.
+    async fn save_finality_signature(
+        &self,
+        fs: &casper_event_types::FinalitySignature,
+        receipt: &Receipt,
+    ) -> Result<u64, DatabaseWriteError> {
+        let finality_signature = FinalitySignature::new(Box::new(fs.clone()));
+        match self
+            .ingestion_checks
+            .verify_finality_signatures
+            .then(|| fs.verify())
+        {
+            Some(Err(error)) => {
+                let validator = fs.public_key().to_hex();
+                warn!(
+                    %validator,
+                    block_hash = %fs.block_hash(),
+                    "Invalid finality signature: {}",
+                    error
                 );
+                metrics::INVALID_FINALITY_SIGNATURES
+                    .with_label_values(&[&validator])
+                    .inc();
+                self.database
+                    .save_invalid_finality_signature(
+                        finality_signature,
+                        receipt.id,
+                        receipt.source.to_string(),
+                    )
+                    .instrument(info_span!("db_write"))
+                    .await
+            }
+            _ => {
+                self.database
+                    .save_finality_signature(
+                        finality_signature,
+                        receipt.id,
+                        receipt.source.to_string(),
+                    )
+                    .instrument(info_span!("db_write"))
+                    .await
             }
-            let finality_signature = FinalitySignature::new(fs.clone());
-            count_internal_event("main_inbound_sse_data", "db_save_start");
-            let res = database
-                .save_finality_signature(
-                    finality_signature.clone(),
-                    sse_event.id,
-                    sse_event.source.to_string(),
-                )
-                .await;
-            handle_database_save_result(
-                "FinalitySignature",
-                "",
-                res,
-                &outbound_sse_data_sender,
-                sse_event.inbound_filter,
-                sse_event.json_data,
-                || SseData::FinalitySignature(fs),
-            )
-            .await;
         }
-        SseData::Step {
-            era_id,
-            execution_effect,
-        } => {
-            let step = Step::new(era_id, execution_effect.clone());
-            if enable_event_logging {
-                info!("Step at era: {}", era_id.value());
+    }
+
+    async fn handle_step(
+        &self,
+        receipt: Receipt,
+        era_id: EraId,
+        execution_effect: Box<RawValue>,
+    ) -> bool {
+        let step = Step::new(era_id, execution_effect.clone());
+        if self.enable_event_logging {
+            info!("Step at era: {}", era_id.value());
+        }
+        count_internal_event("main_inbound_sse_data", "db_save_start");
+        let res = self
+            .database
+            .save_step(step, receipt.id, receipt.source.to_string())
+            .instrument(info_span!("db_write"))
+            .await;
+        self.handle_database_save_result(
+            "Step",
+            format!("{}", era_id.value()).as_str(),
+            res,
+            receipt,
+            || SseData::Step {
+                era_id,
+                execution_effect,
+            },
+        )
+        .await
+    }
+
+    async fn handle_unknown_event(
+        &self,
+        receipt: Receipt,
+        event_type: String,
+        payload: Box<RawValue>,
+    ) -> bool {
+        warn!(
+            "Received event of unknown type {} from {}, storing it as is",
+            event_type, receipt.source
+        );
+        let unknown_event = UnknownEvent::new(event_type.clone(), payload.clone());
+        count_internal_event("main_inbound_sse_data", "db_save_start");
+        let res = self
+            .database
+            .save_unknown_event(unknown_event, receipt.id, receipt.source.to_string())
+            .instrument(info_span!("db_write"))
+            .await;
+        self.handle_database_save_result(
+            "UnknownEvent",
+            event_type.clone().as_str(),
+            res,
+            receipt,
+            || SseData::Unknown {
+                event_type,
+                payload,
+            },
+        )
+        .await
+    }
+
+    /// Stores the shutdown of the node and forwards it. Shutdowns are never handled again.
+    async fn handle_shutdown(&self, receipt: Receipt) -> bool {
+        warn!("Node ({}) is unavailable", receipt.source.to_string());
+        let res = self
+            .database
+            .save_shutdown(receipt.id, receipt.source.to_string())
+            .instrument(info_span!("db_write"))
+            .await;
+        match res {
+            Ok(_) | Err(DatabaseWriteError::UniqueConstraint(_)) => {
+                // We push to outbound on UniqueConstraint error because in sse_server we match shutdowns to outbounds based on the filter they came from to prevent duplicates.
+                // But that also means that we need to pass through all the Shutdown events so the sse_server can determine to which outbound filters they need to be pushed (we
+                // don't store in DB the information from which filter did shutdown came).
+                if let Err(error) = self
+                    .outbound_sse_data_sender
+                    .send((
+                        SseData::Shutdown,
+                        Some(receipt.inbound_filter),
+                        receipt.json_data,
+                    ))
+                    .await
+                {
+                    debug!(
+                        "Error when sending to outbound_sse_data_sender. Error: {}",
+                        error
+                    );
+                }
             }
-            count_internal_event("main_inbound_sse_data", "db_save_start");
-            let res = database
-                .save_step(step, sse_event.id, sse_event.source.to_string())
-                .await;
-            handle_database_save_result(
-                "Step",
-                format!("{}", era_id.value()).as_str(),
-                res,
-                &outbound_sse_data_sender,
-                sse_event.inbound_filter,
-                sse_event.json_data,
-                || SseData::Step {
-                    era_id,
-                    execution_effect,
-                },
+            Err(other_err) => {
+                count_error("db_save_error_shutdown");
+                warn!(?other_err, "Unexpected error saving Shutdown")
+            }
+        }
+        true
+    }
+
+    fn log_deploy_event(&self, event_name: &str, deploy_hash: &DeployHash) {
+        if self.enable_event_logging {
+            let hex_deploy_hash = HexFmt(deploy_hash.inner());
+            info!("{}: {:18}", event_name, hex_deploy_hash);
+            debug!("{}: {}", event_name, hex_deploy_hash);
+        }
+    }
+
+    /// Annotates the stored entity with the time it was received, if enabled in the config. Returns
+    /// whether the entity was saved by `res`, rather than stored before.
+    async fn annotate_receipt(
+        &self,
+        res: &Result<u64, DatabaseWriteError>,
+        receipt: &Receipt,
+        hex_entity_hash: &str,
+        kind: &str,
+        maybe_skew_in_milliseconds: Option<i64>,
+    ) -> bool {
+        let saved = matches!(res, Ok(stored) if *stored > 0);
+        if saved && self.ingestion_checks.clock_skew.annotate_receipt_time {
+            clock_skew::annotate_receipt(
+                &self.database,
+                hex_entity_hash.to_string(),
+                kind,
+                &receipt.source,
+                receipt.received_at,
+                maybe_skew_in_milliseconds,
             )
             .await;
         }
-        SseData::Shutdown => handle_shutdown(sse_event, database, outbound_sse_data_sender).await,
+        saved
     }
-}
 
-async fn handle_shutdown<Db: DatabaseReader + DatabaseWriter + Clone + Send + Sync>(
-    sse_event: SseEvent,
-    sqlite_database: Db,
-    outbound_sse_data_sender: Sender<(SseData, Option<Filter>, Option<String>)>,
-) {
-    warn!("Node ({}) is unavailable", sse_event.source.to_string());
-    let res = sqlite_database
-        .save_shutdown(sse_event.id, sse_event.source.to_string())
-        .await;
-    match res {
-        Ok(_) | Err(DatabaseWriteError::UniqueConstraint(_)) => {
-            // We push to outbound on UniqueConstraint error because in sse_server we match shutdowns to outbounds based on the filter they came from to prevent duplicates.
-            // But that also means that we need to pass through all the Shutdown events so the sse_server can determine to which outbound filters they need to be pushed (we
-            // don't store in DB the information from which filter did shutdown came).
-            if let Err(error) = outbound_sse_data_sender
-                .send((
-                    SseData::Shutdown,
-                    Some(sse_event.inbound_filter),
-                    sse_event.json_data,
-                ))
-                .await
-            {
+    async fn handle_database_save_result(
+        &self,
+        entity_name: &str,
+        entity_identifier: &str,
+        res: Result<u64, DatabaseWriteError>,
+        receipt: Receipt,
+        build_sse_data: impl FnOnce() -> SseData,
+    ) -> bool {
+        count_internal_event("main_inbound_sse_data", "db_save_end");
+        let stored = match res {
+            // Events received from several nodes, or again after a reconnection, are only stored once.
+            Ok(0) => {
                 debug!(
-                    "Error when sending to outbound_sse_data_sender. Error: {}",
-                    error
+                    "Already received {} ({}), logged in event_log",
+                    entity_name, entity_identifier,
                 );
+                true
             }
-        }
-        Err(other_err) => {
-            count_error("db_save_error_shutdown");
-            warn!(?other_err, "Unexpected error saving Shutdown")
-        }
+            Ok(_) => {
+                metrics::STORED_EVENTS
+                    .with_label_values(&[entity_name])
+                    .inc();
+                self.forward_stored_event(build_sse_data(), receipt).await;
+                true
+            }
+            Err(DatabaseWriteError::UniqueConstraint(uc_err)) => {
+                debug!(
+                    "Already received {} ({}), logged in event_log",
+                    entity_name, entity_identifier,
+                );
+                trace!(?uc_err);
+                true
+            }
+            Err(other_err) => {
+                count_error(format!("db_save_error_{}", entity_name).as_str());
+                warn!(?other_err, "Unexpected error saving {}", entity_identifier);
+                false
+            }
+        };
+        count_internal_event("main_inbound_sse_data", "event_received_end");
+        stored
     }
-}
 
-async fn handle_api_version(
-    api_version_manager: std::sync::Arc<tokio::sync::Mutex<ApiVersionManager>>,
-    version: casper_types::ProtocolVersion,
-    outbound_sse_data_sender: &Sender<(SseData, Option<Filter>, Option<String>)>,
-    filter: Filter,
-    enable_event_logging: bool,
-) {
-    let mut manager_guard = api_version_manager.lock().await;
-    let changed_newest_version = manager_guard.store_version(version);
-    if changed_newest_version {
-        if let Err(error) = outbound_sse_data_sender
-            .send((SseData::ApiVersion(version), Some(filter), None))
-            .await
-        {
+    async fn forward_stored_event(&self, sse_data: SseData, receipt: Receipt) {
+        count_internal_event("main_inbound_sse_data", "outbound_sse_data_send_start");
+        let sent = self
+            .outbound_sse_data_sender
+            .send((sse_data, Some(receipt.inbound_filter), receipt.json_data))
+            .instrument(info_span!("enqueue_outbound"))
+            .await;
+        count_internal_event("main_inbound_sse_data", "outbound_sse_data_send_end");
+        if let Err(error) = sent {
             debug!(
                 "Error when sending to outbound_sse_data_sender. Error: {}",
                 error
             );
         }
     }
-    drop(manager_guard);
-    if enable_event_logging {
-        info!(%version, "API Version");
-    }
 }
 
 async fn sse_processor<Db: DatabaseReader + DatabaseWriter + Clone + Send + Sync + 'static>(
     inbound_sse_data_receiver: Receiver<SseEvent>,
-    outbound_sse_data_sender: Sender<(SseData, Option<Filter>, Option<String>)>,
-    database: Db,
+    processor: EventProcessor<Db>,
     database_supports_multithreaded_processing: bool,
-    enable_event_logging: bool,
-    api_version_manager: GuardedApiVersionManager,
+    source_recorder: SourceRecorder,
 ) -> Result<(), Error> {
     #[cfg(feature = "additional-metrics")]
     let metrics_tx = start_metrics_thread("sse_save".to_string());
@@ -710,10 +2378,8 @@ async fn sse_processor<Db: DatabaseReader + DatabaseWriter + Clone + Send + Sync
     if database_supports_multithreaded_processing {
         start_multi_threaded_events_consumer(
             inbound_sse_data_receiver,
-            outbound_sse_data_sender,
-            database,
-            enable_event_logging,
-            api_version_manager,
+            processor,
+            source_recorder,
             #[cfg(feature = "additional-metrics")]
             metrics_tx,
         )
@@ -721,10 +2387,8 @@ async fn sse_processor<Db: DatabaseReader + DatabaseWriter + Clone + Send + Sync
     } else {
         start_single_threaded_events_consumer(
             inbound_sse_data_receiver,
-            outbound_sse_data_sender,
-            database,
-            enable_event_logging,
-            api_version_manager,
+            processor,
+            source_recorder,
             #[cfg(feature = "additional-metrics")]
             metrics_tx,
         )
@@ -735,22 +2399,12 @@ async fn sse_processor<Db: DatabaseReader + DatabaseWriter + Clone + Send + Sync
 
 fn handle_events_in_thread<Db: DatabaseReader + DatabaseWriter + Clone + Send + Sync + 'static>(
     mut queue_rx: Receiver<SseEvent>,
-    database: Db,
-    outbound_sse_data_sender: Sender<(SseData, Option<Filter>, Option<String>)>,
-    api_version_manager: GuardedApiVersionManager,
-    enable_event_logging: bool,
+    processor: EventProcessor<Db>,
     #[cfg(feature = "additional-metrics")] metrics_sender: Sender<()>,
 ) {
     tokio::spawn(async move {
         while let Some(sse_event) = queue_rx.recv().await {
-            handle_single_event(
-                sse_event,
-                database.clone(),
-                enable_event_logging,
-                outbound_sse_data_sender.clone(),
-                api_version_manager.clone(),
-            )
-            .await;
+            processor.process(sse_event).await;
             #[cfg(feature = "additional-metrics")]
             let _ = metrics_sender.send(()).await;
         }
@@ -766,33 +2420,59 @@ fn build_queues(cache_size: usize) -> HashMap<Filter, (Sender<SseEvent>, Receive
     map
 }
 
-async fn start_multi_threaded_events_consumer<
-    Db: DatabaseReader + DatabaseWriter + Clone + Send + Sync + 'static,
->(
-    mut inbound_sse_data_receiver: Receiver<SseEvent>,
-    outbound_sse_data_sender: Sender<(SseData, Option<Filter>, Option<String>)>,
-    database: Db,
-    enable_event_logging: bool,
-    api_version_manager: GuardedApiVersionManager,
-    #[cfg(feature = "additional-metrics")] metrics_sender: Sender<()>,
-) {
+/// Starts the queues of the events of every inbound filter, and the queue of the events relating
+/// to an entity of the watch list, each handled by a task of its own.
+fn start_event_queues<Db: DatabaseReader + DatabaseWriter + Clone + Send + Sync + 'static>(
+    processor: &EventProcessor<Db>,
+    #[cfg(feature = "additional-metrics")] metrics_sender: &Sender<()>,
+) -> (HashMap<Filter, Sender<SseEvent>>, Sender<SseEvent>) {
     let mut senders_and_receivers_map = build_queues(DEFAULT_CHANNEL_SIZE);
     let mut senders_map = HashMap::new();
     for (filter, (tx, rx)) in senders_and_receivers_map.drain() {
         handle_events_in_thread(
             rx,
-            database.clone(),
-            outbound_sse_data_sender.clone(),
-            api_version_manager.clone(),
-            enable_event_logging,
+            processor.clone(),
             #[cfg(feature = "additional-metrics")]
             metrics_sender.clone(),
         );
         senders_map.insert(filter, tx);
     }
+    let (watched_tx, watched_rx) = mpsc_channel(DEFAULT_CHANNEL_SIZE);
+    handle_events_in_thread(
+        watched_rx,
+        processor.clone(),
+        #[cfg(feature = "additional-metrics")]
+        metrics_sender.clone(),
+    );
+    (senders_map, watched_tx)
+}
 
+/// Handles the events of every inbound filter in a queue of its own. Events relating to an entity
+/// of the watch list skip these queues for a dedicated one, so they aren't held up by a backlog.
+async fn start_multi_threaded_events_consumer<
+    Db: DatabaseReader + DatabaseWriter + Clone + Send + Sync + 'static,
+>(
+    mut inbound_sse_data_receiver: Receiver<SseEvent>,
+    processor: EventProcessor<Db>,
+    source_recorder: SourceRecorder,
+    #[cfg(feature = "additional-metrics")] metrics_sender: Sender<()>,
+) {
+    let (senders_map, watched_tx) = start_event_queues(
+        &processor,
+        #[cfg(feature = "additional-metrics")]
+        &metrics_sender,
+    );
     while let Some(sse_event) = inbound_sse_data_receiver.recv().await {
-        if let Some(tx) = senders_map.get(&sse_event.inbound_filter) {
+        source_recorder.record(&sse_event.data);
+        metrics::INBOUND_EVENTS
+            .with_label_values(&[sse_event.data.type_label()])
+            .inc();
+        let maybe_tx = if processor.watch_list.is_watched(&sse_event.data) {
+            Some(&watched_tx)
+        } else {
+            senders_map.get(&sse_event.inbound_filter)
+        };
+        if let Some(tx) = maybe_tx {
             tx.send(sse_event).await.unwrap()
         } else {
             error!(
@@ -808,21 +2488,13 @@ async fn start_single_threaded_events_consumer<
     Db: DatabaseReader + DatabaseWriter + Clone + Send + Sync,
 >(
     mut inbound_sse_data_receiver: Receiver<SseEvent>,
-    outbound_sse_data_sender: Sender<(SseData, Option<Filter>, Option<String>)>,
-    database: Db,
-    enable_event_logging: bool,
-    api_version_manager: GuardedApiVersionManager,
+    processor: EventProcessor<Db>,
+    source_recorder: SourceRecorder,
     #[cfg(feature = "additional-metrics")] metrics_sender: Sender<()>,
 ) {
     while let Some(sse_event) = inbound_sse_data_receiver.recv().await {
-        handle_single_event(
-            sse_event,
-            database.clone(),
-            enable_event_logging,
-            outbound_sse_data_sender.clone(),
-            api_version_manager.clone(),
-        )
-        .await;
+        source_recorder.record(&sse_event.data);
+        processor.process(sse_event).await;
         #[cfg(feature = "additional-metrics")]
         let _ = metrics_sender.send(()).await;
     }
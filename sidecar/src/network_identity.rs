@@ -0,0 +1,242 @@
+//! Check, made when the Sidecar starts, that the connected nodes are on the network of the stored
+//! events.
+//!
+//! Every node reports the name of its chainspec and the root hash of the global state at genesis
+//! on the `/status` endpoint of its REST server. The first network reported is saved in the
+//! database, so that a Sidecar later pointed at the nodes of another network, e.g. at testnet nodes
+//! while storing mainnet events, refuses to mix their events into the database, or only warns about
+//! it if so configured. The nodes which don't report their network, e.g. because they are
+//! unreachable when the Sidecar starts, aren't checked.
+
+use crate::{
+    source_scoring::node_label,
+    types::{
+        config::{Connection, NetworkCheckConfig, NetworkMismatchAction},
+        database::{DatabaseReadError, DatabaseReader, DatabaseWriter, NetworkIdentity},
+    },
+};
+use anyhow::{anyhow, Error};
+use serde_json::Value;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Fetches the network the node is on from the `/status` endpoint of its REST server.
+async fn fetch_network_identity(
+    client: &reqwest::Client,
+    connection: &Connection,
+) -> Result<NetworkIdentity, Error> {
+    let body = client
+        .get(format!(
+            "http://{}:{}/status",
+            connection.ip_address, connection.rest_port
+        ))
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    let status: Value = serde_json::from_slice(&body)?;
+    let field = |name: &str| {
+        status
+            .get(name)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("no {} in the status of the node", name))
+    };
+    Ok(NetworkIdentity {
+        chainspec_name: field("chainspec_name")?,
+        starting_state_root_hash: field("starting_state_root_hash")?,
+    })
+}
+
+/// Checks that the connected nodes are on the network of the stored events, saving the network of
+/// the nodes if none is saved yet. Returns the network of the stored events, or `None` if it isn't
+/// known yet and no node reported its network.
+///
+/// Returns an error if a node is on another network, unless `config` only asks for a warning.
+pub(crate) async fn check_network_identity<Db: DatabaseReader + DatabaseWriter>(
+    config: &NetworkCheckConfig,
+    connections: &[Connection],
+    database: &Db,
+) -> Result<Option<NetworkIdentity>, Error> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(config.timeout_in_seconds()))
+        .build()?;
+    let mut reported = Vec::new();
+    for connection in connections {
+        match fetch_network_identity(&client, connection).await {
+            Ok(network_identity) => reported.push((node_label(connection), network_identity)),
+            Err(error) => warn!(
+                node = %node_label(connection),
+                %error,
+                "Unable to check the network of the node"
+            ),
+        }
+    }
+    check_reported_networks(config.on_mismatch, reported, database).await
+}
+
+/// Compares the networks reported by the nodes, given by node label, with the network of the
+/// stored events, or with the one reported first if none is saved yet.
+async fn check_reported_networks<Db: DatabaseReader + DatabaseWriter>(
+    on_mismatch: NetworkMismatchAction,
+    reported: Vec<(String, NetworkIdentity)>,
+    database: &Db,
+) -> Result<Option<NetworkIdentity>, Error> {
+    let maybe_stored = match database.get_network_identity().await {
+        Ok(network_identity) => Some(network_identity),
+        Err(DatabaseReadError::NotFound) => None,
+        Err(error) => return Err(anyhow!("Error getting the stored network: {:?}", error)),
+    };
+    let expected = match maybe_stored.clone().or_else(|| {
+        reported
+            .first()
+            .map(|(_, network_identity)| network_identity.clone())
+    }) {
+        Some(expected) => expected,
+        None => return Ok(None),
+    };
+    if let Some(message) = mismatch_message(&reported, &expected, maybe_stored.is_some()) {
+        match on_mismatch {
+            NetworkMismatchAction::Refuse => {
+                return Err(anyhow!("Unable to run: {}", message));
+            }
+            NetworkMismatchAction::Warn => warn!("{}", message),
+        }
+    }
+    if maybe_stored.is_none() {
+        database
+            .save_network_identity(expected.clone())
+            .await
+            .map_err(|error| anyhow!("Error saving the network: {:?}", error))?;
+        info!(network = %expected.chainspec_name, "Saved the network of the events");
+    }
+    Ok(Some(expected))
+}
+
+/// Describes the nodes on another network than the `expected` one, if any.
+fn mismatch_message(
+    reported: &[(String, NetworkIdentity)],
+    expected: &NetworkIdentity,
+    is_stored: bool,
+) -> Option<String> {
+    let mismatches: Vec<String> = reported
+        .iter()
+        .filter(|(_, network_identity)| network_identity != expected)
+        .map(|(node, network_identity)| {
+            format!(
+                "node {} is on network {} (genesis state root hash {})",
+                node, network_identity.chainspec_name, network_identity.starting_state_root_hash
+            )
+        })
+        .collect();
+    if mismatches.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "{}, while {} on network {} (genesis state root hash {})",
+        mismatches.join(", "),
+        if is_stored {
+            "the stored events are"
+        } else {
+            "the other nodes are"
+        },
+        expected.chainspec_name,
+        expected.starting_state_root_hash
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::fake_database::FakeDatabase;
+
+    fn network(chainspec_name: &str) -> NetworkIdentity {
+        NetworkIdentity {
+            chainspec_name: chainspec_name.to_string(),
+            starting_state_root_hash: format!("{}-genesis", chainspec_name),
+        }
+    }
+
+    fn reported(networks: &[&str]) -> Vec<(String, NetworkIdentity)> {
+        networks
+            .iter()
+            .enumerate()
+            .map(|(index, chainspec_name)| (format!("node-{}", index), network(chainspec_name)))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn should_save_the_network_reported_first() {
+        let database = FakeDatabase::new();
+
+        let checked =
+            check_reported_networks(NetworkMismatchAction::Refuse, reported(&[]), &database)
+                .await
+                .unwrap();
+        assert_eq!(checked, None);
+
+        let checked = check_reported_networks(
+            NetworkMismatchAction::Refuse,
+            reported(&["casper", "casper"]),
+            &database,
+        )
+        .await
+        .unwrap();
+        assert_eq!(checked, Some(network("casper")));
+        assert_eq!(
+            database.get_network_identity().await.unwrap(),
+            network("casper")
+        );
+    }
+
+    #[tokio::test]
+    async fn should_refuse_nodes_of_another_network() {
+        let database = FakeDatabase::new();
+        database
+            .save_network_identity(network("casper"))
+            .await
+            .unwrap();
+
+        let error = check_reported_networks(
+            NetworkMismatchAction::Refuse,
+            reported(&["casper", "casper-test"]),
+            &database,
+        )
+        .await
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("node-1 is on network casper-test"));
+
+        let error = check_reported_networks(
+            NetworkMismatchAction::Refuse,
+            reported(&["casper-test"]),
+            &database,
+        )
+        .await
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("the stored events are on network casper"));
+    }
+
+    #[tokio::test]
+    async fn should_only_warn_of_nodes_of_another_network_if_configured() {
+        let database = FakeDatabase::new();
+
+        let checked = check_reported_networks(
+            NetworkMismatchAction::Warn,
+            reported(&["casper-test", "casper"]),
+            &database,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(checked, Some(network("casper-test")));
+        assert_eq!(
+            database.get_network_identity().await.unwrap(),
+            network("casper-test")
+        );
+    }
+}
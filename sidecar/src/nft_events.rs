@@ -0,0 +1,289 @@
+//! Events of CEP-78 NFT collections, decoded from the events the collection contracts emit.
+//!
+//! CEP-78 contracts configured with the `CES` events mode emit their events following the Casper
+//! Event Standard, so they are recognized among the execution effects of the deploys as the events
+//! of CEP-18 tokens are, see [crate::token_transfers].
+//!
+//! The `Mint`, `Burn`, `Transfer` and `Approval` events are stored in the `NftEvent` table and
+//! published as `NftEvent` events on the `/events/derived` stream, see [crate::deploy_decoding].
+//! The other events of the collections, e.g. updates of metadata or approvals of operators, are
+//! ignored.
+
+use crate::{
+    deploy_decoding::{DecodingWorker, DeployDecoder},
+    token_transfers::{decode_standard_events, parse_contract_hashes, EVENT_NAME_PREFIX},
+    types::{
+        config::NftEventsConfig,
+        database::{DatabaseWriteError, DatabaseWriter, NftEventEntry, NftEventKind},
+        sse_events::DeployProcessed,
+    },
+};
+use anyhow::Error;
+use async_trait::async_trait;
+use casper_types::{bytesrepr::FromBytes, Key, URefAddr};
+use std::collections::HashMap;
+
+/// Name under which `NftEvent` events are stored and published.
+pub(crate) const NFT_EVENT_DERIVER: &str = "NftEvent";
+
+/// Handle used to submit the processed deploys for the decoding of NFT events.
+pub(crate) type NftEvents = DecodingWorker;
+
+/// Recognizes the events of the configured collections among the transforms of the processed
+/// deploys.
+#[derive(Debug)]
+pub(crate) struct NftDecoder {
+    /// Hex-encoded hashes of the collection contracts, by the address of their events URef.
+    contract_hashes: HashMap<URefAddr, String>,
+}
+
+impl NftDecoder {
+    /// Returns an error if the hash of a contract or the URef of its events isn't valid.
+    pub(crate) fn new(config: &NftEventsConfig) -> Result<Self, Error> {
+        Ok(NftDecoder {
+            contract_hashes: parse_contract_hashes(&config.collections)?,
+        })
+    }
+
+    /// The events of the configured collections emitted by the deploy, in the order of its
+    /// transforms.
+    pub(crate) fn decode(&self, deploy_processed: &DeployProcessed) -> Vec<NftEventEntry> {
+        decode_standard_events(&self.contract_hashes, deploy_processed)
+            .into_iter()
+            .filter_map(|(contract_hash, event_id, event_bytes)| {
+                Some((contract_hash, event_id, NftEvent::from_bytes(&event_bytes)?))
+            })
+            .map(|(contract_hash, event_id, event)| NftEventEntry {
+                contract_hash,
+                event_id,
+                kind: event.kind,
+                token_id: event.token_id,
+                owner: event.owner.map(|key| key.to_formatted_string()),
+                recipient: event.recipient.map(|key| key.to_formatted_string()),
+                spender: event.spender.map(|key| key.to_formatted_string()),
+                deploy_hash: deploy_processed.hex_encoded_hash(),
+                block_hash: deploy_processed.hex_encoded_block_hash(),
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl DeployDecoder for NftDecoder {
+    const DERIVER: &'static str = NFT_EVENT_DERIVER;
+
+    type Entry = NftEventEntry;
+
+    async fn decode_deploy<Db: DatabaseWriter + Sync>(
+        &mut self,
+        _database: &Db,
+        deploy_processed: &DeployProcessed,
+    ) -> Vec<NftEventEntry> {
+        self.decode(deploy_processed)
+    }
+
+    async fn save<Db: DatabaseWriter + Sync>(
+        database: &Db,
+        nft_event: NftEventEntry,
+    ) -> Result<u64, DatabaseWriteError> {
+        database.save_nft_event(nft_event).await
+    }
+}
+
+/// An event of the CEP-78 standard about a single token.
+#[derive(Debug, PartialEq, Eq)]
+struct NftEvent {
+    kind: NftEventKind,
+    token_id: String,
+    owner: Option<Key>,
+    recipient: Option<Key>,
+    spender: Option<Key>,
+}
+
+impl NftEvent {
+    /// Parses the event, returning `None` if it isn't one of the recorded events.
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (name, remainder) = String::from_bytes(bytes).ok()?;
+        match name.strip_prefix(EVENT_NAME_PREFIX)? {
+            "Mint" => Self::parse_mint(remainder),
+            "Burn" => Self::parse_burn(remainder),
+            "Transfer" => Self::parse_transfer(remainder),
+            "Approval" => Self::parse_approval(remainder),
+            _ => None,
+        }
+    }
+
+    fn parse_mint(bytes: &[u8]) -> Option<Self> {
+        let (recipient, remainder) = Key::from_bytes(bytes).ok()?;
+        let (token_id, _) = String::from_bytes(remainder).ok()?;
+        Some(NftEvent {
+            kind: NftEventKind::Mint,
+            token_id,
+            owner: None,
+            recipient: Some(recipient),
+            spender: None,
+        })
+    }
+
+    fn parse_burn(bytes: &[u8]) -> Option<Self> {
+        let (owner, remainder) = Key::from_bytes(bytes).ok()?;
+        let (token_id, remainder) = String::from_bytes(remainder).ok()?;
+        let (burner, _) = Key::from_bytes(remainder).ok()?;
+        Some(NftEvent {
+            kind: NftEventKind::Burn,
+            token_id,
+            owner: Some(owner),
+            recipient: None,
+            spender: Some(burner),
+        })
+    }
+
+    fn parse_transfer(bytes: &[u8]) -> Option<Self> {
+        let (owner, remainder) = Key::from_bytes(bytes).ok()?;
+        let (spender, remainder) = Option::<Key>::from_bytes(remainder).ok()?;
+        let (recipient, remainder) = Key::from_bytes(remainder).ok()?;
+        let (token_id, _) = String::from_bytes(remainder).ok()?;
+        Some(NftEvent {
+            kind: NftEventKind::Transfer,
+            token_id,
+            owner: Some(owner),
+            recipient: Some(recipient),
+            spender,
+        })
+    }
+
+    fn parse_approval(bytes: &[u8]) -> Option<Self> {
+        let (owner, remainder) = Key::from_bytes(bytes).ok()?;
+        let (spender, remainder) = Key::from_bytes(remainder).ok()?;
+        let (token_id, _) = String::from_bytes(remainder).ok()?;
+        Some(NftEvent {
+            kind: NftEventKind::Approval,
+            token_id,
+            owner: Some(owner),
+            recipient: None,
+            spender: Some(spender),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{fake_database::FakeDatabase, shared::recv_derived_payload},
+        token_transfers::tests::{deploy_processed_with, event_transform},
+        types::{config::TokenConfig, database::DatabaseReader},
+    };
+    use casper_types::{
+        account::AccountHash, bytesrepr::ToBytes, testing::TestRng, AccessRights, URef,
+    };
+    use tokio::sync::mpsc::channel;
+
+    const CONTRACT_HASH: &str = "0202020202020202020202020202020202020202020202020202020202020202";
+
+    /// The serialized name and fields of an event.
+    fn event_fields(name: &str, fields: Vec<Vec<u8>>) -> Vec<u8> {
+        let mut bytes = name.to_string().to_bytes().unwrap();
+        for mut field in fields {
+            bytes.append(&mut field);
+        }
+        bytes
+    }
+
+    fn field<T: ToBytes>(value: T) -> Vec<u8> {
+        value.to_bytes().unwrap()
+    }
+
+    fn key(byte: u8) -> Key {
+        Key::Account(AccountHash::new([byte; 32]))
+    }
+
+    fn config(events_uref_addr: URefAddr) -> NftEventsConfig {
+        NftEventsConfig {
+            collections: vec![TokenConfig {
+                contract_hash: CONTRACT_HASH.to_string(),
+                events_uref: URef::new(events_uref_addr, AccessRights::READ_ADD_WRITE)
+                    .to_formatted_string(),
+            }],
+            queue_size: None,
+        }
+    }
+
+    #[test]
+    fn should_decode_events_of_configured_collections() {
+        let mut rng = TestRng::new();
+        let token_id = || field("1".to_string());
+        let metadata = || field("{}".to_string());
+        let mint = event_fields("event_Mint", vec![field(key(2)), token_id(), metadata()]);
+        let transfer = event_fields(
+            "event_Transfer",
+            vec![
+                field(key(2)),
+                field(Some(key(4))),
+                field(key(3)),
+                token_id(),
+            ],
+        );
+        let metadata_updated = event_fields("event_MetadataUpdated", vec![token_id(), metadata()]);
+        let deploy_processed = DeployProcessed::random(&mut rng, None).with_transforms(vec![
+            event_transform([7; 32], 0, mint.clone()),
+            event_transform([7; 32], 1, transfer),
+            event_transform([7; 32], 2, metadata_updated),
+            event_transform([8; 32], 3, mint),
+        ]);
+
+        let nft_events = NftDecoder::new(&config([7; 32]))
+            .unwrap()
+            .decode(&deploy_processed);
+
+        assert_eq!(nft_events.len(), 2);
+        assert_eq!(nft_events[0].contract_hash, CONTRACT_HASH);
+        assert_eq!(nft_events[0].kind, NftEventKind::Mint);
+        assert_eq!(nft_events[0].token_id, "1");
+        assert_eq!(nft_events[0].owner, None);
+        assert_eq!(nft_events[0].recipient, Some(key(2).to_formatted_string()));
+        assert_eq!(nft_events[1].event_id, 1);
+        assert_eq!(nft_events[1].kind, NftEventKind::Transfer);
+        assert_eq!(nft_events[1].owner, Some(key(2).to_formatted_string()));
+        assert_eq!(nft_events[1].recipient, Some(key(3).to_formatted_string()));
+        assert_eq!(nft_events[1].spender, Some(key(4).to_formatted_string()));
+        let deploy_hash = deploy_processed.hex_encoded_hash();
+        assert_eq!(nft_events[1].deploy_hash, deploy_hash);
+    }
+
+    #[tokio::test]
+    async fn should_store_and_publish_nft_events() {
+        let mut rng = TestRng::new();
+        let database = FakeDatabase::new();
+        let (outbound_sender, mut outbound_receiver) = channel(10);
+        let config = config([7; 32]);
+        let nft_events = NftEvents::start(
+            NftDecoder::new(&config).unwrap(),
+            config.queue_size(),
+            database.clone(),
+            outbound_sender,
+        );
+        let sse_data = deploy_processed_with(
+            &mut rng,
+            vec![event_transform(
+                [7; 32],
+                5,
+                event_fields(
+                    "event_Approval",
+                    vec![field(key(2)), field(key(3)), field("abc".to_string())],
+                ),
+            )],
+        );
+
+        nft_events.submit(&sse_data);
+
+        let payload = recv_derived_payload(&mut outbound_receiver, NFT_EVENT_DERIVER).await;
+        assert_eq!(payload["kind"], "Approval");
+        assert_eq!(payload["token_id"], "abc");
+        let stored = database
+            .get_nft_events(CONTRACT_HASH, Some("abc"), None, 10)
+            .await
+            .expect("NFT event was not stored");
+        assert_eq!(stored.len(), 1);
+    }
+}
@@ -0,0 +1,157 @@
+//! Access to a node's JSON-RPC server, shared by the features requesting context the event stream
+//! doesn't carry.
+
+use anyhow::{anyhow, Context, Error};
+use serde_json::{json, Value};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+use warp::http::header::CONTENT_TYPE;
+
+/// Minimal JSON-RPC 2.0 client of the node.
+#[derive(Debug)]
+pub(crate) struct RpcClient {
+    client: reqwest::Client,
+    address: String,
+    next_id: AtomicU64,
+}
+
+impl RpcClient {
+    /// Creates a client of the JSON-RPC endpoint at `address`, e.g. `http://127.0.0.1:7777/rpc`.
+    pub(crate) fn new(address: String) -> Self {
+        RpcClient {
+            client: reqwest::Client::new(),
+            address,
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Calls `method` and returns the `result` of the response.
+    pub(crate) async fn call(&self, method: &str, params: Value) -> Result<Value, Error> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": self.next_id.fetch_add(1, Ordering::Relaxed),
+            "method": method,
+            "params": params,
+        });
+        let response = self
+            .client
+            .post(&self.address)
+            .header(CONTENT_TYPE, "application/json")
+            .body(serde_json::to_vec(&body)?)
+            .send()
+            .await
+            .with_context(|| format!("Error sending {} request", method))?
+            .error_for_status()?;
+        let mut response: Value = serde_json::from_slice(&response.bytes().await?)
+            .with_context(|| format!("Error parsing {} response", method))?;
+        if let Some(error) = response.get("error").filter(|error| !error.is_null()) {
+            return Err(anyhow!("{} failed: {}", method, error));
+        }
+        response
+            .get_mut("result")
+            .map(Value::take)
+            .ok_or_else(|| anyhow!("no result in {} response", method))
+    }
+}
+
+/// Results of RPC requests, evicting the oldest entry once `capacity` is reached.
+pub(crate) struct ResponseCache {
+    capacity: usize,
+    entries: HashMap<String, Value>,
+    insertion_order: VecDeque<String>,
+}
+
+impl ResponseCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        ResponseCache {
+            capacity,
+            entries: HashMap::new(),
+            insertion_order: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<&Value> {
+        self.entries.get(key)
+    }
+
+    pub(crate) fn insert(&mut self, key: String, value: Value) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.insert(key.clone(), value).is_some() {
+            return;
+        }
+        self.insertion_order.push_back(key);
+        while self.insertion_order.len() > self.capacity {
+            if let Some(oldest) = self.insertion_order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// Spaces out requests so that at most `max_requests_per_second` are made.
+pub(crate) struct RateLimiter {
+    interval: Duration,
+    next_allowed: Option<Instant>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(max_requests_per_second: u32) -> Self {
+        RateLimiter {
+            interval: Duration::from_secs(1) / max_requests_per_second.max(1),
+            next_allowed: None,
+        }
+    }
+
+    /// Reserves the next slot for a request made at `now` or later and returns how long to wait
+    /// for it.
+    fn reserve(&mut self, now: Instant) -> Duration {
+        let slot = match self.next_allowed {
+            Some(next_allowed) if next_allowed > now => next_allowed,
+            _ => now,
+        };
+        self.next_allowed = Some(slot + self.interval);
+        slot - now
+    }
+
+    pub(crate) async fn acquire(&mut self) {
+        let wait = self.reserve(Instant::now());
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_should_evict_oldest_entries() {
+        let mut cache = ResponseCache::new(2);
+        cache.insert("a".to_string(), json!(1));
+        cache.insert("b".to_string(), json!(2));
+        cache.insert("a".to_string(), json!(3));
+        cache.insert("c".to_string(), json!(4));
+
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(cache.get("b"), Some(&json!(2)));
+        assert_eq!(cache.get("c"), Some(&json!(4)));
+    }
+
+    #[test]
+    fn rate_limiter_should_space_out_requests() {
+        let mut rate_limiter = RateLimiter::new(4);
+        let now = Instant::now();
+
+        assert_eq!(rate_limiter.reserve(now), Duration::ZERO);
+        assert_eq!(rate_limiter.reserve(now), Duration::from_millis(250));
+        assert_eq!(rate_limiter.reserve(now), Duration::from_millis(500));
+        let later = now + Duration::from_secs(2);
+        assert_eq!(rate_limiter.reserve(later), Duration::ZERO);
+    }
+}
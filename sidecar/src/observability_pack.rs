@@ -0,0 +1,315 @@
+//! A ready-made Grafana dashboard and Prometheus alert rules for the sidecar, generated from the
+//! descriptions of its metrics so that they never refer to metrics which don't exist.
+
+use casper_event_types::metrics::{metric_descriptions, MetricDescription, MetricKind};
+use serde_json::{json, Value};
+
+/// The range over which counters and histograms are turned into rates.
+const RATE_RANGE: &str = "5m";
+/// The quantile of the histograms plotted on the dashboard.
+const HISTOGRAM_QUANTILE: f64 = 0.99;
+const PANEL_WIDTH: usize = 12;
+const PANEL_HEIGHT: usize = 8;
+/// The number of columns of panels of the dashboard, whose grid is 24 units wide.
+const PANELS_PER_ROW: usize = 24 / PANEL_WIDTH;
+
+/// An alert rule on a single metric. `{metric}` in the expression is replaced with the name of the
+/// metric.
+struct AlertRule {
+    alert: &'static str,
+    metric: &'static str,
+    expr: &'static str,
+    for_duration: &'static str,
+    severity: &'static str,
+    summary: &'static str,
+}
+
+const ALERT_RULES: [AlertRule; 13] = [
+    AlertRule {
+        alert: "SidecarNodeNotConnected",
+        metric: "node_statuses",
+        expr: "{metric} != 2",
+        for_duration: "5m",
+        severity: "warning",
+        summary: "The sidecar isn't connected to node {{ $labels.node }}.",
+    },
+    AlertRule {
+        alert: "SidecarDatabasePoolSaturated",
+        metric: "db_pool_saturated",
+        expr: "{metric} == 1",
+        for_duration: "5m",
+        severity: "warning",
+        summary: "The database pool of the sidecar is saturated.",
+    },
+    AlertRule {
+        alert: "SidecarDatabasePoolTimeouts",
+        metric: "db_pool_timeouts",
+        expr: "increase({metric}[5m]) > 0",
+        for_duration: "0m",
+        severity: "critical",
+        summary: "Acquiring a connection from the database pool of the sidecar timed out.",
+    },
+    AlertRule {
+        alert: "SidecarSourceBehindHead",
+        metric: "source_blocks_behind_head",
+        expr: "{metric} > 5",
+        for_duration: "10m",
+        severity: "warning",
+        summary: "The sidecar is more than 5 blocks behind node {{ $labels.node }}.",
+    },
+    AlertRule {
+        alert: "SidecarUpstreamInactive",
+        metric: "upstream_seconds_since_last_bytes",
+        expr: "{metric} > 120",
+        for_duration: "1m",
+        severity: "warning",
+        summary: "Nothing was received on the event stream of node {{ $labels.node }} for over 2 minutes.",
+    },
+    AlertRule {
+        alert: "SidecarSourceHeadDivergence",
+        metric: "source_head_divergence",
+        expr: "{metric} > 5",
+        for_duration: "10m",
+        severity: "warning",
+        summary: "The latest blocks of the connected nodes are more than 5 blocks apart.",
+    },
+    AlertRule {
+        alert: "SidecarBlockIntegrityFailures",
+        metric: "block_integrity_failures",
+        expr: "increase({metric}[15m]) > 0",
+        for_duration: "0m",
+        severity: "critical",
+        summary: "Node {{ $labels.source }} sent blocks whose hashes don't match their contents.",
+    },
+    AlertRule {
+        alert: "SidecarInvalidFinalitySignatures",
+        metric: "invalid_finality_signatures",
+        expr: "increase({metric}[15m]) > 0",
+        for_duration: "0m",
+        severity: "warning",
+        summary: "Finality signatures of validator {{ $labels.validator }} failed verification.",
+    },
+    AlertRule {
+        alert: "SidecarClockSkew",
+        metric: "clock_skew_excesses",
+        expr: "increase({metric}[15m]) > 0",
+        for_duration: "0m",
+        severity: "warning",
+        summary: "Blocks from node {{ $labels.node }} were received too far from their timestamp.",
+    },
+    AlertRule {
+        alert: "SidecarOutboundDrops",
+        metric: "outbound_drops",
+        expr: "sum by (filter, reason) (rate({metric}[5m])) > 0",
+        for_duration: "5m",
+        severity: "warning",
+        summary: "Events of {{ $labels.filter }} are dropped for subscribers ({{ $labels.reason }}).",
+    },
+    AlertRule {
+        alert: "SidecarKafkaPublishingFailures",
+        metric: "kafka_messages",
+        expr: "sum by (outcome) (rate({metric}{outcome!=\"published\"}[5m])) > 0",
+        for_duration: "5m",
+        severity: "warning",
+        summary: "Ingested events aren't published to Kafka ({{ $labels.outcome }}).",
+    },
+    AlertRule {
+        alert: "SidecarJournalBacklog",
+        metric: "journal_unapplied_events",
+        expr: "{metric} > 1000",
+        for_duration: "10m",
+        severity: "warning",
+        summary: "Journaled events aren't being stored in the database.",
+    },
+    AlertRule {
+        alert: "SidecarEjectedEquivocators",
+        metric: "ejected_equivocators",
+        expr: "increase({metric}[1h]) > 0",
+        for_duration: "0m",
+        severity: "info",
+        summary: "Validator {{ $labels.validator }} equivocated and was ejected.",
+    },
+];
+
+/// Returns the Grafana dashboard and the Prometheus alert rules of the sidecar, the latter as a
+/// rule file in its JSON form.
+pub(crate) fn observability_pack() -> Value {
+    let descriptions = metric_descriptions();
+    json!({
+        "dashboard": dashboard(&descriptions),
+        "alert_rules": alert_rules(&descriptions),
+    })
+}
+
+/// Returns a dashboard with a panel plotting every metric.
+fn dashboard(descriptions: &[MetricDescription]) -> Value {
+    let panels: Vec<Value> = descriptions
+        .iter()
+        .enumerate()
+        .map(|(index, description)| {
+            json!({
+                "id": index + 1,
+                "type": "timeseries",
+                "title": description.name,
+                "description": description.help,
+                "datasource": { "type": "prometheus", "uid": "${datasource}" },
+                "gridPos": {
+                    "h": PANEL_HEIGHT,
+                    "w": PANEL_WIDTH,
+                    "x": (index % PANELS_PER_ROW) * PANEL_WIDTH,
+                    "y": (index / PANELS_PER_ROW) * PANEL_HEIGHT,
+                },
+                "targets": [{
+                    "refId": "A",
+                    "expr": panel_expr(description),
+                    "legendFormat": legend_format(description),
+                }],
+            })
+        })
+        .collect();
+    json!({
+        "title": "Casper Event Sidecar",
+        "uid": "casper-event-sidecar",
+        "tags": ["casper", "sidecar"],
+        "timezone": "browser",
+        "refresh": "30s",
+        "time": { "from": "now-6h", "to": "now" },
+        "templating": {
+            "list": [{
+                "name": "datasource",
+                "label": "Data source",
+                "type": "datasource",
+                "query": "prometheus",
+            }],
+        },
+        "panels": panels,
+    })
+}
+
+/// The query plotting the metric: gauges as they are, counters as rates and histograms as their
+/// 99th percentile.
+fn panel_expr(description: &MetricDescription) -> String {
+    let by = |extra_labels: &[&str]| {
+        let labels: Vec<&str> = extra_labels
+            .iter()
+            .copied()
+            .chain(description.labels.iter().map(String::as_str))
+            .collect();
+        if labels.is_empty() {
+            String::new()
+        } else {
+            format!(" by ({})", labels.join(", "))
+        }
+    };
+    match description.kind {
+        MetricKind::Gauge => description.name.clone(),
+        MetricKind::Counter => format!(
+            "sum{} (rate({}[{}]))",
+            by(&[]),
+            description.name,
+            RATE_RANGE
+        ),
+        MetricKind::Histogram => format!(
+            "histogram_quantile({}, sum{} (rate({}_bucket[{}])))",
+            HISTOGRAM_QUANTILE,
+            by(&["le"]),
+            description.name,
+            RATE_RANGE
+        ),
+    }
+}
+
+fn legend_format(description: &MetricDescription) -> String {
+    if description.labels.is_empty() {
+        return description.name.clone();
+    }
+    description
+        .labels
+        .iter()
+        .map(|label| format!("{{{{{}}}}}", label))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Returns the rule file of the alert rules whose metric is described.
+fn alert_rules(descriptions: &[MetricDescription]) -> Value {
+    let rules: Vec<Value> = ALERT_RULES
+        .iter()
+        .filter_map(|rule| {
+            let description = descriptions
+                .iter()
+                .find(|description| description.name == rule.metric)?;
+            Some(json!({
+                "alert": rule.alert,
+                "expr": rule.expr.replace("{metric}", &description.name),
+                "for": rule.for_duration,
+                "labels": { "severity": rule.severity },
+                "annotations": {
+                    "summary": rule.summary,
+                    "description": description.help,
+                },
+            }))
+        })
+        .collect();
+    json!({
+        "groups": [{
+            "name": "casper-event-sidecar",
+            "rules": rules,
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_alert_rule_should_refer_to_a_metric_of_the_sidecar() {
+        let descriptions = metric_descriptions();
+        for rule in &ALERT_RULES {
+            assert!(
+                descriptions
+                    .iter()
+                    .any(|description| description.name == rule.metric),
+                "{} refers to unknown metric {}",
+                rule.alert,
+                rule.metric
+            );
+        }
+        let pack = observability_pack();
+        assert_eq!(
+            pack["alert_rules"]["groups"][0]["rules"]
+                .as_array()
+                .unwrap()
+                .len(),
+            ALERT_RULES.len()
+        );
+    }
+
+    #[test]
+    fn should_plot_every_metric_of_the_sidecar() {
+        let descriptions = metric_descriptions();
+        let pack = observability_pack();
+        let panels = pack["dashboard"]["panels"].as_array().unwrap();
+        assert_eq!(panels.len(), descriptions.len());
+
+        let panel_of = |name: &str| {
+            panels
+                .iter()
+                .find(|panel| panel["title"] == name)
+                .unwrap_or_else(|| panic!("no panel of {}", name))
+        };
+        assert_eq!(
+            panel_of("outbound_events")["targets"][0]["expr"],
+            "sum by (filter, event_type) (rate(outbound_events[5m]))"
+        );
+        assert_eq!(
+            panel_of("db_pool_acquire_times")["targets"][0]["expr"],
+            "histogram_quantile(0.99, sum by (le) (rate(db_pool_acquire_times_bucket[5m])))"
+        );
+        assert_eq!(
+            panel_of("source_head_heights")["targets"][0]["legendFormat"],
+            "{{node}}"
+        );
+    }
+}
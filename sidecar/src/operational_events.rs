@@ -0,0 +1,167 @@
+//! Queryable history of what the sidecar itself did: its starts and stops, failovers between the
+//! nodes, event IDs skipped by the nodes and runs of the retention.
+//!
+//! As for the audit log, events are handed over to a background task which writes them to the
+//! `OperationalEvent` table in the order in which they were recorded, so recording an event never
+//! delays the component it comes from.
+
+use crate::types::database::DatabaseWriter;
+use casper_event_listener::EventIdGap;
+use tokio::sync::{
+    mpsc::{unbounded_channel, Receiver, UnboundedSender},
+    oneshot,
+};
+use tracing::warn;
+
+/// Kinds of events of the sidecar recorded in its operational history.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum OperationalEventKind {
+    /// The sidecar started with a freshly loaded configuration.
+    Started,
+    /// The sidecar stopped.
+    Stopped,
+    /// The sidecar failed over from a lost node to another.
+    Failover,
+    /// A node skipped event IDs, which are fetched again.
+    GapDetected,
+    /// The retention deleted events.
+    PruningRun,
+}
+
+impl OperationalEventKind {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            OperationalEventKind::Started => "started",
+            OperationalEventKind::Stopped => "stopped",
+            OperationalEventKind::Failover => "failover",
+            OperationalEventKind::GapDetected => "gap_detected",
+            OperationalEventKind::PruningRun => "pruning_run",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct OperationalEventRecord {
+    kind: OperationalEventKind,
+    details: Option<String>,
+    /// Notified once the event is written.
+    maybe_written_sender: Option<oneshot::Sender<()>>,
+}
+
+/// Handle used to record events in the operational history. Cloned handles share the same writer
+/// task.
+#[derive(Clone, Debug)]
+pub(crate) struct OperationalEvents {
+    sender: UnboundedSender<OperationalEventRecord>,
+}
+
+impl OperationalEvents {
+    /// Spawns the task writing recorded events to the given database.
+    pub(crate) fn start<Db: DatabaseWriter + Send + Sync + 'static>(database: Db) -> Self {
+        let (sender, mut receiver) = unbounded_channel::<OperationalEventRecord>();
+        tokio::spawn(async move {
+            while let Some(record) = receiver.recv().await {
+                if let Err(error) = database
+                    .save_operational_event(record.kind.as_str().to_string(), record.details)
+                    .await
+                {
+                    warn!(
+                        ?error,
+                        kind = record.kind.as_str(),
+                        "Error saving operational event"
+                    );
+                }
+                if let Some(written_sender) = record.maybe_written_sender {
+                    let _ = written_sender.send(());
+                }
+            }
+        });
+        OperationalEvents { sender }
+    }
+
+    pub(crate) fn record(&self, kind: OperationalEventKind, details: Option<String>) {
+        let _ = self.sender.send(OperationalEventRecord {
+            kind,
+            details,
+            maybe_written_sender: None,
+        });
+    }
+
+    /// Records the event and waits until it's written, e.g. before the sidecar exits.
+    pub(crate) async fn record_and_wait(
+        &self,
+        kind: OperationalEventKind,
+        details: Option<String>,
+    ) {
+        let (written_sender, written_receiver) = oneshot::channel();
+        let record = OperationalEventRecord {
+            kind,
+            details,
+            maybe_written_sender: Some(written_sender),
+        };
+        if self.sender.send(record).is_ok() {
+            let _ = written_receiver.await;
+        }
+    }
+
+    /// Spawns the task recording the event ID gaps reported by the event listeners.
+    pub(crate) fn record_event_id_gaps(&self, mut gap_receiver: Receiver<EventIdGap>) {
+        let operational_events = self.clone();
+        tokio::spawn(async move {
+            while let Some(gap) = gap_receiver.recv().await {
+                operational_events.record(
+                    OperationalEventKind::GapDetected,
+                    Some(format!(
+                        "node {} skipped the events from ID {}",
+                        gap.node, gap.start_from
+                    )),
+                );
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::fake_database::FakeDatabase, types::database::DatabaseReader};
+    use std::time::Duration;
+    use tokio::sync::mpsc::channel;
+
+    #[tokio::test]
+    async fn should_save_recorded_events_in_order() {
+        let database = FakeDatabase::new();
+        let operational_events = OperationalEvents::start(database.clone());
+        let (gap_sender, gap_receiver) = channel(10);
+        operational_events.record_event_id_gaps(gap_receiver);
+
+        operational_events.record(OperationalEventKind::Started, None);
+        gap_sender
+            .send(EventIdGap {
+                node: "127.0.0.1:18101".to_string(),
+                start_from: 42,
+            })
+            .await
+            .unwrap();
+        for _ in 0..50 {
+            if database.get_operational_events(0, 10).await.unwrap().len() == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        operational_events
+            .record_and_wait(
+                OperationalEventKind::Stopped,
+                Some("admin server shutting down".to_string()),
+            )
+            .await;
+
+        let events = database.get_operational_events(0, 10).await.unwrap();
+        let kinds: Vec<&str> = events.iter().map(|event| event.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["started", "gap_detected", "stopped"]);
+        assert_eq!(
+            events[1].details,
+            Some("node 127.0.0.1:18101 skipped the events from ID 42".to_string())
+        );
+    }
+}
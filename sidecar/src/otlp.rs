@@ -0,0 +1,54 @@
+//! Export of the traces of the ingestion of the events to an OpenTelemetry collector over OTLP.
+//!
+//! Each event received from a node is traced in an `ingest_event` span, within which its
+//! deserialization, its wait for room in the inbound channel, its processing, its storage and its
+//! hand-over to the event stream server are traced in turn. The event stream server traces its
+//! broadcast of each event in a `broadcast` span. The spans are sent in batches to the collector
+//! configured in the `[tracing]` table.
+
+use anyhow::{Context, Error};
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{
+    runtime::Tokio,
+    trace::{self, Sampler, Tracer},
+    Resource,
+};
+use tracing::Subscriber;
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::types::config::TracingConfig;
+
+/// The layer of the global collector exporting the spans to the collector of `config`.
+pub(crate) fn layer<S>(config: &TracingConfig) -> Result<OpenTelemetryLayer<S, Tracer>, Error>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    let exporter = opentelemetry_otlp::new_exporter()
+        .tonic()
+        .with_endpoint(config.otlp_endpoint.clone());
+    let sampler = Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+        config.sampling_ratio(),
+    )));
+    let resource = Resource::new(vec![KeyValue::new(
+        "service.name",
+        config.service_name().to_string(),
+    )]);
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(exporter)
+        .with_trace_config(
+            trace::config()
+                .with_sampler(sampler)
+                .with_resource(resource),
+        )
+        .install_batch(Tokio)
+        .context("Error setting up the export of traces")?;
+    Ok(tracing_opentelemetry::layer().with_tracer(tracer))
+}
+
+/// Sends the spans not exported yet to the collector.
+pub(crate) fn shutdown() {
+    opentelemetry::global::shutdown_tracer_provider();
+}
@@ -0,0 +1,191 @@
+//! Gating of the readiness of the sidecar on how far behind the chain it is.
+//!
+//! After a restart the sidecar replays the events it missed, and under load events queue up between
+//! the connections to the nodes and the event stream server. While either the backlog of queued
+//! events or the distance to the chain head exceeds its threshold, the sidecar is marked as not
+//! ready in [Health], so that load balancers don't route subscribers to a sidecar serving stale
+//! data.
+
+use crate::{
+    health::Health,
+    source_scoring::{SourceScores, SourceStatus},
+    types::config::ReadinessConfig,
+};
+use std::time::Duration;
+use tokio::sync::mpsc::{Sender, WeakSender};
+use tracing::{info, warn};
+
+/// Name under which the backlog of queued events is reported in [Health].
+pub const BACKLOG_COMPONENT: &str = "ingestion_backlog";
+/// Name under which the distance to the chain head is reported in [Health].
+pub const CATCH_UP_COMPONENT: &str = "catch_up";
+const SAMPLING_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A queue whose events count towards the ingestion backlog.
+pub(crate) struct IngestionQueue {
+    depth: Box<dyn Fn() -> usize + Send + Sync>,
+}
+
+impl IngestionQueue {
+    /// The queue fed by `sender`. It is observed without keeping it open, so that it still closes
+    /// once all its senders are dropped.
+    pub(crate) fn of<T: Send + 'static>(sender: &Sender<T>) -> Self {
+        let weak_sender: WeakSender<T> = sender.downgrade();
+        IngestionQueue {
+            depth: Box::new(move || {
+                weak_sender
+                    .upgrade()
+                    .map_or(0, |sender| sender.max_capacity() - sender.capacity())
+            }),
+        }
+    }
+
+    fn depth(&self) -> usize {
+        (self.depth)()
+    }
+}
+
+/// Spawns a task sampling the backlog of `queues` and the distance of the ingestion to the head
+/// of the nodes of `source_scores` periodically.
+pub(crate) fn start_readiness_monitor(
+    config: ReadinessConfig,
+    queues: Vec<IngestionQueue>,
+    source_scores: SourceScores,
+    health: Health,
+) {
+    tokio::spawn(async move {
+        let mut ready = true;
+        loop {
+            let queued_events = queues.iter().map(IngestionQueue::depth).sum();
+            update(
+                &health,
+                BACKLOG_COMPONENT,
+                backlog_reason(queued_events, config.max_queued_events()),
+            );
+            update(
+                &health,
+                CATCH_UP_COMPONENT,
+                catch_up_reason(&source_scores.statuses(), config.max_blocks_behind_head()),
+            );
+            let readiness = health.readiness();
+            if readiness.ready != ready {
+                if readiness.ready {
+                    info!("Sidecar caught up and is ready");
+                } else {
+                    warn!("Sidecar is not ready: {:?}", readiness.not_ready);
+                }
+                ready = readiness.ready;
+            }
+            tokio::time::sleep(SAMPLING_INTERVAL).await;
+        }
+    });
+}
+
+fn update(health: &Health, component: &str, maybe_reason: Option<String>) {
+    match maybe_reason {
+        Some(reason) => health.set_not_ready(component, reason),
+        None => health.set_ready(component),
+    }
+}
+
+/// Describes why the backlog holds the sidecar back, `None` if it doesn't.
+fn backlog_reason(queued_events: usize, max_queued_events: usize) -> Option<String> {
+    (queued_events > max_queued_events).then(|| {
+        format!(
+            "{} events are waiting to be ingested, more than {}",
+            queued_events, max_queued_events
+        )
+    })
+}
+
+/// Describes why the distance between the highest block ingested from any node and the highest
+/// head of the nodes holds the sidecar back, `None` if it doesn't or if no head is known.
+fn catch_up_reason(statuses: &[SourceStatus], max_blocks_behind_head: u64) -> Option<String> {
+    let head_height = statuses
+        .iter()
+        .filter_map(|status| status.head_height)
+        .max()?;
+    let maybe_ingested_height = statuses
+        .iter()
+        .filter_map(|status| status.latest_ingested_block_height)
+        .max();
+    match maybe_ingested_height {
+        None => Some(format!(
+            "no block was ingested yet, the chain head is at {}",
+            head_height
+        )),
+        Some(ingested_height) => {
+            let blocks_behind_head = head_height.saturating_sub(ingested_height);
+            (blocks_behind_head > max_blocks_behind_head).then(|| {
+                format!(
+                    "{} blocks behind the chain head at {}, more than {}",
+                    blocks_behind_head, head_height, max_blocks_behind_head
+                )
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc::channel;
+
+    fn status(head_height: Option<u64>, ingested_height: Option<u64>) -> SourceStatus {
+        SourceStatus {
+            node: "127.0.0.1:9999".to_string(),
+            head_height,
+            latency: None,
+            latest_ingested_block_height: ingested_height,
+            last_event_at: None,
+            last_keep_alive_at: None,
+            liveness_window: Duration::from_secs(120),
+            active: true,
+        }
+    }
+
+    #[test]
+    fn should_not_be_ready_while_catching_up() {
+        assert_eq!(catch_up_reason(&[], 5), None);
+        assert_eq!(catch_up_reason(&[status(None, Some(100))], 5), None);
+        assert_eq!(
+            catch_up_reason(&[status(Some(2310), None)], 5),
+            Some("no block was ingested yet, the chain head is at 2310".to_string())
+        );
+        assert_eq!(
+            catch_up_reason(&[status(Some(2310), Some(1200))], 5),
+            Some("1110 blocks behind the chain head at 2310, more than 5".to_string())
+        );
+        // The blocks ingested from a lagging node don't count against those of the others.
+        assert_eq!(
+            catch_up_reason(
+                &[
+                    status(Some(2310), Some(2308)),
+                    status(Some(2300), Some(1200))
+                ],
+                5
+            ),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn should_count_queued_events_without_keeping_queue_open() {
+        let (sender, mut receiver) = channel(10);
+        let queue = IngestionQueue::of(&sender);
+        sender.send(1).await.unwrap();
+        sender.send(2).await.unwrap();
+        assert_eq!(queue.depth(), 2);
+        assert_eq!(backlog_reason(queue.depth(), 2), None);
+        assert_eq!(
+            backlog_reason(queue.depth(), 1),
+            Some("2 events are waiting to be ingested, more than 1".to_string())
+        );
+
+        drop(sender);
+        assert_eq!(receiver.recv().await, Some(1));
+        assert_eq!(receiver.recv().await, Some(2));
+        assert_eq!(receiver.recv().await, None);
+        assert_eq!(queue.depth(), 0);
+    }
+}
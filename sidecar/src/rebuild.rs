@@ -0,0 +1,375 @@
+//! Rebuilds of the tables derived from the stored processed deploys.
+//!
+//! The native transfers, the token transfers, the NFT events and the contract events are decoded
+//! from the `DeployProcessed` events as they are received. Once a decoder is fixed, or the
+//! contracts of its config change, `POST /rebuild?index=<table>` on the admin server deletes the
+//! rows of the table and decodes the stored processed deploys again, oldest first, in a background
+//! task. The ingestion of new events goes on meanwhile: a deploy processed during the rebuild is
+//! stored in the table by whichever decodes it first. The rows decoded again aren't published on
+//! the outbound stream. The progress of the rebuilds is served by `GET /rebuild`.
+
+use crate::{
+    contract_events::ContractEventDecoder,
+    nft_events::NftDecoder,
+    token_transfers::TokenDecoder,
+    types::{
+        config::{Config, ContractEventsConfig, NftEventsConfig, TokenTransfersConfig},
+        database::{
+            DatabaseReader, DatabaseWriteError, DatabaseWriter, DerivedTable, PayloadTable,
+            TransferEntry,
+        },
+        sse_events::DeployProcessed,
+    },
+};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::{info, warn};
+
+/// The number of processed deploys read from the database at once.
+const REBUILD_PAGE_SIZE: u32 = 1000;
+
+/// The state of a rebuild.
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum RebuildState {
+    Running,
+    Completed,
+    Failed,
+}
+
+/// A rebuild of a derived table, along with its progress.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+pub(crate) struct RebuildJob {
+    table: DerivedTable,
+    state: RebuildState,
+    /// Seconds since the UNIX epoch at which the rebuild started.
+    started_at: u64,
+    /// Seconds since the UNIX epoch at which the rebuild completed or failed.
+    finished_at: Option<u64>,
+    /// Number of stored processed deploys decoded so far.
+    processed_deploys: u64,
+    /// Number of rows stored in the table so far.
+    stored_rows: u64,
+    /// Why the rebuild failed.
+    error: Option<String>,
+}
+
+/// Why a rebuild couldn't be started.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum RebuildError {
+    /// A rebuild of the table is already running.
+    AlreadyRunning,
+    /// The decoder of the table isn't configured, or its config is invalid.
+    NotConfigured(String),
+}
+
+/// Starts the rebuilds of the derived tables and keeps track of the last one of each table.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Rebuilds {
+    maybe_token_transfers_config: Option<TokenTransfersConfig>,
+    maybe_nft_events_config: Option<NftEventsConfig>,
+    maybe_contract_events_config: Option<ContractEventsConfig>,
+    jobs: Arc<Mutex<BTreeMap<DerivedTable, RebuildJob>>>,
+}
+
+impl Rebuilds {
+    /// Rebuilds decoding the stored deploys with the decoders configured in `config`.
+    pub(crate) fn new(config: &Config) -> Self {
+        Rebuilds {
+            maybe_token_transfers_config: config.token_transfers.clone(),
+            maybe_nft_events_config: config.nft_events.clone(),
+            maybe_contract_events_config: config.contract_events.clone(),
+            jobs: Arc::default(),
+        }
+    }
+
+    /// The last rebuild of each table started since the Sidecar started.
+    pub(crate) fn jobs(&self) -> Vec<RebuildJob> {
+        self.jobs
+            .lock()
+            .expect("Error acquiring lock on rebuilds")
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// Spawns the task rebuilding `table` from the processed deploys stored in `database`,
+    /// returning the started rebuild.
+    pub(crate) fn start<Db: DatabaseReader + DatabaseWriter + Send + Sync + 'static>(
+        &self,
+        table: DerivedTable,
+        database: Db,
+    ) -> Result<RebuildJob, RebuildError> {
+        let decoder = self.decoder(table)?;
+        let job = {
+            let mut jobs = self.jobs.lock().expect("Error acquiring lock on rebuilds");
+            if jobs
+                .get(&table)
+                .map_or(false, |job| job.state == RebuildState::Running)
+            {
+                return Err(RebuildError::AlreadyRunning);
+            }
+            let job = RebuildJob {
+                table,
+                state: RebuildState::Running,
+                started_at: now(),
+                finished_at: None,
+                processed_deploys: 0,
+                stored_rows: 0,
+                error: None,
+            };
+            jobs.insert(table, job.clone());
+            job
+        };
+        let rebuilds = self.clone();
+        tokio::spawn(async move {
+            info!(table = table.name(), "Rebuilding the derived table");
+            let result = rebuilds.rebuild(table, decoder, &database).await;
+            rebuilds.update(table, |job| {
+                job.finished_at = Some(now());
+                match result {
+                    Ok(()) => job.state = RebuildState::Completed,
+                    Err(error) => {
+                        warn!(table = table.name(), %error, "Error rebuilding the derived table");
+                        job.state = RebuildState::Failed;
+                        job.error = Some(error);
+                    }
+                }
+            });
+        });
+        Ok(job)
+    }
+
+    fn decoder(&self, table: DerivedTable) -> Result<Decoder, RebuildError> {
+        let not_configured = || {
+            RebuildError::NotConfigured(format!(
+                "the decoding of {} isn't configured",
+                table.name()
+            ))
+        };
+        let invalid = |error: anyhow::Error| RebuildError::NotConfigured(error.to_string());
+        match table {
+            DerivedTable::Transfers => Ok(Decoder::Transfers),
+            DerivedTable::TokenTransfers => {
+                let config = self
+                    .maybe_token_transfers_config
+                    .as_ref()
+                    .ok_or_else(not_configured)?;
+                Ok(Decoder::TokenTransfers(
+                    TokenDecoder::new(config).map_err(invalid)?,
+                ))
+            }
+            DerivedTable::NftEvents => {
+                let config = self
+                    .maybe_nft_events_config
+                    .as_ref()
+                    .ok_or_else(not_configured)?;
+                Ok(Decoder::NftEvents(
+                    NftDecoder::new(config).map_err(invalid)?,
+                ))
+            }
+            DerivedTable::ContractEvents => {
+                let config = self
+                    .maybe_contract_events_config
+                    .as_ref()
+                    .ok_or_else(not_configured)?;
+                Ok(Decoder::ContractEvents(
+                    ContractEventDecoder::new(config).map_err(invalid)?,
+                ))
+            }
+        }
+    }
+
+    /// Clears the table, then decodes the stored processed deploys page by page.
+    async fn rebuild<Db: DatabaseReader + DatabaseWriter>(
+        &self,
+        table: DerivedTable,
+        mut decoder: Decoder,
+        database: &Db,
+    ) -> Result<(), String> {
+        if let Decoder::ContractEvents(decoder) = &mut decoder {
+            decoder.load_schemas(database, None).await;
+        }
+        database
+            .clear_derived_table(table)
+            .await
+            .map_err(|error| format!("Error clearing the table: {:?}", error))?;
+        let mut after = 0;
+        loop {
+            let page = database
+                .get_raw_payloads_after(PayloadTable::DeployProcessed, after, REBUILD_PAGE_SIZE)
+                .await
+                .map_err(|error| format!("Error reading the processed deploys: {:?}", error))?;
+            let mut stored_rows = 0;
+            for entry in &page {
+                let deploy_processed = serde_json::from_str::<DeployProcessed>(&entry.raw)
+                    .map_err(|error| {
+                        format!(
+                            "Error parsing the processed deploy of event log entry {}: {}",
+                            entry.event_log_id, error
+                        )
+                    })?;
+                stored_rows += decoder
+                    .store(database, &deploy_processed)
+                    .await
+                    .map_err(|error| format!("Error storing the decoded rows: {:?}", error))?;
+            }
+            self.update(table, |job| {
+                job.processed_deploys += page.len() as u64;
+                job.stored_rows += stored_rows;
+            });
+            match page.last() {
+                Some(last) if page.len() == REBUILD_PAGE_SIZE as usize => after = last.event_log_id,
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn update(&self, table: DerivedTable, update: impl FnOnce(&mut RebuildJob)) {
+        if let Some(job) = self
+            .jobs
+            .lock()
+            .expect("Error acquiring lock on rebuilds")
+            .get_mut(&table)
+        {
+            update(job);
+        }
+    }
+}
+
+/// Decodes the rows of a derived table from the processed deploys.
+enum Decoder {
+    Transfers,
+    TokenTransfers(TokenDecoder),
+    NftEvents(NftDecoder),
+    ContractEvents(ContractEventDecoder),
+}
+
+impl Decoder {
+    /// Stores the rows decoded from the deploy, returning the number of rows stored.
+    async fn store<Db: DatabaseWriter>(
+        &mut self,
+        database: &Db,
+        deploy_processed: &DeployProcessed,
+    ) -> Result<u64, DatabaseWriteError> {
+        let mut stored_rows = 0;
+        match self {
+            Decoder::Transfers => {
+                for transfer in TransferEntry::of_deploy_processed(deploy_processed) {
+                    stored_rows += database.save_transfer(transfer).await?;
+                }
+            }
+            Decoder::TokenTransfers(decoder) => {
+                for token_transfer in decoder.decode(deploy_processed) {
+                    stored_rows += database.save_token_transfer(token_transfer).await?;
+                }
+            }
+            Decoder::NftEvents(decoder) => {
+                for nft_event in decoder.decode(deploy_processed) {
+                    stored_rows += database.save_nft_event(nft_event).await?;
+                }
+            }
+            Decoder::ContractEvents(decoder) => {
+                // The schemas learned are those of the past, the stored ones are left as is.
+                decoder.learn_schemas(deploy_processed);
+                for contract_event in decoder.decode(deploy_processed) {
+                    stored_rows += database.save_contract_event(contract_event).await?;
+                }
+            }
+        }
+        Ok(stored_rows)
+    }
+}
+
+/// Seconds since the UNIX epoch.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::sqlite_database::SqliteDatabase;
+    use casper_types::{
+        account::AccountHash, testing::TestRng, AccessRights, Key, Transfer, TransferAddr,
+        Transform, TransformEntry, URef, U512,
+    };
+    use std::time::Duration;
+
+    fn native_transfer(address: u8) -> TransformEntry {
+        TransformEntry {
+            key: Key::Transfer(TransferAddr::new([address; 32])).to_formatted_string(),
+            transform: Transform::WriteTransfer(Transfer::new(
+                casper_types::DeployHash::new([address; 32]),
+                AccountHash::new([3; 32]),
+                Some(AccountHash::new([4; 32])),
+                URef::new([5; 32], AccessRights::READ_ADD_WRITE),
+                URef::new([6; 32], AccessRights::READ_ADD_WRITE),
+                U512::from(address),
+                U512::zero(),
+                None,
+            )),
+        }
+    }
+
+    async fn finished_job(rebuilds: &Rebuilds) -> RebuildJob {
+        for _ in 0..100 {
+            let jobs = rebuilds.jobs();
+            if jobs[0].state != RebuildState::Running {
+                return jobs[0].clone();
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("the rebuild should finish");
+    }
+
+    #[tokio::test]
+    async fn should_rebuild_transfers_from_the_stored_deploys() {
+        let mut rng = TestRng::new();
+        let db = SqliteDatabase::new_in_memory(10)
+            .await
+            .expect("Error opening database in memory");
+        for (event_id, address) in [1u8, 2].iter().enumerate() {
+            let deploy_processed = DeployProcessed::random(&mut rng, None)
+                .with_transforms(vec![native_transfer(*address)]);
+            db.save_deploy_processed(deploy_processed, event_id as u32, "127.0.0.1".to_string())
+                .await
+                .expect("Error saving deploy_processed");
+        }
+        let rebuilds = Rebuilds::default();
+
+        rebuilds
+            .start(DerivedTable::Transfers, db.clone())
+            .expect("the rebuild should start");
+
+        let job = finished_job(&rebuilds).await;
+        assert_eq!(job.state, RebuildState::Completed);
+        assert_eq!(job.processed_deploys, 2);
+        assert_eq!(job.stored_rows, 2);
+        let transfers = db
+            .get_transfers_by_account(&hex::encode([3; 32]), None, 1000)
+            .await
+            .expect("Error getting transfers");
+        assert_eq!(transfers.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn should_not_rebuild_tables_without_decoder() {
+        let db = SqliteDatabase::new_in_memory(10)
+            .await
+            .expect("Error opening database in memory");
+        let rebuilds = Rebuilds::default();
+
+        let result = rebuilds.start(DerivedTable::NftEvents, db);
+
+        assert!(matches!(result, Err(RebuildError::NotConfigured(_))));
+        assert!(rebuilds.jobs().is_empty());
+    }
+}
@@ -1,47 +1,192 @@
+mod csv;
+pub(crate) mod decoded_args;
 mod errors;
 pub mod filters;
+#[cfg(feature = "graphql")]
+mod graphql;
 mod handlers;
 mod openapi;
+mod streaming;
 #[cfg(test)]
 mod tests;
+#[cfg(feature = "ui")]
+mod ui;
 
 use std::net::TcpListener;
 use std::time::Duration;
 
 use anyhow::Error;
-use hyper::Server;
-use tower::{buffer::Buffer, make::Shared, ServiceBuilder};
-use warp::Filter;
+use hyper::{Body, Request, Server};
+use tower::{buffer::Buffer, make::Shared, service_fn, ServiceBuilder, ServiceExt};
+use tracing::warn;
+use warp::{
+    filters::BoxedFilter,
+    http::{
+        header::{CONTENT_LENGTH, CONTENT_TYPE},
+        StatusCode,
+    },
+    reply::Response,
+    Filter, Reply,
+};
 
 use crate::{
-    types::{config::RestServerConfig, database::DatabaseReader},
+    account_aliases::AccountAliases,
+    api_keys::{require_scope, ApiKeys, API_KEY_HEADER},
+    audit::{audit_requests, AuditAction, AuditLog},
+    canonical_json,
+    deploy_outcomes::DeployOutcomes,
+    state_proofs::StateProofs,
+    types::{
+        config::{ApiKeyScope, RestServerConfig},
+        database::DatabaseReader,
+    },
     utils::resolve_address,
 };
 
-const BIND_ALL_INTERFACES: &str = "0.0.0.0";
+/// The REST server, answering with the data of `database`. With the `ui` feature, it also serves the
+/// web page browsing the events, which subscribes to the event stream server on
+/// `event_stream_port`. With `maybe_api_keys`, it answers only the requests with a key of the
+/// `read_events` scope.
+pub(crate) struct RestServer<Db> {
+    pub(crate) database: Db,
+    pub(crate) maybe_audit_log: Option<AuditLog>,
+    pub(crate) maybe_state_proofs: Option<StateProofs>,
+    pub(crate) account_aliases: AccountAliases,
+    pub(crate) deploy_outcomes: DeployOutcomes,
+    pub(crate) canonical_json: bool,
+    pub(crate) event_stream_port: u16,
+    pub(crate) maybe_api_keys: Option<ApiKeys>,
+}
+
+impl<Db: DatabaseReader + Clone + Send + Sync + 'static> RestServer<Db> {
+    /// The routes of the server, e.g. to serve them with those of other networks.
+    pub(crate) fn routes(self) -> BoxedFilter<(Response,)> {
+        let canonical_json = self.canonical_json;
+        let routes = filters::combined_filters(
+            self.database,
+            self.maybe_state_proofs,
+            self.account_aliases,
+            self.deploy_outcomes,
+        )
+        .then(move |reply| async move {
+            let response = Reply::into_response(reply);
+            if canonical_json {
+                canonicalize_response(response).await
+            } else {
+                response
+            }
+        })
+        .with(warp::cors().allow_any_origin().allow_header(API_KEY_HEADER))
+        .map(Reply::into_response)
+        .boxed();
+        // Refused requests are audited too.
+        let routes = require_scope(self.maybe_api_keys.clone(), ApiKeyScope::ReadEvents, routes)
+            .with(audit_requests(
+                self.maybe_audit_log,
+                AuditAction::RestQuery,
+                self.maybe_api_keys,
+            ))
+            .map(Reply::into_response);
+        // The page goes first as the REST routes answer every other path, if only with an error. It
+        // holds no data so it is served without a key, and passes the key it is opened with, if any,
+        // to the REST and event stream servers.
+        #[cfg(feature = "ui")]
+        let routes = ui::ui(self.event_stream_port)
+            .map(Reply::into_response)
+            .or(routes)
+            .unify();
+        #[cfg(not(feature = "ui"))]
+        let _ = self.event_stream_port;
+        routes.boxed()
+    }
+}
 
-pub async fn run_server<Db: DatabaseReader + Clone + Send + Sync + 'static>(
+/// Serves `routes` on the configured address, e.g. the routes of several networks mounted under
+/// their names.
+pub(crate) async fn serve(
     config: RestServerConfig,
-    database: Db,
+    routes: BoxedFilter<(Response,)>,
 ) -> Result<(), Error> {
-    let api = filters::combined_filters(database);
-    let address = format!("{}:{}", BIND_ALL_INTERFACES, config.port);
-    let socket_address = resolve_address(&address)?;
+    let socket_address = resolve_address(&config.bind_address())?;
 
     let listener = TcpListener::bind(socket_address)?;
 
-    let warp_service = warp::service(api.with(warp::cors().allow_any_origin()));
-    let tower_service = ServiceBuilder::new()
-        .concurrency_limit(config.max_concurrent_requests as usize)
-        .rate_limit(
-            config.max_requests_per_second as u64,
-            Duration::from_secs(1),
-        )
-        .service(warp_service);
+    let warp_service = warp::service(routes);
+    let limited_service = |max_concurrent_requests: u32| {
+        let tower_service = ServiceBuilder::new()
+            .concurrency_limit(max_concurrent_requests as usize)
+            .rate_limit(
+                config.max_requests_per_second as u64,
+                Duration::from_secs(1),
+            )
+            .service(warp_service.clone());
+        Buffer::new(tower_service, 50)
+    };
+    let query_service = limited_service(config.max_concurrent_requests);
+    // Awaiting a deploy holds the request until the timeout, so these requests have their own limit
+    // rather than taking the slots of the other queries.
+    let await_service = limited_service(config.max_concurrent_awaits);
+    let service = service_fn(move |request: Request<Body>| {
+        let service = if is_await_request(request.uri().path()) {
+            await_service.clone()
+        } else {
+            query_service.clone()
+        };
+        service.oneshot(request)
+    });
 
     Server::from_tcp(listener)?
-        .serve(Shared::new(Buffer::new(tower_service, 50)))
+        .serve(Shared::new(service))
         .await?;
 
     Err(Error::msg("REST server shutting down"))
 }
+
+/// Whether the request awaits the outcome of a deploy on `deploy/<deploy-hash>/await`, possibly
+/// mounted under the name of a network.
+fn is_await_request(path: &str) -> bool {
+    let segments: Vec<&str> = path.trim_end_matches('/').rsplit('/').take(3).collect();
+    matches!(segments.as_slice(), ["await", _, "deploy"])
+}
+
+/// Rewrites the JSON, or newline-delimited JSON, body of the response in its canonical form. The
+/// body is read whole, including those of the lists otherwise sent one page at a time.
+async fn canonicalize_response(response: Response) -> Response {
+    let is_ndjson = match response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+    {
+        Some(content_type) if content_type.starts_with("application/json") => false,
+        Some(content_type) if content_type.starts_with("application/x-ndjson") => true,
+        _ => return response,
+    };
+    let (mut parts, body) = response.into_parts();
+    let bytes = match hyper::body::to_bytes(body).await {
+        Ok(bytes) => bytes,
+        Err(error) => {
+            warn!(%error, "Error reading the response to canonicalize it");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let canonicalized = String::from_utf8(bytes.to_vec())
+        .map_err(|error| error.to_string())
+        .and_then(|json| {
+            if is_ndjson {
+                json.lines()
+                    .map(|line| canonical_json::canonicalize(line).map(|line| line + "\n"))
+                    .collect::<Result<String, _>>()
+            } else {
+                canonical_json::canonicalize(&json)
+            }
+            .map_err(|error| error.to_string())
+        });
+    parts.headers.remove(CONTENT_LENGTH);
+    match canonicalized {
+        Ok(canonical_json) => Response::from_parts(parts, Body::from(canonical_json)),
+        Err(error) => {
+            warn!(%error, "Error canonicalizing the response");
+            Response::from_parts(parts, Body::from(bytes))
+        }
+    }
+}
@@ -0,0 +1,236 @@
+//! CSV representation of the lists of the REST API which accounting tools consume
+//! (`?format=csv`).
+//!
+//! Each listed type has a stable set of columns, named in a header row, so that the columns of a
+//! table never depend on its items. Fields are quoted as per RFC 4180: a field containing a comma,
+//! a double quote or a line break is enclosed in double quotes, in which double quotes are doubled,
+//! and rows end with CRLF. Missing optional values are empty fields.
+
+use crate::types::database::{
+    AccountEffectEntry, DeployAccountEntry, DeployErrorEntry, DeployTimestampEntry,
+    DeployTimestampKind, NftEventEntry, NftEventKind, TokenTransferEntry, TokenTransferKind,
+    TransferEntry,
+};
+use casper_types::Timestamp;
+
+/// An item which can be listed as a row of a CSV table.
+pub(super) trait CsvRecord {
+    /// The names of the columns, in order.
+    const COLUMNS: &'static [&'static str];
+
+    /// The fields of the row, one per column.
+    fn fields(&self) -> Vec<String>;
+}
+
+/// Appends the row made of `fields` to `out`.
+pub(super) fn encode_row<S: AsRef<str>>(fields: &[S], out: &mut Vec<u8>) {
+    for (index, field) in fields.iter().enumerate() {
+        if index > 0 {
+            out.push(b',');
+        }
+        encode_field(field.as_ref(), out);
+    }
+    out.extend_from_slice(b"\r\n");
+}
+
+fn encode_field(field: &str, out: &mut Vec<u8>) {
+    if !field.contains(|character| matches!(character, ',' | '"' | '\r' | '\n')) {
+        out.extend_from_slice(field.as_bytes());
+        return;
+    }
+    out.push(b'"');
+    out.extend_from_slice(field.replace('"', "\"\"").as_bytes());
+    out.push(b'"');
+}
+
+impl CsvRecord for DeployTimestampEntry {
+    const COLUMNS: &'static [&'static str] = &["deploy_hash", "kind", "timestamp", "time"];
+
+    fn fields(&self) -> Vec<String> {
+        let kind = match self.kind {
+            DeployTimestampKind::Accepted => "accepted",
+            DeployTimestampKind::Processed => "processed",
+        };
+        vec![
+            self.deploy_hash.clone(),
+            kind.to_string(),
+            self.timestamp.to_string(),
+            Timestamp::from(self.timestamp).to_string(),
+        ]
+    }
+}
+
+impl CsvRecord for DeployErrorEntry {
+    const COLUMNS: &'static [&'static str] = &["deploy_hash", "block_hash", "error_message"];
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.deploy_hash.clone(),
+            self.block_hash.clone(),
+            self.error_message.clone(),
+        ]
+    }
+}
+
+impl CsvRecord for DeployAccountEntry {
+    const COLUMNS: &'static [&'static str] = &["deploy_hash", "account_hash", "account_alias"];
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.deploy_hash.clone(),
+            self.account_hash.clone(),
+            self.account_alias.clone().unwrap_or_default(),
+        ]
+    }
+}
+
+impl CsvRecord for AccountEffectEntry {
+    const COLUMNS: &'static [&'static str] = &["deploy_hash", "account_hash"];
+
+    fn fields(&self) -> Vec<String> {
+        vec![self.deploy_hash.clone(), self.account_hash.clone()]
+    }
+}
+
+impl CsvRecord for TransferEntry {
+    const COLUMNS: &'static [&'static str] = &[
+        "transfer_hash",
+        "deploy_hash",
+        "block_hash",
+        "from",
+        "to",
+        "source",
+        "target",
+        "amount",
+        "gas",
+        "id",
+    ];
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.transfer_hash.clone(),
+            self.deploy_hash.clone(),
+            self.block_hash.clone(),
+            self.from.clone(),
+            self.to.clone().unwrap_or_default(),
+            self.source.clone(),
+            self.target.clone(),
+            self.amount.to_string(),
+            self.gas.to_string(),
+            self.id.map(|id| id.to_string()).unwrap_or_default(),
+        ]
+    }
+}
+
+impl CsvRecord for TokenTransferEntry {
+    const COLUMNS: &'static [&'static str] = &[
+        "contract_hash",
+        "event_id",
+        "kind",
+        "from",
+        "to",
+        "spender",
+        "amount",
+        "deploy_hash",
+        "block_hash",
+    ];
+
+    fn fields(&self) -> Vec<String> {
+        let kind = match self.kind {
+            TokenTransferKind::Mint => "Mint",
+            TokenTransferKind::Burn => "Burn",
+            TokenTransferKind::Transfer => "Transfer",
+            TokenTransferKind::TransferFrom => "TransferFrom",
+        };
+        vec![
+            self.contract_hash.clone(),
+            self.event_id.to_string(),
+            kind.to_string(),
+            self.from.clone().unwrap_or_default(),
+            self.to.clone().unwrap_or_default(),
+            self.spender.clone().unwrap_or_default(),
+            self.amount.to_string(),
+            self.deploy_hash.clone(),
+            self.block_hash.clone(),
+        ]
+    }
+}
+
+impl CsvRecord for NftEventEntry {
+    const COLUMNS: &'static [&'static str] = &[
+        "contract_hash",
+        "event_id",
+        "kind",
+        "token_id",
+        "owner",
+        "recipient",
+        "spender",
+        "deploy_hash",
+        "block_hash",
+    ];
+
+    fn fields(&self) -> Vec<String> {
+        let kind = match self.kind {
+            NftEventKind::Mint => "Mint",
+            NftEventKind::Burn => "Burn",
+            NftEventKind::Transfer => "Transfer",
+            NftEventKind::Approval => "Approval",
+        };
+        vec![
+            self.contract_hash.clone(),
+            self.event_id.to_string(),
+            kind.to_string(),
+            self.token_id.clone(),
+            self.owner.clone().unwrap_or_default(),
+            self.recipient.clone().unwrap_or_default(),
+            self.spender.clone().unwrap_or_default(),
+            self.deploy_hash.clone(),
+            self.block_hash.clone(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_quote_fields_as_per_rfc_4180() {
+        let mut out = Vec::new();
+        encode_row(
+            &[
+                "plain",
+                "with,comma",
+                "with \"quotes\"",
+                "with\r\nbreak",
+                "",
+            ],
+            &mut out,
+        );
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "plain,\"with,comma\",\"with \"\"quotes\"\"\",\"with\r\nbreak\",\r\n"
+        );
+    }
+
+    #[test]
+    fn should_list_deploy_timestamps_with_stable_columns() {
+        let entry = DeployTimestampEntry {
+            deploy_hash: "ab".to_string(),
+            kind: DeployTimestampKind::Processed,
+            timestamp: 1_714_521_600_000,
+        };
+
+        assert_eq!(
+            entry.fields(),
+            vec![
+                "ab".to_string(),
+                "processed".to_string(),
+                "1714521600000".to_string(),
+                "2024-05-01T00:00:00.000Z".to_string(),
+            ]
+        );
+        assert_eq!(entry.fields().len(), DeployTimestampEntry::COLUMNS.len());
+    }
+}
@@ -0,0 +1,327 @@
+//! Decoding of the session and payment arguments of deploys into typed JSON, for the
+//! `decode_args=true` view of the deploy endpoints.
+//!
+//! Each argument is returned as its name, its `cl_type` and its decoded `value`, e.g.
+//! `{"name":"amount","cl_type":"U512","value":"2500000000"}`, instead of the hex-encoded bytes.
+//! Integers wider than 64 bits are decoded to decimal strings, keys and URefs to their formatted
+//! strings, public keys and byte arrays to hex. Arguments which can't be decoded, as they are of
+//! type `Any` or their bytes don't match their type, keep their hex-encoded `bytes` instead of a
+//! `value`.
+//!
+//! The decoding of values is shared with the [contract events](crate::contract_events), whose
+//! fields are decoded in the same way.
+
+use crate::types::{database::DeployAggregate, sse_events::DeployAccepted};
+use casper_types::{
+    bytesrepr::{self, FromBytes},
+    AsymmetricType, CLType, CLValue, Key, PublicKey, RuntimeArgs, URef, U128, U256, U512,
+};
+use serde_json::{json, Map, Value};
+
+const OPTION_NONE_TAG: u8 = 0;
+const OPTION_SOME_TAG: u8 = 1;
+const RESULT_ERR_TAG: u8 = 0;
+const RESULT_OK_TAG: u8 = 1;
+
+/// Serializes `deploy_accepted` with the arguments of its payment and session decoded.
+pub(super) fn decode_deploy_accepted(
+    deploy_accepted: &DeployAccepted,
+) -> Result<Value, serde_json::Error> {
+    let mut json = serde_json::to_value(deploy_accepted)?;
+    let deploy = deploy_accepted.deploy();
+    for (field, item) in [("payment", deploy.payment()), ("session", deploy.session())].iter() {
+        // The item is serialized as an object with the name of its variant as single key.
+        let maybe_args = json["deploy"][field]
+            .as_object_mut()
+            .and_then(|variant| variant.values_mut().next())
+            .and_then(|fields| fields.get_mut("args"));
+        if let Some(args) = maybe_args {
+            *args = decode_runtime_args(item.args());
+        }
+    }
+    Ok(json)
+}
+
+/// Serializes `aggregate` with the arguments of the payment and session of its deploy decoded.
+pub(super) fn decode_deploy_aggregate(
+    aggregate: &DeployAggregate,
+) -> Result<Value, serde_json::Error> {
+    let mut json = serde_json::to_value(aggregate)?;
+    if let Some(deploy_accepted) = &aggregate.deploy_accepted {
+        json["deploy_accepted"] = decode_deploy_accepted(deploy_accepted)?;
+    }
+    Ok(json)
+}
+
+fn decode_runtime_args(args: &RuntimeArgs) -> Value {
+    Value::Array(
+        args.named_args()
+            .map(|named_arg| {
+                let cl_value = named_arg.cl_value();
+                let mut decoded = json!({
+                    "name": named_arg.name(),
+                    "cl_type": cl_value.cl_type(),
+                });
+                match decode_cl_value(cl_value) {
+                    Some(value) => decoded["value"] = value,
+                    None => decoded["bytes"] = Value::String(hex::encode(cl_value.inner_bytes())),
+                }
+                decoded
+            })
+            .collect(),
+    )
+}
+
+/// Decodes `cl_value`, `None` if it is of type `Any` or its bytes don't match its type.
+fn decode_cl_value(cl_value: &CLValue) -> Option<Value> {
+    match decode(cl_value.cl_type(), cl_value.inner_bytes()) {
+        Ok((value, remainder)) if remainder.is_empty() => Some(value),
+        _ => None,
+    }
+}
+
+fn decode_to_string<T: FromBytes + ToString>(
+    bytes: &[u8],
+) -> Result<(Value, &[u8]), bytesrepr::Error> {
+    let (decoded, remainder) = T::from_bytes(bytes)?;
+    Ok((Value::String(decoded.to_string()), remainder))
+}
+
+fn decode_to_json<T: FromBytes + Into<Value>>(
+    bytes: &[u8],
+) -> Result<(Value, &[u8]), bytesrepr::Error> {
+    let (decoded, remainder) = T::from_bytes(bytes)?;
+    Ok((decoded.into(), remainder))
+}
+
+/// Reads the length of a list or map, which can't exceed the number of remaining bytes as each
+/// item takes at least one byte. Lists of items taking no bytes, i.e. of `Unit`, are thus only
+/// decoded if short, so that a forged length can't make the decoding loop for long.
+fn decode_length(bytes: &[u8]) -> Result<(usize, &[u8]), bytesrepr::Error> {
+    let (length, remainder) = u32::from_bytes(bytes)?;
+    let length = length as usize;
+    if length > remainder.len() {
+        return Err(bytesrepr::Error::EarlyEndOfStream);
+    }
+    Ok((length, remainder))
+}
+
+/// Decodes a value of type `cl_type` from the start of `bytes`, returning it along with the bytes
+/// following it.
+pub(crate) fn decode<'a>(
+    cl_type: &CLType,
+    bytes: &'a [u8],
+) -> Result<(Value, &'a [u8]), bytesrepr::Error> {
+    match cl_type {
+        CLType::Bool => decode_to_json::<bool>(bytes),
+        CLType::I32 => decode_to_json::<i32>(bytes),
+        CLType::I64 => decode_to_json::<i64>(bytes),
+        CLType::U8 => decode_to_json::<u8>(bytes),
+        CLType::U32 => decode_to_json::<u32>(bytes),
+        CLType::U64 => decode_to_json::<u64>(bytes),
+        CLType::U128 => decode_to_string::<U128>(bytes),
+        CLType::U256 => decode_to_string::<U256>(bytes),
+        CLType::U512 => decode_to_string::<U512>(bytes),
+        CLType::Unit => Ok((Value::Null, bytes)),
+        CLType::String => decode_to_json::<String>(bytes),
+        CLType::Key => {
+            let (key, remainder) = Key::from_bytes(bytes)?;
+            Ok((Value::String(key.to_formatted_string()), remainder))
+        }
+        CLType::URef => {
+            let (uref, remainder) = URef::from_bytes(bytes)?;
+            Ok((Value::String(uref.to_formatted_string()), remainder))
+        }
+        CLType::PublicKey => {
+            let (public_key, remainder) = PublicKey::from_bytes(bytes)?;
+            Ok((Value::String(public_key.to_hex()), remainder))
+        }
+        CLType::Option(inner_type) => match u8::from_bytes(bytes)? {
+            (OPTION_NONE_TAG, remainder) => Ok((Value::Null, remainder)),
+            (OPTION_SOME_TAG, remainder) => decode(inner_type, remainder),
+            _ => Err(bytesrepr::Error::Formatting),
+        },
+        CLType::List(item_type) => decode_list(item_type, bytes),
+        CLType::ByteArray(length) => decode_byte_array(*length as usize, bytes),
+        CLType::Result { ok, err } => decode_result(ok, err, bytes),
+        CLType::Map { key, value } => decode_map(key, value, bytes),
+        CLType::Tuple1(types) => decode_tuple(types, bytes),
+        CLType::Tuple2(types) => decode_tuple(types, bytes),
+        CLType::Tuple3(types) => decode_tuple(types, bytes),
+        CLType::Any => Err(bytesrepr::Error::Formatting),
+    }
+}
+
+fn decode_list<'a>(
+    item_type: &CLType,
+    bytes: &'a [u8],
+) -> Result<(Value, &'a [u8]), bytesrepr::Error> {
+    let (length, mut remainder) = decode_length(bytes)?;
+    let mut items = Vec::with_capacity(length);
+    for _ in 0..length {
+        let (item, next) = decode(item_type, remainder)?;
+        items.push(item);
+        remainder = next;
+    }
+    Ok((Value::Array(items), remainder))
+}
+
+fn decode_byte_array(length: usize, bytes: &[u8]) -> Result<(Value, &[u8]), bytesrepr::Error> {
+    if bytes.len() < length {
+        return Err(bytesrepr::Error::EarlyEndOfStream);
+    }
+    let (array, remainder) = bytes.split_at(length);
+    Ok((Value::String(hex::encode(array)), remainder))
+}
+
+fn decode_result<'a>(
+    ok: &CLType,
+    err: &CLType,
+    bytes: &'a [u8],
+) -> Result<(Value, &'a [u8]), bytesrepr::Error> {
+    match u8::from_bytes(bytes)? {
+        (RESULT_ERR_TAG, remainder) => {
+            let (error, remainder) = decode(err, remainder)?;
+            Ok((json!({ "Err": error }), remainder))
+        }
+        (RESULT_OK_TAG, remainder) => {
+            let (value, remainder) = decode(ok, remainder)?;
+            Ok((json!({ "Ok": value }), remainder))
+        }
+        _ => Err(bytesrepr::Error::Formatting),
+    }
+}
+
+/// Maps keyed by strings are decoded to objects, other maps to lists of entries.
+fn decode_map<'a>(
+    key: &CLType,
+    value: &CLType,
+    bytes: &'a [u8],
+) -> Result<(Value, &'a [u8]), bytesrepr::Error> {
+    let (length, mut remainder) = decode_length(bytes)?;
+    let mut object = Map::new();
+    let mut entries = Vec::new();
+    for _ in 0..length {
+        let (decoded_key, next) = decode(key, remainder)?;
+        let (decoded_value, next) = decode(value, next)?;
+        match decoded_key {
+            Value::String(string_key) if *key == CLType::String => {
+                object.insert(string_key, decoded_value);
+            }
+            _ => entries.push(json!({ "key": decoded_key, "value": decoded_value })),
+        }
+        remainder = next;
+    }
+    if *key == CLType::String {
+        Ok((Value::Object(object), remainder))
+    } else {
+        Ok((Value::Array(entries), remainder))
+    }
+}
+
+fn decode_tuple<'a>(
+    types: &[Box<CLType>],
+    mut bytes: &'a [u8],
+) -> Result<(Value, &'a [u8]), bytesrepr::Error> {
+    let mut items = Vec::with_capacity(types.len());
+    for item_type in types {
+        let (item, remainder) = decode(item_type, bytes)?;
+        items.push(item);
+        bytes = remainder;
+    }
+    Ok((Value::Array(items), bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use casper_types::{account::AccountHash, runtime_args, AccessRights};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn should_decode_args_to_typed_json() {
+        let purse = URef::new([2; 32], AccessRights::READ_ADD_WRITE);
+        let args = runtime_args! {
+            "amount" => U512::from(2_500_000_000u64),
+            "memo" => Some("gift".to_string()),
+            "id" => Option::<u64>::None,
+            "pair" => (true, -3i32),
+            "purse" => purse,
+            "checksum" => [0xab_u8; 4],
+            "outcome" => Result::<u8, String>::Err("failed".to_string()),
+        };
+
+        assert_eq!(
+            decode_runtime_args(&args),
+            json!([
+                { "name": "amount", "cl_type": "U512", "value": "2500000000" },
+                { "name": "memo", "cl_type": { "Option": "String" }, "value": "gift" },
+                { "name": "id", "cl_type": { "Option": "U64" }, "value": null },
+                { "name": "pair", "cl_type": { "Tuple2": ["Bool", "I32"] }, "value": [true, -3] },
+                { "name": "purse", "cl_type": "URef", "value": purse.to_formatted_string() },
+                { "name": "checksum", "cl_type": { "ByteArray": 4 }, "value": "abababab" },
+                {
+                    "name": "outcome",
+                    "cl_type": { "Result": { "ok": "U8", "err": "String" } },
+                    "value": { "Err": "failed" }
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn should_decode_maps_keyed_by_strings_to_objects() {
+        let mut balances = BTreeMap::new();
+        balances.insert("alice".to_string(), 5u64);
+        let mut holders = BTreeMap::new();
+        holders.insert(Key::Account(AccountHash::new([1; 32])), U256::from(7));
+        let args = runtime_args! {
+            "balances" => balances,
+            "holders" => holders,
+        };
+
+        assert_eq!(
+            decode_runtime_args(&args),
+            json!([
+                {
+                    "name": "balances",
+                    "cl_type": { "Map": { "key": "String", "value": "U64" } },
+                    "value": { "alice": 5 }
+                },
+                {
+                    "name": "holders",
+                    "cl_type": { "Map": { "key": "Key", "value": "U256" } },
+                    "value": [{
+                        "key": "account-hash-0101010101010101010101010101010101010101010101010101010101010101",
+                        "value": "7"
+                    }]
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn should_keep_bytes_of_undecodable_args() {
+        let mut args = RuntimeArgs::new();
+        args.insert_cl_value("any", CLValue::from_components(CLType::Any, vec![1, 2]));
+        args.insert_cl_value(
+            "truncated",
+            CLValue::from_components(CLType::U64, vec![1, 2]),
+        );
+        args.insert_cl_value(
+            "forged_length",
+            CLValue::from_components(CLType::List(Box::new(CLType::Unit)), vec![255; 4]),
+        );
+        args.insert_cl_value("trailing", CLValue::from_components(CLType::U8, vec![1, 2]));
+
+        assert_eq!(
+            decode_runtime_args(&args),
+            json!([
+                { "name": "any", "cl_type": "Any", "bytes": "0102" },
+                { "name": "truncated", "cl_type": "U64", "bytes": "0102" },
+                { "name": "forged_length", "cl_type": { "List": "Unit" }, "bytes": "ffffffff" },
+                { "name": "trailing", "cl_type": "U8", "bytes": "0102" },
+            ])
+        );
+    }
+}
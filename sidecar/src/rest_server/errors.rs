@@ -4,6 +4,8 @@ use crate::{
     types::database::DatabaseReadError,
     utils::{InvalidPath, Unexpected},
 };
+#[cfg(feature = "graphql")]
+use async_graphql_warp::GraphQLBadRequest;
 use http::StatusCode;
 #[cfg(test)]
 use hyper::body::HttpBody;
@@ -25,6 +27,21 @@ impl reject::Reject for InvalidParam {}
 pub(super) struct StorageError(pub(super) DatabaseReadError);
 impl reject::Reject for StorageError {}
 
+/// The node's RPC server didn't provide the requested data.
+#[derive(Debug)]
+pub(super) struct UpstreamError(pub(super) anyhow::Error);
+impl reject::Reject for UpstreamError {}
+
+/// The endpoint needs an optional feature which isn't configured.
+#[derive(Debug)]
+pub(super) struct NotEnabled(pub(super) &'static str);
+impl reject::Reject for NotEnabled {}
+
+/// The client pinned a schema version which isn't supported.
+#[derive(Debug)]
+pub(super) struct UnsupportedSchemaVersion(pub(super) String);
+impl reject::Reject for UnsupportedSchemaVersion {}
+
 /// Handle various REST server errors:
 /// - Unexpected internal server errors
 /// - Queries returning empty result sets
@@ -32,6 +49,10 @@ impl reject::Reject for StorageError {}
 /// - Database-related errors
 /// - Invalid request path errors
 /// - Invalid parameters in the request query
+/// - Errors of the node's RPC server
+/// - Requests to features which aren't enabled
+/// - Requests for unsupported schema versions
+/// - Malformed GraphQL requests
 pub(super) async fn handle_rejection(err: Rejection) -> Result<impl Reply, Infallible> {
     let code;
     let message;
@@ -52,6 +73,15 @@ pub(super) async fn handle_rejection(err: Rejection) -> Result<impl Reply, Infal
     } else if let Some(InvalidParam(err)) = err.find() {
         code = StatusCode::BAD_REQUEST;
         message = format!("Invalid parameter in query: {}", err);
+    } else if let Some(UpstreamError(err)) = err.find() {
+        code = StatusCode::BAD_GATEWAY;
+        message = format!("Error fetching from the node: {}", err);
+    } else if let Some(NotEnabled(feature)) = err.find() {
+        code = StatusCode::NOT_FOUND;
+        message = format!("{} are not enabled", feature);
+    } else if let Some(UnsupportedSchemaVersion(reason)) = err.find() {
+        code = StatusCode::NOT_ACCEPTABLE;
+        message = format!("Invalid Accept-Version header: {}", reason);
     } else {
         (code, message) = fallback_status_code_and_message(err)
     }
@@ -65,6 +95,13 @@ pub(super) async fn handle_rejection(err: Rejection) -> Result<impl Reply, Infal
 }
 
 fn fallback_status_code_and_message(err: Rejection) -> (StatusCode, String) {
+    #[cfg(feature = "graphql")]
+    if let Some(GraphQLBadRequest(err)) = err.find() {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!("Invalid GraphQL request: {}", err),
+        );
+    }
     let err_msg = format!(
         "Unexpected error in REST server - please file a bug report!\n{:?}",
         err
@@ -138,6 +175,34 @@ async fn should_handle_not_found() {
     assert_eq!(api_error.message, "Query returned no results");
 }
 
+#[tokio::test]
+async fn should_handle_upstream_error() {
+    let rejection = reject::custom(UpstreamError(anyhow::Error::msg("connection refused")));
+
+    let api_error = get_api_error_from_rejection(rejection).await;
+
+    assert_eq!(api_error.code, 502);
+    assert_eq!(
+        api_error.message,
+        "Error fetching from the node: connection refused"
+    );
+}
+
+#[tokio::test]
+async fn should_handle_unsupported_schema_version() {
+    let rejection = reject::custom(UnsupportedSchemaVersion(
+        "unsupported schema version '3': expected 1 to 2".to_string(),
+    ));
+
+    let api_error = get_api_error_from_rejection(rejection).await;
+
+    assert_eq!(api_error.code, 406);
+    assert_eq!(
+        api_error.message,
+        "Invalid Accept-Version header: unsupported schema version '3': expected 1 to 2"
+    );
+}
+
 #[tokio::test]
 async fn should_handle_serialisation_error() {
     let rejection = serde_json::from_str::<i32>("")
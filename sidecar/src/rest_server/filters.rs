@@ -1,5 +1,16 @@
-use super::{errors::handle_rejection, handlers, openapi::build_open_api_filters};
+#[cfg(feature = "graphql")]
+use super::graphql::schema as graphql_schema;
+use super::{
+    errors::{handle_rejection, StorageError, UnsupportedSchemaVersion},
+    handlers,
+    openapi::build_open_api_filters,
+    streaming::{FormatQuery, TableFormatQuery},
+};
 use crate::{
+    account_aliases::AccountAliases,
+    deploy_outcomes::DeployOutcomes,
+    schema_version::{self, SchemaVersion, ACCEPT_VERSION_HEADER, SCHEMA_VERSION_HEADER},
+    state_proofs::StateProofs,
     types::database::DatabaseReader,
     utils::{root_filter, InvalidPath},
 };
@@ -7,21 +18,84 @@ use std::convert::Infallible;
 use warp::Filter;
 
 /// Helper function to specify available filters.
-/// Input: the database with data to be filtered.
-/// Return: the filtered data.
-pub(super) fn combined_filters<Db: DatabaseReader + Clone + Send + Sync>(
+/// Input: the database with data to be filtered, if enabled, the proxy of state proofs, the
+/// account aliases and the notifications of the outcomes of deploys.
+/// Return: the filtered data, named with its schema version.
+pub(super) fn combined_filters<Db: DatabaseReader + Clone + Send + Sync + 'static>(
     db: Db,
+    maybe_state_proofs: Option<StateProofs>,
+    account_aliases: AccountAliases,
+    deploy_outcomes: DeployOutcomes,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Infallible> + Clone {
-    root_filter()
+    accepted_schema_version()
+        .and(routes(
+            db,
+            maybe_state_proofs,
+            account_aliases,
+            deploy_outcomes,
+        ))
+        .map(with_schema_version)
+        .recover(handle_rejection)
+}
+
+fn routes<Db: DatabaseReader + Clone + Send + Sync + 'static>(
+    db: Db,
+    maybe_state_proofs: Option<StateProofs>,
+    account_aliases: AccountAliases,
+    deploy_outcomes: DeployOutcomes,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let routes = root_filter()
         .or(root_and_invalid_path())
         .or(block_filters(db.clone()))
-        .or(deploy_filters(db.clone()))
+        .or(deploy_filters(
+            db.clone(),
+            account_aliases.clone(),
+            deploy_outcomes,
+        ))
+        .or(deploys_affecting_account(
+            db.clone(),
+            account_aliases.clone(),
+        ))
+        .or(transfers_by_account(db.clone(), account_aliases.clone()))
+        .or(transfers_by_block(db.clone()))
+        .or(transfers_by_transfer_id(db.clone()))
+        .or(token_transfers(db.clone()))
+        .or(nft_events_by_collection(db.clone()))
+        .or(nft_events_by_token(db.clone()))
         .or(step_by_era(db.clone()))
         .or(faults_by_public_key(db.clone()))
         .or(faults_by_era(db.clone()))
-        .or(finality_signatures_by_block(db))
-        .or(build_open_api_filters())
-        .recover(handle_rejection)
+        .or(validator_set(db.clone()))
+        .or(equivocators_by_era(db.clone()))
+        .or(finality_signatures_by_block(db.clone()))
+        .or(block_finality_signatures(db.clone()))
+        .or(contract_stats(db.clone()))
+        .or(deploy_size_stats(db.clone()))
+        .or(enrichments_by_hash(db.clone()))
+        .or(deploy_approvals(db.clone(), maybe_state_proofs.clone()))
+        .or(state_proof(db.clone(), maybe_state_proofs));
+    #[cfg(feature = "graphql")]
+    let routes = routes.or(graphql(db, account_aliases));
+    routes.or(build_open_api_filters())
+}
+
+/// Negotiates the schema version of the response from the `Accept-Version` header of the request.
+/// Return: the schema version to serve, or a rejection if the client pinned an unsupported one.
+fn accepted_schema_version(
+) -> impl Filter<Extract = (SchemaVersion,), Error = warp::Rejection> + Clone {
+    warp::header::optional::<String>(ACCEPT_VERSION_HEADER).and_then(
+        |maybe_accept_version: Option<String>| async move {
+            schema_version::negotiate(maybe_accept_version.as_deref())
+                .map_err(|reason| warp::reject::custom(UnsupportedSchemaVersion(reason)))
+        },
+    )
+}
+
+fn with_schema_version<R: warp::Reply>(
+    schema_version: SchemaVersion,
+    reply: R,
+) -> impl warp::Reply {
+    warp::reply::with_header(reply, SCHEMA_VERSION_HEADER, schema_version.to_string())
 }
 
 /// Handle the case where an invalid path was provided.
@@ -38,24 +112,35 @@ fn root_and_invalid_path(
 /// Helper function to specify available filters for block information.
 /// Input: the database with data to be filtered.
 /// Return: the filtered data.
-fn block_filters<Db: DatabaseReader + Clone + Send + Sync>(
+fn block_filters<Db: DatabaseReader + Clone + Send + Sync + 'static>(
     db: Db,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     latest_block(db.clone())
         .or(block_by_hash(db.clone()))
-        .or(block_by_height(db))
+        .or(block_by_height(db.clone()))
+        .or(raw_block_by_hash(db.clone()))
+        .or(blocks_by_height_range(db))
 }
 
 /// Helper function to specify available filters for deploy information.
-/// Input: the database with data to be filtered.
+/// Input: the database with data to be filtered, the account aliases and the notifications of the
+/// outcomes of deploys.
 /// Return: the filtered data.
-fn deploy_filters<Db: DatabaseReader + Clone + Send + Sync>(
+fn deploy_filters<Db: DatabaseReader + Clone + Send + Sync + 'static>(
     db: Db,
+    account_aliases: AccountAliases,
+    deploy_outcomes: DeployOutcomes,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
-    deploy_by_hash(db.clone())
-        .or(deploy_accepted_by_hash(db.clone()))
+    deploy_by_hash(db.clone(), account_aliases.clone())
+        .or(deploy_accepted_by_hash(db.clone(), account_aliases.clone()))
         .or(deploy_processed_by_hash(db.clone()))
-        .or(deploy_expired_by_hash(db))
+        .or(deploy_expired_by_hash(db.clone()))
+        .or(raw_deploy_by_hash(db.clone()))
+        .or(deploy_status(db.clone()))
+        .or(await_deploy(db.clone(), deploy_outcomes))
+        .or(deploys_by_time_range(db.clone()))
+        .or(deploys_by_error_message(db.clone()))
+        .or(deploys_by_account(db, account_aliases))
 }
 
 /// Return information about the last block added to the linear chain.
@@ -103,6 +188,59 @@ fn block_by_hash<Db: DatabaseReader + Clone + Send + Sync>(
         .and_then(handlers::get_block_by_hash)
 }
 
+/// Return a block in the form in which the node serializes it, given its block hash, so that its
+/// hash can be verified against the canonical bytes. The bytes are derived from the stored block.
+/// Input: the database with data to be filtered.
+/// Return: the serialized block specified, as `application/octet-stream`.
+/// Path URL: block/<block-hash>/raw
+/// Example: curl http://127.0.0.1:18888/block/c0292d8408e9d83d1aaceadfbeb25dc38cda36bcb91c3d403a0deb594dc3d63f/raw
+#[utoipa::path(
+    get,
+    path = "/block/{block_hash}/raw",
+    params(
+        ("block_hash" = String, Path, description = "Base64 encoded block hash of requested block")
+    ),
+    responses(
+        (status = 200, description = "serialized stored block", content_type = "application/octet-stream", body = Vec<u8>)
+    )
+)]
+fn raw_block_by_hash<Db: DatabaseReader + Clone + Send + Sync>(
+    db: Db,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("block" / String / "raw")
+        .and(warp::get())
+        .and(with_db(db))
+        .and_then(handlers::get_raw_block_by_hash)
+}
+
+/// Return the blocks within a range of heights, ordered by height. The blocks are streamed from
+/// the database page by page.
+/// Input: the database with data to be filtered.
+/// Return: the blocks with a height between `from` and `to`, both inclusive.
+/// Path URL: blocks?from=<block-height>&to=<block-height>
+/// Example: curl "http://127.0.0.1:18888/blocks?from=630000&to=630151&format=ndjson"
+#[utoipa::path(
+    get,
+    path = "/blocks",
+    params(
+        ("from" = Option<u64>, Query, description = "Height of the lowest requested block, 0 by default"),
+        ("to" = Option<u64>, Query, description = "Height of the highest requested block, unbounded by default"),
+        ("format" = Option<String>, Query, description = "Format of the list, `json` (default) or `ndjson` for one item per line")
+    ),
+    responses(
+        (status = 200, description = "blocks within the range of heights", body = [BlockAdded])
+    )
+)]
+fn blocks_by_height_range<Db: DatabaseReader + Clone + Send + Sync + 'static>(
+    db: Db,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("blocks")
+        .and(warp::get())
+        .and(warp::query::<handlers::BlockRangeQuery>())
+        .and(with_db(db))
+        .and_then(handlers::get_blocks_by_height_range)
+}
+
 /// Return information about a block given a specific block height.
 /// Input: the database with data to be filtered.
 /// Return: data about the block requested.
@@ -131,13 +269,14 @@ fn block_by_height<Db: DatabaseReader + Clone + Send + Sync>(
 /// The output differs depending on the deploy's status, which changes over time as the deploy goes through its lifecycle.
 /// Input: the database with data to be filtered.
 /// Return: data about the deploy specified.
-/// Path URL: deploy/<deploy-hash>
+/// Path URL: deploy/<deploy-hash>[?decode_args=true]
 /// Example: curl http://127.0.0.1:18888/deploy/f01544d37354c5f9b2c4956826d32f8e44198f94fb6752e87f422fe3071ab58a
 #[utoipa::path(
     get,
     path = "/deploy/{deploy_hash}",
     params(
-        ("deploy_hash" = String, Path, description = "Base64 encoded deploy hash of requested deploy")
+        ("deploy_hash" = String, Path, description = "Base64 encoded deploy hash of requested deploy"),
+        ("decode_args" = Option<bool>, Query, description = "Whether to decode the payment and session arguments to typed JSON instead of hex-encoded bytes")
     ),
     responses(
         (status = 200, description = "fetch aggregate data for deploy events", body = DeployAggregate)
@@ -145,23 +284,313 @@ fn block_by_height<Db: DatabaseReader + Clone + Send + Sync>(
 )]
 fn deploy_by_hash<Db: DatabaseReader + Clone + Send + Sync>(
     db: Db,
+    account_aliases: AccountAliases,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("deploy" / String)
         .and(warp::get())
+        .and(warp::query::<handlers::DeployQuery>())
         .and(with_db(db))
+        .and(with_account_aliases(account_aliases))
         .and_then(handlers::get_deploy_by_hash)
 }
 
+/// Return the deploys accepted or processed within a time range, ordered by time. Accepted
+/// deploys are timestamped with the time the sidecar stored them, processed deploys with the
+/// timestamp of their block. Each deploy is listed once per event.
+/// Input: the database with data to be filtered.
+/// Return: the deploy hashes, events and timestamps between `from` (inclusive) and `to`
+/// (exclusive).
+/// Path URL: deploys?from=<date-or-timestamp>&to=<date-or-timestamp>
+/// Example: curl "http://127.0.0.1:18888/deploys?from=2024-05-01&to=2024-05-02"
+#[utoipa::path(
+    get,
+    path = "/deploys",
+    params(
+        ("from" = String, Query, description = "Start of the time range, a date such as `2024-05-01` or an RFC 3339 timestamp"),
+        ("to" = String, Query, description = "End of the time range (exclusive), a date such as `2024-05-02` or an RFC 3339 timestamp"),
+        ("format" = Option<String>, Query, description = "Format of the list, `json` (default), `ndjson` for one item per line or `csv` for a CSV table with a header row")
+    ),
+    responses(
+        (status = 200, description = "deploys accepted or processed within the time range", body = [DeployTimestampEntry])
+    )
+)]
+fn deploys_by_time_range<Db: DatabaseReader + Clone + Send + Sync + 'static>(
+    db: Db,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("deploys")
+        .and(warp::get())
+        .and(warp::query::<handlers::DeployTimeRangeQuery>())
+        .and(with_db(db))
+        .and_then(handlers::get_deploys_by_time_range)
+}
+
+/// Return the deploys whose execution failed with an error message containing the given text,
+/// e.g. a revert code, ordered by deploy hash.
+/// Input: the database with data to be filtered.
+/// Return: the deploy hashes, block hashes and error messages of the matching deploys.
+/// Path URL: deploys/errors?contains=<text>
+/// Example: curl "http://127.0.0.1:18888/deploys/errors?contains=User%20error%3A%201"
+#[utoipa::path(
+    get,
+    path = "/deploys/errors",
+    params(
+        ("contains" = String, Query, description = "Text the error message has to contain, e.g. `User error: 1`"),
+        ("format" = Option<String>, Query, description = "Format of the list, `json` (default), `ndjson` for one item per line or `csv` for a CSV table with a header row")
+    ),
+    responses(
+        (status = 200, description = "failed deploys with a matching error message", body = [DeployErrorEntry])
+    )
+)]
+fn deploys_by_error_message<Db: DatabaseReader + Clone + Send + Sync + 'static>(
+    db: Db,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("deploys" / "errors")
+        .and(warp::get())
+        .and(warp::query::<handlers::DeployErrorQuery>())
+        .and(with_db(db))
+        .and_then(handlers::get_deploys_by_error_message)
+}
+
+/// Return the deploys sent by an account, given as a public key, an account hash or an `@`-prefixed
+/// alias, ordered by deploy hash, or a page of them ordered by the time they were accepted if
+/// `limit` or `offset` is given. Only the deploys accepted since the sidecar indexes accounts are
+/// listed.
+/// Input: the database with data to be filtered and the account aliases.
+/// Return: the deploy hashes of the account, along with its hash and its alias, if known.
+/// Path URL: deploys/account/<public-key|account-hash|@alias>
+/// Example: curl http://127.0.0.1:18888/deploys/account/@treasury
+#[utoipa::path(
+    get,
+    path = "/deploys/account/{account}",
+    params(
+        ("account" = String, Path, description = "Hex-encoded public key or account hash, `account-hash-` formatted string or `@`-prefixed alias of the account"),
+        ("at_height" = Option<u64>, Query, description = "If given, only the deploys processed at or below this block height are listed"),
+        ("limit" = Option<u32>, Query, description = "If given, at most this many deploys, up to 1000, are listed, ordered by the time they were accepted"),
+        ("offset" = Option<u32>, Query, description = "If given, the deploys are listed ordered by the time they were accepted, skipping this many"),
+        ("format" = Option<String>, Query, description = "Format of the list, `json` (default), `ndjson` for one item per line or `csv` for a CSV table with a header row")
+    ),
+    responses(
+        (status = 200, description = "deploys sent by the account", body = [DeployAccountEntry])
+    )
+)]
+fn deploys_by_account<Db: DatabaseReader + Clone + Send + Sync + 'static>(
+    db: Db,
+    account_aliases: AccountAliases,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("deploys" / "account" / String)
+        .and(warp::get())
+        .and(warp::query::<handlers::AccountDeploysQuery>())
+        .and(with_db(db))
+        .and(with_account_aliases(account_aliases))
+        .and_then(handlers::get_deploys_by_account)
+}
+
+/// Return the deploys whose execution touched the state of an account, given as a public key, an
+/// account hash or an `@`-prefixed alias, ordered by deploy hash. Only the deploys processed while
+/// the sidecar indexes account effects are listed.
+/// Input: the database with data to be filtered and the account aliases.
+/// Return: the deploy hashes affecting the account, along with its hash.
+/// Path URL: account/<public-key|account-hash|@alias>/affected-by
+/// Example: curl http://127.0.0.1:18888/account/@treasury/affected-by
+#[utoipa::path(
+    get,
+    path = "/account/{account}/affected-by",
+    params(
+        ("account" = String, Path, description = "Hex-encoded public key or account hash, `account-hash-` formatted string or `@`-prefixed alias of the account"),
+        ("format" = Option<String>, Query, description = "Format of the list, `json` (default), `ndjson` for one item per line or `csv` for a CSV table with a header row")
+    ),
+    responses(
+        (status = 200, description = "deploys whose execution touched the state of the account", body = [AccountEffectEntry])
+    )
+)]
+fn deploys_affecting_account<Db: DatabaseReader + Clone + Send + Sync + 'static>(
+    db: Db,
+    account_aliases: AccountAliases,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("account" / String / "affected-by")
+        .and(warp::get())
+        .and(warp::query::<TableFormatQuery>())
+        .and(with_db(db))
+        .and(with_account_aliases(account_aliases))
+        .and_then(handlers::get_deploys_affecting_account)
+}
+
+/// Return the native transfers sent or received by an account, given as a public key, an account
+/// hash or an `@`-prefixed alias, ordered by transfer hash.
+/// Input: the database with data to be filtered and the account aliases.
+/// Return: the transfers of the account.
+/// Path URL: transfers/account/<public-key|account-hash|@alias>
+/// Example: curl http://127.0.0.1:18888/transfers/account/@treasury
+#[utoipa::path(
+    get,
+    path = "/transfers/account/{account}",
+    params(
+        ("account" = String, Path, description = "Hex-encoded public key or account hash, `account-hash-` formatted string or `@`-prefixed alias of the account"),
+        ("format" = Option<String>, Query, description = "Format of the list, `json` (default), `ndjson` for one item per line or `csv` for a CSV table with a header row")
+    ),
+    responses(
+        (status = 200, description = "native transfers sent or received by the account", body = [TransferEntry])
+    )
+)]
+fn transfers_by_account<Db: DatabaseReader + Clone + Send + Sync + 'static>(
+    db: Db,
+    account_aliases: AccountAliases,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("transfers" / "account" / String)
+        .and(warp::get())
+        .and(warp::query::<TableFormatQuery>())
+        .and(with_db(db))
+        .and(with_account_aliases(account_aliases))
+        .and_then(handlers::get_transfers_by_account)
+}
+
+/// Return the native transfers of the deploys processed in a block given its block hash, ordered
+/// by transfer hash.
+/// Input: the database with data to be filtered.
+/// Return: the transfers of the block.
+/// Path URL: transfers/block/<block-hash>
+/// Example: curl http://127.0.0.1:18888/transfers/block/c0292d8408e9d83d1aaceadfbeb25dc38cda36bcb91c3d403a0deb594dc3d63f
+#[utoipa::path(
+    get,
+    path = "/transfers/block/{block_hash}",
+    params(
+        ("block_hash" = String, Path, description = "Hex-encoded hash of the block"),
+        ("format" = Option<String>, Query, description = "Format of the list, `json` (default), `ndjson` for one item per line or `csv` for a CSV table with a header row")
+    ),
+    responses(
+        (status = 200, description = "native transfers of the deploys processed in the block", body = [TransferEntry])
+    )
+)]
+fn transfers_by_block<Db: DatabaseReader + Clone + Send + Sync + 'static>(
+    db: Db,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("transfers" / "block" / String)
+        .and(warp::get())
+        .and(warp::query::<TableFormatQuery>())
+        .and(with_db(db))
+        .and_then(handlers::get_transfers_by_block)
+}
+
+/// Return the native transfers with a given user-defined id, also known as their memo, ordered by
+/// transfer hash.
+/// Input: the database with data to be filtered.
+/// Return: the transfers with the id.
+/// Path URL: transfers?transfer_id=<id>
+/// Example: curl http://127.0.0.1:18888/transfers?transfer_id=12345
+#[utoipa::path(
+    get,
+    path = "/transfers",
+    params(
+        ("transfer_id" = u64, Query, description = "User-defined id of the transfers"),
+        ("format" = Option<String>, Query, description = "Format of the list, `json` (default), `ndjson` for one item per line or `csv` for a CSV table with a header row")
+    ),
+    responses(
+        (status = 200, description = "native transfers with the id", body = [TransferEntry])
+    )
+)]
+fn transfers_by_transfer_id<Db: DatabaseReader + Clone + Send + Sync + 'static>(
+    db: Db,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("transfers")
+        .and(warp::get())
+        .and(warp::query::<handlers::TransferIdQuery>())
+        .and(with_db(db))
+        .and_then(handlers::get_transfers_by_transfer_id)
+}
+
+/// Return the transfers of a CEP-18 token given the hash of its contract, ordered by the id of the
+/// events they were decoded from.
+/// Input: the database with data to be filtered.
+/// Return: the transfers of the token.
+/// Path URL: tokens/<contract-hash>/transfers
+/// Example: curl http://127.0.0.1:18888/tokens/b1a1e0c7d3fa9bc6a9fe1e83c4b8e2f7c1b6d8e0a6f0bcb83e1c5e6a3f5b7c2d/transfers
+#[utoipa::path(
+    get,
+    path = "/tokens/{contract_hash}/transfers",
+    params(
+        ("contract_hash" = String, Path, description = "Hex-encoded hash of the token contract"),
+        ("format" = Option<String>, Query, description = "Format of the list, `json` (default), `ndjson` for one item per line or `csv` for a CSV table with a header row")
+    ),
+    responses(
+        (status = 200, description = "transfers of the token", body = [TokenTransferEntry])
+    )
+)]
+fn token_transfers<Db: DatabaseReader + Clone + Send + Sync + 'static>(
+    db: Db,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("tokens" / String / "transfers")
+        .and(warp::get())
+        .and(warp::query::<TableFormatQuery>())
+        .and(with_db(db))
+        .and_then(handlers::get_token_transfers)
+}
+
+/// Return the events of a CEP-78 NFT collection given the hash of its contract, ordered by event
+/// id.
+/// Input: the database with data to be filtered.
+/// Return: the events of the collection.
+/// Path URL: nfts/<contract-hash>/events
+/// Example: curl http://127.0.0.1:18888/nfts/5f2e3a9c0b7d4e6f8a1c3b5d7e9f0a2c4b6d8e0f1a3c5e7b9d0f2a4c6e8b0d1f/events
+#[utoipa::path(
+    get,
+    path = "/nfts/{contract_hash}/events",
+    params(
+        ("contract_hash" = String, Path, description = "Hex-encoded hash of the collection contract"),
+        ("format" = Option<String>, Query, description = "Format of the list, `json` (default), `ndjson` for one item per line or `csv` for a CSV table with a header row")
+    ),
+    responses(
+        (status = 200, description = "events of the collection", body = [NftEventEntry])
+    )
+)]
+fn nft_events_by_collection<Db: DatabaseReader + Clone + Send + Sync + 'static>(
+    db: Db,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("nfts" / String / "events")
+        .and(warp::get())
+        .and(warp::query::<TableFormatQuery>())
+        .and(with_db(db))
+        .and_then(handlers::get_nft_events_by_collection)
+}
+
+/// Return the events of a token of a CEP-78 NFT collection given the hash of the collection
+/// contract and the identifier of the token, ordered by event id.
+/// Input: the database with data to be filtered.
+/// Return: the events of the token.
+/// Path URL: nfts/<contract-hash>/tokens/<token-id>/events
+/// Example: curl http://127.0.0.1:18888/nfts/5f2e3a9c0b7d4e6f8a1c3b5d7e9f0a2c4b6d8e0f1a3c5e7b9d0f2a4c6e8b0d1f/tokens/42/events
+#[utoipa::path(
+    get,
+    path = "/nfts/{contract_hash}/tokens/{token_id}/events",
+    params(
+        ("contract_hash" = String, Path, description = "Hex-encoded hash of the collection contract"),
+        ("token_id" = String, Path, description = "Identifier of the token, its index or its hash depending on the identifier mode of the collection"),
+        ("format" = Option<String>, Query, description = "Format of the list, `json` (default), `ndjson` for one item per line or `csv` for a CSV table with a header row")
+    ),
+    responses(
+        (status = 200, description = "events of the token", body = [NftEventEntry])
+    )
+)]
+fn nft_events_by_token<Db: DatabaseReader + Clone + Send + Sync + 'static>(
+    db: Db,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("nfts" / String / "tokens" / String / "events")
+        .and(warp::get())
+        .and(warp::query::<TableFormatQuery>())
+        .and(with_db(db))
+        .and_then(handlers::get_nft_events_by_token)
+}
+
 /// Return information about an accepted deploy given its deploy hash.
 /// Input: the database with data to be filtered.
 /// Return: data about the accepted deploy.
-/// Path URL: deploy/accepted/<deploy-hash>
+/// Path URL: deploy/accepted/<deploy-hash>[?decode_args=true]
 /// Example: curl http://127.0.0.1:18888/deploy/accepted/f01544d37354c5f9b2c4956826d32f8e44198f94fb6752e87f422fe3071ab58a
 #[utoipa::path(
     get,
     path = "/deploy/accepted/{deploy_hash}",
     params(
-        ("deploy_hash" = String, Path, description = "Base64 encoded deploy hash of requested deploy accepted")
+        ("deploy_hash" = String, Path, description = "Base64 encoded deploy hash of requested deploy accepted"),
+        ("decode_args" = Option<bool>, Query, description = "Whether to decode the payment and session arguments to typed JSON instead of hex-encoded bytes")
     ),
     responses(
         (status = 200, description = "fetch stored deploy", body = DeployAccepted)
@@ -169,13 +598,126 @@ fn deploy_by_hash<Db: DatabaseReader + Clone + Send + Sync>(
 )]
 fn deploy_accepted_by_hash<Db: DatabaseReader + Clone + Send + Sync>(
     db: Db,
+    account_aliases: AccountAliases,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("deploy" / "accepted" / String)
         .and(warp::get())
+        .and(warp::query::<handlers::DeployQuery>())
         .and(with_db(db))
+        .and(with_account_aliases(account_aliases))
         .and_then(handlers::get_deploy_accepted_by_hash)
 }
 
+/// Return a deploy in the form in which the node serializes it, given its deploy hash, so that its
+/// hash and approvals can be verified against the canonical bytes. The bytes are derived from the
+/// stored accepted deploy.
+/// Input: the database with data to be filtered.
+/// Return: the serialized deploy specified, as `application/octet-stream`.
+/// Path URL: deploy/<deploy-hash>/raw
+/// Example: curl http://127.0.0.1:18888/deploy/f01544d37354c5f9b2c4956826d32f8e44198f94fb6752e87f422fe3071ab58a/raw
+#[utoipa::path(
+    get,
+    path = "/deploy/{deploy_hash}/raw",
+    params(
+        ("deploy_hash" = String, Path, description = "Base64 encoded deploy hash of requested deploy")
+    ),
+    responses(
+        (status = 200, description = "serialized stored deploy", content_type = "application/octet-stream", body = Vec<u8>)
+    )
+)]
+fn raw_deploy_by_hash<Db: DatabaseReader + Clone + Send + Sync>(
+    db: Db,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("deploy" / String / "raw")
+        .and(warp::get())
+        .and(with_db(db))
+        .and_then(handlers::get_raw_deploy_by_hash)
+}
+
+/// Return the approvals of a deploy, given its deploy hash, including those only carried by a later
+/// acceptance of the deploy. If state proofs are enabled, the weights of the signers and the
+/// deployment threshold of the account which sent the deploy are fetched from the node's RPC server
+/// to show whether the approvals collected enough weight.
+/// Input: the database with data to be filtered and the proxy of state proofs.
+/// Return: the approvals of the deploy specified and the weight they collected.
+/// Path URL: deploy/<deploy-hash>/approvals
+/// Example: curl http://127.0.0.1:18888/deploy/f01544d37354c5f9b2c4956826d32f8e44198f94fb6752e87f422fe3071ab58a/approvals
+#[utoipa::path(
+    get,
+    path = "/deploy/{deploy_hash}/approvals",
+    params(
+        ("deploy_hash" = String, Path, description = "Hex-encoded deploy hash of the requested deploy")
+    ),
+    responses(
+        (status = 200, description = "approvals of the stored deploy and the weight they collected", body = DeployApprovals)
+    )
+)]
+fn deploy_approvals<Db: DatabaseReader + Clone + Send + Sync>(
+    db: Db,
+    maybe_state_proofs: Option<StateProofs>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("deploy" / String / "approvals")
+        .and(warp::get())
+        .and(with_snapshot(db))
+        .and(warp::any().map(move || maybe_state_proofs.clone()))
+        .and_then(handlers::get_deploy_approvals)
+}
+
+/// Return the lifecycle status of a deploy, given its deploy hash, combining its stored
+/// `DeployAccepted`, `DeployProcessed` and `DeployExpired` events: its current state, one of
+/// `pending`, `executed_success`, `executed_failure` or `expired`, and the time of each transition.
+/// Input: the database with data to be filtered.
+/// Return: the lifecycle status of the deploy specified.
+/// Path URL: deploy/<deploy-hash>/status
+/// Example: curl http://127.0.0.1:18888/deploy/f01544d37354c5f9b2c4956826d32f8e44198f94fb6752e87f422fe3071ab58a/status
+#[utoipa::path(
+    get,
+    path = "/deploy/{deploy_hash}/status",
+    params(
+        ("deploy_hash" = String, Path, description = "Hex-encoded deploy hash of the requested deploy")
+    ),
+    responses(
+        (status = 200, description = "lifecycle status of the stored deploy", body = DeployStatus)
+    )
+)]
+fn deploy_status<Db: DatabaseReader + Clone + Send + Sync>(
+    db: Db,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("deploy" / String / "status")
+        .and(warp::get())
+        .and(with_snapshot(db))
+        .and_then(handlers::get_deploy_status)
+}
+
+/// Await the outcome of a deploy, given its deploy hash: respond with its lifecycle status as soon
+/// as it is processed or expired, or once the timeout elapses, whichever comes first.
+/// Input: the database with data to be filtered and the notifications of the outcomes of deploys.
+/// Return: the lifecycle status of the deploy specified.
+/// Path URL: deploy/<deploy-hash>/await?timeout=<seconds>s
+/// Example: curl http://127.0.0.1:18888/deploy/f01544d37354c5f9b2c4956826d32f8e44198f94fb6752e87f422fe3071ab58a/await?timeout=60s
+#[utoipa::path(
+    get,
+    path = "/deploy/{deploy_hash}/await",
+    params(
+        ("deploy_hash" = String, Path, description = "Hex-encoded deploy hash of the awaited deploy"),
+        ("timeout" = Option<String>, Query, description = "How long to await the deploy, e.g. `60s`, at most `300s`, `60s` by default")
+    ),
+    responses(
+        (status = 200, description = "lifecycle status of the stored deploy once it is processed or expired, or when the timeout elapses", body = DeployStatus)
+    )
+)]
+fn await_deploy<Db: DatabaseReader + Clone + Send + Sync>(
+    db: Db,
+    deploy_outcomes: DeployOutcomes,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("deploy" / String / "await")
+        .and(warp::get())
+        .and(warp::query::<handlers::AwaitDeployQuery>())
+        .and(with_db(db))
+        .and(warp::any().map(move || deploy_outcomes.clone()))
+        .and_then(handlers::await_deploy)
+}
+
 #[utoipa::path(
     get,
     path = "/deploy/expired/{deploy_hash}",
@@ -228,7 +770,8 @@ fn deploy_processed_by_hash<Db: DatabaseReader + Clone + Send + Sync>(
     get,
     path = "/faults/{public_key}",
     params(
-        ("public_key" = String, Path, description = "Base64 encoded validator's public key")
+        ("public_key" = String, Path, description = "Base64 encoded validator's public key"),
+        ("format" = Option<String>, Query, description = "Format of the list, `json` (default) or `ndjson` for one item per line")
     ),
     responses(
         (status = 200, description = "faults associated with a validator's public key", body = [Fault])
@@ -244,6 +787,7 @@ fn faults_by_public_key<Db: DatabaseReader + Clone + Send + Sync>(
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("faults" / String)
         .and(warp::get())
+        .and(warp::query::<FormatQuery>())
         .and(with_db(db))
         .and_then(handlers::get_faults_by_public_key)
 }
@@ -252,7 +796,8 @@ fn faults_by_public_key<Db: DatabaseReader + Clone + Send + Sync>(
     get,
     path = "/faults/{era}",
     params(
-        ("era" = String, Path, description = "Era identifier")
+        ("era" = String, Path, description = "Era identifier"),
+        ("format" = Option<String>, Query, description = "Format of the list, `json` (default) or `ndjson` for one item per line")
     ),
     responses(
         (status = 200, description = "faults associated with an era ", body = [Fault])
@@ -268,15 +813,118 @@ fn faults_by_era<Db: DatabaseReader + Clone + Send + Sync>(
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("faults" / u64)
         .and(warp::get())
+        .and(warp::query::<FormatQuery>())
         .and(with_db(db))
         .and_then(handlers::get_faults_by_era)
 }
 
+#[utoipa::path(
+    get,
+    path = "/contract/{contract_hash}/stats",
+    params(
+        ("contract_hash" = String, Path, description = "Hex-encoded hash of the contract, or contract package, called by the sessions of deploys"),
+        ("format" = Option<String>, Query, description = "Format of the list, `json` (default) or `ndjson` for one item per line")
+    ),
+    responses(
+        (status = 200, description = "daily outcomes of the deploys calling the contract and the gas they spent", body = [ContractDayStats])
+    )
+)]
+/// Return the daily rollups of the deploys calling a contract.
+/// Input: the database with data to be filtered.
+/// Return: the successes, failures and gas spent of the deploys calling the contract, per day.
+/// Path URL: contract/<contract-hash>/stats
+/// Example: curl http://127.0.0.1:18888/contract/c0292d8408e9d83d1aaceadfbeb25dc38cda36bcb91c3d403a0deb594dc3d63f/stats
+fn contract_stats<Db: DatabaseReader + Clone + Send + Sync>(
+    db: Db,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("contract" / String / "stats")
+        .and(warp::get())
+        .and(warp::query::<FormatQuery>())
+        .and(with_db(db))
+        .and_then(handlers::get_contract_stats)
+}
+
+#[utoipa::path(
+    get,
+    path = "/stats/deploys/size",
+    responses(
+        (status = 200, description = "distribution of the size, approvals count and args count of the accepted deploys", body = DeploySizeStats)
+    )
+)]
+/// Return the distribution statistics of the size and weight of the accepted deploys.
+/// Input: the database with data to be filtered.
+/// Return: the minimum, maximum, mean and percentiles of the serialized size, the number of
+/// approvals and the number of args of the deploys.
+/// Path URL: stats/deploys/size
+/// Example: curl http://127.0.0.1:18888/stats/deploys/size
+fn deploy_size_stats<Db: DatabaseReader + Clone + Send + Sync>(
+    db: Db,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("stats" / "deploys" / "size")
+        .and(warp::get())
+        .and(with_db(db))
+        .and_then(handlers::get_deploy_size_stats)
+}
+
+#[utoipa::path(
+    get,
+    path = "/validators",
+    params(
+        ("at_height" = Option<u64>, Query, description = "Height of the block whose era the validators are the ones of, the latest stored block if not given")
+    ),
+    responses(
+        (status = 200, description = "validators of the era of the block", body = ValidatorSet)
+    )
+)]
+/// Return the validator set of the era of a block.
+/// Input: the database with data to be filtered.
+/// Return: the validators of the era of the block at the given height, or of the latest block.
+/// Path URL: validators?at_height=<block-height>
+/// Example: curl http://127.0.0.1:18888/validators?at_height=630151
+fn validator_set<Db: DatabaseReader + Clone + Send + Sync>(
+    db: Db,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("validators")
+        .and(warp::get())
+        .and(warp::query::<handlers::AtHeightQuery>())
+        .and(with_snapshot(db))
+        .and_then(handlers::get_validator_set)
+}
+
+#[utoipa::path(
+    get,
+    path = "/validators/equivocators",
+    params(
+        ("era" = u64, Query, description = "Era identifier"),
+        ("at_height" = Option<u64>, Query, description = "If given, the equivocators are the ones known as of the block at this height"),
+        ("format" = Option<String>, Query, description = "Format of the list, `json` (default) or `ndjson` for one item per line")
+    ),
+    responses(
+        (status = 200, description = "validators reported in a fault of the era, and whether they were ejected from the next era", body = [Equivocator])
+    )
+)]
+/// Return the validators which equivocated in an era.
+/// Input: the database with data to be filtered.
+/// Return: the validators reported in a fault of the era, and whether they are missing from the
+/// validator set of the next era.
+/// Path URL: validators/equivocators?era=<era-ID>
+/// Example: curl http://127.0.0.1:18888/validators/equivocators?era=2304
+fn equivocators_by_era<Db: DatabaseReader + Clone + Send + Sync>(
+    db: Db,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("validators" / "equivocators")
+        .and(warp::get())
+        .and(warp::query::<handlers::EquivocatorsQuery>())
+        .and(with_snapshot(db))
+        .and_then(handlers::get_equivocators)
+}
+
 #[utoipa::path(
     get,
     path = "/signatures/{block_hash}",
     params(
-        ("block_hash" = String, Path, description = "Base64 encoded block hash of requested block")
+        ("block_hash" = String, Path, description = "Base64 encoded block hash of requested block"),
+        ("format" = Option<String>, Query, description = "Format of the list, `json` (default) or `ndjson` for one item per line")
     ),
     responses(
         (status = 200, description = "finality signatures in a block", body = [FinalitySignature])
@@ -292,10 +940,108 @@ fn finality_signatures_by_block<Db: DatabaseReader + Clone + Send + Sync>(
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("signatures" / String)
         .and(warp::get())
+        .and(warp::query::<FormatQuery>())
         .and(with_db(db))
         .and_then(handlers::get_finality_signatures_by_block)
 }
 
+#[utoipa::path(
+    get,
+    path = "/block/{block_hash}/finality_signatures",
+    params(
+        ("block_hash" = String, Path, description = "Base64 encoded block hash of requested block")
+    ),
+    responses(
+        (status = 200, description = "finality signatures of a block along with the weight of their signers", body = BlockFinalitySignatures)
+    )
+)]
+/// Return the finality signatures of a block given its block hash, along with the sum of the
+/// weights of the validators which signed it and of all the validators of its era, if the switch
+/// block announcing them is stored.
+/// Input: the database with data to be filtered.
+/// Return: the finality signatures for the block specified.
+/// Path URL: block/<block-hash>/finality_signatures
+/// Example: curl http://127.0.0.1:18888/block/c0292d8408e9d83d1aaceadfbeb25dc38cda36bcb91c3d403a0deb594dc3d63f/finality_signatures
+fn block_finality_signatures<Db: DatabaseReader + Clone + Send + Sync>(
+    db: Db,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("block" / String / "finality_signatures")
+        .and(warp::get())
+        .and(with_snapshot(db))
+        .and_then(handlers::get_block_finality_signatures)
+}
+
+#[utoipa::path(
+    get,
+    path = "/enrichments/{hash}",
+    params(
+        ("hash" = String, Path, description = "Hex-encoded hash of the block or deploy"),
+        ("format" = Option<String>, Query, description = "Format of the list, `json` (default) or `ndjson` for one item per line")
+    ),
+    responses(
+        (status = 200, description = "context fetched from the node's RPC server for a block or deploy", body = [Enrichment])
+    )
+)]
+/// Return the context fetched from the node's RPC server for a block or deploy, given its hash.
+/// Input: the database with data to be filtered.
+/// Return: the enrichments stored for the block or deploy specified.
+/// Path URL: enrichments/<hash>
+/// Example: curl http://127.0.0.1:18888/enrichments/c0292d8408e9d83d1aaceadfbeb25dc38cda36bcb91c3d403a0deb594dc3d63f
+fn enrichments_by_hash<Db: DatabaseReader + Clone + Send + Sync>(
+    db: Db,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("enrichments" / String)
+        .and(warp::get())
+        .and(warp::query::<FormatQuery>())
+        .and(with_db(db))
+        .and_then(handlers::get_enrichments_by_hash)
+}
+
+/// Return the result of a GraphQL query composed across the stored blocks, deploys, faults and
+/// steps, each query reading a single snapshot of the database.
+/// Input: the database with data to be filtered and the account aliases.
+/// Return: the data selected by the query, along with its errors.
+/// Path URL: graphql
+/// Example: curl -X POST http://127.0.0.1:18888/graphql -H 'Content-Type: application/json' -d '{"query":"{ block(height: 100) { hash deploys(failed: true) { hash errorMessage } } }"}'
+#[cfg(feature = "graphql")]
+fn graphql<Db: DatabaseReader + Clone + Send + Sync + 'static>(
+    db: Db,
+    account_aliases: AccountAliases,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("graphql")
+        .and(async_graphql_warp::graphql(graphql_schema(account_aliases)))
+        .and(with_snapshot(db))
+        .and_then(handlers::execute_graphql)
+}
+
+#[utoipa::path(
+    get,
+    path = "/state/{state_root_hash}/{key}/proof",
+    params(
+        ("state_root_hash" = String, Path, description = "Hex-encoded state root hash identifying the global state"),
+        ("key" = String, Path, description = "Formatted global state key, e.g. `deploy-<hash>`")
+    ),
+    responses(
+        (status = 200, description = "proof of the key fetched from the node's RPC server, with related stored events", body = StateProof)
+    )
+)]
+/// Return the proof of a key of global state fetched from the node's RPC server with
+/// `query_global_state`, bundled with the stored deploy or step the key refers to, if any.
+/// Input: the database with data to be filtered and the proxy of state proofs.
+/// Return: the proof of the key specified.
+/// Path URL: state/<state-root-hash>/<key>/proof
+/// Example: curl http://127.0.0.1:18888/state/b6a4b2e58aa2c8ea0472b24d3ff3ad6d38b34e57a8e7ac29d9d301cd4b4d3c57/deploy-c0292d8408e9d83d1aaceadfbeb25dc38cda36bcb91c3d403a0deb594dc3d63f/proof
+fn state_proof<Db: DatabaseReader + Clone + Send + Sync>(
+    db: Db,
+    maybe_state_proofs: Option<StateProofs>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("state" / String / String / "proof")
+        .and(warp::get())
+        .and(with_db(db))
+        .and(warp::any().map(move || maybe_state_proofs.clone()))
+        .and_then(handlers::get_state_proof)
+}
+
 #[utoipa::path(
     get,
     path = "/step/{era_id}",
@@ -320,9 +1066,32 @@ fn step_by_era<Db: DatabaseReader + Clone + Send + Sync>(
         .and_then(handlers::get_step_by_era)
 }
 
-/// Helper function to extract data from a database
-fn with_db<Db: DatabaseReader + Clone + Send>(
+/// Helper function to extract data from the database, for the handlers making a single query and
+/// those streaming a list, which read its pages as the response is sent rather than keeping a
+/// connection of the pool for the lifetime of the stream.
+fn with_db<Db: DatabaseReader + Clone + Send + Sync>(
     db: Db,
 ) -> impl Filter<Extract = (Db,), Error = Infallible> + Clone {
     warp::any().map(move || db.clone())
 }
+
+/// Helper function to extract data from a read-only snapshot of a database, for the handlers making
+/// several queries, so that all of them observe the same committed state.
+fn with_snapshot<Db: DatabaseReader + Clone + Send + Sync>(
+    db: Db,
+) -> impl Filter<Extract = (Db::Snapshot,), Error = warp::Rejection> + Clone {
+    warp::any().and_then(move || {
+        let db = db.clone();
+        async move {
+            db.snapshot()
+                .await
+                .map_err(|err| warp::reject::custom(StorageError(err)))
+        }
+    })
+}
+
+fn with_account_aliases(
+    account_aliases: AccountAliases,
+) -> impl Filter<Extract = (AccountAliases,), Error = Infallible> + Clone {
+    warp::any().map(move || account_aliases.clone())
+}
@@ -0,0 +1,437 @@
+//! GraphQL API over the stored events.
+//!
+//! `/graphql` answers queries composed across blocks, deploys, faults and steps in a single round
+//! trip, rather than one REST request per entity joined by the client, e.g. the failed deploys of
+//! an account in a range of blocks:
+//!
+//! ```graphql
+//! {
+//!   blocks(fromHeight: 100, toHeight: 199) {
+//!     height
+//!     deploys(account: "@treasury", failed: true) { hash errorMessage }
+//!   }
+//! }
+//! ```
+//!
+//! Every query reads a single snapshot of the database, so all its parts observe the same stored
+//! events. The depth, the complexity and the number of blocks of a range of the queries are
+//! limited, and so are the reads made by all the fields of a query together, so that neither
+//! aliased fields nor nested ones, e.g. the blocks of the deploys of blocks, can read the whole
+//! database in a single request.
+
+use crate::{
+    account_aliases::AccountAliases,
+    types::{
+        database::{DatabaseReadError, DatabaseReader, DeployAggregate},
+        sse_events::{self, BlockAdded},
+    },
+};
+use async_graphql::{
+    Context, EmptyMutation, EmptySubscription, Error as GraphQlError, Json, Object, Request,
+    Response, Result as GraphQlResult, Schema,
+};
+use async_trait::async_trait;
+use casper_types::AsymmetricType;
+use serde_json::Value;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+/// Maximum number of blocks of a range queried at once.
+const MAX_BLOCKS_PER_QUERY: u64 = 100;
+/// Maximum depth of a query, enough to reach the block of a deploy of a block.
+const MAX_QUERY_DEPTH: usize = 8;
+/// Maximum complexity of a query, each field counting once per block of the ranges it's nested in.
+const MAX_QUERY_COMPLEXITY: usize = 2_000;
+/// Maximum number of stored events read by all the fields of a query together.
+const MAX_READS_PER_QUERY: u64 = 1_000;
+
+pub(super) type GraphQlSchema = Schema<Query, EmptyMutation, EmptySubscription>;
+
+/// The schema of the GraphQL API, resolving the accounts of the queries with `account_aliases`.
+pub(super) fn schema(account_aliases: AccountAliases) -> GraphQlSchema {
+    Schema::build(Query, EmptyMutation, EmptySubscription)
+        .data(account_aliases)
+        .limit_depth(MAX_QUERY_DEPTH)
+        .limit_complexity(MAX_QUERY_COMPLEXITY)
+        .finish()
+}
+
+/// Executes the request, reading the events from the snapshot `db`.
+pub(super) async fn execute<Db: DatabaseReader + Send + Sync + 'static>(
+    schema: &GraphQlSchema,
+    request: Request,
+    db: Db,
+) -> Response {
+    let source: Arc<dyn EventSource> = Arc::new(db);
+    let budget = ReadBudget(AtomicU64::new(MAX_READS_PER_QUERY));
+    schema.execute(request.data(source).data(budget)).await
+}
+
+/// The stored events the fields of a query may still read.
+struct ReadBudget(AtomicU64);
+
+impl ReadBudget {
+    /// Takes `reads` from the budget, failing once it's exhausted.
+    fn spend(&self, reads: u64) -> GraphQlResult<()> {
+        self.0
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |remaining| {
+                remaining.checked_sub(reads)
+            })
+            .map(drop)
+            .map_err(|_| {
+                GraphQlError::new(format!(
+                    "Expected a query reading at most {} stored events",
+                    MAX_READS_PER_QUERY
+                ))
+            })
+    }
+}
+
+/// The reads made by the resolvers, with the type of the database erased so that the types of the
+/// schema aren't generic.
+#[async_trait]
+trait EventSource: Send + Sync {
+    async fn block_by_hash(&self, hash: &str) -> Result<BlockAdded, DatabaseReadError>;
+    async fn block_by_height(&self, height: u64) -> Result<BlockAdded, DatabaseReadError>;
+    async fn blocks_by_height_range(
+        &self,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<BlockAdded>, DatabaseReadError>;
+    async fn deploy(&self, hash: &str) -> Result<DeployAggregate, DatabaseReadError>;
+    async fn faults_by_era(&self, era: u64) -> Result<Vec<sse_events::Fault>, DatabaseReadError>;
+    async fn faults_by_public_key(
+        &self,
+        public_key: &str,
+    ) -> Result<Vec<sse_events::Fault>, DatabaseReadError>;
+    async fn step_by_era(&self, era: u64) -> Result<sse_events::Step, DatabaseReadError>;
+}
+
+#[async_trait]
+impl<Db: DatabaseReader + Send + Sync> EventSource for Db {
+    async fn block_by_hash(&self, hash: &str) -> Result<BlockAdded, DatabaseReadError> {
+        self.get_block_by_hash(hash).await
+    }
+
+    async fn block_by_height(&self, height: u64) -> Result<BlockAdded, DatabaseReadError> {
+        self.get_block_by_height(height).await
+    }
+
+    async fn blocks_by_height_range(
+        &self,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<BlockAdded>, DatabaseReadError> {
+        self.get_blocks_by_height_range(from, to, MAX_BLOCKS_PER_QUERY as u32)
+            .await
+    }
+
+    async fn deploy(&self, hash: &str) -> Result<DeployAggregate, DatabaseReadError> {
+        self.get_deploy_aggregate_by_hash(hash).await
+    }
+
+    async fn faults_by_era(&self, era: u64) -> Result<Vec<sse_events::Fault>, DatabaseReadError> {
+        self.get_faults_by_era(era).await
+    }
+
+    async fn faults_by_public_key(
+        &self,
+        public_key: &str,
+    ) -> Result<Vec<sse_events::Fault>, DatabaseReadError> {
+        self.get_faults_by_public_key(public_key).await
+    }
+
+    async fn step_by_era(&self, era: u64) -> Result<sse_events::Step, DatabaseReadError> {
+        self.get_step_by_era(era).await
+    }
+}
+
+pub(super) struct Query;
+
+#[Object]
+impl Query {
+    /// The block with the given hex-encoded hash or height.
+    async fn block(
+        &self,
+        ctx: &Context<'_>,
+        hash: Option<String>,
+        height: Option<u64>,
+    ) -> GraphQlResult<Option<Block>> {
+        let result = match (hash, height) {
+            (Some(hash), None) => source(ctx, 1)?.block_by_hash(&hash).await,
+            (None, Some(height)) => source(ctx, 1)?.block_by_height(height).await,
+            _ => return Err(GraphQlError::new("Expected either a hash or a height")),
+        };
+        Ok(optional(result)?.map(Block))
+    }
+
+    /// The blocks with a height between `fromHeight` and `toHeight`, both inclusive, ordered by
+    /// height. At most 100 blocks are queried at once.
+    #[graphql(complexity = "child_complexity * block_count(from_height, to_height) as usize")]
+    async fn blocks(
+        &self,
+        ctx: &Context<'_>,
+        from_height: u64,
+        to_height: u64,
+    ) -> GraphQlResult<Vec<Block>> {
+        if from_height > to_height || to_height - from_height >= MAX_BLOCKS_PER_QUERY {
+            return Err(GraphQlError::new(format!(
+                "Expected a range of at most {} blocks, received: fromHeight={}, toHeight={}",
+                MAX_BLOCKS_PER_QUERY, from_height, to_height
+            )));
+        }
+        let blocks = source(ctx, to_height - from_height + 1)?
+            .blocks_by_height_range(from_height, to_height)
+            .await
+            .map_err(read_error)?;
+        Ok(blocks.into_iter().map(Block).collect())
+    }
+
+    /// The deploy with the given hex-encoded hash.
+    async fn deploy(&self, ctx: &Context<'_>, hash: String) -> GraphQlResult<Option<Deploy>> {
+        Ok(optional(source(ctx, 1)?.deploy(&hash).await)?.map(Deploy))
+    }
+
+    /// The faults of the given era or of the validator with the given hex-encoded public key.
+    async fn faults(
+        &self,
+        ctx: &Context<'_>,
+        era: Option<u64>,
+        public_key: Option<String>,
+    ) -> GraphQlResult<Vec<Fault>> {
+        let result = match (era, public_key) {
+            (Some(era), None) => source(ctx, 1)?.faults_by_era(era).await,
+            (None, Some(public_key)) => source(ctx, 1)?.faults_by_public_key(&public_key).await,
+            _ => return Err(GraphQlError::new("Expected either an era or a public key")),
+        };
+        Ok(optional(result)?
+            .unwrap_or_default()
+            .into_iter()
+            .map(Fault)
+            .collect())
+    }
+
+    /// The step of the given era.
+    async fn step(&self, ctx: &Context<'_>, era: u64) -> GraphQlResult<Option<Step>> {
+        Ok(optional(source(ctx, 1)?.step_by_era(era).await)?.map(Step))
+    }
+}
+
+struct Block(BlockAdded);
+
+#[Object]
+impl Block {
+    /// Hex-encoded hash of the block.
+    async fn hash(&self) -> String {
+        self.0.hex_encoded_hash()
+    }
+
+    async fn height(&self) -> u64 {
+        self.0.get_height()
+    }
+
+    async fn era_id(&self) -> u64 {
+        self.0.get_era_id()
+    }
+
+    /// Milliseconds since the UNIX epoch at which the block was proposed.
+    async fn timestamp(&self) -> u64 {
+        self.0.get_timestamp().millis()
+    }
+
+    async fn is_switch_block(&self) -> bool {
+        self.0.is_switch_block()
+    }
+
+    /// The `BlockAdded` event as sent by the node.
+    async fn raw(&self) -> GraphQlResult<Json<Value>> {
+        Ok(Json(serde_json::to_value(&self.0)?))
+    }
+
+    /// The stored deploys of the block, including transfers, only those sent by `account` if
+    /// given, as a public key, an account hash or an `@`-prefixed alias, and only those whose
+    /// execution failed, or succeeded, if `failed` is given.
+    async fn deploys(
+        &self,
+        ctx: &Context<'_>,
+        account: Option<String>,
+        failed: Option<bool>,
+    ) -> GraphQlResult<Vec<Deploy>> {
+        let maybe_account_hash = match account {
+            Some(account) => Some(
+                ctx.data::<AccountAliases>()?
+                    .resolve(&account)
+                    .map_err(GraphQlError::new)?,
+            ),
+            None => None,
+        };
+        let mut deploys = Vec::new();
+        for deploy_hash in self.0.hex_encoded_deploy_hashes() {
+            let Some(deploy) = optional(source(ctx, 1)?.deploy(&deploy_hash).await)?.map(Deploy)
+            else {
+                continue;
+            };
+            let sent_by_account = maybe_account_hash.as_ref().map_or(true, |account_hash| {
+                deploy.sender().as_ref() == Some(account_hash)
+            });
+            let has_outcome = failed.map_or(true, |failed| deploy.has_failed() == Some(failed));
+            if sent_by_account && has_outcome {
+                deploys.push(deploy);
+            }
+        }
+        Ok(deploys)
+    }
+
+    /// The faults of the era of the block.
+    async fn faults(&self, ctx: &Context<'_>) -> GraphQlResult<Vec<Fault>> {
+        let faults = optional(source(ctx, 1)?.faults_by_era(self.0.get_era_id()).await)?;
+        Ok(faults.unwrap_or_default().into_iter().map(Fault).collect())
+    }
+}
+
+struct Deploy(DeployAggregate);
+
+impl Deploy {
+    fn sender(&self) -> Option<String> {
+        self.0
+            .deploy_accepted
+            .as_ref()
+            .map(sse_events::DeployAccepted::hex_encoded_account_hash)
+    }
+
+    fn has_failed(&self) -> Option<bool> {
+        self.0
+            .deploy_processed
+            .as_ref()
+            .map(|deploy_processed| deploy_processed.error_message().is_some())
+    }
+}
+
+#[Object]
+impl Deploy {
+    /// Hex-encoded hash of the deploy.
+    async fn hash(&self) -> String {
+        self.0.deploy_hash.clone()
+    }
+
+    /// Hex-encoded hash of the account which sent the deploy, unless its acceptance isn't stored.
+    async fn account(&self) -> Option<String> {
+        self.sender()
+    }
+
+    /// Hex-encoded hash of the block the deploy was processed in, unless it wasn't processed.
+    async fn block_hash(&self) -> Option<String> {
+        self.0
+            .deploy_processed
+            .as_ref()
+            .map(sse_events::DeployProcessed::hex_encoded_block_hash)
+    }
+
+    /// Whether the execution of the deploy failed, unless it wasn't processed.
+    async fn failed(&self) -> Option<bool> {
+        self.has_failed()
+    }
+
+    /// The error the execution of the deploy failed with.
+    async fn error_message(&self) -> Option<String> {
+        self.0
+            .deploy_processed
+            .as_ref()
+            .and_then(|deploy_processed| deploy_processed.error_message().map(str::to_string))
+    }
+
+    async fn expired(&self) -> bool {
+        self.0.deploy_expired
+    }
+
+    /// The `DeployAccepted` event as sent by the node.
+    async fn accepted(&self) -> GraphQlResult<Option<Json<Value>>> {
+        Ok(match &self.0.deploy_accepted {
+            Some(deploy_accepted) => Some(Json(serde_json::to_value(deploy_accepted)?)),
+            None => None,
+        })
+    }
+
+    /// The `DeployProcessed` event as sent by the node.
+    async fn processed(&self) -> GraphQlResult<Option<Json<Value>>> {
+        Ok(match &self.0.deploy_processed {
+            Some(deploy_processed) => Some(Json(serde_json::to_value(deploy_processed)?)),
+            None => None,
+        })
+    }
+
+    /// The block the deploy was processed in, unless it wasn't processed or the block isn't
+    /// stored.
+    async fn block(&self, ctx: &Context<'_>) -> GraphQlResult<Option<Block>> {
+        let Some(deploy_processed) = &self.0.deploy_processed else {
+            return Ok(None);
+        };
+        let result = source(ctx, 1)?
+            .block_by_hash(&deploy_processed.hex_encoded_block_hash())
+            .await;
+        Ok(optional(result)?.map(Block))
+    }
+}
+
+struct Fault(sse_events::Fault);
+
+#[Object]
+impl Fault {
+    async fn era_id(&self) -> u64 {
+        self.0.era_id.value()
+    }
+
+    /// Hex-encoded public key of the faulty validator.
+    async fn public_key(&self) -> String {
+        self.0.public_key.to_hex()
+    }
+
+    /// Milliseconds since the UNIX epoch at which the fault was reported.
+    async fn timestamp(&self) -> u64 {
+        self.0.timestamp.millis()
+    }
+}
+
+struct Step(sse_events::Step);
+
+#[Object]
+impl Step {
+    async fn era_id(&self) -> u64 {
+        self.0.era_id.value()
+    }
+
+    /// The `Step` event as sent by the node, including its execution effect.
+    async fn raw(&self) -> GraphQlResult<Json<Value>> {
+        Ok(Json(serde_json::to_value(&self.0)?))
+    }
+}
+
+/// The source of the stored events, once `reads` are taken from the budget of the query.
+fn source<'a>(ctx: &Context<'a>, reads: u64) -> GraphQlResult<&'a Arc<dyn EventSource>> {
+    ctx.data::<ReadBudget>()?.spend(reads)?;
+    ctx.data::<Arc<dyn EventSource>>()
+}
+
+/// The number of blocks of a range as counted in the complexity of a query, at most the number
+/// of blocks queried at once.
+fn block_count(from_height: u64, to_height: u64) -> u64 {
+    to_height
+        .saturating_sub(from_height)
+        .saturating_add(1)
+        .min(MAX_BLOCKS_PER_QUERY)
+}
+
+/// The result of a read, `None` if nothing is stored.
+fn optional<T>(result: Result<T, DatabaseReadError>) -> GraphQlResult<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(DatabaseReadError::NotFound) => Ok(None),
+        Err(error) => Err(read_error(error)),
+    }
+}
+
+fn read_error(error: DatabaseReadError) -> GraphQlError {
+    GraphQlError::new(format!("Error reading the stored events: {:?}", error))
+}
@@ -1,13 +1,133 @@
-use super::errors::StorageError;
+#[cfg(feature = "graphql")]
+use super::graphql::{self, GraphQlSchema};
+use super::{
+    decoded_args,
+    errors::{NotEnabled, StorageError, UpstreamError},
+    streaming::{
+        stream_items, stream_pages, stream_table_items, stream_table_pages, FormatQuery,
+        ResponseFormat, TableFormat, TableFormatQuery,
+    },
+};
 use crate::{
+    account_aliases::AccountAliases,
+    block_signatures,
+    deploy_approvals::DeployApprovals,
+    deploy_outcomes::DeployOutcomes,
+    deploy_status::DeployStatus,
+    equivocation,
+    history::{self, AsOf},
     rest_server::errors::InvalidParam,
-    types::database::{DatabaseReadError, DatabaseReader},
+    state_proofs::{StateProofError, StateProofs},
+    types::{
+        database::{
+            AccountEffectEntry, DatabaseReadError, DatabaseReader, DeployAccountEntry,
+            DeployAggregate, DeployErrorEntry, DeployTimestampEntry, NftEventEntry,
+            TokenTransferEntry, TransferEntry,
+        },
+        sse_events::{BlockAdded, DeployAccepted},
+    },
     utils::Unexpected,
 };
 use anyhow::Error;
-use serde::Serialize;
+#[cfg(feature = "graphql")]
+use async_graphql_warp::GraphQLResponse;
+use casper_types::{bytesrepr, Key, Timestamp};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{str::FromStr, time::Duration};
 use warp::{http::StatusCode, Rejection, Reply};
 
+/// The number of blocks read from the database at once when streaming a range of blocks.
+const BLOCKS_PAGE_SIZE: u32 = 100;
+
+/// Query parameters of a request for a range of blocks.
+#[derive(Debug, Deserialize)]
+pub(super) struct BlockRangeQuery {
+    from: Option<u64>,
+    to: Option<u64>,
+    #[serde(default)]
+    format: ResponseFormat,
+}
+
+/// The number of deploy timestamps read from the database at once when streaming a time range.
+const DEPLOYS_PAGE_SIZE: u32 = 1000;
+
+/// Query parameters of a request for the deploys of a time range.
+#[derive(Debug, Deserialize)]
+pub(super) struct DeployTimeRangeQuery {
+    from: String,
+    to: String,
+    #[serde(default)]
+    format: TableFormat,
+}
+
+/// Query parameters of a request for the failed deploys with a given error message.
+#[derive(Debug, Deserialize)]
+pub(super) struct DeployErrorQuery {
+    contains: String,
+    #[serde(default)]
+    format: TableFormat,
+}
+
+/// Query parameters of a request for the deploys sent by an account.
+#[derive(Debug, Deserialize)]
+pub(super) struct AccountDeploysQuery {
+    /// If given, only the deploys processed at or below this block height are listed.
+    at_height: Option<u64>,
+    /// If given, only this many deploys are listed, ordered by the time they were accepted.
+    limit: Option<u32>,
+    /// If given, the deploys are listed ordered by the time they were accepted, skipping this many.
+    offset: Option<u32>,
+    #[serde(default)]
+    format: TableFormat,
+}
+
+/// Query parameters of a request for the transfers with a given user-defined id.
+#[derive(Debug, Deserialize)]
+pub(super) struct TransferIdQuery {
+    transfer_id: u64,
+    #[serde(default)]
+    format: TableFormat,
+}
+
+/// How long the outcome of a deploy is awaited if the request doesn't say.
+const DEFAULT_AWAIT_TIMEOUT_IN_SECONDS: u64 = 60;
+
+/// The longest the outcome of a deploy can be awaited by a single request.
+const MAX_AWAIT_TIMEOUT_IN_SECONDS: u64 = 300;
+
+/// Query parameters of a request awaiting the outcome of a deploy.
+#[derive(Debug, Deserialize)]
+pub(super) struct AwaitDeployQuery {
+    /// How long to await the deploy, e.g. `60s`.
+    timeout: Option<String>,
+}
+
+/// Query parameters of a request for a deploy.
+#[derive(Debug, Deserialize)]
+pub(super) struct DeployQuery {
+    /// Whether to decode the arguments of the payment and session to typed JSON.
+    #[serde(default)]
+    decode_args: bool,
+}
+
+/// Query parameters of a request for state derived from the stored events, answered as of the
+/// block at `at_height` if given, or as of the latest stored block otherwise.
+#[derive(Debug, Deserialize)]
+pub(super) struct AtHeightQuery {
+    at_height: Option<u64>,
+}
+
+/// Query parameters of a request for the equivocators of an era.
+#[derive(Debug, Deserialize)]
+pub(super) struct EquivocatorsQuery {
+    era: u64,
+    /// If given, the equivocators are the ones known as of the block at this height.
+    at_height: Option<u64>,
+    #[serde(default)]
+    format: ResponseFormat,
+}
+
 pub(super) async fn get_latest_block<Db: DatabaseReader + Clone + Send>(
     db: Db,
 ) -> Result<impl Reply, Rejection> {
@@ -24,6 +144,15 @@ pub(super) async fn get_block_by_hash<Db: DatabaseReader + Clone + Send>(
     format_or_reject_storage_result(db_result)
 }
 
+pub(super) async fn get_raw_block_by_hash<Db: DatabaseReader + Clone + Send>(
+    hash: String,
+    db: Db,
+) -> Result<impl Reply, Rejection> {
+    check_hash_is_correct_format(&hash)?;
+    let db_result = db.get_block_by_hash(&hash).await;
+    bytes_or_reject_storage_result(db_result.map(|block_added| block_added.canonical_bytes()))
+}
+
 pub(super) async fn get_block_by_height<Db: DatabaseReader + Clone + Send>(
     height: u64,
     db: Db,
@@ -32,22 +161,463 @@ pub(super) async fn get_block_by_height<Db: DatabaseReader + Clone + Send>(
     format_or_reject_storage_result(db_result)
 }
 
+pub(super) async fn get_blocks_by_height_range<
+    Db: DatabaseReader + Clone + Send + Sync + 'static,
+>(
+    query: BlockRangeQuery,
+    db: Db,
+) -> Result<impl Reply, Rejection> {
+    let from = query.from.unwrap_or(0);
+    let to = query.to.unwrap_or(u64::MAX);
+    if from > to {
+        return Err(warp::reject::custom(InvalidParam(Error::msg(format!(
+            "Expected 'from' to be lower than or equal to 'to', received: from={}, to={}",
+            from, to
+        )))));
+    }
+    let first_page = db
+        .get_blocks_by_height_range(from, to, BLOCKS_PAGE_SIZE)
+        .await
+        .map_err(|err| warp::reject::custom(StorageError(err)))?;
+    Ok(stream_pages(
+        query.format,
+        first_page,
+        BLOCKS_PAGE_SIZE as usize,
+        |block: &BlockAdded| block.get_height().checked_add(1),
+        move |next_from| {
+            let db = db.clone();
+            async move {
+                db.get_blocks_by_height_range(next_from, to, BLOCKS_PAGE_SIZE)
+                    .await
+            }
+        },
+    ))
+}
+
+pub(super) async fn get_deploys_by_time_range<
+    Db: DatabaseReader + Clone + Send + Sync + 'static,
+>(
+    query: DeployTimeRangeQuery,
+    db: Db,
+) -> Result<impl Reply, Rejection> {
+    let from = parse_time("from", &query.from)?;
+    let to = parse_time("to", &query.to)?;
+    if from >= to {
+        return Err(warp::reject::custom(InvalidParam(Error::msg(format!(
+            "Expected 'from' to be earlier than 'to', received: from={}, to={}",
+            query.from, query.to
+        )))));
+    }
+    let first_page = db
+        .get_deploy_timestamps(from, to, None, DEPLOYS_PAGE_SIZE)
+        .await
+        .map_err(|err| warp::reject::custom(StorageError(err)))?;
+    Ok(stream_table_pages(
+        query.format,
+        first_page,
+        DEPLOYS_PAGE_SIZE as usize,
+        |entry: &DeployTimestampEntry| Some(entry.clone()),
+        move |after| {
+            let db = db.clone();
+            async move {
+                db.get_deploy_timestamps(from, to, Some(after), DEPLOYS_PAGE_SIZE)
+                    .await
+            }
+        },
+    ))
+}
+
+pub(super) async fn get_deploys_by_error_message<
+    Db: DatabaseReader + Clone + Send + Sync + 'static,
+>(
+    query: DeployErrorQuery,
+    db: Db,
+) -> Result<impl Reply, Rejection> {
+    if query.contains.is_empty() {
+        return Err(warp::reject::custom(InvalidParam(Error::msg(
+            "Expected 'contains' to be non-empty",
+        ))));
+    }
+    let first_page = db
+        .get_deploys_by_error_message(&query.contains, None, DEPLOYS_PAGE_SIZE)
+        .await
+        .map_err(|err| warp::reject::custom(StorageError(err)))?;
+    let contains = query.contains;
+    Ok(stream_table_pages(
+        query.format,
+        first_page,
+        DEPLOYS_PAGE_SIZE as usize,
+        |entry: &DeployErrorEntry| Some(entry.deploy_hash.clone()),
+        move |after| {
+            let db = db.clone();
+            let contains = contains.clone();
+            async move {
+                db.get_deploys_by_error_message(&contains, Some(after), DEPLOYS_PAGE_SIZE)
+                    .await
+            }
+        },
+    ))
+}
+
+pub(super) async fn get_deploys_by_account<Db: DatabaseReader + Clone + Send + Sync + 'static>(
+    account: String,
+    query: AccountDeploysQuery,
+    db: Db,
+    account_aliases: AccountAliases,
+) -> Result<impl Reply, Rejection> {
+    let account_hash = resolve_account(&account_aliases, &account)?;
+    let maybe_alias = account_aliases.alias_of(&account_hash);
+    let processed_by = maybe_as_of_height(&db, query.at_height)
+        .await?
+        .map(|as_of| as_of.timestamp);
+    if query.limit.is_some() || query.offset.is_some() {
+        return get_page_of_deploys_by_account(
+            &db,
+            &account_hash,
+            processed_by,
+            &query,
+            &maybe_alias,
+        )
+        .await;
+    }
+    let first_page = db
+        .get_deploys_by_account(&account_hash, processed_by, None, DEPLOYS_PAGE_SIZE)
+        .await
+        .map_err(|err| warp::reject::custom(StorageError(err)))?;
+    Ok(stream_table_pages(
+        query.format,
+        with_account_alias(first_page, &maybe_alias),
+        DEPLOYS_PAGE_SIZE as usize,
+        |entry: &DeployAccountEntry| Some(entry.deploy_hash.clone()),
+        move |after| {
+            let db = db.clone();
+            let account_hash = account_hash.clone();
+            let maybe_alias = maybe_alias.clone();
+            async move {
+                db.get_deploys_by_account(
+                    &account_hash,
+                    processed_by,
+                    Some(after),
+                    DEPLOYS_PAGE_SIZE,
+                )
+                .await
+                .map(|entries| with_account_alias(entries, &maybe_alias))
+            }
+        },
+    ))
+}
+
+/// Lists the deploys of the account within the `limit` and `offset` of the query.
+async fn get_page_of_deploys_by_account<Db: DatabaseReader + Sync>(
+    db: &Db,
+    account_hash: &str,
+    processed_by: Option<u64>,
+    query: &AccountDeploysQuery,
+    maybe_alias: &Option<String>,
+) -> Result<warp::reply::Response, Rejection> {
+    let limit = query.limit.unwrap_or(DEPLOYS_PAGE_SIZE);
+    if limit == 0 || limit > DEPLOYS_PAGE_SIZE {
+        return Err(warp::reject::custom(InvalidParam(Error::msg(format!(
+            "Expected 'limit' to be between 1 and {}, received: {}",
+            DEPLOYS_PAGE_SIZE, limit
+        )))));
+    }
+    let page = db
+        .get_deploys_by_account_page(account_hash, processed_by, query.offset.unwrap_or(0), limit)
+        .await
+        .map_err(|err| warp::reject::custom(StorageError(err)))?;
+    Ok(stream_table_items(
+        query.format,
+        with_account_alias(page, maybe_alias),
+    ))
+}
+
+pub(super) async fn get_deploys_affecting_account<
+    Db: DatabaseReader + Clone + Send + Sync + 'static,
+>(
+    account: String,
+    format_query: TableFormatQuery,
+    db: Db,
+    account_aliases: AccountAliases,
+) -> Result<impl Reply, Rejection> {
+    let account_hash = resolve_account(&account_aliases, &account)?;
+    let first_page = db
+        .get_deploys_affecting_account(&account_hash, None, DEPLOYS_PAGE_SIZE)
+        .await
+        .map_err(|err| warp::reject::custom(StorageError(err)))?;
+    Ok(stream_table_pages(
+        format_query.format,
+        first_page,
+        DEPLOYS_PAGE_SIZE as usize,
+        |entry: &AccountEffectEntry| Some(entry.deploy_hash.clone()),
+        move |after| {
+            let db = db.clone();
+            let account_hash = account_hash.clone();
+            async move {
+                db.get_deploys_affecting_account(&account_hash, Some(after), DEPLOYS_PAGE_SIZE)
+                    .await
+            }
+        },
+    ))
+}
+
+/// The number of transfers read from the database at once when streaming the transfers of an
+/// account.
+const TRANSFERS_PAGE_SIZE: u32 = 1000;
+
+pub(super) async fn get_transfers_by_account<Db: DatabaseReader + Clone + Send + Sync + 'static>(
+    account: String,
+    format_query: TableFormatQuery,
+    db: Db,
+    account_aliases: AccountAliases,
+) -> Result<impl Reply, Rejection> {
+    let account_hash = resolve_account(&account_aliases, &account)?;
+    let first_page = db
+        .get_transfers_by_account(&account_hash, None, TRANSFERS_PAGE_SIZE)
+        .await
+        .map_err(|err| warp::reject::custom(StorageError(err)))?;
+    Ok(stream_table_pages(
+        format_query.format,
+        first_page,
+        TRANSFERS_PAGE_SIZE as usize,
+        |transfer: &TransferEntry| Some(transfer.transfer_hash.clone()),
+        move |after| {
+            let db = db.clone();
+            let account_hash = account_hash.clone();
+            async move {
+                db.get_transfers_by_account(&account_hash, Some(after), TRANSFERS_PAGE_SIZE)
+                    .await
+            }
+        },
+    ))
+}
+
+pub(super) async fn get_transfers_by_transfer_id<
+    Db: DatabaseReader + Clone + Send + Sync + 'static,
+>(
+    query: TransferIdQuery,
+    db: Db,
+) -> Result<impl Reply, Rejection> {
+    let transfer_id = query.transfer_id;
+    let first_page = db
+        .get_transfers_by_transfer_id(transfer_id, None, TRANSFERS_PAGE_SIZE)
+        .await
+        .map_err(|err| warp::reject::custom(StorageError(err)))?;
+    Ok(stream_table_pages(
+        query.format,
+        first_page,
+        TRANSFERS_PAGE_SIZE as usize,
+        |transfer: &TransferEntry| Some(transfer.transfer_hash.clone()),
+        move |after| {
+            let db = db.clone();
+            async move {
+                db.get_transfers_by_transfer_id(transfer_id, Some(after), TRANSFERS_PAGE_SIZE)
+                    .await
+            }
+        },
+    ))
+}
+
+pub(super) async fn get_transfers_by_block<Db: DatabaseReader + Clone + Send + Sync + 'static>(
+    block_hash: String,
+    format_query: TableFormatQuery,
+    db: Db,
+) -> Result<impl Reply, Rejection> {
+    check_hash_is_correct_format(&block_hash)?;
+    let transfers = db
+        .get_transfers_by_block(&block_hash)
+        .await
+        .map_err(|err| warp::reject::custom(StorageError(err)))?;
+    Ok(stream_table_items(format_query.format, transfers))
+}
+
+/// The number of token transfers read from the database at once when streaming the transfers of a
+/// token.
+const TOKEN_TRANSFERS_PAGE_SIZE: u32 = 1000;
+
+pub(super) async fn get_token_transfers<Db: DatabaseReader + Clone + Send + Sync + 'static>(
+    contract_hash: String,
+    format_query: TableFormatQuery,
+    db: Db,
+) -> Result<impl Reply, Rejection> {
+    check_hash_is_correct_format(&contract_hash)?;
+    let contract_hash = contract_hash.to_lowercase();
+    let first_page = db
+        .get_token_transfers(&contract_hash, None, TOKEN_TRANSFERS_PAGE_SIZE)
+        .await
+        .map_err(|err| warp::reject::custom(StorageError(err)))?;
+    Ok(stream_table_pages(
+        format_query.format,
+        first_page,
+        TOKEN_TRANSFERS_PAGE_SIZE as usize,
+        |token_transfer: &TokenTransferEntry| Some(token_transfer.event_id),
+        move |after| {
+            let db = db.clone();
+            let contract_hash = contract_hash.clone();
+            async move {
+                db.get_token_transfers(&contract_hash, Some(after), TOKEN_TRANSFERS_PAGE_SIZE)
+                    .await
+            }
+        },
+    ))
+}
+
+/// The number of NFT events read from the database at once when streaming the events of a
+/// collection or of a token.
+const NFT_EVENTS_PAGE_SIZE: u32 = 1000;
+
+pub(super) async fn get_nft_events_by_collection<
+    Db: DatabaseReader + Clone + Send + Sync + 'static,
+>(
+    contract_hash: String,
+    format_query: TableFormatQuery,
+    db: Db,
+) -> Result<impl Reply, Rejection> {
+    check_hash_is_correct_format(&contract_hash)?;
+    stream_nft_events(contract_hash.to_lowercase(), None, format_query, db).await
+}
+
+pub(super) async fn get_nft_events_by_token<Db: DatabaseReader + Clone + Send + Sync + 'static>(
+    contract_hash: String,
+    token_id: String,
+    format_query: TableFormatQuery,
+    db: Db,
+) -> Result<impl Reply, Rejection> {
+    check_hash_is_correct_format(&contract_hash)?;
+    stream_nft_events(
+        contract_hash.to_lowercase(),
+        Some(token_id),
+        format_query,
+        db,
+    )
+    .await
+}
+
+/// Streams the events of the collection, of the given token only if `maybe_token_id` is given.
+async fn stream_nft_events<Db: DatabaseReader + Clone + Send + Sync + 'static>(
+    contract_hash: String,
+    maybe_token_id: Option<String>,
+    format_query: TableFormatQuery,
+    db: Db,
+) -> Result<warp::reply::Response, Rejection> {
+    let first_page = db
+        .get_nft_events(
+            &contract_hash,
+            maybe_token_id.as_deref(),
+            None,
+            NFT_EVENTS_PAGE_SIZE,
+        )
+        .await
+        .map_err(|err| warp::reject::custom(StorageError(err)))?;
+    Ok(stream_table_pages(
+        format_query.format,
+        first_page,
+        NFT_EVENTS_PAGE_SIZE as usize,
+        |nft_event: &NftEventEntry| Some(nft_event.event_id),
+        move |after| {
+            let db = db.clone();
+            let contract_hash = contract_hash.clone();
+            let maybe_token_id = maybe_token_id.clone();
+            async move {
+                db.get_nft_events(
+                    &contract_hash,
+                    maybe_token_id.as_deref(),
+                    Some(after),
+                    NFT_EVENTS_PAGE_SIZE,
+                )
+                .await
+            }
+        },
+    ))
+}
+
+/// Resolves the account given as a public key, an account hash or an `@`-prefixed alias into its
+/// hex-encoded account hash.
+fn resolve_account(account_aliases: &AccountAliases, account: &str) -> Result<String, Rejection> {
+    account_aliases.resolve(account).map_err(|error| {
+        warp::reject::custom(InvalidParam(Error::msg(format!(
+            "Expected a public key, an account hash or a known @alias, received: {} ({})",
+            account, error
+        ))))
+    })
+}
+
+fn with_account_alias(
+    mut entries: Vec<DeployAccountEntry>,
+    maybe_alias: &Option<String>,
+) -> Vec<DeployAccountEntry> {
+    for entry in &mut entries {
+        entry.account_alias = maybe_alias.clone();
+    }
+    entries
+}
+
+/// Parses a point in time given either as an RFC 3339 timestamp or as a date, meaning midnight
+/// UTC of that day, into milliseconds since the UNIX epoch.
+fn parse_time(param_name: &str, value: &str) -> Result<u64, Rejection> {
+    let timestamp = if value.len() == "YYYY-MM-DD".len() {
+        Timestamp::from_str(&format!("{}T00:00:00Z", value))
+    } else {
+        Timestamp::from_str(value)
+    };
+    timestamp
+        .map(|timestamp| timestamp.millis())
+        .map_err(|err| {
+            warp::reject::custom(InvalidParam(Error::msg(format!(
+                "Expected '{}' to be a date or an RFC 3339 timestamp, received: {} ({})",
+                param_name, value, err
+            ))))
+        })
+}
+
 pub(super) async fn get_deploy_by_hash<Db: DatabaseReader + Clone + Send>(
     hash: String,
+    query: DeployQuery,
     db: Db,
+    account_aliases: AccountAliases,
 ) -> Result<impl Reply, Rejection> {
     check_hash_is_correct_format(&hash)?;
     let db_result = db.get_deploy_aggregate_by_hash(&hash).await;
-    format_or_reject_storage_result(db_result)
+    deploy_or_reject_storage_result(
+        db_result,
+        &query,
+        decoded_args::decode_deploy_aggregate,
+        |aggregate: &DeployAggregate| {
+            aggregate
+                .deploy_accepted
+                .as_ref()
+                .map(DeployAccepted::hex_encoded_account_hash)
+        },
+        &account_aliases,
+    )
 }
 
 pub(super) async fn get_deploy_accepted_by_hash<Db: DatabaseReader + Clone + Send>(
     hash: String,
+    query: DeployQuery,
     db: Db,
+    account_aliases: AccountAliases,
 ) -> Result<impl Reply, Rejection> {
     check_hash_is_correct_format(&hash)?;
     let db_result = db.get_deploy_accepted_by_hash(&hash).await;
-    format_or_reject_storage_result(db_result)
+    deploy_or_reject_storage_result(
+        db_result,
+        &query,
+        decoded_args::decode_deploy_accepted,
+        |deploy_accepted: &DeployAccepted| Some(deploy_accepted.hex_encoded_account_hash()),
+        &account_aliases,
+    )
+}
+
+pub(super) async fn get_raw_deploy_by_hash<Db: DatabaseReader + Clone + Send>(
+    hash: String,
+    db: Db,
+) -> Result<impl Reply, Rejection> {
+    check_hash_is_correct_format(&hash)?;
+    let db_result = db.get_deploy_accepted_by_hash(&hash).await;
+    bytes_or_reject_storage_result(
+        db_result.map(|deploy_accepted| deploy_accepted.canonical_bytes()),
+    )
 }
 
 pub(super) async fn get_deploy_processed_by_hash<Db: DatabaseReader + Clone + Send>(
@@ -78,30 +648,240 @@ pub(super) async fn get_step_by_era<Db: DatabaseReader + Clone + Send>(
 
 pub(super) async fn get_faults_by_public_key<Db: DatabaseReader + Clone + Send>(
     public_key: String,
+    format_query: FormatQuery,
     db: Db,
 ) -> Result<impl Reply, Rejection> {
     check_public_key_is_correct_format(&public_key)?;
     let db_result = db.get_faults_by_public_key(&public_key).await;
-    format_or_reject_storage_result(db_result)
+    format_list_or_reject_storage_result(db_result, format_query.format)
 }
 
 pub(super) async fn get_faults_by_era<Db: DatabaseReader + Clone + Send>(
     era: u64,
+    format_query: FormatQuery,
     db: Db,
 ) -> Result<impl Reply, Rejection> {
     let db_result = db.get_faults_by_era(era).await;
+    format_list_or_reject_storage_result(db_result, format_query.format)
+}
+
+pub(super) async fn get_equivocators<Db: DatabaseReader + Clone + Send + Sync>(
+    query: EquivocatorsQuery,
+    db: Db,
+) -> Result<impl Reply, Rejection> {
+    let maybe_as_of = maybe_as_of_height(&db, query.at_height).await?;
+    let db_result = equivocation::get_equivocators(&db, query.era, maybe_as_of).await;
+    format_list_or_reject_storage_result(db_result, query.format)
+}
+
+pub(super) async fn get_validator_set<Db: DatabaseReader + Clone + Send + Sync>(
+    query: AtHeightQuery,
+    db: Db,
+) -> Result<impl Reply, Rejection> {
+    let db_result = history::get_validator_set(&db, query.at_height).await;
+    format_or_reject_storage_result(db_result)
+}
+
+/// Returns the point in history of the block at `maybe_height`, if one is given.
+async fn maybe_as_of_height<Db: DatabaseReader + Sync>(
+    db: &Db,
+    maybe_height: Option<u64>,
+) -> Result<Option<AsOf>, Rejection> {
+    match maybe_height {
+        Some(height) => history::as_of_height(db, Some(height))
+            .await
+            .map(Some)
+            .map_err(|err| warp::reject::custom(StorageError(err))),
+        None => Ok(None),
+    }
+}
+
+pub(super) async fn get_contract_stats<Db: DatabaseReader + Clone + Send>(
+    contract_hash: String,
+    format_query: FormatQuery,
+    db: Db,
+) -> Result<impl Reply, Rejection> {
+    check_hash_is_correct_format(&contract_hash)?;
+    let db_result = db.get_contract_stats(&contract_hash).await;
+    format_list_or_reject_storage_result(db_result, format_query.format)
+}
+
+pub(super) async fn get_deploy_size_stats<Db: DatabaseReader + Clone + Send>(
+    db: Db,
+) -> Result<impl Reply, Rejection> {
+    let db_result = db.get_deploy_size_stats().await;
     format_or_reject_storage_result(db_result)
 }
 
 pub(super) async fn get_finality_signatures_by_block<Db: DatabaseReader + Clone + Send>(
     block_hash: String,
+    format_query: FormatQuery,
     db: Db,
 ) -> Result<impl Reply, Rejection> {
     check_hash_is_correct_format(&block_hash)?;
     let db_result = db.get_finality_signatures_by_block(&block_hash).await;
+    format_list_or_reject_storage_result(db_result, format_query.format)
+}
+
+pub(super) async fn get_block_finality_signatures<Db: DatabaseReader + Clone + Send + Sync>(
+    block_hash: String,
+    db: Db,
+) -> Result<impl Reply, Rejection> {
+    check_hash_is_correct_format(&block_hash)?;
+    let db_result = block_signatures::get_block_finality_signatures(&db, &block_hash).await;
     format_or_reject_storage_result(db_result)
 }
 
+pub(super) async fn get_enrichments_by_hash<Db: DatabaseReader + Clone + Send>(
+    hash: String,
+    format_query: FormatQuery,
+    db: Db,
+) -> Result<impl Reply, Rejection> {
+    check_hash_is_correct_format(&hash)?;
+    let db_result = db.get_enrichments_by_hash(&hash).await;
+    format_list_or_reject_storage_result(db_result, format_query.format)
+}
+
+pub(super) async fn get_deploy_approvals<Db: DatabaseReader + Clone + Send + Sync>(
+    hash: String,
+    db: Db,
+    maybe_state_proofs: Option<StateProofs>,
+) -> Result<impl Reply, Rejection> {
+    check_hash_is_correct_format(&hash)?;
+    let deploy_accepted = db
+        .get_deploy_accepted_by_hash(&hash)
+        .await
+        .map_err(|error| warp::reject::custom(StorageError(error)))?;
+    let entries = db
+        .get_deploy_approvals(&hash)
+        .await
+        .map_err(|error| warp::reject::custom(StorageError(error)))?;
+    let maybe_account_keys = match maybe_state_proofs {
+        Some(state_proofs) => Some(
+            state_proofs
+                .get_account_keys(&deploy_accepted.hex_encoded_account_hash())
+                .await
+                .map_err(|error| warp::reject::custom(UpstreamError(error)))?,
+        ),
+        None => None,
+    };
+    let deploy_approvals =
+        DeployApprovals::new(&deploy_accepted, entries, maybe_account_keys.as_ref());
+    Ok(warp::reply::json(&deploy_approvals))
+}
+
+pub(super) async fn get_deploy_status<Db: DatabaseReader + Clone + Send + Sync>(
+    hash: String,
+    db: Db,
+) -> Result<impl Reply, Rejection> {
+    check_hash_is_correct_format(&hash)?;
+    let deploy_status = read_deploy_status(&db, &hash)
+        .await?
+        .ok_or_else(|| warp::reject::custom(StorageError(DatabaseReadError::NotFound)))?;
+    Ok(warp::reply::json(&deploy_status))
+}
+
+pub(super) async fn await_deploy<Db: DatabaseReader + Clone + Send + Sync>(
+    hash: String,
+    query: AwaitDeployQuery,
+    db: Db,
+    deploy_outcomes: DeployOutcomes,
+) -> Result<impl Reply, Rejection> {
+    check_hash_is_correct_format(&hash)?;
+    let timeout = parse_await_timeout(query.timeout.as_deref())?;
+    let deadline = tokio::time::Instant::now() + timeout;
+    // Subscribing before reading the events of the deploy, no outcome can be missed in between.
+    let mut outcomes = deploy_outcomes.subscribe();
+    loop {
+        let maybe_deploy_status = read_deploy_status(&db, &hash).await?;
+        let is_final = maybe_deploy_status
+            .as_ref()
+            .map_or(false, DeployStatus::is_final);
+        if is_final
+            || tokio::time::timeout_at(deadline, outcomes.outcome_of(&hash))
+                .await
+                .is_err()
+        {
+            let deploy_status = maybe_deploy_status
+                .ok_or_else(|| warp::reject::custom(StorageError(DatabaseReadError::NotFound)))?;
+            return Ok(warp::reply::json(&deploy_status));
+        }
+    }
+}
+
+/// Reads the lifecycle status of the deploy, `None` if no event of the deploy is stored.
+async fn read_deploy_status<Db: DatabaseReader + Sync>(
+    db: &Db,
+    hash: &str,
+) -> Result<Option<DeployStatus>, Rejection> {
+    let event_times = db
+        .get_deploy_event_times(hash)
+        .await
+        .map_err(|error| warp::reject::custom(StorageError(error)))?;
+    let maybe_deploy_processed = if DeployStatus::is_processed(&event_times) {
+        Some(
+            db.get_deploy_processed_by_hash(hash)
+                .await
+                .map_err(|error| warp::reject::custom(StorageError(error)))?,
+        )
+    } else {
+        None
+    };
+    Ok(DeployStatus::new(
+        hash,
+        &event_times,
+        maybe_deploy_processed.as_ref(),
+    ))
+}
+
+/// Parses how long to await a deploy, given in seconds, optionally suffixed with `s`.
+fn parse_await_timeout(maybe_timeout: Option<&str>) -> Result<Duration, Rejection> {
+    let Some(timeout) = maybe_timeout else {
+        return Ok(Duration::from_secs(DEFAULT_AWAIT_TIMEOUT_IN_SECONDS));
+    };
+    match timeout.strip_suffix('s').unwrap_or(timeout).parse::<u64>() {
+        Ok(seconds) if (1..=MAX_AWAIT_TIMEOUT_IN_SECONDS).contains(&seconds) => {
+            Ok(Duration::from_secs(seconds))
+        }
+        _ => Err(warp::reject::custom(InvalidParam(Error::msg(format!(
+            "Expected 'timeout' to be between 1s and {}s, received: {}",
+            MAX_AWAIT_TIMEOUT_IN_SECONDS, timeout
+        ))))),
+    }
+}
+
+pub(super) async fn get_state_proof<Db: DatabaseReader + Clone + Send + Sync>(
+    state_root_hash: String,
+    key: String,
+    db: Db,
+    maybe_state_proofs: Option<StateProofs>,
+) -> Result<impl Reply, Rejection> {
+    let state_proofs =
+        maybe_state_proofs.ok_or_else(|| warp::reject::custom(NotEnabled("State proofs")))?;
+    check_hash_is_correct_format(&state_root_hash)?;
+    if let Err(error) = Key::from_formatted_str(&key) {
+        return Err(warp::reject::custom(InvalidParam(Error::msg(format!(
+            "Expected a formatted global state key, received: {} ({})",
+            key, error
+        )))));
+    }
+    match state_proofs.get(state_root_hash, key, &db).await {
+        Ok(state_proof) => Ok(warp::reply::json(&state_proof)),
+        Err(StateProofError::Upstream(error)) => Err(warp::reject::custom(UpstreamError(error))),
+        Err(StateProofError::Storage(error)) => Err(warp::reject::custom(StorageError(error))),
+    }
+}
+
+#[cfg(feature = "graphql")]
+pub(super) async fn execute_graphql<Db: DatabaseReader + Clone + Send + Sync + 'static>(
+    (schema, request): (GraphQlSchema, async_graphql::Request),
+    db: Db,
+) -> Result<impl Reply, Rejection> {
+    Ok(GraphQLResponse::from(
+        graphql::execute(&schema, request, db).await,
+    ))
+}
+
 fn format_or_reject_storage_result<T>(
     storage_result: Result<T, DatabaseReadError>,
 ) -> Result<impl Reply, Rejection>
@@ -117,6 +897,72 @@ where
     }
 }
 
+/// Replies with the stored deploy, serialized by `decode` if its arguments are to be decoded, and
+/// annotated with the alias of the account which sent it, found by `account_hash`, if known.
+fn deploy_or_reject_storage_result<T: Serialize>(
+    storage_result: Result<T, DatabaseReadError>,
+    query: &DeployQuery,
+    decode: fn(&T) -> Result<Value, serde_json::Error>,
+    account_hash: fn(&T) -> Option<String>,
+    account_aliases: &AccountAliases,
+) -> Result<warp::reply::Response, Rejection> {
+    let data = storage_result.map_err(|req_err| warp::reject::custom(StorageError(req_err)))?;
+    let maybe_alias =
+        account_hash(&data).and_then(|account_hash| account_aliases.alias_of(&account_hash));
+    if !query.decode_args && maybe_alias.is_none() {
+        return Ok(
+            warp::reply::with_status(warp::reply::json(&data), StatusCode::OK).into_response(),
+        );
+    }
+    let serialized = if query.decode_args {
+        decode(&data)
+    } else {
+        serde_json::to_value(&data)
+    };
+    let mut json = serialized.map_err(|error| {
+        warp::reject::custom(Unexpected(Error::msg(format!(
+            "Error serializing the deploy: {}",
+            error
+        ))))
+    })?;
+    if let Some(alias) = maybe_alias {
+        json["account_alias"] = Value::String(alias);
+    }
+    Ok(warp::reply::with_status(warp::reply::json(&json), StatusCode::OK).into_response())
+}
+
+/// Replies with the serialized bytes as `application/octet-stream`.
+fn bytes_or_reject_storage_result(
+    storage_result: Result<Result<Vec<u8>, bytesrepr::Error>, DatabaseReadError>,
+) -> Result<impl Reply, Rejection> {
+    match storage_result {
+        Ok(Ok(bytes)) => Ok(bytes),
+        Ok(Err(error)) => Err(warp::reject::custom(Unexpected(Error::msg(format!(
+            "Error serializing to bytes: {}",
+            error
+        ))))),
+        Err(req_err) => Err(warp::reject::custom(StorageError(req_err))),
+    }
+}
+
+fn format_list_or_reject_storage_result<T>(
+    storage_result: Result<Vec<T>, DatabaseReadError>,
+    format: ResponseFormat,
+) -> Result<impl Reply, Rejection>
+where
+    T: Serialize + Send + 'static,
+{
+    match format {
+        ResponseFormat::Json => {
+            format_or_reject_storage_result(storage_result).map(Reply::into_response)
+        }
+        ResponseFormat::Ndjson => match storage_result {
+            Ok(items) => Ok(stream_items(format, items)),
+            Err(req_err) => Err(warp::reject::custom(StorageError(req_err))),
+        },
+    }
+}
+
 fn check_hash_is_correct_format(hash: &str) -> Result<(), Rejection> {
     let hash_regex = regex::Regex::new("^([0-9A-Fa-f]){64}$")
         .map_err(|err| warp::reject::custom(Unexpected(err.into())))?;
@@ -1,7 +1,20 @@
 mod schema_transformation_visitor;
-use crate::types::{
-    database::DeployAggregate,
-    sse_events::{BlockAdded, DeployAccepted, DeployExpired, DeployProcessed, Fault, Step},
+use crate::{
+    block_signatures::BlockFinalitySignatures,
+    deploy_approvals::{DeployApprovals, SignerApproval},
+    deploy_status::{DeployState, DeployStatus, DeployTransition},
+    equivocation::Equivocator,
+    history::ValidatorSet,
+    state_proofs::StateProof,
+    types::{
+        database::{
+            AccountEffectEntry, ContractDayStats, DeployAccountEntry, DeployAggregate,
+            DeployErrorEntry, DeploySizeStats, DeployTimestampEntry, DeployTimestampKind,
+            Distribution, Enrichment, NftEventEntry, NftEventKind, TokenTransferEntry,
+            TokenTransferKind, TransferEntry,
+        },
+        sse_events::{BlockAdded, DeployAccepted, DeployExpired, DeployProcessed, Fault, Step},
+    },
 };
 use casper_event_types::{
     block::json_compatibility::{
@@ -38,19 +51,42 @@ use self::schema_transformation_visitor::SchemaTransformationVisitor;
         paths(crate::rest_server::filters::latest_block,
             crate::rest_server::filters::block_by_hash,
             crate::rest_server::filters::block_by_height,
+            crate::rest_server::filters::raw_block_by_hash,
+            crate::rest_server::filters::blocks_by_height_range,
             crate::rest_server::filters::deploy_by_hash,
             crate::rest_server::filters::deploy_accepted_by_hash,
             crate::rest_server::filters::deploy_expired_by_hash,
+            crate::rest_server::filters::raw_deploy_by_hash,
+            crate::rest_server::filters::deploy_approvals,
+            crate::rest_server::filters::deploy_status,
+            crate::rest_server::filters::await_deploy,
             crate::rest_server::filters::deploy_processed_by_hash,
+            crate::rest_server::filters::deploys_by_time_range,
+            crate::rest_server::filters::deploys_by_error_message,
+            crate::rest_server::filters::deploys_by_account,
             crate::rest_server::filters::faults_by_public_key,
             crate::rest_server::filters::faults_by_era,
+            crate::rest_server::filters::validator_set,
+            crate::rest_server::filters::equivocators_by_era,
             crate::rest_server::filters::finality_signatures_by_block,
+            crate::rest_server::filters::block_finality_signatures,
+            crate::rest_server::filters::deploys_affecting_account,
+            crate::rest_server::filters::transfers_by_account,
+            crate::rest_server::filters::transfers_by_block,
+            crate::rest_server::filters::transfers_by_transfer_id,
+            crate::rest_server::filters::token_transfers,
+            crate::rest_server::filters::nft_events_by_collection,
+            crate::rest_server::filters::nft_events_by_token,
+            crate::rest_server::filters::contract_stats,
+            crate::rest_server::filters::deploy_size_stats,
+            crate::rest_server::filters::enrichments_by_hash,
+            crate::rest_server::filters::state_proof,
             crate::rest_server::filters::step_by_era,
 
 
         ),
         components(
-            schemas(Step, FinalitySignature, Fault, DeployExpired, Deploy, DeployHeader, ExecutableDeployItem, Approval, DeployAggregate, DeployAccepted, DeployProcessed, BlockAdded, JsonBlock, BlockHash, JsonEraEnd, JsonEraReport, JsonBlockBody, JsonBlockHeader, JsonProof, Digest, DeployHash, ValidatorWeight, Reward)
+            schemas(Step, StateProof, DeployApprovals, SignerApproval, DeployStatus, DeployState, DeployTransition, FinalitySignature, BlockFinalitySignatures, Fault, Equivocator, ValidatorSet, DeployExpired, Deploy, DeployHeader, ExecutableDeployItem, Approval, DeployAggregate, DeployTimestampEntry, DeployTimestampKind, DeployErrorEntry, DeployAccountEntry, AccountEffectEntry, TransferEntry, TokenTransferEntry, TokenTransferKind, NftEventEntry, NftEventKind, ContractDayStats, DeploySizeStats, Distribution, Enrichment, DeployAccepted, DeployProcessed, BlockAdded, JsonBlock, BlockHash, JsonEraEnd, JsonEraReport, JsonBlockBody, JsonBlockHeader, JsonProof, Digest, DeployHash, ValidatorWeight, Reward)
         ),
         tags(
             (name = "event-sidecar", description = "Event-sidecar rest API")
@@ -101,7 +137,10 @@ pub fn build_open_api_filters(
         ],
     );
     doc.components = Some(components);
+    // `/api-docs` is an alias of `/api-doc.json`, which the bundled Swagger UI loads.
     let api_doc = warp::path("api-doc.json")
+        .or(warp::path("api-docs"))
+        .unify()
         .and(warp::get())
         .map(move || warp::reply::json(&doc));
     let config = Arc::new(Config::from("/api-doc.json"));
@@ -189,3 +228,27 @@ struct ApiError {
     code: u16,
     message: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn should_serve_the_same_document_at_api_docs() {
+        let filters = build_open_api_filters();
+
+        let api_doc_json = warp::test::request()
+            .path("/api-doc.json")
+            .reply(&filters)
+            .await;
+        let api_docs = warp::test::request()
+            .path("/api-docs")
+            .reply(&filters)
+            .await;
+
+        assert_eq!(api_docs.status(), StatusCode::OK);
+        assert_eq!(api_docs.body(), api_doc_json.body());
+        let document: serde_json::Value = serde_json::from_slice(api_docs.body()).unwrap();
+        assert!(document["openapi"].as_str().unwrap().starts_with("3."));
+    }
+}
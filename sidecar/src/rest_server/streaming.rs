@@ -0,0 +1,310 @@
+//! Chunked responses for large result sets.
+//!
+//! Instead of serializing a whole result set into one JSON document, the items are serialized and
+//! sent one page at a time, either as the elements of a JSON array, as newline-delimited JSON
+//! (`?format=ndjson`) or, for the lists supporting it, as the rows of a CSV table
+//! (`?format=csv`). The next page is only read from the database once the previous one has been
+//! handed over to the connection, so the memory used by a request is bounded by the page size.
+
+use super::csv::{self, CsvRecord};
+use crate::types::database::DatabaseReadError;
+use anyhow::Error;
+use bytes::Bytes;
+use futures::{stream, Future, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use warp::{
+    http::{header::CONTENT_TYPE, HeaderValue},
+    hyper::Body,
+    reply::Response,
+};
+
+const JSON_CONTENT_TYPE: &str = "application/json";
+const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+const CSV_CONTENT_TYPE: &str = "text/csv; charset=utf-8";
+
+/// Representation of a list of items in a response.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(super) enum ResponseFormat {
+    /// A single JSON array.
+    #[default]
+    Json,
+    /// One JSON document per line.
+    Ndjson,
+}
+
+impl ResponseFormat {
+    fn content_type(&self) -> &'static str {
+        match self {
+            ResponseFormat::Json => JSON_CONTENT_TYPE,
+            ResponseFormat::Ndjson => NDJSON_CONTENT_TYPE,
+        }
+    }
+
+    /// Serializes `item`, the `index`th item of the list.
+    fn encode_item<T: Serialize>(&self, item: &T, index: usize) -> Result<Vec<u8>, Error> {
+        let mut chunk = Vec::new();
+        if *self == ResponseFormat::Json {
+            chunk.push(if index == 0 { b'[' } else { b',' });
+        }
+        serde_json::to_writer(&mut chunk, item)?;
+        if *self == ResponseFormat::Ndjson {
+            chunk.push(b'\n');
+        }
+        Ok(chunk)
+    }
+
+    /// Terminates a list of `item_count` items.
+    fn encode_end(&self, item_count: usize) -> Option<Bytes> {
+        match (self, item_count) {
+            (ResponseFormat::Json, 0) => Some(Bytes::from_static(b"[]")),
+            (ResponseFormat::Json, _) => Some(Bytes::from_static(b"]")),
+            (ResponseFormat::Ndjson, _) => None,
+        }
+    }
+
+    fn encode_page<T: Serialize>(&self, page: &[T], items_sent: usize) -> Result<Bytes, Error> {
+        let mut chunk = Vec::new();
+        for (offset, item) in page.iter().enumerate() {
+            chunk.extend(self.encode_item(item, items_sent + offset)?);
+        }
+        Ok(Bytes::from(chunk))
+    }
+}
+
+/// Query parameters selecting the [ResponseFormat].
+#[derive(Debug, Default, Deserialize)]
+pub(super) struct FormatQuery {
+    #[serde(default)]
+    pub(super) format: ResponseFormat,
+}
+
+/// Representation of a list of items which can also be returned as a CSV table.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(super) enum TableFormat {
+    #[default]
+    Json,
+    Ndjson,
+    /// One row per item, after a header row.
+    Csv,
+}
+
+/// Query parameters selecting the [TableFormat].
+#[derive(Debug, Default, Deserialize)]
+pub(super) struct TableFormatQuery {
+    #[serde(default)]
+    pub(super) format: TableFormat,
+}
+
+/// Encoding of the items of a list into the body of a response.
+trait ListEncoding<T>: Copy + Send + 'static {
+    fn content_type(&self) -> &'static str;
+
+    /// Encodes `page`, whose first item is the `items_sent`th of the list.
+    fn encode_page(&self, page: &[T], items_sent: usize) -> Result<Bytes, Error>;
+
+    /// Terminates a list of `item_count` items.
+    fn encode_end(&self, item_count: usize) -> Option<Bytes>;
+}
+
+impl<T: Serialize> ListEncoding<T> for ResponseFormat {
+    fn content_type(&self) -> &'static str {
+        ResponseFormat::content_type(self)
+    }
+
+    fn encode_page(&self, page: &[T], items_sent: usize) -> Result<Bytes, Error> {
+        ResponseFormat::encode_page(self, page, items_sent)
+    }
+
+    fn encode_end(&self, item_count: usize) -> Option<Bytes> {
+        ResponseFormat::encode_end(self, item_count)
+    }
+}
+
+/// Encoding of the items of a list as the rows of a CSV table.
+#[derive(Clone, Copy)]
+struct CsvEncoding;
+
+impl CsvEncoding {
+    fn header<T: CsvRecord>() -> Vec<u8> {
+        let mut header = Vec::new();
+        csv::encode_row(T::COLUMNS, &mut header);
+        header
+    }
+}
+
+impl<T: CsvRecord> ListEncoding<T> for CsvEncoding {
+    fn content_type(&self) -> &'static str {
+        CSV_CONTENT_TYPE
+    }
+
+    fn encode_page(&self, page: &[T], items_sent: usize) -> Result<Bytes, Error> {
+        let mut chunk = if items_sent == 0 && !page.is_empty() {
+            CsvEncoding::header::<T>()
+        } else {
+            Vec::new()
+        };
+        for item in page {
+            csv::encode_row(&item.fields(), &mut chunk);
+        }
+        Ok(Bytes::from(chunk))
+    }
+
+    fn encode_end(&self, item_count: usize) -> Option<Bytes> {
+        // An empty table still has its header.
+        (item_count == 0).then(|| Bytes::from(CsvEncoding::header::<T>()))
+    }
+}
+
+enum NextPage<T, C> {
+    Ready(Vec<T>),
+    Fetch(C),
+    End,
+    Done,
+}
+
+/// Streams the items read page by page with `fetch_page` as a chunked response.
+///
+/// `first_page` is read by the caller so that errors can still be reported with an appropriate
+/// status. A page with fewer than `page_size` items is the last one, otherwise `fetch_page` is
+/// called with the cursor `next_cursor` derives from the last item of the page. If reading a page
+/// fails, the response is aborted.
+pub(super) fn stream_pages<T, C, F, Fut>(
+    format: ResponseFormat,
+    first_page: Vec<T>,
+    page_size: usize,
+    next_cursor: fn(&T) -> Option<C>,
+    fetch_page: F,
+) -> Response
+where
+    T: Serialize + Send + 'static,
+    C: Send + 'static,
+    F: Fn(C) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<Vec<T>, DatabaseReadError>> + Send,
+{
+    stream_encoded_pages(format, first_page, page_size, next_cursor, fetch_page)
+}
+
+/// Streams the items read page by page with `fetch_page` like [stream_pages], or as the rows of a
+/// CSV table.
+pub(super) fn stream_table_pages<T, C, F, Fut>(
+    format: TableFormat,
+    first_page: Vec<T>,
+    page_size: usize,
+    next_cursor: fn(&T) -> Option<C>,
+    fetch_page: F,
+) -> Response
+where
+    T: Serialize + CsvRecord + Send + 'static,
+    C: Send + 'static,
+    F: Fn(C) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<Vec<T>, DatabaseReadError>> + Send,
+{
+    match format {
+        TableFormat::Json => stream_encoded_pages(
+            ResponseFormat::Json,
+            first_page,
+            page_size,
+            next_cursor,
+            fetch_page,
+        ),
+        TableFormat::Ndjson => stream_encoded_pages(
+            ResponseFormat::Ndjson,
+            first_page,
+            page_size,
+            next_cursor,
+            fetch_page,
+        ),
+        TableFormat::Csv => {
+            stream_encoded_pages(CsvEncoding, first_page, page_size, next_cursor, fetch_page)
+        }
+    }
+}
+
+/// Streams `items` which are already in memory like [stream_items], or as the rows of a CSV table.
+pub(super) fn stream_table_items<T>(format: TableFormat, items: Vec<T>) -> Response
+where
+    T: Serialize + CsvRecord + Send + 'static,
+{
+    let page_size = items.len();
+    stream_table_pages(
+        format,
+        items,
+        page_size,
+        |_| None::<()>,
+        |()| async { Ok(Vec::new()) },
+    )
+}
+
+fn stream_encoded_pages<T, C, F, Fut, E>(
+    encoding: E,
+    first_page: Vec<T>,
+    page_size: usize,
+    next_cursor: fn(&T) -> Option<C>,
+    fetch_page: F,
+) -> Response
+where
+    T: Send + 'static,
+    C: Send + 'static,
+    F: Fn(C) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<Vec<T>, DatabaseReadError>> + Send,
+    E: ListEncoding<T>,
+{
+    let chunks = stream::unfold(
+        (NextPage::Ready(first_page), fetch_page, 0),
+        move |(next_page, fetch_page, items_sent)| async move {
+            let page = match next_page {
+                NextPage::Ready(page) => page,
+                NextPage::Fetch(cursor) => match fetch_page(cursor).await {
+                    Ok(page) => page,
+                    Err(error) => {
+                        warn!(?error, "Error reading the next page of a streamed response");
+                        let error = Error::msg(format!("Error reading page: {:?}", error));
+                        return Some((Err(error), (NextPage::Done, fetch_page, items_sent)));
+                    }
+                },
+                NextPage::End => {
+                    let end = encoding.encode_end(items_sent)?;
+                    return Some((Ok(end), (NextPage::Done, fetch_page, items_sent)));
+                }
+                NextPage::Done => return None,
+            };
+            let chunk = match encoding.encode_page(&page, items_sent) {
+                Ok(chunk) => chunk,
+                Err(error) => return Some((Err(error), (NextPage::Done, fetch_page, items_sent))),
+            };
+            let next_page = match page.last().and_then(next_cursor) {
+                Some(cursor) if page.len() >= page_size => NextPage::Fetch(cursor),
+                _ => NextPage::End,
+            };
+            Some((Ok(chunk), (next_page, fetch_page, items_sent + page.len())))
+        },
+    );
+    chunked_response(encoding.content_type(), chunks)
+}
+
+/// Streams `items` which are already in memory as a chunked response, sparing the serialization
+/// of the whole list into a single buffer.
+pub(super) fn stream_items<T>(format: ResponseFormat, items: Vec<T>) -> Response
+where
+    T: Serialize + Send + 'static,
+{
+    let item_count = items.len();
+    let chunks = stream::iter(items.into_iter().enumerate())
+        .map(move |(index, item)| format.encode_item(&item, index).map(Bytes::from))
+        .chain(stream::iter(format.encode_end(item_count).map(Ok)));
+    chunked_response(format.content_type(), chunks)
+}
+
+fn chunked_response<S>(content_type: &'static str, chunks: S) -> Response
+where
+    S: Stream<Item = Result<Bytes, Error>> + Send + 'static,
+{
+    let mut response = Response::new(Body::wrap_stream(chunks));
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+    response
+}
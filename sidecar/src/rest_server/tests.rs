@@ -1,23 +1,64 @@
-use casper_event_types::FinalitySignature as FinSig;
-use casper_types::AsymmetricType;
+use casper_event_types::{
+    block::BlockHeader, sse_data::SseData, Deploy, Digest, FinalitySignature as FinSig, JsonBlock,
+};
+use casper_types::{
+    account::AccountHash, bytesrepr::ToBytes, testing::TestRng, AccessRights, AsymmetricType,
+    ContractHash, EraId, Key, PublicKey, SecretKey, Timestamp, Transfer, TransferAddr, Transform,
+    TransformEntry, URef, U256, U512,
+};
 use http::StatusCode;
-use warp::test::request;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use warp::{test::request, Filter, Reply};
 
 use super::filters;
 use crate::{
+    account_aliases::AccountAliases,
+    deploy_decoding::deploy_processed_of,
+    deploy_outcomes::DeployOutcomes,
+    equivocation::Equivocator,
+    history::ValidatorSet,
+    state_proofs::StateProofs,
     testing::fake_database::FakeDatabase,
-    types::{database::DeployAggregate, sse_events::*},
+    types::{
+        config::StateProofConfig,
+        database::{
+            AccountEffectEntry, ContractDayStats, DatabaseWriter, DeployAccountEntry,
+            DeployAggregate, DeployErrorEntry, DeploySizeStats, DeployTimestampEntry,
+            DeployTimestampKind, Enrichment, NftEventEntry, NftEventKind, TokenTransferEntry,
+            TokenTransferKind, TransferEntry,
+        },
+        sse_events::*,
+    },
 };
 
 // Path elements
 const BLOCK: &str = "block";
+const BLOCKS: &str = "blocks";
 const DEPLOY: &str = "deploy";
+const DEPLOYS: &str = "deploys";
 const FAULTS: &str = "faults";
+const CONTRACT: &str = "contract";
+const VALIDATORS: &str = "validators";
+const EQUIVOCATORS: &str = "equivocators";
 const SIGNATURES: &str = "signatures";
+const FINALITY_SIGNATURES: &str = "finality_signatures";
+const ENRICHMENTS: &str = "enrichments";
 const STEP: &str = "step";
 const ACCEPTED: &str = "accepted";
 const PROCESSED: &str = "processed";
 const EXPIRED: &str = "expired";
+const RAW: &str = "raw";
+const APPROVALS: &str = "approvals";
+const STATUS: &str = "status";
+const AWAIT: &str = "await";
+const STATE: &str = "state";
+const PROOF: &str = "proof";
 
 // Example parameters
 const VALID_HASH: &str = "0bcd71363b01c1c147c1603d2cc945930dcceecd869275beeee61dfc83b27a2c";
@@ -29,7 +70,12 @@ const INVALID_PUBLIC_KEY: &str = "not_a_public_key";
 async fn should_respond_to_path_with(request_path: String, expected_status: StatusCode) {
     let database = FakeDatabase::new();
 
-    let api = filters::combined_filters(database);
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
 
     let response = request().path(&request_path).reply(&api).await;
 
@@ -57,7 +103,12 @@ async fn block_root_should_return_valid_data() {
 
     // The database doesn't need to be populated with events for this test as it returns a random BlockAdded for get_latest_block()
 
-    let api = filters::combined_filters(database);
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
 
     let request_path = format!("/{}", BLOCK);
 
@@ -78,7 +129,12 @@ async fn block_by_hash_should_return_valid_data() {
         .await
         .expect("Error populating FakeDatabase");
 
-    let api = filters::combined_filters(database);
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
 
     let request_path = format!("/{}/{}", BLOCK, identifiers.block_added_hash);
 
@@ -93,6 +149,43 @@ async fn block_by_hash_should_return_valid_data() {
     assert_eq!(block_added.hex_encoded_hash(), identifiers.block_added_hash);
 }
 
+#[tokio::test]
+async fn raw_block_by_hash_should_return_canonical_bytes() {
+    let database = FakeDatabase::new();
+    let mut test_rng = TestRng::new();
+    let block = JsonBlock::random(&mut test_rng);
+    let header_length = BlockHeader::from(block.header.clone()).serialized_length();
+    let block_added = BlockAdded::new(block.hash, Box::new(block));
+    database
+        .save_block_added(block_added.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving block_added");
+
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let request_path = format!("/{}/{}/{}", BLOCK, block_added.hex_encoded_hash(), RAW);
+
+    let response = request().path(&request_path).reply(&api).await;
+
+    assert!(response.status().is_success());
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "application/octet-stream"
+    );
+
+    let body = response.into_body();
+    assert_eq!(body.to_vec(), block_added.canonical_bytes().unwrap());
+    // The block hash is the hash of the serialized header, which follows the block hash.
+    let hash_length = Digest::LENGTH;
+    let header_hash = Digest::hash(&body[hash_length..hash_length + header_length]);
+    assert_eq!(hex::encode(header_hash), block_added.hex_encoded_hash());
+}
+
 #[tokio::test]
 async fn block_by_height_should_return_valid_data() {
     let database = FakeDatabase::new();
@@ -102,7 +195,12 @@ async fn block_by_height_should_return_valid_data() {
         .await
         .expect("Error populating FakeDatabase");
 
-    let api = filters::combined_filters(database);
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
 
     let request_path = format!("/{}/{}", BLOCK, identifiers.block_added_height);
 
@@ -126,7 +224,12 @@ async fn deploy_by_hash_should_return_valid_data() {
         .await
         .expect("Error populating FakeDatabase");
 
-    let api = filters::combined_filters(database);
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
 
     let request_path = format!("/{}/{}", DEPLOY, identifiers.deploy_accepted_hash);
 
@@ -153,7 +256,12 @@ async fn deploy_accepted_by_hash_should_return_valid_data() {
         .await
         .expect("Error populating FakeDatabase");
 
-    let api = filters::combined_filters(database);
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
 
     let request_path = format!(
         "/{}/{}/{}",
@@ -174,6 +282,60 @@ async fn deploy_accepted_by_hash_should_return_valid_data() {
     );
 }
 
+#[tokio::test]
+async fn deploy_accepted_by_hash_should_decode_args_when_requested() {
+    let database = FakeDatabase::new();
+
+    let identifiers = database
+        .populate_with_events()
+        .await
+        .expect("Error populating FakeDatabase");
+
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let request_path = format!(
+        "/{}/{}/{}?decode_args=true",
+        DEPLOY, ACCEPTED, identifiers.deploy_accepted_hash
+    );
+
+    let response = request().path(&request_path).reply(&api).await;
+
+    assert!(response.status().is_success());
+
+    let body = response.into_body();
+    let deploy_accepted = serde_json::from_slice::<serde_json::Value>(&body)
+        .expect("Error parsing JSON from response");
+
+    assert_eq!(
+        deploy_accepted["deploy"]["payment"]["StoredContractByName"]["args"],
+        serde_json::json!([{ "name": "amount", "cl_type": "U512", "value": "10" }])
+    );
+
+    let request_path = format!(
+        "/{}/{}?decode_args=true",
+        DEPLOY, identifiers.deploy_accepted_hash
+    );
+
+    let response = request().path(&request_path).reply(&api).await;
+
+    assert!(response.status().is_success());
+
+    let body = response.into_body();
+    let aggregate = serde_json::from_slice::<serde_json::Value>(&body)
+        .expect("Error parsing JSON from response");
+
+    assert_eq!(
+        aggregate["deploy_accepted"]["deploy"]["payment"]["StoredContractByName"]["args"][0]
+            ["value"],
+        "10"
+    );
+}
+
 #[tokio::test]
 async fn deploy_processed_by_hash_should_return_valid_data() {
     let database = FakeDatabase::new();
@@ -183,7 +345,12 @@ async fn deploy_processed_by_hash_should_return_valid_data() {
         .await
         .expect("Error populating FakeDatabase");
 
-    let api = filters::combined_filters(database);
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
 
     let request_path = format!(
         "/{}/{}/{}",
@@ -213,7 +380,12 @@ async fn deploy_expired_by_hash_should_return_valid_data() {
         .await
         .expect("Error populating FakeDatabase");
 
-    let api = filters::combined_filters(database);
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
 
     let request_path = format!(
         "/{}/{}/{}",
@@ -243,7 +415,12 @@ async fn step_by_era_should_return_valid_data() {
         .await
         .expect("Error populating FakeDatabase");
 
-    let api = filters::combined_filters(database);
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
 
     let request_path = format!("/{}/{}", STEP, identifiers.step_era_id);
 
@@ -266,7 +443,12 @@ async fn faults_by_public_key_should_return_valid_data() {
         .await
         .expect("Error populating FakeDatabase");
 
-    let api = filters::combined_filters(database);
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
 
     let request_path = format!("/{}/{}", FAULTS, identifiers.fault_public_key);
 
@@ -290,7 +472,12 @@ async fn faults_by_era_should_return_valid_data() {
         .await
         .expect("Error populating FakeDatabase");
 
-    let api = filters::combined_filters(database);
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
 
     let request_path = format!("/{}/{}", FAULTS, identifiers.fault_era_id);
 
@@ -314,7 +501,12 @@ async fn finality_signatures_by_block_should_return_valid_data() {
         .await
         .expect("Error populating FakeDatabase");
 
-    let api = filters::combined_filters(database);
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
 
     let request_path = format!(
         "/{}/{}",
@@ -335,6 +527,111 @@ async fn finality_signatures_by_block_should_return_valid_data() {
     );
 }
 
+#[tokio::test]
+async fn block_finality_signatures_should_return_valid_data() {
+    let database = FakeDatabase::new();
+
+    let identifiers = database
+        .populate_with_events()
+        .await
+        .expect("Error populating FakeDatabase");
+
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let request_path = format!(
+        "/{}/{}/{}",
+        BLOCK, identifiers.finality_signatures_block_hash, FINALITY_SIGNATURES
+    );
+
+    let response = request().path(&request_path).reply(&api).await;
+
+    assert!(response.status().is_success());
+
+    let body = response.into_body();
+    let block_signatures = serde_json::from_slice::<serde_json::Value>(&body)
+        .expect("Error parsing block finality signatures from response");
+
+    assert_eq!(
+        block_signatures["block_hash"],
+        identifiers.finality_signatures_block_hash
+    );
+    assert_eq!(block_signatures["signatures"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn enrichments_by_hash_should_return_valid_data() {
+    let database = FakeDatabase::new();
+    let enrichment = Enrichment {
+        entity_hash: VALID_HASH.to_string(),
+        kind: "era_summary".to_string(),
+        value: serde_json::json!({ "era_id": 1 }),
+    };
+    database
+        .save_enrichment(enrichment.clone())
+        .await
+        .expect("Error saving enrichment");
+
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let request_path = format!("/{}/{}", ENRICHMENTS, VALID_HASH);
+
+    let response = request().path(&request_path).reply(&api).await;
+
+    assert!(response.status().is_success());
+
+    let body = response.into_body();
+    let enrichments = serde_json::from_slice::<Vec<Enrichment>>(&body)
+        .expect("Error parsing Enrichments from response");
+
+    assert_eq!(enrichments, vec![enrichment]);
+}
+
+#[tokio::test]
+async fn raw_deploy_by_hash_should_return_canonical_bytes() {
+    let database = FakeDatabase::new();
+    let mut test_rng = TestRng::new();
+    let deploy = Deploy::random(&mut test_rng);
+    let header_length = deploy.header().serialized_length();
+    let deploy_accepted = DeployAccepted::new(Arc::new(deploy));
+    database
+        .save_deploy_accepted(deploy_accepted.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_accepted");
+
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let request_path = format!("/{}/{}/{}", DEPLOY, deploy_accepted.hex_encoded_hash(), RAW);
+
+    let response = request().path(&request_path).reply(&api).await;
+
+    assert!(response.status().is_success());
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "application/octet-stream"
+    );
+
+    let body = response.into_body();
+    assert_eq!(body.to_vec(), deploy_accepted.canonical_bytes().unwrap());
+    // The deploy hash is the hash of the serialized header, which comes first.
+    let header_hash = Digest::hash(&body[..header_length]);
+    assert_eq!(hex::encode(header_hash), deploy_accepted.hex_encoded_hash());
+}
+
 #[tokio::test]
 async fn block_by_hash_of_not_stored_should_return_404() {
     let request_path = format!("/{}/{}", BLOCK, VALID_HASH);
@@ -363,6 +660,13 @@ async fn deploy_accepted_by_hash_of_not_stored_should_return_404() {
     should_respond_to_path_with(request_path, StatusCode::NOT_FOUND).await
 }
 
+#[tokio::test]
+async fn deploy_status_of_not_stored_should_return_404() {
+    let request_path = format!("/{}/{}/{}", DEPLOY, VALID_HASH, STATUS);
+
+    should_respond_to_path_with(request_path, StatusCode::NOT_FOUND).await
+}
+
 #[tokio::test]
 async fn deploy_processed_by_hash_of_not_stored_should_return_404() {
     let request_path = format!("/{}/{}/{}", DEPLOY, PROCESSED, VALID_HASH);
@@ -398,6 +702,34 @@ async fn finality_signature_by_block_hash_of_not_stored_should_return_404() {
     should_respond_to_path_with(request_path, StatusCode::NOT_FOUND).await
 }
 
+#[tokio::test]
+async fn block_finality_signatures_of_not_stored_should_return_404() {
+    let request_path = format!("/{}/{}/{}", BLOCK, VALID_HASH, FINALITY_SIGNATURES);
+
+    should_respond_to_path_with(request_path, StatusCode::NOT_FOUND).await
+}
+
+#[tokio::test]
+async fn raw_block_by_hash_of_not_stored_should_return_404() {
+    let request_path = format!("/{}/{}/{}", BLOCK, VALID_HASH, RAW);
+
+    should_respond_to_path_with(request_path, StatusCode::NOT_FOUND).await
+}
+
+#[tokio::test]
+async fn raw_deploy_by_hash_of_not_stored_should_return_404() {
+    let request_path = format!("/{}/{}/{}", DEPLOY, VALID_HASH, RAW);
+
+    should_respond_to_path_with(request_path, StatusCode::NOT_FOUND).await
+}
+
+#[tokio::test]
+async fn enrichments_by_hash_of_not_stored_should_return_404() {
+    let request_path = format!("/{}/{}", ENRICHMENTS, VALID_HASH);
+
+    should_respond_to_path_with(request_path, StatusCode::NOT_FOUND).await
+}
+
 #[tokio::test]
 async fn step_by_era_of_not_stored_should_return_404() {
     let request_path = format!("/{}/{}", STEP, VALID_ERA);
@@ -447,6 +779,13 @@ async fn faults_by_invalid_public_key_should_return_400() {
     should_respond_to_path_with(request_path, StatusCode::BAD_REQUEST).await
 }
 
+#[tokio::test]
+async fn enrichments_by_invalid_hash_should_return_400() {
+    let request_path = format!("/{}/{}", ENRICHMENTS, INVALID_HASH);
+
+    should_respond_to_path_with(request_path, StatusCode::BAD_REQUEST).await
+}
+
 #[tokio::test]
 async fn finality_signature_by_invalid_block_hash_should_return_400() {
     let request_path = format!("/{}/{}", SIGNATURES, INVALID_HASH);
@@ -458,7 +797,12 @@ async fn finality_signature_by_invalid_block_hash_should_return_400() {
 async fn should_have_correct_content_type() {
     let database = FakeDatabase::new();
 
-    let api = filters::combined_filters(database);
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
 
     let request_path = format!("/{}", BLOCK);
 
@@ -472,3 +816,1558 @@ async fn should_have_correct_content_type() {
         "application/json"
     );
 }
+
+async fn database_with_blocks_at_heights(heights: impl Iterator<Item = u64>) -> FakeDatabase {
+    let database = FakeDatabase::new();
+    let mut rng = TestRng::new();
+    for height in heights {
+        database
+            .save_block_added(
+                BlockAdded::random_with_height(&mut rng, height),
+                height as u32,
+                "127.0.0.1".to_string(),
+            )
+            .await
+            .expect("Error saving BlockAdded");
+    }
+    database
+}
+
+#[tokio::test]
+async fn blocks_by_height_range_should_stream_all_pages_as_json_array() {
+    let database = database_with_blocks_at_heights(0..250).await;
+
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let request_path = format!("/{}?from=20&to=229", BLOCKS);
+
+    let response = request().path(&request_path).reply(&api).await;
+
+    assert!(response.status().is_success());
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "application/json"
+    );
+
+    let body = response.into_body();
+    let blocks = serde_json::from_slice::<Vec<BlockAdded>>(&body)
+        .expect("Error parsing BlockAdded list from response");
+    let heights: Vec<u64> = blocks.iter().map(BlockAdded::get_height).collect();
+
+    assert_eq!(heights, (20..230).collect::<Vec<u64>>());
+}
+
+#[tokio::test]
+async fn blocks_by_height_range_should_stream_ndjson() {
+    let database = database_with_blocks_at_heights(0..120).await;
+
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let request_path = format!("/{}?from=10&format=ndjson", BLOCKS);
+
+    let response = request().path(&request_path).reply(&api).await;
+
+    assert!(response.status().is_success());
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "application/x-ndjson"
+    );
+
+    let body = String::from_utf8(response.into_body().to_vec()).unwrap();
+    let heights: Vec<u64> = body
+        .lines()
+        .map(|line| {
+            serde_json::from_str::<BlockAdded>(line)
+                .expect("Error parsing BlockAdded from line")
+                .get_height()
+        })
+        .collect();
+
+    assert_eq!(heights, (10..120).collect::<Vec<u64>>());
+}
+
+#[tokio::test]
+async fn blocks_by_height_range_without_blocks_should_return_empty_array() {
+    let api = filters::combined_filters(
+        FakeDatabase::new(),
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let request_path = format!("/{}?from=1&to=2", BLOCKS);
+
+    let response = request().path(&request_path).reply(&api).await;
+
+    assert!(response.status().is_success());
+    assert_eq!(response.into_body(), "[]");
+}
+
+#[tokio::test]
+async fn blocks_by_inverted_height_range_should_return_400() {
+    let request_path = format!("/{}?from=2&to=1", BLOCKS);
+
+    should_respond_to_path_with(request_path, StatusCode::BAD_REQUEST).await
+}
+
+#[tokio::test]
+async fn faults_by_era_should_return_ndjson_when_requested() {
+    let database = FakeDatabase::new();
+
+    let identifiers = database
+        .populate_with_events()
+        .await
+        .expect("Error populating FakeDatabase");
+
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let request_path = format!("/{}/{}?format=ndjson", FAULTS, identifiers.fault_era_id);
+
+    let response = request().path(&request_path).reply(&api).await;
+
+    assert!(response.status().is_success());
+
+    let body = String::from_utf8(response.into_body().to_vec()).unwrap();
+    let faults: Vec<Fault> = body
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("Error parsing Fault from line"))
+        .collect();
+
+    assert_eq!(faults.len(), 1);
+    assert_eq!(faults[0].era_id.value(), identifiers.fault_era_id);
+}
+
+#[tokio::test]
+async fn deploys_by_time_range_should_list_accepted_deploys() {
+    let database = FakeDatabase::new();
+    let mut rng = TestRng::new();
+    let deploy_accepted = DeployAccepted::random(&mut rng);
+    database
+        .save_deploy_accepted(deploy_accepted.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving DeployAccepted");
+
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let request_path = format!("/{}?from=2000-01-01&to=2100-01-01T12:00:00Z", DEPLOYS);
+
+    let response = request().path(&request_path).reply(&api).await;
+
+    assert!(response.status().is_success());
+
+    let body = response.into_body();
+    let entries = serde_json::from_slice::<Vec<DeployTimestampEntry>>(&body)
+        .expect("Error parsing DeployTimestampEntry list from response");
+
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].deploy_hash, deploy_accepted.hex_encoded_hash());
+    assert_eq!(entries[0].kind, DeployTimestampKind::Accepted);
+}
+
+#[tokio::test]
+async fn deploys_by_time_range_outside_of_stored_times_should_return_empty_array() {
+    let database = FakeDatabase::new();
+    database
+        .populate_with_events()
+        .await
+        .expect("Error populating FakeDatabase");
+
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let request_path = format!("/{}?from=2000-01-01&to=2000-01-02", DEPLOYS);
+
+    let response = request().path(&request_path).reply(&api).await;
+
+    assert!(response.status().is_success());
+    assert_eq!(response.into_body(), "[]");
+}
+
+#[tokio::test]
+async fn deploys_by_error_message_should_list_matching_failed_deploys() {
+    let database = FakeDatabase::new();
+    let mut rng = TestRng::new();
+    let reverted = DeployProcessed::random(&mut rng, None).with_error_message("User error: 1");
+    let out_of_gas = DeployProcessed::random(&mut rng, None).with_error_message("Out of gas error");
+    for deploy_processed in [reverted.clone(), out_of_gas] {
+        database
+            .save_deploy_processed(deploy_processed, 1, "127.0.0.1".to_string())
+            .await
+            .expect("Error saving DeployProcessed");
+    }
+
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let request_path = format!("/{}/errors?contains=User%20error%3A%201", DEPLOYS);
+
+    let response = request().path(&request_path).reply(&api).await;
+
+    assert!(response.status().is_success());
+
+    let body = response.into_body();
+    let entries = serde_json::from_slice::<Vec<DeployErrorEntry>>(&body)
+        .expect("Error parsing DeployErrorEntry list from response");
+
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].deploy_hash, reverted.hex_encoded_hash());
+    assert_eq!(entries[0].error_message, "User error: 1");
+}
+
+#[tokio::test]
+async fn deploys_by_error_message_should_return_csv_when_requested() {
+    let database = FakeDatabase::new();
+    let mut rng = TestRng::new();
+    let reverted =
+        DeployProcessed::random(&mut rng, None).with_error_message("User error: 1, \"reverted\"");
+    database
+        .save_deploy_processed(reverted.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving DeployProcessed");
+
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let request_path = format!("/{}/errors?contains=User%20error&format=csv", DEPLOYS);
+
+    let response = request().path(&request_path).reply(&api).await;
+
+    assert!(response.status().is_success());
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "text/csv; charset=utf-8"
+    );
+
+    let body = String::from_utf8(response.into_body().to_vec()).unwrap();
+    assert_eq!(
+        body,
+        format!(
+            "deploy_hash,block_hash,error_message\r\n{},{},\"User error: 1, \"\"reverted\"\"\"\r\n",
+            reverted.hex_encoded_hash(),
+            reverted.hex_encoded_block_hash()
+        )
+    );
+}
+
+#[tokio::test]
+async fn deploys_by_time_range_without_deploys_should_return_csv_header() {
+    let api = filters::combined_filters(
+        FakeDatabase::new(),
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let request_path = format!("/{}?from=2000-01-01&to=2000-01-02&format=csv", DEPLOYS);
+
+    let response = request().path(&request_path).reply(&api).await;
+
+    assert!(response.status().is_success());
+    assert_eq!(response.into_body(), "deploy_hash,kind,timestamp,time\r\n");
+}
+
+#[tokio::test]
+async fn deploys_by_empty_error_message_should_return_400() {
+    let request_path = format!("/{}/errors?contains=", DEPLOYS);
+
+    should_respond_to_path_with(request_path, StatusCode::BAD_REQUEST).await
+}
+
+#[tokio::test]
+async fn deploys_by_account_should_resolve_aliases_and_annotate_responses() {
+    let database = FakeDatabase::new();
+    let mut rng = TestRng::new();
+    let secret_key = SecretKey::random(&mut rng);
+    let treasury = DeployAccepted::random_sent_by(&mut rng, &secret_key);
+    for deploy_accepted in [treasury.clone(), DeployAccepted::random(&mut rng)] {
+        database
+            .save_deploy_accepted(deploy_accepted, 1, "127.0.0.1".to_string())
+            .await
+            .expect("Error saving DeployAccepted");
+    }
+    let account_aliases = AccountAliases::default();
+    account_aliases
+        .add("treasury", &PublicKey::from(&secret_key).to_hex())
+        .expect("Error adding account alias");
+
+    let api = filters::combined_filters(database, None, account_aliases, DeployOutcomes::default());
+
+    let request_path = format!("/{}/account/@treasury", DEPLOYS);
+
+    let response = request().path(&request_path).reply(&api).await;
+
+    assert!(response.status().is_success());
+
+    let body = response.into_body();
+    let entries = serde_json::from_slice::<Vec<DeployAccountEntry>>(&body)
+        .expect("Error parsing DeployAccountEntry list from response");
+
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].deploy_hash, treasury.hex_encoded_hash());
+    assert_eq!(entries[0].account_hash, treasury.hex_encoded_account_hash());
+    assert_eq!(entries[0].account_alias, Some("treasury".to_string()));
+
+    let request_path = format!("/{}/{}", DEPLOY, treasury.hex_encoded_hash());
+
+    let response = request().path(&request_path).reply(&api).await;
+
+    assert!(response.status().is_success());
+
+    let body = response.into_body();
+    let aggregate = serde_json::from_slice::<serde_json::Value>(&body)
+        .expect("Error parsing JSON from response");
+
+    assert_eq!(aggregate["account_alias"], "treasury");
+    assert_eq!(aggregate["deploy_hash"], treasury.hex_encoded_hash());
+}
+
+#[tokio::test]
+async fn deploys_by_account_at_height_should_list_the_deploys_processed_by_then() {
+    let database = FakeDatabase::new();
+    let mut rng = TestRng::new();
+    let secret_key = SecretKey::random(&mut rng);
+    let processed = DeployAccepted::random_sent_by(&mut rng, &secret_key);
+    let pending = DeployAccepted::random_sent_by(&mut rng, &secret_key);
+    for deploy_accepted in vec![processed.clone(), pending] {
+        database
+            .save_deploy_accepted(deploy_accepted, 1, "127.0.0.1".to_string())
+            .await
+            .expect("Error saving DeployAccepted");
+    }
+    let block_added = BlockAdded::random_with_height(&mut rng, 10);
+    let deploy_processed = DeployProcessed::random(&mut rng, Some(processed.deploy_hash()))
+        .with_block_hash(block_added.block_hash());
+    database
+        .save_block_added(block_added, 2, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving BlockAdded");
+    database
+        .save_deploy_processed(deploy_processed, 3, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving DeployProcessed");
+
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let request_path = format!(
+        "/{}/account/{}?at_height=10",
+        DEPLOYS,
+        processed.hex_encoded_account_hash()
+    );
+
+    let response = request().path(&request_path).reply(&api).await;
+
+    assert!(response.status().is_success());
+
+    let body = response.into_body();
+    let entries = serde_json::from_slice::<Vec<DeployAccountEntry>>(&body)
+        .expect("Error parsing DeployAccountEntry list from response");
+
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].deploy_hash, processed.hex_encoded_hash());
+}
+
+#[tokio::test]
+async fn deploys_by_unknown_account_alias_should_return_400() {
+    let request_path = format!("/{}/account/@unknown", DEPLOYS);
+
+    should_respond_to_path_with(request_path, StatusCode::BAD_REQUEST).await
+}
+
+#[tokio::test]
+async fn deploys_affecting_account_should_list_deploys_touching_its_state() {
+    let database = FakeDatabase::new();
+    let mut rng = TestRng::new();
+    let account_hash = AccountHash::new([3; 32]);
+    let deploy_processed =
+        DeployProcessed::random(&mut rng, None).with_transforms(vec![TransformEntry {
+            key: Key::Account(account_hash).to_formatted_string(),
+            transform: Transform::Identity,
+        }]);
+    for deploy_processed in [
+        deploy_processed.clone(),
+        DeployProcessed::random(&mut rng, None).with_transforms(vec![]),
+    ] {
+        database
+            .save_deploy_processed(deploy_processed, 1, "127.0.0.1".to_string())
+            .await
+            .expect("Error saving DeployProcessed");
+    }
+
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let request_path = format!(
+        "/account/{}/affected-by",
+        Key::Account(account_hash).to_formatted_string()
+    );
+
+    let response = request().path(&request_path).reply(&api).await;
+
+    assert!(response.status().is_success());
+
+    let body = response.into_body();
+    let entries = serde_json::from_slice::<Vec<AccountEffectEntry>>(&body)
+        .expect("Error parsing AccountEffectEntry list from response");
+
+    assert_eq!(
+        entries,
+        vec![AccountEffectEntry {
+            deploy_hash: deploy_processed.hex_encoded_hash(),
+            account_hash: hex::encode(account_hash.value()),
+        }]
+    );
+}
+
+/// A processed deploy writing a transfer of 2.5 CSPR from `from`.
+fn deploy_with_transfer(
+    rng: &mut TestRng,
+    from: AccountHash,
+    maybe_to: Option<AccountHash>,
+    maybe_id: Option<u64>,
+) -> DeployProcessed {
+    DeployProcessed::random(rng, None).with_transforms(vec![TransformEntry {
+        key: Key::Transfer(TransferAddr::new([1; 32])).to_formatted_string(),
+        transform: Transform::WriteTransfer(Transfer::new(
+            casper_types::DeployHash::new([1; 32]),
+            from,
+            maybe_to,
+            URef::new([5; 32], AccessRights::READ_ADD_WRITE),
+            URef::new([6; 32], AccessRights::READ_ADD_WRITE),
+            U512::from(2_500_000_000u64),
+            U512::zero(),
+            maybe_id,
+        )),
+    }])
+}
+
+#[tokio::test]
+async fn transfers_should_be_listed_by_account_and_block() {
+    let database = FakeDatabase::new();
+    let mut rng = TestRng::new();
+    let sender = AccountHash::new([3; 32]);
+    let deploy_processed = deploy_with_transfer(&mut rng, sender, None, None);
+    database
+        .save_deploy_processed(deploy_processed.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving DeployProcessed");
+
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let by_account_path = format!(
+        "/transfers/account/{}",
+        Key::Account(sender).to_formatted_string()
+    );
+    let by_account = request().path(&by_account_path).reply(&api).await;
+    assert!(by_account.status().is_success());
+    let transfers = serde_json::from_slice::<Vec<TransferEntry>>(by_account.body())
+        .expect("Error parsing TransferEntry list from response");
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0].transfer_hash, hex::encode([1; 32]));
+    assert_eq!(
+        transfers[0].deploy_hash,
+        deploy_processed.hex_encoded_hash()
+    );
+    assert_eq!(transfers[0].from, hex::encode(sender.value()));
+    assert_eq!(transfers[0].amount, U512::from(2_500_000_000u64));
+
+    let by_block_path = format!(
+        "/transfers/block/{}",
+        deploy_processed.hex_encoded_block_hash()
+    );
+    let by_block = request().path(&by_block_path).reply(&api).await;
+    assert!(by_block.status().is_success());
+    let block_transfers = serde_json::from_slice::<Vec<TransferEntry>>(by_block.body())
+        .expect("Error parsing TransferEntry list from response");
+    assert_eq!(block_transfers, transfers);
+}
+
+#[tokio::test]
+async fn transfers_of_unknown_block_should_be_empty() {
+    let api = filters::combined_filters(
+        FakeDatabase::new(),
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let request_path = format!("/transfers/block/{}", VALID_HASH);
+    let response = request().path(&request_path).reply(&api).await;
+    assert!(response.status().is_success());
+    assert_eq!(response.body().as_ref(), b"[]");
+}
+
+#[tokio::test]
+async fn transfers_should_be_listed_by_transfer_id() {
+    let database = FakeDatabase::new();
+    let mut rng = TestRng::new();
+    let deploy_processed = deploy_with_transfer(
+        &mut rng,
+        AccountHash::new([3; 32]),
+        Some(AccountHash::new([4; 32])),
+        Some(12345),
+    );
+    database
+        .save_deploy_processed(deploy_processed.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving DeployProcessed");
+
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let response = request()
+        .path("/transfers?transfer_id=12345")
+        .reply(&api)
+        .await;
+    assert!(response.status().is_success());
+    let transfers = serde_json::from_slice::<Vec<TransferEntry>>(response.body())
+        .expect("Error parsing TransferEntry list from response");
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(
+        transfers[0].deploy_hash,
+        deploy_processed.hex_encoded_hash()
+    );
+    assert_eq!(transfers[0].id, Some(12345));
+
+    let response = request()
+        .path("/transfers?transfer_id=54321")
+        .reply(&api)
+        .await;
+    assert!(response.status().is_success());
+    assert_eq!(response.body().as_ref(), b"[]");
+}
+
+#[tokio::test]
+async fn transfers_by_invalid_block_hash_should_return_400() {
+    should_respond_to_path_with(
+        format!("/transfers/block/{}", INVALID_HASH),
+        StatusCode::BAD_REQUEST,
+    )
+    .await
+}
+
+/// A transfer of `event_id` tokens of the contract, as its event `event_id`.
+fn token_transfer(contract_hash: &str, event_id: u64) -> TokenTransferEntry {
+    TokenTransferEntry {
+        contract_hash: contract_hash.to_string(),
+        event_id,
+        kind: TokenTransferKind::Transfer,
+        from: Some(Key::Account(AccountHash::new([3; 32])).to_formatted_string()),
+        to: Some(Key::Account(AccountHash::new([4; 32])).to_formatted_string()),
+        spender: None,
+        amount: U256::from(event_id),
+        deploy_hash: "05".repeat(32),
+        block_hash: "06".repeat(32),
+    }
+}
+
+#[tokio::test]
+async fn token_transfers_should_be_listed_by_token() {
+    let database = FakeDatabase::new();
+    let contract_hash = "01".repeat(32);
+    for event_id in [2, 1] {
+        database
+            .save_token_transfer(token_transfer(&contract_hash, event_id))
+            .await
+            .expect("Error saving TokenTransferEntry");
+    }
+
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let response = request()
+        .path(&format!(
+            "/tokens/{}/transfers",
+            contract_hash.to_uppercase()
+        ))
+        .reply(&api)
+        .await;
+    assert!(response.status().is_success());
+    let token_transfers = serde_json::from_slice::<Vec<TokenTransferEntry>>(response.body())
+        .expect("Error parsing TokenTransferEntry list from response");
+    let event_ids: Vec<u64> = token_transfers
+        .iter()
+        .map(|token_transfer| token_transfer.event_id)
+        .collect();
+    assert_eq!(event_ids, vec![1, 2]);
+
+    let response = request()
+        .path(&format!("/tokens/{}/transfers", "02".repeat(32)))
+        .reply(&api)
+        .await;
+    assert!(response.status().is_success());
+    assert_eq!(response.body().as_ref(), b"[]");
+}
+
+#[tokio::test]
+async fn token_transfers_of_invalid_contract_hash_should_return_400() {
+    should_respond_to_path_with(
+        format!("/tokens/{}/transfers", INVALID_HASH),
+        StatusCode::BAD_REQUEST,
+    )
+    .await
+}
+
+/// The mint of the token of the collection, as its event `event_id`.
+fn nft_mint(contract_hash: &str, event_id: u64, token_id: &str) -> NftEventEntry {
+    NftEventEntry {
+        contract_hash: contract_hash.to_string(),
+        event_id,
+        kind: NftEventKind::Mint,
+        token_id: token_id.to_string(),
+        owner: None,
+        recipient: Some(Key::Account(AccountHash::new([4; 32])).to_formatted_string()),
+        spender: None,
+        deploy_hash: "05".repeat(32),
+        block_hash: "06".repeat(32),
+    }
+}
+
+#[tokio::test]
+async fn nft_events_should_be_listed_by_collection_and_token() {
+    let database = FakeDatabase::new();
+    let contract_hash = "01".repeat(32);
+    for (event_id, token_id) in [(2, "7"), (1, "7"), (3, "8")] {
+        database
+            .save_nft_event(nft_mint(&contract_hash, event_id, token_id))
+            .await
+            .expect("Error saving NftEventEntry");
+    }
+
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let event_ids = |body: &[u8]| -> Vec<u64> {
+        serde_json::from_slice::<Vec<NftEventEntry>>(body)
+            .expect("Error parsing NftEventEntry list from response")
+            .iter()
+            .map(|nft_event| nft_event.event_id)
+            .collect()
+    };
+    let response = request()
+        .path(&format!("/nfts/{}/events", contract_hash.to_uppercase()))
+        .reply(&api)
+        .await;
+    assert!(response.status().is_success());
+    assert_eq!(event_ids(response.body()), vec![1, 2, 3]);
+
+    let response = request()
+        .path(&format!("/nfts/{}/tokens/7/events", contract_hash))
+        .reply(&api)
+        .await;
+    assert!(response.status().is_success());
+    assert_eq!(event_ids(response.body()), vec![1, 2]);
+
+    let response = request()
+        .path(&format!("/nfts/{}/tokens/9/events", contract_hash))
+        .reply(&api)
+        .await;
+    assert!(response.status().is_success());
+    assert_eq!(response.body().as_ref(), b"[]");
+}
+
+#[tokio::test]
+async fn nft_events_of_invalid_contract_hash_should_return_400() {
+    should_respond_to_path_with(
+        format!("/nfts/{}/tokens/1/events", INVALID_HASH),
+        StatusCode::BAD_REQUEST,
+    )
+    .await
+}
+
+#[tokio::test]
+async fn deploys_affecting_unknown_account_alias_should_return_400() {
+    should_respond_to_path_with(
+        "/account/@unknown/affected-by".to_string(),
+        StatusCode::BAD_REQUEST,
+    )
+    .await
+}
+
+#[tokio::test]
+async fn equivocators_should_flag_validators_ejected_from_next_era() {
+    let database = FakeDatabase::new();
+    let mut rng = TestRng::new();
+    let fault = Fault::new(
+        EraId::new(VALID_ERA),
+        PublicKey::random(&mut rng),
+        Timestamp::now(),
+    );
+    database
+        .save_fault(fault.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving Fault");
+    let switch_block = BlockAdded::random_switch_block(&mut rng, VALID_ERA, Vec::new());
+    database
+        .save_block_added(switch_block, 2, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving BlockAdded");
+
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let request_path = format!("/{}/{}?era={}", VALIDATORS, EQUIVOCATORS, VALID_ERA);
+
+    let response = request().path(&request_path).reply(&api).await;
+
+    assert!(response.status().is_success());
+
+    let body = response.into_body();
+    let equivocators = serde_json::from_slice::<Vec<Equivocator>>(&body)
+        .expect("Error parsing Equivocator list from response");
+
+    assert_eq!(
+        equivocators,
+        vec![Equivocator {
+            public_key: fault.public_key.to_hex(),
+            era_id: VALID_ERA,
+            fault_timestamp: fault.timestamp.to_string(),
+            ejected: Some(true),
+        }]
+    );
+}
+
+#[tokio::test]
+async fn equivocators_at_height_should_ignore_later_faults() {
+    let database = FakeDatabase::new();
+    let mut rng = TestRng::new();
+    let block_added = BlockAdded::random_with_height(&mut rng, 10);
+    let fault = Fault::new(
+        EraId::new(VALID_ERA),
+        PublicKey::random(&mut rng),
+        Timestamp::from(block_added.get_timestamp().millis() + 1),
+    );
+    database
+        .save_block_added(block_added, 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving BlockAdded");
+    database
+        .save_fault(fault, 2, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving Fault");
+
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let request_path = format!(
+        "/{}/{}?era={}&at_height=10",
+        VALIDATORS, EQUIVOCATORS, VALID_ERA
+    );
+
+    let response = request().path(&request_path).reply(&api).await;
+
+    assert!(response.status().is_success());
+
+    let body = response.into_body();
+    let equivocators = serde_json::from_slice::<Vec<Equivocator>>(&body)
+        .expect("Error parsing Equivocator list from response");
+
+    assert!(equivocators.is_empty());
+}
+
+#[tokio::test]
+async fn validator_set_should_return_the_validators_of_the_era_of_the_block_at_height() {
+    let database = FakeDatabase::new();
+    let mut rng = TestRng::new();
+    let validator = PublicKey::random(&mut rng);
+    let switch_block = BlockAdded::random_switch_block(&mut rng, 4, vec![validator.clone()]);
+    let block_of_next_era = BlockAdded::random_switch_block(&mut rng, 5, Vec::new());
+    for (event_id, block_added) in vec![switch_block, block_of_next_era.clone()]
+        .into_iter()
+        .enumerate()
+    {
+        database
+            .save_block_added(block_added, event_id as u32, "127.0.0.1".to_string())
+            .await
+            .expect("Error saving BlockAdded");
+    }
+
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let request_path = format!(
+        "/{}?at_height={}",
+        VALIDATORS,
+        block_of_next_era.get_height()
+    );
+
+    let response = request().path(&request_path).reply(&api).await;
+
+    assert!(response.status().is_success());
+
+    let body = response.into_body();
+    let validator_set =
+        serde_json::from_slice::<ValidatorSet>(&body).expect("Error parsing ValidatorSet");
+
+    assert_eq!(
+        validator_set,
+        ValidatorSet {
+            height: block_of_next_era.get_height(),
+            era_id: 5,
+            validators: vec![validator.to_hex()],
+        }
+    );
+}
+
+#[tokio::test]
+async fn validator_set_at_unknown_height_should_return_404() {
+    let request_path = format!("/{}?at_height=1", VALIDATORS);
+
+    should_respond_to_path_with(request_path, StatusCode::NOT_FOUND).await
+}
+
+#[tokio::test]
+async fn equivocators_without_era_should_return_400() {
+    let request_path = format!("/{}/{}", VALIDATORS, EQUIVOCATORS);
+
+    should_respond_to_path_with(request_path, StatusCode::BAD_REQUEST).await
+}
+
+#[tokio::test]
+async fn contract_stats_should_roll_up_deploys_calling_the_contract() {
+    let database = FakeDatabase::new();
+    let mut rng = TestRng::new();
+    let contract_hash = ContractHash::new([7; 32]);
+    let deploy_accepted = DeployAccepted::random_calling_contract(&mut rng, contract_hash);
+    let deploy_processed = DeployProcessed::random(&mut rng, Some(deploy_accepted.deploy_hash()))
+        .with_cost(U512::from(42))
+        .with_error_message("User error: 1");
+    database
+        .save_deploy_accepted(deploy_accepted, 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving DeployAccepted");
+    database
+        .save_deploy_processed(deploy_processed.clone(), 2, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving DeployProcessed");
+
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let request_path = format!("/{}/{}/stats", CONTRACT, hex::encode(contract_hash.value()));
+
+    let response = request().path(&request_path).reply(&api).await;
+
+    assert!(response.status().is_success());
+
+    let body = response.into_body();
+    let stats = serde_json::from_slice::<Vec<ContractDayStats>>(&body)
+        .expect("Error parsing ContractDayStats list from response");
+
+    assert_eq!(
+        stats,
+        vec![ContractDayStats {
+            day: deploy_processed.timestamp_day(),
+            successes: 0,
+            failures: 1,
+            gas_spent: 42,
+        }]
+    );
+}
+
+#[tokio::test]
+async fn contract_stats_of_unknown_contract_should_return_404() {
+    let request_path = format!("/{}/{}/stats", CONTRACT, VALID_HASH);
+
+    should_respond_to_path_with(request_path, StatusCode::NOT_FOUND).await
+}
+
+#[tokio::test]
+async fn contract_stats_by_invalid_hash_should_return_400() {
+    let request_path = format!("/{}/{}/stats", CONTRACT, INVALID_HASH);
+
+    should_respond_to_path_with(request_path, StatusCode::BAD_REQUEST).await
+}
+
+#[tokio::test]
+async fn deploy_size_stats_should_describe_accepted_deploys() {
+    let database = FakeDatabase::new();
+    let mut rng = TestRng::new();
+    let deploy_accepted = DeployAccepted::random(&mut rng);
+    database
+        .save_deploy_accepted(deploy_accepted.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving DeployAccepted");
+
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let response = request().path("/stats/deploys/size").reply(&api).await;
+
+    assert!(response.status().is_success());
+
+    let body = response.into_body();
+    let stats = serde_json::from_slice::<DeploySizeStats>(&body)
+        .expect("Error parsing DeploySizeStats from response");
+
+    let size_bytes = deploy_accepted.serialized_length() as u64;
+    assert_eq!(stats.deploys, 1);
+    assert_eq!(stats.size_bytes.min, size_bytes);
+    assert_eq!(stats.size_bytes.p99, size_bytes);
+    assert_eq!(stats.size_bytes.mean, size_bytes as f64);
+    assert_eq!(
+        stats.approvals.max,
+        deploy_accepted.approvals_count() as u64
+    );
+}
+
+#[tokio::test]
+async fn deploy_size_stats_without_deploys_should_return_404() {
+    should_respond_to_path_with("/stats/deploys/size".to_string(), StatusCode::NOT_FOUND).await
+}
+
+#[tokio::test]
+async fn deploys_by_invalid_time_should_return_400() {
+    let request_path = format!("/{}?from=yesterday&to=2024-05-02", DEPLOYS);
+
+    should_respond_to_path_with(request_path, StatusCode::BAD_REQUEST).await
+}
+
+#[tokio::test]
+async fn deploys_by_inverted_time_range_should_return_400() {
+    let request_path = format!("/{}?from=2024-05-02&to=2024-05-01", DEPLOYS);
+
+    should_respond_to_path_with(request_path, StatusCode::BAD_REQUEST).await
+}
+
+fn build_state_proofs(port: u16) -> StateProofs {
+    StateProofs::new(&StateProofConfig {
+        rpc_address: format!("http://127.0.0.1:{}/rpc", port),
+        cache_size: None,
+    })
+}
+
+/// Serves the proofs of the global state on a free port, counting the requests.
+fn serve_state_proofs(request_count: Arc<AtomicUsize>) -> u16 {
+    let rpc_route = warp::post()
+        .and(warp::path("rpc"))
+        .and(warp::body::json())
+        .map(move |body: serde_json::Value| {
+            request_count.fetch_add(1, Ordering::SeqCst);
+            assert_eq!(body["method"], "query_global_state");
+            warp::reply::json(&serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": body["id"],
+                "result": { "stored_value": { "DeployInfo": {} }, "merkle_proof": "01ab" },
+            }))
+        });
+    let port = portpicker::pick_unused_port().expect("Unable to pick a port");
+    tokio::spawn(warp::serve(rpc_route).bind(([127, 0, 0, 1], port)));
+    port
+}
+
+#[tokio::test]
+async fn state_proof_should_bundle_proof_with_stored_deploy() {
+    let request_count = Arc::new(AtomicUsize::new(0));
+    let port = serve_state_proofs(request_count.clone());
+
+    let database = FakeDatabase::new();
+    let mut test_rng = TestRng::new();
+    let deploy_accepted = DeployAccepted::random(&mut test_rng);
+    database
+        .save_deploy_accepted(deploy_accepted.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_accepted");
+
+    let api = filters::combined_filters(
+        database,
+        Some(build_state_proofs(port)),
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let request_path = format!(
+        "/{}/{}/deploy-{}/{}",
+        STATE,
+        VALID_HASH,
+        deploy_accepted.hex_encoded_hash(),
+        PROOF
+    );
+    for _ in 0..2 {
+        let response = request().path(&request_path).reply(&api).await;
+        assert!(response.status().is_success());
+
+        let state_proof = serde_json::from_slice::<serde_json::Value>(response.body())
+            .expect("Error parsing StateProof from response");
+        assert_eq!(state_proof["state_root_hash"], VALID_HASH);
+        assert_eq!(state_proof["proof"]["merkle_proof"], "01ab");
+        assert_eq!(
+            state_proof["deploy"]["deploy_hash"],
+            deploy_accepted.hex_encoded_hash()
+        );
+    }
+    assert_eq!(request_count.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn state_proof_when_node_is_unavailable_should_return_502() {
+    let port = portpicker::pick_unused_port().expect("Unable to pick a port");
+    let api = filters::combined_filters(
+        FakeDatabase::new(),
+        Some(build_state_proofs(port)),
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let request_path = format!("/{}/{}/era-{}/{}", STATE, VALID_HASH, VALID_ERA, PROOF);
+    let response = request().path(&request_path).reply(&api).await;
+
+    assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+}
+
+#[tokio::test]
+async fn state_proof_of_invalid_key_should_return_400() {
+    let port = portpicker::pick_unused_port().expect("Unable to pick a port");
+    let api = filters::combined_filters(
+        FakeDatabase::new(),
+        Some(build_state_proofs(port)),
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let request_path = format!("/{}/{}/{}/{}", STATE, VALID_HASH, INVALID_HASH, PROOF);
+    let response = request().path(&request_path).reply(&api).await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+/// Serves, on a free port, the account of the sender with a weight of 1, as many as the cosigner,
+/// and a deployment threshold of 2.
+fn serve_account(sender_account_hash: String, cosigner_account_hash: AccountHash) -> u16 {
+    let rpc_route = warp::post()
+        .and(warp::path("rpc"))
+        .and(warp::body::json())
+        .map(move |body: serde_json::Value| {
+            assert_eq!(body["method"], "query_global_state");
+            assert_eq!(
+                body["params"]["key"],
+                format!("account-hash-{}", sender_account_hash)
+            );
+            warp::reply::json(&serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": body["id"],
+                "result": {
+                    "stored_value": {
+                        "Account": {
+                            "associated_keys": [
+                                { "account_hash": body["params"]["key"], "weight": 1 },
+                                { "account_hash": cosigner_account_hash.to_formatted_string(), "weight": 1 }
+                            ],
+                            "action_thresholds": { "deployment": 2, "key_management": 2 }
+                        }
+                    },
+                    "merkle_proof": "01ab"
+                },
+            }))
+        });
+    let port = portpicker::pick_unused_port().expect("Unable to pick a port");
+    tokio::spawn(warp::serve(rpc_route).bind(([127, 0, 0, 1], port)));
+    port
+}
+
+/// Requests the approvals of the deploy, with the accounts served on `port`.
+async fn deploy_approvals(
+    database: &FakeDatabase,
+    port: u16,
+    deploy_accepted: &DeployAccepted,
+) -> serde_json::Value {
+    let api = filters::combined_filters(
+        database.clone(),
+        Some(build_state_proofs(port)),
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+    let request_path = format!(
+        "/{}/{}/{}",
+        DEPLOY,
+        deploy_accepted.hex_encoded_hash(),
+        APPROVALS
+    );
+
+    let response = request().path(&request_path).reply(&api).await;
+    assert!(response.status().is_success());
+    serde_json::from_slice::<serde_json::Value>(response.body())
+        .expect("Error parsing DeployApprovals from response")
+}
+
+#[tokio::test]
+async fn deploy_approvals_should_compare_signers_with_threshold() {
+    let mut test_rng = TestRng::new();
+    let deploy_accepted = DeployAccepted::random(&mut test_rng);
+    let cosigner = SecretKey::random(&mut test_rng);
+    let cosigner_account_hash = PublicKey::from(&cosigner).to_account_hash();
+    let sender_account_hash = deploy_accepted.hex_encoded_account_hash();
+    let port = serve_account(sender_account_hash, cosigner_account_hash);
+
+    let database = FakeDatabase::new();
+    database
+        .save_deploy_accepted(deploy_accepted.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_accepted");
+    let approvals = deploy_approvals(&database, port, &deploy_accepted).await;
+    assert_eq!(approvals["collected_weight"], 1);
+    assert_eq!(approvals["deployment_threshold"], 2);
+    assert_eq!(approvals["threshold_reached"], false);
+
+    database
+        .save_deploy_accepted(
+            deploy_accepted.with_approval(&cosigner),
+            2,
+            "127.0.0.1".to_string(),
+        )
+        .await
+        .expect("Error saving deploy_accepted");
+    let approvals = deploy_approvals(&database, port, &deploy_accepted).await;
+    assert_eq!(approvals["collected_weight"], 2);
+    assert_eq!(approvals["threshold_reached"], true);
+    assert_eq!(
+        approvals["approvals"][1]["signer"],
+        PublicKey::from(&cosigner).to_hex()
+    );
+    assert_eq!(approvals["approvals"][1]["added_later"], true);
+}
+
+#[tokio::test]
+async fn deploy_approvals_of_not_stored_should_return_404() {
+    let request_path = format!("/{}/{}/{}", DEPLOY, VALID_HASH, APPROVALS);
+
+    should_respond_to_path_with(request_path, StatusCode::NOT_FOUND).await
+}
+
+#[tokio::test]
+async fn state_proof_when_not_enabled_should_return_404() {
+    let request_path = format!("/{}/{}/era-{}/{}", STATE, VALID_HASH, VALID_ERA, PROOF);
+
+    should_respond_to_path_with(request_path, StatusCode::NOT_FOUND).await
+}
+
+#[tokio::test]
+async fn responses_should_name_their_schema_version() {
+    let api = filters::combined_filters(
+        FakeDatabase::new(),
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+    let request_path = format!("/{}", BLOCK);
+
+    let latest = request().path(&request_path).reply(&api).await;
+    let pinned = request()
+        .path(&request_path)
+        .header("Accept-Version", "1")
+        .reply(&api)
+        .await;
+
+    assert!(latest.status().is_success());
+    assert_eq!(latest.headers()["Schema-Version"], "2");
+    assert!(pinned.status().is_success());
+    assert_eq!(pinned.headers()["Schema-Version"], "1");
+}
+
+#[tokio::test]
+async fn request_for_unsupported_schema_version_should_return_406() {
+    let api = filters::combined_filters(
+        FakeDatabase::new(),
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+
+    let response = request()
+        .path(&format!("/{}", BLOCK))
+        .header("Accept-Version", "3")
+        .reply(&api)
+        .await;
+
+    assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+}
+
+#[tokio::test]
+async fn canonicalized_responses_should_have_sorted_keys() {
+    let json_response = warp::reply::with_header(
+        r#"{"b": 1, "a": {"d": 2.0, "c": [true]}}"#,
+        "content-type",
+        "application/json",
+    )
+    .into_response();
+    let ndjson_response = warp::reply::with_header(
+        "{\"b\":1,\"a\":2}\n{\"d\":3,\"c\":4}\n",
+        "content-type",
+        "application/x-ndjson",
+    )
+    .into_response();
+
+    let json_body = hyper::body::to_bytes(
+        super::canonicalize_response(json_response)
+            .await
+            .into_body(),
+    )
+    .await
+    .unwrap();
+    let ndjson_body = hyper::body::to_bytes(
+        super::canonicalize_response(ndjson_response)
+            .await
+            .into_body(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(json_body, r#"{"a":{"c":[true],"d":2},"b":1}"#);
+    assert_eq!(ndjson_body, "{\"a\":2,\"b\":1}\n{\"c\":4,\"d\":3}\n");
+}
+
+#[tokio::test]
+async fn deploy_status_should_follow_the_lifecycle_of_the_deploy() {
+    let mut test_rng = TestRng::new();
+    let deploy_accepted = DeployAccepted::random(&mut test_rng);
+    let deploy_processed =
+        DeployProcessed::random(&mut test_rng, Some(deploy_accepted.deploy_hash()))
+            .with_error_message("Out of gas");
+    let database = FakeDatabase::new();
+    let api = filters::combined_filters(
+        database.clone(),
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+    let request_path = format!(
+        "/{}/{}/{}",
+        DEPLOY,
+        deploy_accepted.hex_encoded_hash(),
+        STATUS
+    );
+
+    database
+        .save_deploy_accepted(deploy_accepted, 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_accepted");
+    let response = request().path(&request_path).reply(&api).await;
+    assert!(response.status().is_success());
+    let status = serde_json::from_slice::<serde_json::Value>(response.body())
+        .expect("Error parsing DeployStatus from response");
+    assert_eq!(status["state"], "pending");
+
+    database
+        .save_deploy_processed(deploy_processed, 2, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_processed");
+    let response = request().path(&request_path).reply(&api).await;
+    assert!(response.status().is_success());
+    let status = serde_json::from_slice::<serde_json::Value>(response.body())
+        .expect("Error parsing DeployStatus from response");
+    assert_eq!(status["state"], "executed_failure");
+    assert_eq!(status["transitions"][0]["state"], "pending");
+    assert_eq!(status["transitions"][1]["state"], "executed_failure");
+    assert!(status["transitions"][1]["timestamp"].is_string());
+}
+
+#[tokio::test]
+async fn await_deploy_should_respond_once_the_deploy_is_processed() {
+    let mut test_rng = TestRng::new();
+    let sse_data = SseData::random_deploy_processed(&mut test_rng);
+    let deploy_processed = deploy_processed_of(&sse_data).unwrap();
+    let database = FakeDatabase::new();
+    let deploy_outcomes = DeployOutcomes::default();
+    let api = filters::combined_filters(
+        database.clone(),
+        None,
+        AccountAliases::default(),
+        deploy_outcomes.clone(),
+    );
+    // The deploy is awaited before any of its events is stored.
+    let request_path = format!(
+        "/{}/{}/{}?timeout=10s",
+        DEPLOY,
+        deploy_processed.hex_encoded_hash(),
+        AWAIT
+    );
+
+    let response = tokio::spawn(async move { request().path(&request_path).reply(&api).await });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!response.is_finished());
+    database
+        .save_deploy_processed(deploy_processed, 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_processed");
+    deploy_outcomes.submit(&sse_data);
+
+    let response = tokio::time::timeout(Duration::from_secs(5), response)
+        .await
+        .expect("the deploy should no longer be awaited")
+        .unwrap();
+    assert!(response.status().is_success());
+    let status = serde_json::from_slice::<serde_json::Value>(response.body())
+        .expect("Error parsing DeployStatus from response");
+    assert_ne!(status["state"], "pending");
+    assert_eq!(status["transitions"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn await_deploy_should_respond_with_the_pending_deploy_once_the_timeout_elapses() {
+    let mut test_rng = TestRng::new();
+    let deploy_accepted = DeployAccepted::random(&mut test_rng);
+    let database = FakeDatabase::new();
+    let api = filters::combined_filters(
+        database.clone(),
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+    database
+        .save_deploy_accepted(deploy_accepted.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_accepted");
+    let request_path = format!(
+        "/{}/{}/{}?timeout=1s",
+        DEPLOY,
+        deploy_accepted.hex_encoded_hash(),
+        AWAIT
+    );
+
+    let response = request().path(&request_path).reply(&api).await;
+    assert!(response.status().is_success());
+    let status = serde_json::from_slice::<serde_json::Value>(response.body())
+        .expect("Error parsing DeployStatus from response");
+    assert_eq!(status["state"], "pending");
+
+    let request_path = format!("/{}/{}/{}?timeout=1s", DEPLOY, VALID_HASH, AWAIT);
+    should_respond_to_path_with(request_path, StatusCode::NOT_FOUND).await;
+}
+
+#[tokio::test]
+async fn await_deploy_with_invalid_timeout_should_return_400() {
+    for timeout in ["0s", "301s", "a minute"] {
+        let request_path = format!(
+            "/{}/{}/{}?timeout={}",
+            DEPLOY,
+            VALID_HASH,
+            AWAIT,
+            timeout.replace(' ', "%20")
+        );
+
+        should_respond_to_path_with(request_path, StatusCode::BAD_REQUEST).await;
+    }
+}
+
+#[test]
+fn await_deploy_requests_should_be_told_apart_from_other_queries() {
+    let await_path = format!("/{}/{}/{}", DEPLOY, VALID_HASH, AWAIT);
+    assert!(super::is_await_request(&await_path));
+    assert!(super::is_await_request(&format!("/testnet{}/", await_path)));
+    assert!(!super::is_await_request(&format!(
+        "/{}/{}",
+        DEPLOY, VALID_HASH
+    )));
+    assert!(!super::is_await_request(&format!(
+        "/{}/{}/status",
+        DEPLOY, VALID_HASH
+    )));
+    assert!(!super::is_await_request(&format!(
+        "/{}/{}",
+        AWAIT, VALID_HASH
+    )));
+}
+
+#[cfg(feature = "graphql")]
+async fn post_graphql(database: FakeDatabase, query: &str) -> serde_json::Value {
+    let api = filters::combined_filters(
+        database,
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+    let response = request()
+        .method("POST")
+        .path("/graphql")
+        .header("content-type", "application/json")
+        .body(serde_json::json!({ "query": query }).to_string())
+        .reply(&api)
+        .await;
+    assert!(response.status().is_success());
+    serde_json::from_slice(response.body()).expect("Error parsing the GraphQL response")
+}
+
+#[cfg(feature = "graphql")]
+#[tokio::test]
+async fn graphql_should_compose_blocks_deploys_and_faults() {
+    let database = FakeDatabase::new();
+    let identifiers = database
+        .populate_with_events()
+        .await
+        .expect("Error populating FakeDatabase");
+    let query = format!(
+        r#"{{
+            block(height: {}) {{ hash height deploys {{ hash }} }}
+            deploy(hash: "{}") {{ hash failed expired block {{ hash }} }}
+            faults(era: {}) {{ publicKey }}
+            step(era: {}) {{ eraId }}
+        }}"#,
+        identifiers.block_added_height,
+        identifiers.deploy_processed_hash,
+        identifiers.fault_era_id,
+        identifiers.step_era_id
+    );
+
+    let response = post_graphql(database, &query).await;
+
+    assert_eq!(response["errors"], serde_json::Value::Null);
+    let data = &response["data"];
+    assert_eq!(data["block"]["hash"], identifiers.block_added_hash);
+    assert_eq!(data["block"]["height"], identifiers.block_added_height);
+    assert_eq!(data["deploy"]["hash"], identifiers.deploy_processed_hash);
+    assert_eq!(data["deploy"]["expired"], false);
+    assert_eq!(data["deploy"]["block"], serde_json::Value::Null);
+    assert_eq!(data["faults"][0]["publicKey"], identifiers.fault_public_key);
+    assert_eq!(data["step"]["eraId"], identifiers.step_era_id);
+}
+
+#[cfg(feature = "graphql")]
+#[tokio::test]
+async fn graphql_should_reject_invalid_queries() {
+    let response = post_graphql(
+        FakeDatabase::new(),
+        "{ blocks(fromHeight: 0, toHeight: 1000) { hash } }",
+    )
+    .await;
+    assert_eq!(response["data"], serde_json::Value::Null);
+    assert!(response["errors"][0]["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("Expected a range of at most 100 blocks"));
+    let response = post_graphql(FakeDatabase::new(), r#"{ deploy(hash: "ab") { hash } }"#).await;
+    assert_eq!(response["data"]["deploy"], serde_json::Value::Null);
+
+    let api = filters::combined_filters(
+        FakeDatabase::new(),
+        None,
+        AccountAliases::default(),
+        DeployOutcomes::default(),
+    );
+    let response = request()
+        .method("POST")
+        .path("/graphql")
+        .header("content-type", "application/json")
+        .body("not json")
+        .reply(&api)
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[cfg(feature = "graphql")]
+#[tokio::test]
+async fn graphql_should_limit_the_reads_of_aliased_fields() {
+    let aliased_blocks = |count: usize| {
+        let fields: Vec<String> = (0..count)
+            .map(|index| format!("b{}: blocks(fromHeight: 0, toHeight: 99) {{ hash }}", index))
+            .collect();
+        format!("{{ {} }}", fields.join(" "))
+    };
+
+    let response = post_graphql(FakeDatabase::new(), &aliased_blocks(10)).await;
+    assert_eq!(response["errors"], serde_json::Value::Null);
+
+    let response = post_graphql(FakeDatabase::new(), &aliased_blocks(11)).await;
+    assert_eq!(response["data"], serde_json::Value::Null);
+    assert_eq!(
+        response["errors"][0]["message"],
+        "Expected a query reading at most 1000 stored events"
+    );
+
+    let response = post_graphql(FakeDatabase::new(), &aliased_blocks(21)).await;
+    assert_eq!(response["data"], serde_json::Value::Null);
+    assert_eq!(response["errors"][0]["message"], "Query is too complex.");
+}
+
+#[cfg(feature = "graphql")]
+#[tokio::test]
+async fn graphql_should_count_nested_fields_once_per_block() {
+    let response = post_graphql(
+        FakeDatabase::new(),
+        "{ blocks(fromHeight: 0, toHeight: 99) {
+            hash height eraId timestamp isSwitchBlock raw
+            deploys { hash account blockHash failed errorMessage expired accepted processed
+                block { hash deploys { hash block { hash } } } }
+        } }",
+    )
+    .await;
+
+    assert_eq!(response["data"], serde_json::Value::Null);
+    assert_eq!(response["errors"][0]["message"], "Query is too complex.");
+}
@@ -0,0 +1,50 @@
+//! A minimal web page served at `/ui`, for operators to check at a glance that the Sidecar stores
+//! and streams events.
+//!
+//! The page is embedded in the binary. It lists the recent blocks read from the REST server, the
+//! deploys processed since it was opened and the activity of the event stream, linking to the
+//! JSON of the blocks and deploys as served by the REST server.
+
+use warp::{Filter, Rejection, Reply};
+
+/// The page, in which the port of the event stream server is substituted for the placeholder.
+const PAGE: &str = include_str!("ui/index.html");
+
+const EVENT_STREAM_PORT_PLACEHOLDER: &str = "{{EVENT_STREAM_PORT}}";
+
+/// Return the web page browsing the events.
+/// Input: the port of the event stream server, which the page subscribes to.
+/// Return: the HTML page.
+/// Path URL: ui
+/// Example: curl http://127.0.0.1:18888/ui
+pub(super) fn ui(
+    event_stream_port: u16,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let page = PAGE.replace(
+        EVENT_STREAM_PORT_PLACEHOLDER,
+        &event_stream_port.to_string(),
+    );
+    warp::path!("ui")
+        .and(warp::get())
+        .map(move || warp::reply::html(page.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use warp::http::{header::CONTENT_TYPE, StatusCode};
+
+    #[tokio::test]
+    async fn should_serve_the_page_with_the_event_stream_port() {
+        let response = warp::test::request().path("/ui").reply(&ui(19999)).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()[CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("text/html"));
+        let body = String::from_utf8(response.body().to_vec()).unwrap();
+        assert!(body.contains(r#"<meta name="event-stream-port" content="19999">"#));
+        assert!(!body.contains(EVENT_STREAM_PORT_PLACEHOLDER));
+    }
+}
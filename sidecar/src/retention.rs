@@ -0,0 +1,144 @@
+//! Pruning of the stored events which fall outside their configured retention.
+//!
+//! A background task periodically deletes, for every event type with a retention configured, the
+//! events stored more than the maximum age ago and those beyond the maximum number of events,
+//! oldest first. Only the payloads of the events are deleted: their event log entries are kept, so
+//! that the ids of the outbound event stream keep increasing without gaps being reused.
+
+use crate::{
+    operational_events::{OperationalEventKind, OperationalEvents},
+    types::{
+        config::{RetentionConfig, TableRetentionConfig},
+        database::{DatabaseWriter, PayloadTable},
+    },
+};
+use casper_event_types::metrics::PRUNED_EVENTS;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+const MILLISECONDS_PER_DAY: u64 = 24 * 60 * 60 * 1000;
+
+/// The retention of every table which has one configured.
+fn retentions(config: &RetentionConfig) -> Vec<(PayloadTable, TableRetentionConfig)> {
+    [
+        (PayloadTable::BlockAdded, config.block_added),
+        (PayloadTable::DeployAccepted, config.deploy_accepted),
+        (PayloadTable::DeployProcessed, config.deploy_processed),
+        (PayloadTable::DeployExpired, config.deploy_expired),
+        (PayloadTable::Fault, config.fault),
+        (PayloadTable::FinalitySignature, config.finality_signature),
+        (PayloadTable::Step, config.step),
+    ]
+    .into_iter()
+    .filter_map(|(table, maybe_retention)| maybe_retention.map(|retention| (table, retention)))
+    .collect()
+}
+
+/// The time, in milliseconds since the UNIX epoch, before which the events stored are older than
+/// `max_age_days` at `now`.
+fn stored_before(now: u64, max_age_days: u64) -> u64 {
+    now.saturating_sub(max_age_days.saturating_mul(MILLISECONDS_PER_DAY))
+}
+
+/// Describes the events deleted by a pruning run, e.g. `pruned 3 BlockAdded, 12 Step events`.
+fn describe_pruning(pruned: &[(PayloadTable, u64)]) -> String {
+    let counts: Vec<String> = pruned
+        .iter()
+        .map(|(table, count)| format!("{} {}", count, table.table_name()))
+        .collect();
+    format!("pruned {} events", counts.join(", "))
+}
+
+/// Spawns the task pruning the events of the given database according to `config`. Runs which
+/// deleted events are recorded in `operational_events`.
+pub(crate) fn start_retention<Db: DatabaseWriter + Send + Sync + 'static>(
+    config: RetentionConfig,
+    database: Db,
+    operational_events: OperationalEvents,
+) {
+    let interval = Duration::from_secs(config.prune_interval_in_seconds());
+    let retentions = retentions(&config);
+    tokio::spawn(async move {
+        loop {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("Time went backwards")
+                .as_millis() as u64;
+            let mut pruned_per_table = Vec::new();
+            for (table, retention) in &retentions {
+                let maybe_stored_before = retention
+                    .max_age_days
+                    .map(|max_age_days| stored_before(now, max_age_days));
+                match database
+                    .prune_events(*table, maybe_stored_before, retention.max_rows)
+                    .await
+                {
+                    Ok(0) => {}
+                    Ok(pruned) => {
+                        info!("Pruned {} {} events", pruned, table.table_name());
+                        PRUNED_EVENTS
+                            .with_label_values(&[table.table_name()])
+                            .inc_by(pruned);
+                        pruned_per_table.push((*table, pruned));
+                    }
+                    Err(error) => {
+                        warn!(?error, "Error pruning {} events", table.table_name());
+                    }
+                }
+            }
+            if !pruned_per_table.is_empty() {
+                operational_events.record(
+                    OperationalEventKind::PruningRun,
+                    Some(describe_pruning(&pruned_per_table)),
+                );
+            }
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_only_prune_tables_with_a_retention() {
+        let retention = TableRetentionConfig {
+            max_age_days: Some(30),
+            max_rows: None,
+        };
+        let config = RetentionConfig {
+            deploy_processed: Some(retention),
+            ..RetentionConfig::default()
+        };
+
+        assert_eq!(
+            retentions(&config),
+            vec![(PayloadTable::DeployProcessed, retention)]
+        );
+        assert!(retentions(&RetentionConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn should_compute_time_before_which_events_are_pruned() {
+        let now = 100 * MILLISECONDS_PER_DAY + 1234;
+
+        assert_eq!(stored_before(now, 30), 70 * MILLISECONDS_PER_DAY + 1234);
+        assert_eq!(stored_before(now, 0), now);
+        assert_eq!(stored_before(now, u64::MAX), 0);
+    }
+
+    #[test]
+    fn should_describe_events_pruned_per_table() {
+        let pruned = [(PayloadTable::BlockAdded, 3), (PayloadTable::Step, 12)];
+
+        assert_eq!(
+            describe_pruning(&pruned),
+            format!(
+                "pruned 3 {}, 12 {} events",
+                PayloadTable::BlockAdded.table_name(),
+                PayloadTable::Step.table_name()
+            )
+        );
+    }
+}
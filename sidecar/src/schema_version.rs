@@ -0,0 +1,87 @@
+//! Versioning of the payloads the sidecar defines itself, i.e. the responses of the REST server and
+//! the envelopes the event stream server wraps events in. The events forwarded as received from the
+//! nodes aren't versioned here, their format follows the API version of the node.
+//!
+//! Clients pin a schema version with the `Accept-Version` request header, and are served the latest
+//! one otherwise, so that the payloads can evolve without breaking the deployed consumers which
+//! pinned the version they were written against. Responses name their version in the
+//! `Schema-Version` header.
+//!
+//! * `1`: frames of batched events are JSON arrays, status events and warnings are sent as is.
+//! * `2`: frames of batched events, status events and warnings are JSON objects holding their
+//!   `schema_version`. The responses of the REST server are the same as in version `1`.
+
+use serde::Serialize;
+
+/// A version of the schema of the sidecar's payloads.
+pub(crate) type SchemaVersion = u32;
+
+/// The request header with which clients pin a schema version.
+pub(crate) const ACCEPT_VERSION_HEADER: &str = "accept-version";
+/// The response header naming the schema version of the response.
+pub(crate) const SCHEMA_VERSION_HEADER: &str = "schema-version";
+/// The oldest supported schema version.
+pub(crate) const OLDEST_SCHEMA_VERSION: SchemaVersion = 1;
+/// The latest schema version, served to the clients which don't pin one.
+pub(crate) const CURRENT_SCHEMA_VERSION: SchemaVersion = 2;
+
+/// Returns the schema version to serve a client which sent the given `Accept-Version` header, or
+/// the reason why the version it asks for can't be served.
+pub(crate) fn negotiate(maybe_accept_version: Option<&str>) -> Result<SchemaVersion, String> {
+    let accept_version = match maybe_accept_version {
+        Some(accept_version) => accept_version.trim(),
+        None => return Ok(CURRENT_SCHEMA_VERSION),
+    };
+    accept_version
+        .parse::<SchemaVersion>()
+        .ok()
+        .filter(|version| (OLDEST_SCHEMA_VERSION..=CURRENT_SCHEMA_VERSION).contains(version))
+        .ok_or_else(|| {
+            format!(
+                "unsupported schema version '{}': expected {} to {}",
+                accept_version, OLDEST_SCHEMA_VERSION, CURRENT_SCHEMA_VERSION
+            )
+        })
+}
+
+/// Whether the envelopes of `schema_version` hold their schema version.
+pub(crate) fn is_enveloped(schema_version: SchemaVersion) -> bool {
+    schema_version >= 2
+}
+
+/// An envelope of `schema_version` holding `payload`, whose fields it is serialized with.
+#[derive(Serialize)]
+pub(crate) struct Envelope<'a, T: Serialize> {
+    pub(crate) schema_version: SchemaVersion,
+    #[serde(flatten)]
+    pub(crate) payload: &'a T,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn should_serve_the_latest_version_unless_pinned() {
+        assert_eq!(negotiate(None), Ok(CURRENT_SCHEMA_VERSION));
+        assert_eq!(negotiate(Some("1")), Ok(1));
+        assert_eq!(negotiate(Some(" 2 ")), Ok(2));
+        assert!(negotiate(Some("0")).is_err());
+        assert!(negotiate(Some("3")).is_err());
+        assert!(negotiate(Some("latest")).is_err());
+    }
+
+    #[test]
+    fn should_serialize_the_fields_of_the_payload_alongside_the_version() {
+        let envelope = Envelope {
+            schema_version: 2,
+            payload: &json!({ "reason": "lagged" }),
+        };
+
+        assert_eq!(
+            serde_json::to_value(envelope).unwrap(),
+            json!({ "schema_version": 2, "reason": "lagged" })
+        );
+    }
+}
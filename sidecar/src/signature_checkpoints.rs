@@ -0,0 +1,185 @@
+//! Checkpointing of the stored finality signatures.
+//!
+//! Finality signatures make up most of the storage of the Sidecar, while only the validators which
+//! signed a block and their weight are needed to tell whether it is finalized. A background task
+//! periodically replaces the signatures of the blocks whose signatures were all stored before the
+//! configured window with a checkpoint holding the bitset of the validators of the era which signed
+//! the block and their weight, so that `/block/<hash>/finality_signatures` keeps serving them.
+//!
+//! The signers are encoded against the validators of the era, so the signatures of the blocks of
+//! eras whose validator weights aren't known are kept as they are. Signatures of a block received
+//! after it was checkpointed are merged into its checkpoint by a later run.
+
+use crate::{
+    block_signatures::checkpoint_block_signatures,
+    types::{
+        config::FinalitySignatureCheckpointsConfig,
+        database::{DatabaseReader, DatabaseWriter},
+    },
+};
+use casper_event_types::metrics::CHECKPOINTED_BLOCKS;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+const MILLISECONDS_PER_HOUR: u64 = 60 * 60 * 1000;
+/// The number of blocks read from the database at once.
+const BLOCKS_PAGE_SIZE: u32 = 1000;
+
+/// The time, in milliseconds since the UNIX epoch, before which the signatures stored are outside
+/// the window of `full_window_in_hours` at `now`.
+fn stored_before(now: u64, full_window_in_hours: u64) -> u64 {
+    now.saturating_sub(full_window_in_hours.saturating_mul(MILLISECONDS_PER_HOUR))
+}
+
+/// Checkpoints the signatures of all the blocks signed before `stored_before`, returning the number
+/// of blocks checkpointed and of signatures deleted.
+async fn checkpoint_signatures<Db: DatabaseReader + DatabaseWriter + Sync>(
+    database: &Db,
+    stored_before: u64,
+) -> (u64, u64) {
+    let mut checkpointed_blocks = 0;
+    let mut deleted_signatures = 0;
+    let mut maybe_after = None;
+    loop {
+        let block_hashes = match database
+            .get_blocks_to_checkpoint(stored_before, maybe_after.clone(), BLOCKS_PAGE_SIZE)
+            .await
+        {
+            Ok(block_hashes) => block_hashes,
+            Err(error) => {
+                warn!(?error, "Error getting the blocks to checkpoint");
+                break;
+            }
+        };
+        for block_hash in &block_hashes {
+            if let Some(deleted) = checkpoint_block(database, block_hash).await {
+                checkpointed_blocks += 1;
+                deleted_signatures += deleted;
+            }
+        }
+        if block_hashes.len() < BLOCKS_PAGE_SIZE as usize {
+            break;
+        }
+        maybe_after = block_hashes.last().cloned();
+    }
+    (checkpointed_blocks, deleted_signatures)
+}
+
+/// Checkpoints the signatures of the block, returning the number of signatures deleted, or `None`
+/// if the block wasn't checkpointed.
+async fn checkpoint_block<Db: DatabaseReader + DatabaseWriter + Sync>(
+    database: &Db,
+    block_hash: &str,
+) -> Option<u64> {
+    let checkpoint = match checkpoint_block_signatures(database, block_hash).await {
+        Ok(maybe_checkpoint) => maybe_checkpoint?,
+        Err(error) => {
+            warn!(?error, %block_hash, "Error aggregating finality signatures");
+            return None;
+        }
+    };
+    match database
+        .save_finality_signature_checkpoint(checkpoint)
+        .await
+    {
+        Ok(deleted) => {
+            CHECKPOINTED_BLOCKS.inc();
+            Some(deleted)
+        }
+        Err(error) => {
+            warn!(?error, %block_hash, "Error saving finality signature checkpoint");
+            None
+        }
+    }
+}
+
+/// Spawns the task checkpointing the finality signatures of the given database according to
+/// `config`.
+pub(crate) fn start_signature_checkpoints<
+    Db: DatabaseReader + DatabaseWriter + Send + Sync + 'static,
+>(
+    config: FinalitySignatureCheckpointsConfig,
+    database: Db,
+) {
+    let interval = Duration::from_secs(config.checkpoint_interval_in_seconds());
+    let full_window_in_hours = config.full_window_in_hours();
+    tokio::spawn(async move {
+        loop {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("Time went backwards")
+                .as_millis() as u64;
+            let (checkpointed_blocks, deleted_signatures) =
+                checkpoint_signatures(&database, stored_before(now, full_window_in_hours)).await;
+            if checkpointed_blocks > 0 {
+                info!(
+                    "Checkpointed the finality signatures of {} blocks, deleting {} signatures",
+                    checkpointed_blocks, deleted_signatures
+                );
+            }
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        block_signatures::{get_block_finality_signatures, tests::random_signature},
+        testing::fake_database::FakeDatabase,
+        types::sse_events::*,
+    };
+    use casper_types::testing::TestRng;
+
+    #[test]
+    fn should_compute_time_before_which_signatures_are_checkpointed() {
+        let now = 100 * MILLISECONDS_PER_HOUR + 1234;
+
+        assert_eq!(stored_before(now, 24), 76 * MILLISECONDS_PER_HOUR + 1234);
+        assert_eq!(stored_before(now, 0), now);
+        assert_eq!(stored_before(now, u64::MAX), 0);
+    }
+
+    #[tokio::test]
+    async fn should_only_checkpoint_blocks_of_eras_with_known_weights() {
+        let mut rng = TestRng::new();
+        let database = FakeDatabase::new();
+        let known = random_signature(&mut rng, 12);
+        let unknown = random_signature(&mut rng, 20);
+        for (event_id, finality_signature) in
+            [known.clone(), unknown.clone()].into_iter().enumerate()
+        {
+            database
+                .save_finality_signature(
+                    finality_signature,
+                    event_id as u32,
+                    "127.0.0.1".to_string(),
+                )
+                .await
+                .expect("Error saving finality_signature");
+        }
+        let switch_block =
+            BlockAdded::random_switch_block(&mut rng, 11, vec![known.inner().public_key().clone()]);
+        database
+            .save_block_added(switch_block, 3, "127.0.0.1".to_string())
+            .await
+            .expect("Error saving block_added");
+
+        let (checkpointed_blocks, deleted_signatures) =
+            checkpoint_signatures(&database, u64::MAX).await;
+
+        assert_eq!((checkpointed_blocks, deleted_signatures), (1, 1));
+        let checkpointed =
+            get_block_finality_signatures(&database, &known.hex_encoded_block_hash())
+                .await
+                .unwrap();
+        assert!(checkpointed.checkpointed);
+        assert_eq!(checkpointed.signers.len(), 1);
+        let kept = get_block_finality_signatures(&database, &unknown.hex_encoded_block_hash())
+            .await
+            .unwrap();
+        assert!(!kept.checkpointed);
+        assert_eq!(kept.signatures.len(), 1);
+    }
+}
@@ -0,0 +1,667 @@
+//! Replay of recorded upstream traffic.
+//!
+//! `simulate` reads a capture (see [casper_event_types::capture]) and feeds every frame through
+//! the same processing as events received from a live node: storage and the outbound event
+//! stream. Frames are replayed with the delays between them as recorded, divided by the requested
+//! speed. Once the capture is exhausted the storage is checked to contain every replayed event.
+//!
+//! A replay can also produce a canonical dump of the frames sent to the outbound event stream and
+//! of the stored payloads. Replaying the same capture gives the same dump, so comparing the dumps
+//! of two versions reveals any change in how events are transformed. The golden test of this
+//! module guards the dump of the capture in `src/testing/golden` against accidental changes.
+
+use crate::{
+    api_version_manager::ApiVersionManager,
+    build_database, build_watch_list, event_index_store,
+    event_stream_server::sse_data_to_json,
+    health::Health,
+    source_scoring::SourceScores,
+    spawn_sse_processor, start_event_broadcasting,
+    types::{
+        config::Config,
+        database::{Database, DatabaseReadError, DatabaseReader, PayloadTable},
+    },
+    watch_list::WatchList,
+    BroadcastConsumers, EventProcessor, EventStreamServerContext, IngestionChecks,
+    StorageAcknowledgements, DEFAULT_CHANNEL_SIZE,
+};
+use anyhow::{Context, Error};
+use casper_event_listener::SseEvent;
+use casper_event_types::{
+    capture::CapturedFrame,
+    sse_data::{deserialize, SseData},
+    Filter,
+};
+use reqwest::Url;
+use serde_json::{json, Map, Value};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::{Display, Formatter},
+    path::Path,
+    str::FromStr,
+    time::Duration,
+};
+use tokio::{
+    fs::File,
+    io::{AsyncBufReadExt, BufReader},
+    sync::mpsc::{channel as mpsc_channel, Receiver, Sender},
+    task::JoinHandle,
+    time::{sleep, sleep_until, Instant},
+};
+use tracing::{info, warn};
+
+/// How long the validation waits for events which are still being saved.
+const VALIDATION_GRACE_PERIOD: Duration = Duration::from_secs(10);
+const VALIDATION_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+/// Number of stored payloads read at once when dumping the storage.
+const DUMP_PAGE_SIZE: u32 = 1000;
+
+type OutboundFrame = (SseData, Option<Filter>, Option<String>);
+
+/// Speed at which a capture is replayed relative to the recording.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Speed {
+    /// Delays between frames are divided by the given factor.
+    Factor(f64),
+    /// Frames are replayed without any delays.
+    Max,
+}
+
+impl Speed {
+    fn scale(&self, recorded_delay: Duration) -> Option<Duration> {
+        match self {
+            Speed::Factor(factor) => Some(recorded_delay.div_f64(*factor)),
+            Speed::Max => None,
+        }
+    }
+}
+
+impl FromStr for Speed {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if value.eq_ignore_ascii_case("max") {
+            return Ok(Speed::Max);
+        }
+        let factor = value
+            .strip_suffix(['x', 'X'])
+            .unwrap_or(value)
+            .parse::<f64>()
+            .map_err(|_| format!("invalid speed '{}', expected e.g. '10x' or 'max'", value))?;
+        if !factor.is_finite() || factor <= 0.0 {
+            return Err(format!("speed must be greater than 0, got '{}'", value));
+        }
+        Ok(Speed::Factor(factor))
+    }
+}
+
+/// Something replayed which is expected to be retrievable from storage afterwards.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+enum StoredEntity {
+    Block(String),
+    DeployAccepted(String),
+    DeployProcessed(String),
+    DeployExpired(String),
+    Faults(u64),
+    FinalitySignatures(String),
+    Step(u64),
+}
+
+impl StoredEntity {
+    fn from_sse_data(sse_data: &SseData) -> Option<Self> {
+        match sse_data {
+            SseData::BlockAdded { block_hash, .. } => {
+                Some(StoredEntity::Block(hex::encode(block_hash.inner())))
+            }
+            SseData::DeployAccepted { deploy } => Some(StoredEntity::DeployAccepted(hex::encode(
+                deploy.hash().inner(),
+            ))),
+            SseData::DeployProcessed { deploy_hash, .. } => Some(StoredEntity::DeployProcessed(
+                hex::encode(deploy_hash.inner()),
+            )),
+            SseData::DeployExpired { deploy_hash } => Some(StoredEntity::DeployExpired(
+                hex::encode(deploy_hash.inner()),
+            )),
+            SseData::Fault { era_id, .. } => Some(StoredEntity::Faults(era_id.value())),
+            SseData::FinalitySignature(finality_signature) => {
+                Some(StoredEntity::FinalitySignatures(hex::encode(
+                    finality_signature.block_hash().inner(),
+                )))
+            }
+            SseData::Step { era_id, .. } => Some(StoredEntity::Step(era_id.value())),
+            SseData::ApiVersion(_)
+            | SseData::SidecarVersion(_)
+            | SseData::Shutdown
+            | SseData::Unknown { .. }
+            | SseData::Derived { .. } => None,
+        }
+    }
+
+    async fn is_stored<Db: DatabaseReader>(&self, database: &Db) -> Result<bool, Error> {
+        let result = match self {
+            StoredEntity::Block(hash) => database.get_block_by_hash(hash).await.map(|_| ()),
+            StoredEntity::DeployAccepted(hash) => {
+                database.get_deploy_accepted_by_hash(hash).await.map(|_| ())
+            }
+            StoredEntity::DeployProcessed(hash) => database
+                .get_deploy_processed_by_hash(hash)
+                .await
+                .map(|_| ()),
+            StoredEntity::DeployExpired(hash) => {
+                database.get_deploy_expired_by_hash(hash).await.map(|_| ())
+            }
+            StoredEntity::Faults(era) => database.get_faults_by_era(*era).await.map(|_| ()),
+            StoredEntity::FinalitySignatures(block_hash) => database
+                .get_finality_signatures_by_block(block_hash)
+                .await
+                .map(|_| ()),
+            StoredEntity::Step(era) => database.get_step_by_era(*era).await.map(|_| ()),
+        };
+        match result {
+            Ok(()) => Ok(true),
+            Err(DatabaseReadError::NotFound) => Ok(false),
+            Err(error) => Err(Error::msg(format!(
+                "Error validating {:?}: {:?}",
+                self, error
+            ))),
+        }
+    }
+}
+
+/// Summary of a replayed capture.
+#[derive(Debug, Default)]
+struct SimulationReport {
+    frames: u64,
+    malformed_frames: u64,
+    events_by_type: BTreeMap<&'static str, u64>,
+    elapsed: Duration,
+    missing: Vec<String>,
+}
+
+impl Display for SimulationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let rate = self.frames as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON);
+        write!(
+            f,
+            "replayed {} frame(s) in {:.2?} ({:.0} frames/s), {} malformed, by type: {:?}, {} missing from storage",
+            self.frames,
+            self.elapsed,
+            rate,
+            self.malformed_frames,
+            self.events_by_type,
+            self.missing.len()
+        )
+    }
+}
+
+/// Replays the capture at `capture_path` through storage and the outbound event stream
+/// configured in `config`, then validates the storage contents.
+///
+/// The configured node connections and the REST server aren't used. The storage should point to
+/// a scratch location, as the replayed events are saved like any other. If `maybe_dump_path` is
+/// given, the canonical dump of the replay is written to it.
+pub(crate) async fn run_simulation(
+    config: Config,
+    capture_path: &Path,
+    speed: Speed,
+    maybe_dump_path: Option<&Path>,
+) -> Result<(), Error> {
+    let database = build_database(&config.storage).await?;
+    let pipeline = start_pipeline(&config, &database, maybe_dump_path.is_some())?;
+
+    let started = Instant::now();
+    let (mut report, expected) = replay(capture_path, speed, pipeline.sse_event_sender).await?;
+    pipeline
+        .processor_handle
+        .await
+        .context("Error joining the event processor")??;
+    let outbound_frames = pipeline
+        .outbound_frames_handle
+        .await
+        .context("Error joining the outbound frames forwarder")?;
+    report.elapsed = started.elapsed();
+    report.missing = match &database {
+        Database::SqliteDatabaseWrapper(db) => find_missing(db, expected).await?,
+        #[cfg(feature = "postgres")]
+        Database::PostgreSqlDatabaseWrapper(db) => find_missing(db, expected).await?,
+    };
+    if let Some(dump_path) = maybe_dump_path {
+        write_dump(&database, outbound_frames, dump_path).await?;
+    }
+
+    info!("Simulation finished: {}", report);
+    if report.missing.is_empty() {
+        Ok(())
+    } else {
+        for missing in &report.missing {
+            warn!("Not found in storage: {}", missing);
+        }
+        Err(Error::msg(format!(
+            "Simulation failed: {} replayed event(s) missing from storage",
+            report.missing.len()
+        )))
+    }
+}
+
+/// The processing the replayed events are fed to.
+struct Pipeline {
+    sse_event_sender: Sender<SseEvent>,
+    processor_handle: JoinHandle<Result<(), Error>>,
+    outbound_frames_handle: JoinHandle<Vec<Value>>,
+}
+
+/// Starts the event processor and the outbound event stream, as for the events received from a
+/// node. If `record` is set, the frames sent to the outbound event stream are recorded.
+fn start_pipeline(config: &Config, database: &Database, record: bool) -> Result<Pipeline, Error> {
+    let watch_list = build_watch_list(config)?;
+    let outbound_channel_size = config.outbound_channel_size.unwrap_or(DEFAULT_CHANNEL_SIZE);
+    let (outbound_sse_data_sender, processed_sse_data_receiver) =
+        mpsc_channel(outbound_channel_size);
+    let (forwarded_sse_data_sender, outbound_sse_data_receiver) =
+        mpsc_channel(outbound_channel_size);
+    let outbound_frames_handle = forward_outbound_frames(
+        processed_sse_data_receiver,
+        forwarded_sse_data_sender,
+        record,
+    );
+    let context = EventStreamServerContext {
+        maybe_audit_log: None,
+        watch_list: watch_list.clone(),
+        maybe_api_keys: None,
+        event_index_store: event_index_store(database),
+        health: Health::new(),
+        maybe_network_name: None,
+        maybe_network: None,
+        maybe_sse_routes_sender: None,
+    };
+    let _event_broadcasting_handle = start_event_broadcasting(
+        config,
+        outbound_sse_data_receiver,
+        BroadcastConsumers::default(),
+        context,
+    );
+    let (sse_event_sender, processor_handle) =
+        start_processor(config, database, watch_list, outbound_sse_data_sender);
+    Ok(Pipeline {
+        sse_event_sender,
+        processor_handle,
+        outbound_frames_handle,
+    })
+}
+
+/// Starts the event processor, returning the sender of the events to process.
+fn start_processor(
+    config: &Config,
+    database: &Database,
+    watch_list: WatchList,
+    outbound_sse_data_sender: Sender<OutboundFrame>,
+) -> (Sender<SseEvent>, JoinHandle<Result<(), Error>>) {
+    let (sse_event_sender, sse_event_receiver) =
+        mpsc_channel(config.inbound_channel_size.unwrap_or(DEFAULT_CHANNEL_SIZE));
+    let processor = EventProcessor {
+        database: database.clone(),
+        outbound_sse_data_sender,
+        enable_event_logging: false,
+        ingestion_checks: IngestionChecks::from(config),
+        watch_list,
+        api_version_manager: ApiVersionManager::new(),
+        acknowledgements: StorageAcknowledgements::default(),
+    };
+    // The replayed events don't come from any of the configured connections, so aren't scored.
+    let processor_handle = spawn_sse_processor(
+        processor,
+        sse_event_receiver,
+        SourceScores::default().recorder(0),
+    );
+    (sse_event_sender, processor_handle)
+}
+
+/// Writes the canonical dump of the outbound frames and of the stored payloads to `dump_path`.
+async fn write_dump(
+    database: &Database,
+    outbound_frames: Vec<Value>,
+    dump_path: &Path,
+) -> Result<(), Error> {
+    let dump = match database {
+        Database::SqliteDatabaseWrapper(db) => canonical_dump(db, outbound_frames).await?,
+        #[cfg(feature = "postgres")]
+        Database::PostgreSqlDatabaseWrapper(db) => canonical_dump(db, outbound_frames).await?,
+    };
+    tokio::fs::write(dump_path, dump)
+        .await
+        .with_context(|| format!("Error writing dump {}", dump_path.display()))?;
+    info!("Dump of the replay written to {}", dump_path.display());
+    Ok(())
+}
+
+/// Sends every frame of the capture to `sse_event_sender`, keeping the recorded (scaled) delays
+/// between them. Returns the replay statistics and everything which should end up in storage.
+async fn replay(
+    capture_path: &Path,
+    speed: Speed,
+    sse_event_sender: Sender<SseEvent>,
+) -> Result<(SimulationReport, HashSet<StoredEntity>), Error> {
+    let capture = File::open(capture_path)
+        .await
+        .with_context(|| format!("Error opening capture {}", capture_path.display()))?;
+    let mut lines = BufReader::new(capture).lines();
+    let mut report = SimulationReport::default();
+    let mut expected = HashSet::new();
+    let mut maybe_first_frame_at: Option<(u64, Instant)> = None;
+    let mut line_number = 0;
+    while let Some(line) = lines.next_line().await? {
+        line_number += 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        report.frames += 1;
+        let frame: CapturedFrame = match serde_json::from_str(&line) {
+            Ok(frame) => frame,
+            Err(error) => {
+                report.malformed_frames += 1;
+                warn!("Skipping malformed capture line {}: {}", line_number, error);
+                continue;
+            }
+        };
+        let (recorded_start, replay_start) =
+            *maybe_first_frame_at.get_or_insert((frame.received_at, Instant::now()));
+        let recorded_delay =
+            Duration::from_millis(frame.received_at.saturating_sub(recorded_start));
+        if let Some(delay) = speed.scale(recorded_delay) {
+            sleep_until(replay_start + delay).await;
+        }
+        match to_sse_event(frame) {
+            Ok(sse_event) => {
+                send_replayed(sse_event, &sse_event_sender, &mut report, &mut expected).await?;
+            }
+            Err(error) => {
+                report.malformed_frames += 1;
+                warn!("Skipping capture line {}: {}", line_number, error);
+            }
+        }
+    }
+    Ok((report, expected))
+}
+
+/// Sends a replayed event to the event processor, counting it in `report` and adding what should
+/// end up in storage to `expected`.
+async fn send_replayed(
+    sse_event: SseEvent,
+    sse_event_sender: &Sender<SseEvent>,
+    report: &mut SimulationReport,
+    expected: &mut HashSet<StoredEntity>,
+) -> Result<(), Error> {
+    *report
+        .events_by_type
+        .entry(sse_event.data.type_label())
+        .or_default() += 1;
+    if let Some(entity) = StoredEntity::from_sse_data(&sse_event.data) {
+        expected.insert(entity);
+    }
+    sse_event_sender
+        .send(sse_event)
+        .await
+        .map_err(|_| Error::msg("Event processor stopped during the simulation"))
+}
+
+pub(crate) fn to_sse_event(frame: CapturedFrame) -> Result<SseEvent, Error> {
+    let inbound_filter = frame
+        .inbound_filter()
+        .ok_or_else(|| Error::msg(format!("unknown event stream path '{}'", frame.filter)))?;
+    let source =
+        Url::parse(&frame.source).with_context(|| format!("invalid source '{}'", frame.source))?;
+    let (sse_data, needs_raw_json) = deserialize(&frame.data)?;
+    let id = frame.id.and_then(|id| id.parse::<u32>().ok()).unwrap_or(0);
+    let json_data = if needs_raw_json {
+        Some(frame.data)
+    } else {
+        None
+    };
+    Ok(SseEvent::new(
+        id,
+        sse_data,
+        source,
+        json_data,
+        inbound_filter,
+    ))
+}
+
+/// Forwards the frames sent to the outbound event stream to `sender`. If `record` is set, the
+/// returned task yields their canonical form, in the order they were sent, once the event processor
+/// stops.
+fn forward_outbound_frames(
+    mut receiver: Receiver<OutboundFrame>,
+    sender: Sender<OutboundFrame>,
+    record: bool,
+) -> JoinHandle<Vec<Value>> {
+    tokio::spawn(async move {
+        let mut frames = Vec::new();
+        while let Some(frame) = receiver.recv().await {
+            if record {
+                let (sse_data, maybe_filter, maybe_json_data) = &frame;
+                frames.push(canonical(json!({
+                    "filter": maybe_filter.as_ref().map(ToString::to_string),
+                    "data": sse_data_to_json(sse_data, maybe_json_data.as_deref()),
+                })));
+            }
+            // The event stream server only stops with the process, but the frames are recorded
+            // regardless.
+            let _ = sender.send(frame).await;
+        }
+        frames
+    })
+}
+
+/// Returns `value` with the keys of every object sorted, so that its serialization doesn't depend
+/// on the order in which fields were serialized or received.
+fn canonical(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| (key, canonical(value)))
+                .collect::<BTreeMap<_, _>>()
+                .into_iter()
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(canonical).collect()),
+        value => value,
+    }
+}
+
+/// Builds the canonical dump of a replay from the frames sent to the outbound event stream and the
+/// payloads stored in every table, along with the source and id of the event they were stored for.
+/// Nothing depending on when the replay ran, such as timestamps or event log ids, is included, and
+/// the payloads of a table are sorted. Events are processed concurrently on PostgreSQL, so the
+/// order of the outbound frames is only reproducible on SQLite.
+async fn canonical_dump<Db: DatabaseReader>(
+    database: &Db,
+    outbound_frames: Vec<Value>,
+) -> Result<String, Error> {
+    let mut storage = Map::new();
+    for table in PayloadTable::ALL {
+        let mut entries = Vec::new();
+        let mut after = 0;
+        loop {
+            let page = database
+                .get_raw_payloads_after(table, after, DUMP_PAGE_SIZE)
+                .await
+                .map_err(|error| {
+                    Error::msg(format!(
+                        "Error reading {} payloads: {:?}",
+                        table.table_name(),
+                        error
+                    ))
+                })?;
+            let page_size = page.len();
+            for entry in page {
+                after = entry.event_log_id;
+                let payload: Value = serde_json::from_str(&entry.raw)
+                    .with_context(|| format!("Error parsing {} payload", table.table_name()))?;
+                entries.push(canonical(json!({
+                    "source": entry.event_source_address,
+                    "event_id": entry.event_id,
+                    "payload": payload,
+                })));
+            }
+            if page_size < DUMP_PAGE_SIZE as usize {
+                break;
+            }
+        }
+        entries.sort_by_cached_key(Value::to_string);
+        storage.insert(table.table_name().to_string(), Value::Array(entries));
+    }
+    let dump = canonical(json!({ "outbound": outbound_frames, "storage": storage }));
+    Ok(format!("{}\n", serde_json::to_string_pretty(&dump)?))
+}
+
+/// Returns the expected entities which can't be found in storage. Events on the postgres backend
+/// are saved by several tasks, so entities are looked up again until the grace period elapses.
+async fn find_missing<Db: DatabaseReader>(
+    database: &Db,
+    mut expected: HashSet<StoredEntity>,
+) -> Result<Vec<String>, Error> {
+    let deadline = Instant::now() + VALIDATION_GRACE_PERIOD;
+    loop {
+        let mut still_missing = HashSet::new();
+        for entity in expected {
+            if !entity.is_stored(database).await? {
+                still_missing.insert(entity);
+            }
+        }
+        expected = still_missing;
+        if expected.is_empty() || Instant::now() >= deadline {
+            break;
+        }
+        sleep(VALIDATION_RETRY_INTERVAL).await;
+    }
+    Ok(expected
+        .into_iter()
+        .map(|entity| format!("{:?}", entity))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{fake_database::FakeDatabase, testing_config::prepare_config},
+        types::{database::DatabaseWriter, sse_events::BlockAdded},
+    };
+    use casper_types::testing::TestRng;
+    use std::io::Write;
+
+    /// Set to overwrite the golden dump with the dump of the replay instead of comparing them.
+    const UPDATE_GOLDEN_FILES_VAR: &str = "UPDATE_GOLDEN_FILES";
+
+    fn frame(received_at: u64, id: Option<u32>, sse_data: &SseData) -> CapturedFrame {
+        CapturedFrame {
+            received_at,
+            source: "http://127.0.0.1:18101".to_string(),
+            filter: Filter::Main.to_string(),
+            id: id.map(|id| id.to_string()),
+            data: serde_json::to_string(sse_data).unwrap(),
+        }
+    }
+
+    #[test]
+    fn should_parse_speed() {
+        assert_eq!(Speed::from_str("10x"), Ok(Speed::Factor(10.0)));
+        assert_eq!(Speed::from_str("0.5"), Ok(Speed::Factor(0.5)));
+        assert_eq!(Speed::from_str("MAX"), Ok(Speed::Max));
+        assert!(Speed::from_str("0x").is_err());
+        assert!(Speed::from_str("fast").is_err());
+        assert_eq!(
+            Speed::Factor(10.0).scale(Duration::from_secs(5)),
+            Some(Duration::from_millis(500))
+        );
+    }
+
+    #[tokio::test]
+    async fn should_replay_capture_and_validate_storage() {
+        let mut rng = TestRng::new();
+        let block_added = SseData::random_block_added(&mut rng);
+        let step = SseData::random_step(&mut rng);
+        let mut capture = tempfile::NamedTempFile::new().unwrap();
+        for frame in [
+            frame(1_000, None, &SseData::random_api_version(&mut rng)),
+            frame(1_010, Some(1), &block_added),
+            frame(1_020, Some(2), &step),
+        ] {
+            writeln!(capture, "{}", serde_json::to_string(&frame).unwrap()).unwrap();
+        }
+        writeln!(capture, "not a frame").unwrap();
+        let (sender, mut receiver) = mpsc_channel(10);
+
+        let (report, expected) = replay(capture.path(), Speed::Max, sender).await.unwrap();
+
+        assert_eq!(report.frames, 4);
+        assert_eq!(report.malformed_frames, 1);
+        assert_eq!(report.events_by_type.get("BlockAdded"), Some(&1));
+        assert_eq!(expected.len(), 2);
+        let mut ids = Vec::new();
+        while let Some(sse_event) = receiver.recv().await {
+            ids.push(sse_event.id);
+        }
+        assert_eq!(ids, vec![0, 1, 2]);
+
+        let database = FakeDatabase::new();
+        let block_entity = StoredEntity::from_sse_data(&block_added).unwrap();
+        if let SseData::BlockAdded { block_hash, block } = block_added {
+            database
+                .save_block_added(
+                    BlockAdded::new(block_hash, block),
+                    1,
+                    "127.0.0.1".to_string(),
+                )
+                .await
+                .unwrap();
+        }
+        let missing = find_missing(&database, HashSet::from([block_entity]))
+            .await
+            .unwrap();
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn canonical_should_sort_keys_recursively() {
+        let value = json!({ "b": [{ "d": 1, "c": 2 }], "a": null });
+
+        assert_eq!(
+            canonical(value).to_string(),
+            r#"{"a":null,"b":[{"c":2,"d":1}]}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn replay_should_match_golden_dump() {
+        let golden_directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/testing/golden");
+        let temp_storage = tempfile::tempdir().unwrap();
+        let config = prepare_config(&temp_storage).inner();
+        let dump_path = temp_storage.path().join("dump.json");
+
+        run_simulation(
+            config,
+            &golden_directory.join("capture.ndjson"),
+            Speed::Max,
+            Some(&dump_path),
+        )
+        .await
+        .unwrap();
+
+        let dump = std::fs::read_to_string(&dump_path).unwrap();
+        let golden_dump_path = golden_directory.join("dump.json");
+        if std::env::var_os(UPDATE_GOLDEN_FILES_VAR).is_some() {
+            std::fs::write(&golden_dump_path, &dump).unwrap();
+        }
+        let golden_dump = std::fs::read_to_string(&golden_dump_path).unwrap();
+        assert!(
+            dump == golden_dump,
+            "the replay of the golden capture diverged from {}, rerun with {}=1 to update it if \
+             the change is intended:\n{}",
+            golden_dump_path.display(),
+            UPDATE_GOLDEN_FILES_VAR,
+            dump
+        );
+    }
+}
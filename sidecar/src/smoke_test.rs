@@ -0,0 +1,282 @@
+//! End-to-end smoke test of a build of the sidecar.
+//!
+//! `smoke-test` starts the mock node of the tests, runs the sidecar against it with a database in
+//! a temporary directory, then checks that the events of a scripted scenario can be queried from
+//! the REST API and are streamed by the event stream server, so a build can be verified without a
+//! real node. The given config is used apart from what would reach outside of the test: the
+//! nodes, the storage and the ports of the servers are replaced, and the integrations with other
+//! services are disabled, as are the verifications of the events which the scenario's events
+//! wouldn't pass.
+//!
+//! The harness is compiled in tests and when the `smoke-test` feature is enabled, which also adds
+//! the `smoke-test` subcommand, e.g. for packagers to check their builds.
+
+use crate::{
+    run,
+    testing::{
+        mock_node::tests::MockNodeBuilder, ports::get_port,
+        raw_sse_events_utils::tests::EventsWithIds,
+    },
+    types::config::{Config, Connection, SqliteConfig, StorageConfig},
+};
+use anyhow::{anyhow, bail, Context, Error};
+use casper_event_types::sse_data::test_support::{
+    example_api_version, example_block_added_1_5_2, BLOCK_HASH_1, BLOCK_HASH_2,
+};
+use eventsource_stream::Eventsource;
+use futures::StreamExt;
+use http::StatusCode;
+use std::{path::Path, time::Duration};
+use tempfile::tempdir;
+use tokio::time::{sleep, timeout, Instant};
+use tracing::info;
+
+/// Version of the node simulated by the mock node.
+const NODE_VERSION: &str = "1.5.2";
+/// How long the sidecar is given to serve the expected results.
+const SCENARIO_TIMEOUT: Duration = Duration::from_secs(60);
+const RETRY_INTERVAL: Duration = Duration::from_millis(500);
+const SQLITE_FILE_NAME: &str = "smoke_test_database";
+
+/// A scripted scenario: the events sent by the node and what the sidecar is expected to serve.
+#[derive(Clone, Debug)]
+pub(crate) struct Scenario {
+    /// Events sent by the mock node, with their IDs.
+    pub(crate) node_events: EventsWithIds,
+    /// REST API paths, each expected to respond successfully with a body containing the text.
+    pub(crate) rest_checks: Vec<(String, String)>,
+    /// Texts expected, in this order, in the data of the events of the main event stream.
+    pub(crate) event_stream_checks: Vec<String>,
+}
+
+impl Default for Scenario {
+    /// Two blocks added, queried by hash and as the latest block, and streamed in order.
+    fn default() -> Self {
+        Scenario {
+            node_events: vec![
+                (None, example_api_version()),
+                (
+                    Some("1".to_string()),
+                    example_block_added_1_5_2(BLOCK_HASH_1, "1"),
+                ),
+                (
+                    Some("2".to_string()),
+                    example_block_added_1_5_2(BLOCK_HASH_2, "2"),
+                ),
+            ],
+            rest_checks: vec![
+                (format!("/block/{}", BLOCK_HASH_1), BLOCK_HASH_1.to_string()),
+                ("/block".to_string(), BLOCK_HASH_2.to_string()),
+            ],
+            event_stream_checks: vec![
+                "ApiVersion".to_string(),
+                BLOCK_HASH_1.to_string(),
+                BLOCK_HASH_2.to_string(),
+            ],
+        }
+    }
+}
+
+/// Runs the default scenario against the sidecar configured by `config`.
+pub(crate) async fn run_smoke_test(config: Config) -> Result<(), Error> {
+    run_scenario(config, &Scenario::default()).await?;
+    info!("Smoke test passed");
+    Ok(())
+}
+
+/// Runs the sidecar configured by `config` against a mock node sending the events of `scenario`,
+/// and checks its REST API and event stream.
+pub(crate) async fn run_scenario(mut config: Config, scenario: &Scenario) -> Result<(), Error> {
+    let temp_storage = tempdir().context("Error creating the temporary storage directory")?;
+    let mut node = MockNodeBuilder {
+        version: NODE_VERSION.to_string(),
+        data_of_node: scenario.node_events.clone(),
+        cache_of_node: None,
+        sse_port: Some(get_port()),
+        rest_port: Some(get_port()),
+    }
+    .build();
+    isolate(
+        &mut config,
+        temp_storage.path(),
+        node.get_sse_port(),
+        node.get_rest_port(),
+    );
+    let rest_server_port = config.rest_server.port;
+    let event_stream_server_port = config.event_stream_server.port;
+    node.start().await;
+    let mut sidecar = tokio::spawn(run(config));
+    let result = tokio::select! {
+        result = check_scenario(scenario, rest_server_port, event_stream_server_port) => result,
+        exit = &mut sidecar => Err(match exit {
+            Ok(Ok(())) => anyhow!("The sidecar stopped before the scenario completed"),
+            Ok(Err(error)) => error.context("The sidecar failed before the scenario completed"),
+            Err(error) => anyhow!("The sidecar panicked: {}", error),
+        }),
+    };
+    sidecar.abort();
+    node.stop().await;
+    result
+}
+
+/// Points `config` at the mock node, the temporary storage directory and free local ports, and
+/// disables everything reaching other services or rejecting the events of the scenario.
+fn isolate(config: &mut Config, storage_path: &Path, node_sse_port: u16, node_rest_port: u16) {
+    config.connections = vec![Connection {
+        ip_address: "127.0.0.1".to_string(),
+        sse_port: node_sse_port,
+        rest_port: node_rest_port,
+        max_attempts: 3,
+        delay_between_retries_in_seconds: 1,
+        allow_partial_connection: false,
+        enable_logging: false,
+        connection_timeout_in_seconds: None,
+        sleep_between_keep_alive_checks_in_seconds: None,
+        no_message_timeout_in_seconds: None,
+        gap_grace_period_in_seconds: None,
+        priority: None,
+    }];
+    config.storage = sqlite_storage(&config.storage, storage_path);
+    config.rest_server.address = Some("127.0.0.1".to_string());
+    config.rest_server.port = get_port();
+    config.event_stream_server.address = Some("127.0.0.1".to_string());
+    config.event_stream_server.port = get_port();
+    config.event_stream_server.jwt_auth = None;
+    config.admin_server = None;
+    config.metrics_server = None;
+    config.capture = None;
+    config.enrichment = None;
+    config.state_proofs = None;
+    config.kafka = None;
+    config.journal = None;
+    config.webhooks.clear();
+    config.backfill = None;
+    if let Some(account_aliases) = &mut config.account_aliases {
+        account_aliases.url = None;
+    }
+    config.verify_finality_signatures = false;
+    config.verify_block_hashes = false;
+}
+
+/// The SQLite storage in `storage_path`, configured like `storage` as far as possible.
+fn sqlite_storage(storage: &StorageConfig, storage_path: &Path) -> StorageConfig {
+    let sqlite_config = match storage {
+        StorageConfig::SqliteDbConfig { sqlite_config, .. } => sqlite_config.clone(),
+        StorageConfig::PostgreSqlDbConfig {
+            postgresql_config, ..
+        } => SqliteConfig {
+            file_name: SQLITE_FILE_NAME.to_string(),
+            max_connections_in_pool: postgresql_config.max_connections_in_pool,
+            wal_autocheckpointing_interval: 1000,
+            index_deploy_timestamps: postgresql_config.index_deploy_timestamps,
+            index_account_effects: postgresql_config.index_account_effects,
+        },
+    };
+    StorageConfig::SqliteDbConfig {
+        storage_path: storage_path.to_string_lossy().to_string(),
+        sqlite_config,
+    }
+}
+
+async fn check_scenario(
+    scenario: &Scenario,
+    rest_server_port: u16,
+    event_stream_server_port: u16,
+) -> Result<(), Error> {
+    let deadline = Instant::now() + SCENARIO_TIMEOUT;
+    check_event_stream(
+        event_stream_server_port,
+        &scenario.event_stream_checks,
+        deadline,
+    )
+    .await?;
+    for (path, expected) in &scenario.rest_checks {
+        check_rest_query(rest_server_port, path, expected, deadline).await?;
+    }
+    Ok(())
+}
+
+async fn check_event_stream(
+    port: u16,
+    expected: &[String],
+    deadline: Instant,
+) -> Result<(), Error> {
+    let url = format!("http://127.0.0.1:{}/events/main?start_from=0", port);
+    let response = loop {
+        match reqwest::get(&url).await {
+            Ok(response) if response.status().is_success() => break response,
+            _ if Instant::now() >= deadline => bail!("Couldn't connect to the event stream"),
+            _ => sleep(RETRY_INTERVAL).await,
+        }
+    };
+    let mut events = response.bytes_stream().eventsource();
+    let mut remaining = expected.iter().peekable();
+    let read_expected_events = async {
+        while let Some(expected_text) = remaining.peek() {
+            match events.next().await {
+                Some(Ok(event)) if event.data.contains(expected_text.as_str()) => {
+                    remaining.next();
+                }
+                Some(Ok(_)) => {}
+                Some(Err(error)) => bail!("Error reading the event stream: {}", error),
+                None => bail!("The event stream ended before `{}`", expected_text),
+            }
+        }
+        Ok(())
+    };
+    timeout(
+        deadline.saturating_duration_since(Instant::now()),
+        read_expected_events,
+    )
+    .await
+    .map_err(|_| anyhow!("Timed out waiting for the expected events on the event stream"))?
+}
+
+async fn check_rest_query(
+    port: u16,
+    path: &str,
+    expected: &str,
+    deadline: Instant,
+) -> Result<(), Error> {
+    let url = format!("http://127.0.0.1:{}{}", port, path);
+    loop {
+        let outcome = match reqwest::get(&url).await {
+            Ok(response) => {
+                let status = response.status();
+                match response.text().await {
+                    Ok(body) if status == StatusCode::OK && body.contains(expected) => {
+                        return Ok(())
+                    }
+                    Ok(body) => format!("responded {} with {}", status, body),
+                    Err(error) => {
+                        format!("responded {} with an unreadable body: {}", status, error)
+                    }
+                }
+            }
+            Err(error) => format!("failed: {}", error),
+        };
+        if Instant::now() >= deadline {
+            bail!("Query of {} {}", path, outcome);
+        }
+        sleep(RETRY_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::config::ConfigSerdeTarget;
+    use std::convert::TryInto;
+
+    fn example_config() -> Config {
+        toml::from_str::<ConfigSerdeTarget>(include_str!("../../EXAMPLE_NCTL_CONFIG.toml"))
+            .unwrap()
+            .try_into()
+            .unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn should_pass_default_scenario() {
+        run_smoke_test(example_config()).await.unwrap();
+    }
+}
@@ -0,0 +1,618 @@
+//! Scoring of the connected nodes by polling the `/status` endpoint of their REST servers.
+//!
+//! Every poll records the height of the latest block each node has added and the time it took to
+//! respond. The node with the highest block is preferred, the lowest latency breaking ties and the
+//! order of the configured connections breaking equal latencies, so that the sidecar prefers the
+//! most up-to-date node rather than the first configured one. Nodes which didn't respond in time
+//! are never preferred.
+//!
+//! The events ingested from each node are recorded as well, so the lag of the ingestion behind the
+//! head of the node and the divergence of the heads of the nodes can be reported. So are the
+//! keepalives the node sends when it has no event to send, which tell a node which stopped emitting
+//! events, e.g. stuck or no longer producing blocks, from a connection which went silent. Both are
+//! reported in [Health] as a degradation of the node.
+
+use crate::{
+    health::Health,
+    types::config::{Connection, SourceScoringConfig},
+};
+use casper_event_types::{
+    metrics::{
+        PREFERRED_SOURCE, SOURCE_BLOCKS_BEHIND_HEAD, SOURCE_HEAD_DIVERGENCE, SOURCE_HEAD_HEIGHTS,
+        SOURCE_SECONDS_SINCE_LAST_EVENT, SOURCE_STATUS_LATENCIES,
+    },
+    sse_data::SseData,
+};
+use futures::future::join_all;
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc::Receiver;
+use tracing::{info, warn};
+
+/// Prefix of the names under which the liveness of the nodes is reported in [Health], followed by
+/// the address of the node.
+pub const LIVENESS_COMPONENT_PREFIX: &str = "source:";
+const LIVENESS_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// What was recently received from a node whose events are streamed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum SourceLiveness {
+    /// Events were received.
+    ReceivingEvents,
+    /// Keepalives were received but no event: the connection is alive but the node emits nothing.
+    KeepAlivesOnly,
+    /// Nothing was received, not even keepalives.
+    Silent,
+}
+
+/// Status of a node at the latest poll, and of the ingestion of its events.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SourceStatus {
+    /// Address of the node, as `<ip_address>:<sse_port>` like in the `node_statuses` metric.
+    pub(crate) node: String,
+    /// Height of the latest block added by the node, `None` if it didn't return its status.
+    pub(crate) head_height: Option<u64>,
+    /// Time the node took to return its status.
+    pub(crate) latency: Option<Duration>,
+    /// Height of the latest block ingested from the node.
+    pub(crate) latest_ingested_block_height: Option<u64>,
+    /// When the latest event was ingested from the node.
+    pub(crate) last_event_at: Option<Instant>,
+    /// When the latest keepalive was received from the node.
+    pub(crate) last_keep_alive_at: Option<Instant>,
+    /// Time within which something is expected from the node, its `no_message_timeout`.
+    pub(crate) liveness_window: Duration,
+    /// Whether the events of the node are being streamed.
+    pub(crate) active: bool,
+}
+
+impl SourceStatus {
+    pub(crate) fn new(node: String, liveness_window: Duration) -> Self {
+        SourceStatus {
+            node,
+            head_height: None,
+            latency: None,
+            latest_ingested_block_height: None,
+            last_event_at: None,
+            last_keep_alive_at: None,
+            liveness_window,
+            active: false,
+        }
+    }
+
+    /// What was received from the node within the liveness window, `None` if its events aren't
+    /// streamed.
+    fn liveness(&self) -> Option<SourceLiveness> {
+        if !self.active {
+            return None;
+        }
+        let is_recent = |maybe_received_at: Option<Instant>| {
+            maybe_received_at.map_or(false, |received_at| {
+                received_at.elapsed() <= self.liveness_window
+            })
+        };
+        let liveness = if is_recent(self.last_event_at) {
+            SourceLiveness::ReceivingEvents
+        } else if is_recent(self.last_keep_alive_at) {
+            SourceLiveness::KeepAlivesOnly
+        } else {
+            SourceLiveness::Silent
+        };
+        Some(liveness)
+    }
+
+    /// Number of blocks of the node not ingested yet.
+    fn blocks_behind_head(&self) -> Option<u64> {
+        Some(
+            self.head_height?
+                .saturating_sub(self.latest_ingested_block_height?),
+        )
+    }
+}
+
+/// Lag of a node, as returned by the `sources` endpoint of the admin server.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct SourceReport {
+    pub(crate) node: String,
+    pub(crate) head_height: Option<u64>,
+    pub(crate) status_latency_in_milliseconds: Option<u64>,
+    pub(crate) latest_ingested_block_height: Option<u64>,
+    pub(crate) blocks_behind_head: Option<u64>,
+    pub(crate) seconds_since_last_event: Option<u64>,
+    pub(crate) seconds_since_last_keep_alive: Option<u64>,
+    pub(crate) liveness: Option<SourceLiveness>,
+    pub(crate) preferred: bool,
+    pub(crate) active: bool,
+}
+
+/// Lag of every connected node, as returned by the `sources` endpoint of the admin server.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct SourcesReport {
+    /// Difference between the highest and the lowest head of the nodes which returned their status.
+    pub(crate) head_divergence: Option<u64>,
+    pub(crate) sources: Vec<SourceReport>,
+}
+
+/// Shared handle to the latest status of every connected node, in the order of the configured
+/// connections.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SourceScores {
+    statuses: Arc<RwLock<Vec<SourceStatus>>>,
+}
+
+impl SourceScores {
+    pub(crate) fn new(connections: &[Connection]) -> Self {
+        let statuses = connections
+            .iter()
+            .map(|connection| {
+                SourceStatus::new(
+                    node_label(connection),
+                    Duration::from_secs(connection.no_message_timeout_in_seconds()),
+                )
+            })
+            .collect();
+        SourceScores {
+            statuses: Arc::new(RwLock::new(statuses)),
+        }
+    }
+
+    pub(crate) fn statuses(&self) -> Vec<SourceStatus> {
+        self.statuses
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Returns the address of the node scored best, `None` if no node returned its status.
+    pub(crate) fn preferred(&self) -> Option<String> {
+        preferred(&self.statuses()).map(|status| status.node.clone())
+    }
+
+    /// Returns the handle recording the events ingested from the node of the connection at `index`.
+    pub(crate) fn recorder(&self, index: usize) -> SourceRecorder {
+        SourceRecorder {
+            scores: self.clone(),
+            index,
+        }
+    }
+
+    pub(crate) fn report(&self) -> SourcesReport {
+        let statuses = self.statuses();
+        let maybe_preferred = preferred(&statuses).map(|status| status.node.as_str());
+        let heads = || statuses.iter().filter_map(|status| status.head_height);
+        let head_divergence = heads()
+            .max()
+            .and_then(|highest| Some(highest - heads().min()?));
+        let sources = statuses
+            .iter()
+            .map(|status| SourceReport {
+                node: status.node.clone(),
+                head_height: status.head_height,
+                status_latency_in_milliseconds: status
+                    .latency
+                    .map(|latency| latency.as_millis() as u64),
+                latest_ingested_block_height: status.latest_ingested_block_height,
+                blocks_behind_head: status.blocks_behind_head(),
+                seconds_since_last_event: status
+                    .last_event_at
+                    .map(|last_event_at| last_event_at.elapsed().as_secs()),
+                seconds_since_last_keep_alive: status
+                    .last_keep_alive_at
+                    .map(|last_keep_alive_at| last_keep_alive_at.elapsed().as_secs()),
+                liveness: status.liveness(),
+                preferred: maybe_preferred == Some(status.node.as_str()),
+                active: status.active,
+            })
+            .collect();
+        SourcesReport {
+            head_divergence,
+            sources,
+        }
+    }
+
+    /// Records whether the events of the node of the connection at `index` are being streamed.
+    pub(crate) fn set_active(&self, index: usize, active: bool) {
+        let mut statuses = self
+            .statuses
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(status) = statuses.get_mut(index) {
+            status.active = active;
+        }
+    }
+
+    /// Records the result of a poll, given in the order of the configured connections.
+    fn update(&self, polled: Vec<(Option<u64>, Option<Duration>)>) {
+        let mut statuses = self
+            .statuses
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        for (status, (head_height, latency)) in statuses.iter_mut().zip(polled) {
+            status.head_height = head_height;
+            status.latency = latency;
+        }
+    }
+}
+
+/// Handle recording the events ingested from one node.
+#[derive(Clone, Debug)]
+pub(crate) struct SourceRecorder {
+    scores: SourceScores,
+    index: usize,
+}
+
+impl SourceRecorder {
+    pub(crate) fn record(&self, data: &SseData) {
+        let mut statuses = self
+            .scores
+            .statuses
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(status) = statuses.get_mut(self.index) {
+            status.last_event_at = Some(Instant::now());
+            if let SseData::BlockAdded { block, .. } = data {
+                status.latest_ingested_block_height = status
+                    .latest_ingested_block_height
+                    .max(Some(block.header.height));
+            }
+        }
+    }
+}
+
+impl SourceRecorder {
+    /// Spawns a task recording the keepalives of the node reported to `receiver`.
+    pub(crate) fn record_keep_alives(self, mut receiver: Receiver<()>) {
+        tokio::spawn(async move {
+            while receiver.recv().await.is_some() {
+                self.record_keep_alive();
+            }
+        });
+    }
+
+    fn record_keep_alive(&self) {
+        let mut statuses = self
+            .scores
+            .statuses
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(status) = statuses.get_mut(self.index) {
+            status.last_keep_alive_at = Some(Instant::now());
+        }
+    }
+}
+
+/// Spawns a task marking the nodes whose events are streamed as degraded in [Health] while
+/// nothing but keepalives, or nothing at all, is received from them.
+pub(crate) fn start_liveness_monitor(scores: SourceScores, health: Health) {
+    tokio::spawn(async move {
+        loop {
+            for status in scores.statuses() {
+                let component = format!("{}{}", LIVENESS_COMPONENT_PREFIX, status.node);
+                match liveness_reason(&status) {
+                    Some(reason) => health.set_degraded(&component, reason),
+                    None => health.clear(&component),
+                }
+            }
+            tokio::time::sleep(LIVENESS_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// Describes why the node is degraded, `None` if it isn't or if its events aren't streamed.
+fn liveness_reason(status: &SourceStatus) -> Option<String> {
+    match status.liveness()? {
+        SourceLiveness::ReceivingEvents => None,
+        SourceLiveness::KeepAlivesOnly => Some(format!(
+            "receiving keepalives but no events for more than {}s",
+            status.liveness_window.as_secs()
+        )),
+        SourceLiveness::Silent => Some(format!(
+            "received neither events nor keepalives for more than {}s",
+            status.liveness_window.as_secs()
+        )),
+    }
+}
+
+pub(crate) fn node_label(connection: &Connection) -> String {
+    format!("{}:{}", connection.ip_address, connection.sse_port)
+}
+
+fn preferred(statuses: &[SourceStatus]) -> Option<&SourceStatus> {
+    statuses
+        .iter()
+        .enumerate()
+        .filter_map(|(order, status)| Some((status.head_height?, status.latency?, order, status)))
+        .min_by_key(|(head_height, latency, order, _)| {
+            (std::cmp::Reverse(*head_height), *latency, *order)
+        })
+        .map(|(_, _, _, status)| status)
+}
+
+/// Reads the height of the latest block from a response of the `/status` endpoint.
+fn parse_head_height(status: &Value) -> Option<u64> {
+    status.get("last_added_block_info")?.get("height")?.as_u64()
+}
+
+struct Source {
+    node: String,
+    status_endpoint: String,
+}
+
+/// Spawns a task polling the status of every node of `connections` into `scores`.
+pub(crate) fn start_source_scoring(
+    config: &SourceScoringConfig,
+    connections: &[Connection],
+    scores: SourceScores,
+) {
+    let sources: Vec<Source> = connections
+        .iter()
+        .map(|connection| Source {
+            node: node_label(connection),
+            status_endpoint: format!(
+                "http://{}:{}/status",
+                connection.ip_address, connection.rest_port
+            ),
+        })
+        .collect();
+    let poll_interval = Duration::from_secs(config.poll_interval_in_seconds());
+    let timeout = Duration::from_millis(config.timeout_in_milliseconds());
+    tokio::spawn(async move {
+        let client = reqwest::Client::new();
+        let mut maybe_preferred = None;
+        loop {
+            let polled = join_all(
+                sources
+                    .iter()
+                    .map(|source| poll_status(&client, source, timeout)),
+            )
+            .await;
+            scores.update(polled);
+            record_metrics(&scores.report());
+            let maybe_newly_preferred = scores.preferred();
+            if maybe_newly_preferred != maybe_preferred {
+                match &maybe_newly_preferred {
+                    Some(node) => info!("Preferred source is now {}", node),
+                    None => warn!("No source returned its status, none is preferred"),
+                }
+                maybe_preferred = maybe_newly_preferred;
+            }
+            tokio::time::sleep(poll_interval).await;
+        }
+    });
+}
+
+/// Returns the height of the head of the node of `source` and the time it took to return it.
+async fn poll_status(
+    client: &reqwest::Client,
+    source: &Source,
+    timeout: Duration,
+) -> (Option<u64>, Option<Duration>) {
+    let started = Instant::now();
+    let result = async {
+        client
+            .get(&source.status_endpoint)
+            .timeout(timeout)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await
+    }
+    .await;
+    let maybe_head_height = match result {
+        Ok(body) => serde_json::from_slice::<Value>(&body)
+            .ok()
+            .as_ref()
+            .and_then(parse_head_height),
+        Err(error) => {
+            warn!(node = %source.node, "Error polling the status of the node: {}", error);
+            None
+        }
+    };
+    (
+        maybe_head_height,
+        maybe_head_height.map(|_| started.elapsed()),
+    )
+}
+
+fn record_metrics(report: &SourcesReport) {
+    for source in &report.sources {
+        let node = source.node.as_str();
+        if let Some(head_height) = source.head_height {
+            SOURCE_HEAD_HEIGHTS
+                .with_label_values(&[node])
+                .set(head_height as f64);
+        }
+        SOURCE_STATUS_LATENCIES.with_label_values(&[node]).set(
+            source
+                .status_latency_in_milliseconds
+                .map_or(-1.0, |latency| latency as f64),
+        );
+        PREFERRED_SOURCE
+            .with_label_values(&[node])
+            .set(if source.preferred { 1.0 } else { 0.0 });
+        if let Some(blocks_behind_head) = source.blocks_behind_head {
+            SOURCE_BLOCKS_BEHIND_HEAD
+                .with_label_values(&[node])
+                .set(blocks_behind_head as f64);
+        }
+        if let Some(seconds_since_last_event) = source.seconds_since_last_event {
+            SOURCE_SECONDS_SINCE_LAST_EVENT
+                .with_label_values(&[node])
+                .set(seconds_since_last_event as f64);
+        }
+    }
+    if let Some(head_divergence) = report.head_divergence {
+        SOURCE_HEAD_DIVERGENCE.set(head_divergence as f64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use casper_types::testing::TestRng;
+    use serde_json::json;
+
+    fn scores_of(nodes: &[&str]) -> SourceScores {
+        let statuses = nodes
+            .iter()
+            .map(|node| SourceStatus::new(node.to_string(), Duration::from_secs(120)))
+            .collect();
+        SourceScores {
+            statuses: Arc::new(RwLock::new(statuses)),
+        }
+    }
+
+    fn polled(head_height: Option<u64>, latency_in_millis: u64) -> (Option<u64>, Option<Duration>) {
+        (
+            head_height,
+            head_height.map(|_| Duration::from_millis(latency_in_millis)),
+        )
+    }
+
+    fn block_added_at(rng: &mut TestRng, height: u64) -> SseData {
+        match SseData::random_block_added(rng) {
+            SseData::BlockAdded {
+                block_hash,
+                mut block,
+            } => {
+                block.header.height = height;
+                SseData::BlockAdded { block_hash, block }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn should_prefer_highest_head_then_lowest_latency() {
+        let scores = scores_of(&["first", "second", "third", "unreachable"]);
+        assert_eq!(scores.preferred(), None);
+
+        scores.update(vec![
+            polled(Some(100), 10),
+            polled(Some(102), 300),
+            polled(Some(102), 20),
+            polled(None, 0),
+        ]);
+        assert_eq!(scores.preferred(), Some("third".to_string()));
+
+        scores.update(vec![
+            polled(Some(102), 20),
+            polled(Some(102), 20),
+            polled(None, 0),
+            polled(None, 0),
+        ]);
+        assert_eq!(scores.preferred(), Some("first".to_string()));
+
+        scores.update(vec![polled(None, 0); 4]);
+        assert_eq!(scores.preferred(), None);
+    }
+
+    #[test]
+    fn should_report_ingestion_lag_and_head_divergence() {
+        let mut rng = TestRng::new();
+        let scores = scores_of(&["first", "second", "stalled"]);
+        scores.set_active(0, true);
+        scores.recorder(0).record(&block_added_at(&mut rng, 98));
+        scores.recorder(0).record(&block_added_at(&mut rng, 97));
+        scores
+            .recorder(1)
+            .record(&SseData::random_deploy_expired(&mut rng));
+        scores.update(vec![
+            polled(Some(100), 10),
+            polled(Some(103), 20),
+            polled(None, 0),
+        ]);
+
+        let report = scores.report();
+
+        assert_eq!(report.head_divergence, Some(3));
+        assert_eq!(
+            report.sources[0],
+            SourceReport {
+                node: "first".to_string(),
+                head_height: Some(100),
+                status_latency_in_milliseconds: Some(10),
+                latest_ingested_block_height: Some(98),
+                blocks_behind_head: Some(2),
+                seconds_since_last_event: Some(0),
+                seconds_since_last_keep_alive: None,
+                liveness: Some(SourceLiveness::ReceivingEvents),
+                preferred: false,
+                active: true,
+            }
+        );
+        assert!(report.sources[1].preferred);
+        assert!(!report.sources[1].active);
+        assert_eq!(report.sources[1].blocks_behind_head, None);
+        assert_eq!(report.sources[1].seconds_since_last_event, Some(0));
+        assert_eq!(report.sources[2].seconds_since_last_event, None);
+        assert_eq!(scores_of(&[]).report().head_divergence, None);
+    }
+
+    #[test]
+    fn should_tell_keep_alives_only_from_silence() {
+        let mut status = SourceStatus::new("first".to_string(), Duration::from_secs(60));
+        assert_eq!(status.liveness(), None);
+        assert_eq!(liveness_reason(&status), None);
+
+        status.active = true;
+        assert_eq!(status.liveness(), Some(SourceLiveness::Silent));
+        assert_eq!(
+            liveness_reason(&status),
+            Some("received neither events nor keepalives for more than 60s".to_string())
+        );
+
+        let long_ago = Instant::now() - Duration::from_secs(61);
+        status.last_event_at = Some(long_ago);
+        status.last_keep_alive_at = Some(Instant::now());
+        assert_eq!(status.liveness(), Some(SourceLiveness::KeepAlivesOnly));
+        assert_eq!(
+            liveness_reason(&status),
+            Some("receiving keepalives but no events for more than 60s".to_string())
+        );
+
+        status.last_event_at = Some(Instant::now());
+        assert_eq!(status.liveness(), Some(SourceLiveness::ReceivingEvents));
+        assert_eq!(liveness_reason(&status), None);
+    }
+
+    #[tokio::test]
+    async fn should_record_reported_keep_alives() {
+        let scores = scores_of(&["first"]);
+        let (sender, receiver) = tokio::sync::mpsc::channel(1);
+        scores.recorder(0).record_keep_alives(receiver);
+
+        sender.send(()).await.unwrap();
+        drop(sender);
+        for _ in 0..100 {
+            if scores.statuses()[0].last_keep_alive_at.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        assert_eq!(
+            scores.report().sources[0].seconds_since_last_keep_alive,
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn should_parse_head_height_of_status() {
+        let status = json!({
+            "api_version": "1.5.2",
+            "last_added_block_info": { "hash": "abc", "height": 1204, "era_id": 12 }
+        });
+        assert_eq!(parse_head_height(&status), Some(1204));
+        assert_eq!(
+            parse_head_height(&json!({ "last_added_block_info": null })),
+            None
+        );
+    }
+}
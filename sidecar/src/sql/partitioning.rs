@@ -0,0 +1,384 @@
+//! Native range partitioning of the tables keyed by era or block height in PostgreSQL.
+//!
+//! `BlockAdded` is partitioned by block height, `Step` and `Fault` by era. Every partitioned table
+//! has a `DEFAULT` partition holding the rows which are not covered by a range partition, e.g. the
+//! rows stored before the table was partitioned. Range partitions are named
+//! `<table>_<from>_<to>` so that their bounds can be recovered from the catalog.
+
+use super::tables;
+use sea_query::{
+    Alias, Expr, Func, JoinType, PostgresQueryBuilder, Query, SelectStatement, Table,
+    TableCreateStatement, TableDropStatement,
+};
+
+/// Tables which are partitioned in PostgreSQL.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PartitionedTable {
+    BlockAdded,
+    Step,
+    Fault,
+}
+
+/// The kind of value a table is partitioned by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PartitionKey {
+    Height,
+    Era,
+}
+
+impl PartitionKey {
+    pub fn column_name(&self) -> &'static str {
+        match self {
+            PartitionKey::Height => "height",
+            PartitionKey::Era => "era",
+        }
+    }
+}
+
+impl PartitionedTable {
+    pub const ALL: [PartitionedTable; 3] = [
+        PartitionedTable::BlockAdded,
+        PartitionedTable::Step,
+        PartitionedTable::Fault,
+    ];
+
+    pub fn table_name(&self) -> &'static str {
+        match self {
+            PartitionedTable::BlockAdded => "BlockAdded",
+            PartitionedTable::Step => "Step",
+            PartitionedTable::Fault => "Fault",
+        }
+    }
+
+    pub fn partition_key(&self) -> PartitionKey {
+        match self {
+            PartitionedTable::BlockAdded => PartitionKey::Height,
+            PartitionedTable::Step | PartitionedTable::Fault => PartitionKey::Era,
+        }
+    }
+
+    fn primary_key_name(&self) -> String {
+        format!("PDX_{}", self.table_name())
+    }
+
+    fn default_partition_name(&self) -> String {
+        format!("{}_default", self.table_name())
+    }
+
+    fn create_table_stmt(&self, db_supports_unsigned: bool) -> TableCreateStatement {
+        match self {
+            PartitionedTable::BlockAdded => tables::block_added::create_table_stmt(),
+            PartitionedTable::Step => tables::step::create_table_stmt(db_supports_unsigned),
+            PartitionedTable::Fault => tables::fault::create_table_stmt(db_supports_unsigned),
+        }
+    }
+}
+
+/// Range of keys covered by a partition, `from` inclusive and `to` exclusive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PartitionRange {
+    pub from: u64,
+    pub to: u64,
+}
+
+impl PartitionRange {
+    pub fn partition_name(&self, table: PartitionedTable) -> String {
+        format!("{}_{}_{}", table.table_name(), self.from, self.to)
+    }
+
+    /// Recovers the range of a partition of `table` from its name. Returns `None` for the default
+    /// partition and for tables which were not created by the sidecar.
+    pub fn from_partition_name(table: PartitionedTable, name: &str) -> Option<PartitionRange> {
+        let bounds = name
+            .strip_prefix(table.table_name())
+            .and_then(|name| name.strip_prefix('_'))?;
+        let (from, to) = bounds.split_once('_')?;
+        let range = PartitionRange {
+            from: from.parse().ok()?,
+            to: to.parse().ok()?,
+        };
+        (range.from < range.to).then_some(range)
+    }
+}
+
+/// Statements converting the existing, unpartitioned `table` into a partitioned table. The rows
+/// stored so far are moved to the default partition.
+pub fn create_conversion_stmts(table: PartitionedTable, db_supports_unsigned: bool) -> Vec<String> {
+    let table_name = table.table_name();
+    let unpartitioned_table_name = format!("{}_unpartitioned", table_name);
+    let primary_key_name = table.primary_key_name();
+    let create_table_stmt = table
+        .create_table_stmt(db_supports_unsigned)
+        .to_string(PostgresQueryBuilder);
+    vec![
+        Table::rename()
+            .table(
+                Alias::new(table_name),
+                Alias::new(&unpartitioned_table_name),
+            )
+            .to_string(PostgresQueryBuilder),
+        // Index names are unique per schema, so the primary key of the old table has to make
+        // room for the one of the partitioned table.
+        format!(
+            "ALTER TABLE \"{}\" RENAME CONSTRAINT \"{}\" TO \"{}_unpartitioned\"",
+            unpartitioned_table_name, primary_key_name, primary_key_name
+        ),
+        format!(
+            "{} PARTITION BY RANGE (\"{}\")",
+            create_table_stmt,
+            table.partition_key().column_name()
+        ),
+        format!(
+            "CREATE TABLE \"{}\" PARTITION OF \"{}\" DEFAULT",
+            table.default_partition_name(),
+            table_name
+        ),
+        format!(
+            "INSERT INTO \"{}\" SELECT * FROM \"{}\"",
+            table_name, unpartitioned_table_name
+        ),
+        Table::drop()
+            .table(Alias::new(&unpartitioned_table_name))
+            .to_string(PostgresQueryBuilder),
+    ]
+}
+
+pub fn create_partition_stmt(table: PartitionedTable, range: &PartitionRange) -> String {
+    format!(
+        "CREATE TABLE IF NOT EXISTS \"{}\" PARTITION OF \"{}\" FOR VALUES FROM ({}) TO ({})",
+        range.partition_name(table),
+        table.table_name(),
+        range.from,
+        range.to
+    )
+}
+
+pub fn create_drop_partition_stmt(
+    table: PartitionedTable,
+    range: &PartitionRange,
+) -> TableDropStatement {
+    Table::drop()
+        .table(Alias::new(range.partition_name(table)))
+        .if_exists()
+        .to_owned()
+}
+
+/// Selects the names of all partitions of `table`, including the default partition.
+pub fn create_list_partitions_stmt(table: PartitionedTable) -> SelectStatement {
+    let (parent, child) = (Alias::new("parent"), Alias::new("child"));
+    let pg_inherits = Alias::new("pg_inherits");
+    Query::select()
+        .expr(Func::cast_as(
+            Expr::col((child.clone(), Alias::new("relname"))),
+            Alias::new("TEXT"),
+        ))
+        .from(pg_inherits.clone())
+        .join_as(
+            JoinType::InnerJoin,
+            Alias::new("pg_class"),
+            parent.clone(),
+            Expr::col((parent.clone(), Alias::new("oid")))
+                .equals((pg_inherits.clone(), Alias::new("inhparent"))),
+        )
+        .join_as(
+            JoinType::InnerJoin,
+            Alias::new("pg_class"),
+            child.clone(),
+            Expr::col((child, Alias::new("oid"))).equals((pg_inherits, Alias::new("inhrelid"))),
+        )
+        .and_where(Expr::col((parent, Alias::new("relname"))).eq(table.table_name()))
+        .to_owned()
+}
+
+/// Selects the highest key stored in `table`, or only in its default partition. The key is
+/// selected as text since eras are stored as numerics which may exceed the range of a `BIGINT`.
+pub fn create_get_max_key_stmt(
+    table: PartitionedTable,
+    only_default_partition: bool,
+) -> SelectStatement {
+    let from = if only_default_partition {
+        table.default_partition_name()
+    } else {
+        table.table_name().to_string()
+    };
+    Query::select()
+        .expr(Func::cast_as(
+            Func::max(Expr::col(Alias::new(table.partition_key().column_name()))),
+            Alias::new("TEXT"),
+        ))
+        .from(Alias::new(from))
+        .to_owned()
+}
+
+/// Partitions to be created and dropped to bring a table in line with its configuration.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MaintenancePlan {
+    pub to_create: Vec<PartitionRange>,
+    pub to_drop: Vec<PartitionRange>,
+}
+
+/// Computes the partitions needed so that the range holding `max_key` and the following
+/// `partitions_ahead` ranges of `partition_width` keys exist, and the partitions holding data
+/// beyond the newest `maybe_retained_partitions` which should be dropped.
+///
+/// New partitions never overlap `existing` ones nor rows already stored in the default partition,
+/// as Postgres refuses to create a partition for a range which has rows in the default partition.
+pub fn plan_maintenance(
+    existing: &[PartitionRange],
+    max_key: Option<u64>,
+    max_key_in_default: Option<u64>,
+    partition_width: u64,
+    partitions_ahead: u64,
+    maybe_retained_partitions: Option<u64>,
+) -> MaintenancePlan {
+    let partition_width = partition_width.max(1);
+    let align_down = |key: u64| key - key % partition_width;
+    let mut next_from = existing
+        .iter()
+        .map(|range| range.to)
+        .chain(max_key_in_default.map(|key| key.saturating_add(1)))
+        .max()
+        .unwrap_or(0);
+    let target_to = align_down(max_key.unwrap_or(0))
+        .saturating_add(partition_width.saturating_mul(partitions_ahead.saturating_add(1)));
+    let mut to_create = Vec::new();
+    while next_from < target_to {
+        let to = align_down(next_from).saturating_add(partition_width);
+        if to <= next_from {
+            break;
+        }
+        to_create.push(PartitionRange {
+            from: next_from,
+            to,
+        });
+        next_from = to;
+    }
+
+    let mut to_drop = Vec::new();
+    if let (Some(retained_partitions), Some(max_key)) = (maybe_retained_partitions, max_key) {
+        let mut holding_data: Vec<PartitionRange> = existing
+            .iter()
+            .filter(|range| range.from <= max_key)
+            .copied()
+            .collect();
+        holding_data.sort_unstable_by(|a, b| b.cmp(a));
+        to_drop = holding_data
+            .into_iter()
+            .skip(retained_partitions.max(1) as usize)
+            .collect();
+        to_drop.sort_unstable();
+    }
+    MaintenancePlan { to_create, to_drop }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(from: u64, to: u64) -> PartitionRange {
+        PartitionRange { from, to }
+    }
+
+    #[test]
+    fn should_prepare_conversion_stmts() {
+        let stmts = create_conversion_stmts(PartitionedTable::Step, false);
+
+        assert_eq!(stmts.len(), 6);
+        assert_eq!(
+            stmts[0],
+            "ALTER TABLE \"Step\" RENAME TO \"Step_unpartitioned\""
+        );
+        assert_eq!(
+            stmts[1],
+            "ALTER TABLE \"Step_unpartitioned\" RENAME CONSTRAINT \"PDX_Step\" TO \"PDX_Step_unpartitioned\""
+        );
+        assert!(stmts[2].starts_with("CREATE TABLE IF NOT EXISTS \"Step\" ("));
+        assert!(stmts[2].ends_with(") PARTITION BY RANGE (\"era\")"));
+        assert_eq!(
+            stmts[3],
+            "CREATE TABLE \"Step_default\" PARTITION OF \"Step\" DEFAULT"
+        );
+        assert_eq!(
+            stmts[4],
+            "INSERT INTO \"Step\" SELECT * FROM \"Step_unpartitioned\""
+        );
+        assert_eq!(stmts[5], "DROP TABLE \"Step_unpartitioned\"");
+    }
+
+    #[test]
+    fn should_prepare_catalog_and_maintenance_stmts() {
+        assert_eq!(
+            create_list_partitions_stmt(PartitionedTable::Fault).to_string(PostgresQueryBuilder),
+            "SELECT CAST(\"child\".\"relname\" AS TEXT) FROM \"pg_inherits\" \
+             INNER JOIN \"pg_class\" AS \"parent\" ON \"parent\".\"oid\" = \"pg_inherits\".\"inhparent\" \
+             INNER JOIN \"pg_class\" AS \"child\" ON \"child\".\"oid\" = \"pg_inherits\".\"inhrelid\" \
+             WHERE \"parent\".\"relname\" = 'Fault'"
+        );
+        assert_eq!(
+            create_get_max_key_stmt(PartitionedTable::Step, true).to_string(PostgresQueryBuilder),
+            "SELECT CAST(MAX(\"era\") AS TEXT) FROM \"Step_default\""
+        );
+        assert_eq!(
+            create_drop_partition_stmt(PartitionedTable::BlockAdded, &range(0, 100))
+                .to_string(PostgresQueryBuilder),
+            "DROP TABLE IF EXISTS \"BlockAdded_0_100\""
+        );
+    }
+
+    #[test]
+    fn should_recover_range_from_partition_name() {
+        let partition = range(100, 200);
+        let name = partition.partition_name(PartitionedTable::BlockAdded);
+
+        assert_eq!(name, "BlockAdded_100_200");
+        assert_eq!(
+            PartitionRange::from_partition_name(PartitionedTable::BlockAdded, &name),
+            Some(partition)
+        );
+        assert_eq!(
+            PartitionRange::from_partition_name(PartitionedTable::Step, &name),
+            None
+        );
+        assert_eq!(
+            PartitionRange::from_partition_name(PartitionedTable::BlockAdded, "BlockAdded_default"),
+            None
+        );
+    }
+
+    #[test]
+    fn should_plan_partitions_ahead_of_the_newest_key() {
+        let plan = plan_maintenance(&[], None, None, 10, 2, None);
+        assert_eq!(
+            plan.to_create,
+            vec![range(0, 10), range(10, 20), range(20, 30)]
+        );
+
+        let existing = plan.to_create;
+        let plan = plan_maintenance(&existing, Some(15), None, 10, 2, None);
+        assert_eq!(plan.to_create, vec![range(30, 40)]);
+        assert!(plan.to_drop.is_empty());
+    }
+
+    #[test]
+    fn should_not_overlap_rows_in_the_default_partition() {
+        let plan = plan_maintenance(&[], Some(1234), Some(1234), 100, 1, None);
+
+        assert_eq!(plan.to_create, vec![range(1235, 1300), range(1300, 1400)]);
+    }
+
+    #[test]
+    fn should_drop_partitions_beyond_retention() {
+        let existing = vec![
+            range(0, 10),
+            range(10, 20),
+            range(20, 30),
+            range(30, 40),
+            range(40, 50),
+        ];
+
+        let plan = plan_maintenance(&existing, Some(35), None, 10, 1, Some(2));
+
+        assert!(plan.to_create.is_empty());
+        assert_eq!(plan.to_drop, vec![range(0, 10), range(10, 20)]);
+    }
+}
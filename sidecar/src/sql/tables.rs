@@ -1,12 +1,36 @@
+pub mod account_effect;
+pub mod api_key;
+pub mod audit_log;
 pub mod block_added;
+pub mod contract_event;
+pub mod contract_event_schema;
+pub mod contract_stats;
 pub mod deploy_accepted;
+pub mod deploy_account;
+pub mod deploy_approval;
+pub mod deploy_error;
 pub mod deploy_event;
 pub mod deploy_expired;
 pub mod deploy_processed;
+pub mod deploy_size;
+pub mod deploy_timestamp;
+pub mod derived_event;
+pub mod enrichment;
+pub mod era_validator;
+pub mod event_id_allocator;
 pub mod event_log;
 pub mod event_type;
 pub mod fault;
 pub mod finality_signature;
+pub mod finality_signature_checkpoint;
 pub mod migration;
+pub mod network_identity;
+pub mod nft_event;
+pub mod operational_event;
+pub mod raw_payload;
 pub mod shutdown;
 pub mod step;
+pub mod token_transfer;
+pub mod transfer;
+pub mod unknown_event;
+pub mod webhook_delivery;
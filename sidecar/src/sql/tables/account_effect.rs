@@ -0,0 +1,90 @@
+use sea_query::{
+    error::Result as SqResult, ColumnDef, Expr, Iden, Index, InsertStatement, OnConflict, Order,
+    Query, SelectStatement, Table, TableCreateStatement,
+};
+
+/// Index of the accounts whose state the execution of the processed deploys touched, so that the
+/// deploys affecting an account can be looked up.
+#[derive(Iden)]
+pub enum AccountEffect {
+    #[iden = "AccountEffect"]
+    Table,
+    AccountHash,
+    DeployHash,
+}
+
+pub fn create_table_stmt() -> TableCreateStatement {
+    Table::create()
+        .table(AccountEffect::Table)
+        .if_not_exists()
+        .col(
+            ColumnDef::new(AccountEffect::AccountHash)
+                .string()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(AccountEffect::DeployHash)
+                .string()
+                .not_null(),
+        )
+        .index(
+            Index::create()
+                .primary()
+                .name("PDX_AccountEffect")
+                .col(AccountEffect::AccountHash)
+                .col(AccountEffect::DeployHash),
+        )
+        .to_owned()
+}
+
+/// Records that the deploy touched the state of the account with the hex-encoded `account_hash`,
+/// unless already recorded.
+pub fn create_insert_stmt(account_hash: String, deploy_hash: String) -> SqResult<InsertStatement> {
+    Ok(Query::insert()
+        .into_table(AccountEffect::Table)
+        .columns([AccountEffect::AccountHash, AccountEffect::DeployHash])
+        .values(vec![account_hash.into(), deploy_hash.into()])?
+        .on_conflict(
+            OnConflict::columns([AccountEffect::AccountHash, AccountEffect::DeployHash])
+                .do_nothing()
+                .to_owned(),
+        )
+        .to_owned())
+}
+
+/// Selects at most `limit` entries of the deploys which touched the state of the account with the
+/// hex-encoded `account_hash`, ordered by deploy hash. If `after` is given, only the entries with a
+/// greater deploy hash are selected.
+pub fn create_get_by_account_stmt(
+    account_hash: &str,
+    after: Option<String>,
+    limit: u32,
+) -> SelectStatement {
+    let mut select = Query::select();
+    select
+        .columns([AccountEffect::DeployHash, AccountEffect::AccountHash])
+        .from(AccountEffect::Table)
+        .and_where(Expr::col(AccountEffect::AccountHash).eq(account_hash));
+    if let Some(deploy_hash) = after {
+        select.and_where(Expr::col(AccountEffect::DeployHash).gt(deploy_hash));
+    }
+    select
+        .order_by(AccountEffect::DeployHash, Order::Asc)
+        .limit(limit as u64)
+        .to_owned()
+}
+
+#[test]
+fn create_insert_stmt_should_ignore_recorded_effects() {
+    use sea_query::SqliteQueryBuilder;
+
+    let stmt = create_insert_stmt("ab".to_string(), "cd".to_string())
+        .unwrap()
+        .to_string(SqliteQueryBuilder);
+
+    assert_eq!(
+        stmt,
+        "INSERT INTO \"AccountEffect\" (\"account_hash\", \"deploy_hash\") VALUES ('ab', 'cd') \
+         ON CONFLICT (\"account_hash\", \"deploy_hash\") DO NOTHING"
+    );
+}
@@ -0,0 +1,98 @@
+use sea_query::{
+    error::Result as SqResult, ColumnDef, DeleteStatement, Expr, Iden, InsertStatement, Order,
+    Query, SelectStatement, Table, TableCreateStatement,
+};
+
+/// The API keys created through the admin server. Only the SHA-256 hashes of the keys are stored.
+#[derive(Iden)]
+enum ApiKey {
+    #[iden = "ApiKey"]
+    Table,
+    Name,
+    KeyHash,
+    Scopes,
+    CreatedTimestamp,
+}
+
+pub fn create_table_stmt() -> TableCreateStatement {
+    Table::create()
+        .table(ApiKey::Table)
+        .if_not_exists()
+        .col(
+            ColumnDef::new(ApiKey::Name)
+                .string()
+                .not_null()
+                .primary_key(),
+        )
+        .col(
+            ColumnDef::new(ApiKey::KeyHash)
+                .string()
+                .not_null()
+                .unique_key(),
+        )
+        // The names of the scopes, separated by commas.
+        .col(ColumnDef::new(ApiKey::Scopes).string().not_null())
+        // Stored as milliseconds since the UNIX epoch so that sqlite and postgresql return the same
+        // representation.
+        .col(
+            ColumnDef::new(ApiKey::CreatedTimestamp)
+                .big_integer()
+                .not_null(),
+        )
+        .to_owned()
+}
+
+pub fn create_insert_stmt(
+    name: &str,
+    key_hash: &str,
+    scopes: &str,
+    created_timestamp: u64,
+) -> SqResult<InsertStatement> {
+    Query::insert()
+        .into_table(ApiKey::Table)
+        .columns([
+            ApiKey::Name,
+            ApiKey::KeyHash,
+            ApiKey::Scopes,
+            ApiKey::CreatedTimestamp,
+        ])
+        .values(vec![
+            name.into(),
+            key_hash.into(),
+            scopes.into(),
+            created_timestamp.into(),
+        ])
+        .map(|stmt| stmt.to_owned())
+}
+
+pub fn create_delete_stmt(name: &str) -> DeleteStatement {
+    Query::delete()
+        .from_table(ApiKey::Table)
+        .and_where(Expr::col(ApiKey::Name).eq(name))
+        .to_owned()
+}
+
+/// Selects all the keys in the order they were created.
+pub fn create_get_all_stmt() -> SelectStatement {
+    Query::select()
+        .columns([
+            ApiKey::Name,
+            ApiKey::KeyHash,
+            ApiKey::Scopes,
+            ApiKey::CreatedTimestamp,
+        ])
+        .from(ApiKey::Table)
+        .order_by(ApiKey::CreatedTimestamp, Order::Asc)
+        .order_by(ApiKey::Name, Order::Asc)
+        .to_owned()
+}
+
+#[test]
+fn create_delete_stmt_should_delete_the_named_key() {
+    use sea_query::SqliteQueryBuilder;
+    let expected_sql = "DELETE FROM \"ApiKey\" WHERE \"name\" = 'indexer'";
+
+    let got_sql = create_delete_stmt("indexer").to_string(SqliteQueryBuilder);
+
+    assert_eq!(got_sql, expected_sql);
+}
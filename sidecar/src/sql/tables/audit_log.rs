@@ -0,0 +1,107 @@
+use sea_query::{
+    error::Result as SqResult, ColumnDef, Expr, Iden, InsertStatement, Order, Query,
+    SelectStatement, Table, TableCreateStatement,
+};
+
+#[derive(Iden)]
+pub(crate) enum AuditLog {
+    #[iden = "AuditLog"]
+    Table,
+    AuditLogId,
+    RecordedTimestamp,
+    Actor,
+    Action,
+    Details,
+}
+
+pub fn create_table_stmt(is_big_integer_id: bool) -> TableCreateStatement {
+    let mut binding = ColumnDef::new(AuditLog::AuditLogId);
+    let mut audit_log_id_col_definition = binding.auto_increment().not_null().primary_key();
+    if is_big_integer_id {
+        audit_log_id_col_definition = audit_log_id_col_definition.big_integer();
+    } else {
+        audit_log_id_col_definition = audit_log_id_col_definition.integer();
+    }
+    Table::create()
+        .table(AuditLog::Table)
+        .if_not_exists()
+        .col(audit_log_id_col_definition)
+        // Stored as seconds since the UNIX epoch so that sqlite and postgresql return the same
+        // representation.
+        .col(
+            ColumnDef::new(AuditLog::RecordedTimestamp)
+                .big_integer()
+                .not_null(),
+        )
+        .col(ColumnDef::new(AuditLog::Actor).text().not_null())
+        .col(ColumnDef::new(AuditLog::Action).text().not_null())
+        .col(ColumnDef::new(AuditLog::Details).text())
+        .to_owned()
+}
+
+pub fn create_insert_stmt(
+    recorded_timestamp: u64,
+    actor: &str,
+    action: &str,
+    details: Option<&str>,
+) -> SqResult<InsertStatement> {
+    Query::insert()
+        .into_table(AuditLog::Table)
+        .columns([
+            AuditLog::RecordedTimestamp,
+            AuditLog::Actor,
+            AuditLog::Action,
+            AuditLog::Details,
+        ])
+        .values(vec![
+            recorded_timestamp.into(),
+            actor.into(),
+            action.into(),
+            details.into(),
+        ])
+        .map(|stmt| stmt.returning_col(AuditLog::AuditLogId).to_owned())
+}
+
+/// Selects at most `limit` entries with an id greater or equal to `from_id`, oldest first.
+pub fn create_list_stmt(from_id: u64, limit: u32) -> SelectStatement {
+    Query::select()
+        .columns([
+            AuditLog::AuditLogId,
+            AuditLog::RecordedTimestamp,
+            AuditLog::Actor,
+            AuditLog::Action,
+            AuditLog::Details,
+        ])
+        .from(AuditLog::Table)
+        .and_where(Expr::col(AuditLog::AuditLogId).gte(from_id))
+        .order_by(AuditLog::AuditLogId, Order::Asc)
+        .limit(limit as u64)
+        .to_owned()
+}
+
+#[test]
+fn create_insert_stmt_should_produce_insert_sql() {
+    use sea_query::SqliteQueryBuilder;
+    let expected_sql = "INSERT INTO \"AuditLog\" (\"recorded_timestamp\", \"actor\", \"action\", \"details\") VALUES (1700000000, '127.0.0.1:5000', 'admin_api_call', '/metrics') RETURNING \"audit_log_id\"";
+
+    let got_sql = create_insert_stmt(
+        1_700_000_000,
+        "127.0.0.1:5000",
+        "admin_api_call",
+        Some("/metrics"),
+    )
+    .unwrap()
+    .to_string(SqliteQueryBuilder);
+
+    assert_eq!(got_sql, expected_sql);
+}
+
+#[test]
+fn create_list_stmt_should_produce_select_sql() {
+    use sea_query::SqliteQueryBuilder;
+    let expected_sql = "SELECT \"audit_log_id\", \"recorded_timestamp\", \"actor\", \"action\", \"details\" FROM \"AuditLog\" WHERE \"audit_log_id\" >= 10 ORDER BY \"audit_log_id\" ASC LIMIT 100";
+
+    let got_sql = create_list_stmt(10, 100).to_string(SqliteQueryBuilder);
+
+    assert_eq!(got_sql, expected_sql);
+}
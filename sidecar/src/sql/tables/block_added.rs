@@ -1,6 +1,7 @@
 use sea_query::{
     error::Result as SqResult, ColumnDef, Expr, ForeignKey, ForeignKeyAction, Iden, Index,
-    InsertStatement, Query, SelectStatement, Table, TableCreateStatement,
+    InsertStatement, OnConflict, Order, Query, SelectStatement, Table, TableAlterStatement,
+    TableCreateStatement,
 };
 
 use super::event_log::EventLog;
@@ -13,8 +14,13 @@ enum BlockAdded {
     BlockHash,
     Raw,
     EventLogId,
+    /// `failed` if the hashes of the block didn't match its contents at ingestion, `ok` otherwise.
+    Integrity,
 }
 
+const INTEGRITY_OK: &str = "ok";
+const INTEGRITY_FAILED: &str = "failed";
+
 pub fn create_table_stmt() -> TableCreateStatement {
     Table::create()
         .table(BlockAdded::Table)
@@ -50,34 +56,65 @@ pub fn create_table_stmt() -> TableCreateStatement {
         .to_owned()
 }
 
+/// Adds the column marking blocks which failed integrity verification. Blocks stored before it was
+/// added are treated as intact.
+pub fn create_add_integrity_column_stmt() -> TableAlterStatement {
+    Table::alter()
+        .table(BlockAdded::Table)
+        .add_column(
+            ColumnDef::new(BlockAdded::Integrity)
+                .string()
+                .not_null()
+                .default(INTEGRITY_OK),
+        )
+        .to_owned()
+}
+
+/// Stores a block, unless it was already stored.
 pub fn create_insert_stmt(
     height: u64,
     block_hash: String,
     raw: String,
     event_log_id: u64,
+    integrity_failed: bool,
 ) -> SqResult<InsertStatement> {
-    Query::insert()
+    let integrity = if integrity_failed {
+        INTEGRITY_FAILED
+    } else {
+        INTEGRITY_OK
+    };
+    Ok(Query::insert()
         .into_table(BlockAdded::Table)
         .columns([
             BlockAdded::Height,
             BlockAdded::BlockHash,
             BlockAdded::Raw,
             BlockAdded::EventLogId,
+            BlockAdded::Integrity,
         ])
         .values(vec![
             height.into(),
             block_hash.into(),
             raw.into(),
             event_log_id.into(),
-        ])
-        .map(|stmt| stmt.to_owned())
+            integrity.into(),
+        ])?
+        .on_conflict(
+            OnConflict::columns([BlockAdded::BlockHash, BlockAdded::Height])
+                .do_nothing()
+                .to_owned(),
+        )
+        .to_owned())
 }
 
+// Blocks which failed integrity verification are never selected.
+
 pub fn create_get_by_hash_stmt(block_hash: String) -> SelectStatement {
     Query::select()
         .column(BlockAdded::Raw)
         .from(BlockAdded::Table)
         .and_where(Expr::col(BlockAdded::BlockHash).eq(block_hash))
+        .and_where(Expr::col(BlockAdded::Integrity).ne(INTEGRITY_FAILED))
         .to_owned()
 }
 
@@ -86,6 +123,7 @@ pub fn create_get_by_height_stmt(height: u64) -> SelectStatement {
         .column(BlockAdded::Raw)
         .from(BlockAdded::Table)
         .and_where(Expr::col(BlockAdded::Height).eq(height))
+        .and_where(Expr::col(BlockAdded::Integrity).ne(INTEGRITY_FAILED))
         .to_owned()
 }
 
@@ -93,6 +131,7 @@ pub fn create_get_latest_stmt() -> SelectStatement {
     let select_max = Query::select()
         .expr(Expr::col(BlockAdded::Height).max())
         .from(BlockAdded::Table)
+        .and_where(Expr::col(BlockAdded::Integrity).ne(INTEGRITY_FAILED))
         .to_owned();
     Query::select()
         .column(BlockAdded::Raw)
@@ -100,3 +139,15 @@ pub fn create_get_latest_stmt() -> SelectStatement {
         .and_where(Expr::col(BlockAdded::Height).in_subquery(select_max))
         .to_owned()
 }
+
+pub fn create_get_by_height_range_stmt(from: u64, to: u64, limit: u32) -> SelectStatement {
+    Query::select()
+        .column(BlockAdded::Raw)
+        .from(BlockAdded::Table)
+        .and_where(Expr::col(BlockAdded::Height).gte(from))
+        .and_where(Expr::col(BlockAdded::Height).lte(to))
+        .and_where(Expr::col(BlockAdded::Integrity).ne(INTEGRITY_FAILED))
+        .order_by(BlockAdded::Height, Order::Asc)
+        .limit(limit as u64)
+        .to_owned()
+}
@@ -0,0 +1,137 @@
+use sea_query::{
+    error::Result as SqResult, ColumnDef, DeleteStatement, Expr, Iden, Index, IndexCreateStatement,
+    InsertStatement, OnConflict, Order, Query, SelectStatement, Table, TableCreateStatement,
+};
+
+/// Events of contracts following the Casper Event Standard decoded with the schemas of their
+/// events, keyed by the hash of the contract and the id of the event, which numbers the events of a
+/// contract in the order they were emitted.
+#[derive(Iden)]
+enum ContractEvent {
+    #[iden = "ContractEvent"]
+    Table,
+    ContractHash,
+    EventId,
+    EventName,
+    DeployHash,
+    Raw,
+}
+
+pub fn create_table_stmt() -> TableCreateStatement {
+    Table::create()
+        .table(ContractEvent::Table)
+        .if_not_exists()
+        .col(
+            ColumnDef::new(ContractEvent::ContractHash)
+                .string()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(ContractEvent::EventId)
+                .big_unsigned()
+                .not_null(),
+        )
+        .col(ColumnDef::new(ContractEvent::EventName).string().not_null())
+        .col(
+            ColumnDef::new(ContractEvent::DeployHash)
+                .string()
+                .not_null(),
+        )
+        .col(ColumnDef::new(ContractEvent::Raw).text().not_null())
+        .index(
+            Index::create()
+                .primary()
+                .name("PDX_ContractEvent")
+                .col(ContractEvent::ContractHash)
+                .col(ContractEvent::EventId),
+        )
+        .to_owned()
+}
+
+pub fn create_event_name_index_stmt() -> IndexCreateStatement {
+    Index::create()
+        .if_not_exists()
+        .name("IDX_ContractEvent_EventName")
+        .table(ContractEvent::Table)
+        .col(ContractEvent::ContractHash)
+        .col(ContractEvent::EventName)
+        .col(ContractEvent::EventId)
+        .to_owned()
+}
+
+/// Records the event, unless already recorded. `raw` is the JSON of the event as served.
+pub fn create_insert_stmt(
+    contract_hash: String,
+    event_id: u64,
+    event_name: String,
+    deploy_hash: String,
+    raw: String,
+) -> SqResult<InsertStatement> {
+    Ok(Query::insert()
+        .into_table(ContractEvent::Table)
+        .columns([
+            ContractEvent::ContractHash,
+            ContractEvent::EventId,
+            ContractEvent::EventName,
+            ContractEvent::DeployHash,
+            ContractEvent::Raw,
+        ])
+        .values(vec![
+            contract_hash.into(),
+            event_id.into(),
+            event_name.into(),
+            deploy_hash.into(),
+            raw.into(),
+        ])?
+        .on_conflict(
+            OnConflict::columns([ContractEvent::ContractHash, ContractEvent::EventId])
+                .do_nothing()
+                .to_owned(),
+        )
+        .to_owned())
+}
+
+/// Selects at most `limit` events of the contract with the hex-encoded `contract_hash`, ordered by
+/// event id. If `event_name` is given, only the events with that name are selected, and if `after`
+/// is given, only the events with a greater event id.
+pub fn create_get_by_contract_stmt(
+    contract_hash: &str,
+    event_name: Option<&str>,
+    after: Option<u64>,
+    limit: u32,
+) -> SelectStatement {
+    let mut select = Query::select();
+    select
+        .column(ContractEvent::Raw)
+        .from(ContractEvent::Table)
+        .and_where(Expr::col(ContractEvent::ContractHash).eq(contract_hash));
+    if let Some(event_name) = event_name {
+        select.and_where(Expr::col(ContractEvent::EventName).eq(event_name));
+    }
+    if let Some(event_id) = after {
+        select.and_where(Expr::col(ContractEvent::EventId).gt(event_id));
+    }
+    select
+        .order_by(ContractEvent::EventId, Order::Asc)
+        .limit(limit as u64)
+        .to_owned()
+}
+
+/// Deletes all the contract events, to be derived again from the stored processed deploys.
+pub fn create_delete_all_stmt() -> DeleteStatement {
+    Query::delete().from_table(ContractEvent::Table).to_owned()
+}
+
+#[test]
+fn create_get_by_contract_stmt_should_select_events_of_name() {
+    use sea_query::SqliteQueryBuilder;
+
+    let stmt = create_get_by_contract_stmt("ab", Some("Transfer"), Some(3), 10)
+        .to_string(SqliteQueryBuilder);
+
+    assert_eq!(
+        stmt,
+        "SELECT \"raw\" FROM \"ContractEvent\" WHERE \"contract_hash\" = 'ab' AND \
+         \"event_name\" = 'Transfer' AND \"event_id\" > 3 ORDER BY \"event_id\" ASC LIMIT 10"
+    );
+}
@@ -0,0 +1,62 @@
+use sea_query::{
+    error::Result as SqResult, ColumnDef, Expr, Iden, InsertStatement, OnConflict, Query,
+    SelectStatement, Table, TableCreateStatement,
+};
+
+/// The schemas of the events of contracts following the Casper Event Standard, stored as JSON by
+/// hash of the contract.
+#[derive(Iden)]
+enum ContractEventSchema {
+    #[iden = "ContractEventSchema"]
+    Table,
+    ContractHash,
+    Raw,
+}
+
+pub fn create_table_stmt() -> TableCreateStatement {
+    Table::create()
+        .table(ContractEventSchema::Table)
+        .if_not_exists()
+        .col(
+            ColumnDef::new(ContractEventSchema::ContractHash)
+                .string()
+                .not_null()
+                .primary_key(),
+        )
+        .col(ColumnDef::new(ContractEventSchema::Raw).text().not_null())
+        .to_owned()
+}
+
+/// Records the schemas of the contract, replacing those already recorded.
+pub fn create_upsert_stmt(contract_hash: String, raw: String) -> SqResult<InsertStatement> {
+    Ok(Query::insert()
+        .into_table(ContractEventSchema::Table)
+        .columns([ContractEventSchema::ContractHash, ContractEventSchema::Raw])
+        .values(vec![contract_hash.into(), raw.into()])?
+        .on_conflict(
+            OnConflict::column(ContractEventSchema::ContractHash)
+                .update_column(ContractEventSchema::Raw)
+                .to_owned(),
+        )
+        .to_owned())
+}
+
+pub fn create_get_stmt(contract_hash: &str) -> SelectStatement {
+    Query::select()
+        .column(ContractEventSchema::Raw)
+        .from(ContractEventSchema::Table)
+        .and_where(Expr::col(ContractEventSchema::ContractHash).eq(contract_hash))
+        .to_owned()
+}
+
+#[test]
+fn create_upsert_stmt_sql() {
+    use sea_query::SqliteQueryBuilder;
+    let expected_sql = "INSERT INTO \"ContractEventSchema\" (\"contract_hash\", \"raw\") VALUES ('ab', '{}') ON CONFLICT (\"contract_hash\") DO UPDATE SET \"raw\" = \"excluded\".\"raw\"";
+
+    let got_sql = create_upsert_stmt("ab".to_string(), "{}".to_string())
+        .unwrap()
+        .to_string(SqliteQueryBuilder);
+
+    assert_eq!(got_sql, expected_sql);
+}
@@ -0,0 +1,130 @@
+use sea_query::{
+    error::Result as SqResult, Alias, ColumnDef, Expr, Iden, Index, InsertStatement, OnConflict,
+    Order, Query, SelectStatement, SimpleExpr, Table, TableCreateStatement,
+};
+
+/// Daily rollups of the outcomes of the deploys calling a contract and of the gas they spent.
+/// Days are UTC dates formatted as `YYYY-MM-DD`.
+#[derive(Iden)]
+pub enum ContractStats {
+    #[iden = "ContractStats"]
+    Table,
+    ContractHash,
+    Day,
+    Successes,
+    Failures,
+    GasSpent,
+}
+
+pub fn create_table_stmt() -> TableCreateStatement {
+    Table::create()
+        .table(ContractStats::Table)
+        .if_not_exists()
+        .col(
+            ColumnDef::new(ContractStats::ContractHash)
+                .string()
+                .not_null(),
+        )
+        .col(ColumnDef::new(ContractStats::Day).string().not_null())
+        .col(
+            ColumnDef::new(ContractStats::Successes)
+                .big_integer()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(ContractStats::Failures)
+                .big_integer()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(ContractStats::GasSpent)
+                .big_integer()
+                .not_null(),
+        )
+        .index(
+            Index::create()
+                .primary()
+                .name("PDX_ContractStats")
+                .col(ContractStats::ContractHash)
+                .col(ContractStats::Day),
+        )
+        .to_owned()
+}
+
+/// Adds the outcome of a deploy calling the contract on `day`, and the gas it spent, to the
+/// rollup of that contract and day.
+pub fn create_upsert_stmt(
+    contract_hash: String,
+    day: String,
+    succeeded: bool,
+    gas_spent: u64,
+) -> SqResult<InsertStatement> {
+    let (successes, failures) = if succeeded { (1u64, 0u64) } else { (0, 1) };
+    Ok(Query::insert()
+        .into_table(ContractStats::Table)
+        .columns([
+            ContractStats::ContractHash,
+            ContractStats::Day,
+            ContractStats::Successes,
+            ContractStats::Failures,
+            ContractStats::GasSpent,
+        ])
+        .values(vec![
+            contract_hash.into(),
+            day.into(),
+            successes.into(),
+            failures.into(),
+            gas_spent.into(),
+        ])?
+        .on_conflict(
+            OnConflict::columns([ContractStats::ContractHash, ContractStats::Day])
+                .value(
+                    ContractStats::Successes,
+                    accumulated(ContractStats::Successes),
+                )
+                .value(
+                    ContractStats::Failures,
+                    accumulated(ContractStats::Failures),
+                )
+                .value(
+                    ContractStats::GasSpent,
+                    accumulated(ContractStats::GasSpent),
+                )
+                .to_owned(),
+        )
+        .to_owned())
+}
+
+/// The stored value of `column` plus the one of the conflicting row.
+fn accumulated(column: ContractStats) -> SimpleExpr {
+    let excluded = Expr::col((Alias::new("excluded"), Alias::new(column.to_string())));
+    Expr::col((ContractStats::Table, column)).add(excluded)
+}
+
+/// Selects the rollups of the contract, ordered by day.
+pub fn create_get_by_contract_stmt(contract_hash: String) -> SelectStatement {
+    Query::select()
+        .columns([
+            ContractStats::Day,
+            ContractStats::Successes,
+            ContractStats::Failures,
+            ContractStats::GasSpent,
+        ])
+        .from(ContractStats::Table)
+        .and_where(Expr::col(ContractStats::ContractHash).eq(contract_hash))
+        .order_by(ContractStats::Day, Order::Asc)
+        .to_owned()
+}
+
+#[test]
+fn create_get_by_contract_stmt_should_order_by_day() {
+    use sea_query::SqliteQueryBuilder;
+
+    let stmt = create_get_by_contract_stmt("ab".to_string()).to_string(SqliteQueryBuilder);
+
+    assert_eq!(
+        stmt,
+        "SELECT \"day\", \"successes\", \"failures\", \"gas_spent\" FROM \"ContractStats\" \
+         WHERE \"contract_hash\" = 'ab' ORDER BY \"day\" ASC"
+    );
+}
@@ -0,0 +1,237 @@
+use sea_query::{
+    error::Result as SqResult, ColumnDef, Expr, Iden, Index, IndexCreateStatement, InsertStatement,
+    OnConflict, Order, Query, SelectStatement, Table, TableCreateStatement,
+};
+
+use super::{deploy_timestamp::DeployTimestamp, event_type::EventTypeId};
+
+/// Index of the accounts which sent the accepted deploys, so that deploys can be looked up by
+/// account.
+#[derive(Iden)]
+pub enum DeployAccount {
+    #[iden = "DeployAccount"]
+    Table,
+    DeployHash,
+    AccountHash,
+}
+
+pub fn create_table_stmt() -> TableCreateStatement {
+    Table::create()
+        .table(DeployAccount::Table)
+        .if_not_exists()
+        .col(
+            ColumnDef::new(DeployAccount::DeployHash)
+                .string()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(DeployAccount::AccountHash)
+                .string()
+                .not_null(),
+        )
+        .index(
+            Index::create()
+                .primary()
+                .name("PDX_DeployAccount")
+                .col(DeployAccount::DeployHash),
+        )
+        .to_owned()
+}
+
+pub fn create_account_index_stmt() -> IndexCreateStatement {
+    Index::create()
+        .if_not_exists()
+        .name("IDX_DeployAccount_Account")
+        .table(DeployAccount::Table)
+        .col(DeployAccount::AccountHash)
+        .col(DeployAccount::DeployHash)
+        .to_owned()
+}
+
+/// Records the hex-encoded hash of the account which sent the deploy, unless already recorded.
+pub fn create_insert_stmt(deploy_hash: String, account_hash: String) -> SqResult<InsertStatement> {
+    Ok(Query::insert()
+        .into_table(DeployAccount::Table)
+        .columns([DeployAccount::DeployHash, DeployAccount::AccountHash])
+        .values(vec![deploy_hash.into(), account_hash.into()])?
+        .on_conflict(
+            OnConflict::column(DeployAccount::DeployHash)
+                .do_nothing()
+                .to_owned(),
+        )
+        .to_owned())
+}
+
+/// Selects at most `limit` entries of the deploys sent by the account with the hex-encoded
+/// `account_hash`, ordered by deploy hash. If `after` is given, only the entries with a greater
+/// deploy hash are selected.
+pub fn create_get_by_account_stmt(
+    account_hash: &str,
+    after: Option<String>,
+    limit: u32,
+) -> SelectStatement {
+    let mut select = Query::select();
+    select
+        .columns([DeployAccount::DeployHash, DeployAccount::AccountHash])
+        .from(DeployAccount::Table)
+        .and_where(Expr::col(DeployAccount::AccountHash).eq(account_hash));
+    if let Some(deploy_hash) = after {
+        select.and_where(Expr::col(DeployAccount::DeployHash).gt(deploy_hash));
+    }
+    select
+        .order_by(DeployAccount::DeployHash, Order::Asc)
+        .limit(limit as u64)
+        .to_owned()
+}
+
+/// Selects the entries [create_get_by_account_stmt] selects, restricted to the deploys processed in
+/// a block with a timestamp, in milliseconds since the Unix epoch, up to `processed_by`, as
+/// indexed in the deploy timestamps.
+pub fn create_get_processed_by_account_stmt(
+    account_hash: &str,
+    processed_by: u64,
+    after: Option<String>,
+    limit: u32,
+) -> SelectStatement {
+    let mut select = Query::select();
+    select
+        .column((DeployAccount::Table, DeployAccount::DeployHash))
+        .column((DeployAccount::Table, DeployAccount::AccountHash))
+        .from(DeployAccount::Table)
+        .inner_join(
+            DeployTimestamp::Table,
+            Expr::col((DeployTimestamp::Table, DeployTimestamp::DeployHash))
+                .equals((DeployAccount::Table, DeployAccount::DeployHash)),
+        )
+        .and_where(Expr::col((DeployAccount::Table, DeployAccount::AccountHash)).eq(account_hash))
+        .and_where(
+            Expr::col((DeployTimestamp::Table, DeployTimestamp::EventTypeId))
+                .eq(EventTypeId::DeployProcessed as u8),
+        )
+        .and_where(
+            Expr::col((DeployTimestamp::Table, DeployTimestamp::Timestamp)).lte(processed_by),
+        );
+    if let Some(deploy_hash) = after {
+        select.and_where(
+            Expr::col((DeployAccount::Table, DeployAccount::DeployHash)).gt(deploy_hash),
+        );
+    }
+    select
+        .order_by(
+            (DeployAccount::Table, DeployAccount::DeployHash),
+            Order::Asc,
+        )
+        .limit(limit as u64)
+        .to_owned()
+}
+
+/// Selects at most `limit` entries of the deploys sent by the account with the hex-encoded
+/// `account_hash`, ordered by the time at which they were accepted, as indexed in the deploy
+/// timestamps, then by deploy hash, skipping the first `offset` ones. If `processed_by` is given,
+/// only the deploys processed in a block with a timestamp up to it are selected.
+pub fn create_get_page_by_account_stmt(
+    account_hash: &str,
+    processed_by: Option<u64>,
+    offset: u32,
+    limit: u32,
+) -> SelectStatement {
+    let mut select = Query::select();
+    select
+        .column((DeployAccount::Table, DeployAccount::DeployHash))
+        .column((DeployAccount::Table, DeployAccount::AccountHash))
+        .from(DeployAccount::Table)
+        .inner_join(
+            DeployTimestamp::Table,
+            Expr::col((DeployTimestamp::Table, DeployTimestamp::DeployHash))
+                .equals((DeployAccount::Table, DeployAccount::DeployHash)),
+        )
+        .and_where(Expr::col((DeployAccount::Table, DeployAccount::AccountHash)).eq(account_hash))
+        .and_where(
+            Expr::col((DeployTimestamp::Table, DeployTimestamp::EventTypeId))
+                .eq(EventTypeId::DeployAccepted as u8),
+        );
+    if let Some(processed_by) = processed_by {
+        select.and_where(
+            Expr::col((DeployAccount::Table, DeployAccount::DeployHash))
+                .in_subquery(create_get_processed_by_stmt(processed_by)),
+        );
+    }
+    select
+        .order_by(
+            (DeployTimestamp::Table, DeployTimestamp::Timestamp),
+            Order::Asc,
+        )
+        .order_by(
+            (DeployAccount::Table, DeployAccount::DeployHash),
+            Order::Asc,
+        )
+        .limit(limit as u64)
+        .offset(offset as u64)
+        .to_owned()
+}
+
+/// Selects the hashes of the deploys processed in a block with a timestamp up to `processed_by`.
+fn create_get_processed_by_stmt(processed_by: u64) -> SelectStatement {
+    Query::select()
+        .column(DeployTimestamp::DeployHash)
+        .from(DeployTimestamp::Table)
+        .and_where(Expr::col(DeployTimestamp::EventTypeId).eq(EventTypeId::DeployProcessed as u8))
+        .and_where(Expr::col(DeployTimestamp::Timestamp).lte(processed_by))
+        .to_owned()
+}
+
+#[test]
+fn create_get_by_account_stmt_should_page_after_cursor() {
+    use sea_query::SqliteQueryBuilder;
+
+    let stmt =
+        create_get_by_account_stmt("ab", Some("cd".to_string()), 10).to_string(SqliteQueryBuilder);
+
+    assert_eq!(
+        stmt,
+        "SELECT \"deploy_hash\", \"account_hash\" FROM \"DeployAccount\" \
+         WHERE \"account_hash\" = 'ab' AND \"deploy_hash\" > 'cd' \
+         ORDER BY \"deploy_hash\" ASC LIMIT 10"
+    );
+}
+
+#[test]
+fn create_get_processed_by_account_stmt_should_join_processed_timestamps() {
+    use sea_query::SqliteQueryBuilder;
+
+    let stmt = create_get_processed_by_account_stmt("ab", 1000, Some("cd".to_string()), 10)
+        .to_string(SqliteQueryBuilder);
+
+    assert_eq!(
+        stmt,
+        "SELECT \"DeployAccount\".\"deploy_hash\", \"DeployAccount\".\"account_hash\" \
+         FROM \"DeployAccount\" INNER JOIN \"DeployTimestamp\" \
+         ON \"DeployTimestamp\".\"deploy_hash\" = \"DeployAccount\".\"deploy_hash\" \
+         WHERE \"DeployAccount\".\"account_hash\" = 'ab' \
+         AND \"DeployTimestamp\".\"event_type_id\" = 4 \
+         AND \"DeployTimestamp\".\"timestamp\" <= 1000 \
+         AND \"DeployAccount\".\"deploy_hash\" > 'cd' \
+         ORDER BY \"DeployAccount\".\"deploy_hash\" ASC LIMIT 10"
+    );
+}
+
+#[test]
+fn create_get_page_by_account_stmt_should_order_by_accepted_timestamp() {
+    use sea_query::SqliteQueryBuilder;
+
+    let stmt =
+        create_get_page_by_account_stmt("ab", Some(1000), 20, 10).to_string(SqliteQueryBuilder);
+
+    assert_eq!(
+        stmt,
+        "SELECT \"DeployAccount\".\"deploy_hash\", \"DeployAccount\".\"account_hash\" \
+         FROM \"DeployAccount\" INNER JOIN \"DeployTimestamp\" \
+         ON \"DeployTimestamp\".\"deploy_hash\" = \"DeployAccount\".\"deploy_hash\" \
+         WHERE \"DeployAccount\".\"account_hash\" = 'ab' \
+         AND \"DeployTimestamp\".\"event_type_id\" = 2 \
+         AND \"DeployAccount\".\"deploy_hash\" IN (SELECT \"deploy_hash\" FROM \"DeployTimestamp\" \
+         WHERE \"event_type_id\" = 4 AND \"timestamp\" <= 1000) \
+         ORDER BY \"DeployTimestamp\".\"timestamp\" ASC, \"DeployAccount\".\"deploy_hash\" ASC \
+         LIMIT 10 OFFSET 20"
+    );
+}
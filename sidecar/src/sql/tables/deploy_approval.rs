@@ -0,0 +1,92 @@
+use sea_query::{
+    error::Result as SqResult, ColumnDef, Expr, Iden, Index, InsertStatement, OnConflict, Order,
+    Query, SelectStatement, Table, TableCreateStatement,
+};
+
+/// Approvals of the accepted deploys, keyed by the hash of the deploy and the signer, along with
+/// the `DeployAccepted` event which first carried each of them. Later acceptances of a deploy may
+/// carry approvals its first one didn't.
+#[derive(Iden)]
+enum DeployApproval {
+    #[iden = "DeployApproval"]
+    Table,
+    DeployHash,
+    Signer,
+    EventLogId,
+}
+
+pub fn create_table_stmt() -> TableCreateStatement {
+    Table::create()
+        .table(DeployApproval::Table)
+        .if_not_exists()
+        .col(
+            ColumnDef::new(DeployApproval::DeployHash)
+                .string()
+                .not_null(),
+        )
+        .col(ColumnDef::new(DeployApproval::Signer).string().not_null())
+        .col(
+            ColumnDef::new(DeployApproval::EventLogId)
+                .big_unsigned()
+                .not_null(),
+        )
+        .index(
+            Index::create()
+                .primary()
+                .name("PDX_DeployApproval")
+                .col(DeployApproval::DeployHash)
+                .col(DeployApproval::Signer),
+        )
+        .to_owned()
+}
+
+/// Records the approval of the deploy by the hex-encoded public key `signer`, carried by the event
+/// with the given `event_log_id`, unless already recorded.
+pub fn create_insert_stmt(
+    deploy_hash: String,
+    signer: String,
+    event_log_id: u64,
+) -> SqResult<InsertStatement> {
+    Ok(Query::insert()
+        .into_table(DeployApproval::Table)
+        .columns([
+            DeployApproval::DeployHash,
+            DeployApproval::Signer,
+            DeployApproval::EventLogId,
+        ])
+        .values(vec![deploy_hash.into(), signer.into(), event_log_id.into()])?
+        .on_conflict(
+            OnConflict::columns([DeployApproval::DeployHash, DeployApproval::Signer])
+                .do_nothing()
+                .to_owned(),
+        )
+        .to_owned())
+}
+
+/// Selects the approvals of the deploy, in the order in which they were carried by its
+/// acceptances, then by signer.
+pub fn create_get_by_deploy_hash_stmt(deploy_hash: String) -> SelectStatement {
+    Query::select()
+        .columns([
+            DeployApproval::DeployHash,
+            DeployApproval::Signer,
+            DeployApproval::EventLogId,
+        ])
+        .from(DeployApproval::Table)
+        .and_where(Expr::col(DeployApproval::DeployHash).eq(deploy_hash))
+        .order_by(DeployApproval::EventLogId, Order::Asc)
+        .order_by(DeployApproval::Signer, Order::Asc)
+        .to_owned()
+}
+
+#[test]
+fn create_insert_stmt_should_ignore_existing_approvals() {
+    use sea_query::SqliteQueryBuilder;
+    let expected_sql = "INSERT INTO \"DeployApproval\" (\"deploy_hash\", \"signer\", \"event_log_id\") VALUES ('ab', '01cd', 3) ON CONFLICT (\"deploy_hash\", \"signer\") DO NOTHING";
+
+    let got_sql = create_insert_stmt("ab".to_string(), "01cd".to_string(), 3)
+        .unwrap()
+        .to_string(SqliteQueryBuilder);
+
+    assert_eq!(got_sql, expected_sql);
+}
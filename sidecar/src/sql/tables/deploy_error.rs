@@ -0,0 +1,110 @@
+use sea_query::{
+    error::Result as SqResult, ColumnDef, Expr, Iden, Index, InsertStatement, LikeExpr, OnConflict,
+    Order, Query, SelectStatement, Table, TableCreateStatement,
+};
+
+/// Character escaping the wildcards of `LIKE` patterns. Unlike a backslash, it is quoted the same
+/// way in the string literals of all supported databases.
+const LIKE_ESCAPE: char = '!';
+
+/// Index of the error messages of the deploys whose execution failed, so that deploys can be
+/// looked up by error message.
+#[derive(Iden)]
+pub enum DeployError {
+    #[iden = "DeployError"]
+    Table,
+    DeployHash,
+    BlockHash,
+    ErrorMessage,
+}
+
+pub fn create_table_stmt() -> TableCreateStatement {
+    Table::create()
+        .table(DeployError::Table)
+        .if_not_exists()
+        .col(ColumnDef::new(DeployError::DeployHash).string().not_null())
+        .col(ColumnDef::new(DeployError::BlockHash).string().not_null())
+        .col(ColumnDef::new(DeployError::ErrorMessage).text().not_null())
+        .index(
+            Index::create()
+                .primary()
+                .name("PDX_DeployError")
+                .col(DeployError::DeployHash),
+        )
+        .to_owned()
+}
+
+/// Records the error message of the failed execution of the deploy, unless one was already
+/// recorded.
+pub fn create_insert_stmt(
+    deploy_hash: String,
+    block_hash: String,
+    error_message: String,
+) -> SqResult<InsertStatement> {
+    Ok(Query::insert()
+        .into_table(DeployError::Table)
+        .columns([
+            DeployError::DeployHash,
+            DeployError::BlockHash,
+            DeployError::ErrorMessage,
+        ])
+        .values(vec![
+            deploy_hash.into(),
+            block_hash.into(),
+            error_message.into(),
+        ])?
+        .on_conflict(
+            OnConflict::column(DeployError::DeployHash)
+                .do_nothing()
+                .to_owned(),
+        )
+        .to_owned())
+}
+
+/// Selects at most `limit` entries whose error message contains `contains`, ordered by deploy
+/// hash. If `after` is given, only the entries with a greater deploy hash are selected.
+pub fn create_get_by_error_message_stmt(
+    contains: &str,
+    after: Option<String>,
+    limit: u32,
+) -> SelectStatement {
+    let mut select = Query::select();
+    select
+        .columns([
+            DeployError::DeployHash,
+            DeployError::BlockHash,
+            DeployError::ErrorMessage,
+        ])
+        .from(DeployError::Table)
+        .and_where(
+            Expr::col(DeployError::ErrorMessage)
+                .like(LikeExpr::new(contains_pattern(contains)).escape(LIKE_ESCAPE)),
+        );
+    if let Some(deploy_hash) = after {
+        select.and_where(Expr::col(DeployError::DeployHash).gt(deploy_hash));
+    }
+    select
+        .order_by(DeployError::DeployHash, Order::Asc)
+        .limit(limit as u64)
+        .to_owned()
+}
+
+/// Builds a `LIKE` pattern matching any text containing `contains`, in which the wildcards of
+/// `contains` match themselves only.
+fn contains_pattern(contains: &str) -> String {
+    let mut pattern = String::from("%");
+    for character in contains.chars() {
+        if matches!(character, '%' | '_' | LIKE_ESCAPE) {
+            pattern.push(LIKE_ESCAPE);
+        }
+        pattern.push(character);
+    }
+    pattern.push('%');
+    pattern
+}
+
+#[test]
+fn contains_pattern_should_escape_wildcards() {
+    assert_eq!(contains_pattern("User error: 1"), "%User error: 1%");
+    assert_eq!(contains_pattern("50%_off!"), "%50!%!_off!!%");
+}
@@ -1,6 +1,6 @@
 use sea_query::{
-    error::Result as SqResult, ColumnDef, ForeignKey, ForeignKeyAction, Iden, Index,
-    InsertStatement, Query, Table, TableCreateStatement,
+    error::Result as SqResult, Alias, ColumnDef, Expr, ForeignKey, ForeignKeyAction, Iden, Index,
+    InsertStatement, OnConflict, Order, Query, SelectStatement, Table, TableCreateStatement,
 };
 
 use super::event_log::EventLog;
@@ -40,12 +40,50 @@ pub fn create_table_stmt() -> TableCreateStatement {
         .to_owned()
 }
 
+/// Links an event to a deploy, unless they are already linked.
 pub fn create_insert_stmt(event_log_id: u64, deploy_hash: String) -> SqResult<InsertStatement> {
     let insert_stmt = Query::insert()
         .into_table(DeployEvent::Table)
         .columns([DeployEvent::EventLogId, DeployEvent::DeployHash])
         .values(vec![event_log_id.into(), deploy_hash.into()])?
+        .on_conflict(
+            OnConflict::columns([DeployEvent::DeployHash, DeployEvent::EventLogId])
+                .do_nothing()
+                .to_owned(),
+        )
         .to_owned();
 
     Ok(insert_stmt)
 }
+
+/// Selects the type and, as text, the time the events linked to the deploy were stored at, in the
+/// order in which they were stored.
+pub fn create_get_times_by_deploy_hash_stmt(deploy_hash: String) -> SelectStatement {
+    Query::select()
+        .column((EventLog::Table, EventLog::EventTypeId))
+        // Timestamps are read as text as they are typed differently by each database.
+        .expr_as(
+            Expr::cust("CAST(\"event_log\".\"inserted_timestamp\" AS TEXT)"),
+            Alias::new("inserted_timestamp"),
+        )
+        .from(DeployEvent::Table)
+        .inner_join(
+            EventLog::Table,
+            Expr::col((DeployEvent::Table, DeployEvent::EventLogId))
+                .equals((EventLog::Table, EventLog::EventLogId)),
+        )
+        .and_where(Expr::col((DeployEvent::Table, DeployEvent::DeployHash)).eq(deploy_hash))
+        .order_by((DeployEvent::Table, DeployEvent::EventLogId), Order::Asc)
+        .to_owned()
+}
+
+#[test]
+fn create_get_times_by_deploy_hash_stmt_should_select_oldest_first() {
+    use sea_query::SqliteQueryBuilder;
+    let expected_sql = "SELECT \"event_log\".\"event_type_id\", CAST(\"event_log\".\"inserted_timestamp\" AS TEXT) AS \"inserted_timestamp\" FROM \"deploy_event\" INNER JOIN \"event_log\" ON \"deploy_event\".\"event_log_id\" = \"event_log\".\"event_log_id\" WHERE \"deploy_event\".\"deploy_hash\" = 'ab' ORDER BY \"deploy_event\".\"event_log_id\" ASC";
+
+    let got_sql =
+        create_get_times_by_deploy_hash_stmt("ab".to_string()).to_string(SqliteQueryBuilder);
+
+    assert_eq!(got_sql, expected_sql);
+}
@@ -1,6 +1,6 @@
 use sea_query::{
     error::Result as SqResult, ColumnDef, Expr, ForeignKey, ForeignKeyAction, Iden, Index,
-    InsertStatement, Query, SelectStatement, Table, TableCreateStatement,
+    InsertStatement, OnConflict, Query, SelectStatement, Table, TableCreateStatement,
 };
 
 use super::event_log::EventLog;
@@ -46,20 +46,26 @@ pub fn create_table_stmt() -> TableCreateStatement {
         .to_owned()
 }
 
+/// Stores an expired deploy, unless it was already stored.
 pub fn create_insert_stmt(
     deploy_hash: String,
     event_log_id: u64,
     raw: String,
 ) -> SqResult<InsertStatement> {
-    Query::insert()
+    Ok(Query::insert()
         .into_table(DeployExpired::Table)
         .columns([
             DeployExpired::DeployHash,
             DeployExpired::EventLogId,
             DeployExpired::Raw,
         ])
-        .values(vec![deploy_hash.into(), event_log_id.into(), raw.into()])
-        .map(|stmt| stmt.to_owned())
+        .values(vec![deploy_hash.into(), event_log_id.into(), raw.into()])?
+        .on_conflict(
+            OnConflict::columns([DeployExpired::DeployHash])
+                .do_nothing()
+                .to_owned(),
+        )
+        .to_owned())
 }
 
 pub fn create_get_by_hash_stmt(deploy_hash: String) -> SelectStatement {
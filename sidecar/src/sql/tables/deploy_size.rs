@@ -0,0 +1,129 @@
+use sea_query::{
+    error::Result as SqResult, Alias, Asterisk, ColumnDef, Expr, Iden, Index, IndexCreateStatement,
+    InsertStatement, OnConflict, Order, Query, SelectStatement, Table, TableCreateStatement,
+};
+
+/// Size and weight of the accepted deploys: the length of their serialized form, the number of
+/// their approvals and the number of the runtime args of their payment and session.
+#[derive(Iden, Clone, Copy)]
+pub enum DeploySize {
+    #[iden = "DeploySize"]
+    Table,
+    DeployHash,
+    SizeBytes,
+    Approvals,
+    Args,
+}
+
+/// The columns of the measures of a deploy.
+pub const MEASURES: [DeploySize; 3] = [
+    DeploySize::SizeBytes,
+    DeploySize::Approvals,
+    DeploySize::Args,
+];
+
+pub fn create_table_stmt() -> TableCreateStatement {
+    Table::create()
+        .table(DeploySize::Table)
+        .if_not_exists()
+        .col(ColumnDef::new(DeploySize::DeployHash).string().not_null())
+        .col(
+            ColumnDef::new(DeploySize::SizeBytes)
+                .big_integer()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(DeploySize::Approvals)
+                .big_integer()
+                .not_null(),
+        )
+        .col(ColumnDef::new(DeploySize::Args).big_integer().not_null())
+        .index(
+            Index::create()
+                .primary()
+                .name("PDX_DeploySize")
+                .col(DeploySize::DeployHash),
+        )
+        .to_owned()
+}
+
+/// Indexes the values of the measure so that its percentiles are looked up without sorting the
+/// table.
+pub fn create_measure_index_stmt(measure: DeploySize) -> IndexCreateStatement {
+    Index::create()
+        .if_not_exists()
+        .name(format!("IDX_DeploySize_{}", measure.to_string()))
+        .table(DeploySize::Table)
+        .col(measure)
+        .to_owned()
+}
+
+/// Records the measures of the deploy, unless already recorded.
+pub fn create_insert_stmt(
+    deploy_hash: String,
+    size_bytes: u64,
+    approvals: u64,
+    args: u64,
+) -> SqResult<InsertStatement> {
+    Ok(Query::insert()
+        .into_table(DeploySize::Table)
+        .columns([
+            DeploySize::DeployHash,
+            DeploySize::SizeBytes,
+            DeploySize::Approvals,
+            DeploySize::Args,
+        ])
+        .values(vec![
+            deploy_hash.into(),
+            size_bytes.into(),
+            approvals.into(),
+            args.into(),
+        ])?
+        .on_conflict(
+            OnConflict::column(DeploySize::DeployHash)
+                .do_nothing()
+                .to_owned(),
+        )
+        .to_owned())
+}
+
+/// Selects the number of recorded deploys along with the minimum, the maximum and the sum of the
+/// values of the measure, as `deploys`, `min`, `max` and `sum`.
+pub fn create_get_summary_stmt(measure: DeploySize) -> SelectStatement {
+    let column = measure.to_string();
+    Query::select()
+        .expr_as(Expr::col(Asterisk).count(), Alias::new("deploys"))
+        .expr_as(Expr::col(measure).min(), Alias::new("min"))
+        .expr_as(Expr::col(measure).max(), Alias::new("max"))
+        .expr_as(
+            Expr::cust(format!("CAST(SUM(\"{}\") AS BIGINT)", column)),
+            Alias::new("sum"),
+        )
+        .from(DeploySize::Table)
+        .to_owned()
+}
+
+/// Selects the value of the measure which ranks `offset` (starting from 0) among the values of
+/// the recorded deploys sorted in ascending order, as `value`.
+pub fn create_get_ranked_stmt(measure: DeploySize, offset: u64) -> SelectStatement {
+    Query::select()
+        .expr_as(Expr::col(measure), Alias::new("value"))
+        .from(DeploySize::Table)
+        .order_by(measure, Order::Asc)
+        .limit(1)
+        .offset(offset)
+        .to_owned()
+}
+
+#[test]
+fn create_get_ranked_stmt_should_skip_lower_values() {
+    use sea_query::SqliteQueryBuilder;
+
+    let stmt = create_get_ranked_stmt(DeploySize::Approvals, 9).to_string(SqliteQueryBuilder);
+
+    assert_eq!(
+        stmt,
+        "SELECT \"approvals\" AS \"value\" FROM \"DeploySize\" \
+         ORDER BY \"approvals\" ASC LIMIT 1 OFFSET 9"
+    );
+}
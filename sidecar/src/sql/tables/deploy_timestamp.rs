@@ -0,0 +1,142 @@
+use sea_query::{
+    error::Result as SqResult, ColumnDef, Cond, Expr, Iden, Index, IndexCreateStatement,
+    InsertStatement, OnConflict, Order, Query, SelectStatement, Table, TableCreateStatement,
+};
+
+/// Index of the times at which deploys were accepted and processed, so that deploys can be looked
+/// up by time range. Timestamps are stored as milliseconds since the Unix epoch.
+#[derive(Iden)]
+pub enum DeployTimestamp {
+    #[iden = "DeployTimestamp"]
+    Table,
+    DeployHash,
+    EventTypeId,
+    Timestamp,
+}
+
+pub fn create_table_stmt() -> TableCreateStatement {
+    Table::create()
+        .table(DeployTimestamp::Table)
+        .if_not_exists()
+        .col(
+            ColumnDef::new(DeployTimestamp::DeployHash)
+                .string()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(DeployTimestamp::EventTypeId)
+                .tiny_unsigned()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(DeployTimestamp::Timestamp)
+                .big_integer()
+                .not_null(),
+        )
+        .index(
+            Index::create()
+                .primary()
+                .name("PDX_DeployTimestamp")
+                .col(DeployTimestamp::DeployHash)
+                .col(DeployTimestamp::EventTypeId),
+        )
+        .to_owned()
+}
+
+pub fn create_timestamp_index_stmt() -> IndexCreateStatement {
+    Index::create()
+        .if_not_exists()
+        .name("IDX_DeployTimestamp_timestamp")
+        .table(DeployTimestamp::Table)
+        .col(DeployTimestamp::Timestamp)
+        .to_owned()
+}
+
+/// Records that the deploy was accepted or processed at `timestamp`, unless a time was already
+/// recorded for that event.
+pub fn create_insert_stmt(
+    deploy_hash: String,
+    event_type_id: u8,
+    timestamp: u64,
+) -> SqResult<InsertStatement> {
+    Ok(Query::insert()
+        .into_table(DeployTimestamp::Table)
+        .columns([
+            DeployTimestamp::DeployHash,
+            DeployTimestamp::EventTypeId,
+            DeployTimestamp::Timestamp,
+        ])
+        .values(vec![
+            deploy_hash.into(),
+            event_type_id.into(),
+            timestamp.into(),
+        ])?
+        .on_conflict(
+            OnConflict::columns([DeployTimestamp::DeployHash, DeployTimestamp::EventTypeId])
+                .do_nothing()
+                .to_owned(),
+        )
+        .to_owned())
+}
+
+/// Selects at most `limit` entries with a timestamp in `[from, to)`, ordered by timestamp, deploy
+/// hash and event type. If `after` is given, only the entries following it in that order are
+/// selected.
+pub fn create_get_by_timestamp_range_stmt(
+    from: u64,
+    to: u64,
+    after: Option<(u64, String, u8)>,
+    limit: u32,
+) -> SelectStatement {
+    let mut select = Query::select();
+    select
+        .columns([
+            DeployTimestamp::DeployHash,
+            DeployTimestamp::EventTypeId,
+            DeployTimestamp::Timestamp,
+        ])
+        .from(DeployTimestamp::Table)
+        .and_where(Expr::col(DeployTimestamp::Timestamp).gte(from))
+        .and_where(Expr::col(DeployTimestamp::Timestamp).lt(to));
+    if let Some((timestamp, deploy_hash, event_type_id)) = after {
+        select.cond_where(
+            Cond::any()
+                .add(Expr::col(DeployTimestamp::Timestamp).gt(timestamp))
+                .add(
+                    Cond::all()
+                        .add(Expr::col(DeployTimestamp::Timestamp).eq(timestamp))
+                        .add(Expr::col(DeployTimestamp::DeployHash).gt(deploy_hash.clone())),
+                )
+                .add(
+                    Cond::all()
+                        .add(Expr::col(DeployTimestamp::Timestamp).eq(timestamp))
+                        .add(Expr::col(DeployTimestamp::DeployHash).eq(deploy_hash))
+                        .add(Expr::col(DeployTimestamp::EventTypeId).gt(event_type_id)),
+                ),
+        );
+    }
+    select
+        .order_by(DeployTimestamp::Timestamp, Order::Asc)
+        .order_by(DeployTimestamp::DeployHash, Order::Asc)
+        .order_by(DeployTimestamp::EventTypeId, Order::Asc)
+        .limit(limit as u64)
+        .to_owned()
+}
+
+#[test]
+fn create_get_by_timestamp_range_stmt_should_page_after_cursor() {
+    use sea_query::SqliteQueryBuilder;
+
+    let stmt =
+        create_get_by_timestamp_range_stmt(1000, 2000, Some((1500, "ab".to_string(), 2)), 10)
+            .to_string(SqliteQueryBuilder);
+
+    assert_eq!(
+        stmt,
+        "SELECT \"deploy_hash\", \"event_type_id\", \"timestamp\" FROM \"DeployTimestamp\" \
+         WHERE \"timestamp\" >= 1000 AND \"timestamp\" < 2000 AND \
+         (\"timestamp\" > 1500 OR (\"timestamp\" = 1500 AND \"deploy_hash\" > 'ab') OR \
+         (\"timestamp\" = 1500 AND \"deploy_hash\" = 'ab' AND \"event_type_id\" > 2)) \
+         ORDER BY \"timestamp\" ASC, \"deploy_hash\" ASC, \"event_type_id\" ASC LIMIT 10"
+    );
+}
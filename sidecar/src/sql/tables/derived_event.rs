@@ -0,0 +1,73 @@
+use sea_query::{
+    error::Result as SqResult, ColumnDef, Expr, Iden, Index, IndexCreateStatement, InsertStatement,
+    Order, Query, SelectStatement, Table, TableCreateStatement,
+};
+
+/// Events emitted by the configured event derivers, stored with the name of the deriver which
+/// emitted them.
+#[derive(Iden)]
+enum DerivedEvent {
+    #[iden = "DerivedEvent"]
+    Table,
+    DerivedEventId,
+    Deriver,
+    Raw,
+}
+
+pub fn create_table_stmt(is_big_integer_id: bool) -> TableCreateStatement {
+    let mut binding = ColumnDef::new(DerivedEvent::DerivedEventId);
+    let mut derived_event_id_col_definition = binding.auto_increment().not_null().primary_key();
+    if is_big_integer_id {
+        derived_event_id_col_definition = derived_event_id_col_definition.big_integer();
+    } else {
+        derived_event_id_col_definition = derived_event_id_col_definition.integer();
+    }
+    Table::create()
+        .table(DerivedEvent::Table)
+        .if_not_exists()
+        .col(derived_event_id_col_definition)
+        .col(ColumnDef::new(DerivedEvent::Deriver).string().not_null())
+        .col(ColumnDef::new(DerivedEvent::Raw).text().not_null())
+        .to_owned()
+}
+
+pub fn create_deriver_index_stmt() -> IndexCreateStatement {
+    Index::create()
+        .if_not_exists()
+        .name("IDX_DerivedEvent_Deriver")
+        .table(DerivedEvent::Table)
+        .col(DerivedEvent::Deriver)
+        .to_owned()
+}
+
+pub fn create_insert_stmt(deriver: String, raw: String) -> SqResult<InsertStatement> {
+    Query::insert()
+        .into_table(DerivedEvent::Table)
+        .columns([DerivedEvent::Deriver, DerivedEvent::Raw])
+        .values(vec![deriver.into(), raw.into()])
+        .map(|stmt| stmt.to_owned())
+}
+
+/// Selects the events emitted by the deriver in the order they were stored.
+pub fn create_get_by_deriver_stmt(deriver: String) -> SelectStatement {
+    Query::select()
+        .column(DerivedEvent::Raw)
+        .from(DerivedEvent::Table)
+        .and_where(Expr::col(DerivedEvent::Deriver).eq(deriver))
+        .order_by(DerivedEvent::DerivedEventId, Order::Asc)
+        .to_owned()
+}
+
+#[test]
+fn create_get_by_deriver_stmt_should_select_in_storage_order() {
+    use sea_query::SqliteQueryBuilder;
+
+    let stmt =
+        create_get_by_deriver_stmt("large_transfers".to_string()).to_string(SqliteQueryBuilder);
+
+    assert_eq!(
+        stmt,
+        "SELECT \"raw\" FROM \"DerivedEvent\" WHERE \"deriver\" = 'large_transfers' \
+         ORDER BY \"derived_event_id\" ASC"
+    );
+}
@@ -0,0 +1,75 @@
+use sea_query::{
+    error::Result as SqResult, ColumnDef, Expr, Iden, Index, InsertStatement, OnConflict, Order,
+    Query, SelectStatement, Table, TableCreateStatement,
+};
+
+/// Context fetched from a node's RPC server for stored blocks and deploys, keyed by the hash of
+/// the block or deploy and the kind of the enrichment.
+#[derive(Iden)]
+enum Enrichment {
+    #[iden = "Enrichment"]
+    Table,
+    EntityHash,
+    Kind,
+    Raw,
+}
+
+pub fn create_table_stmt() -> TableCreateStatement {
+    Table::create()
+        .table(Enrichment::Table)
+        .if_not_exists()
+        .col(ColumnDef::new(Enrichment::EntityHash).string().not_null())
+        .col(ColumnDef::new(Enrichment::Kind).string().not_null())
+        .col(ColumnDef::new(Enrichment::Raw).text().not_null())
+        .index(
+            Index::create()
+                .primary()
+                .name("PDX_Enrichment")
+                .col(Enrichment::EntityHash)
+                .col(Enrichment::Kind),
+        )
+        .to_owned()
+}
+
+/// Stores an enrichment, unless one of the same kind was already stored for the entity.
+pub fn create_insert_stmt(
+    entity_hash: String,
+    kind: String,
+    raw: String,
+) -> SqResult<InsertStatement> {
+    Ok(Query::insert()
+        .into_table(Enrichment::Table)
+        .columns([Enrichment::EntityHash, Enrichment::Kind, Enrichment::Raw])
+        .values(vec![entity_hash.into(), kind.into(), raw.into()])?
+        .on_conflict(
+            OnConflict::columns([Enrichment::EntityHash, Enrichment::Kind])
+                .do_nothing()
+                .to_owned(),
+        )
+        .to_owned())
+}
+
+pub fn create_get_by_entity_hash_stmt(entity_hash: String) -> SelectStatement {
+    Query::select()
+        .column(Enrichment::Raw)
+        .from(Enrichment::Table)
+        .and_where(Expr::col(Enrichment::EntityHash).eq(entity_hash))
+        .order_by(Enrichment::Kind, Order::Asc)
+        .to_owned()
+}
+
+#[test]
+fn create_insert_stmt_should_ignore_existing_enrichments() {
+    use sea_query::SqliteQueryBuilder;
+    let expected_sql = "INSERT INTO \"Enrichment\" (\"entity_hash\", \"kind\", \"raw\") VALUES ('ab', 'era_summary', '{}') ON CONFLICT (\"entity_hash\", \"kind\") DO NOTHING";
+
+    let got_sql = create_insert_stmt(
+        "ab".to_string(),
+        "era_summary".to_string(),
+        "{}".to_string(),
+    )
+    .unwrap()
+    .to_string(SqliteQueryBuilder);
+
+    assert_eq!(got_sql, expected_sql);
+}
@@ -0,0 +1,96 @@
+use sea_query::{
+    error::Result as SqResult, ColumnDef, Expr, Iden, Index, InsertStatement, OnConflict, Order,
+    Query, SelectStatement, Table, TableAlterStatement, TableCreateStatement,
+};
+
+/// The validators of the eras, as announced by the switch blocks of the eras preceding them.
+#[derive(Iden)]
+pub enum EraValidator {
+    #[iden = "EraValidator"]
+    Table,
+    EraId,
+    PublicKey,
+    /// Decimal weight of the validator in the era. Unknown for validators stored before it was
+    /// recorded.
+    Weight,
+}
+
+pub fn create_table_stmt() -> TableCreateStatement {
+    Table::create()
+        .table(EraValidator::Table)
+        .if_not_exists()
+        .col(ColumnDef::new(EraValidator::EraId).big_integer().not_null())
+        .col(ColumnDef::new(EraValidator::PublicKey).string().not_null())
+        .index(
+            Index::create()
+                .primary()
+                .name("PDX_EraValidator")
+                .col(EraValidator::EraId)
+                .col(EraValidator::PublicKey),
+        )
+        .to_owned()
+}
+
+/// Adds the column of the weights of the validators.
+pub fn create_add_weight_column_stmt() -> TableAlterStatement {
+    Table::alter()
+        .table(EraValidator::Table)
+        .add_column(ColumnDef::new(EraValidator::Weight).string().null())
+        .to_owned()
+}
+
+/// Records that the validator with the hex-encoded `public_key` is a validator of the era with the
+/// decimal `weight`. If the validator was already recorded, only its weight is set, as validators
+/// stored before the weights were recorded don't have one.
+pub fn create_insert_stmt(
+    era_id: u64,
+    public_key: String,
+    weight: String,
+) -> SqResult<InsertStatement> {
+    Ok(Query::insert()
+        .into_table(EraValidator::Table)
+        .columns([
+            EraValidator::EraId,
+            EraValidator::PublicKey,
+            EraValidator::Weight,
+        ])
+        .values(vec![era_id.into(), public_key.into(), weight.into()])?
+        .on_conflict(
+            OnConflict::columns([EraValidator::EraId, EraValidator::PublicKey])
+                .update_column(EraValidator::Weight)
+                .to_owned(),
+        )
+        .to_owned())
+}
+
+pub fn create_get_by_era_stmt(era_id: u64) -> SelectStatement {
+    Query::select()
+        .column(EraValidator::PublicKey)
+        .from(EraValidator::Table)
+        .and_where(Expr::col(EraValidator::EraId).eq(era_id))
+        .order_by(EraValidator::PublicKey, Order::Asc)
+        .to_owned()
+}
+
+/// Selects the validators of the era along with their weights, if known.
+pub fn create_get_weights_by_era_stmt(era_id: u64) -> SelectStatement {
+    Query::select()
+        .columns([EraValidator::PublicKey, EraValidator::Weight])
+        .from(EraValidator::Table)
+        .and_where(Expr::col(EraValidator::EraId).eq(era_id))
+        .order_by(EraValidator::PublicKey, Order::Asc)
+        .to_owned()
+}
+
+#[test]
+fn create_get_by_era_stmt_should_select_validators_of_era() {
+    use sea_query::SqliteQueryBuilder;
+
+    let stmt = create_get_by_era_stmt(12).to_string(SqliteQueryBuilder);
+
+    assert_eq!(
+        stmt,
+        "SELECT \"public_key\" FROM \"EraValidator\" WHERE \"era_id\" = 12 \
+         ORDER BY \"public_key\" ASC"
+    );
+}
@@ -0,0 +1,130 @@
+use sea_query::{
+    error::Result as SqResult, ColumnDef, Expr, Iden, InsertStatement, OnConflict, Query,
+    SelectStatement, Table, TableCreateStatement, UpdateStatement,
+};
+
+/// The allocator state is kept in a single row.
+const ALLOCATOR_ID: u8 = 1;
+/// Event ids wrap around at `u32::MAX`.
+const EVENT_ID_MODULUS: u64 = 1 << 32;
+
+#[derive(Iden)]
+pub(crate) enum EventIdAllocator {
+    #[iden = "EventIdAllocator"]
+    Table,
+    AllocatorId,
+    NextEventId,
+    UpdatedTimestamp,
+}
+
+pub fn create_table_stmt() -> TableCreateStatement {
+    Table::create()
+        .table(EventIdAllocator::Table)
+        .if_not_exists()
+        .col(
+            ColumnDef::new(EventIdAllocator::AllocatorId)
+                .integer()
+                .not_null()
+                .primary_key(),
+        )
+        .col(
+            ColumnDef::new(EventIdAllocator::NextEventId)
+                .big_integer()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(EventIdAllocator::UpdatedTimestamp)
+                .big_integer()
+                .not_null(),
+        )
+        .to_owned()
+}
+
+/// Creates the allocator state starting at `next_event_id`, unless it already exists.
+pub fn create_initialise_stmt(
+    next_event_id: u32,
+    updated_timestamp: u64,
+) -> SqResult<InsertStatement> {
+    Ok(Query::insert()
+        .into_table(EventIdAllocator::Table)
+        .columns([
+            EventIdAllocator::AllocatorId,
+            EventIdAllocator::NextEventId,
+            EventIdAllocator::UpdatedTimestamp,
+        ])
+        .values(vec![
+            ALLOCATOR_ID.into(),
+            next_event_id.into(),
+            updated_timestamp.into(),
+        ])?
+        .on_conflict(
+            OnConflict::column(EventIdAllocator::AllocatorId)
+                .do_nothing()
+                .to_owned(),
+        )
+        .to_owned())
+}
+
+/// Advances the allocator by `count` in a single statement and returns the new `next_event_id`, so
+/// the reserved ids are the `count` ids before the returned value.
+pub fn create_reserve_stmt(count: u32, updated_timestamp: u64) -> UpdateStatement {
+    Query::update()
+        .table(EventIdAllocator::Table)
+        .value(
+            EventIdAllocator::NextEventId,
+            Expr::cust(format!(
+                "(\"next_event_id\" + {}) % {}",
+                count, EVENT_ID_MODULUS
+            )),
+        )
+        .value(EventIdAllocator::UpdatedTimestamp, updated_timestamp)
+        .and_where(Expr::col(EventIdAllocator::AllocatorId).eq(ALLOCATOR_ID))
+        .returning_col(EventIdAllocator::NextEventId)
+        .to_owned()
+}
+
+pub fn create_get_stmt() -> SelectStatement {
+    Query::select()
+        .columns([
+            EventIdAllocator::NextEventId,
+            EventIdAllocator::UpdatedTimestamp,
+        ])
+        .from(EventIdAllocator::Table)
+        .and_where(Expr::col(EventIdAllocator::AllocatorId).eq(ALLOCATOR_ID))
+        .to_owned()
+}
+
+/// Returns the first of the `count` ids reserved by the statement from [create_reserve_stmt] which
+/// returned `next_event_id`.
+pub fn first_reserved_event_id(next_event_id: u64, count: u32) -> u32 {
+    ((next_event_id + EVENT_ID_MODULUS - u64::from(count)) % EVENT_ID_MODULUS) as u32
+}
+
+#[test]
+fn create_initialise_stmt_sql() {
+    use sea_query::SqliteQueryBuilder;
+    let expected_sql = "INSERT INTO \"EventIdAllocator\" (\"allocator_id\", \"next_event_id\", \"updated_timestamp\") VALUES (1, 15, 1700000000) ON CONFLICT (\"allocator_id\") DO NOTHING";
+
+    let got_sql = create_initialise_stmt(15, 1_700_000_000)
+        .unwrap()
+        .to_string(SqliteQueryBuilder);
+
+    assert_eq!(got_sql, expected_sql);
+}
+
+#[test]
+fn create_reserve_stmt_sql() {
+    use sea_query::SqliteQueryBuilder;
+    let expected_sql = "UPDATE \"EventIdAllocator\" SET \"next_event_id\" = (\"next_event_id\" + 100) % 4294967296, \"updated_timestamp\" = 1700000000 WHERE \"allocator_id\" = 1 RETURNING \"next_event_id\"";
+
+    let got_sql = create_reserve_stmt(100, 1_700_000_000).to_string(SqliteQueryBuilder);
+
+    assert_eq!(got_sql, expected_sql);
+}
+
+#[test]
+fn first_reserved_event_id_should_wrap() {
+    assert_eq!(first_reserved_event_id(1, 1), 0);
+    assert_eq!(first_reserved_event_id(0, 1), u32::MAX);
+    assert_eq!(first_reserved_event_id(3, 5), u32::MAX - 1);
+}
@@ -1,6 +1,6 @@
 use sea_query::{
     error::Result as SqResult, Asterisk, ColumnDef, Expr, ForeignKey, ForeignKeyAction, Iden,
-    Index, InsertStatement, Query, SelectStatement, Table, TableCreateStatement,
+    Index, InsertStatement, OnConflict, Query, SelectStatement, Table, TableCreateStatement,
 };
 
 use super::event_type::EventType;
@@ -78,6 +78,8 @@ pub fn create_table_stmt(is_big_integer_id: bool) -> TableCreateStatement {
         .to_owned()
 }
 
+/// Logs an event received from a source, returning the id of its entry. An event the source
+/// sent before keeps the entry it was logged under.
 pub fn create_insert_stmt(
     event_type_id: u8,
     event_source_address: &str,
@@ -97,8 +99,20 @@ pub fn create_insert_stmt(
             event_source_address.into(),
             event_id.into(),
             event_key.into(),
-        ])
-        .map(|stmt| stmt.returning_col(EventLog::EventLogId).to_owned())?;
+        ])?
+        .on_conflict(
+            // Updating the entry without changing it makes the statement return its id.
+            OnConflict::columns([
+                EventLog::EventSourceAddress,
+                EventLog::EventId,
+                EventLog::EventTypeId,
+                EventLog::EventKey,
+            ])
+            .update_column(EventLog::EventKey)
+            .to_owned(),
+        )
+        .returning_col(EventLog::EventLogId)
+        .to_owned();
 
     Ok(insert_stmt)
 }
@@ -109,3 +123,40 @@ pub fn count() -> SelectStatement {
         .from(EventLog::Table)
         .to_owned()
 }
+
+/// Selects the id of the latest entry logged before `inserted_before`, formatted as returned by
+/// [timestamp_literal]. Ids grow with the time entries are logged, so all entries with an id up to
+/// the selected one were logged before `inserted_before`.
+pub fn create_get_last_inserted_before_stmt(inserted_before: &str) -> SelectStatement {
+    Query::select()
+        .expr(Expr::col(EventLog::EventLogId).max())
+        .from(EventLog::Table)
+        .and_where(Expr::col(EventLog::InsertedTimestamp).lt(inserted_before))
+        .to_owned()
+}
+
+/// Formats milliseconds since the UNIX epoch the way the databases store `inserted_timestamp`,
+/// e.g. `2023-05-04 12:34:56`, so that it can be compared with the stored timestamps.
+pub fn timestamp_literal(millis_since_epoch: u64) -> String {
+    casper_types::Timestamp::from(millis_since_epoch)
+        .to_string()
+        .replacen('T', " ", 1)
+        .chars()
+        .take(19)
+        .collect()
+}
+
+#[test]
+fn create_get_last_inserted_before_stmt_should_compare_with_timestamp_literal() {
+    use sea_query::SqliteQueryBuilder;
+
+    let inserted_before = timestamp_literal(1_683_203_696_789);
+    let stmt = create_get_last_inserted_before_stmt(&inserted_before).to_string(SqliteQueryBuilder);
+
+    assert_eq!(inserted_before, "2023-05-04 12:34:56");
+    assert_eq!(
+        stmt,
+        "SELECT MAX(\"event_log_id\") FROM \"event_log\" \
+         WHERE \"inserted_timestamp\" < '2023-05-04 12:34:56'"
+    );
+}
@@ -10,16 +10,7 @@ pub(super) enum EventType {
     EventTypeName,
 }
 
-pub enum EventTypeId {
-    BlockAdded = 1,
-    DeployAccepted = 2,
-    DeployExpired = 3,
-    DeployProcessed = 4,
-    Fault = 5,
-    FinalitySignature = 6,
-    Step = 7,
-    Shutdown = 8,
-}
+pub use casper_event_storage::EventTypeId;
 
 pub fn create_table_stmt() -> TableCreateStatement {
     Table::create()
@@ -78,6 +69,20 @@ pub fn create_initialise_stmt() -> SqResult<InsertStatement> {
         .to_owned())
 }
 
+/// Registers the type of events the sidecar doesn't know, added after the initial types.
+pub fn create_insert_unknown_stmt() -> SqResult<InsertStatement> {
+    Ok(Query::insert()
+        .into_table(EventType::Table)
+        .columns([EventType::EventTypeId, EventType::EventTypeName])
+        .values(vec![(EventTypeId::Unknown as u8).into(), "Unknown".into()])?
+        .on_conflict(
+            OnConflict::column(EventType::EventTypeId)
+                .do_nothing()
+                .to_owned(),
+        )
+        .to_owned())
+}
+
 #[test]
 fn create_initialise_stmt_sql() {
     use sea_query::SqliteQueryBuilder;
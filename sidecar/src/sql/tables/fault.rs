@@ -1,6 +1,6 @@
 use sea_query::{
     error::Result as SqResult, ColumnDef, Expr, ForeignKey, ForeignKeyAction, Iden, Index,
-    InsertStatement, Query, SelectStatement, Table, TableCreateStatement,
+    InsertStatement, OnConflict, Query, SelectStatement, Table, TableCreateStatement,
 };
 
 use super::event_log::EventLog;
@@ -48,13 +48,14 @@ pub fn create_table_stmt(db_supports_unsigned: bool) -> TableCreateStatement {
         .to_owned()
 }
 
+/// Stores a fault, unless one was already stored for the validator in the era.
 pub fn create_insert_stmt(
     era: u64,
     public_key: String,
     raw: String,
     event_log_id: u64,
 ) -> SqResult<InsertStatement> {
-    Query::insert()
+    Ok(Query::insert()
         .into_table(Fault::Table)
         .columns([Fault::Era, Fault::PublicKey, Fault::Raw, Fault::EventLogId])
         .values(vec![
@@ -62,8 +63,13 @@ pub fn create_insert_stmt(
             public_key.into(),
             raw.into(),
             event_log_id.into(),
-        ])
-        .map(|stmt| stmt.to_owned())
+        ])?
+        .on_conflict(
+            OnConflict::columns([Fault::Era, Fault::PublicKey])
+                .do_nothing()
+                .to_owned(),
+        )
+        .to_owned())
 }
 
 pub fn create_get_faults_by_public_key_stmt(public_key: String) -> SelectStatement {
@@ -1,6 +1,7 @@
 use sea_query::{
-    error::Result as SqResult, ColumnDef, Expr, ForeignKey, ForeignKeyAction, Iden, Index,
-    InsertStatement, Query, SelectStatement, Table, TableCreateStatement,
+    error::Result as SqResult, ColumnDef, DeleteStatement, Expr, ForeignKey, ForeignKeyAction,
+    Iden, Index, InsertStatement, OnConflict, Order, Query, SelectStatement, Table,
+    TableAlterStatement, TableCreateStatement,
 };
 
 use super::event_log::EventLog;
@@ -13,6 +14,8 @@ enum FinalitySignature {
     PublicKey,
     Raw,
     EventLogId,
+    /// Set if the signature failed cryptographic verification at ingestion.
+    Invalid,
 }
 
 pub fn create_table_stmt() -> TableCreateStatement {
@@ -53,33 +56,105 @@ pub fn create_table_stmt() -> TableCreateStatement {
         .to_owned()
 }
 
+/// Adds the column marking signatures which failed verification. Signatures stored before it was
+/// added are treated as valid.
+pub fn create_add_invalid_column_stmt() -> TableAlterStatement {
+    Table::alter()
+        .table(FinalitySignature::Table)
+        .add_column(
+            ColumnDef::new(FinalitySignature::Invalid)
+                .boolean()
+                .not_null()
+                .default(false),
+        )
+        .to_owned()
+}
+
+/// Stores a finality signature, unless one was already stored for the block by the same signer.
 pub fn create_insert_stmt(
     block_hash: String,
     public_key: String,
     raw: String,
     event_log_id: u64,
+    invalid: bool,
 ) -> SqResult<InsertStatement> {
-    Query::insert()
+    Ok(Query::insert()
         .into_table(FinalitySignature::Table)
         .columns([
             FinalitySignature::BlockHash,
             FinalitySignature::PublicKey,
             FinalitySignature::Raw,
             FinalitySignature::EventLogId,
+            FinalitySignature::Invalid,
         ])
         .values(vec![
             block_hash.into(),
             public_key.into(),
             raw.into(),
             event_log_id.into(),
-        ])
-        .map(|stmt| stmt.to_owned())
+            invalid.into(),
+        ])?
+        .on_conflict(
+            OnConflict::columns([FinalitySignature::BlockHash, FinalitySignature::PublicKey])
+                .do_nothing()
+                .to_owned(),
+        )
+        .to_owned())
 }
 
+/// Selects the signatures of the block, excluding those which failed verification.
 pub fn create_get_finality_signatures_by_block_stmt(block_hash: String) -> SelectStatement {
     Query::select()
         .column(FinalitySignature::Raw)
         .from(FinalitySignature::Table)
         .and_where(Expr::col(FinalitySignature::BlockHash).eq(block_hash))
+        .and_where(Expr::col(FinalitySignature::Invalid).eq(false))
+        .to_owned()
+}
+
+/// Selects, ordered by hash, at most `limit` blocks whose valid signatures were all logged up to
+/// the event log entry `event_log_id`. If `after` is given, only the blocks with a greater hash are
+/// selected.
+pub fn create_get_blocks_signed_up_to_stmt(
+    event_log_id: u64,
+    after: Option<String>,
+    limit: u32,
+) -> SelectStatement {
+    let mut select = Query::select();
+    select
+        .column(FinalitySignature::BlockHash)
+        .from(FinalitySignature::Table)
+        .and_where(Expr::col(FinalitySignature::Invalid).eq(false));
+    if let Some(block_hash) = after {
+        select.and_where(Expr::col(FinalitySignature::BlockHash).gt(block_hash));
+    }
+    select
+        .group_by_col(FinalitySignature::BlockHash)
+        .and_having(Expr::expr(Expr::col(FinalitySignature::EventLogId).max()).lte(event_log_id))
+        .order_by(FinalitySignature::BlockHash, Order::Asc)
+        .limit(limit as u64)
+        .to_owned()
+}
+
+/// Deletes the signatures of the block, including those which failed verification.
+pub fn create_delete_by_block_stmt(block_hash: String) -> DeleteStatement {
+    Query::delete()
+        .from_table(FinalitySignature::Table)
+        .and_where(Expr::col(FinalitySignature::BlockHash).eq(block_hash))
         .to_owned()
 }
+
+#[test]
+fn create_get_blocks_signed_up_to_stmt_should_select_blocks_by_newest_signature() {
+    use sea_query::SqliteQueryBuilder;
+
+    let stmt = create_get_blocks_signed_up_to_stmt(7, Some("ab".to_string()), 10)
+        .to_string(SqliteQueryBuilder);
+
+    assert_eq!(
+        stmt,
+        "SELECT \"block_hash\" FROM \"FinalitySignature\" \
+         WHERE \"invalid\" = FALSE AND \"block_hash\" > 'ab' GROUP BY \"block_hash\" \
+         HAVING MAX(\"event_log_id\") <= 7 ORDER BY \"block_hash\" ASC LIMIT 10"
+    );
+}
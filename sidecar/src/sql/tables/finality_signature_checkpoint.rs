@@ -0,0 +1,84 @@
+use sea_query::{
+    error::Result as SqResult, ColumnDef, Expr, Iden, InsertStatement, OnConflict, Query,
+    SelectStatement, Table, TableCreateStatement,
+};
+
+/// The signers and signed weight of the blocks whose finality signatures were replaced with a
+/// checkpoint, keyed by block hash.
+#[derive(Iden)]
+enum FinalitySignatureCheckpoint {
+    #[iden = "FinalitySignatureCheckpoint"]
+    Table,
+    BlockHash,
+    EraId,
+    Raw,
+}
+
+pub fn create_table_stmt() -> TableCreateStatement {
+    Table::create()
+        .table(FinalitySignatureCheckpoint::Table)
+        .if_not_exists()
+        .col(
+            ColumnDef::new(FinalitySignatureCheckpoint::BlockHash)
+                .string()
+                .not_null()
+                .primary_key(),
+        )
+        .col(
+            ColumnDef::new(FinalitySignatureCheckpoint::EraId)
+                .big_unsigned()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(FinalitySignatureCheckpoint::Raw)
+                .text()
+                .not_null(),
+        )
+        .to_owned()
+}
+
+/// Records the checkpoint of the block, replacing any previous one. `raw` is the JSON of the
+/// checkpoint.
+pub fn create_upsert_stmt(
+    block_hash: String,
+    era_id: u64,
+    raw: String,
+) -> SqResult<InsertStatement> {
+    Ok(Query::insert()
+        .into_table(FinalitySignatureCheckpoint::Table)
+        .columns([
+            FinalitySignatureCheckpoint::BlockHash,
+            FinalitySignatureCheckpoint::EraId,
+            FinalitySignatureCheckpoint::Raw,
+        ])
+        .values(vec![block_hash.into(), era_id.into(), raw.into()])?
+        .on_conflict(
+            OnConflict::column(FinalitySignatureCheckpoint::BlockHash)
+                .update_column(FinalitySignatureCheckpoint::Raw)
+                .to_owned(),
+        )
+        .to_owned())
+}
+
+pub fn create_get_by_block_stmt(block_hash: String) -> SelectStatement {
+    Query::select()
+        .column(FinalitySignatureCheckpoint::Raw)
+        .from(FinalitySignatureCheckpoint::Table)
+        .and_where(Expr::col(FinalitySignatureCheckpoint::BlockHash).eq(block_hash))
+        .to_owned()
+}
+
+#[test]
+fn create_upsert_stmt_should_replace_the_checkpoint_of_the_block() {
+    use sea_query::SqliteQueryBuilder;
+
+    let stmt = create_upsert_stmt("ab".to_string(), 12, "{}".to_string())
+        .unwrap()
+        .to_string(SqliteQueryBuilder);
+
+    assert_eq!(
+        stmt,
+        "INSERT INTO \"FinalitySignatureCheckpoint\" (\"block_hash\", \"era_id\", \"raw\") \
+         VALUES ('ab', 12, '{}') ON CONFLICT (\"block_hash\") DO UPDATE SET \"raw\" = \"excluded\".\"raw\""
+    );
+}
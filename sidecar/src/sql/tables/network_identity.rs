@@ -0,0 +1,89 @@
+use sea_query::{
+    error::Result as SqResult, ColumnDef, Expr, Iden, InsertStatement, OnConflict, Query,
+    SelectStatement, Table, TableCreateStatement,
+};
+
+/// The network of the stored events is kept in a single row.
+const IDENTITY_ID: u8 = 1;
+
+/// The network the stored events were received from, recorded the first time the nodes reported
+/// it.
+#[derive(Iden)]
+enum NetworkIdentity {
+    #[iden = "NetworkIdentity"]
+    Table,
+    IdentityId,
+    ChainspecName,
+    StartingStateRootHash,
+}
+
+pub fn create_table_stmt() -> TableCreateStatement {
+    Table::create()
+        .table(NetworkIdentity::Table)
+        .if_not_exists()
+        .col(
+            ColumnDef::new(NetworkIdentity::IdentityId)
+                .integer()
+                .not_null()
+                .primary_key(),
+        )
+        .col(
+            ColumnDef::new(NetworkIdentity::ChainspecName)
+                .string()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(NetworkIdentity::StartingStateRootHash)
+                .string()
+                .not_null(),
+        )
+        .to_owned()
+}
+
+/// Records the network, unless a network is already recorded.
+pub fn create_insert_stmt(
+    chainspec_name: String,
+    starting_state_root_hash: String,
+) -> SqResult<InsertStatement> {
+    Ok(Query::insert()
+        .into_table(NetworkIdentity::Table)
+        .columns([
+            NetworkIdentity::IdentityId,
+            NetworkIdentity::ChainspecName,
+            NetworkIdentity::StartingStateRootHash,
+        ])
+        .values(vec![
+            IDENTITY_ID.into(),
+            chainspec_name.into(),
+            starting_state_root_hash.into(),
+        ])?
+        .on_conflict(
+            OnConflict::column(NetworkIdentity::IdentityId)
+                .do_nothing()
+                .to_owned(),
+        )
+        .to_owned())
+}
+
+pub fn create_get_stmt() -> SelectStatement {
+    Query::select()
+        .columns([
+            NetworkIdentity::ChainspecName,
+            NetworkIdentity::StartingStateRootHash,
+        ])
+        .from(NetworkIdentity::Table)
+        .and_where(Expr::col(NetworkIdentity::IdentityId).eq(IDENTITY_ID))
+        .to_owned()
+}
+
+#[test]
+fn create_insert_stmt_sql() {
+    use sea_query::SqliteQueryBuilder;
+    let expected_sql = "INSERT INTO \"NetworkIdentity\" (\"identity_id\", \"chainspec_name\", \"starting_state_root_hash\") VALUES (1, 'casper', 'ab') ON CONFLICT (\"identity_id\") DO NOTHING";
+
+    let got_sql = create_insert_stmt("casper".to_string(), "ab".to_string())
+        .unwrap()
+        .to_string(SqliteQueryBuilder);
+
+    assert_eq!(got_sql, expected_sql);
+}
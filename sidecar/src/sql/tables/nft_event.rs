@@ -0,0 +1,125 @@
+use sea_query::{
+    error::Result as SqResult, ColumnDef, DeleteStatement, Expr, Iden, Index, IndexCreateStatement,
+    InsertStatement, OnConflict, Order, Query, SelectStatement, Table, TableCreateStatement,
+};
+
+/// Events of CEP-78 NFT collections decoded from the events their contracts emitted, keyed by the
+/// hash of the contract and the id of the event, which numbers the events of a contract in the
+/// order they were emitted.
+#[derive(Iden)]
+enum NftEvent {
+    #[iden = "NftEvent"]
+    Table,
+    ContractHash,
+    EventId,
+    TokenId,
+    DeployHash,
+    Raw,
+}
+
+pub fn create_table_stmt() -> TableCreateStatement {
+    Table::create()
+        .table(NftEvent::Table)
+        .if_not_exists()
+        .col(ColumnDef::new(NftEvent::ContractHash).string().not_null())
+        .col(ColumnDef::new(NftEvent::EventId).big_unsigned().not_null())
+        .col(ColumnDef::new(NftEvent::TokenId).string().not_null())
+        .col(ColumnDef::new(NftEvent::DeployHash).string().not_null())
+        .col(ColumnDef::new(NftEvent::Raw).text().not_null())
+        .index(
+            Index::create()
+                .primary()
+                .name("PDX_NftEvent")
+                .col(NftEvent::ContractHash)
+                .col(NftEvent::EventId),
+        )
+        .to_owned()
+}
+
+pub fn create_token_id_index_stmt() -> IndexCreateStatement {
+    Index::create()
+        .if_not_exists()
+        .name("IDX_NftEvent_TokenId")
+        .table(NftEvent::Table)
+        .col(NftEvent::ContractHash)
+        .col(NftEvent::TokenId)
+        .col(NftEvent::EventId)
+        .to_owned()
+}
+
+/// Records the event, unless already recorded. `raw` is the JSON of the event as served.
+pub fn create_insert_stmt(
+    contract_hash: String,
+    event_id: u64,
+    token_id: String,
+    deploy_hash: String,
+    raw: String,
+) -> SqResult<InsertStatement> {
+    Ok(Query::insert()
+        .into_table(NftEvent::Table)
+        .columns([
+            NftEvent::ContractHash,
+            NftEvent::EventId,
+            NftEvent::TokenId,
+            NftEvent::DeployHash,
+            NftEvent::Raw,
+        ])
+        .values(vec![
+            contract_hash.into(),
+            event_id.into(),
+            token_id.into(),
+            deploy_hash.into(),
+            raw.into(),
+        ])?
+        .on_conflict(
+            OnConflict::columns([NftEvent::ContractHash, NftEvent::EventId])
+                .do_nothing()
+                .to_owned(),
+        )
+        .to_owned())
+}
+
+/// Selects at most `limit` events of the collection with the hex-encoded `contract_hash`, ordered
+/// by event id. If `token_id` is given, only the events of that token are selected, and if `after`
+/// is given, only the events with a greater event id.
+pub fn create_get_by_contract_stmt(
+    contract_hash: &str,
+    token_id: Option<&str>,
+    after: Option<u64>,
+    limit: u32,
+) -> SelectStatement {
+    let mut select = Query::select();
+    select
+        .column(NftEvent::Raw)
+        .from(NftEvent::Table)
+        .and_where(Expr::col(NftEvent::ContractHash).eq(contract_hash));
+    if let Some(token_id) = token_id {
+        select.and_where(Expr::col(NftEvent::TokenId).eq(token_id));
+    }
+    if let Some(event_id) = after {
+        select.and_where(Expr::col(NftEvent::EventId).gt(event_id));
+    }
+    select
+        .order_by(NftEvent::EventId, Order::Asc)
+        .limit(limit as u64)
+        .to_owned()
+}
+
+/// Deletes all the NFT events, to be derived again from the stored processed deploys.
+pub fn create_delete_all_stmt() -> DeleteStatement {
+    Query::delete().from_table(NftEvent::Table).to_owned()
+}
+
+#[test]
+fn create_get_by_contract_stmt_should_select_events_of_token() {
+    use sea_query::SqliteQueryBuilder;
+
+    let stmt =
+        create_get_by_contract_stmt("ab", Some("7"), Some(3), 10).to_string(SqliteQueryBuilder);
+
+    assert_eq!(
+        stmt,
+        "SELECT \"raw\" FROM \"NftEvent\" WHERE \"contract_hash\" = 'ab' AND \"token_id\" = '7' \
+         AND \"event_id\" > 3 ORDER BY \"event_id\" ASC LIMIT 10"
+    );
+}
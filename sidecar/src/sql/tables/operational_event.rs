@@ -0,0 +1,86 @@
+use sea_query::{
+    error::Result as SqResult, ColumnDef, Expr, Iden, InsertStatement, Order, Query,
+    SelectStatement, Table, TableCreateStatement,
+};
+
+/// Events of the sidecar itself, such as its starts and stops, one row per event.
+#[derive(Iden)]
+pub(crate) enum OperationalEvent {
+    #[iden = "OperationalEvent"]
+    Table,
+    OperationalEventId,
+    RecordedTimestamp,
+    Kind,
+    Details,
+}
+
+pub fn create_table_stmt(is_big_integer_id: bool) -> TableCreateStatement {
+    let mut binding = ColumnDef::new(OperationalEvent::OperationalEventId);
+    let mut operational_event_id_col_definition = binding.auto_increment().not_null().primary_key();
+    if is_big_integer_id {
+        operational_event_id_col_definition = operational_event_id_col_definition.big_integer();
+    } else {
+        operational_event_id_col_definition = operational_event_id_col_definition.integer();
+    }
+    Table::create()
+        .table(OperationalEvent::Table)
+        .if_not_exists()
+        .col(operational_event_id_col_definition)
+        // Stored as seconds since the UNIX epoch so that sqlite and postgresql return the same
+        // representation.
+        .col(
+            ColumnDef::new(OperationalEvent::RecordedTimestamp)
+                .big_integer()
+                .not_null(),
+        )
+        .col(ColumnDef::new(OperationalEvent::Kind).text().not_null())
+        .col(ColumnDef::new(OperationalEvent::Details).text())
+        .to_owned()
+}
+
+pub fn create_insert_stmt(
+    recorded_timestamp: u64,
+    kind: &str,
+    details: Option<&str>,
+) -> SqResult<InsertStatement> {
+    Query::insert()
+        .into_table(OperationalEvent::Table)
+        .columns([
+            OperationalEvent::RecordedTimestamp,
+            OperationalEvent::Kind,
+            OperationalEvent::Details,
+        ])
+        .values(vec![recorded_timestamp.into(), kind.into(), details.into()])
+        .map(|stmt| {
+            stmt.returning_col(OperationalEvent::OperationalEventId)
+                .to_owned()
+        })
+}
+
+/// Selects at most `limit` events with an id greater or equal to `from_id`, oldest first.
+pub fn create_list_stmt(from_id: u64, limit: u32) -> SelectStatement {
+    Query::select()
+        .columns([
+            OperationalEvent::OperationalEventId,
+            OperationalEvent::RecordedTimestamp,
+            OperationalEvent::Kind,
+            OperationalEvent::Details,
+        ])
+        .from(OperationalEvent::Table)
+        .and_where(Expr::col(OperationalEvent::OperationalEventId).gte(from_id))
+        .order_by(OperationalEvent::OperationalEventId, Order::Asc)
+        .limit(limit as u64)
+        .to_owned()
+}
+
+#[test]
+fn create_insert_stmt_should_produce_insert_sql() {
+    use sea_query::SqliteQueryBuilder;
+    let expected_sql = "INSERT INTO \"OperationalEvent\" (\"recorded_timestamp\", \"kind\", \"details\") VALUES (1700000000, 'started', NULL) RETURNING \"operational_event_id\"";
+
+    let got_sql = create_insert_stmt(1_700_000_000, "started", None)
+        .unwrap()
+        .to_string(SqliteQueryBuilder);
+
+    assert_eq!(got_sql, expected_sql);
+}
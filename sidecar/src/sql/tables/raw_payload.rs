@@ -0,0 +1,105 @@
+use sea_query::{Alias, DeleteStatement, Expr, Order, Query, SelectStatement};
+
+use super::event_log::EventLog;
+
+/// Selects the raw payloads of the latest events stored in `table`, which has to be one of the
+/// event tables having `raw` and `event_log_id` columns.
+pub fn create_get_latest_stmt(table: &str, limit: u32) -> SelectStatement {
+    Query::select()
+        .column(Alias::new("raw"))
+        .from(Alias::new(table))
+        .order_by(Alias::new("event_log_id"), Order::Desc)
+        .limit(u64::from(limit))
+        .to_owned()
+}
+
+/// Selects the raw payloads of at most `limit` events stored in `table` after the event with the
+/// `after` event log id, oldest first, along with their source, event id and, as text, the time
+/// they were stored at.
+pub fn create_get_after_stmt(table: &str, after: u64, limit: u32) -> SelectStatement {
+    let event_log_id = || (Alias::new(table), Alias::new("event_log_id"));
+    Query::select()
+        .column(event_log_id())
+        .column((Alias::new(table), Alias::new("raw")))
+        .column((EventLog::Table, EventLog::EventSourceAddress))
+        .column((EventLog::Table, EventLog::EventId))
+        // Timestamps are read as text as they are typed differently by each database.
+        .expr_as(
+            Expr::cust("CAST(\"event_log\".\"inserted_timestamp\" AS TEXT)"),
+            Alias::new("inserted_timestamp"),
+        )
+        .from(Alias::new(table))
+        .inner_join(
+            EventLog::Table,
+            Expr::col(event_log_id()).equals((EventLog::Table, EventLog::EventLogId)),
+        )
+        .and_where(Expr::col(event_log_id()).gt(after))
+        .order_by(event_log_id(), Order::Asc)
+        .limit(u64::from(limit))
+        .to_owned()
+}
+
+/// Selects the event log id of the event stored in `table` which is preceded by `offset` newer
+/// events.
+pub fn create_get_nth_newest_event_log_id_stmt(table: &str, offset: u64) -> SelectStatement {
+    Query::select()
+        .column(Alias::new("event_log_id"))
+        .from(Alias::new(table))
+        .order_by(Alias::new("event_log_id"), Order::Desc)
+        .limit(1)
+        .offset(offset)
+        .to_owned()
+}
+
+/// Deletes the events stored in `table` up to and including the one with the `up_to` event log id.
+pub fn create_delete_up_to_stmt(table: &str, up_to: u64) -> DeleteStatement {
+    Query::delete()
+        .from_table(Alias::new(table))
+        .and_where(Expr::col(Alias::new("event_log_id")).lte(up_to))
+        .to_owned()
+}
+
+#[test]
+fn create_get_latest_stmt_should_select_newest_first() {
+    use sea_query::SqliteQueryBuilder;
+
+    let stmt = create_get_latest_stmt("DeployAccepted", 100).to_string(SqliteQueryBuilder);
+
+    assert_eq!(
+        stmt,
+        "SELECT \"raw\" FROM \"DeployAccepted\" ORDER BY \"event_log_id\" DESC LIMIT 100"
+    );
+}
+
+#[test]
+fn create_get_after_stmt_should_select_oldest_first() {
+    use sea_query::SqliteQueryBuilder;
+
+    let stmt = create_get_after_stmt("Step", 10, 100).to_string(SqliteQueryBuilder);
+
+    assert_eq!(
+        stmt,
+        "SELECT \"Step\".\"event_log_id\", \"Step\".\"raw\", \"event_log\".\"event_source_address\", \
+         \"event_log\".\"event_id\", CAST(\"event_log\".\"inserted_timestamp\" AS TEXT) AS \"inserted_timestamp\" \
+         FROM \"Step\" INNER JOIN \"event_log\" ON \"Step\".\"event_log_id\" = \"event_log\".\"event_log_id\" \
+         WHERE \"Step\".\"event_log_id\" > 10 ORDER BY \"Step\".\"event_log_id\" ASC LIMIT 100"
+    );
+}
+
+#[test]
+fn create_get_nth_newest_event_log_id_stmt_should_skip_newest_events() {
+    use sea_query::SqliteQueryBuilder;
+
+    let stmt =
+        create_get_nth_newest_event_log_id_stmt("BlockAdded", 1000).to_string(SqliteQueryBuilder);
+
+    assert_eq!(
+        stmt,
+        "SELECT \"event_log_id\" FROM \"BlockAdded\" ORDER BY \"event_log_id\" DESC \
+         LIMIT 1 OFFSET 1000"
+    );
+    assert_eq!(
+        create_delete_up_to_stmt("BlockAdded", 42).to_string(SqliteQueryBuilder),
+        "DELETE FROM \"BlockAdded\" WHERE \"event_log_id\" <= 42"
+    );
+}
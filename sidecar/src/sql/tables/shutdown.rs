@@ -1,6 +1,6 @@
 use sea_query::{
     error::Result as SqResult, ColumnDef, ForeignKey, ForeignKeyAction, Iden, Index,
-    InsertStatement, Query, Table, TableCreateStatement,
+    InsertStatement, OnConflict, Query, Table, TableCreateStatement,
 };
 
 use super::event_log::EventLog;
@@ -52,15 +52,21 @@ pub fn create_table_stmt() -> TableCreateStatement {
         .to_owned()
 }
 
+/// Stores a shutdown of a node, unless one was already stored for the node at the same time.
 pub fn create_insert_stmt(
     event_source_address: String,
     event_log_id: u64,
 ) -> SqResult<InsertStatement> {
-    Query::insert()
+    Ok(Query::insert()
         .into_table(Shutdown::Table)
         .columns([Shutdown::EventSourceAddress, Shutdown::EventLogId])
-        .values(vec![event_source_address.into(), event_log_id.into()])
-        .map(|stmt| stmt.to_owned())
+        .values(vec![event_source_address.into(), event_log_id.into()])?
+        .on_conflict(
+            OnConflict::columns([Shutdown::EventSourceAddress, Shutdown::ShutdownTimestamp])
+                .do_nothing()
+                .to_owned(),
+        )
+        .to_owned())
 }
 
 #[test]
@@ -76,7 +82,7 @@ fn create_table_stmt_should_produce_create_table_sql() {
 #[test]
 fn create_insert_stmt_should_produce_insert_sql() {
     use sea_query::SqliteQueryBuilder;
-    let expected_sql = "INSERT INTO \"Shutdown\" (\"event_source_address\", \"event_log_id\") VALUES ('http://100.100.100.1:1782', 5)";
+    let expected_sql = "INSERT INTO \"Shutdown\" (\"event_source_address\", \"event_log_id\") VALUES ('http://100.100.100.1:1782', 5) ON CONFLICT (\"event_source_address\", \"shutdown_timestamp\") DO NOTHING";
     let address = "http://100.100.100.1:1782".to_string();
 
     let got_sql = create_insert_stmt(address, 5)
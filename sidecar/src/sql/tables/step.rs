@@ -1,6 +1,6 @@
 use sea_query::{
     error::Result as SqResult, ColumnDef, Expr, ForeignKey, ForeignKeyAction, Iden, Index,
-    InsertStatement, Query, SelectStatement, Table, TableCreateStatement,
+    InsertStatement, OnConflict, Query, SelectStatement, Table, TableCreateStatement,
 };
 
 use super::event_log::EventLog;
@@ -40,12 +40,14 @@ pub fn create_table_stmt(db_supports_unsigned: bool) -> TableCreateStatement {
         .to_owned()
 }
 
+/// Stores a step, unless one was already stored for the era.
 pub fn create_insert_stmt(era: u64, raw: String, event_log_id: u64) -> SqResult<InsertStatement> {
-    Query::insert()
+    Ok(Query::insert()
         .into_table(Step::Table)
         .columns([Step::Era, Step::Raw, Step::EventLogId])
-        .values(vec![era.into(), raw.into(), event_log_id.into()])
-        .map(|stmt| stmt.to_owned())
+        .values(vec![era.into(), raw.into(), event_log_id.into()])?
+        .on_conflict(OnConflict::columns([Step::Era]).do_nothing().to_owned())
+        .to_owned())
 }
 
 pub fn create_get_by_era_stmt(era: u64) -> SelectStatement {
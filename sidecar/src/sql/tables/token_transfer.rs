@@ -0,0 +1,115 @@
+use sea_query::{
+    error::Result as SqResult, ColumnDef, DeleteStatement, Expr, Iden, Index, InsertStatement,
+    OnConflict, Order, Query, SelectStatement, Table, TableCreateStatement,
+};
+
+/// Transfers of CEP-18 tokens decoded from the events their contracts emitted, keyed by the hash
+/// of the contract and the id of the event, which numbers the events of a contract in the order
+/// they were emitted.
+#[derive(Iden)]
+enum TokenTransfer {
+    #[iden = "TokenTransfer"]
+    Table,
+    ContractHash,
+    EventId,
+    DeployHash,
+    Raw,
+}
+
+pub fn create_table_stmt() -> TableCreateStatement {
+    Table::create()
+        .table(TokenTransfer::Table)
+        .if_not_exists()
+        .col(
+            ColumnDef::new(TokenTransfer::ContractHash)
+                .string()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(TokenTransfer::EventId)
+                .big_unsigned()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(TokenTransfer::DeployHash)
+                .string()
+                .not_null(),
+        )
+        .col(ColumnDef::new(TokenTransfer::Raw).text().not_null())
+        .index(
+            Index::create()
+                .primary()
+                .name("PDX_TokenTransfer")
+                .col(TokenTransfer::ContractHash)
+                .col(TokenTransfer::EventId),
+        )
+        .to_owned()
+}
+
+/// Records the transfer, unless already recorded. `raw` is the JSON of the transfer as served.
+pub fn create_insert_stmt(
+    contract_hash: String,
+    event_id: u64,
+    deploy_hash: String,
+    raw: String,
+) -> SqResult<InsertStatement> {
+    Ok(Query::insert()
+        .into_table(TokenTransfer::Table)
+        .columns([
+            TokenTransfer::ContractHash,
+            TokenTransfer::EventId,
+            TokenTransfer::DeployHash,
+            TokenTransfer::Raw,
+        ])
+        .values(vec![
+            contract_hash.into(),
+            event_id.into(),
+            deploy_hash.into(),
+            raw.into(),
+        ])?
+        .on_conflict(
+            OnConflict::columns([TokenTransfer::ContractHash, TokenTransfer::EventId])
+                .do_nothing()
+                .to_owned(),
+        )
+        .to_owned())
+}
+
+/// Selects at most `limit` transfers of the token with the hex-encoded `contract_hash`, ordered by
+/// event id. If `after` is given, only the transfers with a greater event id are selected.
+pub fn create_get_by_contract_stmt(
+    contract_hash: &str,
+    after: Option<u64>,
+    limit: u32,
+) -> SelectStatement {
+    let mut select = Query::select();
+    select
+        .column(TokenTransfer::Raw)
+        .from(TokenTransfer::Table)
+        .and_where(Expr::col(TokenTransfer::ContractHash).eq(contract_hash));
+    if let Some(event_id) = after {
+        select.and_where(Expr::col(TokenTransfer::EventId).gt(event_id));
+    }
+    select
+        .order_by(TokenTransfer::EventId, Order::Asc)
+        .limit(limit as u64)
+        .to_owned()
+}
+
+/// Deletes all the token transfers, to be derived again from the stored processed deploys.
+pub fn create_delete_all_stmt() -> DeleteStatement {
+    Query::delete().from_table(TokenTransfer::Table).to_owned()
+}
+
+#[test]
+fn create_get_by_contract_stmt_should_select_in_event_order() {
+    use sea_query::SqliteQueryBuilder;
+
+    let stmt = create_get_by_contract_stmt("ab", Some(7), 10).to_string(SqliteQueryBuilder);
+
+    assert_eq!(
+        stmt,
+        "SELECT \"raw\" FROM \"TokenTransfer\" WHERE \"contract_hash\" = 'ab' AND \"event_id\" > 7 \
+         ORDER BY \"event_id\" ASC LIMIT 10"
+    );
+}
@@ -0,0 +1,218 @@
+use sea_query::{
+    error::Result as SqResult, ColumnDef, Cond, DeleteStatement, Expr, Iden, Index,
+    IndexCreateStatement, InsertStatement, OnConflict, Order, Query, SelectStatement, Table,
+    TableAlterStatement, TableCreateStatement,
+};
+
+/// Native transfers recorded by the execution of the processed deploys, keyed by the address of
+/// the transfer. The sender and the recipient are hex-encoded account hashes. The user-defined id of
+/// the transfer is stored in decimal, as it may not fit in a signed integer.
+#[derive(Iden)]
+enum Transfer {
+    #[iden = "Transfer"]
+    Table,
+    TransferHash,
+    DeployHash,
+    BlockHash,
+    FromAccount,
+    ToAccount,
+    Raw,
+    TransferId,
+}
+
+pub fn create_table_stmt() -> TableCreateStatement {
+    Table::create()
+        .table(Transfer::Table)
+        .if_not_exists()
+        .col(
+            ColumnDef::new(Transfer::TransferHash)
+                .string()
+                .not_null()
+                .primary_key(),
+        )
+        .col(ColumnDef::new(Transfer::DeployHash).string().not_null())
+        .col(ColumnDef::new(Transfer::BlockHash).string().not_null())
+        .col(ColumnDef::new(Transfer::FromAccount).string().not_null())
+        .col(ColumnDef::new(Transfer::ToAccount).string())
+        .col(ColumnDef::new(Transfer::Raw).text().not_null())
+        .to_owned()
+}
+
+pub fn create_from_account_index_stmt() -> IndexCreateStatement {
+    Index::create()
+        .if_not_exists()
+        .name("IDX_Transfer_FromAccount")
+        .table(Transfer::Table)
+        .col(Transfer::FromAccount)
+        .to_owned()
+}
+
+pub fn create_to_account_index_stmt() -> IndexCreateStatement {
+    Index::create()
+        .if_not_exists()
+        .name("IDX_Transfer_ToAccount")
+        .table(Transfer::Table)
+        .col(Transfer::ToAccount)
+        .to_owned()
+}
+
+pub fn create_block_hash_index_stmt() -> IndexCreateStatement {
+    Index::create()
+        .if_not_exists()
+        .name("IDX_Transfer_BlockHash")
+        .table(Transfer::Table)
+        .col(Transfer::BlockHash)
+        .to_owned()
+}
+
+pub fn create_add_transfer_id_column_stmt() -> TableAlterStatement {
+    Table::alter()
+        .table(Transfer::Table)
+        .add_column(ColumnDef::new(Transfer::TransferId).string().null())
+        .to_owned()
+}
+
+pub fn create_transfer_id_index_stmt() -> IndexCreateStatement {
+    Index::create()
+        .if_not_exists()
+        .name("IDX_Transfer_TransferId")
+        .table(Transfer::Table)
+        .col(Transfer::TransferId)
+        .to_owned()
+}
+
+/// Records the transfer, unless already recorded. `raw` is the JSON of the transfer as served.
+pub fn create_insert_stmt(
+    transfer_hash: String,
+    deploy_hash: String,
+    block_hash: String,
+    from_account: String,
+    to_account: Option<String>,
+    transfer_id: Option<u64>,
+    raw: String,
+) -> SqResult<InsertStatement> {
+    Ok(Query::insert()
+        .into_table(Transfer::Table)
+        .columns([
+            Transfer::TransferHash,
+            Transfer::DeployHash,
+            Transfer::BlockHash,
+            Transfer::FromAccount,
+            Transfer::ToAccount,
+            Transfer::TransferId,
+            Transfer::Raw,
+        ])
+        .values(vec![
+            transfer_hash.into(),
+            deploy_hash.into(),
+            block_hash.into(),
+            from_account.into(),
+            to_account.into(),
+            transfer_id.map(|id| id.to_string()).into(),
+            raw.into(),
+        ])?
+        .on_conflict(
+            OnConflict::column(Transfer::TransferHash)
+                .do_nothing()
+                .to_owned(),
+        )
+        .to_owned())
+}
+
+/// Selects at most `limit` transfers sent or received by the account with the hex-encoded
+/// `account_hash`, ordered by transfer hash. If `after` is given, only the transfers with a
+/// greater transfer hash are selected.
+pub fn create_get_by_account_stmt(
+    account_hash: &str,
+    after: Option<String>,
+    limit: u32,
+) -> SelectStatement {
+    let mut condition = Cond::all().add(
+        Cond::any()
+            .add(Expr::col(Transfer::FromAccount).eq(account_hash))
+            .add(Expr::col(Transfer::ToAccount).eq(account_hash)),
+    );
+    if let Some(transfer_hash) = after {
+        condition = condition.add(Expr::col(Transfer::TransferHash).gt(transfer_hash));
+    }
+    Query::select()
+        .column(Transfer::Raw)
+        .from(Transfer::Table)
+        .cond_where(condition)
+        .order_by(Transfer::TransferHash, Order::Asc)
+        .limit(limit as u64)
+        .to_owned()
+}
+
+/// Selects at most `limit` transfers with the user-defined `transfer_id`, ordered by transfer hash.
+/// If `after` is given, only the transfers with a greater transfer hash are selected.
+pub fn create_get_by_transfer_id_stmt(
+    transfer_id: u64,
+    after: Option<String>,
+    limit: u32,
+) -> SelectStatement {
+    let mut select = Query::select();
+    select
+        .column(Transfer::Raw)
+        .from(Transfer::Table)
+        .and_where(Expr::col(Transfer::TransferId).eq(transfer_id.to_string()));
+    if let Some(transfer_hash) = after {
+        select.and_where(Expr::col(Transfer::TransferHash).gt(transfer_hash));
+    }
+    select
+        .order_by(Transfer::TransferHash, Order::Asc)
+        .limit(limit as u64)
+        .to_owned()
+}
+
+/// Selects the transfers of the deploys processed in the block, ordered by transfer hash.
+pub fn create_get_by_block_stmt(block_hash: String) -> SelectStatement {
+    Query::select()
+        .column(Transfer::Raw)
+        .from(Transfer::Table)
+        .and_where(Expr::col(Transfer::BlockHash).eq(block_hash))
+        .order_by(Transfer::TransferHash, Order::Asc)
+        .to_owned()
+}
+
+/// Deletes all the native transfers, to be derived again from the stored processed deploys.
+pub fn create_delete_all_stmt() -> DeleteStatement {
+    Query::delete().from_table(Transfer::Table).to_owned()
+}
+
+#[test]
+fn create_get_by_account_stmt_should_select_sent_and_received_transfers() {
+    use sea_query::SqliteQueryBuilder;
+
+    let stmt =
+        create_get_by_account_stmt("ab", Some("cd".to_string()), 10).to_string(SqliteQueryBuilder);
+
+    assert_eq!(
+        stmt,
+        "SELECT \"raw\" FROM \"Transfer\" \
+         WHERE (\"from_account\" = 'ab' OR \"to_account\" = 'ab') AND \"transfer_hash\" > 'cd' \
+         ORDER BY \"transfer_hash\" ASC LIMIT 10"
+    );
+}
+
+#[test]
+fn create_get_by_transfer_id_stmt_should_match_the_decimal_id() {
+    use sea_query::SqliteQueryBuilder;
+
+    let stmt = create_get_by_transfer_id_stmt(u64::MAX, None, 10).to_string(SqliteQueryBuilder);
+
+    assert_eq!(
+        stmt,
+        "SELECT \"raw\" FROM \"Transfer\" WHERE \"transfer_id\" = '18446744073709551615' \
+         ORDER BY \"transfer_hash\" ASC LIMIT 10"
+    );
+}
+
+#[test]
+fn create_delete_all_stmt_should_delete_every_transfer() {
+    use sea_query::SqliteQueryBuilder;
+
+    let stmt = create_delete_all_stmt().to_string(SqliteQueryBuilder);
+
+    assert_eq!(stmt, "DELETE FROM \"Transfer\"");
+}
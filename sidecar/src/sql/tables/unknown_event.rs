@@ -0,0 +1,88 @@
+use sea_query::{
+    error::Result as SqResult, ColumnDef, Expr, ForeignKey, ForeignKeyAction, Iden, Index,
+    InsertStatement, OnConflict, Order, Query, SelectStatement, Table, TableCreateStatement,
+};
+
+use super::event_log::EventLog;
+
+/// Events of types the sidecar doesn't know, stored with the name of their type and their raw
+/// payload.
+#[derive(Iden)]
+enum UnknownEvent {
+    #[iden = "UnknownEvent"]
+    Table,
+    EventType,
+    Raw,
+    EventLogId,
+}
+
+pub fn create_table_stmt() -> TableCreateStatement {
+    Table::create()
+        .table(UnknownEvent::Table)
+        .if_not_exists()
+        .col(ColumnDef::new(UnknownEvent::EventType).string().not_null())
+        .col(ColumnDef::new(UnknownEvent::Raw).text().not_null())
+        .col(
+            ColumnDef::new(UnknownEvent::EventLogId)
+                .big_unsigned()
+                .not_null(),
+        )
+        .index(
+            Index::create()
+                .primary()
+                .name("PDX_UnknownEvent")
+                .col(UnknownEvent::EventLogId),
+        )
+        .foreign_key(
+            ForeignKey::create()
+                .name("FK_event_log_id")
+                .from(UnknownEvent::Table, UnknownEvent::EventLogId)
+                .to(EventLog::Table, EventLog::EventLogId)
+                .on_delete(ForeignKeyAction::Restrict)
+                .on_update(ForeignKeyAction::Restrict),
+        )
+        .to_owned()
+}
+
+/// Stores an event of an unknown type, unless it was already stored for the same event log entry.
+pub fn create_insert_stmt(
+    event_type: String,
+    raw: String,
+    event_log_id: u64,
+) -> SqResult<InsertStatement> {
+    Ok(Query::insert()
+        .into_table(UnknownEvent::Table)
+        .columns([
+            UnknownEvent::EventType,
+            UnknownEvent::Raw,
+            UnknownEvent::EventLogId,
+        ])
+        .values(vec![event_type.into(), raw.into(), event_log_id.into()])?
+        .on_conflict(
+            OnConflict::columns([UnknownEvent::EventLogId])
+                .do_nothing()
+                .to_owned(),
+        )
+        .to_owned())
+}
+
+/// Selects the stored events of the given type in the order they were received.
+pub fn create_get_by_event_type_stmt(event_type: String) -> SelectStatement {
+    Query::select()
+        .column(UnknownEvent::Raw)
+        .from(UnknownEvent::Table)
+        .and_where(Expr::col(UnknownEvent::EventType).eq(event_type))
+        .order_by(UnknownEvent::EventLogId, Order::Asc)
+        .to_owned()
+}
+
+#[test]
+fn create_get_by_event_type_stmt_should_produce_select_sql() {
+    use sea_query::SqliteQueryBuilder;
+    let expected_sql = "SELECT \"raw\" FROM \"UnknownEvent\" WHERE \"event_type\" = 'BlockFinalized' ORDER BY \"event_log_id\" ASC";
+
+    let got_sql =
+        create_get_by_event_type_stmt("BlockFinalized".to_string()).to_string(SqliteQueryBuilder);
+
+    assert_eq!(got_sql, expected_sql);
+}
@@ -0,0 +1,131 @@
+use sea_query::{
+    error::Result as SqResult, ColumnDef, Expr, Iden, Index, IndexCreateStatement, InsertStatement,
+    Order, Query, SelectStatement, Table, TableCreateStatement,
+};
+
+/// Attempts at delivering events to the configured webhooks, one row per attempt.
+#[derive(Iden)]
+enum WebhookDelivery {
+    #[iden = "WebhookDelivery"]
+    Table,
+    WebhookDeliveryId,
+    Webhook,
+    EventType,
+    Attempt,
+    AttemptedTimestamp,
+    StatusCode,
+    Error,
+}
+
+pub fn create_table_stmt(is_big_integer_id: bool) -> TableCreateStatement {
+    let mut binding = ColumnDef::new(WebhookDelivery::WebhookDeliveryId);
+    let mut webhook_delivery_id_col_definition = binding.auto_increment().not_null().primary_key();
+    if is_big_integer_id {
+        webhook_delivery_id_col_definition = webhook_delivery_id_col_definition.big_integer();
+    } else {
+        webhook_delivery_id_col_definition = webhook_delivery_id_col_definition.integer();
+    }
+    Table::create()
+        .table(WebhookDelivery::Table)
+        .if_not_exists()
+        .col(webhook_delivery_id_col_definition)
+        .col(ColumnDef::new(WebhookDelivery::Webhook).string().not_null())
+        .col(
+            ColumnDef::new(WebhookDelivery::EventType)
+                .string()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(WebhookDelivery::Attempt)
+                .integer()
+                .not_null(),
+        )
+        // Stored as milliseconds since the UNIX epoch so that sqlite and postgresql return the same
+        // representation.
+        .col(
+            ColumnDef::new(WebhookDelivery::AttemptedTimestamp)
+                .big_integer()
+                .not_null(),
+        )
+        .col(ColumnDef::new(WebhookDelivery::StatusCode).integer())
+        .col(ColumnDef::new(WebhookDelivery::Error).text())
+        .to_owned()
+}
+
+pub fn create_webhook_index_stmt() -> IndexCreateStatement {
+    Index::create()
+        .if_not_exists()
+        .name("IDX_WebhookDelivery_Webhook")
+        .table(WebhookDelivery::Table)
+        .col(WebhookDelivery::Webhook)
+        .to_owned()
+}
+
+pub fn create_insert_stmt(
+    webhook: &str,
+    event_type: &str,
+    attempt: u32,
+    attempted_timestamp: u64,
+    status_code: Option<u16>,
+    error: Option<&str>,
+) -> SqResult<InsertStatement> {
+    Query::insert()
+        .into_table(WebhookDelivery::Table)
+        .columns([
+            WebhookDelivery::Webhook,
+            WebhookDelivery::EventType,
+            WebhookDelivery::Attempt,
+            WebhookDelivery::AttemptedTimestamp,
+            WebhookDelivery::StatusCode,
+            WebhookDelivery::Error,
+        ])
+        .values(vec![
+            webhook.into(),
+            event_type.into(),
+            attempt.into(),
+            attempted_timestamp.into(),
+            status_code.map(u32::from).into(),
+            error.into(),
+        ])
+        .map(|stmt| stmt.to_owned())
+}
+
+/// Selects the delivery attempts of the webhook in the order they were made.
+pub fn create_get_by_webhook_stmt(webhook: &str) -> SelectStatement {
+    Query::select()
+        .columns([
+            WebhookDelivery::Webhook,
+            WebhookDelivery::EventType,
+            WebhookDelivery::Attempt,
+            WebhookDelivery::AttemptedTimestamp,
+            WebhookDelivery::StatusCode,
+            WebhookDelivery::Error,
+        ])
+        .from(WebhookDelivery::Table)
+        .and_where(Expr::col(WebhookDelivery::Webhook).eq(webhook))
+        .order_by(WebhookDelivery::WebhookDeliveryId, Order::Asc)
+        .to_owned()
+}
+
+#[test]
+fn create_insert_stmt_should_leave_status_code_null_without_response() {
+    use sea_query::SqliteQueryBuilder;
+
+    let stmt = create_insert_stmt(
+        "indexer",
+        "BlockAdded",
+        2,
+        1_700_000_000_000,
+        None,
+        Some("connection refused"),
+    )
+    .unwrap()
+    .to_string(SqliteQueryBuilder);
+
+    assert_eq!(
+        stmt,
+        "INSERT INTO \"WebhookDelivery\" (\"webhook\", \"event_type\", \"attempt\", \
+         \"attempted_timestamp\", \"status_code\", \"error\") \
+         VALUES ('indexer', 'BlockAdded', 2, 1700000000000, NULL, 'connection refused')"
+    );
+}
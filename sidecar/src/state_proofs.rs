@@ -0,0 +1,143 @@
+//! Proofs of global state fetched from a node's RPC server.
+//!
+//! Light clients verifying events need the Merkle proof of the related global state as well. The
+//! REST server proxies `query_global_state` requests for a state root hash and a key to the node
+//! and bundles the proof with the stored events the key refers to. A state root hash identifies an
+//! immutable state, so the proofs are cached for as long as the cache has room for them.
+
+use crate::{
+    deploy_approvals::AccountKeys,
+    node_rpc::{ResponseCache, RpcClient},
+    types::{
+        config::StateProofConfig,
+        database::{DatabaseReadError, DatabaseReader, DeployAggregate},
+        sse_events::Step,
+    },
+};
+use anyhow::Error;
+use casper_event_types::metrics::STATE_PROOFS;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use utoipa::ToSchema;
+
+/// A proof of global state with the stored events related to the queried key.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StateProof {
+    state_root_hash: String,
+    key: String,
+    /// Result of the node's `query_global_state`, including the stored value and the Merkle proof.
+    #[schema(value_type = Object)]
+    proof: Value,
+    /// The stored deploy, if the key is the `deploy-` key of a stored deploy.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deploy: Option<DeployAggregate>,
+    /// The stored step, if the key is the `era-` key of an era with a stored step.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    step: Option<Step>,
+}
+
+/// An error fetching a [StateProof].
+#[derive(Debug)]
+pub(crate) enum StateProofError {
+    /// The node didn't provide the proof.
+    Upstream(Error),
+    /// The related events couldn't be read.
+    Storage(DatabaseReadError),
+}
+
+/// Handle used to fetch proofs. Cloned handles share the same cache.
+#[derive(Clone)]
+pub struct StateProofs {
+    rpc_client: Arc<RpcClient>,
+    cache: Arc<Mutex<ResponseCache>>,
+}
+
+impl StateProofs {
+    pub(crate) fn new(config: &StateProofConfig) -> Self {
+        StateProofs {
+            rpc_client: Arc::new(RpcClient::new(config.rpc_address.clone())),
+            cache: Arc::new(Mutex::new(ResponseCache::new(config.cache_size()))),
+        }
+    }
+
+    /// Returns the proof of `key` in the state identified by `state_root_hash`, bundled with the
+    /// stored events related to the key.
+    pub(crate) async fn get<Db: DatabaseReader>(
+        &self,
+        state_root_hash: String,
+        key: String,
+        database: &Db,
+    ) -> Result<StateProof, StateProofError> {
+        let proof = self
+            .fetch_proof(&state_root_hash, &key)
+            .await
+            .map_err(StateProofError::Upstream)?;
+        let mut state_proof = StateProof {
+            state_root_hash,
+            key,
+            proof,
+            deploy: None,
+            step: None,
+        };
+        if let Some(deploy_hash) = state_proof.key.strip_prefix("deploy-") {
+            state_proof.deploy = found(database.get_deploy_aggregate_by_hash(deploy_hash).await)?;
+        } else if let Some(era) = state_proof.key.strip_prefix("era-") {
+            if let Ok(era) = era.parse() {
+                state_proof.step = found(database.get_step_by_era(era).await)?;
+            }
+        }
+        Ok(state_proof)
+    }
+
+    /// Returns the associated keys and deployment threshold of the account with the hex-encoded
+    /// `account_hash` in the latest global state. The latest state changes, so they aren't cached.
+    pub(crate) async fn get_account_keys(&self, account_hash: &str) -> Result<AccountKeys, Error> {
+        let params = json!({
+            "key": format!("account-hash-{}", account_hash),
+            "path": [],
+        });
+        let result = self.rpc_client.call("query_global_state", params).await?;
+        AccountKeys::from_query_result(&result)
+    }
+
+    async fn fetch_proof(&self, state_root_hash: &str, key: &str) -> Result<Value, Error> {
+        let cache_key = format!("{}-{}", state_root_hash, key);
+        let cached = self.lock_cache().get(&cache_key).cloned();
+        if let Some(proof) = cached {
+            STATE_PROOFS.with_label_values(&["cached"]).inc();
+            return Ok(proof);
+        }
+        let params = json!({
+            "state_identifier": { "StateRootHash": state_root_hash },
+            "key": key,
+            "path": [],
+        });
+        match self.rpc_client.call("query_global_state", params).await {
+            Ok(proof) => {
+                STATE_PROOFS.with_label_values(&["fetched"]).inc();
+                self.lock_cache().insert(cache_key, proof.clone());
+                Ok(proof)
+            }
+            Err(error) => {
+                STATE_PROOFS.with_label_values(&["failed"]).inc();
+                Err(error)
+            }
+        }
+    }
+
+    fn lock_cache(&self) -> std::sync::MutexGuard<'_, ResponseCache> {
+        self.cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Turns a missing record into `None`, so that the proof is still served without it.
+fn found<T>(result: Result<T, DatabaseReadError>) -> Result<Option<T>, StateProofError> {
+    match result {
+        Ok(record) => Ok(Some(record)),
+        Err(DatabaseReadError::NotFound) => Ok(None),
+        Err(error) => Err(StateProofError::Storage(error)),
+    }
+}
@@ -0,0 +1,247 @@
+//! Estimation of the storage saved by compressing the event payloads.
+//!
+//! `analyze-storage` samples the raw JSON payloads of the latest events of each event table, trains
+//! a zstd dictionary on them and compresses the payloads held out of the training with and without
+//! the dictionary, so the savings can be quantified before enabling compression. The trained
+//! dictionary can be written to a file for the compression to use.
+
+use crate::{
+    build_database,
+    types::{
+        config::Config,
+        database::{Database, DatabaseReadError, DatabaseReader, PayloadTable},
+    },
+};
+use anyhow::{Context, Error};
+use clap::Args;
+use std::{
+    fmt::{Display, Formatter},
+    fs,
+    path::PathBuf,
+};
+use tracing::info;
+
+/// Every this many sampled payloads, one is held out of the training of the dictionary to measure
+/// the savings on payloads the dictionary wasn't trained on.
+const EVALUATION_SAMPLE_INTERVAL: usize = 5;
+
+/// Options of `analyze-storage`.
+#[derive(Args, Clone, Debug)]
+pub(crate) struct AnalysisOptions {
+    /// Maximum number of the latest payloads sampled per event table.
+    #[arg(long, default_value_t = 1000)]
+    pub(crate) samples_per_table: u32,
+    /// Maximum size of the trained dictionary in bytes.
+    #[arg(long, default_value_t = 112_640)]
+    pub(crate) dictionary_size: usize,
+    /// zstd compression level.
+    #[arg(long, default_value_t = 3)]
+    pub(crate) level: i32,
+    /// File to write the trained dictionary to.
+    #[arg(long, value_name = "FILE")]
+    pub(crate) dictionary_output: Option<PathBuf>,
+}
+
+/// Sizes of the payloads of an event table held out of the training of the dictionary.
+#[derive(Debug, PartialEq, Eq)]
+struct TableSavings {
+    table: &'static str,
+    payloads: usize,
+    raw_bytes: usize,
+    compressed_bytes: usize,
+    dictionary_compressed_bytes: usize,
+}
+
+#[derive(Debug)]
+struct StorageAnalysis {
+    dictionary: Vec<u8>,
+    tables: Vec<TableSavings>,
+}
+
+fn saved_percent(raw_bytes: usize, compressed_bytes: usize) -> f64 {
+    if raw_bytes == 0 {
+        return 0.0;
+    }
+    100.0 * (1.0 - compressed_bytes as f64 / raw_bytes as f64)
+}
+
+impl Display for StorageAnalysis {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "trained a {} byte dictionary", self.dictionary.len())?;
+        for savings in &self.tables {
+            write!(
+                f,
+                "; {}: {} payload(s) of {} bytes, {} bytes with zstd ({:.1}% saved), {} bytes with the dictionary ({:.1}% saved)",
+                savings.table,
+                savings.payloads,
+                savings.raw_bytes,
+                savings.compressed_bytes,
+                saved_percent(savings.raw_bytes, savings.compressed_bytes),
+                savings.dictionary_compressed_bytes,
+                saved_percent(savings.raw_bytes, savings.dictionary_compressed_bytes),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Samples the payloads stored in the database configured in `config` and reports the storage
+/// compressing them would save, writing the trained dictionary to the requested file if any.
+pub(crate) async fn run_storage_analysis(
+    config: Config,
+    options: AnalysisOptions,
+) -> Result<(), Error> {
+    let samples = match build_database(&config.storage).await? {
+        Database::SqliteDatabaseWrapper(db) => {
+            sample_payloads(&db, options.samples_per_table).await?
+        }
+        #[cfg(feature = "postgres")]
+        Database::PostgreSqlDatabaseWrapper(db) => {
+            sample_payloads(&db, options.samples_per_table).await?
+        }
+    };
+    let analysis = analyze(&samples, options.dictionary_size, options.level)?;
+    info!("Storage analysis finished: {}", analysis);
+    if let Some(path) = &options.dictionary_output {
+        fs::write(path, &analysis.dictionary)
+            .with_context(|| format!("Error writing the dictionary to {}", path.display()))?;
+        info!("Dictionary written to {}", path.display());
+    }
+    Ok(())
+}
+
+async fn sample_payloads<Db: DatabaseReader + Sync>(
+    db: &Db,
+    samples_per_table: u32,
+) -> Result<Vec<(PayloadTable, Vec<String>)>, Error> {
+    let mut samples = Vec::new();
+    for table in PayloadTable::ALL {
+        let payloads = match db.get_raw_payload_sample(table, samples_per_table).await {
+            Ok(payloads) => payloads,
+            Err(DatabaseReadError::NotFound) => Vec::new(),
+            Err(error) => {
+                return Err(Error::msg(format!(
+                    "Error sampling the payloads of {}: {:?}",
+                    table.table_name(),
+                    error
+                )))
+            }
+        };
+        samples.push((table, payloads));
+    }
+    Ok(samples)
+}
+
+/// Trains a dictionary on the payloads which aren't held out, and measures the savings on those
+/// which are.
+fn analyze(
+    samples: &[(PayloadTable, Vec<String>)],
+    dictionary_size: usize,
+    level: i32,
+) -> Result<StorageAnalysis, Error> {
+    let training: Vec<&[u8]> = samples
+        .iter()
+        .flat_map(|(_, payloads)| {
+            payloads
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| !is_held_out(*index))
+                .map(|(_, payload)| payload.as_bytes())
+        })
+        .collect();
+    let dictionary = zstd::dict::from_samples(&training, dictionary_size).with_context(|| {
+        format!(
+            "Error training the dictionary on {} payload(s), more payloads need to be stored or sampled",
+            training.len()
+        )
+    })?;
+    let mut compressor = zstd::bulk::Compressor::with_dictionary(level, &dictionary)
+        .context("Error loading the trained dictionary")?;
+    let tables = samples
+        .iter()
+        .map(|(table, payloads)| measure_savings(*table, payloads, level, &mut compressor))
+        .collect::<Result<_, _>>()?;
+    Ok(StorageAnalysis { dictionary, tables })
+}
+
+fn is_held_out(index: usize) -> bool {
+    index % EVALUATION_SAMPLE_INTERVAL == 0
+}
+
+/// Compresses the held out payloads of the table with and without the dictionary.
+fn measure_savings(
+    table: PayloadTable,
+    payloads: &[String],
+    level: i32,
+    compressor: &mut zstd::bulk::Compressor,
+) -> Result<TableSavings, Error> {
+    let mut savings = TableSavings {
+        table: table.table_name(),
+        payloads: 0,
+        raw_bytes: 0,
+        compressed_bytes: 0,
+        dictionary_compressed_bytes: 0,
+    };
+    for (_, payload) in payloads
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| is_held_out(*index))
+    {
+        savings.payloads += 1;
+        savings.raw_bytes += payload.len();
+        savings.compressed_bytes += zstd::bulk::compress(payload.as_bytes(), level)
+            .context("Error compressing a payload")?
+            .len();
+        savings.dictionary_compressed_bytes += compressor
+            .compress(payload.as_bytes())
+            .context("Error compressing a payload with the dictionary")?
+            .len();
+    }
+    Ok(savings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::sse_events::DeployAccepted;
+    use casper_types::testing::TestRng;
+
+    #[test]
+    fn should_measure_savings_on_held_out_payloads() {
+        let mut rng = TestRng::new();
+        let payloads: Vec<String> = (0..100)
+            .map(|_| serde_json::to_string(&DeployAccepted::random(&mut rng)).unwrap())
+            .collect();
+        let samples = vec![
+            (PayloadTable::DeployAccepted, payloads.clone()),
+            (PayloadTable::Step, Vec::new()),
+        ];
+
+        let analysis = analyze(&samples, 4096, 3).unwrap();
+
+        assert!(!analysis.dictionary.is_empty());
+        let deploys = &analysis.tables[0];
+        assert_eq!(deploys.table, "DeployAccepted");
+        assert_eq!(deploys.payloads, 100 / EVALUATION_SAMPLE_INTERVAL);
+        assert_eq!(
+            deploys.raw_bytes,
+            payloads
+                .iter()
+                .step_by(EVALUATION_SAMPLE_INTERVAL)
+                .map(String::len)
+                .sum::<usize>()
+        );
+        assert!(deploys.compressed_bytes < deploys.raw_bytes);
+        assert!(deploys.dictionary_compressed_bytes < deploys.raw_bytes);
+        assert_eq!(
+            analysis.tables[1],
+            TableSavings {
+                table: "Step",
+                payloads: 0,
+                raw_bytes: 0,
+                compressed_bytes: 0,
+                dictionary_compressed_bytes: 0,
+            }
+        );
+    }
+}
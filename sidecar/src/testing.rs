@@ -1,8 +1,18 @@
+#[cfg(test)]
 pub(crate) mod fake_database;
+#[cfg(test)]
 pub(crate) mod fake_event_stream;
+// The mock node is also run by the smoke test, which doesn't use all of its helpers.
+#[allow(dead_code)]
 pub(crate) mod mock_node;
+pub(crate) mod ports;
+#[allow(dead_code)]
 pub(crate) mod raw_sse_events_utils;
+#[cfg(test)]
 pub(crate) mod shared;
+#[allow(dead_code)]
 pub(crate) mod simple_sse_server;
+#[cfg(test)]
 pub(crate) mod test_clock;
+#[cfg(test)]
 pub(crate) mod testing_config;
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -9,10 +9,16 @@ use rand::Rng;
 
 use casper_event_types::FinalitySignature as FinSig;
 
+use crate::sql::tables::event_type::EventTypeId;
 use crate::types::{
     database::{
-        DatabaseReadError, DatabaseReader, DatabaseWriteError, DatabaseWriter, DeployAggregate,
-        Migration,
+        AccountEffectEntry, AuditEntry, ContractDayStats, ContractEventEntry, ContractEventSchemas,
+        DatabaseReadError, DatabaseReader, DatabaseWriteError, DatabaseWriter, DeployAccountEntry,
+        DeployAggregate, DeployApprovalEntry, DeployErrorEntry, DeployEventTime, DeploySizeStats,
+        DeployTimestampEntry, DeployTimestampKind, DerivedEvent, DerivedTable, Distribution,
+        Enrichment, EraValidatorWeight, EventIdAllocatorState, FinalitySignatureCheckpoint,
+        NetworkIdentity, NftEventEntry, OperationalEvent, PayloadTable, RawPayloadEntry,
+        StoredApiKey, TokenTransferEntry, TransferEntry, WebhookDeliveryAttempt,
     },
     sse_events::*,
 };
@@ -20,15 +26,100 @@ use crate::types::{
 #[derive(Clone)]
 pub struct FakeDatabase {
     data: Arc<Mutex<HashMap<String, String>>>,
+    audit_entries: Arc<Mutex<Vec<AuditEntry>>>,
+    operational_events: Arc<Mutex<Vec<OperationalEvent>>>,
+    event_id_allocator: Arc<Mutex<Option<EventIdAllocatorState>>>,
+    network_identity: Arc<Mutex<Option<NetworkIdentity>>>,
+    deploy_timestamps: Arc<Mutex<Vec<DeployTimestampEntry>>>,
+    deploy_approvals: Arc<Mutex<Vec<DeployApprovalEntry>>>,
+    deploy_event_times: Arc<Mutex<Vec<(String, DeployEventTime)>>>,
+    unknown_events: Arc<Mutex<Vec<UnknownEvent>>>,
+    enrichments: Arc<Mutex<Vec<Enrichment>>>,
+    derived_events: Arc<Mutex<Vec<DerivedEvent>>>,
+    token_transfers: Arc<Mutex<Vec<TokenTransferEntry>>>,
+    nft_events: Arc<Mutex<Vec<NftEventEntry>>>,
+    contract_event_schemas: Arc<Mutex<HashMap<String, ContractEventSchemas>>>,
+    contract_events: Arc<Mutex<Vec<ContractEventEntry>>>,
+    finality_signature_checkpoints: Arc<Mutex<HashMap<String, FinalitySignatureCheckpoint>>>,
+    webhook_delivery_attempts: Arc<Mutex<Vec<WebhookDeliveryAttempt>>>,
+    api_keys: Arc<Mutex<Vec<StoredApiKey>>>,
 }
 
 impl FakeDatabase {
     pub(crate) fn new() -> Self {
         Self {
             data: Arc::new(Mutex::new(HashMap::new())),
+            audit_entries: Arc::new(Mutex::new(Vec::new())),
+            operational_events: Arc::new(Mutex::new(Vec::new())),
+            event_id_allocator: Arc::new(Mutex::new(None)),
+            network_identity: Arc::new(Mutex::new(None)),
+            deploy_timestamps: Arc::new(Mutex::new(Vec::new())),
+            deploy_approvals: Arc::new(Mutex::new(Vec::new())),
+            deploy_event_times: Arc::new(Mutex::new(Vec::new())),
+            unknown_events: Arc::new(Mutex::new(Vec::new())),
+            enrichments: Arc::new(Mutex::new(Vec::new())),
+            derived_events: Arc::new(Mutex::new(Vec::new())),
+            token_transfers: Arc::new(Mutex::new(Vec::new())),
+            nft_events: Arc::new(Mutex::new(Vec::new())),
+            contract_event_schemas: Arc::new(Mutex::new(HashMap::new())),
+            contract_events: Arc::new(Mutex::new(Vec::new())),
+            finality_signature_checkpoints: Arc::new(Mutex::new(HashMap::new())),
+            webhook_delivery_attempts: Arc::new(Mutex::new(Vec::new())),
+            api_keys: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
+    fn index_deploy_timestamp(
+        &self,
+        deploy_hash: String,
+        kind: DeployTimestampKind,
+        timestamp: u64,
+    ) {
+        let mut deploy_timestamps = self
+            .deploy_timestamps
+            .lock()
+            .expect("Error acquiring lock on deploy timestamps");
+        if !deploy_timestamps
+            .iter()
+            .any(|entry| entry.deploy_hash == deploy_hash && entry.kind == kind)
+        {
+            deploy_timestamps.push(DeployTimestampEntry {
+                deploy_hash,
+                kind,
+                timestamp,
+            });
+        }
+    }
+
+    /// Records the time an event of the deploy is stored at, formatted as the databases do.
+    fn record_deploy_event_time(&self, deploy_hash: String, event_type_id: EventTypeId) {
+        let now = casper_types::Timestamp::now().to_string();
+        self.deploy_event_times
+            .lock()
+            .expect("Error acquiring lock on deploy event times")
+            .push((
+                deploy_hash,
+                DeployEventTime {
+                    event_type_id: event_type_id as u8,
+                    inserted_timestamp: now.replace('T', " ").trim_end_matches('Z').to_string(),
+                },
+            ));
+    }
+
+    /// The native transfers of the stored processed deploys, ordered by transfer hash.
+    fn stored_transfers(&self) -> Vec<TransferEntry> {
+        let data = self.data.lock().expect("Error acquiring lock on data");
+        let mut transfers: Vec<TransferEntry> = data
+            .iter()
+            .filter(|(identifier, _)| identifier.ends_with("-processed"))
+            .filter_map(|(_, event)| serde_json::from_str::<DeployProcessed>(event).ok())
+            .flat_map(|deploy_processed| TransferEntry::of_deploy_processed(&deploy_processed))
+            .collect();
+        transfers.sort_by(|first, second| first.transfer_hash.cmp(&second.transfer_hash));
+        transfers.dedup_by(|first, second| first.transfer_hash == second.transfer_hash);
+        transfers
+    }
+
     /// Creates random SSE event data and saves them, returning the identifiers for each record.
     pub(crate) async fn populate_with_events(
         &self,
@@ -96,7 +187,45 @@ impl DatabaseWriter for FakeDatabase {
 
         data.insert(identifier_height, stringified_event);
 
-        Ok(0)
+        if let Some((next_era_id, validators)) = block_added.next_era_validators() {
+            data.insert(
+                format!("{}-validators", next_era_id),
+                serde_json::to_string(&validators).expect("Error serialising validators"),
+            );
+        }
+        if let Some((next_era_id, validator_weights)) = block_added.next_era_validator_weights() {
+            let validator_weights: Vec<EraValidatorWeight> = validator_weights
+                .into_iter()
+                .map(|(public_key, weight)| EraValidatorWeight {
+                    public_key,
+                    weight: Some(weight.to_string()),
+                })
+                .collect();
+            data.insert(
+                format!("{}-validator-weights", next_era_id),
+                serde_json::to_string(&validator_weights)
+                    .expect("Error serialising validator weights"),
+            );
+        }
+
+        let timestamp = block_added.get_timestamp().millis();
+        for deploy_hash in block_added.hex_encoded_deploy_hashes() {
+            self.index_deploy_timestamp(deploy_hash, DeployTimestampKind::Processed, timestamp);
+        }
+
+        Ok(1)
+    }
+
+    #[allow(unused)]
+    async fn save_block_added_failing_integrity(
+        &self,
+        block_added: BlockAdded,
+        event_id: u32,
+        event_source_address: String,
+    ) -> Result<u64, DatabaseWriteError> {
+        // Blocks failing integrity verification are never returned by the fake, so there is no
+        // need to keep them.
+        Ok(1)
     }
 
     #[allow(unused)]
@@ -116,7 +245,32 @@ impl DatabaseWriter for FakeDatabase {
 
         data.insert(identifier, stringified_event);
 
-        Ok(0)
+        let mut deploy_approvals = self
+            .deploy_approvals
+            .lock()
+            .expect("Error acquiring lock on deploy approvals");
+        for signer in deploy_accepted.hex_encoded_approval_signers() {
+            if !deploy_approvals
+                .iter()
+                .any(|entry| entry.deploy_hash == hash && entry.signer == signer)
+            {
+                deploy_approvals.push(DeployApprovalEntry {
+                    deploy_hash: hash.clone(),
+                    signer,
+                    event_log_id: event_id as u64,
+                });
+            }
+        }
+        drop(deploy_approvals);
+
+        self.record_deploy_event_time(hash.clone(), EventTypeId::DeployAccepted);
+        let accepted_timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_millis() as u64;
+        self.index_deploy_timestamp(hash, DeployTimestampKind::Accepted, accepted_timestamp);
+
+        Ok(1)
     }
 
     #[allow(unused)]
@@ -135,8 +289,17 @@ impl DatabaseWriter for FakeDatabase {
             serde_json::to_string(&deploy_processed).expect("Error serialising event data");
 
         data.insert(identifier, stringified_event);
+        self.record_deploy_event_time(hash.clone(), EventTypeId::DeployProcessed);
+
+        if let Some(block_added) = data
+            .get(&deploy_processed.hex_encoded_block_hash())
+            .and_then(|event| serde_json::from_str::<BlockAdded>(event).ok())
+        {
+            let timestamp = block_added.get_timestamp().millis();
+            self.index_deploy_timestamp(hash, DeployTimestampKind::Processed, timestamp);
+        }
 
-        Ok(0)
+        Ok(1)
     }
 
     #[allow(unused)]
@@ -155,8 +318,9 @@ impl DatabaseWriter for FakeDatabase {
             serde_json::to_string(&deploy_expired).expect("Error serialising event data");
 
         data.insert(identifier, stringified_event);
+        self.record_deploy_event_time(hash, EventTypeId::DeployExpired);
 
-        Ok(0)
+        Ok(1)
     }
 
     #[allow(unused)]
@@ -180,7 +344,7 @@ impl DatabaseWriter for FakeDatabase {
 
         data.insert(identifier_public_key, stringified_event);
 
-        Ok(0)
+        Ok(1)
     }
 
     #[allow(unused)]
@@ -198,7 +362,18 @@ impl DatabaseWriter for FakeDatabase {
 
         data.insert(identifier, stringified_event);
 
-        Ok(0)
+        Ok(1)
+    }
+
+    #[allow(unused)]
+    async fn save_invalid_finality_signature(
+        &self,
+        finality_signature: FinalitySignature,
+        event_id: u32,
+        event_source_address: String,
+    ) -> Result<u64, DatabaseWriteError> {
+        // Invalid signatures are never returned by the fake, so there is no need to keep them.
+        Ok(1)
     }
 
     #[allow(unused)]
@@ -215,7 +390,206 @@ impl DatabaseWriter for FakeDatabase {
 
         data.insert(identifier, stringified_event);
 
-        Ok(0)
+        Ok(1)
+    }
+
+    #[allow(unused)]
+    async fn save_unknown_event(
+        &self,
+        unknown_event: UnknownEvent,
+        event_id: u32,
+        event_source_address: String,
+    ) -> Result<u64, DatabaseWriteError> {
+        self.unknown_events
+            .lock()
+            .expect("Error acquiring lock on unknown events")
+            .push(unknown_event);
+
+        Ok(1)
+    }
+
+    async fn save_enrichment(&self, enrichment: Enrichment) -> Result<u64, DatabaseWriteError> {
+        let mut enrichments = self
+            .enrichments
+            .lock()
+            .expect("Error acquiring lock on enrichments");
+        if enrichments.iter().any(|stored| {
+            stored.entity_hash == enrichment.entity_hash && stored.kind == enrichment.kind
+        }) {
+            return Ok(0);
+        }
+        enrichments.push(enrichment);
+
+        Ok(1)
+    }
+
+    async fn save_derived_event(
+        &self,
+        derived_event: DerivedEvent,
+    ) -> Result<u64, DatabaseWriteError> {
+        self.derived_events
+            .lock()
+            .expect("Error acquiring lock on derived events")
+            .push(derived_event);
+
+        Ok(1)
+    }
+
+    async fn save_token_transfer(
+        &self,
+        token_transfer: TokenTransferEntry,
+    ) -> Result<u64, DatabaseWriteError> {
+        let mut token_transfers = self
+            .token_transfers
+            .lock()
+            .expect("Error acquiring lock on token transfers");
+        if !token_transfers.iter().any(|stored| {
+            stored.contract_hash == token_transfer.contract_hash
+                && stored.event_id == token_transfer.event_id
+        }) {
+            token_transfers.push(token_transfer);
+        }
+
+        Ok(1)
+    }
+
+    async fn save_nft_event(&self, nft_event: NftEventEntry) -> Result<u64, DatabaseWriteError> {
+        let mut nft_events = self
+            .nft_events
+            .lock()
+            .expect("Error acquiring lock on NFT events");
+        if !nft_events.iter().any(|stored| {
+            stored.contract_hash == nft_event.contract_hash && stored.event_id == nft_event.event_id
+        }) {
+            nft_events.push(nft_event);
+        }
+
+        Ok(1)
+    }
+
+    async fn save_contract_event_schemas(
+        &self,
+        contract_event_schemas: ContractEventSchemas,
+    ) -> Result<u64, DatabaseWriteError> {
+        self.contract_event_schemas
+            .lock()
+            .expect("Error acquiring lock on contract event schemas")
+            .insert(
+                contract_event_schemas.contract_hash.clone(),
+                contract_event_schemas,
+            );
+
+        Ok(1)
+    }
+
+    async fn save_contract_event(
+        &self,
+        contract_event: ContractEventEntry,
+    ) -> Result<u64, DatabaseWriteError> {
+        let mut contract_events = self
+            .contract_events
+            .lock()
+            .expect("Error acquiring lock on contract events");
+        if !contract_events.iter().any(|stored| {
+            stored.contract_hash == contract_event.contract_hash
+                && stored.event_id == contract_event.event_id
+        }) {
+            contract_events.push(contract_event);
+        }
+
+        Ok(1)
+    }
+
+    async fn save_transfer(&self, _transfer: TransferEntry) -> Result<u64, DatabaseWriteError> {
+        // The transfers are derived from the stored processed deploys when read.
+        Ok(1)
+    }
+
+    async fn clear_derived_table(&self, table: DerivedTable) -> Result<u64, DatabaseWriteError> {
+        fn clear<T>(rows: &Mutex<Vec<T>>) -> u64 {
+            let mut rows = rows.lock().expect("Error acquiring lock on derived rows");
+            let cleared = rows.len() as u64;
+            rows.clear();
+            cleared
+        }
+
+        Ok(match table {
+            DerivedTable::Transfers => 0,
+            DerivedTable::TokenTransfers => clear(&self.token_transfers),
+            DerivedTable::NftEvents => clear(&self.nft_events),
+            DerivedTable::ContractEvents => clear(&self.contract_events),
+        })
+    }
+
+    async fn save_network_identity(
+        &self,
+        network_identity: NetworkIdentity,
+    ) -> Result<u64, DatabaseWriteError> {
+        let mut stored = self
+            .network_identity
+            .lock()
+            .expect("Error acquiring lock on network identity");
+        if stored.is_some() {
+            return Ok(0);
+        }
+        *stored = Some(network_identity);
+        Ok(1)
+    }
+
+    async fn save_finality_signature_checkpoint(
+        &self,
+        checkpoint: FinalitySignatureCheckpoint,
+    ) -> Result<u64, DatabaseWriteError> {
+        let mut data = self.data.lock().expect("Error acquiring lock on data");
+        let is_finality_signature = data
+            .get(&checkpoint.block_hash)
+            .is_some_and(|event| serde_json::from_str::<FinSig>(event).is_ok());
+        let deleted = if is_finality_signature {
+            data.remove(&checkpoint.block_hash);
+            1
+        } else {
+            0
+        };
+        self.finality_signature_checkpoints
+            .lock()
+            .expect("Error acquiring lock on finality signature checkpoints")
+            .insert(checkpoint.block_hash.clone(), checkpoint);
+
+        Ok(deleted)
+    }
+
+    async fn save_webhook_delivery_attempt(
+        &self,
+        delivery_attempt: WebhookDeliveryAttempt,
+    ) -> Result<u64, DatabaseWriteError> {
+        self.webhook_delivery_attempts
+            .lock()
+            .expect("Error acquiring lock on webhook delivery attempts")
+            .push(delivery_attempt);
+
+        Ok(1)
+    }
+
+    async fn save_api_key(&self, api_key: StoredApiKey) -> Result<u64, DatabaseWriteError> {
+        let mut api_keys = self
+            .api_keys
+            .lock()
+            .expect("Error acquiring lock on API keys");
+        api_keys.retain(|stored| stored.name != api_key.name);
+        api_keys.push(api_key);
+
+        Ok(1)
+    }
+
+    async fn delete_api_key(&self, name: &str) -> Result<u64, DatabaseWriteError> {
+        let mut api_keys = self
+            .api_keys
+            .lock()
+            .expect("Error acquiring lock on API keys");
+        let count = api_keys.len();
+        api_keys.retain(|stored| stored.name != name);
+
+        Ok((count - api_keys.len()) as u64)
     }
 
     #[allow(unused)]
@@ -233,17 +607,110 @@ impl DatabaseWriter for FakeDatabase {
         let stringified_event = serde_json::to_string("{}").expect("Error serialising event data");
 
         data.insert(event_key, stringified_event);
+        Ok(1)
+    }
+
+    async fn save_audit_entry(
+        &self,
+        actor: String,
+        action: String,
+        details: Option<String>,
+    ) -> Result<u64, DatabaseWriteError> {
+        let mut audit_entries = self
+            .audit_entries
+            .lock()
+            .expect("Error acquiring lock on audit entries");
+        let id = audit_entries.len() as u64 + 1;
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs();
+
+        audit_entries.push(AuditEntry {
+            id,
+            timestamp,
+            actor,
+            action,
+            details,
+        });
+        Ok(id)
+    }
+
+    async fn save_operational_event(
+        &self,
+        kind: String,
+        details: Option<String>,
+    ) -> Result<u64, DatabaseWriteError> {
+        let mut operational_events = self
+            .operational_events
+            .lock()
+            .expect("Error acquiring lock on operational events");
+        let id = operational_events.len() as u64 + 1;
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs();
+
+        operational_events.push(OperationalEvent {
+            id,
+            timestamp,
+            kind,
+            details,
+        });
+        Ok(id)
+    }
+
+    async fn prune_events(
+        &self,
+        _table: PayloadTable,
+        _maybe_stored_before: Option<u64>,
+        _maybe_max_rows: Option<u64>,
+    ) -> Result<u64, DatabaseWriteError> {
         Ok(0)
     }
 
-    async fn execute_migration(&self, _migration: Migration) -> Result<(), DatabaseWriteError> {
-        //Nothing to do here
-        Ok(())
+    async fn initialize_event_id_allocator(
+        &self,
+        next_event_id: u32,
+    ) -> Result<u32, DatabaseWriteError> {
+        let mut event_id_allocator = self
+            .event_id_allocator
+            .lock()
+            .expect("Error acquiring lock on event id allocator");
+        let state = event_id_allocator.get_or_insert_with(|| EventIdAllocatorState {
+            next_event_id,
+            updated_timestamp: 0,
+        });
+        Ok(state.next_event_id)
+    }
+
+    async fn reserve_event_ids(&self, count: u32) -> Result<u32, DatabaseWriteError> {
+        let mut event_id_allocator = self
+            .event_id_allocator
+            .lock()
+            .expect("Error acquiring lock on event id allocator");
+        let state = event_id_allocator.as_mut().ok_or_else(|| {
+            DatabaseWriteError::Unhandled(anyhow::Error::msg("Event id allocator not initialized"))
+        })?;
+        let first_reserved = state.next_event_id;
+        state.next_event_id = first_reserved.wrapping_add(count);
+        state.updated_timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs();
+        Ok(first_reserved)
     }
 }
 
 #[async_trait]
 impl DatabaseReader for FakeDatabase {
+    type Snapshot = Self;
+
+    async fn snapshot(&self) -> Result<Self, DatabaseReadError> {
+        // Every read locks the whole data, so reads are consistent without a snapshot.
+        Ok(self.clone())
+    }
+
     async fn get_latest_block(&self) -> Result<BlockAdded, DatabaseReadError> {
         let mut test_rng = TestRng::new();
 
@@ -272,6 +739,27 @@ impl DatabaseReader for FakeDatabase {
         };
     }
 
+    async fn get_blocks_by_height_range(
+        &self,
+        from: u64,
+        to: u64,
+        limit: u32,
+    ) -> Result<Vec<BlockAdded>, DatabaseReadError> {
+        let data = self.data.lock().expect("Error acquiring lock on data");
+
+        let mut blocks: Vec<BlockAdded> = data
+            .iter()
+            .filter(|(key, _)| {
+                key.parse::<u64>()
+                    .map_or(false, |height| from <= height && height <= to)
+            })
+            .filter_map(|(_, event)| serde_json::from_str::<BlockAdded>(event).ok())
+            .collect();
+        blocks.sort_by_key(|block| block.get_height());
+        blocks.truncate(limit as usize);
+        Ok(blocks)
+    }
+
     async fn get_deploy_aggregate_by_hash(
         &self,
         hash: &str,
@@ -410,6 +898,38 @@ impl DatabaseReader for FakeDatabase {
         };
     }
 
+    async fn get_finality_signature_checkpoint(
+        &self,
+        block_hash: &str,
+    ) -> Result<FinalitySignatureCheckpoint, DatabaseReadError> {
+        self.finality_signature_checkpoints
+            .lock()
+            .expect("Error acquiring lock on finality signature checkpoints")
+            .get(block_hash)
+            .cloned()
+            .ok_or(DatabaseReadError::NotFound)
+    }
+
+    /// The fake doesn't record when events are stored, so all the stored signatures are treated as
+    /// stored before `stored_before`.
+    async fn get_blocks_to_checkpoint(
+        &self,
+        _stored_before: u64,
+        after: Option<String>,
+        limit: u32,
+    ) -> Result<Vec<String>, DatabaseReadError> {
+        let data = self.data.lock().expect("Error acquiring lock on data");
+        let mut block_hashes: Vec<String> = data
+            .iter()
+            .filter(|(_, event)| serde_json::from_str::<FinSig>(event).is_ok())
+            .map(|(block_hash, _)| block_hash.clone())
+            .filter(|block_hash| after.as_ref().map_or(true, |after| block_hash > after))
+            .collect();
+        block_hashes.sort();
+        block_hashes.truncate(limit as usize);
+        Ok(block_hashes)
+    }
+
     async fn get_step_by_era(&self, era: u64) -> Result<Step, DatabaseReadError> {
         let data = self.data.lock().expect("Error acquiring lock on data");
 
@@ -420,6 +940,86 @@ impl DatabaseReader for FakeDatabase {
         };
     }
 
+    async fn get_unknown_events_by_type(
+        &self,
+        event_type: &str,
+    ) -> Result<Vec<UnknownEvent>, DatabaseReadError> {
+        let unknown_events: Vec<UnknownEvent> = self
+            .unknown_events
+            .lock()
+            .expect("Error acquiring lock on unknown events")
+            .iter()
+            .filter(|unknown_event| unknown_event.event_type == event_type)
+            .cloned()
+            .collect();
+
+        if unknown_events.is_empty() {
+            return Err(DatabaseReadError::NotFound);
+        }
+        Ok(unknown_events)
+    }
+
+    async fn get_enrichments_by_hash(
+        &self,
+        entity_hash: &str,
+    ) -> Result<Vec<Enrichment>, DatabaseReadError> {
+        let mut enrichments: Vec<Enrichment> = self
+            .enrichments
+            .lock()
+            .expect("Error acquiring lock on enrichments")
+            .iter()
+            .filter(|enrichment| enrichment.entity_hash == entity_hash)
+            .cloned()
+            .collect();
+
+        if enrichments.is_empty() {
+            return Err(DatabaseReadError::NotFound);
+        }
+        enrichments.sort_by(|first, second| first.kind.cmp(&second.kind));
+        Ok(enrichments)
+    }
+
+    async fn get_derived_events_by_deriver(
+        &self,
+        deriver: &str,
+    ) -> Result<Vec<DerivedEvent>, DatabaseReadError> {
+        let derived_events: Vec<DerivedEvent> = self
+            .derived_events
+            .lock()
+            .expect("Error acquiring lock on derived events")
+            .iter()
+            .filter(|derived_event| derived_event.deriver == deriver)
+            .cloned()
+            .collect();
+
+        if derived_events.is_empty() {
+            return Err(DatabaseReadError::NotFound);
+        }
+        Ok(derived_events)
+    }
+
+    async fn get_api_keys(&self) -> Result<Vec<StoredApiKey>, DatabaseReadError> {
+        Ok(self
+            .api_keys
+            .lock()
+            .expect("Error acquiring lock on API keys")
+            .clone())
+    }
+
+    async fn get_webhook_delivery_attempts(
+        &self,
+        webhook: &str,
+    ) -> Result<Vec<WebhookDeliveryAttempt>, DatabaseReadError> {
+        Ok(self
+            .webhook_delivery_attempts
+            .lock()
+            .expect("Error acquiring lock on webhook delivery attempts")
+            .iter()
+            .filter(|delivery_attempt| delivery_attempt.webhook == webhook)
+            .cloned()
+            .collect())
+    }
+
     async fn get_number_of_events(&self) -> Result<u64, DatabaseReadError> {
         Ok(0)
     }
@@ -427,6 +1027,512 @@ impl DatabaseReader for FakeDatabase {
     async fn get_newest_migration_version(&self) -> Result<Option<(u32, bool)>, DatabaseReadError> {
         Ok(None)
     }
+
+    async fn get_audit_entries(
+        &self,
+        from_id: u64,
+        limit: u32,
+    ) -> Result<Vec<AuditEntry>, DatabaseReadError> {
+        let audit_entries = self
+            .audit_entries
+            .lock()
+            .expect("Error acquiring lock on audit entries");
+
+        Ok(audit_entries
+            .iter()
+            .filter(|entry| entry.id >= from_id)
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_operational_events(
+        &self,
+        from_id: u64,
+        limit: u32,
+    ) -> Result<Vec<OperationalEvent>, DatabaseReadError> {
+        let operational_events = self
+            .operational_events
+            .lock()
+            .expect("Error acquiring lock on operational events");
+
+        Ok(operational_events
+            .iter()
+            .filter(|event| event.id >= from_id)
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_event_id_allocator_state(
+        &self,
+    ) -> Result<EventIdAllocatorState, DatabaseReadError> {
+        self.event_id_allocator
+            .lock()
+            .expect("Error acquiring lock on event id allocator")
+            .clone()
+            .ok_or(DatabaseReadError::NotFound)
+    }
+
+    async fn get_network_identity(&self) -> Result<NetworkIdentity, DatabaseReadError> {
+        self.network_identity
+            .lock()
+            .expect("Error acquiring lock on network identity")
+            .clone()
+            .ok_or(DatabaseReadError::NotFound)
+    }
+
+    async fn get_deploy_timestamps(
+        &self,
+        from: u64,
+        to: u64,
+        after: Option<DeployTimestampEntry>,
+        limit: u32,
+    ) -> Result<Vec<DeployTimestampEntry>, DatabaseReadError> {
+        let sort_key = |entry: &DeployTimestampEntry| {
+            (
+                entry.timestamp,
+                entry.deploy_hash.clone(),
+                entry.kind.event_type_id(),
+            )
+        };
+        let mut entries: Vec<DeployTimestampEntry> = self
+            .deploy_timestamps
+            .lock()
+            .expect("Error acquiring lock on deploy timestamps")
+            .iter()
+            .filter(|entry| from <= entry.timestamp && entry.timestamp < to)
+            .filter(|entry| {
+                after
+                    .as_ref()
+                    .map_or(true, |after| sort_key(entry) > sort_key(after))
+            })
+            .cloned()
+            .collect();
+        entries.sort_by_key(sort_key);
+        entries.truncate(limit as usize);
+        Ok(entries)
+    }
+
+    async fn get_deploys_by_error_message(
+        &self,
+        contains: &str,
+        after: Option<String>,
+        limit: u32,
+    ) -> Result<Vec<DeployErrorEntry>, DatabaseReadError> {
+        let data = self.data.lock().expect("Error acquiring lock on data");
+        let mut entries: Vec<DeployErrorEntry> = data
+            .iter()
+            .filter(|(identifier, _)| identifier.ends_with("-processed"))
+            .filter_map(|(_, event)| serde_json::from_str::<DeployProcessed>(event).ok())
+            .filter_map(|deploy_processed| {
+                let error_message = deploy_processed.error_message()?;
+                if !error_message.contains(contains) {
+                    return None;
+                }
+                Some(DeployErrorEntry {
+                    deploy_hash: deploy_processed.hex_encoded_hash(),
+                    block_hash: deploy_processed.hex_encoded_block_hash(),
+                    error_message: error_message.to_string(),
+                })
+            })
+            .filter(|entry| {
+                after
+                    .as_ref()
+                    .map_or(true, |after| entry.deploy_hash > *after)
+            })
+            .collect();
+        entries.sort_by(|first, second| first.deploy_hash.cmp(&second.deploy_hash));
+        entries.truncate(limit as usize);
+        Ok(entries)
+    }
+
+    async fn get_deploys_by_account(
+        &self,
+        account_hash: &str,
+        processed_by: Option<u64>,
+        after: Option<String>,
+        limit: u32,
+    ) -> Result<Vec<DeployAccountEntry>, DatabaseReadError> {
+        let data = self.data.lock().expect("Error acquiring lock on data");
+        let deploy_timestamps = self
+            .deploy_timestamps
+            .lock()
+            .expect("Error acquiring lock on deploy timestamps");
+        let is_processed_by = |deploy_hash: &str| {
+            processed_by.map_or(true, |processed_by| {
+                deploy_timestamps.iter().any(|entry| {
+                    entry.deploy_hash == deploy_hash
+                        && entry.kind == DeployTimestampKind::Processed
+                        && entry.timestamp <= processed_by
+                })
+            })
+        };
+        let mut entries: Vec<DeployAccountEntry> = data
+            .iter()
+            .filter(|(identifier, _)| identifier.ends_with("-accepted"))
+            .filter_map(|(_, event)| serde_json::from_str::<DeployAccepted>(event).ok())
+            .filter(|deploy_accepted| deploy_accepted.hex_encoded_account_hash() == account_hash)
+            .map(|deploy_accepted| DeployAccountEntry {
+                deploy_hash: deploy_accepted.hex_encoded_hash(),
+                account_hash: account_hash.to_string(),
+                account_alias: None,
+            })
+            .filter(|entry| {
+                after
+                    .as_ref()
+                    .map_or(true, |after| entry.deploy_hash > *after)
+            })
+            .filter(|entry| is_processed_by(&entry.deploy_hash))
+            .collect();
+        entries.sort_by(|first, second| first.deploy_hash.cmp(&second.deploy_hash));
+        entries.truncate(limit as usize);
+        Ok(entries)
+    }
+
+    async fn get_deploys_by_account_page(
+        &self,
+        account_hash: &str,
+        processed_by: Option<u64>,
+        offset: u32,
+        limit: u32,
+    ) -> Result<Vec<DeployAccountEntry>, DatabaseReadError> {
+        let entries = self
+            .get_deploys_by_account(account_hash, processed_by, None, u32::MAX)
+            .await?;
+        let deploy_timestamps = self
+            .deploy_timestamps
+            .lock()
+            .expect("Error acquiring lock on deploy timestamps");
+        let accepted_at = |deploy_hash: &str| {
+            deploy_timestamps
+                .iter()
+                .find(|entry| {
+                    entry.deploy_hash == deploy_hash && entry.kind == DeployTimestampKind::Accepted
+                })
+                .map(|entry| entry.timestamp)
+        };
+        let mut entries: Vec<(u64, DeployAccountEntry)> = entries
+            .into_iter()
+            .filter_map(|entry| accepted_at(&entry.deploy_hash).map(|timestamp| (timestamp, entry)))
+            .collect();
+        entries.sort_by(|(first_timestamp, first), (second_timestamp, second)| {
+            (first_timestamp, &first.deploy_hash).cmp(&(second_timestamp, &second.deploy_hash))
+        });
+        Ok(entries
+            .into_iter()
+            .map(|(_, entry)| entry)
+            .skip(offset as usize)
+            .take(limit as usize)
+            .collect())
+    }
+
+    async fn get_deploy_approvals(
+        &self,
+        hash: &str,
+    ) -> Result<Vec<DeployApprovalEntry>, DatabaseReadError> {
+        let mut entries: Vec<DeployApprovalEntry> = self
+            .deploy_approvals
+            .lock()
+            .expect("Error acquiring lock on deploy approvals")
+            .iter()
+            .filter(|entry| entry.deploy_hash == hash)
+            .cloned()
+            .collect();
+        entries.sort_by(|first, second| {
+            (first.event_log_id, &first.signer).cmp(&(second.event_log_id, &second.signer))
+        });
+        Ok(entries)
+    }
+
+    async fn get_deploy_event_times(
+        &self,
+        hash: &str,
+    ) -> Result<Vec<DeployEventTime>, DatabaseReadError> {
+        Ok(self
+            .deploy_event_times
+            .lock()
+            .expect("Error acquiring lock on deploy event times")
+            .iter()
+            .filter(|(deploy_hash, _)| deploy_hash == hash)
+            .map(|(_, event_time)| event_time.clone())
+            .collect())
+    }
+
+    async fn get_deploys_affecting_account(
+        &self,
+        account_hash: &str,
+        after: Option<String>,
+        limit: u32,
+    ) -> Result<Vec<AccountEffectEntry>, DatabaseReadError> {
+        let data = self.data.lock().expect("Error acquiring lock on data");
+        let mut entries: Vec<AccountEffectEntry> = data
+            .iter()
+            .filter(|(identifier, _)| identifier.ends_with("-processed"))
+            .filter_map(|(_, event)| serde_json::from_str::<DeployProcessed>(event).ok())
+            .filter(|deploy_processed| {
+                deploy_processed
+                    .hex_encoded_affected_account_hashes()
+                    .iter()
+                    .any(|affected| affected == account_hash)
+            })
+            .map(|deploy_processed| AccountEffectEntry {
+                deploy_hash: deploy_processed.hex_encoded_hash(),
+                account_hash: account_hash.to_string(),
+            })
+            .filter(|entry| {
+                after
+                    .as_ref()
+                    .map_or(true, |after| entry.deploy_hash > *after)
+            })
+            .collect();
+        entries.sort_by(|first, second| first.deploy_hash.cmp(&second.deploy_hash));
+        entries.truncate(limit as usize);
+        Ok(entries)
+    }
+
+    async fn get_transfers_by_account(
+        &self,
+        account_hash: &str,
+        after: Option<String>,
+        limit: u32,
+    ) -> Result<Vec<TransferEntry>, DatabaseReadError> {
+        let mut transfers: Vec<TransferEntry> = self
+            .stored_transfers()
+            .into_iter()
+            .filter(|transfer| {
+                transfer.from == account_hash || transfer.to.as_deref() == Some(account_hash)
+            })
+            .filter(|transfer| {
+                after
+                    .as_ref()
+                    .map_or(true, |after| transfer.transfer_hash > *after)
+            })
+            .collect();
+        transfers.truncate(limit as usize);
+        Ok(transfers)
+    }
+
+    async fn get_transfers_by_transfer_id(
+        &self,
+        transfer_id: u64,
+        after: Option<String>,
+        limit: u32,
+    ) -> Result<Vec<TransferEntry>, DatabaseReadError> {
+        let mut transfers: Vec<TransferEntry> = self
+            .stored_transfers()
+            .into_iter()
+            .filter(|transfer| transfer.id == Some(transfer_id))
+            .filter(|transfer| {
+                after
+                    .as_ref()
+                    .map_or(true, |after| transfer.transfer_hash > *after)
+            })
+            .collect();
+        transfers.truncate(limit as usize);
+        Ok(transfers)
+    }
+
+    async fn get_transfers_by_block(
+        &self,
+        block_hash: &str,
+    ) -> Result<Vec<TransferEntry>, DatabaseReadError> {
+        Ok(self
+            .stored_transfers()
+            .into_iter()
+            .filter(|transfer| transfer.block_hash == block_hash)
+            .collect())
+    }
+
+    async fn get_token_transfers(
+        &self,
+        contract_hash: &str,
+        after: Option<u64>,
+        limit: u32,
+    ) -> Result<Vec<TokenTransferEntry>, DatabaseReadError> {
+        let mut token_transfers: Vec<TokenTransferEntry> = self
+            .token_transfers
+            .lock()
+            .expect("Error acquiring lock on token transfers")
+            .iter()
+            .filter(|token_transfer| token_transfer.contract_hash == contract_hash)
+            .filter(|token_transfer| after.map_or(true, |after| token_transfer.event_id > after))
+            .cloned()
+            .collect();
+        token_transfers.sort_by_key(|token_transfer| token_transfer.event_id);
+        token_transfers.truncate(limit as usize);
+        Ok(token_transfers)
+    }
+
+    async fn get_nft_events(
+        &self,
+        contract_hash: &str,
+        token_id: Option<&str>,
+        after: Option<u64>,
+        limit: u32,
+    ) -> Result<Vec<NftEventEntry>, DatabaseReadError> {
+        let mut nft_events: Vec<NftEventEntry> = self
+            .nft_events
+            .lock()
+            .expect("Error acquiring lock on NFT events")
+            .iter()
+            .filter(|nft_event| nft_event.contract_hash == contract_hash)
+            .filter(|nft_event| token_id.map_or(true, |token_id| nft_event.token_id == token_id))
+            .filter(|nft_event| after.map_or(true, |after| nft_event.event_id > after))
+            .cloned()
+            .collect();
+        nft_events.sort_by_key(|nft_event| nft_event.event_id);
+        nft_events.truncate(limit as usize);
+        Ok(nft_events)
+    }
+
+    async fn get_contract_event_schemas(
+        &self,
+        contract_hash: &str,
+    ) -> Result<ContractEventSchemas, DatabaseReadError> {
+        self.contract_event_schemas
+            .lock()
+            .expect("Error acquiring lock on contract event schemas")
+            .get(contract_hash)
+            .cloned()
+            .ok_or(DatabaseReadError::NotFound)
+    }
+
+    async fn get_contract_events(
+        &self,
+        contract_hash: &str,
+        event_name: Option<&str>,
+        after: Option<u64>,
+        limit: u32,
+    ) -> Result<Vec<ContractEventEntry>, DatabaseReadError> {
+        let mut contract_events: Vec<ContractEventEntry> = self
+            .contract_events
+            .lock()
+            .expect("Error acquiring lock on contract events")
+            .iter()
+            .filter(|contract_event| contract_event.contract_hash == contract_hash)
+            .filter(|contract_event| {
+                event_name.map_or(true, |event_name| contract_event.event_name == event_name)
+            })
+            .filter(|contract_event| after.map_or(true, |after| contract_event.event_id > after))
+            .cloned()
+            .collect();
+        contract_events.sort_by_key(|contract_event| contract_event.event_id);
+        contract_events.truncate(limit as usize);
+        Ok(contract_events)
+    }
+
+    async fn get_contract_stats(
+        &self,
+        contract_hash: &str,
+    ) -> Result<Vec<ContractDayStats>, DatabaseReadError> {
+        let data = self.data.lock().expect("Error acquiring lock on data");
+        let mut stats_by_day: BTreeMap<String, ContractDayStats> = BTreeMap::new();
+        for (identifier, event) in data.iter() {
+            let hash = match identifier.strip_suffix("-processed") {
+                Some(hash) => hash,
+                None => continue,
+            };
+            let deploy_accepted = match data
+                .get(&format!("{}-accepted", hash))
+                .and_then(|accepted| serde_json::from_str::<DeployAccepted>(accepted).ok())
+            {
+                Some(deploy_accepted) => deploy_accepted,
+                None => continue,
+            };
+            if deploy_accepted.hex_encoded_target_contract().as_deref() != Some(contract_hash) {
+                continue;
+            }
+            let deploy_processed = serde_json::from_str::<DeployProcessed>(event)
+                .map_err(DatabaseReadError::Serialisation)?;
+            let day = deploy_processed.timestamp_day();
+            let stats = stats_by_day
+                .entry(day.clone())
+                .or_insert_with(|| ContractDayStats {
+                    day,
+                    successes: 0,
+                    failures: 0,
+                    gas_spent: 0,
+                });
+            if deploy_processed.error_message().is_none() {
+                stats.successes += 1;
+            } else {
+                stats.failures += 1;
+            }
+            stats.gas_spent += deploy_processed.gas_spent();
+        }
+        if stats_by_day.is_empty() {
+            return Err(DatabaseReadError::NotFound);
+        }
+        Ok(stats_by_day.into_values().collect())
+    }
+
+    async fn get_deploy_size_stats(&self) -> Result<DeploySizeStats, DatabaseReadError> {
+        let data = self.data.lock().expect("Error acquiring lock on data");
+        let deploys: Vec<DeployAccepted> = data
+            .iter()
+            .filter(|(identifier, _)| identifier.ends_with("-accepted"))
+            .filter_map(|(_, event)| serde_json::from_str::<DeployAccepted>(event).ok())
+            .collect();
+        if deploys.is_empty() {
+            return Err(DatabaseReadError::NotFound);
+        }
+        let distribution = |measure: fn(&DeployAccepted) -> usize| {
+            let mut values: Vec<u64> = deploys
+                .iter()
+                .map(|deploy| measure(deploy) as u64)
+                .collect();
+            values.sort_unstable();
+            Distribution::of_sorted(&values)
+        };
+        Ok(DeploySizeStats {
+            deploys: deploys.len() as u64,
+            size_bytes: distribution(DeployAccepted::serialized_length),
+            approvals: distribution(DeployAccepted::approvals_count),
+            args: distribution(DeployAccepted::args_count),
+        })
+    }
+
+    async fn get_era_validators(&self, era: u64) -> Result<Vec<String>, DatabaseReadError> {
+        let data = self.data.lock().expect("Error acquiring lock on data");
+
+        let event = data
+            .get(&format!("{}-validators", era))
+            .ok_or(DatabaseReadError::NotFound)?;
+        serde_json::from_str::<Vec<String>>(event).map_err(DatabaseReadError::Serialisation)
+    }
+
+    async fn get_era_validator_weights(
+        &self,
+        era: u64,
+    ) -> Result<Vec<EraValidatorWeight>, DatabaseReadError> {
+        let data = self.data.lock().expect("Error acquiring lock on data");
+
+        let event = data
+            .get(&format!("{}-validator-weights", era))
+            .ok_or(DatabaseReadError::NotFound)?;
+        serde_json::from_str::<Vec<EraValidatorWeight>>(event)
+            .map_err(DatabaseReadError::Serialisation)
+    }
+
+    async fn get_raw_payload_sample(
+        &self,
+        _table: PayloadTable,
+        _limit: u32,
+    ) -> Result<Vec<String>, DatabaseReadError> {
+        // The fake doesn't keep the events by table, so there is nothing to sample.
+        Err(DatabaseReadError::NotFound)
+    }
+
+    async fn get_raw_payloads_after(
+        &self,
+        _table: PayloadTable,
+        _after: u64,
+        _limit: u32,
+    ) -> Result<Vec<RawPayloadEntry>, DatabaseReadError> {
+        // The fake doesn't keep the events by table, so there is nothing to export.
+        Ok(Vec::new())
+    }
 }
 
 pub struct IdentifiersForStoredEvents {
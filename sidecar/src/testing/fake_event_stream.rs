@@ -1,12 +1,9 @@
-use core::time;
 use derive_new::new;
 use itertools::Itertools;
-use serde_json::json;
 use std::{
     fmt::{Display, Formatter},
     iter,
     ops::Div,
-    thread,
     time::Duration,
 };
 use tempfile::TempDir;
@@ -17,11 +14,12 @@ use tokio::{
 
 use crate::{
     event_stream_server::{Config as EssConfig, EventStreamServer},
+    testing::mock_node::tests::setup_mock_build_version_server_with_version,
     utils::tests::display_duration,
+    watch_list::WatchList,
 };
 use casper_event_types::{sse_data::SseData, Filter as SseFilter};
 use casper_types::{testing::TestRng, ProtocolVersion};
-use warp::{path::end, Filter};
 
 const TIME_BETWEEN_BLOCKS: Duration = Duration::from_secs(30);
 const BLOCKS_IN_ERA: u64 = 4;
@@ -174,8 +172,14 @@ fn build_event_stream_server(
     println!("{} :: Started", log_details);
     let temp_dir = TempDir::new().expect("Error creating temporary directory");
 
-    let event_stream_server = EventStreamServer::new(ess_config, temp_dir.path().to_path_buf())
-        .expect("Error spinning up Event Stream Server");
+    let event_stream_server = EventStreamServer::new(
+        ess_config,
+        temp_dir.path().to_path_buf(),
+        None,
+        WatchList::default(),
+        None,
+    )
+    .expect("Error spinning up Event Stream Server");
     (event_stream_server, log_details)
 }
 
@@ -193,7 +197,10 @@ async fn do_spam_testing(
 
     let broadcasting_task = tokio::spawn(async move {
         while let Some(event) = events_receiver.recv().await {
-            event_stream_server.broadcast(event, Some(SseFilter::Main), None);
+            event_stream_server
+                .broadcast(event, Some(SseFilter::Main), None)
+                .await
+                .expect("Error broadcasting event");
         }
     });
 
@@ -236,7 +243,10 @@ async fn do_load_testing_deploy(
 
     let broadcasting_task = tokio::spawn(async move {
         while let Some(event) = events_receiver.recv().await {
-            event_stream_server.broadcast(event, Some(SseFilter::Main), None);
+            event_stream_server
+                .broadcast(event, Some(SseFilter::Main), None)
+                .await
+                .expect("Error broadcasting event");
         }
     });
 
@@ -278,7 +288,10 @@ async fn do_load_testing_step(
     });
     let broadcasting_task = tokio::spawn(async move {
         while let Some(event) = events_receiver.recv().await {
-            event_stream_server.broadcast(event, Some(SseFilter::Main), None);
+            event_stream_server
+                .broadcast(event, Some(SseFilter::Main), None)
+                .await
+                .expect("Error broadcasting event");
         }
     });
     let (test_rng, _) = tokio::join!(scenario_task, broadcasting_task);
@@ -313,7 +326,10 @@ async fn handle_realistic_scenario(
     });
     let broadcasting_task = tokio::spawn(async move {
         while let Some(event) = events_receiver.recv().await {
-            event_stream_server.broadcast(event, Some(SseFilter::Main), None);
+            event_stream_server
+                .broadcast(event, Some(SseFilter::Main), None)
+                .await
+                .expect("Error broadcasting event");
         }
     });
     let (test_rng, _) = tokio::join!(scenario_task, broadcasting_task);
@@ -596,57 +612,3 @@ async fn load_testing_deploy(
 pub async fn setup_mock_build_version_server(port: u16) -> (Sender<()>, Receiver<()>) {
     setup_mock_build_version_server_with_version(port, "1.5.2".to_string()).await
 }
-
-pub async fn setup_mock_build_version_server_with_version(
-    port: u16,
-    version: String,
-) -> (Sender<()>, Receiver<()>) {
-    let (shutdown_tx, mut shutdown_rx) = mpsc_channel(10);
-    let (after_shutdown_tx, after_shutdown_rx) = mpsc_channel(10);
-    let api = warp::path!("status")
-        .and(warp::get())
-        .map(move || {
-            let result = json!({ "build_version": version.clone() });
-            warp::reply::json(&result)
-        })
-        .and(end());
-    let server_thread = tokio::spawn(async move {
-        let server = warp::serve(api)
-            .bind_with_graceful_shutdown(([127, 0, 0, 1], port), async move {
-                let _ = shutdown_rx.recv().await;
-            })
-            .1;
-        server.await;
-        let _ = after_shutdown_tx.send(()).await;
-    });
-
-    tokio::spawn(async move {
-        let _ = server_thread.await;
-    });
-    wait_for_build_version_server_to_be_up(port).await;
-    (shutdown_tx, after_shutdown_rx)
-}
-
-pub async fn wait_for_build_version_server_to_be_up(port: u16) {
-    let max_attempts = 10;
-    let mut attempts = 0;
-    loop {
-        attempts += 1;
-        if attempts >= max_attempts {
-            panic!(
-                "Couldn't connect to status server in {} attempts",
-                max_attempts
-            );
-        }
-        let res = reqwest::get(format!("http://127.0.0.1:{}/status", port)).await;
-        match res {
-            Err(_) => {}
-            Ok(response) => {
-                if response.text().await.unwrap().contains("build_version") {
-                    break;
-                }
-            }
-        }
-        thread::sleep(time::Duration::from_secs(1));
-    }
-}
@@ -1,15 +1,16 @@
-#[cfg(test)]
+#[cfg(any(test, feature = "smoke-test"))]
 pub mod tests {
-    use std::time::Duration;
+    use std::{thread, time::Duration};
 
-    use crate::testing::fake_event_stream::setup_mock_build_version_server_with_version;
+    use crate::testing::ports::get_port;
     use crate::testing::raw_sse_events_utils::tests::{
         example_data_1_5_3, simple_sse_server, sse_server_example_data, EventsWithIds,
     };
-    use crate::testing::testing_config::get_port;
     use futures::join;
-    use tokio::sync::mpsc::{Receiver, Sender};
+    use serde_json::json;
+    use tokio::sync::mpsc::{channel as mpsc_channel, Receiver, Sender};
     use tokio::task::JoinHandle;
+    use warp::{path::end, Filter};
 
     pub struct MockNodeBuilder {
         pub version: String,
@@ -202,4 +203,58 @@ pub mod tests {
             panic!("{}", potential_error_message);
         }
     }
+
+    pub async fn setup_mock_build_version_server_with_version(
+        port: u16,
+        version: String,
+    ) -> (Sender<()>, Receiver<()>) {
+        let (shutdown_tx, mut shutdown_rx) = mpsc_channel(10);
+        let (after_shutdown_tx, after_shutdown_rx) = mpsc_channel(10);
+        let api = warp::path!("status")
+            .and(warp::get())
+            .map(move || {
+                let result = json!({ "build_version": version.clone() });
+                warp::reply::json(&result)
+            })
+            .and(end());
+        let server_thread = tokio::spawn(async move {
+            let server = warp::serve(api)
+                .bind_with_graceful_shutdown(([127, 0, 0, 1], port), async move {
+                    let _ = shutdown_rx.recv().await;
+                })
+                .1;
+            server.await;
+            let _ = after_shutdown_tx.send(()).await;
+        });
+
+        tokio::spawn(async move {
+            let _ = server_thread.await;
+        });
+        wait_for_build_version_server_to_be_up(port).await;
+        (shutdown_tx, after_shutdown_rx)
+    }
+
+    pub async fn wait_for_build_version_server_to_be_up(port: u16) {
+        let max_attempts = 10;
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            if attempts >= max_attempts {
+                panic!(
+                    "Couldn't connect to status server in {} attempts",
+                    max_attempts
+                );
+            }
+            let res = reqwest::get(format!("http://127.0.0.1:{}/status", port)).await;
+            match res {
+                Err(_) => {}
+                Ok(response) => {
+                    if response.text().await.unwrap().contains("build_version") {
+                        break;
+                    }
+                }
+            }
+            thread::sleep(Duration::from_secs(1));
+        }
+    }
 }
@@ -0,0 +1,22 @@
+use once_cell::sync::Lazy;
+use std::sync::{Arc, Mutex};
+
+static USED_PORTS: Lazy<Arc<Mutex<Vec<u16>>>> = Lazy::new(|| Arc::new(Mutex::new(Vec::new())));
+
+/// This function (used in tests and the smoke test only) is used to make sure that concurrently
+/// running IT tests don't accidentally pick the same port. If in the future our tests would run
+/// slowly or not run at all because of this we need to figure out a way of returning ports after an IT test finishes
+pub fn get_port() -> u16 {
+    let mut guard = USED_PORTS.lock().unwrap();
+    let mut maybe_port = portpicker::pick_unused_port().unwrap();
+    let mut attempt = 0;
+    while guard.contains(&maybe_port) {
+        maybe_port = portpicker::pick_unused_port().unwrap();
+        attempt += 1;
+        if attempt > 100 {
+            panic!("Couldn't find a unique port in {} tries!", attempt);
+        }
+    }
+    guard.push(maybe_port);
+    maybe_port
+}
@@ -1,4 +1,4 @@
-#[cfg(test)]
+#[cfg(any(test, feature = "smoke-test"))]
 pub(crate) mod tests {
     use crate::testing::simple_sse_server::tests::{CacheAndData, SimpleSseServer};
     use casper_event_types::sse_data::test_support::*;
@@ -1,5 +1,9 @@
-use casper_event_types::sse_data::SseData;
-use std::fmt::{Display, Formatter};
+use casper_event_types::{sse_data::SseData, Filter};
+use std::{
+    fmt::{Display, Formatter},
+    time::Duration,
+};
+use tokio::sync::mpsc::Receiver;
 
 #[derive(Clone, Debug, PartialEq)]
 pub(crate) enum EventType {
@@ -13,6 +17,8 @@ pub(crate) enum EventType {
     FinalitySignature,
     Step,
     Shutdown,
+    Unknown,
+    Derived,
 }
 
 impl From<SseData> for EventType {
@@ -28,6 +34,8 @@ impl From<SseData> for EventType {
             SseData::FinalitySignature(_) => EventType::FinalitySignature,
             SseData::Step { .. } => EventType::Step,
             SseData::Shutdown => EventType::Shutdown,
+            SseData::Unknown { .. } => EventType::Unknown,
+            SseData::Derived { .. } => EventType::Derived,
         }
     }
 }
@@ -45,7 +53,32 @@ impl Display for EventType {
             EventType::FinalitySignature => "FinalitySignature",
             EventType::Step => "Step",
             EventType::Shutdown => "Shutdown",
+            EventType::Unknown => "Unknown",
+            EventType::Derived => "Derived",
         };
         write!(f, "{}", string)
     }
 }
+
+/// The payload of the next event published on the outbound channel, which has to be an event
+/// derived by `expected_deriver`, published on every stream.
+pub(crate) async fn recv_derived_payload(
+    outbound_receiver: &mut Receiver<(SseData, Option<Filter>, Option<String>)>,
+    expected_deriver: &str,
+) -> serde_json::Value {
+    let (sse_data, inbound_filter, _) =
+        tokio::time::timeout(Duration::from_secs(10), outbound_receiver.recv())
+            .await
+            .expect("No derived event was published")
+            .unwrap();
+    assert!(inbound_filter.is_none());
+    match sse_data {
+        SseData::Derived {
+            deriver, payload, ..
+        } => {
+            assert_eq!(deriver, expected_deriver);
+            serde_json::from_str(payload.get()).unwrap()
+        }
+        other => panic!("expected a derived event, got {:?}", other),
+    }
+}
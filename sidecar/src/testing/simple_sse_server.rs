@@ -1,4 +1,4 @@
-#[cfg(test)]
+#[cfg(any(test, feature = "smoke-test"))]
 pub(crate) mod tests {
     use async_stream::stream;
     use futures::Stream;
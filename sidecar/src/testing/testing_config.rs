@@ -1,37 +1,17 @@
 #[cfg(test)]
 use portpicker::Port;
-use std::sync::{Arc, Mutex};
 use tempfile::TempDir;
 
-use crate::types::config::{Config, Connection, StorageConfig};
+use crate::{
+    testing::ports::get_port,
+    types::config::{Config, Connection, StorageConfig},
+};
 
 /// A basic wrapper with helper methods for constructing and tweaking [Config]s for use in tests.
 pub struct TestingConfig {
     pub(crate) config: Config,
 }
 
-#[cfg(test)]
-use once_cell::sync::Lazy;
-#[cfg(test)]
-static USED_PORTS: Lazy<Arc<Mutex<Vec<u16>>>> = Lazy::new(|| Arc::new(Mutex::new(Vec::new())));
-#[cfg(test)]
-/// This function (used in tests only) is used to make sure that concurrently running
-/// IT tests don't accidentally pick the same port. If in the future our tests would run
-/// slowly or not run at all because of this we need to figure out a way of returning ports after an IT test finishes
-pub fn get_port() -> u16 {
-    let mut guard = USED_PORTS.lock().unwrap();
-    let mut maybe_port = portpicker::pick_unused_port().unwrap();
-    let mut attempt = 0;
-    while guard.contains(&maybe_port) {
-        maybe_port = portpicker::pick_unused_port().unwrap();
-        attempt += 1;
-        if attempt > 100 {
-            panic!("Couldn't find a unique port in {} tries!", attempt);
-        }
-    }
-    guard.push(maybe_port);
-    maybe_port
-}
 /// Prepares an instance of [TestingConfig]. The instance has default values except:
 /// - `storage_path` is set to the path of the [TempDir] provided.
 /// - `node_connection_port` is set dynamically to a free port.
@@ -84,6 +64,8 @@ impl TestingConfig {
             connection_timeout_in_seconds: Some(100),
             sleep_between_keep_alive_checks_in_seconds: Some(100),
             no_message_timeout_in_seconds: Some(100),
+            gap_grace_period_in_seconds: None,
+            priority: None,
         };
         self.config.connections.push(connection);
         random_port_for_sse
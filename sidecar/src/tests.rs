@@ -1,3 +1,5 @@
 pub mod integration_tests;
 pub mod integration_tests_version_switch;
+// The throughput of the sidecar is measured against an embedded PostgreSQL database.
+#[cfg(feature = "postgres")]
 pub mod performance_tests;
@@ -24,6 +24,7 @@ use crate::{
         testing_config::{prepare_config, TestingConfig},
     },
     types::{
+        config::NetworkConfig,
         database::DatabaseWriter,
         sse_events::{BlockAdded, Fault},
     },
@@ -54,6 +55,30 @@ async fn should_not_allow_zero_max_attempts() {
     );
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn should_not_allow_networks_of_the_same_name() {
+    let temp_storage_dir = tempdir().expect("Should have created a temporary storage directory");
+
+    let mut testing_config = prepare_config(&temp_storage_dir);
+    testing_config.add_connection(None, None, None);
+    let mut config = testing_config.inner();
+    let network = NetworkConfig {
+        name: "casper".to_string(),
+        connections: std::mem::take(&mut config.connections),
+        storage: config.storage.namespaced("casper"),
+    };
+    config.networks = vec![network.clone(), network];
+
+    let error = run(config)
+        .await
+        .expect_err("Sidecar should refuse networks of the same name");
+
+    assert_eq!(
+        error.to_string(),
+        "Unable to run: network casper is configured more than once"
+    );
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
 async fn given_sidecar_when_only_node_shuts_down_then_shut_down() {
     let (
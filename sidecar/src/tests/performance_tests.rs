@@ -157,6 +157,8 @@ pub(crate) enum EventType {
     FinalitySignature,
     Step,
     Shutdown,
+    Unknown,
+    Derived,
 }
 
 impl From<SseData> for EventType {
@@ -172,6 +174,8 @@ impl From<SseData> for EventType {
             SseData::FinalitySignature(_) => EventType::FinalitySignature,
             SseData::Step { .. } => EventType::Step,
             SseData::Shutdown => EventType::Shutdown,
+            SseData::Unknown { .. } => EventType::Unknown,
+            SseData::Derived { .. } => EventType::Derived,
         }
     }
 }
@@ -189,6 +193,8 @@ impl Display for EventType {
             EventType::FinalitySignature => "FinalitySignature",
             EventType::Step => "Step",
             EventType::Shutdown => "Shutdown",
+            EventType::Unknown => "Unknown",
+            EventType::Derived => "Derived",
         };
         write!(f, "{}", string)
     }
@@ -213,6 +219,8 @@ impl TimestampedEvent {
             SseData::FinalitySignature(signature) => signature.signature().to_string(),
             SseData::Step { era_id, .. } => era_id.to_string(),
             SseData::Shutdown => "Shutdown".to_string(),
+            SseData::Unknown { event_type, .. } => event_type.clone(),
+            SseData::Derived { deriver, .. } => deriver.clone(),
         }
     }
 
@@ -230,7 +238,9 @@ impl TimestampedEvent {
             | (SseData::Fault { .. }, SseData::Fault { .. })
             | (SseData::FinalitySignature(_), SseData::FinalitySignature(_))
             | (SseData::Step { .. }, SseData::Step { .. })
-            | (SseData::Shutdown, SseData::Shutdown) => (),
+            | (SseData::Shutdown, SseData::Shutdown)
+            | (SseData::Unknown { .. }, SseData::Unknown { .. })
+            | (SseData::Derived { .. }, SseData::Derived { .. }) => (),
             _ => return false,
         }
         self.identifier() == other.identifier()
@@ -268,7 +278,7 @@ async fn performance_check(scenario: Scenario, duration: Duration, acceptable_la
     let (_shutdown_tx, _after_shutdown_rx) =
         setup_mock_build_version_server(node_port_for_rest_connection).await;
 
-    let ess_config = EssConfig::new(node_port_for_sse_connection, None, None);
+    let ess_config = EssConfig::new(node_port_for_sse_connection, None, None, None);
 
     tokio::spawn(spin_up_fake_event_stream(test_rng, ess_config, scenario));
 
@@ -291,6 +301,10 @@ async fn performance_check(scenario: Scenario, duration: Duration, acceptable_la
         connection_timeout: Duration::from_secs(100),
         sleep_between_keep_alive_checks: Duration::from_secs(100),
         no_message_timeout: Duration::from_secs(100),
+        capture_sender: None,
+        keep_alive_sender: None,
+        gap_grace_period: Duration::from_secs(5),
+        gap_sender: None,
     }
     .build()
     .unwrap();
@@ -318,6 +332,10 @@ async fn performance_check(scenario: Scenario, duration: Duration, acceptable_la
         connection_timeout: Duration::from_secs(100),
         sleep_between_keep_alive_checks: Duration::from_secs(100),
         no_message_timeout: Duration::from_secs(100),
+        capture_sender: None,
+        keep_alive_sender: None,
+        gap_grace_period: Duration::from_secs(5),
+        gap_sender: None,
     }
     .build()
     .unwrap();
@@ -656,7 +674,7 @@ async fn fake_event_stream(
     let test_rng = TestRng::new();
     let (_shutdown_tx, _after_shutdown_rx) =
         setup_mock_build_version_server(node_port_for_rest_connection).await;
-    let ess_config = EssConfig::new(node_port_for_sse_connection, None, None);
+    let ess_config = EssConfig::new(node_port_for_sse_connection, None, None, None);
     spin_up_fake_event_stream(test_rng, ess_config, scenario).await;
 }
 
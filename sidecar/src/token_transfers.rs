@@ -0,0 +1,419 @@
+//! Transfers of CEP-18 fungible tokens, decoded from the events the token contracts emit.
+//!
+//! CEP-18 contracts emit their events following the Casper Event Standard: each event is put in
+//! the `__events` dictionary of the contract, under the number of the event, as the name of the
+//! event prefixed with `event_` followed by its fields. Such writes show up in the execution
+//! effects of the deploys as `WriteCLValue` transforms of dictionary keys whose value carries the
+//! address of the seed URef of the dictionary, which is how the events of the configured tokens
+//! are recognized.
+//!
+//! The `Mint`, `Burn`, `Transfer` and `TransferFrom` events are stored in the `TokenTransfer`
+//! table and published as `TokenTransfer` events on the `/events/derived` stream, see
+//! [crate::deploy_decoding]. The other events of the tokens, e.g. changes of allowances, are
+//! ignored.
+
+use crate::{
+    deploy_decoding::{DecodingWorker, DeployDecoder},
+    types::{
+        config::{TokenConfig, TokenTransfersConfig},
+        database::{DatabaseWriteError, DatabaseWriter, TokenTransferEntry, TokenTransferKind},
+        sse_events::DeployProcessed,
+    },
+};
+use anyhow::{anyhow, Error};
+use async_trait::async_trait;
+use casper_types::{
+    bytesrepr::{Bytes, FromBytes},
+    CLValue, Key, Transform, URef, URefAddr, U256,
+};
+use std::{collections::HashMap, convert::TryFrom};
+
+/// Name under which `TokenTransfer` events are stored and published.
+pub(crate) const TOKEN_TRANSFER_DERIVER: &str = "TokenTransfer";
+/// Prefix of the names of the events of the Casper Event Standard.
+pub(crate) const EVENT_NAME_PREFIX: &str = "event_";
+
+/// Handle used to submit the processed deploys for the decoding of token transfers.
+pub(crate) type TokenTransfers = DecodingWorker;
+
+/// Recognizes the events of the configured tokens among the transforms of the processed deploys.
+#[derive(Debug)]
+pub(crate) struct TokenDecoder {
+    /// Hex-encoded hashes of the token contracts, by the address of their events URef.
+    contract_hashes: HashMap<URefAddr, String>,
+}
+
+impl TokenDecoder {
+    /// Returns an error if the hash of a contract or the URef of its events isn't valid.
+    pub(crate) fn new(config: &TokenTransfersConfig) -> Result<Self, Error> {
+        Ok(TokenDecoder {
+            contract_hashes: parse_contract_hashes(&config.tokens)?,
+        })
+    }
+
+    /// The transfers of the configured tokens carried out by the deploy, in the order of its
+    /// transforms.
+    pub(crate) fn decode(&self, deploy_processed: &DeployProcessed) -> Vec<TokenTransferEntry> {
+        decode_standard_events(&self.contract_hashes, deploy_processed)
+            .into_iter()
+            .filter_map(|(contract_hash, event_id, event_bytes)| {
+                Some((
+                    contract_hash,
+                    event_id,
+                    TokenEvent::from_bytes(&event_bytes)?,
+                ))
+            })
+            .map(|(contract_hash, event_id, event)| TokenTransferEntry {
+                contract_hash,
+                event_id,
+                kind: event.kind,
+                from: event.from.map(|key| key.to_formatted_string()),
+                to: event.to.map(|key| key.to_formatted_string()),
+                spender: event.spender.map(|key| key.to_formatted_string()),
+                amount: event.amount,
+                deploy_hash: deploy_processed.hex_encoded_hash(),
+                block_hash: deploy_processed.hex_encoded_block_hash(),
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl DeployDecoder for TokenDecoder {
+    const DERIVER: &'static str = TOKEN_TRANSFER_DERIVER;
+
+    type Entry = TokenTransferEntry;
+
+    async fn decode_deploy<Db: DatabaseWriter + Sync>(
+        &mut self,
+        _database: &Db,
+        deploy_processed: &DeployProcessed,
+    ) -> Vec<TokenTransferEntry> {
+        self.decode(deploy_processed)
+    }
+
+    async fn save<Db: DatabaseWriter + Sync>(
+        database: &Db,
+        token_transfer: TokenTransferEntry,
+    ) -> Result<u64, DatabaseWriteError> {
+        database.save_token_transfer(token_transfer).await
+    }
+}
+
+/// Maps the address of the events URef of each of the given contracts to its lowercase hex-encoded
+/// hash. Returns an error if the hash of a contract or the URef of its events isn't valid.
+pub(crate) fn parse_contract_hashes(
+    tokens: &[TokenConfig],
+) -> Result<HashMap<URefAddr, String>, Error> {
+    let mut contract_hashes = HashMap::new();
+    for token in tokens {
+        let contract_hash = token.contract_hash.to_lowercase();
+        if !matches!(hex::decode(&contract_hash), Ok(bytes) if bytes.len() == 32) {
+            return Err(anyhow!(
+                "token contract hash {} isn't a hex-encoded hash",
+                token.contract_hash
+            ));
+        }
+        let events_uref = URef::from_formatted_str(&token.events_uref).map_err(|error| {
+            anyhow!(
+                "events URef {} of token {} isn't a formatted URef: {:?}",
+                token.events_uref,
+                token.contract_hash,
+                error
+            )
+        })?;
+        contract_hashes.insert(events_uref.addr(), contract_hash);
+    }
+    Ok(contract_hashes)
+}
+
+/// The events of the Casper Event Standard emitted by the given contracts in the deploy, in the
+/// order of its transforms, as the hash of the contract, the id of the event and its serialized
+/// name and fields.
+pub(crate) fn decode_standard_events(
+    contract_hashes: &HashMap<URefAddr, String>,
+    deploy_processed: &DeployProcessed,
+) -> Vec<(String, u64, Bytes)> {
+    deploy_processed
+        .transforms()
+        .iter()
+        .filter_map(
+            |entry| match (&entry.transform, Key::from_formatted_str(&entry.key)) {
+                (Transform::WriteCLValue(cl_value), Ok(Key::Dictionary(_))) => {
+                    decode_dictionary_value(contract_hashes, cl_value)
+                }
+                _ => None,
+            },
+        )
+        .collect()
+}
+
+/// Decodes a value put in a dictionary, returning the contract hash and the id of the event if
+/// it's an event of one of the contracts.
+fn decode_dictionary_value(
+    contract_hashes: &HashMap<URefAddr, String>,
+    cl_value: &CLValue,
+) -> Option<(String, u64, Bytes)> {
+    // Dictionary values are stored along with the address of the dictionary and their key.
+    let (event_value, remainder) = CLValue::from_bytes(cl_value.inner_bytes()).ok()?;
+    let (seed_uref_addr, remainder) = Bytes::from_bytes(remainder).ok()?;
+    let (item_key, _) = Bytes::from_bytes(remainder).ok()?;
+    let contract_hash =
+        contract_hashes.get(&URefAddr::try_from(seed_uref_addr.as_slice()).ok()?)?;
+    let event_id = parse_event_id(&item_key)?;
+    let (event_bytes, _) = Bytes::from_bytes(event_value.inner_bytes()).ok()?;
+    Some((contract_hash.clone(), event_id, event_bytes))
+}
+
+/// Parses the key of an event in the `__events` dictionary, the decimal number of the event.
+fn parse_event_id(item_key: &[u8]) -> Option<u64> {
+    match std::str::from_utf8(item_key) {
+        Ok(item_key) => item_key.parse().ok(),
+        // Keys may also be stored serialized.
+        Err(_) => String::from_bytes(item_key).ok()?.0.parse().ok(),
+    }
+}
+
+/// A token transfer event of the CEP-18 standard.
+#[derive(Debug, PartialEq, Eq)]
+struct TokenEvent {
+    kind: TokenTransferKind,
+    from: Option<Key>,
+    to: Option<Key>,
+    spender: Option<Key>,
+    amount: U256,
+}
+
+impl TokenEvent {
+    /// Parses the event, returning `None` if it isn't a token transfer event.
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (name, remainder) = String::from_bytes(bytes).ok()?;
+        match name.strip_prefix(EVENT_NAME_PREFIX)? {
+            "Mint" => Self::parse_mint(remainder),
+            "Burn" => Self::parse_burn(remainder),
+            "Transfer" => Self::parse_transfer(remainder),
+            "TransferFrom" => Self::parse_transfer_from(remainder),
+            _ => None,
+        }
+    }
+
+    fn parse_mint(bytes: &[u8]) -> Option<Self> {
+        let (recipient, remainder) = Key::from_bytes(bytes).ok()?;
+        let (amount, _) = U256::from_bytes(remainder).ok()?;
+        Some(TokenEvent {
+            kind: TokenTransferKind::Mint,
+            from: None,
+            to: Some(recipient),
+            spender: None,
+            amount,
+        })
+    }
+
+    fn parse_burn(bytes: &[u8]) -> Option<Self> {
+        let (owner, remainder) = Key::from_bytes(bytes).ok()?;
+        let (amount, _) = U256::from_bytes(remainder).ok()?;
+        Some(TokenEvent {
+            kind: TokenTransferKind::Burn,
+            from: Some(owner),
+            to: None,
+            spender: None,
+            amount,
+        })
+    }
+
+    fn parse_transfer(bytes: &[u8]) -> Option<Self> {
+        let (sender, remainder) = Key::from_bytes(bytes).ok()?;
+        let (recipient, remainder) = Key::from_bytes(remainder).ok()?;
+        let (amount, _) = U256::from_bytes(remainder).ok()?;
+        Some(TokenEvent {
+            kind: TokenTransferKind::Transfer,
+            from: Some(sender),
+            to: Some(recipient),
+            spender: None,
+            amount,
+        })
+    }
+
+    fn parse_transfer_from(bytes: &[u8]) -> Option<Self> {
+        let (spender, remainder) = Key::from_bytes(bytes).ok()?;
+        let (owner, remainder) = Key::from_bytes(remainder).ok()?;
+        let (recipient, remainder) = Key::from_bytes(remainder).ok()?;
+        let (amount, _) = U256::from_bytes(remainder).ok()?;
+        Some(TokenEvent {
+            kind: TokenTransferKind::TransferFrom,
+            from: Some(owner),
+            to: Some(recipient),
+            spender: Some(spender),
+            amount,
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{
+        testing::{fake_database::FakeDatabase, shared::recv_derived_payload},
+        types::database::DatabaseReader,
+    };
+    use casper_event_types::sse_data::SseData;
+    use casper_types::{
+        account::AccountHash, bytesrepr::ToBytes, testing::TestRng, AccessRights, CLType,
+        ExecutionResult, TransformEntry,
+    };
+    use tokio::sync::mpsc::channel;
+
+    const CONTRACT_HASH: &str = "0101010101010101010101010101010101010101010101010101010101010101";
+
+    /// The transform putting an event in the `__events` dictionary with the given seed URef.
+    pub(crate) fn event_transform(
+        seed_uref_addr: URefAddr,
+        event_id: u64,
+        fields: Vec<u8>,
+    ) -> TransformEntry {
+        let mut dictionary_value = CLValue::from_t(Bytes::from(fields))
+            .unwrap()
+            .to_bytes()
+            .unwrap();
+        dictionary_value.append(&mut Bytes::from(seed_uref_addr.to_vec()).to_bytes().unwrap());
+        dictionary_value.append(
+            &mut Bytes::from(event_id.to_string().into_bytes())
+                .to_bytes()
+                .unwrap(),
+        );
+        TransformEntry {
+            key: Key::Dictionary([event_id as u8; 32]).to_formatted_string(),
+            transform: Transform::WriteCLValue(CLValue::from_components(
+                CLType::Any,
+                dictionary_value,
+            )),
+        }
+    }
+
+    /// A `DeployProcessed` event of a deploy whose execution wrote the given transforms.
+    pub(crate) fn deploy_processed_with(
+        rng: &mut TestRng,
+        transforms: Vec<TransformEntry>,
+    ) -> SseData {
+        let mut sse_data = SseData::random_deploy_processed(rng);
+        if let SseData::DeployProcessed {
+            execution_result, ..
+        } = &mut sse_data
+        {
+            match execution_result.as_mut() {
+                ExecutionResult::Success { effect, .. }
+                | ExecutionResult::Failure { effect, .. } => effect.transforms = transforms,
+            }
+        }
+        sse_data
+    }
+
+    fn event_fields(name: &str, keys: &[Key], amount: u64) -> Vec<u8> {
+        let mut fields = name.to_string().to_bytes().unwrap();
+        for key in keys {
+            fields.append(&mut key.to_bytes().unwrap());
+        }
+        fields.append(&mut U256::from(amount).to_bytes().unwrap());
+        fields
+    }
+
+    fn config(events_uref_addr: URefAddr) -> TokenTransfersConfig {
+        TokenTransfersConfig {
+            tokens: vec![TokenConfig {
+                contract_hash: CONTRACT_HASH.to_uppercase(),
+                events_uref: URef::new(events_uref_addr, AccessRights::READ_ADD_WRITE)
+                    .to_formatted_string(),
+            }],
+            queue_size: None,
+        }
+    }
+
+    #[test]
+    fn should_decode_transfer_events_of_configured_tokens() {
+        let mut rng = TestRng::new();
+        let owner = Key::Account(AccountHash::new([2; 32]));
+        let recipient = Key::Account(AccountHash::new([3; 32]));
+        let spender = Key::Account(AccountHash::new([4; 32]));
+        let deploy_processed = DeployProcessed::random(&mut rng, None).with_transforms(vec![
+            event_transform([7; 32], 0, event_fields("event_Mint", &[owner], 100)),
+            event_transform(
+                [7; 32],
+                1,
+                event_fields("event_TransferFrom", &[spender, owner, recipient], 40),
+            ),
+            event_transform(
+                [7; 32],
+                2,
+                event_fields("event_SetAllowance", &[owner, spender], 10),
+            ),
+            event_transform(
+                [8; 32],
+                3,
+                event_fields("event_Transfer", &[owner, recipient], 1),
+            ),
+        ]);
+
+        let token_transfers = TokenDecoder::new(&config([7; 32]))
+            .unwrap()
+            .decode(&deploy_processed);
+
+        assert_eq!(token_transfers.len(), 2);
+        assert_eq!(token_transfers[0].contract_hash, CONTRACT_HASH);
+        assert_eq!(token_transfers[0].event_id, 0);
+        assert_eq!(token_transfers[0].kind, TokenTransferKind::Mint);
+        assert_eq!(token_transfers[0].from, None);
+        assert_eq!(token_transfers[0].to, Some(owner.to_formatted_string()));
+        assert_eq!(token_transfers[1].event_id, 1);
+        assert_eq!(token_transfers[1].kind, TokenTransferKind::TransferFrom);
+        assert_eq!(token_transfers[1].from, Some(owner.to_formatted_string()));
+        assert_eq!(token_transfers[1].to, Some(recipient.to_formatted_string()));
+        let spender = Some(spender.to_formatted_string());
+        assert_eq!(token_transfers[1].spender, spender);
+        assert_eq!(token_transfers[1].amount, U256::from(40));
+        let deploy_hash = deploy_processed.hex_encoded_hash();
+        assert_eq!(token_transfers[1].deploy_hash, deploy_hash);
+    }
+
+    #[test]
+    fn should_reject_invalid_token_config() {
+        let mut config = config([7; 32]);
+        config.tokens[0].events_uref = "uref-xyz".to_string();
+        assert!(TokenDecoder::new(&config).is_err());
+        config.tokens[0].contract_hash = "01".to_string();
+        assert!(TokenDecoder::new(&config).is_err());
+    }
+
+    #[tokio::test]
+    async fn should_store_and_publish_token_transfers() {
+        let mut rng = TestRng::new();
+        let database = FakeDatabase::new();
+        let (outbound_sender, mut outbound_receiver) = channel(10);
+        let config = config([7; 32]);
+        let token_transfers = TokenTransfers::start(
+            TokenDecoder::new(&config).unwrap(),
+            config.queue_size(),
+            database.clone(),
+            outbound_sender,
+        );
+        let owner = Key::Account(AccountHash::new([2; 32]));
+        let sse_data = deploy_processed_with(
+            &mut rng,
+            vec![event_transform(
+                [7; 32],
+                5,
+                event_fields("event_Burn", &[owner], 3),
+            )],
+        );
+
+        token_transfers.submit(&sse_data);
+
+        let payload = recv_derived_payload(&mut outbound_receiver, TOKEN_TRANSFER_DERIVER).await;
+        assert_eq!(payload["kind"], "Burn");
+        assert_eq!(payload["event_id"], 5);
+        let stored = database
+            .get_token_transfers(CONTRACT_HASH, None, 10)
+            .await
+            .expect("Token transfer was not stored");
+        assert_eq!(stored.len(), 1);
+    }
+}
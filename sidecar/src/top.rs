@@ -0,0 +1,655 @@
+//! `top`, a live view of a running sidecar for operators on its host.
+//!
+//! The view is refreshed from the admin server of the sidecar. The rates of the events received
+//! from the nodes, stored and sent to subscribers are computed per event type from the counters of
+//! the `metrics` endpoint between two refreshes, the lag of the nodes is read from `sources` and
+//! the degraded components from `health`. The errors counted since the view started are listed,
+//! most recent first.
+
+use crate::types::config::Config;
+use anyhow::Error;
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    io::{stdout, Write},
+    time::{Duration, Instant},
+};
+use tokio::time::sleep;
+
+/// Time allowed to each request to the admin server.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+/// Maximum number of errors listed.
+const MAX_RECENT_ERRORS: usize = 10;
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// Options of `top`.
+#[derive(Clone, Debug)]
+pub(crate) struct TopOptions {
+    /// URL of the admin server, derived from the config if not given.
+    pub(crate) admin_url: Option<String>,
+    pub(crate) refresh_interval: Duration,
+}
+
+/// A sample of the `metrics` endpoint.
+#[derive(Clone, Debug, PartialEq)]
+struct Sample {
+    name: String,
+    labels: BTreeMap<String, String>,
+    value: f64,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+struct HealthView {
+    status: String,
+    #[serde(default)]
+    degraded: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+struct SourcesView {
+    head_divergence: Option<u64>,
+    sources: Vec<SourceView>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct SourceView {
+    node: String,
+    head_height: Option<u64>,
+    status_latency_in_milliseconds: Option<u64>,
+    latest_ingested_block_height: Option<u64>,
+    blocks_behind_head: Option<u64>,
+    seconds_since_last_event: Option<u64>,
+    preferred: bool,
+}
+
+/// Everything fetched from the admin server at a refresh.
+struct Snapshot {
+    samples: Vec<Sample>,
+    health: HealthView,
+    sources: SourcesView,
+}
+
+/// The counters the rates are computed from, keyed by event type, or by category and description
+/// for the errors.
+#[derive(Clone, Debug, Default)]
+struct Counters {
+    received: BTreeMap<String, f64>,
+    stored: BTreeMap<String, f64>,
+    sent: BTreeMap<String, f64>,
+    errors: BTreeMap<(String, String), f64>,
+    pool_timeouts: f64,
+}
+
+/// Per second rates of the counters between two refreshes.
+#[derive(Clone, Debug, Default)]
+struct Rates {
+    received: BTreeMap<String, f64>,
+    stored: BTreeMap<String, f64>,
+    sent: BTreeMap<String, f64>,
+    pool_timeouts: f64,
+}
+
+/// Error counted between two refreshes.
+#[derive(Clone, Debug, PartialEq)]
+struct RecentError {
+    at: Instant,
+    category: String,
+    description: String,
+    count: f64,
+}
+
+struct View {
+    health: HealthView,
+    sources: SourcesView,
+    /// Subscribers of the event stream, keyed by filter.
+    subscribers: BTreeMap<String, f64>,
+    /// Connections of the database pool, keyed by state.
+    pool: BTreeMap<String, f64>,
+    /// `None` until the second refresh.
+    maybe_rates: Option<Rates>,
+}
+
+/// State of the view, updated at every refresh.
+struct Dashboard {
+    admin_url: String,
+    maybe_previous: Option<(Instant, Counters)>,
+    maybe_view: Option<View>,
+    maybe_fetch_error: Option<String>,
+    recent_errors: VecDeque<RecentError>,
+}
+
+/// Shows the view of the sidecar whose admin server is given in `options`, or configured in
+/// `config`, until interrupted.
+pub(crate) async fn run_top(config: Config, options: TopOptions) -> Result<(), Error> {
+    let admin_url = match options.admin_url {
+        Some(admin_url) => admin_url.trim_end_matches('/').to_string(),
+        None => default_admin_url(&config)?,
+    };
+    let client = reqwest::Client::builder().timeout(FETCH_TIMEOUT).build()?;
+    let mut dashboard = Dashboard::new(admin_url.clone());
+    loop {
+        let result = fetch_snapshot(&client, &admin_url).await;
+        let now = Instant::now();
+        dashboard.update(result.map_err(|error| format!("{:#}", error)), now);
+        let mut out = stdout();
+        write!(out, "{}{}", CLEAR_SCREEN, dashboard.render(now))?;
+        out.flush()?;
+        tokio::select! {
+            _ = sleep(options.refresh_interval) => {}
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+    println!();
+    Ok(())
+}
+
+/// Returns the URL of the configured admin server, reached on the loopback interface if it is
+/// bound to all interfaces.
+fn default_admin_url(config: &Config) -> Result<String, Error> {
+    let admin_server = config.admin_server.as_ref().ok_or_else(|| {
+        Error::msg("The admin server isn't configured, give its URL with --admin-url")
+    })?;
+    let host = match admin_server.address.as_deref() {
+        None | Some("0.0.0.0") => "127.0.0.1",
+        Some("::") => "::1",
+        Some(address) => address,
+    };
+    if host.contains(':') {
+        Ok(format!("http://[{}]:{}", host, admin_server.port))
+    } else {
+        Ok(format!("http://{}:{}", host, admin_server.port))
+    }
+}
+
+async fn fetch_snapshot(client: &reqwest::Client, admin_url: &str) -> Result<Snapshot, Error> {
+    let metrics = client
+        .get(format!("{}/metrics", admin_url))
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    // The health endpoint responds with 503 while the sidecar is degraded, with the same body.
+    let health = client
+        .get(format!("{}/health", admin_url))
+        .send()
+        .await?
+        .bytes()
+        .await?;
+    let sources = client
+        .get(format!("{}/sources", admin_url))
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    Ok(Snapshot {
+        samples: parse_samples(&metrics),
+        health: serde_json::from_slice(&health)?,
+        sources: serde_json::from_slice(&sources)?,
+    })
+}
+
+/// Parses the samples of metrics in the Prometheus text format. Lines which can't be parsed are
+/// skipped.
+fn parse_samples(text: &str) -> Vec<Sample> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(parse_sample)
+        .collect()
+}
+
+fn parse_sample(line: &str) -> Option<Sample> {
+    let (name_and_labels, value) = line.rsplit_once(' ')?;
+    let value = value.parse().ok()?;
+    let (name, labels) = match name_and_labels.split_once('{') {
+        Some((name, labels)) => (name, parse_labels(labels.strip_suffix('}')?)?),
+        None => (name_and_labels, BTreeMap::new()),
+    };
+    Some(Sample {
+        name: name.to_string(),
+        labels,
+        value,
+    })
+}
+
+/// Parses labels such as `category="main",description="db_save_error"`.
+fn parse_labels(text: &str) -> Option<BTreeMap<String, String>> {
+    let mut labels = BTreeMap::new();
+    let mut chars = text.chars();
+    loop {
+        let name: String = chars
+            .by_ref()
+            .take_while(|character| *character != '=')
+            .collect();
+        let name = name.trim_start_matches(',').trim();
+        if name.is_empty() {
+            return Some(labels);
+        }
+        if chars.next()? != '"' {
+            return None;
+        }
+        let mut value = String::new();
+        loop {
+            match chars.next()? {
+                '"' => break,
+                '\\' => match chars.next()? {
+                    'n' => value.push('\n'),
+                    escaped => value.push(escaped),
+                },
+                character => value.push(character),
+            }
+        }
+        labels.insert(name.to_string(), value);
+    }
+}
+
+fn label(sample: &Sample, name: &str) -> String {
+    sample.labels.get(name).cloned().unwrap_or_default()
+}
+
+/// Sums the values of the samples named `name` by the value of their label `label_name`.
+fn sum_by_label(samples: &[Sample], name: &str, label_name: &str) -> BTreeMap<String, f64> {
+    let mut sums = BTreeMap::new();
+    for sample in samples.iter().filter(|sample| sample.name == name) {
+        *sums.entry(label(sample, label_name)).or_default() += sample.value;
+    }
+    sums
+}
+
+impl Counters {
+    fn from_samples(samples: &[Sample]) -> Self {
+        let mut errors = BTreeMap::new();
+        for sample in samples
+            .iter()
+            .filter(|sample| sample.name == "error_counts")
+        {
+            let key = (label(sample, "category"), label(sample, "description"));
+            *errors.entry(key).or_default() += sample.value;
+        }
+        Counters {
+            received: sum_by_label(samples, "inbound_events", "event_type"),
+            stored: sum_by_label(samples, "stored_events", "event_type"),
+            sent: sum_by_label(samples, "outbound_events", "event_type"),
+            errors,
+            pool_timeouts: samples
+                .iter()
+                .filter(|sample| sample.name == "db_pool_timeouts")
+                .map(|sample| sample.value)
+                .sum(),
+        }
+    }
+
+    fn rates_since(&self, previous: &Counters, elapsed: Duration) -> Rates {
+        let seconds = elapsed.as_secs_f64().max(f64::EPSILON);
+        let rates = |current: &BTreeMap<String, f64>, previous: &BTreeMap<String, f64>| {
+            current
+                .iter()
+                .map(|(key, value)| {
+                    let increase = increase(*value, previous.get(key).copied().unwrap_or(0.0));
+                    (key.clone(), increase / seconds)
+                })
+                .collect()
+        };
+        Rates {
+            received: rates(&self.received, &previous.received),
+            stored: rates(&self.stored, &previous.stored),
+            sent: rates(&self.sent, &previous.sent),
+            pool_timeouts: increase(self.pool_timeouts, previous.pool_timeouts) / seconds,
+        }
+    }
+}
+
+/// The increase of a counter, which restarted from 0 if lower than before.
+fn increase(current: f64, previous: f64) -> f64 {
+    if current >= previous {
+        current - previous
+    } else {
+        current
+    }
+}
+
+impl Dashboard {
+    fn new(admin_url: String) -> Self {
+        Dashboard {
+            admin_url,
+            maybe_previous: None,
+            maybe_view: None,
+            maybe_fetch_error: None,
+            recent_errors: VecDeque::new(),
+        }
+    }
+
+    /// Updates the view with the result of the refresh at `now`. The previous view is kept if the
+    /// admin server couldn't be reached.
+    fn update(&mut self, result: Result<Snapshot, String>, now: Instant) {
+        let snapshot = match result {
+            Ok(snapshot) => snapshot,
+            Err(error) => {
+                self.maybe_fetch_error = Some(error);
+                return;
+            }
+        };
+        let counters = Counters::from_samples(&snapshot.samples);
+        let maybe_rates = match self.maybe_previous.take() {
+            Some((at, previous)) => {
+                self.record_errors(&counters, &previous, now);
+                Some(counters.rates_since(&previous, now.saturating_duration_since(at)))
+            }
+            None => None,
+        };
+        self.maybe_view = Some(View {
+            health: snapshot.health,
+            sources: snapshot.sources,
+            subscribers: sum_by_label(&snapshot.samples, "event_stream_subscribers", "filter"),
+            pool: sum_by_label(&snapshot.samples, "db_pool_connections", "state"),
+            maybe_rates,
+        });
+        self.maybe_previous = Some((now, counters));
+        self.maybe_fetch_error = None;
+    }
+
+    fn record_errors(&mut self, counters: &Counters, previous: &Counters, now: Instant) {
+        for ((category, description), value) in &counters.errors {
+            let key = (category.clone(), description.clone());
+            let count = increase(*value, previous.errors.get(&key).copied().unwrap_or(0.0));
+            if count > 0.0 {
+                self.recent_errors.push_front(RecentError {
+                    at: now,
+                    category: category.clone(),
+                    description: description.clone(),
+                    count,
+                });
+            }
+        }
+        self.recent_errors.truncate(MAX_RECENT_ERRORS);
+    }
+
+    fn render(&self, now: Instant) -> String {
+        let mut lines = vec![self.render_title()];
+        if let Some(error) = &self.maybe_fetch_error {
+            lines.push(format!("Error fetching from the admin server: {}", error));
+        }
+        if let Some(view) = &self.maybe_view {
+            lines.push(String::new());
+            lines.extend(render_events(view));
+            lines.push(String::new());
+            lines.extend(render_clients_and_database(view));
+            lines.push(String::new());
+            lines.extend(render_sources(&view.sources));
+        }
+        lines.push(String::new());
+        lines.extend(self.render_errors(now));
+        lines.push(String::new());
+        lines.join("\n")
+    }
+
+    fn render_title(&self) -> String {
+        let status = self
+            .maybe_view
+            .as_ref()
+            .map_or("unknown", |view| view.health.status.as_str());
+        format!(
+            "casper-event-sidecar - {} - {} (Ctrl-C to quit)",
+            self.admin_url, status
+        )
+    }
+
+    fn render_errors(&self, now: Instant) -> Vec<String> {
+        let mut lines = vec!["RECENT ERRORS".to_string()];
+        if let Some(view) = &self.maybe_view {
+            for (component, reason) in &view.health.degraded {
+                lines.push(format!("  degraded {}: {}", component, reason));
+            }
+        }
+        for error in &self.recent_errors {
+            lines.push(format!(
+                "  {:>5}s ago  {} {} (+{})",
+                now.saturating_duration_since(error.at).as_secs(),
+                error.category,
+                error.description,
+                error.count
+            ));
+        }
+        if lines.len() == 1 {
+            lines.push("  none".to_string());
+        }
+        lines
+    }
+}
+
+fn format_rate(maybe_rate: Option<f64>) -> String {
+    maybe_rate.map_or_else(|| "-".to_string(), |rate| format!("{:.2}", rate))
+}
+
+fn format_optional(maybe_value: Option<u64>, unit: &str) -> String {
+    maybe_value.map_or_else(|| "-".to_string(), |value| format!("{}{}", value, unit))
+}
+
+fn render_events(view: &View) -> Vec<String> {
+    let mut lines = vec![format!(
+        "{:<20} {:>12} {:>12} {:>12}",
+        "EVENTS PER SECOND", "received", "stored", "sent"
+    )];
+    let maybe_rates = view.maybe_rates.as_ref();
+    let event_types: BTreeSet<&String> = maybe_rates
+        .into_iter()
+        .flat_map(|rates| {
+            rates
+                .received
+                .keys()
+                .chain(rates.stored.keys())
+                .chain(rates.sent.keys())
+        })
+        .collect();
+    for event_type in event_types {
+        lines.push(render_rates(event_type, maybe_rates, |rates_by_type| {
+            rates_by_type.get(event_type).copied().unwrap_or_default()
+        }));
+    }
+    lines.push(render_rates("total", maybe_rates, |rates_by_type| {
+        rates_by_type.values().sum()
+    }));
+    lines
+}
+
+/// The line of the received, stored and sent rates, each picked from the rates by event type with
+/// `rate_of`.
+fn render_rates(
+    label: &str,
+    maybe_rates: Option<&Rates>,
+    rate_of: impl Fn(&BTreeMap<String, f64>) -> f64,
+) -> String {
+    let format = |rates_by_type: fn(&Rates) -> &BTreeMap<String, f64>| {
+        format_rate(maybe_rates.map(|rates| rate_of(rates_by_type(rates))))
+    };
+    format!(
+        "{:<20} {:>12} {:>12} {:>12}",
+        label,
+        format(|rates| &rates.received),
+        format(|rates| &rates.stored),
+        format(|rates| &rates.sent),
+    )
+}
+
+fn render_clients_and_database(view: &View) -> Vec<String> {
+    let subscribers: Vec<String> = view
+        .subscribers
+        .iter()
+        .filter(|(_, count)| **count > 0.0)
+        .map(|(filter, count)| format!("{} {}", filter, count))
+        .collect();
+    let pool = |state: &str| view.pool.get(state).copied().unwrap_or_default();
+    vec![
+        format!(
+            "CLIENTS   {} subscribed{}",
+            view.subscribers.values().sum::<f64>(),
+            if subscribers.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", subscribers.join(", "))
+            }
+        ),
+        format!(
+            "DATABASE  {} writes/s, pool {} active, {} idle of {}, {} timeouts/s",
+            format_rate(
+                view.maybe_rates
+                    .as_ref()
+                    .map(|rates| rates.stored.values().sum())
+            ),
+            pool("active"),
+            pool("idle"),
+            pool("max"),
+            format_rate(view.maybe_rates.as_ref().map(|rates| rates.pool_timeouts)),
+        ),
+    ]
+}
+
+fn render_sources(sources: &SourcesView) -> Vec<String> {
+    let mut lines = vec![format!(
+        "{:<32} {:>10} {:>10} {:>8} {:>11} {:>9}",
+        "SOURCES", "head", "ingested", "behind", "last event", "latency"
+    )];
+    for source in &sources.sources {
+        lines.push(format!(
+            "{:<32} {:>10} {:>10} {:>8} {:>11} {:>9}",
+            format!(
+                "{}{}",
+                if source.preferred { "* " } else { "  " },
+                source.node
+            ),
+            format_optional(source.head_height, ""),
+            format_optional(source.latest_ingested_block_height, ""),
+            format_optional(source.blocks_behind_head, ""),
+            format_optional(source.seconds_since_last_event, "s ago"),
+            format_optional(source.status_latency_in_milliseconds, "ms"),
+        ));
+    }
+    lines.push(format!(
+        "head divergence: {}",
+        format_optional(sources.head_divergence, " blocks")
+    ));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn snapshot(metrics: &str) -> Snapshot {
+        Snapshot {
+            samples: parse_samples(metrics),
+            health: serde_json::from_value(json!({
+                "status": "degraded",
+                "degraded": { "database_pool": "saturated" }
+            }))
+            .unwrap(),
+            sources: serde_json::from_value(json!({
+                "head_divergence": 0,
+                "sources": [{
+                    "node": "127.0.0.1:18101",
+                    "head_height": 120,
+                    "status_latency_in_milliseconds": 12,
+                    "latest_ingested_block_height": 118,
+                    "blocks_behind_head": 2,
+                    "seconds_since_last_event": 3,
+                    "preferred": true
+                }]
+            }))
+            .unwrap(),
+        }
+    }
+
+    #[test]
+    fn should_parse_prometheus_samples() {
+        let samples = parse_samples(
+            "# HELP error_counts Error counts\n\
+             # TYPE error_counts counter\n\
+             error_counts{category=\"main\",description=\"a \\\"quoted\\\" reason, really\"} 3\n\
+             db_pool_timeouts 1\n\
+             not a sample\n",
+        );
+
+        let mut labels = BTreeMap::new();
+        labels.insert("category".to_string(), "main".to_string());
+        labels.insert(
+            "description".to_string(),
+            "a \"quoted\" reason, really".to_string(),
+        );
+        assert_eq!(
+            samples,
+            vec![
+                Sample {
+                    name: "error_counts".to_string(),
+                    labels,
+                    value: 3.0,
+                },
+                Sample {
+                    name: "db_pool_timeouts".to_string(),
+                    labels: BTreeMap::new(),
+                    value: 1.0,
+                },
+            ]
+        );
+    }
+
+    const FIRST_METRICS: &str = "inbound_events{event_type=\"BlockAdded\"} 10\n\
+         stored_events{event_type=\"BlockAdded\"} 10\n\
+         outbound_events{filter=\"events/main\",event_type=\"BlockAdded\"} 4\n\
+         event_stream_subscribers{filter=\"events/main\"} 2\n\
+         error_counts{category=\"main\",description=\"db_save_error_BlockAdded\"} 1\n";
+
+    const SECOND_METRICS: &str = "inbound_events{event_type=\"BlockAdded\"} 14\n\
+         stored_events{event_type=\"BlockAdded\"} 13\n\
+         outbound_events{filter=\"events/main\",event_type=\"BlockAdded\"} 8\n\
+         outbound_events{filter=\"events\",event_type=\"BlockAdded\"} 2\n\
+         event_stream_subscribers{filter=\"events/main\"} 2\n\
+         error_counts{category=\"main\",description=\"db_save_error_BlockAdded\"} 3\n";
+
+    #[test]
+    fn should_compute_rates_between_refreshes() {
+        let mut dashboard = Dashboard::new("http://127.0.0.1:18887".to_string());
+        let start = Instant::now();
+        dashboard.update(Ok(snapshot(FIRST_METRICS)), start);
+        assert!(dashboard.render(start).contains("BlockAdded"));
+        assert!(dashboard.recent_errors.is_empty());
+
+        let later = start + Duration::from_secs(2);
+        dashboard.update(Ok(snapshot(SECOND_METRICS)), later);
+        let rates = dashboard
+            .maybe_view
+            .as_ref()
+            .and_then(|view| view.maybe_rates.clone())
+            .unwrap();
+        assert_eq!(rates.received.get("BlockAdded"), Some(&2.0));
+        assert_eq!(rates.stored.get("BlockAdded"), Some(&1.5));
+        assert_eq!(rates.sent.get("BlockAdded"), Some(&3.0));
+        assert_eq!(
+            dashboard.recent_errors.iter().collect::<Vec<_>>(),
+            vec![&RecentError {
+                at: later,
+                category: "main".to_string(),
+                description: "db_save_error_BlockAdded".to_string(),
+                count: 2.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn should_keep_the_last_view_when_a_refresh_fails() {
+        let mut dashboard = Dashboard::new("http://127.0.0.1:18887".to_string());
+        let start = Instant::now();
+        dashboard.update(Ok(snapshot(FIRST_METRICS)), start);
+        let later = start + Duration::from_secs(2);
+        dashboard.update(Ok(snapshot(SECOND_METRICS)), later);
+
+        dashboard.update(Err("connection refused".to_string()), later);
+        let rendered = dashboard.render(later);
+        assert!(rendered.contains("connection refused"));
+        assert!(rendered.contains("degraded database_pool: saturated"));
+        assert!(rendered.contains("* 127.0.0.1:18101"));
+        assert!(rendered.contains("1.50 writes/s"));
+    }
+}
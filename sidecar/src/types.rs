@@ -1,3 +1,5 @@
 pub mod config;
+pub mod config_errors;
+pub mod config_profiles;
 pub mod database;
-pub mod sse_events;
+pub use casper_event_types::sse_events;
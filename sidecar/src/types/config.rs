@@ -1,12 +1,15 @@
 use std::string::ToString;
 use std::{
+    collections::BTreeMap,
     convert::{TryFrom, TryInto},
+    net::IpAddr,
     num::ParseIntError,
 };
 
 use anyhow::{Context, Error};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
+use super::config_errors::parse_config;
 use crate::database::{
     database_errors::DatabaseConfigError,
     env_vars::{
@@ -24,10 +27,13 @@ pub(crate) const DEFAULT_PORT: u16 = 5432;
 pub(crate) const DEFAULT_POSTGRES_STORAGE_PATH: &str =
     "/casper/sidecar-storage/casper-event-sidecar";
 
+/// The interface the servers bind to if none is configured.
+const BIND_ALL_INTERFACES: &str = "0.0.0.0";
+
 pub fn read_config(config_path: &str) -> Result<ConfigSerdeTarget, Error> {
     let toml_content =
         std::fs::read_to_string(config_path).context("Error reading config file contents")?;
-    toml::from_str(&toml_content).context("Error parsing config into TOML format")
+    parse_config(&toml_content).context("Error parsing config")
 }
 
 // This struct is used to parse the toml-formatted config file so the values can be utilised in the code.
@@ -41,30 +47,208 @@ pub struct Config {
     pub rest_server: RestServerConfig,
     pub event_stream_server: EventStreamServerConfig,
     pub admin_server: Option<AdminServerConfig>,
+    pub metrics_server: Option<MetricsServerConfig>,
+    pub audit: Option<AuditConfig>,
+    pub capture: Option<CaptureConfig>,
+    pub enrichment: Option<EnrichmentConfig>,
+    pub state_proofs: Option<StateProofConfig>,
+    pub watch_list: Option<WatchListConfig>,
+    /// If set, the signatures of FinalitySignature events are verified against the signer's key
+    /// before they are stored.
+    #[serde(default)]
+    pub verify_finality_signatures: bool,
+    /// If set, the hashes of BlockAdded events are recomputed from the block before it is stored.
+    #[serde(default)]
+    pub verify_block_hashes: bool,
+    /// If set, the events of the event stream server and the responses of the REST server are
+    /// serialized as canonical JSON.
+    #[serde(default)]
+    pub canonical_json: bool,
+    /// WASM plugins deriving custom events from the ingested events.
+    #[serde(default)]
+    pub event_derivers: Vec<EventDeriverConfig>,
+    pub source_scoring: Option<SourceScoringConfig>,
+    pub account_aliases: Option<AccountAliasesConfig>,
+    pub clock_skew: Option<ClockSkewConfig>,
+    pub readiness: Option<ReadinessConfig>,
+    pub kafka: Option<KafkaConfig>,
+    pub tracing: Option<TracingConfig>,
+    pub api_keys: Option<ApiKeysConfig>,
+    pub delivery: Option<DeliveryConfig>,
+    pub journal: Option<JournalConfig>,
+    /// HTTP endpoints the ingested events are posted to.
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+    pub retention: Option<RetentionConfig>,
+    pub finality_signature_checkpoints: Option<FinalitySignatureCheckpointsConfig>,
+    pub backfill: Option<BackfillConfig>,
+    pub block_finality: Option<BlockFinalityConfig>,
+    pub token_transfers: Option<TokenTransfersConfig>,
+    pub nft_events: Option<NftEventsConfig>,
+    pub contract_events: Option<ContractEventsConfig>,
+    pub network_check: Option<NetworkCheckConfig>,
+    /// Networks ingested alongside each other, each with its own nodes, storage and URL prefix, in
+    /// place of the top-level connections.
+    #[serde(default)]
+    pub networks: Vec<NetworkConfig>,
 }
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
 #[cfg_attr(test, derive(Default))]
 pub struct ConfigSerdeTarget {
     pub inbound_channel_size: Option<usize>,
     pub outbound_channel_size: Option<usize>,
+    /// The nodes events are ingested from, unless `networks` are configured instead.
+    #[serde(default)]
     pub connections: Vec<Connection>,
     pub storage: Option<StorageConfigSerdeTarget>,
     pub rest_server: RestServerConfig,
     pub event_stream_server: EventStreamServerConfig,
     pub admin_server: Option<AdminServerConfig>,
+    pub metrics_server: Option<MetricsServerConfig>,
+    pub audit: Option<AuditConfig>,
+    pub capture: Option<CaptureConfig>,
+    pub enrichment: Option<EnrichmentConfig>,
+    pub state_proofs: Option<StateProofConfig>,
+    pub watch_list: Option<WatchListConfig>,
+    /// If set, the signatures of FinalitySignature events are verified against the signer's key
+    /// before they are stored.
+    #[serde(default)]
+    pub verify_finality_signatures: bool,
+    /// If set, the hashes of BlockAdded events are recomputed from the block before it is stored.
+    #[serde(default)]
+    pub verify_block_hashes: bool,
+    /// If set, the events of the event stream server and the responses of the REST server are
+    /// serialized as canonical JSON.
+    #[serde(default)]
+    pub canonical_json: bool,
+    /// WASM plugins deriving custom events from the ingested events.
+    #[serde(default)]
+    pub event_derivers: Vec<EventDeriverConfig>,
+    pub source_scoring: Option<SourceScoringConfig>,
+    pub account_aliases: Option<AccountAliasesConfig>,
+    pub clock_skew: Option<ClockSkewConfig>,
+    pub readiness: Option<ReadinessConfig>,
+    pub kafka: Option<KafkaConfig>,
+    pub tracing: Option<TracingConfig>,
+    pub api_keys: Option<ApiKeysConfig>,
+    pub delivery: Option<DeliveryConfig>,
+    pub journal: Option<JournalConfig>,
+    /// HTTP endpoints the ingested events are posted to.
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+    pub retention: Option<RetentionConfig>,
+    pub finality_signature_checkpoints: Option<FinalitySignatureCheckpointsConfig>,
+    pub backfill: Option<BackfillConfig>,
+    pub block_finality: Option<BlockFinalityConfig>,
+    pub token_transfers: Option<TokenTransfersConfig>,
+    pub nft_events: Option<NftEventsConfig>,
+    pub contract_events: Option<ContractEventsConfig>,
+    pub network_check: Option<NetworkCheckConfig>,
+    /// Networks ingested alongside each other, each with its own nodes, storage and URL prefix, in
+    /// place of the top-level connections.
+    #[serde(default)]
+    pub networks: Vec<NetworkConfigSerdeTarget>,
 }
 impl TryFrom<ConfigSerdeTarget> for Config {
     type Error = DatabaseConfigError;
 
     fn try_from(value: ConfigSerdeTarget) -> Result<Self, Self::Error> {
+        let storage: StorageConfig = value.storage.unwrap_or_default().try_into()?;
+        let networks = NetworkConfigSerdeTarget::into_network_configs(value.networks, &storage)?;
         Ok(Config {
             inbound_channel_size: value.inbound_channel_size,
             outbound_channel_size: value.outbound_channel_size,
             connections: value.connections,
-            storage: value.storage.unwrap_or_default().try_into()?,
+            storage,
             rest_server: value.rest_server,
             event_stream_server: value.event_stream_server,
             admin_server: value.admin_server,
+            metrics_server: value.metrics_server,
+            audit: value.audit,
+            capture: value.capture,
+            enrichment: value.enrichment,
+            state_proofs: value.state_proofs,
+            watch_list: value.watch_list,
+            verify_finality_signatures: value.verify_finality_signatures,
+            verify_block_hashes: value.verify_block_hashes,
+            canonical_json: value.canonical_json,
+            event_derivers: value.event_derivers,
+            source_scoring: value.source_scoring,
+            account_aliases: value.account_aliases,
+            clock_skew: value.clock_skew,
+            readiness: value.readiness,
+            kafka: value.kafka,
+            tracing: value.tracing,
+            api_keys: value.api_keys,
+            delivery: value.delivery,
+            journal: value.journal,
+            webhooks: value.webhooks,
+            retention: value.retention,
+            finality_signature_checkpoints: value.finality_signature_checkpoints,
+            backfill: value.backfill,
+            block_finality: value.block_finality,
+            token_transfers: value.token_transfers,
+            nft_events: value.nft_events,
+            contract_events: value.contract_events,
+            network_check: value.network_check,
+            networks,
+        })
+    }
+}
+
+impl Config {
+    /// The configuration of the pipeline ingesting the events of `network`: the top-level
+    /// configuration, with the nodes and storage of the network.
+    pub fn for_network(&self, network: &NetworkConfig) -> Config {
+        Config {
+            connections: network.connections.clone(),
+            storage: network.storage.clone(),
+            networks: Vec::new(),
+            ..self.clone()
+        }
+    }
+}
+
+/// A network ingested alongside the other networks of the config.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct NetworkConfig {
+    /// Name of the network, prefixing the URLs its events are served under, e.g. `mainnet`.
+    pub name: String,
+    pub connections: Vec<Connection>,
+    pub storage: StorageConfig,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct NetworkConfigSerdeTarget {
+    pub name: String,
+    pub connections: Vec<Connection>,
+    /// If not set, the top-level storage, namespaced by the name of the network.
+    pub storage: Option<StorageConfigSerdeTarget>,
+}
+
+impl NetworkConfigSerdeTarget {
+    fn into_network_configs(
+        networks: Vec<NetworkConfigSerdeTarget>,
+        storage: &StorageConfig,
+    ) -> Result<Vec<NetworkConfig>, DatabaseConfigError> {
+        networks
+            .into_iter()
+            .map(|network| network.into_network_config(storage))
+            .collect()
+    }
+
+    fn into_network_config(
+        self,
+        storage: &StorageConfig,
+    ) -> Result<NetworkConfig, DatabaseConfigError> {
+        let storage = match self.storage {
+            Some(storage) => storage.try_into()?,
+            None => storage.namespaced(&self.name),
+        };
+        Ok(NetworkConfig {
+            name: self.name,
+            connections: self.connections,
+            storage,
         })
     }
 }
@@ -80,6 +264,21 @@ pub struct Connection {
     pub connection_timeout_in_seconds: Option<usize>,
     pub sleep_between_keep_alive_checks_in_seconds: Option<usize>,
     pub no_message_timeout_in_seconds: Option<usize>,
+    pub gap_grace_period_in_seconds: Option<usize>,
+    pub priority: Option<u32>,
+}
+
+/// Default time without any bytes received on the event stream of a node after which it is
+/// reconnected.
+const DEFAULT_NO_MESSAGE_TIMEOUT_IN_SECONDS: u64 = 120;
+
+impl Connection {
+    pub fn no_message_timeout_in_seconds(&self) -> u64 {
+        self.no_message_timeout_in_seconds
+            .map_or(DEFAULT_NO_MESSAGE_TIMEOUT_IN_SECONDS, |timeout| {
+                timeout as u64
+            })
+    }
 }
 
 #[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
@@ -104,7 +303,7 @@ impl StorageConfig {
         }
     }
 
-    #[cfg(test)]
+    #[cfg(all(test, feature = "postgres"))]
     pub fn postgres_with_port(port: u16) -> Self {
         StorageConfig::PostgreSqlDbConfig {
             storage_path: "/target/test_storage".to_string(),
@@ -115,6 +314,40 @@ impl StorageConfig {
                 database_password: "p@$$w0rd".to_string(),
                 max_connections_in_pool: 100,
                 port,
+                partitioning: PartitioningConfig::default(),
+                index_deploy_timestamps: true,
+                index_account_effects: false,
+            },
+        }
+    }
+
+    /// The storage of the events of the network `name` when they share the storage of other
+    /// networks: a subdirectory of the storage path, and for PostgreSQL a database of its own,
+    /// suffixed with the name of the network.
+    pub fn namespaced(&self, name: &str) -> StorageConfig {
+        let namespaced_path = |storage_path: &String| {
+            std::path::Path::new(storage_path)
+                .join(name)
+                .to_string_lossy()
+                .into_owned()
+        };
+        match self {
+            StorageConfig::SqliteDbConfig {
+                storage_path,
+                sqlite_config,
+            } => StorageConfig::SqliteDbConfig {
+                storage_path: namespaced_path(storage_path),
+                sqlite_config: sqlite_config.clone(),
+            },
+            StorageConfig::PostgreSqlDbConfig {
+                storage_path,
+                postgresql_config,
+            } => StorageConfig::PostgreSqlDbConfig {
+                storage_path: namespaced_path(storage_path),
+                postgresql_config: PostgresqlConfig {
+                    database_name: format!("{}_{}", postgresql_config.database_name, name),
+                    ..postgresql_config.clone()
+                },
             },
         }
     }
@@ -176,6 +409,18 @@ pub struct SqliteConfig {
     pub file_name: String,
     pub max_connections_in_pool: u32,
     pub wal_autocheckpointing_interval: u16,
+    /// Whether the times at which deploys are accepted and processed are indexed, enabling
+    /// `GET /deploys?from=..&to=..`.
+    #[serde(default = "default_index_deploy_timestamps")]
+    pub index_deploy_timestamps: bool,
+    /// Whether the accounts whose state the processed deploys touched are indexed, enabling
+    /// `GET /account/<account>/affected-by`.
+    #[serde(default)]
+    pub index_account_effects: bool,
+}
+
+fn default_index_deploy_timestamps() -> bool {
+    true
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
@@ -186,6 +431,9 @@ pub struct PostgresqlConfig {
     pub database_password: String,
     pub max_connections_in_pool: u32,
     pub port: u16,
+    pub partitioning: PartitioningConfig,
+    pub index_deploy_timestamps: bool,
+    pub index_account_effects: bool,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
@@ -196,6 +444,9 @@ pub struct PostgresqlConfigSerdeTarget {
     pub database_password: Option<String>,
     pub max_connections_in_pool: Option<u32>,
     pub port: Option<u16>,
+    pub partitioning: Option<PartitioningConfig>,
+    pub index_deploy_timestamps: Option<bool>,
+    pub index_account_effects: Option<bool>,
 }
 
 impl TryFrom<PostgresqlConfigSerdeTarget> for PostgresqlConfig {
@@ -243,31 +494,901 @@ impl TryFrom<PostgresqlConfigSerdeTarget> for PostgresqlConfig {
             database_password,
             max_connections_in_pool: max_connections,
             port,
+            partitioning: value.partitioning.unwrap_or_default(),
+            index_deploy_timestamps: value
+                .index_deploy_timestamps
+                .unwrap_or_else(default_index_deploy_timestamps),
+            index_account_effects: value.index_account_effects.unwrap_or_default(),
         })
     }
 }
 
+/// The default number of eras held by a partition of the tables partitioned by era.
+const DEFAULT_ERAS_PER_PARTITION: u64 = 100;
+/// The default number of blocks held by a partition of the tables partitioned by block height.
+const DEFAULT_BLOCKS_PER_PARTITION: u64 = 100_000;
+/// The default number of partitions created ahead of the newest stored era or block.
+const DEFAULT_PARTITIONS_AHEAD: u64 = 2;
+/// The default interval between two runs of the partition maintenance.
+const DEFAULT_PARTITION_MAINTENANCE_INTERVAL_IN_SECONDS: u64 = 3600;
+
+/// Configuration of the partitioning of the tables keyed by era or block height in PostgreSQL.
+/// The partition sizes only apply to partitions created after they are changed.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct PartitioningConfig {
+    pub eras_per_partition: Option<u64>,
+    pub blocks_per_partition: Option<u64>,
+    pub partitions_ahead: Option<u64>,
+    /// If set, only the given number of the newest partitions holding data are kept per table and
+    /// older partitions are dropped.
+    pub retained_partitions: Option<u64>,
+    pub maintenance_interval_in_seconds: Option<u64>,
+}
+
+impl PartitioningConfig {
+    pub fn eras_per_partition(&self) -> u64 {
+        self.eras_per_partition
+            .unwrap_or(DEFAULT_ERAS_PER_PARTITION)
+            .max(1)
+    }
+
+    pub fn blocks_per_partition(&self) -> u64 {
+        self.blocks_per_partition
+            .unwrap_or(DEFAULT_BLOCKS_PER_PARTITION)
+            .max(1)
+    }
+
+    pub fn partitions_ahead(&self) -> u64 {
+        self.partitions_ahead.unwrap_or(DEFAULT_PARTITIONS_AHEAD)
+    }
+
+    pub fn maintenance_interval_in_seconds(&self) -> u64 {
+        self.maintenance_interval_in_seconds
+            .unwrap_or(DEFAULT_PARTITION_MAINTENANCE_INTERVAL_IN_SECONDS)
+            .max(1)
+    }
+}
+
+/// Returns the socket address to bind to, on `maybe_interface` or on all interfaces.
+fn bind_address(maybe_interface: &Option<String>, port: u16) -> String {
+    match maybe_interface.as_deref() {
+        Some(interface) if interface.contains(':') && !interface.starts_with('[') => {
+            format!("[{}]:{}", interface, port)
+        }
+        Some(interface) => format!("{}:{}", interface, port),
+        None => format!("{}:{}", BIND_ALL_INTERFACES, port),
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
 pub struct RestServerConfig {
+    /// The interface to bind to, e.g. `127.0.0.1` to keep the server internal. Defaults to all
+    /// interfaces.
+    pub address: Option<String>,
     pub port: u16,
     pub max_concurrent_requests: u32,
     pub max_requests_per_second: u32,
+    /// The maximum number of simultaneous requests awaiting the outcome of a deploy. They're held
+    /// for up to their timeout, so they don't count towards `max_concurrent_requests`.
+    #[serde(default = "default_max_concurrent_awaits")]
+    pub max_concurrent_awaits: u32,
+}
+
+fn default_max_concurrent_awaits() -> u32 {
+    100
+}
+
+impl RestServerConfig {
+    pub fn bind_address(&self) -> String {
+        bind_address(&self.address, self.port)
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
 pub struct EventStreamServerConfig {
+    /// The interface to bind to. Defaults to all interfaces.
+    pub address: Option<String>,
     pub port: u16,
     pub max_concurrent_subscribers: u32,
     pub event_stream_buffer_length: u32,
+    pub jwt_auth: Option<JwtAuthConfig>,
+    /// How long a subscriber's buffer may stay saturated before the subscriber is disconnected.
+    /// Slow subscribers are only disconnected once their buffer overflows if not set.
+    pub slow_subscriber_timeout_in_seconds: Option<u64>,
+    /// If set, events of low priority classes are dropped for subscribers falling behind.
+    pub priority_classes: Option<PriorityClassesConfig>,
+    /// Whether subscribers asking to replay events which are no longer buffered are rejected with
+    /// a 416 response rather than served from the earliest buffered event.
+    #[serde(default)]
+    pub reject_unavailable_replay: bool,
+}
+
+impl EventStreamServerConfig {
+    pub fn bind_address(&self) -> String {
+        bind_address(&self.address, self.port)
+    }
+}
+
+/// Classification of the event types into priority classes. Under backpressure, events of the
+/// normal and low priority classes are dropped for a subscriber once they reach it later than the
+/// shedding delay of their class. Events of other types are of high priority and never dropped.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct PriorityClassesConfig {
+    #[serde(default = "default_normal_priority_events")]
+    pub normal: Vec<String>,
+    #[serde(default = "default_low_priority_events")]
+    pub low: Vec<String>,
+    #[serde(default = "default_normal_shedding_delay_in_milliseconds")]
+    pub normal_shedding_delay_in_milliseconds: u64,
+    #[serde(default = "default_low_shedding_delay_in_milliseconds")]
+    pub low_shedding_delay_in_milliseconds: u64,
+}
+
+fn default_normal_priority_events() -> Vec<String> {
+    vec![
+        "DeployAccepted".to_string(),
+        "DeployProcessed".to_string(),
+        "DeployExpired".to_string(),
+    ]
+}
+
+fn default_low_priority_events() -> Vec<String> {
+    vec!["FinalitySignature".to_string()]
+}
+
+fn default_normal_shedding_delay_in_milliseconds() -> u64 {
+    5000
+}
+
+fn default_low_shedding_delay_in_milliseconds() -> u64 {
+    1000
+}
+
+impl Default for PriorityClassesConfig {
+    fn default() -> Self {
+        PriorityClassesConfig {
+            normal: default_normal_priority_events(),
+            low: default_low_priority_events(),
+            normal_shedding_delay_in_milliseconds: default_normal_shedding_delay_in_milliseconds(),
+            low_shedding_delay_in_milliseconds: default_low_shedding_delay_in_milliseconds(),
+        }
+    }
+}
+
+/// Configuration of subscriber authentication on the event stream server. If present, every
+/// subscriber needs to present a JWT signed with `secret` (HS256) in the `Authorization` header.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct JwtAuthConfig {
+    pub secret: String,
+    pub issuer: Option<String>,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
 pub struct AdminServerConfig {
+    /// The interface to bind to. Defaults to all interfaces.
+    pub address: Option<String>,
+    pub port: u16,
+    pub max_concurrent_requests: u32,
+    pub max_requests_per_second: u32,
+}
+
+impl AdminServerConfig {
+    pub fn bind_address(&self) -> String {
+        bind_address(&self.address, self.port)
+    }
+}
+
+/// Configuration of a server exposing only the metrics, so that they can be scraped without access
+/// to the admin server.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct MetricsServerConfig {
+    /// The interface to bind to. Defaults to all interfaces.
+    pub address: Option<String>,
     pub port: u16,
     pub max_concurrent_requests: u32,
     pub max_requests_per_second: u32,
 }
 
+impl MetricsServerConfig {
+    pub fn bind_address(&self) -> String {
+        bind_address(&self.address, self.port)
+    }
+}
+
+/// Configuration of the audit log. If present, admin API calls, configuration loads and the usage of
+/// subscriber tokens are recorded in an append-only table which can be exported from the admin
+/// server.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct AuditConfig {
+    /// If set, every query made to the REST server is recorded as well.
+    #[serde(default)]
+    pub log_rest_queries: bool,
+    /// Addresses of the reverse proxies whose `X-Forwarded-For` header is trusted to report the
+    /// address of the client. The header is ignored for requests from other addresses.
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+/// The default size at which capture files are rotated.
+const DEFAULT_CAPTURE_MAX_FILE_SIZE_IN_BYTES: u64 = 100 * 1024 * 1024;
+/// The default number of capture files which are kept.
+const DEFAULT_CAPTURE_MAX_FILES: usize = 10;
+
+/// Configuration of the capture of upstream traffic. If present, every raw frame received from the
+/// connected nodes is recorded in rotating files in `directory`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct CaptureConfig {
+    pub directory: String,
+    pub max_file_size_in_bytes: Option<u64>,
+    pub max_files: Option<usize>,
+}
+
+impl CaptureConfig {
+    pub fn max_file_size_in_bytes(&self) -> u64 {
+        self.max_file_size_in_bytes
+            .unwrap_or(DEFAULT_CAPTURE_MAX_FILE_SIZE_IN_BYTES)
+    }
+
+    pub fn max_files(&self) -> usize {
+        self.max_files.unwrap_or(DEFAULT_CAPTURE_MAX_FILES).max(1)
+    }
+}
+
+/// The default number of requests per second made to the RPC server by the enrichment stage.
+const DEFAULT_ENRICHMENT_MAX_REQUESTS_PER_SECOND: u32 = 10;
+/// The default number of responses of the RPC server kept in memory.
+const DEFAULT_ENRICHMENT_CACHE_SIZE: usize = 1000;
+/// The default number of events waiting to be enriched.
+const DEFAULT_ENRICHMENT_QUEUE_SIZE: usize = 1000;
+
+/// Configuration of the enrichment of stored events with context fetched from a node's RPC server.
+/// Each enrichment has to be enabled explicitly.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct EnrichmentConfig {
+    /// Address of the JSON-RPC endpoint, e.g. `http://127.0.0.1:7777/rpc`.
+    pub rpc_address: String,
+    pub max_requests_per_second: Option<u32>,
+    pub cache_size: Option<usize>,
+    pub queue_size: Option<usize>,
+    /// If set, the summary of the era ended by a switch block is stored for its `BlockAdded` event.
+    #[serde(default)]
+    pub era_summary: bool,
+    /// If set, the main purse of the account which sent a deploy is stored for its
+    /// `DeployProcessed` event.
+    #[serde(default)]
+    pub account_main_purse: bool,
+}
+
+impl EnrichmentConfig {
+    pub fn max_requests_per_second(&self) -> u32 {
+        self.max_requests_per_second
+            .unwrap_or(DEFAULT_ENRICHMENT_MAX_REQUESTS_PER_SECOND)
+            .max(1)
+    }
+
+    pub fn cache_size(&self) -> usize {
+        self.cache_size.unwrap_or(DEFAULT_ENRICHMENT_CACHE_SIZE)
+    }
+
+    pub fn queue_size(&self) -> usize {
+        self.queue_size
+            .unwrap_or(DEFAULT_ENRICHMENT_QUEUE_SIZE)
+            .max(1)
+    }
+}
+
+/// The default number of proofs of global state kept in memory.
+const DEFAULT_STATE_PROOF_CACHE_SIZE: usize = 1000;
+
+/// Configuration of the proxying of proofs of global state from a node's RPC server.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct StateProofConfig {
+    /// Address of the JSON-RPC endpoint, e.g. `http://127.0.0.1:7777/rpc`.
+    pub rpc_address: String,
+    pub cache_size: Option<usize>,
+}
+
+impl StateProofConfig {
+    pub fn cache_size(&self) -> usize {
+        self.cache_size.unwrap_or(DEFAULT_STATE_PROOF_CACHE_SIZE)
+    }
+}
+
+/// Entities whose events are prioritized and published on the `/events/watched` stream.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct WatchListConfig {
+    /// Hex-encoded public keys of accounts.
+    #[serde(default)]
+    pub accounts: Vec<String>,
+    /// Hex-encoded hashes of contracts or contract packages, optionally prefixed by `hash-`.
+    #[serde(default)]
+    pub contracts: Vec<String>,
+    /// Hex-encoded public keys of validators.
+    #[serde(default)]
+    pub validators: Vec<String>,
+}
+
+/// Names given to accounts, usable as `@<alias>` in the REST API.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct AccountAliasesConfig {
+    /// Accounts by alias, each a hex-encoded public key, a hex-encoded account hash or an
+    /// `account-hash-` formatted string.
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
+    /// Endpoint serving more aliases as a JSON object of the same form, fetched at startup.
+    pub url: Option<String>,
+}
+
+/// The default amount of fuel an event deriver may consume per event.
+const DEFAULT_EVENT_DERIVER_MAX_FUEL: u64 = 10_000_000;
+/// The default time an event deriver may run per event.
+const DEFAULT_EVENT_DERIVER_TIMEOUT_IN_MILLISECONDS: u64 = 100;
+/// The default number of events waiting to be passed to the event derivers.
+const DEFAULT_EVENT_DERIVER_QUEUE_SIZE: usize = 1000;
+
+/// Configuration of a WASM plugin receiving every ingested event and emitting derived events on
+/// the `/events/derived` stream.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct EventDeriverConfig {
+    /// Name of the deriver, attached to the events it emits.
+    pub name: String,
+    /// Path to the WASM module.
+    pub wasm_path: String,
+    pub max_fuel: Option<u64>,
+    pub timeout_in_milliseconds: Option<u64>,
+    pub queue_size: Option<usize>,
+}
+
+impl EventDeriverConfig {
+    pub fn max_fuel(&self) -> u64 {
+        self.max_fuel.unwrap_or(DEFAULT_EVENT_DERIVER_MAX_FUEL)
+    }
+
+    pub fn timeout_in_milliseconds(&self) -> u64 {
+        self.timeout_in_milliseconds
+            .unwrap_or(DEFAULT_EVENT_DERIVER_TIMEOUT_IN_MILLISECONDS)
+            .max(1)
+    }
+
+    pub fn queue_size(&self) -> usize {
+        self.queue_size
+            .unwrap_or(DEFAULT_EVENT_DERIVER_QUEUE_SIZE)
+            .max(1)
+    }
+}
+
+/// The default interval between two polls of the status of the nodes.
+const DEFAULT_SOURCE_SCORING_POLL_INTERVAL_IN_SECONDS: u64 = 10;
+/// The default time a node may take to return its status.
+const DEFAULT_SOURCE_SCORING_TIMEOUT_IN_MILLISECONDS: u64 = 2000;
+
+/// Polling of the `/status` endpoint of the REST server of every connected node, scoring the nodes
+/// by the height of their latest block and their latency.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct SourceScoringConfig {
+    pub poll_interval_in_seconds: Option<u64>,
+    pub timeout_in_milliseconds: Option<u64>,
+}
+
+impl SourceScoringConfig {
+    pub fn poll_interval_in_seconds(&self) -> u64 {
+        self.poll_interval_in_seconds
+            .unwrap_or(DEFAULT_SOURCE_SCORING_POLL_INTERVAL_IN_SECONDS)
+            .max(1)
+    }
+
+    pub fn timeout_in_milliseconds(&self) -> u64 {
+        self.timeout_in_milliseconds
+            .unwrap_or(DEFAULT_SOURCE_SCORING_TIMEOUT_IN_MILLISECONDS)
+            .max(1)
+    }
+}
+
+/// The default skew, between the timestamp of a block and the time it was received, above which
+/// the skew is reported.
+const DEFAULT_MAX_CLOCK_SKEW_IN_SECONDS: u64 = 60;
+
+/// Comparison of the timestamps embedded in the events with the times the sidecar received them.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct ClockSkewConfig {
+    pub max_skew_in_seconds: Option<u64>,
+    /// If set, the time at which each block and deploy was first received is stored along with
+    /// it, as an enrichment of kind `receipt`.
+    #[serde(default)]
+    pub annotate_receipt_time: bool,
+}
+
+impl ClockSkewConfig {
+    pub fn max_skew_in_seconds(&self) -> u64 {
+        self.max_skew_in_seconds
+            .unwrap_or(DEFAULT_MAX_CLOCK_SKEW_IN_SECONDS)
+    }
+}
+
+/// The default number of events waiting to be ingested above which the sidecar isn't ready.
+const DEFAULT_READINESS_MAX_QUEUED_EVENTS: usize = 500;
+/// The default number of blocks behind the chain head above which the sidecar isn't ready.
+const DEFAULT_READINESS_MAX_BLOCKS_BEHIND_HEAD: u64 = 5;
+
+/// Thresholds above which the sidecar reports that it isn't ready to serve traffic.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct ReadinessConfig {
+    pub max_queued_events: Option<usize>,
+    pub max_blocks_behind_head: Option<u64>,
+}
+
+impl ReadinessConfig {
+    pub fn max_queued_events(&self) -> usize {
+        self.max_queued_events
+            .unwrap_or(DEFAULT_READINESS_MAX_QUEUED_EVENTS)
+    }
+
+    pub fn max_blocks_behind_head(&self) -> u64 {
+        self.max_blocks_behind_head
+            .unwrap_or(DEFAULT_READINESS_MAX_BLOCKS_BEHIND_HEAD)
+    }
+}
+
+/// The default topic of the events published to Kafka.
+const DEFAULT_KAFKA_TOPIC_TEMPLATE: &str = "casper.{event_type}";
+/// The default time Kafka has to acknowledge a message before it is reported as failed.
+const DEFAULT_KAFKA_MESSAGE_TIMEOUT_IN_MILLISECONDS: u64 = 30_000;
+/// The default number of messages waiting to be sent to Kafka above which events are dropped.
+const DEFAULT_KAFKA_QUEUE_SIZE: usize = 100_000;
+
+/// Publishing of every event ingested from the nodes to Kafka.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct KafkaConfig {
+    /// Addresses of the brokers, e.g. `10.0.0.1:9092`.
+    pub brokers: Vec<String>,
+    /// Topic of the events, in which `{event_type}` is replaced with the type of the event, e.g.
+    /// `BlockAdded`.
+    pub topic_template: Option<String>,
+    #[serde(default)]
+    pub compression: KafkaCompression,
+    pub message_timeout_in_milliseconds: Option<u64>,
+    pub queue_size: Option<usize>,
+}
+
+impl KafkaConfig {
+    pub fn topic_template(&self) -> &str {
+        self.topic_template
+            .as_deref()
+            .unwrap_or(DEFAULT_KAFKA_TOPIC_TEMPLATE)
+    }
+
+    pub fn message_timeout_in_milliseconds(&self) -> u64 {
+        self.message_timeout_in_milliseconds
+            .unwrap_or(DEFAULT_KAFKA_MESSAGE_TIMEOUT_IN_MILLISECONDS)
+            .max(1)
+    }
+
+    pub fn queue_size(&self) -> usize {
+        self.queue_size.unwrap_or(DEFAULT_KAFKA_QUEUE_SIZE).max(1)
+    }
+}
+
+/// Compression of the batches of messages sent to Kafka.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum KafkaCompression {
+    #[default]
+    None,
+    Gzip,
+    Snappy,
+    Lz4,
+    Zstd,
+}
+
+impl KafkaCompression {
+    /// The value of the `compression.type` setting of the producer.
+    pub fn codec(&self) -> &'static str {
+        match self {
+            KafkaCompression::None => "none",
+            KafkaCompression::Gzip => "gzip",
+            KafkaCompression::Snappy => "snappy",
+            KafkaCompression::Lz4 => "lz4",
+            KafkaCompression::Zstd => "zstd",
+        }
+    }
+}
+
+/// The default number of events waiting to be handed to each external sink.
+const DEFAULT_SINK_QUEUE_SIZE: usize = 10_000;
+
+/// Sharing out of the broadcast events between the subscribers of the event stream server and the
+/// external sinks, Kafka and the webhooks, when either can't keep up.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct DeliveryConfig {
+    #[serde(default)]
+    pub priority: DeliveryPriority,
+    /// Number of events waiting to be handed to each external sink, apart from the queues of the
+    /// sink itself.
+    pub sink_queue_size: Option<usize>,
+}
+
+impl DeliveryConfig {
+    pub fn sink_queue_size(&self) -> usize {
+        self.sink_queue_size
+            .unwrap_or(DEFAULT_SINK_QUEUE_SIZE)
+            .max(1)
+    }
+}
+
+/// Which delivery path gets the events first when the external sinks are saturated.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryPriority {
+    /// Events are broadcast without waiting for the external sinks, which drop the events they
+    /// have no room for.
+    #[default]
+    LocalClients,
+    /// The broadcasting of events waits for the external sinks to have room for them.
+    ExternalSinks,
+}
+
+/// The default name under which the sidecar reports its traces.
+const DEFAULT_TRACING_SERVICE_NAME: &str = "casper-event-sidecar";
+
+/// Export of the traces of the ingestion of the events to an OpenTelemetry collector over OTLP.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct TracingConfig {
+    /// Address of the gRPC endpoint of the collector, e.g. `http://localhost:4317`.
+    pub otlp_endpoint: String,
+    pub service_name: Option<String>,
+    /// Percentage of the events whose ingestion is traced.
+    pub sampling_percentage: Option<u8>,
+}
+
+impl TracingConfig {
+    pub fn service_name(&self) -> &str {
+        self.service_name
+            .as_deref()
+            .unwrap_or(DEFAULT_TRACING_SERVICE_NAME)
+    }
+
+    pub fn sampling_ratio(&self) -> f64 {
+        f64::from(self.sampling_percentage.unwrap_or(100).min(100)) / 100.0
+    }
+}
+
+/// Authentication of the requests to the REST, event stream and admin servers with static API
+/// keys. If present, every request needs to present a key with the scope of the server in the
+/// `X-API-Key` header: `read_events` for the REST and event stream servers, `admin` for the admin
+/// server.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct ApiKeysConfig {
+    #[serde(default)]
+    pub keys: Vec<ApiKeyConfig>,
+    /// If set, the keys created through the admin server are stored in the database and accepted
+    /// along with the keys of the config.
+    #[serde(default)]
+    pub from_database: bool,
+}
+
+/// An API key of the config.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct ApiKeyConfig {
+    /// Name of the key, identifying its holder in the logs and the audit log.
+    pub name: String,
+    pub key: String,
+    pub scopes: Vec<ApiKeyScope>,
+}
+
+pub use casper_event_storage::ApiKeyScope;
+
+/// The default size at which journal segments are rotated.
+const DEFAULT_JOURNAL_MAX_SEGMENT_SIZE_IN_BYTES: u64 = 64 * 1024 * 1024;
+/// The default interval at which the journaled events which failed to be stored are retried.
+const DEFAULT_JOURNAL_RETRY_INTERVAL_IN_SECONDS: u64 = 10;
+
+/// Configuration of the ingestion journal. If present, every event received from the connected
+/// nodes is durably appended to segment files in `directory` before it is stored, and kept there
+/// until it is.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct JournalConfig {
+    pub directory: String,
+    pub max_segment_size_in_bytes: Option<u64>,
+    pub retry_interval_in_seconds: Option<u64>,
+}
+
+impl JournalConfig {
+    pub fn max_segment_size_in_bytes(&self) -> u64 {
+        self.max_segment_size_in_bytes
+            .unwrap_or(DEFAULT_JOURNAL_MAX_SEGMENT_SIZE_IN_BYTES)
+    }
+
+    pub fn retry_interval_in_seconds(&self) -> u64 {
+        self.retry_interval_in_seconds
+            .unwrap_or(DEFAULT_JOURNAL_RETRY_INTERVAL_IN_SECONDS)
+            .max(1)
+    }
+}
+
+/// The default number of attempts at delivering an event to a webhook.
+const DEFAULT_WEBHOOK_MAX_ATTEMPTS: u32 = 5;
+/// The default delay before the second attempt at delivering an event, doubled for every further
+/// attempt.
+const DEFAULT_WEBHOOK_INITIAL_BACKOFF_IN_MILLISECONDS: u64 = 1000;
+/// The default maximum delay between two attempts at delivering an event.
+const DEFAULT_WEBHOOK_MAX_BACKOFF_IN_MILLISECONDS: u64 = 60_000;
+/// The default time a webhook has to respond before the attempt fails.
+const DEFAULT_WEBHOOK_TIMEOUT_IN_MILLISECONDS: u64 = 5000;
+/// The default number of events waiting to be delivered to a webhook.
+const DEFAULT_WEBHOOK_QUEUE_SIZE: usize = 1000;
+
+/// Configuration of an HTTP endpoint the ingested events are posted to.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct WebhookConfig {
+    /// Name of the webhook, under which its delivery attempts are recorded.
+    pub name: String,
+    /// URL the events are posted to.
+    pub url: String,
+    /// Types of the events posted, e.g. `BlockAdded`. Every event is posted if empty.
+    #[serde(default)]
+    pub event_types: Vec<String>,
+    /// Secret with which the payloads are signed in the `X-Signature` header.
+    pub secret: Option<String>,
+    pub max_attempts: Option<u32>,
+    pub initial_backoff_in_milliseconds: Option<u64>,
+    pub max_backoff_in_milliseconds: Option<u64>,
+    pub timeout_in_milliseconds: Option<u64>,
+    pub queue_size: Option<usize>,
+}
+
+impl WebhookConfig {
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+            .unwrap_or(DEFAULT_WEBHOOK_MAX_ATTEMPTS)
+            .max(1)
+    }
+
+    pub fn initial_backoff_in_milliseconds(&self) -> u64 {
+        self.initial_backoff_in_milliseconds
+            .unwrap_or(DEFAULT_WEBHOOK_INITIAL_BACKOFF_IN_MILLISECONDS)
+    }
+
+    pub fn max_backoff_in_milliseconds(&self) -> u64 {
+        self.max_backoff_in_milliseconds
+            .unwrap_or(DEFAULT_WEBHOOK_MAX_BACKOFF_IN_MILLISECONDS)
+    }
+
+    pub fn timeout_in_milliseconds(&self) -> u64 {
+        self.timeout_in_milliseconds
+            .unwrap_or(DEFAULT_WEBHOOK_TIMEOUT_IN_MILLISECONDS)
+            .max(1)
+    }
+
+    pub fn queue_size(&self) -> usize {
+        self.queue_size.unwrap_or(DEFAULT_WEBHOOK_QUEUE_SIZE).max(1)
+    }
+}
+
+/// The default interval between two runs of the pruning of stored events.
+const DEFAULT_RETENTION_PRUNE_INTERVAL_IN_SECONDS: u64 = 3600;
+
+/// Pruning of the stored events. Only the events of the types which have a retention configured
+/// are pruned.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct RetentionConfig {
+    pub prune_interval_in_seconds: Option<u64>,
+    pub block_added: Option<TableRetentionConfig>,
+    pub deploy_accepted: Option<TableRetentionConfig>,
+    pub deploy_processed: Option<TableRetentionConfig>,
+    pub deploy_expired: Option<TableRetentionConfig>,
+    pub fault: Option<TableRetentionConfig>,
+    pub finality_signature: Option<TableRetentionConfig>,
+    pub step: Option<TableRetentionConfig>,
+}
+
+impl RetentionConfig {
+    pub fn prune_interval_in_seconds(&self) -> u64 {
+        self.prune_interval_in_seconds
+            .unwrap_or(DEFAULT_RETENTION_PRUNE_INTERVAL_IN_SECONDS)
+            .max(1)
+    }
+}
+
+/// Retention of the stored events of one type. Events stored more than `max_age_days` ago and
+/// events beyond the newest `max_rows` are pruned.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct TableRetentionConfig {
+    pub max_age_days: Option<u64>,
+    pub max_rows: Option<u64>,
+}
+
+const DEFAULT_CHECKPOINT_FULL_WINDOW_IN_HOURS: u64 = 24;
+const DEFAULT_CHECKPOINT_INTERVAL_IN_SECONDS: u64 = 3600;
+
+/// Checkpointing of the stored finality signatures. The signatures of the blocks signed more than
+/// `full_window_in_hours` ago are replaced with the validators which signed them and their weight.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct FinalitySignatureCheckpointsConfig {
+    pub full_window_in_hours: Option<u64>,
+    pub checkpoint_interval_in_seconds: Option<u64>,
+}
+
+impl FinalitySignatureCheckpointsConfig {
+    pub fn full_window_in_hours(&self) -> u64 {
+        self.full_window_in_hours
+            .unwrap_or(DEFAULT_CHECKPOINT_FULL_WINDOW_IN_HOURS)
+    }
+
+    pub fn checkpoint_interval_in_seconds(&self) -> u64 {
+        self.checkpoint_interval_in_seconds
+            .unwrap_or(DEFAULT_CHECKPOINT_INTERVAL_IN_SECONDS)
+            .max(1)
+    }
+}
+
+/// The default number of requests per second made to the RPC server by the backfill.
+const DEFAULT_BACKFILL_MAX_REQUESTS_PER_SECOND: u32 = 10;
+
+/// Configuration of the backfill, at startup, of the blocks and processed deploys of a range of
+/// heights missing from the database, fetched from a node's RPC server.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct BackfillConfig {
+    /// Address of the JSON-RPC endpoint, e.g. `http://127.0.0.1:7777/rpc`.
+    pub rpc_address: String,
+    pub from_height: u64,
+    /// The height of the node's latest block at startup if unset.
+    pub to_height: Option<u64>,
+    pub max_requests_per_second: Option<u32>,
+}
+
+impl BackfillConfig {
+    pub fn max_requests_per_second(&self) -> u32 {
+        self.max_requests_per_second
+            .unwrap_or(DEFAULT_BACKFILL_MAX_REQUESTS_PER_SECOND)
+            .max(1)
+    }
+}
+
+/// The default share of the weight of the validators of an era which must sign a block for it to
+/// be finalized, in percent.
+const DEFAULT_BLOCK_FINALITY_THRESHOLD_PERCENT: u8 = 67;
+/// The default number of finality signatures waiting to be aggregated.
+const DEFAULT_BLOCK_FINALITY_QUEUE_SIZE: usize = 1000;
+
+/// Configuration of the `BlockFinalized` events, emitted on the `/events/derived` stream once the
+/// validators which signed a block hold enough of the weight of the validators of its era.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct BlockFinalityConfig {
+    pub threshold_percent: Option<u8>,
+    pub queue_size: Option<usize>,
+}
+
+impl BlockFinalityConfig {
+    pub fn threshold_percent(&self) -> u8 {
+        self.threshold_percent
+            .unwrap_or(DEFAULT_BLOCK_FINALITY_THRESHOLD_PERCENT)
+            .clamp(1, 100)
+    }
+
+    pub fn queue_size(&self) -> usize {
+        self.queue_size
+            .unwrap_or(DEFAULT_BLOCK_FINALITY_QUEUE_SIZE)
+            .max(1)
+    }
+}
+
+/// The default number of processed deploys waiting to be decoded for token transfers.
+const DEFAULT_TOKEN_TRANSFERS_QUEUE_SIZE: usize = 1000;
+
+/// Configuration of the decoding of the transfers of CEP-18 fungible tokens from the execution
+/// effects of the processed deploys.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct TokenTransfersConfig {
+    /// The token contracts whose transfers are decoded.
+    pub tokens: Vec<TokenConfig>,
+    pub queue_size: Option<usize>,
+}
+
+impl TokenTransfersConfig {
+    pub fn queue_size(&self) -> usize {
+        self.queue_size
+            .unwrap_or(DEFAULT_TOKEN_TRANSFERS_QUEUE_SIZE)
+            .max(1)
+    }
+}
+
+/// The default number of processed deploys waiting to be decoded for NFT events.
+const DEFAULT_NFT_EVENTS_QUEUE_SIZE: usize = 1000;
+
+/// Configuration of the decoding of the events of CEP-78 NFT collections from the execution effects
+/// of the processed deploys.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct NftEventsConfig {
+    /// The collection contracts whose events are decoded.
+    pub collections: Vec<TokenConfig>,
+    pub queue_size: Option<usize>,
+}
+
+impl NftEventsConfig {
+    pub fn queue_size(&self) -> usize {
+        self.queue_size
+            .unwrap_or(DEFAULT_NFT_EVENTS_QUEUE_SIZE)
+            .max(1)
+    }
+}
+
+/// The default number of processed deploys waiting to be decoded for contract events.
+const DEFAULT_CONTRACT_EVENTS_QUEUE_SIZE: usize = 1000;
+
+/// Configuration of the decoding of the events of contracts following the Casper Event Standard
+/// from the execution effects of the processed deploys.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct ContractEventsConfig {
+    /// The contracts whose events are decoded.
+    pub contracts: Vec<EventContractConfig>,
+    /// If set, the address of the JSON-RPC endpoint of a node, e.g. `http://127.0.0.1:7777/rpc`,
+    /// from which the schemas of the events of the contracts not stored yet are fetched at start.
+    pub rpc_address: Option<String>,
+    pub queue_size: Option<usize>,
+}
+
+impl ContractEventsConfig {
+    pub fn queue_size(&self) -> usize {
+        self.queue_size
+            .unwrap_or(DEFAULT_CONTRACT_EVENTS_QUEUE_SIZE)
+            .max(1)
+    }
+}
+
+/// A contract emitting its events following the Casper Event Standard.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct EventContractConfig {
+    /// Hex-encoded hash of the contract.
+    pub contract_hash: String,
+    /// Formatted URef of the `__events` dictionary the contract emits its events to, found in its
+    /// named keys.
+    pub events_uref: String,
+    /// Formatted URef of the `__events_schema` the contract describes its events in, found in its
+    /// named keys.
+    pub schema_uref: String,
+}
+
+/// A CEP-18 token contract, or a CEP-78 collection contract.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct TokenConfig {
+    /// Hex-encoded hash of the token contract.
+    pub contract_hash: String,
+    /// Formatted URef of the `__events` dictionary the contract emits its events to, found in its
+    /// named keys.
+    pub events_uref: String,
+}
+
+/// The default time allowed to each node to report its network when the Sidecar starts.
+const DEFAULT_NETWORK_CHECK_TIMEOUT_IN_SECONDS: u64 = 10;
+
+/// Configuration of the check that the connected nodes are on the network of the events already
+/// stored, made when the Sidecar starts.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct NetworkCheckConfig {
+    #[serde(default)]
+    pub on_mismatch: NetworkMismatchAction,
+    pub timeout_in_seconds: Option<u64>,
+}
+
+impl NetworkCheckConfig {
+    pub fn timeout_in_seconds(&self) -> u64 {
+        self.timeout_in_seconds
+            .unwrap_or(DEFAULT_NETWORK_CHECK_TIMEOUT_IN_SECONDS)
+            .max(1)
+    }
+}
+
+/// What the Sidecar does if a node isn't on the network of the other nodes or of the stored events.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NetworkMismatchAction {
+    /// The Sidecar refuses to start.
+    #[default]
+    Refuse,
+    /// The Sidecar logs a warning and starts anyway.
+    Warn,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -275,8 +1396,6 @@ mod tests {
     #[test]
     fn should_parse_nctl_config_toml() {
         let expected_config = Config {
-            inbound_channel_size: None,
-            outbound_channel_size: None,
             connections: vec![
                 Connection::example_connection_1(),
                 Connection::example_connection_2(),
@@ -288,11 +1407,13 @@ mod tests {
                     file_name: "sqlite_database.db3".to_string(),
                     max_connections_in_pool: 100,
                     wal_autocheckpointing_interval: 1000,
+                    index_deploy_timestamps: true,
+                    index_account_effects: false,
                 },
             },
             rest_server: build_rest_server_config(),
             event_stream_server: EventStreamServerConfig::default(),
-            admin_server: None,
+            ..Default::default()
         };
 
         let parsed_config: Config = read_config("../EXAMPLE_NCTL_CONFIG.toml")
@@ -315,8 +1436,6 @@ mod tests {
         let mut expected_connection_3 = expected_connection.clone();
         expected_connection_3.ip_address = "168.254.51.3".to_string();
         let expected_config = Config {
-            inbound_channel_size: None,
-            outbound_channel_size: None,
             connections: vec![
                 expected_connection,
                 expected_connection_2,
@@ -328,15 +1447,19 @@ mod tests {
                     file_name: "sqlite_database.db3".to_string(),
                     max_connections_in_pool: 100,
                     wal_autocheckpointing_interval: 1000,
+                    index_deploy_timestamps: true,
+                    index_account_effects: false,
                 },
             },
             rest_server: build_rest_server_config(),
             event_stream_server: EventStreamServerConfig::default(),
             admin_server: Some(AdminServerConfig {
+                address: None,
                 port: 18887,
                 max_concurrent_requests: 1,
                 max_requests_per_second: 1,
             }),
+            ..Default::default()
         };
         let parsed_config: Config = read_config("../EXAMPLE_NODE_CONFIG.toml")
             .expect("Error parsing EXAMPLE_NODE_CONFIG.toml")
@@ -346,11 +1469,58 @@ mod tests {
         assert_eq!(parsed_config, expected_config);
     }
 
+    #[test]
+    fn should_bind_to_configured_interface() {
+        let mut config = build_rest_server_config();
+        assert_eq!(config.bind_address(), "0.0.0.0:18888");
+        config.address = Some("127.0.0.1".to_string());
+        assert_eq!(config.bind_address(), "127.0.0.1:18888");
+        config.address = Some("::1".to_string());
+        assert_eq!(config.bind_address(), "[::1]:18888");
+    }
+
+    #[test]
+    fn should_namespace_the_storage_of_networks() {
+        let storage = StorageConfig::PostgreSqlDbConfig {
+            storage_path: "/casper/storage".to_string(),
+            postgresql_config: PostgresqlConfig {
+                host: "localhost".to_string(),
+                database_name: "event_sidecar".to_string(),
+                database_username: "postgres".to_string(),
+                database_password: "p@$$w0rd".to_string(),
+                max_connections_in_pool: 10,
+                port: 5432,
+                partitioning: PartitioningConfig::default(),
+                index_deploy_timestamps: true,
+                index_account_effects: false,
+            },
+        };
+        let network = NetworkConfigSerdeTarget {
+            name: "testnet".to_string(),
+            connections: vec![Connection::example_connection_1()],
+            storage: None,
+        }
+        .into_network_config(&storage)
+        .unwrap();
+
+        let StorageConfig::PostgreSqlDbConfig {
+            storage_path,
+            postgresql_config,
+        } = network.storage
+        else {
+            panic!("expected the PostgreSQL storage");
+        };
+        assert_eq!(storage_path, "/casper/storage/testnet");
+        assert_eq!(postgresql_config.database_name, "event_sidecar_testnet");
+    }
+
     fn build_rest_server_config() -> RestServerConfig {
         RestServerConfig {
+            address: None,
             port: 18888,
             max_concurrent_requests: 50,
             max_requests_per_second: 50,
+            max_concurrent_awaits: 100,
         }
     }
 
@@ -367,6 +1537,8 @@ mod tests {
                 connection_timeout_in_seconds: None,
                 sleep_between_keep_alive_checks_in_seconds: None,
                 no_message_timeout_in_seconds: None,
+                gap_grace_period_in_seconds: None,
+                priority: None,
             }
         }
 
@@ -382,6 +1554,8 @@ mod tests {
                 connection_timeout_in_seconds: None,
                 sleep_between_keep_alive_checks_in_seconds: None,
                 no_message_timeout_in_seconds: None,
+                gap_grace_period_in_seconds: None,
+                priority: None,
             }
         }
 
@@ -397,6 +1571,8 @@ mod tests {
                 connection_timeout_in_seconds: Some(3),
                 sleep_between_keep_alive_checks_in_seconds: None,
                 no_message_timeout_in_seconds: None,
+                gap_grace_period_in_seconds: None,
+                priority: None,
             }
         }
     }
@@ -414,6 +1590,8 @@ mod tests {
                 connection_timeout_in_seconds: None,
                 sleep_between_keep_alive_checks_in_seconds: None,
                 no_message_timeout_in_seconds: None,
+                gap_grace_period_in_seconds: None,
+                priority: None,
             }
         }
     }
@@ -433,6 +1611,8 @@ mod tests {
                 file_name: "test_sqlite_database".to_string(),
                 max_connections_in_pool: 100,
                 wal_autocheckpointing_interval: 1000,
+                index_deploy_timestamps: true,
+                index_account_effects: false,
             }
         }
     }
@@ -440,9 +1620,11 @@ mod tests {
     impl Default for RestServerConfig {
         fn default() -> Self {
             Self {
+                address: None,
                 port: 17777,
                 max_concurrent_requests: 50,
                 max_requests_per_second: 50,
+                max_concurrent_awaits: 100,
             }
         }
     }
@@ -450,9 +1632,14 @@ mod tests {
     impl Default for EventStreamServerConfig {
         fn default() -> Self {
             Self {
+                address: None,
                 port: 19999,
                 max_concurrent_subscribers: 100,
                 event_stream_buffer_length: 5000,
+                jwt_auth: None,
+                slow_subscriber_timeout_in_seconds: None,
+                priority_classes: None,
+                reject_unavailable_replay: false,
             }
         }
     }
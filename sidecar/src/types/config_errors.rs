@@ -0,0 +1,353 @@
+//! Errors of the config file, located at the TOML path, line and column of the offending value.
+//!
+//! Instead of stopping at the first error, the config is deserialized repeatedly: after each error
+//! the top-level entry holding the offending value is left out, so the errors of the other entries
+//! are reported as well. The entries of the subsystems compiled out of the build, like the Kafka
+//! sink without the `kafka` feature, are reported as errors rather than silently ignored.
+
+use super::config_profiles::{apply_profile, PROFILE_KEY};
+use serde::de::DeserializeOwned;
+use serde_path_to_error::{Path, Segment};
+use std::fmt::{self, Display, Formatter, Write};
+use thiserror::Error;
+use toml::Value;
+
+/// An invalid or missing value of the config file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigFieldError {
+    /// TOML path of the value, e.g. `connections[1].sse_port`. For a missing value, the path of
+    /// the table it is missing from, or `.` for the top level.
+    pub path: String,
+    /// Line and column, starting from 1, of the key or table header of the value, if found.
+    pub location: Option<(usize, usize)>,
+    /// What is wrong with the value, like the expected type or the accepted values.
+    pub message: String,
+}
+
+impl Display for ConfigFieldError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}`", self.path)?;
+        if let Some((line, column)) = self.location {
+            write!(f, " at line {}, column {}", line, column)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("invalid TOML: {message}")]
+    Syntax {
+        /// Line and column, starting from 1, of the error.
+        location: Option<(usize, usize)>,
+        message: String,
+    },
+    #[error("{} error(s) in config:{}", .0.len(), format_field_errors(.0))]
+    Invalid(Vec<ConfigFieldError>),
+}
+
+fn format_field_errors(errors: &[ConfigFieldError]) -> String {
+    errors.iter().fold(String::new(), |mut formatted, error| {
+        let _ = write!(formatted, "\n  {}", error);
+        formatted
+    })
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+fn format_path(path: &[PathSegment]) -> String {
+    if path.is_empty() {
+        return ".".to_string();
+    }
+    let mut formatted = String::new();
+    for segment in path {
+        match segment {
+            PathSegment::Key(key) if formatted.is_empty() => formatted.push_str(key),
+            PathSegment::Key(key) => {
+                formatted.push('.');
+                formatted.push_str(key);
+            }
+            PathSegment::Index(index) => formatted.push_str(&format!("[{}]", index)),
+        }
+    }
+    formatted
+}
+
+/// Parses the TOML `source` into `T`, reporting all the errors found. The `source` is laid over
+/// the defaults of the profile it names, if any.
+pub fn parse_config<T: DeserializeOwned>(source: &str) -> Result<T, ConfigError> {
+    let mut value: Value = toml::from_str(source).map_err(|error| ConfigError::Syntax {
+        location: error
+            .line_col()
+            .map(|(line, column)| (line + 1, column + 1)),
+        message: error.to_string(),
+    })?;
+    apply_profile(&mut value).map_err(|message| {
+        let path = [PathSegment::Key(PROFILE_KEY.to_string())];
+        ConfigError::Invalid(vec![field_error(source, &path, message)])
+    })?;
+    let mut left_out: Vec<String> = Vec::new();
+    let mut errors = disabled_feature_errors(source, &mut value, &mut left_out);
+    loop {
+        let error = match serde_path_to_error::deserialize::<_, T>(value.clone()) {
+            Ok(config) if errors.is_empty() => return Ok(config),
+            Ok(_) => break,
+            Err(error) => error,
+        };
+        let path = path_segments(error.path());
+        let message = error.into_inner().to_string();
+        // Leaving an entry out makes it missing if it is required, which was already reported.
+        if path.is_empty() && left_out.iter().any(|key| is_missing_field(&message, key)) {
+            break;
+        }
+        errors.push(field_error(source, &path, message));
+        match (path.first(), value.as_table_mut()) {
+            (Some(PathSegment::Key(key)), Some(table)) => {
+                table.remove(key);
+                left_out.push(key.clone());
+            }
+            _ => break,
+        }
+    }
+    Err(ConfigError::Invalid(errors))
+}
+
+fn path_segments(path: &Path) -> Vec<PathSegment> {
+    path.iter()
+        .filter_map(|segment| match segment {
+            Segment::Map { key } => Some(PathSegment::Key(key.clone())),
+            Segment::Seq { index } => Some(PathSegment::Index(*index)),
+            Segment::Enum { .. } | Segment::Unknown => None,
+        })
+        .collect()
+}
+
+fn field_error(source: &str, path: &[PathSegment], message: String) -> ConfigFieldError {
+    ConfigFieldError {
+        path: format_path(path),
+        location: locate(source, path),
+        message,
+    }
+}
+
+/// Reports and leaves out the entries of `value` configuring subsystems compiled out of the build.
+fn disabled_feature_errors(
+    source: &str,
+    value: &mut Value,
+    left_out: &mut Vec<String>,
+) -> Vec<ConfigFieldError> {
+    let mut errors = Vec::new();
+    let Some(table) = value.as_table_mut() else {
+        return errors;
+    };
+    let mut disabled = Vec::new();
+    if !cfg!(feature = "kafka") && table.contains_key("kafka") {
+        disabled.push((
+            "kafka",
+            "publishing to Kafka requires the `kafka` feature, which this build doesn't have",
+        ));
+    }
+    if !cfg!(feature = "otlp") && table.contains_key("tracing") {
+        disabled.push((
+            "tracing",
+            "exporting traces over OTLP requires the `otlp` feature, which this build doesn't have",
+        ));
+    }
+    let is_sqlite = table
+        .get("storage")
+        .and_then(Value::as_table)
+        .is_some_and(|storage| storage.contains_key("sqlite_config"));
+    if !cfg!(feature = "postgres") && !is_sqlite {
+        disabled.push((
+            "storage",
+            "the PostgreSQL storage requires the `postgres` feature, which this build doesn't \
+            have; configure `sqlite_config` instead",
+        ));
+    }
+    for (key, message) in disabled {
+        let path = [PathSegment::Key(key.to_string())];
+        errors.push(field_error(source, &path, message.to_string()));
+        table.remove(key);
+        left_out.push(key.to_string());
+    }
+    errors
+}
+
+fn is_missing_field(message: &str, key: &str) -> bool {
+    message.starts_with(&format!("missing field `{}`", key))
+}
+
+fn parse_key(key: &str) -> Vec<PathSegment> {
+    key.split('.')
+        .map(|part| PathSegment::Key(part.trim().trim_matches('"').to_string()))
+        .collect()
+}
+
+/// Returns the line and column of the deepest key or table header of the `source` leading to
+/// `path`, so values within inline tables and arrays are located at their enclosing key.
+fn locate(source: &str, path: &[PathSegment]) -> Option<(usize, usize)> {
+    let mut table: Vec<PathSegment> = Vec::new();
+    let mut array_table_counts: Vec<(Vec<PathSegment>, usize)> = Vec::new();
+    let mut best: Option<(usize, (usize, usize))> = None;
+    for (line_index, line) in source.lines().enumerate() {
+        let trimmed = line.trim_start();
+        let column = line.len() - trimmed.len() + 1;
+        let candidate = if let Some(header) = trimmed.strip_prefix("[[") {
+            let key = parse_key(header.split("]]").next().unwrap_or_default());
+            let index = match array_table_counts.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, count)) => {
+                    *count += 1;
+                    *count - 1
+                }
+                None => {
+                    array_table_counts.push((key.clone(), 1));
+                    0
+                }
+            };
+            table = key;
+            table.push(PathSegment::Index(index));
+            table.clone()
+        } else if let Some(header) = trimmed.strip_prefix('[') {
+            table = parse_key(header.split(']').next().unwrap_or_default());
+            table.clone()
+        } else if trimmed.starts_with('#') || !trimmed.contains('=') {
+            continue;
+        } else {
+            let mut key = table.clone();
+            key.extend(parse_key(trimmed.split('=').next().unwrap_or_default()));
+            key
+        };
+        let matched = path
+            .iter()
+            .zip(&candidate)
+            .take_while(|(expected, found)| expected == found)
+            .count();
+        if matched == candidate.len() && best.map_or(true, |(depth, _)| matched > depth) {
+            best = Some((matched, (line_index + 1, column)));
+        }
+    }
+    best.map(|(_, location)| location)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::config::ConfigSerdeTarget;
+
+    #[test]
+    fn should_report_the_errors_of_every_entry() {
+        let source = include_str!("../../../EXAMPLE_NCTL_CONFIG.toml")
+            .replacen("sse_port = 18102", "sse_port = \"18102\"", 1)
+            .replacen(
+                "max_concurrent_requests = 50",
+                "max_concurrent_requests = -5",
+                1,
+            );
+
+        let errors = match parse_config::<ConfigSerdeTarget>(&source) {
+            Err(ConfigError::Invalid(errors)) => errors,
+            other => panic!("unexpected result: {:?}", other),
+        };
+
+        assert_eq!(
+            errors,
+            vec![
+                ConfigFieldError {
+                    path: "connections[1].sse_port".to_string(),
+                    location: Some((12, 1)),
+                    message: "invalid type: string \"18102\", expected u16".to_string(),
+                },
+                ConfigFieldError {
+                    path: "rest_server.max_concurrent_requests".to_string(),
+                    location: Some((40, 1)),
+                    message: "invalid value: integer `-5`, expected u32".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_missing_values_at_their_table() {
+        let source =
+            include_str!("../../../EXAMPLE_NCTL_CONFIG.toml").replacen("port = 19999\n", "", 1);
+
+        let errors = match parse_config::<ConfigSerdeTarget>(&source) {
+            Err(ConfigError::Invalid(errors)) => errors,
+            other => panic!("unexpected result: {:?}", other),
+        };
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, "event_stream_server");
+        assert_eq!(errors[0].location, Some((43, 1)));
+        assert_eq!(errors[0].message, "missing field `port`");
+    }
+
+    #[test]
+    fn should_report_sections_of_disabled_features() {
+        let source = format!(
+            "{}\n[kafka]\nbrokers = [\"localhost:9092\"]\n",
+            include_str!("../../../EXAMPLE_NCTL_CONFIG.toml")
+        );
+        let postgres_source = include_str!("../../../EXAMPLE_NCTL_POSTGRES_CONFIG.toml");
+
+        let result = parse_config::<ConfigSerdeTarget>(&source);
+        if cfg!(feature = "kafka") {
+            assert!(result.unwrap().kafka.is_some());
+        } else {
+            let errors = match result {
+                Err(ConfigError::Invalid(errors)) => errors,
+                other => panic!("unexpected result: {:?}", other),
+            };
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].path, "kafka");
+            assert!(errors[0].message.contains("`kafka` feature"));
+        }
+        let result = parse_config::<ConfigSerdeTarget>(postgres_source);
+        if cfg!(feature = "postgres") {
+            assert!(result.unwrap().storage.is_some());
+        } else {
+            let errors = match result {
+                Err(ConfigError::Invalid(errors)) => errors,
+                other => panic!("unexpected result: {:?}", other),
+            };
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].path, "storage");
+            assert!(errors[0].message.contains("`postgres` feature"));
+        }
+    }
+
+    #[test]
+    fn should_report_the_export_of_traces_without_the_otlp_feature() {
+        let source = format!(
+            "{}\n[tracing]\notlp_endpoint = \"http://localhost:4317\"\nsampling_percentage = 10\n",
+            include_str!("../../../EXAMPLE_NCTL_CONFIG.toml")
+        );
+
+        let result = parse_config::<ConfigSerdeTarget>(&source);
+        if cfg!(feature = "otlp") {
+            let tracing = result.unwrap().tracing.unwrap();
+            assert_eq!(tracing.service_name(), "casper-event-sidecar");
+            assert_eq!(tracing.sampling_ratio(), 0.1);
+        } else {
+            let errors = match result {
+                Err(ConfigError::Invalid(errors)) => errors,
+                other => panic!("unexpected result: {:?}", other),
+            };
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].path, "tracing");
+            assert!(errors[0].message.contains("`otlp` feature"));
+        }
+    }
+
+    #[test]
+    fn should_report_the_location_of_syntax_errors() {
+        match parse_config::<ConfigSerdeTarget>("[storage\nstorage_path = \"x\"") {
+            Err(ConfigError::Syntax { location, .. }) => assert_eq!(location.unwrap().0, 1),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
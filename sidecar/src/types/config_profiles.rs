@@ -0,0 +1,233 @@
+//! Built-in profiles presetting the config for common deployment shapes.
+//!
+//! A config file naming a profile with `profile = "<name>"` is laid over the defaults of the
+//! profile: tables are merged key by key, and any value of the file, arrays included, replaces the
+//! value of the profile. The indexing defaults of a profile apply to the table of whichever
+//! database the file configures, under `[storage]`.
+
+use toml::{value::Table, Value};
+
+/// Key of the config naming the profile.
+pub const PROFILE_KEY: &str = "profile";
+
+/// Storage backends whose table, under `[storage]`, receives the indexing defaults of a profile.
+const DATABASE_TABLES: [&str; 2] = ["sqlite_config", "postgresql_config"];
+
+struct Profile {
+    name: &'static str,
+    defaults: &'static str,
+    storage_defaults: &'static str,
+}
+
+/// Forwards events to many subscribers, only keeping the events of the last day.
+const RELAY: Profile = Profile {
+    name: "relay",
+    defaults: r#"
+inbound_channel_size = 5000
+outbound_channel_size = 5000
+
+[event_stream_server]
+event_stream_buffer_length = 20000
+max_concurrent_subscribers = 1000
+slow_subscriber_timeout_in_seconds = 30
+
+[event_stream_server.priority_classes]
+
+[source_scoring]
+
+[retention]
+prune_interval_in_seconds = 600
+block_added = { max_age_days = 1 }
+deploy_accepted = { max_age_days = 1 }
+deploy_processed = { max_age_days = 1 }
+deploy_expired = { max_age_days = 1 }
+fault = { max_age_days = 1 }
+finality_signature = { max_age_days = 1 }
+step = { max_age_days = 1 }
+"#,
+    storage_defaults: r#"
+index_deploy_timestamps = false
+index_account_effects = false
+"#,
+};
+
+/// Serves queries of the history of blocks and deploys, without keeping signatures for long.
+const INDEXER: Profile = Profile {
+    name: "indexer",
+    defaults: r#"
+verify_block_hashes = true
+
+[source_scoring]
+
+[readiness]
+max_blocks_behind_head = 2
+
+[retention]
+deploy_accepted = { max_age_days = 30 }
+finality_signature = { max_age_days = 7 }
+"#,
+    storage_defaults: r#"
+index_deploy_timestamps = true
+index_account_effects = true
+"#,
+};
+
+/// Keeps every event, verified, and audits the access to the sidecar.
+const ARCHIVAL: Profile = Profile {
+    name: "archival",
+    defaults: r#"
+verify_finality_signatures = true
+verify_block_hashes = true
+
+[audit]
+"#,
+    storage_defaults: r#"
+index_deploy_timestamps = true
+index_account_effects = true
+"#,
+};
+
+const PROFILES: [Profile; 3] = [RELAY, INDEXER, ARCHIVAL];
+
+fn parse_table(toml: &str) -> Table {
+    toml::from_str(toml).expect("built-in profile should be valid TOML")
+}
+
+/// Lays `overrides` over `defaults`, merging the tables they both have.
+fn merge(defaults: &mut Table, overrides: Table) {
+    for (key, value) in overrides {
+        match (defaults.get_mut(&key), value) {
+            (Some(Value::Table(default_table)), Value::Table(override_table)) => {
+                merge(default_table, override_table)
+            }
+            (_, value) => {
+                defaults.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Lays the config `value` over the defaults of the profile it names, if any, and removes the
+/// profile's name from it. Fails with the reason if the profile isn't known.
+pub fn apply_profile(value: &mut Value) -> Result<(), String> {
+    let Some(table) = value.as_table_mut() else {
+        return Ok(());
+    };
+    let name = match table.remove(PROFILE_KEY) {
+        None => return Ok(()),
+        Some(Value::String(name)) => name,
+        Some(other) => {
+            return Err(format!(
+                "invalid type: {}, expected a string",
+                other.type_str()
+            ))
+        }
+    };
+    let profile = PROFILES
+        .iter()
+        .find(|profile| profile.name == name)
+        .ok_or_else(|| {
+            let names: Vec<String> = PROFILES
+                .iter()
+                .map(|profile| format!("`{}`", profile.name))
+                .collect();
+            format!(
+                "unknown profile `{}`, expected one of {}",
+                name,
+                names.join(", ")
+            )
+        })?;
+    let mut config = parse_table(profile.defaults);
+    if let Some(Value::Table(storage)) = table.get_mut("storage") {
+        for database_table in DATABASE_TABLES {
+            if let Some(Value::Table(database)) = storage.get_mut(database_table) {
+                let mut indexing = parse_table(profile.storage_defaults);
+                merge(&mut indexing, std::mem::take(database));
+                *database = indexing;
+            }
+        }
+    }
+    merge(&mut config, std::mem::take(table));
+    *table = config;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{
+        config::{Config, ConfigSerdeTarget, StorageConfig, TableRetentionConfig},
+        config_errors::{parse_config, ConfigError},
+    };
+    use std::convert::TryInto;
+
+    fn parse_with_profile(profile: &str, overrides: &str) -> Config {
+        let source = format!(
+            "profile = \"{}\"\n{}\n{}",
+            profile,
+            overrides,
+            include_str!("../../../EXAMPLE_NCTL_CONFIG.toml")
+        );
+        parse_config::<ConfigSerdeTarget>(&source)
+            .unwrap()
+            .try_into()
+            .unwrap()
+    }
+
+    #[test]
+    fn should_lay_config_over_profile_defaults() {
+        let config = parse_with_profile("relay", "outbound_channel_size = 100");
+
+        assert_eq!(config.inbound_channel_size, Some(5000));
+        assert_eq!(config.outbound_channel_size, Some(100));
+        assert_eq!(config.event_stream_server.max_concurrent_subscribers, 100);
+        assert!(config.event_stream_server.priority_classes.is_some());
+        assert_eq!(
+            config.retention.unwrap().step,
+            Some(TableRetentionConfig {
+                max_age_days: Some(1),
+                max_rows: None,
+            })
+        );
+        match config.storage {
+            StorageConfig::SqliteDbConfig { sqlite_config, .. } => {
+                assert!(!sqlite_config.index_deploy_timestamps);
+                assert_eq!(sqlite_config.max_connections_in_pool, 100);
+            }
+            other => panic!("unexpected storage: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn should_apply_each_profile() {
+        let indexer = parse_with_profile("indexer", "");
+        assert!(indexer.verify_block_hashes);
+        assert!(!indexer.verify_finality_signatures);
+        assert!(indexer.source_scoring.is_some());
+
+        let archival = parse_with_profile("archival", "");
+        assert!(archival.verify_finality_signatures);
+        assert!(archival.audit.is_some());
+        assert!(archival.retention.is_none());
+    }
+
+    #[test]
+    fn should_reject_unknown_profile() {
+        let source = format!(
+            "profile = \"miner\"\n{}",
+            include_str!("../../../EXAMPLE_NCTL_CONFIG.toml")
+        );
+
+        let errors = match parse_config::<ConfigSerdeTarget>(&source) {
+            Err(ConfigError::Invalid(errors)) => errors,
+            other => panic!("unexpected result: {:?}", other),
+        };
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, "profile");
+        assert_eq!(errors[0].location, Some((1, 1)));
+        assert_eq!(
+            errors[0].message,
+            "unknown profile `miner`, expected one of `relay`, `indexer`, `archival`"
+        );
+    }
+}
@@ -1,303 +1,38 @@
+#[cfg(feature = "postgres")]
+use crate::database::postgresql_database::PostgreSqlDatabase;
 use crate::{
-    database::{
-        postgresql_database::PostgreSqlDatabase, sqlite_database::SqliteDatabase,
-        types::DDLConfiguration,
-    },
-    sql::tables,
-    types::sse_events::{
-        BlockAdded, DeployAccepted, DeployExpired, DeployProcessed, Fault, FinalitySignature, Step,
+    database::{sqlite_database::SqliteDatabase, types::DDLConfiguration},
+    sql::{
+        partitioning::{self, PartitionedTable},
+        tables,
     },
 };
 use anyhow::Error;
 use async_trait::async_trait;
-use casper_event_types::FinalitySignature as FinSig;
-use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use utoipa::ToSchema;
+
+pub use casper_event_storage::*;
 
 #[derive(Clone)]
 pub enum Database {
     SqliteDatabaseWrapper(SqliteDatabase),
+    #[cfg(feature = "postgres")]
     PostgreSqlDatabaseWrapper(PostgreSqlDatabase),
 }
 
-/// Describes a reference for the writing interface of an 'Event Store' database.
-/// There is a one-to-one relationship between each method and each event that can be received from the node.
-/// Each method takes the `data` and `id` fields as well as the source IP address (useful for tying the node-specific `id` to the relevant node).
-///
-/// For a reference implementation using Sqlite see, [SqliteDatabase](crate::sqlite_database::SqliteDatabase)
+/// Executes the migrations of the relational databases of the sidecar, see [Migration].
 #[async_trait]
-pub trait DatabaseWriter {
-    /// Save a BlockAdded event to the database.
-    ///
-    /// * `block_added`: the [BlockAdded] from the `data` field.
-    /// * `event_id`: the node-specific assigned `id`.
-    /// * `event_source_address`: the IP address of the source node.
-    async fn save_block_added(
-        &self,
-        block_added: BlockAdded,
-        event_id: u32,
-        event_source_address: String,
-    ) -> Result<u64, DatabaseWriteError>;
-    /// Save a DeployAccepted event to the database.
-    ///
-    /// * `deploy_accepted`: the [DeployAccepted] from the `data` field.
-    /// * `event_id`: the node-specific assigned `id`.
-    /// * `event_source_address`: the IP address of the source node.
-    async fn save_deploy_accepted(
-        &self,
-        deploy_accepted: DeployAccepted,
-        event_id: u32,
-        event_source_address: String,
-    ) -> Result<u64, DatabaseWriteError>;
-    /// Save a DeployProcessed event to the database.
-    ///
-    /// * `deploy_accepted`: the [DeployProcessed] from the `data` field.
-    /// * `event_id`: the node-specific assigned `id`.
-    /// * `event_source_address`: the IP address of the source node.
-    async fn save_deploy_processed(
-        &self,
-        deploy_processed: DeployProcessed,
-        event_id: u32,
-        event_source_address: String,
-    ) -> Result<u64, DatabaseWriteError>;
-    /// Save a DeployExpired event to the database.
-    ///
-    /// * `deploy_expired`: the [DeployExpired] from the `data` field.
-    /// * `event_id`: the node-specific assigned `id`.
-    /// * `event_source_address`: the IP address of the source node.
-    async fn save_deploy_expired(
-        &self,
-        deploy_expired: DeployExpired,
-        event_id: u32,
-        event_source_address: String,
-    ) -> Result<u64, DatabaseWriteError>;
-    /// Save a Fault event to the database.
-    ///
-    /// * `fault`: the [Fault] from the `data` field.
-    /// * `event_id`: the node-specific assigned `id`.
-    /// * `event_source_address`: the IP address of the source node.
-    async fn save_fault(
-        &self,
-        fault: Fault,
-        event_id: u32,
-        event_source_address: String,
-    ) -> Result<u64, DatabaseWriteError>;
-    /// Save a FinalitySignature event to the database.
-    ///
-    /// * `finality_signature`: the [FinalitySignature] from the `data` field.
-    /// * `event_id`: the node-specific assigned `id`.
-    /// * `event_source_address`: the IP address of the source node.
-    async fn save_finality_signature(
-        &self,
-        finality_signature: FinalitySignature,
-        event_id: u32,
-        event_source_address: String,
-    ) -> Result<u64, DatabaseWriteError>;
-    /// Save a Step event to the database.
-    ///
-    /// * `step`: the [Step] from the `data` field.
-    /// * `event_id`: the node-specific assigned `id`.
-    /// * `event_source_address`: the IP address of the source node.
-    async fn save_step(
-        &self,
-        step: Step,
-        event_id: u32,
-        event_source_address: String,
-    ) -> Result<u64, DatabaseWriteError>;
-
-    // Save data about shutdown to the database
-    async fn save_shutdown(
-        &self,
-        event_id: u32,
-        event_source_address: String,
-    ) -> Result<u64, DatabaseWriteError>;
-
+pub trait MigrationExecutor {
     /// Executes migration and stores current migration version
     ///
     /// * `migration`: migration to execute
     async fn execute_migration(&self, migration: Migration) -> Result<(), DatabaseWriteError>;
 }
 
-#[derive(Debug)]
-pub struct UniqueConstraintError {
-    pub table: String,
-    pub error: sqlx::Error,
-}
-
-/// The database failed to insert a record(s).
-#[derive(Debug)]
-pub enum DatabaseWriteError {
-    /// The insert failed prior to execution because the data could not be serialised.
-    Serialisation(serde_json::Error),
-    /// The insert failed prior to execution because the SQL could not be constructed.
-    SqlConstruction(sea_query::error::Error),
-    /// The insert was rejected by the database because it would break a unique constraint.
-    UniqueConstraint(UniqueConstraintError),
-    /// The insert was rejected by the database.
-    Database(sqlx::Error),
-    /// An error occurred somewhere unexpected.
-    Unhandled(anyhow::Error),
-}
-
-impl ToString for DatabaseWriteError {
-    fn to_string(&self) -> String {
-        format!("{:?}", self)
-    }
-}
-
-impl From<serde_json::Error> for DatabaseWriteError {
-    fn from(serde_err: serde_json::Error) -> Self {
-        Self::Serialisation(serde_err)
-    }
-}
-
-impl From<sea_query::error::Error> for DatabaseWriteError {
-    fn from(sea_query_err: sea_query::error::Error) -> Self {
-        Self::SqlConstruction(sea_query_err)
-    }
-}
-
-impl From<sqlx::Error> for DatabaseWriteError {
-    fn from(sqlx_err: sqlx::Error) -> Self {
-        if let Some(db_err) = sqlx_err.as_database_error() {
-            if let Some(code) = db_err.code() {
-                match code.as_ref() {
-                    "23505" => {
-                        //"23505" is postgresql unique constraint violation violation
-                        let table = db_err
-                            .table()
-                            .map(|table_name| table_name.to_string())
-                            .unwrap_or(db_err.message().to_string());
-                        return Self::UniqueConstraint(UniqueConstraintError {
-                            table,
-                            error: sqlx_err,
-                        });
-                    }
-                    "1555" | "2067" => {
-                        // The message looks something like this:
-                        // UNIQUE constraint failed: DeployProcessed.deploy_hash
-
-                        let table = db_err.message().split(':').collect::<Vec<&str>>()[1]
-                            .split('.')
-                            .collect::<Vec<&str>>()[0]
-                            .trim()
-                            .to_string();
-                        return Self::UniqueConstraint(UniqueConstraintError {
-                            table,
-                            error: sqlx_err,
-                        });
-                    }
-                    _ => {}
-                }
-            }
-        }
-        Self::Database(sqlx_err)
-    }
-}
-
-impl From<anyhow::Error> for DatabaseWriteError {
-    fn from(anyhow_err: anyhow::Error) -> Self {
-        Self::Unhandled(anyhow_err)
-    }
-}
-
-/// Describes a reference for the reading interface of an 'Event Store' database.
-///
-/// For a reference implementation using Sqlite see, [SqliteDatabase](crate::sqlite_database::SqliteDatabase)
-#[async_trait]
-pub trait DatabaseReader {
-    /// Returns the latest [BlockAdded] by height from the database.
-    async fn get_latest_block(&self) -> Result<BlockAdded, DatabaseReadError>;
-    /// Returns the [BlockAdded] corresponding to the provided `height`.
-    ///
-    /// * `height` - Height of the block which should be retrieved
-    async fn get_block_by_height(&self, height: u64) -> Result<BlockAdded, DatabaseReadError>;
-    /// Returns the [BlockAdded] corresponding to the provided hex-encoded `hash`.
-    ///
-    /// * `hash` - hash which identifies the block
-    async fn get_block_by_hash(&self, hash: &str) -> Result<BlockAdded, DatabaseReadError>;
-    /// Returns an aggregate of the deploy's events corresponding to the given hex-encoded `hash`
-    ///
-    /// * `hash` - deploy hash of which the aggregate data should be fetched
-    async fn get_deploy_aggregate_by_hash(
-        &self,
-        hash: &str,
-    ) -> Result<DeployAggregate, DatabaseReadError>;
-    /// Returns the [DeployAccepted] corresponding to the given hex-encoded `hash`
-    ///
-    /// * `hash` - deploy hash which identifies the deploy accepted
-    async fn get_deploy_accepted_by_hash(
-        &self,
-        hash: &str,
-    ) -> Result<DeployAccepted, DatabaseReadError>;
-    /// Returns the [DeployProcessed] corresponding to the given hex-encoded `hash`
-    ///
-    /// * `hash` - deploy hash which identifies the deploy pocessed
-    async fn get_deploy_processed_by_hash(
-        &self,
-        hash: &str,
-    ) -> Result<DeployProcessed, DatabaseReadError>;
-
-    /// Returns the [DeployExpired] corresponding to the given hex-encoded `hash`
-    ///
-    /// * `hash` - deploy hash which identifies the deploy expired
-    async fn get_deploy_expired_by_hash(
-        &self,
-        hash: &str,
-    ) -> Result<DeployExpired, DatabaseReadError>;
-    /// Returns all [Fault]s that correspond to the given hex-encoded `public_key`
-    ///
-    /// * `public_key` - key which identifies the fault
-    async fn get_faults_by_public_key(
-        &self,
-        public_key: &str,
-    ) -> Result<Vec<Fault>, DatabaseReadError>;
-    /// Returns all [Fault]s that occurred in the given `era`
-    ///
-    /// * `era` - number of era for which faults should be fetched
-    async fn get_faults_by_era(&self, era: u64) -> Result<Vec<Fault>, DatabaseReadError>;
-    /// Returns all [FinalitySignature](casper_event_types::FinalitySignature)s for the given hex-encoded `block_hash`.
-    ///
-    /// * `block_hash` - block hash for which finality signatures should be fetched
-    async fn get_finality_signatures_by_block(
-        &self,
-        block_hash: &str,
-    ) -> Result<Vec<FinSig>, DatabaseReadError>;
-    /// Returns the [Step] event for the given era.
-    ///
-    /// * `era` - identifier of era
-    async fn get_step_by_era(&self, era: u64) -> Result<Step, DatabaseReadError>;
-
-    /// Returns number of events stored in db.
-    async fn get_number_of_events(&self) -> Result<u64, DatabaseReadError>;
-
-    /// Gets the newest migration version.
-    async fn get_newest_migration_version(&self) -> Result<Option<(u32, bool)>, DatabaseReadError>;
-}
-
-/// The database was unable to fulfil the request.
-#[derive(Debug)]
-pub enum DatabaseReadError {
-    /// The requested record was not present in the database.
-    NotFound,
-    /// An error occurred serialising or deserialising data from the database.
-    Serialisation(serde_json::Error),
-    /// An error occurred somewhere unexpected.
-    Unhandled(anyhow::Error),
-}
-
-#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
-pub struct DeployAggregate {
-    pub(crate) deploy_hash: String,
-    pub(crate) deploy_accepted: Option<DeployAccepted>,
-    pub(crate) deploy_processed: Option<DeployProcessed>,
-    pub(crate) deploy_expired: bool,
-}
-
-#[allow(dead_code)] //Allowing dead code here because the Raw enum is used only in ITs
 pub enum StatementWrapper {
     TableCreateStatement(Box<sea_query::TableCreateStatement>),
+    IndexCreateStatement(Box<sea_query::IndexCreateStatement>),
+    TableAlterStatement(Box<sea_query::TableAlterStatement>),
     InsertStatement(sea_query::InsertStatement),
     Raw(String),
 }
@@ -338,7 +73,36 @@ pub struct Migration {
 
 impl Migration {
     pub fn get_all_migrations() -> Vec<Migration> {
-        vec![Migration::migration_1()]
+        vec![
+            Migration::migration_1(),
+            Migration::migration_2(),
+            Migration::migration_3(),
+            Migration::migration_4(),
+            Migration::migration_5(),
+            Migration::migration_6(),
+            Migration::migration_7(),
+            Migration::migration_8(),
+            Migration::migration_9(),
+            Migration::migration_10(),
+            Migration::migration_11(),
+            Migration::migration_12(),
+            Migration::migration_13(),
+            Migration::migration_14(),
+            Migration::migration_15(),
+            Migration::migration_16(),
+            Migration::migration_17(),
+            Migration::migration_18(),
+            Migration::migration_19(),
+            Migration::migration_20(),
+            Migration::migration_21(),
+            Migration::migration_22(),
+            Migration::migration_23(),
+            Migration::migration_24(),
+            Migration::migration_25(),
+            Migration::migration_26(),
+            Migration::migration_27(),
+            Migration::migration_28(),
+        ]
     }
 
     pub fn initial() -> Migration {
@@ -367,6 +131,402 @@ impl Migration {
         }
     }
 
+    pub fn migration_2() -> Migration {
+        Migration {
+            version: Some(2),
+            statement_producers: |config: DDLConfiguration| {
+                Ok(vec![StatementWrapper::TableCreateStatement(Box::new(
+                    tables::audit_log::create_table_stmt(config.is_big_integer_id),
+                ))])
+            },
+            script_executor: None,
+        }
+    }
+
+    pub fn migration_3() -> Migration {
+        Migration {
+            version: Some(3),
+            statement_producers: |_config: DDLConfiguration| {
+                Ok(vec![StatementWrapper::TableCreateStatement(Box::new(
+                    tables::event_id_allocator::create_table_stmt(),
+                ))])
+            },
+            script_executor: None,
+        }
+    }
+
+    pub fn migration_4() -> Migration {
+        Migration {
+            version: Some(4),
+            statement_producers: |config: DDLConfiguration| {
+                if !config.supports_partitioning {
+                    return Ok(vec![]);
+                }
+                Ok(PartitionedTable::ALL
+                    .iter()
+                    .flat_map(|table| {
+                        partitioning::create_conversion_stmts(*table, config.db_supports_unsigned)
+                    })
+                    .map(StatementWrapper::Raw)
+                    .collect())
+            },
+            script_executor: None,
+        }
+    }
+
+    pub fn migration_5() -> Migration {
+        Migration {
+            version: Some(5),
+            statement_producers: |_config: DDLConfiguration| {
+                Ok(vec![
+                    StatementWrapper::TableCreateStatement(Box::new(
+                        tables::deploy_timestamp::create_table_stmt(),
+                    )),
+                    StatementWrapper::IndexCreateStatement(Box::new(
+                        tables::deploy_timestamp::create_timestamp_index_stmt(),
+                    )),
+                ])
+            },
+            script_executor: None,
+        }
+    }
+
+    pub fn migration_6() -> Migration {
+        Migration {
+            version: Some(6),
+            statement_producers: |_config: DDLConfiguration| {
+                let insert_type_stmt =
+                    tables::event_type::create_insert_unknown_stmt().map_err(|err| {
+                        Error::msg(format!(
+                            "Error building create_insert_unknown_stmt: {:?}",
+                            err
+                        ))
+                    })?;
+                Ok(vec![
+                    StatementWrapper::InsertStatement(insert_type_stmt),
+                    StatementWrapper::TableCreateStatement(Box::new(
+                        tables::unknown_event::create_table_stmt(),
+                    )),
+                ])
+            },
+            script_executor: None,
+        }
+    }
+
+    pub fn migration_7() -> Migration {
+        Migration {
+            version: Some(7),
+            statement_producers: |_config: DDLConfiguration| {
+                Ok(vec![StatementWrapper::TableCreateStatement(Box::new(
+                    tables::enrichment::create_table_stmt(),
+                ))])
+            },
+            script_executor: None,
+        }
+    }
+
+    pub fn migration_8() -> Migration {
+        Migration {
+            version: Some(8),
+            statement_producers: |_config: DDLConfiguration| {
+                Ok(vec![StatementWrapper::TableAlterStatement(Box::new(
+                    tables::finality_signature::create_add_invalid_column_stmt(),
+                ))])
+            },
+            script_executor: None,
+        }
+    }
+
+    pub fn migration_9() -> Migration {
+        Migration {
+            version: Some(9),
+            statement_producers: |_config: DDLConfiguration| {
+                Ok(vec![StatementWrapper::TableAlterStatement(Box::new(
+                    tables::block_added::create_add_integrity_column_stmt(),
+                ))])
+            },
+            script_executor: None,
+        }
+    }
+
+    pub fn migration_10() -> Migration {
+        Migration {
+            version: Some(10),
+            statement_producers: |_config: DDLConfiguration| {
+                Ok(vec![StatementWrapper::TableCreateStatement(Box::new(
+                    tables::deploy_error::create_table_stmt(),
+                ))])
+            },
+            script_executor: None,
+        }
+    }
+
+    pub fn migration_11() -> Migration {
+        Migration {
+            version: Some(11),
+            statement_producers: |_config: DDLConfiguration| {
+                Ok(vec![StatementWrapper::TableCreateStatement(Box::new(
+                    tables::contract_stats::create_table_stmt(),
+                ))])
+            },
+            script_executor: None,
+        }
+    }
+
+    pub fn migration_12() -> Migration {
+        Migration {
+            version: Some(12),
+            statement_producers: |_config: DDLConfiguration| {
+                Ok(vec![StatementWrapper::TableCreateStatement(Box::new(
+                    tables::era_validator::create_table_stmt(),
+                ))])
+            },
+            script_executor: None,
+        }
+    }
+
+    pub fn migration_13() -> Migration {
+        Migration {
+            version: Some(13),
+            statement_producers: |config: DDLConfiguration| {
+                Ok(vec![
+                    StatementWrapper::TableCreateStatement(Box::new(
+                        tables::derived_event::create_table_stmt(config.is_big_integer_id),
+                    )),
+                    StatementWrapper::IndexCreateStatement(Box::new(
+                        tables::derived_event::create_deriver_index_stmt(),
+                    )),
+                ])
+            },
+            script_executor: None,
+        }
+    }
+
+    pub fn migration_14() -> Migration {
+        Migration {
+            version: Some(14),
+            statement_producers: |_config: DDLConfiguration| {
+                Ok(vec![
+                    StatementWrapper::TableCreateStatement(Box::new(
+                        tables::deploy_account::create_table_stmt(),
+                    )),
+                    StatementWrapper::IndexCreateStatement(Box::new(
+                        tables::deploy_account::create_account_index_stmt(),
+                    )),
+                ])
+            },
+            script_executor: None,
+        }
+    }
+
+    pub fn migration_15() -> Migration {
+        Migration {
+            version: Some(15),
+            statement_producers: |_config: DDLConfiguration| {
+                let mut statements = vec![StatementWrapper::TableCreateStatement(Box::new(
+                    tables::deploy_size::create_table_stmt(),
+                ))];
+                for measure in tables::deploy_size::MEASURES {
+                    statements.push(StatementWrapper::IndexCreateStatement(Box::new(
+                        tables::deploy_size::create_measure_index_stmt(measure),
+                    )));
+                }
+                Ok(statements)
+            },
+            script_executor: None,
+        }
+    }
+
+    pub fn migration_16() -> Migration {
+        Migration {
+            version: Some(16),
+            statement_producers: |_config: DDLConfiguration| {
+                Ok(vec![StatementWrapper::TableCreateStatement(Box::new(
+                    tables::account_effect::create_table_stmt(),
+                ))])
+            },
+            script_executor: None,
+        }
+    }
+
+    pub fn migration_17() -> Migration {
+        Migration {
+            version: Some(17),
+            statement_producers: |config: DDLConfiguration| {
+                Ok(vec![
+                    StatementWrapper::TableCreateStatement(Box::new(
+                        tables::webhook_delivery::create_table_stmt(config.is_big_integer_id),
+                    )),
+                    StatementWrapper::IndexCreateStatement(Box::new(
+                        tables::webhook_delivery::create_webhook_index_stmt(),
+                    )),
+                ])
+            },
+            script_executor: None,
+        }
+    }
+
+    pub fn migration_18() -> Migration {
+        Migration {
+            version: Some(18),
+            statement_producers: |config: DDLConfiguration| {
+                Ok(vec![StatementWrapper::TableCreateStatement(Box::new(
+                    tables::operational_event::create_table_stmt(config.is_big_integer_id),
+                ))])
+            },
+            script_executor: None,
+        }
+    }
+
+    pub fn migration_19() -> Migration {
+        Migration {
+            version: Some(19),
+            statement_producers: |_config: DDLConfiguration| {
+                Ok(vec![StatementWrapper::TableCreateStatement(Box::new(
+                    tables::deploy_approval::create_table_stmt(),
+                ))])
+            },
+            script_executor: None,
+        }
+    }
+
+    pub fn migration_20() -> Migration {
+        Migration {
+            version: Some(20),
+            statement_producers: |_config: DDLConfiguration| {
+                Ok(vec![StatementWrapper::TableAlterStatement(Box::new(
+                    tables::era_validator::create_add_weight_column_stmt(),
+                ))])
+            },
+            script_executor: None,
+        }
+    }
+
+    pub fn migration_21() -> Migration {
+        Migration {
+            version: Some(21),
+            statement_producers: |_config: DDLConfiguration| {
+                Ok(vec![
+                    StatementWrapper::TableCreateStatement(Box::new(
+                        tables::transfer::create_table_stmt(),
+                    )),
+                    StatementWrapper::IndexCreateStatement(Box::new(
+                        tables::transfer::create_from_account_index_stmt(),
+                    )),
+                    StatementWrapper::IndexCreateStatement(Box::new(
+                        tables::transfer::create_to_account_index_stmt(),
+                    )),
+                    StatementWrapper::IndexCreateStatement(Box::new(
+                        tables::transfer::create_block_hash_index_stmt(),
+                    )),
+                ])
+            },
+            script_executor: None,
+        }
+    }
+
+    pub fn migration_22() -> Migration {
+        Migration {
+            version: Some(22),
+            statement_producers: |_config: DDLConfiguration| {
+                Ok(vec![
+                    StatementWrapper::TableAlterStatement(Box::new(
+                        tables::transfer::create_add_transfer_id_column_stmt(),
+                    )),
+                    StatementWrapper::IndexCreateStatement(Box::new(
+                        tables::transfer::create_transfer_id_index_stmt(),
+                    )),
+                ])
+            },
+            script_executor: None,
+        }
+    }
+
+    pub fn migration_23() -> Migration {
+        Migration {
+            version: Some(23),
+            statement_producers: |_config: DDLConfiguration| {
+                Ok(vec![StatementWrapper::TableCreateStatement(Box::new(
+                    tables::token_transfer::create_table_stmt(),
+                ))])
+            },
+            script_executor: None,
+        }
+    }
+
+    pub fn migration_24() -> Migration {
+        Migration {
+            version: Some(24),
+            statement_producers: |_config: DDLConfiguration| {
+                Ok(vec![StatementWrapper::TableCreateStatement(Box::new(
+                    tables::finality_signature_checkpoint::create_table_stmt(),
+                ))])
+            },
+            script_executor: None,
+        }
+    }
+
+    pub fn migration_25() -> Migration {
+        Migration {
+            version: Some(25),
+            statement_producers: |_config: DDLConfiguration| {
+                Ok(vec![
+                    StatementWrapper::TableCreateStatement(Box::new(
+                        tables::nft_event::create_table_stmt(),
+                    )),
+                    StatementWrapper::IndexCreateStatement(Box::new(
+                        tables::nft_event::create_token_id_index_stmt(),
+                    )),
+                ])
+            },
+            script_executor: None,
+        }
+    }
+
+    pub fn migration_26() -> Migration {
+        Migration {
+            version: Some(26),
+            statement_producers: |_config: DDLConfiguration| {
+                Ok(vec![StatementWrapper::TableCreateStatement(Box::new(
+                    tables::network_identity::create_table_stmt(),
+                ))])
+            },
+            script_executor: None,
+        }
+    }
+
+    pub fn migration_27() -> Migration {
+        Migration {
+            version: Some(27),
+            statement_producers: |_config: DDLConfiguration| {
+                Ok(vec![
+                    StatementWrapper::TableCreateStatement(Box::new(
+                        tables::contract_event_schema::create_table_stmt(),
+                    )),
+                    StatementWrapper::TableCreateStatement(Box::new(
+                        tables::contract_event::create_table_stmt(),
+                    )),
+                    StatementWrapper::IndexCreateStatement(Box::new(
+                        tables::contract_event::create_event_name_index_stmt(),
+                    )),
+                ])
+            },
+            script_executor: None,
+        }
+    }
+
+    pub fn migration_28() -> Migration {
+        Migration {
+            version: Some(28),
+            statement_producers: |_config: DDLConfiguration| {
+                Ok(vec![StatementWrapper::TableCreateStatement(Box::new(
+                    tables::api_key::create_table_stmt(),
+                ))])
+            },
+            script_executor: None,
+        }
+    }
+
     pub fn get_version(&self) -> Option<u32> {
         self.version
     }
@@ -17,7 +17,7 @@ use tokio::sync::{
     mpsc::{channel, Sender},
     Mutex,
 };
-use warp::{reject, Filter};
+use warp::{filters::BoxedFilter, reject, reply::Response, Filter};
 
 #[derive(Debug)]
 pub struct Unexpected(pub(super) anyhow::Error);
@@ -101,6 +101,22 @@ pub fn root_filter() -> impl Filter<Extract = (impl warp::Reply,), Error = warp:
         .and_then(|| async { Err::<String, warp::Rejection>(warp::reject::custom(InvalidPath)) })
 }
 
+/// Serves the routes of each network under the name of the network, e.g. `/mainnet/events/main`,
+/// when the Sidecar ingests the events of several networks.
+pub(crate) fn mount_networks(
+    routes: Vec<(String, BoxedFilter<(Response,)>)>,
+) -> BoxedFilter<(Response,)> {
+    routes
+        .into_iter()
+        .map(|(name, routes)| warp::path(name).and(routes).boxed())
+        .reduce(|mounted, routes| mounted.or(routes).unify().boxed())
+        .unwrap_or_else(|| {
+            warp::any()
+                .and_then(|| async { Err::<Response, _>(reject::not_found()) })
+                .boxed()
+        })
+}
+
 #[cfg(feature = "additional-metrics")]
 struct MetricsData {
     last_measurement: Instant,
@@ -147,21 +163,24 @@ pub fn start_metrics_thread(module_name: String) -> Sender<()> {
 
 #[cfg(test)]
 pub mod tests {
+    #[cfg(feature = "postgres")]
     use crate::database::postgresql_database::PostgreSqlDatabase;
     use crate::run;
     use crate::testing::mock_node::tests::MockNode;
-    use crate::testing::testing_config::get_port;
+    use crate::testing::ports::get_port;
     use crate::testing::testing_config::prepare_config;
     use crate::testing::testing_config::TestingConfig;
     use crate::types::config::Config;
     use anyhow::Error;
+    #[cfg(feature = "postgres")]
     use anyhow::Error as AnyhowError;
-    use pg_embed::pg_enums::PgAuthMethod;
-    use pg_embed::postgres::PgSettings;
+    #[cfg(feature = "postgres")]
     use pg_embed::{
+        pg_enums::PgAuthMethod,
         pg_fetch::{PgFetchSettings, PG_V13},
-        postgres::PgEmbed,
+        postgres::{PgEmbed, PgSettings},
     };
+    #[cfg(feature = "postgres")]
     use std::path::PathBuf;
     use std::time::Duration;
     use tempfile::{tempdir, TempDir};
@@ -293,17 +312,20 @@ pub mod tests {
             event_stream_server_port,
         )
     }
+    #[cfg(feature = "postgres")]
     pub struct PostgresTestContext {
         pub pg: PgEmbed,
         pub _temp_dir: TempDir,
         pub db: PostgreSqlDatabase,
         pub port: u16,
     }
+    #[cfg(feature = "postgres")]
     impl Drop for PostgresTestContext {
         fn drop(&mut self) {
             let _ = self.pg.stop_db_sync();
         }
     }
+    #[cfg(feature = "postgres")]
     async fn spin_up_postgres(
         pg_settings: PgSettings,
         temp_dir: TempDir,
@@ -332,6 +354,7 @@ pub mod tests {
         }
         Ok((pg, temp_dir))
     }
+    #[cfg(feature = "postgres")]
     async fn start_embedded_postgres() -> (PgEmbed, TempDir, u16) {
         let port = get_port();
         let temp_storage_path =
@@ -352,6 +375,7 @@ pub mod tests {
             .unwrap();
         (db, folder, port)
     }
+    #[cfg(feature = "postgres")]
     pub async fn build_postgres_database() -> Result<PostgresTestContext, AnyhowError> {
         let (pg, temp_dir, port) = start_embedded_postgres().await;
         let database_name = "event_sidecar";
@@ -367,6 +391,7 @@ pub mod tests {
             port,
         })
     }
+    #[cfg(feature = "postgres")]
     pub async fn build_postgres_based_test_config(
         max_attempts: usize,
         delay_between_retries: usize,
@@ -0,0 +1,71 @@
+//! Checks of the identifiers taken by the write endpoints of the admin server and by the config.
+//!
+//! Malformed input is rejected with an error saying what was expected and what is wrong with what
+//! was received, rather than being stored as is and silently never matching anything.
+
+use casper_types::{AsymmetricType, PublicKey};
+
+/// Length, in bytes, of the hashes identifying accounts, contracts and contract packages.
+pub(crate) const HASH_LENGTH: usize = 32;
+
+/// Parses a hex-encoded hash, returning it in lowercase.
+pub(crate) fn parse_hash(hash: &str) -> Result<String, String> {
+    let hash = hash.trim().to_lowercase();
+    let bytes = hex::decode(&hash)
+        .map_err(|error| format!("expected a hex-encoded hash, received {}: {}", hash, error))?;
+    if bytes.len() != HASH_LENGTH {
+        return Err(format!(
+            "expected a hash of {} bytes, received {} bytes: {}",
+            HASH_LENGTH,
+            bytes.len(),
+            hash
+        ));
+    }
+    Ok(hash)
+}
+
+/// Parses a hex-encoded public key, returning it in the form events carry it in.
+pub(crate) fn parse_public_key(public_key: &str) -> Result<String, String> {
+    let public_key = public_key.trim();
+    PublicKey::from_hex(public_key)
+        .map(|public_key| public_key.to_hex())
+        .map_err(|error| {
+            format!(
+                "expected a hex-encoded public key, received {}: {}",
+                public_key, error
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use casper_types::testing::TestRng;
+
+    #[test]
+    fn should_parse_hashes_of_the_right_length() {
+        let hash = "AB".repeat(HASH_LENGTH);
+
+        assert_eq!(parse_hash(&hash), Ok(hash.to_lowercase()));
+        assert_eq!(
+            parse_hash("abcd"),
+            Err("expected a hash of 32 bytes, received 2 bytes: abcd".to_string())
+        );
+        assert!(parse_hash(&"zz".repeat(HASH_LENGTH))
+            .unwrap_err()
+            .starts_with("expected a hex-encoded hash"));
+    }
+
+    #[test]
+    fn should_parse_public_keys() {
+        let public_key = PublicKey::random(&mut TestRng::new()).to_hex();
+
+        assert_eq!(
+            parse_public_key(&format!(" {} ", public_key.to_uppercase())),
+            Ok(public_key)
+        );
+        assert!(parse_public_key(&"ab".repeat(HASH_LENGTH))
+            .unwrap_err()
+            .starts_with("expected a hex-encoded public key"));
+    }
+}
@@ -0,0 +1,336 @@
+//! Accounts, contracts and validators an operator follows closely.
+//!
+//! Events relating to a watched entity are processed in a lane of their own so that they don't
+//! wait behind the backlog of other events, are published on the `/events/watched` stream and are
+//! counted per entity. The list is read from the config and can be changed at runtime through the
+//! admin server; such changes are not persisted.
+
+use crate::{types::config::WatchListConfig, validation};
+use anyhow::Error;
+use casper_event_types::{
+    metrics::WATCHED_EVENTS, sse_data::SseData, Deploy, ExecutableDeployItem,
+};
+use casper_types::{AsymmetricType, ExecutionResult};
+use serde::Serialize;
+use std::{
+    collections::BTreeSet,
+    str::FromStr,
+    sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+/// The kinds of entities which can be watched.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum WatchedKind {
+    Account,
+    Contract,
+    Validator,
+}
+
+impl WatchedKind {
+    fn label(&self) -> &'static str {
+        match self {
+            WatchedKind::Account => "account",
+            WatchedKind::Contract => "contract",
+            WatchedKind::Validator => "validator",
+        }
+    }
+
+    /// Brings `entity` to the form it is compared in: lowercase hex, without the `hash-` or
+    /// `contract-` prefix of contract hashes. Fails if `entity` isn't a public key, for accounts
+    /// and validators, or a hash, for contracts.
+    fn parse(&self, entity: &str) -> Result<String, String> {
+        match self {
+            WatchedKind::Contract => {
+                let entity = entity.trim().to_lowercase();
+                let hash = entity
+                    .strip_prefix("hash-")
+                    .or_else(|| entity.strip_prefix("contract-"))
+                    .unwrap_or(&entity);
+                validation::parse_hash(hash)
+            }
+            WatchedKind::Account | WatchedKind::Validator => validation::parse_public_key(entity),
+        }
+    }
+}
+
+/// Parses the plural used in the paths of the admin server, e.g. `accounts`.
+impl FromStr for WatchedKind {
+    type Err = String;
+
+    fn from_str(kind: &str) -> Result<Self, Self::Err> {
+        match kind {
+            "accounts" => Ok(WatchedKind::Account),
+            "contracts" => Ok(WatchedKind::Contract),
+            "validators" => Ok(WatchedKind::Validator),
+            _ => Err(format!("unknown kind of watched entity: {}", kind)),
+        }
+    }
+}
+
+/// The watched entities, as hex-encoded public keys for accounts and validators and as hex-encoded
+/// hashes for contracts and contract packages.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub(crate) struct WatchedEntities {
+    pub(crate) accounts: BTreeSet<String>,
+    pub(crate) contracts: BTreeSet<String>,
+    pub(crate) validators: BTreeSet<String>,
+}
+
+impl WatchedEntities {
+    fn of_kind(&self, kind: WatchedKind) -> &BTreeSet<String> {
+        match kind {
+            WatchedKind::Account => &self.accounts,
+            WatchedKind::Contract => &self.contracts,
+            WatchedKind::Validator => &self.validators,
+        }
+    }
+
+    fn of_kind_mut(&mut self, kind: WatchedKind) -> &mut BTreeSet<String> {
+        match kind {
+            WatchedKind::Account => &mut self.accounts,
+            WatchedKind::Contract => &mut self.contracts,
+            WatchedKind::Validator => &mut self.validators,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.accounts.is_empty() && self.contracts.is_empty() && self.validators.is_empty()
+    }
+}
+
+/// Handle to the watch list. Cloned handles share the same list.
+#[derive(Clone, Debug, Default)]
+pub struct WatchList {
+    entities: Arc<RwLock<WatchedEntities>>,
+}
+
+impl WatchList {
+    pub(crate) fn new(config: &WatchListConfig) -> Result<Self, Error> {
+        let watch_list = WatchList::default();
+        for (kind, entities) in [
+            (WatchedKind::Account, &config.accounts),
+            (WatchedKind::Contract, &config.contracts),
+            (WatchedKind::Validator, &config.validators),
+        ] {
+            for entity in entities {
+                watch_list.add(kind, entity).map_err(|error| {
+                    Error::msg(format!("watched {} '{}': {}", kind.label(), entity, error))
+                })?;
+            }
+        }
+        Ok(watch_list)
+    }
+
+    /// Starts watching `entity`. Returns false if it was already watched.
+    pub(crate) fn add(&self, kind: WatchedKind, entity: &str) -> Result<bool, String> {
+        let entity = kind.parse(entity)?;
+        Ok(self.write().of_kind_mut(kind).insert(entity))
+    }
+
+    /// Stops watching `entity`. Returns false if it wasn't watched.
+    pub(crate) fn remove(&self, kind: WatchedKind, entity: &str) -> Result<bool, String> {
+        let entity = kind.parse(entity)?;
+        Ok(self.write().of_kind_mut(kind).remove(&entity))
+    }
+
+    pub(crate) fn entities(&self) -> WatchedEntities {
+        self.read().clone()
+    }
+
+    /// Returns whether `data` relates to a watched entity.
+    pub(crate) fn is_watched(&self, data: &SseData) -> bool {
+        !self.matches(data).is_empty()
+    }
+
+    /// Returns whether `data` relates to a watched entity, counting the event for every watched
+    /// entity it relates to.
+    pub(crate) fn count_if_watched(&self, data: &SseData) -> bool {
+        let matches = self.matches(data);
+        for (kind, entity) in &matches {
+            WATCHED_EVENTS
+                .with_label_values(&[kind.label(), entity])
+                .inc();
+        }
+        !matches.is_empty()
+    }
+
+    /// Returns the watched entities `data` relates to.
+    fn matches(&self, data: &SseData) -> Vec<(WatchedKind, String)> {
+        let entities = self.read();
+        if entities.is_empty() {
+            return Vec::new();
+        }
+        let mut matches: Vec<(WatchedKind, String)> = Vec::new();
+        for (kind, entity) in related_entities(data) {
+            if entities.of_kind(kind).contains(&entity)
+                && !matches.contains(&(kind, entity.clone()))
+            {
+                matches.push((kind, entity));
+            }
+        }
+        matches
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, WatchedEntities> {
+        self.entities
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, WatchedEntities> {
+        self.entities
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// The entities `data` relates to, whether watched or not.
+fn related_entities(data: &SseData) -> Vec<(WatchedKind, String)> {
+    match data {
+        SseData::BlockAdded { block, .. } => {
+            vec![(WatchedKind::Validator, block.proposer().to_hex())]
+        }
+        SseData::DeployAccepted { deploy } => {
+            let mut entities = vec![(WatchedKind::Account, deploy.header().account().to_hex())];
+            entities
+                .extend(called_contracts(deploy).map(|contract| (WatchedKind::Contract, contract)));
+            entities
+        }
+        SseData::DeployProcessed {
+            account,
+            execution_result,
+            ..
+        } => {
+            let mut entities = vec![(WatchedKind::Account, account.to_hex())];
+            entities.extend(
+                touched_hashes(execution_result).map(|contract| (WatchedKind::Contract, contract)),
+            );
+            entities
+        }
+        SseData::Fault { public_key, .. } => vec![(WatchedKind::Validator, public_key.to_hex())],
+        SseData::FinalitySignature(signature) => {
+            vec![(WatchedKind::Validator, signature.public_key().to_hex())]
+        }
+        SseData::ApiVersion(_)
+        | SseData::SidecarVersion(_)
+        | SseData::DeployExpired { .. }
+        | SseData::Step { .. }
+        | SseData::Shutdown
+        | SseData::Unknown { .. }
+        | SseData::Derived { .. } => Vec::new(),
+    }
+}
+
+/// Hashes of the stored contracts and contract packages the payment and session of `deploy` call.
+fn called_contracts(deploy: &Deploy) -> impl Iterator<Item = String> + '_ {
+    vec![deploy.payment(), deploy.session()]
+        .into_iter()
+        .filter_map(|item| match item {
+            ExecutableDeployItem::StoredContractByHash { hash, .. } => {
+                Some(hex::encode(hash.value()))
+            }
+            ExecutableDeployItem::StoredVersionedContractByHash { hash, .. } => {
+                Some(hex::encode(hash.value()))
+            }
+            _ => None,
+        })
+}
+
+/// Hashes of the `hash-` keys the execution of a deploy transformed.
+fn touched_hashes(execution_result: &ExecutionResult) -> impl Iterator<Item = String> + '_ {
+    let effect = match execution_result {
+        ExecutionResult::Success { effect, .. } | ExecutionResult::Failure { effect, .. } => effect,
+    };
+    effect
+        .transforms
+        .iter()
+        .filter_map(|entry| entry.key.strip_prefix("hash-").map(str::to_string))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use casper_types::testing::TestRng;
+
+    #[test]
+    fn should_match_events_of_watched_validators() {
+        let mut rng = TestRng::new();
+        let data = SseData::random_finality_signature(&mut rng);
+        let validator = match &data {
+            SseData::FinalitySignature(signature) => signature.public_key().to_hex(),
+            _ => unreachable!(),
+        };
+        let watch_list = WatchList::default();
+        assert!(!watch_list.is_watched(&data));
+
+        assert_eq!(
+            watch_list.add(WatchedKind::Validator, &validator.to_uppercase()),
+            Ok(true)
+        );
+        assert_eq!(
+            watch_list.add(WatchedKind::Validator, &validator),
+            Ok(false)
+        );
+        assert!(watch_list.is_watched(&data));
+        assert!(!watch_list.is_watched(&SseData::random_deploy_expired(&mut rng)));
+
+        assert_eq!(
+            watch_list.remove(WatchedKind::Validator, &validator),
+            Ok(true)
+        );
+        assert!(!watch_list.is_watched(&data));
+    }
+
+    #[test]
+    fn should_match_deploys_of_watched_accounts() {
+        let mut rng = TestRng::new();
+        let (data, deploy) = SseData::random_deploy_accepted(&mut rng);
+        let watch_list = WatchList::default();
+        watch_list
+            .add(WatchedKind::Account, &deploy.header().account().to_hex())
+            .unwrap();
+
+        assert!(watch_list.count_if_watched(&data));
+        assert!(!watch_list.is_watched(&SseData::random_deploy_accepted(&mut rng).0));
+    }
+
+    #[test]
+    fn should_normalize_contract_hashes() {
+        let config = WatchListConfig {
+            accounts: Vec::new(),
+            contracts: vec![
+                format!("hash-{}", "AB".repeat(32)),
+                format!("contract-{}", "ef".repeat(32)),
+            ],
+            validators: Vec::new(),
+        };
+        let entities = WatchList::new(&config).unwrap().entities();
+        assert_eq!(
+            entities.contracts.into_iter().collect::<Vec<_>>(),
+            vec!["ab".repeat(32), "ef".repeat(32)]
+        );
+    }
+
+    #[test]
+    fn should_reject_malformed_entities() {
+        let watch_list = WatchList::default();
+
+        assert_eq!(
+            watch_list.add(WatchedKind::Contract, "hash-abcd"),
+            Err("expected a hash of 32 bytes, received 2 bytes: abcd".to_string())
+        );
+        assert!(watch_list.add(WatchedKind::Account, "abcd").is_err());
+        assert!(watch_list
+            .remove(WatchedKind::Validator, "not-a-key")
+            .is_err());
+        assert!(watch_list.entities().is_empty());
+
+        let config = WatchListConfig {
+            accounts: vec!["abcd".to_string()],
+            contracts: Vec::new(),
+            validators: Vec::new(),
+        };
+        let error = WatchList::new(&config).unwrap_err().to_string();
+        assert!(error.starts_with("watched account 'abcd': expected a hex-encoded public key"));
+    }
+}
@@ -0,0 +1,408 @@
+//! Delivery of the events broadcast by the sidecar to the webhooks registered in the config.
+//!
+//! Every webhook has a queue and a task posting the queued events to its URL one at a time, in the
+//! order they were broadcast, as the JSON they are sent to subscribers as. A failed delivery is
+//! retried with exponential backoff until the webhook responds with a success status or the
+//! attempts run out, and every attempt is recorded in the `WebhookDelivery` table. If the queue of
+//! a webhook is full, the event is dropped or waits for room depending on the priority of the
+//! external sinks in the `[delivery]` config.
+//!
+//! If the webhook has a secret, the body of every request is signed with HMAC-SHA256 and the
+//! signature sent as `X-Signature: sha256=<hex-encoded signature>`, so that receivers can
+//! authenticate the payloads.
+
+use crate::{
+    event_stream_server::sse_data_to_json,
+    types::{
+        config::WebhookConfig,
+        database::{DatabaseWriter, WebhookDeliveryAttempt},
+    },
+};
+use anyhow::{Context, Error};
+use casper_event_types::{metrics::WEBHOOK_DELIVERIES, sse_data::SseData};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::mpsc::{channel, error::TrySendError, Sender};
+use tracing::warn;
+use warp::http::header::CONTENT_TYPE;
+
+/// Header carrying the signature of the body of a request.
+const SIGNATURE_HEADER: &str = "X-Signature";
+/// Header carrying the type of the posted event.
+const EVENT_TYPE_HEADER: &str = "X-Event-Type";
+
+/// The event types which can be posted to webhooks. The versions only make sense to the
+/// subscribers of a stream, so they aren't posted.
+pub(crate) const WEBHOOK_EVENT_TYPES: [&str; 10] = [
+    "BlockAdded",
+    "DeployAccepted",
+    "DeployProcessed",
+    "DeployExpired",
+    "Fault",
+    "FinalitySignature",
+    "Step",
+    "Shutdown",
+    "Unknown",
+    "Derived",
+];
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug)]
+struct QueuedEvent {
+    event_type: &'static str,
+    payload: Arc<str>,
+}
+
+struct WebhookWorker<Db> {
+    name: String,
+    url: String,
+    maybe_secret: Option<Vec<u8>>,
+    client: reqwest::Client,
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    database: Db,
+}
+
+impl<Db: DatabaseWriter> WebhookWorker<Db> {
+    /// Posts `event` until the webhook accepts it or the attempts run out, returning the outcome
+    /// reported in the metrics.
+    async fn deliver(&self, event: &QueuedEvent) -> &'static str {
+        for attempt in 1..=self.max_attempts {
+            if attempt > 1 {
+                tokio::time::sleep(backoff(self.initial_backoff, self.max_backoff, attempt)).await;
+            }
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("Time went backwards")
+                .as_millis() as u64;
+            let (status_code, maybe_error) = self.post(event).await;
+            let delivered = maybe_error.is_none();
+            let delivery_attempt = WebhookDeliveryAttempt {
+                webhook: self.name.clone(),
+                event_type: event.event_type.to_string(),
+                attempt,
+                timestamp,
+                status_code,
+                error: maybe_error,
+            };
+            if let Err(error) = self
+                .database
+                .save_webhook_delivery_attempt(delivery_attempt)
+                .await
+            {
+                warn!(
+                    ?error,
+                    webhook = self.name,
+                    "Error saving webhook delivery attempt"
+                );
+            }
+            if delivered {
+                return "delivered";
+            }
+        }
+        warn!(
+            webhook = self.name,
+            event_type = event.event_type,
+            attempts = self.max_attempts,
+            "Giving up delivering event to webhook"
+        );
+        "failed"
+    }
+
+    /// Posts `event` once, returning the status code of the response, if any, and why the attempt
+    /// failed, if it did.
+    async fn post(&self, event: &QueuedEvent) -> (Option<u16>, Option<String>) {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header(CONTENT_TYPE, "application/json")
+            .header(EVENT_TYPE_HEADER, event.event_type)
+            .body(event.payload.to_string());
+        if let Some(secret) = &self.maybe_secret {
+            request = request.header(SIGNATURE_HEADER, signature(secret, &event.payload));
+        }
+        match request.send().await {
+            Ok(response) => {
+                let status = response.status();
+                let maybe_error = (!status.is_success())
+                    .then(|| format!("webhook responded with status {}", status));
+                (Some(status.as_u16()), maybe_error)
+            }
+            Err(error) => (None, Some(error.to_string())),
+        }
+    }
+}
+
+/// The delay before the `attempt`th attempt, doubling from `initial` with every failed attempt up
+/// to `max`.
+fn backoff(initial: Duration, max: Duration, attempt: u32) -> Duration {
+    let doublings = attempt.saturating_sub(2).min(31);
+    initial.saturating_mul(1 << doublings).min(max)
+}
+
+/// The value of the signature header of a request with the given body.
+fn signature(secret: &[u8], payload: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(payload.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[derive(Debug)]
+struct WebhookQueue {
+    name: String,
+    event_types: Vec<String>,
+    sender: Sender<QueuedEvent>,
+}
+
+impl WebhookQueue {
+    fn accepts(&self, event_type: &str) -> bool {
+        self.event_types.is_empty() || self.event_types.iter().any(|name| name == event_type)
+    }
+}
+
+/// Handle used to submit broadcast events to the configured webhooks. Cloned handles share the
+/// same worker tasks.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Webhooks {
+    queues: Arc<Vec<WebhookQueue>>,
+}
+
+impl Webhooks {
+    /// Spawns a task per webhook, recording the delivery attempts in the given database.
+    pub(crate) fn start<Db: DatabaseWriter + Clone + Send + Sync + 'static>(
+        configs: &[WebhookConfig],
+        database: Db,
+    ) -> Result<Self, Error> {
+        let mut queues = Vec::with_capacity(configs.len());
+        for config in configs {
+            let client = reqwest::Client::builder()
+                .timeout(Duration::from_millis(config.timeout_in_milliseconds()))
+                .build()
+                .with_context(|| format!("Error creating the client of webhook {}", config.name))?;
+            let worker = WebhookWorker {
+                name: config.name.clone(),
+                url: config.url.clone(),
+                maybe_secret: config
+                    .secret
+                    .as_ref()
+                    .map(|secret| secret.as_bytes().to_vec()),
+                client,
+                max_attempts: config.max_attempts(),
+                initial_backoff: Duration::from_millis(config.initial_backoff_in_milliseconds()),
+                max_backoff: Duration::from_millis(config.max_backoff_in_milliseconds()),
+                database: database.clone(),
+            };
+            let (sender, mut receiver) = channel::<QueuedEvent>(config.queue_size());
+            tokio::spawn(async move {
+                while let Some(event) = receiver.recv().await {
+                    let outcome = worker.deliver(&event).await;
+                    WEBHOOK_DELIVERIES
+                        .with_label_values(&[&worker.name, outcome])
+                        .inc();
+                }
+            });
+            queues.push(WebhookQueue {
+                name: config.name.clone(),
+                event_types: config.event_types.clone(),
+                sender,
+            });
+        }
+        Ok(Webhooks {
+            queues: Arc::new(queues),
+        })
+    }
+
+    /// Whether no webhook is registered.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.queues.is_empty()
+    }
+
+    /// Queues a broadcast event for every webhook accepting its type. Never waits for the queues
+    /// to have capacity.
+    pub(crate) fn submit(&self, sse_data: &SseData, maybe_json_data: Option<&str>) {
+        for (queue, event) in self.queued_events(sse_data, maybe_json_data) {
+            if let Err(TrySendError::Full(_)) = queue.sender.try_send(event) {
+                WEBHOOK_DELIVERIES
+                    .with_label_values(&[&queue.name, "dropped"])
+                    .inc();
+            }
+        }
+    }
+
+    /// Queues a broadcast event for every webhook accepting its type, waiting for each queue in
+    /// turn to have capacity.
+    pub(crate) async fn submit_waiting(&self, sse_data: &SseData, maybe_json_data: Option<&str>) {
+        for (queue, event) in self.queued_events(sse_data, maybe_json_data) {
+            let _ = queue.sender.send(event).await;
+        }
+    }
+
+    /// The event to queue for every webhook accepting the type of `sse_data`.
+    fn queued_events(
+        &self,
+        sse_data: &SseData,
+        maybe_json_data: Option<&str>,
+    ) -> Vec<(&WebhookQueue, QueuedEvent)> {
+        let event_type = sse_data.type_label();
+        if !WEBHOOK_EVENT_TYPES.contains(&event_type) {
+            return Vec::new();
+        }
+        let mut maybe_payload: Option<Arc<str>> = None;
+        self.queues
+            .iter()
+            .filter(|queue| queue.accepts(event_type))
+            .map(|queue| {
+                let payload = maybe_payload
+                    .get_or_insert_with(|| {
+                        sse_data_to_json(sse_data, maybe_json_data)
+                            .to_string()
+                            .into()
+                    })
+                    .clone();
+                (
+                    queue,
+                    QueuedEvent {
+                        event_type,
+                        payload,
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::fake_database::FakeDatabase, types::database::DatabaseReader};
+    use casper_types::testing::TestRng;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    };
+    use warp::{http::StatusCode, Filter};
+
+    const SECRET: &str = "very-secret";
+
+    fn build_config(url: String) -> WebhookConfig {
+        WebhookConfig {
+            name: "indexer".to_string(),
+            url,
+            event_types: vec!["BlockAdded".to_string()],
+            secret: Some(SECRET.to_string()),
+            max_attempts: Some(3),
+            initial_backoff_in_milliseconds: Some(10),
+            max_backoff_in_milliseconds: None,
+            timeout_in_milliseconds: None,
+            queue_size: None,
+        }
+    }
+
+    #[test]
+    fn backoff_should_double_up_to_max() {
+        let initial = Duration::from_millis(500);
+        let max = Duration::from_secs(3);
+
+        assert_eq!(backoff(initial, max, 2), Duration::from_millis(500));
+        assert_eq!(backoff(initial, max, 3), Duration::from_millis(1000));
+        assert_eq!(backoff(initial, max, 4), Duration::from_millis(2000));
+        assert_eq!(backoff(initial, max, 5), max);
+        assert_eq!(backoff(initial, max, u32::MAX), max);
+    }
+
+    #[test]
+    fn should_sign_payload_with_hmac_sha256() {
+        // Test case 2 of RFC 4231.
+        assert_eq!(
+            signature(b"Jefe", "what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    /// Serves a webhook on a free port answering 503 to the first request and 200 to the next
+    /// ones, which are recorded with their signature in `received`.
+    fn serve_flaky_webhook(received: Arc<Mutex<Vec<(String, String)>>>) -> u16 {
+        let request_count = Arc::new(AtomicUsize::new(0));
+        let webhook_route = warp::post()
+            .and(warp::path("hook"))
+            .and(warp::header::<String>("x-signature"))
+            .and(warp::body::bytes())
+            .map(move |signature: String, body: bytes::Bytes| {
+                if request_count.fetch_add(1, Ordering::SeqCst) == 0 {
+                    return StatusCode::SERVICE_UNAVAILABLE;
+                }
+                received
+                    .lock()
+                    .unwrap()
+                    .push((signature, String::from_utf8(body.to_vec()).unwrap()));
+                StatusCode::OK
+            });
+        let port = portpicker::pick_unused_port().expect("Unable to pick a port");
+        tokio::spawn(warp::serve(webhook_route).bind(([127, 0, 0, 1], port)));
+        port
+    }
+
+    /// Waits for `count` delivery attempts of the `indexer` webhook to be recorded.
+    async fn wait_for_delivery_attempts(
+        database: &FakeDatabase,
+        count: usize,
+    ) -> Vec<WebhookDeliveryAttempt> {
+        let mut attempts = 0;
+        loop {
+            let delivery_attempts = database.get_webhook_delivery_attempts("indexer").await;
+            match delivery_attempts {
+                Ok(delivery_attempts) if delivery_attempts.len() == count => {
+                    return delivery_attempts
+                }
+                _ if attempts < 50 => {
+                    attempts += 1;
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+                _ => panic!("Event was not delivered: {:?}", delivery_attempts),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn should_retry_until_delivered_and_record_attempts() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let port = serve_flaky_webhook(received.clone());
+
+        let database = FakeDatabase::new();
+        let webhooks = Webhooks::start(
+            &[build_config(format!("http://127.0.0.1:{}/hook", port))],
+            database.clone(),
+        )
+        .unwrap();
+        let mut test_rng = TestRng::new();
+        webhooks.submit(&SseData::random_fault(&mut test_rng), None);
+        let block_added = SseData::random_block_added(&mut test_rng);
+        webhooks.submit(&block_added, None);
+
+        let delivery_attempts = wait_for_delivery_attempts(&database, 2).await;
+        assert_eq!(delivery_attempts[0].attempt, 1);
+        assert_eq!(delivery_attempts[0].status_code, Some(503));
+        assert!(delivery_attempts[0].error.is_some());
+        assert_eq!(delivery_attempts[1].attempt, 2);
+        assert_eq!(delivery_attempts[1].status_code, Some(200));
+        assert_eq!(delivery_attempts[1].error, None);
+        assert!(delivery_attempts
+            .iter()
+            .all(|delivery_attempt| delivery_attempt.event_type == "BlockAdded"));
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        let (received_signature, body) = &received[0];
+        assert_eq!(*received_signature, signature(SECRET.as_bytes(), body));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(body).unwrap(),
+            serde_json::to_value(&block_added).unwrap()
+        );
+    }
+}
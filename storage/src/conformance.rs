@@ -0,0 +1,114 @@
+//! Storage conformance suite.
+//!
+//! Every storage backend has to behave identically from the point of view of the sidecar. This
+//! module runs all [checks] - insertion and retrieval of each event type, pagination,
+//! deduplication of events and `NotFound` semantics - against a backend, so a new implementation
+//! of [DatabaseReader] and [DatabaseWriter] can be verified with a single call from its own tests.
+//!
+//! The suite is compiled with the `conformance` feature.
+
+pub mod checks;
+
+use crate::{DatabaseReader, DatabaseWriter};
+use std::future::Future;
+
+/// Runs the whole conformance suite. `build_database` is called once per check and has to return
+/// a freshly migrated, empty database each time.
+pub async fn run_conformance_suite<DB, F, Fut>(build_database: F)
+where
+    DB: DatabaseReader + DatabaseWriter,
+    F: Fn() -> Fut,
+    Fut: Future<Output = DB>,
+{
+    run_query_checks(&build_database).await;
+    run_insertion_checks(&build_database).await;
+    run_deduplication_checks(&build_database).await;
+}
+
+/// Insertion of events and the queries of the data derived from them.
+async fn run_query_checks<DB, F, Fut>(build_database: &F)
+where
+    DB: DatabaseReader + DatabaseWriter,
+    F: Fn() -> Fut,
+    Fut: Future<Output = DB>,
+{
+    checks::should_save_and_retrieve_block_added(build_database().await).await;
+    checks::should_exclude_blocks_failing_integrity(build_database().await).await;
+    checks::should_retrieve_blocks_by_height_range(build_database().await).await;
+    checks::should_retrieve_deploys_by_timestamp_range(build_database().await).await;
+    checks::should_retrieve_accepted_deploys_by_timestamp_range(build_database().await).await;
+    checks::should_retrieve_deploys_by_error_message(build_database().await).await;
+    checks::should_retrieve_deploys_by_account(build_database().await).await;
+    checks::should_retrieve_page_of_deploys_by_account(build_database().await).await;
+    checks::should_retrieve_deploys_by_account_processed_by(build_database().await).await;
+    checks::should_save_approvals_of_later_acceptances(build_database().await).await;
+    checks::should_retrieve_deploy_event_times(build_database().await).await;
+    checks::should_retrieve_deploys_affecting_account(build_database().await).await;
+    checks::should_retrieve_native_transfers(build_database().await).await;
+    checks::should_retrieve_native_transfers_by_transfer_id(build_database().await).await;
+    checks::should_retrieve_token_transfers(build_database().await).await;
+    checks::should_clear_derived_table(build_database().await).await;
+    checks::should_retrieve_nft_events(build_database().await).await;
+    checks::should_save_network_identity_once(build_database().await).await;
+    checks::should_retrieve_contract_events(build_database().await).await;
+    checks::should_replace_contract_event_schemas(build_database().await).await;
+    checks::should_roll_up_contract_stats(build_database().await).await;
+    checks::should_compute_deploy_size_stats(build_database().await).await;
+    checks::should_retrieve_era_validators_of_switch_blocks(build_database().await).await;
+    checks::should_retrieve_raw_payloads_after_event_log_id(build_database().await).await;
+}
+
+/// Insertion and retrieval of each event type, and the `NotFound` semantics.
+async fn run_insertion_checks<DB, F, Fut>(build_database: &F)
+where
+    DB: DatabaseReader + DatabaseWriter,
+    F: Fn() -> Fut,
+    Fut: Future<Output = DB>,
+{
+    checks::should_save_and_retrieve_deploy_accepted(build_database().await).await;
+    checks::should_save_and_retrieve_deploy_processed(build_database().await).await;
+    checks::should_save_and_retrieve_deploy_expired(build_database().await).await;
+    checks::should_retrieve_deploy_aggregate_of_accepted(build_database().await).await;
+    checks::should_retrieve_deploy_aggregate_of_processed(build_database().await).await;
+    checks::should_retrieve_deploy_aggregate_of_expired(build_database().await).await;
+    checks::should_save_and_retrieve_fault(build_database().await).await;
+    checks::should_save_and_retrieve_fault_with_a_u64max(build_database().await).await;
+    checks::should_save_and_retrieve_finality_signature(build_database().await).await;
+    checks::should_exclude_invalid_finality_signatures(build_database().await).await;
+    checks::should_checkpoint_finality_signatures(build_database().await).await;
+    checks::should_save_and_retrieve_step(build_database().await).await;
+    checks::should_save_and_retrieve_a_step_with_u64_max_era(build_database().await).await;
+    checks::should_save_and_retrieve_unknown_events(build_database().await).await;
+    checks::should_save_and_retrieve_enrichments(build_database().await).await;
+    checks::should_save_and_retrieve_derived_events(build_database().await).await;
+    checks::should_save_and_retrieve_webhook_delivery_attempts(build_database().await).await;
+    checks::should_save_and_delete_api_keys(build_database().await).await;
+    checks::should_sample_latest_raw_payloads(build_database().await).await;
+    checks::should_save_and_retrieve_audit_entries(build_database().await).await;
+    checks::should_save_and_retrieve_operational_events(build_database().await).await;
+    checks::should_return_not_found_for_missing_records(build_database().await).await;
+    checks::should_prune_events_beyond_max_rows_and_age(build_database().await).await;
+    checks::should_reserve_event_ids_without_gaps(build_database().await).await;
+    checks::get_number_of_events_should_return_0(build_database().await).await;
+    checks::get_number_of_events_should_return_1_when_event_stored(build_database().await).await;
+}
+
+/// Pagination and deduplication of events.
+async fn run_deduplication_checks<DB, F, Fut>(build_database: &F)
+where
+    DB: DatabaseReader + DatabaseWriter,
+    F: Fn() -> Fut,
+    Fut: Future<Output = DB>,
+{
+    // Pagination
+    checks::should_paginate_audit_entries(build_database().await).await;
+    // Deduplication
+    checks::should_ignore_duplicate_event_id_from_source(build_database().await).await;
+    checks::should_ignore_insert_of_existing_block_added(build_database().await).await;
+    checks::should_ignore_insert_of_existing_deploy_accepted(build_database().await).await;
+    checks::should_ignore_insert_of_existing_deploy_expired(build_database().await).await;
+    checks::should_ignore_insert_of_existing_deploy_processed(build_database().await).await;
+    checks::should_ignore_insert_of_existing_fault(build_database().await).await;
+    checks::should_ignore_insert_of_existing_finality_signature(build_database().await).await;
+    checks::should_ignore_insert_of_existing_step(build_database().await).await;
+}
@@ -0,0 +1,2055 @@
+//! The checks of the conformance suite, each taking a freshly migrated, empty database.
+
+use crate::{
+    ApiKeyScope, ContractEventEntry, ContractEventField, ContractEventSchemas, DatabaseReadError,
+    DatabaseReader, DatabaseWriter, DeployErrorEntry, DeployEventTime, DeployTimestampEntry,
+    DeployTimestampKind, DerivedEvent, DerivedTable, Distribution, Enrichment, EventTypeId,
+    FinalitySignatureCheckpoint, NetworkIdentity, NftEventEntry, NftEventKind, PayloadTable,
+    StoredApiKey, TokenTransferEntry, TokenTransferKind, TransferEntry, WebhookDeliveryAttempt,
+};
+use casper_event_types::sse_events::*;
+use casper_types::{
+    account::AccountHash, testing::TestRng, AccessRights, AsymmetricType, CLType, ContractHash,
+    EraId, Key, PublicKey, SecretKey, Transfer, TransferAddr, Transform, TransformEntry, URef,
+    U256, U512,
+};
+use rand::Rng;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub async fn should_save_and_retrieve_block_added<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let mut test_rng = TestRng::new();
+    let block_added = BlockAdded::random(&mut test_rng);
+
+    db.save_block_added(block_added.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving block_added");
+
+    db.get_latest_block()
+        .await
+        .expect("Error getting latest block_added");
+
+    db.get_block_by_hash(&block_added.hex_encoded_hash())
+        .await
+        .expect("Error getting block_added by hash");
+
+    db.get_block_by_height(block_added.get_height())
+        .await
+        .expect("Error getting block_added by height");
+}
+
+pub async fn should_exclude_blocks_failing_integrity<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let mut test_rng = TestRng::new();
+    let intact = BlockAdded::random_with_height(&mut test_rng, 10);
+    let failing = BlockAdded::random_with_height(&mut test_rng, 11);
+
+    db.save_block_added(intact.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving block_added");
+    db.save_block_added_failing_integrity(failing.clone(), 2, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving block_added failing integrity");
+
+    let latest = db
+        .get_latest_block()
+        .await
+        .expect("Error getting latest block_added");
+    assert_eq!(latest.hex_encoded_hash(), intact.hex_encoded_hash());
+    assert!(matches!(
+        db.get_block_by_hash(&failing.hex_encoded_hash()).await,
+        Err(DatabaseReadError::NotFound)
+    ));
+    assert!(matches!(
+        db.get_block_by_height(failing.get_height()).await,
+        Err(DatabaseReadError::NotFound)
+    ));
+}
+
+pub async fn should_retrieve_blocks_by_height_range<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let mut test_rng = TestRng::new();
+    for (event_id, height) in [(1, 10), (2, 12), (3, 11), (4, 20)] {
+        db.save_block_added(
+            BlockAdded::random_with_height(&mut test_rng, height),
+            event_id,
+            "127.0.0.1".to_string(),
+        )
+        .await
+        .expect("Error saving block_added");
+    }
+
+    let heights = |blocks: Vec<BlockAdded>| -> Vec<u64> {
+        blocks.iter().map(BlockAdded::get_height).collect()
+    };
+    let blocks = db
+        .get_blocks_by_height_range(10, 20, 100)
+        .await
+        .expect("Error getting blocks by height range");
+    assert_eq!(heights(blocks), vec![10, 11, 12, 20]);
+    let blocks = db
+        .get_blocks_by_height_range(11, 19, 1)
+        .await
+        .expect("Error getting blocks by height range");
+    assert_eq!(heights(blocks), vec![11]);
+    let blocks = db
+        .get_blocks_by_height_range(13, 19, 100)
+        .await
+        .expect("Error getting blocks by height range");
+    assert!(blocks.is_empty());
+}
+
+/// Milliseconds since the Unix epoch.
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_millis() as u64
+}
+
+/// The deploy timestamps in `[from, to)`, read one per page.
+async fn deploy_timestamps_one_by_one<DB: DatabaseReader>(
+    db: &DB,
+    from: u64,
+    to: u64,
+) -> Vec<DeployTimestampEntry> {
+    let mut paged = Vec::new();
+    let mut after = None;
+    loop {
+        let page = db
+            .get_deploy_timestamps(from, to, after, 1)
+            .await
+            .expect("Error getting deploy timestamps");
+        match page.last() {
+            Some(entry) => after = Some(entry.clone()),
+            None => return paged,
+        }
+        paged.extend(page);
+    }
+}
+
+pub async fn should_retrieve_deploys_by_timestamp_range<DB: DatabaseReader + DatabaseWriter>(
+    db: DB,
+) {
+    let mut test_rng = TestRng::new();
+    let block_added = BlockAdded::random(&mut test_rng);
+    let block_timestamp = block_added.get_timestamp().millis();
+    let deploy_processed =
+        DeployProcessed::random(&mut test_rng, None).with_block_hash(block_added.block_hash());
+
+    db.save_block_added(block_added.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving block_added");
+    db.save_deploy_processed(deploy_processed.clone(), 2, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_processed");
+
+    let at_block_timestamp = db
+        .get_deploy_timestamps(block_timestamp, block_timestamp + 1, None, 1000)
+        .await
+        .expect("Error getting deploy timestamps");
+    assert!(at_block_timestamp
+        .iter()
+        .all(|entry| entry.timestamp == block_timestamp
+            && entry.kind == DeployTimestampKind::Processed));
+    // The deploys listed in the block are indexed along with the processed deploy.
+    assert_eq!(
+        at_block_timestamp.len(),
+        block_added.hex_encoded_deploy_hashes().len() + 1
+    );
+    assert!(at_block_timestamp
+        .iter()
+        .any(|entry| entry.deploy_hash == deploy_processed.hex_encoded_hash()));
+    assert_eq!(
+        deploy_timestamps_one_by_one(&db, block_timestamp, block_timestamp + 1).await,
+        at_block_timestamp
+    );
+
+    let empty = db
+        .get_deploy_timestamps(block_timestamp + 1, block_timestamp + 1, None, 1000)
+        .await
+        .expect("Error getting deploy timestamps");
+    assert!(empty.is_empty());
+}
+
+pub async fn should_retrieve_accepted_deploys_by_timestamp_range<
+    DB: DatabaseReader + DatabaseWriter,
+>(
+    db: DB,
+) {
+    let mut test_rng = TestRng::new();
+    let deploy_accepted = DeployAccepted::random(&mut test_rng);
+
+    let before_accepted = now_millis();
+    db.save_deploy_accepted(deploy_accepted.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_accepted");
+    let after_accepted = now_millis();
+
+    let accepted = db
+        .get_deploy_timestamps(before_accepted, after_accepted + 1, None, 1000)
+        .await
+        .expect("Error getting deploy timestamps");
+    assert!(accepted.iter().any(
+        |entry| entry.deploy_hash == deploy_accepted.hex_encoded_hash()
+            && entry.kind == DeployTimestampKind::Accepted
+    ));
+}
+
+/// Saves the processed deploys, with their index as event id.
+async fn save_deploys_processed<DB: DatabaseWriter>(db: &DB, deploys: &[DeployProcessed]) {
+    for (event_id, deploy_processed) in deploys.iter().enumerate() {
+        db.save_deploy_processed(
+            deploy_processed.clone(),
+            event_id as u32,
+            "127.0.0.1".to_string(),
+        )
+        .await
+        .expect("Error saving deploy_processed");
+    }
+}
+
+async fn deploys_by_error_message<DB: DatabaseReader>(
+    db: &DB,
+    error_message: &str,
+    after: Option<String>,
+    limit: u32,
+) -> Vec<DeployErrorEntry> {
+    db.get_deploys_by_error_message(error_message, after, limit)
+        .await
+        .expect("Error getting deploys by error message")
+}
+
+pub async fn should_retrieve_deploys_by_error_message<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let mut test_rng = TestRng::new();
+    let deploys = vec![
+        DeployProcessed::random(&mut test_rng, None).with_error_message("User error: 1"),
+        DeployProcessed::random(&mut test_rng, None).with_error_message("User error: 1"),
+        DeployProcessed::random(&mut test_rng, None).with_error_message("Out of gas error"),
+        DeployProcessed::random(&mut test_rng, None).with_error_message("User error: 1_0%"),
+    ];
+    save_deploys_processed(&db, &deploys).await;
+
+    let wildcard = &deploys[3];
+    let mut expected: Vec<String> = [&deploys[0], &deploys[1], wildcard]
+        .into_iter()
+        .map(DeployProcessed::hex_encoded_hash)
+        .collect();
+    expected.sort();
+    let found = deploys_by_error_message(&db, "User error: 1", None, 1000).await;
+    assert_eq!(
+        found
+            .iter()
+            .map(|entry| entry.deploy_hash.clone())
+            .collect::<Vec<_>>(),
+        expected
+    );
+    assert!(found
+        .iter()
+        .all(|entry| entry.error_message.starts_with("User error: 1")));
+
+    let page = deploys_by_error_message(&db, "User error: 1", Some(expected[0].clone()), 1).await;
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].deploy_hash, expected[1]);
+
+    // Wildcards of the searched text only match themselves.
+    let found = deploys_by_error_message(&db, "1_0%", None, 1000).await;
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].deploy_hash, wildcard.hex_encoded_hash());
+    assert!(deploys_by_error_message(&db, "r_e", None, 1000)
+        .await
+        .is_empty());
+}
+
+/// Saves two deploys sent by the same account and an unrelated one, returning the former sorted by
+/// hash.
+async fn save_deploys_of_one_account<DB: DatabaseWriter>(
+    db: &DB,
+    test_rng: &mut TestRng,
+) -> Vec<DeployAccepted> {
+    let secret_key = SecretKey::random(test_rng);
+    let mut sent = vec![
+        DeployAccepted::random_sent_by(test_rng, &secret_key),
+        DeployAccepted::random_sent_by(test_rng, &secret_key),
+    ];
+    let unrelated = DeployAccepted::random(test_rng);
+    for (event_id, deploy_accepted) in sent.iter().chain(vec![&unrelated]).enumerate() {
+        db.save_deploy_accepted(
+            deploy_accepted.clone(),
+            event_id as u32,
+            "127.0.0.1".to_string(),
+        )
+        .await
+        .expect("Error saving deploy_accepted");
+    }
+    sent.sort_by_key(DeployAccepted::hex_encoded_hash);
+    sent
+}
+
+pub async fn should_retrieve_deploys_by_account<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let mut test_rng = TestRng::new();
+    let sent = save_deploys_of_one_account(&db, &mut test_rng).await;
+
+    let account_hash = sent[0].hex_encoded_account_hash();
+    let expected: Vec<String> = sent.iter().map(DeployAccepted::hex_encoded_hash).collect();
+    let found = db
+        .get_deploys_by_account(&account_hash, None, None, 1000)
+        .await
+        .expect("Error getting deploys by account");
+    assert_eq!(
+        found
+            .iter()
+            .map(|entry| entry.deploy_hash.clone())
+            .collect::<Vec<_>>(),
+        expected
+    );
+    assert!(found.iter().all(|entry| entry.account_hash == account_hash));
+
+    let page = db
+        .get_deploys_by_account(&account_hash, None, Some(expected[0].clone()), 1)
+        .await
+        .expect("Error getting deploys by account");
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].deploy_hash, expected[1]);
+}
+
+pub async fn should_retrieve_page_of_deploys_by_account<DB: DatabaseReader + DatabaseWriter>(
+    db: DB,
+) {
+    let mut test_rng = TestRng::new();
+    let sent = save_deploys_of_one_account(&db, &mut test_rng).await;
+
+    let account_hash = sent[0].hex_encoded_account_hash();
+    let by_timestamp = db
+        .get_deploys_by_account_page(&account_hash, None, 0, 10)
+        .await
+        .expect("Error getting page of deploys by account");
+    let mut found_by_timestamp: Vec<String> = by_timestamp
+        .iter()
+        .map(|entry| entry.deploy_hash.clone())
+        .collect();
+    found_by_timestamp.sort();
+    assert_eq!(
+        found_by_timestamp,
+        sent.iter()
+            .map(DeployAccepted::hex_encoded_hash)
+            .collect::<Vec<_>>()
+    );
+    let second = db
+        .get_deploys_by_account_page(&account_hash, None, 1, 1)
+        .await
+        .expect("Error getting page of deploys by account");
+    assert_eq!(second, vec![by_timestamp[1].clone()]);
+}
+
+pub async fn should_retrieve_deploys_by_account_processed_by<
+    DB: DatabaseReader + DatabaseWriter,
+>(
+    db: DB,
+) {
+    let mut test_rng = TestRng::new();
+    let sent = save_deploys_of_one_account(&db, &mut test_rng).await;
+
+    let account_hash = sent[0].hex_encoded_account_hash();
+    let block_added = BlockAdded::random(&mut test_rng);
+    let deploy_processed = DeployProcessed::random(&mut test_rng, Some(sent[0].deploy_hash()))
+        .with_block_hash(block_added.block_hash());
+    let processed_at = block_added.get_timestamp().millis();
+    db.save_block_added(block_added, 10, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving block_added");
+    db.save_deploy_processed(deploy_processed, 11, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_processed");
+    let processed = db
+        .get_deploys_by_account(&account_hash, Some(processed_at), None, 1000)
+        .await
+        .expect("Error getting deploys by account");
+    assert_eq!(processed.len(), 1);
+    assert_eq!(processed[0].deploy_hash, sent[0].hex_encoded_hash());
+    let processed_page = db
+        .get_deploys_by_account_page(&account_hash, Some(processed_at), 0, 10)
+        .await
+        .expect("Error getting page of deploys by account");
+    assert_eq!(processed_page, processed);
+    let processed_earlier = db
+        .get_deploys_by_account(&account_hash, Some(processed_at - 1), None, 1000)
+        .await
+        .expect("Error getting deploys by account");
+    assert!(processed_earlier.is_empty());
+}
+
+pub async fn should_save_approvals_of_later_acceptances<DB: DatabaseReader + DatabaseWriter>(
+    db: DB,
+) {
+    let mut test_rng = TestRng::new();
+    let deploy_accepted = DeployAccepted::random(&mut test_rng);
+    let cosigner = SecretKey::random(&mut test_rng);
+    let cosigned = deploy_accepted.with_approval(&cosigner);
+    let hash = deploy_accepted.hex_encoded_hash();
+
+    db.save_deploy_accepted(deploy_accepted.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_accepted");
+    let first_approvals = db
+        .get_deploy_approvals(&hash)
+        .await
+        .expect("Error getting deploy approvals");
+    assert_eq!(
+        first_approvals
+            .iter()
+            .map(|entry| entry.signer.clone())
+            .collect::<Vec<_>>(),
+        deploy_accepted.hex_encoded_approval_signers()
+    );
+
+    let stored = db
+        .save_deploy_accepted(cosigned, 2, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_accepted");
+    assert_eq!(stored, 0);
+    let approvals = db
+        .get_deploy_approvals(&hash)
+        .await
+        .expect("Error getting deploy approvals");
+    assert_eq!(approvals.len(), 2);
+    assert_eq!(approvals[0], first_approvals[0]);
+    assert_eq!(approvals[1].signer, PublicKey::from(&cosigner).to_hex());
+    assert!(approvals[1].event_log_id > approvals[0].event_log_id);
+    assert!(approvals.iter().all(|entry| entry.deploy_hash == hash));
+}
+
+async fn deploy_event_times<DB: DatabaseReader>(db: &DB, hash: &str) -> Vec<DeployEventTime> {
+    db.get_deploy_event_times(hash)
+        .await
+        .expect("Error getting deploy event times")
+}
+
+pub async fn should_retrieve_deploy_event_times<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let mut test_rng = TestRng::new();
+    let deploy_accepted = DeployAccepted::random(&mut test_rng);
+    let hash = deploy_accepted.hex_encoded_hash();
+    let deploy_processed =
+        DeployProcessed::random(&mut test_rng, Some(deploy_accepted.deploy_hash()));
+    let other_expired = DeployExpired::random(&mut test_rng, None);
+
+    db.save_deploy_accepted(deploy_accepted, 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_accepted");
+    db.save_deploy_processed(deploy_processed, 2, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_processed");
+    db.save_deploy_expired(other_expired.clone(), 3, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_expired");
+
+    let event_times = deploy_event_times(&db, &hash).await;
+    assert_eq!(
+        event_times
+            .iter()
+            .map(|event_time| event_time.event_type_id)
+            .collect::<Vec<_>>(),
+        vec![
+            EventTypeId::DeployAccepted as u8,
+            EventTypeId::DeployProcessed as u8
+        ]
+    );
+    // Stored timestamps start with the date of the event.
+    assert!(event_times
+        .iter()
+        .all(|event_time| event_time.inserted_timestamp.as_bytes()[4] == b'-'));
+
+    let expired_times = deploy_event_times(&db, &other_expired.hex_encoded_hash()).await;
+    assert_eq!(expired_times.len(), 1);
+    assert_eq!(
+        expired_times[0].event_type_id,
+        EventTypeId::DeployExpired as u8
+    );
+
+    assert!(deploy_event_times(&db, &hex::encode([0; 32]))
+        .await
+        .is_empty());
+}
+
+/// A transform recording a native transfer of `amount` motes at the address made of `address`.
+fn native_transfer(
+    address: u8,
+    from: AccountHash,
+    to: Option<AccountHash>,
+    amount: u64,
+) -> TransformEntry {
+    TransformEntry {
+        key: Key::Transfer(TransferAddr::new([address; 32])).to_formatted_string(),
+        transform: Transform::WriteTransfer(Transfer::new(
+            casper_types::DeployHash::new([address; 32]),
+            from,
+            to,
+            URef::new([5; 32], AccessRights::READ_ADD_WRITE),
+            URef::new([6; 32], AccessRights::READ_ADD_WRITE),
+            U512::from(amount),
+            U512::zero(),
+            Some(address as u64),
+        )),
+    }
+}
+
+/// Saves a deploy transferring from `sender` to `recipient` with the transfer id 1, one
+/// transferring back to no particular account with the transfer id 2, and one without transfers,
+/// returning the first two.
+async fn save_native_transfers<DB: DatabaseWriter>(
+    db: &DB,
+    sender: AccountHash,
+    recipient: AccountHash,
+) -> (DeployProcessed, DeployProcessed) {
+    let mut test_rng = TestRng::new();
+    let paying = DeployProcessed::random(&mut test_rng, None).with_transforms(vec![
+        native_transfer(1, sender, Some(recipient), 2_500_000_000),
+        TransformEntry {
+            key: Key::Account(sender).to_formatted_string(),
+            transform: Transform::Identity,
+        },
+    ]);
+    let paying_back = DeployProcessed::random(&mut test_rng, None)
+        .with_transforms(vec![native_transfer(2, recipient, None, 1)]);
+    let unrelated = DeployProcessed::random(&mut test_rng, None).with_transforms(vec![]);
+    save_deploys_processed(db, &[paying.clone(), paying_back.clone(), unrelated]).await;
+    (paying, paying_back)
+}
+
+async fn transfers_by_account<DB: DatabaseReader>(
+    db: &DB,
+    account_hash: AccountHash,
+    after: Option<String>,
+    limit: u32,
+) -> Vec<TransferEntry> {
+    db.get_transfers_by_account(&hex::encode(account_hash.value()), after, limit)
+        .await
+        .expect("Error getting transfers by account")
+}
+
+pub async fn should_retrieve_native_transfers<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let sender = AccountHash::new([3; 32]);
+    let recipient = AccountHash::new([4; 32]);
+    let (paying, paying_back) = save_native_transfers(&db, sender, recipient).await;
+
+    let by_block = db
+        .get_transfers_by_block(&paying.hex_encoded_block_hash())
+        .await
+        .expect("Error getting transfers by block");
+    assert_eq!(by_block.len(), 1);
+    let transfer = &by_block[0];
+    assert_eq!(transfer.transfer_hash, hex::encode([1; 32]));
+    assert_eq!(transfer.deploy_hash, paying.hex_encoded_hash());
+    assert_eq!(transfer.from, hex::encode(sender.value()));
+    assert_eq!(transfer.to, Some(hex::encode(recipient.value())));
+    assert_eq!(transfer.amount, U512::from(2_500_000_000u64));
+    assert_eq!(transfer.id, Some(1));
+
+    let sent = transfers_by_account(&db, sender, None, 1000).await;
+    assert_eq!(sent, by_block);
+
+    let recipient_transfers = transfers_by_account(&db, recipient, None, 1000).await;
+    assert_eq!(
+        recipient_transfers
+            .iter()
+            .map(|transfer| transfer.deploy_hash.clone())
+            .collect::<Vec<_>>(),
+        vec![paying.hex_encoded_hash(), paying_back.hex_encoded_hash()]
+    );
+
+    let page = transfers_by_account(&db, recipient, Some(hex::encode([1; 32])), 1).await;
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].deploy_hash, paying_back.hex_encoded_hash());
+    assert_eq!(page[0].to, None);
+}
+
+pub async fn should_retrieve_native_transfers_by_transfer_id<
+    DB: DatabaseReader + DatabaseWriter,
+>(
+    db: DB,
+) {
+    let sender = AccountHash::new([3; 32]);
+    let recipient = AccountHash::new([4; 32]);
+    save_native_transfers(&db, sender, recipient).await;
+
+    let by_transfer_id = db
+        .get_transfers_by_transfer_id(2, None, 1000)
+        .await
+        .expect("Error getting transfers by transfer id");
+    assert_eq!(
+        by_transfer_id,
+        transfers_by_account(&db, recipient, Some(hex::encode([1; 32])), 1).await
+    );
+    assert_eq!(by_transfer_id.len(), 1);
+    assert_eq!(by_transfer_id[0].id, Some(2));
+    let unknown_transfer_id = db
+        .get_transfers_by_transfer_id(3, None, 1000)
+        .await
+        .expect("Error getting transfers by transfer id");
+    assert!(unknown_transfer_id.is_empty());
+}
+
+/// A transfer of the token `token_id` of the collection `contract_hash`.
+fn nft_event(contract_hash: &str, event_id: u64, token_id: &str) -> NftEventEntry {
+    NftEventEntry {
+        contract_hash: contract_hash.to_string(),
+        event_id,
+        kind: NftEventKind::Transfer,
+        token_id: token_id.to_string(),
+        owner: Some(Key::Account(AccountHash::new([3; 32])).to_formatted_string()),
+        recipient: Some(Key::Account(AccountHash::new([4; 32])).to_formatted_string()),
+        spender: None,
+        deploy_hash: hex::encode([event_id as u8; 32]),
+        block_hash: hex::encode([9; 32]),
+    }
+}
+
+pub async fn should_retrieve_nft_events<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let first = nft_event("ab", 0, "1");
+    let second = nft_event("ab", 1, "2");
+    let third = nft_event("ab", 2, "1");
+    for event in [
+        third.clone(),
+        first.clone(),
+        second.clone(),
+        nft_event("cd", 0, "1"),
+        nft_event("ab", 1, "3"),
+    ] {
+        db.save_nft_event(event)
+            .await
+            .expect("Error saving NFT event");
+    }
+
+    let collection_events = db
+        .get_nft_events("ab", None, None, 1000)
+        .await
+        .expect("Error getting NFT events");
+    assert_eq!(
+        collection_events,
+        vec![first.clone(), second, third.clone()]
+    );
+
+    let token_events = db
+        .get_nft_events("ab", Some("1"), None, 1000)
+        .await
+        .expect("Error getting NFT events");
+    assert_eq!(token_events, vec![first, third.clone()]);
+
+    let page = db
+        .get_nft_events("ab", Some("1"), Some(0), 1)
+        .await
+        .expect("Error getting NFT events");
+    assert_eq!(page, vec![third]);
+
+    let unknown = db
+        .get_nft_events("ab", Some("4"), None, 1000)
+        .await
+        .expect("Error getting NFT events");
+    assert!(unknown.is_empty());
+}
+
+pub async fn should_save_network_identity_once<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let network_identity = |chainspec_name: &str| NetworkIdentity {
+        chainspec_name: chainspec_name.to_string(),
+        starting_state_root_hash: hex::encode([7; 32]),
+    };
+    assert!(matches!(
+        db.get_network_identity().await,
+        Err(DatabaseReadError::NotFound)
+    ));
+
+    let saved = db
+        .save_network_identity(network_identity("casper"))
+        .await
+        .expect("Error saving network identity");
+    assert_eq!(saved, 1);
+    let saved = db
+        .save_network_identity(network_identity("casper-test"))
+        .await
+        .expect("Error saving network identity");
+    assert_eq!(saved, 0);
+
+    let stored = db
+        .get_network_identity()
+        .await
+        .expect("Error getting network identity");
+    assert_eq!(stored, network_identity("casper"));
+}
+
+/// An event named `event_name` emitted by the contract `contract_hash`.
+fn contract_event(contract_hash: &str, event_id: u64, event_name: &str) -> ContractEventEntry {
+    let mut fields = serde_json::Map::new();
+    fields.insert(
+        "amount".to_string(),
+        serde_json::json!(event_id.to_string()),
+    );
+    ContractEventEntry {
+        contract_hash: contract_hash.to_string(),
+        event_id,
+        event_name: event_name.to_string(),
+        fields,
+        deploy_hash: hex::encode([event_id as u8; 32]),
+        block_hash: hex::encode([9; 32]),
+    }
+}
+
+pub async fn should_retrieve_contract_events<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let first = contract_event("ab", 0, "Deposit");
+    let second = contract_event("ab", 1, "Withdrawal");
+    let third = contract_event("ab", 2, "Deposit");
+    for event in [
+        third.clone(),
+        first.clone(),
+        second.clone(),
+        contract_event("cd", 0, "Deposit"),
+        contract_event("ab", 1, "Deposit"),
+    ] {
+        db.save_contract_event(event)
+            .await
+            .expect("Error saving contract event");
+    }
+
+    let contract_events = db
+        .get_contract_events("ab", None, None, 1000)
+        .await
+        .expect("Error getting contract events");
+    assert_eq!(contract_events, vec![first.clone(), second, third.clone()]);
+
+    let named_events = db
+        .get_contract_events("ab", Some("Deposit"), None, 1000)
+        .await
+        .expect("Error getting contract events");
+    assert_eq!(named_events, vec![first, third.clone()]);
+
+    let page = db
+        .get_contract_events("ab", Some("Deposit"), Some(0), 1)
+        .await
+        .expect("Error getting contract events");
+    assert_eq!(page, vec![third]);
+
+    let unknown = db
+        .get_contract_events("ab", Some("Refund"), None, 1000)
+        .await
+        .expect("Error getting contract events");
+    assert!(unknown.is_empty());
+}
+
+pub async fn should_replace_contract_event_schemas<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let contract_event_schemas = |field_type: CLType| ContractEventSchemas {
+        contract_hash: "ab".to_string(),
+        schemas: [(
+            "Deposit".to_string(),
+            vec![
+                ContractEventField {
+                    name: "account".to_string(),
+                    cl_type: CLType::Key,
+                },
+                ContractEventField {
+                    name: "amount".to_string(),
+                    cl_type: field_type,
+                },
+            ],
+        )]
+        .into_iter()
+        .collect(),
+    };
+    assert!(matches!(
+        db.get_contract_event_schemas("ab").await,
+        Err(DatabaseReadError::NotFound)
+    ));
+
+    for field_type in [CLType::U256, CLType::Option(Box::new(CLType::U512))] {
+        db.save_contract_event_schemas(contract_event_schemas(field_type.clone()))
+            .await
+            .expect("Error saving contract event schemas");
+        let stored = db
+            .get_contract_event_schemas("ab")
+            .await
+            .expect("Error getting contract event schemas");
+        assert_eq!(stored, contract_event_schemas(field_type));
+    }
+}
+
+pub async fn should_retrieve_token_transfers<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let token_transfer = |contract_hash: &str, event_id: u64, amount: u64| TokenTransferEntry {
+        contract_hash: contract_hash.to_string(),
+        event_id,
+        kind: TokenTransferKind::Transfer,
+        from: Some(Key::Account(AccountHash::new([3; 32])).to_formatted_string()),
+        to: Some(Key::Account(AccountHash::new([4; 32])).to_formatted_string()),
+        spender: None,
+        amount: U256::from(amount),
+        deploy_hash: hex::encode([event_id as u8; 32]),
+        block_hash: hex::encode([9; 32]),
+    };
+    let first = token_transfer("ab", 0, 10);
+    let second = token_transfer("ab", 1, 20);
+    for token_transfer in [
+        second.clone(),
+        first.clone(),
+        token_transfer("cd", 0, 30),
+        token_transfer("ab", 1, 40),
+    ] {
+        db.save_token_transfer(token_transfer)
+            .await
+            .expect("Error saving token transfer");
+    }
+
+    let transfers = db
+        .get_token_transfers("ab", None, 1000)
+        .await
+        .expect("Error getting token transfers");
+    assert_eq!(transfers, vec![first, second.clone()]);
+
+    let page = db
+        .get_token_transfers("ab", Some(0), 1)
+        .await
+        .expect("Error getting token transfers");
+    assert_eq!(page, vec![second]);
+
+    let unknown = db
+        .get_token_transfers("ef", None, 1000)
+        .await
+        .expect("Error getting token transfers");
+    assert!(unknown.is_empty());
+}
+
+pub async fn should_clear_derived_table<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    for event_id in 0..2 {
+        db.save_token_transfer(TokenTransferEntry {
+            contract_hash: "ab".to_string(),
+            event_id,
+            kind: TokenTransferKind::Mint,
+            from: None,
+            to: Some(Key::Account(AccountHash::new([4; 32])).to_formatted_string()),
+            spender: None,
+            amount: U256::from(10),
+            deploy_hash: hex::encode([event_id as u8; 32]),
+            block_hash: hex::encode([9; 32]),
+        })
+        .await
+        .expect("Error saving token transfer");
+    }
+
+    let cleared = db
+        .clear_derived_table(DerivedTable::NftEvents)
+        .await
+        .expect("Error clearing NFT events");
+    assert_eq!(cleared, 0);
+    let cleared = db
+        .clear_derived_table(DerivedTable::TokenTransfers)
+        .await
+        .expect("Error clearing token transfers");
+    assert_eq!(cleared, 2);
+
+    let transfers = db
+        .get_token_transfers("ab", None, 1000)
+        .await
+        .expect("Error getting token transfers");
+    assert!(transfers.is_empty());
+}
+
+pub async fn should_retrieve_deploys_affecting_account<DB: DatabaseReader + DatabaseWriter>(
+    db: DB,
+) {
+    let mut test_rng = TestRng::new();
+    let account_hash = AccountHash::new([3; 32]);
+    let writing_account =
+        DeployProcessed::random(&mut test_rng, None).with_transforms(vec![TransformEntry {
+            key: Key::Account(account_hash).to_formatted_string(),
+            transform: Transform::Identity,
+        }]);
+    let writing_account_record =
+        DeployProcessed::random(&mut test_rng, None).with_transforms(vec![TransformEntry {
+            key: Key::Hash([5; 32]).to_formatted_string(),
+            transform: Transform::WriteAccount(account_hash),
+        }]);
+    let unrelated = DeployProcessed::random(&mut test_rng, None).with_transforms(vec![]);
+    let processed = vec![writing_account, writing_account_record, unrelated];
+    save_deploys_processed(&db, &processed).await;
+
+    let hex_encoded_account_hash = hex::encode(account_hash.value());
+    let mut expected: Vec<String> = processed[..2]
+        .iter()
+        .map(DeployProcessed::hex_encoded_hash)
+        .collect();
+    expected.sort();
+    let found = db
+        .get_deploys_affecting_account(&hex_encoded_account_hash, None, 1000)
+        .await
+        .expect("Error getting deploys affecting account");
+    assert_eq!(
+        found
+            .iter()
+            .map(|entry| entry.deploy_hash.clone())
+            .collect::<Vec<_>>(),
+        expected
+    );
+
+    let page = db
+        .get_deploys_affecting_account(&hex_encoded_account_hash, Some(expected[0].clone()), 1)
+        .await
+        .expect("Error getting deploys affecting account");
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].deploy_hash, expected[1]);
+}
+
+async fn save_accepted<DB: DatabaseWriter>(
+    db: &DB,
+    deploy_accepted: DeployAccepted,
+    event_id: u32,
+) {
+    db.save_deploy_accepted(deploy_accepted, event_id, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_accepted");
+}
+
+async fn save_processed<DB: DatabaseWriter>(
+    db: &DB,
+    deploy_processed: DeployProcessed,
+    event_id: u32,
+) {
+    db.save_deploy_processed(deploy_processed, event_id, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_processed");
+}
+
+pub async fn should_roll_up_contract_stats<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let mut test_rng = TestRng::new();
+    let contract_hash = ContractHash::new([7; 32]);
+
+    let accepted_first = DeployAccepted::random_calling_contract(&mut test_rng, contract_hash);
+    let processed_after =
+        DeployProcessed::random(&mut test_rng, Some(accepted_first.deploy_hash()))
+            .with_cost(U512::from(100));
+    let accepted_last = DeployAccepted::random_calling_contract(&mut test_rng, contract_hash);
+    let processed_before =
+        DeployProcessed::random(&mut test_rng, Some(accepted_last.deploy_hash()))
+            .with_cost(U512::from(20))
+            .with_error_message("Out of gas error");
+    let unrelated = DeployAccepted::random(&mut test_rng);
+    let unrelated_processed = DeployProcessed::random(&mut test_rng, Some(unrelated.deploy_hash()))
+        .with_cost(U512::one());
+
+    save_accepted(&db, accepted_first, 1).await;
+    save_processed(&db, processed_after.clone(), 2).await;
+    // Deploys processed before being accepted are rolled up when they are accepted.
+    save_processed(&db, processed_before.clone(), 3).await;
+    save_accepted(&db, accepted_last, 4).await;
+    save_accepted(&db, unrelated, 5).await;
+    save_processed(&db, unrelated_processed, 6).await;
+
+    let stats = db
+        .get_contract_stats(&hex::encode(contract_hash.value()))
+        .await
+        .expect("Error getting contract stats");
+    let mut expected_days = vec![
+        processed_after.timestamp_day(),
+        processed_before.timestamp_day(),
+    ];
+    expected_days.sort();
+    expected_days.dedup();
+    assert_eq!(
+        stats.iter().map(|day| day.day.clone()).collect::<Vec<_>>(),
+        expected_days
+    );
+    assert_eq!(stats.iter().map(|day| day.successes).sum::<u64>(), 1);
+    assert_eq!(stats.iter().map(|day| day.failures).sum::<u64>(), 1);
+    assert_eq!(stats.iter().map(|day| day.gas_spent).sum::<u64>(), 120);
+
+    assert!(matches!(
+        db.get_contract_stats(&hex::encode([8u8; 32])).await,
+        Err(DatabaseReadError::NotFound)
+    ));
+}
+
+pub async fn should_compute_deploy_size_stats<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let mut test_rng = TestRng::new();
+
+    assert!(matches!(
+        db.get_deploy_size_stats().await,
+        Err(DatabaseReadError::NotFound)
+    ));
+
+    let deploys: Vec<DeployAccepted> = (0..5)
+        .map(|_| DeployAccepted::random(&mut test_rng))
+        .collect();
+    for (event_id, deploy_accepted) in deploys.iter().enumerate() {
+        db.save_deploy_accepted(
+            deploy_accepted.clone(),
+            event_id as u32,
+            "127.0.0.1".to_string(),
+        )
+        .await
+        .expect("Error saving deploy_accepted");
+    }
+
+    let stats = db
+        .get_deploy_size_stats()
+        .await
+        .expect("Error getting deploy size stats");
+    let expected = |measure: fn(&DeployAccepted) -> usize| {
+        let mut values: Vec<u64> = deploys
+            .iter()
+            .map(|deploy| measure(deploy) as u64)
+            .collect();
+        values.sort_unstable();
+        Distribution::of_sorted(&values)
+    };
+    assert_eq!(stats.deploys, 5);
+    assert_eq!(
+        stats.size_bytes,
+        expected(DeployAccepted::serialized_length)
+    );
+    assert_eq!(stats.approvals, expected(DeployAccepted::approvals_count));
+    assert_eq!(stats.args, expected(DeployAccepted::args_count));
+}
+
+pub async fn should_retrieve_era_validators_of_switch_blocks<
+    DB: DatabaseReader + DatabaseWriter,
+>(
+    db: DB,
+) {
+    let mut test_rng = TestRng::new();
+    let mut validators = vec![
+        PublicKey::random(&mut test_rng),
+        PublicKey::random(&mut test_rng),
+    ];
+    let switch_block = BlockAdded::random_switch_block(&mut test_rng, 11, validators.clone());
+
+    db.save_block_added(switch_block, 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving block_added");
+
+    validators.sort_by_key(|validator| validator.to_hex());
+    let era_validators = db
+        .get_era_validators(12)
+        .await
+        .expect("Error getting era validators");
+    assert_eq!(
+        era_validators,
+        validators
+            .iter()
+            .map(|validator| validator.to_hex())
+            .collect::<Vec<_>>()
+    );
+    assert!(matches!(
+        db.get_era_validators(11).await,
+        Err(DatabaseReadError::NotFound)
+    ));
+    let validator_weights = db
+        .get_era_validator_weights(12)
+        .await
+        .expect("Error getting era validator weights");
+    assert_eq!(
+        validator_weights
+            .into_iter()
+            .map(|validator_weight| (validator_weight.public_key, validator_weight.weight))
+            .collect::<Vec<_>>(),
+        era_validators
+            .into_iter()
+            .map(|public_key| (public_key, Some("1".to_string())))
+            .collect::<Vec<_>>()
+    );
+}
+
+pub async fn should_retrieve_raw_payloads_after_event_log_id<
+    DB: DatabaseReader + DatabaseWriter,
+>(
+    db: DB,
+) {
+    let mut test_rng = TestRng::new();
+    let blocks = vec![
+        BlockAdded::random(&mut test_rng),
+        BlockAdded::random(&mut test_rng),
+    ];
+    for (event_id, block_added) in blocks.iter().enumerate() {
+        db.save_block_added(
+            block_added.clone(),
+            event_id as u32,
+            "127.0.0.1".to_string(),
+        )
+        .await
+        .expect("Error saving block_added");
+    }
+
+    let entries = db
+        .get_raw_payloads_after(PayloadTable::BlockAdded, 0, 1000)
+        .await
+        .expect("Error getting raw payloads");
+    assert_eq!(entries.len(), 2);
+    assert!(entries[0].event_log_id < entries[1].event_log_id);
+    assert_eq!(entries[1].event_id, 1);
+    assert_eq!(entries[1].event_source_address, "127.0.0.1");
+    assert_eq!(
+        serde_json::from_str::<BlockAdded>(&entries[1].raw)
+            .expect("Error parsing raw payload")
+            .hex_encoded_hash(),
+        blocks[1].hex_encoded_hash()
+    );
+    // Stored timestamps start with the date of the event.
+    assert_eq!(entries[0].inserted_timestamp.as_bytes()[4], b'-');
+
+    let after_first = db
+        .get_raw_payloads_after(PayloadTable::BlockAdded, entries[0].event_log_id, 1000)
+        .await
+        .expect("Error getting raw payloads");
+    assert_eq!(after_first, entries[1..].to_vec());
+    assert!(db
+        .get_raw_payloads_after(PayloadTable::Step, 0, 1000)
+        .await
+        .expect("Error getting raw payloads")
+        .is_empty());
+}
+
+pub async fn should_save_and_retrieve_deploy_accepted<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let mut test_rng = TestRng::new();
+
+    let deploy_accepted = DeployAccepted::random(&mut test_rng);
+
+    db.save_deploy_accepted(deploy_accepted.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_accepted");
+
+    db.get_deploy_accepted_by_hash(&deploy_accepted.hex_encoded_hash())
+        .await
+        .expect("Error getting deploy_accepted by hash");
+}
+
+pub async fn should_save_and_retrieve_deploy_processed<DB: DatabaseReader + DatabaseWriter>(
+    db: DB,
+) {
+    let mut test_rng = TestRng::new();
+    let deploy_processed = DeployProcessed::random(&mut test_rng, None);
+
+    db.save_deploy_processed(deploy_processed.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_processed");
+
+    db.get_deploy_processed_by_hash(&deploy_processed.hex_encoded_hash())
+        .await
+        .expect("Error getting deploy_processed by hash");
+}
+
+pub async fn should_save_and_retrieve_deploy_expired<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let mut test_rng = TestRng::new();
+    let deploy_expired = DeployExpired::random(&mut test_rng, None);
+
+    db.save_deploy_expired(deploy_expired.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_expired");
+
+    db.get_deploy_expired_by_hash(&deploy_expired.hex_encoded_hash())
+        .await
+        .expect("Error getting deploy_expired by hash");
+}
+
+pub async fn should_retrieve_deploy_aggregate_of_accepted<DB: DatabaseReader + DatabaseWriter>(
+    db: DB,
+) {
+    let mut test_rng = TestRng::new();
+
+    let deploy_accepted = DeployAccepted::random(&mut test_rng);
+
+    db.save_deploy_accepted(deploy_accepted.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_accepted");
+
+    db.get_deploy_aggregate_by_hash(&deploy_accepted.hex_encoded_hash())
+        .await
+        .expect("Error getting deploy aggregate by hash");
+}
+
+pub async fn should_retrieve_deploy_aggregate_of_processed<DB: DatabaseReader + DatabaseWriter>(
+    db: DB,
+) {
+    let mut test_rng = TestRng::new();
+    let deploy_accepted = DeployAccepted::random(&mut test_rng);
+    let deploy_processed =
+        DeployProcessed::random(&mut test_rng, Some(deploy_accepted.deploy_hash()));
+
+    db.save_deploy_accepted(deploy_accepted.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_accepted");
+
+    db.save_deploy_processed(deploy_processed, 2, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_processed");
+
+    db.get_deploy_aggregate_by_hash(&deploy_accepted.hex_encoded_hash())
+        .await
+        .expect("Error getting deploy aggregate by hash");
+}
+
+pub async fn should_retrieve_deploy_aggregate_of_expired<DB: DatabaseReader + DatabaseWriter>(
+    db: DB,
+) {
+    let mut test_rng = TestRng::new();
+    let deploy_accepted = DeployAccepted::random(&mut test_rng);
+    let deploy_expired = DeployExpired::random(&mut test_rng, Some(deploy_accepted.deploy_hash()));
+
+    db.save_deploy_accepted(deploy_accepted.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_accepted");
+
+    db.save_deploy_expired(deploy_expired, 2, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_expired");
+
+    db.get_deploy_aggregate_by_hash(&deploy_accepted.hex_encoded_hash())
+        .await
+        .expect("Error getting deploy aggregate by hash");
+}
+
+async fn latest_block_height<DB: DatabaseReader>(db: &DB) -> u64 {
+    db.get_latest_block()
+        .await
+        .expect("Error getting latest block_added")
+        .get_height()
+}
+
+pub async fn should_read_a_snapshot_unaffected_by_later_writes<
+    DB: DatabaseReader + DatabaseWriter,
+>(
+    db: DB,
+) {
+    let mut test_rng = TestRng::new();
+    let first_block = BlockAdded::random_with_height(&mut test_rng, 1);
+    let second_block = BlockAdded::random_with_height(&mut test_rng, 2);
+    let deploy_accepted = DeployAccepted::random(&mut test_rng);
+    let deploy_processed =
+        DeployProcessed::random(&mut test_rng, Some(deploy_accepted.deploy_hash()));
+
+    db.save_block_added(first_block, 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving block_added");
+    save_accepted(&db, deploy_accepted.clone(), 2).await;
+
+    let snapshot = db.snapshot().await.expect("Error opening a snapshot");
+    assert_eq!(latest_block_height(&snapshot).await, 1);
+
+    db.save_block_added(second_block, 3, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving block_added");
+    save_processed(&db, deploy_processed, 4).await;
+
+    assert_eq!(latest_block_height(&snapshot).await, 1);
+    let deploy_aggregate = snapshot
+        .get_deploy_aggregate_by_hash(&deploy_accepted.hex_encoded_hash())
+        .await
+        .expect("Error getting deploy aggregate by hash");
+    assert!(deploy_aggregate.deploy_processed.is_none());
+
+    assert_eq!(latest_block_height(&db).await, 2);
+    let deploy_aggregate = db
+        .get_deploy_aggregate_by_hash(&deploy_accepted.hex_encoded_hash())
+        .await
+        .expect("Error getting deploy aggregate by hash");
+    assert!(deploy_aggregate.deploy_processed.is_some());
+}
+
+pub async fn should_save_and_retrieve_fault<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let mut test_rng = TestRng::new();
+    let fault = Fault::random(&mut test_rng);
+
+    db.save_fault(fault.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving fault");
+
+    db.get_faults_by_era(fault.era_id.value())
+        .await
+        .expect("Error getting faults by era");
+
+    db.get_faults_by_public_key(&fault.public_key.to_hex())
+        .await
+        .expect("Error getting faults by public key");
+}
+
+pub async fn should_save_and_retrieve_fault_with_a_u64max<DB: DatabaseReader + DatabaseWriter>(
+    db: DB,
+) {
+    let mut test_rng = TestRng::new();
+    let mut fault = Fault::random(&mut test_rng);
+    fault.era_id = EraId::new(u64::MAX);
+
+    db.save_fault(fault.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving fault with a u64::MAX era id");
+
+    let faults = db
+        .get_faults_by_era(u64::MAX)
+        .await
+        .expect("Error getting faults by era with u64::MAX era id");
+
+    assert_eq!(faults[0].era_id.value(), u64::MAX);
+
+    let faults = db
+        .get_faults_by_public_key(&fault.public_key.to_hex())
+        .await
+        .expect("Error getting faults by public key with u64::MAX era id");
+
+    assert_eq!(faults[0].era_id.value(), u64::MAX);
+}
+
+pub async fn should_save_and_retrieve_finality_signature<DB: DatabaseReader + DatabaseWriter>(
+    db: DB,
+) {
+    let mut test_rng = TestRng::new();
+    let finality_signature = FinalitySignature::random(&mut test_rng);
+
+    db.save_finality_signature(finality_signature.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving finality_signature");
+
+    db.get_finality_signatures_by_block(&finality_signature.hex_encoded_block_hash())
+        .await
+        .expect("Error getting finality signatures by block_hash");
+}
+
+pub async fn should_exclude_invalid_finality_signatures<DB: DatabaseReader + DatabaseWriter>(
+    db: DB,
+) {
+    let mut test_rng = TestRng::new();
+    let valid = FinalitySignature::random(&mut test_rng);
+    let block_hash = *valid.inner().block_hash();
+    let invalid = FinalitySignature::new(Box::new(
+        casper_event_types::FinalitySignature::random_for_block(
+            block_hash,
+            test_rng.gen(),
+            &mut test_rng,
+        ),
+    ));
+
+    db.save_finality_signature(valid.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving finality_signature");
+    db.save_invalid_finality_signature(invalid, 2, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving invalid finality_signature");
+
+    let finality_signatures = db
+        .get_finality_signatures_by_block(&valid.hex_encoded_block_hash())
+        .await
+        .expect("Error getting finality signatures by block_hash");
+    assert_eq!(finality_signatures, vec![valid.inner()]);
+}
+
+async fn blocks_to_checkpoint<DB: DatabaseReader>(db: &DB, stored_before: u64) -> Vec<String> {
+    db.get_blocks_to_checkpoint(stored_before, None, 10)
+        .await
+        .expect("Error getting blocks to checkpoint")
+}
+
+pub async fn should_checkpoint_finality_signatures<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let mut test_rng = TestRng::new();
+    let finality_signature = FinalitySignature::random(&mut test_rng);
+    let only_invalid = FinalitySignature::random(&mut test_rng);
+    let block_hash = finality_signature.hex_encoded_block_hash();
+    db.save_finality_signature(finality_signature.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving finality_signature");
+    db.save_invalid_finality_signature(only_invalid, 2, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving invalid finality_signature");
+    let now = now_millis();
+    let a_day = 24 * 60 * 60 * 1000;
+
+    assert!(blocks_to_checkpoint(&db, now - a_day).await.is_empty());
+    assert_eq!(
+        blocks_to_checkpoint(&db, now + a_day).await,
+        vec![block_hash.clone()]
+    );
+    let checkpoint = FinalitySignatureCheckpoint {
+        block_hash: block_hash.clone(),
+        era_id: finality_signature.inner().era_id().value(),
+        signers: "01".to_string(),
+        signed_weight: U512::from(3),
+        total_weight: U512::from(4),
+    };
+    assert_eq!(
+        db.save_finality_signature_checkpoint(checkpoint.clone())
+            .await
+            .expect("Error saving checkpoint"),
+        1
+    );
+
+    assert!(matches!(
+        db.get_finality_signatures_by_block(&block_hash).await,
+        Err(DatabaseReadError::NotFound)
+    ));
+    assert_eq!(
+        db.get_finality_signature_checkpoint(&block_hash)
+            .await
+            .expect("Error getting checkpoint"),
+        checkpoint
+    );
+    assert!(blocks_to_checkpoint(&db, now + a_day).await.is_empty());
+}
+
+pub async fn should_save_and_retrieve_step<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let mut test_rng = TestRng::new();
+    let step = Step::random(&mut test_rng);
+
+    db.save_step(step.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving step");
+
+    db.get_step_by_era(step.era_id.value())
+        .await
+        .expect("Error getting step by era");
+}
+
+pub async fn should_save_and_retrieve_unknown_events<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let mut test_rng = TestRng::new();
+    let first = UnknownEvent::random(&mut test_rng);
+    let mut second = UnknownEvent::random(&mut test_rng);
+    second.event_type = first.event_type.clone();
+    let mut other = UnknownEvent::random(&mut test_rng);
+    other.event_type = format!("Other{}", first.event_type);
+
+    for (event_id, unknown_event) in [(1, &first), (2, &other), (3, &second)] {
+        db.save_unknown_event(unknown_event.clone(), event_id, "127.0.0.1".to_string())
+            .await
+            .expect("Error saving unknown event");
+    }
+
+    let retrieved = db
+        .get_unknown_events_by_type(&first.event_type)
+        .await
+        .expect("Error getting unknown events by type");
+    let as_json = |events: &[&UnknownEvent]| serde_json::to_string(events).unwrap();
+    assert_eq!(
+        as_json(&retrieved.iter().collect::<Vec<_>>()),
+        as_json(&[&first, &second])
+    );
+    assert!(matches!(
+        db.get_unknown_events_by_type("NeverSeen").await,
+        Err(DatabaseReadError::NotFound)
+    ));
+}
+
+pub async fn should_save_and_retrieve_enrichments<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let mut test_rng = TestRng::new();
+    let block_added = BlockAdded::random(&mut test_rng);
+    let block_hash = block_added.hex_encoded_hash();
+    let era_summary = Enrichment {
+        entity_hash: block_hash.clone(),
+        kind: "era_summary".to_string(),
+        value: serde_json::json!({ "era_id": 1 }),
+    };
+    let main_purse = Enrichment {
+        entity_hash: block_hash.clone(),
+        kind: "account_main_purse".to_string(),
+        value: serde_json::json!({ "main_purse": "uref-01-007" }),
+    };
+
+    for enrichment in [&era_summary, &main_purse] {
+        let rows_affected = db
+            .save_enrichment(enrichment.clone())
+            .await
+            .expect("Error saving enrichment");
+        assert_eq!(rows_affected, 1);
+    }
+    let replaced = Enrichment {
+        value: serde_json::json!({ "era_id": 2 }),
+        ..era_summary.clone()
+    };
+    let rows_affected = db
+        .save_enrichment(replaced)
+        .await
+        .expect("Error saving enrichment a second time");
+    assert_eq!(rows_affected, 0);
+
+    let retrieved = db
+        .get_enrichments_by_hash(&block_hash)
+        .await
+        .expect("Error getting enrichments by hash");
+    assert_eq!(retrieved, vec![main_purse, era_summary]);
+    assert!(matches!(
+        db.get_enrichments_by_hash("ffff").await,
+        Err(DatabaseReadError::NotFound)
+    ));
+}
+
+pub async fn should_save_and_retrieve_derived_events<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let derived_events: Vec<DerivedEvent> = (0..3)
+        .map(|index| DerivedEvent {
+            deriver: "large_transfers".to_string(),
+            source_event_type: "DeployProcessed".to_string(),
+            payload: serde_json::json!({ "index": index }),
+        })
+        .collect();
+    let other = DerivedEvent {
+        deriver: "contract_calls".to_string(),
+        source_event_type: "DeployAccepted".to_string(),
+        payload: serde_json::json!({ "entry_point": "transfer" }),
+    };
+
+    for derived_event in derived_events.iter().chain(vec![&other]) {
+        db.save_derived_event(derived_event.clone())
+            .await
+            .expect("Error saving derived event");
+    }
+
+    let retrieved = db
+        .get_derived_events_by_deriver("large_transfers")
+        .await
+        .expect("Error getting derived events");
+    assert_eq!(retrieved, derived_events);
+    assert!(matches!(
+        db.get_derived_events_by_deriver("never_configured").await,
+        Err(DatabaseReadError::NotFound)
+    ));
+}
+
+pub async fn should_save_and_retrieve_webhook_delivery_attempts<
+    DB: DatabaseReader + DatabaseWriter,
+>(
+    db: DB,
+) {
+    let failed_attempt = WebhookDeliveryAttempt {
+        webhook: "indexer".to_string(),
+        event_type: "BlockAdded".to_string(),
+        attempt: 1,
+        timestamp: 1_700_000_000_000,
+        status_code: Some(503),
+        error: Some("HTTP status server error (503 Service Unavailable)".to_string()),
+    };
+    let delivered_attempt = WebhookDeliveryAttempt {
+        attempt: 2,
+        timestamp: 1_700_000_001_000,
+        status_code: Some(200),
+        error: None,
+        ..failed_attempt.clone()
+    };
+    let other = WebhookDeliveryAttempt {
+        webhook: "alerts".to_string(),
+        event_type: "Fault".to_string(),
+        attempt: 1,
+        timestamp: 1_700_000_000_500,
+        status_code: None,
+        error: Some("connection refused".to_string()),
+    };
+
+    for delivery_attempt in [&failed_attempt, &other, &delivered_attempt] {
+        db.save_webhook_delivery_attempt(delivery_attempt.clone())
+            .await
+            .expect("Error saving webhook delivery attempt");
+    }
+
+    let retrieved = db
+        .get_webhook_delivery_attempts("indexer")
+        .await
+        .expect("Error getting webhook delivery attempts");
+    assert_eq!(retrieved, vec![failed_attempt, delivered_attempt]);
+    assert!(db
+        .get_webhook_delivery_attempts("never_configured")
+        .await
+        .expect("Error getting webhook delivery attempts")
+        .is_empty());
+}
+
+async fn api_keys<DB: DatabaseReader>(db: &DB) -> Vec<StoredApiKey> {
+    db.get_api_keys().await.expect("Error getting API keys")
+}
+
+pub async fn should_save_and_delete_api_keys<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let indexer = StoredApiKey {
+        name: "indexer".to_string(),
+        key_hash: "aa".repeat(32),
+        scopes: vec![ApiKeyScope::ReadEvents],
+        created_timestamp: 1_700_000_000_000,
+    };
+    let operator = StoredApiKey {
+        name: "operator".to_string(),
+        key_hash: "bb".repeat(32),
+        scopes: vec![ApiKeyScope::ReadEvents, ApiKeyScope::Admin],
+        created_timestamp: 1_700_000_001_000,
+    };
+
+    assert!(api_keys(&db).await.is_empty());
+    for api_key in [&operator, &indexer] {
+        db.save_api_key(api_key.clone())
+            .await
+            .expect("Error saving API key");
+    }
+    assert_eq!(api_keys(&db).await, vec![indexer.clone(), operator]);
+
+    assert_eq!(
+        db.delete_api_key("operator")
+            .await
+            .expect("Error deleting API key"),
+        1
+    );
+    assert_eq!(
+        db.delete_api_key("operator")
+            .await
+            .expect("Error deleting API key"),
+        0
+    );
+    assert_eq!(api_keys(&db).await, vec![indexer]);
+}
+
+pub async fn should_sample_latest_raw_payloads<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let mut test_rng = TestRng::new();
+    let older = DeployAccepted::random(&mut test_rng);
+    let newer = DeployAccepted::random(&mut test_rng);
+
+    db.save_deploy_accepted(older, 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_accepted");
+    db.save_deploy_accepted(newer.clone(), 2, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving deploy_accepted");
+
+    let sample = db
+        .get_raw_payload_sample(PayloadTable::DeployAccepted, 1)
+        .await
+        .expect("Error sampling raw payloads");
+    assert_eq!(sample.len(), 1);
+    let sampled =
+        serde_json::from_str::<DeployAccepted>(&sample[0]).expect("Error parsing sampled payload");
+    assert_eq!(sampled.hex_encoded_hash(), newer.hex_encoded_hash());
+    assert_eq!(
+        db.get_raw_payload_sample(PayloadTable::DeployAccepted, 10)
+            .await
+            .expect("Error sampling raw payloads")
+            .len(),
+        2
+    );
+    assert!(matches!(
+        db.get_raw_payload_sample(PayloadTable::Step, 10).await,
+        Err(DatabaseReadError::NotFound)
+    ));
+}
+
+pub async fn should_save_and_retrieve_a_step_with_u64_max_era<
+    DB: DatabaseReader + DatabaseWriter,
+>(
+    db: DB,
+) {
+    let mut test_rng = TestRng::new();
+    let mut step = Step::random(&mut test_rng);
+    step.era_id = EraId::new(u64::MAX);
+
+    db.save_step(step.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving Step with u64::MAX era id");
+
+    let retrieved_step = db
+        .get_step_by_era(u64::MAX)
+        .await
+        .expect("Error retrieving Step with u64::MAX era id");
+
+    assert_eq!(retrieved_step.era_id.value(), u64::MAX)
+}
+
+pub async fn should_ignore_duplicate_event_id_from_source<DB: DatabaseReader + DatabaseWriter>(
+    db: DB,
+) {
+    let mut test_rng = TestRng::new();
+    let event_id = test_rng.gen::<u32>();
+    let block_added = BlockAdded::random(&mut test_rng);
+
+    assert!(db
+        .save_block_added(block_added.clone(), event_id, "127.0.0.1".to_string())
+        .await
+        .is_ok());
+    let stored = db
+        .save_block_added(block_added, event_id, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving BlockAdded sent twice by the same source");
+    assert_eq!(stored, 0);
+}
+
+pub async fn should_ignore_insert_of_existing_block_added<DB: DatabaseReader + DatabaseWriter>(
+    db: DB,
+) {
+    let mut test_rng = TestRng::new();
+    let block_added = BlockAdded::random(&mut test_rng);
+
+    assert!(db
+        .save_block_added(block_added.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .is_ok());
+
+    let stored = db
+        .save_block_added(block_added, 2, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving existing BlockAdded");
+
+    assert_eq!(stored, 0);
+}
+
+pub async fn should_ignore_insert_of_existing_deploy_accepted<
+    DB: DatabaseReader + DatabaseWriter,
+>(
+    db: DB,
+) {
+    let mut test_rng = TestRng::new();
+    let deploy_accepted = DeployAccepted::random(&mut test_rng);
+
+    assert!(db
+        .save_deploy_accepted(deploy_accepted.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .is_ok());
+
+    let stored = db
+        .save_deploy_accepted(deploy_accepted, 2, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving existing DeployAccepted");
+
+    assert_eq!(stored, 0);
+}
+
+pub async fn should_ignore_insert_of_existing_deploy_expired<
+    DB: DatabaseReader + DatabaseWriter,
+>(
+    db: DB,
+) {
+    let mut test_rng = TestRng::new();
+    let deploy_expired = DeployExpired::random(&mut test_rng, None);
+
+    assert!(db
+        .save_deploy_expired(deploy_expired.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .is_ok());
+
+    let stored = db
+        .save_deploy_expired(deploy_expired, 2, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving existing DeployExpired");
+
+    assert_eq!(stored, 0);
+}
+
+pub async fn should_ignore_insert_of_existing_deploy_processed<
+    DB: DatabaseReader + DatabaseWriter,
+>(
+    db: DB,
+) {
+    let mut test_rng = TestRng::new();
+    let deploy_processed = DeployProcessed::random(&mut test_rng, None);
+
+    assert!(db
+        .save_deploy_processed(deploy_processed.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .is_ok());
+
+    let stored = db
+        .save_deploy_processed(deploy_processed, 2, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving existing DeployProcessed");
+
+    assert_eq!(stored, 0);
+}
+
+pub async fn should_ignore_insert_of_existing_fault<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let mut test_rng = TestRng::new();
+    let fault = Fault::random(&mut test_rng);
+
+    assert!(db
+        .save_fault(fault.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .is_ok());
+
+    let stored = db
+        .save_fault(fault, 2, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving existing Fault");
+
+    assert_eq!(stored, 0);
+}
+
+pub async fn should_ignore_insert_of_existing_finality_signature<
+    DB: DatabaseReader + DatabaseWriter,
+>(
+    db: DB,
+) {
+    let mut test_rng = TestRng::new();
+    let finality_signature = FinalitySignature::random(&mut test_rng);
+
+    assert!(db
+        .save_finality_signature(finality_signature.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .is_ok());
+
+    let stored = db
+        .save_finality_signature(finality_signature, 2, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving existing FinalitySignature");
+
+    assert_eq!(stored, 0);
+}
+
+pub async fn should_ignore_insert_of_existing_step<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let mut test_rng = TestRng::new();
+    let step = Step::random(&mut test_rng);
+
+    assert!(db
+        .save_step(step.clone(), 1, "127.0.0.1".to_string())
+        .await
+        .is_ok());
+
+    let stored = db
+        .save_step(step, 2, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving existing Step");
+
+    assert_eq!(stored, 0);
+}
+
+pub async fn get_number_of_events_should_return_0<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    assert_eq!(db.get_number_of_events().await.unwrap(), 0);
+}
+
+pub async fn get_number_of_events_should_return_1_when_event_stored<
+    DB: DatabaseReader + DatabaseWriter,
+>(
+    db: DB,
+) {
+    let mut test_rng = TestRng::new();
+    let fault = Fault::random(&mut test_rng);
+
+    assert!(db
+        .save_fault(fault, 1, "127.0.0.1".to_string())
+        .await
+        .is_ok());
+    assert_eq!(db.get_number_of_events().await.unwrap(), 1);
+}
+
+async fn prune<DB: DatabaseWriter>(
+    db: &DB,
+    table: PayloadTable,
+    maybe_stored_before: Option<u64>,
+    maybe_max_rows: Option<u64>,
+) -> u64 {
+    db.prune_events(table, maybe_stored_before, maybe_max_rows)
+        .await
+        .expect("Error pruning events")
+}
+
+pub async fn should_prune_events_beyond_max_rows_and_age<DB: DatabaseReader + DatabaseWriter>(
+    db: DB,
+) {
+    let mut test_rng = TestRng::new();
+    let blocks: Vec<BlockAdded> = (0..3)
+        .map(|height| BlockAdded::random_with_height(&mut test_rng, height))
+        .collect();
+    for (index, block_added) in blocks.iter().enumerate() {
+        db.save_block_added(
+            block_added.clone(),
+            index as u32 + 1,
+            "127.0.0.1".to_string(),
+        )
+        .await
+        .expect("Error saving block_added");
+    }
+    let fault = Fault::random(&mut test_rng);
+    db.save_fault(fault.clone(), 4, "127.0.0.1".to_string())
+        .await
+        .expect("Error saving fault");
+
+    assert_eq!(prune(&db, PayloadTable::BlockAdded, None, Some(1)).await, 2);
+    assert!(matches!(
+        db.get_block_by_hash(&blocks[1].hex_encoded_hash()).await,
+        Err(DatabaseReadError::NotFound)
+    ));
+    db.get_block_by_hash(&blocks[2].hex_encoded_hash())
+        .await
+        .expect("Error getting the newest block_added");
+    // The event log entries of the pruned events are kept.
+    assert_eq!(db.get_number_of_events().await.unwrap(), 4);
+
+    let now = now_millis();
+    let a_day = 24 * 60 * 60 * 1000;
+    assert_eq!(
+        prune(&db, PayloadTable::Fault, Some(now - a_day), None).await,
+        0
+    );
+    assert_eq!(
+        prune(&db, PayloadTable::Fault, Some(now + a_day), None).await,
+        1
+    );
+    assert!(matches!(
+        db.get_faults_by_era(fault.era_id.value()).await,
+        Err(DatabaseReadError::NotFound)
+    ));
+}
+
+pub async fn should_reserve_event_ids_without_gaps<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    assert!(matches!(
+        db.get_event_id_allocator_state().await,
+        Err(DatabaseReadError::NotFound)
+    ));
+    let next_event_id = db
+        .initialize_event_id_allocator(u32::MAX - 1)
+        .await
+        .expect("Error initializing event id allocator");
+    assert_eq!(next_event_id, u32::MAX - 1);
+    // An existing allocator state is never overwritten.
+    let next_event_id = db
+        .initialize_event_id_allocator(5)
+        .await
+        .expect("Error initializing event id allocator");
+    assert_eq!(next_event_id, u32::MAX - 1);
+
+    let mut reserved = Vec::new();
+    for count in [1, 3, 2] {
+        reserved.push(
+            db.reserve_event_ids(count)
+                .await
+                .expect("Error reserving event ids"),
+        );
+    }
+
+    assert_eq!(reserved, vec![u32::MAX - 1, u32::MAX, 2]);
+    let state = db
+        .get_event_id_allocator_state()
+        .await
+        .expect("Error getting event id allocator state");
+    assert_eq!(state.next_event_id, 4);
+}
+
+pub async fn should_save_and_retrieve_audit_entries<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let first_id = db
+        .save_audit_entry(
+            "127.0.0.1".to_string(),
+            "admin_api_call".to_string(),
+            Some("GET /metrics 200".to_string()),
+        )
+        .await
+        .expect("Error saving audit entry");
+    let second_id = db
+        .save_audit_entry("sidecar".to_string(), "config_loaded".to_string(), None)
+        .await
+        .expect("Error saving audit entry");
+
+    let entries = db
+        .get_audit_entries(first_id, 10)
+        .await
+        .expect("Error getting audit entries");
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].id, first_id);
+    assert_eq!(entries[0].actor, "127.0.0.1");
+    assert_eq!(entries[0].details, Some("GET /metrics 200".to_string()));
+    assert_eq!(entries[1].id, second_id);
+    assert_eq!(entries[1].action, "config_loaded");
+    assert_eq!(entries[1].details, None);
+
+    let entries = db
+        .get_audit_entries(second_id, 10)
+        .await
+        .expect("Error getting audit entries");
+    assert_eq!(entries.len(), 1);
+    assert!(db
+        .get_audit_entries(second_id + 1, 10)
+        .await
+        .expect("Error getting audit entries")
+        .is_empty());
+}
+
+pub async fn should_paginate_audit_entries<DB: DatabaseReader + DatabaseWriter>(db: DB) {
+    let mut ids = Vec::new();
+    for index in 0..5 {
+        let id = db
+            .save_audit_entry(
+                "127.0.0.1".to_string(),
+                "rest_query".to_string(),
+                Some(format!("GET /block/{} 200", index)),
+            )
+            .await
+            .expect("Error saving audit entry");
+        ids.push(id);
+    }
+
+    let first_page = db
+        .get_audit_entries(ids[0], 2)
+        .await
+        .expect("Error getting audit entries");
+    let second_page = db
+        .get_audit_entries(first_page[1].id + 1, 2)
+        .await
+        .expect("Error getting audit entries");
+    let last_page = db
+        .get_audit_entries(second_page[1].id + 1, 2)
+        .await
+        .expect("Error getting audit entries");
+
+    let paginated_ids: Vec<u64> = first_page
+        .iter()
+        .chain(second_page.iter())
+        .chain(last_page.iter())
+        .map(|entry| entry.id)
+        .collect();
+    assert_eq!(paginated_ids, ids);
+}
+
+pub async fn should_save_and_retrieve_operational_events<DB: DatabaseReader + DatabaseWriter>(
+    db: DB,
+) {
+    let first_id = db
+        .save_operational_event("started".to_string(), None)
+        .await
+        .expect("Error saving operational event");
+    let second_id = db
+        .save_operational_event(
+            "failover".to_string(),
+            Some("127.0.0.1:18101 is now preferred".to_string()),
+        )
+        .await
+        .expect("Error saving operational event");
+
+    let events = db
+        .get_operational_events(first_id, 10)
+        .await
+        .expect("Error getting operational events");
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].id, first_id);
+    assert_eq!(events[0].kind, "started");
+    assert_eq!(events[0].details, None);
+    assert_eq!(events[1].id, second_id);
+    assert_eq!(events[1].kind, "failover");
+    assert_eq!(
+        events[1].details,
+        Some("127.0.0.1:18101 is now preferred".to_string())
+    );
+
+    let events = db
+        .get_operational_events(second_id, 1)
+        .await
+        .expect("Error getting operational events");
+    assert_eq!(events.len(), 1);
+    assert!(db
+        .get_operational_events(second_id + 1, 10)
+        .await
+        .expect("Error getting operational events")
+        .is_empty());
+}
+
+pub async fn should_return_not_found_for_missing_records<DB: DatabaseReader + DatabaseWriter>(
+    db: DB,
+) {
+    let mut test_rng = TestRng::new();
+    let block_added = BlockAdded::random(&mut test_rng);
+    let deploy_accepted = DeployAccepted::random(&mut test_rng);
+
+    assert!(matches!(
+        db.get_block_by_hash(&block_added.hex_encoded_hash()).await,
+        Err(DatabaseReadError::NotFound)
+    ));
+    assert!(matches!(
+        db.get_block_by_height(block_added.get_height()).await,
+        Err(DatabaseReadError::NotFound)
+    ));
+    assert!(matches!(
+        db.get_deploy_aggregate_by_hash(&deploy_accepted.hex_encoded_hash())
+            .await,
+        Err(DatabaseReadError::NotFound)
+    ));
+    assert!(matches!(
+        db.get_step_by_era(u64::MAX).await,
+        Err(DatabaseReadError::NotFound)
+    ));
+    assert!(matches!(
+        db.get_faults_by_era(u64::MAX).await,
+        Err(DatabaseReadError::NotFound)
+    ));
+}
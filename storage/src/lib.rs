@@ -0,0 +1,1374 @@
+#![deny(clippy::complexity)]
+#![deny(clippy::cognitive_complexity)]
+#![deny(clippy::too_many_lines)]
+
+//! The storage interface of the Casper event sidecar: the [DatabaseWriter] and [DatabaseReader]
+//! traits every storage backend implements, along with the types of the stored rows.
+//!
+//! Backends other than the SQLite and PostgreSQL ones of the sidecar can be checked against the
+//! same conformance suite as those, the `conformance` module, which is compiled with the
+//! `conformance` feature.
+
+#[cfg(feature = "conformance")]
+pub mod conformance;
+
+use anyhow::Error;
+use async_trait::async_trait;
+use casper_event_types::{
+    metrics::DB_POOL_TIMEOUTS,
+    sse_events::{
+        BlockAdded, DeployAccepted, DeployExpired, DeployProcessed, Fault, FinalitySignature, Step,
+        UnknownEvent,
+    },
+    FinalitySignature as FinSig,
+};
+use casper_types::{CLType, U256, U512};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+
+/// Ids of the types of the stored events.
+pub enum EventTypeId {
+    BlockAdded = 1,
+    DeployAccepted = 2,
+    DeployExpired = 3,
+    DeployProcessed = 4,
+    Fault = 5,
+    FinalitySignature = 6,
+    Step = 7,
+    Shutdown = 8,
+    Unknown = 9,
+}
+
+/// What an API key gives access to.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKeyScope {
+    /// The REST server and the event streams.
+    ReadEvents,
+    /// The admin server.
+    Admin,
+}
+
+impl ApiKeyScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiKeyScope::ReadEvents => "read_events",
+            ApiKeyScope::Admin => "admin",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "read_events" => Some(ApiKeyScope::ReadEvents),
+            "admin" => Some(ApiKeyScope::Admin),
+            _ => None,
+        }
+    }
+}
+
+/// Describes a reference for the writing interface of an 'Event Store' database.
+/// There is a one-to-one relationship between each method and each event that can be received from the node.
+/// Each method takes the `data` and `id` fields as well as the source IP address (useful for tying the node-specific `id` to the relevant node).
+///
+/// Saving an event is idempotent: an event which is already stored, e.g. because it was received
+/// from another node or replayed, is left as is and the method returns `Ok(0)` rather than failing,
+/// while a newly stored event returns `Ok(1)`. Events are identified by their natural keys: the
+/// hash of a block, the hash of a deploy for each deploy event, the block and signer of a finality
+/// signature, the era and validator of a fault and the era of a step.
+///
+/// For a reference implementation using Sqlite see the `SqliteDatabase` of the sidecar.
+#[async_trait]
+pub trait DatabaseWriter {
+    /// Save a BlockAdded event to the database.
+    ///
+    /// * `block_added`: the [BlockAdded] from the `data` field.
+    /// * `event_id`: the node-specific assigned `id`.
+    /// * `event_source_address`: the IP address of the source node.
+    async fn save_block_added(
+        &self,
+        block_added: BlockAdded,
+        event_id: u32,
+        event_source_address: String,
+    ) -> Result<u64, DatabaseWriteError>;
+    /// Save a BlockAdded event whose hashes don't match the contents of the block. The block is
+    /// stored marked as failing integrity verification and not returned by queries of blocks.
+    ///
+    /// * `block_added`: the [BlockAdded] from the `data` field.
+    /// * `event_id`: the node-specific assigned `id`.
+    /// * `event_source_address`: the IP address of the source node.
+    async fn save_block_added_failing_integrity(
+        &self,
+        block_added: BlockAdded,
+        event_id: u32,
+        event_source_address: String,
+    ) -> Result<u64, DatabaseWriteError>;
+    /// Save a DeployAccepted event to the database.
+    ///
+    /// * `deploy_accepted`: the [DeployAccepted] from the `data` field.
+    /// * `event_id`: the node-specific assigned `id`.
+    /// * `event_source_address`: the IP address of the source node.
+    async fn save_deploy_accepted(
+        &self,
+        deploy_accepted: DeployAccepted,
+        event_id: u32,
+        event_source_address: String,
+    ) -> Result<u64, DatabaseWriteError>;
+    /// Save a DeployProcessed event to the database.
+    ///
+    /// * `deploy_accepted`: the [DeployProcessed] from the `data` field.
+    /// * `event_id`: the node-specific assigned `id`.
+    /// * `event_source_address`: the IP address of the source node.
+    async fn save_deploy_processed(
+        &self,
+        deploy_processed: DeployProcessed,
+        event_id: u32,
+        event_source_address: String,
+    ) -> Result<u64, DatabaseWriteError>;
+    /// Save a DeployExpired event to the database.
+    ///
+    /// * `deploy_expired`: the [DeployExpired] from the `data` field.
+    /// * `event_id`: the node-specific assigned `id`.
+    /// * `event_source_address`: the IP address of the source node.
+    async fn save_deploy_expired(
+        &self,
+        deploy_expired: DeployExpired,
+        event_id: u32,
+        event_source_address: String,
+    ) -> Result<u64, DatabaseWriteError>;
+    /// Save a Fault event to the database.
+    ///
+    /// * `fault`: the [Fault] from the `data` field.
+    /// * `event_id`: the node-specific assigned `id`.
+    /// * `event_source_address`: the IP address of the source node.
+    async fn save_fault(
+        &self,
+        fault: Fault,
+        event_id: u32,
+        event_source_address: String,
+    ) -> Result<u64, DatabaseWriteError>;
+    /// Save a FinalitySignature event to the database.
+    ///
+    /// * `finality_signature`: the [FinalitySignature] from the `data` field.
+    /// * `event_id`: the node-specific assigned `id`.
+    /// * `event_source_address`: the IP address of the source node.
+    async fn save_finality_signature(
+        &self,
+        finality_signature: FinalitySignature,
+        event_id: u32,
+        event_source_address: String,
+    ) -> Result<u64, DatabaseWriteError>;
+    /// Save a FinalitySignature event which failed cryptographic verification. The signature is
+    /// stored marked as invalid and not returned when querying the signatures of its block.
+    ///
+    /// * `finality_signature`: the [FinalitySignature] from the `data` field.
+    /// * `event_id`: the node-specific assigned `id`.
+    /// * `event_source_address`: the IP address of the source node.
+    async fn save_invalid_finality_signature(
+        &self,
+        finality_signature: FinalitySignature,
+        event_id: u32,
+        event_source_address: String,
+    ) -> Result<u64, DatabaseWriteError>;
+    /// Save a Step event to the database.
+    ///
+    /// * `step`: the [Step] from the `data` field.
+    /// * `event_id`: the node-specific assigned `id`.
+    /// * `event_source_address`: the IP address of the source node.
+    async fn save_step(
+        &self,
+        step: Step,
+        event_id: u32,
+        event_source_address: String,
+    ) -> Result<u64, DatabaseWriteError>;
+
+    /// Save an event of a type unknown to the sidecar to the database.
+    ///
+    /// * `unknown_event`: the [UnknownEvent] built from the `data` field.
+    /// * `event_id`: the node-specific assigned `id`.
+    /// * `event_source_address`: the IP address of the source node.
+    async fn save_unknown_event(
+        &self,
+        unknown_event: UnknownEvent,
+        event_id: u32,
+        event_source_address: String,
+    ) -> Result<u64, DatabaseWriteError>;
+
+    /// Save context fetched from a node's RPC server for a stored block or deploy. An enrichment
+    /// of the same kind already stored for the entity is left unchanged.
+    ///
+    /// * `enrichment`: the [Enrichment] to store.
+    async fn save_enrichment(&self, enrichment: Enrichment) -> Result<u64, DatabaseWriteError>;
+
+    /// Save an event emitted by one of the configured event derivers.
+    ///
+    /// * `derived_event`: the [DerivedEvent] to store.
+    async fn save_derived_event(
+        &self,
+        derived_event: DerivedEvent,
+    ) -> Result<u64, DatabaseWriteError>;
+
+    /// Save a transfer of a CEP-18 token. A transfer already stored for the event of the contract
+    /// is left unchanged.
+    ///
+    /// * `token_transfer`: the [TokenTransferEntry] to store.
+    async fn save_token_transfer(
+        &self,
+        token_transfer: TokenTransferEntry,
+    ) -> Result<u64, DatabaseWriteError>;
+
+    /// Save an event of a CEP-78 NFT collection. An event already stored for the event id of the
+    /// contract is left unchanged.
+    ///
+    /// * `nft_event`: the [NftEventEntry] to store.
+    async fn save_nft_event(&self, nft_event: NftEventEntry) -> Result<u64, DatabaseWriteError>;
+
+    /// Save the schemas of the events of a contract, replacing any previously saved for the
+    /// contract.
+    ///
+    /// * `contract_event_schemas`: the [ContractEventSchemas] to store.
+    async fn save_contract_event_schemas(
+        &self,
+        contract_event_schemas: ContractEventSchemas,
+    ) -> Result<u64, DatabaseWriteError>;
+
+    /// Save an event of a contract following the Casper Event Standard. An event already stored
+    /// for the event id of the contract is left unchanged.
+    ///
+    /// * `contract_event`: the [ContractEventEntry] to store.
+    async fn save_contract_event(
+        &self,
+        contract_event: ContractEventEntry,
+    ) -> Result<u64, DatabaseWriteError>;
+
+    /// Save a native transfer recorded by the execution of a processed deploy. A transfer already
+    /// stored for the address of the transfer is left unchanged.
+    ///
+    /// * `transfer`: the [TransferEntry] to store.
+    async fn save_transfer(&self, transfer: TransferEntry) -> Result<u64, DatabaseWriteError>;
+
+    /// Deletes all the rows of a table derived from the stored processed deploys, so that it can be
+    /// rebuilt. Returns the number of deleted rows.
+    async fn clear_derived_table(&self, table: DerivedTable) -> Result<u64, DatabaseWriteError>;
+
+    /// Save the network the stored events are received from, unless a network is already saved.
+    /// Returns 1 if the network was saved, 0 otherwise.
+    ///
+    /// * `network_identity`: the [NetworkIdentity] reported by the connected nodes.
+    async fn save_network_identity(
+        &self,
+        network_identity: NetworkIdentity,
+    ) -> Result<u64, DatabaseWriteError>;
+
+    /// Save the checkpoint of the finality signatures of a block, replacing any previous one, and
+    /// delete the signatures of the block. Returns the number of signatures deleted.
+    ///
+    /// * `checkpoint`: the [FinalitySignatureCheckpoint] to store.
+    async fn save_finality_signature_checkpoint(
+        &self,
+        checkpoint: FinalitySignatureCheckpoint,
+    ) -> Result<u64, DatabaseWriteError>;
+
+    /// Save an attempt at delivering an event to one of the configured webhooks.
+    ///
+    /// * `delivery_attempt`: the [WebhookDeliveryAttempt] to store.
+    async fn save_webhook_delivery_attempt(
+        &self,
+        delivery_attempt: WebhookDeliveryAttempt,
+    ) -> Result<u64, DatabaseWriteError>;
+
+    /// Save an API key created through the admin server.
+    ///
+    /// * `api_key`: the [StoredApiKey] to store.
+    async fn save_api_key(&self, api_key: StoredApiKey) -> Result<u64, DatabaseWriteError>;
+
+    /// Delete the API key of the given name. Returns 1 if the key was deleted, 0 if there was no
+    /// such key.
+    ///
+    /// * `name`: name of the key.
+    async fn delete_api_key(&self, name: &str) -> Result<u64, DatabaseWriteError>;
+
+    // Save data about shutdown to the database
+    async fn save_shutdown(
+        &self,
+        event_id: u32,
+        event_source_address: String,
+    ) -> Result<u64, DatabaseWriteError>;
+
+    /// Appends an entry to the audit log. Entries are never updated or deleted.
+    ///
+    /// * `actor`: who performed the action, e.g. the remote address or the token subject.
+    /// * `action`: the kind of action performed.
+    /// * `details`: free-form details of the action, e.g. the requested path.
+    async fn save_audit_entry(
+        &self,
+        actor: String,
+        action: String,
+        details: Option<String>,
+    ) -> Result<u64, DatabaseWriteError>;
+
+    /// Appends an event of the sidecar itself to its operational history.
+    ///
+    /// * `kind`: what the sidecar did, e.g. `started`.
+    /// * `details`: free-form details of the event, e.g. the node which became preferred.
+    async fn save_operational_event(
+        &self,
+        kind: String,
+        details: Option<String>,
+    ) -> Result<u64, DatabaseWriteError>;
+
+    /// Deletes the events of `table` stored before `maybe_stored_before`, in milliseconds since the
+    /// UNIX epoch, as well as those beyond the newest `maybe_max_rows`. The event log entries of
+    /// the deleted events are kept, so the event ids allocated for the outbound stream are never
+    /// reused. Returns the number of deleted events.
+    async fn prune_events(
+        &self,
+        table: PayloadTable,
+        maybe_stored_before: Option<u64>,
+        maybe_max_rows: Option<u64>,
+    ) -> Result<u64, DatabaseWriteError>;
+
+    /// Creates the state of the allocator of outbound event ids, starting at `next_event_id`. If
+    /// the state already exists it is left unchanged. Returns the id which will be allocated next.
+    async fn initialize_event_id_allocator(
+        &self,
+        next_event_id: u32,
+    ) -> Result<u32, DatabaseWriteError>;
+
+    /// Reserves the next `count` outbound event ids and returns the first of them. The reservation
+    /// is persisted before it is returned so ids are never reused across restarts, while those
+    /// reserved but not used before a crash are skipped.
+    async fn reserve_event_ids(&self, count: u32) -> Result<u32, DatabaseWriteError>;
+}
+
+#[derive(Debug)]
+pub struct UniqueConstraintError {
+    pub table: String,
+    pub error: sqlx::Error,
+}
+
+/// The database failed to insert a record(s).
+#[derive(Debug)]
+pub enum DatabaseWriteError {
+    /// The insert failed prior to execution because the data could not be serialised.
+    Serialisation(serde_json::Error),
+    /// The insert failed prior to execution because the SQL could not be constructed.
+    SqlConstruction(sea_query::error::Error),
+    /// The insert was rejected by the database because it would break a unique constraint.
+    UniqueConstraint(UniqueConstraintError),
+    /// The insert was rejected by the database.
+    Database(sqlx::Error),
+    /// An error occurred somewhere unexpected.
+    Unhandled(anyhow::Error),
+}
+
+impl ToString for DatabaseWriteError {
+    fn to_string(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+impl From<serde_json::Error> for DatabaseWriteError {
+    fn from(serde_err: serde_json::Error) -> Self {
+        Self::Serialisation(serde_err)
+    }
+}
+
+impl From<sea_query::error::Error> for DatabaseWriteError {
+    fn from(sea_query_err: sea_query::error::Error) -> Self {
+        Self::SqlConstruction(sea_query_err)
+    }
+}
+
+impl From<sqlx::Error> for DatabaseWriteError {
+    fn from(sqlx_err: sqlx::Error) -> Self {
+        if let sqlx::Error::PoolTimedOut = sqlx_err {
+            DB_POOL_TIMEOUTS.inc();
+        }
+        if let Some(db_err) = sqlx_err.as_database_error() {
+            if let Some(code) = db_err.code() {
+                match code.as_ref() {
+                    "23505" => {
+                        //"23505" is postgresql unique constraint violation violation
+                        let table = db_err
+                            .table()
+                            .map(|table_name| table_name.to_string())
+                            .unwrap_or(db_err.message().to_string());
+                        return Self::UniqueConstraint(UniqueConstraintError {
+                            table,
+                            error: sqlx_err,
+                        });
+                    }
+                    "1555" | "2067" => {
+                        // The message looks something like this:
+                        // UNIQUE constraint failed: DeployProcessed.deploy_hash
+
+                        let table = db_err.message().split(':').collect::<Vec<&str>>()[1]
+                            .split('.')
+                            .collect::<Vec<&str>>()[0]
+                            .trim()
+                            .to_string();
+                        return Self::UniqueConstraint(UniqueConstraintError {
+                            table,
+                            error: sqlx_err,
+                        });
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Self::Database(sqlx_err)
+    }
+}
+
+impl From<anyhow::Error> for DatabaseWriteError {
+    fn from(anyhow_err: anyhow::Error) -> Self {
+        Self::Unhandled(anyhow_err)
+    }
+}
+
+/// Describes a reference for the reading interface of an 'Event Store' database.
+///
+/// For a reference implementation using Sqlite see the `SqliteDatabase` of the sidecar.
+#[async_trait]
+pub trait DatabaseReader {
+    /// A read-only snapshot of the database, see [DatabaseReader::snapshot].
+    type Snapshot: DatabaseReader + Clone + Send + Sync + 'static;
+    /// Returns a read-only snapshot of the database. All the reads of a snapshot observe the same
+    /// committed state, so they never see a batch of rows written by a single event partially,
+    /// e.g. a block without its deploys, nor the effects of the events saved after their first
+    /// read. A snapshot holds a connection of the pool until its last clone is dropped, and a
+    /// snapshot of a snapshot is the snapshot itself.
+    async fn snapshot(&self) -> Result<Self::Snapshot, DatabaseReadError>;
+    /// Returns the latest [BlockAdded] by height from the database.
+    async fn get_latest_block(&self) -> Result<BlockAdded, DatabaseReadError>;
+    /// Returns the [BlockAdded] corresponding to the provided `height`.
+    ///
+    /// * `height` - Height of the block which should be retrieved
+    async fn get_block_by_height(&self, height: u64) -> Result<BlockAdded, DatabaseReadError>;
+    /// Returns the [BlockAdded] corresponding to the provided hex-encoded `hash`.
+    ///
+    /// * `hash` - hash which identifies the block
+    async fn get_block_by_hash(&self, hash: &str) -> Result<BlockAdded, DatabaseReadError>;
+    /// Returns at most `limit` [BlockAdded]s with a height between `from` and `to` (both
+    /// inclusive), ordered by height. Returns an empty list if there are no such blocks.
+    ///
+    /// * `from` - height of the lowest block which should be retrieved
+    /// * `to` - height of the highest block which should be retrieved
+    /// * `limit` - maximum number of blocks which should be retrieved
+    async fn get_blocks_by_height_range(
+        &self,
+        from: u64,
+        to: u64,
+        limit: u32,
+    ) -> Result<Vec<BlockAdded>, DatabaseReadError>;
+    /// Returns an aggregate of the deploy's events corresponding to the given hex-encoded `hash`
+    ///
+    /// * `hash` - deploy hash of which the aggregate data should be fetched
+    async fn get_deploy_aggregate_by_hash(
+        &self,
+        hash: &str,
+    ) -> Result<DeployAggregate, DatabaseReadError>;
+    /// Returns the [DeployAccepted] corresponding to the given hex-encoded `hash`
+    ///
+    /// * `hash` - deploy hash which identifies the deploy accepted
+    async fn get_deploy_accepted_by_hash(
+        &self,
+        hash: &str,
+    ) -> Result<DeployAccepted, DatabaseReadError>;
+    /// Returns the [DeployProcessed] corresponding to the given hex-encoded `hash`
+    ///
+    /// * `hash` - deploy hash which identifies the deploy pocessed
+    async fn get_deploy_processed_by_hash(
+        &self,
+        hash: &str,
+    ) -> Result<DeployProcessed, DatabaseReadError>;
+
+    /// Returns the [DeployExpired] corresponding to the given hex-encoded `hash`
+    ///
+    /// * `hash` - deploy hash which identifies the deploy expired
+    async fn get_deploy_expired_by_hash(
+        &self,
+        hash: &str,
+    ) -> Result<DeployExpired, DatabaseReadError>;
+    /// Returns all [Fault]s that correspond to the given hex-encoded `public_key`
+    ///
+    /// * `public_key` - key which identifies the fault
+    async fn get_faults_by_public_key(
+        &self,
+        public_key: &str,
+    ) -> Result<Vec<Fault>, DatabaseReadError>;
+    /// Returns all [Fault]s that occurred in the given `era`
+    ///
+    /// * `era` - number of era for which faults should be fetched
+    async fn get_faults_by_era(&self, era: u64) -> Result<Vec<Fault>, DatabaseReadError>;
+    /// Returns all [FinalitySignature](casper_event_types::FinalitySignature)s for the given hex-encoded `block_hash`.
+    ///
+    /// * `block_hash` - block hash for which finality signatures should be fetched
+    async fn get_finality_signatures_by_block(
+        &self,
+        block_hash: &str,
+    ) -> Result<Vec<FinSig>, DatabaseReadError>;
+    /// Returns the [FinalitySignatureCheckpoint] of the block with the given hex-encoded
+    /// `block_hash`, or `NotFound` if its signatures weren't checkpointed.
+    ///
+    /// * `block_hash` - hash of the block
+    async fn get_finality_signature_checkpoint(
+        &self,
+        block_hash: &str,
+    ) -> Result<FinalitySignatureCheckpoint, DatabaseReadError>;
+    /// Returns the hex-encoded hashes of at most `limit` blocks whose valid finality signatures
+    /// were all stored before the given time, ordered by hash. An empty vector is returned if
+    /// there are no such blocks.
+    ///
+    /// * `stored_before` - time, in milliseconds since the UNIX epoch
+    /// * `after` - if given, only the blocks with a greater hash are returned
+    /// * `limit` - maximum number of blocks to return
+    async fn get_blocks_to_checkpoint(
+        &self,
+        stored_before: u64,
+        after: Option<String>,
+        limit: u32,
+    ) -> Result<Vec<String>, DatabaseReadError>;
+    /// Returns the [Step] event for the given era.
+    ///
+    /// * `era` - identifier of era
+    async fn get_step_by_era(&self, era: u64) -> Result<Step, DatabaseReadError>;
+    /// Returns all [UnknownEvent]s of the given type, oldest first.
+    ///
+    /// * `event_type` - name of the type of the events
+    async fn get_unknown_events_by_type(
+        &self,
+        event_type: &str,
+    ) -> Result<Vec<UnknownEvent>, DatabaseReadError>;
+
+    /// Returns all [Enrichment]s stored for the given hex-encoded block or deploy hash.
+    ///
+    /// * `entity_hash` - hash of the block or deploy
+    async fn get_enrichments_by_hash(
+        &self,
+        entity_hash: &str,
+    ) -> Result<Vec<Enrichment>, DatabaseReadError>;
+
+    /// Returns all [DerivedEvent]s emitted by the given deriver, oldest first.
+    ///
+    /// * `deriver` - name of the deriver, as configured
+    async fn get_derived_events_by_deriver(
+        &self,
+        deriver: &str,
+    ) -> Result<Vec<DerivedEvent>, DatabaseReadError>;
+
+    /// Returns all [WebhookDeliveryAttempt]s made for the given webhook, oldest first. An empty
+    /// vector is returned if no attempt was made.
+    ///
+    /// * `webhook` - name of the webhook
+    async fn get_webhook_delivery_attempts(
+        &self,
+        webhook: &str,
+    ) -> Result<Vec<WebhookDeliveryAttempt>, DatabaseReadError>;
+
+    /// Returns all the [StoredApiKey]s, oldest first. An empty vector is returned if no key was
+    /// created.
+    async fn get_api_keys(&self) -> Result<Vec<StoredApiKey>, DatabaseReadError>;
+
+    /// Returns number of events stored in db.
+    async fn get_number_of_events(&self) -> Result<u64, DatabaseReadError>;
+
+    /// Gets the newest migration version.
+    async fn get_newest_migration_version(&self) -> Result<Option<(u32, bool)>, DatabaseReadError>;
+
+    /// Returns at most `limit` [AuditEntry]s starting with the entry identified by `from_id`,
+    /// oldest first. An empty vector is returned if there are no such entries.
+    ///
+    /// * `from_id` - id of the first entry to return
+    /// * `limit` - maximum number of entries to return
+    async fn get_audit_entries(
+        &self,
+        from_id: u64,
+        limit: u32,
+    ) -> Result<Vec<AuditEntry>, DatabaseReadError>;
+
+    /// Returns at most `limit` [OperationalEvent]s starting with the event identified by
+    /// `from_id`, oldest first. An empty vector is returned if there are no such events.
+    ///
+    /// * `from_id` - id of the first event to return
+    /// * `limit` - maximum number of events to return
+    async fn get_operational_events(
+        &self,
+        from_id: u64,
+        limit: u32,
+    ) -> Result<Vec<OperationalEvent>, DatabaseReadError>;
+
+    /// Returns the state of the allocator of outbound event ids. Returns `NotFound` if the
+    /// allocator hasn't been initialized.
+    async fn get_event_id_allocator_state(
+        &self,
+    ) -> Result<EventIdAllocatorState, DatabaseReadError>;
+
+    /// Returns the network the stored events were received from. Returns `NotFound` if no network
+    /// has been saved.
+    async fn get_network_identity(&self) -> Result<NetworkIdentity, DatabaseReadError>;
+
+    /// Returns at most `limit` [DeployTimestampEntry]s with a timestamp between `from` (inclusive)
+    /// and `to` (exclusive), ordered by timestamp. An empty vector is returned if there are no
+    /// such entries.
+    ///
+    /// * `from` - milliseconds since the UNIX epoch at which the time range starts
+    /// * `to` - milliseconds since the UNIX epoch at which the time range ends
+    /// * `after` - if given, only the entries following this one are returned
+    /// * `limit` - maximum number of entries to return
+    async fn get_deploy_timestamps(
+        &self,
+        from: u64,
+        to: u64,
+        after: Option<DeployTimestampEntry>,
+        limit: u32,
+    ) -> Result<Vec<DeployTimestampEntry>, DatabaseReadError>;
+
+    /// Returns at most `limit` [DeployErrorEntry]s of the deploys whose execution failed with an
+    /// error message containing `contains`, ordered by deploy hash. An empty vector is returned if
+    /// there are no such entries.
+    ///
+    /// * `contains` - text the error message has to contain
+    /// * `after` - if given, only the entries with a greater deploy hash are returned
+    /// * `limit` - maximum number of entries to return
+    async fn get_deploys_by_error_message(
+        &self,
+        contains: &str,
+        after: Option<String>,
+        limit: u32,
+    ) -> Result<Vec<DeployErrorEntry>, DatabaseReadError>;
+
+    /// Returns at most `limit` [DeployAccountEntry]s of the deploys sent by the account with the
+    /// given hex-encoded `account_hash`, ordered by deploy hash. An empty vector is returned if
+    /// there are no such entries.
+    ///
+    /// * `account_hash` - hash of the account which sent the deploys
+    /// * `processed_by` - if given, only the deploys processed in a block with a timestamp, in
+    ///   milliseconds since the Unix epoch, up to this one are returned. Requires the deploy
+    ///   timestamps to be indexed
+    /// * `after` - if given, only the entries with a greater deploy hash are returned
+    /// * `limit` - maximum number of entries to return
+    async fn get_deploys_by_account(
+        &self,
+        account_hash: &str,
+        processed_by: Option<u64>,
+        after: Option<String>,
+        limit: u32,
+    ) -> Result<Vec<DeployAccountEntry>, DatabaseReadError>;
+
+    /// Returns a page of at most `limit` [DeployAccountEntry]s of the deploys sent by the account
+    /// with the given hex-encoded `account_hash`, ordered by the time at which they were accepted,
+    /// then by deploy hash. Requires the deploy timestamps to be indexed. An empty vector is
+    /// returned if there are no such entries.
+    ///
+    /// * `account_hash` - hash of the account which sent the deploys
+    /// * `processed_by` - if given, only the deploys processed in a block with a timestamp, in
+    ///   milliseconds since the Unix epoch, up to this one are returned
+    /// * `offset` - number of entries skipped before the page
+    /// * `limit` - maximum number of entries to return
+    async fn get_deploys_by_account_page(
+        &self,
+        account_hash: &str,
+        processed_by: Option<u64>,
+        offset: u32,
+        limit: u32,
+    ) -> Result<Vec<DeployAccountEntry>, DatabaseReadError>;
+
+    /// Returns the [DeployApprovalEntry]s of the deploy with the given hex-encoded `hash`, in the
+    /// order in which they were carried by its acceptances, then by signer. An empty vector is
+    /// returned if there are no such entries.
+    ///
+    /// * `hash` - hash of the accepted deploy
+    async fn get_deploy_approvals(
+        &self,
+        hash: &str,
+    ) -> Result<Vec<DeployApprovalEntry>, DatabaseReadError>;
+
+    /// Returns the [DeployEventTime]s of the `DeployAccepted`, `DeployProcessed` and
+    /// `DeployExpired` events of the deploy with the given hex-encoded `hash`, in the order in
+    /// which they were stored. An empty vector is returned if there are no such events.
+    ///
+    /// * `hash` - hash of the deploy
+    async fn get_deploy_event_times(
+        &self,
+        hash: &str,
+    ) -> Result<Vec<DeployEventTime>, DatabaseReadError>;
+
+    /// Returns the daily [ContractDayStats] of the deploys calling the contract with the given
+    /// hex-encoded `contract_hash`, ordered by day.
+    ///
+    /// * `contract_hash` - hash of the contract, or of the contract package, the deploys call
+    async fn get_contract_stats(
+        &self,
+        contract_hash: &str,
+    ) -> Result<Vec<ContractDayStats>, DatabaseReadError>;
+
+    /// Returns at most `limit` [AccountEffectEntry]s of the deploys whose execution touched the
+    /// state of the account with the given hex-encoded `account_hash`, ordered by deploy hash. An
+    /// empty vector is returned if there are no such entries.
+    ///
+    /// * `account_hash` - hash of the account affected by the deploys
+    /// * `after` - if given, only the entries with a greater deploy hash are returned
+    /// * `limit` - maximum number of entries to return
+    async fn get_deploys_affecting_account(
+        &self,
+        account_hash: &str,
+        after: Option<String>,
+        limit: u32,
+    ) -> Result<Vec<AccountEffectEntry>, DatabaseReadError>;
+
+    /// Returns at most `limit` [TransferEntry]s of the native transfers sent or received by the
+    /// account with the given hex-encoded `account_hash`, ordered by transfer hash. An empty
+    /// vector is returned if there are no such transfers.
+    ///
+    /// * `account_hash` - hash of the sender or the recipient of the transfers
+    /// * `after` - if given, only the transfers with a greater transfer hash are returned
+    /// * `limit` - maximum number of transfers to return
+    async fn get_transfers_by_account(
+        &self,
+        account_hash: &str,
+        after: Option<String>,
+        limit: u32,
+    ) -> Result<Vec<TransferEntry>, DatabaseReadError>;
+
+    /// Returns at most `limit` [TransferEntry]s of the native transfers with the given
+    /// user-defined id, ordered by transfer hash. An empty vector is returned if there are no such
+    /// transfers.
+    ///
+    /// * `transfer_id` - the id of the transfers, also known as their memo
+    /// * `after` - if given, only the transfers with a greater transfer hash are returned
+    /// * `limit` - maximum number of transfers to return
+    async fn get_transfers_by_transfer_id(
+        &self,
+        transfer_id: u64,
+        after: Option<String>,
+        limit: u32,
+    ) -> Result<Vec<TransferEntry>, DatabaseReadError>;
+
+    /// Returns the [TransferEntry]s of the native transfers of the deploys processed in the block
+    /// with the given hex-encoded hash, ordered by transfer hash. An empty vector is returned if
+    /// there are no such transfers.
+    ///
+    /// * `block_hash` - hash of the block
+    async fn get_transfers_by_block(
+        &self,
+        block_hash: &str,
+    ) -> Result<Vec<TransferEntry>, DatabaseReadError>;
+
+    /// Returns at most `limit` [TokenTransferEntry]s of the CEP-18 token with the given
+    /// hex-encoded `contract_hash`, ordered by event id. An empty vector is returned if there are
+    /// no such transfers.
+    ///
+    /// * `contract_hash` - hash of the token contract
+    /// * `after` - if given, only the transfers with a greater event id are returned
+    /// * `limit` - maximum number of transfers to return
+    async fn get_token_transfers(
+        &self,
+        contract_hash: &str,
+        after: Option<u64>,
+        limit: u32,
+    ) -> Result<Vec<TokenTransferEntry>, DatabaseReadError>;
+
+    /// Returns at most `limit` [NftEventEntry]s of the CEP-78 collection with the given hex-encoded
+    /// `contract_hash`, ordered by event id. An empty vector is returned if there are no such
+    /// events.
+    ///
+    /// * `contract_hash` - hash of the collection contract
+    /// * `token_id` - if given, only the events of the token with this identifier are returned
+    /// * `after` - if given, only the events with a greater event id are returned
+    /// * `limit` - maximum number of events to return
+    async fn get_nft_events(
+        &self,
+        contract_hash: &str,
+        token_id: Option<&str>,
+        after: Option<u64>,
+        limit: u32,
+    ) -> Result<Vec<NftEventEntry>, DatabaseReadError>;
+
+    /// Returns the [ContractEventSchemas] of the contract with the given hex-encoded
+    /// `contract_hash`. Returns `NotFound` if no schemas are stored for the contract.
+    ///
+    /// * `contract_hash` - hash of the contract
+    async fn get_contract_event_schemas(
+        &self,
+        contract_hash: &str,
+    ) -> Result<ContractEventSchemas, DatabaseReadError>;
+
+    /// Returns at most `limit` [ContractEventEntry]s of the contract with the given hex-encoded
+    /// `contract_hash`, ordered by event id. An empty vector is returned if there are no such
+    /// events.
+    ///
+    /// * `contract_hash` - hash of the contract
+    /// * `event_name` - if given, only the events with this name are returned
+    /// * `after` - if given, only the events with a greater event id are returned
+    /// * `limit` - maximum number of events to return
+    async fn get_contract_events(
+        &self,
+        contract_hash: &str,
+        event_name: Option<&str>,
+        after: Option<u64>,
+        limit: u32,
+    ) -> Result<Vec<ContractEventEntry>, DatabaseReadError>;
+
+    /// Returns the [DeploySizeStats] of the accepted deploys. Returns `NotFound` if no deploy has
+    /// been recorded.
+    async fn get_deploy_size_stats(&self) -> Result<DeploySizeStats, DatabaseReadError>;
+
+    /// Returns the hex-encoded public keys of the validators of the given `era`, as announced by
+    /// the switch block of the previous era.
+    ///
+    /// * `era` - number of the era
+    async fn get_era_validators(&self, era: u64) -> Result<Vec<String>, DatabaseReadError>;
+
+    /// Returns the validators of the given `era` along with their weights, if known, ordered by
+    /// public key.
+    ///
+    /// * `era` - number of the era
+    async fn get_era_validator_weights(
+        &self,
+        era: u64,
+    ) -> Result<Vec<EraValidatorWeight>, DatabaseReadError>;
+
+    /// Returns the raw JSON payloads of the latest events stored in the given table, newest first.
+    ///
+    /// * `table` - the table to sample
+    /// * `limit` - maximum number of payloads to return
+    async fn get_raw_payload_sample(
+        &self,
+        table: PayloadTable,
+        limit: u32,
+    ) -> Result<Vec<String>, DatabaseReadError>;
+
+    /// Returns at most `limit` [RawPayloadEntry]s of the events stored in the given table, ordered
+    /// by event log id. An empty vector is returned if there are no such events.
+    ///
+    /// * `table` - the table to read
+    /// * `after` - only the events with a greater event log id are returned
+    /// * `limit` - maximum number of entries to return
+    async fn get_raw_payloads_after(
+        &self,
+        table: PayloadTable,
+        after: u64,
+        limit: u32,
+    ) -> Result<Vec<RawPayloadEntry>, DatabaseReadError>;
+}
+
+/// The database was unable to fulfil the request.
+#[derive(Debug)]
+pub enum DatabaseReadError {
+    /// The requested record was not present in the database.
+    NotFound,
+    /// An error occurred serialising or deserialising data from the database.
+    Serialisation(serde_json::Error),
+    /// An error occurred somewhere unexpected.
+    Unhandled(anyhow::Error),
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct DeployAggregate {
+    pub deploy_hash: String,
+    pub deploy_accepted: Option<DeployAccepted>,
+    pub deploy_processed: Option<DeployProcessed>,
+    pub deploy_expired: bool,
+}
+
+/// A single entry of the append-only audit log.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct AuditEntry {
+    pub id: u64,
+    /// Seconds since the UNIX epoch at which the action was recorded.
+    pub timestamp: u64,
+    pub actor: String,
+    pub action: String,
+    pub details: Option<String>,
+}
+
+/// A single event of the operational history of the sidecar.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct OperationalEvent {
+    pub id: u64,
+    /// Seconds since the UNIX epoch at which the event was recorded.
+    pub timestamp: u64,
+    pub kind: String,
+    pub details: Option<String>,
+}
+
+/// The event of a deploy recorded in a [DeployTimestampEntry].
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DeployTimestampKind {
+    /// The deploy was accepted by the node, timestamped with the time it was stored.
+    Accepted,
+    /// The deploy was processed, timestamped with the timestamp of its block.
+    Processed,
+}
+
+impl DeployTimestampKind {
+    pub fn event_type_id(&self) -> u8 {
+        match self {
+            DeployTimestampKind::Accepted => EventTypeId::DeployAccepted as u8,
+            DeployTimestampKind::Processed => EventTypeId::DeployProcessed as u8,
+        }
+    }
+
+    pub fn from_event_type_id(event_type_id: u8) -> Option<Self> {
+        if event_type_id == EventTypeId::DeployAccepted as u8 {
+            Some(DeployTimestampKind::Accepted)
+        } else if event_type_id == EventTypeId::DeployProcessed as u8 {
+            Some(DeployTimestampKind::Processed)
+        } else {
+            None
+        }
+    }
+}
+
+/// A single entry of the index of deploy timestamps.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct DeployTimestampEntry {
+    pub deploy_hash: String,
+    pub kind: DeployTimestampKind,
+    /// Milliseconds since the UNIX epoch at which the deploy was accepted or processed.
+    pub timestamp: u64,
+}
+
+/// A deploy whose execution failed, along with its error message.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct DeployErrorEntry {
+    pub deploy_hash: String,
+    /// Hash of the block the deploy was executed in.
+    pub block_hash: String,
+    pub error_message: String,
+}
+
+/// A deploy along with the account which sent it.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct DeployAccountEntry {
+    pub deploy_hash: String,
+    /// Hex-encoded hash of the account which sent the deploy.
+    pub account_hash: String,
+    /// Alias of the account, if known to the sidecar.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_alias: Option<String>,
+}
+
+/// An approval of an accepted deploy.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct DeployApprovalEntry {
+    pub deploy_hash: String,
+    /// Hex-encoded public key of the signer.
+    pub signer: String,
+    /// ID of the first `DeployAccepted` event carrying the approval in the event log.
+    pub event_log_id: u64,
+}
+
+/// The time an event of a deploy was stored at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeployEventTime {
+    pub event_type_id: u8,
+    /// UTC time the event was stored at, formatted as `YYYY-MM-DD HH:MM:SS`, possibly followed by
+    /// fractional seconds.
+    pub inserted_timestamp: String,
+}
+
+/// A validator of an era.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct EraValidatorWeight {
+    /// Hex-encoded public key of the validator.
+    pub public_key: String,
+    /// Decimal weight of the validator in the era, unless it was stored before the weights were
+    /// recorded.
+    pub weight: Option<String>,
+}
+
+/// The finality signatures of a block aggregated into the validators of its era which signed it,
+/// stored in place of the signatures once they fall outside the configured window.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct FinalitySignatureCheckpoint {
+    pub block_hash: String,
+    pub era_id: u64,
+    /// Hex-encoded bitset of the validators of the era which signed the block, in order of public
+    /// key: the `i`-th validator signed if bit `i % 8` of byte `i / 8` is set.
+    pub signers: String,
+    pub signed_weight: U512,
+    pub total_weight: U512,
+}
+
+/// A deploy along with an account whose state its execution touched.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct AccountEffectEntry {
+    pub deploy_hash: String,
+    /// Hex-encoded hash of the account affected by the deploy.
+    pub account_hash: String,
+}
+
+/// A native transfer recorded by the execution of a processed deploy.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct TransferEntry {
+    /// Hex-encoded address of the transfer.
+    pub transfer_hash: String,
+    pub deploy_hash: String,
+    /// Hash of the block the deploy was processed in.
+    pub block_hash: String,
+    /// Hex-encoded hash of the account which sent the transfer.
+    pub from: String,
+    /// Hex-encoded hash of the recipient account, if the transfer named one.
+    pub to: Option<String>,
+    /// Formatted URef of the purse the motes were taken from.
+    pub source: String,
+    /// Formatted URef of the purse the motes were sent to.
+    pub target: String,
+    /// Amount of motes transferred.
+    #[schema(value_type = String)]
+    pub amount: U512,
+    #[schema(value_type = String)]
+    pub gas: U512,
+    /// User-defined id of the transfer.
+    pub id: Option<u64>,
+}
+
+impl TransferEntry {
+    /// The native transfers recorded by the execution of the deploy.
+    pub fn of_deploy_processed(deploy_processed: &DeployProcessed) -> Vec<Self> {
+        deploy_processed
+            .native_transfers()
+            .into_iter()
+            .map(|(transfer_addr, transfer)| TransferEntry {
+                transfer_hash: hex::encode(transfer_addr.value()),
+                deploy_hash: deploy_processed.hex_encoded_hash(),
+                block_hash: deploy_processed.hex_encoded_block_hash(),
+                from: hex::encode(transfer.from.value()),
+                to: transfer.to.map(|to| hex::encode(to.value())),
+                source: transfer.source.to_formatted_string(),
+                target: transfer.target.to_formatted_string(),
+                amount: transfer.amount,
+                gas: transfer.gas,
+                id: transfer.id,
+            })
+            .collect()
+    }
+}
+
+/// The event of a CEP-18 token recorded in a [TokenTransferEntry].
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub enum TokenTransferKind {
+    /// Tokens were minted to the recipient.
+    Mint,
+    /// Tokens of the owner were burnt.
+    Burn,
+    /// The sender transferred its tokens.
+    Transfer,
+    /// A spender transferred the tokens of their owner within its allowance.
+    TransferFrom,
+}
+
+/// A transfer of a CEP-18 token, decoded from an event its contract emitted.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct TokenTransferEntry {
+    /// Hex-encoded hash of the token contract.
+    pub contract_hash: String,
+    /// Number of the event among the events of the contract, in the order they were emitted.
+    pub event_id: u64,
+    pub kind: TokenTransferKind,
+    /// Formatted key of the account or contract the tokens were taken from, unless minted.
+    pub from: Option<String>,
+    /// Formatted key of the account or contract the tokens were sent to, unless burnt.
+    pub to: Option<String>,
+    /// Formatted key of the spender of a `TransferFrom`.
+    pub spender: Option<String>,
+    #[schema(value_type = String)]
+    pub amount: U256,
+    pub deploy_hash: String,
+    /// Hash of the block the deploy was processed in.
+    pub block_hash: String,
+}
+
+/// The event of a CEP-78 collection recorded in an [NftEventEntry].
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub enum NftEventKind {
+    /// The token was minted to the recipient.
+    Mint,
+    /// The token of the owner was burnt by the spender.
+    Burn,
+    /// The token was transferred from the owner to the recipient, by the spender if given.
+    Transfer,
+    /// The owner approved the spender to transfer the token.
+    Approval,
+}
+
+/// An event of a CEP-78 NFT collection, decoded from an event its contract emitted.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct NftEventEntry {
+    /// Hex-encoded hash of the collection contract.
+    pub contract_hash: String,
+    /// Number of the event among the events of the contract, in the order they were emitted.
+    pub event_id: u64,
+    pub kind: NftEventKind,
+    /// Identifier of the token within the collection, its index or its hash depending on the
+    /// identifier mode of the collection.
+    pub token_id: String,
+    /// Formatted key of the owner of the token, unless minted.
+    pub owner: Option<String>,
+    /// Formatted key of the account or contract the token was sent to by a `Mint` or `Transfer`.
+    pub recipient: Option<String>,
+    /// Formatted key of the burner of a `Burn`, the approved spender of an `Approval`, or the
+    /// operator of a `Transfer` not made by the owner.
+    pub spender: Option<String>,
+    pub deploy_hash: String,
+    /// Hash of the block the deploy was processed in.
+    pub block_hash: String,
+}
+
+/// A field of the events of a contract following the Casper Event Standard.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct ContractEventField {
+    pub name: String,
+    pub cl_type: CLType,
+}
+
+/// The schemas of the events of a contract following the Casper Event Standard, as found in its
+/// `__events_schema` named key.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct ContractEventSchemas {
+    /// Hex-encoded hash of the contract.
+    pub contract_hash: String,
+    /// The fields of each event, in the order they are serialized, by name of the event.
+    pub schemas: BTreeMap<String, Vec<ContractEventField>>,
+}
+
+/// An event of a contract following the Casper Event Standard, decoded with the schema of the
+/// event.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct ContractEventEntry {
+    /// Hex-encoded hash of the contract.
+    pub contract_hash: String,
+    /// Number of the event among the events of the contract, in the order they were emitted.
+    pub event_id: u64,
+    /// Name of the event, without the `event_` prefix.
+    pub event_name: String,
+    /// Values of the fields of the event, by name.
+    pub fields: Map<String, Value>,
+    pub deploy_hash: String,
+    /// Hash of the block the deploy was processed in.
+    pub block_hash: String,
+}
+
+/// Outcomes of the deploys calling a contract on one day, and the gas they spent.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct ContractDayStats {
+    /// UTC date of the deploys' timestamps, formatted as `YYYY-MM-DD`.
+    pub day: String,
+    pub successes: u64,
+    pub failures: u64,
+    pub gas_spent: u64,
+}
+
+/// Distribution of a measure of the accepted deploys. Percentiles are nearest-rank ones.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, ToSchema)]
+pub struct Distribution {
+    pub min: u64,
+    pub max: u64,
+    pub mean: f64,
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+}
+
+impl Distribution {
+    /// Index, in the ascending values of `count` deploys, of the value of the given percentile.
+    pub fn rank_offset(count: u64, percentile: u64) -> u64 {
+        // The nearest rank is ceil(percentile / 100 * count), starting from 1.
+        ((percentile * count + 99) / 100).max(1) - 1
+    }
+
+    /// The distribution of the given values, which have to be sorted in ascending order and not
+    /// be empty.
+    pub fn of_sorted(values: &[u64]) -> Self {
+        let count = values.len() as u64;
+        let percentile = |percentile| values[Self::rank_offset(count, percentile) as usize];
+        Distribution {
+            min: values[0],
+            max: values[values.len() - 1],
+            mean: values.iter().sum::<u64>() as f64 / count as f64,
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+        }
+    }
+}
+
+/// Distribution statistics of the size and weight of the accepted deploys.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, ToSchema)]
+pub struct DeploySizeStats {
+    /// Number of the deploys the statistics are computed from.
+    pub deploys: u64,
+    /// Length, in bytes, of the serialized deploys.
+    pub size_bytes: Distribution,
+    /// Number of the approvals of the deploys.
+    pub approvals: Distribution,
+    /// Number of the runtime args of the payment and session of the deploys.
+    pub args: Distribution,
+}
+
+/// Context fetched from a node's RPC server and attached to a stored block or deploy.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct Enrichment {
+    /// Hex-encoded hash of the block or deploy the context belongs to.
+    pub entity_hash: String,
+    /// The kind of context, e.g. `era_summary`.
+    pub kind: String,
+    /// The context as returned by the node.
+    #[schema(value_type = Object)]
+    pub value: serde_json::Value,
+}
+
+/// An event emitted by one of the configured event derivers.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct DerivedEvent {
+    /// Name of the deriver which emitted the event.
+    pub deriver: String,
+    /// Type of the ingested event the event was derived from, e.g. `DeployProcessed`.
+    pub source_event_type: String,
+    /// The payload emitted by the deriver.
+    #[schema(value_type = Object)]
+    pub payload: serde_json::Value,
+}
+
+/// An attempt at delivering an event to one of the configured webhooks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookDeliveryAttempt {
+    /// Name of the webhook the event was posted to.
+    pub webhook: String,
+    /// Type of the posted event, e.g. `BlockAdded`.
+    pub event_type: String,
+    /// Number of the attempt, starting at 1.
+    pub attempt: u32,
+    /// Milliseconds since the UNIX epoch at which the attempt was made.
+    pub timestamp: u64,
+    /// Status code of the response, `None` if no response was received.
+    pub status_code: Option<u16>,
+    /// Why the attempt failed, `None` if the event was delivered.
+    pub error: Option<String>,
+}
+
+/// An API key created through the admin server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredApiKey {
+    /// Name of the key, identifying its holder.
+    pub name: String,
+    /// Hex-encoded SHA-256 hash of the key, the key itself being shown only once to its creator.
+    pub key_hash: String,
+    pub scopes: Vec<ApiKeyScope>,
+    /// Milliseconds since the UNIX epoch at which the key was created.
+    pub created_timestamp: u64,
+}
+
+/// A table holding the raw JSON payloads of one type of the events received from the nodes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PayloadTable {
+    BlockAdded,
+    DeployAccepted,
+    DeployProcessed,
+    DeployExpired,
+    Fault,
+    FinalitySignature,
+    Step,
+}
+
+impl PayloadTable {
+    pub const ALL: [PayloadTable; 7] = [
+        PayloadTable::BlockAdded,
+        PayloadTable::DeployAccepted,
+        PayloadTable::DeployProcessed,
+        PayloadTable::DeployExpired,
+        PayloadTable::Fault,
+        PayloadTable::FinalitySignature,
+        PayloadTable::Step,
+    ];
+
+    pub fn table_name(&self) -> &'static str {
+        match self {
+            PayloadTable::BlockAdded => "BlockAdded",
+            PayloadTable::DeployAccepted => "DeployAccepted",
+            PayloadTable::DeployProcessed => "DeployProcessed",
+            PayloadTable::DeployExpired => "DeployExpired",
+            PayloadTable::Fault => "Fault",
+            PayloadTable::FinalitySignature => "FinalitySignature",
+            PayloadTable::Step => "Step",
+        }
+    }
+}
+
+/// A table whose rows are derived from the stored processed deploys, and can be rebuilt from them.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum DerivedTable {
+    /// The native transfers recorded by the execution of the deploys.
+    Transfers,
+    /// The transfers of the configured CEP-18 tokens.
+    TokenTransfers,
+    /// The events of the configured CEP-78 NFT collections.
+    NftEvents,
+    /// The events of the configured contracts following the Casper Event Standard.
+    ContractEvents,
+}
+
+impl DerivedTable {
+    pub const ALL: [DerivedTable; 4] = [
+        DerivedTable::Transfers,
+        DerivedTable::TokenTransfers,
+        DerivedTable::NftEvents,
+        DerivedTable::ContractEvents,
+    ];
+
+    /// The name of the table as given to the `rebuild` endpoint of the admin server.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DerivedTable::Transfers => "transfers",
+            DerivedTable::TokenTransfers => "token_transfers",
+            DerivedTable::NftEvents => "nft_events",
+            DerivedTable::ContractEvents => "contract_events",
+        }
+    }
+
+    /// The table with the given name, if any.
+    pub fn from_name(name: &str) -> Option<Self> {
+        DerivedTable::ALL
+            .iter()
+            .copied()
+            .find(|table| table.name() == name)
+    }
+}
+
+/// The raw JSON payload of a stored event, along with the details of its event log entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawPayloadEntry {
+    pub event_log_id: u64,
+    /// Address of the node the event was received from.
+    pub event_source_address: String,
+    /// Id of the event on the stream of the node.
+    pub event_id: u64,
+    /// UTC time the event was stored at, formatted as `YYYY-MM-DD HH:MM:SS`, possibly followed by
+    /// fractional seconds.
+    pub inserted_timestamp: String,
+    pub raw: String,
+}
+
+/// State of the allocator of outbound event ids.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct EventIdAllocatorState {
+    /// The id which will be given to the next outbound event.
+    pub next_event_id: u32,
+    /// Seconds since the UNIX epoch at which the last id was allocated.
+    pub updated_timestamp: u64,
+}
+
+/// The network a node is on, as reported by its `/status` endpoint.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct NetworkIdentity {
+    /// Name of the chainspec of the network, e.g. `casper` for the mainnet.
+    pub chainspec_name: String,
+    /// Root hash of the global state at genesis, telling apart networks sharing a chainspec name.
+    pub starting_state_root_hash: String,
+}
@@ -0,0 +1,89 @@
+//! Property-based testing support.
+//!
+//! Provides [proptest] strategies generating every kind of [SseData] and the round-trip checks
+//! shared by the property tests of the workspace and the cargo-fuzz targets in `fuzz/`. Each
+//! strategy is driven by a seed so that failing cases can be shrunk and reproduced.
+
+use crate::sse_data::{deserialize, SseData, SseDataDeserializer};
+use casper_types::testing::TestRng;
+use proptest::{prelude::*, strategy::Union};
+
+/// Seed used to initialise a [TestRng] inside a strategy.
+pub type Seed = [u8; 16];
+
+/// Builds a value from a fresh [TestRng] seeded with `seed`.
+///
+/// The rng is dropped before returning since only one [TestRng] may exist per thread.
+pub fn with_seeded_rng<T>(seed: Seed, build: impl FnOnce(&mut TestRng) -> T) -> T {
+    let mut rng = TestRng::from_seed(seed);
+    build(&mut rng)
+}
+
+/// Strategy generating any [SseData] a node can emit.
+pub fn sse_data() -> impl Strategy<Value = SseData> {
+    Union::new(vec![
+        any::<Seed>()
+            .prop_map(|seed| with_seeded_rng(seed, SseData::random_api_version))
+            .boxed(),
+        any::<Seed>()
+            .prop_map(|seed| with_seeded_rng(seed, SseData::random_block_added))
+            .boxed(),
+        any::<Seed>()
+            .prop_map(|seed| with_seeded_rng(seed, |rng| SseData::random_deploy_accepted(rng).0))
+            .boxed(),
+        any::<Seed>()
+            .prop_map(|seed| with_seeded_rng(seed, SseData::random_deploy_processed))
+            .boxed(),
+        any::<Seed>()
+            .prop_map(|seed| with_seeded_rng(seed, SseData::random_deploy_expired))
+            .boxed(),
+        any::<Seed>()
+            .prop_map(|seed| with_seeded_rng(seed, SseData::random_fault))
+            .boxed(),
+        any::<Seed>()
+            .prop_map(|seed| with_seeded_rng(seed, SseData::random_finality_signature))
+            .boxed(),
+        any::<Seed>()
+            .prop_map(|seed| with_seeded_rng(seed, SseData::random_step))
+            .boxed(),
+        Just(SseData::Shutdown).boxed(),
+    ])
+}
+
+/// Strategy generating the JSON representation of any [SseData] as sent by a node.
+pub fn sse_data_json() -> impl Strategy<Value = String> {
+    sse_data().prop_map(|sse_data| {
+        serde_json::to_string(&sse_data).expect("should serialize generated SseData")
+    })
+}
+
+/// Checks that `json_raw` is either rejected by `deserializer` or survives a round trip: the event
+/// as forwarded to clients deserializes again to an event which is forwarded identically.
+///
+/// Events are forwarded re-serialized, unless the deserializer asks for their raw json to be kept.
+///
+/// Panics if the round trip fails, which makes it usable both in property tests and as a fuzz
+/// target.
+pub fn check_sse_data_round_trip(json_raw: &str, deserializer: SseDataDeserializer) {
+    let (sse_data, keep_raw) = match deserializer(json_raw) {
+        Ok(deserialized) => deserialized,
+        Err(_) => return,
+    };
+    let serialized = outbound_json(json_raw, &sse_data, keep_raw);
+    let (reparsed, reparsed_keep_raw) = deserializer(&serialized)
+        .unwrap_or_else(|error| panic!("should deserialize {}: {}", serialized, error));
+    let reserialized = outbound_json(&serialized, &reparsed, reparsed_keep_raw);
+    assert_eq!(serialized, reserialized);
+}
+
+fn outbound_json(json_raw: &str, sse_data: &SseData, keep_raw: bool) -> String {
+    if keep_raw {
+        return json_raw.to_string();
+    }
+    serde_json::to_string(sse_data).expect("should serialize SseData")
+}
+
+/// [check_sse_data_round_trip] using the default [deserialize] function.
+pub fn check_default_sse_data_round_trip(json_raw: &str) {
+    check_sse_data_round_trip(json_raw, deserialize)
+}
@@ -1,18 +1,20 @@
+#[cfg(feature = "testing")]
+use casper_types::testing::TestRng;
 use casper_types::{
-    bytesrepr, EraId, ProtocolVersion, PublicKey, SecretKey, Signature, Timestamp, U512,
+    bytesrepr::{self, ToBytes},
+    crypto, EraId, ProtocolVersion, PublicKey, SecretKey, Signature, Timestamp, U512,
 };
-#[cfg(feature = "sse-data-testing")]
-use casper_types::{bytesrepr::ToBytes, crypto, testing::TestRng};
-#[cfg(feature = "sse-data-testing")]
+#[cfg(feature = "testing")]
 use rand::Rng;
 use serde::{Deserialize, Serialize};
-#[cfg(feature = "sse-data-testing")]
+#[cfg(feature = "testing")]
 use std::iter;
 use std::{
     collections::BTreeMap,
     fmt::{self, Display, Formatter},
     hash::Hash,
 };
+use thiserror::Error;
 use utoipa::ToSchema;
 
 use crate::{DeployHash, Digest};
@@ -48,7 +50,7 @@ impl Display for BlockHash {
     }
 }
 
-#[cfg(feature = "sse-data-testing")]
+#[cfg(feature = "testing")]
 impl BlockHash {
     /// Creates a random block hash.
     pub fn random(rng: &mut TestRng) -> Self {
@@ -57,7 +59,6 @@ impl BlockHash {
     }
 }
 
-#[cfg(feature = "sse-data-testing")]
 impl ToBytes for BlockHash {
     fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
         self.0.to_bytes()
@@ -78,7 +79,6 @@ pub struct EraReport {
     inactive_validators: Vec<PublicKey>,
 }
 
-#[cfg(feature = "sse-data-testing")]
 impl ToBytes for EraReport {
     fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
         let mut buffer = bytesrepr::allocate_buffer(self)?;
@@ -105,7 +105,6 @@ pub struct EraEnd {
     next_era_validator_weights: BTreeMap<PublicKey, U512>,
 }
 
-#[cfg(feature = "sse-data-testing")]
 impl ToBytes for EraEnd {
     fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
         let mut buffer = bytesrepr::allocate_buffer(self)?;
@@ -138,7 +137,6 @@ pub struct BlockHeader {
     protocol_version: ProtocolVersion,
 }
 
-#[cfg(feature = "sse-data-testing")]
 impl ToBytes for BlockHeader {
     fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
         let mut buffer = bytesrepr::allocate_buffer(self)?;
@@ -178,7 +176,6 @@ pub struct BlockBody {
     transfer_hashes: Vec<DeployHash>,
 }
 
-#[cfg(feature = "sse-data-testing")]
 impl ToBytes for BlockBody {
     fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
         let mut buffer = bytesrepr::allocate_buffer(self)?;
@@ -202,7 +199,70 @@ pub struct Block {
     body: BlockBody,
 }
 
-#[cfg(feature = "sse-data-testing")]
+/// The canonical serialization of a block, as used by the node, e.g. for gossiping.
+impl ToBytes for Block {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(self.hash.to_bytes()?);
+        buffer.extend(self.header.to_bytes()?);
+        buffer.extend(self.body.to_bytes()?);
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.hash.serialized_length()
+            + self.header.serialized_length()
+            + self.body.serialized_length()
+    }
+}
+
+/// An error returned when the hashes of a [`Block`] don't match its contents.
+#[derive(Error, Debug)]
+pub enum BlockValidationError {
+    #[error("Couldn't serialize the block: {0}")]
+    Bytesrepr(bytesrepr::Error),
+    #[error("Block body hashes to {actual} but the header has {expected}")]
+    UnexpectedBodyHash { expected: Digest, actual: Digest },
+    #[error("Block header hashes to {actual} but the block has {expected}")]
+    UnexpectedBlockHash {
+        expected: BlockHash,
+        actual: BlockHash,
+    },
+}
+
+impl Block {
+    /// Recomputes the hashes of the body and the header, and checks that they match the body hash
+    /// recorded in the header and the hash of the block.
+    pub fn verify(&self) -> Result<(), BlockValidationError> {
+        let body_hash = hash_body(&self.body).map_err(BlockValidationError::Bytesrepr)?;
+        if body_hash != self.header.body_hash {
+            return Err(BlockValidationError::UnexpectedBodyHash {
+                expected: self.header.body_hash,
+                actual: body_hash,
+            });
+        }
+        let block_hash = hash_header(&self.header).map_err(BlockValidationError::Bytesrepr)?;
+        if block_hash != self.hash {
+            return Err(BlockValidationError::UnexpectedBlockHash {
+                expected: self.hash,
+                actual: block_hash,
+            });
+        }
+        Ok(())
+    }
+}
+
+fn hash_body(body: &BlockBody) -> Result<Digest, bytesrepr::Error> {
+    body.to_bytes().map(Digest::hash)
+}
+
+fn hash_header(header: &BlockHeader) -> Result<BlockHash, bytesrepr::Error> {
+    header
+        .to_bytes()
+        .map(|bytes| BlockHash(Digest::hash(bytes)))
+}
+
+#[cfg(feature = "testing")]
 impl Block {
     /// The hash of this block's header.
     pub fn hash(&self) -> &BlockHash {
@@ -229,23 +289,19 @@ impl Block {
         let header = random_block_header(rng, &body);
 
         // Create the block hash.
-        let serialized_header = header
-            .to_bytes()
+        let hash = hash_header(&header)
             .unwrap_or_else(|error| panic!("should serialize block header: {}", error));
-        let hash = BlockHash(Digest::hash(serialized_header));
 
         Block { hash, header, body }
     }
 }
 
-#[cfg(feature = "sse-data-testing")]
+#[cfg(feature = "testing")]
 fn random_block_header(rng: &mut TestRng, body: &BlockBody) -> BlockHeader {
     let parent_hash = BlockHash(Digest::random(rng));
     let state_root_hash = Digest::random(rng);
-    let serialized_body = body
-        .to_bytes()
-        .unwrap_or_else(|error| panic!("should serialize block body: {}", error));
-    let body_hash = Digest::hash(serialized_body);
+    let body_hash =
+        hash_body(body).unwrap_or_else(|error| panic!("should serialize block body: {}", error));
     let random_bit = rng.gen();
     let accumulated_seed = Digest::random(rng);
     let is_switch = rng.gen_bool(0.1);
@@ -271,7 +327,7 @@ fn random_block_header(rng: &mut TestRng, body: &BlockBody) -> BlockHeader {
     }
 }
 
-#[cfg(feature = "sse-data-testing")]
+#[cfg(feature = "testing")]
 fn random_era_end(rng: &mut TestRng) -> EraEnd {
     const BLOCK_REWARD: u64 = 1_000_000_000_000;
     let equivocators_count = rng.gen_range(0..5);
@@ -302,6 +358,59 @@ fn random_era_end(rng: &mut TestRng) -> EraEnd {
     }
 }
 
+/// Where a random block sits in the chain and which deploys it holds.
+#[cfg(feature = "testing")]
+pub(crate) struct BlockShape {
+    pub(crate) parent_hash: BlockHash,
+    pub(crate) height: u64,
+    pub(crate) era_id: u64,
+    pub(crate) timestamp: Timestamp,
+    pub(crate) deploy_hashes: Vec<DeployHash>,
+    pub(crate) transfer_hashes: Vec<DeployHash>,
+    /// The validators of the next era if the block is a switch block.
+    pub(crate) next_era_validators: Option<Vec<PublicKey>>,
+}
+
+#[cfg(feature = "testing")]
+impl Block {
+    /// Random block of the given shape, whose hashes match its contents.
+    pub(crate) fn random_with_shape(rng: &mut TestRng, shape: BlockShape) -> Self {
+        let body = BlockBody {
+            proposer: PublicKey::random(rng),
+            deploy_hashes: shape.deploy_hashes,
+            transfer_hashes: shape.transfer_hashes,
+        };
+        let era_end = shape.next_era_validators.map(|validators| EraEnd {
+            era_report: EraReport {
+                equivocators: Vec::new(),
+                rewards: BTreeMap::new(),
+                inactive_validators: Vec::new(),
+            },
+            next_era_validator_weights: validators
+                .into_iter()
+                .map(|validator| (validator, U512::from(rng.gen_range(1..u64::MAX))))
+                .collect(),
+        });
+        let header = BlockHeader {
+            parent_hash: shape.parent_hash,
+            state_root_hash: Digest::random(rng),
+            body_hash: hash_body(&body)
+                .unwrap_or_else(|error| panic!("should serialize block body: {}", error)),
+            random_bit: rng.gen(),
+            accumulated_seed: Digest::random(rng),
+            era_end,
+            timestamp: shape.timestamp,
+            era_id: EraId::new(shape.era_id),
+            height: shape.height,
+            protocol_version: ProtocolVersion::V1_0_0,
+        };
+        let hash = hash_header(&header)
+            .unwrap_or_else(|error| panic!("should serialize block header: {}", error));
+
+        Block { hash, header, body }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
 pub struct FinalitySignature {
     block_hash: BlockHash,
@@ -334,13 +443,28 @@ impl FinalitySignature {
     pub fn public_key(&self) -> &PublicKey {
         &self.public_key
     }
+
+    /// Verifies that the signature was made by the signing validator over the block hash and era.
+    pub fn verify(&self) -> Result<(), crypto::Error> {
+        crypto::verify(
+            signed_finality_data(&self.block_hash, self.era_id),
+            &self.signature,
+            &self.public_key,
+        )
+    }
 }
 
-#[cfg(feature = "sse-data-testing")]
+/// The data signed by a validator to finalize a block.
+fn signed_finality_data(block_hash: &BlockHash, era_id: EraId) -> Vec<u8> {
+    let mut bytes = block_hash.inner().as_ref().to_vec();
+    bytes.extend_from_slice(&era_id.value().to_le_bytes());
+    bytes
+}
+
+#[cfg(feature = "testing")]
 impl FinalitySignature {
     pub fn random_for_block(block_hash: BlockHash, era_id: u64, rng: &mut TestRng) -> Self {
-        let mut bytes = block_hash.inner().into_vec();
-        bytes.extend_from_slice(&era_id.to_le_bytes());
+        let bytes = signed_finality_data(&block_hash, EraId::new(era_id));
         let secret_key = SecretKey::random(rng);
         let public_key = PublicKey::from(&secret_key);
         let signature = crypto::sign(bytes, &secret_key, &public_key);
@@ -463,6 +587,42 @@ pub mod json_compatibility {
         }
     }
 
+    impl JsonEraEnd {
+        /// Returns the public keys of the validators of the next era.
+        pub fn next_era_validators(&self) -> impl Iterator<Item = &PublicKey> {
+            self.next_era_validator_weights
+                .iter()
+                .map(|validator_weight| &validator_weight.validator)
+        }
+
+        /// Returns the public keys of the validators of the next era along with their weights.
+        pub fn next_era_validator_weights(&self) -> impl Iterator<Item = (&PublicKey, &U512)> {
+            self.next_era_validator_weights
+                .iter()
+                .map(|validator_weight| (&validator_weight.validator, &validator_weight.weight))
+        }
+
+        /// Creates an era end with an empty era report, followed by an era of the given
+        /// validators.
+        #[cfg(feature = "testing")]
+        pub fn with_next_era_validators(validators: Vec<PublicKey>) -> Self {
+            JsonEraEnd {
+                era_report: JsonEraReport {
+                    equivocators: Vec::new(),
+                    rewards: Vec::new(),
+                    inactive_validators: Vec::new(),
+                },
+                next_era_validator_weights: validators
+                    .into_iter()
+                    .map(|validator| ValidatorWeight {
+                        validator,
+                        weight: U512::one(),
+                    })
+                    .collect(),
+            }
+        }
+    }
+
     /// JSON representation of a block header.
     #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
     #[serde(deny_unknown_fields)]
@@ -592,9 +752,20 @@ pub mod json_compatibility {
             &self.body.transfer_hashes
         }
 
-        #[cfg(feature = "sse-data-testing")]
+        /// Returns the public key of the validator which proposed the `Block`.
+        pub fn proposer(&self) -> &PublicKey {
+            &self.body.proposer
+        }
+
+        #[cfg(feature = "testing")]
         pub fn random(rng: &mut TestRng) -> Self {
             let block = Block::random(rng);
+            Self::with_random_proofs(block, rng)
+        }
+
+        /// The given block along with a random number of finality signatures of it.
+        #[cfg(feature = "testing")]
+        pub(crate) fn with_random_proofs(block: Block, rng: &mut TestRng) -> Self {
             let proofs_count = rng.gen_range(0..11);
             let proofs = iter::repeat_with(|| {
                 let finality_signature = FinalitySignature::random_for_block(
@@ -654,3 +825,49 @@ pub mod json_compatibility {
         }
     }
 }
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_verify_finality_signatures() {
+        let mut rng = TestRng::new();
+        let block_hash = BlockHash::random(&mut rng);
+        let finality_signature = FinalitySignature::random_for_block(block_hash, 7, &mut rng);
+        assert!(finality_signature.verify().is_ok());
+
+        let other_era = FinalitySignature {
+            era_id: EraId::new(8),
+            ..finality_signature.clone()
+        };
+        assert!(other_era.verify().is_err());
+
+        let other_signer = FinalitySignature {
+            public_key: PublicKey::random(&mut rng),
+            ..finality_signature
+        };
+        assert!(other_signer.verify().is_err());
+    }
+
+    #[test]
+    fn should_verify_block_hashes() {
+        let mut rng = TestRng::new();
+        let block = Block::random(&mut rng);
+        assert!(block.verify().is_ok());
+
+        let mut other_height = block.clone();
+        other_height.header.height += 1;
+        assert!(matches!(
+            other_height.verify(),
+            Err(BlockValidationError::UnexpectedBlockHash { .. })
+        ));
+
+        let mut other_proposer = block;
+        other_proposer.body.proposer = PublicKey::random(&mut rng);
+        assert!(matches!(
+            other_proposer.verify(),
+            Err(BlockValidationError::UnexpectedBodyHash { .. })
+        ));
+    }
+}
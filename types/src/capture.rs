@@ -0,0 +1,54 @@
+//! Format of recorded upstream event stream traffic.
+//!
+//! A capture is a newline-delimited JSON (NDJSON) file with one [CapturedFrame] per line, in the
+//! order in which the frames were received.
+
+use crate::Filter;
+use serde::{Deserialize, Serialize};
+
+/// A single event stream frame received from a node.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CapturedFrame {
+    /// Milliseconds since the UNIX epoch at which the frame was received.
+    pub received_at: u64,
+    /// Address of the node the frame was received from, e.g. `http://127.0.0.1:18101`.
+    pub source: String,
+    /// Event stream path the frame was received on, e.g. `events/main`.
+    pub filter: String,
+    /// The `id` field of the frame. Absent for the `ApiVersion` event.
+    pub id: Option<String>,
+    /// The `data` field of the frame, exactly as sent by the node.
+    pub data: String,
+}
+
+impl CapturedFrame {
+    /// Returns the [Filter] the frame was received on, or `None` if the path isn't known.
+    pub fn inbound_filter(&self) -> Option<Filter> {
+        [Filter::Events, Filter::Main, Filter::Deploys, Filter::Sigs]
+            .into_iter()
+            .find(|filter| filter.to_string() == self.filter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_round_trip_through_ndjson_line() {
+        let frame = CapturedFrame {
+            received_at: 1_700_000_000_000,
+            source: "http://127.0.0.1:18101".to_string(),
+            filter: Filter::Main.to_string(),
+            id: Some("42".to_string()),
+            data: "\"Shutdown\"".to_string(),
+        };
+
+        let line = serde_json::to_string(&frame).unwrap();
+
+        assert!(!line.contains('\n'));
+        let parsed: CapturedFrame = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed, frame);
+        assert_eq!(parsed.inbound_filter(), Some(Filter::Main));
+    }
+}
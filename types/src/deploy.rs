@@ -1,18 +1,18 @@
-#[cfg(feature = "sse-data-testing")]
+#[cfg(feature = "testing")]
 use std::iter;
 use std::{
     collections::BTreeSet,
     fmt::{self, Display, Formatter},
 };
 
-#[cfg(feature = "sse-data-testing")]
+#[cfg(feature = "testing")]
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 
-#[cfg(feature = "sse-data-testing")]
-use casper_types::{bytesrepr::ToBytes, testing::TestRng};
+#[cfg(feature = "testing")]
+use casper_types::testing::TestRng;
 use casper_types::{
-    bytesrepr::{self},
+    bytesrepr::{self, ToBytes},
     runtime_args, PublicKey, RuntimeArgs, SecretKey, Signature, TimeDiff, Timestamp, U512,
 };
 use utoipa::ToSchema;
@@ -55,7 +55,6 @@ impl Display for DeployHash {
     }
 }
 
-#[cfg(feature = "sse-data-testing")]
 impl ToBytes for DeployHash {
     fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
         self.0.to_bytes()
@@ -130,7 +129,6 @@ impl Display for DeployHeader {
     }
 }
 
-#[cfg(feature = "sse-data-testing")]
 impl ToBytes for DeployHeader {
     fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
         let mut buffer = bytesrepr::allocate_buffer(self)?;
@@ -166,7 +164,14 @@ pub struct Approval {
     signature: Signature,
 }
 
-#[cfg(feature = "sse-data-testing")]
+impl Approval {
+    /// Returns the public key of the signer.
+    pub fn signer(&self) -> &PublicKey {
+        &self.signer
+    }
+}
+
+#[cfg(feature = "testing")]
 impl Approval {
     pub fn create(hash: &DeployHash, secret_key: &SecretKey) -> Self {
         let signer = PublicKey::from(secret_key);
@@ -175,6 +180,19 @@ impl Approval {
     }
 }
 
+impl ToBytes for Approval {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(self.signer.to_bytes()?);
+        buffer.extend(self.signature.to_bytes()?);
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.signer.serialized_length() + self.signature.serialized_length()
+    }
+}
+
 /// A signed item sent to the network used to request execution of Wasm.
 #[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize, Debug, ToSchema)]
 #[serde(deny_unknown_fields)]
@@ -214,6 +232,27 @@ impl Deploy {
     }
 }
 
+/// The canonical serialization of a deploy, as used by the node, e.g. for gossiping.
+impl ToBytes for Deploy {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(self.header.to_bytes()?);
+        buffer.extend(self.hash.to_bytes()?);
+        buffer.extend(self.payment.to_bytes()?);
+        buffer.extend(self.session.to_bytes()?);
+        buffer.extend(self.approvals.to_bytes()?);
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.header.serialized_length()
+            + self.hash.serialized_length()
+            + self.payment.serialized_length()
+            + self.session.serialized_length()
+            + self.approvals.serialized_length()
+    }
+}
+
 impl Display for Deploy {
     fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
         write!(
@@ -229,7 +268,7 @@ impl Display for Deploy {
     }
 }
 
-#[cfg(feature = "sse-data-testing")]
+#[cfg(feature = "testing")]
 impl Deploy {
     pub fn random(rng: &mut TestRng) -> Self {
         let timestamp = Timestamp::random(rng);
@@ -242,6 +281,49 @@ impl Deploy {
         rng: &mut TestRng,
         timestamp: Timestamp,
         ttl: TimeDiff,
+    ) -> Self {
+        let session = rng.gen();
+        Deploy::random_with(rng, timestamp, ttl, session)
+    }
+
+    /// Generates a random instance but using the specified `session`.
+    pub fn random_with_session(rng: &mut TestRng, session: ExecutableDeployItem) -> Self {
+        let timestamp = Timestamp::random(rng);
+        let ttl = TimeDiff::from_millis(rng.gen_range(60_000..3_600_000));
+        Deploy::random_with(rng, timestamp, ttl, session)
+    }
+
+    /// Returns the deploy with the approval of `secret_key` added.
+    pub fn with_approval(mut self, secret_key: &SecretKey) -> Self {
+        self.approvals
+            .insert(Approval::create(&self.hash, secret_key));
+        self
+    }
+
+    /// Generates a random instance but sent and signed by the account of `secret_key`.
+    pub fn random_signed_by(rng: &mut TestRng, secret_key: &SecretKey) -> Self {
+        let timestamp = Timestamp::random(rng);
+        let ttl = TimeDiff::from_millis(rng.gen_range(60_000..3_600_000));
+        let session = rng.gen();
+        Deploy::random_with_key(rng, timestamp, ttl, session, secret_key)
+    }
+
+    fn random_with(
+        rng: &mut TestRng,
+        timestamp: Timestamp,
+        ttl: TimeDiff,
+        session: ExecutableDeployItem,
+    ) -> Self {
+        let secret_key = SecretKey::random(rng);
+        Deploy::random_with_key(rng, timestamp, ttl, session, &secret_key)
+    }
+
+    fn random_with_key(
+        rng: &mut TestRng,
+        timestamp: Timestamp,
+        ttl: TimeDiff,
+        session: ExecutableDeployItem,
+        secret_key: &SecretKey,
     ) -> Self {
         // Create the deploy "body", i.e. the payment and session items.
         //
@@ -254,11 +336,9 @@ impl Deploy {
             entry_point: String::from("example-entry-point"),
             args: payment_args,
         };
-        let session = rng.gen();
 
         // Create the deploy header.
-        let secret_key = SecretKey::random(rng);
-        let account = PublicKey::from(&secret_key);
+        let account = PublicKey::from(secret_key);
         let gas_price = rng.gen_range(1..100);
         let body_hash = Digest::hash(serialize_body(&payment, &session));
         let dependencies_count = rng.gen_range(0..4);
@@ -278,7 +358,7 @@ impl Deploy {
 
         // Create the deploy hash and approval.
         let hash = DeployHash::new(Digest::hash(serialize_header(&header)));
-        let approvals = iter::once(Approval::create(&hash, &secret_key)).collect();
+        let approvals = iter::once(Approval::create(&hash, secret_key)).collect();
 
         Deploy {
             hash,
@@ -290,14 +370,14 @@ impl Deploy {
     }
 }
 
-#[cfg(feature = "sse-data-testing")]
+#[cfg(feature = "testing")]
 fn serialize_header(header: &DeployHeader) -> Vec<u8> {
     header
         .to_bytes()
         .unwrap_or_else(|error| panic!("should serialize deploy header: {}", error))
 }
 
-#[cfg(feature = "sse-data-testing")]
+#[cfg(feature = "testing")]
 fn serialize_body(payment: &ExecutableDeployItem, session: &ExecutableDeployItem) -> Vec<u8> {
     let mut buffer = payment
         .to_bytes()
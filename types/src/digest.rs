@@ -3,17 +3,16 @@ use std::{
     fmt::{self, Debug, Display, Formatter},
 };
 
-#[cfg(feature = "sse-data-testing")]
+#[cfg(feature = "testing")]
 use blake2::{
     digest::{Update, VariableOutput},
     VarBlake2b,
 };
 use hex_fmt::HexFmt;
-#[cfg(feature = "sse-data-testing")]
+#[cfg(feature = "testing")]
 use rand::Rng;
 use serde::{de::Error as SerdeError, Deserialize, Deserializer, Serialize, Serializer};
 
-#[cfg(feature = "sse-data-testing")]
 use casper_types::bytesrepr::{self, ToBytes};
 use casper_types::{checksummed_hex, testing::TestRng};
 use utoipa::ToSchema;
@@ -75,7 +74,7 @@ impl Display for Digest {
     }
 }
 
-#[cfg(feature = "sse-data-testing")]
+#[cfg(feature = "testing")]
 impl Digest {
     pub fn hash<T: AsRef<[u8]>>(data: T) -> Digest {
         let mut ret = [0u8; Digest::LENGTH];
@@ -95,21 +94,19 @@ impl Digest {
     }
 }
 
-#[cfg(feature = "sse-data-testing")]
 impl AsRef<[u8]> for Digest {
     fn as_ref(&self) -> &[u8] {
         self.0.as_ref()
     }
 }
 
-#[cfg(feature = "sse-data-testing")]
+#[cfg(feature = "testing")]
 impl From<[u8; Digest::LENGTH]> for Digest {
     fn from(arr: [u8; Digest::LENGTH]) -> Self {
         Digest(arr)
     }
 }
 
-#[cfg(feature = "sse-data-testing")]
 impl ToBytes for Digest {
     fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
         self.0.to_bytes()
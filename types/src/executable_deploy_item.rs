@@ -1,12 +1,11 @@
 use hex_buffer_serde::{Hex, HexForm};
-#[cfg(feature = "sse-data-testing")]
+#[cfg(feature = "testing")]
 use rand::{
     distributions::{Alphanumeric, Distribution, Standard},
     Rng,
 };
 use serde::{Deserialize, Serialize};
 
-#[cfg(feature = "sse-data-testing")]
 use casper_types::bytesrepr::{self, Bytes, ToBytes};
 use casper_types::{
     system::auction::ARG_AMOUNT, CLValue, ContractHash, ContractPackageHash, ContractVersion,
@@ -14,28 +13,20 @@ use casper_types::{
 };
 use utoipa::ToSchema;
 
-#[cfg(feature = "sse-data-testing")]
 macro_rules! bx {
     ($e:expr) => {
         Box::new($e)
     };
 }
 
-#[cfg(feature = "sse-data-testing")]
 const TAG_LENGTH: usize = 1;
-#[cfg(feature = "sse-data-testing")]
 const MODULE_BYTES_TAG: u8 = 0;
-#[cfg(feature = "sse-data-testing")]
 const STORED_CONTRACT_BY_HASH_TAG: u8 = 1;
-#[cfg(feature = "sse-data-testing")]
 const STORED_CONTRACT_BY_NAME_TAG: u8 = 2;
-#[cfg(feature = "sse-data-testing")]
 const STORED_VERSIONED_CONTRACT_BY_HASH_TAG: u8 = 3;
-#[cfg(feature = "sse-data-testing")]
 const STORED_VERSIONED_CONTRACT_BY_NAME_TAG: u8 = 4;
-#[cfg(feature = "sse-data-testing")]
 const TRANSFER_TAG: u8 = 5;
-#[cfg(feature = "sse-data-testing")]
+#[cfg(feature = "testing")]
 const MAX_PAYMENT_AMOUNT: u64 = 2_500_000_000;
 
 /// The payment or session code of a [`Deploy`].
@@ -104,8 +95,19 @@ pub enum ExecutableDeployItem {
     },
 }
 
-#[cfg(feature = "sse-data-testing")]
 impl ExecutableDeployItem {
+    /// Returns the arguments passed at runtime.
+    pub fn args(&self) -> &RuntimeArgs {
+        match self {
+            ExecutableDeployItem::ModuleBytes { args, .. }
+            | ExecutableDeployItem::StoredContractByHash { args, .. }
+            | ExecutableDeployItem::StoredContractByName { args, .. }
+            | ExecutableDeployItem::StoredVersionedContractByHash { args, .. }
+            | ExecutableDeployItem::StoredVersionedContractByName { args, .. }
+            | ExecutableDeployItem::Transfer { args } => args,
+        }
+    }
+
     fn fields_serialized_length(&self) -> usize {
         let components: Vec<Box<dyn ToBytes>> = match self {
             ExecutableDeployItem::ModuleBytes { module_bytes, args } => {
@@ -142,7 +144,6 @@ impl ExecutableDeployItem {
     }
 }
 
-#[cfg(feature = "sse-data-testing")]
 impl ToBytes for ExecutableDeployItem {
     fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
         let mut buffer = bytesrepr::allocate_buffer(self)?;
@@ -182,14 +183,12 @@ impl ToBytes for ExecutableDeployItem {
     }
 }
 
-#[cfg(feature = "sse-data-testing")]
 fn write_transfer(buffer: &mut Vec<u8>, args: &RuntimeArgs) -> Result<(), bytesrepr::Error> {
     buffer.insert(0, TRANSFER_TAG);
     buffer.extend(args.to_bytes()?);
     Ok(())
 }
 
-#[cfg(feature = "sse-data-testing")]
 fn write_versioned_contract_by_name(
     buffer: &mut Vec<u8>,
     name: &String,
@@ -205,7 +204,6 @@ fn write_versioned_contract_by_name(
     Ok(())
 }
 
-#[cfg(feature = "sse-data-testing")]
 fn write_versioned_contract_by_hash(
     buffer: &mut Vec<u8>,
     hash: &ContractPackageHash,
@@ -221,7 +219,6 @@ fn write_versioned_contract_by_hash(
     Ok(())
 }
 
-#[cfg(feature = "sse-data-testing")]
 fn write_stored_contract_by_name(
     buffer: &mut Vec<u8>,
     name: &String,
@@ -235,7 +232,6 @@ fn write_stored_contract_by_name(
     Ok(())
 }
 
-#[cfg(feature = "sse-data-testing")]
 fn write_stored_contract(
     buffer: &mut Vec<u8>,
     hash: &ContractHash,
@@ -249,7 +245,6 @@ fn write_stored_contract(
     Ok(())
 }
 
-#[cfg(feature = "sse-data-testing")]
 fn write_module_bytes(
     buffer: &mut Vec<u8>,
     module_bytes: &Bytes,
@@ -261,7 +256,7 @@ fn write_module_bytes(
     Ok(())
 }
 
-#[cfg(feature = "sse-data-testing")]
+#[cfg(feature = "testing")]
 impl Distribution<ExecutableDeployItem> for Standard {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> ExecutableDeployItem {
         let mut args = RuntimeArgs::new();
@@ -299,7 +294,7 @@ impl Distribution<ExecutableDeployItem> for Standard {
     }
 }
 
-#[cfg(feature = "sse-data-testing")]
+#[cfg(feature = "testing")]
 fn random_string<R: Rng + ?Sized>(rng: &mut R) -> String {
     rng.sample_iter(&Alphanumeric)
         .take(20)
@@ -307,14 +302,14 @@ fn random_string<R: Rng + ?Sized>(rng: &mut R) -> String {
         .collect()
 }
 
-#[cfg(feature = "sse-data-testing")]
+#[cfg(feature = "testing")]
 fn random_bytes<R: Rng + ?Sized>(rng: &mut R) -> Vec<u8> {
     let mut bytes = vec![0u8; rng.gen_range(0..100)];
     rng.fill_bytes(bytes.as_mut());
     bytes
 }
 
-#[cfg(feature = "sse-data-testing")]
+#[cfg(feature = "testing")]
 fn random_transfer<R: Rng + ?Sized>(rng: &mut R) -> ExecutableDeployItem {
     let amount = rng.gen_range(MAX_PAYMENT_AMOUNT..1_000_000_000_000_000);
     let mut transfer_args = RuntimeArgs::new();
@@ -4,15 +4,19 @@
 
 #[cfg_attr(not(test), macro_use)]
 extern crate alloc;
+#[cfg(feature = "fuzzing")]
+pub mod arbitrary;
 pub mod block;
+pub mod capture;
 pub mod deploy;
 mod digest;
 mod executable_deploy_item;
 mod filter;
 pub mod metrics;
 pub mod sse_data;
-#[cfg(feature = "sse-data-testing")]
-mod testing;
+pub mod sse_events;
+#[cfg(feature = "testing")]
+pub mod testing;
 
 pub use crate::executable_deploy_item::ExecutableDeployItem;
 pub use block::{json_compatibility::JsonBlock, Block, BlockHash, FinalitySignature};
@@ -1,12 +1,20 @@
 use once_cell::sync::Lazy;
-use prometheus::{GaugeVec, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry};
-#[cfg(feature = "additional-metrics")]
+use prometheus::{
+    core::Collector, Gauge, GaugeVec, Histogram, HistogramOpts, HistogramVec, IntCounter,
+    IntCounterVec, Opts, Registry,
+};
 const DB_OPERATION_BUCKETS: &[f64; 8] = &[
     3e+5_f64, 3e+6_f64, 10e+6_f64, 20e+6_f64, 5e+7_f64, 1e+8_f64, 5e+8_f64, 1e+9_f64,
 ];
 const BUCKETS: &[f64; 8] = &[
     5e+2_f64, 1e+3_f64, 2e+3_f64, 5e+3_f64, 5e+4_f64, 5e+5_f64, 5e+6_f64, 5e+7_f64,
 ];
+const DB_POOL_ACQUIRE_BUCKETS: &[f64; 8] = &[
+    1e+0_f64, 5e+0_f64, 1e+1_f64, 5e+1_f64, 1e+2_f64, 5e+2_f64, 1e+3_f64, 5e+3_f64,
+];
+const SEND_LATENCY_BUCKETS: &[f64; 8] = &[
+    1e+0_f64, 5e+0_f64, 1e+1_f64, 5e+1_f64, 1e+2_f64, 5e+2_f64, 1e+3_f64, 5e+3_f64,
+];
 
 static REGISTRY: Lazy<Registry> = Lazy::new(Registry::new);
 pub static ERROR_COUNTS: Lazy<IntCounterVec> = Lazy::new(|| {
@@ -57,13 +65,488 @@ pub static NODE_STATUSES: Lazy<GaugeVec> = Lazy::new(|| {
     counter
 });
 
-#[cfg(feature = "additional-metrics")]
+pub static DB_POOL_CONNECTIONS: Lazy<GaugeVec> = Lazy::new(|| {
+    let gauge = GaugeVec::new(
+        Opts::new("db_pool_connections", "Connections of the database pool. Split by \"state\" which is either \"active\" (in use), \"idle\" or \"max\" (the configured maximum)."),
+        &["state"]
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(gauge.clone()))
+        .expect("cannot register metric");
+    gauge
+});
+pub static DB_POOL_ACQUIRE_TIMES: Lazy<Histogram> = Lazy::new(|| {
+    let histogram = Histogram::with_opts(HistogramOpts {
+        common_opts: Opts::new(
+            "db_pool_acquire_times",
+            "Times (in milliseconds) it took to acquire a connection from the database pool.",
+        ),
+        buckets: Vec::from(DB_POOL_ACQUIRE_BUCKETS as &'static [f64]),
+    })
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(histogram.clone()))
+        .expect("cannot register metric");
+    histogram
+});
+pub static DB_POOL_TIMEOUTS: Lazy<IntCounter> = Lazy::new(|| {
+    let counter = IntCounter::new(
+        "db_pool_timeouts",
+        "Count of attempts to acquire a connection from the database pool which timed out.",
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
+pub static DB_POOL_SATURATED: Lazy<Gauge> = Lazy::new(|| {
+    let gauge = Gauge::new(
+        "db_pool_saturated",
+        "1 if the database pool is saturated, i.e. all connections are in use or acquiring one is slow, 0 otherwise.",
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(gauge.clone()))
+        .expect("cannot register metric");
+    gauge
+});
+pub static ENRICHMENTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new("enrichments", "Count of enrichments of stored events with context fetched from a node's RPC server. Split by \"kind\" of enrichment and by \"outcome\" which is either \"stored\", \"cached\" (stored without an RPC request), \"failed\" or \"dropped\" (the enrichment queue was full)."),
+        &["kind", "outcome"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
+pub static KAFKA_MESSAGES: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new("kafka_messages", "Count of ingested events published to Kafka. Split by \"event_type\" and by \"outcome\" which is either \"published\", \"failed\" (Kafka didn't acknowledge the message in time) or \"dropped\" (the producer queue was full)."),
+        &["event_type", "outcome"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
+pub static WEBHOOK_DELIVERIES: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new("webhook_deliveries", "Count of events posted to the configured webhooks. Split by \"webhook\" and by \"outcome\" which is either \"delivered\", \"failed\" (every attempt failed) or \"dropped\" (the delivery queue was full)."),
+        &["webhook", "outcome"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
+pub static SINK_EVENTS_DROPPED: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new("sink_events_dropped", "Count of broadcast events dropped before reaching an external sink because its delivery queue was full, when the local clients have priority. Split by \"sink\" which is either \"kafka\" or \"webhooks\"."),
+        &["sink"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
+pub static JOURNAL_UNAPPLIED_EVENTS: Lazy<Gauge> = Lazy::new(|| {
+    let gauge = Gauge::new(
+        "journal_unapplied_events",
+        "Number of events in the ingestion journal which aren't stored in the database yet.",
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(gauge.clone()))
+        .expect("cannot register metric");
+    gauge
+});
+pub static PRUNED_EVENTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new(
+            "pruned_events",
+            "Count of stored events deleted by the retention. Split by \"event_type\".",
+        ),
+        &["event_type"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
+pub static CHECKPOINTED_BLOCKS: Lazy<IntCounter> = Lazy::new(|| {
+    let counter = IntCounter::new(
+        "checkpointed_blocks",
+        "Count of blocks whose stored finality signatures were replaced with a checkpoint.",
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
+pub static BACKFILLED_EVENTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new(
+            "backfilled_events",
+            "Count of events missing from the database fetched from a node's RPC server at startup. Split by \"event_type\" and by \"outcome\" which is either \"stored\" or \"failed\".",
+        ),
+        &["event_type", "outcome"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
+pub static SKIPPED_EVENTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new(
+            "skipped_events",
+            "Count of the events whose IDs each node skipped on its event streams. Split by \"node\" and by \"outcome\" which is either \"recovered\" (the event was received once the events were fetched again from the first skipped ID) or \"lost\".",
+        ),
+        &["node", "outcome"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
+pub static DEDUPLICATED_EVENTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new(
+            "deduplicated_events",
+            "Count of the BlockAdded, DeployProcessed and FinalitySignature events received when several nodes are configured. Split by \"event_type\", by \"node\" and by \"outcome\" which is either \"first\" (no copy of the event was stored yet, so it is passed on to be stored) or \"duplicate\" (a copy of the event was stored already, so it is dropped).",
+        ),
+        &["event_type", "node", "outcome"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
+pub static DERIVATIONS: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new("derivations", "Count of runs of the configured event derivers on ingested events. Split by \"deriver\" and by \"outcome\" which is either \"derived\" (at least one event was emitted), \"none\", \"out_of_fuel\", \"timed_out\", \"failed\" (the plugin trapped or returned invalid output) or \"dropped\" (the derivation queue was full)."),
+        &["deriver", "outcome"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
+pub static STATE_PROOFS: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new("state_proofs", "Count of requests for proofs of global state proxied to a node's RPC server. Split by \"outcome\" which is either \"fetched\", \"cached\" (served without an RPC request) or \"failed\"."),
+        &["outcome"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
+pub static SOURCE_HEAD_HEIGHTS: Lazy<GaugeVec> = Lazy::new(|| {
+    let gauge = GaugeVec::new(
+        Opts::new("source_head_heights", "Height of the latest block of each connected node, as reported by the \"/status\" endpoint of its REST server."),
+        &["node"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(gauge.clone()))
+        .expect("cannot register metric");
+    gauge
+});
+pub static SOURCE_STATUS_LATENCIES: Lazy<GaugeVec> = Lazy::new(|| {
+    let gauge = GaugeVec::new(
+        Opts::new("source_status_latencies", "Time in milliseconds each connected node took to return its status at the latest poll, -1 if it didn't respond in time."),
+        &["node"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(gauge.clone()))
+        .expect("cannot register metric");
+    gauge
+});
+pub static PREFERRED_SOURCE: Lazy<GaugeVec> = Lazy::new(|| {
+    let gauge = GaugeVec::new(
+        Opts::new("preferred_source", "1 for the connected node currently scored best, i.e. with the highest latest block and the lowest latency, 0 for the others."),
+        &["node"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(gauge.clone()))
+        .expect("cannot register metric");
+    gauge
+});
+pub static SOURCE_BLOCKS_BEHIND_HEAD: Lazy<GaugeVec> = Lazy::new(|| {
+    let gauge = GaugeVec::new(
+        Opts::new("source_blocks_behind_head", "Number of blocks each connected node added which weren't ingested from it yet, i.e. the height of its latest block at the latest poll of its status less the height of the latest block ingested from it."),
+        &["node"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(gauge.clone()))
+        .expect("cannot register metric");
+    gauge
+});
+pub static SOURCE_SECONDS_SINCE_LAST_EVENT: Lazy<GaugeVec> = Lazy::new(|| {
+    let gauge = GaugeVec::new(
+        Opts::new("source_seconds_since_last_event", "Seconds elapsed since the latest event was ingested from each connected node, as of the latest poll of the statuses of the nodes."),
+        &["node"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(gauge.clone()))
+        .expect("cannot register metric");
+    gauge
+});
+pub static UPSTREAM_SECONDS_SINCE_LAST_BYTES: Lazy<GaugeVec> = Lazy::new(|| {
+    let gauge = GaugeVec::new(
+        Opts::new("upstream_seconds_since_last_bytes", "Seconds elapsed since any bytes, keepalive comments included, were received on the event stream of each connected node, as of the latest inactivity check."),
+        &["node"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(gauge.clone()))
+        .expect("cannot register metric");
+    gauge
+});
+pub static UPSTREAM_INACTIVITY_TIMEOUTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new("upstream_inactivity_timeouts", "Count of the event streams of each node dropped and reconnected because no bytes were received on them for longer than the configured no_message_timeout."),
+        &["node"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
+pub static UPSTREAM_KEEP_ALIVES: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new("upstream_keep_alives", "Count of the keepalive comments received on the event streams of each connected node, which carry no event."),
+        &["node"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
+pub static UPSTREAM_RECONNECTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new(
+            "upstream_reconnects",
+            "Count of the reconnections to each connected node after its connection was lost.",
+        ),
+        &["node"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
+pub static SOURCE_CLOCK_SKEW_SECONDS: Lazy<GaugeVec> = Lazy::new(|| {
+    let gauge = GaugeVec::new(
+        Opts::new("source_clock_skew_seconds", "Seconds elapsed between the timestamp of the latest block received from each node and the time the sidecar received it, negative if the timestamp is ahead of the clock of the sidecar."),
+        &["node"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(gauge.clone()))
+        .expect("cannot register metric");
+    gauge
+});
+pub static CLOCK_SKEW_EXCESSES: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new("clock_skew_excesses", "Count of the blocks received from each node whose timestamp was further from the time the sidecar received them than the configured max_skew_in_seconds."),
+        &["node"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
+pub static SOURCE_HEAD_DIVERGENCE: Lazy<Gauge> = Lazy::new(|| {
+    let gauge = Gauge::new(
+        "source_head_divergence",
+        "Difference between the heights of the highest and the lowest latest blocks of the connected nodes which returned their status at the latest poll.",
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(gauge.clone()))
+        .expect("cannot register metric");
+    gauge
+});
+pub static INVALID_FINALITY_SIGNATURES: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new("invalid_finality_signatures", "Count of ingested finality signatures which failed cryptographic verification. Split by the hex-encoded public key of the \"validator\" which supposedly signed them."),
+        &["validator"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
+pub static BLOCK_INTEGRITY_FAILURES: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new("block_integrity_failures", "Count of ingested blocks whose hashes didn't match their contents. Split by the \"source\" node which sent them."),
+        &["source"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
+pub static EJECTED_EQUIVOCATORS: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new("ejected_equivocators", "Count of validators reported in a fault which are not validators of the era following the fault. Split by the hex-encoded public key of the \"validator\"."),
+        &["validator"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
+pub static WATCHED_EVENTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new("watched_events", "Count of outbound events relating to entities of the watch list. Split by \"kind\" of entity, which is either \"account\", \"contract\" or \"validator\", and by watched \"entity\"."),
+        &["kind", "entity"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
+pub static OUTBOUND_EVENTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new("outbound_events", "Count of events sent to subscribers of the event stream. Split by the \"filter\" the subscribers connected to, e.g. \"events/main\", and by \"event_type\"."),
+        &["filter", "event_type"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
+pub static OUTBOUND_BYTES: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new("outbound_bytes", "Count of bytes of event data sent to subscribers of the event stream. Split by \"filter\" and by \"event_type\", which is \"Batch\" for frames of batched events."),
+        &["filter", "event_type"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
+pub static OUTBOUND_SEND_LATENCIES: Lazy<HistogramVec> = Lazy::new(|| {
+    let histogram = HistogramVec::new(
+        HistogramOpts {
+            common_opts: Opts::new(
+                "outbound_send_latencies",
+                "Times (in milliseconds) from the broadcast of an event to it being sent to a subscriber. Replayed events are not included. Split by \"filter\" and by \"event_type\".",
+            ),
+            buckets: Vec::from(SEND_LATENCY_BUCKETS as &'static [f64]),
+        },
+        &["filter", "event_type"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(histogram.clone()))
+        .expect("cannot register metric");
+    histogram
+});
+pub static OUTBOUND_DROPS: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new("outbound_drops", "Count of events which couldn't be sent to subscribers of the event stream. Split by \"filter\" and by \"reason\", which is \"lagged\" if the subscriber fell too far behind and was disconnected, or \"shed_normal_priority\" or \"shed_low_priority\" if the event was dropped for its priority class."),
+        &["filter", "reason"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
+
+pub static INBOUND_EVENTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new("inbound_events", "Count of events received from the connected nodes, before deduplication. Split by \"event_type\"."),
+        &["event_type"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
+pub static STORED_EVENTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new("stored_events", "Count of events written to the database. Events already stored are not counted. Split by \"event_type\"."),
+        &["event_type"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
+pub static EVENT_STREAM_SUBSCRIBERS: Lazy<GaugeVec> = Lazy::new(|| {
+    let gauge = GaugeVec::new(
+        Opts::new("event_stream_subscribers", "Number of clients subscribed to the event stream. Split by the \"filter\" the subscribers connected to, e.g. \"events/main\"."),
+        &["filter"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(gauge.clone()))
+        .expect("cannot register metric");
+    gauge
+});
+pub static EVENT_STREAM_SATURATED_SUBSCRIBERS: Lazy<GaugeVec> = Lazy::new(|| {
+    let gauge = GaugeVec::new(
+        Opts::new("event_stream_saturated_subscribers", "Number of clients subscribed to the event stream which receive the events more than a second after their broadcast, i.e. which fall behind. Split by the \"filter\" the subscribers connected to, e.g. \"events/main\"."),
+        &["filter"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(gauge.clone()))
+        .expect("cannot register metric");
+    gauge
+});
+pub static NETWORK_EVENTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new("network_events", "Count of events broadcast on the event streams of each network, when several networks are configured. Split by \"network\" and by \"event_type\"."),
+        &["network", "event_type"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
 pub static DB_OPERATION_TIMES: Lazy<HistogramVec> = Lazy::new(|| {
     let counter = HistogramVec::new(
         HistogramOpts {
             common_opts: Opts::new(
                 "db_operation_times",
-                "Times (in nanoseconds) it took to perform a database operation.",
+                "Times (in nanoseconds) it took to perform a database operation. Split by the operation, e.g. \"save_block_added\", under \"filter\".",
             ),
             buckets: Vec::from(DB_OPERATION_BUCKETS as &'static [f64]),
         },
@@ -75,6 +558,17 @@ pub static DB_OPERATION_TIMES: Lazy<HistogramVec> = Lazy::new(|| {
         .expect("cannot register metric");
     counter
 });
+pub static API_KEY_REJECTIONS: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new("api_key_rejections", "Count of requests rejected for their API key, when API keys are configured. Split by \"scope\" the request needed, \"read_events\" or \"admin\", and by \"reason\": \"missing\", \"invalid\" or \"forbidden\"."),
+        &["scope", "reason"],
+    )
+    .expect("metric can't be created");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("cannot register metric");
+    counter
+});
 #[cfg(feature = "additional-metrics")]
 pub static EVENTS_PROCESSED_PER_SECOND: Lazy<GaugeVec> = Lazy::new(|| {
     let counter = GaugeVec::new(
@@ -88,6 +582,109 @@ pub static EVENTS_PROCESSED_PER_SECOND: Lazy<GaugeVec> = Lazy::new(|| {
     counter
 });
 
+/// The type of a metric, which determines how it is queried.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetricKind {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+/// The description of a metric of the sidecar, as exposed on the metrics endpoint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MetricDescription {
+    pub name: String,
+    pub help: String,
+    /// Names of the labels splitting the metric, empty if it isn't split.
+    pub labels: Vec<String>,
+    pub kind: MetricKind,
+}
+
+/// Returns the descriptions of all the metrics of the sidecar, in the order they are defined. Unlike
+/// the metrics summary, the metrics split by labels are described even if they have no samples yet.
+pub fn metric_descriptions() -> Vec<MetricDescription> {
+    let mut collectors: Vec<(&dyn Collector, MetricKind)> = vec![
+        (&*ERROR_COUNTS, MetricKind::Counter),
+        (&*RECEIVED_BYTES, MetricKind::Histogram),
+        (&*INTERNAL_EVENTS, MetricKind::Counter),
+        (&*NODE_STATUSES, MetricKind::Gauge),
+        (&*DB_POOL_CONNECTIONS, MetricKind::Gauge),
+        (&*DB_POOL_ACQUIRE_TIMES, MetricKind::Histogram),
+        (&*DB_POOL_TIMEOUTS, MetricKind::Counter),
+        (&*DB_POOL_SATURATED, MetricKind::Gauge),
+        (&*ENRICHMENTS, MetricKind::Counter),
+        (&*KAFKA_MESSAGES, MetricKind::Counter),
+        (&*WEBHOOK_DELIVERIES, MetricKind::Counter),
+        (&*SINK_EVENTS_DROPPED, MetricKind::Counter),
+        (&*JOURNAL_UNAPPLIED_EVENTS, MetricKind::Gauge),
+        (&*PRUNED_EVENTS, MetricKind::Counter),
+        (&*CHECKPOINTED_BLOCKS, MetricKind::Counter),
+        (&*BACKFILLED_EVENTS, MetricKind::Counter),
+        (&*SKIPPED_EVENTS, MetricKind::Counter),
+        (&*DEDUPLICATED_EVENTS, MetricKind::Counter),
+        (&*DERIVATIONS, MetricKind::Counter),
+        (&*STATE_PROOFS, MetricKind::Counter),
+    ];
+    collectors.extend(source_collectors());
+    collectors.extend(serving_collectors());
+    collectors
+        .into_iter()
+        .flat_map(|(collector, kind)| {
+            collector
+                .desc()
+                .into_iter()
+                .map(move |desc| MetricDescription {
+                    name: desc.fq_name.clone(),
+                    help: desc.help.clone(),
+                    labels: desc.variable_labels.clone(),
+                    kind,
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// The collectors of the metrics of the sources of events, and of the events they send.
+fn source_collectors() -> Vec<(&'static dyn Collector, MetricKind)> {
+    vec![
+        (&*SOURCE_HEAD_HEIGHTS, MetricKind::Gauge),
+        (&*SOURCE_STATUS_LATENCIES, MetricKind::Gauge),
+        (&*PREFERRED_SOURCE, MetricKind::Gauge),
+        (&*SOURCE_BLOCKS_BEHIND_HEAD, MetricKind::Gauge),
+        (&*SOURCE_SECONDS_SINCE_LAST_EVENT, MetricKind::Gauge),
+        (&*UPSTREAM_SECONDS_SINCE_LAST_BYTES, MetricKind::Gauge),
+        (&*UPSTREAM_INACTIVITY_TIMEOUTS, MetricKind::Counter),
+        (&*UPSTREAM_KEEP_ALIVES, MetricKind::Counter),
+        (&*UPSTREAM_RECONNECTS, MetricKind::Counter),
+        (&*SOURCE_CLOCK_SKEW_SECONDS, MetricKind::Gauge),
+        (&*CLOCK_SKEW_EXCESSES, MetricKind::Counter),
+        (&*SOURCE_HEAD_DIVERGENCE, MetricKind::Gauge),
+        (&*INVALID_FINALITY_SIGNATURES, MetricKind::Counter),
+        (&*BLOCK_INTEGRITY_FAILURES, MetricKind::Counter),
+        (&*EJECTED_EQUIVOCATORS, MetricKind::Counter),
+    ]
+}
+
+/// The collectors of the metrics of the events received, stored and served by the sidecar.
+fn serving_collectors() -> Vec<(&'static dyn Collector, MetricKind)> {
+    vec![
+        (&*WATCHED_EVENTS, MetricKind::Counter),
+        (&*OUTBOUND_EVENTS, MetricKind::Counter),
+        (&*OUTBOUND_BYTES, MetricKind::Counter),
+        (&*OUTBOUND_SEND_LATENCIES, MetricKind::Histogram),
+        (&*OUTBOUND_DROPS, MetricKind::Counter),
+        (&*INBOUND_EVENTS, MetricKind::Counter),
+        (&*STORED_EVENTS, MetricKind::Counter),
+        (&*EVENT_STREAM_SUBSCRIBERS, MetricKind::Gauge),
+        (&*EVENT_STREAM_SATURATED_SUBSCRIBERS, MetricKind::Gauge),
+        (&*NETWORK_EVENTS, MetricKind::Counter),
+        (&*DB_OPERATION_TIMES, MetricKind::Histogram),
+        (&*API_KEY_REJECTIONS, MetricKind::Counter),
+        #[cfg(feature = "additional-metrics")]
+        (&*EVENTS_PROCESSED_PER_SECOND, MetricKind::Gauge),
+    ]
+}
+
 pub struct MetricCollectionError {
     reason: String,
 }
@@ -13,21 +13,23 @@ pub enum EventFilter {
     Fault,
     FinalitySignature,
     Step,
+    Unknown,
+    Derived,
 }
 
-#[cfg(feature = "sse-data-testing")]
+#[cfg(feature = "testing")]
 use super::testing;
 use crate::{BlockHash, Deploy, DeployHash, FinalitySignature, JsonBlock};
-#[cfg(feature = "sse-data-testing")]
+#[cfg(feature = "testing")]
 use casper_types::testing::TestRng;
 use casper_types::{EraId, ExecutionResult, ProtocolVersion, PublicKey, TimeDiff, Timestamp};
-#[cfg(feature = "sse-data-testing")]
+#[cfg(feature = "testing")]
 use rand::Rng;
 use serde::{Deserialize, Serialize};
-#[cfg(feature = "sse-data-testing")]
+#[cfg(feature = "testing")]
 use serde_json::value::to_raw_value;
 use serde_json::value::RawValue;
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -40,18 +42,67 @@ pub(crate) fn to_error(msg: String) -> SseDataDeserializeError {
     SseDataDeserializeError::DeserializationError(msg)
 }
 
+/// Signature of functions turning the raw json data of a node's event into [SseData]. The boolean
+/// returned alongside the data indicates whether the raw json needs to be kept.
+///
+/// [deserialize] is the default implementation; others can be injected into the listener so that
+/// parsing can be tested and fuzzed in isolation.
+pub type SseDataDeserializer = fn(&str) -> Result<(SseData, bool), SseDataDeserializeError>;
+
+/// Names of the event types the sidecar knows how to parse.
+const KNOWN_EVENT_TYPES: [&str; 10] = [
+    "ApiVersion",
+    "SidecarVersion",
+    "BlockAdded",
+    "DeployAccepted",
+    "DeployProcessed",
+    "DeployExpired",
+    "Fault",
+    "FinalitySignature",
+    "Step",
+    "Shutdown",
+];
+
 /// Deserializes a string which should contain json data and returns a result of either SseData (which is 1.4.x compliant) or an SseDataDeserializeError
 ///
+/// Events of a type the sidecar doesn't know, e.g. introduced by a newer node, are returned as
+/// [SseData::Unknown] and need their raw json to be kept. Malformed events of a known type are
+/// still an error.
+///
 /// * `json_raw`: string slice which should contain raw json data.
 pub fn deserialize(json_raw: &str) -> Result<(SseData, bool), SseDataDeserializeError> {
     serde_json::from_str::<SseData>(json_raw)
         .map(|el| (el, false))
-        .map_err(|err| {
-            let error_message = format!("Serde Error: {}", err);
-            to_error(error_message)
+        .or_else(|err| match deserialize_unknown(json_raw) {
+            Some(unknown) => Ok((unknown, true)),
+            None => {
+                let error_message = format!("Serde Error: {}", err);
+                Err(to_error(error_message))
+            }
         })
 }
 
+/// Reads an event of an unknown type: either an object with the type name as its single key, or
+/// just the type name for events without payload.
+fn deserialize_unknown(json_raw: &str) -> Option<SseData> {
+    let (event_type, payload) =
+        match serde_json::from_str::<BTreeMap<String, Box<RawValue>>>(json_raw) {
+            Ok(map) if map.len() == 1 => map.into_iter().next()?,
+            Ok(_) => return None,
+            Err(_) => {
+                let event_type = serde_json::from_str::<String>(json_raw).ok()?;
+                (event_type, RawValue::from_string("null".to_string()).ok()?)
+            }
+        };
+    if KNOWN_EVENT_TYPES.contains(&event_type.as_str()) {
+        return None;
+    }
+    Some(SseData::Unknown {
+        event_type,
+        payload,
+    })
+}
+
 /// The "data" field of the events sent on the event stream to clients.
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub enum SseData {
@@ -100,9 +151,41 @@ pub enum SseData {
     },
     /// The node is about to shut down.
     Shutdown,
+    /// An event of a type this version of the sidecar doesn't know, kept as it was received.
+    #[serde(skip_deserializing)]
+    Unknown {
+        event_type: String,
+        payload: Box<RawValue>,
+    },
+    /// An event emitted by one of the event derivers registered in the sidecar's config, derived
+    /// from an event of type `source_event_type`.
+    #[serde(skip_deserializing)]
+    Derived {
+        deriver: String,
+        source_event_type: String,
+        payload: Box<RawValue>,
+    },
 }
 
 impl SseData {
+    /// The name of the type of the event, as used in metrics and reports.
+    pub fn type_label(&self) -> &'static str {
+        match self {
+            SseData::ApiVersion(_) => "ApiVersion",
+            SseData::SidecarVersion(_) => "SidecarVersion",
+            SseData::BlockAdded { .. } => "BlockAdded",
+            SseData::DeployAccepted { .. } => "DeployAccepted",
+            SseData::DeployProcessed { .. } => "DeployProcessed",
+            SseData::DeployExpired { .. } => "DeployExpired",
+            SseData::Fault { .. } => "Fault",
+            SseData::FinalitySignature(_) => "FinalitySignature",
+            SseData::Step { .. } => "Step",
+            SseData::Shutdown => "Shutdown",
+            SseData::Unknown { .. } => "Unknown",
+            SseData::Derived { .. } => "Derived",
+        }
+    }
+
     pub fn should_include(&self, filter: &[EventFilter]) -> bool {
         match self {
             SseData::Shutdown => true,
@@ -115,11 +198,13 @@ impl SseData {
             SseData::Fault { .. } => filter.contains(&EventFilter::Fault),
             SseData::FinalitySignature(_) => filter.contains(&EventFilter::FinalitySignature),
             SseData::Step { .. } => filter.contains(&EventFilter::Step),
+            SseData::Unknown { .. } => filter.contains(&EventFilter::Unknown),
+            SseData::Derived { .. } => filter.contains(&EventFilter::Derived),
         }
     }
 }
 
-#[cfg(feature = "sse-data-testing")]
+#[cfg(feature = "testing")]
 impl SseData {
     /// Returns a random `SseData::ApiVersion`.
     pub fn random_api_version(rng: &mut TestRng) -> Self {
@@ -203,7 +288,7 @@ impl SseData {
     }
 }
 
-#[cfg(feature = "sse-data-testing")]
+#[cfg(feature = "testing")]
 pub mod test_support {
     pub const BLOCK_HASH_1: &str =
         "ca52062424e9d5631a34b7b401e123927ce29d4bd10bc97c7df0aa752f131bb7";
@@ -234,3 +319,33 @@ pub mod test_support {
         raw_block_added
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_deserialize_events_of_unknown_type() {
+        let (sse_data, keep_raw) =
+            deserialize("{\"BlockFinalized\":{\"height\":10,\"proofs\":[]}}").unwrap();
+        assert!(keep_raw);
+        match sse_data {
+            SseData::Unknown {
+                event_type,
+                payload,
+            } => {
+                assert_eq!(event_type, "BlockFinalized");
+                assert_eq!(payload.get(), "{\"height\":10,\"proofs\":[]}");
+            }
+            other => panic!("expected an unknown event, got {:?}", other),
+        }
+
+        let (sse_data, _) = deserialize("\"Paused\"").unwrap();
+        assert!(matches!(sse_data, SseData::Unknown { event_type, .. } if event_type == "Paused"));
+
+        // Malformed events of a known type aren't mistaken for unknown ones.
+        assert!(deserialize("{\"BlockAdded\":{\"height\":10}}").is_err());
+        assert!(deserialize("{\"Unknown\":1,\"Other\":2}").is_err());
+        assert!(deserialize("[1]").is_err());
+    }
+}
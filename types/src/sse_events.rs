@@ -0,0 +1,595 @@
+#[cfg(feature = "testing")]
+use crate::{block::json_compatibility::JsonEraEnd, sse_data::SseData, Digest};
+use crate::{
+    block::BlockValidationError, Block, BlockHash, Deploy, DeployHash, ExecutableDeployItem,
+    FinalitySignature as FinSig, JsonBlock,
+};
+#[cfg(feature = "testing")]
+use casper_types::testing::TestRng;
+use casper_types::{
+    account::AccountHash,
+    bytesrepr::{self, ToBytes},
+    AsymmetricType, EraId, ExecutionEffect, ExecutionResult, Key, ProtocolVersion, PublicKey,
+    TimeDiff, Timestamp, Transfer, TransferAddr, Transform, TransformEntry, U512,
+};
+#[cfg(feature = "testing")]
+use casper_types::{ContractHash, RuntimeArgs, SecretKey};
+use derive_new::new;
+#[cfg(feature = "testing")]
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use std::{
+    collections::BTreeSet,
+    fmt::{Display, Formatter},
+    sync::Arc,
+};
+use utoipa::ToSchema;
+
+/// The version of this node's API server.  This event will always be the first sent to a new
+/// client, and will have no associated event ID provided.
+#[derive(Clone, Debug, Serialize, Deserialize, new)]
+pub struct ApiVersion(ProtocolVersion);
+
+/// The given block has been added to the linear chain and stored locally.
+#[derive(Clone, Debug, Serialize, Deserialize, new, ToSchema)]
+pub struct BlockAdded {
+    block_hash: BlockHash,
+    block: Box<JsonBlock>,
+}
+
+#[cfg(feature = "testing")]
+impl BlockAdded {
+    pub fn random(rng: &mut TestRng) -> Self {
+        let block = JsonBlock::random(rng);
+        Self {
+            block_hash: block.hash,
+            block: Box::new(block),
+        }
+    }
+
+    pub fn random_with_height(rng: &mut TestRng, height: u64) -> Self {
+        let mut block_added = Self::random(rng);
+        block_added.block.header.height = height;
+        block_added
+    }
+
+    /// Random switch block of `era_id`, followed by an era of the given validators.
+    pub fn random_switch_block(
+        rng: &mut TestRng,
+        era_id: u64,
+        next_era_validators: Vec<PublicKey>,
+    ) -> Self {
+        let mut block_added = Self::random(rng);
+        block_added.block.header.era_id = EraId::new(era_id);
+        block_added.block.header.era_end =
+            Some(JsonEraEnd::with_next_era_validators(next_era_validators));
+        block_added
+    }
+
+    pub fn block_hash(&self) -> BlockHash {
+        self.block_hash
+    }
+
+    pub fn parent_hash(&self) -> BlockHash {
+        self.block.header.parent_hash
+    }
+}
+
+impl BlockAdded {
+    pub fn hex_encoded_hash(&self) -> String {
+        hex::encode(self.block_hash.inner())
+    }
+
+    pub fn get_height(&self) -> u64 {
+        self.block.header.height
+    }
+
+    pub fn get_timestamp(&self) -> Timestamp {
+        self.block.header.timestamp
+    }
+
+    pub fn get_era_id(&self) -> u64 {
+        self.block.header.era_id.value()
+    }
+
+    /// Whether the block is the last block of its era.
+    pub fn is_switch_block(&self) -> bool {
+        self.block.header.era_end.is_some()
+    }
+
+    /// The block serialized the way the node serializes it. Its proofs are not part of it.
+    pub fn canonical_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        Block::from((*self.block).clone()).to_bytes()
+    }
+
+    /// Checks that the hashes of the block match its contents and that the block is the one the
+    /// event is for.
+    pub fn verify(&self) -> Result<(), BlockValidationError> {
+        Block::from((*self.block).clone()).verify()?;
+        if self.block.hash != self.block_hash {
+            return Err(BlockValidationError::UnexpectedBlockHash {
+                expected: self.block_hash,
+                actual: self.block.hash,
+            });
+        }
+        Ok(())
+    }
+
+    /// The ID of the next era and the hex-encoded public keys of its validators, if the block is
+    /// the switch block of its era.
+    pub fn next_era_validators(&self) -> Option<(u64, Vec<String>)> {
+        let era_end = self.block.header.era_end.as_ref()?;
+        let validators = era_end
+            .next_era_validators()
+            .map(|public_key| public_key.to_hex())
+            .collect();
+        Some((self.block.header.era_id.value() + 1, validators))
+    }
+
+    /// The ID of the next era and the hex-encoded public keys of its validators along with their
+    /// weights, if the block is the switch block of its era.
+    pub fn next_era_validator_weights(&self) -> Option<(u64, Vec<(String, U512)>)> {
+        let era_end = self.block.header.era_end.as_ref()?;
+        let validator_weights = era_end
+            .next_era_validator_weights()
+            .map(|(public_key, weight)| (public_key.to_hex(), *weight))
+            .collect();
+        Some((self.block.header.era_id.value() + 1, validator_weights))
+    }
+
+    /// Hex-encoded hashes of all deploys, including transfers, of the block.
+    pub fn hex_encoded_deploy_hashes(&self) -> Vec<String> {
+        self.block
+            .deploy_hashes()
+            .iter()
+            .chain(self.block.transfer_hashes())
+            .map(|deploy_hash| hex::encode(deploy_hash.inner()))
+            .collect()
+    }
+}
+
+/// The given deploy has been newly-accepted by this node.
+#[derive(Clone, Debug, Serialize, Deserialize, new, ToSchema)]
+pub struct DeployAccepted {
+    // It's an Arc to not create multiple copies of the same deploy for multiple subscribers.
+    deploy: Arc<Deploy>,
+}
+
+impl DeployAccepted {
+    #[cfg(feature = "testing")]
+    pub fn random(rng: &mut TestRng) -> Self {
+        Self {
+            deploy: Arc::new(Deploy::random(rng)),
+        }
+    }
+
+    /// Random deploy whose session calls the stored contract with the given hash.
+    #[cfg(feature = "testing")]
+    pub fn random_calling_contract(rng: &mut TestRng, contract_hash: ContractHash) -> Self {
+        let session = ExecutableDeployItem::StoredContractByHash {
+            hash: contract_hash,
+            entry_point: "transfer".to_string(),
+            args: RuntimeArgs::new(),
+        };
+        Self {
+            deploy: Arc::new(Deploy::random_with_session(rng, session)),
+        }
+    }
+
+    /// Random deploy sent by the account of the given secret key.
+    #[cfg(feature = "testing")]
+    pub fn random_sent_by(rng: &mut TestRng, secret_key: &SecretKey) -> Self {
+        Self {
+            deploy: Arc::new(Deploy::random_signed_by(rng, secret_key)),
+        }
+    }
+
+    /// The same deploy with the approval of `secret_key` added, as a later acceptance of it may
+    /// carry.
+    #[cfg(feature = "testing")]
+    pub fn with_approval(&self, secret_key: &SecretKey) -> Self {
+        Self {
+            deploy: Arc::new(self.deploy.as_ref().clone().with_approval(secret_key)),
+        }
+    }
+
+    #[cfg(feature = "testing")]
+    pub fn deploy_hash(&self) -> DeployHash {
+        self.deploy.hash().to_owned()
+    }
+
+    pub fn deploy(&self) -> &Deploy {
+        &self.deploy
+    }
+
+    pub fn hex_encoded_hash(&self) -> String {
+        hex::encode(self.deploy.hash().inner())
+    }
+
+    /// The hex-encoded hash of the account which sent the deploy.
+    pub fn hex_encoded_account_hash(&self) -> String {
+        hex::encode(self.deploy.header().account().to_account_hash().value())
+    }
+
+    /// The hex-encoded hash of the stored contract, or contract package, the session of the deploy
+    /// calls, if any.
+    pub fn hex_encoded_target_contract(&self) -> Option<String> {
+        match self.deploy.session() {
+            ExecutableDeployItem::StoredContractByHash { hash, .. } => {
+                Some(hex::encode(hash.value()))
+            }
+            ExecutableDeployItem::StoredVersionedContractByHash { hash, .. } => {
+                Some(hex::encode(hash.value()))
+            }
+            _ => None,
+        }
+    }
+
+    /// The deploy serialized the way the node serializes it.
+    pub fn canonical_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        self.deploy.to_bytes()
+    }
+
+    /// The length, in bytes, of the deploy serialized the way the node serializes it.
+    pub fn serialized_length(&self) -> usize {
+        self.deploy.serialized_length()
+    }
+
+    /// The number of the approvals of the deploy.
+    pub fn approvals_count(&self) -> usize {
+        self.deploy.approvals().len()
+    }
+
+    /// The hex-encoded public keys of the signers of the approvals of the deploy.
+    pub fn hex_encoded_approval_signers(&self) -> Vec<String> {
+        self.deploy
+            .approvals()
+            .iter()
+            .map(|approval| approval.signer().to_hex())
+            .collect()
+    }
+
+    /// The number of the runtime args of the payment and session of the deploy.
+    pub fn args_count(&self) -> usize {
+        self.deploy.payment().args().len() + self.deploy.session().args().len()
+    }
+}
+
+/// The given deploy has been executed, committed and forms part of the given block.
+#[derive(Clone, Debug, Serialize, Deserialize, new, ToSchema)]
+pub struct DeployProcessed {
+    deploy_hash: Box<DeployHash>,
+    #[schema(value_type = String)]
+    account: Box<PublicKey>,
+    #[schema(value_type = String)]
+    timestamp: Timestamp,
+    #[schema(value_type = String)]
+    ttl: TimeDiff,
+    dependencies: Vec<DeployHash>,
+    block_hash: Box<BlockHash>,
+    execution_result: Box<ExecutionResult>,
+}
+
+impl DeployProcessed {
+    #[cfg(feature = "testing")]
+    pub fn random(rng: &mut TestRng, with_deploy_hash: Option<DeployHash>) -> Self {
+        let deploy = Deploy::random(rng);
+        Self {
+            deploy_hash: Box::new(with_deploy_hash.unwrap_or(*deploy.hash())),
+            account: Box::new(deploy.header().account().clone()),
+            timestamp: deploy.header().timestamp(),
+            ttl: deploy.header().ttl(),
+            dependencies: deploy.header().dependencies().clone(),
+            block_hash: Box::new(BlockHash::random(rng)),
+            execution_result: Box::new(rng.gen()),
+        }
+    }
+
+    #[cfg(feature = "testing")]
+    pub fn with_block_hash(mut self, block_hash: BlockHash) -> Self {
+        self.block_hash = Box::new(block_hash);
+        self
+    }
+
+    pub fn deploy_hash(&self) -> DeployHash {
+        *self.deploy_hash
+    }
+
+    pub fn hex_encoded_hash(&self) -> String {
+        hex::encode(self.deploy_hash.inner())
+    }
+
+    pub fn hex_encoded_block_hash(&self) -> String {
+        hex::encode(self.block_hash.inner())
+    }
+
+    /// The error message of the execution of the deploy if it failed.
+    pub fn error_message(&self) -> Option<&str> {
+        match self.execution_result.as_ref() {
+            ExecutionResult::Failure { error_message, .. } => Some(error_message),
+            ExecutionResult::Success { .. } => None,
+        }
+    }
+
+    /// The UTC date of the deploy's timestamp, formatted as `YYYY-MM-DD`.
+    pub fn timestamp_day(&self) -> String {
+        let timestamp = self.timestamp.to_string();
+        timestamp
+            .split('T')
+            .next()
+            .unwrap_or(&timestamp)
+            .to_string()
+    }
+
+    /// The cost of the execution of the deploy, capped at the largest value stored by the
+    /// databases, which no real deploy comes close to.
+    pub fn gas_spent(&self) -> u64 {
+        let cost = match self.execution_result.as_ref() {
+            ExecutionResult::Success { cost, .. } | ExecutionResult::Failure { cost, .. } => cost,
+        };
+        if *cost > U512::from(i64::MAX as u64) {
+            i64::MAX as u64
+        } else {
+            cost.as_u64()
+        }
+    }
+
+    #[cfg(feature = "testing")]
+    pub fn with_cost(mut self, new_cost: U512) -> Self {
+        match self.execution_result.as_mut() {
+            ExecutionResult::Success { cost, .. } | ExecutionResult::Failure { cost, .. } => {
+                *cost = new_cost
+            }
+        }
+        self
+    }
+
+    /// The hex-encoded hashes of the accounts whose state the execution of the deploy touched,
+    /// sorted: the accounts written by its transforms, and the senders and recipients of the
+    /// transfers and deploys it recorded.
+    pub fn hex_encoded_affected_account_hashes(&self) -> Vec<String> {
+        let mut account_hashes = BTreeSet::new();
+        for entry in &self.execution_effect().transforms {
+            if let Ok(Key::Account(account_hash)) = Key::from_formatted_str(&entry.key) {
+                account_hashes.insert(account_hash);
+            }
+            match &entry.transform {
+                Transform::WriteAccount(account_hash) => {
+                    account_hashes.insert(*account_hash);
+                }
+                Transform::WriteTransfer(transfer) => {
+                    account_hashes.insert(transfer.from);
+                    account_hashes.extend(transfer.to);
+                }
+                Transform::WriteDeployInfo(deploy_info) => {
+                    account_hashes.insert(deploy_info.from);
+                }
+                _ => {}
+            }
+        }
+        account_hashes
+            .into_iter()
+            .map(|account_hash: AccountHash| hex::encode(account_hash.value()))
+            .collect()
+    }
+
+    /// The native transfers recorded by the execution of the deploy, along with their addresses,
+    /// in the order of its transforms.
+    pub fn native_transfers(&self) -> Vec<(TransferAddr, &Transfer)> {
+        self.execution_effect()
+            .transforms
+            .iter()
+            .filter_map(
+                |entry| match (&entry.transform, Key::from_formatted_str(&entry.key)) {
+                    (Transform::WriteTransfer(transfer), Ok(Key::Transfer(transfer_addr))) => {
+                        Some((transfer_addr, transfer))
+                    }
+                    _ => None,
+                },
+            )
+            .collect()
+    }
+
+    /// The transforms of the execution of the deploy, in order.
+    pub fn transforms(&self) -> &[TransformEntry] {
+        &self.execution_effect().transforms
+    }
+
+    fn execution_effect(&self) -> &ExecutionEffect {
+        match self.execution_result.as_ref() {
+            ExecutionResult::Success { effect, .. } | ExecutionResult::Failure { effect, .. } => {
+                effect
+            }
+        }
+    }
+
+    #[cfg(feature = "testing")]
+    pub fn with_transforms(mut self, transforms: Vec<TransformEntry>) -> Self {
+        match self.execution_result.as_mut() {
+            ExecutionResult::Success { effect, .. } | ExecutionResult::Failure { effect, .. } => {
+                effect.transforms = transforms
+            }
+        }
+        self
+    }
+
+    #[cfg(feature = "testing")]
+    pub fn with_error_message(mut self, error_message: &str) -> Self {
+        let (effect, transfers, cost) = match *self.execution_result {
+            ExecutionResult::Success {
+                effect,
+                transfers,
+                cost,
+            }
+            | ExecutionResult::Failure {
+                effect,
+                transfers,
+                cost,
+                ..
+            } => (effect, transfers, cost),
+        };
+        self.execution_result = Box::new(ExecutionResult::Failure {
+            effect,
+            transfers,
+            cost,
+            error_message: error_message.to_string(),
+        });
+        self
+    }
+}
+
+/// The given deploy has expired.
+#[derive(Clone, Debug, Serialize, Deserialize, new, ToSchema)]
+pub struct DeployExpired {
+    deploy_hash: DeployHash,
+}
+
+impl DeployExpired {
+    #[cfg(feature = "testing")]
+    pub fn random(rng: &mut TestRng, with_deploy_hash: Option<DeployHash>) -> Self {
+        Self {
+            deploy_hash: with_deploy_hash.unwrap_or_else(|| DeployHash::new(Digest::random(rng))),
+        }
+    }
+
+    pub fn hex_encoded_hash(&self) -> String {
+        hex::encode(self.deploy_hash.inner())
+    }
+}
+
+/// Generic representation of validator's fault in an era.
+#[derive(Clone, Debug, Serialize, Deserialize, new, ToSchema)]
+pub struct Fault {
+    #[schema(value_type = u64)]
+    pub era_id: EraId,
+    /// "Hex-encoded cryptographic public key, including the algorithm tag prefix."
+    #[schema(value_type = String)]
+    pub public_key: PublicKey,
+    #[schema(value_type = String)]
+    pub timestamp: Timestamp,
+}
+
+impl Fault {
+    #[cfg(feature = "testing")]
+    pub fn random(rng: &mut TestRng) -> Self {
+        Self {
+            era_id: EraId::new(rng.gen()),
+            public_key: PublicKey::random(rng),
+            timestamp: Timestamp::random(rng),
+        }
+    }
+}
+
+impl Display for Fault {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#?}", self)
+    }
+}
+
+/// New finality signature received.
+#[derive(Clone, Debug, Serialize, Deserialize, new)]
+pub struct FinalitySignature(Box<FinSig>);
+
+impl FinalitySignature {
+    #[cfg(feature = "testing")]
+    pub fn random(rng: &mut TestRng) -> Self {
+        Self(Box::new(FinSig::random_for_block(
+            BlockHash::random(rng),
+            rng.gen(),
+            rng,
+        )))
+    }
+
+    pub fn inner(&self) -> FinSig {
+        *self.0.clone()
+    }
+
+    pub fn hex_encoded_block_hash(&self) -> String {
+        hex::encode(self.0.block_hash().inner())
+    }
+
+    pub fn hex_encoded_public_key(&self) -> String {
+        self.0.public_key().to_hex()
+    }
+}
+
+/// The execution effects produced by a `StepRequest`.
+#[derive(Clone, Debug, Serialize, Deserialize, new, ToSchema)]
+pub struct Step {
+    #[schema(value_type = u64)]
+    pub era_id: EraId,
+    #[schema(value_type = ExecutionEffect)]
+    //This technically is not amorphic, but this field is potentially > 30MB of size. By not parsing it we make the process of intaking these messages much quicker and less memory consuming
+    execution_effect: Box<RawValue>,
+}
+
+impl Step {
+    #[cfg(feature = "testing")]
+    pub fn random(rng: &mut TestRng) -> Self {
+        use serde_json::value::to_raw_value;
+
+        let execution_effect = match rng.gen::<ExecutionResult>() {
+            ExecutionResult::Success { effect, .. } | ExecutionResult::Failure { effect, .. } => {
+                effect
+            }
+        };
+        Self {
+            era_id: EraId::new(rng.gen()),
+            execution_effect: to_raw_value(&execution_effect).unwrap(),
+        }
+    }
+}
+
+/// An event of a type the sidecar doesn't know, e.g. emitted by a newer node, kept as received.
+#[derive(Clone, Debug, Serialize, Deserialize, new)]
+pub struct UnknownEvent {
+    /// The name of the event's type, i.e. the key under which the node sent its payload.
+    pub event_type: String,
+    payload: Box<RawValue>,
+}
+
+impl UnknownEvent {
+    #[cfg(feature = "testing")]
+    pub fn random(rng: &mut TestRng) -> Self {
+        let payload = format!("{{\"height\":{}}}", rng.gen::<u64>());
+        Self {
+            event_type: format!("FutureEvent{}", rng.gen::<u16>()),
+            payload: RawValue::from_string(payload).unwrap(),
+        }
+    }
+}
+
+#[cfg(feature = "testing")]
+impl From<BlockAdded> for SseData {
+    fn from(block_added: BlockAdded) -> Self {
+        SseData::BlockAdded {
+            block_hash: block_added.block_hash,
+            block: block_added.block,
+        }
+    }
+}
+
+#[cfg(feature = "testing")]
+impl From<DeployAccepted> for SseData {
+    fn from(deploy_accepted: DeployAccepted) -> Self {
+        SseData::DeployAccepted {
+            deploy: deploy_accepted.deploy,
+        }
+    }
+}
+
+#[cfg(feature = "testing")]
+impl From<DeployProcessed> for SseData {
+    fn from(deploy_processed: DeployProcessed) -> Self {
+        SseData::DeployProcessed {
+            deploy_hash: deploy_processed.deploy_hash,
+            account: deploy_processed.account,
+            timestamp: deploy_processed.timestamp,
+            ttl: deploy_processed.ttl,
+            dependencies: deploy_processed.dependencies,
+            block_hash: deploy_processed.block_hash,
+            execution_result: deploy_processed.execution_result,
+        }
+    }
+}
@@ -1,11 +1,20 @@
 //! Testing utilities.
 //!
-//! Contains various parts and components to aid writing tests and simulations using the
-//! `casper-node` library.
+//! Contains various parts and components to aid writing tests and simulations, available to other
+//! crates through the `testing` feature. All the random values are drawn from the given [TestRng],
+//! so the fixtures built from a [TestRng::from_seed] are the same from one run to the next.
 
-use casper_types::{testing::TestRng, TimeDiff, Timestamp};
+use std::ops::RangeInclusive;
 
-use crate::Deploy;
+use casper_types::{testing::TestRng, PublicKey, TimeDiff, Timestamp};
+use rand::Rng;
+
+use crate::{
+    block::BlockShape,
+    sse_data::SseData,
+    sse_events::{BlockAdded, DeployAccepted, DeployProcessed},
+    Block, BlockHash, Deploy, JsonBlock,
+};
 
 /// Creates a test deploy created at given instant and with given ttl.
 pub fn create_test_deploy(
@@ -26,3 +35,268 @@ pub fn create_expired_deploy(now: Timestamp, test_rng: &mut TestRng) -> Deploy {
         test_rng,
     )
 }
+
+/// 2024-01-01T00:00:00Z, the timestamp of the first block of a chain by default.
+const DEFAULT_STARTING_TIMESTAMP: u64 = 1_704_067_200_000;
+
+/// The time to live of the generated deploys.
+const DEPLOY_TTL_IN_SECONDS: u32 = 30 * 60;
+
+/// Builds a chain of consecutive blocks along with the deploys they hold, as a node emits them.
+///
+/// The blocks link to their parents, follow each other at a fixed interval and are grouped in
+/// eras of a fixed length, the last block of each era being a switch block. Every deploy of a
+/// block is accepted within the interval before the block and processed in it.
+///
+/// ```ignore
+/// let mut rng = TestRng::from_seed([7; 16]);
+/// let chain = ChainBuilder::new()
+///     .starting_at(1_000, 100)
+///     .blocks_per_era(5)
+///     .deploys_per_block(2..=2)
+///     .build(&mut rng, 20);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ChainBuilder {
+    first_height: u64,
+    first_era_id: u64,
+    blocks_per_era: u64,
+    deploys_per_block: RangeInclusive<usize>,
+    transfers_per_block: RangeInclusive<usize>,
+    validators_per_era: usize,
+    starting_timestamp: Timestamp,
+    block_interval: TimeDiff,
+}
+
+impl Default for ChainBuilder {
+    fn default() -> Self {
+        ChainBuilder {
+            first_height: 0,
+            first_era_id: 0,
+            blocks_per_era: 10,
+            deploys_per_block: 0..=5,
+            transfers_per_block: 0..=2,
+            validators_per_era: 5,
+            starting_timestamp: Timestamp::from(DEFAULT_STARTING_TIMESTAMP),
+            block_interval: TimeDiff::from_seconds(16),
+        }
+    }
+}
+
+impl ChainBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Height and era of the first block of the chain.
+    pub fn starting_at(mut self, height: u64, era_id: u64) -> Self {
+        self.first_height = height;
+        self.first_era_id = era_id;
+        self
+    }
+
+    /// Number of blocks of each era, counted from the first block of the chain.
+    pub fn blocks_per_era(mut self, blocks_per_era: u64) -> Self {
+        self.blocks_per_era = blocks_per_era.max(1);
+        self
+    }
+
+    /// Number of the deploys, other than transfers, of each block.
+    pub fn deploys_per_block(mut self, deploys_per_block: RangeInclusive<usize>) -> Self {
+        self.deploys_per_block = deploys_per_block;
+        self
+    }
+
+    /// Number of the transfers of each block.
+    pub fn transfers_per_block(mut self, transfers_per_block: RangeInclusive<usize>) -> Self {
+        self.transfers_per_block = transfers_per_block;
+        self
+    }
+
+    /// Number of the validators announced by each switch block for the next era.
+    pub fn validators_per_era(mut self, validators_per_era: usize) -> Self {
+        self.validators_per_era = validators_per_era;
+        self
+    }
+
+    /// Timestamp of the first block of the chain.
+    pub fn starting_timestamp(mut self, starting_timestamp: Timestamp) -> Self {
+        self.starting_timestamp = starting_timestamp;
+        self
+    }
+
+    /// Time between two consecutive blocks.
+    pub fn block_interval(mut self, block_interval: TimeDiff) -> Self {
+        self.block_interval = block_interval;
+        self
+    }
+
+    /// Builds `block_count` blocks from the first one on.
+    pub fn build(&self, rng: &mut TestRng, block_count: u64) -> Vec<GeneratedBlock> {
+        let mut parent_hash = BlockHash::random(rng);
+        let mut blocks = Vec::new();
+        for index in 0..block_count {
+            let block = self.build_block(rng, index, parent_hash);
+            parent_hash = block.block_added.block_hash();
+            blocks.push(block);
+        }
+        blocks
+    }
+
+    /// Deploys accepted within the block interval before the block's timestamp.
+    fn random_deploys(&self, rng: &mut TestRng, timestamp: Timestamp, count: usize) -> Vec<Deploy> {
+        let interval = self.block_interval.millis();
+        (0..count)
+            .map(|_| {
+                let accepted_ago = TimeDiff::from_millis(rng.gen_range(0..=interval));
+                Deploy::random_with_timestamp_and_ttl(
+                    rng,
+                    timestamp - accepted_ago,
+                    TimeDiff::from_seconds(DEPLOY_TTL_IN_SECONDS),
+                )
+            })
+            .collect()
+    }
+
+    fn build_block(&self, rng: &mut TestRng, index: u64, parent_hash: BlockHash) -> GeneratedBlock {
+        let interval = self.block_interval.millis();
+        let timestamp = self.starting_timestamp + TimeDiff::from_millis(interval * index);
+        let deploy_count = rng.gen_range(self.deploys_per_block.clone());
+        let transfer_count = rng.gen_range(self.transfers_per_block.clone());
+        let deploys = self.random_deploys(rng, timestamp, deploy_count + transfer_count);
+        let is_switch_block = (index + 1) % self.blocks_per_era == 0;
+        let next_era_validators = is_switch_block.then(|| {
+            (0..self.validators_per_era)
+                .map(|_| PublicKey::random(rng))
+                .collect()
+        });
+        let shape = BlockShape {
+            parent_hash,
+            height: self.first_height + index,
+            era_id: self.first_era_id + index / self.blocks_per_era,
+            timestamp,
+            deploy_hashes: deploys[..deploy_count].iter().map(|d| *d.hash()).collect(),
+            transfer_hashes: deploys[deploy_count..].iter().map(|d| *d.hash()).collect(),
+            next_era_validators,
+        };
+        let block = JsonBlock::with_random_proofs(Block::random_with_shape(rng, shape), rng);
+        let block_hash = block.hash;
+        let deploys_processed = deploys
+            .iter()
+            .map(|deploy| processed_in_block(rng, deploy, block_hash))
+            .collect();
+        GeneratedBlock {
+            block_added: BlockAdded::new(block_hash, Box::new(block)),
+            deploys_accepted: deploys
+                .into_iter()
+                .map(|deploy| DeployAccepted::new(deploy.into()))
+                .collect(),
+            deploys_processed,
+        }
+    }
+}
+
+/// The processing of the deploy in the block, with a random execution result.
+fn processed_in_block(
+    rng: &mut TestRng,
+    deploy: &Deploy,
+    block_hash: BlockHash,
+) -> DeployProcessed {
+    DeployProcessed::new(
+        Box::new(*deploy.hash()),
+        Box::new(deploy.header().account().clone()),
+        deploy.header().timestamp(),
+        deploy.header().ttl(),
+        deploy.header().dependencies().clone(),
+        Box::new(block_hash),
+        Box::new(rng.gen()),
+    )
+}
+
+/// A block built by a [ChainBuilder], along with the events of its deploys.
+#[derive(Clone, Debug)]
+pub struct GeneratedBlock {
+    pub block_added: BlockAdded,
+    /// The acceptances of the deploys of the block, transfers last.
+    pub deploys_accepted: Vec<DeployAccepted>,
+    /// The executions of the deploys of the block, in the same order as their acceptances.
+    pub deploys_processed: Vec<DeployProcessed>,
+}
+
+impl GeneratedBlock {
+    /// The events of the block in the order a node emits them: the acceptances of its deploys,
+    /// the block itself, then the executions of its deploys.
+    pub fn events(&self) -> Vec<SseData> {
+        self.deploys_accepted
+            .iter()
+            .cloned()
+            .map(SseData::from)
+            .chain(Some(SseData::from(self.block_added.clone())))
+            .chain(self.deploys_processed.iter().cloned().map(SseData::from))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_chain(seed: [u8; 16]) -> Vec<GeneratedBlock> {
+        let mut rng = TestRng::from_seed(seed);
+        ChainBuilder::new()
+            .starting_at(1_000, 100)
+            .blocks_per_era(4)
+            .deploys_per_block(1..=3)
+            .transfers_per_block(0..=0)
+            .build(&mut rng, 10)
+    }
+
+    fn to_json(blocks: &[GeneratedBlock]) -> String {
+        let events: Vec<SseData> = blocks.iter().flat_map(GeneratedBlock::events).collect();
+        serde_json::to_string(&events).unwrap()
+    }
+
+    #[test]
+    fn should_build_the_same_chain_from_the_same_seed() {
+        let first = to_json(&build_chain([1; 16]));
+        let second = to_json(&build_chain([1; 16]));
+        let other = to_json(&build_chain([2; 16]));
+
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+    }
+
+    #[test]
+    fn should_build_a_chain_of_the_given_shape() {
+        let blocks = build_chain([3; 16]);
+        let blocks_added: Vec<&BlockAdded> =
+            blocks.iter().map(|block| &block.block_added).collect();
+
+        assert_eq!(blocks.len(), 10);
+        for (index, block_added) in blocks_added.iter().enumerate() {
+            assert!(block_added.verify().is_ok());
+            assert_eq!(block_added.get_height(), 1_000 + index as u64);
+            assert_eq!(block_added.get_era_id(), 100 + index as u64 / 4);
+            assert_eq!(block_added.is_switch_block(), index % 4 == 3);
+        }
+        for pair in blocks_added.windows(2) {
+            assert_eq!(pair[1].parent_hash(), pair[0].block_hash());
+        }
+        for block in &blocks {
+            let deploy_hashes = block.block_added.hex_encoded_deploy_hashes();
+            assert!((1..=3).contains(&deploy_hashes.len()));
+            let accepted: Vec<String> = block
+                .deploys_accepted
+                .iter()
+                .map(DeployAccepted::hex_encoded_hash)
+                .collect();
+            assert_eq!(accepted, deploy_hashes);
+            for deploy_processed in &block.deploys_processed {
+                assert_eq!(
+                    deploy_processed.hex_encoded_block_hash(),
+                    block.block_added.hex_encoded_hash()
+                );
+            }
+        }
+    }
+}